@@ -0,0 +1,6 @@
+-- Allow portfolios to be archived (hidden from lists and skipped by background
+-- jobs) without deleting their history, and remember where a clone came from.
+ALTER TABLE portfolios ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+ALTER TABLE portfolios ADD COLUMN IF NOT EXISTS cloned_from UUID REFERENCES portfolios(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_portfolios_active ON portfolios(user_id) WHERE archived_at IS NULL;
@@ -0,0 +1,23 @@
+-- Free-form tags and markdown notes on positions and transactions.
+--
+-- Positions are derived from holdings snapshots, which are re-imported over time,
+-- so annotations live in their own table keyed by (account_id, ticker) and survive
+-- new snapshots.
+
+CREATE TABLE IF NOT EXISTS position_annotations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    ticker TEXT NOT NULL,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(account_id, ticker)
+);
+
+CREATE INDEX IF NOT EXISTS idx_position_annotations_tags ON position_annotations USING GIN(tags);
+
+ALTER TABLE detected_transactions ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE detected_transactions ADD COLUMN IF NOT EXISTS notes TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_detected_transactions_tags ON detected_transactions USING GIN(tags);
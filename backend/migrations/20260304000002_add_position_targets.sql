@@ -0,0 +1,14 @@
+-- Target price, stop-loss level, and investment thesis on positions.
+--
+-- The *_alerted_at columns record when the watchlist monitoring job last notified
+-- the user of a crossing, so each crossing is reported once. They are cleared when
+-- the price moves back across the level or the level itself is changed.
+
+ALTER TABLE position_annotations ADD COLUMN IF NOT EXISTS target_price DOUBLE PRECISION;
+ALTER TABLE position_annotations ADD COLUMN IF NOT EXISTS stop_loss DOUBLE PRECISION;
+ALTER TABLE position_annotations ADD COLUMN IF NOT EXISTS thesis TEXT;
+ALTER TABLE position_annotations ADD COLUMN IF NOT EXISTS target_alerted_at TIMESTAMPTZ;
+ALTER TABLE position_annotations ADD COLUMN IF NOT EXISTS stop_alerted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_position_annotations_levels ON position_annotations(ticker)
+    WHERE target_price IS NOT NULL OR stop_loss IS NOT NULL;
@@ -0,0 +1,9 @@
+-- Wash sale flagging on sell transactions.
+--
+-- A sell at a loss with a repurchase of the same ticker within 30 days before or
+-- after it has (part of) its loss disallowed. NULL means the sell is not a wash sale.
+
+ALTER TABLE detected_transactions ADD COLUMN IF NOT EXISTS wash_sale_disallowed_loss DOUBLE PRECISION;
+
+CREATE INDEX IF NOT EXISTS idx_detected_transactions_wash_sales ON detected_transactions(account_id)
+    WHERE wash_sale_disallowed_loss IS NOT NULL;
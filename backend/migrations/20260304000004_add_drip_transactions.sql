@@ -0,0 +1,17 @@
+-- Dividend reinvestment (DRIP) support.
+--
+-- DRIP transactions buy fractional shares with a dividend's cash. Accounts that
+-- reinvest automatically can have them generated from their dividend history;
+-- generated rows point back at the dividend they reinvest.
+
+ALTER TABLE detected_transactions DROP CONSTRAINT IF EXISTS detected_transactions_transaction_type_check;
+ALTER TABLE detected_transactions ADD CONSTRAINT detected_transactions_transaction_type_check
+    CHECK (transaction_type IN ('BUY', 'SELL', 'DIVIDEND', 'DRIP', 'SPLIT', 'OTHER'));
+
+ALTER TABLE detected_transactions ADD COLUMN IF NOT EXISTS source_transaction_id UUID
+    REFERENCES detected_transactions(id) ON DELETE CASCADE;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_detected_transactions_drip_source ON detected_transactions(source_transaction_id)
+    WHERE source_transaction_id IS NOT NULL;
+
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS drip_enabled BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,14 @@
+-- Daily market breadth metrics computed over the tracked universe
+-- (every ticker with recent prices in price_points)
+CREATE TABLE IF NOT EXISTS market_breadth (
+    date DATE PRIMARY KEY,
+    universe_size INTEGER NOT NULL,
+    advancers INTEGER NOT NULL,
+    decliners INTEGER NOT NULL,
+    unchanged INTEGER NOT NULL,
+    pct_above_sma50 DOUBLE PRECISION,
+    pct_above_sma200 DOUBLE PRECISION,
+    new_highs INTEGER NOT NULL,
+    new_lows INTEGER NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
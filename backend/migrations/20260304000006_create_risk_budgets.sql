@@ -0,0 +1,10 @@
+-- Portfolio risk budgets: a total volatility or VaR budget plus optional
+-- per-sleeve sub-budgets, where a sleeve is the set of positions carrying a tag
+CREATE TABLE IF NOT EXISTS portfolio_risk_budgets (
+    portfolio_id UUID PRIMARY KEY REFERENCES portfolios(id) ON DELETE CASCADE,
+    metric TEXT NOT NULL CHECK (metric IN ('volatility', 'var_95')),
+    total_budget DOUBLE PRECISION NOT NULL CHECK (total_budget > 0),
+    sleeves JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
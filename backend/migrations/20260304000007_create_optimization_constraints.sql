@@ -0,0 +1,11 @@
+-- User constraints for the portfolio rebalancer: position and sector weight
+-- caps (percent), a cash floor, and tickers that must not be sold
+CREATE TABLE IF NOT EXISTS portfolio_optimization_constraints (
+    portfolio_id UUID PRIMARY KEY REFERENCES portfolios(id) ON DELETE CASCADE,
+    max_position_weight DOUBLE PRECISION CHECK (max_position_weight > 0 AND max_position_weight <= 100),
+    sector_caps JSONB NOT NULL DEFAULT '{}',
+    min_cash_pct DOUBLE PRECISION CHECK (min_cash_pct >= 0 AND min_cash_pct < 100),
+    do_not_sell TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
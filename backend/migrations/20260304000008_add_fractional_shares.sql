@@ -0,0 +1,3 @@
+-- Whether the account's broker supports fractional shares. Trade lists for
+-- accounts without it are rounded to whole shares.
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS fractional_shares BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,12 @@
+-- Scored headline feed for a portfolio's holdings, cached per lookback window
+-- so paging through the feed does not re-query the news provider
+CREATE TABLE IF NOT EXISTS portfolio_news_feed_cache (
+    portfolio_id UUID NOT NULL REFERENCES portfolios(id) ON DELETE CASCADE,
+    days INTEGER NOT NULL,
+    items JSONB NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (portfolio_id, days)
+);
+
+CREATE INDEX IF NOT EXISTS idx_portfolio_news_feed_cache_expires_at ON portfolio_news_feed_cache(expires_at);
@@ -0,0 +1,7 @@
+-- Raw insider/institutional ownership data per ticker from the ownership provider.
+-- Summaries (insider activity windows, top holders) are derived on read.
+CREATE TABLE IF NOT EXISTS ownership_snapshots (
+    ticker TEXT PRIMARY KEY,
+    data JSONB NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,7 @@
+-- Raw analyst rating distribution and price targets per ticker from the analyst provider.
+-- Upside vs the current price is derived on read.
+CREATE TABLE IF NOT EXISTS analyst_consensus (
+    ticker TEXT PRIMARY KEY,
+    data JSONB NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
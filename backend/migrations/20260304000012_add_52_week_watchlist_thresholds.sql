@@ -0,0 +1,15 @@
+-- Allow watchlist thresholds on distance from the 52-week high and low
+ALTER TABLE watchlist_thresholds DROP CONSTRAINT IF EXISTS valid_threshold_type;
+ALTER TABLE watchlist_thresholds ADD CONSTRAINT valid_threshold_type CHECK (
+    threshold_type IN (
+        'price_above',
+        'price_below',
+        'price_change_pct',
+        'volatility',
+        'volume_spike',
+        'rsi_overbought',
+        'rsi_oversold',
+        'pct_off_52w_high',
+        'pct_above_52w_low'
+    )
+);
@@ -0,0 +1,3 @@
+-- How an account's income and gains are taxed. NULL means inferred from the nickname.
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS tax_treatment TEXT
+    CHECK (tax_treatment IN ('taxable', 'tax_deferred', 'tax_free'));
@@ -0,0 +1,68 @@
+-- ETF / mutual fund metadata used for fee analysis.
+-- expense_ratio is the annual management expense ratio in percent (0.03 = 0.03%).
+-- Funds in the same category and currency are considered interchangeable for swap suggestions.
+CREATE TABLE IF NOT EXISTS fund_metadata (
+    ticker TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    category TEXT NOT NULL,
+    currency TEXT NOT NULL DEFAULT 'USD',
+    expense_ratio DOUBLE PRECISION NOT NULL CHECK (expense_ratio >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_fund_metadata_category ON fund_metadata (category, currency);
+
+INSERT INTO fund_metadata (ticker, name, category, currency, expense_ratio) VALUES
+    -- US-listed
+    ('VTI',  'Vanguard Total Stock Market ETF',                    'us_total_market',         'USD', 0.03),
+    ('ITOT', 'iShares Core S&P Total U.S. Stock Market ETF',       'us_total_market',         'USD', 0.03),
+    ('SCHB', 'Schwab U.S. Broad Market ETF',                       'us_total_market',         'USD', 0.03),
+    ('VOO',  'Vanguard S&P 500 ETF',                               'us_large_cap',            'USD', 0.03),
+    ('IVV',  'iShares Core S&P 500 ETF',                           'us_large_cap',            'USD', 0.03),
+    ('SPLG', 'SPDR Portfolio S&P 500 ETF',                         'us_large_cap',            'USD', 0.02),
+    ('SPY',  'SPDR S&P 500 ETF Trust',                             'us_large_cap',            'USD', 0.0945),
+    ('QQQ',  'Invesco QQQ Trust',                                  'nasdaq_100',              'USD', 0.20),
+    ('QQQM', 'Invesco NASDAQ 100 ETF',                             'nasdaq_100',              'USD', 0.15),
+    ('VEA',  'Vanguard FTSE Developed Markets ETF',                'international_developed', 'USD', 0.05),
+    ('IEFA', 'iShares Core MSCI EAFE ETF',                         'international_developed', 'USD', 0.07),
+    ('EFA',  'iShares MSCI EAFE ETF',                              'international_developed', 'USD', 0.33),
+    ('VWO',  'Vanguard FTSE Emerging Markets ETF',                 'emerging_markets',        'USD', 0.08),
+    ('IEMG', 'iShares Core MSCI Emerging Markets ETF',             'emerging_markets',        'USD', 0.09),
+    ('EEM',  'iShares MSCI Emerging Markets ETF',                  'emerging_markets',        'USD', 0.70),
+    ('VT',   'Vanguard Total World Stock ETF',                     'global_equity',           'USD', 0.06),
+    ('ACWI', 'iShares MSCI ACWI ETF',                              'global_equity',           'USD', 0.32),
+    ('BND',  'Vanguard Total Bond Market ETF',                     'us_aggregate_bond',       'USD', 0.03),
+    ('AGG',  'iShares Core U.S. Aggregate Bond ETF',               'us_aggregate_bond',       'USD', 0.03),
+    ('SCHD', 'Schwab U.S. Dividend Equity ETF',                    'us_dividend',             'USD', 0.06),
+    ('VYM',  'Vanguard High Dividend Yield ETF',                   'us_dividend',             'USD', 0.06),
+    ('DVY',  'iShares Select Dividend ETF',                        'us_dividend',             'USD', 0.38),
+    ('VTV',  'Vanguard Value ETF',                                 'us_value',                'USD', 0.04),
+    ('IUSV', 'iShares Core S&P U.S. Value ETF',                    'us_value',                'USD', 0.04),
+    ('VLUE', 'iShares MSCI USA Value Factor ETF',                  'us_value',                'USD', 0.15),
+    ('VUG',  'Vanguard Growth ETF',                                'us_growth',               'USD', 0.04),
+    ('IWF',  'iShares Russell 1000 Growth ETF',                    'us_growth',               'USD', 0.19),
+    ('ARKK', 'ARK Innovation ETF',                                 'thematic_innovation',     'USD', 0.75),
+    -- TSX-listed
+    ('XIC',  'iShares Core S&P/TSX Capped Composite Index ETF',    'canadian_equity',         'CAD', 0.06),
+    ('VCN',  'Vanguard FTSE Canada All Cap Index ETF',             'canadian_equity',         'CAD', 0.05),
+    ('ZCN',  'BMO S&P/TSX Capped Composite Index ETF',             'canadian_equity',         'CAD', 0.06),
+    ('XIU',  'iShares S&P/TSX 60 Index ETF',                       'canadian_equity',         'CAD', 0.18),
+    ('VDY',  'Vanguard FTSE Canadian High Dividend Yield Index ETF','canadian_dividend',      'CAD', 0.22),
+    ('XEI',  'iShares S&P/TSX Composite High Dividend Index ETF',  'canadian_dividend',       'CAD', 0.22),
+    ('ZDV',  'BMO Canadian Dividend ETF',                          'canadian_dividend',       'CAD', 0.39),
+    ('VFV',  'Vanguard S&P 500 Index ETF',                         'us_large_cap',            'CAD', 0.09),
+    ('ZSP',  'BMO S&P 500 Index ETF',                              'us_large_cap',            'CAD', 0.09),
+    ('XUS',  'iShares Core S&P 500 Index ETF',                     'us_large_cap',            'CAD', 0.10),
+    ('XAW',  'iShares Core MSCI All Country World ex Canada ETF',  'global_ex_canada',        'CAD', 0.22),
+    ('VXC',  'Vanguard FTSE Global All Cap ex Canada Index ETF',   'global_ex_canada',        'CAD', 0.21),
+    ('ZAG',  'BMO Aggregate Bond Index ETF',                       'canadian_bond',           'CAD', 0.09),
+    ('VAB',  'Vanguard Canadian Aggregate Bond Index ETF',         'canadian_bond',           'CAD', 0.09),
+    ('XBB',  'iShares Core Canadian Universe Bond Index ETF',      'canadian_bond',           'CAD', 0.10),
+    ('XEQT', 'iShares Core Equity ETF Portfolio',                  'all_equity_portfolio',    'CAD', 0.20),
+    ('ZEQT', 'BMO All-Equity ETF',                                 'all_equity_portfolio',    'CAD', 0.20),
+    ('VEQT', 'Vanguard All-Equity ETF Portfolio',                  'all_equity_portfolio',    'CAD', 0.24),
+    ('XGRO', 'iShares Core Growth ETF Portfolio',                  'growth_portfolio',        'CAD', 0.20),
+    ('VGRO', 'Vanguard Growth ETF Portfolio',                      'growth_portfolio',        'CAD', 0.24),
+    ('XBAL', 'iShares Core Balanced ETF Portfolio',                'balanced_portfolio',      'CAD', 0.20),
+    ('VBAL', 'Vanguard Balanced ETF Portfolio',                    'balanced_portfolio',      'CAD', 0.24)
+ON CONFLICT (ticker) DO NOTHING;
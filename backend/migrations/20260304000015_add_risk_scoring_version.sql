@@ -0,0 +1,7 @@
+-- Record which risk scoring model produced each stored score so history stays
+-- comparable when the weighting changes. Existing rows were scored with v1.
+ALTER TABLE risk_snapshots
+    ADD COLUMN IF NOT EXISTS scoring_version INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE portfolio_risk_cache
+    ADD COLUMN IF NOT EXISTS scoring_version INTEGER NOT NULL DEFAULT 1;
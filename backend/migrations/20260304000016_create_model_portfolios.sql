@@ -0,0 +1,39 @@
+-- Reference model portfolios that user portfolios can be benchmarked against.
+-- Weights are in percent and sum to 100 per model.
+CREATE TABLE IF NOT EXISTS model_portfolios (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS model_portfolio_allocations (
+    model_id TEXT NOT NULL REFERENCES model_portfolios(id) ON DELETE CASCADE,
+    ticker TEXT NOT NULL,
+    asset_class TEXT NOT NULL,
+    weight DOUBLE PRECISION NOT NULL CHECK (weight > 0 AND weight <= 100),
+    PRIMARY KEY (model_id, ticker)
+);
+
+INSERT INTO model_portfolios (id, name, description) VALUES
+    ('sixty_forty', '60/40', 'Classic balanced portfolio: 60% US stocks, 40% US bonds'),
+    ('three_fund', 'Three-Fund', 'US total market, international stocks and US bonds'),
+    ('all_weather', 'All-Weather', 'Risk-balanced mix of stocks, long and intermediate Treasuries, gold and commodities'),
+    ('eighty_twenty', '80/20', 'Growth-oriented portfolio: 80% global stocks, 20% US bonds')
+ON CONFLICT (id) DO NOTHING;
+
+INSERT INTO model_portfolio_allocations (model_id, ticker, asset_class, weight) VALUES
+    ('sixty_forty', 'VTI', 'us_equity', 60),
+    ('sixty_forty', 'BND', 'us_bonds', 40),
+    ('three_fund', 'VTI', 'us_equity', 48),
+    ('three_fund', 'VXUS', 'international_equity', 32),
+    ('three_fund', 'BND', 'us_bonds', 20),
+    ('all_weather', 'VTI', 'us_equity', 30),
+    ('all_weather', 'TLT', 'long_treasuries', 40),
+    ('all_weather', 'IEF', 'intermediate_treasuries', 15),
+    ('all_weather', 'GLD', 'gold', 7.5),
+    ('all_weather', 'DBC', 'commodities', 7.5),
+    ('eighty_twenty', 'VT', 'global_equity', 80),
+    ('eighty_twenty', 'BND', 'us_bonds', 20)
+ON CONFLICT (model_id, ticker) DO NOTHING;
@@ -0,0 +1,25 @@
+-- Opt-in for contributing to anonymous aggregate statistics. Only portfolios of
+-- users who opted in are read by the peer statistics job.
+ALTER TABLE user_preferences
+    ADD COLUMN IF NOT EXISTS share_anonymous_stats BOOLEAN NOT NULL DEFAULT false;
+
+COMMENT ON COLUMN user_preferences.share_anonymous_stats IS
+    'Whether the user contributes to, and sees, anonymous aggregate statistics across portfolios';
+
+-- Distribution of a portfolio-level risk metric across opted-in portfolios.
+-- Only the 1st..99th percentile cut points are stored, never individual values.
+CREATE TABLE IF NOT EXISTS peer_metric_distributions (
+    metric TEXT PRIMARY KEY,
+    sample_size INTEGER NOT NULL,
+    percentiles DOUBLE PRECISION[] NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Tickers held by the most opted-in portfolios. Tickers held by only a few
+-- portfolios are never stored.
+CREATE TABLE IF NOT EXISTS peer_common_holdings (
+    ticker TEXT PRIMARY KEY,
+    portfolio_count INTEGER NOT NULL,
+    portfolio_share DOUBLE PRECISION NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,9 @@
+-- Snapshot retention: daily risk snapshots older than the configured horizons are
+-- compacted to the last snapshot of each week, then of each month. The tier a
+-- row now stands for is recorded so history charts can tell the points apart.
+ALTER TABLE risk_snapshots
+    ADD COLUMN IF NOT EXISTS granularity TEXT NOT NULL DEFAULT 'daily'
+        CHECK (granularity IN ('daily', 'weekly', 'monthly'));
+
+COMMENT ON COLUMN risk_snapshots.granularity IS
+    'Period the snapshot represents after retention compaction: daily, weekly or monthly';
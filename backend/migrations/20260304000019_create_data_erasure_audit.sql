@@ -0,0 +1,17 @@
+-- Audit trail of personal data exports and erasures. There is deliberately no
+-- foreign key to users: the record of an erasure must outlive the account it
+-- erased. Only the user id and per-table row counts are kept, never the data.
+CREATE TABLE IF NOT EXISTS data_erasure_audit (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL,
+    event TEXT NOT NULL CHECK (event IN ('export', 'erasure')),
+    account_deleted BOOLEAN NOT NULL DEFAULT false,
+    row_counts JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_erasure_audit_user
+    ON data_erasure_audit(user_id, created_at DESC);
+
+COMMENT ON TABLE data_erasure_audit IS
+    'Audit log of user data exports and erasures (row counts only, no personal data)';
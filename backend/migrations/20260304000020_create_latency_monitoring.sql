@@ -0,0 +1,51 @@
+-- Raw timing samples, flushed from memory by the latency budget job and kept
+-- for a week. `name` is "METHOD /route/:pattern" for endpoints and the
+-- normalized SQL statement for queries.
+CREATE TABLE IF NOT EXISTS latency_samples (
+    id BIGSERIAL PRIMARY KEY,
+    kind TEXT NOT NULL CHECK (kind IN ('endpoint', 'query')),
+    name TEXT NOT NULL,
+    duration_ms DOUBLE PRECISION NOT NULL,
+    status SMALLINT,
+    request_id TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_latency_samples_name
+    ON latency_samples(kind, name, recorded_at DESC);
+CREATE INDEX IF NOT EXISTS idx_latency_samples_recorded_at
+    ON latency_samples(recorded_at);
+
+-- Per-endpoint or per-query p95 budgets. Anything not listed here gets the
+-- default budget for its kind (ENDPOINT_LATENCY_BUDGET_MS / QUERY_LATENCY_BUDGET_MS).
+CREATE TABLE IF NOT EXISTS latency_budgets (
+    kind TEXT NOT NULL CHECK (kind IN ('endpoint', 'query')),
+    name TEXT NOT NULL,
+    budget_ms INTEGER NOT NULL CHECK (budget_ms > 0),
+    PRIMARY KEY (kind, name)
+);
+
+-- Analytics endpoints that compute on cache misses get more room
+INSERT INTO latency_budgets (kind, name, budget_ms) VALUES
+    ('endpoint', 'GET /api/risk/portfolios/:portfolio_id', 3000),
+    ('endpoint', 'GET /api/risk/portfolios/:portfolio_id/correlations', 5000),
+    ('endpoint', 'GET /api/risk/positions/:ticker/rolling-beta', 3000),
+    ('endpoint', 'GET /api/risk/portfolios/:portfolio_id/downside', 5000),
+    ('endpoint', 'GET /api/optimization/portfolios/:portfolio_id', 5000),
+    ('endpoint', 'GET /api/analytics/:portfolio_id/forecast', 5000)
+ON CONFLICT (kind, name) DO NOTHING;
+
+-- Latency statistics over the trailing window, rebuilt by each job run
+CREATE TABLE IF NOT EXISTS latency_stats (
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    sample_count INTEGER NOT NULL,
+    p50_ms DOUBLE PRECISION NOT NULL,
+    p95_ms DOUBLE PRECISION NOT NULL,
+    max_ms DOUBLE PRECISION NOT NULL,
+    budget_ms INTEGER NOT NULL,
+    breached BOOLEAN NOT NULL,
+    breached_since TIMESTAMPTZ,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (kind, name)
+);
@@ -0,0 +1,16 @@
+-- How sales in an account are matched to tax lots
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS cost_basis_method TEXT NOT NULL DEFAULT 'fifo'
+    CHECK (cost_basis_method IN ('fifo', 'average_cost', 'specific_lot'));
+
+-- Lots a sale closes under specific-lot identification. lot_transaction_id is the
+-- BUY/DRIP that opened the lot; NULL designates the account's opening balance lot.
+CREATE TABLE IF NOT EXISTS sell_lot_selections (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    sell_transaction_id UUID NOT NULL REFERENCES detected_transactions(id) ON DELETE CASCADE,
+    lot_transaction_id UUID REFERENCES detected_transactions(id) ON DELETE CASCADE,
+    quantity DOUBLE PRECISION NOT NULL CHECK (quantity > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_sell_lot_selections_sell
+    ON sell_lot_selections(sell_transaction_id);
@@ -0,0 +1,29 @@
+-- Recurring advisor/management fees charged to an account. `amount` is annual
+-- basis points of account value for 'aum_bps' and dollars per month for
+-- 'flat_monthly'. Fees accrue at each month end from start_date to end_date.
+CREATE TABLE IF NOT EXISTS account_fee_schedules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    fee_type TEXT NOT NULL CHECK (fee_type IN ('aum_bps', 'flat_monthly')),
+    amount DOUBLE PRECISION NOT NULL CHECK (amount >= 0),
+    start_date DATE NOT NULL,
+    end_date DATE,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (end_date IS NULL OR end_date >= start_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_fee_schedules_account
+    ON account_fee_schedules(account_id);
+
+-- Fee cash flows are generated from the schedules and replaced whenever they
+-- are regenerated. They do not count towards deposits or withdrawals, so the
+-- true performance figures stay gross of fees.
+ALTER TABLE cash_flows DROP CONSTRAINT IF EXISTS cash_flows_flow_type_check;
+ALTER TABLE cash_flows ADD CONSTRAINT cash_flows_flow_type_check
+    CHECK (flow_type IN ('DEPOSIT', 'WITHDRAWAL', 'FEE'));
+ALTER TABLE cash_flows ADD COLUMN IF NOT EXISTS fee_schedule_id UUID
+    REFERENCES account_fee_schedules(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_cash_flows_fee_schedule
+    ON cash_flows(fee_schedule_id) WHERE fee_schedule_id IS NOT NULL;
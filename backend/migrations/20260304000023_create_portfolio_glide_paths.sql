@@ -0,0 +1,12 @@
+-- Saved glide path per portfolio: the equity share declines linearly from
+-- start_equity_pct to end_equity_pct over the glide_years before goal_date.
+-- The rebalancer targets the point on the path for the current date.
+CREATE TABLE IF NOT EXISTS portfolio_glide_paths (
+    portfolio_id UUID PRIMARY KEY REFERENCES portfolios(id) ON DELETE CASCADE,
+    goal_date DATE NOT NULL,
+    start_equity_pct DOUBLE PRECISION NOT NULL CHECK (start_equity_pct >= 0 AND start_equity_pct <= 100),
+    end_equity_pct DOUBLE PRECISION NOT NULL CHECK (end_equity_pct >= 0 AND end_equity_pct <= start_equity_pct),
+    glide_years DOUBLE PRECISION NOT NULL CHECK (glide_years > 0 AND glide_years <= 60),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,16 @@
+-- Public wallet addresses whose on-chain balances are imported into an
+-- account's holdings snapshots (asset_category 'CRYPTO'). Read-only: only the
+-- address is stored, never keys.
+CREATE TABLE IF NOT EXISTS crypto_wallets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    chain TEXT NOT NULL CHECK (chain IN ('bitcoin', 'ethereum')),
+    address TEXT NOT NULL,
+    label TEXT,
+    last_synced_at TIMESTAMPTZ,
+    last_sync_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (account_id, chain, address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_crypto_wallets_account ON crypto_wallets(account_id);
@@ -0,0 +1,9 @@
+-- Incremental daily risk snapshots: positions (or whole portfolios) whose
+-- inputs did not change since the previous snapshot are copied forward instead
+-- of recomputed. Copied rows are flagged so the weekly full recompute can be
+-- scheduled from the last snapshot that was computed in full.
+ALTER TABLE risk_snapshots
+    ADD COLUMN IF NOT EXISTS carried_forward BOOLEAN NOT NULL DEFAULT false;
+
+COMMENT ON COLUMN risk_snapshots.carried_forward IS
+    'True when copied from the previous snapshot because no prices or holdings behind it changed';
@@ -0,0 +1,35 @@
+-- Postgres-backed work queue for scheduled jobs. Every instance's cron ticker
+-- enqueues each due job under a key naming the tick, so a tick is queued once no
+-- matter how many instances run; workers on any instance claim queued jobs with
+-- a lease (visible_until) that they extend while running. A job whose lease
+-- expires is claimed again, and only the holder of the current claim_token can
+-- complete it.
+CREATE TABLE IF NOT EXISTS job_queue (
+    id BIGSERIAL PRIMARY KEY,
+    job_name TEXT NOT NULL,
+    dedupe_key TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'succeeded', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 3,
+    run_after TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    claim_token UUID,
+    claimed_by TEXT,
+    visible_until TIMESTAMPTZ,
+    last_error TEXT,
+    enqueued_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ,
+
+    UNIQUE (job_name, dedupe_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_job_queue_claimable
+    ON job_queue(run_after)
+    WHERE status IN ('queued', 'running');
+
+CREATE INDEX IF NOT EXISTS idx_job_queue_finished
+    ON job_queue(finished_at)
+    WHERE finished_at IS NOT NULL;
+
+COMMENT ON COLUMN job_queue.dedupe_key IS 'Identifies the schedule tick (or manual request) the job was queued for';
+COMMENT ON COLUMN job_queue.visible_until IS 'Lease expiry of a running job; past it the job can be claimed by another worker';
@@ -0,0 +1,18 @@
+-- Saved column mappings for holdings CSV imports. A template is keyed by the
+-- broker and the normalized header row of the statement it was built from, so
+-- a later upload with the same headers can be imported without re-mapping.
+CREATE TABLE IF NOT EXISTS csv_import_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    broker TEXT NOT NULL,
+    name TEXT NOT NULL,
+    header_signature TEXT NOT NULL,
+    -- Holding field -> source column header
+    mapping JSONB NOT NULL,
+    last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, broker, header_signature)
+);
+
+CREATE INDEX IF NOT EXISTS idx_csv_import_templates_user ON csv_import_templates(user_id, header_signature);
@@ -0,0 +1,75 @@
+-- Import batches: every import runs in one transaction that sets
+-- rustfolio.import_batch_id, and the triggers below record each row it
+-- inserts, updates or deletes so the whole import can be reversed later.
+CREATE TABLE IF NOT EXISTS import_batches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    portfolio_id UUID NOT NULL REFERENCES portfolios(id) ON DELETE CASCADE,
+    source TEXT NOT NULL,
+    filename TEXT,
+    status TEXT NOT NULL DEFAULT 'applied' CHECK (status IN ('applied', 'rolled_back')),
+    accounts_created INTEGER NOT NULL DEFAULT 0,
+    holdings_created INTEGER NOT NULL DEFAULT 0,
+    transactions_detected INTEGER NOT NULL DEFAULT 0,
+    error_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    rolled_back_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_import_batches_portfolio ON import_batches(portfolio_id, created_at DESC);
+
+-- One row per change, in the order made. old_row holds the row before an
+-- UPDATE or DELETE; an INSERT is undone by deleting row_id.
+CREATE TABLE IF NOT EXISTS import_batch_changes (
+    id BIGSERIAL PRIMARY KEY,
+    batch_id UUID NOT NULL REFERENCES import_batches(id) ON DELETE CASCADE,
+    table_name TEXT NOT NULL,
+    operation TEXT NOT NULL CHECK (operation IN ('INSERT', 'UPDATE', 'DELETE')),
+    row_id UUID NOT NULL,
+    old_row JSONB
+);
+
+CREATE INDEX IF NOT EXISTS idx_import_batch_changes_batch ON import_batch_changes(batch_id, id);
+CREATE INDEX IF NOT EXISTS idx_import_batch_changes_row ON import_batch_changes(table_name, row_id);
+
+CREATE OR REPLACE FUNCTION record_import_batch_change()
+RETURNS TRIGGER AS $$
+DECLARE
+    -- Unset outside an import; empty after an import transaction on the same connection ends
+    batch TEXT := current_setting('rustfolio.import_batch_id', true);
+BEGIN
+    IF batch IS NULL OR batch = '' THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO import_batch_changes (batch_id, table_name, operation, row_id)
+        VALUES (batch::UUID, TG_TABLE_NAME, TG_OP, NEW.id);
+    ELSE
+        INSERT INTO import_batch_changes (batch_id, table_name, operation, row_id, old_row)
+        VALUES (batch::UUID, TG_TABLE_NAME, TG_OP, OLD.id, to_jsonb(OLD));
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS record_import_batch_change ON accounts;
+CREATE TRIGGER record_import_batch_change
+    AFTER INSERT OR UPDATE OR DELETE ON accounts
+    FOR EACH ROW EXECUTE FUNCTION record_import_batch_change();
+
+DROP TRIGGER IF EXISTS record_import_batch_change ON holdings_snapshots;
+CREATE TRIGGER record_import_batch_change
+    AFTER INSERT OR UPDATE OR DELETE ON holdings_snapshots
+    FOR EACH ROW EXECUTE FUNCTION record_import_batch_change();
+
+DROP TRIGGER IF EXISTS record_import_batch_change ON detected_transactions;
+CREATE TRIGGER record_import_batch_change
+    AFTER INSERT OR UPDATE OR DELETE ON detected_transactions
+    FOR EACH ROW EXECUTE FUNCTION record_import_batch_change();
+
+DROP TRIGGER IF EXISTS record_import_batch_change ON cash_flows;
+CREATE TRIGGER record_import_batch_change
+    AFTER INSERT OR UPDATE OR DELETE ON cash_flows
+    FOR EACH ROW EXECUTE FUNCTION record_import_batch_change();
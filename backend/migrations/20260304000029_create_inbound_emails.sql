@@ -0,0 +1,32 @@
+-- Email-in statement ingestion: each portfolio can have a private forwarding
+-- address (import+<token>@INBOUND_EMAIL_DOMAIN). Statement emails sent to it
+-- arrive through the mail provider's parsing webhook and their attachments
+-- are imported into the portfolio.
+CREATE TABLE IF NOT EXISTS inbound_email_addresses (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    portfolio_id UUID NOT NULL UNIQUE REFERENCES portfolios(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One row per accepted email. attachments holds the outcome for each
+-- attachment, including the import batch to roll back to undo it.
+CREATE TABLE IF NOT EXISTS inbound_emails (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    portfolio_id UUID NOT NULL REFERENCES portfolios(id) ON DELETE CASCADE,
+    -- Provider's Message-Id; redelivered webhooks are ignored
+    message_id TEXT,
+    sender TEXT NOT NULL,
+    subject TEXT,
+    status TEXT NOT NULL DEFAULT 'received'
+        CHECK (status IN ('received', 'processed', 'partial', 'failed', 'rejected')),
+    attachments JSONB NOT NULL DEFAULT '[]',
+    error TEXT,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_inbound_emails_portfolio ON inbound_emails(portfolio_id, received_at DESC);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_inbound_emails_message_id ON inbound_emails(portfolio_id, message_id);
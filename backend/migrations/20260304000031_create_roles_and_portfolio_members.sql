@@ -0,0 +1,31 @@
+-- Instance-wide role of each user. Admins may use the destructive admin
+-- endpoints; everyone else gets 'editor'.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'editor'
+    CHECK (role IN ('viewer', 'editor', 'admin'));
+
+-- The default seed user and the first real user administer the instance
+UPDATE users SET role = 'admin'
+WHERE id = '00000000-0000-0000-0000-000000000001'::UUID
+   OR id = (
+       SELECT u.id FROM users u
+       WHERE u.password_hash IS NOT NULL
+          OR EXISTS (SELECT 1 FROM user_identities i WHERE i.user_id = u.id)
+       ORDER BY u.created_at
+       LIMIT 1
+   );
+
+-- Users a portfolio is shared with, besides its owner (portfolios.user_id,
+-- who is implicitly an admin of it). Viewers can read the portfolio, editors
+-- can also change its accounts, positions, thresholds and snapshots, and
+-- admins can also rename, archive and share it.
+CREATE TABLE IF NOT EXISTS portfolio_members (
+    portfolio_id UUID NOT NULL REFERENCES portfolios(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('viewer', 'editor', 'admin')),
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (portfolio_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_portfolio_members_user ON portfolio_members(user_id);
@@ -0,0 +1,58 @@
+-- Daily valuations of each account between statement imports: the latest
+-- imported holdings priced at the latest close on or before the date. Dates
+-- with an imported snapshot are not valued; the import is authoritative.
+CREATE TABLE IF NOT EXISTS portfolio_value_history (
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    portfolio_id UUID NOT NULL REFERENCES portfolios(id) ON DELETE CASCADE,
+    snapshot_date DATE NOT NULL,
+    total_value NUMERIC NOT NULL,
+    total_cost NUMERIC NOT NULL,
+    -- Imported snapshot the valuation is based on
+    holdings_date DATE NOT NULL,
+    -- Holdings repriced from price history; the rest keep their imported value
+    priced_holdings INTEGER NOT NULL,
+    unpriced_holdings INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (account_id, snapshot_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_portfolio_value_history_portfolio
+    ON portfolio_value_history(portfolio_id, snapshot_date);
+
+-- Value history now fills the days between imports with the valuations
+-- above. `source` tells the two apart.
+CREATE OR REPLACE VIEW account_value_history AS
+SELECT * FROM (
+    SELECT
+        account_id,
+        snapshot_date,
+        SUM(market_value) as total_value,
+        SUM(book_value) as total_cost,
+        SUM(gain_loss) as total_gain_loss,
+        CASE
+            WHEN SUM(book_value) > 0 THEN (SUM(gain_loss) / SUM(book_value)) * 100
+            ELSE 0
+        END as total_gain_loss_pct,
+        'import'::TEXT as source
+    FROM holdings_snapshots
+    GROUP BY account_id, snapshot_date
+    UNION ALL
+    SELECT
+        v.account_id,
+        v.snapshot_date,
+        v.total_value,
+        v.total_cost,
+        v.total_value - v.total_cost,
+        CASE
+            WHEN v.total_cost > 0 THEN ((v.total_value - v.total_cost) / v.total_cost) * 100
+            ELSE 0
+        END,
+        'valuation'::TEXT
+    FROM portfolio_value_history v
+    WHERE NOT EXISTS (
+        SELECT 1 FROM holdings_snapshots hs
+        WHERE hs.account_id = v.account_id AND hs.snapshot_date = v.snapshot_date
+    )
+) history
+ORDER BY account_id, snapshot_date;
@@ -0,0 +1,20 @@
+-- Who changed what. Rows are written by the API after each data-changing
+-- operation and never updated. portfolio_id is kept without a foreign key so
+-- the record of a deleted portfolio outlives it.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    portfolio_id UUID,
+    entity_type TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('create', 'update', 'delete')),
+    -- The entity before and after the change; NULL for the side that doesn't exist
+    before JSONB,
+    after JSONB,
+    -- Changed fields only: {"field": {"before": ..., "after": ...}}
+    changes JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_portfolio ON audit_log(portfolio_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_user ON audit_log(user_id, created_at DESC);
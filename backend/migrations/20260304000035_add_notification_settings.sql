@@ -0,0 +1,11 @@
+-- Per-user notification settings. Quiet hours stay in notification_preferences
+-- next to the channel settings; these decide which alerts are emailed and when.
+ALTER TABLE user_preferences
+    ADD COLUMN IF NOT EXISTS email_on_critical_violations BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN IF NOT EXISTS digest_frequency VARCHAR(10) NOT NULL DEFAULT 'immediate'
+        CHECK (digest_frequency IN ('immediate', 'daily', 'weekly')),
+    ADD COLUMN IF NOT EXISTS last_digest_sent_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN user_preferences.email_on_critical_violations IS 'Email the owner when a portfolio newly crosses a critical risk threshold';
+COMMENT ON COLUMN user_preferences.digest_frequency IS 'Email alerts as they happen (immediate) or collected in a daily or weekly digest';
+COMMENT ON COLUMN user_preferences.last_digest_sent_at IS 'When the last digest was emailed; the next one covers notifications since then';
@@ -0,0 +1,58 @@
+-- Scope cached analytics to the user they were computed for.
+--
+-- Per-portfolio caches carry the owner's user_id, tied to the portfolio by a
+-- composite foreign key so a row can never name a different owner than its
+-- portfolio. Screening results are per user and keyed by (user_id, cache_key).
+
+ALTER TABLE portfolios ADD CONSTRAINT portfolios_id_user_id_key UNIQUE (id, user_id);
+
+-- portfolio_risk_cache
+ALTER TABLE portfolio_risk_cache ADD COLUMN user_id UUID;
+UPDATE portfolio_risk_cache c SET user_id = p.user_id FROM portfolios p WHERE p.id = c.portfolio_id;
+ALTER TABLE portfolio_risk_cache
+    ALTER COLUMN user_id SET NOT NULL,
+    ADD CONSTRAINT portfolio_risk_cache_tenant_fkey FOREIGN KEY (portfolio_id, user_id)
+        REFERENCES portfolios (id, user_id) ON DELETE CASCADE ON UPDATE CASCADE;
+CREATE INDEX idx_portfolio_risk_cache_user_id ON portfolio_risk_cache(user_id);
+
+-- portfolio_narrative_cache
+ALTER TABLE portfolio_narrative_cache ADD COLUMN user_id UUID;
+UPDATE portfolio_narrative_cache c SET user_id = p.user_id FROM portfolios p WHERE p.id = c.portfolio_id;
+ALTER TABLE portfolio_narrative_cache
+    ALTER COLUMN user_id SET NOT NULL,
+    ADD CONSTRAINT portfolio_narrative_cache_tenant_fkey FOREIGN KEY (portfolio_id, user_id)
+        REFERENCES portfolios (id, user_id) ON DELETE CASCADE ON UPDATE CASCADE;
+CREATE INDEX idx_portfolio_narrative_cache_user_id ON portfolio_narrative_cache(user_id);
+
+-- portfolio_correlations_cache
+ALTER TABLE portfolio_correlations_cache ADD COLUMN user_id UUID;
+UPDATE portfolio_correlations_cache c SET user_id = p.user_id FROM portfolios p WHERE p.id = c.portfolio_id;
+ALTER TABLE portfolio_correlations_cache
+    ALTER COLUMN user_id SET NOT NULL,
+    ADD CONSTRAINT portfolio_correlations_cache_tenant_fkey FOREIGN KEY (portfolio_id, user_id)
+        REFERENCES portfolios (id, user_id) ON DELETE CASCADE ON UPDATE CASCADE;
+CREATE INDEX idx_portfolio_correlations_cache_user_id ON portfolio_correlations_cache(user_id);
+
+-- downside_risk_cache never had a foreign key, so drop rows of deleted portfolios first
+DELETE FROM downside_risk_cache c WHERE NOT EXISTS (SELECT 1 FROM portfolios p WHERE p.id = c.portfolio_id);
+ALTER TABLE downside_risk_cache ADD COLUMN user_id UUID;
+UPDATE downside_risk_cache c SET user_id = p.user_id FROM portfolios p WHERE p.id = c.portfolio_id;
+ALTER TABLE downside_risk_cache
+    ALTER COLUMN user_id SET NOT NULL,
+    ADD CONSTRAINT downside_risk_cache_tenant_fkey FOREIGN KEY (portfolio_id, user_id)
+        REFERENCES portfolios (id, user_id) ON DELETE CASCADE ON UPDATE CASCADE;
+CREATE INDEX idx_downside_risk_cache_user_id ON downside_risk_cache(user_id);
+
+-- screening_cache: entries only live 15 minutes, so start over rather than guess owners
+DELETE FROM screening_cache;
+ALTER TABLE screening_cache
+    ADD COLUMN user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    DROP CONSTRAINT screening_cache_cache_key_key,
+    ADD CONSTRAINT screening_cache_user_id_cache_key_key UNIQUE (user_id, cache_key);
+DROP INDEX IF EXISTS idx_screening_cache_key;
+
+COMMENT ON COLUMN portfolio_risk_cache.user_id IS 'Owner of the portfolio; cache reads filter on it';
+COMMENT ON COLUMN portfolio_narrative_cache.user_id IS 'Owner of the portfolio; cache reads filter on it';
+COMMENT ON COLUMN portfolio_correlations_cache.user_id IS 'Owner of the portfolio; cache reads filter on it';
+COMMENT ON COLUMN downside_risk_cache.user_id IS 'Owner of the portfolio; cache reads filter on it';
+COMMENT ON COLUMN screening_cache.user_id IS 'User who ran the screen; results are never shared between users';
@@ -0,0 +1,21 @@
+-- Versioned record of LLM consent. Each grant or revocation is a new row so the
+-- history is kept; the latest row for a user is their current decision. A grant
+-- only counts while its version matches the consent text currently shown.
+CREATE TABLE llm_consents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL CHECK (version > 0),
+    granted BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_llm_consents_user_created ON llm_consents(user_id, created_at DESC);
+
+-- Carry over consent already given through user_preferences as version 1
+INSERT INTO llm_consents (user_id, version, granted, created_at)
+SELECT user_id, 1, TRUE, consent_given_at
+FROM user_preferences
+WHERE llm_enabled = TRUE AND consent_given_at IS NOT NULL;
+
+COMMENT ON TABLE llm_consents IS 'History of LLM consent grants and revocations; the latest row per user is in force';
+COMMENT ON COLUMN llm_consents.version IS 'Version of the consent text the user agreed to or withdrew from';
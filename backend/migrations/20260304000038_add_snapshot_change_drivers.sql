@@ -0,0 +1,8 @@
+-- Structured reasons for a material move in a portfolio's risk score, worked out
+-- against the previous snapshot when the snapshot is taken. NULL when the score
+-- barely moved, there was no comparable previous snapshot, or on position rows.
+ALTER TABLE risk_snapshots
+    ADD COLUMN IF NOT EXISTS change_drivers JSONB;
+
+COMMENT ON COLUMN risk_snapshots.change_drivers IS
+    'Why the risk score moved since the previous snapshot: positions added or removed, price shocks and weight shifts';
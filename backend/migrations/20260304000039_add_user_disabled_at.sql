@@ -0,0 +1,5 @@
+-- Administrators can disable an account without deleting its data. A disabled
+-- user cannot sign in, and disabling revokes their open sessions.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS disabled_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN users.disabled_at IS 'When an administrator disabled the account; NULL while it is active';
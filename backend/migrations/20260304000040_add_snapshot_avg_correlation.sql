@@ -0,0 +1,9 @@
+-- Average pairwise correlation between a portfolio's positions, taken from the
+-- fresh 90-day correlation cache when the portfolio snapshot is written. Gives
+-- the anomaly detector a correlation history next to volatility and beta.
+-- NULL on position rows and when no fresh correlation data was cached.
+ALTER TABLE risk_snapshots
+    ADD COLUMN IF NOT EXISTS avg_correlation NUMERIC(10, 4);
+
+COMMENT ON COLUMN risk_snapshots.avg_correlation IS
+    'Average pairwise position correlation (90-day) at snapshot time; portfolio rows only';
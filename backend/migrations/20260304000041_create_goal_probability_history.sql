@@ -0,0 +1,27 @@
+-- Monte Carlo success probability of each financial goal, recalculated nightly
+-- from the goal's current savings, planned contributions and the survey's risk
+-- profile. One row per goal per day, so clients can chart the trend rather
+-- than a single point estimate. Re-running a day replaces its row.
+CREATE TABLE IF NOT EXISTS goal_probability_history (
+    goal_id UUID NOT NULL REFERENCES survey_goals(id) ON DELETE CASCADE,
+    survey_id UUID NOT NULL REFERENCES financial_surveys(id) ON DELETE CASCADE,
+    calculated_on DATE NOT NULL,
+    -- Share of simulated paths ending at or above the target (0-1)
+    success_probability NUMERIC(5, 4) NOT NULL,
+    median_outcome NUMERIC(15, 2) NOT NULL,
+    p10_outcome NUMERIC(15, 2) NOT NULL,
+    p90_outcome NUMERIC(15, 2) NOT NULL,
+    -- Inputs the simulation ran with
+    target_amount NUMERIC(15, 2) NOT NULL,
+    starting_balance NUMERIC(15, 2) NOT NULL,
+    annual_contribution NUMERIC(15, 2) NOT NULL,
+    horizon_months INTEGER NOT NULL,
+    expected_return NUMERIC(6, 4) NOT NULL,
+    volatility NUMERIC(6, 4) NOT NULL,
+    simulations INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (goal_id, calculated_on)
+);
+
+CREATE INDEX IF NOT EXISTS idx_goal_probability_history_survey
+    ON goal_probability_history(survey_id, calculated_on);
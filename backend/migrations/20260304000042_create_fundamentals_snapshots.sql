@@ -0,0 +1,8 @@
+-- Valuation fundamentals (P/E, P/B, market cap) per ticker from the fundamentals
+-- provider, refreshed at most daily. Screening and factor scoring read the
+-- stored copy and fall back to price-based proxies for tickers without one.
+CREATE TABLE IF NOT EXISTS fundamentals_snapshots (
+    ticker TEXT PRIMARY KEY,
+    data JSONB NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
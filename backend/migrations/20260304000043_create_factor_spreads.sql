@@ -0,0 +1,13 @@
+-- Daily long-short factor returns over the tracked universe: the equal-weighted
+-- return of the top quintile by factor score minus the bottom quintile, ranked
+-- on the previous close. Re-running a day replaces its rows.
+CREATE TABLE IF NOT EXISTS factor_spreads (
+    date DATE NOT NULL,
+    factor TEXT NOT NULL,
+    long_return DOUBLE PRECISION NOT NULL,
+    short_return DOUBLE PRECISION NOT NULL,
+    spread DOUBLE PRECISION NOT NULL,
+    universe_size INTEGER NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (date, factor)
+);
@@ -0,0 +1,30 @@
+-- Constituents and sector weights of ETFs held in portfolios, refreshed weekly by
+-- the ETF constituent job from issuer holdings files or the ETF holdings provider.
+-- Each refresh replaces all of an ETF's rows. Weights are percent of the fund (0-100).
+CREATE TABLE IF NOT EXISTS etf_constituents (
+    etf_ticker TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    -- NULL for positions without a listed symbol (cash, futures, swaps)
+    constituent_ticker TEXT,
+    name TEXT NOT NULL,
+    weight DOUBLE PRECISION NOT NULL,
+    sector TEXT,
+    PRIMARY KEY (etf_ticker, position)
+);
+
+CREATE INDEX IF NOT EXISTS idx_etf_constituents_constituent ON etf_constituents(constituent_ticker);
+
+CREATE TABLE IF NOT EXISTS etf_sector_weights (
+    etf_ticker TEXT NOT NULL,
+    sector TEXT NOT NULL,
+    weight DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (etf_ticker, sector)
+);
+
+-- Last successful refresh per ETF
+CREATE TABLE IF NOT EXISTS etf_constituent_refreshes (
+    etf_ticker TEXT PRIMARY KEY,
+    source TEXT NOT NULL,
+    constituent_count INTEGER NOT NULL,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,8 @@
+-- Split- and dividend-adjusted close next to the raw close, from providers
+-- that report one (Tiingo). Risk metrics read the adjusted close where present
+-- so splits don't show up as drawdowns; valuations keep using the raw close.
+ALTER TABLE price_points
+    ADD COLUMN IF NOT EXISTS adjusted_close NUMERIC;
+
+COMMENT ON COLUMN price_points.adjusted_close IS
+    'Close adjusted for later splits and dividends; NULL when the provider reports raw closes only';
@@ -0,0 +1,24 @@
+-- Announced dividends of held tickers, refreshed daily from the dividend
+-- provider. Amounts are per share and may be the previous payment when the
+-- provider announces the date first.
+CREATE TABLE IF NOT EXISTS dividend_events (
+    ticker TEXT NOT NULL,
+    ex_date DATE NOT NULL,
+    pay_date DATE,
+    amount DOUBLE PRECISION,
+    annual_rate DOUBLE PRECISION,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (ticker, ex_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dividend_events_ex_date ON dividend_events (ex_date);
+
+-- Ex-dividend reminders already sent, so each rule reminds once per ticker and
+-- ex-date however often the job runs
+CREATE TABLE IF NOT EXISTS ex_dividend_reminders (
+    rule_id UUID NOT NULL REFERENCES alert_rules(id) ON DELETE CASCADE,
+    ticker TEXT NOT NULL,
+    ex_date DATE NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (rule_id, ticker, ex_date)
+);
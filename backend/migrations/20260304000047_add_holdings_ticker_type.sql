@@ -0,0 +1,33 @@
+-- Ticker type of a holding: equities are priced by the configured stock
+-- provider, crypto (tickered <SYMBOL>-USD) by CoinGecko
+ALTER TABLE holdings_snapshots
+    ADD COLUMN ticker_type TEXT NOT NULL DEFAULT 'equity'
+        CHECK (ticker_type IN ('equity', 'crypto'));
+
+-- Wallet imports already write crypto holdings under the CRYPTO asset category
+UPDATE holdings_snapshots
+SET ticker_type = 'crypto'
+WHERE asset_category = 'CRYPTO' OR ticker ~ '^[A-Z0-9]{2,10}-USD$';
+
+DROP VIEW IF EXISTS latest_account_holdings;
+
+CREATE VIEW latest_account_holdings AS
+SELECT DISTINCT ON (h.account_id, h.ticker)
+    h.id,
+    h.account_id,
+    a.account_nickname,
+    a.account_number,
+    h.ticker,
+    h.holding_name,
+    h.asset_category,
+    h.industry,
+    h.quantity,
+    h.price,
+    h.market_value,
+    h.gain_loss,
+    h.gain_loss_pct,
+    h.snapshot_date,
+    h.ticker_type
+FROM holdings_snapshots h
+JOIN accounts a ON h.account_id = a.id
+ORDER BY h.account_id, h.ticker, h.snapshot_date DESC;
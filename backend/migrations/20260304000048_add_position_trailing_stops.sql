@@ -0,0 +1,11 @@
+-- Trailing stops on positions: alert when the price falls trailing_stop_pct percent
+-- below the highest close since the position was opened.
+--
+-- trailing_high is the high-water mark kept by the watchlist monitoring job. It is
+-- seeded from stored closes since the account's first snapshot holding the ticker,
+-- and reset whenever the percentage is changed.
+
+ALTER TABLE position_annotations ADD COLUMN IF NOT EXISTS trailing_stop_pct DOUBLE PRECISION
+    CHECK (trailing_stop_pct > 0 AND trailing_stop_pct < 100);
+ALTER TABLE position_annotations ADD COLUMN IF NOT EXISTS trailing_high DOUBLE PRECISION;
+ALTER TABLE position_annotations ADD COLUMN IF NOT EXISTS trailing_alerted_at TIMESTAMPTZ;
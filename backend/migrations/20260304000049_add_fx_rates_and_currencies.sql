@@ -0,0 +1,63 @@
+-- Multi-currency portfolios.
+--
+-- fx_rates holds daily reference rates (ECB, via Frankfurter) as units of each
+-- currency per US dollar. Holdings and prices record the currency they are
+-- quoted in, and each user picks the base currency portfolio totals, weights and
+-- analytics are converted into.
+
+CREATE TABLE IF NOT EXISTS fx_rates (
+    rate_date DATE NOT NULL,
+    currency TEXT NOT NULL,
+    units_per_usd NUMERIC NOT NULL CHECK (units_per_usd > 0),
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (rate_date, currency)
+);
+
+ALTER TABLE holdings_snapshots ADD COLUMN IF NOT EXISTS currency TEXT NOT NULL DEFAULT 'USD';
+ALTER TABLE price_points ADD COLUMN IF NOT EXISTS currency TEXT NOT NULL DEFAULT 'USD';
+ALTER TABLE user_preferences ADD COLUMN IF NOT EXISTS base_currency TEXT NOT NULL DEFAULT 'USD';
+
+-- Listings outside the US are quoted in their exchange's currency
+UPDATE holdings_snapshots SET currency = CASE
+        WHEN ticker ~* '\.(TO|V|CN|NE)$' THEN 'CAD'
+        WHEN ticker ~* '\.AX$' THEN 'AUD'
+        WHEN ticker ~* '\.HK$' THEN 'HKD'
+        WHEN ticker ~* '\.T$' THEN 'JPY'
+        WHEN ticker ~* '\.SW$' THEN 'CHF'
+        ELSE 'EUR'
+    END
+WHERE ticker ~* '\.(TO|V|CN|NE|AX|HK|T|SW|DE|F|PA|AS|MI|MC)$';
+
+UPDATE price_points SET currency = CASE
+        WHEN ticker ~* '\.(TO|V|CN|NE)$' THEN 'CAD'
+        WHEN ticker ~* '\.AX$' THEN 'AUD'
+        WHEN ticker ~* '\.HK$' THEN 'HKD'
+        WHEN ticker ~* '\.T$' THEN 'JPY'
+        WHEN ticker ~* '\.SW$' THEN 'CHF'
+        ELSE 'EUR'
+    END
+WHERE ticker ~* '\.(TO|V|CN|NE|AX|HK|T|SW|DE|F|PA|AS|MI|MC)$';
+
+DROP VIEW IF EXISTS latest_account_holdings;
+
+CREATE VIEW latest_account_holdings AS
+SELECT DISTINCT ON (h.account_id, h.ticker)
+    h.id,
+    h.account_id,
+    a.account_nickname,
+    a.account_number,
+    h.ticker,
+    h.holding_name,
+    h.asset_category,
+    h.industry,
+    h.quantity,
+    h.price,
+    h.market_value,
+    h.gain_loss,
+    h.gain_loss_pct,
+    h.snapshot_date,
+    h.ticker_type,
+    h.currency
+FROM holdings_snapshots h
+JOIN accounts a ON h.account_id = a.id
+ORDER BY h.account_id, h.ticker, h.snapshot_date DESC;
@@ -0,0 +1,5 @@
+-- Language of prose services produce for a user (recommendations, findings,
+-- LLM narratives). Formatting conventions follow from the locale.
+ALTER TABLE user_preferences
+    ADD COLUMN IF NOT EXISTS locale TEXT NOT NULL DEFAULT 'en'
+    CHECK (locale IN ('en', 'fr', 'es'));
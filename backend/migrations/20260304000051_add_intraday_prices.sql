@@ -0,0 +1,19 @@
+-- Intraday bars for today's movement on the dashboard.
+--
+-- Only recent sessions are kept: bars are refreshed on read and older ones are
+-- pruned, so daily history stays in price_points.
+
+CREATE TABLE IF NOT EXISTS intraday_prices (
+    ticker TEXT NOT NULL,
+    interval TEXT NOT NULL CHECK (interval IN ('1min', '5min', '15min', '30min', '60min')),
+    bar_time TIMESTAMPTZ NOT NULL,
+    open NUMERIC NOT NULL,
+    high NUMERIC NOT NULL,
+    low NUMERIC NOT NULL,
+    close NUMERIC NOT NULL,
+    volume BIGINT,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (ticker, interval, bar_time)
+);
+
+CREATE INDEX IF NOT EXISTS idx_intraday_prices_bar_time ON intraday_prices (bar_time);
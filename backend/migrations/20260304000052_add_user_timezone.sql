@@ -0,0 +1,4 @@
+-- Time zone of a user's "daily" boundaries: snapshot dates of their portfolios
+-- and when digests are delivered. An IANA name such as 'America/New_York'.
+ALTER TABLE user_preferences
+    ADD COLUMN IF NOT EXISTS timezone TEXT NOT NULL DEFAULT 'UTC';
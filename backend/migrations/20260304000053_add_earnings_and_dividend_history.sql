@@ -0,0 +1,26 @@
+-- Reported fundamentals per ticker, refreshed daily by the fundamentals job for
+-- held tickers. Key ratios (P/E, ROE, margins, leverage, dividend yield) extend
+-- the JSON in fundamentals_snapshots; earnings and dividends are time series.
+
+-- Quarterly earnings per share with the consensus estimate before each report
+CREATE TABLE IF NOT EXISTS earnings_reports (
+    ticker TEXT NOT NULL,
+    period_end DATE NOT NULL,
+    fiscal_year INTEGER NOT NULL,
+    fiscal_quarter INTEGER NOT NULL,
+    eps_actual NUMERIC,
+    eps_estimate NUMERIC,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (ticker, period_end)
+);
+
+-- Past dividend payments per share, by ex-date
+CREATE TABLE IF NOT EXISTS dividend_history (
+    ticker TEXT NOT NULL,
+    ex_date DATE NOT NULL,
+    pay_date DATE,
+    amount NUMERIC NOT NULL CHECK (amount > 0),
+    currency TEXT,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (ticker, ex_date)
+);
@@ -0,0 +1,14 @@
+-- Why a buy or sell was made and what it was expected to achieve, so past
+-- decisions can be reviewed against what the price actually did.
+CREATE TABLE IF NOT EXISTS trade_journal_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL UNIQUE REFERENCES detected_transactions(id) ON DELETE CASCADE,
+    rationale TEXT NOT NULL,
+    target_price DOUBLE PRECISION CHECK (target_price > 0),
+    stop_price DOUBLE PRECISION CHECK (stop_price > 0),
+    expected_return_pct DOUBLE PRECISION,
+    horizon_days INTEGER CHECK (horizon_days > 0),
+    confidence SMALLINT CHECK (confidence BETWEEN 1 AND 5),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
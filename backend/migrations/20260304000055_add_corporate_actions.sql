@@ -0,0 +1,12 @@
+-- Splits and cash dividends per ticker, fetched from the price provider. The
+-- adjustment pass rebuilds price_points.adjusted_close from them so returns
+-- across an ex-date aren't read as a crash or a jump.
+CREATE TABLE IF NOT EXISTS corporate_actions (
+    ticker TEXT NOT NULL,
+    ex_date DATE NOT NULL,
+    action_type TEXT NOT NULL CHECK (action_type IN ('split', 'dividend')),
+    -- New shares per old share for a split, cash per share for a dividend
+    value NUMERIC NOT NULL CHECK (value > 0),
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (ticker, ex_date, action_type)
+);
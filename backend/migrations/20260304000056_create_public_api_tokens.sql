@@ -0,0 +1,13 @@
+-- Read-only tokens for the public API (home dashboards such as Grafana or
+-- Home Assistant). Only the SHA-256 of each token is stored; the token itself
+-- is shown once, when it is created.
+CREATE TABLE IF NOT EXISTS public_api_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_public_api_tokens_user ON public_api_tokens(user_id);
@@ -0,0 +1,13 @@
+-- Symbol search results from the price provider, so tickers can be validated
+-- and looked up again without another provider call.
+CREATE TABLE IF NOT EXISTS ticker_metadata (
+    ticker TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    exchange TEXT,
+    asset_type TEXT NOT NULL,
+    region TEXT,
+    currency TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticker_metadata_name ON ticker_metadata (LOWER(name) text_pattern_ops);
@@ -0,0 +1,25 @@
+-- Announced earnings dates of held tickers, refreshed by the fundamentals job.
+-- hour is the provider's session marker: bmo (before open), amc (after close)
+-- or dmh (during market hours).
+CREATE TABLE IF NOT EXISTS earnings_calendar (
+    ticker TEXT NOT NULL,
+    report_date DATE NOT NULL,
+    hour TEXT,
+    fiscal_year INTEGER,
+    fiscal_quarter INTEGER,
+    eps_estimate DOUBLE PRECISION,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (ticker, report_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_earnings_calendar_date ON earnings_calendar (report_date);
+
+-- Recurring rebalance reminder per portfolio: the first rebalance is on
+-- start_date and later ones follow every frequency.
+CREATE TABLE IF NOT EXISTS portfolio_rebalance_schedules (
+    portfolio_id UUID PRIMARY KEY REFERENCES portfolios(id) ON DELETE CASCADE,
+    frequency TEXT NOT NULL CHECK (frequency IN ('monthly', 'quarterly', 'semiannual', 'annual')),
+    start_date DATE NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,15 @@
+-- Scores of stored snapshots recomputed under another scoring model version,
+-- kept beside the live score so a methodology change can be compared against
+-- history before it is promoted. NULL until a recompute covers the row; an
+-- upsert of fresh metrics clears them again.
+ALTER TABLE risk_snapshots
+    ADD COLUMN IF NOT EXISTS shadow_risk_score NUMERIC(5, 2),
+    ADD COLUMN IF NOT EXISTS shadow_risk_level TEXT,
+    ADD COLUMN IF NOT EXISTS shadow_scoring_version INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_risk_snapshots_shadow_version
+    ON risk_snapshots (shadow_scoring_version)
+    WHERE shadow_scoring_version IS NOT NULL;
+
+COMMENT ON COLUMN risk_snapshots.shadow_risk_score IS
+    'Risk score recomputed from the stored metrics under shadow_scoring_version';
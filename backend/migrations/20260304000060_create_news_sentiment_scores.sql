@@ -0,0 +1,15 @@
+-- News sentiment per ticker as scored by the news provider, one row per
+-- refresh, so screening can use the latest score and its age.
+CREATE TABLE IF NOT EXISTS news_sentiment_scores (
+    ticker TEXT NOT NULL,
+    scored_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Relevance-weighted mean of the articles' scores, -1 (bearish) to +1 (bullish)
+    sentiment_score DOUBLE PRECISION NOT NULL,
+    articles_analyzed INTEGER NOT NULL,
+    -- Publication time of the newest article scored
+    latest_article_at TIMESTAMPTZ,
+    source TEXT NOT NULL,
+    PRIMARY KEY (ticker, scored_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_news_sentiment_scores_scored_at ON news_sentiment_scores (scored_at);
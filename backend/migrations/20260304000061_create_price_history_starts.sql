@@ -0,0 +1,8 @@
+-- Earliest close the price provider has for a ticker, recorded when a full
+-- history backfill comes back shorter than requested (recent listings), so
+-- the backfill job stops asking for history that doesn't exist.
+CREATE TABLE IF NOT EXISTS price_history_starts (
+    ticker TEXT PRIMARY KEY,
+    history_starts DATE NOT NULL,
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,15 @@
+-- Tokens for portfolio widgets embedded on users' own sites. Each token reads
+-- one portfolio's headline figures and is served with CORS for one origin.
+-- Only the SHA-256 of each token is stored; the token itself is shown once.
+CREATE TABLE IF NOT EXISTS widget_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    portfolio_id UUID NOT NULL REFERENCES portfolios(id) ON DELETE CASCADE,
+    created_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    allowed_origin TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_widget_tokens_portfolio ON widget_tokens(portfolio_id);
@@ -0,0 +1,12 @@
+-- Daily academic factor returns (Kenneth French's data library) for the
+-- per-holding factor regressions: market excess return, size (SMB), value
+-- (HML), momentum and the risk-free rate, as fractions.
+CREATE TABLE IF NOT EXISTS factor_returns (
+    factor TEXT NOT NULL,
+    date DATE NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (factor, date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_factor_returns_date ON factor_returns(date);
@@ -0,0 +1,49 @@
+-- Migration: Covering index for per-ticker price windows
+-- Created: 2026-03-04
+-- Purpose: Keep fetch_window / fetch_window_batch fast on multi-year history
+--
+-- fetch_window_batch used to read every stored close for every requested ticker
+-- (`WHERE ticker = ANY($1) ORDER BY ticker, date DESC`) and trim to the last N
+-- days in Rust. With a few hundred tickers of ten years' history that is a
+-- sequential scan plus an on-disk sort of close to a million rows per call. The
+-- query now walks each ticker's newest N rows through a LATERAL ... LIMIT, which
+-- this index serves as an index-only scan.
+--
+-- EXPLAIN ANALYZE execution times from scripts/bench_price_windows.sql
+-- (PostgreSQL 15, 400 tickers x ~10.8 years of weekday closes, 1.13M rows
+-- interleaved by date as the nightly ingest writes them), fetching a 90-day
+-- window for 300 tickers, the correlation endpoint's default:
+--
+--   old query, old indexes        ~2.5 s    844,800 rows returned
+--   LATERAL query, old indexes    ~66 ms     27,000 rows returned
+--   LATERAL query, this index     ~56 ms     27,000 rows returned
+--
+-- The single-ticker fetch_window / fetch_adjusted_window queries were already
+-- per-ticker LIMITs; with this index they are index-only scans too (~0.2 ms for
+-- a year of closes).
+--
+-- Monthly partitioning was considered and rejected: every hot query is keyed by
+-- ticker rather than by date range, so partition pruning would not help, and the
+-- (ticker, date) upsert target would have to span all partitions.
+
+-- =============================================================================
+-- PRICE_POINTS TABLE INDEXES
+-- =============================================================================
+
+-- Newest-first per ticker, carrying every column the window queries select so
+-- the heap is only touched for pages not yet marked all-visible.
+CREATE INDEX IF NOT EXISTS idx_price_points_ticker_date_desc
+ON price_points (ticker, date DESC)
+INCLUDE (close_price, adjusted_close, created_at, id);
+
+-- Duplicate of the index behind price_points_ticker_date_unique; the planner
+-- never prefers it and every insert pays to maintain it.
+DROP INDEX IF EXISTS idx_prices_ticker_date;
+
+-- Trigram GIN index added for `ticker = ANY(array)` lookups. The planner picks a
+-- B-tree (or a sequential scan) for equality on ticker, so this index only
+-- costs write amplification on the nightly price upserts.
+DROP INDEX IF EXISTS idx_prices_ticker_array;
+
+COMMENT ON INDEX idx_price_points_ticker_date_desc IS
+'Covering index for the most recent N closes per ticker (fetch_window, fetch_adjusted_window and their batch forms). Serves LATERAL ... LIMIT lookups as index-only scans.';
@@ -0,0 +1,10 @@
+-- Mailgun signs each webhook over (timestamp, token) with a fresh random
+-- token. Tokens of accepted webhooks are kept for the signature window so a
+-- captured request cannot be replayed while its timestamp is still current.
+CREATE TABLE IF NOT EXISTS mailgun_webhook_tokens (
+    token TEXT PRIMARY KEY,
+    -- The webhook's signed timestamp; rows older than the window are pruned
+    signed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_mailgun_webhook_tokens_signed_at ON mailgun_webhook_tokens(signed_at);
@@ -0,0 +1,95 @@
+-- Benchmark for the per-ticker price window queries in db/price_queries.rs
+-- (fetch_window_batch, fetch_adjusted_window_batch, fetch_window,
+-- fetch_adjusted_window) and the covering index from migration
+-- 20260304000064_optimize_price_points_windows.sql.
+--
+-- Run it against a scratch database with all migrations applied, never a
+-- shared one: it writes ~1.13M rows and briefly drops the covering index
+-- inside a rolled-back transaction.
+--
+--   psql "$DATABASE_URL" -f scripts/bench_price_windows.sql
+--
+-- It seeds 400 BENCH tickers with weekday closes from 2016-01-01 to
+-- 2026-10-16, interleaved by date as the nightly ingest writes them, then
+-- prints EXPLAIN ANALYZE for a 90-day window over 300 tickers (the correlation
+-- endpoint's default) with the old and current query and index, and finally
+-- deletes the synthetic rows.
+
+\set ON_ERROR_STOP on
+
+INSERT INTO price_points (id, ticker, date, close_price, adjusted_close)
+SELECT gen_random_uuid(),
+       'BENCH' || lpad(t::text, 4, '0'),
+       d::date,
+       round((50 + random() * 100)::numeric, 2),
+       CASE WHEN t % 4 = 0 THEN round((50 + random() * 100)::numeric, 2) END
+FROM generate_series('2016-01-01'::date, '2026-10-16'::date, interval '1 day') AS d
+CROSS JOIN generate_series(1, 400) AS t
+WHERE extract(isodow FROM d) < 6
+ORDER BY d, t;
+
+-- Set the visibility map so index-only scans behave as on a settled table
+VACUUM ANALYZE price_points;
+
+SELECT '{' || string_agg('BENCH' || lpad(g::text, 4, '0'), ',') || '}' AS tickers
+FROM generate_series(1, 300) AS g \gset
+
+\echo '== Before: the old fetch_window_batch query, old indexes'
+BEGIN;
+DROP INDEX idx_price_points_ticker_date_desc;
+EXPLAIN (ANALYZE, BUFFERS)
+SELECT id, ticker, date, close_price, created_at
+FROM price_points
+WHERE ticker = ANY(:'tickers'::text[])
+ORDER BY ticker, date DESC;
+
+\echo '== LATERAL fetch_window_batch query, old indexes'
+EXPLAIN (ANALYZE, BUFFERS)
+SELECT p.id, p.ticker, p.date, p.close_price, p.created_at
+FROM (SELECT DISTINCT UNNEST(:'tickers'::text[])) AS t(ticker)
+CROSS JOIN LATERAL (
+    SELECT id, ticker, date, close_price, created_at
+    FROM price_points
+    WHERE ticker = t.ticker
+    ORDER BY date DESC
+    LIMIT 90
+) p
+ORDER BY p.ticker, p.date;
+ROLLBACK;
+
+\echo '== After: LATERAL fetch_window_batch query, covering index'
+EXPLAIN (ANALYZE, BUFFERS)
+SELECT p.id, p.ticker, p.date, p.close_price, p.created_at
+FROM (SELECT DISTINCT UNNEST(:'tickers'::text[])) AS t(ticker)
+CROSS JOIN LATERAL (
+    SELECT id, ticker, date, close_price, created_at
+    FROM price_points
+    WHERE ticker = t.ticker
+    ORDER BY date DESC
+    LIMIT 90
+) p
+ORDER BY p.ticker, p.date;
+
+\echo '== After: LATERAL fetch_adjusted_window_batch query, covering index'
+EXPLAIN (ANALYZE, BUFFERS)
+SELECT p.id, p.ticker, p.date, p.close_price, p.created_at
+FROM (SELECT DISTINCT UNNEST(:'tickers'::text[])) AS t(ticker)
+CROSS JOIN LATERAL (
+    SELECT id, ticker, date, COALESCE(adjusted_close, close_price) AS close_price, created_at
+    FROM price_points
+    WHERE ticker = t.ticker
+    ORDER BY date DESC
+    LIMIT 90
+) p
+ORDER BY p.ticker, p.date;
+
+\echo '== After: single-ticker fetch_adjusted_window, one year, covering index'
+EXPLAIN (ANALYZE, BUFFERS)
+SELECT id, ticker, date, COALESCE(adjusted_close, close_price) AS close_price, created_at
+FROM price_points
+WHERE ticker = 'BENCH0004'
+ORDER BY date DESC
+LIMIT 252;
+
+DELETE FROM price_points WHERE ticker LIKE 'BENCH%';
+VACUUM ANALYZE price_points;
@@ -1,38 +1,79 @@
 use axum::Router;
 
 use crate::routes::{
-    portfolios, prices, analytics, health, accounts, imports, cash_flows, transactions,
+    portfolios, prices, tickers, analytics, health, accounts, imports, cash_flows, transactions,
     admin, risk, optimization, llm, news, qa, sentiment, jobs, alerts, market, preferences,
-    signals, recommendations, watchlists, financial_planning, auth,
+    signals, recommendations, watchlists, financial_planning, auth, ownership, analyst, fundamentals, model_portfolios,
+    user_data, inbound_email, audit, admin_users, public_api, datasource, providers, chaos,
+    widgets,
 };
+use crate::middleware::request_context::{self, REQUEST_ID_HEADER};
+use crate::services::chaos as chaos_service;
+use crate::services::public_api_service::PublicApiConfig;
+use crate::services::widget_service::WidgetConfig;
 use crate::state::AppState;
-use tower_http::cors::{AllowOrigin, CorsLayer};
+use axum::middleware::from_fn;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowCredentials, AllowOrigin, CorsLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::TraceLayer;
 use http::header::{AUTHORIZATION, CONTENT_TYPE, HeaderValue};
 use http::Method;
 
+/// Responses smaller than this are sent uncompressed
+const COMPRESSION_MIN_BYTES: u16 = 1024;
 
 
 pub fn create_app(state: AppState) -> Router {
+    // Widgets answer their token's origin themselves and never with credentials
+    let is_widget = |parts: &http::request::Parts| parts.uri.path().starts_with(widgets::WIDGET_PATH_PREFIX);
     let cors = CorsLayer::new()
-        .allow_origin(AllowOrigin::predicate(|origin: &HeaderValue, _| {
-            origin.as_bytes().starts_with(b"http://localhost:")
-                || origin.as_bytes().starts_with(b"http://127.0.0.1:")
+        .allow_origin(AllowOrigin::predicate(move |origin: &HeaderValue, parts| {
+            (origin.as_bytes().starts_with(b"http://localhost:")
+                || origin.as_bytes().starts_with(b"http://127.0.0.1:"))
+                && !is_widget(parts)
         }))
         .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE, Method::OPTIONS])
         .allow_headers([CONTENT_TYPE, AUTHORIZATION])
-        .allow_credentials(true);
+        .allow_credentials(AllowCredentials::predicate(move |_, parts| !is_widget(parts)));
 
-    Router::<AppState>::new()
+    // Correlation matrices, rolling beta series and exports run to hundreds of
+    // KB of JSON; small responses aren't worth compressing. XLSX is already zipped.
+    let compression = CompressionLayer::new()
+        .br(true)
+        .gzip(true)
+        .compress_when(
+            SizeAbove::new(COMPRESSION_MIN_BYTES)
+                .and(NotForContentType::GRPC)
+                .and(NotForContentType::IMAGES)
+                .and(NotForContentType::SSE)
+                .and(NotForContentType::const_new("application/vnd.openxmlformats")),
+        );
+
+    let slow_request_threshold = request_context::slow_request_threshold();
+    let trace = TraceLayer::new_for_http()
+        .make_span_with(request_context::make_request_span)
+        .on_response(move |response: &axum::response::Response, latency, span: &tracing::Span| {
+            request_context::on_response(response, latency, span, slow_request_threshold)
+        })
+        .on_failure(());
+
+    let mut router = Router::<AppState>::new()
         .nest("/health", health::router())
         .nest("/api/auth", auth::router())
         .nest("/api/portfolios", portfolios::router())
         .nest("/api", accounts::router())
         .nest("/api", imports::router())
+        .nest("/api", inbound_email::router())
         .nest("/api", cash_flows::router())
         .nest("/api", transactions::router())
         .nest("/api", admin::router())
         .nest("/api/admin/jobs", jobs::router())
+        .nest("/api/admin/users", admin_users::router())
         .nest("/api/prices", prices::router())
+        .nest("/api/tickers", tickers::router())
+        .nest("/api/providers", providers::router())
         .nest("/api/analytics", analytics::router())
         .nest("/api/risk", risk::router())
         .nest("/api/optimization", optimization::router())
@@ -42,11 +83,46 @@ pub fn create_app(state: AppState) -> Router {
         .nest("/api/sentiment", sentiment::router())
         .nest("/api", alerts::router())
         .nest("/api", market::router())
+        .nest("/api", ownership::router())
+        .nest("/api", analyst::router())
+        .nest("/api", fundamentals::router())
+        .nest("/api/model-portfolios", model_portfolios::router())
         .nest("/api", preferences::router())
+        .nest("/api", user_data::router())
         .nest("/api/stocks", signals::router())
         .nest("/api/recommendations", recommendations::router())
         .nest("/api", watchlists::router())
         .nest("/api/financial-planning", financial_planning::router())
+        .nest("/api/audit", audit::router())
+        .nest("/api", public_api::router())
+        .nest("/api", widgets::router());
+
+    // Read-only routes for home dashboards, authenticated by public API token
+    if PublicApiConfig::from_env().enabled {
+        tracing::info!("Public read-only API enabled at /api/public and /api/datasource");
+        router = router
+            .nest("/api/public", public_api::public_router())
+            .nest("/api/datasource", datasource::router());
+    }
+
+    // Portfolio widgets for embedding on users' own sites, authenticated by widget token
+    if WidgetConfig::from_env().enabled {
+        tracing::info!("Embeddable widgets enabled at /api/widgets");
+        router = router.nest("/api/widgets", widgets::widget_router());
+    }
+
+    // Fault injection settings, only while chaos is enabled for resilience tests
+    if chaos_service::installed().is_some() {
+        router = router.nest("/api/admin/chaos", chaos::router());
+    }
+
+    router
         .with_state(state)
+        .layer(from_fn(request_context::record_latency))
+        .layer(from_fn(request_context::record_path_context))
+        .layer(trace)
+        .layer(PropagateRequestIdLayer::new(REQUEST_ID_HEADER))
+        .layer(SetRequestIdLayer::new(REQUEST_ID_HEADER, MakeRequestUuid))
+        .layer(compression)
         .layer(cors)
 }
@@ -5,10 +5,18 @@ use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation}
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+
+/// Lifetime of an access token; the browser renews it with its refresh token
+pub const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+/// How long a session lasts without being refreshed
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
     sub: String,
+    /// Session the token was issued for
+    sid: String,
     exp: usize,
     iat: usize,
 }
@@ -29,13 +37,14 @@ pub fn verify_password(hash: &str, password: &str) -> Result<bool, String> {
         .is_ok())
 }
 
-pub fn create_jwt(user_id: Uuid, secret: &str) -> Result<String, String> {
+pub fn create_jwt(user_id: Uuid, session_id: Uuid, secret: &str) -> Result<String, String> {
     let now = Utc::now();
-    let exp = (now + Duration::hours(24)).timestamp() as usize;
+    let exp = (now + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES)).timestamp() as usize;
     let iat = now.timestamp() as usize;
 
     let claims = Claims {
         sub: user_id.to_string(),
+        sid: session_id.to_string(),
         exp,
         iat,
     };
@@ -48,7 +57,8 @@ pub fn create_jwt(user_id: Uuid, secret: &str) -> Result<String, String> {
     .map_err(|e| e.to_string())
 }
 
-pub fn validate_jwt(token: &str, secret: &str) -> Result<Uuid, String> {
+/// Returns the user and session the token was issued for
+pub fn validate_jwt(token: &str, secret: &str) -> Result<(Uuid, Uuid), String> {
     let token_data = decode::<Claims>(
         token,
         &DecodingKey::from_secret(secret.as_bytes()),
@@ -56,5 +66,42 @@ pub fn validate_jwt(token: &str, secret: &str) -> Result<Uuid, String> {
     )
     .map_err(|e| e.to_string())?;
 
-    Uuid::parse_str(&token_data.claims.sub).map_err(|e| e.to_string())
+    let user_id = Uuid::parse_str(&token_data.claims.sub).map_err(|e| e.to_string())?;
+    let session_id = Uuid::parse_str(&token_data.claims.sid).map_err(|e| e.to_string())?;
+    Ok((user_id, session_id))
+}
+
+/// A new random refresh token: two UUIDs of hex, 64 characters
+pub fn generate_refresh_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Refresh tokens are stored hashed, so a database leak doesn't expose live sessions
+pub fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jwt_round_trips_user_and_session() {
+        let user_id = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+        let token = create_jwt(user_id, session_id, "secret").unwrap();
+        assert_eq!(validate_jwt(&token, "secret").unwrap(), (user_id, session_id));
+        assert!(validate_jwt(&token, "other-secret").is_err());
+    }
+
+    #[test]
+    fn test_refresh_tokens_are_random_and_hashed() {
+        let a = generate_refresh_token();
+        let b = generate_refresh_token();
+        assert_eq!(a.len(), 64);
+        assert_ne!(a, b);
+        assert_eq!(hash_refresh_token(&a), hash_refresh_token(&a));
+        assert_ne!(hash_refresh_token(&a), a);
+        assert_eq!(hash_refresh_token(&a).len(), 64);
+    }
 }
@@ -0,0 +1,97 @@
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{AccountFeeSchedule, CreateAccountFeeSchedule};
+
+pub async fn fetch_schedules(pool: &PgPool, account_id: Uuid) -> Result<Vec<AccountFeeSchedule>, sqlx::Error> {
+    sqlx::query_as::<_, AccountFeeSchedule>(
+        "SELECT id, account_id, fee_type, amount, start_date, end_date, description, created_at
+         FROM account_fee_schedules
+         WHERE account_id = $1
+         ORDER BY start_date, created_at"
+    )
+    .bind(account_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn create_schedule(
+    pool: &PgPool,
+    account_id: Uuid,
+    data: &CreateAccountFeeSchedule,
+) -> Result<AccountFeeSchedule, sqlx::Error> {
+    sqlx::query_as::<_, AccountFeeSchedule>(
+        "INSERT INTO account_fee_schedules (account_id, fee_type, amount, start_date, end_date, description)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, account_id, fee_type, amount, start_date, end_date, description, created_at"
+    )
+    .bind(account_id)
+    .bind(data.fee_type.as_str())
+    .bind(data.amount)
+    .bind(data.start_date)
+    .bind(data.end_date)
+    .bind(&data.description)
+    .fetch_one(pool)
+    .await
+}
+
+/// Delete a schedule and, by cascade, the fee flows generated from it
+pub async fn delete_schedule(pool: &PgPool, account_id: Uuid, schedule_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM account_fee_schedules WHERE id = $1 AND account_id = $2")
+        .bind(schedule_id)
+        .bind(account_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Accounts with at least one fee schedule
+pub async fn fetch_accounts_with_schedules(pool: &PgPool) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>("SELECT DISTINCT account_id FROM account_fee_schedules")
+        .fetch_all(pool)
+        .await
+}
+
+/// Replace the account's generated fee flows with `flows` (schedule id, month end, amount)
+pub async fn replace_fee_flows(
+    pool: &PgPool,
+    account_id: Uuid,
+    flows: &[(Uuid, NaiveDate, f64)],
+) -> Result<(), sqlx::Error> {
+    let schedules: Vec<Uuid> = flows.iter().map(|f| f.0).collect();
+    let dates: Vec<NaiveDate> = flows.iter().map(|f| f.1).collect();
+    let amounts: Vec<f64> = flows.iter().map(|f| f.2).collect();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM cash_flows WHERE account_id = $1 AND fee_schedule_id IS NOT NULL")
+        .bind(account_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO cash_flows (id, account_id, flow_type, amount, flow_date, description, fee_schedule_id)
+         SELECT gen_random_uuid(), $1, 'FEE', ROUND(f.amount::NUMERIC, 2), f.flow_date,
+                COALESCE(s.description, 'Account fee'), f.schedule_id
+         FROM UNNEST($2::UUID[], $3::DATE[], $4::DOUBLE PRECISION[]) AS f(schedule_id, flow_date, amount)
+         JOIN account_fee_schedules s ON s.id = f.schedule_id"
+    )
+    .bind(account_id)
+    .bind(&schedules)
+    .bind(&dates)
+    .bind(&amounts)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Total of the account's FEE cash flows, generated or entered by hand
+pub async fn fetch_total_fees(pool: &PgPool, account_id: Uuid) -> Result<f64, sqlx::Error> {
+    sqlx::query_scalar::<_, f64>(
+        "SELECT COALESCE(SUM(amount), 0)::DOUBLE PRECISION FROM cash_flows WHERE account_id = $1 AND flow_type = 'FEE'"
+    )
+    .bind(account_id)
+    .fetch_one(pool)
+    .await
+}
@@ -1,13 +1,15 @@
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 use crate::models::{Account, CreateAccount};
 
+/// Whether the account is in a portfolio the user owns or that is shared with them
 pub async fn belongs_to_user(pool: &PgPool, account_id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
     let result: (bool,) = sqlx::query_as(
         "SELECT EXISTS(
             SELECT 1 FROM accounts a
             JOIN portfolios p ON a.portfolio_id = p.id
-            WHERE a.id = $1 AND p.user_id = $2
+            WHERE a.id = $1
+              AND (p.user_id = $2 OR p.id IN (SELECT portfolio_id FROM portfolio_members WHERE user_id = $2))
          )"
     )
     .bind(account_id)
@@ -19,7 +21,7 @@ pub async fn belongs_to_user(pool: &PgPool, account_id: Uuid, user_id: Uuid) ->
 
 pub async fn fetch_all(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<Account>, sqlx::Error> {
     sqlx::query_as::<_, Account>(
-        "SELECT id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at
+        "SELECT id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at, drip_enabled, fractional_shares, tax_treatment, cost_basis_method
          FROM accounts
          WHERE portfolio_id = $1
          ORDER BY created_at DESC"
@@ -29,30 +31,30 @@ pub async fn fetch_all(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<Account>
     .await
 }
 
-pub async fn fetch_one(pool: &PgPool, id: Uuid) -> Result<Option<Account>, sqlx::Error> {
+pub async fn fetch_one<'e>(executor: impl PgExecutor<'e>, id: Uuid) -> Result<Option<Account>, sqlx::Error> {
     sqlx::query_as::<_, Account>(
-        "SELECT id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at
+        "SELECT id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at, drip_enabled, fractional_shares, tax_treatment, cost_basis_method
          FROM accounts
          WHERE id = $1"
     )
     .bind(id)
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await
 }
 
-pub async fn find_by_account_number(
-    pool: &PgPool,
+pub async fn find_by_account_number<'e>(
+    executor: impl PgExecutor<'e>,
     portfolio_id: Uuid,
     account_number: &str,
 ) -> Result<Option<Account>, sqlx::Error> {
     sqlx::query_as::<_, Account>(
-        "SELECT id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at
+        "SELECT id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at, drip_enabled, fractional_shares, tax_treatment, cost_basis_method
          FROM accounts
          WHERE portfolio_id = $1 AND account_number = $2"
     )
     .bind(portfolio_id)
     .bind(account_number)
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await
 }
 
@@ -66,7 +68,7 @@ pub async fn create(
     sqlx::query_as::<_, Account>(
         "INSERT INTO accounts (id, portfolio_id, account_number, account_nickname, client_id, client_name)
          VALUES ($1, $2, $3, $4, $5, $6)
-         RETURNING id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at"
+         RETURNING id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at, drip_enabled, fractional_shares, tax_treatment, cost_basis_method"
     )
     .bind(id)
     .bind(portfolio_id)
@@ -78,8 +80,8 @@ pub async fn create(
     .await
 }
 
-pub async fn upsert(
-    pool: &PgPool,
+pub async fn upsert<'e>(
+    executor: impl PgExecutor<'e>,
     portfolio_id: Uuid,
     input: CreateAccount,
 ) -> Result<Account, sqlx::Error> {
@@ -92,7 +94,7 @@ pub async fn upsert(
              account_nickname = EXCLUDED.account_nickname,
              client_id = EXCLUDED.client_id,
              client_name = EXCLUDED.client_name
-         RETURNING id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at"
+         RETURNING id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at, drip_enabled, fractional_shares, tax_treatment, cost_basis_method"
     )
     .bind(id)
     .bind(portfolio_id)
@@ -100,7 +102,51 @@ pub async fn upsert(
     .bind(input.account_nickname)
     .bind(input.client_id)
     .bind(input.client_name)
-    .fetch_one(pool)
+    .fetch_one(executor)
+    .await
+}
+
+pub async fn set_drip_enabled(pool: &PgPool, id: Uuid, enabled: bool) -> Result<Option<Account>, sqlx::Error> {
+    sqlx::query_as::<_, Account>(
+        "UPDATE accounts SET drip_enabled = $2 WHERE id = $1
+         RETURNING id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at, drip_enabled, fractional_shares, tax_treatment, cost_basis_method"
+    )
+    .bind(id)
+    .bind(enabled)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn set_fractional_shares(pool: &PgPool, id: Uuid, enabled: bool) -> Result<Option<Account>, sqlx::Error> {
+    sqlx::query_as::<_, Account>(
+        "UPDATE accounts SET fractional_shares = $2 WHERE id = $1
+         RETURNING id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at, drip_enabled, fractional_shares, tax_treatment, cost_basis_method"
+    )
+    .bind(id)
+    .bind(enabled)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn set_tax_treatment(pool: &PgPool, id: Uuid, tax_treatment: Option<&str>) -> Result<Option<Account>, sqlx::Error> {
+    sqlx::query_as::<_, Account>(
+        "UPDATE accounts SET tax_treatment = $2 WHERE id = $1
+         RETURNING id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at, drip_enabled, fractional_shares, tax_treatment, cost_basis_method"
+    )
+    .bind(id)
+    .bind(tax_treatment)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn set_cost_basis_method(pool: &PgPool, id: Uuid, method: &str) -> Result<Option<Account>, sqlx::Error> {
+    sqlx::query_as::<_, Account>(
+        "UPDATE accounts SET cost_basis_method = $2 WHERE id = $1
+         RETURNING id, portfolio_id, account_number, account_nickname, client_id, client_name, created_at, drip_enabled, fractional_shares, tax_treatment, cost_basis_method"
+    )
+    .bind(id)
+    .bind(method)
+    .fetch_optional(pool)
     .await
 }
 
@@ -95,7 +95,6 @@ pub async fn get_active_alert_rules_for_user(
     Ok(rules)
 }
 
-#[allow(dead_code)]
 pub async fn get_all_active_alert_rules(pool: &PgPool) -> Result<Vec<AlertRule>, sqlx::Error> {
     let rules = sqlx::query_as::<_, AlertRule>(
         r#"
@@ -396,6 +395,26 @@ pub async fn create_notification(
     Ok(notification)
 }
 
+/// Notifications created for a user after `since`, oldest first
+pub async fn get_notifications_since(
+    pool: &PgPool,
+    user_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<Vec<Notification>, sqlx::Error> {
+    sqlx::query_as::<_, Notification>(
+        r#"
+        SELECT * FROM notifications
+        WHERE user_id = $1
+          AND created_at > $2
+        ORDER BY created_at ASC
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
 pub async fn get_user_notifications(
     pool: &PgPool,
     user_id: Uuid,
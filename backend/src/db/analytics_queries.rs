@@ -2,9 +2,11 @@ use chrono::NaiveDate;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-#[derive(Debug, Clone)]
+/// Value of a portfolio's holdings quoted in one currency on a snapshot date
+#[derive(Debug, Clone, sqlx::FromRow)]
 pub struct PortfolioValueRow {
     pub date: NaiveDate,
+    pub currency: String,
     pub value: f64,
 }
 
@@ -12,30 +14,24 @@ pub async fn fetch_portfolio_value_series(
     pool: &PgPool,
     portfolio_id: Uuid,
 ) -> Result<Vec<PortfolioValueRow>, sqlx::Error> {
-    // Aggregate holdings_snapshots across all accounts in a portfolio
-    let rows = sqlx::query!(
+    // Aggregate holdings_snapshots across all accounts in a portfolio, per
+    // currency so callers can convert before summing
+    sqlx::query_as::<_, PortfolioValueRow>(
         r#"
         SELECT
-          h.snapshot_date as "date!",
-          SUM(h.market_value)::double precision as "value!"
+          h.snapshot_date AS date,
+          h.currency,
+          SUM(h.market_value)::double precision AS value
         FROM holdings_snapshots h
         JOIN accounts a ON h.account_id = a.id
         WHERE a.portfolio_id = $1
-        GROUP BY h.snapshot_date
-        ORDER BY h.snapshot_date ASC
-        "#,
-        portfolio_id
+        GROUP BY h.snapshot_date, h.currency
+        ORDER BY h.snapshot_date ASC, h.currency ASC
+        "#
     )
+        .bind(portfolio_id)
         .fetch_all(pool)
-        .await?;
-
-    Ok(rows
-        .into_iter()
-        .map(|r| PortfolioValueRow {
-            date: r.date,
-            value: r.value,
-        })
-        .collect())
+        .await
 }
 
 #[derive(Debug, Clone)]
@@ -76,4 +72,41 @@ pub async fn fetch_allocations_at_latest_date(
         .into_iter()
         .map(|r| AllocationRow { ticker: r.ticker, value: r.value })
         .collect())
-}
\ No newline at end of file
+}
+/// Value of a ticker held in one currency at the latest snapshot date
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CurrencyAllocationRow {
+    pub ticker: String,
+    pub currency: String,
+    pub value: f64,
+}
+
+pub async fn fetch_currency_allocations_at_latest_date(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+) -> Result<Vec<CurrencyAllocationRow>, sqlx::Error> {
+    sqlx::query_as::<_, CurrencyAllocationRow>(
+        r#"
+        WITH latest_snapshot AS (
+          SELECT MAX(h.snapshot_date) AS snapshot_date
+          FROM holdings_snapshots h
+          JOIN accounts a ON h.account_id = a.id
+          WHERE a.portfolio_id = $1
+        )
+        SELECT
+          h.ticker,
+          h.currency,
+          SUM(h.market_value)::double precision AS value
+        FROM holdings_snapshots h
+        JOIN accounts a ON h.account_id = a.id
+        JOIN latest_snapshot l ON h.snapshot_date = l.snapshot_date
+        WHERE a.portfolio_id = $1
+          AND h.ticker != ''
+        GROUP BY h.ticker, h.currency
+        ORDER BY h.ticker ASC
+        "#
+    )
+        .bind(portfolio_id)
+        .fetch_all(pool)
+        .await
+}
@@ -0,0 +1,163 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{MonitoredPosition, PositionAnnotation, UpdateAnnotation};
+
+const ANNOTATION_COLUMNS: &str = "id, account_id, ticker, tags, notes, target_price, stop_loss, thesis,
+     trailing_stop_pct, trailing_high, target_alerted_at, stop_alerted_at, trailing_alerted_at,
+     created_at, updated_at";
+
+/// Insert or update the annotation for a position. `None` leaves the field unchanged.
+///
+/// Changing (or clearing) a target or stop-loss re-arms its crossing alert. Changing
+/// the trailing stop percentage also drops its high-water mark so it's seeded afresh.
+pub async fn upsert_position_annotation(
+    pool: &PgPool,
+    account_id: Uuid,
+    ticker: &str,
+    input: &UpdateAnnotation,
+) -> Result<PositionAnnotation, sqlx::Error> {
+    sqlx::query_as::<_, PositionAnnotation>(&format!(
+        "INSERT INTO position_annotations
+             (account_id, ticker, tags, notes, target_price, stop_loss, thesis, trailing_stop_pct)
+         VALUES ($1, $2, COALESCE($3, '{{}}'::TEXT[]), $4,
+                 CASE WHEN $8 THEN NULL ELSE $5 END, CASE WHEN $8 THEN NULL ELSE $6 END, $7,
+                 CASE WHEN $8 THEN NULL ELSE $9 END)
+         ON CONFLICT (account_id, ticker)
+         DO UPDATE SET
+             tags = COALESCE($3, position_annotations.tags),
+             notes = COALESCE($4, position_annotations.notes),
+             target_price = CASE WHEN $8 THEN NULL ELSE COALESCE($5, position_annotations.target_price) END,
+             stop_loss = CASE WHEN $8 THEN NULL ELSE COALESCE($6, position_annotations.stop_loss) END,
+             thesis = COALESCE($7, position_annotations.thesis),
+             trailing_stop_pct = CASE WHEN $8 THEN NULL ELSE COALESCE($9, position_annotations.trailing_stop_pct) END,
+             trailing_high = CASE WHEN $8 OR $9 IS NOT NULL THEN NULL ELSE position_annotations.trailing_high END,
+             trailing_alerted_at = CASE WHEN $8 OR $9 IS NOT NULL THEN NULL
+                                        ELSE position_annotations.trailing_alerted_at END,
+             target_alerted_at = CASE WHEN $8 OR $5 IS NOT NULL THEN NULL
+                                      ELSE position_annotations.target_alerted_at END,
+             stop_alerted_at = CASE WHEN $8 OR $6 IS NOT NULL THEN NULL
+                                    ELSE position_annotations.stop_alerted_at END,
+             updated_at = NOW()
+         RETURNING {}",
+        ANNOTATION_COLUMNS
+    ))
+    .bind(account_id)
+    .bind(ticker)
+    .bind(input.tags.as_deref())
+    .bind(input.notes.as_deref())
+    .bind(input.target_price)
+    .bind(input.stop_loss)
+    .bind(input.thesis.as_deref())
+    .bind(input.clear_levels)
+    .bind(input.trailing_stop_pct)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn fetch_for_account(
+    pool: &PgPool,
+    account_id: Uuid,
+) -> Result<Vec<PositionAnnotation>, sqlx::Error> {
+    sqlx::query_as::<_, PositionAnnotation>(&format!(
+        "SELECT {}
+         FROM position_annotations
+         WHERE account_id = $1
+         ORDER BY ticker",
+        ANNOTATION_COLUMNS
+    ))
+    .bind(account_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn fetch_for_portfolio(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+) -> Result<Vec<PositionAnnotation>, sqlx::Error> {
+    sqlx::query_as::<_, PositionAnnotation>(
+        "SELECT pa.id, pa.account_id, pa.ticker, pa.tags, pa.notes, pa.target_price, pa.stop_loss,
+                pa.thesis, pa.trailing_stop_pct, pa.trailing_high, pa.target_alerted_at, pa.stop_alerted_at,
+                pa.trailing_alerted_at, pa.created_at, pa.updated_at
+         FROM position_annotations pa
+         JOIN accounts a ON a.id = pa.account_id
+         WHERE a.portfolio_id = $1
+         ORDER BY pa.ticker"
+    )
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Positions with a target price, stop-loss or trailing stop in active (non-archived) portfolios
+pub async fn fetch_monitored_positions(pool: &PgPool) -> Result<Vec<MonitoredPosition>, sqlx::Error> {
+    sqlx::query_as::<_, MonitoredPosition>(
+        "SELECT pa.id, pa.account_id, pa.ticker, pa.tags, pa.notes, pa.target_price, pa.stop_loss,
+                pa.thesis, pa.trailing_stop_pct, pa.trailing_high, pa.target_alerted_at, pa.stop_alerted_at,
+                pa.trailing_alerted_at, pa.created_at, pa.updated_at,
+                p.user_id, p.id AS portfolio_id, a.account_nickname
+         FROM position_annotations pa
+         JOIN accounts a ON a.id = pa.account_id
+         JOIN portfolios p ON p.id = a.portfolio_id
+         WHERE (pa.target_price IS NOT NULL OR pa.stop_loss IS NOT NULL OR pa.trailing_stop_pct IS NOT NULL)
+           AND p.user_id IS NOT NULL
+           AND p.archived_at IS NULL
+         ORDER BY pa.ticker"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Record whether the crossing of a position's target (`"target"`), stop-loss
+/// (`"stop_loss"`) or trailing stop (`"trailing_stop"`) has been alerted. Passing
+/// `false` re-arms the alert.
+pub async fn set_level_alerted(
+    pool: &PgPool,
+    annotation_id: Uuid,
+    level_type: &str,
+    alerted: bool,
+) -> Result<(), sqlx::Error> {
+    let column = match level_type {
+        "target" => "target_alerted_at",
+        "stop_loss" => "stop_alerted_at",
+        "trailing_stop" => "trailing_alerted_at",
+        other => return Err(sqlx::Error::Protocol(format!("Unknown level type: {}", other))),
+    };
+    sqlx::query(&format!(
+        "UPDATE position_annotations
+         SET {col} = CASE WHEN $2 THEN COALESCE({col}, NOW()) ELSE NULL END
+         WHERE id = $1",
+        col = column
+    ))
+    .bind(annotation_id)
+    .bind(alerted)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Store a position's trailing-stop high-water mark
+pub async fn set_trailing_high(pool: &PgPool, annotation_id: Uuid, high: f64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE position_annotations SET trailing_high = $2 WHERE id = $1")
+        .bind(annotation_id)
+        .bind(high)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Highest stored close of a position's ticker since the account's first snapshot holding it
+pub async fn fetch_high_since_entry(pool: &PgPool, account_id: Uuid, ticker: &str) -> Result<Option<f64>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<f64>>(
+        "SELECT MAX(pp.close_price)::DOUBLE PRECISION
+         FROM price_points pp
+         WHERE pp.ticker = $2
+           AND pp.date >= (
+               SELECT MIN(h.snapshot_date) FROM holdings_snapshots h
+               WHERE h.account_id = $1 AND h.ticker = $2 AND h.quantity > 0
+           )"
+    )
+    .bind(account_id)
+    .bind(ticker)
+    .fetch_one(pool)
+    .await
+}
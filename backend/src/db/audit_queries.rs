@@ -0,0 +1,65 @@
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::models::{AuditLogEntry, AuditLogQuery, NewAuditEntry};
+
+pub async fn insert<'e>(
+    executor: impl PgExecutor<'e>,
+    entry: &NewAuditEntry,
+    changes: Option<&serde_json::Value>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO audit_log (user_id, portfolio_id, entity_type, entity_id, action, before, after, changes)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+    )
+    .bind(entry.user_id)
+    .bind(entry.portfolio_id)
+    .bind(entry.entity_type)
+    .bind(&entry.entity_id)
+    .bind(entry.action)
+    .bind(&entry.before)
+    .bind(&entry.after)
+    .bind(changes)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Entries visible to `user_id`, newest first: their own changes, including
+/// to portfolios since deleted, and every change to portfolios they own or are
+/// a member of
+pub async fn fetch_visible(
+    pool: &PgPool,
+    user_id: Uuid,
+    query: &AuditLogQuery,
+    limit: i64,
+) -> Result<Vec<AuditLogEntry>, sqlx::Error> {
+    sqlx::query_as::<_, AuditLogEntry>(
+        "SELECT a.id, a.user_id, u.email AS user_email, a.portfolio_id, a.entity_type, a.entity_id,
+                a.action, a.before, a.after, a.changes, a.created_at
+         FROM audit_log a
+         LEFT JOIN users u ON u.id = a.user_id
+         WHERE (
+                a.user_id = $1
+                OR a.portfolio_id IN (
+                    SELECT id FROM portfolios WHERE user_id = $1
+                    UNION
+                    SELECT portfolio_id FROM portfolio_members WHERE user_id = $1
+                )
+           )
+           AND ($2::uuid IS NULL OR a.portfolio_id = $2)
+           AND ($3::date IS NULL OR a.created_at >= $3::date)
+           AND ($4::date IS NULL OR a.created_at < $4::date + 1)
+           AND ($5::text IS NULL OR a.entity_type = $5)
+         ORDER BY a.created_at DESC, a.id DESC
+         LIMIT $6"
+    )
+    .bind(user_id)
+    .bind(query.portfolio_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.entity_type.as_deref())
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
@@ -1,6 +1,7 @@
 use sqlx::PgPool;
 use uuid::Uuid;
 use crate::models::alert::User;
+use crate::models::{OidcLoginState, Role, UserIdentity};
 
 pub async fn create_user_with_password(
     pool: &PgPool,
@@ -24,6 +25,27 @@ pub async fn create_user_with_password(
     Ok(user)
 }
 
+/// Create a user who signs in through an external identity provider only
+pub async fn create_user_without_password(
+    pool: &PgPool,
+    email: &str,
+    name: Option<&str>,
+) -> Result<User, sqlx::Error> {
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        INSERT INTO users (email, name)
+        VALUES ($1, $2)
+        RETURNING *
+        "#,
+    )
+    .bind(email)
+    .bind(name)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(user)
+}
+
 pub async fn get_user_by_email(pool: &PgPool, email: &str) -> Result<Option<User>, sqlx::Error> {
     let user = sqlx::query_as::<_, User>(
         r#"
@@ -76,12 +98,14 @@ pub async fn set_user_password(
     Ok(user)
 }
 
-/// Count real users (any user with a password hash set, including a claimed default account)
+/// Count real users (any user with a password hash set, including a claimed
+/// default account, or with a linked sign-in identity)
 pub async fn count_non_default_users(pool: &PgPool) -> Result<i64, sqlx::Error> {
     let count: (i64,) = sqlx::query_as(
         r#"
-        SELECT COUNT(*) FROM users
-        WHERE password_hash IS NOT NULL
+        SELECT COUNT(*) FROM users u
+        WHERE u.password_hash IS NOT NULL
+           OR EXISTS (SELECT 1 FROM user_identities i WHERE i.user_id = u.id)
         "#,
     )
     .fetch_one(pool)
@@ -90,6 +114,64 @@ pub async fn count_non_default_users(pool: &PgPool) -> Result<i64, sqlx::Error>
     Ok(count.0)
 }
 
+/// Every user, oldest first
+pub async fn list_users(pool: &PgPool) -> Result<Vec<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>("SELECT * FROM users ORDER BY created_at, email")
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_user(pool: &PgPool, user_id: Uuid) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Disable or re-enable an account. Disabling an already disabled account
+/// keeps its original `disabled_at`.
+pub async fn set_user_disabled(pool: &PgPool, user_id: Uuid, disabled: bool) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET disabled_at = CASE WHEN $2 THEN COALESCE(disabled_at, NOW()) END,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(disabled)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Set a user's instance-wide role
+pub async fn set_user_role(pool: &PgPool, user_id: Uuid, role: Role) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>(
+        "UPDATE users SET role = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+    )
+    .bind(user_id)
+    .bind(role)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Count enabled admins who can sign in, with a password or a linked identity
+pub async fn count_active_admins(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM users u
+        WHERE u.role = 'admin'
+          AND u.disabled_at IS NULL
+          AND (u.password_hash IS NOT NULL
+               OR EXISTS (SELECT 1 FROM user_identities i WHERE i.user_id = u.id))
+        "#,
+    )
+    .fetch_one(pool)
+    .await
+}
+
 /// Update a user's profile (email and/or name).
 pub async fn update_user_profile(
     pool: &PgPool,
@@ -223,7 +305,113 @@ pub async fn migrate_default_user_data(
         .execute(&mut *tx)
         .await?;
 
+    // The first real user administers the instance
+    sqlx::query("UPDATE users SET role = 'admin' WHERE id = $1")
+        .bind(new_user_id)
+        .execute(&mut *tx)
+        .await?;
+
     tx.commit().await?;
 
     Ok(())
 }
+
+/// Store the state of a sign-in redirect (10-minute expiry).
+pub async fn create_oidc_login_state(
+    pool: &PgPool,
+    state: &str,
+    provider: &str,
+    code_verifier: &str,
+    nonce: &str,
+    redirect_to: &str,
+) -> Result<(), sqlx::Error> {
+    // Abandoned sign-ins would otherwise accumulate
+    sqlx::query("DELETE FROM oidc_login_states WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO oidc_login_states (state, provider, code_verifier, nonce, redirect_to, expires_at) \
+         VALUES ($1, $2, $3, $4, $5, NOW() + INTERVAL '10 minutes')",
+    )
+    .bind(state)
+    .bind(provider)
+    .bind(code_verifier)
+    .bind(nonce)
+    .bind(redirect_to)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Consume a sign-in state (delete it) and return it if valid and not expired.
+pub async fn consume_oidc_login_state(
+    pool: &PgPool,
+    state: &str,
+) -> Result<Option<OidcLoginState>, sqlx::Error> {
+    sqlx::query_as::<_, OidcLoginState>(
+        "DELETE FROM oidc_login_states \
+         WHERE state = $1 AND expires_at > NOW() \
+         RETURNING provider, code_verifier, nonce, redirect_to",
+    )
+    .bind(state)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn find_user_identity(
+    pool: &PgPool,
+    provider: &str,
+    subject: &str,
+) -> Result<Option<UserIdentity>, sqlx::Error> {
+    sqlx::query_as::<_, UserIdentity>(
+        r#"
+        SELECT * FROM user_identities WHERE provider = $1 AND subject = $2
+        "#,
+    )
+    .bind(provider)
+    .bind(subject)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Link an external identity to a user.
+pub async fn create_user_identity(
+    pool: &PgPool,
+    user_id: Uuid,
+    provider: &str,
+    subject: &str,
+    email: Option<&str>,
+) -> Result<UserIdentity, sqlx::Error> {
+    sqlx::query_as::<_, UserIdentity>(
+        r#"
+        INSERT INTO user_identities (user_id, provider, subject, email)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(provider)
+    .bind(subject)
+    .bind(email)
+    .fetch_one(pool)
+    .await
+}
+
+/// Record a sign-in through an identity, with the email the provider reported.
+pub async fn touch_user_identity(
+    pool: &PgPool,
+    identity_id: Uuid,
+    email: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE user_identities SET email = COALESCE($1, email), last_login_at = NOW() WHERE id = $2",
+    )
+    .bind(email)
+    .bind(identity_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -1,10 +1,10 @@
 use chrono::NaiveDate;
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 use crate::models::{CashFlow, CreateCashFlow};
 
-pub async fn create(
-    pool: &PgPool,
+pub async fn create<'e>(
+    executor: impl PgExecutor<'e>,
     account_id: Uuid,
     data: CreateCashFlow,
 ) -> Result<CashFlow, sqlx::Error> {
@@ -13,7 +13,7 @@ pub async fn create(
     sqlx::query_as::<_, CashFlow>(
         "INSERT INTO cash_flows (id, account_id, flow_type, amount, flow_date, description)
          VALUES ($1, $2, $3, $4, $5, $6)
-         RETURNING id, account_id, flow_type, amount, flow_date, description, created_at"
+         RETURNING id, account_id, flow_type, amount, flow_date, description, created_at, fee_schedule_id"
     )
     .bind(cash_flow.id)
     .bind(cash_flow.account_id)
@@ -21,7 +21,7 @@ pub async fn create(
     .bind(&cash_flow.amount)
     .bind(cash_flow.flow_date)
     .bind(&cash_flow.description)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
@@ -30,7 +30,7 @@ pub async fn fetch_by_account(
     account_id: Uuid,
 ) -> Result<Vec<CashFlow>, sqlx::Error> {
     sqlx::query_as::<_, CashFlow>(
-        "SELECT id, account_id, flow_type, amount, flow_date, description, created_at
+        "SELECT id, account_id, flow_type, amount, flow_date, description, created_at, fee_schedule_id
          FROM cash_flows
          WHERE account_id = $1
          ORDER BY flow_date DESC"
@@ -48,7 +48,7 @@ pub async fn fetch_by_date_range(
     end_date: NaiveDate,
 ) -> Result<Vec<CashFlow>, sqlx::Error> {
     sqlx::query_as::<_, CashFlow>(
-        "SELECT id, account_id, flow_type, amount, flow_date, description, created_at
+        "SELECT id, account_id, flow_type, amount, flow_date, description, created_at, fee_schedule_id
          FROM cash_flows
          WHERE account_id = $1 AND flow_date BETWEEN $2 AND $3
          ORDER BY flow_date DESC"
@@ -60,8 +60,8 @@ pub async fn fetch_by_date_range(
     .await
 }
 
-pub async fn update_account_totals(
-    pool: &PgPool,
+pub async fn update_account_totals<'e>(
+    executor: impl PgExecutor<'e>,
     account_id: Uuid,
 ) -> Result<(), sqlx::Error> {
     sqlx::query!(
@@ -77,7 +77,7 @@ pub async fn update_account_totals(
          WHERE id = $1",
         account_id
     )
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
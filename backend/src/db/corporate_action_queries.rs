@@ -0,0 +1,81 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::PgPool;
+
+use crate::external::price_provider::ExternalCorporateAction;
+use crate::models::CorporateAction;
+
+/// Store a ticker's actions. Returns how many were new or changed.
+pub async fn upsert_actions(
+    pool: &PgPool,
+    ticker: &str,
+    actions: &[ExternalCorporateAction],
+    fetched_at: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let dates: Vec<NaiveDate> = actions.iter().map(|a| a.ex_date).collect();
+    let types: Vec<&str> = actions.iter().map(|a| a.action_type.as_str()).collect();
+    let values: Vec<f64> = actions.iter().map(|a| a.value).collect();
+
+    let result = sqlx::query(
+        "INSERT INTO corporate_actions (ticker, ex_date, action_type, value, fetched_at)
+         SELECT $1, a.ex_date, a.action_type, a.value, $5
+         FROM UNNEST($2::date[], $3::text[], $4::float8[]) AS a(ex_date, action_type, value)
+         ON CONFLICT (ticker, ex_date, action_type) DO UPDATE SET
+            value = EXCLUDED.value,
+            fetched_at = EXCLUDED.fetched_at
+         WHERE corporate_actions.value <> EXCLUDED.value"
+    )
+    .bind(ticker)
+    .bind(&dates)
+    .bind(&types)
+    .bind(&values)
+    .bind(fetched_at)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// A ticker's actions, oldest first
+pub async fn fetch_for_ticker(pool: &PgPool, ticker: &str) -> Result<Vec<CorporateAction>, sqlx::Error> {
+    sqlx::query_as::<_, CorporateAction>(
+        "SELECT ticker, ex_date, action_type, value::float8 AS value, fetched_at
+         FROM corporate_actions
+         WHERE ticker = $1
+         ORDER BY ex_date, action_type"
+    )
+    .bind(ticker)
+    .fetch_all(pool)
+    .await
+}
+
+/// Raw closes of a ticker, oldest first
+pub async fn fetch_raw_closes(pool: &PgPool, ticker: &str) -> Result<Vec<(NaiveDate, f64)>, sqlx::Error> {
+    sqlx::query_as::<_, (NaiveDate, f64)>(
+        "SELECT date, close_price::float8 FROM price_points WHERE ticker = $1 ORDER BY date"
+    )
+    .bind(ticker)
+    .fetch_all(pool)
+    .await
+}
+
+/// Overwrite the adjusted closes of a ticker on the given dates
+pub async fn update_adjusted_closes(
+    pool: &PgPool,
+    ticker: &str,
+    adjusted: &[(NaiveDate, f64)],
+) -> Result<u64, sqlx::Error> {
+    let dates: Vec<NaiveDate> = adjusted.iter().map(|(d, _)| *d).collect();
+    let closes: Vec<f64> = adjusted.iter().map(|(_, c)| *c).collect();
+
+    let result = sqlx::query(
+        "UPDATE price_points pp
+         SET adjusted_close = a.adjusted_close
+         FROM UNNEST($2::date[], $3::float8[]) AS a(date, adjusted_close)
+         WHERE pp.ticker = $1 AND pp.date = a.date"
+    )
+    .bind(ticker)
+    .bind(&dates)
+    .bind(&closes)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,83 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{CryptoWallet, CRYPTO_ASSET_CATEGORY};
+
+pub async fn fetch_by_account(pool: &PgPool, account_id: Uuid) -> Result<Vec<CryptoWallet>, sqlx::Error> {
+    sqlx::query_as::<_, CryptoWallet>(
+        "SELECT id, account_id, chain, address, label, last_synced_at, last_sync_error, created_at
+         FROM crypto_wallets
+         WHERE account_id = $1
+         ORDER BY created_at"
+    )
+    .bind(account_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Add a wallet; `None` when the account already tracks the address
+pub async fn create(
+    pool: &PgPool,
+    account_id: Uuid,
+    chain: &str,
+    address: &str,
+    label: Option<&str>,
+) -> Result<Option<CryptoWallet>, sqlx::Error> {
+    sqlx::query_as::<_, CryptoWallet>(
+        "INSERT INTO crypto_wallets (account_id, chain, address, label)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (account_id, chain, address) DO NOTHING
+         RETURNING id, account_id, chain, address, label, last_synced_at, last_sync_error, created_at"
+    )
+    .bind(account_id)
+    .bind(chain)
+    .bind(address)
+    .bind(label)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete(pool: &PgPool, account_id: Uuid, wallet_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM crypto_wallets WHERE id = $1 AND account_id = $2")
+        .bind(wallet_id)
+        .bind(account_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Accounts with at least one wallet
+pub async fn fetch_accounts_with_wallets(pool: &PgPool) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>("SELECT DISTINCT account_id FROM crypto_wallets")
+        .fetch_all(pool)
+        .await
+}
+
+/// Record the outcome of a sync attempt; `error` is `None` on success
+pub async fn record_sync(pool: &PgPool, wallet_id: Uuid, error: Option<&str>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE crypto_wallets
+         SET last_sync_error = $2,
+             last_synced_at = CASE WHEN $2::TEXT IS NULL THEN NOW() ELSE last_synced_at END
+         WHERE id = $1"
+    )
+    .bind(wallet_id)
+    .bind(error)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Latest quantity and average cost of each crypto holding in the account
+pub async fn fetch_crypto_positions(pool: &PgPool, account_id: Uuid) -> Result<Vec<(String, f64, f64)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, f64, f64)>(
+        "SELECT DISTINCT ON (ticker) ticker, quantity::DOUBLE PRECISION, average_cost::DOUBLE PRECISION
+         FROM holdings_snapshots
+         WHERE account_id = $1 AND asset_category = $2
+         ORDER BY ticker, snapshot_date DESC"
+    )
+    .bind(account_id)
+    .bind(CRYPTO_ASSET_CATEGORY)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,99 @@
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{ColumnMapping, CsvImportTemplate};
+
+pub async fn fetch_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<CsvImportTemplate>, sqlx::Error> {
+    sqlx::query_as::<_, CsvImportTemplate>(
+        "SELECT id, user_id, broker, name, header_signature, mapping, last_used_at, created_at, updated_at
+         FROM csv_import_templates
+         WHERE user_id = $1
+         ORDER BY broker, name"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn fetch_one(pool: &PgPool, user_id: Uuid, template_id: Uuid) -> Result<Option<CsvImportTemplate>, sqlx::Error> {
+    sqlx::query_as::<_, CsvImportTemplate>(
+        "SELECT id, user_id, broker, name, header_signature, mapping, last_used_at, created_at, updated_at
+         FROM csv_import_templates
+         WHERE id = $1 AND user_id = $2"
+    )
+    .bind(template_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Most recently used template for a header row, optionally limited to one broker
+pub async fn find_by_signature(
+    pool: &PgPool,
+    user_id: Uuid,
+    header_signature: &str,
+    broker: Option<&str>,
+) -> Result<Option<CsvImportTemplate>, sqlx::Error> {
+    sqlx::query_as::<_, CsvImportTemplate>(
+        "SELECT id, user_id, broker, name, header_signature, mapping, last_used_at, created_at, updated_at
+         FROM csv_import_templates
+         WHERE user_id = $1 AND header_signature = $2 AND ($3::TEXT IS NULL OR broker = $3)
+         ORDER BY last_used_at DESC NULLS LAST, updated_at DESC
+         LIMIT 1"
+    )
+    .bind(user_id)
+    .bind(header_signature)
+    .bind(broker)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Save a template, replacing the mapping of an existing one for the same
+/// broker and header row. Saving counts as a use.
+pub async fn upsert(
+    pool: &PgPool,
+    user_id: Uuid,
+    broker: &str,
+    name: &str,
+    header_signature: &str,
+    mapping: &ColumnMapping,
+) -> Result<CsvImportTemplate, sqlx::Error> {
+    sqlx::query_as::<_, CsvImportTemplate>(
+        "INSERT INTO csv_import_templates (user_id, broker, name, header_signature, mapping, last_used_at)
+         VALUES ($1, $2, $3, $4, $5, NOW())
+         ON CONFLICT (user_id, broker, header_signature) DO UPDATE
+         SET name = EXCLUDED.name,
+             mapping = EXCLUDED.mapping,
+             last_used_at = NOW(),
+             updated_at = NOW()
+         RETURNING id, user_id, broker, name, header_signature, mapping, last_used_at, created_at, updated_at"
+    )
+    .bind(user_id)
+    .bind(broker)
+    .bind(name)
+    .bind(header_signature)
+    .bind(Json(mapping))
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn mark_used(pool: &PgPool, template_id: Uuid) -> Result<CsvImportTemplate, sqlx::Error> {
+    sqlx::query_as::<_, CsvImportTemplate>(
+        "UPDATE csv_import_templates SET last_used_at = NOW()
+         WHERE id = $1
+         RETURNING id, user_id, broker, name, header_signature, mapping, last_used_at, created_at, updated_at"
+    )
+    .bind(template_id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete(pool: &PgPool, user_id: Uuid, template_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM csv_import_templates WHERE id = $1 AND user_id = $2")
+        .bind(template_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -1,10 +1,11 @@
+use bigdecimal::BigDecimal;
 use chrono::NaiveDate;
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
-use crate::models::{DetectedTransaction, CreateDetectedTransaction, AccountActivity, AccountTruePerformance};
+use crate::models::{DetectedTransaction, CreateDetectedTransaction, AccountActivity, AccountTruePerformance, LotSelection};
 
-pub async fn create(
-    pool: &PgPool,
+pub async fn create<'e>(
+    executor: impl PgExecutor<'e>,
     account_id: Uuid,
     transaction_date: NaiveDate,
     data: CreateDetectedTransaction,
@@ -17,7 +18,8 @@ pub async fn create(
           from_snapshot_date, to_snapshot_date, description)
          VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
          RETURNING id, account_id, transaction_type, ticker, quantity, price, amount,
-                   transaction_date, from_snapshot_date, to_snapshot_date, description, created_at"
+                   transaction_date, from_snapshot_date, to_snapshot_date, description, created_at,
+                   tags, notes, wash_sale_disallowed_loss, source_transaction_id"
     )
     .bind(transaction.id)
     .bind(transaction.account_id)
@@ -30,26 +32,295 @@ pub async fn create(
     .bind(transaction.from_snapshot_date)
     .bind(transaction.to_snapshot_date)
     .bind(&transaction.description)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
 pub async fn fetch_by_account(
     pool: &PgPool,
     account_id: Uuid,
+    tag: Option<&str>,
 ) -> Result<Vec<DetectedTransaction>, sqlx::Error> {
     sqlx::query_as::<_, DetectedTransaction>(
         "SELECT id, account_id, transaction_type, ticker, quantity, price, amount,
-                transaction_date, from_snapshot_date, to_snapshot_date, description, created_at
+                transaction_date, from_snapshot_date, to_snapshot_date, description, created_at,
+                tags, notes, wash_sale_disallowed_loss, source_transaction_id
          FROM detected_transactions
          WHERE account_id = $1
+           AND ($2::TEXT IS NULL OR $2 = ANY(tags))
          ORDER BY transaction_date DESC"
     )
     .bind(account_id)
+    .bind(tag)
+    .fetch_all(pool)
+    .await
+}
+
+/// All BUY, SELL and DRIP transactions across a portfolio's accounts, oldest first
+pub async fn fetch_trades_for_portfolio(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+) -> Result<Vec<DetectedTransaction>, sqlx::Error> {
+    sqlx::query_as::<_, DetectedTransaction>(
+        "SELECT t.id, t.account_id, t.transaction_type, t.ticker, t.quantity, t.price, t.amount,
+                t.transaction_date, t.from_snapshot_date, t.to_snapshot_date, t.description, t.created_at,
+                t.tags, t.notes, t.wash_sale_disallowed_loss, t.source_transaction_id
+         FROM detected_transactions t
+         JOIN accounts a ON a.id = t.account_id
+         WHERE a.portfolio_id = $1
+           AND t.transaction_type IN ('BUY', 'SELL', 'DRIP')
+         ORDER BY t.transaction_date, t.created_at"
+    )
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Dividend income per ticker across a portfolio's accounts since `since`
+pub async fn fetch_dividend_totals_for_portfolio(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    since: chrono::NaiveDate,
+) -> Result<Vec<(String, f64)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, f64)>(
+        "SELECT t.ticker, SUM(ABS(t.amount))::DOUBLE PRECISION
+         FROM detected_transactions t
+         JOIN accounts a ON a.id = t.account_id
+         WHERE a.portfolio_id = $1
+           AND t.transaction_type = 'DIVIDEND'
+           AND t.ticker <> ''
+           AND t.amount IS NOT NULL
+           AND t.transaction_date >= $2
+         GROUP BY t.ticker"
+    )
+    .bind(portfolio_id)
+    .bind(since)
     .fetch_all(pool)
     .await
 }
 
+/// Dividends of an account that have no reinvestment recorded: neither a generated DRIP
+/// pointing at them nor an imported DRIP of the same ticker within 3 days.
+pub async fn fetch_unreinvested_dividends<'e>(
+    executor: impl PgExecutor<'e>,
+    account_id: Uuid,
+) -> Result<Vec<DetectedTransaction>, sqlx::Error> {
+    sqlx::query_as::<_, DetectedTransaction>(
+        "SELECT d.id, d.account_id, d.transaction_type, d.ticker, d.quantity, d.price, d.amount,
+                d.transaction_date, d.from_snapshot_date, d.to_snapshot_date, d.description, d.created_at,
+                d.tags, d.notes, d.wash_sale_disallowed_loss, d.source_transaction_id
+         FROM detected_transactions d
+         WHERE d.account_id = $1
+           AND d.transaction_type = 'DIVIDEND'
+           AND d.ticker <> ''
+           AND ABS(COALESCE(d.amount, 0)) > 0
+           AND NOT EXISTS (
+               SELECT 1 FROM detected_transactions r
+               WHERE r.transaction_type = 'DRIP'
+                 AND (r.source_transaction_id = d.id
+                      OR (r.source_transaction_id IS NULL
+                          AND r.account_id = d.account_id
+                          AND r.ticker = d.ticker
+                          AND ABS(r.transaction_date - d.transaction_date) <= 3))
+           )
+         ORDER BY d.transaction_date"
+    )
+    .bind(account_id)
+    .fetch_all(executor)
+    .await
+}
+
+/// Record the reinvestment of `dividend`. Returns `None` if it was already reinvested.
+pub async fn create_drip<'e>(
+    executor: impl PgExecutor<'e>,
+    dividend: &DetectedTransaction,
+    quantity: &BigDecimal,
+    price: &BigDecimal,
+    amount: &BigDecimal,
+) -> Result<Option<DetectedTransaction>, sqlx::Error> {
+    sqlx::query_as::<_, DetectedTransaction>(
+        "INSERT INTO detected_transactions
+         (id, account_id, transaction_type, ticker, quantity, price, amount, transaction_date,
+          description, source_transaction_id)
+         VALUES ($1, $2, 'DRIP', $3, $4, $5, $6, $7, $8, $9)
+         ON CONFLICT (source_transaction_id) WHERE source_transaction_id IS NOT NULL DO NOTHING
+         RETURNING id, account_id, transaction_type, ticker, quantity, price, amount,
+                   transaction_date, from_snapshot_date, to_snapshot_date, description, created_at,
+                   tags, notes, wash_sale_disallowed_loss, source_transaction_id"
+    )
+    .bind(Uuid::new_v4())
+    .bind(dividend.account_id)
+    .bind(&dividend.ticker)
+    .bind(quantity)
+    .bind(price)
+    .bind(amount)
+    .bind(dividend.transaction_date)
+    .bind("Dividend reinvestment (auto-generated)")
+    .bind(dividend.id)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Shares bought through DRIP per ticker in an account between two dates (exclusive, inclusive]
+pub async fn fetch_drip_quantities<'e>(
+    executor: impl PgExecutor<'e>,
+    account_id: Uuid,
+    after: NaiveDate,
+    through: NaiveDate,
+) -> Result<Vec<(String, BigDecimal)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, BigDecimal)>(
+        "SELECT ticker, SUM(ABS(quantity))
+         FROM detected_transactions
+         WHERE account_id = $1
+           AND transaction_type = 'DRIP'
+           AND quantity IS NOT NULL
+           AND transaction_date > $2 AND transaction_date <= $3
+         GROUP BY ticker"
+    )
+    .bind(account_id)
+    .bind(after)
+    .bind(through)
+    .fetch_all(executor)
+    .await
+}
+
+/// IDs of the portfolio's sell transactions currently flagged as wash sales
+pub async fn fetch_wash_sale_ids(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>(
+        "SELECT t.id
+         FROM detected_transactions t
+         JOIN accounts a ON a.id = t.account_id
+         WHERE a.portfolio_id = $1 AND t.wash_sale_disallowed_loss IS NOT NULL"
+    )
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Replace the wash sale flags of a portfolio's transactions with `flags`
+/// (transaction id, disallowed loss). All other transactions are cleared.
+pub async fn replace_wash_sale_flags(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    flags: &[(Uuid, f64)],
+) -> Result<(), sqlx::Error> {
+    let (ids, losses): (Vec<Uuid>, Vec<f64>) = flags.iter().copied().unzip();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "UPDATE detected_transactions t
+         SET wash_sale_disallowed_loss = NULL
+         FROM accounts a
+         WHERE a.id = t.account_id AND a.portfolio_id = $1 AND t.wash_sale_disallowed_loss IS NOT NULL"
+    )
+    .bind(portfolio_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE detected_transactions t
+         SET wash_sale_disallowed_loss = f.loss
+         FROM UNNEST($1::UUID[], $2::DOUBLE PRECISION[]) AS f(id, loss)
+         WHERE t.id = f.id"
+    )
+    .bind(&ids)
+    .bind(&losses)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Designated lots of every sell transaction in the portfolio, as (sell transaction id, selection)
+pub async fn fetch_lot_selections_for_portfolio(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+) -> Result<Vec<(Uuid, LotSelection)>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, Option<Uuid>, f64)>(
+        "SELECT s.sell_transaction_id, s.lot_transaction_id, s.quantity
+         FROM sell_lot_selections s
+         JOIN detected_transactions t ON t.id = s.sell_transaction_id
+         JOIN accounts a ON a.id = t.account_id
+         WHERE a.portfolio_id = $1
+         ORDER BY s.sell_transaction_id, s.lot_transaction_id NULLS FIRST"
+    )
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(sell, lot_transaction_id, quantity)| (sell, LotSelection { lot_transaction_id, quantity }))
+        .collect())
+}
+
+/// Replace the designated lots of a sell transaction
+pub async fn replace_lot_selections(
+    pool: &PgPool,
+    sell_transaction_id: Uuid,
+    selections: &[LotSelection],
+) -> Result<(), sqlx::Error> {
+    let lots: Vec<Option<Uuid>> = selections.iter().map(|s| s.lot_transaction_id).collect();
+    let quantities: Vec<f64> = selections.iter().map(|s| s.quantity).collect();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM sell_lot_selections WHERE sell_transaction_id = $1")
+        .bind(sell_transaction_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO sell_lot_selections (sell_transaction_id, lot_transaction_id, quantity)
+         SELECT $1, s.lot, s.quantity
+         FROM UNNEST($2::UUID[], $3::DOUBLE PRECISION[]) AS s(lot, quantity)"
+    )
+    .bind(sell_transaction_id)
+    .bind(&lots)
+    .bind(&quantities)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+pub async fn fetch_one(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<DetectedTransaction>, sqlx::Error> {
+    sqlx::query_as::<_, DetectedTransaction>(
+        "SELECT id, account_id, transaction_type, ticker, quantity, price, amount,
+                transaction_date, from_snapshot_date, to_snapshot_date, description, created_at,
+                tags, notes, wash_sale_disallowed_loss, source_transaction_id
+         FROM detected_transactions
+         WHERE id = $1"
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Set tags and/or notes on a transaction. `None` leaves the field unchanged.
+pub async fn update_annotation(
+    pool: &PgPool,
+    id: Uuid,
+    tags: Option<&[String]>,
+    notes: Option<&str>,
+) -> Result<Option<DetectedTransaction>, sqlx::Error> {
+    sqlx::query_as::<_, DetectedTransaction>(
+        "UPDATE detected_transactions
+         SET tags = COALESCE($2, tags),
+             notes = COALESCE($3, notes)
+         WHERE id = $1
+         RETURNING id, account_id, transaction_type, ticker, quantity, price, amount,
+                   transaction_date, from_snapshot_date, to_snapshot_date, description, created_at,
+                   tags, notes, wash_sale_disallowed_loss, source_transaction_id"
+    )
+    .bind(id)
+    .bind(tags)
+    .bind(notes)
+    .fetch_optional(pool)
+    .await
+}
+
 pub async fn fetch_account_activity(
     pool: &PgPool,
     account_id: Uuid,
@@ -97,8 +368,8 @@ pub async fn fetch_all_true_performance(
     .await
 }
 
-pub async fn delete_transactions_for_snapshot(
-    pool: &PgPool,
+pub async fn delete_transactions_for_snapshot<'e>(
+    executor: impl PgExecutor<'e>,
     account_id: Uuid,
     snapshot_date: NaiveDate,
 ) -> Result<u64, sqlx::Error> {
@@ -108,7 +379,7 @@ pub async fn delete_transactions_for_snapshot(
         account_id,
         snapshot_date
     )
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(result.rows_affected())
@@ -0,0 +1,77 @@
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::external::dividend_provider::ExternalDividendEvent;
+use crate::models::DividendEvent;
+
+/// Tickers held in any active portfolio
+pub async fn fetch_held_tickers(pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT DISTINCT lah.ticker
+         FROM latest_account_holdings lah
+         JOIN accounts a ON lah.account_id = a.id
+         JOIN portfolios p ON a.portfolio_id = p.id
+         WHERE p.archived_at IS NULL
+           AND lah.quantity > 0
+           AND lah.ticker != ''
+         ORDER BY lah.ticker"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn upsert(pool: &PgPool, ticker: &str, event: &ExternalDividendEvent) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO dividend_events (ticker, ex_date, pay_date, amount, annual_rate, fetched_at)
+         VALUES ($1, $2, $3, $4, $5, NOW())
+         ON CONFLICT (ticker, ex_date) DO UPDATE SET
+            pay_date = COALESCE(EXCLUDED.pay_date, dividend_events.pay_date),
+            amount = COALESCE(EXCLUDED.amount, dividend_events.amount),
+            annual_rate = COALESCE(EXCLUDED.annual_rate, dividend_events.annual_rate),
+            fetched_at = EXCLUDED.fetched_at"
+    )
+    .bind(ticker)
+    .bind(event.ex_date)
+    .bind(event.pay_date)
+    .bind(event.amount)
+    .bind(event.annual_rate)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Dividends of `tickers` going ex between `from` and `to` (inclusive), soonest first
+pub async fn fetch_upcoming(
+    pool: &PgPool,
+    tickers: &[String],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<DividendEvent>, sqlx::Error> {
+    sqlx::query_as::<_, DividendEvent>(
+        "SELECT ticker, ex_date, pay_date, amount, annual_rate, fetched_at
+         FROM dividend_events
+         WHERE ticker = ANY($1) AND ex_date BETWEEN $2 AND $3
+         ORDER BY ex_date, ticker"
+    )
+    .bind(tickers)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
+
+/// Record a reminder for a rule, ticker and ex-date; false if it was already sent
+pub async fn claim_reminder(pool: &PgPool, rule_id: Uuid, ticker: &str, ex_date: NaiveDate) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO ex_dividend_reminders (rule_id, ticker, ex_date)
+         VALUES ($1, $2, $3)
+         ON CONFLICT DO NOTHING"
+    )
+    .bind(rule_id)
+    .bind(ticker)
+    .bind(ex_date)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
@@ -0,0 +1,113 @@
+use sqlx::PgPool;
+
+use crate::external::etf_holdings_provider::ExternalEtfHoldings;
+use crate::models::{EtfConstituent, EtfConstituentRefresh, EtfSectorWeight};
+
+/// Tickers held in any account that are ETFs: listed in the fund metadata, or
+/// described as ETFs by the brokerage
+pub async fn fetch_held_etfs(pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT DISTINCT lah.ticker
+         FROM latest_account_holdings lah
+         LEFT JOIN fund_metadata fm ON fm.ticker = lah.ticker
+         WHERE fm.ticker IS NOT NULL
+            OR lah.asset_category ILIKE '%etf%'
+            OR lah.holding_name ILIKE '%etf%'
+         ORDER BY lah.ticker"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn fetch_refreshes(pool: &PgPool, etfs: &[String]) -> Result<Vec<EtfConstituentRefresh>, sqlx::Error> {
+    sqlx::query_as::<_, EtfConstituentRefresh>(
+        "SELECT etf_ticker, source, constituent_count, fetched_at
+         FROM etf_constituent_refreshes
+         WHERE etf_ticker = ANY($1)"
+    )
+    .bind(etfs)
+    .fetch_all(pool)
+    .await
+}
+
+/// Replace an ETF's constituents and sector weights and record the refresh
+pub async fn replace(pool: &PgPool, etf: &str, data: &ExternalEtfHoldings) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM etf_constituents WHERE etf_ticker = $1")
+        .bind(etf)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM etf_sector_weights WHERE etf_ticker = $1")
+        .bind(etf)
+        .execute(&mut *tx)
+        .await?;
+
+    for (position, holding) in data.holdings.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO etf_constituents (etf_ticker, position, constituent_ticker, name, weight, sector)
+             VALUES ($1, $2, $3, $4, $5, $6)"
+        )
+        .bind(etf)
+        .bind(position as i32)
+        .bind(&holding.ticker)
+        .bind(&holding.name)
+        .bind(holding.weight)
+        .bind(&holding.sector)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for (sector, weight) in &data.sector_weights {
+        sqlx::query(
+            "INSERT INTO etf_sector_weights (etf_ticker, sector, weight)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (etf_ticker, sector) DO UPDATE SET weight = EXCLUDED.weight"
+        )
+        .bind(etf)
+        .bind(sector)
+        .bind(weight)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query(
+        "INSERT INTO etf_constituent_refreshes (etf_ticker, source, constituent_count, fetched_at)
+         VALUES ($1, $2, $3, NOW())
+         ON CONFLICT (etf_ticker) DO UPDATE SET
+            source = EXCLUDED.source,
+            constituent_count = EXCLUDED.constituent_count,
+            fetched_at = EXCLUDED.fetched_at"
+    )
+    .bind(etf)
+    .bind(&data.source)
+    .bind(data.holdings.len() as i32)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+pub async fn fetch_constituents(pool: &PgPool, etfs: &[String]) -> Result<Vec<EtfConstituent>, sqlx::Error> {
+    sqlx::query_as::<_, EtfConstituent>(
+        "SELECT etf_ticker, constituent_ticker, name, weight, sector
+         FROM etf_constituents
+         WHERE etf_ticker = ANY($1)
+         ORDER BY etf_ticker, position"
+    )
+    .bind(etfs)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn fetch_sector_weights(pool: &PgPool, etfs: &[String]) -> Result<Vec<EtfSectorWeight>, sqlx::Error> {
+    sqlx::query_as::<_, EtfSectorWeight>(
+        "SELECT etf_ticker, sector, weight
+         FROM etf_sector_weights
+         WHERE etf_ticker = ANY($1)
+         ORDER BY etf_ticker, weight DESC"
+    )
+    .bind(etfs)
+    .fetch_all(pool)
+    .await
+}
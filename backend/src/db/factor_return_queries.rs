@@ -0,0 +1,41 @@
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use crate::external::ken_french::ExternalFactorReturn;
+
+/// Insert or replace factor returns, returning how many rows were written
+pub async fn upsert_returns(pool: &PgPool, returns: &[ExternalFactorReturn]) -> Result<u64, sqlx::Error> {
+    let factors: Vec<&str> = returns.iter().map(|r| r.factor.as_str()).collect();
+    let dates: Vec<NaiveDate> = returns.iter().map(|r| r.date).collect();
+    let values: Vec<f64> = returns.iter().map(|r| r.value).collect();
+    let result = sqlx::query(
+        "INSERT INTO factor_returns (factor, date, value, updated_at)
+         SELECT factor, date, value, NOW()
+         FROM UNNEST($1::text[], $2::date[], $3::float8[]) AS r(factor, date, value)
+         ON CONFLICT (factor, date) DO UPDATE SET value = EXCLUDED.value, updated_at = NOW()
+         WHERE factor_returns.value IS DISTINCT FROM EXCLUDED.value",
+    )
+    .bind(&factors)
+    .bind(&dates)
+    .bind(&values)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// (date, factor, value) from `from` onwards, oldest first
+pub async fn fetch_since(pool: &PgPool, from: NaiveDate) -> Result<Vec<(NaiveDate, String, f64)>, sqlx::Error> {
+    sqlx::query_as::<_, (NaiveDate, String, f64)>(
+        "SELECT date, factor, value FROM factor_returns WHERE date >= $1 ORDER BY date, factor",
+    )
+    .bind(from)
+    .fetch_all(pool)
+    .await
+}
+
+/// Latest date with a stored return for every factor
+pub async fn fetch_latest_date(pool: &PgPool) -> Result<Option<NaiveDate>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<NaiveDate>>("SELECT MAX(date) FROM factor_returns")
+        .fetch_one(pool)
+        .await
+}
@@ -0,0 +1,42 @@
+use sqlx::PgPool;
+
+use crate::models::factor::FactorSpread;
+
+const SPREAD_COLUMNS: &str = "date, factor, long_return, short_return, spread, universe_size, computed_at";
+
+/// Insert or replace the spread row for its date and factor
+pub async fn upsert(pool: &PgPool, spread: &FactorSpread) -> Result<FactorSpread, sqlx::Error> {
+    sqlx::query_as::<_, FactorSpread>(&format!(
+        "INSERT INTO factor_spreads (date, factor, long_return, short_return, spread, universe_size, computed_at)
+         VALUES ($1, $2, $3, $4, $5, $6, NOW())
+         ON CONFLICT (date, factor) DO UPDATE SET
+            long_return = EXCLUDED.long_return,
+            short_return = EXCLUDED.short_return,
+            spread = EXCLUDED.spread,
+            universe_size = EXCLUDED.universe_size,
+            computed_at = NOW()
+         RETURNING {}",
+        SPREAD_COLUMNS
+    ))
+    .bind(spread.date)
+    .bind(&spread.factor)
+    .bind(spread.long_return)
+    .bind(spread.short_return)
+    .bind(spread.spread)
+    .bind(spread.universe_size)
+    .fetch_one(pool)
+    .await
+}
+
+/// Spreads for the most recent `days` dates with any stored, oldest first
+pub async fn fetch_recent(pool: &PgPool, days: i64) -> Result<Vec<FactorSpread>, sqlx::Error> {
+    sqlx::query_as::<_, FactorSpread>(&format!(
+        "SELECT {} FROM factor_spreads
+         WHERE date IN (SELECT DISTINCT date FROM factor_spreads ORDER BY date DESC LIMIT $1)
+         ORDER BY date ASC, factor ASC",
+        SPREAD_COLUMNS
+    ))
+    .bind(days)
+    .fetch_all(pool)
+    .await
+}
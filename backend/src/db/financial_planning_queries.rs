@@ -778,6 +778,28 @@ pub async fn get_goals(
     .await
 }
 
+/// Goals of all of a user's surveys with a target date between `from` and `to`
+pub async fn fetch_goals_due(
+    pool: &PgPool,
+    user_id: Uuid,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> Result<Vec<SurveyGoal>, sqlx::Error> {
+    sqlx::query_as::<_, SurveyGoal>(
+        r#"
+        SELECT g.* FROM survey_goals g
+        JOIN financial_surveys s ON s.id = g.survey_id
+        WHERE s.user_id = $1 AND g.target_date BETWEEN $2 AND $3
+        ORDER BY g.target_date ASC
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
+
 pub async fn update_goal(
     pool: &PgPool,
     goal_id: Uuid,
@@ -826,6 +848,84 @@ pub async fn delete_goal(pool: &PgPool, goal_id: Uuid) -> Result<(), sqlx::Error
     Ok(())
 }
 
+// ==============================================================================
+// Goal Probability Operations
+// ==============================================================================
+
+/// Surveys with at least one goal that has a target amount
+pub async fn get_surveys_with_targeted_goals(pool: &PgPool) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT DISTINCT survey_id FROM survey_goals WHERE target_amount > 0",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn upsert_goal_probability(
+    pool: &PgPool,
+    record: &GoalProbabilityRecord,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO goal_probability_history (
+            goal_id, survey_id, calculated_on, success_probability,
+            median_outcome, p10_outcome, p90_outcome, target_amount,
+            starting_balance, annual_contribution, horizon_months,
+            expected_return, volatility, simulations
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+        ON CONFLICT (goal_id, calculated_on) DO UPDATE SET
+            success_probability = EXCLUDED.success_probability,
+            median_outcome = EXCLUDED.median_outcome,
+            p10_outcome = EXCLUDED.p10_outcome,
+            p90_outcome = EXCLUDED.p90_outcome,
+            target_amount = EXCLUDED.target_amount,
+            starting_balance = EXCLUDED.starting_balance,
+            annual_contribution = EXCLUDED.annual_contribution,
+            horizon_months = EXCLUDED.horizon_months,
+            expected_return = EXCLUDED.expected_return,
+            volatility = EXCLUDED.volatility,
+            simulations = EXCLUDED.simulations,
+            created_at = NOW()
+        "#,
+    )
+    .bind(record.goal_id)
+    .bind(record.survey_id)
+    .bind(record.calculated_on)
+    .bind(&record.success_probability)
+    .bind(&record.median_outcome)
+    .bind(&record.p10_outcome)
+    .bind(&record.p90_outcome)
+    .bind(&record.target_amount)
+    .bind(&record.starting_balance)
+    .bind(&record.annual_contribution)
+    .bind(record.horizon_months)
+    .bind(&record.expected_return)
+    .bind(&record.volatility)
+    .bind(record.simulations)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_goal_probability_history(
+    pool: &PgPool,
+    survey_id: Uuid,
+    goal_id: Uuid,
+) -> Result<Vec<GoalProbabilityRecord>, sqlx::Error> {
+    sqlx::query_as::<_, GoalProbabilityRecord>(
+        r#"
+        SELECT * FROM goal_probability_history
+        WHERE survey_id = $1 AND goal_id = $2
+        ORDER BY calculated_on ASC
+        "#,
+    )
+    .bind(survey_id)
+    .bind(goal_id)
+    .fetch_all(pool)
+    .await
+}
+
 // ==============================================================================
 // Risk Profile Operations
 // ==============================================================================
@@ -0,0 +1,26 @@
+use sqlx::PgPool;
+
+use crate::models::FundMetadata;
+
+pub async fn fetch_for_tickers(pool: &PgPool, tickers: &[String]) -> Result<Vec<FundMetadata>, sqlx::Error> {
+    sqlx::query_as::<_, FundMetadata>(
+        "SELECT ticker, name, category, currency, expense_ratio
+         FROM fund_metadata
+         WHERE ticker = ANY($1)"
+    )
+    .bind(tickers)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn fetch_for_categories(pool: &PgPool, categories: &[String]) -> Result<Vec<FundMetadata>, sqlx::Error> {
+    sqlx::query_as::<_, FundMetadata>(
+        "SELECT ticker, name, category, currency, expense_ratio
+         FROM fund_metadata
+         WHERE category = ANY($1)
+         ORDER BY expense_ratio, ticker"
+    )
+    .bind(categories)
+    .fetch_all(pool)
+    .await
+}
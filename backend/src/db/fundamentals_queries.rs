@@ -0,0 +1,197 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::PgPool;
+
+use crate::external::fundamentals_provider::{ExternalDividend, ExternalEarnings, ExternalEarningsDate};
+use crate::models::{DividendPayment, EarningsDate, EarningsReport};
+
+/// When fundamentals were last fetched for any ticker
+pub async fn fetch_last_fetched_at(pool: &PgPool) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<DateTime<Utc>>>("SELECT MAX(fetched_at) FROM fundamentals_snapshots")
+        .fetch_one(pool)
+        .await
+}
+
+/// Stored fundamentals for a ticker and when they were fetched, regardless of age
+pub async fn fetch(pool: &PgPool, ticker: &str) -> Result<Option<(serde_json::Value, DateTime<Utc>)>, sqlx::Error> {
+    sqlx::query_as::<_, (serde_json::Value, DateTime<Utc>)>(
+        "SELECT data, fetched_at FROM fundamentals_snapshots WHERE ticker = $1"
+    )
+    .bind(ticker)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn upsert(pool: &PgPool, ticker: &str, data: serde_json::Value) -> Result<DateTime<Utc>, sqlx::Error> {
+    sqlx::query_scalar::<_, DateTime<Utc>>(
+        "INSERT INTO fundamentals_snapshots (ticker, data, fetched_at)
+         VALUES ($1, $2, NOW())
+         ON CONFLICT (ticker) DO UPDATE SET data = EXCLUDED.data, fetched_at = EXCLUDED.fetched_at
+         RETURNING fetched_at"
+    )
+    .bind(ticker)
+    .bind(data)
+    .fetch_one(pool)
+    .await
+}
+
+/// All stored fundamentals, regardless of age
+pub async fn fetch_all(pool: &PgPool) -> Result<Vec<(String, serde_json::Value)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, serde_json::Value)>("SELECT ticker, data FROM fundamentals_snapshots")
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn upsert_earnings(pool: &PgPool, ticker: &str, reports: &[ExternalEarnings]) -> Result<(), sqlx::Error> {
+    let periods: Vec<NaiveDate> = reports.iter().map(|r| r.period_end).collect();
+    let years: Vec<i32> = reports.iter().map(|r| r.fiscal_year).collect();
+    let quarters: Vec<i32> = reports.iter().map(|r| r.fiscal_quarter).collect();
+    let actuals: Vec<Option<f64>> = reports.iter().map(|r| r.eps_actual).collect();
+    let estimates: Vec<Option<f64>> = reports.iter().map(|r| r.eps_estimate).collect();
+
+    sqlx::query(
+        "INSERT INTO earnings_reports (ticker, period_end, fiscal_year, fiscal_quarter, eps_actual, eps_estimate, fetched_at)
+         SELECT $1, r.period_end, r.fiscal_year, r.fiscal_quarter, r.eps_actual, r.eps_estimate, NOW()
+         FROM UNNEST($2::date[], $3::int4[], $4::int4[], $5::float8[], $6::float8[])
+              AS r(period_end, fiscal_year, fiscal_quarter, eps_actual, eps_estimate)
+         ON CONFLICT (ticker, period_end) DO UPDATE SET
+            fiscal_year = EXCLUDED.fiscal_year,
+            fiscal_quarter = EXCLUDED.fiscal_quarter,
+            eps_actual = COALESCE(EXCLUDED.eps_actual, earnings_reports.eps_actual),
+            eps_estimate = COALESCE(EXCLUDED.eps_estimate, earnings_reports.eps_estimate),
+            fetched_at = EXCLUDED.fetched_at"
+    )
+    .bind(ticker)
+    .bind(&periods)
+    .bind(&years)
+    .bind(&quarters)
+    .bind(&actuals)
+    .bind(&estimates)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Stored quarterly earnings, most recent first
+pub async fn fetch_earnings(pool: &PgPool, ticker: &str) -> Result<Vec<EarningsReport>, sqlx::Error> {
+    sqlx::query_as::<_, EarningsReport>(
+        "SELECT ticker, period_end, fiscal_year, fiscal_quarter,
+                eps_actual::float8 AS eps_actual, eps_estimate::float8 AS eps_estimate
+         FROM earnings_reports
+         WHERE ticker = $1
+         ORDER BY period_end DESC"
+    )
+    .bind(ticker)
+    .fetch_all(pool)
+    .await
+}
+
+/// Replace a ticker's announced earnings dates on or after `from`; a
+/// rescheduled release would otherwise linger under its old date
+pub async fn replace_earnings_dates(
+    pool: &PgPool,
+    ticker: &str,
+    from: NaiveDate,
+    dates: &[ExternalEarningsDate],
+) -> Result<(), sqlx::Error> {
+    let report_dates: Vec<NaiveDate> = dates.iter().map(|d| d.report_date).collect();
+    let hours: Vec<Option<String>> = dates.iter().map(|d| d.hour.clone()).collect();
+    let years: Vec<Option<i32>> = dates.iter().map(|d| d.fiscal_year).collect();
+    let quarters: Vec<Option<i32>> = dates.iter().map(|d| d.fiscal_quarter).collect();
+    let estimates: Vec<Option<f64>> = dates.iter().map(|d| d.eps_estimate).collect();
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM earnings_calendar WHERE ticker = $1 AND report_date >= $2")
+        .bind(ticker)
+        .bind(from)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO earnings_calendar (ticker, report_date, hour, fiscal_year, fiscal_quarter, eps_estimate, fetched_at)
+         SELECT $1, e.report_date, e.hour, e.fiscal_year, e.fiscal_quarter, e.eps_estimate, NOW()
+         FROM UNNEST($2::date[], $3::text[], $4::int4[], $5::int4[], $6::float8[])
+              AS e(report_date, hour, fiscal_year, fiscal_quarter, eps_estimate)
+         ON CONFLICT (ticker, report_date) DO UPDATE SET
+            hour = EXCLUDED.hour,
+            fiscal_year = EXCLUDED.fiscal_year,
+            fiscal_quarter = EXCLUDED.fiscal_quarter,
+            eps_estimate = EXCLUDED.eps_estimate,
+            fetched_at = EXCLUDED.fetched_at"
+    )
+    .bind(ticker)
+    .bind(&report_dates)
+    .bind(&hours)
+    .bind(&years)
+    .bind(&quarters)
+    .bind(&estimates)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+/// Announced earnings releases of `tickers` between `from` and `to`, soonest first
+pub async fn fetch_earnings_dates(
+    pool: &PgPool,
+    tickers: &[String],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<EarningsDate>, sqlx::Error> {
+    sqlx::query_as::<_, EarningsDate>(
+        "SELECT ticker, report_date, hour, fiscal_year, fiscal_quarter, eps_estimate
+         FROM earnings_calendar
+         WHERE ticker = ANY($1) AND report_date BETWEEN $2 AND $3
+         ORDER BY report_date, ticker"
+    )
+    .bind(tickers)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn upsert_dividends(pool: &PgPool, ticker: &str, payments: &[ExternalDividend]) -> Result<(), sqlx::Error> {
+    let ex_dates: Vec<NaiveDate> = payments.iter().map(|p| p.ex_date).collect();
+    let pay_dates: Vec<Option<NaiveDate>> = payments.iter().map(|p| p.pay_date).collect();
+    let amounts: Vec<f64> = payments.iter().map(|p| p.amount).collect();
+    let currencies: Vec<Option<String>> = payments.iter().map(|p| p.currency.clone()).collect();
+
+    sqlx::query(
+        "INSERT INTO dividend_history (ticker, ex_date, pay_date, amount, currency, fetched_at)
+         SELECT $1, d.ex_date, d.pay_date, d.amount, d.currency, NOW()
+         FROM UNNEST($2::date[], $3::date[], $4::float8[], $5::text[]) AS d(ex_date, pay_date, amount, currency)
+         ON CONFLICT (ticker, ex_date) DO UPDATE SET
+            pay_date = COALESCE(EXCLUDED.pay_date, dividend_history.pay_date),
+            amount = EXCLUDED.amount,
+            currency = COALESCE(EXCLUDED.currency, dividend_history.currency),
+            fetched_at = EXCLUDED.fetched_at"
+    )
+    .bind(ticker)
+    .bind(&ex_dates)
+    .bind(&pay_dates)
+    .bind(&amounts)
+    .bind(&currencies)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Stored dividends with an ex-date on or after `since`, most recent first
+pub async fn fetch_dividends(pool: &PgPool, ticker: &str, since: NaiveDate) -> Result<Vec<DividendPayment>, sqlx::Error> {
+    sqlx::query_as::<_, DividendPayment>(
+        "SELECT ticker, ex_date, pay_date, amount::float8 AS amount, currency
+         FROM dividend_history
+         WHERE ticker = $1 AND ex_date >= $2
+         ORDER BY ex_date DESC"
+    )
+    .bind(ticker)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
+/// Ex-date of the latest stored dividend of a ticker
+pub async fn fetch_latest_dividend_date(pool: &PgPool, ticker: &str) -> Result<Option<NaiveDate>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<NaiveDate>>("SELECT MAX(ex_date) FROM dividend_history WHERE ticker = $1")
+        .bind(ticker)
+        .fetch_one(pool)
+        .await
+}
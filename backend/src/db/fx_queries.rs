@@ -0,0 +1,80 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::external::fx_provider::ExternalFxRates;
+
+/// When exchange rates were last fetched
+pub async fn fetch_last_fetched_at(pool: &PgPool) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<DateTime<Utc>>>("SELECT MAX(fetched_at) FROM fx_rates")
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn upsert_rates(pool: &PgPool, rates: &ExternalFxRates) -> Result<(), sqlx::Error> {
+    let (currencies, units): (Vec<String>, Vec<f64>) =
+        rates.units_per_usd.iter().map(|(c, r)| (c.to_uppercase(), *r)).unzip();
+    sqlx::query(
+        "INSERT INTO fx_rates (rate_date, currency, units_per_usd, fetched_at)
+         SELECT $1, c.currency, c.units, NOW()
+         FROM UNNEST($2::text[], $3::float8[]) AS c(currency, units)
+         WHERE c.units > 0
+         ON CONFLICT (rate_date, currency) DO UPDATE SET
+            units_per_usd = EXCLUDED.units_per_usd,
+            fetched_at = EXCLUDED.fetched_at"
+    )
+    .bind(rates.date)
+    .bind(&currencies)
+    .bind(&units)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Rates published between `start` and `end`, plus the last one of each
+/// currency before `start` so the first days of the range have a rate
+pub async fn fetch_rates_between(
+    pool: &PgPool,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<(NaiveDate, String, f64)>, sqlx::Error> {
+    sqlx::query_as::<_, (NaiveDate, String, f64)>(
+        "SELECT rate_date, currency, units_per_usd::float8
+         FROM fx_rates
+         WHERE rate_date BETWEEN $1 AND $2
+         UNION ALL
+         SELECT * FROM (
+            SELECT DISTINCT ON (currency) rate_date, currency, units_per_usd::float8
+            FROM fx_rates
+            WHERE rate_date < $1
+            ORDER BY currency, rate_date DESC
+         ) earlier
+         ORDER BY 1, 2"
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+}
+
+/// Most recent publication day stored
+pub async fn fetch_latest_rate_date(pool: &PgPool) -> Result<Option<NaiveDate>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<NaiveDate>>("SELECT MAX(rate_date) FROM fx_rates")
+        .fetch_one(pool)
+        .await
+}
+
+/// Base currency of a portfolio's owner; USD when the owner hasn't picked one
+pub async fn fetch_portfolio_base_currency(pool: &PgPool, portfolio_id: Uuid) -> Result<String, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT COALESCE(
+            (SELECT up.base_currency
+             FROM portfolios p
+             JOIN user_preferences up ON up.user_id = p.user_id
+             WHERE p.id = $1),
+            'USD')"
+    )
+    .bind(portfolio_id)
+    .fetch_one(pool)
+    .await
+}
@@ -0,0 +1,49 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::GlidePathSettings;
+
+pub async fn fetch(pool: &PgPool, portfolio_id: Uuid) -> Result<Option<GlidePathSettings>, sqlx::Error> {
+    sqlx::query_as::<_, GlidePathSettings>(
+        "SELECT goal_date, start_equity_pct, end_equity_pct, glide_years
+         FROM portfolio_glide_paths
+         WHERE portfolio_id = $1"
+    )
+    .bind(portfolio_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn upsert(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    settings: &GlidePathSettings,
+) -> Result<GlidePathSettings, sqlx::Error> {
+    sqlx::query_as::<_, GlidePathSettings>(
+        "INSERT INTO portfolio_glide_paths
+            (portfolio_id, goal_date, start_equity_pct, end_equity_pct, glide_years)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (portfolio_id) DO UPDATE SET
+            goal_date = EXCLUDED.goal_date,
+            start_equity_pct = EXCLUDED.start_equity_pct,
+            end_equity_pct = EXCLUDED.end_equity_pct,
+            glide_years = EXCLUDED.glide_years,
+            updated_at = NOW()
+         RETURNING goal_date, start_equity_pct, end_equity_pct, glide_years"
+    )
+    .bind(portfolio_id)
+    .bind(settings.goal_date)
+    .bind(settings.start_equity_pct)
+    .bind(settings.end_equity_pct)
+    .bind(settings.glide_years)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete(pool: &PgPool, portfolio_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM portfolio_glide_paths WHERE portfolio_id = $1")
+        .bind(portfolio_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
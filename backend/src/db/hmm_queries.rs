@@ -7,6 +7,7 @@ use uuid::Uuid;
 use crate::errors::AppError;
 use crate::models::StateProbabilities;
 use crate::services::hmm_training_service::TrainedHmmModel;
+use crate::services::clock;
 
 /// Database record for HMM model
 #[allow(dead_code)]
@@ -88,7 +89,7 @@ pub async fn save_hmm_model(
     .bind(sqlx::types::Json(&model.transition_matrix))
     .bind(sqlx::types::Json(&model.emission_params))
     .bind(sqlx::types::Json(&model.observation_symbols))
-    .bind(chrono::Utc::now().date_naive())
+    .bind(clock::today())
     .bind(model.training_data_start)
     .bind(model.training_data_end)
     .bind(model_accuracy)
@@ -1,5 +1,5 @@
-use chrono::NaiveDate;
-use sqlx::PgPool;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 use crate::models::{AccountValueHistory, CreateHoldingSnapshot, HoldingSnapshot, LatestAccountHolding};
 
@@ -15,11 +15,11 @@ pub async fn create(
         "INSERT INTO holdings_snapshots
          (id, account_id, snapshot_date, ticker, holding_name, asset_category, industry,
           quantity, price, average_cost, book_value, market_value, fund,
-          accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+          accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets, ticker_type, currency)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
          RETURNING id, account_id, snapshot_date, ticker, holding_name, asset_category, industry,
                    quantity, price, average_cost, book_value, market_value, fund,
-                   accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets, created_at"
+                   accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets, ticker_type, currency, created_at"
     )
     .bind(id)
     .bind(account_id)
@@ -38,12 +38,14 @@ pub async fn create(
     .bind(&input.gain_loss)
     .bind(&input.gain_loss_pct)
     .bind(&input.percentage_of_assets)
+    .bind(input.ticker_type.as_str())
+    .bind(&input.currency)
     .fetch_one(pool)
     .await
 }
 
-pub async fn upsert(
-    pool: &PgPool,
+pub async fn upsert<'e>(
+    executor: impl PgExecutor<'e>,
     account_id: Uuid,
     snapshot_date: NaiveDate,
     input: CreateHoldingSnapshot,
@@ -53,8 +55,8 @@ pub async fn upsert(
         "INSERT INTO holdings_snapshots
          (id, account_id, snapshot_date, ticker, holding_name, asset_category, industry,
           quantity, price, average_cost, book_value, market_value, fund,
-          accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+          accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets, ticker_type, currency)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
          ON CONFLICT (account_id, snapshot_date, ticker)
          DO UPDATE SET
              holding_name = EXCLUDED.holding_name,
@@ -69,10 +71,12 @@ pub async fn upsert(
              accrued_interest = EXCLUDED.accrued_interest,
              gain_loss = EXCLUDED.gain_loss,
              gain_loss_pct = EXCLUDED.gain_loss_pct,
-             percentage_of_assets = EXCLUDED.percentage_of_assets
+             percentage_of_assets = EXCLUDED.percentage_of_assets,
+             ticker_type = EXCLUDED.ticker_type,
+             currency = EXCLUDED.currency
          RETURNING id, account_id, snapshot_date, ticker, holding_name, asset_category, industry,
                    quantity, price, average_cost, book_value, market_value, fund,
-                   accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets, created_at"
+                   accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets, ticker_type, currency, created_at"
     )
     .bind(id)
     .bind(account_id)
@@ -91,7 +95,9 @@ pub async fn upsert(
     .bind(&input.gain_loss)
     .bind(&input.gain_loss_pct)
     .bind(&input.percentage_of_assets)
-    .fetch_one(pool)
+    .bind(input.ticker_type.as_str())
+    .bind(&input.currency)
+    .fetch_one(executor)
     .await
 }
 
@@ -103,7 +109,7 @@ pub async fn fetch_by_account(
     sqlx::query_as::<_, HoldingSnapshot>(
         "SELECT id, account_id, snapshot_date, ticker, holding_name, asset_category, industry,
                 quantity, price, average_cost, book_value, market_value, fund,
-                accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets, created_at
+                accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets, ticker_type, currency, created_at
          FROM holdings_snapshots
          WHERE account_id = $1
          ORDER BY snapshot_date DESC, ticker"
@@ -113,21 +119,74 @@ pub async fn fetch_by_account(
     .await
 }
 
-pub async fn fetch_by_account_and_date(
-    pool: &PgPool,
+pub async fn fetch_by_account_and_date<'e>(
+    executor: impl PgExecutor<'e>,
     account_id: Uuid,
     snapshot_date: NaiveDate,
 ) -> Result<Vec<HoldingSnapshot>, sqlx::Error> {
     sqlx::query_as::<_, HoldingSnapshot>(
         "SELECT id, account_id, snapshot_date, ticker, holding_name, asset_category, industry,
                 quantity, price, average_cost, book_value, market_value, fund,
-                accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets, created_at
+                accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets, ticker_type, currency, created_at
          FROM holdings_snapshots
          WHERE account_id = $1 AND snapshot_date = $2
          ORDER BY ticker"
     )
     .bind(account_id)
     .bind(snapshot_date)
+    .fetch_all(executor)
+    .await
+}
+
+/// Holdings from each account's earliest snapshot in a portfolio.
+///
+/// These are the opening balances for lot tracking: later changes are recorded as
+/// detected transactions.
+pub async fn fetch_portfolio_opening_holdings(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+) -> Result<Vec<HoldingSnapshot>, sqlx::Error> {
+    sqlx::query_as::<_, HoldingSnapshot>(
+        "SELECT h.id, h.account_id, h.snapshot_date, h.ticker, h.holding_name, h.asset_category, h.industry,
+                h.quantity, h.price, h.average_cost, h.book_value, h.market_value, h.fund,
+                h.accrued_interest, h.gain_loss, h.gain_loss_pct, h.percentage_of_assets, h.ticker_type, h.currency, h.created_at
+         FROM holdings_snapshots h
+         JOIN accounts a ON a.id = h.account_id
+         WHERE a.portfolio_id = $1
+           AND h.snapshot_date = (
+               SELECT MIN(h2.snapshot_date) FROM holdings_snapshots h2 WHERE h2.account_id = h.account_id
+           )
+         ORDER BY h.account_id, h.ticker"
+    )
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Every holdings snapshot of a portfolio's accounts that was in effect on or
+/// after `since`: each account's last snapshot on or before `since` and all later
+/// ones. Accounts first imported after `since` return all their snapshots.
+pub async fn fetch_portfolio_holdings_since(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    since: NaiveDate,
+) -> Result<Vec<HoldingSnapshot>, sqlx::Error> {
+    sqlx::query_as::<_, HoldingSnapshot>(
+        "SELECT h.id, h.account_id, h.snapshot_date, h.ticker, h.holding_name, h.asset_category, h.industry,
+                h.quantity, h.price, h.average_cost, h.book_value, h.market_value, h.fund,
+                h.accrued_interest, h.gain_loss, h.gain_loss_pct, h.percentage_of_assets, h.ticker_type, h.currency, h.created_at
+         FROM holdings_snapshots h
+         JOIN accounts a ON a.id = h.account_id
+         WHERE a.portfolio_id = $1
+           AND h.snapshot_date >= COALESCE(
+               (SELECT MAX(h2.snapshot_date) FROM holdings_snapshots h2
+                WHERE h2.account_id = h.account_id AND h2.snapshot_date <= $2),
+               h.snapshot_date
+           )
+         ORDER BY h.account_id, h.snapshot_date, h.ticker"
+    )
+    .bind(portfolio_id)
+    .bind(since)
     .fetch_all(pool)
     .await
 }
@@ -160,6 +219,52 @@ pub async fn fetch_portfolio_latest_holdings(
     .await
 }
 
+/// Whether any holdings of the portfolio were recorded after `since`
+pub async fn holdings_changed_since(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM holdings_snapshots hs
+            JOIN accounts a ON a.id = hs.account_id
+            WHERE a.portfolio_id = $1
+              AND hs.created_at > $2
+        )
+        "#,
+    )
+    .bind(portfolio_id)
+    .bind(since)
+    .fetch_one(pool)
+    .await
+}
+
+/// Tickers currently held by a user, optionally limited to one portfolio
+pub async fn fetch_user_held_tickers(
+    pool: &PgPool,
+    user_id: Uuid,
+    portfolio_id: Option<Uuid>,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT DISTINCT lah.ticker
+         FROM latest_account_holdings lah
+         JOIN accounts a ON lah.account_id = a.id
+         JOIN portfolios p ON a.portfolio_id = p.id
+         WHERE p.user_id = $1
+           AND p.archived_at IS NULL
+           AND ($2::uuid IS NULL OR p.id = $2)
+           AND lah.quantity > 0
+         ORDER BY lah.ticker"
+    )
+    .bind(user_id)
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await
+}
+
 pub async fn fetch_account_value_history(
     pool: &PgPool,
     account_id: Uuid,
@@ -183,7 +288,8 @@ pub async fn fetch_portfolio_value_history(
             avh.total_value,
             avh.total_cost,
             avh.total_gain_loss,
-            avh.total_gain_loss_pct
+            avh.total_gain_loss_pct,
+            avh.source
          FROM account_value_history avh
          JOIN accounts a ON avh.account_id = a.id
          WHERE a.portfolio_id = $1
@@ -201,3 +307,138 @@ pub async fn delete_by_account(pool: &PgPool, account_id: Uuid) -> Result<u64, s
         .await?;
     Ok(result.rows_affected())
 }
+
+/// Keep only each account's last snapshot date per `period` ("week" or "month")
+/// among snapshot dates in `[from, before)`; every holding row of the other dates
+/// is removed. An account's first snapshot date is always kept: opening lots and
+/// annotations are seeded from it. Returns the number of rows removed.
+pub async fn compact(
+    pool: &PgPool,
+    from: Option<NaiveDate>,
+    before: NaiveDate,
+    period: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM holdings_snapshots hs
+         USING (
+             SELECT account_id, snapshot_date, ROW_NUMBER() OVER (
+                 PARTITION BY account_id, date_trunc($3, snapshot_date)
+                 ORDER BY snapshot_date DESC
+             ) AS rn
+             FROM (
+                 SELECT DISTINCT account_id, snapshot_date
+                 FROM holdings_snapshots
+                 WHERE ($1::DATE IS NULL OR snapshot_date >= $1)
+                   AND snapshot_date < $2
+             ) dates
+         ) ranked
+         WHERE hs.account_id = ranked.account_id
+           AND hs.snapshot_date = ranked.snapshot_date
+           AND ranked.rn > 1
+           AND hs.snapshot_date > (
+               SELECT MIN(snapshot_date) FROM holdings_snapshots WHERE account_id = hs.account_id
+           )"
+    )
+    .bind(from)
+    .bind(before)
+    .bind(period)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Name, industry and category of each ticker in a portfolio's latest snapshot
+#[derive(Debug, Clone, sqlx::FromRow)]
+#[allow(dead_code)]
+pub struct HoldingDetail {
+    pub ticker: String,
+    pub holding_name: Option<String>,
+    pub industry: Option<String>,
+    pub asset_category: Option<String>,
+    pub market_value: Option<f64>,
+}
+
+pub async fn fetch_latest_holding_details(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+) -> Result<Vec<HoldingDetail>, sqlx::Error> {
+    sqlx::query_as::<_, HoldingDetail>(
+        r#"
+        WITH latest AS (
+            SELECT MAX(h.snapshot_date) as snapshot_date
+            FROM holdings_snapshots h
+            JOIN accounts a ON h.account_id = a.id
+            WHERE a.portfolio_id = $1
+        )
+        SELECT DISTINCT ON (h.ticker)
+            h.ticker,
+            h.holding_name,
+            h.industry,
+            h.asset_category,
+            h.market_value::double precision as market_value
+        FROM holdings_snapshots h
+        JOIN accounts a ON h.account_id = a.id
+        JOIN latest l ON h.snapshot_date = l.snapshot_date
+        WHERE a.portfolio_id = $1
+          AND h.ticker != ''
+        ORDER BY h.ticker, h.market_value DESC
+        "#,
+    )
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    async fn insert_snapshot(pool: &PgPool, account_id: Uuid, snapshot_date: NaiveDate) {
+        sqlx::query(
+            "INSERT INTO holdings_snapshots (id, account_id, snapshot_date, ticker, quantity, price, average_cost, book_value, market_value)
+             VALUES ($1, $2, $3, 'AAPL', 10, 150, 100, 1000, 1500)"
+        )
+        .bind(Uuid::new_v4())
+        .bind(account_id)
+        .bind(snapshot_date)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_compaction_keeps_opening_snapshot() {
+        let pool = test_support::pool().await;
+        let owner = test_support::user(&pool).await;
+        let portfolio_id = test_support::portfolio(&pool, owner).await;
+        let account_id = test_support::account(&pool, portfolio_id).await;
+        // Tuesday 2024-01-02 opens the account; the 5th is the last of that week
+        for day in [2, 3, 5, 8, 10] {
+            insert_snapshot(&pool, account_id, date(2024, 1, day)).await;
+        }
+
+        compact(&pool, None, date(2024, 2, 1), "week").await.unwrap();
+
+        let dates: Vec<NaiveDate> = sqlx::query_scalar(
+            "SELECT DISTINCT snapshot_date FROM holdings_snapshots WHERE account_id = $1 ORDER BY snapshot_date"
+        )
+        .bind(account_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(dates, vec![date(2024, 1, 2), date(2024, 1, 5), date(2024, 1, 10)]);
+
+        let opening = fetch_portfolio_opening_holdings(&pool, portfolio_id).await.unwrap();
+        assert!(opening.iter().all(|h| h.snapshot_date == date(2024, 1, 2)));
+        assert_eq!(opening.len(), 1);
+
+        test_support::delete_user(&pool, owner).await;
+    }
+}
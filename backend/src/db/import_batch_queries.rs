@@ -0,0 +1,212 @@
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::models::{ImportBatch, ImportBatchChange, ImportChangeSummary, IMPORT_BATCH_TABLES};
+
+const BATCH_COLUMNS: &str = "id, user_id, portfolio_id, source, filename, status, accounts_created, holdings_created,
+     transactions_detected, error_count, created_at, rolled_back_at";
+
+/// Create a batch and tag the current transaction with it, so the change
+/// triggers record everything written until the transaction ends
+pub async fn begin(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    portfolio_id: Uuid,
+    source: &str,
+    filename: Option<&str>,
+) -> Result<ImportBatch, sqlx::Error> {
+    let batch = sqlx::query_as::<_, ImportBatch>(&format!(
+        "INSERT INTO import_batches (user_id, portfolio_id, source, filename)
+         VALUES ($1, $2, $3, $4)
+         RETURNING {}",
+        BATCH_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(portfolio_id)
+    .bind(source)
+    .bind(filename)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query("SELECT set_config('rustfolio.import_batch_id', $1, true)")
+        .bind(batch.id.to_string())
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(batch)
+}
+
+pub async fn record_counts<'e>(
+    executor: impl PgExecutor<'e>,
+    batch_id: Uuid,
+    accounts_created: usize,
+    holdings_created: usize,
+    transactions_detected: usize,
+    error_count: usize,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE import_batches
+         SET accounts_created = $2, holdings_created = $3, transactions_detected = $4, error_count = $5
+         WHERE id = $1"
+    )
+    .bind(batch_id)
+    .bind(accounts_created as i32)
+    .bind(holdings_created as i32)
+    .bind(transactions_detected as i32)
+    .bind(error_count as i32)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Rows the batch inserted, updated and deleted, per table. Each row counts
+/// once, by the first change made to it: a row inserted and then updated by
+/// the same import was inserted.
+pub async fn change_summary<'e>(
+    executor: impl PgExecutor<'e>,
+    batch_id: Uuid,
+) -> Result<Vec<ImportChangeSummary>, sqlx::Error> {
+    sqlx::query_as::<_, ImportChangeSummary>(
+        "SELECT table_name,
+                COUNT(*) FILTER (WHERE operation = 'INSERT') AS inserted,
+                COUNT(*) FILTER (WHERE operation = 'UPDATE') AS updated,
+                COUNT(*) FILTER (WHERE operation = 'DELETE') AS deleted
+         FROM (
+             SELECT DISTINCT ON (table_name, row_id) table_name, operation
+             FROM import_batch_changes
+             WHERE batch_id = $1
+             ORDER BY table_name, row_id, id
+         ) first_changes
+         GROUP BY table_name
+         ORDER BY table_name"
+    )
+    .bind(batch_id)
+    .fetch_all(executor)
+    .await
+}
+
+pub async fn fetch_by_portfolio(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<ImportBatch>, sqlx::Error> {
+    sqlx::query_as::<_, ImportBatch>(&format!(
+        "SELECT {} FROM import_batches WHERE portfolio_id = $1 ORDER BY created_at DESC",
+        BATCH_COLUMNS
+    ))
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Lock one of the user's batches for the duration of a rollback
+pub async fn lock<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: Uuid,
+    batch_id: Uuid,
+) -> Result<Option<ImportBatch>, sqlx::Error> {
+    sqlx::query_as::<_, ImportBatch>(&format!(
+        "SELECT {} FROM import_batches WHERE id = $1 AND user_id = $2 FOR UPDATE",
+        BATCH_COLUMNS
+    ))
+    .bind(batch_id)
+    .bind(user_id)
+    .fetch_optional(executor)
+    .await
+}
+
+/// A later, still applied batch that changed a row this batch also changed.
+/// Reverting this batch first would overwrite that batch's changes.
+pub async fn find_later_overlapping_batch<'e>(
+    executor: impl PgExecutor<'e>,
+    batch_id: Uuid,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>(
+        "SELECT later.batch_id
+         FROM import_batch_changes mine
+         JOIN import_batch_changes later
+           ON later.table_name = mine.table_name
+          AND later.row_id = mine.row_id
+          AND later.batch_id <> mine.batch_id
+          AND later.id > mine.id
+         JOIN import_batches b ON b.id = later.batch_id AND b.status = 'applied'
+         WHERE mine.batch_id = $1
+         ORDER BY later.id DESC
+         LIMIT 1"
+    )
+    .bind(batch_id)
+    .fetch_optional(executor)
+    .await
+}
+
+/// The batch's changes, newest first (the order they must be undone in)
+pub async fn fetch_changes<'e>(
+    executor: impl PgExecutor<'e>,
+    batch_id: Uuid,
+) -> Result<Vec<ImportBatchChange>, sqlx::Error> {
+    sqlx::query_as::<_, ImportBatchChange>(
+        "SELECT table_name, operation, row_id, old_row
+         FROM import_batch_changes
+         WHERE batch_id = $1
+         ORDER BY id DESC"
+    )
+    .bind(batch_id)
+    .fetch_all(executor)
+    .await
+}
+
+/// Updatable columns of a table other than `id`
+pub async fn fetch_table_columns<'e>(executor: impl PgExecutor<'e>, table: &str) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT column_name::TEXT
+         FROM information_schema.columns
+         WHERE table_schema = current_schema() AND table_name = $1
+           AND column_name <> 'id' AND is_generated = 'NEVER'
+         ORDER BY ordinal_position"
+    )
+    .bind(table)
+    .fetch_all(executor)
+    .await
+}
+
+/// Undo one recorded change. `columns` are the table's columns from
+/// `fetch_table_columns`, used to restore an updated row in place.
+pub async fn revert_change<'e>(
+    executor: impl PgExecutor<'e>,
+    change: &ImportBatchChange,
+    columns: &[String],
+) -> Result<u64, sqlx::Error> {
+    let table = IMPORT_BATCH_TABLES
+        .iter()
+        .find(|t| **t == change.table_name)
+        .ok_or_else(|| sqlx::Error::Protocol(format!("Unexpected import batch table {}", change.table_name)))?;
+
+    let sql = match change.operation.as_str() {
+        "INSERT" => format!("DELETE FROM {} WHERE id = $1", table),
+        "UPDATE" => {
+            let columns = columns.join(", ");
+            format!(
+                "UPDATE {table} SET ({columns}) = (SELECT {columns} FROM jsonb_populate_record(NULL::{table}, $2))
+                 WHERE id = $1"
+            )
+        }
+        _ => format!(
+            "INSERT INTO {table} SELECT * FROM jsonb_populate_record(NULL::{table}, $2) ON CONFLICT DO NOTHING"
+        ),
+    };
+
+    let result = sqlx::query(&sql)
+        .bind(change.row_id)
+        .bind(&change.old_row)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn mark_rolled_back<'e>(executor: impl PgExecutor<'e>, batch_id: Uuid) -> Result<ImportBatch, sqlx::Error> {
+    sqlx::query_as::<_, ImportBatch>(&format!(
+        "UPDATE import_batches SET status = 'rolled_back', rolled_back_at = NOW()
+         WHERE id = $1
+         RETURNING {}",
+        BATCH_COLUMNS
+    ))
+    .bind(batch_id)
+    .fetch_one(executor)
+    .await
+}
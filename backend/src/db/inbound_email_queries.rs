@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{InboundAttachmentResult, InboundEmail, InboundEmailAddress};
+
+const EMAIL_COLUMNS: &str = "id, user_id, portfolio_id, message_id, sender, subject, status, attachments, error,
+     received_at, processed_at";
+
+pub async fn fetch_address(pool: &PgPool, portfolio_id: Uuid) -> Result<Option<InboundEmailAddress>, sqlx::Error> {
+    sqlx::query_as::<_, InboundEmailAddress>(
+        "SELECT user_id, portfolio_id, token, created_at
+         FROM inbound_email_addresses
+         WHERE portfolio_id = $1"
+    )
+    .bind(portfolio_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn find_address_by_token(pool: &PgPool, token: &str) -> Result<Option<InboundEmailAddress>, sqlx::Error> {
+    sqlx::query_as::<_, InboundEmailAddress>(
+        "SELECT user_id, portfolio_id, token, created_at
+         FROM inbound_email_addresses
+         WHERE token = $1"
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Create the portfolio's address, or replace its token so the old address
+/// stops working
+pub async fn upsert_address(
+    pool: &PgPool,
+    user_id: Uuid,
+    portfolio_id: Uuid,
+    token: &str,
+) -> Result<InboundEmailAddress, sqlx::Error> {
+    sqlx::query_as::<_, InboundEmailAddress>(
+        "INSERT INTO inbound_email_addresses (user_id, portfolio_id, token)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (portfolio_id) DO UPDATE
+         SET token = EXCLUDED.token,
+             created_at = NOW()
+         RETURNING user_id, portfolio_id, token, created_at"
+    )
+    .bind(user_id)
+    .bind(portfolio_id)
+    .bind(token)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete_address(pool: &PgPool, portfolio_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM inbound_email_addresses WHERE portfolio_id = $1")
+        .bind(portfolio_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Record a received email. Returns None when an email with the same
+/// Message-Id was already recorded for the portfolio.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_email(
+    pool: &PgPool,
+    user_id: Uuid,
+    portfolio_id: Uuid,
+    message_id: Option<&str>,
+    sender: &str,
+    subject: Option<&str>,
+    status: &str,
+    error: Option<&str>,
+) -> Result<Option<InboundEmail>, sqlx::Error> {
+    sqlx::query_as::<_, InboundEmail>(&format!(
+        "INSERT INTO inbound_emails (user_id, portfolio_id, message_id, sender, subject, status, error, processed_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, CASE WHEN $6 = 'received' THEN NULL ELSE NOW() END)
+         ON CONFLICT (portfolio_id, message_id) DO NOTHING
+         RETURNING {}",
+        EMAIL_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(portfolio_id)
+    .bind(message_id)
+    .bind(sender)
+    .bind(subject)
+    .bind(status)
+    .bind(error)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn finish_email(
+    pool: &PgPool,
+    email_id: Uuid,
+    status: &str,
+    attachments: &[InboundAttachmentResult],
+    error: Option<&str>,
+) -> Result<InboundEmail, sqlx::Error> {
+    sqlx::query_as::<_, InboundEmail>(&format!(
+        "UPDATE inbound_emails
+         SET status = $2, attachments = $3, error = $4, processed_at = NOW()
+         WHERE id = $1
+         RETURNING {}",
+        EMAIL_COLUMNS
+    ))
+    .bind(email_id)
+    .bind(status)
+    .bind(Json(attachments))
+    .bind(error)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn fetch_emails(pool: &PgPool, portfolio_id: Uuid, limit: i64) -> Result<Vec<InboundEmail>, sqlx::Error> {
+    sqlx::query_as::<_, InboundEmail>(&format!(
+        "SELECT {}
+         FROM inbound_emails
+         WHERE portfolio_id = $1
+         ORDER BY received_at DESC
+         LIMIT $2",
+        EMAIL_COLUMNS
+    ))
+    .bind(portfolio_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Record a webhook signature token; false when it was already used
+pub async fn claim_webhook_token(
+    pool: &PgPool,
+    token: &str,
+    signed_at: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO mailgun_webhook_tokens (token, signed_at)
+         VALUES ($1, $2)
+         ON CONFLICT (token) DO NOTHING"
+    )
+    .bind(token)
+    .bind(signed_at)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Forget tokens signed before `before`; their signatures have expired anyway
+pub async fn prune_webhook_tokens(pool: &PgPool, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM mailgun_webhook_tokens WHERE signed_at < $1")
+        .bind(before)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,100 @@
+use sqlx::PgPool;
+
+/// A consistency rule over one table: rows of `table` matching `predicate`
+/// violate it. Repairable checks only match derived rows (snapshots and
+/// caches) that are rebuilt on demand, so deleting them loses nothing.
+#[derive(Debug, Clone, Copy)]
+pub struct IntegrityCheck {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Table with an alias the predicate refers to, e.g. "accounts a"
+    pub table: &'static str,
+    pub predicate: &'static str,
+    pub repairable: bool,
+}
+
+const fn orphaned_cache(name: &'static str, table: &'static str) -> IntegrityCheck {
+    IntegrityCheck {
+        name,
+        description: "Cache rows for a deleted portfolio",
+        table,
+        predicate: "NOT EXISTS (SELECT 1 FROM portfolios p WHERE p.id = c.portfolio_id)",
+        repairable: true,
+    }
+}
+
+pub const CHECKS: &[IntegrityCheck] = &[
+    IntegrityCheck {
+        name: "accounts_without_portfolio",
+        description: "Accounts whose portfolio no longer exists",
+        table: "accounts a",
+        predicate: "NOT EXISTS (SELECT 1 FROM portfolios p WHERE p.id = a.portfolio_id)",
+        repairable: false,
+    },
+    IntegrityCheck {
+        name: "portfolios_without_owner",
+        description: "Portfolios whose owning user no longer exists",
+        table: "portfolios p",
+        predicate: "p.user_id IS NOT NULL AND NOT EXISTS (SELECT 1 FROM users u WHERE u.id = p.user_id)",
+        repairable: false,
+    },
+    IntegrityCheck {
+        name: "orphaned_holdings_snapshots",
+        description: "Holdings snapshots of a deleted account",
+        table: "holdings_snapshots s",
+        predicate: "NOT EXISTS (SELECT 1 FROM accounts a WHERE a.id = s.account_id)",
+        repairable: true,
+    },
+    IntegrityCheck {
+        name: "orphaned_risk_snapshots",
+        description: "Risk snapshots of a deleted portfolio",
+        table: "risk_snapshots s",
+        predicate: "NOT EXISTS (SELECT 1 FROM portfolios p WHERE p.id = s.portfolio_id)",
+        repairable: true,
+    },
+    IntegrityCheck {
+        name: "orphaned_portfolio_value_history",
+        description: "Value history of a deleted account or portfolio",
+        table: "portfolio_value_history h",
+        predicate: "NOT EXISTS (SELECT 1 FROM accounts a WHERE a.id = h.account_id)
+                    OR NOT EXISTS (SELECT 1 FROM portfolios p WHERE p.id = h.portfolio_id)",
+        repairable: true,
+    },
+    orphaned_cache("orphaned_portfolio_risk_cache", "portfolio_risk_cache c"),
+    orphaned_cache("orphaned_downside_risk_cache", "downside_risk_cache c"),
+    orphaned_cache("orphaned_correlations_cache", "portfolio_correlations_cache c"),
+    orphaned_cache("orphaned_narrative_cache", "portfolio_narrative_cache c"),
+    orphaned_cache("orphaned_news_cache", "portfolio_news_cache c"),
+    orphaned_cache("orphaned_news_feed_cache", "portfolio_news_feed_cache c"),
+    orphaned_cache("orphaned_optimization_cache", "portfolio_optimization_cache c"),
+    orphaned_cache("orphaned_long_term_guidance_cache", "long_term_guidance_cache c"),
+    IntegrityCheck {
+        name: "negative_snapshot_quantities",
+        description: "Holdings snapshots with a negative quantity",
+        table: "holdings_snapshots s",
+        predicate: "s.quantity < 0",
+        repairable: false,
+    },
+    IntegrityCheck {
+        name: "negative_transaction_quantities",
+        description: "Transactions with a negative quantity",
+        table: "transactions t",
+        predicate: "t.quantity < 0",
+        repairable: false,
+    },
+];
+
+/// Rows violating `check`
+pub async fn count_violations(pool: &PgPool, check: &IntegrityCheck) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {} WHERE {}", check.table, check.predicate))
+        .fetch_one(pool)
+        .await
+}
+
+/// Delete the rows violating `check`, returning how many went
+pub async fn delete_violations(pool: &PgPool, check: &IntegrityCheck) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(&format!("DELETE FROM {} WHERE {}", check.table, check.predicate))
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() as i64)
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::external::price_provider::{ExternalIntradayBar, IntradayInterval};
+use crate::models::IntradayPrice;
+
+pub async fn upsert_bars(
+    pool: &PgPool,
+    ticker: &str,
+    interval: IntradayInterval,
+    bars: &[ExternalIntradayBar],
+    fetched_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    let times: Vec<DateTime<Utc>> = bars.iter().map(|b| b.timestamp).collect();
+    let opens: Vec<f64> = bars.iter().map(|b| b.open).collect();
+    let highs: Vec<f64> = bars.iter().map(|b| b.high).collect();
+    let lows: Vec<f64> = bars.iter().map(|b| b.low).collect();
+    let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+    let volumes: Vec<Option<i64>> = bars.iter().map(|b| b.volume).collect();
+
+    sqlx::query(
+        "INSERT INTO intraday_prices (ticker, interval, bar_time, open, high, low, close, volume, fetched_at)
+         SELECT $1, $2, b.bar_time, b.open, b.high, b.low, b.close, b.volume, $9
+         FROM UNNEST($3::timestamptz[], $4::float8[], $5::float8[], $6::float8[], $7::float8[], $8::int8[])
+              AS b(bar_time, open, high, low, close, volume)
+         ON CONFLICT (ticker, interval, bar_time) DO UPDATE SET
+            open = EXCLUDED.open,
+            high = EXCLUDED.high,
+            low = EXCLUDED.low,
+            close = EXCLUDED.close,
+            volume = EXCLUDED.volume,
+            fetched_at = EXCLUDED.fetched_at"
+    )
+    .bind(ticker)
+    .bind(interval.as_str())
+    .bind(&times)
+    .bind(&opens)
+    .bind(&highs)
+    .bind(&lows)
+    .bind(&closes)
+    .bind(&volumes)
+    .bind(fetched_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Bars of the latest stored session (the UTC day of the latest bar), oldest first
+pub async fn fetch_latest_session(
+    pool: &PgPool,
+    ticker: &str,
+    interval: IntradayInterval,
+) -> Result<Vec<IntradayPrice>, sqlx::Error> {
+    sqlx::query_as::<_, IntradayPrice>(
+        "SELECT bar_time, open::float8 AS open, high::float8 AS high, low::float8 AS low,
+                close::float8 AS close, volume
+         FROM intraday_prices
+         WHERE ticker = $1 AND interval = $2
+           AND bar_time >= (
+               SELECT date_trunc('day', MAX(bar_time) AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+               FROM intraday_prices
+               WHERE ticker = $1 AND interval = $2
+           )
+         ORDER BY bar_time"
+    )
+    .bind(ticker)
+    .bind(interval.as_str())
+    .fetch_all(pool)
+    .await
+}
+
+/// When bars of the ticker and interval were last fetched
+pub async fn fetch_last_fetched_at(
+    pool: &PgPool,
+    ticker: &str,
+    interval: IntradayInterval,
+) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+        "SELECT MAX(fetched_at) FROM intraday_prices WHERE ticker = $1 AND interval = $2"
+    )
+    .bind(ticker)
+    .bind(interval.as_str())
+    .fetch_one(pool)
+    .await
+}
+
+/// Delete a ticker's bars that started before `before`
+pub async fn delete_before(pool: &PgPool, ticker: &str, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM intraday_prices WHERE ticker = $1 AND bar_time < $2")
+        .bind(ticker)
+        .bind(before)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
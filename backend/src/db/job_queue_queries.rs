@@ -0,0 +1,202 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{JobQueueSummary, QueuedJob};
+
+/// Advisory lock serializing claims, so per-job concurrency limits hold across workers
+const CLAIM_LOCK_KEY: i64 = 0x6a6f_6271_7565; // "jobque"
+
+/// Queue a job unless one was already queued under the same key. Returns the
+/// new job's ID, or `None` for a duplicate.
+pub async fn enqueue(
+    pool: &PgPool,
+    job_name: &str,
+    dedupe_key: &str,
+    max_attempts: i32,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        r#"
+        INSERT INTO job_queue (job_name, dedupe_key, max_attempts)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (job_name, dedupe_key) DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(job_name)
+    .bind(dedupe_key)
+    .bind(max_attempts)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Claim the next due job whose name is below its concurrency limit: a queued
+/// job, or a running one whose lease expired. `limits` pairs job names with
+/// their maximum concurrent runs; other jobs get `default_limit`.
+pub async fn claim(
+    pool: &PgPool,
+    worker: &str,
+    limits: &[(String, i32)],
+    default_limit: i32,
+    visibility_secs: i64,
+) -> Result<Option<QueuedJob>, sqlx::Error> {
+    let (names, maxima): (Vec<String>, Vec<i32>) = limits.iter().cloned().unzip();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(CLAIM_LOCK_KEY)
+        .execute(&mut *tx)
+        .await?;
+
+    let job = sqlx::query_as::<_, QueuedJob>(
+        r#"
+        WITH limits AS (
+            SELECT * FROM UNNEST($2::TEXT[], $3::INT[]) AS l(job_name, max_running)
+        ),
+        running AS (
+            SELECT job_name, COUNT(*) AS n
+            FROM job_queue
+            WHERE status = 'running' AND visible_until > NOW()
+            GROUP BY job_name
+        ),
+        next AS (
+            SELECT q.id
+            FROM job_queue q
+            LEFT JOIN limits l ON l.job_name = q.job_name
+            LEFT JOIN running r ON r.job_name = q.job_name
+            WHERE ((q.status = 'queued' AND q.run_after <= NOW())
+                OR (q.status = 'running' AND q.visible_until <= NOW()))
+              AND COALESCE(r.n, 0) < COALESCE(l.max_running, $4)
+            ORDER BY q.run_after, q.id
+            LIMIT 1
+            FOR UPDATE OF q SKIP LOCKED
+        )
+        UPDATE job_queue j
+        SET status = 'running',
+            attempts = j.attempts + 1,
+            claim_token = gen_random_uuid(),
+            claimed_by = $1,
+            visible_until = NOW() + make_interval(secs => $5)
+        FROM next
+        WHERE j.id = next.id
+        RETURNING j.*
+        "#,
+    )
+    .bind(worker)
+    .bind(&names)
+    .bind(&maxima)
+    .bind(default_limit)
+    .bind(visibility_secs as f64)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(job)
+}
+
+/// Extend the lease of a running job. Returns false if the claim was lost.
+pub async fn extend_lease(pool: &PgPool, id: i64, claim_token: Uuid, visibility_secs: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE job_queue
+        SET visible_until = NOW() + make_interval(secs => $3)
+        WHERE id = $1 AND claim_token = $2 AND status = 'running'
+        "#,
+    )
+    .bind(id)
+    .bind(claim_token)
+    .bind(visibility_secs as f64)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Mark a job succeeded. Returns false if the claim was lost, in which case
+/// another worker owns the job.
+pub async fn complete(pool: &PgPool, id: i64, claim_token: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE job_queue
+        SET status = 'succeeded', finished_at = NOW(), visible_until = NULL, last_error = NULL
+        WHERE id = $1 AND claim_token = $2 AND status = 'running'
+        "#,
+    )
+    .bind(id)
+    .bind(claim_token)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Record a failed attempt: the job is queued again after `retry_delay_secs`
+/// while it has attempts left, and marked failed otherwise. Returns the new
+/// status, or `None` if the claim was lost.
+pub async fn fail(
+    pool: &PgPool,
+    id: i64,
+    claim_token: Uuid,
+    error: &str,
+    retry_delay_secs: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        r#"
+        UPDATE job_queue
+        SET status = CASE WHEN attempts < max_attempts THEN 'queued' ELSE 'failed' END,
+            run_after = NOW() + make_interval(secs => $4),
+            finished_at = CASE WHEN attempts < max_attempts THEN NULL ELSE NOW() END,
+            visible_until = NULL,
+            last_error = $3
+        WHERE id = $1 AND claim_token = $2 AND status = 'running'
+        RETURNING status
+        "#,
+    )
+    .bind(id)
+    .bind(claim_token)
+    .bind(error)
+    .bind(retry_delay_secs as f64)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Job counts per name and status
+pub async fn summary(pool: &PgPool) -> Result<Vec<JobQueueSummary>, sqlx::Error> {
+    sqlx::query_as::<_, JobQueueSummary>(
+        r#"
+        SELECT job_name, status, COUNT(*) AS jobs, MIN(enqueued_at) AS oldest_enqueued_at
+        FROM job_queue
+        GROUP BY job_name, status
+        ORDER BY job_name, status
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Unfinished jobs, oldest first
+pub async fn fetch_pending(pool: &PgPool, limit: i64) -> Result<Vec<QueuedJob>, sqlx::Error> {
+    sqlx::query_as::<_, QueuedJob>(
+        r#"
+        SELECT *
+        FROM job_queue
+        WHERE status IN ('queued', 'running')
+        ORDER BY run_after, id
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Delete jobs that finished more than `days` ago
+pub async fn prune_finished(pool: &PgPool, days: i32) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM job_queue WHERE finished_at < NOW() - make_interval(days => $1)"
+    )
+    .bind(days)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,67 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{JournalEntry, UpsertJournalEntry};
+
+const ENTRY_SELECT: &str =
+    "SELECT j.id, j.transaction_id, t.account_id, t.ticker, t.transaction_type, t.transaction_date,
+            t.price::float8 AS transaction_price, j.rationale, j.target_price, j.stop_price,
+            j.expected_return_pct, j.horizon_days, j.confidence, j.created_at, j.updated_at
+     FROM trade_journal_entries j
+     JOIN detected_transactions t ON t.id = j.transaction_id";
+
+/// Record or replace the journal entry of a transaction
+pub async fn upsert(pool: &PgPool, transaction_id: Uuid, input: &UpsertJournalEntry) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO trade_journal_entries
+             (transaction_id, rationale, target_price, stop_price, expected_return_pct, horizon_days, confidence)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (transaction_id) DO UPDATE SET
+             rationale = EXCLUDED.rationale,
+             target_price = EXCLUDED.target_price,
+             stop_price = EXCLUDED.stop_price,
+             expected_return_pct = EXCLUDED.expected_return_pct,
+             horizon_days = EXCLUDED.horizon_days,
+             confidence = EXCLUDED.confidence,
+             updated_at = NOW()"
+    )
+    .bind(transaction_id)
+    .bind(&input.rationale)
+    .bind(input.target_price)
+    .bind(input.stop_price)
+    .bind(input.expected_return_pct)
+    .bind(input.horizon_days)
+    .bind(input.confidence)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn fetch_for_transaction(pool: &PgPool, transaction_id: Uuid) -> Result<Option<JournalEntry>, sqlx::Error> {
+    sqlx::query_as::<_, JournalEntry>(&format!("{} WHERE j.transaction_id = $1", ENTRY_SELECT))
+        .bind(transaction_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Journal entries across a portfolio's accounts, most recent trade first
+pub async fn fetch_for_portfolio(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<JournalEntry>, sqlx::Error> {
+    sqlx::query_as::<_, JournalEntry>(&format!(
+        "{}
+         JOIN accounts a ON a.id = t.account_id
+         WHERE a.portfolio_id = $1
+         ORDER BY t.transaction_date DESC, t.created_at DESC",
+        ENTRY_SELECT
+    ))
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete(pool: &PgPool, transaction_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM trade_journal_entries WHERE transaction_id = $1")
+        .bind(transaction_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
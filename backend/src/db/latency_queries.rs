@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::models::{LatencyDistribution, LatencyExample, LatencySample, LatencyStat};
+
+/// Rows per INSERT when flushing samples
+const INSERT_CHUNK: usize = 5_000;
+
+pub async fn insert_samples(pool: &PgPool, samples: &[LatencySample]) -> Result<u64, sqlx::Error> {
+    let mut inserted = 0;
+    for chunk in samples.chunks(INSERT_CHUNK) {
+        let kinds: Vec<&str> = chunk.iter().map(|s| s.kind.as_str()).collect();
+        let names: Vec<&str> = chunk.iter().map(|s| s.name.as_str()).collect();
+        let durations: Vec<f64> = chunk.iter().map(|s| s.duration_ms).collect();
+        let statuses: Vec<Option<i16>> = chunk.iter().map(|s| s.status).collect();
+        let request_ids: Vec<Option<&str>> = chunk.iter().map(|s| s.request_id.as_deref()).collect();
+        let recorded_at: Vec<DateTime<Utc>> = chunk.iter().map(|s| s.recorded_at).collect();
+
+        inserted += sqlx::query(
+            "INSERT INTO latency_samples (kind, name, duration_ms, status, request_id, recorded_at)
+             SELECT * FROM UNNEST($1::TEXT[], $2::TEXT[], $3::DOUBLE PRECISION[], $4::SMALLINT[], $5::TEXT[], $6::TIMESTAMPTZ[])"
+        )
+        .bind(&kinds)
+        .bind(&names)
+        .bind(&durations)
+        .bind(&statuses)
+        .bind(&request_ids)
+        .bind(&recorded_at)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    }
+    Ok(inserted)
+}
+
+pub async fn delete_samples_before(pool: &PgPool, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    Ok(sqlx::query("DELETE FROM latency_samples WHERE recorded_at < $1")
+        .bind(before)
+        .execute(pool)
+        .await?
+        .rows_affected())
+}
+
+/// p50/p95/max per endpoint and query since `since`, for names with at least
+/// `min_samples` samples
+pub async fn fetch_distributions(
+    pool: &PgPool,
+    since: DateTime<Utc>,
+    min_samples: i64,
+) -> Result<Vec<LatencyDistribution>, sqlx::Error> {
+    sqlx::query_as::<_, LatencyDistribution>(
+        "SELECT kind, name,
+                COUNT(*)::INTEGER AS sample_count,
+                percentile_cont(0.5) WITHIN GROUP (ORDER BY duration_ms) AS p50_ms,
+                percentile_cont(0.95) WITHIN GROUP (ORDER BY duration_ms) AS p95_ms,
+                MAX(duration_ms) AS max_ms
+         FROM latency_samples
+         WHERE recorded_at >= $1
+         GROUP BY kind, name
+         HAVING COUNT(*) >= $2"
+    )
+    .bind(since)
+    .bind(min_samples)
+    .fetch_all(pool)
+    .await
+}
+
+/// Budget overrides keyed by (kind, name)
+pub async fn fetch_budgets(pool: &PgPool) -> Result<HashMap<(String, String), i32>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, String, i32)>("SELECT kind, name, budget_ms FROM latency_budgets")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|(kind, name, budget)| ((kind, name), budget)).collect())
+}
+
+pub async fn fetch_stats(pool: &PgPool) -> Result<Vec<LatencyStat>, sqlx::Error> {
+    sqlx::query_as::<_, LatencyStat>(
+        "SELECT kind, name, sample_count, p50_ms, p95_ms, max_ms, budget_ms, breached, breached_since, computed_at
+         FROM latency_stats"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Replace all stored statistics with `stats`
+pub async fn replace_stats(pool: &PgPool, stats: &[LatencyStat]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM latency_stats").execute(&mut *tx).await?;
+    for stat in stats {
+        sqlx::query(
+            "INSERT INTO latency_stats
+                 (kind, name, sample_count, p50_ms, p95_ms, max_ms, budget_ms, breached, breached_since, computed_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
+        )
+        .bind(&stat.kind)
+        .bind(&stat.name)
+        .bind(stat.sample_count)
+        .bind(stat.p50_ms)
+        .bind(stat.p95_ms)
+        .bind(stat.max_ms)
+        .bind(stat.budget_ms)
+        .bind(stat.breached)
+        .bind(stat.breached_since)
+        .bind(stat.computed_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Slowest samples of one endpoint or query since `since`
+pub async fn fetch_slowest_samples(
+    pool: &PgPool,
+    kind: &str,
+    name: &str,
+    since: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<LatencyExample>, sqlx::Error> {
+    sqlx::query_as::<_, LatencyExample>(
+        "SELECT duration_ms, status, request_id, recorded_at
+         FROM latency_samples
+         WHERE kind = $1 AND name = $2 AND recorded_at >= $3
+         ORDER BY duration_ms DESC
+         LIMIT $4"
+    )
+    .bind(kind)
+    .bind(name)
+    .bind(since)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
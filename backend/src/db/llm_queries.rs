@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
-use crate::models::{LlmUsage, CreateLlmUsage, LlmUsageStats};
+use crate::models::{LlmConsent, LlmUsage, CreateLlmUsage, LlmUsageStats};
 
 /// Log LLM usage to database
 #[allow(dead_code)]
@@ -127,3 +127,68 @@ pub async fn get_recent_usage(
     .fetch_all(pool)
     .await
 }
+
+/// Latest consent decision recorded for a user
+pub async fn fetch_latest_consent(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Option<LlmConsent>, sqlx::Error> {
+    sqlx::query_as::<_, LlmConsent>(
+        r#"
+        SELECT id, user_id, version, granted, created_at
+        FROM llm_consents
+        WHERE user_id = $1
+        ORDER BY created_at DESC, id DESC
+        LIMIT 1
+        "#
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Record a consent grant or revocation and bring `user_preferences` in line:
+/// a grant enables LLM features and stamps `consent_given_at`, a revocation
+/// disables them and clears it.
+pub async fn record_consent(
+    pool: &PgPool,
+    user_id: Uuid,
+    version: i32,
+    granted: bool,
+) -> Result<LlmConsent, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let consent = sqlx::query_as::<_, LlmConsent>(
+        r#"
+        INSERT INTO llm_consents (user_id, version, granted)
+        VALUES ($1, $2, $3)
+        RETURNING id, user_id, version, granted, created_at
+        "#
+    )
+    .bind(user_id)
+    .bind(version)
+    .bind(granted)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_preferences (user_id, llm_enabled, consent_given_at, updated_at)
+        VALUES ($1, $2, CASE WHEN $2 THEN $3 END, NOW())
+        ON CONFLICT (user_id)
+        DO UPDATE SET
+            llm_enabled = EXCLUDED.llm_enabled,
+            consent_given_at = EXCLUDED.consent_given_at,
+            updated_at = NOW()
+        "#
+    )
+    .bind(user_id)
+    .bind(granted)
+    .bind(consent.created_at)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(consent)
+}
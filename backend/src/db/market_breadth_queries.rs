@@ -0,0 +1,61 @@
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use crate::models::MarketBreadth;
+
+const BREADTH_COLUMNS: &str = "date, universe_size, advancers, decliners, unchanged, pct_above_sma50, \
+     pct_above_sma200, new_highs, new_lows, computed_at";
+
+/// Insert or replace the breadth row for its date
+pub async fn upsert(pool: &PgPool, breadth: &MarketBreadth) -> Result<MarketBreadth, sqlx::Error> {
+    sqlx::query_as::<_, MarketBreadth>(&format!(
+        "INSERT INTO market_breadth (
+            date, universe_size, advancers, decliners, unchanged,
+            pct_above_sma50, pct_above_sma200, new_highs, new_lows, computed_at
+         )
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW())
+         ON CONFLICT (date) DO UPDATE SET
+            universe_size = EXCLUDED.universe_size,
+            advancers = EXCLUDED.advancers,
+            decliners = EXCLUDED.decliners,
+            unchanged = EXCLUDED.unchanged,
+            pct_above_sma50 = EXCLUDED.pct_above_sma50,
+            pct_above_sma200 = EXCLUDED.pct_above_sma200,
+            new_highs = EXCLUDED.new_highs,
+            new_lows = EXCLUDED.new_lows,
+            computed_at = NOW()
+         RETURNING {}",
+        BREADTH_COLUMNS
+    ))
+    .bind(breadth.date)
+    .bind(breadth.universe_size)
+    .bind(breadth.advancers)
+    .bind(breadth.decliners)
+    .bind(breadth.unchanged)
+    .bind(breadth.pct_above_sma50)
+    .bind(breadth.pct_above_sma200)
+    .bind(breadth.new_highs)
+    .bind(breadth.new_lows)
+    .fetch_one(pool)
+    .await
+}
+
+/// Breadth rows on or after `since`, oldest first
+pub async fn fetch_since(pool: &PgPool, since: NaiveDate) -> Result<Vec<MarketBreadth>, sqlx::Error> {
+    sqlx::query_as::<_, MarketBreadth>(&format!(
+        "SELECT {} FROM market_breadth WHERE date >= $1 ORDER BY date ASC",
+        BREADTH_COLUMNS
+    ))
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn fetch_latest(pool: &PgPool) -> Result<Option<MarketBreadth>, sqlx::Error> {
+    sqlx::query_as::<_, MarketBreadth>(&format!(
+        "SELECT {} FROM market_breadth ORDER BY date DESC LIMIT 1",
+        BREADTH_COLUMNS
+    ))
+    .fetch_optional(pool)
+    .await
+}
@@ -1,5 +1,8 @@
 pub mod portfolio_queries;
 pub(crate) mod price_queries;
+pub mod corporate_action_queries;
+pub mod ticker_metadata_queries;
+pub mod intraday_price_queries;
 pub mod analytics_queries;
 pub mod account_queries;
 pub mod holding_snapshot_queries;
@@ -18,4 +21,43 @@ pub mod recommendation_queries;
 pub mod watchlist_queries;
 pub mod long_term_guidance_queries;
 pub mod financial_planning_queries;
-pub mod auth_queries;
\ No newline at end of file
+pub mod auth_queries;
+pub mod annotation_queries;
+pub mod journal_queries;
+pub mod market_breadth_queries;
+pub mod factor_spread_queries;
+pub mod risk_budget_queries;
+pub mod optimization_constraint_queries;
+pub mod news_feed_queries;
+pub mod ownership_queries;
+pub mod fundamentals_queries;
+pub mod etf_constituent_queries;
+pub mod dividend_queries;
+pub mod fx_queries;
+pub mod analyst_queries;
+pub mod fund_metadata_queries;
+pub mod model_portfolio_queries;
+pub mod peer_statistics_queries;
+pub mod user_data_queries;
+pub mod latency_queries;
+pub mod risk_cache_queries;
+pub mod tenant;
+pub mod account_fee_queries;
+pub mod glide_path_queries;
+pub mod rebalance_schedule_queries;
+pub mod crypto_wallet_queries;
+pub mod job_queue_queries;
+pub mod csv_import_template_queries;
+pub mod import_batch_queries;
+pub mod inbound_email_queries;
+pub mod portfolio_member_queries;
+pub mod portfolio_value_history_queries;
+pub mod session_queries;
+pub mod public_api_token_queries;
+pub mod audit_queries;
+pub mod news_sentiment_queries;
+pub mod integrity_queries;
+pub mod widget_token_queries;
+pub mod factor_return_queries;
+#[cfg(test)]
+pub mod test_support;
@@ -0,0 +1,60 @@
+use sqlx::PgPool;
+
+use crate::models::{ModelAllocation, ModelPortfolio};
+
+#[derive(sqlx::FromRow)]
+struct ModelRow {
+    id: String,
+    name: String,
+    description: String,
+}
+
+async fn fetch_allocations(pool: &PgPool, model_id: &str) -> Result<Vec<ModelAllocation>, sqlx::Error> {
+    sqlx::query_as::<_, ModelAllocation>(
+        "SELECT ticker, asset_class, weight
+         FROM model_portfolio_allocations
+         WHERE model_id = $1
+         ORDER BY weight DESC, ticker"
+    )
+    .bind(model_id)
+    .fetch_all(pool)
+    .await
+}
+
+async fn with_allocations(pool: &PgPool, row: ModelRow) -> Result<ModelPortfolio, sqlx::Error> {
+    let allocations = fetch_allocations(pool, &row.id).await?;
+    Ok(ModelPortfolio {
+        id: row.id,
+        name: row.name,
+        description: row.description,
+        allocations,
+    })
+}
+
+pub async fn fetch_all(pool: &PgPool) -> Result<Vec<ModelPortfolio>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, ModelRow>(
+        "SELECT id, name, description FROM model_portfolios ORDER BY name"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut models = Vec::with_capacity(rows.len());
+    for row in rows {
+        models.push(with_allocations(pool, row).await?);
+    }
+    Ok(models)
+}
+
+pub async fn fetch_one(pool: &PgPool, id: &str) -> Result<Option<ModelPortfolio>, sqlx::Error> {
+    let row = sqlx::query_as::<_, ModelRow>(
+        "SELECT id, name, description FROM model_portfolios WHERE id = $1"
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    match row {
+        Some(row) => Ok(Some(with_allocations(pool, row).await?)),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Cached feed items and their fetch time, if the entry has not expired
+pub async fn fetch_fresh(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    days: i32,
+) -> Result<Option<(serde_json::Value, DateTime<Utc>)>, sqlx::Error> {
+    sqlx::query_as::<_, (serde_json::Value, DateTime<Utc>)>(
+        "SELECT items, fetched_at
+         FROM portfolio_news_feed_cache
+         WHERE portfolio_id = $1 AND days = $2 AND expires_at > NOW()"
+    )
+    .bind(portfolio_id)
+    .bind(days)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn upsert(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    days: i32,
+    items: serde_json::Value,
+    fetched_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO portfolio_news_feed_cache (portfolio_id, days, items, fetched_at, expires_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (portfolio_id, days) DO UPDATE SET
+            items = EXCLUDED.items,
+            fetched_at = EXCLUDED.fetched_at,
+            expires_at = EXCLUDED.expires_at"
+    )
+    .bind(portfolio_id)
+    .bind(days)
+    .bind(items)
+    .bind(fetched_at)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
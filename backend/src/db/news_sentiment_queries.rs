@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+pub async fn insert_score(
+    pool: &PgPool,
+    ticker: &str,
+    sentiment_score: f64,
+    articles_analyzed: i32,
+    latest_article_at: Option<DateTime<Utc>>,
+    source: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO news_sentiment_scores (ticker, sentiment_score, articles_analyzed, latest_article_at, source)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (ticker, scored_at) DO NOTHING"
+    )
+    .bind(ticker)
+    .bind(sentiment_score)
+    .bind(articles_analyzed)
+    .bind(latest_article_at)
+    .bind(source)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Latest score for a ticker, if one was recorded within `max_age_days`
+pub async fn fetch_latest_score(pool: &PgPool, ticker: &str, max_age_days: i32) -> Result<Option<f64>, sqlx::Error> {
+    sqlx::query_scalar::<_, f64>(
+        "SELECT sentiment_score FROM news_sentiment_scores
+         WHERE ticker = $1 AND scored_at > NOW() - make_interval(days => $2)
+         ORDER BY scored_at DESC
+         LIMIT 1"
+    )
+    .bind(ticker)
+    .bind(max_age_days)
+    .fetch_optional(pool)
+    .await
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::OptimizationConstraints;
+
+type ConstraintRow = (Option<f64>, Json<HashMap<String, f64>>, Option<f64>, Vec<String>);
+
+fn from_row((max_position_weight, sector_caps, min_cash_pct, do_not_sell): ConstraintRow) -> OptimizationConstraints {
+    OptimizationConstraints {
+        max_position_weight,
+        sector_caps: sector_caps.0,
+        min_cash_pct,
+        do_not_sell,
+    }
+}
+
+pub async fn fetch(pool: &PgPool, portfolio_id: Uuid) -> Result<Option<OptimizationConstraints>, sqlx::Error> {
+    sqlx::query_as::<_, ConstraintRow>(
+        "SELECT max_position_weight, sector_caps, min_cash_pct, do_not_sell
+         FROM portfolio_optimization_constraints
+         WHERE portfolio_id = $1"
+    )
+    .bind(portfolio_id)
+    .fetch_optional(pool)
+    .await
+    .map(|row| row.map(from_row))
+}
+
+pub async fn upsert(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    constraints: &OptimizationConstraints,
+) -> Result<OptimizationConstraints, sqlx::Error> {
+    sqlx::query_as::<_, ConstraintRow>(
+        "INSERT INTO portfolio_optimization_constraints
+            (portfolio_id, max_position_weight, sector_caps, min_cash_pct, do_not_sell)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (portfolio_id) DO UPDATE SET
+            max_position_weight = EXCLUDED.max_position_weight,
+            sector_caps = EXCLUDED.sector_caps,
+            min_cash_pct = EXCLUDED.min_cash_pct,
+            do_not_sell = EXCLUDED.do_not_sell,
+            updated_at = NOW()
+         RETURNING max_position_weight, sector_caps, min_cash_pct, do_not_sell"
+    )
+    .bind(portfolio_id)
+    .bind(constraints.max_position_weight)
+    .bind(Json(&constraints.sector_caps))
+    .bind(constraints.min_cash_pct)
+    .bind(&constraints.do_not_sell)
+    .fetch_one(pool)
+    .await
+    .map(from_row)
+}
+
+pub async fn delete(pool: &PgPool, portfolio_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM portfolio_optimization_constraints WHERE portfolio_id = $1")
+        .bind(portfolio_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
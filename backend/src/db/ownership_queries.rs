@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// Stored ownership data for a ticker and when it was fetched, regardless of age
+pub async fn fetch(pool: &PgPool, ticker: &str) -> Result<Option<(serde_json::Value, DateTime<Utc>)>, sqlx::Error> {
+    sqlx::query_as::<_, (serde_json::Value, DateTime<Utc>)>(
+        "SELECT data, fetched_at FROM ownership_snapshots WHERE ticker = $1"
+    )
+    .bind(ticker)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn upsert(pool: &PgPool, ticker: &str, data: serde_json::Value) -> Result<DateTime<Utc>, sqlx::Error> {
+    sqlx::query_scalar::<_, DateTime<Utc>>(
+        "INSERT INTO ownership_snapshots (ticker, data, fetched_at)
+         VALUES ($1, $2, NOW())
+         ON CONFLICT (ticker) DO UPDATE SET data = EXCLUDED.data, fetched_at = EXCLUDED.fetched_at
+         RETURNING fetched_at"
+    )
+    .bind(ticker)
+    .bind(data)
+    .fetch_one(pool)
+    .await
+}
@@ -0,0 +1,142 @@
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use crate::models::{PeerCommonHolding, PeerMetricDistribution};
+
+/// Latest portfolio-level risk metrics of one opted-in portfolio
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PeerPortfolioMetrics {
+    pub volatility: f64,
+    pub max_drawdown: f64,
+    pub beta: Option<f64>,
+    pub sharpe: Option<f64>,
+    pub risk_score: f64,
+}
+
+/// Latest portfolio snapshot (on or after `since`, scored with `scoring_version`)
+/// for every active portfolio whose owner opted in to anonymous statistics
+pub async fn fetch_opted_in_portfolio_metrics(
+    pool: &PgPool,
+    since: NaiveDate,
+    scoring_version: i32,
+) -> Result<Vec<PeerPortfolioMetrics>, sqlx::Error> {
+    sqlx::query_as::<_, PeerPortfolioMetrics>(
+        "SELECT volatility, max_drawdown, beta, sharpe, risk_score
+         FROM (
+             SELECT DISTINCT ON (rs.portfolio_id)
+                 rs.volatility::DOUBLE PRECISION AS volatility,
+                 rs.max_drawdown::DOUBLE PRECISION AS max_drawdown,
+                 rs.beta::DOUBLE PRECISION AS beta,
+                 rs.sharpe::DOUBLE PRECISION AS sharpe,
+                 rs.risk_score::DOUBLE PRECISION AS risk_score,
+                 rs.scoring_version
+             FROM risk_snapshots rs
+             JOIN portfolios p ON p.id = rs.portfolio_id
+             JOIN user_preferences up ON up.user_id = p.user_id
+             WHERE rs.snapshot_type = 'portfolio'
+               AND rs.snapshot_date >= $1
+               AND p.archived_at IS NULL
+               AND up.share_anonymous_stats
+             ORDER BY rs.portfolio_id, rs.snapshot_date DESC
+         ) latest
+         WHERE scoring_version = $2"
+    )
+    .bind(since)
+    .bind(scoring_version)
+    .fetch_all(pool)
+    .await
+}
+
+/// Number of active opted-in portfolios with holdings, and per-ticker portfolio
+/// counts for tickers held by at least `min_portfolios` of them
+pub async fn fetch_opted_in_holding_counts(
+    pool: &PgPool,
+    min_portfolios: i64,
+) -> Result<(i64, Vec<(String, i64)>), sqlx::Error> {
+    let total = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(DISTINCT a.portfolio_id)
+         FROM latest_account_holdings lah
+         JOIN accounts a ON lah.account_id = a.id
+         JOIN portfolios p ON p.id = a.portfolio_id
+         JOIN user_preferences up ON up.user_id = p.user_id
+         WHERE p.archived_at IS NULL AND up.share_anonymous_stats"
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let counts = sqlx::query_as::<_, (String, i64)>(
+        "SELECT lah.ticker, COUNT(DISTINCT a.portfolio_id) AS portfolio_count
+         FROM latest_account_holdings lah
+         JOIN accounts a ON lah.account_id = a.id
+         JOIN portfolios p ON p.id = a.portfolio_id
+         JOIN user_preferences up ON up.user_id = p.user_id
+         WHERE p.archived_at IS NULL
+           AND up.share_anonymous_stats
+           AND lah.ticker <> ''
+         GROUP BY lah.ticker
+         HAVING COUNT(DISTINCT a.portfolio_id) >= $1
+         ORDER BY portfolio_count DESC, lah.ticker"
+    )
+    .bind(min_portfolios)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((total, counts))
+}
+
+/// Replace all stored distributions with `distributions`
+pub async fn replace_distributions(
+    pool: &PgPool,
+    distributions: &[(String, i32, Vec<f64>)],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM peer_metric_distributions").execute(&mut *tx).await?;
+    for (metric, sample_size, percentiles) in distributions {
+        sqlx::query(
+            "INSERT INTO peer_metric_distributions (metric, sample_size, percentiles, computed_at)
+             VALUES ($1, $2, $3, NOW())"
+        )
+        .bind(metric)
+        .bind(sample_size)
+        .bind(percentiles)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Replace all stored common holdings with `holdings`
+pub async fn replace_common_holdings(pool: &PgPool, holdings: &[PeerCommonHolding]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM peer_common_holdings").execute(&mut *tx).await?;
+    for holding in holdings {
+        sqlx::query(
+            "INSERT INTO peer_common_holdings (ticker, portfolio_count, portfolio_share, computed_at)
+             VALUES ($1, $2, $3, NOW())"
+        )
+        .bind(&holding.ticker)
+        .bind(holding.portfolio_count)
+        .bind(holding.portfolio_share)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+pub async fn fetch_distributions(pool: &PgPool) -> Result<Vec<PeerMetricDistribution>, sqlx::Error> {
+    sqlx::query_as::<_, PeerMetricDistribution>(
+        "SELECT metric, sample_size, percentiles, computed_at FROM peer_metric_distributions ORDER BY metric"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn fetch_common_holdings(pool: &PgPool) -> Result<Vec<PeerCommonHolding>, sqlx::Error> {
+    sqlx::query_as::<_, PeerCommonHolding>(
+        "SELECT ticker, portfolio_count, portfolio_share
+         FROM peer_common_holdings
+         ORDER BY portfolio_count DESC, ticker"
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,121 @@
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::models::{PortfolioMember, Role};
+
+/// The owner (listed first, as admin) and every member of a portfolio
+const MEMBER_SELECT: &str = "
+    SELECT * FROM (
+        SELECT p.id AS portfolio_id, u.id AS user_id, u.email, u.name, 'admin' AS role,
+               TRUE AS is_owner, NULL::UUID AS invited_by, p.created_at
+        FROM portfolios p
+        JOIN users u ON u.id = p.user_id
+        UNION ALL
+        SELECT m.portfolio_id, u.id, u.email, u.name, m.role, FALSE, m.invited_by, m.created_at
+        FROM portfolio_members m
+        JOIN users u ON u.id = m.user_id
+    ) members";
+
+/// The role `user_id` holds on a portfolio, with the portfolio's owner: the owner
+/// is an admin, a member has their membership role, and anyone else gets `None`.
+pub async fn role_for<'e>(
+    executor: impl PgExecutor<'e>,
+    portfolio_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<(Role, Uuid)>, sqlx::Error> {
+    sqlx::query_as::<_, (Role, Uuid)>(
+        "SELECT CASE WHEN p.user_id = $2 THEN 'admin' ELSE m.role END, p.user_id
+         FROM portfolios p
+         LEFT JOIN portfolio_members m ON m.portfolio_id = p.id AND m.user_id = $2
+         WHERE p.id = $1 AND (p.user_id = $2 OR m.user_id IS NOT NULL)"
+    )
+    .bind(portfolio_id)
+    .bind(user_id)
+    .fetch_optional(executor)
+    .await
+}
+
+/// The portfolio an account belongs to
+pub async fn portfolio_for_account<'e>(executor: impl PgExecutor<'e>, account_id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>("SELECT portfolio_id FROM accounts WHERE id = $1")
+        .bind(account_id)
+        .fetch_optional(executor)
+        .await
+}
+
+/// The portfolio a detected transaction belongs to, through its account
+pub async fn portfolio_for_transaction<'e>(executor: impl PgExecutor<'e>, transaction_id: Uuid) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>(
+        "SELECT a.portfolio_id FROM detected_transactions t JOIN accounts a ON a.id = t.account_id WHERE t.id = $1"
+    )
+    .bind(transaction_id)
+    .fetch_optional(executor)
+    .await
+}
+
+pub async fn fetch_all(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<PortfolioMember>, sqlx::Error> {
+    sqlx::query_as::<_, PortfolioMember>(&format!(
+        "{} WHERE portfolio_id = $1 ORDER BY is_owner DESC, created_at",
+        MEMBER_SELECT
+    ))
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn fetch_one(pool: &PgPool, portfolio_id: Uuid, user_id: Uuid) -> Result<Option<PortfolioMember>, sqlx::Error> {
+    sqlx::query_as::<_, PortfolioMember>(&format!(
+        "{} WHERE portfolio_id = $1 AND user_id = $2",
+        MEMBER_SELECT
+    ))
+    .bind(portfolio_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Share a portfolio with a user, or change the role of an existing member
+pub async fn upsert(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    user_id: Uuid,
+    role: Role,
+    invited_by: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO portfolio_members (portfolio_id, user_id, role, invited_by)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (portfolio_id, user_id) DO UPDATE SET
+            role = EXCLUDED.role,
+            updated_at = NOW()"
+    )
+    .bind(portfolio_id)
+    .bind(user_id)
+    .bind(role)
+    .bind(invited_by)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_role(pool: &PgPool, portfolio_id: Uuid, user_id: Uuid, role: Role) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE portfolio_members SET role = $3, updated_at = NOW()
+         WHERE portfolio_id = $1 AND user_id = $2"
+    )
+    .bind(portfolio_id)
+    .bind(user_id)
+    .bind(role)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete(pool: &PgPool, portfolio_id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM portfolio_members WHERE portfolio_id = $1 AND user_id = $2")
+        .bind(portfolio_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
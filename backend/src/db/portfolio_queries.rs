@@ -2,23 +2,31 @@ use sqlx::PgPool;
 use uuid::Uuid;
 use crate::models::{Portfolio, UpdatePortfolio};
 
-pub async fn fetch_all(pool: &PgPool, user_id: Uuid) -> Result<Vec<Portfolio>, sqlx::Error> {
+/// Portfolios the user owns or that are shared with them
+pub async fn fetch_all(pool: &PgPool, user_id: Uuid, include_archived: bool) -> Result<Vec<Portfolio>, sqlx::Error> {
     sqlx::query_as::<_, Portfolio>(
-        "SELECT id, name, user_id, created_at
+        "SELECT id, name, user_id, created_at, archived_at, cloned_from
          FROM portfolios
-         WHERE user_id = $1
+         WHERE (user_id = $1 OR id IN (SELECT portfolio_id FROM portfolio_members WHERE user_id = $1))
+           AND ($2 OR archived_at IS NULL)
          ORDER BY created_at DESC",
     )
     .bind(user_id)
+    .bind(include_archived)
     .fetch_all(pool)
     .await
 }
 
+/// A portfolio the user owns or that is shared with them, with any role.
+///
+/// This is a view check only: viewers pass it too. Handlers that change a
+/// portfolio or its derived state take `PortfolioAccess<CanEdit>` instead.
 pub async fn fetch_one(pool: &PgPool, id: Uuid, user_id: Uuid) -> Result<Option<Portfolio>, sqlx::Error> {
     sqlx::query_as::<_, Portfolio>(
-        "SELECT id, name, user_id, created_at
+        "SELECT id, name, user_id, created_at, archived_at, cloned_from
          FROM portfolios
-         WHERE id = $1 AND user_id = $2",
+         WHERE id = $1
+           AND (user_id = $2 OR id IN (SELECT portfolio_id FROM portfolio_members WHERE user_id = $2))",
     )
     .bind(id)
     .bind(user_id)
@@ -28,14 +36,15 @@ pub async fn fetch_one(pool: &PgPool, id: Uuid, user_id: Uuid) -> Result<Option<
 
 pub async fn insert(pool: &PgPool, input: Portfolio) -> Result<Portfolio, sqlx::Error> {
     sqlx::query_as::<_, Portfolio>(
-        "INSERT INTO portfolios (id, name, user_id, created_at)
-         VALUES ($1, $2, $3, $4)
-         RETURNING id, name, user_id, created_at",
+        "INSERT INTO portfolios (id, name, user_id, created_at, cloned_from)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, name, user_id, created_at, archived_at, cloned_from",
     )
     .bind(input.id)
     .bind(input.name)
     .bind(input.user_id)
     .bind(input.created_at)
+    .bind(input.cloned_from)
     .fetch_one(pool)
     .await
 }
@@ -43,7 +52,7 @@ pub async fn insert(pool: &PgPool, input: Portfolio) -> Result<Portfolio, sqlx::
 pub async fn update(pool: &PgPool, id: Uuid, user_id: Uuid, input: UpdatePortfolio) -> Result<Option<Portfolio>, sqlx::Error> {
     sqlx::query_as::<_, Portfolio>(
         "UPDATE portfolios SET name = $1 WHERE id = $2 AND user_id = $3
-         RETURNING id, name, user_id, created_at",
+         RETURNING id, name, user_id, created_at, archived_at, cloned_from",
     )
     .bind(input.name)
     .bind(id)
@@ -52,10 +61,122 @@ pub async fn update(pool: &PgPool, id: Uuid, user_id: Uuid, input: UpdatePortfol
     .await
 }
 
+/// Archive or unarchive a portfolio. Archiving keeps the first archive timestamp.
+pub async fn set_archived(pool: &PgPool, id: Uuid, user_id: Uuid, archived: bool) -> Result<Option<Portfolio>, sqlx::Error> {
+    sqlx::query_as::<_, Portfolio>(
+        "UPDATE portfolios
+         SET archived_at = CASE WHEN $1 THEN COALESCE(archived_at, NOW()) ELSE NULL END
+         WHERE id = $2 AND user_id = $3
+         RETURNING id, name, user_id, created_at, archived_at, cloned_from",
+    )
+    .bind(archived)
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Copy a portfolio into `target`: its accounts, the latest holdings snapshot of each
+/// account, legacy positions, and risk threshold settings. Runs in one transaction.
+///
+/// Returns the number of holdings copied.
+pub async fn clone_contents(pool: &PgPool, source_id: Uuid, target: Portfolio) -> Result<(Portfolio, u64), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let portfolio = sqlx::query_as::<_, Portfolio>(
+        "INSERT INTO portfolios (id, name, user_id, created_at, cloned_from)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, name, user_id, created_at, archived_at, cloned_from",
+    )
+    .bind(target.id)
+    .bind(&target.name)
+    .bind(target.user_id)
+    .bind(target.created_at)
+    .bind(source_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // Map each source account to a fresh id so snapshots can follow
+    sqlx::query(
+        "CREATE TEMP TABLE clone_account_map ON COMMIT DROP AS
+         SELECT id AS old_id, gen_random_uuid() AS new_id
+         FROM accounts WHERE portfolio_id = $1",
+    )
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO accounts (id, portfolio_id, account_number, account_nickname, client_id, client_name,
+                               total_deposits, total_withdrawals, drip_enabled, fractional_shares, tax_treatment,
+                               cost_basis_method)
+         SELECT m.new_id, $1, a.account_number, a.account_nickname, a.client_id, a.client_name,
+                a.total_deposits, a.total_withdrawals, a.drip_enabled, a.fractional_shares, a.tax_treatment,
+                a.cost_basis_method
+         FROM accounts a
+         JOIN clone_account_map m ON m.old_id = a.id",
+    )
+    .bind(portfolio.id)
+    .execute(&mut *tx)
+    .await?;
+
+    let holdings = sqlx::query(
+        "INSERT INTO holdings_snapshots
+         (id, account_id, snapshot_date, ticker, holding_name, asset_category, industry,
+          quantity, price, average_cost, book_value, market_value, fund,
+          accrued_interest, gain_loss, gain_loss_pct, percentage_of_assets)
+         SELECT gen_random_uuid(), m.new_id, h.snapshot_date, h.ticker, h.holding_name, h.asset_category,
+                h.industry, h.quantity, h.price, h.average_cost, h.book_value, h.market_value, h.fund,
+                h.accrued_interest, h.gain_loss, h.gain_loss_pct, h.percentage_of_assets
+         FROM holdings_snapshots h
+         JOIN clone_account_map m ON m.old_id = h.account_id
+         WHERE h.snapshot_date = (
+             SELECT MAX(h2.snapshot_date) FROM holdings_snapshots h2 WHERE h2.account_id = h.account_id
+         )",
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    sqlx::query(
+        "INSERT INTO positions (id, portfolio_id, ticker, shares, avg_buy_price, account_id, notes)
+         SELECT gen_random_uuid(), $1, p.ticker, p.shares, p.avg_buy_price, m.new_id, p.notes
+         FROM positions p
+         LEFT JOIN clone_account_map m ON m.old_id = p.account_id
+         WHERE p.portfolio_id = $2",
+    )
+    .bind(portfolio.id)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO risk_threshold_settings
+         (portfolio_id, volatility_warning_threshold, volatility_critical_threshold,
+          drawdown_warning_threshold, drawdown_critical_threshold,
+          beta_warning_threshold, beta_critical_threshold,
+          risk_score_warning_threshold, risk_score_critical_threshold,
+          var_warning_threshold, var_critical_threshold)
+         SELECT $1, volatility_warning_threshold, volatility_critical_threshold,
+                drawdown_warning_threshold, drawdown_critical_threshold,
+                beta_warning_threshold, beta_critical_threshold,
+                risk_score_warning_threshold, risk_score_critical_threshold,
+                var_warning_threshold, var_critical_threshold
+         FROM risk_threshold_settings WHERE portfolio_id = $2",
+    )
+    .bind(portfolio.id)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok((portfolio, holdings))
+}
+
 /// Fetch a portfolio by ID without an ownership check — for internal services only.
 pub async fn fetch_one_unchecked(pool: &PgPool, id: Uuid) -> Result<Option<Portfolio>, sqlx::Error> {
     sqlx::query_as::<_, Portfolio>(
-        "SELECT id, name, user_id, created_at FROM portfolios WHERE id = $1",
+        "SELECT id, name, user_id, created_at, archived_at, cloned_from FROM portfolios WHERE id = $1",
     )
     .bind(id)
     .fetch_optional(pool)
@@ -0,0 +1,53 @@
+use chrono::NaiveDate;
+use sqlx::PgExecutor;
+
+/// Value every active account on `date` from its latest imported holdings and
+/// the latest close on or before `date`. Holdings with no price since their
+/// import keep their imported market value. Accounts with an import on `date`,
+/// or none before it, are skipped. Re-valuing a date replaces its row.
+///
+/// Returns the number of accounts valued.
+pub async fn record_valuations<'e>(executor: impl PgExecutor<'e>, date: NaiveDate) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO portfolio_value_history (
+            account_id, portfolio_id, snapshot_date, total_value, total_cost,
+            holdings_date, priced_holdings, unpriced_holdings
+         )
+         SELECT
+            a.id,
+            a.portfolio_id,
+            $1,
+            SUM(COALESCE(hs.quantity * px.close_price, hs.market_value)),
+            SUM(hs.book_value),
+            latest.snapshot_date,
+            COUNT(px.close_price),
+            COUNT(*) - COUNT(px.close_price)
+         FROM accounts a
+         JOIN portfolios p ON p.id = a.portfolio_id AND p.archived_at IS NULL
+         JOIN LATERAL (
+            SELECT MAX(snapshot_date) AS snapshot_date
+            FROM holdings_snapshots
+            WHERE account_id = a.id AND snapshot_date <= $1
+         ) latest ON latest.snapshot_date < $1
+         JOIN holdings_snapshots hs ON hs.account_id = a.id AND hs.snapshot_date = latest.snapshot_date
+         LEFT JOIN LATERAL (
+            SELECT pp.close_price
+            FROM price_points pp
+            WHERE pp.ticker = hs.ticker AND pp.date <= $1 AND pp.date >= latest.snapshot_date
+            ORDER BY pp.date DESC
+            LIMIT 1
+         ) px ON TRUE
+         GROUP BY a.id, a.portfolio_id, latest.snapshot_date
+         ON CONFLICT (account_id, snapshot_date) DO UPDATE SET
+            total_value = EXCLUDED.total_value,
+            total_cost = EXCLUDED.total_cost,
+            holdings_date = EXCLUDED.holdings_date,
+            priced_holdings = EXCLUDED.priced_holdings,
+            unpriced_holdings = EXCLUDED.unpriced_holdings,
+            updated_at = NOW()"
+    )
+    .bind(date)
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -1,8 +1,9 @@
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 use tracing::error;
-use crate::models::PricePoint;
+use crate::models::{FiftyTwoWeekRange, PricePoint};
 use crate::external::price_provider::ExternalPricePoint;
+use crate::services::fx_service;
 
 #[allow(dead_code)]
 pub async fn insert_many(
@@ -63,6 +64,25 @@ pub async fn fetch_latest(
         .await
 }
 
+/// The most recent close on or before `date`
+pub async fn fetch_close_on_or_before<'e>(
+    executor: impl PgExecutor<'e>,
+    ticker: &str,
+    date: chrono::NaiveDate,
+) -> Result<Option<PricePoint>, sqlx::Error> {
+    sqlx::query_as::<_, PricePoint>(
+        "SELECT id, ticker, date, close_price, created_at
+         FROM price_points
+         WHERE ticker = $1 AND date <= $2
+         ORDER BY date DESC
+         LIMIT 1",
+    )
+    .bind(ticker)
+    .bind(date)
+    .fetch_optional(executor)
+    .await
+}
+
 pub async fn fetch_latest_batch(
     pool: &PgPool,
     tickers: &[String],
@@ -92,6 +112,79 @@ pub async fn fetch_latest_batch(
     Ok(map)
 }
 
+/// Latest close and the one before it per ticker, for day-over-day changes.
+/// Tickers with a single stored close map to one point.
+pub async fn fetch_last_two_batch(
+    pool: &PgPool,
+    tickers: &[String],
+) -> Result<std::collections::HashMap<String, Vec<PricePoint>>, sqlx::Error> {
+    if tickers.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+
+    let prices = sqlx::query_as::<_, PricePoint>(
+        r#"
+        SELECT id, ticker, date, close_price, created_at
+        FROM (
+            SELECT *, ROW_NUMBER() OVER (PARTITION BY ticker ORDER BY date DESC) AS rn
+            FROM price_points
+            WHERE ticker = ANY($1)
+        ) ranked
+        WHERE rn <= 2
+        ORDER BY ticker, date ASC
+        "#,
+    )
+    .bind(tickers)
+    .fetch_all(pool)
+    .await?;
+
+    let mut map: std::collections::HashMap<String, Vec<PricePoint>> = std::collections::HashMap::new();
+    for p in prices {
+        map.entry(p.ticker.clone()).or_default().push(p);
+    }
+
+    Ok(map)
+}
+
+/// 52-week high/low and latest close per ticker, over the year ending at each
+/// ticker's latest stored date
+pub async fn fetch_52_week_range_batch(
+    pool: &PgPool,
+    tickers: &[String],
+) -> Result<std::collections::HashMap<String, FiftyTwoWeekRange>, sqlx::Error> {
+    if tickers.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+
+    let rows = sqlx::query_as::<_, (String, f64, f64, f64)>(
+        r#"
+        WITH latest AS (
+            SELECT DISTINCT ON (ticker) ticker, date, close_price
+            FROM price_points
+            WHERE ticker = ANY($1)
+            ORDER BY ticker, date DESC
+        )
+        SELECT l.ticker,
+               MAX(p.close_price)::DOUBLE PRECISION,
+               MIN(p.close_price)::DOUBLE PRECISION,
+               l.close_price::DOUBLE PRECISION
+        FROM latest l
+        JOIN price_points p ON p.ticker = l.ticker AND p.date > l.date - 365
+        GROUP BY l.ticker, l.close_price
+        "#,
+    )
+    .bind(tickers)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(ticker, high, low, current)| {
+            FiftyTwoWeekRange::new(high, low, current).map(|range| (ticker, range))
+        })
+        .collect())
+}
+
 pub async fn upsert_external_points(
     pool: &PgPool,
     ticker: &str,
@@ -102,19 +195,54 @@ pub async fn upsert_external_points(
         e
     })?;
 
+    // Adjusted closes are relative to the fetch date. When a split or dividend
+    // happened since the last fetch, the overlapping day's adjusted close moves;
+    // rescale the stored ones older than this fetch by the same ratio so the
+    // adjusted series stays continuous.
+    if let Some((date, adjusted)) = points.iter().find_map(|p| p.adjusted_close.as_ref().map(|a| (p.date, a))) {
+        sqlx::query(
+            r#"
+            UPDATE price_points pp
+            SET adjusted_close = pp.adjusted_close * ($3 / anchor.adjusted_close)
+            FROM (
+                SELECT adjusted_close FROM price_points
+                WHERE ticker = $1 AND date = $2 AND adjusted_close IS NOT NULL AND adjusted_close <> 0
+            ) anchor
+            WHERE pp.ticker = $1 AND pp.date < $2 AND pp.adjusted_close IS NOT NULL
+              AND anchor.adjusted_close <> $3
+            "#,
+        )
+        .bind(ticker)
+        .bind(date)
+        .bind(adjusted)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to rescale adjusted closes for ticker {} before {}: {}", ticker, date, e);
+            e
+        })?;
+    }
+
+    // Providers quote in the listing's currency
+    let currency = fx_service::currency_for_ticker(ticker);
     for (i, p) in points.iter().enumerate() {
-        if let Err(e) = sqlx::query!(
+        // A provider without adjusted closes keeps the stored adjusted close
+        if let Err(e) = sqlx::query(
             r#"
-            INSERT INTO price_points (id, ticker, date, close_price)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO price_points (id, ticker, date, close_price, adjusted_close, currency)
+            VALUES ($1, $2, $3, $4, $5, $6)
             ON CONFLICT (ticker, date)
-            DO UPDATE SET close_price = EXCLUDED.close_price
+            DO UPDATE SET close_price = EXCLUDED.close_price,
+                          adjusted_close = COALESCE(EXCLUDED.adjusted_close, price_points.adjusted_close),
+                          currency = EXCLUDED.currency
             "#,
-            Uuid::new_v4(),
-            ticker,
-            p.date,
-            p.close
         )
+            .bind(Uuid::new_v4())
+            .bind(ticker)
+            .bind(p.date)
+            .bind(&p.close)
+            .bind(&p.adjusted_close)
+            .bind(currency)
             .execute(&mut *tx)
             .await {
             error!("Failed to upsert price point {} for ticker {} (date: {}, price: {}): {}", 
@@ -159,6 +287,102 @@ pub async fn fetch_window(
     })
 }
 
+/// Closes of a ticker dated `from` through `to` inclusive, oldest first
+pub async fn fetch_range(
+    pool: &PgPool,
+    ticker: &str,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> Result<Vec<PricePoint>, sqlx::Error> {
+    sqlx::query_as::<_, PricePoint>(
+        "SELECT id, ticker, date, close_price, created_at
+         FROM price_points
+         WHERE ticker = $1 AND date BETWEEN $2 AND $3
+         ORDER BY date",
+    )
+    .bind(ticker)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
+
+/// Like `fetch_window`, but with `close_price` set to the split- and
+/// dividend-adjusted close where one is stored. Use for return-based analytics.
+pub async fn fetch_adjusted_window(
+    pool: &PgPool,
+    ticker: &str,
+    days: i64,
+) -> Result<Vec<PricePoint>, sqlx::Error> {
+    let mut points = sqlx::query_as::<_, PricePoint>(
+        r#"
+        SELECT id, ticker, date, COALESCE(adjusted_close, close_price) AS close_price, created_at
+        FROM price_points
+        WHERE ticker = $1
+        ORDER BY date DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(ticker)
+    .bind(days)
+    .fetch_all(pool)
+    .await?;
+    points.reverse();
+    Ok(points)
+}
+
+/// Like `fetch_range`, with the split- and dividend-adjusted close where one is stored
+pub async fn fetch_adjusted_range(
+    pool: &PgPool,
+    ticker: &str,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> Result<Vec<PricePoint>, sqlx::Error> {
+    sqlx::query_as::<_, PricePoint>(
+        "SELECT id, ticker, date, COALESCE(adjusted_close, close_price) AS close_price, created_at
+         FROM price_points
+         WHERE ticker = $1 AND date BETWEEN $2 AND $3
+         ORDER BY date",
+    )
+    .bind(ticker)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
+
+/// Tickers with at least one close on or after `since`
+pub async fn fetch_tickers_with_prices_since(pool: &PgPool, since: chrono::NaiveDate) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT DISTINCT ticker FROM price_points WHERE date >= $1 ORDER BY ticker"
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
+/// When the most recent new close was stored, from any provider
+pub async fn fetch_last_stored_at(pool: &PgPool) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<chrono::DateTime<chrono::Utc>>>("SELECT MAX(created_at) FROM price_points")
+        .fetch_one(pool)
+        .await
+}
+
+/// Those of `tickers` with a close first stored after `since`
+pub async fn fetch_tickers_added_since(
+    pool: &PgPool,
+    tickers: &[String],
+    since: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT DISTINCT ticker FROM price_points WHERE ticker = ANY($1) AND created_at > $2 ORDER BY ticker"
+    )
+    .bind(tickers)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
 /// Fetch the most recent N days of price history for multiple tickers in one query.
 ///
 /// Returns a map of ticker -> price points ordered by date ascending (oldest first).
@@ -167,40 +391,194 @@ pub async fn fetch_window_batch(
     tickers: &[String],
     days: i64,
 ) -> Result<std::collections::HashMap<String, Vec<PricePoint>>, sqlx::Error> {
-    use std::collections::HashMap;
+    if tickers.is_empty() {
+        return Ok(std::collections::HashMap::new());
+    }
+
+    // Per-ticker LIMIT so only the newest `days` rows of each ticker are read,
+    // served by idx_price_points_ticker_date_desc as an index-only scan
+    let points = sqlx::query_as::<_, PricePoint>(
+        r#"
+        SELECT p.id, p.ticker, p.date, p.close_price, p.created_at
+        FROM (SELECT DISTINCT UNNEST($1::text[])) AS t(ticker)
+        CROSS JOIN LATERAL (
+            SELECT id, ticker, date, close_price, created_at
+            FROM price_points
+            WHERE ticker = t.ticker
+            ORDER BY date DESC
+            LIMIT $2
+        ) p
+        ORDER BY p.ticker, p.date
+        "#,
+    )
+    .bind(tickers)
+    .bind(days.max(0))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(group_by_ticker(points))
+}
 
+/// Like `fetch_window_batch`, with the split- and dividend-adjusted close where one is stored
+pub async fn fetch_adjusted_window_batch(
+    pool: &PgPool,
+    tickers: &[String],
+    days: i64,
+) -> Result<std::collections::HashMap<String, Vec<PricePoint>>, sqlx::Error> {
     if tickers.is_empty() {
-        return Ok(HashMap::new());
+        return Ok(std::collections::HashMap::new());
     }
 
-    // Use query_as instead of query_as! to avoid compile-time verification issues with arrays
     let points = sqlx::query_as::<_, PricePoint>(
         r#"
-        SELECT id, ticker, date, close_price, created_at
-        FROM price_points
-        WHERE ticker = ANY($1)
-        ORDER BY ticker, date DESC
+        SELECT p.id, p.ticker, p.date, p.close_price, p.created_at
+        FROM (SELECT DISTINCT UNNEST($1::text[])) AS t(ticker)
+        CROSS JOIN LATERAL (
+            SELECT id, ticker, date, COALESCE(adjusted_close, close_price) AS close_price, created_at
+            FROM price_points
+            WHERE ticker = t.ticker
+            ORDER BY date DESC
+            LIMIT $2
+        ) p
+        ORDER BY p.ticker, p.date
         "#,
     )
     .bind(tickers)
+    .bind(days.max(0))
     .fetch_all(pool)
     .await?;
 
-    // Group by ticker and take only the most recent N days for each
-    let mut result: HashMap<String, Vec<PricePoint>> = HashMap::new();
+    Ok(group_by_ticker(points))
+}
 
+/// Rows arrive oldest first within each ticker; keep that order per ticker
+fn group_by_ticker(points: Vec<PricePoint>) -> std::collections::HashMap<String, Vec<PricePoint>> {
+    let mut result: std::collections::HashMap<String, Vec<PricePoint>> = std::collections::HashMap::new();
     for point in points {
-        result
-            .entry(point.ticker.clone())
-            .or_insert_with(Vec::new)
-            .push(point);
+        result.entry(point.ticker.clone()).or_default().push(point);
     }
+    result
+}
 
-    // Limit each ticker to N days and reverse to ascending order
-    for (_, points) in result.iter_mut() {
-        points.truncate(days as usize);
-        points.reverse();
+/// Number of stored price points per ticker on or after `since`. Tickers without
+/// any points are absent from the map.
+pub async fn fetch_point_counts(
+    pool: &PgPool,
+    tickers: &[String],
+    since: chrono::NaiveDate,
+) -> Result<std::collections::HashMap<String, i64>, sqlx::Error> {
+    if tickers.is_empty() {
+        return Ok(std::collections::HashMap::new());
     }
 
-    Ok(result)
-}
\ No newline at end of file
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT ticker, COUNT(*)
+        FROM price_points
+        WHERE ticker = ANY($1) AND date >= $2
+        GROUP BY ticker
+        "#,
+    )
+    .bind(tickers)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Tickers held in active portfolios or followed on a watchlist
+pub async fn fetch_tracked_tickers(pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT lah.ticker
+        FROM latest_account_holdings lah
+        JOIN accounts a ON lah.account_id = a.id
+        JOIN portfolios p ON a.portfolio_id = p.id
+        WHERE p.archived_at IS NULL AND lah.quantity > 0 AND lah.ticker != ''
+        UNION
+        SELECT UPPER(symbol) FROM watchlist_items
+        ORDER BY 1
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Earliest stored date and number of stored closes per ticker. Tickers
+/// without any closes are absent from the map.
+pub async fn fetch_history_coverage(
+    pool: &PgPool,
+    tickers: &[String],
+) -> Result<std::collections::HashMap<String, (chrono::NaiveDate, i64)>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, chrono::NaiveDate, i64)>(
+        "SELECT ticker, MIN(date), COUNT(*) FROM price_points WHERE ticker = ANY($1) GROUP BY ticker",
+    )
+    .bind(tickers)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|(ticker, earliest, points)| (ticker, (earliest, points))).collect())
+}
+
+/// Recorded start of the provider's history per ticker
+pub async fn fetch_history_starts(
+    pool: &PgPool,
+    tickers: &[String],
+) -> Result<std::collections::HashMap<String, chrono::NaiveDate>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, chrono::NaiveDate)>(
+        "SELECT ticker, history_starts FROM price_history_starts WHERE ticker = ANY($1)",
+    )
+    .bind(tickers)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+pub async fn upsert_history_start(pool: &PgPool, ticker: &str, history_starts: chrono::NaiveDate) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO price_history_starts (ticker, history_starts, checked_at)
+         VALUES ($1, $2, NOW())
+         ON CONFLICT (ticker) DO UPDATE SET history_starts = EXCLUDED.history_starts, checked_at = EXCLUDED.checked_at",
+    )
+    .bind(ticker)
+    .bind(history_starts)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Closes stored for any spelling of a ticker (the unique constraint only
+/// covers the exact symbol), oldest first
+pub async fn fetch_closes_any_case(
+    pool: &PgPool,
+    ticker: &str,
+) -> Result<Vec<(String, chrono::NaiveDate, f64)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, chrono::NaiveDate, f64)>(
+        "SELECT ticker, date, close_price::float8
+         FROM price_points
+         WHERE UPPER(ticker) = UPPER($1)
+         ORDER BY date, ticker",
+    )
+    .bind(ticker)
+    .fetch_all(pool)
+    .await
+}
+
+/// Dates with a stored close for `ticker` from `from` through `to`
+pub async fn fetch_dates(
+    pool: &PgPool,
+    ticker: &str,
+    from: chrono::NaiveDate,
+    to: chrono::NaiveDate,
+) -> Result<Vec<chrono::NaiveDate>, sqlx::Error> {
+    sqlx::query_scalar::<_, chrono::NaiveDate>(
+        "SELECT date FROM price_points WHERE ticker = $1 AND date BETWEEN $2 AND $3 ORDER BY date",
+    )
+    .bind(ticker)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
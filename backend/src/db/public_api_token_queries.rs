@@ -0,0 +1,53 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::PublicApiToken;
+
+pub async fn create(pool: &PgPool, user_id: Uuid, name: &str, token_hash: &str) -> Result<PublicApiToken, sqlx::Error> {
+    sqlx::query_as::<_, PublicApiToken>(
+        "INSERT INTO public_api_tokens (user_id, name, token_hash)
+         VALUES ($1, $2, $3)
+         RETURNING id, name, created_at, last_used_at"
+    )
+    .bind(user_id)
+    .bind(name)
+    .bind(token_hash)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn fetch_for_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<PublicApiToken>, sqlx::Error> {
+    sqlx::query_as::<_, PublicApiToken>(
+        "SELECT id, name, created_at, last_used_at
+         FROM public_api_tokens
+         WHERE user_id = $1
+         ORDER BY created_at DESC"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete(pool: &PgPool, id: Uuid, user_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM public_api_tokens WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// The user a token belongs to, recording its use. `None` when the token is
+/// unknown or its user has been disabled.
+pub async fn authenticate(pool: &PgPool, token_hash: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>(
+        "UPDATE public_api_tokens t
+         SET last_used_at = NOW()
+         FROM users u
+         WHERE t.token_hash = $1 AND u.id = t.user_id AND u.disabled_at IS NULL
+         RETURNING t.user_id"
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
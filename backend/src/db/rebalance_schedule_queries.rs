@@ -0,0 +1,44 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::RebalanceSchedule;
+
+pub async fn fetch(pool: &PgPool, portfolio_id: Uuid) -> Result<Option<RebalanceSchedule>, sqlx::Error> {
+    sqlx::query_as::<_, RebalanceSchedule>(
+        "SELECT frequency, start_date
+         FROM portfolio_rebalance_schedules
+         WHERE portfolio_id = $1"
+    )
+    .bind(portfolio_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn upsert(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    schedule: &RebalanceSchedule,
+) -> Result<RebalanceSchedule, sqlx::Error> {
+    sqlx::query_as::<_, RebalanceSchedule>(
+        "INSERT INTO portfolio_rebalance_schedules (portfolio_id, frequency, start_date)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (portfolio_id) DO UPDATE SET
+            frequency = EXCLUDED.frequency,
+            start_date = EXCLUDED.start_date,
+            updated_at = NOW()
+         RETURNING frequency, start_date"
+    )
+    .bind(portfolio_id)
+    .bind(schedule.frequency)
+    .bind(schedule.start_date)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete(pool: &PgPool, portfolio_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM portfolio_rebalance_schedules WHERE portfolio_id = $1")
+        .bind(portfolio_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
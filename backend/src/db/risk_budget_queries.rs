@@ -0,0 +1,49 @@
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{RiskBudget, SleeveBudget};
+
+pub async fn fetch(pool: &PgPool, portfolio_id: Uuid) -> Result<Option<RiskBudget>, sqlx::Error> {
+    sqlx::query_as::<_, RiskBudget>(
+        "SELECT portfolio_id, metric, total_budget, sleeves, created_at, updated_at
+         FROM portfolio_risk_budgets
+         WHERE portfolio_id = $1"
+    )
+    .bind(portfolio_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn upsert(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    metric: &str,
+    total_budget: f64,
+    sleeves: &[SleeveBudget],
+) -> Result<RiskBudget, sqlx::Error> {
+    sqlx::query_as::<_, RiskBudget>(
+        "INSERT INTO portfolio_risk_budgets (portfolio_id, metric, total_budget, sleeves)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (portfolio_id) DO UPDATE SET
+            metric = EXCLUDED.metric,
+            total_budget = EXCLUDED.total_budget,
+            sleeves = EXCLUDED.sleeves,
+            updated_at = NOW()
+         RETURNING portfolio_id, metric, total_budget, sleeves, created_at, updated_at"
+    )
+    .bind(portfolio_id)
+    .bind(metric)
+    .bind(total_budget)
+    .bind(Json(sleeves))
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete(pool: &PgPool, portfolio_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM portfolio_risk_budgets WHERE portfolio_id = $1")
+        .bind(portfolio_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,344 @@
+//! Typed queries for the portfolio risk, correlation, narrative, rolling beta
+//! and downside risk caches.
+//!
+//! Per-portfolio entries are read and written through a [`TenantScope`], and
+//! every statement filters on the owner's `user_id` as well as the portfolio.
+//!
+//! These are on the hot path of the risk endpoints and their background jobs, so
+//! every statement is checked against the schema at compile time. After changing
+//! one, run `cargo sqlx prepare` to refresh the offline metadata in `.sqlx/`.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::PgPool;
+
+use crate::db::tenant::TenantScope;
+use crate::models::risk::{BetaPoint, CorrelationMatrixWithStats, PortfolioDownsideRisk};
+
+/// A portfolio_risk_cache row. `risk_data` stays untyped because 'calculating'
+/// and 'error' placeholder rows hold `{}`.
+#[derive(Debug, Clone)]
+pub struct RiskCacheEntry {
+    pub risk_data: Value,
+    pub calculation_status: String,
+    pub expires_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub scoring_version: i32,
+}
+
+/// A rolling_beta_cache row
+#[derive(Debug)]
+pub struct RollingBetaCacheEntry {
+    pub beta_30d: Json<Vec<BetaPoint>>,
+    pub beta_60d: Json<Vec<BetaPoint>>,
+    pub beta_90d: Json<Vec<BetaPoint>>,
+    pub current_beta: f64,
+    pub beta_volatility: f64,
+    pub calculated_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+/// A downside_risk_cache row
+#[derive(Debug)]
+pub struct DownsideRiskCacheEntry {
+    pub risk_data: Json<PortfolioDownsideRisk>,
+    pub calculated_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Cached portfolio risk, whatever its status; a missing status reads as 'stale'
+pub async fn fetch_risk_cache(
+    pool: &PgPool,
+    tenant: &TenantScope,
+    days: i32,
+    benchmark: &str,
+) -> Result<Option<RiskCacheEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        RiskCacheEntry,
+        r#"
+        SELECT risk_data,
+               COALESCE(calculation_status, 'stale') AS "calculation_status!",
+               expires_at,
+               last_error,
+               scoring_version
+        FROM portfolio_risk_cache
+        WHERE portfolio_id = $1 AND days = $2 AND benchmark = $3 AND user_id = $4
+        "#,
+        tenant.portfolio_id(),
+        days,
+        benchmark,
+        tenant.user_id(),
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Store freshly calculated risk, clearing any previous error
+pub async fn store_risk_cache(
+    pool: &PgPool,
+    tenant: &TenantScope,
+    days: i32,
+    benchmark: &str,
+    risk_data: &Value,
+    expires_at: DateTime<Utc>,
+    scoring_version: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO portfolio_risk_cache (
+            portfolio_id, days, benchmark, risk_data, calculated_at, expires_at,
+            calculation_status, last_error, retry_count, scoring_version, user_id
+        )
+        VALUES ($1, $2, $3, $4, NOW(), $5, 'fresh', NULL, 0, $6, $7)
+        ON CONFLICT (portfolio_id, days, benchmark)
+        DO UPDATE SET
+            risk_data = EXCLUDED.risk_data,
+            calculated_at = EXCLUDED.calculated_at,
+            expires_at = EXCLUDED.expires_at,
+            calculation_status = 'fresh',
+            last_error = NULL,
+            retry_count = 0,
+            scoring_version = EXCLUDED.scoring_version,
+            updated_at = NOW()
+        "#,
+        tenant.portfolio_id(),
+        days,
+        benchmark,
+        risk_data,
+        expires_at,
+        scoring_version,
+        tenant.user_id(),
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Mark a risk cache entry as being calculated, creating a placeholder if needed
+pub async fn mark_risk_cache_calculating(
+    pool: &PgPool,
+    tenant: &TenantScope,
+    days: i32,
+    benchmark: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO portfolio_risk_cache (portfolio_id, days, benchmark, risk_data, calculated_at, expires_at, calculation_status, user_id)
+        VALUES ($1, $2, $3, '{}'::jsonb, NOW(), NOW() + INTERVAL '4 hours', 'calculating', $4)
+        ON CONFLICT (portfolio_id, days, benchmark)
+        DO UPDATE SET
+            calculation_status = 'calculating',
+            updated_at = NOW()
+        "#,
+        tenant.portfolio_id(),
+        days,
+        benchmark,
+        tenant.user_id(),
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a failed risk calculation and bump the retry count
+pub async fn mark_risk_cache_error(
+    pool: &PgPool,
+    tenant: &TenantScope,
+    days: i32,
+    benchmark: &str,
+    error_message: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO portfolio_risk_cache (
+            portfolio_id, days, benchmark, risk_data, calculated_at, expires_at,
+            calculation_status, last_error, retry_count, user_id
+        )
+        VALUES ($1, $2, $3, '{}'::jsonb, NOW(), NOW() + INTERVAL '1 hour', 'error', $4, 1, $5)
+        ON CONFLICT (portfolio_id, days, benchmark)
+        DO UPDATE SET
+            calculation_status = 'error',
+            last_error = EXCLUDED.last_error,
+            retry_count = COALESCE(portfolio_risk_cache.retry_count, 0) + 1,
+            updated_at = NOW()
+        "#,
+        tenant.portfolio_id(),
+        days,
+        benchmark,
+        error_message,
+        tenant.user_id(),
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Cached correlation matrix, only if fresh and unexpired
+pub async fn fetch_fresh_correlations(
+    pool: &PgPool,
+    tenant: &TenantScope,
+    days: i32,
+) -> Result<Option<CorrelationMatrixWithStats>, sqlx::Error> {
+    let row = sqlx::query_scalar!(
+        r#"
+        SELECT correlations_data AS "correlations_data: Json<CorrelationMatrixWithStats>"
+        FROM portfolio_correlations_cache
+        WHERE portfolio_id = $1
+          AND days = $2
+          AND user_id = $3
+          AND calculation_status = 'fresh'
+          AND expires_at > NOW()
+        "#,
+        tenant.portfolio_id(),
+        days,
+        tenant.user_id(),
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|Json(correlations)| correlations))
+}
+
+/// Unexpired cached narrative for a time period
+pub async fn fetch_narrative_cache(
+    pool: &PgPool,
+    tenant: &TenantScope,
+    time_period: &str,
+) -> Result<Option<Value>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT narrative_data
+        FROM portfolio_narrative_cache
+        WHERE portfolio_id = $1 AND time_period = $2 AND user_id = $3 AND expires_at > NOW()
+        "#,
+        tenant.portfolio_id(),
+        time_period,
+        tenant.user_id(),
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Store a generated narrative until `expires_at`
+pub async fn store_narrative_cache(
+    pool: &PgPool,
+    tenant: &TenantScope,
+    time_period: &str,
+    narrative_data: &Value,
+    generated_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO portfolio_narrative_cache (portfolio_id, time_period, narrative_data, generated_at, expires_at, user_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (portfolio_id, time_period)
+        DO UPDATE SET
+            narrative_data = EXCLUDED.narrative_data,
+            generated_at = EXCLUDED.generated_at,
+            expires_at = EXCLUDED.expires_at,
+            updated_at = NOW()
+        "#,
+        tenant.portfolio_id(),
+        time_period,
+        narrative_data,
+        generated_at,
+        expires_at,
+        tenant.user_id(),
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Cached rolling beta series for a ticker, expired or not
+pub async fn fetch_rolling_beta_cache(
+    pool: &PgPool,
+    ticker: &str,
+    benchmark: &str,
+    days: i32,
+) -> Result<Option<RollingBetaCacheEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        RollingBetaCacheEntry,
+        r#"
+        SELECT
+            beta_30d AS "beta_30d: Json<Vec<BetaPoint>>",
+            beta_60d AS "beta_60d: Json<Vec<BetaPoint>>",
+            beta_90d AS "beta_90d: Json<Vec<BetaPoint>>",
+            current_beta, beta_volatility,
+            calculated_at, expires_at
+        FROM rolling_beta_cache
+        WHERE ticker = $1 AND benchmark = $2 AND total_days = $3
+        "#,
+        ticker,
+        benchmark,
+        days,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Expiry of the cached rolling beta for a ticker
+pub async fn fetch_rolling_beta_expiry(
+    pool: &PgPool,
+    ticker: &str,
+    benchmark: &str,
+    days: i32,
+) -> Result<Option<NaiveDateTime>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT expires_at FROM rolling_beta_cache
+         WHERE ticker = $1 AND benchmark = $2 AND total_days = $3",
+        ticker,
+        benchmark,
+        days,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Cached downside risk for a portfolio, expired or not
+pub async fn fetch_downside_risk_cache(
+    pool: &PgPool,
+    tenant: &TenantScope,
+    days: i32,
+    benchmark: &str,
+) -> Result<Option<DownsideRiskCacheEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        DownsideRiskCacheEntry,
+        r#"
+        SELECT risk_data AS "risk_data: Json<PortfolioDownsideRisk>", calculated_at, expires_at
+        FROM downside_risk_cache
+        WHERE portfolio_id = $1 AND days = $2 AND benchmark = $3 AND user_id = $4
+        "#,
+        tenant.portfolio_id(),
+        days,
+        benchmark,
+        tenant.user_id(),
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Expiry of the cached downside risk for a portfolio
+pub async fn fetch_downside_risk_expiry(
+    pool: &PgPool,
+    tenant: &TenantScope,
+    days: i32,
+    benchmark: &str,
+) -> Result<Option<NaiveDateTime>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT expires_at FROM downside_risk_cache
+         WHERE portfolio_id = $1 AND days = $2 AND benchmark = $3 AND user_id = $4",
+        tenant.portfolio_id(),
+        days,
+        benchmark,
+        tenant.user_id(),
+    )
+    .fetch_optional(pool)
+    .await
+}
@@ -24,6 +24,7 @@ pub async fn get_preferences_by_user_id(
             technical_weight,
             fundamental_weight,
             custom_settings,
+            share_anonymous_stats,
             created_at,
             updated_at
         FROM user_preferences
@@ -74,6 +75,7 @@ pub async fn upsert_preferences(
             technical_weight,
             fundamental_weight,
             custom_settings,
+            share_anonymous_stats,
             updated_at
         )
         VALUES (
@@ -88,6 +90,7 @@ pub async fn upsert_preferences(
             COALESCE($8, 0.4),
             COALESCE($9, 0.3),
             $10,
+            COALESCE($11, false),
             NOW()
         )
         ON CONFLICT (user_id)
@@ -106,6 +109,7 @@ pub async fn upsert_preferences(
             technical_weight = COALESCE($8, user_preferences.technical_weight),
             fundamental_weight = COALESCE($9, user_preferences.fundamental_weight),
             custom_settings = COALESCE($10, user_preferences.custom_settings),
+            share_anonymous_stats = COALESCE($11, user_preferences.share_anonymous_stats),
             updated_at = NOW()
         RETURNING
             id,
@@ -120,6 +124,7 @@ pub async fn upsert_preferences(
             technical_weight,
             fundamental_weight,
             custom_settings,
+            share_anonymous_stats,
             created_at,
             updated_at
         "#,
@@ -134,6 +139,7 @@ pub async fn upsert_preferences(
     .bind(technical_weight)
     .bind(fundamental_weight)
     .bind(&update.custom_settings)
+    .bind(update.share_anonymous_stats)
     .fetch_one(pool)
     .await
 }
@@ -161,9 +167,10 @@ pub async fn upsert_full_preferences(
             technical_weight,
             fundamental_weight,
             custom_settings,
+            share_anonymous_stats,
             updated_at
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW())
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW())
         ON CONFLICT (user_id)
         DO UPDATE SET
             llm_enabled = $2,
@@ -176,6 +183,7 @@ pub async fn upsert_full_preferences(
             technical_weight = $9,
             fundamental_weight = $10,
             custom_settings = $11,
+            share_anonymous_stats = $12,
             updated_at = NOW()
         RETURNING
             id,
@@ -190,6 +198,7 @@ pub async fn upsert_full_preferences(
             technical_weight,
             fundamental_weight,
             custom_settings,
+            share_anonymous_stats,
             created_at,
             updated_at
         "#,
@@ -205,6 +214,7 @@ pub async fn upsert_full_preferences(
     .bind(&prefs.technical_weight)
     .bind(&prefs.fundamental_weight)
     .bind(&prefs.custom_settings)
+    .bind(prefs.share_anonymous_stats)
     .fetch_one(pool)
     .await
 }
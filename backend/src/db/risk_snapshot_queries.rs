@@ -1,8 +1,11 @@
 use chrono::NaiveDate;
+use sqlx::types::Json;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::models::risk_snapshot::{CreateRiskSnapshot, RiskSnapshot};
+use crate::models::risk_snapshot::{
+    CreateRiskSnapshot, RiskRecomputeRequest, RiskScoringInput, RiskSnapshot, ShadowScoreRow, SnapshotBaseline,
+};
 
 /// Upsert a risk snapshot (idempotent daily snapshots)
 pub async fn upsert_snapshot(
@@ -15,9 +18,10 @@ pub async fn upsert_snapshot(
             portfolio_id, ticker, snapshot_date, snapshot_type,
             volatility, max_drawdown, beta, sharpe, value_at_risk,
             var_95, var_99, expected_shortfall_95, expected_shortfall_99,
-            risk_score, risk_level, total_value, market_value
+            risk_score, risk_level, total_value, market_value, scoring_version, change_drivers,
+            avg_correlation
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
         ON CONFLICT (portfolio_id, ticker, snapshot_date, snapshot_type)
         DO UPDATE SET
             volatility = EXCLUDED.volatility,
@@ -33,6 +37,13 @@ pub async fn upsert_snapshot(
             risk_level = EXCLUDED.risk_level,
             total_value = EXCLUDED.total_value,
             market_value = EXCLUDED.market_value,
+            scoring_version = EXCLUDED.scoring_version,
+            change_drivers = EXCLUDED.change_drivers,
+            avg_correlation = EXCLUDED.avg_correlation,
+            carried_forward = false,
+            shadow_risk_score = NULL,
+            shadow_risk_level = NULL,
+            shadow_scoring_version = NULL,
             created_at = NOW()
         RETURNING *
         "#,
@@ -54,6 +65,9 @@ pub async fn upsert_snapshot(
     .bind(snapshot.risk_level)
     .bind(snapshot.total_value)
     .bind(snapshot.market_value)
+    .bind(snapshot.scoring_version)
+    .bind(snapshot.change_drivers.map(Json))
+    .bind(snapshot.avg_correlation)
     .fetch_one(pool)
     .await
 }
@@ -107,7 +121,6 @@ pub async fn fetch_history(
 }
 
 /// Get the latest snapshot for a portfolio or position
-#[allow(dead_code)]
 pub async fn fetch_latest(
     pool: &PgPool,
     portfolio_id: Uuid,
@@ -147,8 +160,31 @@ pub async fn fetch_latest(
     }
 }
 
+/// Latest portfolio-level snapshot dated before `date`
+pub async fn fetch_previous(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    date: NaiveDate,
+) -> Result<Option<RiskSnapshot>, sqlx::Error> {
+    sqlx::query_as::<_, RiskSnapshot>(
+        r#"
+        SELECT *
+        FROM risk_snapshots
+        WHERE portfolio_id = $1
+          AND ticker IS NULL
+          AND snapshot_type = 'portfolio'
+          AND snapshot_date < $2
+        ORDER BY snapshot_date DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(portfolio_id)
+    .bind(date)
+    .fetch_optional(pool)
+    .await
+}
+
 /// Fetch all position snapshots for a portfolio on a specific date
-#[allow(dead_code)]
 pub async fn fetch_portfolio_positions_by_date(
     pool: &PgPool,
     portfolio_id: Uuid,
@@ -171,6 +207,99 @@ pub async fn fetch_portfolio_positions_by_date(
     .await
 }
 
+/// Latest daily portfolio-level snapshot of a portfolio, with the last date it
+/// was computed in full and the positions it covered
+pub async fn fetch_baseline(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+) -> Result<Option<SnapshotBaseline>, sqlx::Error> {
+    sqlx::query_as::<_, SnapshotBaseline>(
+        r#"
+        SELECT p.snapshot_date, p.created_at, p.scoring_version,
+               (
+                   SELECT MAX(f.snapshot_date)
+                   FROM risk_snapshots f
+                   WHERE f.portfolio_id = p.portfolio_id
+                     AND f.snapshot_type = 'portfolio'
+                     AND NOT EXISTS (
+                         SELECT 1 FROM risk_snapshots c
+                         WHERE c.portfolio_id = f.portfolio_id
+                           AND c.snapshot_date = f.snapshot_date
+                           AND c.carried_forward
+                     )
+               ) AS last_full_date,
+               ARRAY(
+                   SELECT DISTINCT s.ticker
+                   FROM risk_snapshots s
+                   WHERE s.portfolio_id = p.portfolio_id
+                     AND s.snapshot_date = p.snapshot_date
+                     AND s.snapshot_type = 'position'
+                     AND s.ticker IS NOT NULL
+               ) AS position_tickers
+        FROM risk_snapshots p
+        WHERE p.portfolio_id = $1
+          AND p.snapshot_type = 'portfolio'
+          AND p.granularity = 'daily'
+        ORDER BY p.snapshot_date DESC, p.created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(portfolio_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Copy snapshots from `from` to the later date `to`, flagged as carried
+/// forward. `tickers` limits the copy to those positions; `None` copies the
+/// portfolio-level snapshot and every position. Rows already present on `to`
+/// are left alone. Returns the number of rows copied.
+pub async fn carry_forward(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+    tickers: Option<&[String]>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO risk_snapshots (
+            portfolio_id, ticker, snapshot_date, snapshot_type,
+            volatility, max_drawdown, beta, sharpe, value_at_risk,
+            var_95, var_99, expected_shortfall_95, expected_shortfall_99,
+            risk_score, risk_level, total_value, market_value, scoring_version,
+            avg_correlation, carried_forward
+        )
+        SELECT DISTINCT ON (s.ticker, s.snapshot_type)
+            s.portfolio_id, s.ticker, $3, s.snapshot_type,
+            s.volatility, s.max_drawdown, s.beta, s.sharpe, s.value_at_risk,
+            s.var_95, s.var_99, s.expected_shortfall_95, s.expected_shortfall_99,
+            s.risk_score, s.risk_level, s.total_value, s.market_value, s.scoring_version,
+            s.avg_correlation, true
+        FROM risk_snapshots s
+        WHERE s.portfolio_id = $1
+          AND s.snapshot_date = $2
+          AND $2 < $3
+          AND ($4::TEXT[] IS NULL OR (s.snapshot_type = 'position' AND s.ticker = ANY($4)))
+          AND NOT EXISTS (
+              SELECT 1 FROM risk_snapshots t
+              WHERE t.portfolio_id = s.portfolio_id
+                AND t.snapshot_date = $3
+                AND t.snapshot_type = s.snapshot_type
+                AND t.ticker IS NOT DISTINCT FROM s.ticker
+          )
+        ORDER BY s.ticker, s.snapshot_type, s.created_at DESC
+        "#,
+    )
+    .bind(portfolio_id)
+    .bind(from)
+    .bind(to)
+    .bind(tickers)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
 /// Fetch snapshots for multiple dates (for trend analysis)
 #[allow(dead_code)]
 pub async fn fetch_snapshots_by_dates(
@@ -214,3 +343,164 @@ pub async fn fetch_snapshots_by_dates(
         .await
     }
 }
+
+/// Keep only the last snapshot per portfolio, ticker and `period` ("week" or
+/// "month") among snapshots dated in `[from, before)`, and mark survivors of a finer
+/// tier with `granularity`. Returns the number of rows removed.
+pub async fn compact(
+    pool: &PgPool,
+    from: Option<NaiveDate>,
+    before: NaiveDate,
+    period: &str,
+    granularity: &str,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let removed = sqlx::query(
+        r#"
+        DELETE FROM risk_snapshots
+        WHERE id IN (
+            SELECT id FROM (
+                SELECT id, ROW_NUMBER() OVER (
+                    PARTITION BY portfolio_id, ticker, snapshot_type, date_trunc($3, snapshot_date)
+                    ORDER BY snapshot_date DESC
+                ) AS rn
+                FROM risk_snapshots
+                WHERE ($1::DATE IS NULL OR snapshot_date >= $1)
+                  AND snapshot_date < $2
+            ) ranked
+            WHERE rn > 1
+        )
+        "#,
+    )
+    .bind(from)
+    .bind(before)
+    .bind(period)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    sqlx::query(
+        r#"
+        UPDATE risk_snapshots
+        SET granularity = $3
+        WHERE ($1::DATE IS NULL OR snapshot_date >= $1)
+          AND snapshot_date < $2
+          AND array_position(ARRAY['daily', 'weekly', 'monthly'], granularity)
+            < array_position(ARRAY['daily', 'weekly', 'monthly'], $3)
+        "#,
+    )
+    .bind(from)
+    .bind(before)
+    .bind(granularity)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(removed)
+}
+
+/// Up to `limit` snapshots matching the recompute filters with an id after
+/// `after`, in id order, for paging through history
+pub async fn fetch_scoring_inputs(
+    pool: &PgPool,
+    filter: &RiskRecomputeRequest,
+    after: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<RiskScoringInput>, sqlx::Error> {
+    sqlx::query_as::<_, RiskScoringInput>(
+        r#"
+        SELECT id, volatility, max_drawdown, beta, value_at_risk
+        FROM risk_snapshots
+        WHERE ($1::UUID IS NULL OR portfolio_id = $1)
+          AND ($2::DATE IS NULL OR snapshot_date >= $2)
+          AND ($3::DATE IS NULL OR snapshot_date <= $3)
+          AND ($4::UUID IS NULL OR id > $4)
+        ORDER BY id
+        LIMIT $5
+        "#,
+    )
+    .bind(filter.portfolio_id)
+    .bind(filter.from)
+    .bind(filter.to)
+    .bind(after)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Store shadow scores computed under `scoring_version`
+pub async fn store_shadow_scores(
+    pool: &PgPool,
+    scoring_version: i32,
+    ids: &[Uuid],
+    scores: &[bigdecimal::BigDecimal],
+    levels: &[String],
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE risk_snapshots rs
+        SET shadow_risk_score = s.score,
+            shadow_risk_level = s.level,
+            shadow_scoring_version = $4
+        FROM UNNEST($1::UUID[], $2::NUMERIC[], $3::TEXT[]) AS s(id, score, level)
+        WHERE rs.id = s.id
+        "#,
+    )
+    .bind(ids)
+    .bind(scores)
+    .bind(levels)
+    .bind(scoring_version)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Snapshots matching the filters that have a shadow score under `scoring_version`
+pub async fn fetch_shadow_scores(
+    pool: &PgPool,
+    filter: &RiskRecomputeRequest,
+) -> Result<Vec<ShadowScoreRow>, sqlx::Error> {
+    sqlx::query_as::<_, ShadowScoreRow>(
+        r#"
+        SELECT id, portfolio_id, ticker, snapshot_date, risk_score, risk_level,
+               shadow_risk_score, shadow_risk_level
+        FROM risk_snapshots
+        WHERE shadow_scoring_version = $1
+          AND shadow_risk_score IS NOT NULL
+          AND shadow_risk_level IS NOT NULL
+          AND ($2::UUID IS NULL OR portfolio_id = $2)
+          AND ($3::DATE IS NULL OR snapshot_date >= $3)
+          AND ($4::DATE IS NULL OR snapshot_date <= $4)
+        "#,
+    )
+    .bind(filter.scoring_version)
+    .bind(filter.portfolio_id)
+    .bind(filter.from)
+    .bind(filter.to)
+    .fetch_all(pool)
+    .await
+}
+
+/// Make the shadow scores under `scoring_version` the live scores. Returns
+/// the number of snapshots updated.
+pub async fn promote_shadow_scores(pool: &PgPool, scoring_version: i32) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE risk_snapshots
+        SET risk_score = shadow_risk_score,
+            risk_level = shadow_risk_level,
+            scoring_version = shadow_scoring_version,
+            shadow_risk_score = NULL,
+            shadow_risk_level = NULL,
+            shadow_scoring_version = NULL
+        WHERE shadow_scoring_version = $1
+          AND shadow_risk_score IS NOT NULL
+          AND shadow_risk_level IS NOT NULL
+        "#,
+    )
+    .bind(scoring_version)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// Open a session for `user_id`, clearing out the user's expired and revoked ones
+pub async fn create(
+    pool: &PgPool,
+    user_id: Uuid,
+    refresh_token_hash: &str,
+    user_agent: Option<&str>,
+    expires_at: DateTime<Utc>,
+) -> Result<Uuid, sqlx::Error> {
+    sqlx::query("DELETE FROM sessions WHERE user_id = $1 AND (expires_at <= NOW() OR revoked_at IS NOT NULL)")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO sessions (user_id, refresh_token_hash, user_agent, expires_at)
+         VALUES ($1, $2, $3, $4)
+         RETURNING id"
+    )
+    .bind(user_id)
+    .bind(refresh_token_hash)
+    .bind(user_agent)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await
+}
+
+/// Swap a live session's refresh token for a new one and extend it. Returns
+/// the session and its user, or `None` when the token is unknown, expired,
+/// revoked or was already rotated.
+pub async fn rotate(
+    pool: &PgPool,
+    refresh_token_hash: &str,
+    new_refresh_token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<Option<(Uuid, Uuid)>, sqlx::Error> {
+    sqlx::query_as::<_, (Uuid, Uuid)>(
+        "UPDATE sessions
+         SET refresh_token_hash = $2, expires_at = $3, last_used_at = NOW()
+         WHERE refresh_token_hash = $1 AND revoked_at IS NULL AND expires_at > NOW()
+         RETURNING id, user_id"
+    )
+    .bind(refresh_token_hash)
+    .bind(new_refresh_token_hash)
+    .bind(expires_at)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Whether the session exists, belongs to `user_id` and is neither expired nor revoked
+pub async fn is_active<'e>(executor: impl PgExecutor<'e>, session_id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (
+            SELECT 1 FROM sessions
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > NOW()
+         )"
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_one(executor)
+    .await
+}
+
+/// Revoke the session holding a refresh token
+pub async fn revoke_by_token(pool: &PgPool, refresh_token_hash: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE sessions SET revoked_at = NOW() WHERE refresh_token_hash = $1 AND revoked_at IS NULL"
+    )
+    .bind(refresh_token_hash)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revoke every live session of a user except `keep`. Returns the number revoked.
+pub async fn revoke_all(pool: &PgPool, user_id: Uuid, keep: Option<Uuid>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE sessions SET revoked_at = NOW()
+         WHERE user_id = $1 AND revoked_at IS NULL AND ($2::uuid IS NULL OR id <> $2)"
+    )
+    .bind(user_id)
+    .bind(keep)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
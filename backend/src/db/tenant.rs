@@ -0,0 +1,77 @@
+//! Tenant scoping for the per-portfolio caches.
+//!
+//! Cached analytics rows carry the `user_id` of the portfolio's owner, held in
+//! step with `portfolios` by a composite foreign key. The cache queries take a
+//! [`TenantScope`] instead of a bare portfolio id and filter on both columns.
+//! A scope can only be obtained from a database check of who owns the
+//! portfolio, so a handler cannot reach another user's cache entries by id.
+//! Members of a shared portfolio read the owner's entries.
+
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+/// Proof that a portfolio belongs to a user, for scoping cache queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TenantScope {
+    user_id: Uuid,
+    portfolio_id: Uuid,
+}
+
+impl TenantScope {
+    /// Scope for work on a portfolio under whoever owns it: background jobs, and
+    /// requests once `PortfolioAccess` has checked the caller's role
+    pub async fn for_job<'e>(executor: impl PgExecutor<'e>, portfolio_id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            TenantScope,
+            "SELECT user_id, id AS portfolio_id FROM portfolios WHERE id = $1",
+            portfolio_id,
+        )
+        .fetch_optional(executor)
+        .await
+    }
+
+    pub fn user_id(&self) -> Uuid {
+        self.user_id
+    }
+
+    pub fn portfolio_id(&self) -> Uuid {
+        self.portfolio_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    use crate::db::{portfolio_member_queries, risk_cache_queries, test_support};
+    use crate::middleware::permissions::{CanView, RequiredRole, require_role};
+    use crate::models::Role;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_viewer_reads_owners_risk_cache() {
+        let pool = test_support::pool().await;
+        let owner = test_support::user(&pool).await;
+        let viewer = test_support::user(&pool).await;
+        let portfolio_id = test_support::portfolio(&pool, owner).await;
+        portfolio_member_queries::upsert(&pool, portfolio_id, viewer, Role::Viewer, owner).await.unwrap();
+
+        let job_scope = TenantScope::for_job(&pool, portfolio_id).await.unwrap().unwrap();
+        let risk_data = serde_json::json!({ "portfolio_id": portfolio_id });
+        risk_cache_queries::store_risk_cache(&pool, &job_scope, 90, "SPY", &risk_data, Utc::now() + Duration::hours(1), 1)
+            .await
+            .unwrap();
+
+        // The check PortfolioAccess<CanView> makes, then the scope the risk views build
+        let (role, owner_id) = portfolio_member_queries::role_for(&pool, portfolio_id, viewer).await.unwrap().unwrap();
+        assert!(require_role(role, CanView::ROLE).is_ok());
+        assert_eq!(owner_id, owner);
+        let scope = TenantScope::for_job(&pool, portfolio_id).await.unwrap().unwrap();
+        let entry = risk_cache_queries::fetch_risk_cache(&pool, &scope, 90, "SPY").await.unwrap();
+        assert_eq!(entry.map(|e| e.risk_data), Some(risk_data));
+
+        test_support::delete_user(&pool, viewer).await;
+        test_support::delete_user(&pool, owner).await;
+    }
+}
@@ -0,0 +1,58 @@
+//! Fixtures for tests that need a real database.
+//!
+//! Such tests are `#[ignore]`d so `cargo test` runs without one. Point
+//! `TEST_DATABASE_URL` at a scratch database and run them with
+//! `cargo test -- --ignored`; migrations are applied on connect.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn pool() -> PgPool {
+    let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a scratch database");
+    let pool = PgPool::connect(&url).await.expect("Failed to connect to TEST_DATABASE_URL");
+    sqlx::migrate!("./migrations").run(&pool).await.expect("Failed to apply migrations");
+    pool
+}
+
+pub async fn user(pool: &PgPool) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("{}@example.test", id))
+        .execute(pool)
+        .await
+        .expect("Failed to insert user");
+    id
+}
+
+pub async fn portfolio(pool: &PgPool, owner_id: Uuid) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO portfolios (id, name, user_id) VALUES ($1, 'Test portfolio', $2)")
+        .bind(id)
+        .bind(owner_id)
+        .execute(pool)
+        .await
+        .expect("Failed to insert portfolio");
+    id
+}
+
+pub async fn account(pool: &PgPool, portfolio_id: Uuid) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO accounts (id, portfolio_id, account_number, account_nickname) VALUES ($1, $2, $3, 'Test')")
+        .bind(id)
+        .bind(portfolio_id)
+        .bind(id.to_string())
+        .execute(pool)
+        .await
+        .expect("Failed to insert account");
+    id
+}
+
+/// Remove a user and, through cascades, everything they own
+pub async fn delete_user(pool: &PgPool, user_id: Uuid) {
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .expect("Failed to delete user");
+}
@@ -0,0 +1,54 @@
+use sqlx::PgPool;
+
+use crate::models::TickerMetadata;
+
+/// Store search results, replacing what was known about each symbol
+pub async fn upsert_many(pool: &PgPool, entries: &[TickerMetadata]) -> Result<u64, sqlx::Error> {
+    let tickers: Vec<&str> = entries.iter().map(|e| e.ticker.as_str()).collect();
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    let exchanges: Vec<Option<&str>> = entries.iter().map(|e| e.exchange.as_deref()).collect();
+    let types: Vec<&str> = entries.iter().map(|e| e.asset_type.as_str()).collect();
+    let regions: Vec<Option<&str>> = entries.iter().map(|e| e.region.as_deref()).collect();
+    let currencies: Vec<&str> = entries.iter().map(|e| e.currency.as_str()).collect();
+
+    let result = sqlx::query(
+        "INSERT INTO ticker_metadata (ticker, name, exchange, asset_type, region, currency, updated_at)
+         SELECT t.ticker, t.name, t.exchange, t.asset_type, t.region, t.currency, NOW()
+         FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[])
+              AS t(ticker, name, exchange, asset_type, region, currency)
+         ON CONFLICT (ticker) DO UPDATE SET
+            name = EXCLUDED.name,
+            exchange = COALESCE(EXCLUDED.exchange, ticker_metadata.exchange),
+            asset_type = EXCLUDED.asset_type,
+            region = COALESCE(EXCLUDED.region, ticker_metadata.region),
+            currency = EXCLUDED.currency,
+            updated_at = EXCLUDED.updated_at"
+    )
+    .bind(&tickers)
+    .bind(&names)
+    .bind(&exchanges)
+    .bind(&types)
+    .bind(&regions)
+    .bind(&currencies)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Stored symbols starting with `query`, or whose name contains it: the exact
+/// symbol first, then symbol prefixes, then name matches
+pub async fn search(pool: &PgPool, query: &str, limit: i64) -> Result<Vec<TickerMetadata>, sqlx::Error> {
+    let pattern = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    sqlx::query_as::<_, TickerMetadata>(
+        "SELECT ticker, name, exchange, asset_type, region, currency, updated_at
+         FROM ticker_metadata
+         WHERE ticker LIKE UPPER($1) || '%' OR LOWER(name) LIKE '%' || LOWER($1) || '%'
+         ORDER BY ticker = UPPER($2) DESC, ticker LIKE UPPER($1) || '%' DESC, ticker
+         LIMIT $3"
+    )
+    .bind(&pattern)
+    .bind(query)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// How rows of a table are tied to a user. Every filter binds the user id as $1.
+#[derive(Debug, Clone, Copy)]
+enum Owner {
+    User,
+    Portfolio,
+    Account,
+    Transaction,
+    Recommendation,
+    Watchlist,
+    WatchlistItem,
+    Survey,
+    ImportBatch,
+}
+
+impl Owner {
+    fn filter(&self, column: &str) -> String {
+        let ids = match self {
+            Owner::User => return format!("{} = $1", column),
+            Owner::Portfolio => "SELECT id FROM portfolios WHERE user_id = $1",
+            Owner::Account => {
+                "SELECT a.id FROM accounts a JOIN portfolios p ON p.id = a.portfolio_id WHERE p.user_id = $1"
+            }
+            Owner::Transaction => {
+                "SELECT t.id FROM detected_transactions t JOIN accounts a ON a.id = t.account_id
+                 JOIN portfolios p ON p.id = a.portfolio_id WHERE p.user_id = $1"
+            }
+            Owner::Recommendation => "SELECT id FROM recommendations WHERE user_id = $1",
+            Owner::Watchlist => "SELECT id FROM watchlists WHERE user_id = $1",
+            Owner::WatchlistItem => {
+                "SELECT wi.id FROM watchlist_items wi JOIN watchlists w ON w.id = wi.watchlist_id WHERE w.user_id = $1"
+            }
+            Owner::Survey => "SELECT id FROM financial_surveys WHERE user_id = $1",
+            Owner::ImportBatch => "SELECT id FROM import_batches WHERE user_id = $1",
+        };
+        format!("{} IN ({})", column, ids)
+    }
+}
+
+/// A table holding user data. Rows match if any of `owners` matches.
+struct UserDataTable {
+    table: &'static str,
+    owners: &'static [(&'static str, Owner)],
+    /// Derived caches and delivery bookkeeping are erased but not exported
+    exported: bool,
+}
+
+impl UserDataTable {
+    fn filter(&self) -> String {
+        self.owners
+            .iter()
+            .map(|(column, owner)| owner.filter(column))
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    }
+}
+
+const fn table(table: &'static str, owners: &'static [(&'static str, Owner)], exported: bool) -> UserDataTable {
+    UserDataTable { table, owners, exported }
+}
+
+/// Every table holding user data, children before parents so each delete sees
+/// its rows before a cascade would remove them uncounted. `transactions` has no
+/// cascade at all and must go before `portfolios`; `llm_usage`, `audit_log` and
+/// the downside/guidance caches have no foreign keys to portfolios and would
+/// otherwise survive, `audit_log` with full before/after copies of the data.
+const USER_DATA_TABLES: &[UserDataTable] = &[
+    table("recommendation_explanations", &[("recommendation_id", Owner::Recommendation)], true),
+    table("recommendations", &[("user_id", Owner::User)], true),
+    table("notifications", &[("user_id", Owner::User)], true),
+    table("alert_history", &[("user_id", Owner::User)], true),
+    table("alert_rules", &[("user_id", Owner::User)], true),
+    table("notification_preferences", &[("user_id", Owner::User)], true),
+    table("daily_email_counts", &[("user_id", Owner::User)], false),
+    table("watchlist_monitoring_state", &[("watchlist_item_id", Owner::WatchlistItem)], false),
+    table("watchlist_thresholds", &[("watchlist_item_id", Owner::WatchlistItem)], true),
+    table("watchlist_alerts", &[("watchlist_id", Owner::Watchlist)], true),
+    table("watchlist_items", &[("watchlist_id", Owner::Watchlist)], true),
+    table("watchlists", &[("user_id", Owner::User)], true),
+    table("survey_personal_info", &[("survey_id", Owner::Survey)], true),
+    table("survey_income_info", &[("survey_id", Owner::Survey)], true),
+    table("survey_additional_income", &[("survey_id", Owner::Survey)], true),
+    table("survey_liabilities", &[("survey_id", Owner::Survey)], true),
+    table("survey_assets", &[("survey_id", Owner::Survey)], true),
+    table("survey_expenses", &[("survey_id", Owner::Survey)], true),
+    table("survey_household_expenses", &[("survey_id", Owner::Survey)], true),
+    table("survey_goals", &[("survey_id", Owner::Survey)], true),
+    table("survey_risk_profile", &[("survey_id", Owner::Survey)], true),
+    table("financial_snapshots", &[("survey_id", Owner::Survey)], true),
+    table("financial_surveys", &[("user_id", Owner::User)], true),
+    table("transactions", &[("portfolio_id", Owner::Portfolio)], true),
+    table("cash_flows", &[("account_id", Owner::Account)], true),
+    table("account_fee_schedules", &[("account_id", Owner::Account)], true),
+    table("crypto_wallets", &[("account_id", Owner::Account)], true),
+    table("sell_lot_selections", &[("sell_transaction_id", Owner::Transaction)], true),
+    table("trade_journal_entries", &[("transaction_id", Owner::Transaction)], true),
+    table("detected_transactions", &[("account_id", Owner::Account)], true),
+    table("holdings_snapshots", &[("account_id", Owner::Account)], true),
+    table("portfolio_value_history", &[("account_id", Owner::Account)], false),
+    table("position_annotations", &[("account_id", Owner::Account)], true),
+    table("positions", &[("portfolio_id", Owner::Portfolio)], true),
+    table("accounts", &[("portfolio_id", Owner::Portfolio)], true),
+    table("risk_snapshots", &[("portfolio_id", Owner::Portfolio)], true),
+    table("risk_threshold_settings", &[("portfolio_id", Owner::Portfolio)], true),
+    table("portfolio_risk_budgets", &[("portfolio_id", Owner::Portfolio)], true),
+    table("portfolio_optimization_constraints", &[("portfolio_id", Owner::Portfolio)], true),
+    table("portfolio_glide_paths", &[("portfolio_id", Owner::Portfolio)], true),
+    table("portfolio_rebalance_schedules", &[("portfolio_id", Owner::Portfolio)], true),
+    table("portfolio_risk_cache", &[("user_id", Owner::User)], false),
+    table("portfolio_correlations_cache", &[("user_id", Owner::User)], false),
+    table("portfolio_narrative_cache", &[("user_id", Owner::User)], false),
+    table("portfolio_news_cache", &[("portfolio_id", Owner::Portfolio)], false),
+    table("portfolio_news_feed_cache", &[("portfolio_id", Owner::Portfolio)], false),
+    table("portfolio_optimization_cache", &[("portfolio_id", Owner::Portfolio)], false),
+    table("downside_risk_cache", &[("user_id", Owner::User)], false),
+    table("long_term_guidance_cache", &[("portfolio_id", Owner::Portfolio)], false),
+    table("screening_cache", &[("user_id", Owner::User)], false),
+    table("llm_usage", &[("user_id", Owner::User), ("portfolio_id", Owner::Portfolio)], true),
+    table("import_batch_changes", &[("batch_id", Owner::ImportBatch)], false),
+    table("inbound_emails", &[("user_id", Owner::User)], true),
+    table("inbound_email_addresses", &[("user_id", Owner::User)], false),
+    table("public_api_tokens", &[("user_id", Owner::User)], false),
+    table("widget_tokens", &[("created_by", Owner::User), ("portfolio_id", Owner::Portfolio)], false),
+    table("import_batches", &[("user_id", Owner::User)], true),
+    table("audit_log", &[("user_id", Owner::User), ("portfolio_id", Owner::Portfolio)], true),
+    table("portfolio_members", &[("user_id", Owner::User), ("portfolio_id", Owner::Portfolio)], true),
+    table("portfolios", &[("user_id", Owner::User)], true),
+    table("csv_import_templates", &[("user_id", Owner::User)], true),
+    table("llm_consents", &[("user_id", Owner::User)], true),
+    table("user_preferences", &[("user_id", Owner::User)], true),
+];
+
+/// Profile fields of the user row, without the password hash
+pub async fn fetch_user_profile(pool: &PgPool, user_id: Uuid) -> Result<Option<Value>, sqlx::Error> {
+    sqlx::query_scalar::<_, Value>(
+        "SELECT row_to_json(u)::jsonb
+         FROM (SELECT id, email, name, role, created_at, updated_at FROM users WHERE id = $1) u"
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Rows of every exported table, keyed by table name
+pub async fn fetch_user_tables(pool: &PgPool, user_id: Uuid) -> Result<BTreeMap<String, Vec<Value>>, sqlx::Error> {
+    let mut tables = BTreeMap::new();
+    for entry in USER_DATA_TABLES.iter().filter(|t| t.exported) {
+        let rows = sqlx::query_scalar::<_, Value>(&format!(
+            "SELECT row_to_json(t)::jsonb FROM {} t WHERE {}",
+            entry.table,
+            entry.filter()
+        ))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+        tables.insert(entry.table.to_string(), rows);
+    }
+    Ok(tables)
+}
+
+/// Delete all of a user's data (and, with `delete_account`, the user row) and
+/// write the audit record, all in one transaction. Returns rows deleted per table.
+pub async fn erase_user_data(
+    pool: &PgPool,
+    user_id: Uuid,
+    delete_account: bool,
+) -> Result<BTreeMap<String, u64>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut counts = BTreeMap::new();
+
+    for entry in USER_DATA_TABLES {
+        let deleted = sqlx::query(&format!("DELETE FROM {} WHERE {}", entry.table, entry.filter()))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        counts.insert(entry.table.to_string(), deleted);
+    }
+
+    if delete_account {
+        for (table, column) in [
+            ("password_reset_tokens", "user_id"),
+            ("user_identities", "user_id"),
+            ("sessions", "user_id"),
+            ("users", "id"),
+        ] {
+            let deleted = sqlx::query(&format!("DELETE FROM {} WHERE {} = $1", table, column))
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            counts.insert(table.to_string(), deleted);
+        }
+    }
+
+    record_event(&mut *tx, user_id, "erasure", delete_account, &counts).await?;
+    tx.commit().await?;
+    Ok(counts)
+}
+
+/// Record that the user downloaded an export of their data
+pub async fn record_export(pool: &PgPool, user_id: Uuid, counts: &BTreeMap<String, u64>) -> Result<(), sqlx::Error> {
+    record_event(pool, user_id, "export", false, counts).await
+}
+
+async fn record_event<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: Uuid,
+    event: &str,
+    account_deleted: bool,
+    counts: &BTreeMap<String, u64>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO data_erasure_audit (user_id, event, account_deleted, row_counts)
+         VALUES ($1, $2, $3, $4)"
+    )
+    .bind(user_id)
+    .bind(event)
+    .bind(account_deleted)
+    .bind(Json(counts))
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// When the user last exported their data, if ever
+pub async fn fetch_last_export_at(pool: &PgPool, user_id: Uuid) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+        "SELECT MAX(created_at) FROM data_erasure_audit WHERE user_id = $1 AND event = 'export'"
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(table: &str) -> usize {
+        USER_DATA_TABLES
+            .iter()
+            .position(|t| t.table == table)
+            .unwrap_or_else(|| panic!("{} is not in USER_DATA_TABLES", table))
+    }
+
+    #[test]
+    fn test_erasure_covers_tables_without_portfolio_cascade() {
+        let portfolios = position("portfolios");
+        for table in ["transactions", "llm_usage", "audit_log"] {
+            assert!(position(table) < portfolios, "{} must be erased before portfolios", table);
+        }
+
+        let audit_log = &USER_DATA_TABLES[position("audit_log")];
+        assert!(audit_log.exported);
+        assert_eq!(
+            audit_log.filter(),
+            "user_id = $1 OR portfolio_id IN (SELECT id FROM portfolios WHERE user_id = $1)"
+        );
+    }
+
+    #[test]
+    fn test_each_table_is_listed_once() {
+        for entry in USER_DATA_TABLES {
+            assert_eq!(
+                USER_DATA_TABLES.iter().filter(|t| t.table == entry.table).count(),
+                1,
+                "{} is listed twice",
+                entry.table
+            );
+        }
+    }
+}
@@ -1,8 +1,8 @@
-use chrono::Utc;
-use sqlx::PgPool;
+use chrono::{DateTime, NaiveTime, Utc};
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 
-use crate::models::{UserPreferences, UpdateUserPreferences};
+use crate::models::{CurrencySettings, DigestFrequency, Locale, NotificationSettings, UpdateNotificationSettings, UserPreferences, UpdateUserPreferences};
 
 /// Get user preferences by user ID
 pub async fn get_by_user_id(
@@ -60,56 +60,253 @@ pub async fn upsert(
     .await
 }
 
-/// Update LLM consent for a user
-pub async fn update_llm_consent(
+/// Delete user preferences (revoke all consent and preferences)
+#[allow(dead_code)]
+pub async fn delete(
     pool: &PgPool,
     user_id: Uuid,
-    consent: bool,
-) -> Result<UserPreferences, sqlx::Error> {
-    let consent_given_at = if consent {
-        Some(Utc::now())
-    } else {
-        None
-    };
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM user_preferences
+        WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
 
-    sqlx::query_as::<_, UserPreferences>(
+    Ok(result.rows_affected())
+}
+
+/// Get a user's notification settings, with defaults for anything not yet saved.
+/// Quiet hours are stored with the other delivery settings in `notification_preferences`.
+pub async fn get_notification_settings<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: Uuid,
+) -> Result<NotificationSettings, sqlx::Error> {
+    sqlx::query_as::<_, NotificationSettings>(
+        r#"
+        SELECT
+            COALESCE(up.email_on_critical_violations, TRUE) AS email_on_critical_violations,
+            COALESCE(up.digest_frequency, 'immediate')::text AS digest_frequency,
+            np.quiet_hours_start,
+            np.quiet_hours_end
+        FROM (SELECT $1::uuid AS user_id) u
+        LEFT JOIN user_preferences up ON up.user_id = u.user_id
+        LEFT JOIN notification_preferences np ON np.user_id = u.user_id
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(executor)
+    .await
+}
+
+/// Replace a user's notification settings
+pub async fn upsert_notification_settings(
+    pool: &PgPool,
+    user_id: Uuid,
+    settings: &UpdateNotificationSettings,
+    quiet_hours: Option<(NaiveTime, NaiveTime)>,
+) -> Result<NotificationSettings, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
         r#"
-        INSERT INTO user_preferences (user_id, llm_enabled, consent_given_at, updated_at)
+        INSERT INTO user_preferences (user_id, email_on_critical_violations, digest_frequency, updated_at)
         VALUES ($1, $2, $3, NOW())
         ON CONFLICT (user_id)
         DO UPDATE SET
-            llm_enabled = $2,
-            consent_given_at = CASE
-                WHEN $2 = TRUE AND user_preferences.consent_given_at IS NULL
-                THEN $3
-                ELSE user_preferences.consent_given_at
-            END,
+            email_on_critical_violations = EXCLUDED.email_on_critical_violations,
+            digest_frequency = EXCLUDED.digest_frequency,
             updated_at = NOW()
-        RETURNING id, user_id, llm_enabled, consent_given_at, narrative_cache_hours, created_at, updated_at
         "#
     )
     .bind(user_id)
-    .bind(consent)
-    .bind(consent_given_at)
+    .bind(settings.email_on_critical_violations)
+    .bind(settings.digest_frequency)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO notification_preferences (user_id, quiet_hours_start, quiet_hours_end)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id)
+        DO UPDATE SET
+            quiet_hours_start = EXCLUDED.quiet_hours_start,
+            quiet_hours_end = EXCLUDED.quiet_hours_end
+        "#
+    )
+    .bind(user_id)
+    .bind(quiet_hours.map(|(start, _)| start))
+    .bind(quiet_hours.map(|(_, end)| end))
+    .execute(&mut *tx)
+    .await?;
+
+    let updated = get_notification_settings(&mut *tx, user_id).await?;
+    tx.commit().await?;
+    Ok(updated)
+}
+
+/// Users receiving digests at `frequency`, with when each was last sent and
+/// their time zone
+pub async fn list_digest_recipients(
+    pool: &PgPool,
+    frequency: DigestFrequency,
+) -> Result<Vec<(Uuid, Option<DateTime<Utc>>, String)>, sqlx::Error> {
+    sqlx::query_as::<_, (Uuid, Option<DateTime<Utc>>, String)>(
+        r#"
+        SELECT user_id, last_digest_sent_at, timezone
+        FROM user_preferences
+        WHERE digest_frequency = $1
+        "#
+    )
+    .bind(frequency)
+    .fetch_all(pool)
+    .await
+}
+
+/// Record that a digest was emailed to the user at `sent_at`
+pub async fn mark_digest_sent(
+    pool: &PgPool,
+    user_id: Uuid,
+    sent_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE user_preferences SET last_digest_sent_at = $2 WHERE user_id = $1")
+        .bind(user_id)
+        .bind(sent_at)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Get a user's base currency, USD until one is saved
+pub async fn get_currency_settings(pool: &PgPool, user_id: Uuid) -> Result<CurrencySettings, sqlx::Error> {
+    sqlx::query_as::<_, CurrencySettings>(
+        r#"
+        SELECT COALESCE(up.base_currency, 'USD') AS base_currency
+        FROM (SELECT $1::uuid AS user_id) u
+        LEFT JOIN user_preferences up ON up.user_id = u.user_id
+        "#
+    )
+    .bind(user_id)
     .fetch_one(pool)
     .await
 }
 
-/// Delete user preferences (revoke all consent and preferences)
-#[allow(dead_code)]
-pub async fn delete(
+/// Set a user's base currency
+pub async fn upsert_currency_settings(
     pool: &PgPool,
     user_id: Uuid,
-) -> Result<u64, sqlx::Error> {
-    let result = sqlx::query(
+    base_currency: &str,
+) -> Result<CurrencySettings, sqlx::Error> {
+    sqlx::query_as::<_, CurrencySettings>(
         r#"
-        DELETE FROM user_preferences
-        WHERE user_id = $1
+        INSERT INTO user_preferences (user_id, base_currency, updated_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (user_id)
+        DO UPDATE SET
+            base_currency = EXCLUDED.base_currency,
+            updated_at = NOW()
+        RETURNING base_currency
         "#
     )
     .bind(user_id)
-    .execute(pool)
-    .await?;
+    .bind(base_currency)
+    .fetch_one(pool)
+    .await
+}
 
-    Ok(result.rows_affected())
+/// Get a user's locale, English until one is saved
+pub async fn get_locale(pool: &PgPool, user_id: Uuid) -> Result<Locale, sqlx::Error> {
+    sqlx::query_scalar::<_, Locale>(
+        r#"
+        SELECT COALESCE(up.locale, 'en')
+        FROM (SELECT $1::uuid AS user_id) u
+        LEFT JOIN user_preferences up ON up.user_id = u.user_id
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Set a user's locale
+pub async fn upsert_locale(pool: &PgPool, user_id: Uuid, locale: Locale) -> Result<Locale, sqlx::Error> {
+    sqlx::query_scalar::<_, Locale>(
+        r#"
+        INSERT INTO user_preferences (user_id, locale, updated_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (user_id)
+        DO UPDATE SET
+            locale = EXCLUDED.locale,
+            updated_at = NOW()
+        RETURNING locale
+        "#
+    )
+    .bind(user_id)
+    .bind(locale)
+    .fetch_one(pool)
+    .await
+}
+
+/// Get a user's time zone name, UTC until one is saved
+pub async fn get_timezone(pool: &PgPool, user_id: Uuid) -> Result<String, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT COALESCE(up.timezone, 'UTC')
+        FROM (SELECT $1::uuid AS user_id) u
+        LEFT JOIN user_preferences up ON up.user_id = u.user_id
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Set a user's time zone
+pub async fn upsert_timezone(pool: &PgPool, user_id: Uuid, timezone: &str) -> Result<String, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        r#"
+        INSERT INTO user_preferences (user_id, timezone, updated_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (user_id)
+        DO UPDATE SET
+            timezone = EXCLUDED.timezone,
+            updated_at = NOW()
+        RETURNING timezone
+        "#
+    )
+    .bind(user_id)
+    .bind(timezone)
+    .fetch_one(pool)
+    .await
+}
+
+/// Time zone of each portfolio's owner, UTC for owners without one
+pub async fn fetch_portfolio_timezones(
+    pool: &PgPool,
+    portfolio_ids: &[Uuid],
+) -> Result<Vec<(Uuid, String)>, sqlx::Error> {
+    sqlx::query_as::<_, (Uuid, String)>(
+        r#"
+        SELECT p.id, COALESCE(up.timezone, 'UTC')
+        FROM portfolios p
+        LEFT JOIN user_preferences up ON up.user_id = p.user_id
+        WHERE p.id = ANY($1)
+        "#
+    )
+    .bind(portfolio_ids)
+    .fetch_all(pool)
+    .await
+}
+
+/// Time zone of a portfolio's owner, UTC for an owner without one
+pub async fn fetch_portfolio_timezone(pool: &PgPool, portfolio_id: Uuid) -> Result<String, sqlx::Error> {
+    Ok(fetch_portfolio_timezones(pool, &[portfolio_id])
+        .await?
+        .pop()
+        .map(|(_, timezone)| timezone)
+        .unwrap_or_else(|| "UTC".to_string()))
 }
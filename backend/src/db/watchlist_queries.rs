@@ -305,7 +305,7 @@ pub async fn set_threshold(
     value: f64,
     enabled: bool,
 ) -> Result<WatchlistThreshold, sqlx::Error> {
-    tracing::info!("📝 DB: set_threshold - item_id={}, type={}, comparison={}, value={}, enabled={}",
+    tracing::info!("DB: set_threshold - item_id={}, type={}, comparison={}, value={}, enabled={}",
         watchlist_item_id, threshold_type, comparison, value, enabled);
 
     // Convert f64 to BigDecimal for database storage
@@ -331,10 +331,10 @@ pub async fn set_threshold(
 
     match &result {
         Ok(threshold) => {
-            tracing::info!("✅ DB: Threshold saved successfully with id={}", threshold.id);
+            tracing::info!("DB: Threshold saved successfully with id={}", threshold.id);
         }
         Err(e) => {
-            tracing::error!("❌ DB: Failed to save threshold: {:?}", e);
+            tracing::error!("DB: Failed to save threshold: {:?}", e);
         }
     }
 
@@ -0,0 +1,66 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{WidgetGrant, WidgetToken};
+
+pub async fn create(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    created_by: Uuid,
+    name: &str,
+    allowed_origin: &str,
+    token_hash: &str,
+) -> Result<WidgetToken, sqlx::Error> {
+    sqlx::query_as::<_, WidgetToken>(
+        "INSERT INTO widget_tokens (portfolio_id, created_by, name, allowed_origin, token_hash)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, portfolio_id, name, allowed_origin, created_at, last_used_at"
+    )
+    .bind(portfolio_id)
+    .bind(created_by)
+    .bind(name)
+    .bind(allowed_origin)
+    .bind(token_hash)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn fetch_for_portfolio(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<WidgetToken>, sqlx::Error> {
+    sqlx::query_as::<_, WidgetToken>(
+        "SELECT id, portfolio_id, name, allowed_origin, created_at, last_used_at
+         FROM widget_tokens
+         WHERE portfolio_id = $1
+         ORDER BY created_at DESC"
+    )
+    .bind(portfolio_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete(pool: &PgPool, id: Uuid, portfolio_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM widget_tokens WHERE id = $1 AND portfolio_id = $2")
+        .bind(id)
+        .bind(portfolio_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// The portfolio and origin a token grants, recording its use. `None` when
+/// the token is unknown, or the portfolio's owner or the token's creator has
+/// been disabled.
+pub async fn authenticate(pool: &PgPool, token_hash: &str) -> Result<Option<WidgetGrant>, sqlx::Error> {
+    sqlx::query_as::<_, WidgetGrant>(
+        "UPDATE widget_tokens t
+         SET last_used_at = NOW()
+         FROM portfolios p, users owner, users creator
+         WHERE t.token_hash = $1
+           AND p.id = t.portfolio_id
+           AND owner.id = p.user_id AND owner.disabled_at IS NULL
+           AND creator.id = t.created_by AND creator.disabled_at IS NULL
+         RETURNING t.portfolio_id, t.allowed_origin"
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
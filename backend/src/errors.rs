@@ -20,11 +20,20 @@ pub enum AppError {
     External(String),
     #[error("Unauthorized")]
     Unauthorized,
+    /// 403 Forbidden - Authenticated, but the user's role does not allow the action
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
     #[error("LLM error: {0}")]
     Llm(LlmError),
     /// 503 Service Unavailable - Resource is being computed in background
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
+    /// 413 Payload Too Large - Request body exceeds the endpoint's limit
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+    /// 403 Forbidden - The user has not consented to the current LLM consent version
+    #[error("LLM consent required (version {required_version})")]
+    ConsentRequired { required_version: i32 },
 }
 
 #[derive(Debug, Error)]
@@ -51,6 +60,8 @@ impl IntoResponse for AppError {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg).into_response(),
             AppError::Validation(msg) => (StatusCode::BAD_REQUEST, msg).into_response(),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized").into_response(),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg).into_response(),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg).into_response(),
             AppError::RateLimited => {
                 let mut headers = HeaderMap::new();
                 headers.insert("Retry-After", HeaderValue::from_static("60"));
@@ -69,6 +80,14 @@ impl IntoResponse for AppError {
                 });
                 (StatusCode::SERVICE_UNAVAILABLE, headers, Json(body)).into_response()
             },
+            AppError::ConsentRequired { required_version } => {
+                let body = json!({
+                    "error": "consent_required",
+                    "message": "AI features require consent to the current terms",
+                    "required_version": required_version
+                });
+                (StatusCode::FORBIDDEN, Json(body)).into_response()
+            },
             AppError::Llm(llm_err) => match llm_err {
                 LlmError::RateLimited => {
                     let mut headers = HeaderMap::new();
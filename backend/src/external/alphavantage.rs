@@ -1,4 +1,5 @@
 use crate::external::price_provider::{ExternalPricePoint, ExternalTickerMatch, PriceProvider, PriceProviderError};
+use crate::external::provider_factory::ProviderSettings;
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
 use chrono::NaiveDate;
@@ -8,16 +9,15 @@ use std::collections::BTreeMap;
 pub struct AlphaVantageProvider {
     client: reqwest::Client,
     api_key: String,
+    base_url: String,
 }
 
 impl AlphaVantageProvider {
-    pub fn from_env() -> Result<Self, PriceProviderError> {
-        let api_key = std::env::var("ALPHAVANTAGE_API_KEY")
-            .map_err(|_| PriceProviderError::BadResponse("ALPHAVANTAGE_API_KEY not set".into()))?;
-
+    pub fn from_settings(settings: &ProviderSettings) -> Result<Self, PriceProviderError> {
         Ok(Self {
             client: reqwest::Client::new(),
-            api_key,
+            api_key: settings.require_api_key("ALPHAVANTAGE_API_KEY")?,
+            base_url: settings.base_url_or("https://www.alphavantage.co"),
         })
     }
 }
@@ -82,10 +82,10 @@ impl PriceProvider for AlphaVantageProvider {
         &self,
         keyword: &str
     ) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
-    let url = "https://www.alphavantage.co/query";
+    let url = format!("{}/query", self.base_url);
     let resp = self
     .client
-    .get(url)
+    .get(&url)
     .query(&[
     ("function", "SYMBOL_SEARCH"),
     ("keywords", keyword),
@@ -98,7 +98,7 @@ impl PriceProvider for AlphaVantageProvider {
 
     let text = resp.text().await
         .map_err(|e| PriceProviderError::Network(e.to_string()))?;
-    println!("{}", text);
+    tracing::debug!(response_bytes = text.len(), "Alpha Vantage ticker search response");
 
     let response_wrapper: TickerSearchWrapper = serde_json::from_str(&text)
         .map_err(|e| PriceProviderError::Parse(format!("JSON parse error: {} | Response: {}", e, text)))?;
@@ -113,6 +113,7 @@ impl PriceProvider for AlphaVantageProvider {
                 _type: ticker_match._type,
                 region: ticker_match.region,
                 currency: ticker_match.currency,
+                exchange: None,
                 match_score: ticker_match.match_score.parse::<f64>()
                     .map_err(|e| PriceProviderError::Parse(e.to_string()))?,
             })
@@ -131,11 +132,11 @@ impl PriceProvider for AlphaVantageProvider {
         let outputsize = if days <= 100 { "compact" } else { "full" };
 
         // TIME_SERIES_DAILY is easiest to parse and reliable for closes
-        let url = "https://www.alphavantage.co/query";
+        let url = format!("{}/query", self.base_url);
 
         let resp = self
             .client
-            .get(url)
+            .get(&url)
             .query(&[
                 ("function", "TIME_SERIES_DAILY"),
                 ("symbol", ticker),
@@ -172,7 +173,7 @@ impl PriceProvider for AlphaVantageProvider {
                     .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
                 let close = bar.close.parse::<BigDecimal>()
                     .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
-                Ok(ExternalPricePoint { date, close })
+                Ok(ExternalPricePoint { date, close, adjusted_close: None })
             })
             .collect::<Result<Vec<_>, _>>()?;
 
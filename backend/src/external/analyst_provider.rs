@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::external::price_provider::PriceProviderError;
+use crate::models::RatingDistribution;
+
+/// Analyst consensus for a ticker as reported by a provider
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExternalAnalystConsensus {
+    /// Rating counts for the current month
+    pub ratings: RatingDistribution,
+    pub target_mean: Option<f64>,
+    pub target_high: Option<f64>,
+    pub target_low: Option<f64>,
+    pub analyst_count: Option<i64>,
+    /// Mean recommendation on a 1 (strong buy) to 5 (strong sell) scale
+    pub recommendation_mean: Option<f64>,
+}
+
+#[async_trait]
+pub trait AnalystProvider: Send + Sync {
+    async fn fetch_analyst_consensus(&self, ticker: &str) -> Result<ExternalAnalystConsensus, PriceProviderError>;
+}
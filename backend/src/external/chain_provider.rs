@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::external::price_provider::PriceProviderError;
+use crate::models::CryptoChain;
+
+/// A token balance held by a wallet address
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalTokenBalance {
+    pub symbol: String,
+    pub name: String,
+    /// In whole tokens (decimals already applied)
+    pub quantity: f64,
+    /// USD price reported with the balance, if the API provides one
+    pub price_usd: Option<f64>,
+}
+
+/// Read-only access to on-chain balances of public addresses
+#[async_trait]
+pub trait ChainProvider: Send + Sync {
+    async fn fetch_balances(&self, chain: CryptoChain, address: &str) -> Result<Vec<ExternalTokenBalance>, PriceProviderError>;
+}
+
+/// Public block explorers: Blockstream's Esplora API for Bitcoin and Ethplorer
+/// for Ethereum and its ERC-20 tokens. Neither needs an account; Ethplorer's
+/// shared "freekey" is rate limited, so set `ETHPLORER_API_KEY` for regular use.
+pub struct PublicChainProvider {
+    client: reqwest::Client,
+    esplora_url: String,
+    ethplorer_key: String,
+}
+
+impl PublicChainProvider {
+    pub fn from_env() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .user_agent("Rustfolio/0.1")
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            esplora_url: std::env::var("ESPLORA_API_URL").unwrap_or_else(|_| "https://blockstream.info/api".to_string()),
+            ethplorer_key: std::env::var("ETHPLORER_API_KEY").unwrap_or_else(|_| "freekey".to_string()),
+        }
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str, query: &[(&str, &str)]) -> Result<T, PriceProviderError> {
+        let resp = self
+            .client
+            .get(url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        match resp.status().as_u16() {
+            200..=299 => {}
+            400 | 404 => return Err(PriceProviderError::NotFound),
+            429 => return Err(PriceProviderError::RateLimited),
+            status => return Err(PriceProviderError::BadResponse(format!("HTTP {}", status))),
+        }
+        resp.json().await.map_err(|e| PriceProviderError::Parse(e.to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EsploraAddress {
+    chain_stats: EsploraStats,
+}
+
+#[derive(Debug, Deserialize)]
+struct EsploraStats {
+    funded_txo_sum: u64,
+    spent_txo_sum: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EthplorerAddress {
+    #[serde(rename = "ETH")]
+    eth: EthplorerEth,
+    #[serde(default)]
+    tokens: Vec<EthplorerToken>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EthplorerEth {
+    balance: f64,
+    #[serde(default)]
+    price: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EthplorerToken {
+    token_info: EthplorerTokenInfo,
+    /// Raw units; divide by 10^decimals
+    balance: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct EthplorerTokenInfo {
+    #[serde(default)]
+    symbol: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    /// Ethplorer sends decimals as a string
+    #[serde(default)]
+    decimals: serde_json::Value,
+    /// `false` when unpriced, otherwise an object with a USD `rate`
+    #[serde(default)]
+    price: serde_json::Value,
+}
+
+fn ethplorer_rate(price: &serde_json::Value) -> Option<f64> {
+    price.get("rate").and_then(|r| r.as_f64()).filter(|r| *r > 0.0)
+}
+
+fn ethplorer_decimals(decimals: &serde_json::Value) -> u32 {
+    decimals
+        .as_u64()
+        .or_else(|| decimals.as_str().and_then(|s| s.parse().ok()))
+        .unwrap_or(18) as u32
+}
+
+#[async_trait]
+impl ChainProvider for PublicChainProvider {
+    async fn fetch_balances(&self, chain: CryptoChain, address: &str) -> Result<Vec<ExternalTokenBalance>, PriceProviderError> {
+        match chain {
+            CryptoChain::Bitcoin => {
+                let url = format!("{}/address/{}", self.esplora_url.trim_end_matches('/'), address);
+                let body: EsploraAddress = self.get_json(&url, &[]).await?;
+                let sats = body.chain_stats.funded_txo_sum.saturating_sub(body.chain_stats.spent_txo_sum);
+                Ok(vec![ExternalTokenBalance {
+                    symbol: "BTC".to_string(),
+                    name: "Bitcoin".to_string(),
+                    quantity: sats as f64 / 100_000_000.0,
+                    price_usd: None,
+                }])
+            }
+            CryptoChain::Ethereum => {
+                let url = format!("https://api.ethplorer.io/getAddressInfo/{}", address);
+                let body: EthplorerAddress = self.get_json(&url, &[("apiKey", self.ethplorer_key.as_str())]).await?;
+                let mut balances = vec![ExternalTokenBalance {
+                    symbol: "ETH".to_string(),
+                    name: "Ethereum".to_string(),
+                    quantity: body.eth.balance,
+                    price_usd: ethplorer_rate(&body.eth.price),
+                }];
+                for token in body.tokens {
+                    let Some(symbol) = token.token_info.symbol.filter(|s| !s.trim().is_empty()) else {
+                        continue;
+                    };
+                    let quantity = token.balance / 10f64.powi(ethplorer_decimals(&token.token_info.decimals) as i32);
+                    balances.push(ExternalTokenBalance {
+                        name: token.token_info.name.unwrap_or_else(|| symbol.clone()),
+                        symbol: symbol.trim().to_uppercase(),
+                        quantity,
+                        price_usd: ethplorer_rate(&token.token_info.price),
+                    });
+                }
+                Ok(balances)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ethplorer_response() {
+        let body: EthplorerAddress = serde_json::from_str(
+            r#"{"address":"0xabc","ETH":{"balance":1.5,"price":{"rate":3000.5}},
+                "tokens":[{"tokenInfo":{"symbol":"USDC","name":"USD Coin","decimals":"6","price":{"rate":1.0}},"balance":2500000000},
+                          {"tokenInfo":{"symbol":"SPAM","decimals":"18","price":false},"balance":1e18}]}"#,
+        )
+        .unwrap();
+        assert_eq!(body.eth.balance, 1.5);
+        assert_eq!(ethplorer_rate(&body.eth.price), Some(3000.5));
+        assert_eq!(ethplorer_decimals(&body.tokens[0].token_info.decimals), 6);
+        assert_eq!(ethplorer_rate(&body.tokens[1].token_info.price), None);
+    }
+}
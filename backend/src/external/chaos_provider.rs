@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::external::price_provider::{
+    ExternalCorporateAction, ExternalIntradayBar, ExternalPricePoint, ExternalQuote, ExternalTickerMatch,
+    IntradayInterval, PriceProvider, PriceProviderError, ProviderHealth, ProviderQuota,
+};
+use crate::services::chaos::Chaos;
+
+/// Wraps the configured provider and injects the faults of [`Chaos`] before
+/// each upstream call. Only installed when `CHAOS_ENABLED=true`.
+pub struct ChaosPriceProvider {
+    inner: Arc<dyn PriceProvider>,
+    chaos: Arc<Chaos>,
+}
+
+impl ChaosPriceProvider {
+    pub fn new(inner: Arc<dyn PriceProvider>, chaos: Arc<Chaos>) -> Self {
+        Self { inner, chaos }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for ChaosPriceProvider {
+    async fn fetch_daily_history(&self, ticker: &str, days: u32) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+        self.chaos.before_provider_call().await?;
+        self.inner.fetch_daily_history(ticker, days).await
+    }
+
+    async fn search_ticker_by_keyword(&self, keyword: &str) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
+        self.chaos.before_provider_call().await?;
+        self.inner.search_ticker_by_keyword(keyword).await
+    }
+
+    async fn fetch_quote(&self, ticker: &str) -> Result<ExternalQuote, PriceProviderError> {
+        self.chaos.before_provider_call().await?;
+        self.inner.fetch_quote(ticker).await
+    }
+
+    async fn fetch_intraday(
+        &self,
+        ticker: &str,
+        interval: IntradayInterval,
+    ) -> Result<Vec<ExternalIntradayBar>, PriceProviderError> {
+        self.chaos.before_provider_call().await?;
+        self.inner.fetch_intraday(ticker, interval).await
+    }
+
+    async fn fetch_corporate_actions(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalCorporateAction>, PriceProviderError> {
+        self.chaos.before_provider_call().await?;
+        self.inner.fetch_corporate_actions(ticker, days).await
+    }
+
+    fn health(&self) -> Vec<ProviderHealth> {
+        self.inner.health()
+    }
+
+    async fn probe_quota(&self) -> Result<Option<ProviderQuota>, PriceProviderError> {
+        self.chaos.before_provider_call().await?;
+        self.inner.probe_quota().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::chaos::ChaosConfig;
+
+    struct EmptyProvider;
+
+    #[async_trait]
+    impl PriceProvider for EmptyProvider {
+        async fn fetch_daily_history(&self, _: &str, _: u32) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+            Ok(Vec::new())
+        }
+
+        async fn search_ticker_by_keyword(&self, _: &str) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_injects_configured_faults() {
+        let chaos = Arc::new(Chaos::new(ChaosConfig { seed: Some(3), ..Default::default() }));
+        let provider = ChaosPriceProvider::new(Arc::new(EmptyProvider), chaos.clone());
+        assert!(provider.fetch_daily_history("AAPL", 30).await.unwrap().is_empty());
+
+        chaos.update(ChaosConfig { provider_rate_limit_rate: 1.0, ..Default::default() }).unwrap();
+        assert!(matches!(provider.fetch_daily_history("AAPL", 30).await, Err(PriceProviderError::RateLimited)));
+
+        chaos.update(ChaosConfig { provider_failure_rate: 1.0, ..Default::default() }).unwrap();
+        assert!(matches!(provider.search_ticker_by_keyword("apple").await, Err(PriceProviderError::Network(_))));
+    }
+}
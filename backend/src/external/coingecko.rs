@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::external::price_provider::{
+    ExternalCorporateAction, ExternalIntradayBar, ExternalPricePoint, ExternalQuote, ExternalTickerMatch, IntradayInterval, PriceProvider,
+    PriceProviderError, ProviderHealth, ProviderQuota,
+};
+use crate::models::TickerType;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDate};
+use serde::Deserialize;
+
+const BASE_URL: &str = "https://api.coingecko.com/api/v3";
+
+/// Keyless and demo access only serve the last 365 days of history
+const MAX_HISTORY_DAYS: u32 = 365;
+
+/// CoinGecko ids of coins commonly held, so most lookups skip the search call
+const KNOWN_COINS: &[(&str, &str)] = &[
+    ("BTC", "bitcoin"),
+    ("ETH", "ethereum"),
+    ("USDT", "tether"),
+    ("USDC", "usd-coin"),
+    ("BNB", "binancecoin"),
+    ("SOL", "solana"),
+    ("XRP", "ripple"),
+    ("ADA", "cardano"),
+    ("DOGE", "dogecoin"),
+    ("AVAX", "avalanche-2"),
+    ("DOT", "polkadot"),
+    ("LINK", "chainlink"),
+    ("MATIC", "matic-network"),
+    ("LTC", "litecoin"),
+    ("BCH", "bitcoin-cash"),
+    ("XLM", "stellar"),
+    ("ATOM", "cosmos"),
+    ("UNI", "uniswap"),
+];
+
+/// CoinGecko provider - USD prices of crypto assets, for holdings tickered
+/// `<SYMBOL>-USD`. Works without a key; `COINGECKO_API_KEY` (a demo key) raises
+/// the rate limit.
+pub struct CoinGeckoProvider {
+    client: reqwest::Client,
+    api_key: Option<String>,
+    /// Symbol -> coin id resolved through search
+    coin_ids: Mutex<HashMap<String, String>>,
+}
+
+impl CoinGeckoProvider {
+    pub fn from_env() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: std::env::var("COINGECKO_API_KEY").ok().filter(|k| !k.is_empty()),
+            coin_ids: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn get_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, PriceProviderError> {
+        let mut request = self.client.get(format!("{}{}", BASE_URL, path)).query(query);
+        if let Some(key) = &self.api_key {
+            request = request.header("x-cg-demo-api-key", key);
+        }
+        let resp = request
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(match status.as_u16() {
+                404 => PriceProviderError::NotFound,
+                429 => PriceProviderError::RateLimited,
+                _ => PriceProviderError::BadResponse(format!("HTTP {}", status)),
+            });
+        }
+
+        resp.json().await.map_err(|e| PriceProviderError::Parse(e.to_string()))
+    }
+
+    /// CoinGecko id of a `<SYMBOL>-USD` ticker
+    async fn coin_id(&self, ticker: &str) -> Result<String, PriceProviderError> {
+        let symbol = ticker.strip_suffix("-USD").unwrap_or(ticker).to_uppercase();
+        if let Some((_, id)) = KNOWN_COINS.iter().find(|(s, _)| *s == symbol) {
+            return Ok(id.to_string());
+        }
+        if let Some(id) = self.coin_ids.lock().unwrap().get(&symbol) {
+            return Ok(id.clone());
+        }
+
+        let search: CoinGeckoSearch = self.get_json("/search", &[("query", symbol.clone())]).await?;
+        let id = best_symbol_match(&search.coins, &symbol).ok_or(PriceProviderError::NotFound)?;
+        self.coin_ids.lock().unwrap().insert(symbol, id.clone());
+        Ok(id)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinGeckoSearch {
+    coins: Vec<CoinGeckoCoin>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinGeckoCoin {
+    id: String,
+    name: String,
+    symbol: String,
+    market_cap_rank: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CoinGeckoMarketChart {
+    /// `[unix millis, price]` pairs
+    prices: Vec<(f64, f64)>,
+}
+
+/// Id of the largest coin trading under `symbol`; symbols aren't unique
+fn best_symbol_match(coins: &[CoinGeckoCoin], symbol: &str) -> Option<String> {
+    coins
+        .iter()
+        .filter(|c| c.symbol.eq_ignore_ascii_case(symbol))
+        .min_by_key(|c| c.market_cap_rank.unwrap_or(u32::MAX))
+        .map(|c| c.id.clone())
+}
+
+/// One close per UTC day. Daily series end with a point for the current
+/// moment, which is kept as today's close.
+fn daily_points(prices: Vec<(f64, f64)>) -> Vec<ExternalPricePoint> {
+    let mut by_date: Vec<(NaiveDate, f64)> = Vec::new();
+    for (millis, price) in prices {
+        let Some(date) = DateTime::from_timestamp_millis(millis as i64).map(|t| t.date_naive()) else {
+            continue;
+        };
+        match by_date.last_mut() {
+            Some(last) if last.0 == date => last.1 = price,
+            _ => by_date.push((date, price)),
+        }
+    }
+    by_date
+        .into_iter()
+        .filter_map(|(date, price)| {
+            let close = BigDecimal::try_from(price).ok()?;
+            Some(ExternalPricePoint { date, close, adjusted_close: None })
+        })
+        .collect()
+}
+
+/// Bars of `interval` built from a price series. A one-day chart has a point
+/// every five minutes and no volumes, so finer bars hold a single price.
+fn intraday_bars(prices: Vec<(f64, f64)>, interval: IntradayInterval) -> Vec<ExternalIntradayBar> {
+    let bar_millis = interval.minutes() * 60_000;
+    let mut bars: Vec<ExternalIntradayBar> = Vec::new();
+    for (millis, price) in prices {
+        let start = (millis as i64).div_euclid(bar_millis) * bar_millis;
+        let Some(timestamp) = DateTime::from_timestamp_millis(start) else {
+            continue;
+        };
+        match bars.last_mut() {
+            Some(bar) if bar.timestamp == timestamp => {
+                bar.high = bar.high.max(price);
+                bar.low = bar.low.min(price);
+                bar.close = price;
+            }
+            _ => bars.push(ExternalIntradayBar {
+                timestamp,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: None,
+            }),
+        }
+    }
+    bars
+}
+
+#[async_trait]
+impl PriceProvider for CoinGeckoProvider {
+    async fn fetch_daily_history(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+        let id = self.coin_id(ticker).await?;
+        let query = [
+            ("vs_currency", "usd".to_string()),
+            ("days", days.min(MAX_HISTORY_DAYS).to_string()),
+            ("interval", "daily".to_string()),
+        ];
+        let chart: CoinGeckoMarketChart = self.get_json(&format!("/coins/{}/market_chart", id), &query).await?;
+
+        let mut points = daily_points(chart.prices);
+        if points.is_empty() {
+            return Err(PriceProviderError::NotFound);
+        }
+        let excess = points.len().saturating_sub(days as usize);
+        points.drain(..excess);
+
+        Ok(points)
+    }
+
+    /// Crypto trades around the clock; the session is the last 24 hours
+    async fn fetch_intraday(
+        &self,
+        ticker: &str,
+        interval: IntradayInterval,
+    ) -> Result<Vec<ExternalIntradayBar>, PriceProviderError> {
+        let id = self.coin_id(ticker).await?;
+        let query = [("vs_currency", "usd".to_string()), ("days", "1".to_string())];
+        let chart: CoinGeckoMarketChart = self.get_json(&format!("/coins/{}/market_chart", id), &query).await?;
+
+        let bars = intraday_bars(chart.prices, interval);
+        if bars.is_empty() {
+            return Err(PriceProviderError::NotFound);
+        }
+        Ok(bars)
+    }
+
+    async fn search_ticker_by_keyword(
+        &self,
+        keyword: &str,
+    ) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
+        let search: CoinGeckoSearch = self.get_json("/search", &[("query", keyword.to_string())]).await?;
+
+        let matches = search
+            .coins
+            .into_iter()
+            .take(10)
+            .enumerate()
+            .map(|(idx, c)| ExternalTickerMatch {
+                symbol: format!("{}-USD", c.symbol.to_uppercase()),
+                name: c.name,
+                _type: "Crypto".to_string(),
+                region: "Global".to_string(),
+                currency: "USD".to_string(),
+                exchange: None,
+                // Calculate match score based on position (first result = highest score)
+                match_score: 1.0 - (idx as f64 * 0.05),
+            })
+            .collect();
+
+        Ok(matches)
+    }
+}
+
+/// Sends crypto tickers to CoinGecko and everything else to the configured
+/// stock provider, so crypto holdings get prices alongside equities
+pub struct CryptoRoutingProvider {
+    equities: Arc<dyn PriceProvider>,
+    crypto: CoinGeckoProvider,
+}
+
+impl CryptoRoutingProvider {
+    pub fn new(equities: Arc<dyn PriceProvider>, crypto: CoinGeckoProvider) -> Self {
+        Self { equities, crypto }
+    }
+
+    fn route(&self, ticker: &str) -> &dyn PriceProvider {
+        match TickerType::of_ticker(ticker) {
+            TickerType::Crypto => &self.crypto,
+            TickerType::Equity => self.equities.as_ref(),
+        }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for CryptoRoutingProvider {
+    async fn fetch_daily_history(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+        self.route(ticker).fetch_daily_history(ticker, days).await
+    }
+
+    /// Stock matches first, then coins; a failing coin search doesn't hide stock results
+    async fn search_ticker_by_keyword(
+        &self,
+        keyword: &str,
+    ) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
+        let mut matches = self.equities.search_ticker_by_keyword(keyword).await?;
+        if let Ok(coins) = self.crypto.search_ticker_by_keyword(keyword).await {
+            matches.extend(coins);
+        }
+        Ok(matches)
+    }
+
+    async fn fetch_quote(&self, ticker: &str) -> Result<ExternalQuote, PriceProviderError> {
+        self.route(ticker).fetch_quote(ticker).await
+    }
+
+    async fn fetch_intraday(
+        &self,
+        ticker: &str,
+        interval: IntradayInterval,
+    ) -> Result<Vec<ExternalIntradayBar>, PriceProviderError> {
+        self.route(ticker).fetch_intraday(ticker, interval).await
+    }
+
+    async fn fetch_corporate_actions(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalCorporateAction>, PriceProviderError> {
+        self.route(ticker).fetch_corporate_actions(ticker, days).await
+    }
+
+    fn health(&self) -> Vec<ProviderHealth> {
+        self.equities.health()
+    }
+
+    async fn probe_quota(&self) -> Result<Option<ProviderQuota>, PriceProviderError> {
+        self.equities.probe_quota().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::ToPrimitive;
+
+    #[test]
+    fn test_daily_points_keep_last_price_per_day() {
+        let chart: CoinGeckoMarketChart = serde_json::from_str(
+            r#"{"prices":[[1772755200000,87012.5],[1772841600000,88100.0],[1772887512000,88420.25]],
+                "market_caps":[],"total_volumes":[]}"#,
+        )
+        .unwrap();
+
+        let points = daily_points(chart.prices);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1].date, NaiveDate::from_ymd_opt(2026, 3, 7).unwrap());
+        // The intraday point replaces the midnight one as today's close
+        assert!((points[1].close.to_f64().unwrap() - 88420.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_intraday_bars_bucket_prices() {
+        // 2026-03-06 00:00, 00:05, 00:10 and 00:15 UTC
+        let prices = vec![
+            (1772755200000.0, 100.0),
+            (1772755500000.0, 104.0),
+            (1772755800000.0, 98.0),
+            (1772756100000.0, 101.0),
+        ];
+        let bars = intraday_bars(prices, IntradayInterval::FifteenMinutes);
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].timestamp.to_rfc3339(), "2026-03-06T00:00:00+00:00");
+        assert_eq!((bars[0].open, bars[0].high, bars[0].low, bars[0].close), (100.0, 104.0, 98.0, 98.0));
+        assert_eq!((bars[1].open, bars[1].close), (101.0, 101.0));
+        assert_eq!(bars[1].volume, None);
+    }
+
+    #[test]
+    fn test_symbol_search_prefers_largest_coin() {
+        let coins = vec![
+            CoinGeckoCoin { id: "pepe-clone".into(), name: "Pepe Clone".into(), symbol: "PEPE".into(), market_cap_rank: None },
+            CoinGeckoCoin { id: "pepe".into(), name: "Pepe".into(), symbol: "pepe".into(), market_cap_rank: Some(40) },
+            CoinGeckoCoin { id: "pepecoin".into(), name: "PepeCoin".into(), symbol: "PEPECOIN".into(), market_cap_rank: Some(900) },
+        ];
+        assert_eq!(best_symbol_match(&coins, "PEPE").as_deref(), Some("pepe"));
+        assert_eq!(best_symbol_match(&coins, "DOGE"), None);
+    }
+}
@@ -0,0 +1,427 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::external::price_provider::{
+    ExternalCorporateAction, ExternalIntradayBar, ExternalPricePoint, ExternalQuote, ExternalTickerMatch,
+    IntradayInterval, PriceProvider, PriceProviderError, ProviderHealth, ProviderQuota,
+};
+use crate::external::provider_factory;
+use crate::services::failure_cache::{FailureCache, FailureType};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::{debug, info, warn};
+
+const DEFAULT_CHAIN: &str = "twelvedata,alphavantage,yahoo";
+
+/// Consecutive network or server errors that open a provider's circuit
+const FAILURE_THRESHOLD: u32 = 3;
+/// How long a circuit stays open after repeated errors
+const ERROR_COOLDOWN: Duration = Duration::from_secs(300);
+/// How long a circuit stays open after the provider reports a rate limit
+const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Provider names in `PRICE_PROVIDER_CHAIN`, lowercased, in the order given
+pub fn configured_chain() -> Vec<String> {
+    std::env::var("PRICE_PROVIDER_CHAIN")
+        .unwrap_or_else(|_| DEFAULT_CHAIN.to_string())
+        .split(',')
+        .map(|n| n.trim().to_lowercase())
+        .filter(|n| !n.is_empty())
+        .collect()
+}
+
+/// Per-provider circuit breaker.
+///
+/// Closed while the provider answers. A rate limit opens it straight away,
+/// other errors after `FAILURE_THRESHOLD` in a row. Once the cooldown passes the
+/// next call goes through as a trial: success closes the circuit, another
+/// failure opens it again.
+struct CircuitBreaker {
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    last_success_at: Option<DateTime<Utc>>,
+}
+
+impl CircuitBreaker {
+    fn new() -> Self {
+        Self { state: Mutex::new(BreakerState::default()) }
+    }
+
+    fn allows(&self, now: Instant) -> bool {
+        self.state.lock().unwrap().open_until.is_none_or(|until| now >= until)
+    }
+
+    fn record_success(&self) {
+        *self.state.lock().unwrap() = BreakerState {
+            last_success_at: Some(Utc::now()),
+            ..BreakerState::default()
+        };
+    }
+
+    fn health(&self, name: &str, now: Instant) -> ProviderHealth {
+        let state = self.state.lock().unwrap();
+        ProviderHealth {
+            name: name.to_string(),
+            circuit_open: state.open_until.is_some_and(|until| now < until),
+            consecutive_failures: state.consecutive_failures,
+            last_success_at: state.last_success_at,
+        }
+    }
+
+    fn record_error(&self, error: &PriceProviderError, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        match error {
+            // A missing ticker says nothing about the provider's health
+            PriceProviderError::NotFound => {}
+            PriceProviderError::RateLimited => {
+                state.consecutive_failures += 1;
+                state.open_until = Some(now + RATE_LIMIT_COOLDOWN);
+            }
+            _ => {
+                state.consecutive_failures += 1;
+                if state.consecutive_failures >= FAILURE_THRESHOLD {
+                    state.open_until = Some(now + ERROR_COOLDOWN);
+                }
+            }
+        }
+    }
+}
+
+struct ChainMember {
+    name: String,
+    provider: Box<dyn PriceProvider>,
+    breaker: CircuitBreaker,
+}
+
+/// CompositePriceProvider tries an ordered chain of providers until one answers.
+///
+/// Strategy:
+/// 1. Skip providers whose circuit is open, and providers the failure cache
+///    remembers as not carrying the ticker (keyed `"<provider>:<ticker>"`)
+/// 2. Fall through to the next provider on any error
+/// 3. When every provider misses the ticker, report `NotFound`; when any was
+///    rate limited or has an open circuit, report `RateLimited` so callers
+///    retry later rather than caching the ticker as missing
+pub struct CompositePriceProvider {
+    chain: Vec<ChainMember>,
+    failure_cache: FailureCache,
+}
+
+impl CompositePriceProvider {
+    pub fn new(providers: Vec<(String, Box<dyn PriceProvider>)>, failure_cache: FailureCache) -> Self {
+        let chain = providers
+            .into_iter()
+            .map(|(name, provider)| ChainMember { name, provider, breaker: CircuitBreaker::new() })
+            .collect();
+        Self { chain, failure_cache }
+    }
+
+    /// Chain from the comma-separated provider names in `PRICE_PROVIDER_CHAIN`,
+    /// tried in the order given, each configured from its own settings
+    pub fn from_env(failure_cache: FailureCache) -> Result<Self, PriceProviderError> {
+        let mut providers = Vec::new();
+        for name in configured_chain() {
+            let provider = provider_factory::build_single(&name).map_err(|e| match e {
+                PriceProviderError::BadResponse(msg) => {
+                    PriceProviderError::BadResponse(format!("{} in PRICE_PROVIDER_CHAIN", msg))
+                }
+                other => other,
+            })?;
+            providers.push((name, provider));
+        }
+        if providers.is_empty() {
+            return Err(PriceProviderError::BadResponse("PRICE_PROVIDER_CHAIN is empty".into()));
+        }
+        Ok(Self::new(providers, failure_cache))
+    }
+
+    /// Provider names in the order they're tried
+    pub fn order(&self) -> Vec<&str> {
+        self.chain.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    fn not_found_key(member: &ChainMember, ticker: &str) -> String {
+        format!("{}:{}", member.name, ticker)
+    }
+
+    /// Reason to skip a provider for `ticker` right now, if any
+    fn skip_reason(&self, member: &ChainMember, ticker: Option<&str>) -> Option<PriceProviderError> {
+        if !member.breaker.allows(Instant::now()) {
+            debug!("Circuit open for {}, skipping", member.name);
+            return Some(PriceProviderError::RateLimited);
+        }
+        let ticker = ticker?;
+        let cached = self.failure_cache.is_failed(&Self::not_found_key(member, ticker));
+        if cached.is_some_and(|info| info.error_type == FailureType::NotFound) {
+            debug!("{} is known not to carry {}, skipping", member.name, ticker);
+            return Some(PriceProviderError::NotFound);
+        }
+        None
+    }
+
+    /// Update the breaker and failure cache with one provider's outcome
+    fn settle<T>(
+        &self,
+        member: &ChainMember,
+        ticker: Option<&str>,
+        result: Result<T, PriceProviderError>,
+    ) -> Result<T, PriceProviderError> {
+        match &result {
+            Ok(_) => member.breaker.record_success(),
+            Err(e) => {
+                member.breaker.record_error(e, Instant::now());
+                if let (PriceProviderError::NotFound, Some(ticker)) = (e, ticker) {
+                    self.failure_cache.record_failure(&Self::not_found_key(member, ticker), FailureType::NotFound);
+                }
+                warn!("{} failed{}: {}", member.name, ticker.map(|t| format!(" for {}", t)).unwrap_or_default(), e);
+            }
+        }
+        result
+    }
+}
+
+/// Error reported once the whole chain has been tried. Open circuits count as
+/// rate limits, known misses as `NotFound`.
+fn exhausted(errors: Vec<PriceProviderError>) -> PriceProviderError {
+    if errors.iter().any(|e| matches!(e, PriceProviderError::RateLimited)) {
+        return PriceProviderError::RateLimited;
+    }
+    errors
+        .into_iter()
+        .rfind(|e| !matches!(e, PriceProviderError::NotFound))
+        .unwrap_or(PriceProviderError::NotFound)
+}
+
+#[async_trait]
+impl PriceProvider for CompositePriceProvider {
+    async fn fetch_daily_history(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+        let mut errors = Vec::new();
+        for member in &self.chain {
+            if let Some(skipped) = self.skip_reason(member, Some(ticker)) {
+                errors.push(skipped);
+                continue;
+            }
+            let result = member.provider.fetch_daily_history(ticker, days).await;
+            match self.settle(member, Some(ticker), result) {
+                Ok(points) => {
+                    info!("Fetched {} history from {}", ticker, member.name);
+                    return Ok(points);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        Err(exhausted(errors))
+    }
+
+    async fn search_ticker_by_keyword(
+        &self,
+        keyword: &str,
+    ) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
+        let mut errors = Vec::new();
+        for member in &self.chain {
+            if let Some(skipped) = self.skip_reason(member, None) {
+                errors.push(skipped);
+                continue;
+            }
+            let result = member.provider.search_ticker_by_keyword(keyword).await;
+            match self.settle(member, None, result) {
+                Ok(matches) if !matches.is_empty() => return Ok(matches),
+                Ok(_) => errors.push(PriceProviderError::NotFound),
+                Err(e) => errors.push(e),
+            }
+        }
+        // A search nobody could answer is an empty result, not an error
+        match exhausted(errors) {
+            PriceProviderError::NotFound => Ok(Vec::new()),
+            e => Err(e),
+        }
+    }
+
+    async fn fetch_quote(&self, ticker: &str) -> Result<ExternalQuote, PriceProviderError> {
+        let mut errors = Vec::new();
+        for member in &self.chain {
+            if let Some(skipped) = self.skip_reason(member, Some(ticker)) {
+                errors.push(skipped);
+                continue;
+            }
+            let result = member.provider.fetch_quote(ticker).await;
+            match self.settle(member, Some(ticker), result) {
+                Ok(quote) => return Ok(quote),
+                Err(e) => errors.push(e),
+            }
+        }
+        Err(exhausted(errors))
+    }
+
+    async fn fetch_intraday(
+        &self,
+        ticker: &str,
+        interval: IntradayInterval,
+    ) -> Result<Vec<ExternalIntradayBar>, PriceProviderError> {
+        let mut errors = Vec::new();
+        for member in &self.chain {
+            if let Some(skipped) = self.skip_reason(member, Some(ticker)) {
+                errors.push(skipped);
+                continue;
+            }
+            match member.provider.fetch_intraday(ticker, interval).await {
+                // Providers without intraday bars are passed over without counting against them
+                Err(PriceProviderError::Unsupported(_)) => continue,
+                result => match self.settle(member, Some(ticker), result) {
+                    Ok(bars) => return Ok(bars),
+                    Err(e) => errors.push(e),
+                },
+            }
+        }
+        if errors.is_empty() {
+            return Err(PriceProviderError::Unsupported("intraday prices".to_string()));
+        }
+        Err(exhausted(errors))
+    }
+
+    async fn fetch_corporate_actions(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalCorporateAction>, PriceProviderError> {
+        let mut errors = Vec::new();
+        for member in &self.chain {
+            if let Some(skipped) = self.skip_reason(member, Some(ticker)) {
+                errors.push(skipped);
+                continue;
+            }
+            match member.provider.fetch_corporate_actions(ticker, days).await {
+                Err(PriceProviderError::Unsupported(_)) => continue,
+                result => match self.settle(member, Some(ticker), result) {
+                    Ok(actions) => return Ok(actions),
+                    Err(e) => errors.push(e),
+                },
+            }
+        }
+        if errors.is_empty() {
+            return Err(PriceProviderError::Unsupported("corporate actions".to_string()));
+        }
+        Err(exhausted(errors))
+    }
+
+    fn health(&self) -> Vec<ProviderHealth> {
+        let now = Instant::now();
+        self.chain.iter().map(|m| m.breaker.health(&m.name, now)).collect()
+    }
+
+    /// Quota of the first provider in the chain, the one that takes most requests
+    async fn probe_quota(&self) -> Result<Option<ProviderQuota>, PriceProviderError> {
+        match self.chain.first() {
+            Some(member) => member.provider.probe_quota().await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use chrono::NaiveDate;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Answers every call with the same outcome and counts the calls
+    struct StubProvider {
+        outcome: fn() -> Result<Vec<ExternalPricePoint>, PriceProviderError>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl PriceProvider for StubProvider {
+        async fn fetch_daily_history(&self, _: &str, _: u32) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            (self.outcome)()
+        }
+
+        async fn search_ticker_by_keyword(&self, _: &str) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn stub(
+        name: &str,
+        outcome: fn() -> Result<Vec<ExternalPricePoint>, PriceProviderError>,
+    ) -> ((String, Box<dyn PriceProvider>), Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = StubProvider { outcome, calls: calls.clone() };
+        ((name.to_string(), Box::new(provider)), calls)
+    }
+
+    fn one_point() -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+        Ok(vec![ExternalPricePoint {
+            date: NaiveDate::from_ymd_opt(2026, 3, 6).unwrap(),
+            close: BigDecimal::from(100),
+            adjusted_close: None,
+        }])
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_falls_back_and_opens_circuit() {
+        let (limited, limited_calls) = stub("twelvedata", || Err(PriceProviderError::RateLimited));
+        let (backup, backup_calls) = stub("yahoo", one_point);
+        let composite = CompositePriceProvider::new(vec![limited, backup], FailureCache::new());
+
+        assert!(composite.fetch_daily_history("AAPL", 5).await.is_ok());
+        assert!(composite.fetch_daily_history("MSFT", 5).await.is_ok());
+
+        // The rate-limited provider is skipped while its circuit is open
+        assert_eq!(limited_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(backup_calls.load(Ordering::SeqCst), 2);
+
+        let health = composite.health();
+        assert!(health[0].circuit_open);
+        assert_eq!(health[0].consecutive_failures, 1);
+        assert_eq!(health[0].last_success_at, None);
+        assert!(!health[1].circuit_open);
+        assert!(health[1].last_success_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_not_found_is_cached_per_provider() {
+        let (missing, missing_calls) = stub("twelvedata", || Err(PriceProviderError::NotFound));
+        let (also_missing, _) = stub("yahoo", || Err(PriceProviderError::NotFound));
+        let cache = FailureCache::new();
+        let composite = CompositePriceProvider::new(vec![missing, also_missing], cache.clone());
+
+        let result = composite.fetch_daily_history("XYZ.TO", 5).await;
+        assert!(matches!(result, Err(PriceProviderError::NotFound)));
+        assert!(cache.is_failed("twelvedata:XYZ.TO").is_some());
+
+        // Known misses are skipped without another request
+        let result = composite.fetch_daily_history("XYZ.TO", 5).await;
+        assert!(matches!(result, Err(PriceProviderError::NotFound)));
+        assert_eq!(missing_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_breaker_opens_after_repeated_errors_and_half_opens() {
+        let breaker = CircuitBreaker::new();
+        let start = Instant::now();
+        for _ in 0..FAILURE_THRESHOLD - 1 {
+            breaker.record_error(&PriceProviderError::Network("timeout".into()), start);
+        }
+        assert!(breaker.allows(start));
+
+        breaker.record_error(&PriceProviderError::Network("timeout".into()), start);
+        assert!(!breaker.allows(start));
+        assert!(breaker.allows(start + ERROR_COOLDOWN));
+
+        breaker.record_success();
+        assert!(breaker.allows(start));
+    }
+}
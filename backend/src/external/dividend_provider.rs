@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::external::price_provider::PriceProviderError;
+
+/// The next (or most recent) dividend of a ticker as reported by a provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalDividendEvent {
+    pub ex_date: NaiveDate,
+    pub pay_date: Option<NaiveDate>,
+    /// Cash per share; providers announce dates before amounts, so this is
+    /// often the previous payment
+    pub amount: Option<f64>,
+    /// Annual dividend per share
+    pub annual_rate: Option<f64>,
+}
+
+#[async_trait]
+pub trait DividendProvider: Send + Sync {
+    /// `NotFound` for tickers that don't pay dividends
+    async fn fetch_dividend_event(&self, ticker: &str) -> Result<ExternalDividendEvent, PriceProviderError>;
+}
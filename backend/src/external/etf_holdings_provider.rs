@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::external::price_provider::PriceProviderError;
+
+/// One position inside an ETF
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalEtfHolding {
+    /// `None` for positions without a listed symbol (cash, futures, swaps)
+    pub ticker: Option<String>,
+    pub name: String,
+    /// Percent of the fund (0-100)
+    pub weight: f64,
+    pub sector: Option<String>,
+}
+
+/// An ETF's constituents and sector breakdown as reported by a provider or issuer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExternalEtfHoldings {
+    pub holdings: Vec<ExternalEtfHolding>,
+    /// Percent of the fund per sector (0-100); empty when the source only lists holdings
+    pub sector_weights: Vec<(String, f64)>,
+    /// Where the data came from, e.g. "yahoo" or "issuer_file"
+    pub source: String,
+}
+
+#[async_trait]
+pub trait EtfHoldingsProvider: Send + Sync {
+    async fn fetch_etf_holdings(&self, etf: &str) -> Result<ExternalEtfHoldings, PriceProviderError>;
+}
+
+/// Issuer holdings files (`<ETF>.csv` with `ticker,name,weight,sector` columns,
+/// weight in percent) read from a directory, so full constituent lists exported
+/// from issuer sites take precedence over a provider's top holdings. ETFs without
+/// a file go to the fallback provider.
+pub struct IssuerFileEtfHoldingsProvider {
+    dir: PathBuf,
+    fallback: Box<dyn EtfHoldingsProvider>,
+}
+
+impl IssuerFileEtfHoldingsProvider {
+    pub fn new(dir: PathBuf, fallback: Box<dyn EtfHoldingsProvider>) -> Self {
+        Self { dir, fallback }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct IssuerFileRow {
+    ticker: Option<String>,
+    name: String,
+    weight: f64,
+    sector: Option<String>,
+}
+
+/// Parse an issuer holdings file; blank tickers and sectors become `None`
+pub fn parse_issuer_file(text: &str) -> Result<ExternalEtfHoldings, PriceProviderError> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(text.as_bytes());
+    let holdings = reader
+        .deserialize::<IssuerFileRow>()
+        .map(|row| {
+            let row = row.map_err(|e| PriceProviderError::Parse(e.to_string()))?;
+            Ok(ExternalEtfHolding {
+                ticker: row.ticker.filter(|t| !t.is_empty()).map(|t| t.to_uppercase()),
+                name: row.name,
+                weight: row.weight,
+                sector: row.sector.filter(|s| !s.is_empty()),
+            })
+        })
+        .collect::<Result<Vec<_>, PriceProviderError>>()?;
+
+    Ok(ExternalEtfHoldings {
+        holdings,
+        sector_weights: vec![],
+        source: "issuer_file".to_string(),
+    })
+}
+
+#[async_trait]
+impl EtfHoldingsProvider for IssuerFileEtfHoldingsProvider {
+    async fn fetch_etf_holdings(&self, etf: &str) -> Result<ExternalEtfHoldings, PriceProviderError> {
+        let path = self.dir.join(format!("{}.csv", etf.to_uppercase()));
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => parse_issuer_file(&text)
+                .map_err(|e| PriceProviderError::Parse(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => self.fallback.fetch_etf_holdings(etf).await,
+            Err(e) => Err(PriceProviderError::BadResponse(format!("cannot read {}: {}", path.display(), e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_issuer_file() {
+        let holdings = parse_issuer_file(
+            "ticker,name,weight,sector\n\
+             aapl,Apple Inc.,7.1,Information Technology\n\
+             , US Dollar Cash ,0.2,\n",
+        )
+        .unwrap();
+
+        assert_eq!(holdings.source, "issuer_file");
+        assert_eq!(holdings.holdings.len(), 2);
+        assert_eq!(holdings.holdings[0].ticker.as_deref(), Some("AAPL"));
+        assert_eq!(holdings.holdings[0].sector.as_deref(), Some("Information Technology"));
+        assert_eq!(holdings.holdings[1].ticker, None);
+        assert_eq!(holdings.holdings[1].name, "US Dollar Cash");
+        assert_eq!(holdings.holdings[1].sector, None);
+
+        assert!(parse_issuer_file("ticker,name,weight\nAAPL,Apple,abc\n").is_err());
+    }
+}
@@ -0,0 +1,505 @@
+use crate::external::fundamentals_provider::{
+    ExternalDividend, ExternalEarnings, ExternalEarningsDate, ExternalFundamentals, FundamentalsProvider,
+};
+use crate::external::price_provider::{
+    ExternalPricePoint, ExternalQuote, ExternalTickerMatch, PriceProvider, PriceProviderError,
+};
+use crate::external::provider_factory::ProviderSettings;
+use crate::services::clock;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate};
+use serde::Deserialize;
+
+const BASE_URL: &str = "https://finnhub.io/api/v1";
+
+/// Finnhub provider - daily candles and quotes plus basic financials, reported
+/// earnings and dividend history, so one key covers prices and fundamentals
+pub struct FinnhubProvider {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+}
+
+impl FinnhubProvider {
+    pub fn from_env() -> Result<Self, PriceProviderError> {
+        Self::from_settings(&ProviderSettings::from_env("finnhub"))
+    }
+
+    pub fn from_settings(settings: &ProviderSettings) -> Result<Self, PriceProviderError> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            api_key: settings.require_api_key("FINNHUB_API_KEY")?,
+            base_url: settings.base_url_or(BASE_URL),
+        })
+    }
+
+    /// GET a Finnhub endpoint and decode the body
+    async fn get_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, PriceProviderError> {
+        let resp = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .header("X-Finnhub-Token", &self.api_key)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            // 403 means the endpoint isn't included in the plan, not a missing ticker
+            let body: Option<FinnhubError> = resp.json().await.ok();
+            return Err(match status.as_u16() {
+                404 => PriceProviderError::NotFound,
+                429 => PriceProviderError::RateLimited,
+                _ => PriceProviderError::BadResponse(
+                    body.map(|b| b.error).unwrap_or_else(|| format!("HTTP {}", status)),
+                ),
+            });
+        }
+
+        resp.json()
+            .await
+            .map_err(|e| PriceProviderError::Parse(e.to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FinnhubError {
+    error: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FinnhubCandles {
+    /// "ok" or "no_data"
+    s: String,
+    #[serde(default)]
+    c: Vec<f64>,
+    /// Unix seconds
+    #[serde(default)]
+    t: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FinnhubQuote {
+    /// Current price
+    c: f64,
+    /// Open
+    o: f64,
+    /// Previous close
+    pc: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct FinnhubSearchResponse {
+    #[serde(default)]
+    result: Vec<FinnhubSymbol>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FinnhubSymbol {
+    symbol: String,
+    description: String,
+    #[serde(rename = "type")]
+    symbol_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FinnhubMetricResponse {
+    #[serde(default)]
+    metric: FinnhubMetrics,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FinnhubMetrics {
+    #[serde(rename = "peTTM")]
+    pe_ttm: Option<f64>,
+    #[serde(rename = "peBasicExclExtraTTM")]
+    pe_basic_excl_extra_ttm: Option<f64>,
+    pb_quarterly: Option<f64>,
+    pb_annual: Option<f64>,
+    /// Millions of dollars
+    market_capitalization: Option<f64>,
+    #[serde(rename = "roeTTM")]
+    roe_ttm: Option<f64>,
+    #[serde(rename = "netProfitMarginTTM")]
+    net_profit_margin_ttm: Option<f64>,
+    #[serde(rename = "totalDebt/totalEquityQuarterly")]
+    debt_to_equity_quarterly: Option<f64>,
+    #[serde(rename = "totalDebt/totalEquityAnnual")]
+    debt_to_equity_annual: Option<f64>,
+    dividend_yield_indicated_annual: Option<f64>,
+    #[serde(rename = "payoutRatioTTM")]
+    payout_ratio_ttm: Option<f64>,
+    #[serde(rename = "epsGrowth5Y")]
+    eps_growth_5y: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FinnhubEarnings {
+    actual: Option<f64>,
+    estimate: Option<f64>,
+    /// Fiscal quarter end, YYYY-MM-DD
+    period: String,
+    quarter: i32,
+    year: i32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FinnhubEarningsCalendar {
+    #[serde(default)]
+    earnings_calendar: Vec<FinnhubEarningsDate>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FinnhubEarningsDate {
+    /// Announcement date, YYYY-MM-DD
+    date: String,
+    hour: Option<String>,
+    quarter: Option<i32>,
+    year: Option<i32>,
+    eps_estimate: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FinnhubDividend {
+    /// Ex-dividend date, YYYY-MM-DD
+    date: String,
+    amount: f64,
+    pay_date: Option<String>,
+    currency: Option<String>,
+}
+
+/// Daily closes from a candle response, oldest first
+fn candle_points(candles: FinnhubCandles) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+    if candles.s == "no_data" {
+        return Err(PriceProviderError::NotFound);
+    }
+    if candles.s != "ok" {
+        return Err(PriceProviderError::BadResponse(format!("candle status {}", candles.s)));
+    }
+    if candles.c.len() != candles.t.len() {
+        return Err(PriceProviderError::Parse(
+            "Timestamp and close arrays have different lengths".into(),
+        ));
+    }
+
+    let mut points: Vec<ExternalPricePoint> = candles
+        .t
+        .iter()
+        .zip(&candles.c)
+        .filter_map(|(&t, &c)| {
+            let date = chrono::DateTime::from_timestamp(t, 0)?.date_naive();
+            let close = BigDecimal::try_from(c).ok()?;
+            Some(ExternalPricePoint { date, close, adjusted_close: None })
+        })
+        .collect();
+    points.sort_by_key(|p| p.date);
+
+    if points.is_empty() {
+        return Err(PriceProviderError::NotFound);
+    }
+    Ok(points)
+}
+
+/// Fundamentals from the basic financials metrics, preferring the most recent
+/// figures Finnhub reports
+fn metric_fundamentals(metrics: FinnhubMetrics) -> ExternalFundamentals {
+    ExternalFundamentals {
+        pe_ratio: metrics.pe_ttm.or(metrics.pe_basic_excl_extra_ttm),
+        pb_ratio: metrics.pb_quarterly.or(metrics.pb_annual),
+        market_cap: metrics.market_capitalization.map(|m| m * 1_000_000.0),
+        roe: metrics.roe_ttm,
+        net_margin: metrics.net_profit_margin_ttm,
+        debt_to_equity: metrics.debt_to_equity_quarterly.or(metrics.debt_to_equity_annual),
+        dividend_yield: metrics.dividend_yield_indicated_annual,
+        payout_ratio: metrics.payout_ratio_ttm,
+        eps_growth_5y: metrics.eps_growth_5y,
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// Reported quarters, most recent first; rows with an unreadable period are skipped
+fn earnings_reports(rows: Vec<FinnhubEarnings>) -> Vec<ExternalEarnings> {
+    let mut reports: Vec<ExternalEarnings> = rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(ExternalEarnings {
+                period_end: parse_date(&row.period)?,
+                fiscal_year: row.year,
+                fiscal_quarter: row.quarter,
+                eps_actual: row.actual,
+                eps_estimate: row.estimate,
+            })
+        })
+        .collect();
+    reports.sort_by_key(|r| std::cmp::Reverse(r.period_end));
+    reports
+}
+
+/// Announced earnings releases oldest first, skipping unreadable dates
+fn earnings_dates(calendar: FinnhubEarningsCalendar) -> Vec<ExternalEarningsDate> {
+    let mut dates: Vec<ExternalEarningsDate> = calendar
+        .earnings_calendar
+        .into_iter()
+        .filter_map(|row| {
+            Some(ExternalEarningsDate {
+                report_date: parse_date(&row.date)?,
+                hour: row.hour.filter(|h| !h.is_empty()),
+                fiscal_year: row.year,
+                fiscal_quarter: row.quarter,
+                eps_estimate: row.eps_estimate,
+            })
+        })
+        .collect();
+    dates.sort_by_key(|d| d.report_date);
+    dates
+}
+
+/// Dividend payments oldest first, skipping zero amounts and unreadable dates
+fn dividend_payments(rows: Vec<FinnhubDividend>) -> Vec<ExternalDividend> {
+    let mut payments: Vec<ExternalDividend> = rows
+        .into_iter()
+        .filter(|row| row.amount > 0.0)
+        .filter_map(|row| {
+            Some(ExternalDividend {
+                ex_date: parse_date(&row.date)?,
+                pay_date: row.pay_date.as_deref().and_then(parse_date),
+                amount: row.amount,
+                currency: row.currency.filter(|c| !c.is_empty()),
+            })
+        })
+        .collect();
+    payments.sort_by_key(|p| p.ex_date);
+    payments
+}
+
+#[async_trait]
+impl PriceProvider for FinnhubProvider {
+    async fn fetch_daily_history(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+        // Calendar span covering `days` trading days, allowing for weekends and holidays
+        let to = clock::now();
+        let from = to - Duration::days(days as i64 * 7 / 5 + 10);
+        let query = [
+            ("symbol", ticker.to_uppercase()),
+            ("resolution", "D".to_string()),
+            ("from", from.timestamp().to_string()),
+            ("to", to.timestamp().to_string()),
+        ];
+
+        let candles: FinnhubCandles = self.get_json("/stock/candle", &query).await?;
+        let mut points = candle_points(candles)?;
+
+        let excess = points.len().saturating_sub(days as usize);
+        points.drain(..excess);
+
+        Ok(points)
+    }
+
+    async fn search_ticker_by_keyword(
+        &self,
+        keyword: &str,
+    ) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
+        let body: FinnhubSearchResponse = self
+            .get_json("/search", &[("q", keyword.to_string())])
+            .await?;
+
+        let matches = body.result
+            .into_iter()
+            .enumerate()
+            .map(|(idx, s)| {
+                // Listings outside the US carry an exchange suffix (e.g. SHOP.TO)
+                let (region, currency) = if s.symbol.ends_with(".TO") || s.symbol.ends_with(".V") {
+                    ("Canada", "CAD")
+                } else {
+                    ("United States", "USD")
+                };
+                ExternalTickerMatch {
+                    symbol: s.symbol,
+                    name: s.description,
+                    _type: s.symbol_type.filter(|t| !t.is_empty()).unwrap_or_else(|| "Stock".to_string()),
+                    region: region.to_string(),
+                    currency: currency.to_string(),
+                    exchange: None,
+                    // Calculate match score based on position (first result = highest score)
+                    match_score: 1.0 - (idx as f64 * 0.05),
+                }
+            })
+            .collect();
+
+        Ok(matches)
+    }
+
+    async fn fetch_quote(&self, ticker: &str) -> Result<ExternalQuote, PriceProviderError> {
+        let quote: FinnhubQuote = self
+            .get_json("/quote", &[("symbol", ticker.to_uppercase())])
+            .await?;
+
+        // Unknown symbols come back as an all-zero quote rather than an error
+        if quote.c == 0.0 && quote.pc == 0.0 {
+            return Err(PriceProviderError::NotFound);
+        }
+
+        Ok(ExternalQuote {
+            price: quote.c,
+            open: Some(quote.o).filter(|&o| o > 0.0),
+            previous_close: quote.pc,
+        })
+    }
+}
+
+#[async_trait]
+impl FundamentalsProvider for FinnhubProvider {
+    async fn fetch_fundamentals(&self, ticker: &str) -> Result<ExternalFundamentals, PriceProviderError> {
+        let query = [("symbol", ticker.to_uppercase()), ("metric", "all".to_string())];
+        let body: FinnhubMetricResponse = self.get_json("/stock/metric", &query).await?;
+
+        let fundamentals = metric_fundamentals(body.metric);
+        if fundamentals == ExternalFundamentals::default() {
+            return Err(PriceProviderError::NotFound);
+        }
+        Ok(fundamentals)
+    }
+
+    async fn fetch_earnings(&self, ticker: &str) -> Result<Vec<ExternalEarnings>, PriceProviderError> {
+        let rows: Vec<FinnhubEarnings> = self
+            .get_json("/stock/earnings", &[("symbol", ticker.to_uppercase())])
+            .await?;
+        Ok(earnings_reports(rows))
+    }
+
+    async fn fetch_upcoming_earnings(
+        &self,
+        ticker: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ExternalEarningsDate>, PriceProviderError> {
+        let query = [
+            ("symbol", ticker.to_uppercase()),
+            ("from", from.to_string()),
+            ("to", to.to_string()),
+        ];
+        let calendar: FinnhubEarningsCalendar = self.get_json("/calendar/earnings", &query).await?;
+        Ok(earnings_dates(calendar))
+    }
+
+    async fn fetch_dividend_history(
+        &self,
+        ticker: &str,
+        from: NaiveDate,
+    ) -> Result<Vec<ExternalDividend>, PriceProviderError> {
+        let query = [
+            ("symbol", ticker.to_uppercase()),
+            ("from", from.to_string()),
+            ("to", clock::today().to_string()),
+        ];
+        let rows: Vec<FinnhubDividend> = self.get_json("/stock/dividend", &query).await?;
+        Ok(dividend_payments(rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::ToPrimitive;
+
+    #[test]
+    fn test_candles_parse_into_points() {
+        let candles: FinnhubCandles = serde_json::from_str(
+            r#"{"c":[185.64,184.25],"h":[188.44,185.88],"l":[183.885,183.43],"o":[187.15,184.22],
+                "s":"ok","t":[1704153600,1704240000],"v":[58414460,58418916]}"#,
+        )
+        .unwrap();
+
+        let points = candle_points(candles).unwrap();
+        let dates: Vec<String> = points.iter().map(|p| p.date.to_string()).collect();
+        assert_eq!(dates, vec!["2024-01-02", "2024-01-03"]);
+        assert!((points[1].close.to_f64().unwrap() - 184.25).abs() < 1e-9);
+
+        let empty: FinnhubCandles = serde_json::from_str(r#"{"s":"no_data"}"#).unwrap();
+        assert!(matches!(candle_points(empty), Err(PriceProviderError::NotFound)));
+    }
+
+    #[test]
+    fn test_metrics_map_to_fundamentals() {
+        let body: FinnhubMetricResponse = serde_json::from_str(
+            r#"{"metric":{"peTTM":29.8,"peBasicExclExtraTTM":30.1,"pbAnnual":47.2,
+                "marketCapitalization":2912345.5,"52WeekHigh":199.62,"roeTTM":160.58,
+                "netProfitMarginTTM":25.31,"totalDebt/totalEquityAnnual":1.87,
+                "dividendYieldIndicatedAnnual":0.44,"payoutRatioTTM":15.5,"epsGrowth5Y":17.9},
+                "metricType":"all","symbol":"AAPL"}"#,
+        )
+        .unwrap();
+
+        let fundamentals = metric_fundamentals(body.metric);
+        assert_eq!(fundamentals.pe_ratio, Some(29.8));
+        assert_eq!(fundamentals.roe, Some(160.58));
+        assert_eq!(fundamentals.debt_to_equity, Some(1.87));
+        assert_eq!(fundamentals.dividend_yield, Some(0.44));
+        assert_eq!(fundamentals.payout_ratio, Some(15.5));
+        // Falls back to the annual P/B without a quarterly figure
+        assert_eq!(fundamentals.pb_ratio, Some(47.2));
+        assert!((fundamentals.market_cap.unwrap() - 2_912_345_500_000.0).abs() < 1.0);
+
+        // Unknown symbols return an empty metric object
+        let unknown: FinnhubMetricResponse =
+            serde_json::from_str(r#"{"metric":{},"metricType":"","symbol":"ZZZZ"}"#).unwrap();
+        assert_eq!(metric_fundamentals(unknown.metric), ExternalFundamentals::default());
+    }
+
+    #[test]
+    fn test_earnings_and_dividends_parse() {
+        let rows: Vec<FinnhubEarnings> = serde_json::from_str(
+            r#"[{"actual":1.46,"estimate":1.39,"period":"2023-09-30","quarter":4,"surprise":0.07,"symbol":"AAPL","year":2023},
+                {"actual":2.18,"estimate":2.1,"period":"2023-12-31","quarter":1,"surprise":0.08,"symbol":"AAPL","year":2024},
+                {"actual":null,"estimate":1.5,"period":"bad","quarter":2,"symbol":"AAPL","year":2024}]"#,
+        )
+        .unwrap();
+        let reports = earnings_reports(rows);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].period_end.to_string(), "2023-12-31");
+        assert_eq!((reports[0].fiscal_year, reports[0].fiscal_quarter), (2024, 1));
+        assert_eq!(reports[0].eps_actual, Some(2.18));
+
+        let rows: Vec<FinnhubDividend> = serde_json::from_str(
+            r#"[{"symbol":"AAPL","date":"2024-02-09","amount":0.24,"adjustedAmount":0.24,"payDate":"2024-02-15","currency":"USD"},
+                {"symbol":"AAPL","date":"2023-11-10","amount":0.24,"adjustedAmount":0.24,"payDate":"","currency":"USD"},
+                {"symbol":"AAPL","date":"2023-08-11","amount":0.0,"currency":"USD"}]"#,
+        )
+        .unwrap();
+        let payments = dividend_payments(rows);
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].ex_date.to_string(), "2023-11-10");
+        assert_eq!(payments[0].pay_date, None);
+        assert_eq!(payments[1].pay_date.map(|d| d.to_string()).as_deref(), Some("2024-02-15"));
+        assert_eq!(payments[1].currency.as_deref(), Some("USD"));
+
+        let calendar: FinnhubEarningsCalendar = serde_json::from_str(
+            r#"{"earningsCalendar":[
+                {"date":"2024-05-02","epsActual":null,"epsEstimate":1.5,"hour":"amc","quarter":2,"revenueEstimate":null,"symbol":"AAPL","year":2024},
+                {"date":"2024-01-31","epsEstimate":2.1,"hour":"","quarter":1,"symbol":"AAPL","year":2024}]}"#,
+        )
+        .unwrap();
+        let dates = earnings_dates(calendar);
+        assert_eq!(dates.len(), 2);
+        assert_eq!(dates[0].report_date.to_string(), "2024-01-31");
+        assert_eq!(dates[0].hour, None);
+        assert_eq!(dates[1].hour.as_deref(), Some("amc"));
+        assert_eq!((dates[1].fiscal_year, dates[1].fiscal_quarter), (Some(2024), Some(2)));
+    }
+}
@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::external::price_provider::PriceProviderError;
+
+/// Valuation and key financial ratios for a ticker as reported by a provider.
+/// Percentages are in percent (15.0 is 15%).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExternalFundamentals {
+    /// Trailing twelve-month price/earnings; negative when the company is loss-making
+    pub pe_ratio: Option<f64>,
+    /// Price/book value
+    pub pb_ratio: Option<f64>,
+    /// Market capitalization in dollars
+    pub market_cap: Option<f64>,
+    /// Trailing twelve-month return on equity
+    pub roe: Option<f64>,
+    /// Trailing twelve-month net profit margin
+    pub net_margin: Option<f64>,
+    /// Total debt / total equity
+    pub debt_to_equity: Option<f64>,
+    /// Indicated annual dividend yield
+    pub dividend_yield: Option<f64>,
+    /// Trailing twelve-month dividends as a share of earnings
+    pub payout_ratio: Option<f64>,
+    /// Five-year annualized EPS growth
+    pub eps_growth_5y: Option<f64>,
+}
+
+/// One quarter's reported earnings per share
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalEarnings {
+    /// Last day of the fiscal quarter
+    pub period_end: NaiveDate,
+    pub fiscal_year: i32,
+    pub fiscal_quarter: i32,
+    pub eps_actual: Option<f64>,
+    /// Consensus estimate before the report
+    pub eps_estimate: Option<f64>,
+}
+
+/// An announced earnings release
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalEarningsDate {
+    pub report_date: NaiveDate,
+    /// "bmo" (before open), "amc" (after close) or "dmh" (during market hours)
+    pub hour: Option<String>,
+    pub fiscal_year: Option<i32>,
+    pub fiscal_quarter: Option<i32>,
+    pub eps_estimate: Option<f64>,
+}
+
+/// One past dividend payment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalDividend {
+    pub ex_date: NaiveDate,
+    pub pay_date: Option<NaiveDate>,
+    /// Cash per share
+    pub amount: f64,
+    pub currency: Option<String>,
+}
+
+#[async_trait]
+pub trait FundamentalsProvider: Send + Sync {
+    async fn fetch_fundamentals(&self, ticker: &str) -> Result<ExternalFundamentals, PriceProviderError>;
+
+    /// Reported quarters, most recent first
+    async fn fetch_earnings(&self, ticker: &str) -> Result<Vec<ExternalEarnings>, PriceProviderError>;
+
+    /// Announced earnings releases between `from` and `to`, oldest first
+    async fn fetch_upcoming_earnings(
+        &self,
+        ticker: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ExternalEarningsDate>, PriceProviderError>;
+
+    /// Dividends with an ex-date on or after `from`, oldest first; empty for non-payers
+    async fn fetch_dividend_history(
+        &self,
+        ticker: &str,
+        from: NaiveDate,
+    ) -> Result<Vec<ExternalDividend>, PriceProviderError>;
+}
@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::external::price_provider::PriceProviderError;
+
+const FRANKFURTER_BASE_URL: &str = "https://api.frankfurter.app";
+
+/// Reference rates published for one day, as units of each currency per US dollar
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalFxRates {
+    pub date: NaiveDate,
+    pub units_per_usd: HashMap<String, f64>,
+}
+
+#[async_trait]
+pub trait FxProvider: Send + Sync {
+    /// Rates for every publication day between `start` and `end`, oldest first.
+    /// A range without publications (a weekend) yields the last day before it.
+    async fn fetch_rates(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<ExternalFxRates>, PriceProviderError>;
+}
+
+/// ECB reference rates through Frankfurter - free, no key, published on
+/// TARGET business days around 16:00 CET
+pub struct FrankfurterProvider {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl FrankfurterProvider {
+    pub fn from_env() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: std::env::var("FRANKFURTER_BASE_URL")
+                .ok()
+                .filter(|u| !u.is_empty())
+                .unwrap_or_else(|| FRANKFURTER_BASE_URL.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FrankfurterResponse {
+    /// `/{start}..{end}`
+    Series { rates: BTreeMap<NaiveDate, HashMap<String, f64>> },
+    /// `/{date}`, which a range collapses to when it holds one publication
+    Single { date: NaiveDate, rates: HashMap<String, f64> },
+}
+
+impl FrankfurterResponse {
+    fn into_rates(self) -> Vec<ExternalFxRates> {
+        match self {
+            FrankfurterResponse::Series { rates } => rates
+                .into_iter()
+                .map(|(date, units_per_usd)| ExternalFxRates { date, units_per_usd })
+                .collect(),
+            FrankfurterResponse::Single { date, rates } => vec![ExternalFxRates { date, units_per_usd: rates }],
+        }
+    }
+}
+
+#[async_trait]
+impl FxProvider for FrankfurterProvider {
+    async fn fetch_rates(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<ExternalFxRates>, PriceProviderError> {
+        let url = if start == end {
+            format!("{}/{}", self.base_url, start)
+        } else {
+            format!("{}/{}..{}", self.base_url, start, end)
+        };
+        let resp = self
+            .client
+            .get(&url)
+            .query(&[("from", "USD")])
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(match status.as_u16() {
+                404 => PriceProviderError::NotFound,
+                429 => PriceProviderError::RateLimited,
+                _ => PriceProviderError::BadResponse(format!("HTTP {}", status)),
+            });
+        }
+
+        let body: FrankfurterResponse = resp.json().await.map_err(|e| PriceProviderError::Parse(e.to_string()))?;
+        let rates = body.into_rates();
+        if rates.is_empty() {
+            return Err(PriceProviderError::NotFound);
+        }
+        Ok(rates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_series_and_single_day_responses() {
+        let series: FrankfurterResponse = serde_json::from_str(
+            r#"{"amount":1.0,"base":"USD","start_date":"2026-03-05","end_date":"2026-03-06",
+                "rates":{"2026-03-05":{"CAD":1.4321,"EUR":0.9213},"2026-03-06":{"CAD":1.4302,"EUR":0.9198}}}"#,
+        )
+        .unwrap();
+        let rates = series.into_rates();
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates[1].date, NaiveDate::from_ymd_opt(2026, 3, 6).unwrap());
+        assert_eq!(rates[1].units_per_usd["CAD"], 1.4302);
+
+        let single: FrankfurterResponse = serde_json::from_str(
+            r#"{"amount":1.0,"base":"USD","date":"2026-03-06","rates":{"CAD":1.4302}}"#,
+        )
+        .unwrap();
+        let rates = single.into_rates();
+        assert_eq!(rates.len(), 1);
+        assert_eq!(rates[0].units_per_usd["CAD"], 1.4302);
+    }
+}
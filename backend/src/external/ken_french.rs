@@ -0,0 +1,152 @@
+use std::io::{Cursor, Read};
+
+use chrono::NaiveDate;
+
+use crate::external::price_provider::PriceProviderError;
+
+const KEN_FRENCH_BASE_URL: &str = "https://mba.tuck.dartmouth.edu/pages/faculty/ken.french/ftp";
+/// Daily market, size and value factors and the risk-free rate
+const THREE_FACTORS_FILE: &str = "F-F_Research_Data_Factors_daily_CSV.zip";
+/// Daily momentum factor
+const MOMENTUM_FILE: &str = "F-F_Momentum_Factor_daily_CSV.zip";
+
+/// One day's return of a factor, as a fraction (0.01 = 1%)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalFactorReturn {
+    pub date: NaiveDate,
+    /// "mkt_rf", "smb", "hml", "mom" or "rf"
+    pub factor: String,
+    pub value: f64,
+}
+
+/// US factor returns from Kenneth French's data library - free, no key,
+/// updated monthly a few weeks after month end
+pub struct KenFrenchProvider {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl KenFrenchProvider {
+    pub fn from_env() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: std::env::var("KEN_FRENCH_BASE_URL")
+                .ok()
+                .filter(|u| !u.is_empty())
+                .unwrap_or_else(|| KEN_FRENCH_BASE_URL.to_string()),
+        }
+    }
+
+    /// Daily market excess return, SMB, HML, momentum and risk-free rate for
+    /// the library's whole history
+    pub async fn fetch_daily_returns(&self) -> Result<Vec<ExternalFactorReturn>, PriceProviderError> {
+        let mut returns = parse_factor_csv(&self.fetch_csv(THREE_FACTORS_FILE).await?)?;
+        returns.extend(parse_factor_csv(&self.fetch_csv(MOMENTUM_FILE).await?)?);
+        Ok(returns)
+    }
+
+    /// The CSV inside one of the library's zip files
+    async fn fetch_csv(&self, file: &str) -> Result<String, PriceProviderError> {
+        let resp = self
+            .client
+            .get(format!("{}/{}", self.base_url, file))
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+        if !resp.status().is_success() {
+            return Err(PriceProviderError::BadResponse(format!("{} returned {}", file, resp.status())));
+        }
+        let bytes = resp.bytes().await.map_err(|e| PriceProviderError::Network(e.to_string()))?;
+        unzip_first(&bytes).map_err(|e| PriceProviderError::Parse(format!("{}: {}", file, e)))
+    }
+}
+
+fn unzip_first(bytes: &[u8]) -> Result<String, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let mut entry = archive.by_index(0).map_err(|e| e.to_string())?;
+    let mut raw = Vec::new();
+    entry.read_to_end(&mut raw).map_err(|e| e.to_string())?;
+    // The files are plain ASCII apart from the odd Latin-1 copyright sign
+    Ok(String::from_utf8_lossy(&raw).into_owned())
+}
+
+/// Column names as the library spells them, and the keys they are stored under
+fn factor_key(column: &str) -> Option<&'static str> {
+    match column.trim() {
+        "Mkt-RF" => Some("mkt_rf"),
+        "SMB" => Some("smb"),
+        "HML" => Some("hml"),
+        "Mom" => Some("mom"),
+        "RF" => Some("rf"),
+        _ => None,
+    }
+}
+
+/// Parse a daily factor file: a few lines of description, a header row
+/// starting with a comma, then `YYYYMMDD,value,...` rows in percent. Reading
+/// stops at the first line after the data that isn't a row.
+pub fn parse_factor_csv(text: &str) -> Result<Vec<ExternalFactorReturn>, PriceProviderError> {
+    let mut lines = text.lines();
+    let header = lines
+        .by_ref()
+        .find(|line| line.starts_with(','))
+        .ok_or_else(|| PriceProviderError::Parse("factor file without a header row".to_string()))?;
+    let columns: Vec<Option<&'static str>> = header.split(',').skip(1).map(factor_key).collect();
+    if columns.iter().all(Option::is_none) {
+        return Err(PriceProviderError::Parse(format!("no known factors in header: {}", header.trim())));
+    }
+
+    let mut returns = Vec::new();
+    for line in lines {
+        let mut fields = line.split(',');
+        let Some(date) = fields.next().and_then(|d| NaiveDate::parse_from_str(d.trim(), "%Y%m%d").ok()) else {
+            if returns.is_empty() {
+                continue;
+            }
+            break;
+        };
+        for (column, value) in columns.iter().zip(fields) {
+            let (Some(factor), Ok(percent)) = (column, value.trim().parse::<f64>()) else {
+                continue;
+            };
+            // -99.99 and -999 mark missing observations
+            if percent <= -99.99 {
+                continue;
+            }
+            returns.push(ExternalFactorReturn { date, factor: factor.to_string(), value: percent / 100.0 });
+        }
+    }
+    Ok(returns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_factor_csv() {
+        let text = "This file was created by CMPT_ME_BEME_RETS_DAILY using the 202601 CRSP database.\r\n\
+                    The 1-month TBill return is from Ibbotson and Associates Inc.\r\n\
+                    \r\n\
+                    ,Mkt-RF,SMB,HML,RF\r\n\
+                    19260701,    0.10,   -0.25,   -0.27,    0.01\r\n\
+                    19260702,    0.45,   -0.33,   -0.06,    0.01\r\n\
+                    \r\n\
+                    Copyright 2026 Eugene F. Fama and Kenneth R. French\r\n";
+        let returns = parse_factor_csv(text).unwrap();
+        assert_eq!(returns.len(), 8);
+        let date = NaiveDate::from_ymd_opt(1926, 7, 1).unwrap();
+        assert_eq!(returns[0], ExternalFactorReturn { date, factor: "mkt_rf".to_string(), value: 0.001 });
+        assert_eq!(returns[3].factor, "rf");
+        assert!((returns[5].value + 0.0033).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_parse_momentum_skips_missing_values() {
+        let text = "Missing data are indicated by -99.99 or -999.\n\n,Mom   \n19261103,  -99.99\n19261104,    0.56\n";
+        let returns = parse_factor_csv(text).unwrap();
+        assert_eq!(returns.len(), 1);
+        assert_eq!(returns[0].factor, "mom");
+        assert!(parse_factor_csv("no header here\n19260701,1.0\n").is_err());
+    }
+}
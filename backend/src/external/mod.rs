@@ -2,4 +2,21 @@ pub mod price_provider;
 pub mod alphavantage;
 pub mod twelvedata;
 pub mod yahoofinance;
-pub mod multi_provider;
\ No newline at end of file
+pub mod polygon;
+pub mod finnhub;
+pub mod tiingo;
+pub mod coingecko;
+pub mod multi_provider;
+pub mod composite_provider;
+pub mod provider_factory;
+pub mod ownership_provider;
+pub mod analyst_provider;
+pub mod dividend_provider;
+pub mod fx_provider;
+pub mod etf_holdings_provider;
+pub mod fundamentals_provider;
+pub mod fixture;
+pub mod chain_provider;
+pub mod chaos_provider;
+pub mod news;
+pub mod ken_french;
@@ -1,4 +1,7 @@
-use crate::external::price_provider::{ExternalPricePoint, ExternalTickerMatch, PriceProvider, PriceProviderError};
+use crate::external::price_provider::{
+    ExternalCorporateAction, ExternalIntradayBar, ExternalPricePoint, ExternalQuote, ExternalTickerMatch,
+    IntradayInterval, PriceProvider, PriceProviderError, ProviderQuota,
+};
 use async_trait::async_trait;
 use tracing::{info, warn};
 
@@ -93,12 +96,12 @@ impl PriceProvider for MultiProvider {
         let (is_canadian, normalized_ticker) = Self::detect_canadian_ticker(ticker);
 
         if is_canadian {
-            info!("🍁 Detected Canadian ticker: {} -> {}, routing to Yahoo Finance", ticker, normalized_ticker);
+            info!("Detected Canadian ticker: {} -> {}, routing to Yahoo Finance", ticker, normalized_ticker);
 
             // Try Yahoo Finance for Canadian stocks (free, no API key, unlimited)
             match self.yahoo.fetch_daily_history(&normalized_ticker, days).await {
                 Ok(data) => {
-                    info!("✓ Successfully fetched {} from Yahoo Finance", ticker);
+                    info!("Successfully fetched {} from Yahoo Finance", ticker);
                     return Ok(data);
                 }
                 Err(e) => {
@@ -111,15 +114,15 @@ impl PriceProvider for MultiProvider {
         // Try primary provider (Twelve Data for US stocks)
         match self.primary.fetch_daily_history(ticker, days).await {
             Ok(data) => {
-                info!("✓ Successfully fetched {} from primary provider", ticker);
+                info!("Successfully fetched {} from primary provider", ticker);
                 return Ok(data);
             }
             Err(PriceProviderError::BadResponse(msg)) if msg.contains("404") || msg.contains("Pro plan") => {
                 // Ticker not available in primary provider's free tier
-                info!("⚠️ Ticker {} not available in primary provider, trying fallback", ticker);
+                info!("Ticker {} not available in primary provider, trying fallback", ticker);
             }
             Err(PriceProviderError::RateLimited) => {
-                info!("⚠️ Primary provider rate limited, trying fallback");
+                info!("Primary provider rate limited, trying fallback");
             }
             Err(e) => {
                 // Other error (network, etc.) - try fallback anyway
@@ -130,7 +133,7 @@ impl PriceProvider for MultiProvider {
         // Try fallback provider (Alpha Vantage)
         match self.fallback.fetch_daily_history(ticker, days).await {
             Ok(data) => {
-                info!("✓ Successfully fetched {} from fallback provider", ticker);
+                info!("Successfully fetched {} from fallback provider", ticker);
                 return Ok(data);
             }
             Err(e) => {
@@ -148,7 +151,7 @@ impl PriceProvider for MultiProvider {
         info!("Last resort: Trying Yahoo Finance with ticker {}", yahoo_ticker);
         match self.yahoo.fetch_daily_history(&yahoo_ticker, days).await {
             Ok(data) => {
-                info!("✓ Successfully fetched {} as {} from Yahoo Finance (last resort)", ticker, yahoo_ticker);
+                info!("Successfully fetched {} as {} from Yahoo Finance (last resort)", ticker, yahoo_ticker);
                 return Ok(data);
             }
             Err(e) => {
@@ -187,4 +190,54 @@ impl PriceProvider for MultiProvider {
         // Try fallback provider
         self.fallback.search_ticker_by_keyword(keyword).await
     }
+
+    async fn fetch_quote(&self, ticker: &str) -> Result<ExternalQuote, PriceProviderError> {
+        // Yahoo has a real quote endpoint (with the opening price) for both markets
+        let (_, normalized_ticker) = Self::detect_canadian_ticker(ticker);
+        match self.yahoo.fetch_quote(&normalized_ticker).await {
+            Ok(quote) => return Ok(quote),
+            Err(e) => warn!("Yahoo Finance quote failed for {}: {}", normalized_ticker, e),
+        }
+
+        match self.primary.fetch_quote(ticker).await {
+            Ok(quote) => Ok(quote),
+            Err(e) => {
+                warn!("Primary provider quote failed for {}: {}", ticker, e);
+                self.fallback.fetch_quote(ticker).await
+            }
+        }
+    }
+
+    async fn fetch_intraday(
+        &self,
+        ticker: &str,
+        interval: IntradayInterval,
+    ) -> Result<Vec<ExternalIntradayBar>, PriceProviderError> {
+        // Yahoo's chart endpoint has intraday bars for both markets without using
+        // the primary provider's credits
+        let (_, normalized_ticker) = Self::detect_canadian_ticker(ticker);
+        match self.yahoo.fetch_intraday(&normalized_ticker, interval).await {
+            Ok(bars) => return Ok(bars),
+            Err(e) => warn!("Yahoo Finance intraday failed for {}: {}", normalized_ticker, e),
+        }
+        self.primary.fetch_intraday(ticker, interval).await
+    }
+
+    async fn fetch_corporate_actions(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalCorporateAction>, PriceProviderError> {
+        let (_, normalized_ticker) = Self::detect_canadian_ticker(ticker);
+        match self.yahoo.fetch_corporate_actions(&normalized_ticker, days).await {
+            Ok(actions) => return Ok(actions),
+            Err(e) => warn!("Yahoo Finance corporate actions failed for {}: {}", normalized_ticker, e),
+        }
+        self.primary.fetch_corporate_actions(ticker, days).await
+    }
+
+    async fn probe_quota(&self) -> Result<Option<ProviderQuota>, PriceProviderError> {
+        // The rate limiter is sized for the primary provider's free tier
+        self.primary.probe_quota().await
+    }
 }
@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::Deserialize;
+
+use crate::external::price_provider::PriceProviderError;
+use crate::external::provider_factory::ProviderSettings;
+use crate::services::clock;
+
+/// A news article about a ticker, scored by the provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalNewsArticle {
+    pub title: String,
+    pub url: String,
+    pub source: String,
+    pub published_at: DateTime<Utc>,
+    pub summary: String,
+    /// Sentiment toward the ticker, -1.0 (bearish) to +1.0 (bullish)
+    pub sentiment_score: f64,
+    /// How much the article is about the ticker, 0.0 to 1.0
+    pub relevance: f64,
+}
+
+/// News with per-ticker sentiment already scored, so sentiment can be cached
+/// without clustering articles through the LLM
+#[async_trait]
+pub trait NewsProvider: Send + Sync {
+    /// Articles about `ticker` published in the last `days` days, newest first
+    async fn fetch_ticker_news(&self, ticker: &str, days: u32) -> Result<Vec<ExternalNewsArticle>, PriceProviderError>;
+}
+
+/// Alpha Vantage's NEWS_SENTIMENT feed. Uses the same `ALPHAVANTAGE_*`
+/// settings as the price provider, so it shares the key's daily allowance.
+pub struct AlphaVantageNewsProvider {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+}
+
+/// Articles requested per call; the API's maximum
+const FEED_LIMIT: &str = "1000";
+
+impl AlphaVantageNewsProvider {
+    pub fn from_settings(settings: &ProviderSettings) -> Result<Self, PriceProviderError> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            api_key: settings.require_api_key("ALPHAVANTAGE_API_KEY")?,
+            base_url: settings.base_url_or("https://www.alphavantage.co"),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AvNewsResponse {
+    feed: Option<Vec<AvNewsItem>>,
+    // Rate limited responses carry a "Note" or, on newer keys, an "Information"
+    #[serde(rename = "Note")]
+    note: Option<String>,
+    #[serde(rename = "Information")]
+    information: Option<String>,
+    #[serde(rename = "Error Message")]
+    error_message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AvNewsItem {
+    title: String,
+    url: String,
+    /// e.g. "20260302T143000"
+    time_published: String,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    ticker_sentiment: Vec<AvTickerSentiment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AvTickerSentiment {
+    ticker: String,
+    relevance_score: String,
+    ticker_sentiment_score: String,
+}
+
+fn parse_news(ticker: &str, body: &str) -> Result<Vec<ExternalNewsArticle>, PriceProviderError> {
+    let response: AvNewsResponse =
+        serde_json::from_str(body).map_err(|e| PriceProviderError::Parse(format!("news response: {}", e)))?;
+    if response.note.is_some() || response.information.is_some() {
+        return Err(PriceProviderError::RateLimited);
+    }
+    if let Some(message) = response.error_message {
+        return Err(PriceProviderError::BadResponse(message));
+    }
+    let feed = response
+        .feed
+        .ok_or_else(|| PriceProviderError::BadResponse("news response without a feed".to_string()))?;
+
+    let mut articles: Vec<ExternalNewsArticle> = feed
+        .into_iter()
+        .filter_map(|item| {
+            let sentiment = item.ticker_sentiment.iter().find(|s| s.ticker.eq_ignore_ascii_case(ticker))?;
+            let published_at = NaiveDateTime::parse_from_str(&item.time_published, "%Y%m%dT%H%M%S").ok()?.and_utc();
+            Some(ExternalNewsArticle {
+                sentiment_score: sentiment.ticker_sentiment_score.parse::<f64>().ok()?.clamp(-1.0, 1.0),
+                relevance: sentiment.relevance_score.parse::<f64>().ok()?.clamp(0.0, 1.0),
+                title: item.title,
+                url: item.url,
+                source: item.source,
+                published_at,
+                summary: item.summary,
+            })
+        })
+        .collect();
+    articles.sort_by_key(|a| std::cmp::Reverse(a.published_at));
+    Ok(articles)
+}
+
+#[async_trait]
+impl NewsProvider for AlphaVantageNewsProvider {
+    async fn fetch_ticker_news(&self, ticker: &str, days: u32) -> Result<Vec<ExternalNewsArticle>, PriceProviderError> {
+        let time_from = (clock::now() - Duration::days(days as i64)).format("%Y%m%dT%H%M").to_string();
+        let resp = self
+            .client
+            .get(format!("{}/query", self.base_url))
+            .query(&[
+                ("function", "NEWS_SENTIMENT"),
+                ("tickers", ticker),
+                ("time_from", time_from.as_str()),
+                ("sort", "LATEST"),
+                ("limit", FEED_LIMIT),
+                ("apikey", self.api_key.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        if resp.status().as_u16() == 429 {
+            return Err(PriceProviderError::RateLimited);
+        }
+        let body = resp.text().await.map_err(|e| PriceProviderError::Network(e.to_string()))?;
+        parse_news(ticker, &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_news_keeps_scores_for_ticker() {
+        let body = r#"{
+            "items": "3",
+            "feed": [
+                {
+                    "title": "Older", "url": "https://example.com/1", "time_published": "20260301T090000",
+                    "summary": "s", "source": "Wire",
+                    "ticker_sentiment": [{"ticker": "AAPL", "relevance_score": "0.8", "ticker_sentiment_score": "0.25"}]
+                },
+                {
+                    "title": "Newer", "url": "https://example.com/2", "time_published": "20260302T143000",
+                    "summary": "s", "source": "Wire",
+                    "ticker_sentiment": [
+                        {"ticker": "MSFT", "relevance_score": "0.9", "ticker_sentiment_score": "0.5"},
+                        {"ticker": "AAPL", "relevance_score": "0.3", "ticker_sentiment_score": "-1.4"}
+                    ]
+                },
+                {
+                    "title": "Unrelated", "url": "https://example.com/3", "time_published": "20260302T150000",
+                    "ticker_sentiment": [{"ticker": "MSFT", "relevance_score": "0.9", "ticker_sentiment_score": "0.5"}]
+                }
+            ]
+        }"#;
+        let articles = parse_news("AAPL", body).unwrap();
+        assert_eq!(articles.len(), 2);
+        assert_eq!(articles[0].title, "Newer");
+        assert_eq!(articles[0].sentiment_score, -1.0);
+        assert_eq!(articles[0].relevance, 0.3);
+        assert_eq!(articles[1].sentiment_score, 0.25);
+    }
+
+    #[test]
+    fn test_parse_news_errors() {
+        assert!(matches!(
+            parse_news("AAPL", r#"{"Information": "You have reached the daily limit"}"#),
+            Err(PriceProviderError::RateLimited)
+        ));
+        assert!(matches!(
+            parse_news("AAPL", r#"{"Error Message": "Invalid API call"}"#),
+            Err(PriceProviderError::BadResponse(_))
+        ));
+    }
+}
@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::external::price_provider::PriceProviderError;
+use crate::models::{InsiderTransaction, InstitutionalHolder};
+
+/// Ownership data for a ticker as reported by a provider; percentages are of
+/// shares outstanding (0-100)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExternalOwnership {
+    pub insider_pct: Option<f64>,
+    pub institutional_pct: Option<f64>,
+    pub institution_count: Option<i64>,
+    pub institutional_holders: Vec<InstitutionalHolder>,
+    pub insider_transactions: Vec<InsiderTransaction>,
+}
+
+#[async_trait]
+pub trait OwnershipProvider: Send + Sync {
+    async fn fetch_ownership(&self, ticker: &str) -> Result<ExternalOwnership, PriceProviderError>;
+}
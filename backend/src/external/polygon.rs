@@ -0,0 +1,241 @@
+use crate::external::price_provider::{
+    ExternalPricePoint, ExternalTickerMatch, PriceProvider, PriceProviderError,
+};
+use crate::external::provider_factory::ProviderSettings;
+use crate::services::clock;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate};
+use serde::Deserialize;
+
+const BASE_URL: &str = "https://api.polygon.io";
+
+/// Bars requested per page; Polygon's maximum for aggregates
+const PAGE_LIMIT: u32 = 50_000;
+
+/// Pages followed before giving up on a history request
+const MAX_PAGES: usize = 20;
+
+/// Polygon.io provider - paid plans have no per-minute cap, so multi-year
+/// history backfills run in a handful of requests
+pub struct PolygonProvider {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+}
+
+impl PolygonProvider {
+    pub fn from_settings(settings: &ProviderSettings) -> Result<Self, PriceProviderError> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            api_key: settings.require_api_key("POLYGON_API_KEY")?,
+            base_url: settings.base_url_or(BASE_URL),
+        })
+    }
+
+    /// GET a Polygon URL and decode the body, mapping HTTP failures and
+    /// `status: ERROR` bodies to provider errors
+    async fn get_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, PriceProviderError> {
+        let resp = self
+            .client
+            .get(url)
+            .query(query)
+            .query(&[("apiKey", self.api_key.as_str())])
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body: Option<PolygonError> = resp.json().await.ok();
+            let message = body
+                .and_then(|b| b.error.or(b.message))
+                .unwrap_or_else(|| format!("HTTP {}", status));
+            return Err(match status.as_u16() {
+                404 => PriceProviderError::NotFound,
+                429 => PriceProviderError::RateLimited,
+                _ => PriceProviderError::BadResponse(message),
+            });
+        }
+
+        resp.json()
+            .await
+            .map_err(|e| PriceProviderError::Parse(e.to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonError {
+    error: Option<String>,
+    message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonAggregatesResponse {
+    #[serde(default)]
+    results: Vec<PolygonBar>,
+    /// Full URL of the next page, without the API key
+    next_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonBar {
+    /// Close
+    c: f64,
+    /// Start of the bar, Unix milliseconds
+    t: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonTickersResponse {
+    #[serde(default)]
+    results: Vec<PolygonTicker>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolygonTicker {
+    ticker: String,
+    name: String,
+    #[serde(rename = "type")]
+    ticker_type: Option<String>,
+    locale: Option<String>,
+    currency_name: Option<String>,
+    primary_exchange: Option<String>,
+}
+
+/// First calendar day to request so `days` trading days are covered, allowing
+/// for weekends and holidays
+fn history_start(today: NaiveDate, days: u32) -> NaiveDate {
+    today - Duration::days(days as i64 * 7 / 5 + 10)
+}
+
+/// Daily closes from aggregate bars
+fn bar_points(bars: Vec<PolygonBar>) -> Vec<ExternalPricePoint> {
+    bars.into_iter()
+        .filter_map(|bar| {
+            let date = chrono::DateTime::from_timestamp_millis(bar.t)?.date_naive();
+            let close = BigDecimal::try_from(bar.c).ok()?;
+            Some(ExternalPricePoint { date, close, adjusted_close: None })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl PriceProvider for PolygonProvider {
+    async fn fetch_daily_history(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+        let today = clock::today();
+        let url = format!(
+            "{}/v2/aggs/ticker/{}/range/1/day/{}/{}",
+            self.base_url,
+            ticker.to_uppercase(),
+            history_start(today, days),
+            today
+        );
+        let query = [
+            ("adjusted", "true".to_string()),
+            ("sort", "asc".to_string()),
+            ("limit", PAGE_LIMIT.to_string()),
+        ];
+
+        let mut page: PolygonAggregatesResponse = self.get_json(&url, &query).await?;
+        let mut points = bar_points(std::mem::take(&mut page.results));
+
+        // Long ranges come back in pages; next_url already carries the query
+        let mut pages = 1;
+        while let Some(next_url) = page.next_url.take() {
+            if pages >= MAX_PAGES {
+                tracing::warn!("Polygon history for {} truncated after {} pages", ticker, pages);
+                break;
+            }
+            page = self.get_json(&next_url, &[]).await?;
+            points.extend(bar_points(std::mem::take(&mut page.results)));
+            pages += 1;
+        }
+
+        if points.is_empty() {
+            return Err(PriceProviderError::NotFound);
+        }
+
+        points.sort_by_key(|p| p.date);
+        points.dedup_by(|a, b| a.date == b.date);
+        let excess = points.len().saturating_sub(days as usize);
+        points.drain(..excess);
+
+        Ok(points)
+    }
+
+    async fn search_ticker_by_keyword(
+        &self,
+        keyword: &str,
+    ) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
+        let url = format!("{}/v3/reference/tickers", self.base_url);
+        let query = [
+            ("search", keyword.to_string()),
+            ("active", "true".to_string()),
+            ("limit", "30".to_string()),
+        ];
+
+        let body: PolygonTickersResponse = self.get_json(&url, &query).await?;
+
+        let matches = body.results
+            .into_iter()
+            .enumerate()
+            .map(|(idx, t)| ExternalTickerMatch {
+                symbol: t.ticker,
+                name: t.name,
+                _type: t.ticker_type.unwrap_or_else(|| "Stock".to_string()),
+                region: t.locale.unwrap_or_else(|| "us".to_string()),
+                currency: t.currency_name.unwrap_or_else(|| "usd".to_string()).to_uppercase(),
+                exchange: t.primary_exchange,
+                // Calculate match score based on position (first result = highest score)
+                match_score: 1.0 - (idx as f64 * 0.05),
+            })
+            .collect();
+
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::ToPrimitive;
+
+    #[test]
+    fn test_aggregates_page_parses_into_points() {
+        let page: PolygonAggregatesResponse = serde_json::from_str(
+            r#"{"ticker":"AAPL","queryCount":2,"resultsCount":2,"adjusted":true,
+                "results":[{"v":5.8e7,"vw":185.9,"o":187.15,"c":185.64,"h":188.44,"l":183.885,"t":1704171600000,"n":1},
+                           {"v":5.9e7,"vw":184.3,"o":184.22,"c":184.25,"h":185.88,"l":183.43,"t":1704258000000,"n":1}],
+                "status":"OK","request_id":"abc","count":2,
+                "next_url":"https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/day/1704344400000/2024-06-30?cursor=xyz"}"#,
+        )
+        .unwrap();
+
+        assert!(page.next_url.is_some());
+        let points = bar_points(page.results);
+        let dates: Vec<String> = points.iter().map(|p| p.date.to_string()).collect();
+        assert_eq!(dates, vec!["2024-01-02", "2024-01-03"]);
+        assert!((points[0].close.to_f64().unwrap() - 185.64).abs() < 1e-9);
+
+        // Tickers with no bars in range omit `results`
+        let empty: PolygonAggregatesResponse =
+            serde_json::from_str(r#"{"ticker":"ZZZZ","queryCount":0,"resultsCount":0,"status":"OK"}"#).unwrap();
+        assert!(empty.results.is_empty());
+    }
+
+    #[test]
+    fn test_history_start_covers_trading_days() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+        // 250 trading days is roughly a calendar year
+        assert_eq!(history_start(today, 250), today - Duration::days(360));
+    }
+}
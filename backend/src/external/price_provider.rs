@@ -1,13 +1,100 @@
 use async_trait::async_trait;
-use bigdecimal::BigDecimal;
-use chrono::NaiveDate;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use thiserror::Error;
 
+use crate::services::rate_limiter::RateLimiter;
+
 #[derive(Debug, Clone)]
 pub struct ExternalPricePoint {
     pub date: NaiveDate,
     pub close: BigDecimal,
+    /// Close adjusted for later splits and dividends, from providers that report one
+    pub adjusted_close: Option<BigDecimal>,
+}
+
+/// Latest quote for a ticker; `open` is today's opening price when the session has started
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalQuote {
+    pub price: f64,
+    pub open: Option<f64>,
+    pub previous_close: f64,
+}
+
+/// Bar size of intraday prices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+pub enum IntradayInterval {
+    #[serde(rename = "1min")]
+    OneMinute,
+    #[default]
+    #[serde(rename = "5min")]
+    FiveMinutes,
+    #[serde(rename = "15min")]
+    FifteenMinutes,
+    #[serde(rename = "30min")]
+    ThirtyMinutes,
+    #[serde(rename = "60min")]
+    SixtyMinutes,
+}
+
+impl IntradayInterval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntradayInterval::OneMinute => "1min",
+            IntradayInterval::FiveMinutes => "5min",
+            IntradayInterval::FifteenMinutes => "15min",
+            IntradayInterval::ThirtyMinutes => "30min",
+            IntradayInterval::SixtyMinutes => "60min",
+        }
+    }
+
+    pub fn minutes(&self) -> i64 {
+        match self {
+            IntradayInterval::OneMinute => 1,
+            IntradayInterval::FiveMinutes => 5,
+            IntradayInterval::FifteenMinutes => 15,
+            IntradayInterval::ThirtyMinutes => 30,
+            IntradayInterval::SixtyMinutes => 60,
+        }
+    }
+}
+
+/// One intraday bar; `timestamp` is the start of the bar
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalIntradayBar {
+    pub timestamp: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: Option<i64>,
+}
+
+/// Kind of corporate action that changes the price series
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorporateActionType {
+    Split,
+    Dividend,
+}
+
+impl CorporateActionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CorporateActionType::Split => "split",
+            CorporateActionType::Dividend => "dividend",
+        }
+    }
+}
+
+/// A split or cash dividend effective at the open of `ex_date`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalCorporateAction {
+    pub ex_date: NaiveDate,
+    pub action_type: CorporateActionType,
+    /// New shares per old share for a split (4.0 for 4-for-1), cash per share for a dividend
+    pub value: f64,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -17,9 +104,32 @@ pub struct ExternalTickerMatch {
     pub _type: String,
     pub region: String,
     pub currency: String,
+    /// Listing exchange, when the provider's search reports it
+    pub exchange: Option<String>,
     pub match_score: f64,
 }
 
+/// Health of one provider as tracked by a provider chain's circuit breaker
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderHealth {
+    pub name: String,
+    /// Calls skip the provider until its circuit closes
+    pub circuit_open: bool,
+    /// Errors since its last successful call
+    pub consecutive_failures: u32,
+    pub last_success_at: Option<DateTime<Utc>>,
+}
+
+/// Request quota as reported by a provider, used to calibrate the rate limiter
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderQuota {
+    pub provider: String,
+    /// Plan limit in requests per minute
+    pub requests_per_minute: Option<u32>,
+    /// Requests left in the provider's current window
+    pub remaining: Option<u32>,
+}
+
 #[derive(Debug, Error)]
 pub enum PriceProviderError {
     #[error("network error: {0}")]
@@ -36,6 +146,9 @@ pub enum PriceProviderError {
 
     #[error("ticker not found")]
     NotFound,
+
+    #[error("not supported: {0}")]
+    Unsupported(String),
 }
 
 #[async_trait]
@@ -50,4 +163,130 @@ pub trait PriceProvider: Send + Sync {
         &self,
         keyword: &str
     ) -> Result<Vec<ExternalTickerMatch>, PriceProviderError>;
+
+    /// Daily history of several tickers, in the order given. Fetches run
+    /// concurrently, each holding a rate limiter permit, so the limiter's
+    /// concurrency and per-minute budget still apply. Once the provider rejects
+    /// a call as rate limited, the fetches not yet started are reported as
+    /// rate limited rather than spending more of the quota.
+    async fn fetch_many(
+        &self,
+        tickers: &[&str],
+        days: u32,
+        rate_limiter: &RateLimiter,
+    ) -> Vec<(String, Result<Vec<ExternalPricePoint>, PriceProviderError>)> {
+        let rate_limited = AtomicBool::new(false);
+        let fetches = tickers.iter().map(|ticker| {
+            let rate_limited = &rate_limited;
+            async move {
+                let _guard = rate_limiter.acquire().await;
+                if rate_limited.load(Ordering::Relaxed) {
+                    return (ticker.to_string(), Err(PriceProviderError::RateLimited));
+                }
+                let result = self.fetch_daily_history(ticker, days).await;
+                if let Err(PriceProviderError::RateLimited) = &result {
+                    rate_limiter.record_rejection();
+                    rate_limited.store(true, Ordering::Relaxed);
+                }
+                (ticker.to_string(), result)
+            }
+        });
+        futures::future::join_all(fetches).await
+    }
+
+    /// Latest quote. Providers without a quote endpoint derive it from the last two
+    /// daily closes, which carries no opening price.
+    async fn fetch_quote(&self, ticker: &str) -> Result<ExternalQuote, PriceProviderError> {
+        let history = self.fetch_daily_history(ticker, 5).await?;
+        let [.., previous, latest] = history.as_slice() else {
+            return Err(PriceProviderError::NotFound);
+        };
+        let to_f64 = |p: &ExternalPricePoint| {
+            p.close.to_f64().ok_or_else(|| PriceProviderError::Parse(format!("invalid close for {}", ticker)))
+        };
+        Ok(ExternalQuote {
+            price: to_f64(latest)?,
+            open: None,
+            previous_close: to_f64(previous)?,
+        })
+    }
+
+    /// Bars of the latest trading session, oldest first. Providers without an
+    /// intraday endpoint return `Unsupported`.
+    async fn fetch_intraday(
+        &self,
+        _ticker: &str,
+        _interval: IntradayInterval,
+    ) -> Result<Vec<ExternalIntradayBar>, PriceProviderError> {
+        Err(PriceProviderError::Unsupported("intraday prices".to_string()))
+    }
+
+    /// Splits and dividends over roughly the last `days` days, oldest first.
+    /// Providers without corporate action data return `Unsupported`.
+    async fn fetch_corporate_actions(
+        &self,
+        _ticker: &str,
+        _days: u32,
+    ) -> Result<Vec<ExternalCorporateAction>, PriceProviderError> {
+        Err(PriceProviderError::Unsupported("corporate actions".to_string()))
+    }
+
+    /// Health of each provider behind this one. Empty for providers that don't
+    /// track it, i.e. anything but a provider chain.
+    fn health(&self) -> Vec<ProviderHealth> {
+        Vec::new()
+    }
+
+    /// Current request quota. Providers that don't report one return `None`,
+    /// which leaves the rate limiter's budget unchanged.
+    async fn probe_quota(&self) -> Result<Option<ProviderQuota>, PriceProviderError> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Rate limits "LIMIT" and returns one close for anything else
+    struct StubProvider {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl PriceProvider for StubProvider {
+        async fn fetch_daily_history(&self, ticker: &str, _: u32) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if ticker == "LIMIT" {
+                return Err(PriceProviderError::RateLimited);
+            }
+            Ok(vec![ExternalPricePoint {
+                date: NaiveDate::from_ymd_opt(2026, 3, 6).unwrap(),
+                close: BigDecimal::from(100),
+                adjusted_close: None,
+            }])
+        }
+
+        async fn search_ticker_by_keyword(&self, _: &str) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_many_stops_after_rate_limit() {
+        let provider = StubProvider { calls: AtomicUsize::new(0) };
+        // One permit at a time, so the fetches start in order
+        let rate_limiter = RateLimiter::new(1, 6000);
+
+        let results = provider.fetch_many(&["AAPL", "LIMIT", "MSFT"], 5, &rate_limiter).await;
+
+        let tickers: Vec<&str> = results.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(tickers, vec!["AAPL", "LIMIT", "MSFT"]);
+        assert_eq!(results[0].1.as_ref().map(Vec::len).ok(), Some(1));
+        assert!(matches!(results[1].1, Err(PriceProviderError::RateLimited)));
+        assert!(matches!(results[2].1, Err(PriceProviderError::RateLimited)));
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(rate_limiter.metrics().rejections, 1);
+    }
 }
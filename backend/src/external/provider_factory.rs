@@ -0,0 +1,258 @@
+//! Price provider selection and per-provider settings.
+//!
+//! `PRICE_PROVIDER` picks the provider (default `multi`, `fixture` in demo
+//! mode). Each provider reads its settings from variables prefixed with its
+//! upper-cased name, so switching providers or plans needs no code change:
+//!
+//! - `<NAME>_API_KEY` - required by every provider except `yahoo`
+//! - `<NAME>_BASE_URL` - alternative endpoint, e.g. a proxy or a sandbox
+//! - `<NAME>_RATE_LIMIT` - requests per minute the plan allows (default 8)
+//! - `<NAME>_DAILY_LIMIT` - requests per day the plan allows
+//!
+//! `multi` and `composite` chain several providers and take their limits from
+//! the first one they try. `PRICE_PROVIDER_RATE_LIMIT` and
+//! `PRICE_PROVIDER_DAILY_LIMIT` override the limits of whichever is selected.
+
+use std::sync::Arc;
+
+use crate::external::alphavantage::AlphaVantageProvider;
+use crate::external::coingecko::{CoinGeckoProvider, CryptoRoutingProvider};
+use crate::external::composite_provider::{self, CompositePriceProvider};
+use crate::external::finnhub::FinnhubProvider;
+use crate::external::fixture::FixturePriceProvider;
+use crate::external::multi_provider::MultiProvider;
+use crate::external::polygon::PolygonProvider;
+use crate::external::price_provider::{PriceProvider, PriceProviderError};
+use crate::external::tiingo::TiingoProvider;
+use crate::external::twelvedata::TwelveDataProvider;
+use crate::external::yahoofinance::YahooFinanceProvider;
+use crate::services::clock;
+use crate::services::failure_cache::FailureCache;
+use crate::services::rate_limiter::RateLimiter;
+
+pub const DEFAULT_PROVIDER: &str = "multi";
+/// Free tier limit of the default providers
+pub const DEFAULT_REQUESTS_PER_MINUTE: u32 = 8;
+/// Provider requests in flight at once
+const MAX_CONCURRENT: usize = 3;
+/// Providers that serve prices on their own
+pub const SINGLE_PROVIDERS: &[&str] = &["alphavantage", "twelvedata", "polygon", "finnhub", "tiingo", "yahoo"];
+/// Members of the `multi` provider, in the order tried
+const MULTI_CHAIN: [&str; 3] = ["twelvedata", "alphavantage", "yahoo"];
+
+/// Settings of one provider, read from `<NAME>_*` variables
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProviderSettings {
+    pub api_key: Option<String>,
+    pub base_url: Option<String>,
+    pub requests_per_minute: Option<u32>,
+    pub daily_limit: Option<u32>,
+}
+
+impl ProviderSettings {
+    pub fn from_env(name: &str) -> Self {
+        Self::from_vars(name, |key| std::env::var(key).ok())
+    }
+
+    /// Settings of `name` from a variable lookup. Empty values count as unset
+    /// and unparseable limits are ignored.
+    pub fn from_vars(name: &str, var: impl Fn(&str) -> Option<String>) -> Self {
+        let prefix = name.to_uppercase();
+        let get = |suffix: &str| {
+            var(&format!("{}_{}", prefix, suffix))
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Self {
+            api_key: get("API_KEY"),
+            base_url: get("BASE_URL").map(|url| url.trim_end_matches('/').to_string()),
+            requests_per_minute: get("RATE_LIMIT").and_then(|v| v.parse().ok()).filter(|v| *v > 0),
+            daily_limit: get("DAILY_LIMIT").and_then(|v| v.parse().ok()),
+        }
+    }
+
+    /// The API key, or an error naming the variable that should hold it
+    pub fn require_api_key(&self, variable: &str) -> Result<String, PriceProviderError> {
+        self.api_key
+            .clone()
+            .ok_or_else(|| PriceProviderError::BadResponse(format!("{} not set", variable)))
+    }
+
+    pub fn base_url_or(&self, default: &str) -> String {
+        self.base_url.clone().unwrap_or_else(|| default.to_string())
+    }
+}
+
+/// Name of the provider to use, lowercased
+pub fn selected_name(demo_mode: bool) -> String {
+    if demo_mode {
+        return "fixture".to_string();
+    }
+    std::env::var("PRICE_PROVIDER")
+        .map(|name| name.trim().to_lowercase())
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| DEFAULT_PROVIDER.to_string())
+}
+
+/// One provider that serves prices on its own, configured from the environment
+pub fn build_single(name: &str) -> Result<Box<dyn PriceProvider>, PriceProviderError> {
+    let settings = ProviderSettings::from_env(name);
+    Ok(match name {
+        "alphavantage" => Box::new(AlphaVantageProvider::from_settings(&settings)?),
+        "twelvedata" => Box::new(TwelveDataProvider::from_settings(&settings)?),
+        "polygon" => Box::new(PolygonProvider::from_settings(&settings)?),
+        "finnhub" => Box::new(FinnhubProvider::from_settings(&settings)?),
+        "tiingo" => Box::new(TiingoProvider::from_settings(&settings)?),
+        "yahoo" => Box::new(YahooFinanceProvider::from_settings(&settings)),
+        other => {
+            return Err(PriceProviderError::BadResponse(format!(
+                "unknown price provider '{}' (expected one of {})",
+                other,
+                SINGLE_PROVIDERS.join(", ")
+            )))
+        }
+    })
+}
+
+fn build_fixture() -> Result<FixturePriceProvider, PriceProviderError> {
+    let seed = std::env::var("DEMO_SEED")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(42);
+    tracing::info!("Using price provider: Fixture (seed {})", seed);
+    let mut fixture = FixturePriceProvider::new(seed, clock::current());
+    if let Ok(dir) = std::env::var("PRICE_FIXTURES_DIR") {
+        fixture = fixture.with_fixture_dir(std::path::Path::new(&dir))?;
+    }
+    Ok(fixture)
+}
+
+/// The provider named `name`. Crypto tickers are routed to CoinGecko whichever
+/// stock provider is selected; the fixture provider already synthesizes any ticker.
+pub fn build(name: &str, failure_cache: FailureCache) -> Result<Arc<dyn PriceProvider>, PriceProviderError> {
+    let provider: Arc<dyn PriceProvider> = match name {
+        "fixture" => return Ok(Arc::new(build_fixture()?)),
+        "multi" => {
+            tracing::info!("Using price provider: Multi-provider (Twelve Data + Alpha Vantage + Yahoo Finance)");
+            let [primary, fallback, yahoo] = MULTI_CHAIN;
+            Arc::new(MultiProvider::new(build_single(primary)?, build_single(fallback)?, build_single(yahoo)?))
+        }
+        "composite" => {
+            let composite = CompositePriceProvider::from_env(failure_cache)?;
+            tracing::info!("Using price provider: Composite chain ({})", composite.order().join(" -> "));
+            Arc::new(composite)
+        }
+        single => {
+            let provider = build_single(single)?;
+            tracing::info!("Using price provider: {} only", single);
+            Arc::from(provider)
+        }
+    };
+    tracing::info!("Crypto prices: CoinGecko");
+    Ok(Arc::new(CryptoRoutingProvider::new(provider, CoinGeckoProvider::from_env())))
+}
+
+/// Provider whose plan limits apply to `name`: the first member of a chain
+fn limits_source(name: &str, composite_chain: &[String]) -> String {
+    match name {
+        "multi" => MULTI_CHAIN[0].to_string(),
+        "composite" => composite_chain.first().cloned().unwrap_or_default(),
+        other => other.to_string(),
+    }
+}
+
+/// Requests per minute and per day allowed for `name`, from its settings and
+/// the `PRICE_PROVIDER_*` overrides
+pub fn rate_limits(
+    name: &str,
+    composite_chain: &[String],
+    var: impl Fn(&str) -> Option<String>,
+) -> (u32, Option<u32>) {
+    let settings = ProviderSettings::from_vars(&limits_source(name, composite_chain), &var);
+    let overrides = ProviderSettings::from_vars("PRICE_PROVIDER", &var);
+    let per_minute = overrides
+        .requests_per_minute
+        .or(settings.requests_per_minute)
+        .unwrap_or(DEFAULT_REQUESTS_PER_MINUTE);
+    (per_minute, overrides.daily_limit.or(settings.daily_limit))
+}
+
+/// Rate limiter sized to the selected provider's plan
+pub fn rate_limiter(name: &str) -> RateLimiter {
+    let (per_minute, daily_limit) =
+        rate_limits(name, &composite_provider::configured_chain(), |key| std::env::var(key).ok());
+    tracing::info!(
+        "Rate limiter initialized: {} concurrent, {} requests/min, daily limit {:?}",
+        MAX_CONCURRENT,
+        per_minute,
+        daily_limit
+    );
+    RateLimiter::new(MAX_CONCURRENT, per_minute).with_daily_limit(daily_limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |key| map.get(key).cloned()
+    }
+
+    #[test]
+    fn test_settings_from_prefixed_vars() {
+        let settings = ProviderSettings::from_vars(
+            "twelvedata",
+            vars(&[
+                ("TWELVEDATA_API_KEY", " abc "),
+                ("TWELVEDATA_BASE_URL", "http://proxy.local/twelvedata/"),
+                ("TWELVEDATA_RATE_LIMIT", "55"),
+                ("TWELVEDATA_DAILY_LIMIT", "not a number"),
+                ("POLYGON_API_KEY", "other"),
+            ]),
+        );
+        assert_eq!(
+            settings,
+            ProviderSettings {
+                api_key: Some("abc".to_string()),
+                base_url: Some("http://proxy.local/twelvedata".to_string()),
+                requests_per_minute: Some(55),
+                daily_limit: None,
+            }
+        );
+        assert_eq!(settings.base_url_or("https://api.twelvedata.com"), "http://proxy.local/twelvedata");
+
+        let empty = ProviderSettings::from_vars("polygon", vars(&[("POLYGON_API_KEY", ""), ("POLYGON_RATE_LIMIT", "0")]));
+        assert_eq!(empty, ProviderSettings::default());
+        assert!(empty.require_api_key("POLYGON_API_KEY").is_err());
+        assert_eq!(empty.base_url_or("https://api.polygon.io"), "https://api.polygon.io");
+    }
+
+    #[test]
+    fn test_rate_limits_follow_selected_provider() {
+        let env = vars(&[
+            ("POLYGON_RATE_LIMIT", "100"),
+            ("TIINGO_RATE_LIMIT", "50"),
+            ("TIINGO_DAILY_LIMIT", "1000"),
+            ("TWELVEDATA_RATE_LIMIT", "8"),
+            ("TWELVEDATA_DAILY_LIMIT", "800"),
+        ]);
+        assert_eq!(rate_limits("polygon", &[], &env), (100, None));
+        assert_eq!(rate_limits("yahoo", &[], &env), (DEFAULT_REQUESTS_PER_MINUTE, None));
+        assert_eq!(rate_limits("multi", &[], &env), (8, Some(800)));
+        let chain = vec!["tiingo".to_string(), "yahoo".to_string()];
+        assert_eq!(rate_limits("composite", &chain, &env), (50, Some(1000)));
+
+        let overridden = vars(&[("TIINGO_RATE_LIMIT", "50"), ("PRICE_PROVIDER_DAILY_LIMIT", "200")]);
+        assert_eq!(rate_limits("tiingo", &[], &overridden), (50, Some(200)));
+    }
+
+    #[test]
+    fn test_build_single_rejects_unknown_provider() {
+        assert!(build_single("yahoo").is_ok());
+        let err = build_single("bloomberg").err().unwrap();
+        assert!(err.to_string().contains("unknown price provider 'bloomberg'"));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::external::price_provider::{
+    ExternalPricePoint, ExternalTickerMatch, PriceProvider, PriceProviderError,
+};
+use crate::external::provider_factory::ProviderSettings;
+use crate::services::clock;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{Duration, NaiveDate};
+use serde::Deserialize;
+
+const BASE_URL: &str = "https://api.tiingo.com";
+
+/// Tiingo provider - end-of-day prices with split- and dividend-adjusted
+/// closes alongside the raw close, so risk metrics don't see splits as crashes
+pub struct TiingoProvider {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+}
+
+impl TiingoProvider {
+    pub fn from_settings(settings: &ProviderSettings) -> Result<Self, PriceProviderError> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            api_key: settings.require_api_key("TIINGO_API_KEY")?,
+            base_url: settings.base_url_or(BASE_URL),
+        })
+    }
+
+    /// GET a Tiingo endpoint and decode the body
+    async fn get_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, PriceProviderError> {
+        let resp = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .header("Authorization", format!("Token {}", self.api_key))
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        if !status.is_success() {
+            let detail = serde_json::from_str::<TiingoError>(&body)
+                .map(|e| e.detail)
+                .unwrap_or_else(|_| format!("HTTP {}", status));
+            return Err(match status.as_u16() {
+                404 => PriceProviderError::NotFound,
+                429 => PriceProviderError::RateLimited,
+                _ => PriceProviderError::BadResponse(detail),
+            });
+        }
+
+        serde_json::from_str(&body).map_err(|e| {
+            // Exhausting the hourly allocation comes back as a plain-text 200
+            if body.contains("request allocation") {
+                PriceProviderError::RateLimited
+            } else {
+                PriceProviderError::Parse(e.to_string())
+            }
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TiingoError {
+    detail: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TiingoBar {
+    /// Midnight UTC of the trading day, e.g. "2024-01-02T00:00:00.000Z"
+    date: String,
+    close: f64,
+    adj_close: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TiingoSearchResult {
+    ticker: String,
+    name: String,
+    asset_type: Option<String>,
+    country_code: Option<String>,
+}
+
+/// First calendar day to request so `days` trading days are covered, allowing
+/// for weekends and holidays
+fn history_start(today: NaiveDate, days: u32) -> NaiveDate {
+    today - Duration::days(days as i64 * 7 / 5 + 10)
+}
+
+/// Raw and adjusted daily closes from end-of-day bars
+fn bar_points(bars: Vec<TiingoBar>) -> Vec<ExternalPricePoint> {
+    bars.into_iter()
+        .filter_map(|bar| {
+            let date = NaiveDate::parse_from_str(bar.date.get(..10)?, "%Y-%m-%d").ok()?;
+            let close = BigDecimal::try_from(bar.close).ok()?;
+            let adjusted_close = bar.adj_close.and_then(|c| BigDecimal::try_from(c).ok());
+            Some(ExternalPricePoint { date, close, adjusted_close })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl PriceProvider for TiingoProvider {
+    async fn fetch_daily_history(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+        let today = clock::today();
+        let path = format!("/tiingo/daily/{}/prices", ticker.to_lowercase());
+        let query = [
+            ("startDate", history_start(today, days).to_string()),
+            ("endDate", today.to_string()),
+            ("resampleFreq", "daily".to_string()),
+        ];
+
+        let bars: Vec<TiingoBar> = self.get_json(&path, &query).await?;
+        let mut points = bar_points(bars);
+        if points.is_empty() {
+            return Err(PriceProviderError::NotFound);
+        }
+
+        points.sort_by_key(|p| p.date);
+        let excess = points.len().saturating_sub(days as usize);
+        points.drain(..excess);
+
+        Ok(points)
+    }
+
+    async fn search_ticker_by_keyword(
+        &self,
+        keyword: &str,
+    ) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
+        let results: Vec<TiingoSearchResult> = self
+            .get_json("/tiingo/utilities/search", &[("query", keyword.to_string())])
+            .await?;
+
+        let matches = results
+            .into_iter()
+            .enumerate()
+            .map(|(idx, r)| ExternalTickerMatch {
+                symbol: r.ticker.to_uppercase(),
+                name: r.name,
+                _type: r.asset_type.unwrap_or_else(|| "Stock".to_string()),
+                region: r.country_code.unwrap_or_else(|| "US".to_string()),
+                currency: "USD".to_string(),
+                exchange: None,
+                // Calculate match score based on position (first result = highest score)
+                match_score: 1.0 - (idx as f64 * 0.05),
+            })
+            .collect();
+
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::ToPrimitive;
+
+    #[test]
+    fn test_bars_keep_raw_and_adjusted_closes() {
+        // AAPL around its 4:1 split on 2020-08-31
+        let bars: Vec<TiingoBar> = serde_json::from_str(
+            r#"[{"date":"2020-08-28T00:00:00.000Z","close":499.23,"high":505.0,"low":490.0,"open":504.05,
+                 "volume":46907479,"adjClose":122.46,"adjHigh":123.87,"adjLow":120.19,"adjOpen":123.64,
+                 "adjVolume":187629916,"divCash":0.0,"splitFactor":1.0},
+                {"date":"2020-08-31T00:00:00.000Z","close":129.04,"high":131.0,"low":126.0,"open":127.58,
+                 "volume":225702700,"adjClose":126.62,"adjHigh":128.54,"adjLow":123.64,"adjOpen":125.18,
+                 "adjVolume":225702700,"divCash":0.0,"splitFactor":4.0}]"#,
+        )
+        .unwrap();
+
+        let points = bar_points(bars);
+        assert_eq!(points[0].date, NaiveDate::from_ymd_opt(2020, 8, 28).unwrap());
+        assert!((points[0].close.to_f64().unwrap() - 499.23).abs() < 1e-9);
+
+        // The raw series drops 74% across the split; the adjusted one rises
+        let adjusted: Vec<f64> = points.iter().map(|p| p.adjusted_close.as_ref().unwrap().to_f64().unwrap()).collect();
+        assert!(adjusted[1] > adjusted[0]);
+    }
+}
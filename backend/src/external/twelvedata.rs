@@ -1,22 +1,25 @@
-use crate::external::price_provider::{ExternalPricePoint, ExternalTickerMatch, PriceProvider, PriceProviderError};
+use crate::external::price_provider::{
+    ExternalIntradayBar, ExternalPricePoint, ExternalTickerMatch, IntradayInterval, PriceProvider, PriceProviderError,
+    ProviderQuota,
+};
+use crate::external::provider_factory::ProviderSettings;
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::Deserialize;
 
 pub struct TwelveDataProvider {
     client: reqwest::Client,
     api_key: String,
+    base_url: String,
 }
 
 impl TwelveDataProvider {
-    pub fn from_env() -> Result<Self, PriceProviderError> {
-        let api_key = std::env::var("TWELVEDATA_API_KEY")
-            .map_err(|_| PriceProviderError::BadResponse("TWELVEDATA_API_KEY not set".into()))?;
-
+    pub fn from_settings(settings: &ProviderSettings) -> Result<Self, PriceProviderError> {
         Ok(Self {
             client: reqwest::Client::new(),
-            api_key,
+            api_key: settings.require_api_key("TWELVEDATA_API_KEY")?,
+            base_url: settings.base_url_or("https://api.twelvedata.com"),
         })
     }
 }
@@ -31,7 +34,6 @@ struct TwelveDataSearchResponse {
 struct TwelveDataSearchMatch {
     symbol: String,
     instrument_name: String,
-    #[allow(dead_code)]
     exchange: String,
     #[serde(default)]
     #[allow(dead_code)]
@@ -71,28 +73,100 @@ struct TwelveDataMeta {
 #[derive(Debug, Deserialize)]
 struct TwelveDataValue {
     datetime: String,
-    #[allow(dead_code)]
     open: String,
-    #[allow(dead_code)]
     high: String,
-    #[allow(dead_code)]
     low: String,
     close: String,
-    #[allow(dead_code)]
     volume: Option<String>,
 }
 
+/// Response of /api_usage; error responses carry `status` and `message` instead
+#[derive(Debug, Deserialize)]
+struct TwelveDataApiUsage {
+    current_usage: Option<u32>,
+    plan_limit: Option<u32>,
+    status: Option<String>,
+    message: Option<String>,
+}
+
+/// Header Twelve Data sets on every response with the credits left this minute
+const CREDITS_LEFT_HEADER: &str = "api-credits-left";
+
+fn header_u32(headers: &reqwest::header::HeaderMap, name: &str) -> Option<u32> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Quota from the usage body, preferring the credits-left header over the
+/// body's usage count for what remains
+fn quota_from_usage(credits_left: Option<u32>, usage: &TwelveDataApiUsage) -> ProviderQuota {
+    let remaining = credits_left.or_else(|| {
+        usage.plan_limit.zip(usage.current_usage).map(|(limit, used)| limit.saturating_sub(used))
+    });
+    ProviderQuota {
+        provider: "twelvedata".to_string(),
+        requests_per_minute: usage.plan_limit,
+        remaining,
+    }
+}
+
+/// Values of a time series response, with rate limits and errors mapped to provider errors
+fn series_values(body: TwelveDataTimeSeriesResponse) -> Result<Vec<TwelveDataValue>, PriceProviderError> {
+    // Check for rate limiting or errors
+    if body.status != "ok" {
+        if let Some(msg) = body.message {
+            // Check for rate limit messages
+            if msg.contains("API rate limit") || msg.contains("credits") {
+                return Err(PriceProviderError::RateLimited);
+            }
+            return Err(PriceProviderError::BadResponse(msg));
+        }
+        return Err(PriceProviderError::BadResponse(
+            format!("API returned status: {}", body.status)
+        ));
+    }
+
+    body.values
+        .ok_or_else(|| PriceProviderError::BadResponse("missing values in response".into()))
+}
+
+/// Intraday bars of the latest UTC day in the values, oldest first. Values
+/// come newest first with UTC datetimes (requested with `timezone=UTC`).
+fn session_bars(values: Vec<TwelveDataValue>) -> Result<Vec<ExternalIntradayBar>, PriceProviderError> {
+    let parse = |v: &str| v.parse::<f64>().map_err(|e| PriceProviderError::Parse(e.to_string()));
+    let mut bars = values
+        .into_iter()
+        .map(|v| -> Result<ExternalIntradayBar, PriceProviderError> {
+            let timestamp = NaiveDateTime::parse_from_str(&v.datetime, "%Y-%m-%d %H:%M:%S")
+                .map_err(|e| PriceProviderError::Parse(e.to_string()))?
+                .and_utc();
+            Ok(ExternalIntradayBar {
+                timestamp,
+                open: parse(&v.open)?,
+                high: parse(&v.high)?,
+                low: parse(&v.low)?,
+                close: parse(&v.close)?,
+                volume: v.volume.as_deref().and_then(|vol| vol.parse().ok()),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let session = bars.first().ok_or(PriceProviderError::NotFound)?.timestamp.date_naive();
+    bars.retain(|b| b.timestamp.date_naive() == session);
+    bars.reverse();
+    Ok(bars)
+}
+
 #[async_trait]
 impl PriceProvider for TwelveDataProvider {
     async fn search_ticker_by_keyword(
         &self,
         keyword: &str
     ) -> Result<Vec<ExternalTickerMatch>, PriceProviderError> {
-        let url = "https://api.twelvedata.com/symbol_search";
+        let url = format!("{}/symbol_search", self.base_url);
 
         let resp = self
             .client
-            .get(url)
+            .get(&url)
             .query(&[
                 ("symbol", keyword),
                 ("outputsize", "30"),
@@ -122,6 +196,7 @@ impl PriceProvider for TwelveDataProvider {
                 _type: m.instrument_type,
                 region: m.country,
                 currency: m.currency,
+                exchange: Some(m.exchange).filter(|e| !e.is_empty()),
                 // Calculate match score based on position (first result = highest score)
                 match_score: 1.0 - (idx as f64 * 0.05),
             })
@@ -135,7 +210,7 @@ impl PriceProvider for TwelveDataProvider {
         ticker: &str,
         days: u32,
     ) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
-        let url = "https://api.twelvedata.com/time_series";
+        let url = format!("{}/time_series", self.base_url);
 
         // Twelve Data uses "1day" for daily data
         // outputsize determines how many data points (default 30, max 5000)
@@ -143,7 +218,7 @@ impl PriceProvider for TwelveDataProvider {
 
         let resp = self
             .client
-            .get(url)
+            .get(&url)
             .query(&[
                 ("symbol", ticker),
                 ("interval", "1day"),
@@ -159,22 +234,7 @@ impl PriceProvider for TwelveDataProvider {
             .await
             .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
 
-        // Check for rate limiting or errors
-        if body.status != "ok" {
-            if let Some(msg) = body.message {
-                // Check for rate limit messages
-                if msg.contains("API rate limit") || msg.contains("credits") {
-                    return Err(PriceProviderError::RateLimited);
-                }
-                return Err(PriceProviderError::BadResponse(msg));
-            }
-            return Err(PriceProviderError::BadResponse(
-                format!("API returned status: {}", body.status)
-            ));
-        }
-
-        let values = body.values
-            .ok_or_else(|| PriceProviderError::BadResponse("missing values in response".into()))?;
+        let values = series_values(body)?;
 
         // Convert to our format
         let mut points: Vec<ExternalPricePoint> = values
@@ -196,7 +256,7 @@ impl PriceProvider for TwelveDataProvider {
                 let close = v.close.parse::<BigDecimal>()
                     .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
 
-                Ok(ExternalPricePoint { date, close })
+                Ok(ExternalPricePoint { date, close, adjusted_close: None })
             })
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -205,4 +265,106 @@ impl PriceProvider for TwelveDataProvider {
 
         Ok(points)
     }
+
+    async fn fetch_intraday(
+        &self,
+        ticker: &str,
+        interval: IntradayInterval,
+    ) -> Result<Vec<ExternalIntradayBar>, PriceProviderError> {
+        let url = format!("{}/time_series", self.base_url);
+        let twelvedata_interval = match interval {
+            IntradayInterval::SixtyMinutes => "1h",
+            other => other.as_str(),
+        };
+        // Enough bars to cover a full day
+        let outputsize = (24 * 60 / interval.minutes()).to_string();
+
+        let resp = self
+            .client
+            .get(&url)
+            .query(&[
+                ("symbol", ticker),
+                ("interval", twelvedata_interval),
+                ("outputsize", outputsize.as_str()),
+                ("timezone", "UTC"),
+                ("apikey", self.api_key.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        let body: TwelveDataTimeSeriesResponse = resp
+            .json()
+            .await
+            .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
+
+        session_bars(series_values(body)?)
+    }
+
+    async fn probe_quota(&self) -> Result<Option<ProviderQuota>, PriceProviderError> {
+        let resp = self
+            .client
+            .get(format!("{}/api_usage", self.base_url))
+            .query(&[("apikey", self.api_key.as_str())])
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(PriceProviderError::RateLimited);
+        }
+        let credits_left = header_u32(resp.headers(), CREDITS_LEFT_HEADER);
+
+        let body: TwelveDataApiUsage = resp
+            .json()
+            .await
+            .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
+
+        if body.status.as_deref() == Some("error") {
+            let msg = body.message.unwrap_or_else(|| "API returned status: error".to_string());
+            if msg.contains("API rate limit") || msg.contains("credits") {
+                return Err(PriceProviderError::RateLimited);
+            }
+            return Err(PriceProviderError::BadResponse(msg));
+        }
+
+        Ok(Some(quota_from_usage(credits_left, &body)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_prefers_credits_header_over_usage() {
+        let usage: TwelveDataApiUsage = serde_json::from_str(
+            r#"{"timestamp":"2025-06-30 14:00:00","current_usage":3,"plan_limit":8,"plan_category":"basic"}"#,
+        )
+        .unwrap();
+
+        let from_body = quota_from_usage(None, &usage);
+        assert_eq!(from_body.requests_per_minute, Some(8));
+        assert_eq!(from_body.remaining, Some(5));
+
+        let from_header = quota_from_usage(Some(2), &usage);
+        assert_eq!(from_header.remaining, Some(2));
+    }
+
+    #[test]
+    fn test_session_bars_keep_latest_day_oldest_first() {
+        let body: TwelveDataTimeSeriesResponse = serde_json::from_str(
+            r#"{"meta":null,"status":"ok","values":[
+                {"datetime":"2026-03-06 14:35:00","open":"186.1","high":"186.6","low":"186.0","close":"186.5","volume":"98100"},
+                {"datetime":"2026-03-06 14:30:00","open":"185.0","high":"185.9","low":"184.8","close":"185.7","volume":"120400"},
+                {"datetime":"2026-03-05 20:55:00","open":"184.0","high":"184.2","low":"183.9","close":"184.1","volume":"301000"}]}"#,
+        )
+        .unwrap();
+
+        let bars = session_bars(series_values(body).unwrap()).unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].timestamp.to_rfc3339(), "2026-03-06T14:30:00+00:00");
+        assert_eq!(bars[1].close, 186.5);
+        assert_eq!(bars[1].volume, Some(98100));
+    }
 }
@@ -1,7 +1,20 @@
-use crate::external::price_provider::{ExternalPricePoint, ExternalTickerMatch, PriceProvider, PriceProviderError};
+use crate::external::analyst_provider::{AnalystProvider, ExternalAnalystConsensus};
+use crate::external::dividend_provider::{DividendProvider, ExternalDividendEvent};
+use crate::external::etf_holdings_provider::{EtfHoldingsProvider, ExternalEtfHolding, ExternalEtfHoldings};
+use crate::external::ownership_provider::{ExternalOwnership, OwnershipProvider};
+use crate::external::price_provider::{
+    CorporateActionType, ExternalCorporateAction, ExternalIntradayBar, ExternalPricePoint, ExternalQuote,
+    ExternalTickerMatch, IntradayInterval, PriceProvider, PriceProviderError,
+};
+use crate::external::provider_factory::ProviderSettings;
+use crate::models::{InsiderTransaction, InsiderTransactionType, InstitutionalHolder, RatingDistribution};
 use async_trait::async_trait;
 use bigdecimal::BigDecimal;
 use serde::Deserialize;
+use std::collections::HashMap;
+
+const CHART_HOST: &str = "https://query1.finance.yahoo.com";
+const SUMMARY_HOST: &str = "https://query2.finance.yahoo.com";
 
 /// Yahoo Finance provider - Free API with excellent support for Canadian stocks
 ///
@@ -9,6 +22,8 @@ use serde::Deserialize;
 /// in free tiers of other providers.
 pub struct YahooFinanceProvider {
     client: reqwest::Client,
+    /// Replaces both Yahoo hosts when set, e.g. to route through a proxy
+    base_url: Option<String>,
 }
 
 impl YahooFinanceProvider {
@@ -18,8 +33,17 @@ impl YahooFinanceProvider {
                 .user_agent("Mozilla/5.0 (compatible; Rustfolio/0.1)")
                 .build()
                 .unwrap_or_else(|_| reqwest::Client::new()),
+            base_url: None,
         }
     }
+
+    pub fn from_settings(settings: &ProviderSettings) -> Self {
+        Self { base_url: settings.base_url.clone(), ..Self::new() }
+    }
+
+    fn chart_url(&self, ticker: &str) -> String {
+        format!("{}/v8/finance/chart/{}", self.base_url.as_deref().unwrap_or(CHART_HOST), ticker)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,8 +64,43 @@ struct YahooError {
 
 #[derive(Debug, Deserialize)]
 struct YahooResult {
+    #[serde(default)]
+    meta: Option<YahooMeta>,
+    #[serde(default)]
     timestamp: Vec<i64>,
     indicators: YahooIndicators,
+    /// Present when requested with `events=div,splits`, keyed by timestamp
+    #[serde(default)]
+    events: Option<YahooEvents>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct YahooEvents {
+    #[serde(default)]
+    dividends: HashMap<String, YahooDividendEvent>,
+    #[serde(default)]
+    splits: HashMap<String, YahooSplitEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooDividendEvent {
+    amount: f64,
+    date: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooSplitEvent {
+    date: i64,
+    numerator: f64,
+    denominator: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooMeta {
+    regular_market_price: Option<f64>,
+    previous_close: Option<f64>,
+    chart_previous_close: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -51,7 +110,173 @@ struct YahooIndicators {
 
 #[derive(Debug, Deserialize)]
 struct YahooQuote {
+    #[serde(default)]
+    open: Vec<Option<f64>>,
+    #[serde(default)]
+    high: Vec<Option<f64>>,
+    #[serde(default)]
+    low: Vec<Option<f64>>,
     close: Vec<Option<f64>>,
+    #[serde(default)]
+    volume: Vec<Option<i64>>,
+}
+
+/// Map a failed HTTP status to a provider error, so the failure cache backs
+/// off from rate limits for an hour rather than treating them as API errors
+fn status_error(status: reqwest::StatusCode) -> PriceProviderError {
+    match status.as_u16() {
+        404 => PriceProviderError::NotFound,
+        429 => PriceProviderError::RateLimited,
+        _ => PriceProviderError::BadResponse(format!("HTTP {}", status)),
+    }
+}
+
+/// The single result of a chart response, with errors mapped to provider errors
+fn chart_result(body: YahooChartResponse) -> Result<YahooResult, PriceProviderError> {
+    // Check for API errors
+    if let Some(error) = body.chart.error {
+        if error.description.contains("No data found") {
+            return Err(PriceProviderError::NotFound);
+        }
+        return Err(PriceProviderError::BadResponse(error.description));
+    }
+
+    // Extract results
+    let results = body.chart.result
+        .ok_or_else(|| PriceProviderError::BadResponse("No results in response".into()))?;
+
+    let result = results.into_iter().next().ok_or(PriceProviderError::NotFound)?;
+
+    if result.indicators.quote.is_empty() {
+        return Err(PriceProviderError::BadResponse("No quote data in response".into()));
+    }
+    if result.timestamp.len() != result.indicators.quote[0].close.len() {
+        return Err(PriceProviderError::Parse(
+            "Timestamp and close price arrays have different lengths".into()
+        ));
+    }
+
+    Ok(result)
+}
+
+/// Daily closes from a chart response, oldest first. Sessions without a close
+/// (holidays, halts) are skipped.
+fn chart_points(body: YahooChartResponse) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
+    let result = chart_result(body)?;
+    let timestamps = &result.timestamp;
+    let closes = &result.indicators.quote[0].close;
+
+    // Convert to our format
+    let mut points: Vec<ExternalPricePoint> = timestamps
+        .iter()
+        .zip(closes.iter())
+        .filter_map(|(timestamp, close_opt)| {
+            // Skip null values (market holidays, etc.)
+            let close = (*close_opt)?;
+
+            // Convert Unix timestamp to NaiveDate
+            let date = chrono::DateTime::from_timestamp(*timestamp, 0)
+                .map(|dt| dt.date_naive())?;
+
+            // Convert f64 to BigDecimal
+            let close_bd = BigDecimal::try_from(close).ok()?;
+
+            Some(ExternalPricePoint {
+                date,
+                close: close_bd,
+                adjusted_close: None,
+            })
+        })
+        .collect();
+
+    // Sort by date (oldest first)
+    points.sort_by_key(|p| p.date);
+
+    if points.is_empty() {
+        return Err(PriceProviderError::NotFound);
+    }
+
+    Ok(points)
+}
+
+/// Splits and dividends from a chart response requested with events, oldest first
+fn chart_actions(body: YahooChartResponse) -> Result<Vec<ExternalCorporateAction>, PriceProviderError> {
+    let events = chart_result(body)?.events.unwrap_or_default();
+    let ex_date = |timestamp: i64| chrono::DateTime::from_timestamp(timestamp, 0).map(|dt| dt.date_naive());
+
+    let mut actions: Vec<ExternalCorporateAction> = events
+        .splits
+        .values()
+        .filter(|s| s.numerator > 0.0 && s.denominator > 0.0)
+        .filter_map(|s| {
+            Some(ExternalCorporateAction {
+                ex_date: ex_date(s.date)?,
+                action_type: CorporateActionType::Split,
+                value: s.numerator / s.denominator,
+            })
+        })
+        .chain(events.dividends.values().filter(|d| d.amount > 0.0).filter_map(|d| {
+            Some(ExternalCorporateAction {
+                ex_date: ex_date(d.date)?,
+                action_type: CorporateActionType::Dividend,
+                value: d.amount,
+            })
+        }))
+        .collect();
+    actions.sort_by_key(|a| (a.ex_date, a.action_type.as_str()));
+    Ok(actions)
+}
+
+/// Chart range covering the last `days` days
+fn chart_range(days: u32) -> &'static str {
+    // Yahoo uses "1d", "5d", "1mo", "3mo", "6mo", "1y", "2y", "5y", "10y", "ytd", "max"
+    if days <= 5 {
+        "5d"
+    } else if days <= 30 {
+        "1mo"
+    } else if days <= 90 {
+        "3mo"
+    } else if days <= 180 {
+        "6mo"
+    } else if days <= 365 {
+        "1y"
+    } else if days <= 730 {
+        "2y"
+    } else if days <= 1825 {
+        "5y"
+    } else {
+        "10y"
+    }
+}
+
+/// Intraday bars from a chart response. Minutes without a trade come back
+/// as nulls and are skipped.
+fn chart_bars(body: YahooChartResponse) -> Result<Vec<ExternalIntradayBar>, PriceProviderError> {
+    let result = chart_result(body)?;
+    let quote = &result.indicators.quote[0];
+    let at = |values: &[Option<f64>], i: usize| values.get(i).copied().flatten();
+
+    let bars: Vec<ExternalIntradayBar> = result
+        .timestamp
+        .iter()
+        .enumerate()
+        .filter_map(|(i, timestamp)| {
+            let close = at(&quote.close, i)?;
+            Some(ExternalIntradayBar {
+                timestamp: chrono::DateTime::from_timestamp(*timestamp, 0)?,
+                open: at(&quote.open, i).unwrap_or(close),
+                high: at(&quote.high, i).unwrap_or(close),
+                low: at(&quote.low, i).unwrap_or(close),
+                close,
+                volume: quote.volume.get(i).copied().flatten(),
+            })
+        })
+        .collect();
+
+    if bars.is_empty() {
+        return Err(PriceProviderError::NotFound);
+    }
+    Ok(bars)
 }
 
 #[async_trait]
@@ -62,25 +287,9 @@ impl PriceProvider for YahooFinanceProvider {
         days: u32,
     ) -> Result<Vec<ExternalPricePoint>, PriceProviderError> {
         // Yahoo Finance v8 API endpoint
-        let url = format!("https://query1.finance.yahoo.com/v8/finance/chart/{}", ticker);
-
-        // Calculate date range
-        // Yahoo uses "1d", "5d", "1mo", "3mo", "6mo", "1y", "2y", "5y", "10y", "ytd", "max"
-        let range = if days <= 5 {
-            "5d"
-        } else if days <= 30 {
-            "1mo"
-        } else if days <= 90 {
-            "3mo"
-        } else if days <= 180 {
-            "6mo"
-        } else if days <= 365 {
-            "1y"
-        } else if days <= 730 {
-            "2y"
-        } else {
-            "5y"
-        };
+        let url = self.chart_url(ticker);
+
+        let range = chart_range(days);
 
         let resp = self
             .client
@@ -94,14 +303,8 @@ impl PriceProvider for YahooFinanceProvider {
             .await
             .map_err(|e| PriceProviderError::Network(e.to_string()))?;
 
-        // Check HTTP status
         if !resp.status().is_success() {
-            if resp.status().as_u16() == 404 {
-                return Err(PriceProviderError::NotFound);
-            }
-            return Err(PriceProviderError::BadResponse(
-                format!("HTTP {}", resp.status())
-            ));
+            return Err(status_error(resp.status()));
         }
 
         let body: YahooChartResponse = resp
@@ -109,67 +312,7 @@ impl PriceProvider for YahooFinanceProvider {
             .await
             .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
 
-        // Check for API errors
-        if let Some(error) = body.chart.error {
-            if error.description.contains("No data found") {
-                return Err(PriceProviderError::NotFound);
-            }
-            return Err(PriceProviderError::BadResponse(error.description));
-        }
-
-        // Extract results
-        let results = body.chart.result
-            .ok_or_else(|| PriceProviderError::BadResponse("No results in response".into()))?;
-
-        if results.is_empty() {
-            return Err(PriceProviderError::NotFound);
-        }
-
-        let result = &results[0];
-        let timestamps = &result.timestamp;
-
-        if result.indicators.quote.is_empty() {
-            return Err(PriceProviderError::BadResponse("No quote data in response".into()));
-        }
-
-        let closes = &result.indicators.quote[0].close;
-
-        if timestamps.len() != closes.len() {
-            return Err(PriceProviderError::Parse(
-                "Timestamp and close price arrays have different lengths".into()
-            ));
-        }
-
-        // Convert to our format
-        let mut points: Vec<ExternalPricePoint> = timestamps
-            .iter()
-            .zip(closes.iter())
-            .filter_map(|(timestamp, close_opt)| {
-                // Skip null values (market holidays, etc.)
-                let close = (*close_opt)?;
-
-                // Convert Unix timestamp to NaiveDate
-                let date = chrono::DateTime::from_timestamp(*timestamp, 0)
-                    .map(|dt| dt.date_naive())?;
-
-                // Convert f64 to BigDecimal
-                let close_bd = BigDecimal::try_from(close).ok()?;
-
-                Some(ExternalPricePoint {
-                    date,
-                    close: close_bd,
-                })
-            })
-            .collect();
-
-        // Sort by date (oldest first)
-        points.sort_by(|a, b| a.date.cmp(&b.date));
-
-        if points.is_empty() {
-            return Err(PriceProviderError::NotFound);
-        }
-
-        Ok(points)
+        chart_points(body)
     }
 
     async fn search_ticker_by_keyword(
@@ -188,6 +331,7 @@ impl PriceProvider for YahooFinanceProvider {
                 _type: "Stock".to_string(),
                 region: "Unknown".to_string(),
                 currency: "Unknown".to_string(),
+                exchange: None,
                 match_score: 1.0,
             }]);
         }
@@ -201,6 +345,7 @@ impl PriceProvider for YahooFinanceProvider {
                 _type: "Stock".to_string(),
                 region: "Canada".to_string(),
                 currency: "CAD".to_string(),
+                exchange: None,
                 match_score: 1.0,
             }]);
         }
@@ -208,4 +353,594 @@ impl PriceProvider for YahooFinanceProvider {
         // No matches found
         Ok(vec![])
     }
+
+    async fn fetch_intraday(
+        &self,
+        ticker: &str,
+        interval: IntradayInterval,
+    ) -> Result<Vec<ExternalIntradayBar>, PriceProviderError> {
+        let url = self.chart_url(ticker);
+        let yahoo_interval = match interval {
+            IntradayInterval::OneMinute => "1m",
+            IntradayInterval::FiveMinutes => "5m",
+            IntradayInterval::FifteenMinutes => "15m",
+            IntradayInterval::ThirtyMinutes => "30m",
+            IntradayInterval::SixtyMinutes => "60m",
+        };
+
+        // A one-day range is the latest session, or the last one before a weekend
+        let resp = self
+            .client
+            .get(&url)
+            .query(&[("interval", yahoo_interval), ("range", "1d")])
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        if !resp.status().is_success() {
+            return Err(status_error(resp.status()));
+        }
+
+        let body: YahooChartResponse = resp
+            .json()
+            .await
+            .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
+
+        chart_bars(body)
+    }
+
+    async fn fetch_corporate_actions(
+        &self,
+        ticker: &str,
+        days: u32,
+    ) -> Result<Vec<ExternalCorporateAction>, PriceProviderError> {
+        let url = self.chart_url(ticker);
+        let resp = self
+            .client
+            .get(&url)
+            .query(&[("interval", "1d"), ("range", chart_range(days)), ("events", "div,splits")])
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        if !resp.status().is_success() {
+            return Err(status_error(resp.status()));
+        }
+
+        let body: YahooChartResponse = resp
+            .json()
+            .await
+            .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
+
+        chart_actions(body)
+    }
+
+    async fn fetch_quote(&self, ticker: &str) -> Result<ExternalQuote, PriceProviderError> {
+        let url = self.chart_url(ticker);
+
+        // A one-day range makes the chart meta carry the previous session's close
+        let resp = self
+            .client
+            .get(&url)
+            .query(&[("interval", "1d"), ("range", "1d")])
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        if !resp.status().is_success() {
+            return Err(status_error(resp.status()));
+        }
+
+        let body: YahooChartResponse = resp
+            .json()
+            .await
+            .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
+
+        if let Some(error) = body.chart.error {
+            return Err(PriceProviderError::BadResponse(error.description));
+        }
+
+        let result = body.chart.result
+            .and_then(|results| results.into_iter().next())
+            .ok_or(PriceProviderError::NotFound)?;
+        let meta = result.meta
+            .ok_or_else(|| PriceProviderError::BadResponse("No quote metadata in response".into()))?;
+        let quote = result.indicators.quote.first();
+
+        let price = meta.regular_market_price
+            .or_else(|| quote.and_then(|q| q.close.iter().rev().find_map(|c| *c)))
+            .ok_or(PriceProviderError::NotFound)?;
+        let previous_close = meta.previous_close
+            .or(meta.chart_previous_close)
+            .ok_or_else(|| PriceProviderError::BadResponse("No previous close in response".into()))?;
+        let open = quote.and_then(|q| q.open.iter().rev().find_map(|o| *o));
+
+        Ok(ExternalQuote { price, open, previous_close })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooSummaryResponse {
+    quote_summary: YahooSummary,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooSummary {
+    result: Option<Vec<YahooSummaryResult>>,
+    error: Option<YahooError>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooSummaryResult {
+    major_holders_breakdown: Option<YahooMajorHolders>,
+    institution_ownership: Option<YahooInstitutionOwnership>,
+    insider_transactions: Option<YahooInsiderTransactions>,
+    recommendation_trend: Option<YahooRecommendationTrend>,
+    financial_data: Option<YahooFinancialData>,
+    top_holdings: Option<YahooTopHoldings>,
+    calendar_events: Option<YahooCalendarEvents>,
+    summary_detail: Option<YahooSummaryDetail>,
+    default_key_statistics: Option<YahooKeyStatistics>,
+}
+
+/// Yahoo wraps numbers as `{"raw": 0.61, "fmt": "61%"}`
+#[derive(Debug, Default, Deserialize)]
+struct YahooValue {
+    raw: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooMajorHolders {
+    insiders_percent_held: Option<YahooValue>,
+    institutions_percent_held: Option<YahooValue>,
+    institutions_count: Option<YahooValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooInstitutionOwnership {
+    #[serde(default)]
+    ownership_list: Vec<YahooInstitution>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooInstitution {
+    organization: String,
+    pct_held: Option<YahooValue>,
+    position: Option<YahooValue>,
+    report_date: Option<YahooValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct YahooInsiderTransactions {
+    #[serde(default)]
+    transactions: Vec<YahooInsiderTransaction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooInsiderTransaction {
+    filer_name: String,
+    filer_relation: Option<String>,
+    #[serde(default)]
+    transaction_text: String,
+    shares: Option<YahooValue>,
+    value: Option<YahooValue>,
+    start_date: Option<YahooValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct YahooRecommendationTrend {
+    #[serde(default)]
+    trend: Vec<YahooRecommendationPeriod>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooRecommendationPeriod {
+    /// "0m" is the current month, "-1m" the previous one, ...
+    period: String,
+    #[serde(default)]
+    strong_buy: i32,
+    #[serde(default)]
+    buy: i32,
+    #[serde(default)]
+    hold: i32,
+    #[serde(default)]
+    sell: i32,
+    #[serde(default)]
+    strong_sell: i32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooFinancialData {
+    target_mean_price: Option<YahooValue>,
+    target_high_price: Option<YahooValue>,
+    target_low_price: Option<YahooValue>,
+    number_of_analyst_opinions: Option<YahooValue>,
+    recommendation_mean: Option<YahooValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooTopHoldings {
+    #[serde(default)]
+    holdings: Vec<YahooFundHolding>,
+    /// One single-key object per sector, e.g. `{"technology": {"raw": 0.31}}`
+    #[serde(default)]
+    sector_weightings: Vec<std::collections::HashMap<String, YahooValue>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooFundHolding {
+    symbol: Option<String>,
+    holding_name: Option<String>,
+    holding_percent: Option<YahooValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooCalendarEvents {
+    ex_dividend_date: Option<YahooValue>,
+    /// Payment date
+    dividend_date: Option<YahooValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooSummaryDetail {
+    dividend_rate: Option<YahooValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YahooKeyStatistics {
+    last_dividend_value: Option<YahooValue>,
+}
+
+/// Next dividend from the `calendarEvents`, `summaryDetail` and
+/// `defaultKeyStatistics` modules; `None` without an ex-date
+fn dividend_event(result: YahooSummaryResult) -> Option<ExternalDividendEvent> {
+    let calendar = result.calendar_events?;
+    let raw = |v: Option<YahooValue>| v.and_then(|v| v.raw).filter(|r| *r > 0.0);
+    Some(ExternalDividendEvent {
+        ex_date: yahoo_date(&calendar.ex_dividend_date)?,
+        pay_date: yahoo_date(&calendar.dividend_date),
+        amount: raw(result.default_key_statistics.unwrap_or_default().last_dividend_value),
+        annual_rate: raw(result.summary_detail.unwrap_or_default().dividend_rate),
+    })
+}
+
+/// Fund holdings and sector weights from the `topHoldings` module, as percents.
+/// Yahoo only lists the largest (usually ten) holdings.
+fn top_holdings(top: YahooTopHoldings) -> ExternalEtfHoldings {
+    let holdings = top
+        .holdings
+        .into_iter()
+        .filter_map(|h| {
+            let weight = h.holding_percent?.raw? * 100.0;
+            let ticker = h.symbol.filter(|s| !s.is_empty());
+            let name = h.holding_name.or_else(|| ticker.clone())?;
+            Some(ExternalEtfHolding { ticker, name, weight, sector: None })
+        })
+        .collect();
+    let sector_weights = top
+        .sector_weightings
+        .into_iter()
+        .flatten()
+        .filter_map(|(sector, value)| Some((sector, value.raw? * 100.0)))
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect();
+
+    ExternalEtfHoldings {
+        holdings,
+        sector_weights,
+        source: "yahoo".to_string(),
+    }
+}
+
+fn yahoo_date(value: &Option<YahooValue>) -> Option<chrono::NaiveDate> {
+    let timestamp = value.as_ref()?.raw? as i64;
+    chrono::DateTime::from_timestamp(timestamp, 0).map(|dt| dt.date_naive())
+}
+
+/// Map Yahoo's free-text transaction description to a Form 4 transaction type
+fn insider_transaction_type(text: &str) -> Option<InsiderTransactionType> {
+    let text = text.to_lowercase();
+    if text.starts_with("sale") {
+        Some(InsiderTransactionType::Sale)
+    } else if text.starts_with("purchase") {
+        Some(InsiderTransactionType::Purchase)
+    } else if text.contains("exercise") {
+        Some(InsiderTransactionType::Exercise)
+    } else if text.contains("award") || text.contains("grant") {
+        Some(InsiderTransactionType::Grant)
+    } else {
+        None
+    }
+}
+
+impl YahooFinanceProvider {
+    /// Fetch the requested quoteSummary modules for a ticker
+    async fn fetch_quote_summary(&self, ticker: &str, modules: &str) -> Result<YahooSummaryResult, PriceProviderError> {
+        let url = format!(
+            "{}/v10/finance/quoteSummary/{}",
+            self.base_url.as_deref().unwrap_or(SUMMARY_HOST),
+            ticker
+        );
+
+        let resp = self
+            .client
+            .get(&url)
+            .query(&[("modules", modules)])
+            .send()
+            .await
+            .map_err(|e| PriceProviderError::Network(e.to_string()))?;
+
+        if !resp.status().is_success() {
+            return Err(status_error(resp.status()));
+        }
+
+        let body: YahooSummaryResponse = resp
+            .json()
+            .await
+            .map_err(|e| PriceProviderError::Parse(e.to_string()))?;
+
+        if let Some(error) = body.quote_summary.error {
+            return Err(PriceProviderError::BadResponse(error.description));
+        }
+
+        body.quote_summary.result
+            .and_then(|results| results.into_iter().next())
+            .ok_or(PriceProviderError::NotFound)
+    }
+}
+
+#[async_trait]
+impl OwnershipProvider for YahooFinanceProvider {
+    async fn fetch_ownership(&self, ticker: &str) -> Result<ExternalOwnership, PriceProviderError> {
+        let result = self
+            .fetch_quote_summary(ticker, "majorHoldersBreakdown,institutionOwnership,insiderTransactions")
+            .await?;
+
+        let holders = result.major_holders_breakdown.unwrap_or_default();
+        let percent = |v: Option<YahooValue>| v.and_then(|v| v.raw).map(|raw| raw * 100.0);
+
+        let institutional_holders = result
+            .institution_ownership
+            .unwrap_or_default()
+            .ownership_list
+            .into_iter()
+            .map(|i| InstitutionalHolder {
+                report_date: yahoo_date(&i.report_date),
+                pct_held: percent(i.pct_held),
+                shares: i.position.and_then(|p| p.raw).map(|s| s as i64),
+                name: i.organization,
+            })
+            .collect();
+
+        let insider_transactions = result
+            .insider_transactions
+            .unwrap_or_default()
+            .transactions
+            .into_iter()
+            .filter_map(|t| {
+                let transaction_type = insider_transaction_type(&t.transaction_text)?;
+                let transaction_date = yahoo_date(&t.start_date)?;
+                let shares = t.shares.as_ref().and_then(|s| s.raw).unwrap_or(0.0);
+                let price_per_share = t
+                    .value
+                    .and_then(|v| v.raw)
+                    .filter(|_| shares > 0.0)
+                    .and_then(|value| BigDecimal::try_from(value / shares).ok());
+                Some(InsiderTransaction {
+                    ticker: ticker.to_string(),
+                    transaction_date,
+                    reporting_person: t.filer_name,
+                    title: t.filer_relation,
+                    transaction_type,
+                    shares: shares as i64,
+                    price_per_share,
+                    ownership_after: None,
+                })
+            })
+            .collect();
+
+        Ok(ExternalOwnership {
+            insider_pct: percent(holders.insiders_percent_held),
+            institutional_pct: percent(holders.institutions_percent_held),
+            institution_count: holders.institutions_count.and_then(|c| c.raw).map(|c| c as i64),
+            institutional_holders,
+            insider_transactions,
+        })
+    }
+}
+
+#[async_trait]
+impl AnalystProvider for YahooFinanceProvider {
+    async fn fetch_analyst_consensus(&self, ticker: &str) -> Result<ExternalAnalystConsensus, PriceProviderError> {
+        let result = self.fetch_quote_summary(ticker, "recommendationTrend,financialData").await?;
+
+        let ratings = result
+            .recommendation_trend
+            .unwrap_or_default()
+            .trend
+            .into_iter()
+            .find(|t| t.period == "0m")
+            .map(|t| RatingDistribution {
+                strong_buy: t.strong_buy,
+                buy: t.buy,
+                hold: t.hold,
+                sell: t.sell,
+                strong_sell: t.strong_sell,
+            })
+            .unwrap_or_default();
+        let financial = result.financial_data.unwrap_or_default();
+        let raw = |v: Option<YahooValue>| v.and_then(|v| v.raw);
+
+        if ratings.total() == 0 && financial.target_mean_price.is_none() {
+            return Err(PriceProviderError::NotFound);
+        }
+
+        Ok(ExternalAnalystConsensus {
+            ratings,
+            target_mean: raw(financial.target_mean_price),
+            target_high: raw(financial.target_high_price),
+            target_low: raw(financial.target_low_price),
+            analyst_count: raw(financial.number_of_analyst_opinions).map(|n| n as i64),
+            recommendation_mean: raw(financial.recommendation_mean),
+        })
+    }
+}
+
+#[async_trait]
+impl EtfHoldingsProvider for YahooFinanceProvider {
+    async fn fetch_etf_holdings(&self, etf: &str) -> Result<ExternalEtfHoldings, PriceProviderError> {
+        let result = self.fetch_quote_summary(etf, "topHoldings").await?;
+        let holdings = top_holdings(result.top_holdings.ok_or(PriceProviderError::NotFound)?);
+        if holdings.holdings.is_empty() && holdings.sector_weights.is_empty() {
+            return Err(PriceProviderError::NotFound);
+        }
+        Ok(holdings)
+    }
+}
+
+#[async_trait]
+impl DividendProvider for YahooFinanceProvider {
+    async fn fetch_dividend_event(&self, ticker: &str) -> Result<ExternalDividendEvent, PriceProviderError> {
+        let result = self
+            .fetch_quote_summary(ticker, "calendarEvents,summaryDetail,defaultKeyStatistics")
+            .await?;
+        dividend_event(result).ok_or(PriceProviderError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::ToPrimitive;
+
+    #[test]
+    fn test_chart_points_skip_null_closes() {
+        // 2024-01-02, 2024-01-03 (no close), 2024-01-04 at 14:30 UTC
+        let body: YahooChartResponse = serde_json::from_str(
+            r#"{"chart":{"result":[{"meta":{"regularMarketPrice":186.5},
+                "timestamp":[1704205800,1704292200,1704378600],
+                "indicators":{"quote":[{"open":[187.1,null,182.1],"close":[185.64,null,181.91]}]}}],
+                "error":null}}"#,
+        )
+        .unwrap();
+
+        let points = chart_points(body).unwrap();
+        let dates: Vec<String> = points.iter().map(|p| p.date.to_string()).collect();
+        assert_eq!(dates, vec!["2024-01-02", "2024-01-04"]);
+        assert!((points[0].close.to_f64().unwrap() - 185.64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chart_bars_from_intraday_chart() {
+        // 2026-03-06 14:30, 14:35 (no trade) and 14:40 UTC
+        let body: YahooChartResponse = serde_json::from_str(
+            r#"{"chart":{"result":[{"meta":{"regularMarketPrice":186.5},
+                "timestamp":[1772807400,1772807700,1772808000],
+                "indicators":{"quote":[{"open":[185.0,null,186.1],"high":[185.9,null,186.6],
+                    "low":[184.8,null,186.0],"close":[185.7,null,186.5],"volume":[120400,null,98100]}]}}],
+                "error":null}}"#,
+        )
+        .unwrap();
+
+        let bars = chart_bars(body).unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].timestamp.to_rfc3339(), "2026-03-06T14:30:00+00:00");
+        assert_eq!((bars[0].open, bars[0].high, bars[0].low, bars[0].close), (185.0, 185.9, 184.8, 185.7));
+        assert_eq!(bars[1].volume, Some(98100));
+    }
+
+    #[test]
+    fn test_chart_actions_from_events() {
+        // AAPL's 4-for-1 split of 2020-08-31 and a dividend on 2020-08-07
+        let body: YahooChartResponse = serde_json::from_str(
+            r#"{"chart":{"result":[{"meta":{"regularMarketPrice":129.0},
+                "timestamp":[1598880600],
+                "indicators":{"quote":[{"close":[129.04]}]},
+                "events":{"dividends":{"1596807000":{"amount":0.82,"date":1596807000}},
+                          "splits":{"1598880600":{"date":1598880600,"numerator":4,"denominator":1,"splitRatio":"4:1"}}}}],
+                "error":null}}"#,
+        )
+        .unwrap();
+
+        let actions = chart_actions(body).unwrap();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].action_type, CorporateActionType::Dividend);
+        assert_eq!(actions[0].ex_date.to_string(), "2020-08-07");
+        assert_eq!(actions[0].value, 0.82);
+        assert_eq!(actions[1].action_type, CorporateActionType::Split);
+        assert_eq!(actions[1].ex_date.to_string(), "2020-08-31");
+        assert_eq!(actions[1].value, 4.0);
+    }
+
+    #[test]
+    fn test_chart_errors_map_to_provider_errors() {
+        let body: YahooChartResponse = serde_json::from_str(
+            r#"{"chart":{"result":null,"error":{"code":"Not Found","description":"No data found, symbol may be delisted"}}}"#,
+        )
+        .unwrap();
+        assert!(matches!(chart_points(body), Err(PriceProviderError::NotFound)));
+
+        assert!(matches!(status_error(reqwest::StatusCode::TOO_MANY_REQUESTS), PriceProviderError::RateLimited));
+        assert!(matches!(status_error(reqwest::StatusCode::NOT_FOUND), PriceProviderError::NotFound));
+    }
+
+    #[test]
+    fn test_top_holdings_convert_to_percent() {
+        let body: YahooSummaryResponse = serde_json::from_str(
+            r#"{"quoteSummary":{"result":[{"topHoldings":{
+                "holdings":[{"symbol":"AAPL","holdingName":"Apple Inc","holdingPercent":{"raw":0.0712,"fmt":"7.12%"}},
+                            {"symbol":"","holdingName":"Cash","holdingPercent":{"raw":0.002}}],
+                "sectorWeightings":[{"technology":{"raw":0.312}},{"realestate":{"raw":0.0}}]}}],
+                "error":null}}"#,
+        )
+        .unwrap();
+        let top = body.quote_summary.result.unwrap().remove(0).top_holdings.unwrap();
+
+        let holdings = top_holdings(top);
+        assert_eq!(holdings.holdings[0].ticker.as_deref(), Some("AAPL"));
+        assert!((holdings.holdings[0].weight - 7.12).abs() < 1e-9);
+        assert_eq!(holdings.holdings[1].ticker, None);
+        // Zero-weight sectors are dropped
+        assert_eq!(holdings.sector_weights.len(), 1);
+        assert_eq!(holdings.sector_weights[0].0, "technology");
+        assert!((holdings.sector_weights[0].1 - 31.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dividend_event_from_calendar() {
+        let body: YahooSummaryResponse = serde_json::from_str(
+            r#"{"quoteSummary":{"result":[{
+                "calendarEvents":{"exDividendDate":{"raw":1707350400,"fmt":"2024-02-08"},
+                                  "dividendDate":{"raw":1707955200,"fmt":"2024-02-15"}},
+                "summaryDetail":{"dividendRate":{"raw":0.96,"fmt":"0.96"}},
+                "defaultKeyStatistics":{"lastDividendValue":{"raw":0.24,"fmt":"0.24"}}}],
+                "error":null}}"#,
+        )
+        .unwrap();
+        let event = dividend_event(body.quote_summary.result.unwrap().remove(0)).unwrap();
+        assert_eq!(event.ex_date.to_string(), "2024-02-08");
+        assert_eq!(event.pay_date.map(|d| d.to_string()).as_deref(), Some("2024-02-15"));
+        assert_eq!(event.amount, Some(0.24));
+        assert_eq!(event.annual_rate, Some(0.96));
+
+        // Non-payers have no ex-date
+        let body: YahooSummaryResponse = serde_json::from_str(
+            r#"{"quoteSummary":{"result":[{"calendarEvents":{"earnings":{}},"summaryDetail":{}}],"error":null}}"#,
+        )
+        .unwrap();
+        assert!(dividend_event(body.quote_summary.result.unwrap().remove(0)).is_none());
+    }
 }
@@ -0,0 +1,43 @@
+//! Advisor Fee Background Job
+//!
+//! Runs daily and regenerates the fee cash flows of every account with a fee
+//! schedule, so month-end charges appear as soon as the month closes and AUM fees
+//! follow the latest account values. Flows are replaced wholesale, so re-running
+//! the job is a no-op.
+
+use crate::db::account_fee_queries;
+use crate::errors::AppError;
+use crate::services::{advisor_fee_service, job_scheduler_service::{JobContext, JobResult}};
+use tracing::{error, info};
+
+/// Main entry point for the account fee job.
+pub async fn generate_account_fees(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting account fee generation job");
+
+    let accounts = account_fee_queries::fetch_accounts_with_schedules(&ctx.pool)
+        .await
+        .map_err(AppError::Db)?;
+
+    let mut processed = 0;
+    let mut failed = 0;
+    for account_id in accounts {
+        match advisor_fee_service::generate_for_account(&ctx.pool, account_id).await {
+            Ok(result) => {
+                info!(
+                    "Generated {} fee flows ({:.2} total) for account {}",
+                    result.flows, result.total_fees, account_id
+                );
+                processed += 1;
+            }
+            Err(e) => {
+                error!("Failed to generate fees for account {}: {}", account_id, e);
+                failed += 1;
+            }
+        }
+    }
+
+    Ok(JobResult {
+        items_processed: processed,
+        items_failed: failed,
+    })
+}
@@ -0,0 +1,57 @@
+//! Corporate Actions Background Job
+//!
+//! Runs every morning before the markets open. Fetches the splits and
+//! dividends of every ticker priced during the last week and, when new ones
+//! appear, rebuilds that ticker's adjusted closes so returns, volatility and
+//! drawdowns are not distorted by a split or an ex-dividend drop. Raw closes
+//! are left untouched for valuations.
+
+use crate::db::price_queries;
+use crate::errors::AppError;
+use crate::services::job_scheduler_service::{JobContext, JobResult};
+use crate::services::{clock, price_service};
+use chrono::Duration as ChronoDuration;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+/// Pause between tickers, on top of the shared rate limiter
+const TICKER_DELAY_MS: u64 = 250;
+
+/// Main entry point for the corporate actions job.
+pub async fn refresh_corporate_actions(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting corporate actions job");
+
+    let since = clock::today() - ChronoDuration::days(7);
+    let tickers = price_queries::fetch_tickers_with_prices_since(&ctx.pool, since).await?;
+
+    let mut adjusted = 0;
+    let mut failed = 0;
+    for ticker in &tickers {
+        match price_service::refresh_corporate_actions(
+            &ctx.pool,
+            ctx.price_provider.as_ref(),
+            ctx.rate_limiter.as_ref(),
+            ticker,
+        )
+        .await
+        {
+            Ok(true) => adjusted += 1,
+            Ok(false) => {}
+            Err(e) => {
+                warn!("Failed to refresh corporate actions for {}: {}", ticker, e);
+                failed += 1;
+            }
+        }
+        sleep(Duration::from_millis(TICKER_DELAY_MS)).await;
+    }
+
+    info!(
+        "Corporate actions job complete: {} tickers checked, {} re-adjusted, {} failed",
+        tickers.len(), adjusted, failed
+    );
+
+    Ok(JobResult {
+        items_processed: (tickers.len() - failed) as i32,
+        items_failed: failed as i32,
+    })
+}
@@ -0,0 +1,51 @@
+//! Crypto Wallet Sync Background Job
+//!
+//! Runs every 4 hours and re-reads the balances of every tracked wallet address,
+//! writing today's crypto holdings snapshot for each account. Re-running it on
+//! the same day overwrites that day's snapshot, so it is safe to repeat.
+
+use crate::db::crypto_wallet_queries;
+use crate::errors::AppError;
+use crate::services::{crypto_wallet_service, job_scheduler_service::{JobContext, JobResult}};
+use tracing::{error, info, warn};
+
+/// Main entry point for the crypto wallet sync job.
+pub async fn sync_crypto_wallets(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting crypto wallet sync job");
+
+    let accounts = crypto_wallet_queries::fetch_accounts_with_wallets(&ctx.pool)
+        .await
+        .map_err(AppError::Db)?;
+
+    let mut processed = 0;
+    let mut failed = 0;
+    for account_id in accounts {
+        match crypto_wallet_service::sync_account(
+            &ctx.pool,
+            account_id,
+            ctx.chain_provider.as_ref(),
+            ctx.price_provider.as_ref(),
+        )
+        .await
+        {
+            Ok(result) if result.failed_wallets.is_empty() => processed += 1,
+            Ok(result) => {
+                warn!(
+                    "Skipped crypto holdings for account {}: could not read {}",
+                    account_id,
+                    result.failed_wallets.join(", ")
+                );
+                failed += 1;
+            }
+            Err(e) => {
+                error!("Failed to sync crypto wallets for account {}: {}", account_id, e);
+                failed += 1;
+            }
+        }
+    }
+
+    Ok(JobResult {
+        items_processed: processed,
+        items_failed: failed,
+    })
+}
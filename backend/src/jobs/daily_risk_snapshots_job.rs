@@ -39,13 +39,17 @@
 //! # Processing Strategy
 //!
 //! 1. Query all portfolios with active holdings
-//! 2. For each portfolio:
-//!    - Call `risk_snapshot_service::create_daily_snapshots()`
-//!    - This creates both portfolio-level and position-level snapshots
+//! 2. For each portfolio, dated by the calendar day in its owner's time zone:
+//!    - Call `risk_snapshot_service::create_incremental_snapshots()`
+//!    - Tickers with prices stored since the previous snapshot are recomputed,
+//!      along with the portfolio-level snapshot; unchanged positions are
+//!      carried forward, and a portfolio with no new prices is copied whole
+//!    - New or removed holdings, a new benchmark close, or a last full
+//!      recompute a week or more ago trigger a full recompute
 //!    - Snapshots are upserted into `risk_snapshots` table
 //!    - Errors for individual positions don't stop portfolio processing
 //! 3. Track success/failure counts for monitoring
-//! 4. Add 1-second delay between portfolios to respect rate limits
+//! 4. Add 1-second delay between recomputed portfolios to respect rate limits
 //!
 //! # Error Handling
 //!
@@ -59,14 +63,21 @@
 //!
 //! - Runs after market close to capture end-of-day prices
 //! - Uses existing price data (no additional fetching needed)
+//! - Skips recomputing positions whose prices haven't changed (weekends, holidays)
 //! - Implements delays between portfolios to respect rate limits
 //! - Leverages existing risk calculation infrastructure
 //! - Designed for idempotent execution (can be safely re-run)
 
+use crate::db::user_preferences_queries;
 use crate::errors::AppError;
-use crate::services::{job_scheduler_service::{JobContext, JobResult}, risk_snapshot_service};
-use chrono::Utc;
+use crate::services::{
+    clock,
+    job_scheduler_service::{JobContext, JobResult},
+    risk_snapshot_service::{self, SnapshotPlan},
+    timezone,
+};
 use sqlx::PgPool;
+use std::collections::HashMap;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
@@ -88,11 +99,7 @@ const INTER_PORTFOLIO_DELAY_MS: u64 = 1000;
 /// * `Ok(JobResult)` - Success with counts of processed and failed portfolios
 /// * `Err(AppError)` - Critical failure that should stop the job
 pub async fn create_all_daily_risk_snapshots(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("📸 Starting daily risk snapshots job");
-
-    // Get today's date for snapshots
-    let today = Utc::now().date_naive();
-    info!("Creating snapshots for date: {}", today);
+    info!("Starting daily risk snapshots job");
 
     // Query all portfolios with holdings
     let portfolios = query_portfolios_with_holdings(&ctx.pool).await?;
@@ -116,12 +123,22 @@ pub async fn create_all_daily_risk_snapshots(ctx: JobContext) -> Result<JobResul
         .and_then(|v| v.parse::<f64>().ok())
         .unwrap_or(0.045); // Default 4.5%
 
+    // Snapshots are dated by the calendar day in the portfolio owner's time zone
+    let now = clock::now();
+    let timezones: HashMap<Uuid, String> =
+        user_preferences_queries::fetch_portfolio_timezones(&ctx.pool, &portfolios).await?.into_iter().collect();
+
     // Process each portfolio
     for portfolio_id in portfolios {
-        info!("Creating snapshots for portfolio {}...", portfolio_id);
+        let tz = timezone::resolve_or_default(
+            timezones.get(&portfolio_id).map_or(timezone::DEFAULT_TIMEZONE, String::as_str),
+        );
+        let today = tz.local_date(now);
+        info!("Creating snapshots for portfolio {} dated {} ({})...", portfolio_id, today, tz.name());
 
         // Create daily snapshots for this portfolio
-        match risk_snapshot_service::create_daily_snapshots(
+        let mut recomputed_any = true;
+        match risk_snapshot_service::create_incremental_snapshots(
             &ctx.pool,
             portfolio_id,
             today,
@@ -132,14 +149,17 @@ pub async fn create_all_daily_risk_snapshots(ctx: JobContext) -> Result<JobResul
         )
         .await
         {
-            Ok(snapshots) => {
-                let snapshot_count = snapshots.len();
+            Ok(run) => {
+                let mode = match &run.plan {
+                    SnapshotPlan::Full => "full",
+                    SnapshotPlan::Incremental(_) => "incremental",
+                    SnapshotPlan::CarryForward => "carried forward",
+                };
                 info!(
-                    "✅ Created {} snapshots for portfolio {} (1 portfolio + {} positions)",
-                    snapshot_count,
-                    portfolio_id,
-                    snapshot_count.saturating_sub(1)
+                    "Snapshots for portfolio {} ({}): {} recomputed, {} carried forward",
+                    portfolio_id, mode, run.recomputed, run.carried_forward
                 );
+                recomputed_any = run.recomputed > 0;
                 processed += 1;
             }
             Err(e) => {
@@ -147,14 +167,14 @@ pub async fn create_all_daily_risk_snapshots(ctx: JobContext) -> Result<JobResul
                 let error_str = e.to_string();
                 if error_str.contains("No holdings found") {
                     warn!(
-                        "⚠️  Portfolio {} has no holdings, skipping snapshot",
+                        "Portfolio {} has no holdings, skipping snapshot",
                         portfolio_id
                     );
                     // Count as processed since this is not an error condition
                     processed += 1;
                 } else {
                     error!(
-                        "❌ Failed to create snapshots for portfolio {}: {}",
+                        "Failed to create snapshots for portfolio {}: {}",
                         portfolio_id, e
                     );
                     failed += 1;
@@ -163,14 +183,16 @@ pub async fn create_all_daily_risk_snapshots(ctx: JobContext) -> Result<JobResul
         }
 
         // Add delay between portfolios to avoid rate limiting
-        tokio::time::sleep(tokio::time::Duration::from_millis(
-            INTER_PORTFOLIO_DELAY_MS,
-        ))
-        .await;
+        if recomputed_any {
+            tokio::time::sleep(tokio::time::Duration::from_millis(
+                INTER_PORTFOLIO_DELAY_MS,
+            ))
+            .await;
+        }
     }
 
     info!(
-        "✅ Daily risk snapshots job completed: {} portfolios processed, {} failed",
+        "Daily risk snapshots job completed: {} portfolios processed, {} failed",
         processed, failed
     );
 
@@ -201,6 +223,7 @@ async fn query_portfolios_with_holdings(pool: &PgPool) -> Result<Vec<Uuid>, AppE
         FROM portfolios p
         INNER JOIN accounts a ON a.portfolio_id = p.id
         INNER JOIN holdings_snapshots hs ON hs.account_id = a.id
+        WHERE p.archived_at IS NULL
         ORDER BY p.id
         "#,
     )
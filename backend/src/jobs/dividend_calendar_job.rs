@@ -0,0 +1,72 @@
+//! Dividend Calendar Background Job
+//!
+//! Runs every morning before the notification digests. Stores the next
+//! ex-dividend date of every held ticker, then evaluates `ex_dividend` alert
+//! rules: each rule reminds its owner once per ticker and ex-date, as soon as
+//! the ex-date is within the rule's `days_before` window.
+
+use crate::db::{alert_queries, dividend_queries};
+use crate::errors::AppError;
+use crate::models::alert::{AlertRule, AlertType};
+use crate::services::{alert_service, clock, dividend_calendar_service, notification_service};
+use crate::services::job_scheduler_service::{JobContext, JobResult};
+use tracing::{error, info};
+
+/// Main entry point for the dividend calendar job.
+pub async fn refresh_dividend_calendar(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting dividend calendar job");
+
+    let pool = ctx.pool.as_ref();
+    let refresh = dividend_calendar_service::refresh_held_tickers(pool, ctx.dividend_provider.as_ref()).await?;
+    info!(
+        "Stored dividends for {} tickers ({} non-payers, {} failed)",
+        refresh.stored, refresh.non_payers, refresh.failed
+    );
+
+    let rules: Vec<(AlertRule, i64)> = alert_queries::get_all_active_alert_rules(pool)
+        .await?
+        .into_iter()
+        .filter_map(|rule| match serde_json::from_str::<AlertType>(&rule.rule_type) {
+            Ok(AlertType::ExDividend { days_before }) => Some((rule, days_before)),
+            _ => None,
+        })
+        .collect();
+    let today = clock::today();
+    let mut failed = refresh.failed as i32;
+    let mut reminded = 0;
+
+    for (rule, days_before) in &rules {
+        let result = async {
+            let mut due = Vec::new();
+            for event in alert_service::upcoming_ex_dividends(pool, rule, *days_before).await? {
+                if dividend_queries::claim_reminder(pool, rule.id, &event.ticker, event.ex_date).await? {
+                    due.push(event);
+                }
+            }
+            let Some(result) = alert_service::ex_dividend_result(rule, *days_before, &due, today) else {
+                return Ok(false);
+            };
+            let history = alert_service::process_triggered_alert(pool, rule, &result).await?;
+            notification_service::send_notification(pool, rule.user_id, &history).await?;
+            Ok::<bool, sqlx::Error>(true)
+        }
+        .await;
+
+        match result {
+            Ok(true) => reminded += 1,
+            Ok(false) => {}
+            Err(e) => {
+                error!("Failed to evaluate ex-dividend rule {}: {}", rule.id, e);
+                failed += 1;
+            }
+        }
+    }
+
+    info!("Ex-dividend reminders: {} rules evaluated, {} reminded", rules.len(), reminded);
+
+    Ok(JobResult {
+        items_processed: refresh.stored as i32 + rules.len() as i32,
+        items_failed: failed,
+    })
+}
+
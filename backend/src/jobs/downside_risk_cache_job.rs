@@ -17,6 +17,8 @@
 //! 4. Store results in downside_risk_cache table
 //! 5. Use delays to avoid overwhelming external APIs
 
+use crate::db::risk_cache_queries;
+use crate::db::tenant::TenantScope;
 use crate::errors::AppError;
 use crate::services::job_scheduler_service::{JobContext, JobResult};
 use crate::services::risk_service;
@@ -31,25 +33,29 @@ const COMPUTATION_TIMEOUT_SECONDS: u64 = 300; // 5 minute timeout for downside r
 
 /// Main entry point for downside risk cache population
 pub async fn populate_downside_risk_caches(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🔄 [DOWNSIDE_RISK_JOB] Starting downside risk cache population job");
+    info!("[DOWNSIDE_RISK_JOB] Starting downside risk cache population job");
 
     // Get all portfolios with positions
-    info!("🔍 [DOWNSIDE_RISK_JOB] Querying portfolios with positions...");
+    info!("[DOWNSIDE_RISK_JOB] Querying portfolios with positions...");
     let portfolios = sqlx::query_scalar::<_, Uuid>(
-        "SELECT DISTINCT portfolio_id FROM positions ORDER BY portfolio_id"
+        "SELECT DISTINCT pos.portfolio_id
+         FROM positions pos
+         JOIN portfolios p ON p.id = pos.portfolio_id
+         WHERE p.archived_at IS NULL
+         ORDER BY pos.portfolio_id"
     )
     .fetch_all(ctx.pool.as_ref())
     .await?;
 
     if portfolios.is_empty() {
-        info!("⚠️ [DOWNSIDE_RISK_JOB] No portfolios found to cache");
+        info!("[DOWNSIDE_RISK_JOB] No portfolios found to cache");
         return Ok(JobResult {
             items_processed: 0,
             items_failed: 0,
         });
     }
 
-    info!("✅ [DOWNSIDE_RISK_JOB] Found {} portfolios to process", portfolios.len());
+    info!("[DOWNSIDE_RISK_JOB] Found {} portfolios to process", portfolios.len());
 
     let mut processed = 0;
     let mut failed = 0;
@@ -59,25 +65,30 @@ pub async fn populate_downside_risk_caches(ctx: JobContext) -> Result<JobResult,
     let benchmark = "SPY";
 
     for (index, portfolio_id) in portfolios.iter().enumerate() {
-        info!("📊 [DOWNSIDE_RISK_JOB] Processing portfolio {}/{}: {}", index + 1, portfolios.len(), portfolio_id);
+        info!("[DOWNSIDE_RISK_JOB] Processing portfolio {}/{}: {}", index + 1, portfolios.len(), portfolio_id);
+
+        // Cache entries are scoped to the portfolio's owner
+        let Some(tenant) = TenantScope::for_job(ctx.pool.as_ref(), *portfolio_id).await? else {
+            continue;
+        };
 
         // Check if cache needs refresh
-        info!("🔍 [DOWNSIDE_RISK_JOB] Checking cache status for portfolio {}", portfolio_id);
+        info!("[DOWNSIDE_RISK_JOB] Checking cache status for portfolio {}", portfolio_id);
         let needs_refresh = check_cache_needs_refresh(
             ctx.pool.as_ref(),
-            *portfolio_id,
+            &tenant,
             days,
             benchmark,
         )
         .await?;
 
         if !needs_refresh {
-            info!("⏭️ [DOWNSIDE_RISK_JOB] Cache for portfolio {} is still fresh, skipping", portfolio_id);
+            info!("[DOWNSIDE_RISK_JOB] Cache for portfolio {} is still fresh, skipping", portfolio_id);
             processed += 1;
             continue;
         }
 
-        info!("🚀 [DOWNSIDE_RISK_JOB] Starting computation for portfolio {} (timeout: {}s)", portfolio_id, COMPUTATION_TIMEOUT_SECONDS);
+        info!("[DOWNSIDE_RISK_JOB] Starting computation for portfolio {} (timeout: {}s)", portfolio_id, COMPUTATION_TIMEOUT_SECONDS);
         let start_time = std::time::Instant::now();
 
         // Compute and cache downside risk with timeout
@@ -85,7 +96,7 @@ pub async fn populate_downside_risk_caches(ctx: JobContext) -> Result<JobResult,
             tokio::time::Duration::from_secs(COMPUTATION_TIMEOUT_SECONDS),
             compute_and_cache_downside_risk(
                 &ctx,
-                *portfolio_id,
+                &tenant,
                 days,
                 benchmark,
             )
@@ -97,25 +108,25 @@ pub async fn populate_downside_risk_caches(ctx: JobContext) -> Result<JobResult,
         match computation_result {
             Ok(Ok(_)) => {
                 processed += 1;
-                info!("✅ [DOWNSIDE_RISK_JOB] Successfully cached downside risk for portfolio {} (took {:.2}s)", portfolio_id, elapsed.as_secs_f64());
+                info!("[DOWNSIDE_RISK_JOB] Successfully cached downside risk for portfolio {} (took {:.2}s)", portfolio_id, elapsed.as_secs_f64());
             }
             Ok(Err(e)) => {
                 failed += 1;
-                warn!("❌ [DOWNSIDE_RISK_JOB] Failed to cache downside risk for portfolio {}: {} (took {:.2}s)", portfolio_id, e, elapsed.as_secs_f64());
+                warn!("[DOWNSIDE_RISK_JOB] Failed to cache downside risk for portfolio {}: {} (took {:.2}s)", portfolio_id, e, elapsed.as_secs_f64());
             }
             Err(_) => {
                 failed += 1;
-                warn!("⏱️ [DOWNSIDE_RISK_JOB] Downside risk computation TIMED OUT after {} seconds for portfolio {}", COMPUTATION_TIMEOUT_SECONDS, portfolio_id);
+                warn!("[DOWNSIDE_RISK_JOB] Downside risk computation TIMED OUT after {} seconds for portfolio {}", COMPUTATION_TIMEOUT_SECONDS, portfolio_id);
             }
         }
 
         // Delay to avoid rate limiting external APIs
-        info!("⏸️ [DOWNSIDE_RISK_JOB] Waiting {}ms before next portfolio...", INTER_PORTFOLIO_DELAY_MS);
+        info!("[DOWNSIDE_RISK_JOB] Waiting {}ms before next portfolio...", INTER_PORTFOLIO_DELAY_MS);
         tokio::time::sleep(tokio::time::Duration::from_millis(INTER_PORTFOLIO_DELAY_MS)).await;
     }
 
     info!(
-        "🏁 [DOWNSIDE_RISK_JOB] Downside risk cache population COMPLETED: {} processed, {} failed",
+        "[DOWNSIDE_RISK_JOB] Downside risk cache population COMPLETED: {} processed, {} failed",
         processed, failed
     );
 
@@ -128,19 +139,11 @@ pub async fn populate_downside_risk_caches(ctx: JobContext) -> Result<JobResult,
 /// Check if cache needs refresh
 async fn check_cache_needs_refresh(
     pool: &sqlx::PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     days: i64,
     benchmark: &str,
 ) -> Result<bool, AppError> {
-    let result = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
-        "SELECT expires_at FROM downside_risk_cache
-         WHERE portfolio_id = $1 AND days = $2 AND benchmark = $3"
-    )
-    .bind(portfolio_id)
-    .bind(days as i32)
-    .bind(benchmark)
-    .fetch_optional(pool)
-    .await?;
+    let result = risk_cache_queries::fetch_downside_risk_expiry(pool, tenant, days as i32, benchmark).await?;
 
     match result {
         Some(expires_at) => {
@@ -156,11 +159,12 @@ async fn check_cache_needs_refresh(
 /// Compute downside risk and store in cache
 async fn compute_and_cache_downside_risk(
     ctx: &JobContext,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     days: i64,
     benchmark: &str,
 ) -> Result<(), AppError> {
-    info!("🧮 [COMPUTE_DOWNSIDE] Starting computation for portfolio {} (days={}, benchmark={})", portfolio_id, days, benchmark);
+    let portfolio_id = tenant.portfolio_id();
+    info!("[COMPUTE_DOWNSIDE] Starting computation for portfolio {} (days={}, benchmark={})", portfolio_id, days, benchmark);
 
     // Compute downside risk analysis
     let compute_start = std::time::Instant::now();
@@ -176,25 +180,25 @@ async fn compute_and_cache_downside_risk(
     )
     .await?;
     let compute_elapsed = compute_start.elapsed();
-    info!("✅ [COMPUTE_DOWNSIDE] Computation completed for portfolio {} in {:.2}s", portfolio_id, compute_elapsed.as_secs_f64());
+    info!("[COMPUTE_DOWNSIDE] Computation completed for portfolio {} in {:.2}s", portfolio_id, compute_elapsed.as_secs_f64());
 
-    info!("📝 [COMPUTE_DOWNSIDE] Serializing risk data to JSONB for portfolio {}", portfolio_id);
+    info!("[COMPUTE_DOWNSIDE] Serializing risk data to JSONB for portfolio {}", portfolio_id);
     // Serialize to JSONB
     let risk_data = serde_json::to_value(&downside_risk)
         .map_err(|e| AppError::External(format!("Failed to serialize risk data: {}", e)))?;
 
     let expires_at = (Utc::now() + Duration::hours(CACHE_EXPIRATION_HOURS)).naive_utc();
 
-    info!("💾 [COMPUTE_DOWNSIDE] Storing in cache for portfolio {} (expires in {} hours)", portfolio_id, CACHE_EXPIRATION_HOURS);
+    info!("[COMPUTE_DOWNSIDE] Storing in cache for portfolio {} (expires in {} hours)", portfolio_id, CACHE_EXPIRATION_HOURS);
     // Upsert into cache
     let db_start = std::time::Instant::now();
     sqlx::query!(
         r#"
         INSERT INTO downside_risk_cache (
             portfolio_id, days, benchmark,
-            risk_data, calculated_at, expires_at
+            risk_data, calculated_at, expires_at, user_id
         )
-        VALUES ($1, $2, $3, $4, NOW(), $5)
+        VALUES ($1, $2, $3, $4, NOW(), $5, $6)
         ON CONFLICT (portfolio_id, days, benchmark)
         DO UPDATE SET
             risk_data = EXCLUDED.risk_data,
@@ -205,12 +209,13 @@ async fn compute_and_cache_downside_risk(
         days as i32,
         benchmark,
         risk_data,
-        expires_at
+        expires_at,
+        tenant.user_id()
     )
     .execute(ctx.pool.as_ref())
     .await?;
     let db_elapsed = db_start.elapsed();
-    info!("✅ [COMPUTE_DOWNSIDE] Cached successfully for portfolio {} (DB write took {:.2}s)", portfolio_id, db_elapsed.as_secs_f64());
+    info!("[COMPUTE_DOWNSIDE] Cached successfully for portfolio {} (DB write took {:.2}s)", portfolio_id, db_elapsed.as_secs_f64());
 
     Ok(())
 }
@@ -0,0 +1,27 @@
+//! ETF Constituent Background Job
+//!
+//! Runs weekly and stores the constituents and sector weights of every ETF
+//! held in an account, from issuer holdings files when configured and the ETF
+//! holdings provider otherwise. Funds rebalance slowly, so ETFs refreshed
+//! during the last six days are skipped; a failed ETF keeps its previous data.
+
+use crate::errors::AppError;
+use crate::services::{etf_constituent_service, job_scheduler_service::{JobContext, JobResult}};
+use tracing::info;
+
+/// Main entry point for the ETF constituent job.
+pub async fn refresh_etf_constituents(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting ETF constituent job");
+
+    let refresh = etf_constituent_service::refresh_held_etfs(&ctx.pool, ctx.etf_holdings_provider.as_ref(), false).await?;
+
+    info!(
+        "ETF constituent job complete: {} refreshed, {} up to date, {} failed",
+        refresh.refreshed, refresh.skipped, refresh.failed
+    );
+
+    Ok(JobResult {
+        items_processed: refresh.refreshed as i32,
+        items_failed: refresh.failed as i32,
+    })
+}
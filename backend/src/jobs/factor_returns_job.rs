@@ -0,0 +1,32 @@
+//! Factor Returns Background Job
+//!
+//! Runs weekly. Downloads the daily Fama-French factors and the momentum
+//! factor from Kenneth French's data library and stores them for the
+//! per-holding factor regressions. The library is revised monthly, so the
+//! whole history is re-read and only changed rows are written.
+
+use crate::errors::AppError;
+use crate::services::{factor_regression_service, job_scheduler_service::{JobContext, JobResult}};
+use tracing::{error, info};
+
+/// Main entry point for the factor returns job.
+pub async fn refresh_factor_returns(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting factor returns job");
+
+    match factor_regression_service::refresh_factor_returns(&ctx.pool).await {
+        Ok(updated) => {
+            info!("Factor returns job complete: {} rows new or revised", updated);
+            Ok(JobResult {
+                items_processed: updated as i32,
+                items_failed: 0,
+            })
+        }
+        Err(e) => {
+            error!("Failed to refresh factor returns: {}", e);
+            Ok(JobResult {
+                items_processed: 0,
+                items_failed: 1,
+            })
+        }
+    }
+}
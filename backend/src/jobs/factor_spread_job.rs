@@ -0,0 +1,29 @@
+//! Factor Spread Background Job
+//!
+//! Runs daily after market close, once prices for the day are in, and stores
+//! each factor's long-short spread (top minus bottom quintile of the tracked
+//! universe) for the latest trading day. Re-running on the same day replaces
+//! that day's rows.
+
+use crate::errors::AppError;
+use crate::services::{factor_spread_service, job_scheduler_service::{JobContext, JobResult}};
+use tracing::{error, info};
+
+/// Main entry point for the factor spread job.
+pub async fn update_factor_spreads(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting factor spread job");
+
+    match factor_spread_service::update_spreads(&ctx.pool).await {
+        Ok(spreads) => Ok(JobResult {
+            items_processed: spreads.len() as i32,
+            items_failed: 0,
+        }),
+        Err(e) => {
+            error!("Failed to update factor spreads: {}", e);
+            Ok(JobResult {
+                items_processed: 0,
+                items_failed: 1,
+            })
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Fundamentals Background Job
+//!
+//! Runs every morning before the markets open. Refreshes the key ratios,
+//! reported quarterly earnings and dividend history of every held ticker from
+//! the fundamentals provider, so factor scoring and long-term guidance work
+//! from reported figures instead of price-based proxies. Does nothing without
+//! a fundamentals provider configured.
+
+use crate::errors::AppError;
+use crate::services::fundamentals_service;
+use crate::services::job_scheduler_service::{JobContext, JobResult};
+use tracing::info;
+
+/// Main entry point for the fundamentals job.
+pub async fn refresh_fundamentals(ctx: JobContext) -> Result<JobResult, AppError> {
+    let Some(provider) = ctx.fundamentals_provider.as_ref() else {
+        info!("No fundamentals provider configured, skipping fundamentals refresh");
+        return Ok(JobResult {
+            items_processed: 0,
+            items_failed: 0,
+        });
+    };
+
+    info!("Starting fundamentals job");
+    let refresh = fundamentals_service::refresh_held_tickers(ctx.pool.as_ref(), provider.as_ref()).await?;
+    info!("Refreshed fundamentals for {} tickers ({} failed)", refresh.refreshed, refresh.failed);
+
+    Ok(JobResult {
+        items_processed: refresh.refreshed as i32,
+        items_failed: refresh.failed as i32,
+    })
+}
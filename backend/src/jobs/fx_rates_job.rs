@@ -0,0 +1,41 @@
+//! FX Rates Background Job
+//!
+//! Runs every afternoon, after the ECB publishes its reference rates and
+//! before the evening risk snapshots. Stores every rate published since the
+//! last stored day, so portfolio totals in a base currency don't have to wait
+//! on the rate provider.
+
+use chrono::Duration;
+use tracing::info;
+
+use crate::db::fx_queries;
+use crate::errors::AppError;
+use crate::services::{clock, fx_service};
+use crate::services::job_scheduler_service::{JobContext, JobResult};
+
+/// History fetched when no rates are stored yet
+const INITIAL_DAYS: i64 = 30;
+
+/// Main entry point for the FX rates job.
+pub async fn refresh_fx_rates(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting FX rates job");
+
+    let pool = ctx.pool.as_ref();
+    let today = clock::today();
+    let start = match fx_queries::fetch_latest_rate_date(pool).await? {
+        Some(latest) if latest >= today => {
+            info!("Exchange rates are up to date ({})", latest);
+            return Ok(JobResult { items_processed: 0, items_failed: 0 });
+        }
+        Some(latest) => latest + Duration::days(1),
+        None => today - Duration::days(INITIAL_DAYS),
+    };
+
+    let days = fx_service::refresh_rates(pool, ctx.fx_provider.as_ref(), start, today).await?;
+    info!("Stored {} days of exchange rates from {} to {}", days, start, today);
+
+    Ok(JobResult {
+        items_processed: days as i32,
+        items_failed: 0,
+    })
+}
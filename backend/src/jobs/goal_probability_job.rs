@@ -0,0 +1,36 @@
+//! Goal Probability Background Job
+//!
+//! Runs nightly after account valuations and recomputes each financial goal's
+//! Monte Carlo success probability from current savings, planned contributions
+//! and the survey's risk profile. One result is kept per goal per day, so users
+//! see a probability trend line; re-running the job on the same day replaces
+//! that day's results.
+
+use crate::errors::AppError;
+use crate::services::{clock, goal_probability_service, job_scheduler_service::{JobContext, JobResult}};
+use tracing::{error, info};
+
+/// Main entry point for the goal probability job.
+pub async fn recalculate_goal_probabilities(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting goal probability job");
+
+    match goal_probability_service::recalculate_goal_probabilities(&ctx.pool, clock::today()).await {
+        Ok(refresh) => {
+            info!(
+                "Recalculated success probabilities for {} goals ({} surveys failed)",
+                refresh.goals, refresh.failed_surveys
+            );
+            Ok(JobResult {
+                items_processed: refresh.goals as i32,
+                items_failed: refresh.failed_surveys as i32,
+            })
+        }
+        Err(e) => {
+            error!("Failed to recalculate goal probabilities: {}", e);
+            Ok(JobResult {
+                items_processed: 0,
+                items_failed: 1,
+            })
+        }
+    }
+}
@@ -3,7 +3,7 @@ use tracing::{error, info};
 
 use crate::db::hmm_queries;
 use crate::errors::AppError;
-use crate::external::alphavantage::AlphaVantageProvider;
+use crate::external::price_provider::PriceProvider;
 use crate::services::hmm_training_service::{train_hmm_model, HmmTrainingConfig};
 use crate::services::job_scheduler_service::{JobContext, JobResult};
 
@@ -12,14 +12,14 @@ use crate::services::job_scheduler_service::{JobContext, JobResult};
 /// This wrapper function adapts the HMM training logic to work with the
 /// job scheduler system, returning JobResult for proper tracking and reporting.
 pub async fn train_hmm_model_job(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🧠 Starting HMM model training job");
+    info!("Starting HMM model training job");
     let start_time = std::time::Instant::now();
 
-    match run_hmm_training_job(&ctx.pool).await {
+    match run_hmm_training_job(&ctx.pool, ctx.price_provider.as_ref()).await {
         Ok(()) => {
             let elapsed = start_time.elapsed();
             info!(
-                "✅ HMM training job completed successfully in {:.2}s",
+                "HMM training job completed successfully in {:.2}s",
                 elapsed.as_secs_f64()
             );
             Ok(JobResult {
@@ -28,7 +28,7 @@ pub async fn train_hmm_model_job(ctx: JobContext) -> Result<JobResult, AppError>
             })
         }
         Err(e) => {
-            error!("❌ HMM training job failed: {}", e);
+            error!("HMM training job failed: {}", e);
             Err(e)
         }
     }
@@ -41,20 +41,11 @@ pub async fn train_hmm_model_job(ctx: JobContext) -> Result<JobResult, AppError>
 ///
 /// Schedule: `0 0 1 * *` (cron: At 00:00 on day-of-month 1)
 #[allow(dead_code)]
-pub async fn run_hmm_training_job(pool: &PgPool) -> Result<(), AppError> {
+pub async fn run_hmm_training_job(pool: &PgPool, price_provider: &dyn PriceProvider) -> Result<(), AppError> {
     info!("Starting HMM model retraining job");
 
     let start_time = std::time::Instant::now();
 
-    // Initialize price provider
-    let price_provider = match AlphaVantageProvider::from_env() {
-        Ok(provider) => provider,
-        Err(e) => {
-            error!("Failed to initialize Alpha Vantage provider: {}", e);
-            return Err(AppError::External(format!("Price provider init failed: {}", e)));
-        }
-    };
-
     // Configure training
     let config = HmmTrainingConfig {
         ticker: "SPY".to_string(),
@@ -64,7 +55,7 @@ pub async fn run_hmm_training_job(pool: &PgPool) -> Result<(), AppError> {
     };
 
     // Train HMM model
-    match train_hmm_model(pool, &config, &price_provider).await {
+    match train_hmm_model(pool, &config, price_provider).await {
         Ok(trained_model) => {
             info!(
                 "HMM training completed successfully: {} states, accuracy: {:.2}%",
@@ -115,17 +106,9 @@ pub async fn run_hmm_training_job(pool: &PgPool) -> Result<(), AppError> {
 ///
 /// This can be used for manual testing or validation
 #[allow(dead_code)]
-pub async fn run_hmm_training_test(pool: &PgPool) -> Result<(), AppError> {
+pub async fn run_hmm_training_test(pool: &PgPool, price_provider: &dyn PriceProvider) -> Result<(), AppError> {
     info!("Running HMM training test with limited dataset");
 
-    let price_provider = match AlphaVantageProvider::from_env() {
-        Ok(provider) => provider,
-        Err(e) => {
-            error!("Failed to initialize Alpha Vantage provider: {}", e);
-            return Err(AppError::External(format!("Price provider init failed: {}", e)));
-        }
-    };
-
     // Use shorter lookback for testing
     let config = HmmTrainingConfig {
         ticker: "SPY".to_string(),
@@ -134,7 +117,7 @@ pub async fn run_hmm_training_test(pool: &PgPool) -> Result<(), AppError> {
         volatility_window_days: 20,
     };
 
-    match train_hmm_model(pool, &config, &price_provider).await {
+    match train_hmm_model(pool, &config, price_provider).await {
         Ok(trained_model) => {
             info!(
                 "Test HMM training successful: {} states, accuracy: {:.2}%",
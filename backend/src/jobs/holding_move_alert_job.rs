@@ -0,0 +1,105 @@
+//! Holding Move Alert Job
+//!
+//! Lightweight intraday job that evaluates every enabled `holding_move` alert rule
+//! against the latest quotes for the user's held tickers. Each ticker is quoted at
+//! most once per run, however many rules cover it. Rules in cooldown are skipped.
+
+use std::collections::HashMap;
+
+use crate::db::{alert_queries, holding_snapshot_queries};
+use crate::errors::AppError;
+use crate::models::alert::{AlertRule, AlertType};
+use crate::services::alert_service::{self, HoldingMove};
+use crate::services::job_scheduler_service::{JobContext, JobResult};
+use crate::services::notification_service;
+use tracing::{error, info, warn};
+
+const INTER_TICKER_DELAY_MS: u64 = 200;
+
+/// Main entry point for the holding move alert job.
+///
+/// Designed to run every 15 minutes during market hours.
+pub async fn run_holding_move_alerts(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting holding move alert job");
+
+    let pool = ctx.pool.as_ref();
+    let rules: Vec<(AlertRule, f64, Option<f64>)> = alert_queries::get_all_active_alert_rules(pool)
+        .await?
+        .into_iter()
+        .filter(|rule| !alert_service::is_in_cooldown(rule.last_triggered_at, rule.cooldown_hours))
+        .filter_map(|rule| match serde_json::from_str::<AlertType>(&rule.rule_type) {
+            Ok(AlertType::HoldingMove { percentage, gap_percentage }) => Some((rule, percentage, gap_percentage)),
+            _ => None,
+        })
+        .collect();
+
+    if rules.is_empty() {
+        info!("No holding move rules to evaluate");
+        return Ok(JobResult {
+            items_processed: 0,
+            items_failed: 0,
+        });
+    }
+
+    let mut moves: HashMap<String, Option<HoldingMove>> = HashMap::new();
+    let mut processed = 0;
+    let mut failed = 0;
+    let mut triggered = 0;
+
+    for (rule, percentage, gap_percentage) in &rules {
+        let tickers = match holding_snapshot_queries::fetch_user_held_tickers(pool, rule.user_id, rule.portfolio_id).await {
+            Ok(tickers) => tickers,
+            Err(e) => {
+                error!("Failed to load holdings for rule {}: {}", rule.id, e);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let mut rule_moves = Vec::new();
+        for ticker in tickers {
+            if !moves.contains_key(&ticker) {
+                let latest = alert_service::latest_holding_move(pool, ctx.price_provider.as_ref(), &ticker)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to compute move for {}: {}", ticker, e);
+                        None
+                    });
+                moves.insert(ticker.clone(), latest);
+                tokio::time::sleep(tokio::time::Duration::from_millis(INTER_TICKER_DELAY_MS)).await;
+            }
+            if let Some(Some(m)) = moves.get(&ticker) {
+                rule_moves.push(m.clone());
+            }
+        }
+
+        processed += 1;
+        let Some(result) = alert_service::holding_move_result(rule, *percentage, *gap_percentage, &rule_moves) else {
+            continue;
+        };
+
+        let notified = match alert_service::process_triggered_alert(pool, rule, &result).await {
+            Ok(history) => notification_service::send_notification(pool, rule.user_id, &history).await,
+            Err(e) => Err(e),
+        };
+        match notified {
+            Ok(()) => triggered += 1,
+            Err(e) => {
+                error!("Failed to record holding move alert for rule {}: {}", rule.id, e);
+                failed += 1;
+            }
+        }
+    }
+
+    info!(
+        "Holding move alerts: {} rules evaluated, {} triggered, {} tickers quoted",
+        processed,
+        triggered,
+        moves.len()
+    );
+
+    Ok(JobResult {
+        items_processed: processed,
+        items_failed: failed,
+    })
+}
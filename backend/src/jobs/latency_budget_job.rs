@@ -0,0 +1,36 @@
+//! Latency Budget Background Job
+//!
+//! Runs every five minutes, flushes the request and query timings buffered in
+//! memory to `latency_samples`, and recomputes per-endpoint and per-query p95
+//! latency over the last 24 hours. Endpoints and queries whose p95 exceeds their
+//! budget are flagged in `latency_stats` and logged when they start breaching.
+
+use crate::errors::AppError;
+use crate::services::latency_monitor_service::{self, BudgetDefaults};
+use crate::services::job_scheduler_service::{JobContext, JobResult};
+use tracing::{error, info};
+
+/// Main entry point for the latency budget job.
+pub async fn check_latency_budgets(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting latency budget job");
+
+    match latency_monitor_service::refresh_latency_stats(&ctx.pool, BudgetDefaults::from_env()).await {
+        Ok(refresh) => {
+            info!(
+                "Latency budgets checked: {} endpoints and {} queries over budget ({} new)",
+                refresh.endpoints_breaching, refresh.queries_breaching, refresh.new_breaches
+            );
+            Ok(JobResult {
+                items_processed: refresh.samples_flushed as i32,
+                items_failed: 0,
+            })
+        }
+        Err(e) => {
+            error!("Failed to check latency budgets: {}", e);
+            Ok(JobResult {
+                items_processed: 0,
+                items_failed: 1,
+            })
+        }
+    }
+}
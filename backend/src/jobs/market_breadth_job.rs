@@ -0,0 +1,35 @@
+//! Market Breadth Background Job
+//!
+//! Runs daily after market close, once prices for the day are in, and stores
+//! breadth metrics (advancers/decliners, % above SMA50/SMA200, 52-week highs and
+//! lows) for the tracked universe. Re-running on the same day replaces that day's row.
+
+use crate::errors::AppError;
+use crate::services::{job_scheduler_service::{JobContext, JobResult}, market_service};
+use tracing::{error, info};
+
+/// Main entry point for the market breadth job.
+pub async fn update_market_breadth(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting market breadth update job");
+
+    match market_service::update_breadth(&ctx.pool).await {
+        Ok(Some(breadth)) => {
+            info!("Market breadth stored for {} ({} tickers)", breadth.date, breadth.universe_size);
+            Ok(JobResult {
+                items_processed: breadth.universe_size,
+                items_failed: 0,
+            })
+        }
+        Ok(None) => Ok(JobResult {
+            items_processed: 0,
+            items_failed: 0,
+        }),
+        Err(e) => {
+            error!("Failed to update market breadth: {}", e);
+            Ok(JobResult {
+                items_processed: 0,
+                items_failed: 1,
+            })
+        }
+    }
+}
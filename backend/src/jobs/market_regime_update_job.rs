@@ -48,7 +48,7 @@
 use crate::errors::AppError;
 use crate::models::RegimeDetectionParams;
 use crate::services::{job_scheduler_service::{JobContext, JobResult}, market_regime_service};
-use chrono::Utc;
+use crate::services::clock;
 use tracing::{error, info};
 
 /// Main entry point for the market regime update job.
@@ -65,9 +65,9 @@ use tracing::{error, info};
 /// * `Ok(JobResult)` - Success with processed count
 /// * `Err(AppError)` - Critical failure that should be logged
 pub async fn update_market_regime(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("📊 Starting market regime update job");
+    info!("Starting market regime update job");
 
-    let today = Utc::now().date_naive();
+    let today = clock::today();
     info!("Detecting market regime for date: {}", today);
 
     // Use default detection parameters
@@ -90,7 +90,7 @@ pub async fn update_market_regime(ctx: JobContext) -> Result<JobResult, AppError
     {
         Ok(regime) => {
             info!(
-                "✅ Market regime updated: {} (volatility: {}%, confidence: {}%)",
+                "Market regime updated: {} (volatility: {}%, confidence: {}%)",
                 regime.regime_type,
                 regime.volatility_level,
                 regime.confidence
@@ -102,7 +102,7 @@ pub async fn update_market_regime(ctx: JobContext) -> Result<JobResult, AppError
             })
         }
         Err(e) => {
-            error!("❌ Failed to update market regime: {}", e);
+            error!("Failed to update market regime: {}", e);
 
             // Return error but don't panic - previous regime will remain in effect
             Ok(JobResult {
@@ -12,6 +12,25 @@
 //! - `daily_risk_snapshots_job` - Creates historical risk snapshots for tracking
 //! - `populate_sentiment_cache_job` - Pre-caches sentiment signals for portfolio tickers
 //! - `populate_optimization_cache_job` - Pre-caches optimization recommendations
+//! - `market_breadth_job` - Stores daily market breadth for the tracked universe
+//! - `holding_move_alert_job` - Alerts on large single-day moves in held positions
+//! - `peer_statistics_job` - Rebuilds anonymous aggregate statistics from opted-in portfolios
+//! - `snapshot_retention_job` - Compacts old risk and holdings snapshots into weekly/monthly tiers
+//! - `latency_budget_job` - Flushes request/query timings and flags endpoints over their latency budget
+//! - `advisor_fee_job` - Regenerates month-end cash flows for recurring account fees
+//! - `crypto_wallet_sync_job` - Imports on-chain balances of tracked wallet addresses as holdings
+//! - `rate_limit_calibration_job` - Retunes the price API rate limiter from the provider's reported quota
+//! - `portfolio_valuation_job` - Values accounts daily from their latest holdings and prices between imports
+//! - `notification_digest_job` - Emails daily and weekly digests of notifications
+//! - `goal_probability_job` - Recomputes Monte Carlo success probabilities of financial goals
+//! - `factor_spread_job` - Stores daily long-short factor spreads over the tracked universe
+//! - `factor_returns_job` - Downloads the Fama-French and momentum factor returns for the factor regressions
+//! - `etf_constituent_job` - Stores constituents and sector weights of ETFs held in portfolios
+//! - `dividend_calendar_job` - Stores upcoming ex-dividend dates of held tickers and sends ex-dividend reminders
+//! - `fx_rates_job` - Stores daily ECB reference exchange rates for base-currency conversion
+//! - `fundamentals_job` - Refreshes key ratios, earnings and dividend history of held tickers
+//! - `corporate_actions_job` - Stores splits and dividends and rebuilds split/dividend-adjusted closes
+//! - `price_backfill_job` - Extends the price history of held and watched tickers to the configured years
 //!
 //! # Job Architecture
 //!
@@ -34,3 +53,22 @@ pub mod regime_forecast_job;
 pub mod rolling_beta_cache_job;
 pub mod downside_risk_cache_job;
 pub mod watchlist_monitoring_job;
+pub mod market_breadth_job;
+pub mod holding_move_alert_job;
+pub mod peer_statistics_job;
+pub mod snapshot_retention_job;
+pub mod latency_budget_job;
+pub mod advisor_fee_job;
+pub mod crypto_wallet_sync_job;
+pub mod rate_limit_calibration_job;
+pub mod portfolio_valuation_job;
+pub mod notification_digest_job;
+pub mod goal_probability_job;
+pub mod factor_spread_job;
+pub mod etf_constituent_job;
+pub mod dividend_calendar_job;
+pub mod fx_rates_job;
+pub mod fundamentals_job;
+pub mod corporate_actions_job;
+pub mod price_backfill_job;
+pub mod factor_returns_job;
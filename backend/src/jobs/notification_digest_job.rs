@@ -0,0 +1,111 @@
+//! Notification Digest Background Job
+//!
+//! Runs every hour and emails users who chose a daily or weekly digest a
+//! summary of the notifications they received since their last one. Digests go
+//! out once the morning starts in the user's time zone; weekly ones on their
+//! Monday. A digest is due until one is sent on that local day, so a missed run
+//! is picked up by the next one without sending twice.
+
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc, Weekday};
+use crate::db::user_preferences_queries;
+use crate::errors::AppError;
+use crate::models::DigestFrequency;
+use crate::services::{clock, job_scheduler_service::{JobContext, JobResult}, notification_service, timezone::{self, Tz}};
+use tracing::{error, info};
+
+/// Local hour from which the day's digest goes out
+const DELIVERY_HOUR: u32 = 7;
+
+/// Main entry point for the notification digest job.
+pub async fn send_notification_digests(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting notification digest job");
+
+    let now = clock::now();
+    let mut processed = 0;
+    let mut failed = 0;
+    for frequency in [DigestFrequency::Daily, DigestFrequency::Weekly] {
+        let recipients = user_preferences_queries::list_digest_recipients(ctx.pool.as_ref(), frequency).await?;
+        for (user_id, last_sent, timezone_name) in recipients {
+            if !digest_due(frequency, last_sent, now, timezone::resolve_or_default(&timezone_name)) {
+                continue;
+            }
+            let since = last_sent.unwrap_or(now - period(frequency));
+            match notification_service::send_digest(ctx.pool.as_ref(), user_id, frequency, since).await {
+                Ok(count) => {
+                    user_preferences_queries::mark_digest_sent(ctx.pool.as_ref(), user_id, now).await?;
+                    info!("Sent {:?} digest of {} notifications to user {}", frequency, count, user_id);
+                    processed += 1;
+                }
+                Err(e) => {
+                    error!("Failed to send digest to user {}: {}", user_id, e);
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    Ok(JobResult {
+        items_processed: processed,
+        items_failed: failed,
+    })
+}
+
+fn period(frequency: DigestFrequency) -> Duration {
+    match frequency {
+        DigestFrequency::Weekly => Duration::days(7),
+        _ => Duration::days(1),
+    }
+}
+
+/// Whether a user in `tz` should get their digest at `now`
+fn digest_due(frequency: DigestFrequency, last_sent: Option<DateTime<Utc>>, now: DateTime<Utc>, tz: Tz) -> bool {
+    let local = tz.local_datetime(now);
+    if frequency == DigestFrequency::Immediate
+        || local.hour() < DELIVERY_HOUR
+        || (frequency == DigestFrequency::Weekly && local.weekday() != Weekday::Mon)
+    {
+        return false;
+    }
+    let period_days = period(frequency).num_days();
+    last_sent.is_none_or(|sent| (local.date() - tz.local_date(sent)).num_days() >= period_days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_digest_due() {
+        let utc = timezone::resolve("UTC").unwrap();
+        // Monday 2026-03-09, 07:00 UTC
+        let monday = Utc.with_ymd_and_hms(2026, 3, 9, 7, 0, 0).unwrap();
+        let tuesday = monday + Duration::days(1);
+
+        assert!(!digest_due(DigestFrequency::Immediate, None, monday, utc));
+        assert!(digest_due(DigestFrequency::Daily, None, tuesday, utc));
+        assert!(!digest_due(DigestFrequency::Daily, None, tuesday - Duration::hours(1), utc));
+        assert!(digest_due(DigestFrequency::Daily, Some(monday), tuesday, utc));
+        assert!(digest_due(DigestFrequency::Daily, Some(monday + Duration::hours(12)), tuesday, utc));
+        assert!(!digest_due(DigestFrequency::Daily, Some(tuesday), tuesday + Duration::hours(3), utc));
+
+        assert!(digest_due(DigestFrequency::Weekly, None, monday, utc));
+        assert!(!digest_due(DigestFrequency::Weekly, None, tuesday, utc));
+        assert!(digest_due(DigestFrequency::Weekly, Some(monday - Duration::days(7)), monday, utc));
+        assert!(!digest_due(DigestFrequency::Weekly, Some(monday - Duration::days(1)), monday, utc));
+    }
+
+    #[test]
+    fn test_digest_due_in_local_morning() {
+        let new_york = timezone::resolve("America/New_York").unwrap();
+        // 07:00 UTC Monday is 03:00 in New York (EDT); 11:00 UTC is 07:00
+        let monday = Utc.with_ymd_and_hms(2026, 3, 9, 7, 0, 0).unwrap();
+        assert!(!digest_due(DigestFrequency::Daily, None, monday, new_york));
+        assert!(digest_due(DigestFrequency::Daily, None, monday + Duration::hours(4), new_york));
+
+        // 02:00 UTC Tuesday is still Monday evening in New York
+        let monday_evening = Utc.with_ymd_and_hms(2026, 3, 10, 2, 0, 0).unwrap();
+        assert!(digest_due(DigestFrequency::Weekly, None, monday_evening, new_york));
+        assert!(!digest_due(DigestFrequency::Weekly, None, monday_evening, timezone::resolve("UTC").unwrap()));
+    }
+}
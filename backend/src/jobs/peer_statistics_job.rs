@@ -0,0 +1,34 @@
+//! Peer Statistics Background Job
+//!
+//! Runs daily after the risk snapshots are taken and rebuilds the anonymous
+//! aggregate statistics (metric percentiles, common holdings) from portfolios of
+//! users who opted in. Each run replaces the previous statistics entirely.
+
+use crate::errors::AppError;
+use crate::services::{job_scheduler_service::{JobContext, JobResult}, peer_statistics_service};
+use tracing::{error, info};
+
+/// Main entry point for the peer statistics job.
+pub async fn refresh_peer_statistics(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting peer statistics job");
+
+    match peer_statistics_service::refresh_peer_statistics(&ctx.pool).await {
+        Ok(refresh) => {
+            info!(
+                "Peer statistics refreshed from {} portfolios ({} metrics, {} common holdings)",
+                refresh.portfolios, refresh.metrics_published, refresh.holdings_published
+            );
+            Ok(JobResult {
+                items_processed: refresh.portfolios as i32,
+                items_failed: 0,
+            })
+        }
+        Err(e) => {
+            error!("Failed to refresh peer statistics: {}", e);
+            Ok(JobResult {
+                items_processed: 0,
+                items_failed: 1,
+            })
+        }
+    }
+}
@@ -53,7 +53,7 @@ const INTER_PORTFOLIO_DELAY_MS: u64 = 1000; // 1 second delay between portfolios
 /// * `Ok(JobResult)` - Success with counts of processed and failed portfolios
 /// * `Err(AppError)` - Critical error that prevents job execution
 pub async fn populate_all_optimization_caches(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🎯 Starting optimization cache population job");
+    info!("Starting optimization cache population job");
 
     // 1. Get all portfolios with active holdings
     let portfolios = get_active_portfolios(ctx.pool.as_ref()).await?;
@@ -98,14 +98,14 @@ pub async fn populate_all_optimization_caches(ctx: JobContext) -> Result<JobResu
             Ok(_) => {
                 let duration = start.elapsed();
                 info!(
-                    "✅ Successfully cached optimization for portfolio {} in {:?}",
+                    "Successfully cached optimization for portfolio {} in {:?}",
                     portfolio_id, duration
                 );
                 processed += 1;
             }
             Err(e) => {
                 error!(
-                    "❌ Failed to cache optimization for portfolio {}: {}",
+                    "Failed to cache optimization for portfolio {}: {}",
                     portfolio_id, e
                 );
                 failed += 1;
@@ -119,7 +119,7 @@ pub async fn populate_all_optimization_caches(ctx: JobContext) -> Result<JobResu
     }
 
     info!(
-        "✅ Optimization cache population complete: {} processed, {} failed",
+        "Optimization cache population complete: {} processed, {} failed",
         processed, failed
     );
 
@@ -138,6 +138,7 @@ async fn get_active_portfolios(pool: &sqlx::PgPool) -> Result<Vec<Uuid>, AppErro
         JOIN accounts a ON a.portfolio_id = p.id
         JOIN holdings_snapshots hs ON hs.account_id = a.id
         WHERE hs.quantity > 0
+          AND p.archived_at IS NULL
         ORDER BY p.id
         "#
     )
@@ -170,7 +171,7 @@ pub async fn calculate_single_portfolio_optimization(
     ctx: &JobContext,
     portfolio_id: Uuid,
 ) -> Result<(), AppError> {
-    info!("📊 Manually calculating optimization for portfolio {}", portfolio_id);
+    info!("Manually calculating optimization for portfolio {}", portfolio_id);
     calculate_and_cache_optimization(ctx, &portfolio_id).await
 }
 
@@ -14,15 +14,19 @@
 //!
 //! 1. Query all unique tickers from active portfolio holdings
 //! 2. For each ticker, check if cache is expired or missing
-//! 3. Fetch sentiment data from news API and calculate signals
-//! 4. Store results in sentiment_signal_cache table
+//! 3. Fetch scored news from the news provider and build the signal
+//! 4. Store it in sentiment_signal_cache and record the score in
+//!    news_sentiment_scores, where stock screening reads it
 //! 5. Add delays between tickers to respect rate limits
 //!
+//! Does nothing without a news provider configured (ALPHAVANTAGE_API_KEY).
+//!
 //! # Error Handling
 //!
 //! - Individual ticker failures don't stop the entire job
 //! - Errors are logged with ticker context
-//! - Failed tickers are tracked and reported in job results
+//! - Once the provider rate limits the job, the remaining tickers are counted
+//!   as failed rather than spending more of the quota
 //!
 //! # Performance Considerations
 //!
@@ -31,16 +35,17 @@
 //! - Processes tickers sequentially to avoid overwhelming external services
 
 use crate::errors::AppError;
+use crate::external::news::NewsProvider;
+use crate::external::price_provider::PriceProviderError;
 use crate::services::job_scheduler_service::{JobContext, JobResult};
 use crate::services::sentiment_service;
-use crate::services::news_service::NewsService;
-use crate::services::llm_service::LlmService;
-use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 const CACHE_EXPIRATION_HOURS: i64 = 4;
 const INTER_TICKER_DELAY_MS: u64 = 500; // 500ms delay between tickers
-const SENTIMENT_LOOKBACK_DAYS: usize = 30;
+const SENTIMENT_LOOKBACK_DAYS: u32 = 30;
+/// Recorded with each score
+const NEWS_SOURCE: &str = "alphavantage";
 
 /// Main entry point for the sentiment cache population job.
 ///
@@ -56,7 +61,15 @@ const SENTIMENT_LOOKBACK_DAYS: usize = 30;
 /// * `Ok(JobResult)` - Success with counts of processed and failed tickers
 /// * `Err(AppError)` - Critical error that prevents job execution
 pub async fn populate_all_sentiment_caches(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("📊 Starting sentiment cache population job");
+    let Some(news_provider) = ctx.news_provider.as_ref() else {
+        info!("No news provider configured, skipping sentiment cache population");
+        return Ok(JobResult {
+            items_processed: 0,
+            items_failed: 0,
+        });
+    };
+
+    info!("Starting sentiment cache population job");
 
     // 1. Get all unique tickers from active portfolio holdings
     let tickers = get_active_portfolio_tickers(ctx.pool.as_ref()).await?;
@@ -91,20 +104,19 @@ pub async fn populate_all_sentiment_caches(ctx: JobContext) -> Result<JobResult,
         }
 
         // Fetch and cache sentiment for this ticker
-        match fetch_and_cache_sentiment(
-            ctx.pool.clone(),
-            ctx.news_service.clone(),
-            ctx.llm_service.clone(),
-            ticker,
-        )
-        .await
-        {
+        match fetch_and_cache_sentiment(ctx.pool.as_ref(), news_provider.as_ref(), ticker).await {
             Ok(_) => {
-                info!("✅ Successfully cached sentiment for {}", ticker);
+                info!("Successfully cached sentiment for {}", ticker);
                 processed += 1;
             }
+            Err(AppError::RateLimited) => {
+                let remaining = (tickers.len() - idx) as i32;
+                warn!("News provider rate limited, skipping the remaining {} tickers", remaining);
+                failed += remaining;
+                break;
+            }
             Err(e) => {
-                error!("❌ Failed to cache sentiment for {}: {}", ticker, e);
+                error!("Failed to cache sentiment for {}: {}", ticker, e);
                 failed += 1;
             }
         }
@@ -116,7 +128,7 @@ pub async fn populate_all_sentiment_caches(ctx: JobContext) -> Result<JobResult,
     }
 
     info!(
-        "✅ Sentiment cache population complete: {} processed, {} failed",
+        "Sentiment cache population complete: {} processed, {} failed",
         processed, failed
     );
 
@@ -133,8 +145,9 @@ async fn get_active_portfolio_tickers(pool: &sqlx::PgPool) -> Result<Vec<String>
         SELECT DISTINCT hs.ticker
         FROM holdings_snapshots hs
         JOIN accounts a ON hs.account_id = a.id
+        JOIN portfolios p ON p.id = a.portfolio_id
         WHERE hs.quantity > 0
-          AND a.portfolio_id IS NOT NULL
+          AND p.archived_at IS NULL
         ORDER BY hs.ticker
         "#
     )
@@ -168,17 +181,20 @@ async fn is_cache_fresh(pool: &sqlx::PgPool, ticker: &str) -> Result<bool, AppEr
         .unwrap_or(false))
 }
 
-/// Fetch sentiment data and store in cache
+/// Fetch scored news and store the sentiment signal in cache
 async fn fetch_and_cache_sentiment(
-    pool: Arc<sqlx::PgPool>,
-    news_service: Arc<NewsService>,
-    _llm_service: Arc<LlmService>, // Used by news_service.cluster_into_themes internally
+    pool: &sqlx::PgPool,
+    news_provider: &dyn NewsProvider,
     ticker: &str,
 ) -> Result<(), AppError> {
-    use uuid::Uuid;
-
-    // 1. Fetch news articles for the ticker
-    let articles = news_service.fetch_ticker_news(ticker, SENTIMENT_LOOKBACK_DAYS as i32).await?;
+    // 1. Fetch news articles for the ticker, already scored by the provider
+    let articles = news_provider
+        .fetch_ticker_news(ticker, SENTIMENT_LOOKBACK_DAYS)
+        .await
+        .map_err(|e| match e {
+            PriceProviderError::RateLimited => AppError::RateLimited,
+            e => AppError::External(format!("Failed to fetch news for {}: {}", ticker, e)),
+        })?;
 
     if articles.is_empty() {
         return Err(AppError::Validation(
@@ -188,22 +204,8 @@ async fn fetch_and_cache_sentiment(
 
     info!("Fetched {} news articles for {}", articles.len(), ticker);
 
-    // 2. Cluster articles into themes using LLM
-    let demo_user_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001")
-        .map_err(|e| AppError::External(format!("Failed to parse demo user UUID: {}", e)))?;
-
-    let themes = news_service.cluster_into_themes(articles, demo_user_id).await?;
-
-    if themes.is_empty() {
-        return Err(AppError::Validation(
-            format!("Failed to extract themes from news for {}", ticker)
-        ));
-    }
-
-    info!("Extracted {} themes for {}", themes.len(), ticker);
-
-    // 3. Fetch price history for correlation analysis
-    let prices = crate::services::price_service::get_history(pool.as_ref(), ticker).await?;
+    // 2. Fetch price history for correlation analysis
+    let prices = crate::services::price_service::get_history(pool, ticker).await?;
 
     if prices.is_empty() {
         return Err(AppError::Validation(
@@ -213,19 +215,18 @@ async fn fetch_and_cache_sentiment(
 
     info!("Fetched {} price points for {}", prices.len(), ticker);
 
-    // 4. Generate sentiment signal
-    let signal = sentiment_service::generate_sentiment_signal(
-        pool.as_ref(),
+    // 3. Generate the sentiment signal, replacing the cached one
+    let signal = sentiment_service::cache_scored_news(
+        pool,
         ticker,
-        themes,
-        prices,
+        &articles,
+        &prices,
+        SENTIMENT_LOOKBACK_DAYS as i32,
+        NEWS_SOURCE,
     )
     .await?;
 
     info!("Generated sentiment signal for {}: score={:.2}", ticker, signal.current_sentiment);
 
-    // Note: The signal is already cached by generate_sentiment_signal() at sentiment_service.rs:341
-    // No need to cache again here
-
     Ok(())
 }
@@ -29,15 +29,23 @@
 ///
 /// **Performance Optimization**:
 /// - Limited to top 10 positions by value to prevent timeouts
-/// - Batch price fetching for all tickers at once
+/// - Batch price fetching for all tickers at once, refreshing stale closes
+///   concurrently first
 /// - Filters out mutual funds and proprietary tickers (no price data)
 /// - Only positions >= 1% of portfolio value are included
 
 use crate::db::{holding_snapshot_queries, price_queries};
+use crate::db::tenant::TenantScope;
 use crate::errors::AppError;
+use crate::external::fx_provider::FxProvider;
+use crate::external::price_provider::PriceProvider;
 use crate::models::risk::{CorrelationMatrix, CorrelationMatrixWithStats, CorrelationPair};
+use crate::models::TickerType;
 use crate::services::job_scheduler_service::{JobContext, JobResult};
-use crate::services::risk_service;
+use crate::services::failure_cache::FailureCache;
+use crate::services::fx_service::PortfolioFx;
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::{price_service, risk_service};
 use sqlx::PgPool;
 use std::collections::HashMap;
 use tracing::{error, info, warn};
@@ -56,7 +64,7 @@ use uuid::Uuid;
 /// * `Ok(JobResult)` - Success with counts of processed/failed portfolios
 /// * `Err(AppError)` - Critical error that stops the entire job
 pub async fn calculate_all_portfolio_correlations(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🔗 Starting portfolio correlations calculation job...");
+    info!("Starting portfolio correlations calculation job...");
 
     // Get all portfolios that have at least one account (which would have holdings)
     let portfolios = sqlx::query!(
@@ -64,6 +72,7 @@ pub async fn calculate_all_portfolio_correlations(ctx: JobContext) -> Result<Job
         SELECT DISTINCT p.id, p.name
         FROM portfolios p
         INNER JOIN accounts a ON p.id = a.portfolio_id
+        WHERE p.archived_at IS NULL
         ORDER BY p.name
         "#
     )
@@ -95,13 +104,18 @@ pub async fn calculate_all_portfolio_correlations(ctx: JobContext) -> Result<Job
             portfolio_name, portfolio_id
         );
 
+        // Cache entries are scoped to the portfolio's owner
+        let Some(tenant) = TenantScope::for_job(ctx.pool.as_ref(), portfolio_id).await? else {
+            continue;
+        };
+
         // Check if cache needs refresh
-        match check_correlations_cache_needs_refresh(ctx.pool.as_ref(), portfolio_id, days).await
+        match check_correlations_cache_needs_refresh(ctx.pool.as_ref(), &tenant, days).await
         {
             Ok(needs_refresh) => {
                 if !needs_refresh {
                     info!(
-                        "✓ Skipping {} - cache is fresh",
+                        "Skipping {} - cache is fresh",
                         portfolio_name
                     );
                     processed += 1;
@@ -118,18 +132,26 @@ pub async fn calculate_all_portfolio_correlations(ctx: JobContext) -> Result<Job
         }
 
         // Calculate correlations for this portfolio
-        match calculate_portfolio_correlations_internal(ctx.pool.as_ref(), portfolio_id, days)
-            .await
+        match calculate_portfolio_correlations_internal(
+            ctx.pool.as_ref(),
+            ctx.fx_provider.as_ref(),
+            ctx.price_provider.as_ref(),
+            ctx.failure_cache.as_ref(),
+            ctx.rate_limiter.as_ref(),
+            portfolio_id,
+            days,
+        )
+        .await
         {
             Ok(result) => {
                 // Store in cache
-                match store_correlations_cache(ctx.pool.as_ref(), portfolio_id, days, &result)
+                match store_correlations_cache(ctx.pool.as_ref(), &tenant, days, &result)
                     .await
                 {
                     Ok(_) => {
                         processed += 1;
                         info!(
-                            "✅ Successfully calculated correlations for {} ({} tickers)",
+                            "Successfully calculated correlations for {} ({} tickers)",
                             portfolio_name, result.matrix.tickers.len()
                         );
                     }
@@ -146,7 +168,7 @@ pub async fn calculate_all_portfolio_correlations(ctx: JobContext) -> Result<Job
                 failed += 1;
                 // Store error in cache to prevent repeated failures
                 if let Err(cache_err) =
-                    store_correlations_error(ctx.pool.as_ref(), portfolio_id, days, &e.to_string())
+                    store_correlations_error(ctx.pool.as_ref(), &tenant, days, &e.to_string())
                         .await
                 {
                     error!(
@@ -167,7 +189,7 @@ pub async fn calculate_all_portfolio_correlations(ctx: JobContext) -> Result<Job
     }
 
     info!(
-        "🏁 Portfolio correlations job completed: {} processed, {} failed",
+        "Portfolio correlations job completed: {} processed, {} failed",
         processed, failed
     );
 
@@ -186,21 +208,22 @@ pub async fn calculate_all_portfolio_correlations(ctx: JobContext) -> Result<Job
 ///
 /// # Arguments
 /// * `pool` - Database connection pool
-/// * `portfolio_id` - Portfolio to check
+/// * `tenant` - Portfolio to check and its owner
 /// * `days` - Lookback period for correlation calculation
 async fn check_correlations_cache_needs_refresh(
     pool: &PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     days: i64,
 ) -> Result<bool, AppError> {
     let result = sqlx::query!(
         r#"
         SELECT expires_at, calculation_status
         FROM portfolio_correlations_cache
-        WHERE portfolio_id = $1 AND days = $2
+        WHERE portfolio_id = $1 AND days = $2 AND user_id = $3
         "#,
-        portfolio_id,
-        days as i32
+        tenant.portfolio_id(),
+        days as i32,
+        tenant.user_id()
     )
     .fetch_optional(pool)
     .await?;
@@ -227,12 +250,12 @@ async fn check_correlations_cache_needs_refresh(
 ///
 /// # Arguments
 /// * `pool` - Database connection pool
-/// * `portfolio_id` - Portfolio ID
+/// * `tenant` - Portfolio and its owner
 /// * `days` - Lookback period used
 /// * `result` - Calculated correlation matrix with statistics
 async fn store_correlations_cache(
     pool: &PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     days: i64,
     result: &CorrelationMatrixWithStats,
 ) -> Result<(), AppError> {
@@ -243,8 +266,8 @@ async fn store_correlations_cache(
     sqlx::query!(
         r#"
         INSERT INTO portfolio_correlations_cache
-            (portfolio_id, days, correlations_data, calculated_at, expires_at, calculation_status, last_error)
-        VALUES ($1, $2, $3, NOW(), $4, 'fresh', NULL)
+            (portfolio_id, days, correlations_data, calculated_at, expires_at, calculation_status, last_error, user_id)
+        VALUES ($1, $2, $3, NOW(), $4, 'fresh', NULL, $5)
         ON CONFLICT (portfolio_id, days)
         DO UPDATE SET
             correlations_data = EXCLUDED.correlations_data,
@@ -254,10 +277,11 @@ async fn store_correlations_cache(
             last_error = NULL,
             updated_at = NOW()
         "#,
-        portfolio_id,
+        tenant.portfolio_id(),
         days as i32,
         correlations_json,
-        expires_at
+        expires_at,
+        tenant.user_id()
     )
     .execute(pool)
     .await?;
@@ -272,12 +296,12 @@ async fn store_correlations_cache(
 ///
 /// # Arguments
 /// * `pool` - Database connection pool
-/// * `portfolio_id` - Portfolio ID
+/// * `tenant` - Portfolio and its owner
 /// * `days` - Lookback period attempted
 /// * `error_message` - Error message to store
 async fn store_correlations_error(
     pool: &PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     days: i64,
     error_message: &str,
 ) -> Result<(), AppError> {
@@ -286,8 +310,8 @@ async fn store_correlations_error(
     sqlx::query!(
         r#"
         INSERT INTO portfolio_correlations_cache
-            (portfolio_id, days, correlations_data, calculated_at, expires_at, calculation_status, last_error)
-        VALUES ($1, $2, '{}'::jsonb, NOW(), $3, 'error', $4)
+            (portfolio_id, days, correlations_data, calculated_at, expires_at, calculation_status, last_error, user_id)
+        VALUES ($1, $2, '{}'::jsonb, NOW(), $3, 'error', $4, $5)
         ON CONFLICT (portfolio_id, days)
         DO UPDATE SET
             calculated_at = NOW(),
@@ -296,10 +320,11 @@ async fn store_correlations_error(
             last_error = EXCLUDED.last_error,
             updated_at = NOW()
         "#,
-        portfolio_id,
+        tenant.portfolio_id(),
         days as i32,
         expires_at,
-        error_message
+        error_message,
+        tenant.user_id()
     )
     .execute(pool)
     .await?;
@@ -322,6 +347,7 @@ async fn store_correlations_error(
 ///
 /// # Arguments
 /// * `pool` - Database connection pool
+/// * `fx_provider` - Exchange rates for converting into the base currency
 /// * `portfolio_id` - Portfolio to analyze
 /// * `days` - Number of days of historical data for correlation
 ///
@@ -330,6 +356,10 @@ async fn store_correlations_error(
 /// * `Err(AppError)` - Calculation failed (insufficient data, DB error, etc.)
 async fn calculate_portfolio_correlations_internal(
     pool: &PgPool,
+    fx_provider: &dyn FxProvider,
+    price_provider: &dyn PriceProvider,
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
     portfolio_id: Uuid,
     days: i64,
 ) -> Result<CorrelationMatrixWithStats, AppError> {
@@ -344,17 +374,16 @@ async fn calculate_portfolio_correlations_internal(
         )));
     }
 
+    // Values from accounts in different currencies are summed in the owner's base currency
+    let fx = PortfolioFx::load(pool, fx_provider, portfolio_id, &holdings).await?;
+
     // 2. Aggregate holdings by ticker and filter out mutual funds and proprietary tickers
     let mut ticker_aggregates: HashMap<String, f64> = HashMap::new();
     let mut total_value = 0.0;
     let mut filtered_count = 0;
 
     for holding in &holdings {
-        let market_value = holding
-            .market_value
-            .to_string()
-            .parse::<f64>()
-            .unwrap_or(0.0);
+        let market_value = fx.market_value(holding);
         total_value += market_value;
 
         // Skip mutual funds and proprietary tickers (no price data available)
@@ -372,7 +401,10 @@ async fn calculate_portfolio_correlations_internal(
             || holding.ticker.starts_with("EDG")
             || holding.ticker.len() > 5;
 
-        if is_mutual_fund || is_proprietary_ticker {
+        // Crypto tickers (BTC-USD) are long but priced from CoinGecko
+        let is_crypto = holding.ticker_type() == TickerType::Crypto;
+
+        if !is_crypto && (is_mutual_fund || is_proprietary_ticker) {
             filtered_count += 1;
             continue;
         }
@@ -428,7 +460,8 @@ async fn calculate_portfolio_correlations_internal(
         return Err(AppError::External(msg));
     }
 
-    // 3. Fetch price data for all tickers in one batch query (much faster!)
+    // 3. Refresh stale closes concurrently, then read them all in one batch query (much faster!)
+    price_service::refresh_many_from_api(pool, price_provider, &tickers, failure_cache, rate_limiter).await;
     let price_data = price_queries::fetch_window_batch(pool, &tickers, days).await?;
 
     // Filter tickers to only those with sufficient price data (at least 2 points)
@@ -44,14 +44,18 @@
 //! - Adds delays between portfolios to respect rate limits
 //! - Skips portfolios with no holdings or negligible value
 
-use crate::db::holding_snapshot_queries;
+use bigdecimal::ToPrimitive;
+use crate::db::{holding_snapshot_queries, portfolio_queries, risk_cache_queries};
+use crate::db::tenant::TenantScope;
 use crate::errors::AppError;
+use crate::external::fx_provider::FxProvider;
 use crate::external::price_provider::PriceProvider;
 use crate::models::risk::{PortfolioRiskWithViolations, ThresholdViolation, ViolationSeverity};
 use crate::models::{PositionRiskContribution, RiskLevel};
 use crate::services::failure_cache::FailureCache;
+use crate::services::fx_service::PortfolioFx;
 use crate::services::rate_limiter::RateLimiter;
-use crate::services::{job_scheduler_service::{JobContext, JobResult}, risk_service};
+use crate::services::{job_scheduler_service::{JobContext, JobResult}, notification_service, portfolio_return_service, risk_service};
 use chrono::{Duration, Utc};
 use sqlx::PgPool;
 use std::collections::HashMap;
@@ -80,7 +84,7 @@ const INTER_PORTFOLIO_DELAY_MS: u64 = 1000;
 /// * `Ok(JobResult)` - Success with counts of processed and failed portfolios
 /// * `Err(AppError)` - Critical failure that should stop the job
 pub async fn calculate_all_portfolio_risks(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🔍 Starting portfolio risk pre-calculation job");
+    info!("Starting portfolio risk pre-calculation job");
 
     // Query all portfolios with holdings
     let portfolios = query_portfolios_with_holdings(&ctx.pool).await?;
@@ -100,8 +104,13 @@ pub async fn calculate_all_portfolio_risks(ctx: JobContext) -> Result<JobResult,
 
     // Process each portfolio
     for portfolio_id in portfolios {
+        // Cache entries are scoped to the portfolio's owner
+        let Some(tenant) = TenantScope::for_job(ctx.pool.as_ref(), portfolio_id).await? else {
+            continue;
+        };
+
         // Check if cache needs refresh
-        match check_cache_needs_refresh(&ctx.pool, portfolio_id, DEFAULT_DAYS, DEFAULT_BENCHMARK).await {
+        match check_cache_needs_refresh(&ctx.pool, &tenant, DEFAULT_DAYS, DEFAULT_BENCHMARK).await {
             Ok(needs_refresh) => {
                 if !needs_refresh {
                     info!("Portfolio {} cache is fresh, skipping", portfolio_id);
@@ -117,8 +126,11 @@ pub async fn calculate_all_portfolio_risks(ctx: JobContext) -> Result<JobResult,
 
         info!("Processing portfolio {}...", portfolio_id);
 
+        // Violations from the last run, so only new ones are notified
+        let previous_violations = fetch_cached_violations(&ctx.pool, &tenant).await;
+
         // Mark cache as 'calculating'
-        if let Err(e) = mark_cache_calculating(&ctx.pool, portfolio_id, DEFAULT_DAYS, DEFAULT_BENCHMARK).await {
+        if let Err(e) = mark_cache_calculating(&ctx.pool, &tenant, DEFAULT_DAYS, DEFAULT_BENCHMARK).await {
             error!("Failed to mark cache as calculating for portfolio {}: {}", portfolio_id, e);
             failed += 1;
             continue;
@@ -133,6 +145,7 @@ pub async fn calculate_all_portfolio_risks(ctx: JobContext) -> Result<JobResult,
                 DEFAULT_DAYS,
                 DEFAULT_BENCHMARK,
                 ctx.price_provider.as_ref(),
+                ctx.fx_provider.as_ref(),
                 ctx.failure_cache.as_ref(),
                 ctx.rate_limiter.as_ref(),
             )
@@ -143,30 +156,34 @@ pub async fn calculate_all_portfolio_risks(ctx: JobContext) -> Result<JobResult,
                 // Successfully calculated risk metrics
                 if let Err(e) = store_portfolio_risk_cache(
                     &ctx.pool,
-                    portfolio_id,
+                    &tenant,
                     DEFAULT_DAYS,
                     DEFAULT_BENCHMARK,
                     &risk_data,
                 ).await {
                     error!("Failed to store risk cache for portfolio {}: {}", portfolio_id, e);
-                    mark_cache_error(&ctx.pool, portfolio_id, DEFAULT_DAYS, DEFAULT_BENCHMARK, &e.to_string()).await.ok();
+                    mark_cache_error(&ctx.pool, &tenant, DEFAULT_DAYS, DEFAULT_BENCHMARK, &e.to_string()).await.ok();
                     failed += 1;
                 } else {
-                    info!("✅ Successfully calculated and cached risk for portfolio {}", portfolio_id);
+                    info!("Successfully calculated and cached risk for portfolio {}", portfolio_id);
+                    let new_critical = new_critical_violations(&previous_violations, &risk_data.violations);
+                    if let Err(e) = notify_owner(&ctx.pool, portfolio_id, &new_critical).await {
+                        warn!("Failed to notify owner of portfolio {} about violations: {}", portfolio_id, e);
+                    }
                     processed += 1;
                 }
             }
             Ok(Err(e)) => {
                 // Calculation failed
                 error!("Failed to calculate risk for portfolio {}: {}", portfolio_id, e);
-                mark_cache_error(&ctx.pool, portfolio_id, DEFAULT_DAYS, DEFAULT_BENCHMARK, &e.to_string()).await.ok();
+                mark_cache_error(&ctx.pool, &tenant, DEFAULT_DAYS, DEFAULT_BENCHMARK, &e.to_string()).await.ok();
                 failed += 1;
             }
             Err(_) => {
                 // Timeout
                 let error_msg = format!("Calculation timed out after {} seconds", PORTFOLIO_TIMEOUT_SECONDS);
                 error!("{} for portfolio {}", error_msg, portfolio_id);
-                mark_cache_error(&ctx.pool, portfolio_id, DEFAULT_DAYS, DEFAULT_BENCHMARK, &error_msg).await.ok();
+                mark_cache_error(&ctx.pool, &tenant, DEFAULT_DAYS, DEFAULT_BENCHMARK, &error_msg).await.ok();
                 failed += 1;
             }
         }
@@ -176,7 +193,7 @@ pub async fn calculate_all_portfolio_risks(ctx: JobContext) -> Result<JobResult,
     }
 
     info!(
-        "✅ Portfolio risk job completed: {} processed, {} failed",
+        "Portfolio risk job completed: {} processed, {} failed",
         processed, failed
     );
 
@@ -206,6 +223,7 @@ async fn query_portfolios_with_holdings(pool: &PgPool) -> Result<Vec<Uuid>, AppE
         FROM portfolios p
         INNER JOIN accounts a ON a.portfolio_id = p.id
         INNER JOIN holdings_snapshots hs ON hs.account_id = a.id
+        WHERE p.archived_at IS NULL
         ORDER BY p.id
         "#
     )
@@ -231,7 +249,7 @@ async fn query_portfolios_with_holdings(pool: &PgPool) -> Result<Vec<Uuid>, AppE
 /// # Arguments
 ///
 /// * `pool` - Database connection pool
-/// * `portfolio_id` - Portfolio UUID
+/// * `tenant` - Portfolio and its owner
 /// * `days` - Rolling window in days
 /// * `benchmark` - Benchmark ticker
 ///
@@ -241,29 +259,12 @@ async fn query_portfolios_with_holdings(pool: &PgPool) -> Result<Vec<Uuid>, AppE
 /// * `Err(AppError)` - Database query error
 async fn check_cache_needs_refresh(
     pool: &PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     days: i64,
     benchmark: &str,
 ) -> Result<bool, AppError> {
-    #[derive(sqlx::FromRow)]
-    struct CacheRow {
-        calculation_status: Option<String>,
-        expires_at: chrono::DateTime<Utc>,
-    }
-
-    let result = sqlx::query_as::<_, CacheRow>(
-        r#"
-        SELECT calculation_status, expires_at
-        FROM portfolio_risk_cache
-        WHERE portfolio_id = $1 AND days = $2 AND benchmark = $3
-        "#
-    )
-    .bind(portfolio_id)
-    .bind(days as i32)
-    .bind(benchmark)
-    .fetch_optional(pool)
-    .await
-    .map_err(AppError::Db)?;
+    let portfolio_id = tenant.portfolio_id();
+    let result = risk_cache_queries::fetch_risk_cache(pool, tenant, days as i32, benchmark).await?;
 
     match result {
         None => {
@@ -271,7 +272,7 @@ async fn check_cache_needs_refresh(
             Ok(true)
         }
         Some(row) => {
-            let status = row.calculation_status.as_deref().unwrap_or("stale");
+            let status = row.calculation_status.as_str();
             let expires_at = row.expires_at;
 
             // Check if expired
@@ -280,6 +281,15 @@ async fn check_cache_needs_refresh(
                 return Ok(true);
             }
 
+            // Scores from an older scoring model need recalculating
+            if row.scoring_version != risk_service::CURRENT_SCORING_VERSION {
+                info!(
+                    "Cache for portfolio {} uses scoring v{}, current is v{}",
+                    portfolio_id, row.scoring_version, risk_service::CURRENT_SCORING_VERSION
+                );
+                return Ok(true);
+            }
+
             // Check status
             match status {
                 "fresh" => {
@@ -312,7 +322,7 @@ async fn check_cache_needs_refresh(
 /// # Arguments
 ///
 /// * `pool` - Database connection pool
-/// * `portfolio_id` - Portfolio UUID
+/// * `tenant` - Portfolio and its owner
 /// * `days` - Rolling window in days
 /// * `benchmark` - Benchmark ticker
 ///
@@ -322,26 +332,11 @@ async fn check_cache_needs_refresh(
 /// * `Err(AppError)` - Database update error
 async fn mark_cache_calculating(
     pool: &PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     days: i64,
     benchmark: &str,
 ) -> Result<(), AppError> {
-    sqlx::query(
-        r#"
-        INSERT INTO portfolio_risk_cache (portfolio_id, days, benchmark, risk_data, calculated_at, expires_at, calculation_status)
-        VALUES ($1, $2, $3, '{}'::jsonb, NOW(), NOW() + INTERVAL '4 hours', 'calculating')
-        ON CONFLICT (portfolio_id, days, benchmark)
-        DO UPDATE SET
-            calculation_status = 'calculating',
-            updated_at = NOW()
-        "#
-    )
-    .bind(portfolio_id)
-    .bind(days as i32)
-    .bind(benchmark)
-    .execute(pool)
-    .await
-    .map_err(AppError::Db)?;
+    risk_cache_queries::mark_risk_cache_calculating(pool, tenant, days as i32, benchmark).await?;
 
     Ok(())
 }
@@ -355,7 +350,7 @@ async fn mark_cache_calculating(
 /// # Arguments
 ///
 /// * `pool` - Database connection pool
-/// * `portfolio_id` - Portfolio UUID
+/// * `tenant` - Portfolio and its owner
 /// * `days` - Rolling window in days
 /// * `benchmark` - Benchmark ticker
 /// * `risk_data` - Calculated risk data
@@ -366,7 +361,7 @@ async fn mark_cache_calculating(
 /// * `Err(AppError)` - Serialization or database error
 async fn store_portfolio_risk_cache(
     pool: &PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     days: i64,
     benchmark: &str,
     risk_data: &PortfolioRiskWithViolations,
@@ -374,36 +369,18 @@ async fn store_portfolio_risk_cache(
     let risk_json = serde_json::to_value(risk_data)
         .map_err(|e| AppError::External(format!("Failed to serialize risk data: {}", e)))?;
 
-    let calculated_at = Utc::now();
-    let expires_at = calculated_at + Duration::hours(CACHE_EXPIRATION_HOURS);
+    let expires_at = Utc::now() + Duration::hours(CACHE_EXPIRATION_HOURS);
 
-    sqlx::query(
-        r#"
-        INSERT INTO portfolio_risk_cache (
-            portfolio_id, days, benchmark, risk_data, calculated_at, expires_at,
-            calculation_status, last_error, retry_count
-        )
-        VALUES ($1, $2, $3, $4, $5, $6, 'fresh', NULL, 0)
-        ON CONFLICT (portfolio_id, days, benchmark)
-        DO UPDATE SET
-            risk_data = $4,
-            calculated_at = $5,
-            expires_at = $6,
-            calculation_status = 'fresh',
-            last_error = NULL,
-            retry_count = 0,
-            updated_at = NOW()
-        "#
+    risk_cache_queries::store_risk_cache(
+        pool,
+        tenant,
+        days as i32,
+        benchmark,
+        &risk_json,
+        expires_at,
+        risk_data.portfolio_risk.scoring_version,
     )
-    .bind(portfolio_id)
-    .bind(days as i32)
-    .bind(benchmark)
-    .bind(risk_json)
-    .bind(calculated_at)
-    .bind(expires_at)
-    .execute(pool)
-    .await
-    .map_err(AppError::Db)?;
+    .await?;
 
     Ok(())
 }
@@ -416,7 +393,7 @@ async fn store_portfolio_risk_cache(
 /// # Arguments
 ///
 /// * `pool` - Database connection pool
-/// * `portfolio_id` - Portfolio UUID
+/// * `tenant` - Portfolio and its owner
 /// * `days` - Rolling window in days
 /// * `benchmark` - Benchmark ticker
 /// * `error_message` - Error description
@@ -427,33 +404,12 @@ async fn store_portfolio_risk_cache(
 /// * `Err(AppError)` - Database update error
 async fn mark_cache_error(
     pool: &PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     days: i64,
     benchmark: &str,
     error_message: &str,
 ) -> Result<(), AppError> {
-    sqlx::query(
-        r#"
-        INSERT INTO portfolio_risk_cache (
-            portfolio_id, days, benchmark, risk_data, calculated_at, expires_at,
-            calculation_status, last_error, retry_count
-        )
-        VALUES ($1, $2, $3, '{}'::jsonb, NOW(), NOW() + INTERVAL '1 hour', 'error', $4, 1)
-        ON CONFLICT (portfolio_id, days, benchmark)
-        DO UPDATE SET
-            calculation_status = 'error',
-            last_error = $4,
-            retry_count = COALESCE(portfolio_risk_cache.retry_count, 0) + 1,
-            updated_at = NOW()
-        "#
-    )
-    .bind(portfolio_id)
-    .bind(days as i32)
-    .bind(benchmark)
-    .bind(error_message)
-    .execute(pool)
-    .await
-    .map_err(AppError::Db)?;
+    risk_cache_queries::mark_risk_cache_error(pool, tenant, days as i32, benchmark, error_message).await?;
 
     Ok(())
 }
@@ -477,6 +433,7 @@ async fn mark_cache_error(
 /// * `days` - Rolling window in days
 /// * `benchmark` - Benchmark ticker
 /// * `price_provider` - External price data provider
+/// * `fx_provider` - Exchange rates for converting into the base currency
 /// * `failure_cache` - Cache to avoid repeated failed API calls
 /// * `rate_limiter` - Rate limiter for API requests
 ///
@@ -484,12 +441,14 @@ async fn mark_cache_error(
 ///
 /// * `Ok(PortfolioRiskWithViolations)` - Calculated risk data
 /// * `Err(AppError)` - Calculation error
+#[allow(clippy::too_many_arguments)]
 async fn calculate_portfolio_risk_internal(
     pool: &PgPool,
     portfolio_id: Uuid,
     days: i64,
     benchmark: &str,
     price_provider: &dyn PriceProvider,
+    fx_provider: &dyn FxProvider,
     failure_cache: &FailureCache,
     rate_limiter: &RateLimiter,
 ) -> Result<PortfolioRiskWithViolations, AppError> {
@@ -508,12 +467,15 @@ async fn calculate_portfolio_risk_internal(
         ));
     }
 
+    // Values from accounts in different currencies are summed in the owner's base currency
+    let fx = PortfolioFx::load(pool, fx_provider, portfolio_id, &holdings).await?;
+
     // 2. Aggregate holdings by ticker (same ticker across multiple accounts)
     let mut ticker_aggregates: HashMap<String, (f64, f64)> = HashMap::new(); // (quantity, market_value)
 
     for holding in &holdings {
-        let market_value = holding.market_value.to_string().parse::<f64>().unwrap_or(0.0);
-        let quantity = holding.quantity.to_string().parse::<f64>().unwrap_or(0.0);
+        let market_value = fx.market_value(holding);
+        let quantity = holding.quantity.to_f64().unwrap_or(0.0);
 
         ticker_aggregates
             .entry(holding.ticker.clone())
@@ -556,6 +518,15 @@ async fn calculate_portfolio_risk_internal(
         .and_then(|v| v.parse::<f64>().ok())
         .unwrap_or(0.045); // Default 4.5%
 
+
+    // Refresh every position's closes in one batch up front
+    let tickers: Vec<String> = ticker_aggregates
+        .iter()
+        .filter(|(_, (_, market_value))| market_value / total_value >= 0.001)
+        .map(|(ticker, _)| ticker.clone())
+        .collect();
+    risk_service::prefetch_prices(pool, &tickers, benchmark, price_provider, failure_cache, rate_limiter).await;
+
     for (ticker, (_quantity, market_value)) in ticker_aggregates {
         // Skip positions with negligible value (< 0.1% of portfolio)
         let weight = market_value / total_value;
@@ -629,17 +600,29 @@ async fn calculate_portfolio_risk_internal(
         ));
     }
 
+    // Volatility, drawdown, Sharpe and VaR come from the portfolio's own daily
+    // returns; averaging the position metrics ignores diversification and cash.
+    // The weighted averages are kept as a fallback for too little history.
+    let returns = portfolio_return_service::compute_metrics(pool, portfolio_id, days, benchmark, risk_free_rate)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Could not build the return series for portfolio {}: {}", portfolio_id, e);
+            Default::default()
+        });
+    let portfolio_volatility = returns.volatility.unwrap_or(weighted_volatility);
+    let portfolio_max_drawdown = returns.max_drawdown.unwrap_or(weighted_max_drawdown);
+
     // 4. Calculate portfolio-level risk score
     let portfolio_risk_score = risk_service::score_risk(&crate::models::PositionRisk {
-        volatility: weighted_volatility,
-        max_drawdown: weighted_max_drawdown,
+        volatility: portfolio_volatility,
+        max_drawdown: portfolio_max_drawdown,
         beta: if beta_count > 0 { Some(weighted_beta) } else { None },
         beta_spy: if beta_count > 0 { Some(weighted_beta) } else { None },
         beta_qqq: None,
         beta_iwm: None,
         risk_decomposition: None,
-        sharpe: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
-        sortino: None,
+        sharpe: returns.sharpe,
+        sortino: returns.sortino,
         annualized_return: None,
         value_at_risk: None,
         var_95: None,
@@ -658,16 +641,22 @@ async fn calculate_portfolio_risk_internal(
     let portfolio_risk = crate::models::PortfolioRisk {
         portfolio_id: portfolio_id.to_string(),
         total_value,
-        portfolio_volatility: weighted_volatility,
-        portfolio_max_drawdown: weighted_max_drawdown,
+        portfolio_volatility,
+        portfolio_max_drawdown,
         portfolio_beta: if beta_count > 0 { Some(weighted_beta) } else { None },
-        portfolio_sharpe: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
-        portfolio_var_95: if var_95_count > 0 { Some(weighted_var_95) } else { None },
-        portfolio_var_99: if var_99_count > 0 { Some(weighted_var_99) } else { None },
-        portfolio_expected_shortfall_95: if es_95_count > 0 { Some(weighted_es_95) } else { None },
-        portfolio_expected_shortfall_99: if es_99_count > 0 { Some(weighted_es_99) } else { None },
+        portfolio_regression_beta: returns.beta,
+        portfolio_alpha: returns.alpha,
+        portfolio_r_squared: returns.r_squared,
+        portfolio_sharpe: returns.sharpe,
+        portfolio_sortino: returns.sortino,
+        portfolio_sharpe_weighted_average: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
+        portfolio_var_95: returns.var_95.or(if var_95_count > 0 { Some(weighted_var_95) } else { None }),
+        portfolio_var_99: returns.var_99.or(if var_99_count > 0 { Some(weighted_var_99) } else { None }),
+        portfolio_expected_shortfall_95: returns.expected_shortfall_95.or(if es_95_count > 0 { Some(weighted_es_95) } else { None }),
+        portfolio_expected_shortfall_99: returns.expected_shortfall_99.or(if es_99_count > 0 { Some(weighted_es_99) } else { None }),
         portfolio_risk_score,
         risk_level,
+        scoring_version: risk_service::CURRENT_SCORING_VERSION,
         position_risks: position_risks.clone(),
     };
 
@@ -698,9 +687,48 @@ async fn calculate_portfolio_risk_internal(
         portfolio_risk,
         thresholds,
         violations,
+        budget_utilization: None,
+        peer_context: None,
     })
 }
 
+/// Violations stored by the previous calculation, empty when there is none
+async fn fetch_cached_violations(pool: &PgPool, tenant: &TenantScope) -> Vec<ThresholdViolation> {
+    risk_cache_queries::fetch_risk_cache(pool, tenant, DEFAULT_DAYS as i32, DEFAULT_BENCHMARK)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|entry| entry.risk_data.get("violations").cloned())
+        .and_then(|violations| serde_json::from_value(violations).ok())
+        .unwrap_or_default()
+}
+
+/// Critical violations in `current` that weren't critical in `previous`
+fn new_critical_violations(previous: &[ThresholdViolation], current: &[ThresholdViolation]) -> Vec<ThresholdViolation> {
+    let was_critical = |v: &ThresholdViolation| {
+        previous.iter().any(|p| {
+            p.threshold_type == ViolationSeverity::Critical && p.ticker == v.ticker && p.metric_name == v.metric_name
+        })
+    };
+    current
+        .iter()
+        .filter(|v| v.threshold_type == ViolationSeverity::Critical && !was_critical(v))
+        .cloned()
+        .collect()
+}
+
+async fn notify_owner(pool: &PgPool, portfolio_id: Uuid, violations: &[ThresholdViolation]) -> Result<(), AppError> {
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let Some(portfolio) = portfolio_queries::fetch_one_unchecked(pool, portfolio_id).await? else {
+        return Ok(());
+    };
+    notification_service::notify_critical_violations(pool, portfolio.user_id, portfolio_id, &portfolio.name, violations)
+        .await?;
+    Ok(())
+}
+
 /// Detect threshold violations in portfolio risk data.
 ///
 /// This function checks each position's risk metrics against the configured
@@ -851,4 +879,40 @@ mod tests {
         assert_eq!(PORTFOLIO_TIMEOUT_SECONDS, 300);
         assert_eq!(INTER_PORTFOLIO_DELAY_MS, 1000);
     }
+
+    fn violation(ticker: &str, metric: &str, severity: ViolationSeverity) -> ThresholdViolation {
+        ThresholdViolation {
+            ticker: ticker.to_string(),
+            holding_name: None,
+            metric_name: metric.to_string(),
+            metric_value: 0.0,
+            threshold_value: 0.0,
+            threshold_type: severity,
+        }
+    }
+
+    #[test]
+    fn test_new_critical_violations() {
+        let previous = vec![
+            violation("AAPL", "Volatility", ViolationSeverity::Critical),
+            violation("MSFT", "Volatility", ViolationSeverity::Warning),
+        ];
+        let current = vec![
+            violation("AAPL", "Volatility", ViolationSeverity::Critical),
+            violation("AAPL", "Max Drawdown", ViolationSeverity::Critical),
+            violation("MSFT", "Volatility", ViolationSeverity::Critical),
+            violation("TSLA", "Volatility", ViolationSeverity::Warning),
+        ];
+        let new: Vec<(String, String)> = new_critical_violations(&previous, &current)
+            .into_iter()
+            .map(|v| (v.ticker, v.metric_name))
+            .collect();
+        assert_eq!(
+            new,
+            vec![
+                ("AAPL".to_string(), "Max Drawdown".to_string()),
+                ("MSFT".to_string(), "Volatility".to_string()),
+            ]
+        );
+    }
 }
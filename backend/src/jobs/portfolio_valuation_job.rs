@@ -0,0 +1,63 @@
+//! Portfolio Valuation Background Job
+//!
+//! Runs daily after the close and values every account from its latest imported
+//! holdings and the latest prices, so value history has a point per trading day
+//! even when statements are only imported monthly. The last week is re-valued
+//! on each run to pick up late prices and missed runs; valuations are upserted,
+//! so re-running the job is a no-op.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use crate::db::portfolio_value_history_queries;
+use crate::errors::AppError;
+use crate::services::{clock, job_scheduler_service::{JobContext, JobResult}};
+use tracing::{error, info};
+
+/// Trading days re-valued on each run, counting back from today
+const CATCH_UP_DAYS: i64 = 7;
+
+/// Main entry point for the portfolio valuation job.
+pub async fn record_portfolio_valuations(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting portfolio valuation job");
+
+    let mut processed = 0;
+    let mut failed = 0;
+    for date in valuation_dates(clock::today()) {
+        match portfolio_value_history_queries::record_valuations(ctx.pool.as_ref(), date).await {
+            Ok(accounts) => {
+                info!("Valued {} accounts on {}", accounts, date);
+                processed += accounts as i32;
+            }
+            Err(e) => {
+                error!("Failed to value accounts on {}: {}", date, e);
+                failed += 1;
+            }
+        }
+    }
+
+    Ok(JobResult {
+        items_processed: processed,
+        items_failed: failed,
+    })
+}
+
+/// Weekdays in the last `CATCH_UP_DAYS` days up to and including `today`, oldest first
+fn valuation_dates(today: NaiveDate) -> Vec<NaiveDate> {
+    (0..CATCH_UP_DAYS)
+        .rev()
+        .map(|days| today - Duration::days(days))
+        .filter(|date| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valuation_dates_skip_weekends() {
+        // Monday 2026-03-09: back to Tuesday 2026-03-03, without the weekend
+        let dates = valuation_dates(NaiveDate::from_ymd_opt(2026, 3, 9).unwrap());
+        let days: Vec<u32> = dates.iter().map(|d| d.day()).collect();
+        assert_eq!(days, vec![3, 4, 5, 6, 9]);
+    }
+}
@@ -0,0 +1,38 @@
+//! Price Backfill Background Job
+//!
+//! Runs nightly after the price refresh. Extends the stored closes of every
+//! ticker held in a portfolio or followed on a watchlist to
+//! `PRICE_BACKFILL_YEARS` of history (default 5) with the provider's full
+//! history, so rolling beta and other long-window analytics work for newly
+//! added tickers. At most `PRICE_BACKFILL_MAX_TICKERS` tickers are fetched
+//! per run, newest first, through the shared rate limiter; the rest follow on
+//! later runs.
+
+use crate::errors::AppError;
+use crate::services::job_scheduler_service::{JobContext, JobResult};
+use crate::services::price_backfill_service::{self, BackfillConfig};
+use tracing::info;
+
+/// Main entry point for the price backfill job.
+pub async fn backfill_price_history(ctx: JobContext) -> Result<JobResult, AppError> {
+    let config = BackfillConfig::from_env();
+    info!("Starting price backfill job ({} years, up to {} tickers)", config.years, config.max_tickers);
+
+    let run = price_backfill_service::backfill(
+        &ctx.pool,
+        ctx.price_provider.as_ref(),
+        ctx.rate_limiter.as_ref(),
+        &config,
+    )
+    .await?;
+
+    info!(
+        "Price backfill job complete: {} backfilled, {} failed, {} still short of {} years",
+        run.backfilled, run.failed, run.remaining, config.years
+    );
+
+    Ok(JobResult {
+        items_processed: run.backfilled as i32,
+        items_failed: run.failed as i32,
+    })
+}
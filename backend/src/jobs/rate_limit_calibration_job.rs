@@ -0,0 +1,66 @@
+//! Rate Limit Calibration Background Job
+//!
+//! Runs every 15 minutes and asks the price provider for its current quota
+//! (Twelve Data reports its plan limit and the credits left this minute). The
+//! shared rate limiter's per-minute budget is then set just under the reported
+//! limit, so upgrading or downgrading the provider plan takes effect without a
+//! config change. Providers that report no quota leave the budget unchanged.
+
+use crate::errors::AppError;
+use crate::external::price_provider::PriceProviderError;
+use crate::services::job_scheduler_service::{JobContext, JobResult};
+use tracing::{error, info, warn};
+
+/// Main entry point for the rate limit calibration job.
+pub async fn calibrate_rate_limits(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting rate limit calibration job");
+
+    // The probe counts against the same quota as price requests
+    let probe = {
+        let _guard = ctx.rate_limiter.acquire().await;
+        ctx.price_provider.probe_quota().await
+    };
+
+    match probe {
+        Ok(Some(quota)) => {
+            let calibration = ctx.rate_limiter.calibrate(&quota);
+            info!(
+                "Calibrated rate limiter from {} quota (limit {:?}/min, {:?} remaining): {} -> {} requests/min",
+                calibration.provider,
+                calibration.reported_limit_per_minute,
+                calibration.reported_remaining,
+                calibration.previous_per_minute,
+                calibration.applied_per_minute
+            );
+            Ok(JobResult {
+                items_processed: 1,
+                items_failed: 0,
+            })
+        }
+        Ok(None) => {
+            info!(
+                "Price provider reports no quota; keeping {} requests/min",
+                ctx.rate_limiter.requests_per_minute()
+            );
+            Ok(JobResult {
+                items_processed: 0,
+                items_failed: 0,
+            })
+        }
+        Err(PriceProviderError::RateLimited) => {
+            ctx.rate_limiter.record_rejection();
+            warn!("Quota probe was rate limited; keeping current budget");
+            Ok(JobResult {
+                items_processed: 0,
+                items_failed: 1,
+            })
+        }
+        Err(e) => {
+            error!("Failed to probe price provider quota: {}", e);
+            Ok(JobResult {
+                items_processed: 0,
+                items_failed: 1,
+            })
+        }
+    }
+}
@@ -23,9 +23,9 @@ use uuid::Uuid;
 
 use crate::db::hmm_queries;
 use crate::errors::AppError;
-use crate::external::alphavantage::AlphaVantageProvider;
 use crate::external::price_provider::PriceProvider;
 use crate::services::hmm_inference_service;
+use crate::services::clock;
 use crate::services::job_scheduler_service::{JobContext, JobResult};
 use crate::models::{ObservationSymbol, PricePoint};
 
@@ -33,14 +33,14 @@ use crate::models::{ObservationSymbol, PricePoint};
 ///
 /// This is the main entry point called by the job scheduler
 pub async fn generate_all_regime_forecasts(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🔮 Starting regime forecast generation job");
+    info!("Starting regime forecast generation job");
     let start_time = std::time::Instant::now();
 
-    match run_forecast_generation(&ctx.pool).await {
+    match run_forecast_generation(&ctx.pool, ctx.price_provider.as_ref()).await {
         Ok(forecast_count) => {
             let elapsed = start_time.elapsed();
             info!(
-                "✅ Generated {} regime forecasts in {:.2}s",
+                "Generated {} regime forecasts in {:.2}s",
                 forecast_count,
                 elapsed.as_secs_f64()
             );
@@ -50,14 +50,14 @@ pub async fn generate_all_regime_forecasts(ctx: JobContext) -> Result<JobResult,
             })
         }
         Err(e) => {
-            error!("❌ Regime forecast generation failed: {}", e);
+            error!("Regime forecast generation failed: {}", e);
             Err(e)
         }
     }
 }
 
 /// Core forecast generation logic
-async fn run_forecast_generation(pool: &PgPool) -> Result<usize, AppError> {
+async fn run_forecast_generation(pool: &PgPool, price_provider: &dyn PriceProvider) -> Result<usize, AppError> {
     // 1. Check if HMM model exists
     match hmm_queries::load_latest_hmm_model(pool, "SPY").await {
         Ok(model) => {
@@ -72,10 +72,7 @@ async fn run_forecast_generation(pool: &PgPool) -> Result<usize, AppError> {
     }
 
     // 2. Fetch recent market data for observations
-    let price_provider = AlphaVantageProvider::from_env()
-        .map_err(|e| AppError::External(format!("Failed to init price provider: {}", e)))?;
-
-    let recent_prices = match fetch_recent_market_data(&price_provider).await {
+    let recent_prices = match fetch_recent_market_data(price_provider).await {
         Ok(prices) => prices,
         Err(e) => {
             error!("Failed to fetch recent market data: {}", e);
@@ -101,7 +98,7 @@ async fn run_forecast_generation(pool: &PgPool) -> Result<usize, AppError> {
     info!("Generated {} recent observations for forecasting", observations.len());
 
     // 4. Generate forecasts for multiple horizons
-    let forecast_date = Utc::now().date_naive();
+    let forecast_date = clock::today();
     let forecasts = hmm_inference_service::generate_regime_forecasts(
         pool,
         forecast_date,
@@ -129,7 +126,7 @@ async fn run_forecast_generation(pool: &PgPool) -> Result<usize, AppError> {
 
 /// Fetch recent market data for observation generation
 async fn fetch_recent_market_data(
-    price_provider: &AlphaVantageProvider,
+    price_provider: &dyn PriceProvider,
 ) -> Result<Vec<PricePoint>, AppError> {
     // Fetch last 30 days of SPY data
     let days = 30;
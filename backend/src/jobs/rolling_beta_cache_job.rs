@@ -17,6 +17,7 @@
 //! 4. Store results in rolling_beta_cache table
 //! 5. Add delays between tickers to avoid overloading the system
 
+use crate::db::risk_cache_queries;
 use crate::errors::AppError;
 use crate::services::job_scheduler_service::{JobContext, JobResult};
 use crate::services::risk_service;
@@ -29,7 +30,7 @@ const INTER_TICKER_DELAY_MS: u64 = 1000; // 1 second delay between tickers
 
 /// Main entry point for the rolling beta cache population job.
 pub async fn populate_rolling_beta_caches(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🔄 Populating rolling beta caches...");
+    info!("Populating rolling beta caches...");
 
     // Get all unique tickers from positions
     let tickers = sqlx::query_scalar::<_, String>(
@@ -82,11 +83,11 @@ pub async fn populate_rolling_beta_caches(ctx: JobContext) -> Result<JobResult,
         {
             Ok(_) => {
                 processed += 1;
-                info!("✅ Cached rolling beta for {}", ticker);
+                info!("Cached rolling beta for {}", ticker);
             }
             Err(e) => {
                 failed += 1;
-                warn!("❌ Failed to cache rolling beta for {}: {}", ticker, e);
+                warn!("Failed to cache rolling beta for {}: {}", ticker, e);
             }
         }
 
@@ -112,15 +113,7 @@ async fn check_cache_needs_refresh(
     benchmark: &str,
     days: i64,
 ) -> Result<bool, AppError> {
-    let result = sqlx::query_scalar::<_, chrono::NaiveDateTime>(
-        "SELECT expires_at FROM rolling_beta_cache
-         WHERE ticker = $1 AND benchmark = $2 AND total_days = $3"
-    )
-    .bind(ticker)
-    .bind(benchmark)
-    .bind(days as i32)
-    .fetch_optional(pool)
-    .await?;
+    let result = risk_cache_queries::fetch_rolling_beta_expiry(pool, ticker, benchmark, days as i32).await?;
 
     match result {
         Some(expires_at) => {
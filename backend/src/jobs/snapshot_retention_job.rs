@@ -0,0 +1,35 @@
+//! Snapshot Retention Background Job
+//!
+//! Runs weekly and compacts old risk and holdings snapshots into weekly and then
+//! monthly tiers, per `SNAPSHOT_WEEKLY_AFTER_MONTHS` / `SNAPSHOT_MONTHLY_AFTER_MONTHS`.
+//! Compaction only ever removes rows, so re-running it is a no-op.
+
+use crate::errors::AppError;
+use crate::services::{job_scheduler_service::{JobContext, JobResult}, snapshot_retention_service};
+use crate::services::snapshot_retention_service::RetentionConfig;
+use tracing::{error, info};
+
+/// Main entry point for the snapshot retention job.
+pub async fn compact_snapshots(ctx: JobContext) -> Result<JobResult, AppError> {
+    info!("Starting snapshot compaction job");
+
+    match snapshot_retention_service::compact_snapshots(&ctx.pool, RetentionConfig::from_env()).await {
+        Ok(result) => {
+            info!(
+                "Compacted snapshots: removed {} risk rows and {} holding rows",
+                result.risk_rows_removed, result.holding_rows_removed
+            );
+            Ok(JobResult {
+                items_processed: (result.risk_rows_removed + result.holding_rows_removed) as i32,
+                items_failed: 0,
+            })
+        }
+        Err(e) => {
+            error!("Failed to compact snapshots: {}", e);
+            Ok(JobResult {
+                items_processed: 0,
+                items_failed: 1,
+            })
+        }
+    }
+}
@@ -12,6 +12,8 @@ const INTER_TICKER_DELAY_MS: u64 = 500;
 /// 1. Gets all distinct tickers across all watchlists
 /// 2. For each ticker, runs monitoring checks (thresholds, patterns, sentiment)
 /// 3. Stores generated alerts in the database
+/// 4. Notifies users whose positions crossed their target price, stop-loss or
+///    trailing stop (a percentage below the high since entry)
 ///
 /// Designed to run every 30 minutes during market hours.
 pub async fn run_watchlist_monitoring(ctx: JobContext) -> Result<JobResult, AppError> {
@@ -24,20 +26,33 @@ pub async fn run_watchlist_monitoring(ctx: JobContext) -> Result<JobResult, AppE
         .await
         .map_err(AppError::Db)?;
 
+    let mut processed = 0;
+    let mut failed = 0;
+    let mut total_alerts = 0;
+
+    match watchlist_monitoring_service::check_position_levels(pool).await {
+        Ok(count) => {
+            total_alerts += count;
+            if count > 0 {
+                info!("Generated {} position target/stop-loss/trailing-stop alerts", count);
+            }
+        }
+        Err(e) => {
+            error!("Failed to check position targets and stop-losses: {}", e);
+            failed += 1;
+        }
+    }
+
     if tickers.is_empty() {
         info!("No watchlist tickers to monitor");
         return Ok(JobResult {
-            items_processed: 0,
-            items_failed: 0,
+            items_processed: processed,
+            items_failed: failed,
         });
     }
 
     info!("Monitoring {} watchlist tickers", tickers.len());
 
-    let mut processed = 0;
-    let mut failed = 0;
-    let mut total_alerts = 0;
-
     for ticker in &tickers {
         match watchlist_monitoring_service::monitor_ticker(pool, ticker).await {
             Ok(results) => {
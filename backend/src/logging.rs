@@ -1,4 +1,28 @@
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::Level;
+use tracing_subscriber::filter::{EnvFilter, Targets};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer, Registry};
+
+use crate::middleware::request_context::REQUEST_SPAN_TARGET;
+use crate::services::latency_monitor_service::QueryTimingLayer;
+
+/// Console output format, from `LOG_FORMAT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One JSON object per line, with the fields of the enclosing spans
+    /// (request_id, user_id, portfolio_id, ...) listed under `spans`
+    Json,
+    /// Human-readable lines for local development
+    Text,
+}
+
+impl LogFormat {
+    pub fn from_param(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "text" | "pretty" => LogFormat::Text,
+            _ => LogFormat::Json,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct LoggingConfig {
@@ -7,6 +31,7 @@ pub struct LoggingConfig {
     pub service_name: String,
     pub environment: String,
     pub log_level: String,
+    pub log_format: LogFormat,
 }
 
 impl LoggingConfig {
@@ -23,6 +48,7 @@ impl LoggingConfig {
                 .unwrap_or_else(|_| "development".to_string()),
             log_level: std::env::var("RUST_LOG")
                 .unwrap_or_else(|_| "info".to_string()),
+            log_format: LogFormat::from_param(&std::env::var("LOG_FORMAT").unwrap_or_default()),
         }
     }
 
@@ -41,21 +67,47 @@ pub fn init_logging(config: LoggingConfig) -> Result<(), Box<dyn std::error::Err
     {
         if config.loki_enabled {
             if let Some(loki_url) = config.loki_url.clone() {
-                tracing::info!("📊 Initializing logging with Loki at {}", loki_url);
+                tracing::info!("Initializing logging with Loki at {}", loki_url);
                 return init_with_loki(config, &loki_url);
             }
         }
     }
 
     // Fallback to console-only logging
-    tracing::info!("📊 Initializing console-only logging");
+    tracing::info!("Initializing console-only logging");
     init_console_only(config)
 }
 
+/// Console layer in the configured format
+fn console_layer(format: LogFormat) -> Box<dyn Layer<Registry> + Send + Sync> {
+    match format {
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_list(true)
+            .boxed(),
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+    }
+}
+
+/// Query timing for the latency budget monitor. sqlx reports every statement
+/// at debug level, so this layer gets its own filter instead of `RUST_LOG`.
+fn query_timing_layer<S>() -> impl Layer<S>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    QueryTimingLayer.with_filter(
+        Targets::new()
+            .with_target("sqlx::query", Level::DEBUG)
+            .with_target(REQUEST_SPAN_TARGET, Level::INFO),
+    )
+}
+
 fn init_console_only(config: LoggingConfig) -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(&config.log_level))
-        .with(tracing_subscriber::fmt::layer())
+        .with(console_layer(config.log_format).with_filter(EnvFilter::new(&config.log_level)))
+        .with(query_timing_layer())
         .init();
 
     Ok(())
@@ -80,12 +132,12 @@ fn init_with_loki(config: LoggingConfig, loki_url: &str) -> Result<(), Box<dyn s
     tokio::spawn(task);
 
     tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(&config.log_level))
-        .with(tracing_subscriber::fmt::layer())
-        .with(loki_layer)
+        .with(console_layer(config.log_format).with_filter(EnvFilter::new(&config.log_level)))
+        .with(loki_layer.with_filter(EnvFilter::new(&config.log_level)))
+        .with(query_timing_layer())
         .init();
 
-    tracing::info!("✅ Loki logging initialized successfully");
+    tracing::info!("Loki logging initialized successfully");
 
     Ok(())
 }
@@ -13,21 +13,32 @@ mod logging;
 mod jobs;
 mod auth;
 mod middleware;
+mod repositories;
 
 use std::net::SocketAddr;
 use std::sync::Arc;
 use sqlx::postgres::PgPoolOptions;
 use tokio::net::TcpListener;
-use crate::external::alphavantage::AlphaVantageProvider;
-use crate::external::twelvedata::TwelveDataProvider;
 use crate::external::yahoofinance::YahooFinanceProvider;
-use crate::external::multi_provider::MultiProvider;
+use crate::external::finnhub::FinnhubProvider;
+use crate::external::fundamentals_provider::FundamentalsProvider;
+use crate::external::etf_holdings_provider::{EtfHoldingsProvider, IssuerFileEtfHoldingsProvider};
+use crate::external::provider_factory::{self, ProviderSettings};
+use crate::external::news::{AlphaVantageNewsProvider, NewsProvider};
+use crate::external::chaos_provider::ChaosPriceProvider;
+use crate::external::price_provider::PriceProvider;
+use crate::external::chain_provider::{ChainProvider, PublicChainProvider};
+use crate::external::fixture::{FixtureChainProvider, FixtureFxProvider};
+use crate::external::fx_provider::{FrankfurterProvider, FxProvider};
+use crate::repositories::Repositories;
 use crate::state::AppState;
 use crate::services::failure_cache::FailureCache;
-use crate::services::rate_limiter::RateLimiter;
+use crate::services::clock::{self, FixedClock};
+use crate::services::chaos::{self, Chaos, ChaosConfig};
 use crate::services::llm_service::{LlmService, LlmConfig};
 use crate::services::news_service::{NewsService, NewsConfig};
 use crate::services::job_scheduler_service::JobSchedulerService;
+use crate::services::{admin_user_service, benchmark_seed_service, integrity_service};
 use crate::logging::{LoggingConfig, init_logging};
 
 #[tokio::main]
@@ -40,50 +51,125 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let database_url = std::env::var("DATABASE_URL")?;
 
-    let pool = PgPoolOptions::new()
-        .max_connections(10)
-        .connect(&database_url)
-        .await?;
+    // Fault injection for resilience tests; never set CHAOS_ENABLED in production
+    let chaos = ChaosConfig::from_env().map(|config| Arc::new(Chaos::new(config)));
+
+    let mut pool_options = PgPoolOptions::new().max_connections(10);
+    if let Some(chaos) = &chaos {
+        pool_options = chaos::with_db_faults(pool_options, chaos.clone());
+    }
+    let pool = pool_options.connect(&database_url).await?;
 
     // Run pending migrations automatically on startup
     sqlx::migrate!("./migrations").run(&pool).await?;
-    tracing::info!("✅ Database migrations applied");
-
-    // Select price provider based on PRICE_PROVIDER env var (defaults to multi)
-    let provider_name = std::env::var("PRICE_PROVIDER")
-        .unwrap_or_else(|_| "multi".to_string());
-
-    let provider: Arc<dyn crate::external::price_provider::PriceProvider> = match provider_name.to_lowercase().as_str() {
-        "alphavantage" => {
-            tracing::info!("📊 Using price provider: Alpha Vantage only");
-            Arc::new(AlphaVantageProvider::from_env()
-                .expect("Failed to create AlphaVantageProvider (check ALPHAVANTAGE_API_KEY)"))
-        },
-        "twelvedata" => {
-            tracing::info!("📊 Using price provider: Twelve Data only");
-            Arc::new(TwelveDataProvider::from_env()
-                .expect("Failed to create TwelveDataProvider (check TWELVEDATA_API_KEY)"))
-        },
-        "multi" => {
-            tracing::info!("📊 Using price provider: Multi-provider (Twelve Data + Alpha Vantage + Yahoo Finance)");
-            let primary = Box::new(TwelveDataProvider::from_env()
-                .expect("Failed to create TwelveDataProvider (check TWELVEDATA_API_KEY)"));
-            let fallback = Box::new(AlphaVantageProvider::from_env()
-                .expect("Failed to create AlphaVantageProvider (check ALPHAVANTAGE_API_KEY)"));
-            let yahoo = Box::new(YahooFinanceProvider::new());
-            Arc::new(MultiProvider::new(primary, fallback, yahoo))
-        },
-        _ => {
-            panic!("Invalid PRICE_PROVIDER: {}. Must be 'alphavantage', 'twelvedata', or 'multi'", provider_name);
+    tracing::info!("Database migrations applied");
+
+    if let Some(chaos) = &chaos {
+        chaos.arm_db();
+        chaos::install(chaos.clone());
+        tracing::warn!("Chaos enabled, injecting faults: {:?}", chaos.config());
+    }
+
+    // Demo mode pins the clock and serves seeded fixture prices, so the whole
+    // service runs deterministically without network access
+    let demo_mode = std::env::args().any(|arg| arg == "--demo")
+        || std::env::var("DEMO_MODE").map(|v| v == "true").unwrap_or(false);
+
+    if demo_mode {
+        let as_of = std::env::var("DEMO_AS_OF")
+            .ok()
+            .and_then(|s| chrono::NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok())
+            .unwrap_or_else(|| chrono::NaiveDate::from_ymd_opt(2025, 6, 30).unwrap());
+        let market_close = as_of.and_hms_opt(21, 0, 0).unwrap().and_utc();
+        clock::install(Arc::new(FixedClock::new(market_close)));
+        tracing::info!("Demo mode: clock fixed at {}", market_close);
+    }
+
+    // PRICE_PROVIDER selects the price provider (defaults to multi, or fixture in demo
+    // mode); each provider reads its key, base URL and rate limits from its own settings
+    let provider_name = provider_factory::selected_name(demo_mode);
+
+    // Shared with the composite provider, which remembers per-provider misses in it
+    let failure_cache = FailureCache::new();
+
+    let provider = provider_factory::build(&provider_name, failure_cache.clone())
+        .unwrap_or_else(|e| panic!("Failed to create price provider '{}': {}", provider_name, e));
+    let provider: Arc<dyn PriceProvider> = match &chaos {
+        Some(chaos) => Arc::new(ChaosPriceProvider::new(provider, chaos.clone())),
+        None => provider,
+    };
+    // Wallet balances come from public block explorers, or seeded fixtures in demo mode
+    let chain_provider: Arc<dyn ChainProvider> =
+        if demo_mode || std::env::var("CHAIN_PROVIDER").is_ok_and(|p| p.eq_ignore_ascii_case("fixture")) {
+            let seed = std::env::var("DEMO_SEED")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(42);
+            tracing::info!("Using chain provider: Fixture (seed {})", seed);
+            Arc::new(FixtureChainProvider::new(seed))
+        } else {
+            tracing::info!("Using chain provider: public block explorers");
+            Arc::new(PublicChainProvider::from_env())
+        };
+
+    // P/E, P/B and market cap come from Finnhub whenever a key is configured,
+    // independent of the price provider; without one, scoring uses price proxies
+    let fundamentals_provider: Option<Arc<dyn FundamentalsProvider>> = if demo_mode {
+        None
+    } else {
+        match FinnhubProvider::from_env() {
+            Ok(finnhub) => {
+                tracing::info!("Using fundamentals provider: Finnhub");
+                Some(Arc::new(finnhub))
+            }
+            Err(_) => {
+                tracing::info!("No fundamentals provider configured (FINNHUB_API_KEY not set)");
+                None
+            }
         }
     };
+
+    // Scored news for the sentiment cache comes from Alpha Vantage whenever its key
+    // is configured, independent of the price provider
+    let news_provider: Option<Arc<dyn NewsProvider>> = if demo_mode {
+        None
+    } else {
+        match AlphaVantageNewsProvider::from_settings(&ProviderSettings::from_env("alphavantage")) {
+            Ok(alphavantage) => {
+                tracing::info!("Using news provider: Alpha Vantage");
+                Some(Arc::new(alphavantage))
+            }
+            Err(_) => {
+                tracing::info!("No news provider configured (ALPHAVANTAGE_API_KEY not set)");
+                None
+            }
+        }
+    };
+
+    // ETF constituents come from issuer holdings files in ETF_HOLDINGS_DIR when
+    // present, and Yahoo's top holdings otherwise
+    let etf_holdings_provider: Arc<dyn EtfHoldingsProvider> = match std::env::var("ETF_HOLDINGS_DIR") {
+        Ok(dir) => {
+            tracing::info!("Using ETF holdings: issuer files in {}, Yahoo Finance fallback", dir);
+            Arc::new(IssuerFileEtfHoldingsProvider::new(dir.into(), Box::new(YahooFinanceProvider::new())))
+        }
+        Err(_) => Arc::new(YahooFinanceProvider::new()),
+    };
+
+    // Exchange rates are ECB reference rates from Frankfurter, or fixed ones in demo mode
+    let (fx_provider, fx_provider_name): (Arc<dyn FxProvider>, &str) = if demo_mode {
+        (Arc::new(FixtureFxProvider), "fixture")
+    } else {
+        (Arc::new(FrankfurterProvider::from_env()), "frankfurter")
+    };
+
     // Read risk-free rate from environment (default to 4.5% = 0.045 annual rate)
     let risk_free_rate = std::env::var("RISK_FREE_RATE")
         .ok()
         .and_then(|s| s.parse::<f64>().ok())
         .unwrap_or(0.045); // Default: 4.5% (US 10-year Treasury approximation)
 
-    tracing::info!("📈 Risk-free rate set to: {:.2}%", risk_free_rate * 100.0);
+    tracing::info!("Risk-free rate set to: {:.2}%", risk_free_rate * 100.0);
 
     // Initialize LLM service
     let llm_provider = std::env::var("LLM_PROVIDER")
@@ -115,9 +201,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let llm_service = Arc::new(LlmService::new(llm_config));
 
     if llm_service.is_enabled() {
-        tracing::info!("🤖 LLM service enabled");
+        tracing::info!("LLM service enabled");
     } else {
-        tracing::info!("🤖 LLM service disabled");
+        tracing::info!("LLM service disabled");
     }
 
     // Initialize News service
@@ -125,23 +211,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let news_service = Arc::new(NewsService::new(news_config, llm_service.clone()));
 
     if news_service.is_enabled() {
-        tracing::info!("📰 News service enabled");
+        tracing::info!("News service enabled");
     } else {
-        tracing::info!("📰 News service disabled");
+        tracing::info!("News service disabled");
     }
 
-    // Initialize rate limiter for API calls
-    // Allow max 3 concurrent requests, 8 per minute (free tier limit)
-    let rate_limiter = Arc::new(RateLimiter::new(3, 8));
-    tracing::info!("⏱️  Rate limiter initialized: 3 concurrent, 8 requests/min");
+    // Rate limiter sized to the selected provider's plan (3 concurrent, 8 requests/min
+    // unless configured). The plan's daily allowance, when set, feeds the quota
+    // estimate of GET /api/providers/status.
+    let rate_limiter = Arc::new(provider_factory::rate_limiter(&provider_name));
 
     let jwt_secret = std::env::var("JWT_SECRET")
         .unwrap_or_else(|_| "change-me-in-production-use-a-long-random-secret".to_string());
 
     let state = AppState {
         pool: pool.clone(),
+        repos: Repositories::postgres(pool.clone()),
         price_provider: provider.clone(),
-        failure_cache: FailureCache::new(),
+        price_provider_name: provider_name.clone(),
+        ownership_provider: Arc::new(YahooFinanceProvider::new()),
+        analyst_provider: Arc::new(YahooFinanceProvider::new()),
+        fundamentals_provider,
+        news_provider,
+        etf_holdings_provider: etf_holdings_provider.clone(),
+        dividend_provider: Arc::new(YahooFinanceProvider::new()),
+        fx_provider,
+        fx_provider_name: fx_provider_name.to_string(),
+        chain_provider: chain_provider.clone(),
+        failure_cache,
         rate_limiter: rate_limiter.clone(),
         risk_free_rate,
         llm_service,
@@ -149,18 +246,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         jwt_secret,
     };
 
+    // A fresh instance gets its first administrator from ADMIN_EMAIL
+    if let Err(e) = admin_user_service::bootstrap_from_env(&pool).await {
+        tracing::warn!("Admin bootstrap from ADMIN_EMAIL failed: {}", e);
+    }
+
+    // Backfill benchmark history on first run so beta and regime jobs have
+    // data before anyone looks those tickers up
+    benchmark_seed_service::spawn(pool.clone(), provider.clone(), rate_limiter.clone());
+
+    // Report orphaned rows and negative quantities left by restores or imports
+    integrity_service::spawn_startup_check(pool.clone());
+
     // Initialize and start job scheduler
     let mut job_scheduler = JobSchedulerService::new(
         Arc::new(pool),
         provider.clone(),
+        chain_provider,
+        etf_holdings_provider,
+        state.dividend_provider.clone(),
+        state.fundamentals_provider.clone(),
+        state.news_provider.clone(),
+        state.fx_provider.clone(),
         Arc::new(state.failure_cache.clone()),
         rate_limiter.clone(),
-        state.news_service.clone(),
-        state.llm_service.clone(),
     ).await?;
 
     job_scheduler.start().await?;
-    tracing::info!("✅ Job scheduler started");
+    tracing::info!("Job scheduler started");
 
     let app = app::create_app(state);
 
@@ -170,7 +283,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
     let listener = TcpListener::bind(&addr).await.unwrap();
-    tracing::info!("🚀 Rustfolio backend running at http://{}/", addr);
+    tracing::info!("Rustfolio backend running at http://{}/", addr);
     axum::serve(listener, app)
         .await?;
     
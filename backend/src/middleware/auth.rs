@@ -1,44 +1,164 @@
 use async_trait::async_trait;
-use axum::extract::FromRequestParts;
-use axum::http::request::Parts;
+use axum::extract::{FromRequestParts, Query};
+use axum::http::{request::Parts, HeaderMap, Uri};
+use std::collections::HashMap;
 use uuid::Uuid;
 use crate::auth;
+use crate::db::session_queries;
 use crate::errors::AppError;
+use crate::services::public_api_service;
 use crate::state::AppState;
 
 /// Axum extractor that validates the `auth_token` httpOnly cookie and
 /// provides the authenticated user's UUID to handlers.
 pub struct AuthUser(pub Uuid);
 
+/// Like [`AuthUser`], also naming the session the request was made in.
+pub struct AuthSession {
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+}
+
+/// Axum extractor for the read-only public API: validates the
+/// `Authorization: Bearer` public API token and provides its user's UUID.
+pub struct PublicApiUser(pub Uuid);
+
+/// Axum extractor for subscription feeds such as the iCal calendar. Calendar
+/// apps can send neither the session cookie nor headers, so besides both of
+/// those it accepts a public API token as the `token` query parameter.
+pub struct FeedUser(pub Uuid);
+
+/// Token of an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+}
+
+/// Value of a cookie sent with the request
+pub fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get("cookie")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .split(';')
+        .map(|s| s.trim())
+        .find_map(|s| {
+            let mut kv = s.splitn(2, '=');
+            let key = kv.next()?.trim();
+            let value = kv.next()?.trim();
+            if key == name {
+                Some(value.to_owned())
+            } else {
+                None
+            }
+        })
+}
+
+/// Value of the `token` query parameter
+pub fn query_token(uri: &Uri) -> Option<String> {
+    Query::<HashMap<String, String>>::try_from_uri(uri)
+        .ok()
+        .and_then(|Query(mut params)| params.remove("token"))
+        .map(|t| t.trim().to_owned())
+        .filter(|t| !t.is_empty())
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthSession {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = cookie_value(&parts.headers, "auth_token").ok_or(AppError::Unauthorized)?;
+
+        let (user_id, session_id) = auth::validate_jwt(&token, &state.jwt_secret)
+            .map_err(|_| AppError::Unauthorized)?;
+
+        // Revoked sessions are rejected even while their access token is unexpired
+        if !session_queries::is_active(&state.pool, session_id, user_id).await? {
+            return Err(AppError::Unauthorized);
+        }
+        tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+        Ok(AuthSession { user_id, session_id })
+    }
+}
+
 #[async_trait]
 impl FromRequestParts<AppState> for AuthUser {
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
-        let cookie_header = parts
-            .headers
-            .get("cookie")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-
-        let token = cookie_header
-            .split(';')
-            .map(|s| s.trim())
-            .find_map(|s| {
-                let mut kv = s.splitn(2, '=');
-                let name = kv.next()?.trim();
-                let value = kv.next()?.trim();
-                if name == "auth_token" {
-                    Some(value.to_owned())
-                } else {
-                    None
-                }
-            })
+        let session = AuthSession::from_request_parts(parts, state).await?;
+        Ok(AuthUser(session.user_id))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for PublicApiUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(&parts.headers).ok_or(AppError::Unauthorized)?;
+        let user_id = public_api_service::authenticate(&state.pool, token).await?;
+        tracing::Span::current().record("user_id", tracing::field::display(user_id));
+        Ok(PublicApiUser(user_id))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for FeedUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if cookie_value(&parts.headers, "auth_token").is_some() {
+            return Ok(FeedUser(AuthUser::from_request_parts(parts, state).await?.0));
+        }
+        let token = bearer_token(&parts.headers)
+            .map(str::to_owned)
+            .or_else(|| query_token(&parts.uri))
             .ok_or(AppError::Unauthorized)?;
+        let user_id = public_api_service::authenticate(&state.pool, &token).await?;
+        tracing::Span::current().record("user_id", tracing::field::display(user_id));
+        Ok(FeedUser(user_id))
+    }
+}
 
-        let user_id = auth::validate_jwt(&token, &state.jwt_secret)
-            .map_err(|_| AppError::Unauthorized)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_cookie_value() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(cookie_value(&headers, "auth_token"), None);
+        headers.insert("cookie", HeaderValue::from_static("theme=dark; auth_token=abc=; refresh_token=xyz"));
+        assert_eq!(cookie_value(&headers, "auth_token").as_deref(), Some("abc="));
+        assert_eq!(cookie_value(&headers, "refresh_token").as_deref(), Some("xyz"));
+        assert_eq!(cookie_value(&headers, "token"), None);
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+        headers.insert("authorization", HeaderValue::from_static("Basic abc"));
+        assert_eq!(bearer_token(&headers), None);
+        headers.insert("authorization", HeaderValue::from_static("Bearer rfp_abc"));
+        assert_eq!(bearer_token(&headers), Some("rfp_abc"));
+    }
 
-        Ok(AuthUser(user_id))
+    #[test]
+    fn test_query_token() {
+        let uri: Uri = "/api/portfolios/1/calendar.ics?days=90&token=rfp_abc".parse().unwrap();
+        assert_eq!(query_token(&uri).as_deref(), Some("rfp_abc"));
+        let uri: Uri = "/api/portfolios/1/calendar.ics?token=".parse().unwrap();
+        assert_eq!(query_token(&uri), None);
+        let uri: Uri = "/api/portfolios/1/calendar.ics".parse().unwrap();
+        assert_eq!(query_token(&uri), None);
     }
 }
@@ -0,0 +1,54 @@
+//! Request body size limits for upload endpoints.
+//!
+//! Axum already rejects oversized bodies with 413 Payload Too Large, but with a
+//! bare "length limit exceeded" message. [`explain_payload_too_large`] replaces
+//! that with the limit that applies and how to raise it.
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+use crate::errors::AppError;
+
+const DEFAULT_IMPORT_MAX_BODY_MB: usize = 10;
+
+/// Maximum import request body in bytes, from `IMPORT_MAX_BODY_MB`
+pub fn import_body_limit() -> usize {
+    let mb = std::env::var("IMPORT_MAX_BODY_MB")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|mb| *mb > 0)
+        .unwrap_or(DEFAULT_IMPORT_MAX_BODY_MB);
+    mb * 1024 * 1024
+}
+
+/// Rewrite a 413 response into a message stating the import limit
+pub fn explain_payload_too_large(response: Response, limit_bytes: usize) -> Response {
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE {
+        return response;
+    }
+    AppError::PayloadTooLarge(format!(
+        "Import payload exceeds the {} MB limit. Split the file into smaller imports \
+         or raise IMPORT_MAX_BODY_MB on the server",
+        limit_bytes / (1024 * 1024)
+    ))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explains_only_payload_too_large() {
+        let limit = 10 * 1024 * 1024;
+
+        let ok = explain_payload_too_large(StatusCode::OK.into_response(), limit);
+        assert_eq!(ok.status(), StatusCode::OK);
+
+        let rejected = explain_payload_too_large(
+            (StatusCode::PAYLOAD_TOO_LARGE, "length limit exceeded").into_response(),
+            limit,
+        );
+        assert_eq!(rejected.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use axum::extract::{FromRequestParts, Path};
+use axum::http::request::Parts;
+use uuid::Uuid;
+
+use crate::db::{auth_queries, portfolio_member_queries};
+use crate::errors::AppError;
+use crate::middleware::auth::AuthUser;
+use crate::models::Role;
+use crate::state::AppState;
+
+/// The minimum portfolio role a handler requires.
+pub trait RequiredRole: Send + Sync + 'static {
+    const ROLE: Role;
+}
+
+/// Read access: any member of the portfolio
+pub struct CanView;
+/// Write access to accounts, positions, thresholds and snapshots
+pub struct CanEdit;
+/// Renaming, archiving and sharing the portfolio
+pub struct CanAdmin;
+
+impl RequiredRole for CanView {
+    const ROLE: Role = Role::Viewer;
+}
+
+impl RequiredRole for CanEdit {
+    const ROLE: Role = Role::Editor;
+}
+
+impl RequiredRole for CanAdmin {
+    const ROLE: Role = Role::Admin;
+}
+
+/// Axum extractor that authenticates the user and checks their role on the
+/// portfolio named by the path: `:portfolio_id`, `:id` on the portfolio routes,
+/// or the portfolio owning `:account_id` or `:transaction_id`.
+///
+/// Rejects with 404 when the portfolio is not shared with the user, so its
+/// existence is not revealed, and with 403 when their role is below `R`.
+pub struct PortfolioAccess<R: RequiredRole> {
+    pub user_id: Uuid,
+    pub portfolio_id: Uuid,
+    pub owner_id: Uuid,
+    pub role: Role,
+    _required: PhantomData<R>,
+}
+
+impl<R: RequiredRole> PortfolioAccess<R> {
+    pub fn is_owner(&self) -> bool {
+        self.user_id == self.owner_id
+    }
+}
+
+/// What the path identifies
+#[derive(Debug, PartialEq)]
+enum PathTarget {
+    Portfolio(Uuid),
+    Account(Uuid),
+    Transaction(Uuid),
+}
+
+impl PathTarget {
+    fn not_found(&self) -> AppError {
+        match self {
+            PathTarget::Portfolio(id) => AppError::NotFound(format!("Portfolio {} not found", id)),
+            PathTarget::Account(id) => AppError::NotFound(format!("Account {} not found", id)),
+            PathTarget::Transaction(id) => AppError::NotFound(format!("Transaction {} not found", id)),
+        }
+    }
+}
+
+fn path_target(params: &HashMap<String, String>) -> Result<PathTarget, AppError> {
+    let parse = |value: &str, what: &str| {
+        Uuid::parse_str(value).map_err(|_| AppError::Validation(format!("Invalid {} id: {}", what, value)))
+    };
+    if let Some(value) = params.get("portfolio_id").or_else(|| params.get("id")) {
+        return parse(value, "portfolio").map(PathTarget::Portfolio);
+    }
+    if let Some(value) = params.get("account_id") {
+        return parse(value, "account").map(PathTarget::Account);
+    }
+    if let Some(value) = params.get("transaction_id") {
+        return parse(value, "transaction").map(PathTarget::Transaction);
+    }
+    Err(AppError::Validation("The path does not identify a portfolio".into()))
+}
+
+/// Ensure `role` is at least `required`
+pub fn require_role(role: Role, required: Role) -> Result<(), AppError> {
+    if role < required {
+        return Err(AppError::Forbidden(format!(
+            "This action requires the {} role on the portfolio; your role is {}",
+            required, role
+        )));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl<R: RequiredRole> FromRequestParts<AppState> for PortfolioAccess<R> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let AuthUser(user_id) = AuthUser::from_request_parts(parts, state).await?;
+        let Path(params) = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| AppError::Validation(e.body_text()))?;
+
+        let target = path_target(&params)?;
+        let portfolio_id = match target {
+            PathTarget::Portfolio(id) => Some(id),
+            PathTarget::Account(id) => portfolio_member_queries::portfolio_for_account(&state.pool, id).await?,
+            PathTarget::Transaction(id) => portfolio_member_queries::portfolio_for_transaction(&state.pool, id).await?,
+        }
+        .ok_or_else(|| target.not_found())?;
+
+        let (role, owner_id) = portfolio_member_queries::role_for(&state.pool, portfolio_id, user_id)
+            .await?
+            .ok_or_else(|| target.not_found())?;
+        require_role(role, R::ROLE)?;
+
+        Ok(PortfolioAccess { user_id, portfolio_id, owner_id, role, _required: PhantomData })
+    }
+}
+
+/// Axum extractor for instance-wide admin endpoints: the authenticated user
+/// must have the admin role on their account.
+pub struct AdminUser(pub Uuid);
+
+#[async_trait]
+impl FromRequestParts<AppState> for AdminUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let AuthUser(user_id) = AuthUser::from_request_parts(parts, state).await?;
+        let user = auth_queries::get_user(&state.pool, user_id).await?;
+        if user.role != Role::Admin {
+            return Err(AppError::Forbidden("This action requires an administrator account".into()));
+        }
+        Ok(AdminUser(user_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_path_target_prefers_the_portfolio() {
+        let portfolio = Uuid::new_v4();
+        let account = Uuid::new_v4();
+        assert_eq!(
+            path_target(&params(&[("portfolio_id", &portfolio.to_string())])).unwrap(),
+            PathTarget::Portfolio(portfolio)
+        );
+        assert_eq!(
+            path_target(&params(&[("id", &portfolio.to_string())])).unwrap(),
+            PathTarget::Portfolio(portfolio)
+        );
+        assert_eq!(
+            path_target(&params(&[("account_id", &account.to_string()), ("ticker", "AAPL")])).unwrap(),
+            PathTarget::Account(account)
+        );
+        assert_eq!(
+            path_target(&params(&[("transaction_id", &account.to_string())])).unwrap(),
+            PathTarget::Transaction(account)
+        );
+        assert!(matches!(path_target(&params(&[("portfolio_id", "nope")])), Err(AppError::Validation(_))));
+        assert!(matches!(path_target(&params(&[("ticker", "AAPL")])), Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn test_require_role() {
+        assert!(require_role(Role::Admin, Role::Editor).is_ok());
+        assert!(require_role(Role::Editor, Role::Editor).is_ok());
+        assert!(require_role(Role::Viewer, Role::Viewer).is_ok());
+        assert!(matches!(require_role(Role::Viewer, Role::Editor), Err(AppError::Forbidden(_))));
+        assert!(matches!(require_role(Role::Editor, Role::Admin), Err(AppError::Forbidden(_))));
+    }
+}
@@ -0,0 +1,167 @@
+//! Per-request tracing span with a request ID.
+//!
+//! Every request runs inside a `request` span carrying `request_id` (taken from
+//! the `x-request-id` header, or generated), `method` and the matched route.
+//! `user_id` is filled in by the [`AuthUser`](super::auth::AuthUser) extractor,
+//! `portfolio_id` and `ticker` from the route's path parameters, and `status` and
+//! `duration_ms` when the response is sent. All log lines emitted while handling
+//! the request carry these fields, and the request ID is echoed back in the
+//! response so slow calls can be traced from the client.
+
+use std::time::{Duration, Instant};
+
+use axum::extract::{MatchedPath, RawPathParams, Request};
+use axum::http::HeaderName;
+use axum::response::Response;
+use axum::middleware::Next;
+use tower_http::request_id::RequestId;
+use tracing::{field, info, info_span, warn, Span};
+use uuid::Uuid;
+
+use crate::services::latency_monitor_service;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Target of the `request` span, for per-layer filters
+pub const REQUEST_SPAN_TARGET: &str = module_path!();
+
+const DEFAULT_SLOW_REQUEST_MS: u64 = 1000;
+
+/// Requests slower than this are logged at warn level, from `SLOW_REQUEST_MS`
+pub fn slow_request_threshold() -> Duration {
+    let ms = std::env::var("SLOW_REQUEST_MS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_SLOW_REQUEST_MS);
+    Duration::from_millis(ms)
+}
+
+/// Build the `request` span for an incoming request
+pub fn make_request_span(request: &Request) -> Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or_default();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str())
+        .unwrap_or_else(|| request.uri().path());
+
+    info_span!(
+        "request",
+        request_id,
+        method = %request.method(),
+        route,
+        user_id = field::Empty,
+        portfolio_id = field::Empty,
+        ticker = field::Empty,
+        status = field::Empty,
+        duration_ms = field::Empty,
+    )
+}
+
+/// Record status and duration on the request span and log completion
+pub fn on_response(response: &Response, latency: Duration, span: &Span, slow_threshold: Duration) {
+    let status = response.status().as_u16();
+    let duration_ms = latency.as_millis() as u64;
+    span.record("status", status);
+    span.record("duration_ms", duration_ms);
+
+    if latency >= slow_threshold {
+        warn!(status, duration_ms, "Slow request");
+    } else {
+        info!(status, duration_ms, "Request completed");
+    }
+}
+
+/// Portfolio and ticker named by a route's path parameters. `:id` only means a
+/// portfolio under `/api/portfolios`; elsewhere it is some other resource.
+pub fn path_context<'a>(route: &str, params: &[(&str, &'a str)]) -> (Option<Uuid>, Option<&'a str>) {
+    let mut portfolio_id = None;
+    let mut ticker = None;
+    for (name, value) in params {
+        match *name {
+            "portfolio_id" => portfolio_id = Uuid::parse_str(value).ok(),
+            "id" if route.starts_with("/api/portfolios/") => portfolio_id = Uuid::parse_str(value).ok(),
+            "ticker" | "symbol" => ticker = Some(*value),
+            _ => {}
+        }
+    }
+    (portfolio_id, ticker)
+}
+
+/// Middleware recording `portfolio_id` and `ticker` on the request span
+pub async fn record_path_context(
+    route: Option<MatchedPath>,
+    params: Option<RawPathParams>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (Some(route), Some(params)) = (route, params) else {
+        return next.run(request).await;
+    };
+    let params: Vec<(&str, &str)> = params.iter().collect();
+    let (portfolio_id, ticker) = path_context(route.as_str(), &params);
+
+    let span = Span::current();
+    if let Some(portfolio_id) = portfolio_id {
+        span.record("portfolio_id", field::display(portfolio_id));
+    }
+    if let Some(ticker) = ticker {
+        span.record("ticker", ticker);
+    }
+
+    next.run(request).await
+}
+
+/// Middleware timing each matched route for the latency budget monitor
+pub async fn record_latency(route: Option<MatchedPath>, request: Request, next: Next) -> Response {
+    let Some(route) = route else {
+        return next.run(request).await;
+    };
+    let method = request.method().clone();
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .map(str::to_string);
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    latency_monitor_service::record_endpoint(
+        method.as_str(),
+        route.as_str(),
+        response.status().as_u16(),
+        started.elapsed(),
+        request_id,
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PORTFOLIO: &str = "6f1c2a4e-8d7b-4c3a-9e5f-1a2b3c4d5e6f";
+
+    #[test]
+    fn test_path_context_reads_portfolio_and_ticker() {
+        let (portfolio_id, ticker) = path_context(
+            "/api/risk/portfolios/:portfolio_id/positions/:ticker",
+            &[("portfolio_id", PORTFOLIO), ("ticker", "AAPL")],
+        );
+        assert_eq!(portfolio_id, Uuid::parse_str(PORTFOLIO).ok());
+        assert_eq!(ticker, Some("AAPL"));
+    }
+
+    #[test]
+    fn test_path_context_id_is_portfolio_only_under_portfolios() {
+        let (portfolio_id, _) = path_context("/api/portfolios/:id", &[("id", PORTFOLIO)]);
+        assert!(portfolio_id.is_some());
+
+        let (portfolio_id, _) = path_context("/api/alerts/:id", &[("id", PORTFOLIO)]);
+        assert!(portfolio_id.is_none());
+    }
+}
@@ -11,6 +11,115 @@ pub struct Account {
     pub client_id: Option<String>,
     pub client_name: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Dividends are reinvested automatically; DRIP transactions are generated for them
+    pub drip_enabled: bool,
+    /// The broker supports fractional shares; otherwise trades are rounded to whole shares
+    pub fractional_shares: bool,
+    /// How the account is taxed; `None` means inferred from the nickname
+    pub tax_treatment: Option<String>,
+    /// How sales are matched to tax lots: "fifo", "average_cost" or "specific_lot"
+    pub cost_basis_method: String,
+}
+
+/// How income and gains inside an account are taxed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountTaxTreatment {
+    /// Non-registered / brokerage: income taxed yearly, gains when realized
+    Taxable,
+    /// RRSP, RRIF, LIRA, IRA, 401(k): taxed on withdrawal
+    TaxDeferred,
+    /// TFSA, FHSA, Roth IRA: never taxed
+    TaxFree,
+}
+
+impl AccountTaxTreatment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountTaxTreatment::Taxable => "taxable",
+            AccountTaxTreatment::TaxDeferred => "tax_deferred",
+            AccountTaxTreatment::TaxFree => "tax_free",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "taxable" => Some(AccountTaxTreatment::Taxable),
+            "tax_deferred" => Some(AccountTaxTreatment::TaxDeferred),
+            "tax_free" => Some(AccountTaxTreatment::TaxFree),
+            _ => None,
+        }
+    }
+
+    /// Guess the treatment from an account nickname such as "RRSP - Self Directed"
+    pub fn infer_from_nickname(nickname: &str) -> Self {
+        let upper = nickname.to_uppercase();
+        let words: Vec<&str> = upper.split(|c: char| !c.is_ascii_alphanumeric()).collect();
+        let has = |w: &str| words.contains(&w);
+        if has("TFSA") || has("FHSA") || has("ROTH") {
+            AccountTaxTreatment::TaxFree
+        } else if ["RRSP", "RSP", "RRIF", "RIF", "LIRA", "LIF", "RESP", "IRA", "401K", "403B", "SEP"]
+            .iter()
+            .any(|w| has(w))
+        {
+            AccountTaxTreatment::TaxDeferred
+        } else {
+            AccountTaxTreatment::Taxable
+        }
+    }
+}
+
+/// How sales are matched to the lots they close
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostBasisMethod {
+    /// Oldest lots are sold first
+    #[default]
+    Fifo,
+    /// Every share carries the pooled adjusted cost base (Canada)
+    AverageCost,
+    /// The lots sold are designated per sale; undesignated shares fall back to FIFO
+    SpecificLot,
+}
+
+impl CostBasisMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostBasisMethod::Fifo => "fifo",
+            CostBasisMethod::AverageCost => "average_cost",
+            CostBasisMethod::SpecificLot => "specific_lot",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "fifo" => Some(CostBasisMethod::Fifo),
+            "average_cost" => Some(CostBasisMethod::AverageCost),
+            "specific_lot" => Some(CostBasisMethod::SpecificLot),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCostBasisMethodSetting {
+    pub cost_basis_method: CostBasisMethod,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTaxTreatmentSetting {
+    /// `null` to go back to inferring from the nickname
+    pub tax_treatment: Option<AccountTaxTreatment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateDripSetting {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateFractionalShareSetting {
+    pub enabled: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -38,6 +147,46 @@ impl Account {
             client_id,
             client_name,
             created_at: chrono::Utc::now(),
+            drip_enabled: false,
+            fractional_shares: false,
+            tax_treatment: None,
+            cost_basis_method: CostBasisMethod::Fifo.as_str().to_string(),
         }
     }
+
+    /// The configured lot-matching method, FIFO if unrecognised
+    pub fn cost_basis_method(&self) -> CostBasisMethod {
+        CostBasisMethod::from_str(&self.cost_basis_method).unwrap_or_default()
+    }
+
+    /// The explicit tax treatment, else the one inferred from the nickname
+    pub fn effective_tax_treatment(&self) -> AccountTaxTreatment {
+        self.tax_treatment
+            .as_deref()
+            .and_then(AccountTaxTreatment::from_str)
+            .unwrap_or_else(|| AccountTaxTreatment::infer_from_nickname(&self.account_nickname))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_tax_treatment_from_nickname() {
+        assert_eq!(AccountTaxTreatment::infer_from_nickname("TFSA - Questrade"), AccountTaxTreatment::TaxFree);
+        assert_eq!(AccountTaxTreatment::infer_from_nickname("Spousal RRSP"), AccountTaxTreatment::TaxDeferred);
+        assert_eq!(AccountTaxTreatment::infer_from_nickname("Roth IRA"), AccountTaxTreatment::TaxFree);
+        assert_eq!(AccountTaxTreatment::infer_from_nickname("Investment Account"), AccountTaxTreatment::Taxable);
+        // Substrings of other words do not count
+        assert_eq!(AccountTaxTreatment::infer_from_nickname("Irate savings"), AccountTaxTreatment::Taxable);
+    }
+
+    #[test]
+    fn test_explicit_tax_treatment_overrides_nickname() {
+        let mut account = Account::new(uuid::Uuid::new_v4(), "1".into(), "TFSA".into(), None, None);
+        assert_eq!(account.effective_tax_treatment(), AccountTaxTreatment::TaxFree);
+        account.tax_treatment = Some("taxable".into());
+        assert_eq!(account.effective_tax_treatment(), AccountTaxTreatment::Taxable);
+    }
 }
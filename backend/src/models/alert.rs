@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::models::Role;
+
 // ==============================================================================
 // Alert Rule Models
 // ==============================================================================
@@ -85,6 +87,21 @@ pub enum AlertType {
     Divergence {
         divergence_type: DivergenceType,
     },
+    /// Any owned position moving more than `percentage` on the day, or gapping more
+    /// than `gap_percentage` at the open. Covers every portfolio of the user unless
+    /// the rule has a portfolio_id.
+    #[serde(rename = "holding_move")]
+    HoldingMove {
+        percentage: f64,
+        gap_percentage: Option<f64>,
+    },
+    /// Reminder `days_before` days ahead of an owned ticker going ex-dividend,
+    /// once per ticker and ex-date. Covers every portfolio of the user unless the
+    /// rule has a portfolio_id.
+    #[serde(rename = "ex_dividend")]
+    ExDividend {
+        days_before: i64,
+    },
 }
 
 impl AlertType {
@@ -97,6 +114,8 @@ impl AlertType {
             AlertType::RiskThreshold { .. } => "risk_threshold".to_string(),
             AlertType::SentimentChange { .. } => "sentiment_change".to_string(),
             AlertType::Divergence { .. } => "divergence".to_string(),
+            AlertType::HoldingMove { .. } => "holding_move".to_string(),
+            AlertType::ExDividend { .. } => "ex_dividend".to_string(),
         }
     }
 }
@@ -428,6 +447,10 @@ pub struct User {
     pub name: Option<String>,
     #[serde(skip_serializing)]
     pub password_hash: Option<String>,
+    /// Instance-wide role; admins may use the admin endpoints
+    pub role: Role,
+    /// Set while an administrator has disabled the account
+    pub disabled_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
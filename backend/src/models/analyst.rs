@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Count of analysts at each rating
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RatingDistribution {
+    pub strong_buy: i32,
+    pub buy: i32,
+    pub hold: i32,
+    pub sell: i32,
+    pub strong_sell: i32,
+}
+
+impl RatingDistribution {
+    pub fn total(&self) -> i32 {
+        self.strong_buy + self.buy + self.hold + self.sell + self.strong_sell
+    }
+}
+
+/// Consensus rating bucket
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsensusRating {
+    StrongBuy,
+    Buy,
+    Hold,
+    Sell,
+    StrongSell,
+}
+
+/// Analyst ratings and price targets for a ticker, with the implied move from
+/// the current price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalystConsensus {
+    pub ticker: String,
+    pub ratings: RatingDistribution,
+    /// Mean recommendation on a 1 (strong buy) to 5 (strong sell) scale
+    pub recommendation_mean: Option<f64>,
+    pub consensus: Option<ConsensusRating>,
+    pub analyst_count: Option<i64>,
+    pub target_mean: Option<f64>,
+    pub target_high: Option<f64>,
+    pub target_low: Option<f64>,
+    /// Latest close used for the upside calculation
+    pub current_price: Option<f64>,
+    /// % move from the current price to the mean target; negative = downside
+    pub upside_pct: Option<f64>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AnalystConsensusQuery {
+    /// Bypass the cached consensus (default: false)
+    pub force: Option<bool>,
+}
@@ -22,6 +22,8 @@ pub struct AnalyticsMeta {
     pub points: usize,
     pub start: Option<NaiveDate>,
     pub end: Option<NaiveDate>,
+    /// Currency values and allocations are reported in
+    pub base_currency: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
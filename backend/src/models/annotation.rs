@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::{FiftyTwoWeekRange, LatestAccountHolding};
+
+/// User tags, notes, price levels and thesis attached to a position (account + ticker).
+///
+/// Stored separately from holdings snapshots so they persist across imports.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PositionAnnotation {
+    pub id: uuid::Uuid,
+    pub account_id: uuid::Uuid,
+    pub ticker: String,
+    pub tags: Vec<String>,
+    /// Markdown notes
+    pub notes: Option<String>,
+    /// Price at which the position is expected to be sold for a gain
+    pub target_price: Option<f64>,
+    /// Price at which the position should be cut
+    pub stop_loss: Option<f64>,
+    /// Why the position is held
+    pub thesis: Option<String>,
+    /// Percent drop from the high-water mark that triggers the trailing stop
+    pub trailing_stop_pct: Option<f64>,
+    /// Highest close since entry, kept by the monitoring job
+    pub trailing_high: Option<f64>,
+    pub target_alerted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub stop_alerted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub trailing_alerted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Request body for setting tags/notes on a position or transaction.
+/// Omitted fields are left unchanged; an empty `tags` list clears the tags.
+///
+/// `target_price`, `stop_loss`, `trailing_stop_pct` and `thesis` only apply to positions.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateAnnotation {
+    pub tags: Option<Vec<String>>,
+    pub notes: Option<String>,
+    pub target_price: Option<f64>,
+    pub stop_loss: Option<f64>,
+    pub trailing_stop_pct: Option<f64>,
+    pub thesis: Option<String>,
+    /// Remove the target price, stop-loss and trailing stop
+    #[serde(default)]
+    pub clear_levels: bool,
+}
+
+/// Query parameters for list endpoints that support tag filtering
+#[derive(Debug, Default, Deserialize)]
+pub struct TagFilterQuery {
+    /// Only return rows carrying this tag (case-insensitive)
+    pub tag: Option<String>,
+}
+
+/// A latest holding together with its position annotation
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnotatedHolding {
+    #[serde(flatten)]
+    pub holding: LatestAccountHolding,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    pub target_price: Option<f64>,
+    pub stop_loss: Option<f64>,
+    pub trailing_stop_pct: Option<f64>,
+    /// Price the trailing stop currently sits at
+    pub trailing_stop: Option<f64>,
+    pub thesis: Option<String>,
+    /// 52-week range of the ticker from stored prices
+    pub fifty_two_week: Option<FiftyTwoWeekRange>,
+}
+
+/// A position whose price crossed its target, stop-loss or trailing stop
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionLevelCrossing {
+    pub annotation_id: uuid::Uuid,
+    pub ticker: String,
+    /// "target", "stop_loss" or "trailing_stop"
+    pub level_type: &'static str,
+    pub level: f64,
+    pub price: f64,
+    /// High-water mark the trailing stop hangs from
+    pub peak: Option<f64>,
+}
+
+/// A position with a target, stop-loss or trailing stop set, with its owner, for the monitoring job
+#[derive(Debug, Clone, FromRow)]
+pub struct MonitoredPosition {
+    #[sqlx(flatten)]
+    pub annotation: PositionAnnotation,
+    pub user_id: uuid::Uuid,
+    pub portfolio_id: uuid::Uuid,
+    pub account_nickname: String,
+}
+
+impl PositionAnnotation {
+    /// Price the trailing stop sits at, once a high-water mark is known
+    pub fn trailing_stop(&self) -> Option<f64> {
+        Some(self.trailing_high? * (1.0 - self.trailing_stop_pct? / 100.0))
+    }
+}
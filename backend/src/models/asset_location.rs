@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::AccountTaxTreatment;
+
+/// How a holding pays its return, which decides how much tax it costs in a taxable account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncomeProfile {
+    /// Bonds and fixed income: interest taxed as ordinary income
+    Interest,
+    /// REIT distributions: mostly taxed as ordinary income
+    ReitDistributions,
+    /// Dividend payers (yield of 2% or more)
+    Dividends,
+    /// Little income; return comes mostly from deferred capital gains
+    Growth,
+}
+
+/// An account and the tax treatment used for it
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountLocationSummary {
+    pub account_id: Uuid,
+    pub account_nickname: String,
+    pub tax_treatment: AccountTaxTreatment,
+    /// The treatment was guessed from the nickname rather than set on the account
+    pub inferred: bool,
+    pub market_value: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocationAmount {
+    pub tax_treatment: AccountTaxTreatment,
+    pub market_value: f64,
+}
+
+/// Where a holding sits today and where it would sit with the least tax drag
+#[derive(Debug, Clone, Serialize)]
+pub struct HoldingLocation {
+    pub ticker: String,
+    pub holding_name: Option<String>,
+    pub income_profile: IncomeProfile,
+    /// Trailing 12-month income yield, in percent (assumed for bonds/REITs without recorded income)
+    pub yield_pct: f64,
+    /// Yearly tax cost of holding it in a taxable account, in percent of its value
+    pub tax_drag_pct: f64,
+    pub market_value: f64,
+    pub current: Vec<LocationAmount>,
+    pub suggested: Vec<LocationAmount>,
+}
+
+/// Value of a holding to move between account types
+#[derive(Debug, Clone, Serialize)]
+pub struct LocationMove {
+    pub ticker: String,
+    pub from: AccountTaxTreatment,
+    pub to: AccountTaxTreatment,
+    pub market_value: f64,
+    /// Yearly tax saved by the move (negative when moving into a taxable account)
+    pub estimated_annual_savings: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetLocationAnalysis {
+    pub portfolio_id: Uuid,
+    /// Marginal tax rate assumed for the drag estimates, in percent
+    pub marginal_tax_rate: f64,
+    pub accounts: Vec<AccountLocationSummary>,
+    /// Highest tax drag first
+    pub holdings: Vec<HoldingLocation>,
+    pub moves: Vec<LocationMove>,
+    pub current_annual_tax_drag: f64,
+    pub optimized_annual_tax_drag: f64,
+    pub estimated_annual_savings: f64,
+    pub notes: Vec<String>,
+}
+
+/// Query parameters for the asset location endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct AssetLocationQuery {
+    /// Marginal tax rate in percent (default: 40)
+    pub marginal_tax_rate: Option<f64>,
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Kind of change recorded in the audit log
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+/// One recorded change, with the user who made it
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    /// `None` once the user's account has been deleted
+    pub user_id: Option<Uuid>,
+    pub user_email: Option<String>,
+    pub portfolio_id: Option<Uuid>,
+    /// What was changed, e.g. "risk_thresholds" or "portfolio"
+    pub entity_type: String,
+    pub entity_id: String,
+    pub action: AuditAction,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    /// Changed fields only: `{"field": {"before": ..., "after": ...}}`
+    pub changes: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query parameters for the audit log endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditLogQuery {
+    /// Only changes to this portfolio
+    pub portfolio_id: Option<Uuid>,
+    /// Only changes on or after this date
+    pub from: Option<NaiveDate>,
+    /// Only changes on or before this date
+    pub to: Option<NaiveDate>,
+    /// Only changes to this kind of entity
+    pub entity_type: Option<String>,
+    /// Maximum entries, newest first (default 100, at most 1000)
+    pub limit: Option<i64>,
+}
+
+/// A change to record, built at the call site:
+/// `NewAuditEntry::new(user_id, AuditAction::Update, "portfolio", id).portfolio(id).before(&old).after(&new)`
+#[derive(Debug, Clone)]
+pub struct NewAuditEntry {
+    pub user_id: Uuid,
+    pub portfolio_id: Option<Uuid>,
+    pub entity_type: &'static str,
+    pub entity_id: String,
+    pub action: AuditAction,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+impl NewAuditEntry {
+    pub fn new(user_id: Uuid, action: AuditAction, entity_type: &'static str, entity_id: impl ToString) -> Self {
+        Self {
+            user_id,
+            portfolio_id: None,
+            entity_type,
+            entity_id: entity_id.to_string(),
+            action,
+            before: None,
+            after: None,
+        }
+    }
+
+    /// An entity was created, or updated from `previous` when one existed
+    pub fn saved<T: Serialize>(
+        user_id: Uuid,
+        entity_type: &'static str,
+        entity_id: impl ToString,
+        previous: Option<&T>,
+    ) -> Self {
+        match previous {
+            Some(previous) => Self::new(user_id, AuditAction::Update, entity_type, entity_id).before(previous),
+            None => Self::new(user_id, AuditAction::Create, entity_type, entity_id),
+        }
+    }
+
+    /// An entity was deleted; `previous` is what it held, when known
+    pub fn deleted<T: Serialize>(
+        user_id: Uuid,
+        entity_type: &'static str,
+        entity_id: impl ToString,
+        previous: Option<&T>,
+    ) -> Self {
+        let entry = Self::new(user_id, AuditAction::Delete, entity_type, entity_id);
+        match previous {
+            Some(previous) => entry.before(previous),
+            None => entry,
+        }
+    }
+
+    pub fn portfolio(mut self, portfolio_id: Uuid) -> Self {
+        self.portfolio_id = Some(portfolio_id);
+        self
+    }
+
+    pub fn before(mut self, value: &impl Serialize) -> Self {
+        self.before = serde_json::to_value(value).ok();
+        self
+    }
+
+    pub fn after(mut self, value: &impl Serialize) -> Self {
+        self.after = serde_json::to_value(value).ok();
+        self
+    }
+}
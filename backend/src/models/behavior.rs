@@ -0,0 +1,104 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Query parameters for the behavioral insights endpoint
+#[derive(Debug, Deserialize)]
+pub struct BehaviorQuery {
+    /// Calendar days after a trade its timing is judged over (default: 30)
+    #[serde(default = "default_forward_days")]
+    pub forward_days: i64,
+}
+
+fn default_forward_days() -> i64 {
+    30
+}
+
+/// Trading activity of one calendar year relative to the portfolio's size
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct YearTurnover {
+    pub year: i32,
+    pub purchases: f64,
+    pub sales: f64,
+    /// Mean recorded portfolio value during the year
+    pub average_value: Option<f64>,
+    /// min(purchases, sales) / average value, in percent
+    pub turnover_pct: Option<f64>,
+}
+
+/// How long positions are held, weighted by cost basis
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HoldingPeriodStats {
+    /// Days from purchase to sale of closed lots
+    pub average_realized_days: Option<f64>,
+    /// Days open lots have been held so far
+    pub average_open_days: Option<f64>,
+}
+
+/// Outcome of closed trades, one per sale
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WinRateStats {
+    pub closed_trades: usize,
+    pub winning_trades: usize,
+    /// Share of sales closed at a gain, in percent
+    pub win_rate: Option<f64>,
+    pub average_gain: Option<f64>,
+    /// Average loss of losing sales, as a positive amount
+    pub average_loss: Option<f64>,
+    /// Cost-weighted holding days of winning and losing lots; losers held much
+    /// longer than winners suggests cutting gains early and riding losses
+    pub average_winner_days: Option<f64>,
+    pub average_loser_days: Option<f64>,
+}
+
+/// Where a trade's price sat in its trailing range and what the stock did next
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TradeTiming {
+    pub transaction_id: Uuid,
+    pub ticker: String,
+    /// "BUY" or "SELL"
+    pub side: String,
+    pub date: NaiveDate,
+    pub price: f64,
+    /// Position of the price within the trailing range of closes: 0 at the low, 100 at the high
+    pub range_percentile: Option<f64>,
+    /// Percent change from the trade price to the close `forward_days` later;
+    /// `None` until that much history exists
+    pub forward_return: Option<f64>,
+    /// A buy followed by a rise, or a sell followed by a fall
+    pub well_timed: Option<bool>,
+}
+
+/// Timing of one side of the ledger
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TimingStats {
+    pub trades: usize,
+    /// Trades with a forward return to judge by
+    pub judged: usize,
+    pub average_range_percentile: Option<f64>,
+    pub average_forward_return: Option<f64>,
+    /// Share of judged trades that were well timed, in percent
+    pub well_timed_pct: Option<f64>,
+    /// Buys in the top fifth of their range followed by a fall ("bought high"), or
+    /// sells in the bottom fifth followed by a rise ("sold low")
+    pub mistimed: usize,
+}
+
+/// Turnover, holding period, win rate and timing of a portfolio's trades
+#[derive(Debug, Clone, Serialize)]
+pub struct BehaviorInsights {
+    pub portfolio_id: Uuid,
+    pub forward_days: i64,
+    pub annual_turnover: Vec<YearTurnover>,
+    /// Mean turnover of the years with a recorded portfolio value
+    pub average_turnover_pct: Option<f64>,
+    pub holding_period: HoldingPeriodStats,
+    pub win_rate: WinRateStats,
+    pub buy_timing: TimingStats,
+    pub sell_timing: TimingStats,
+    /// Share of all judged buys and sells that were well timed, 0–100
+    pub timing_score: Option<f64>,
+    pub trades: Vec<TradeTiming>,
+    /// Plain-language findings in the user's locale
+    pub insights: Vec<String>,
+}
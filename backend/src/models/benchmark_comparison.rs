@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// One holding's period return compared with its sector index ETF and the portfolio benchmark
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HoldingComparison {
+    pub ticker: String,
+    pub industry: Option<String>,
+    /// Sector ETF the holding is compared against, when its industry maps to one
+    pub sector_etf: Option<String>,
+    /// Share of portfolio market value (0-1)
+    pub weight: f64,
+    /// Returns over the period, in percent
+    pub holding_return: f64,
+    pub sector_return: Option<f64>,
+    pub benchmark_return: Option<f64>,
+    pub excess_vs_sector: Option<f64>,
+    pub excess_vs_benchmark: Option<f64>,
+    /// weight × holding return, in percentage points of portfolio return
+    pub contribution: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkComparison {
+    pub portfolio_id: Uuid,
+    pub days: i64,
+    pub benchmark: String,
+    pub benchmark_return: Option<f64>,
+    /// Weighted return of the holdings with price history, in percent
+    pub portfolio_return: f64,
+    pub holdings: Vec<HoldingComparison>,
+    /// Largest positive contributions to portfolio return
+    pub leaders: Vec<HoldingComparison>,
+    /// Largest negative contributions to portfolio return
+    pub laggards: Vec<HoldingComparison>,
+    /// Tickers without enough price history in the period
+    pub missing_prices: Vec<String>,
+}
+
+/// Query parameters for the benchmark comparison endpoint
+#[derive(Debug, Deserialize)]
+pub struct BenchmarkComparisonQuery {
+    /// Number of trading days to compare over (default: 90)
+    #[serde(default = "default_days")]
+    pub days: i64,
+    /// Portfolio benchmark ticker (default: "SPY")
+    #[serde(default = "default_benchmark")]
+    pub benchmark: String,
+}
+
+fn default_days() -> i64 {
+    90
+}
+
+fn default_benchmark() -> String {
+    "SPY".to_string()
+}
@@ -0,0 +1,71 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// How often a scheduled rebalance recurs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum RebalanceFrequency {
+    Monthly,
+    Quarterly,
+    Semiannual,
+    Annual,
+}
+
+impl RebalanceFrequency {
+    pub fn months(self) -> u32 {
+        match self {
+            RebalanceFrequency::Monthly => 1,
+            RebalanceFrequency::Quarterly => 3,
+            RebalanceFrequency::Semiannual => 6,
+            RebalanceFrequency::Annual => 12,
+        }
+    }
+}
+
+/// A portfolio's recurring rebalance reminder
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RebalanceSchedule {
+    pub frequency: RebalanceFrequency,
+    /// First scheduled rebalance; later ones follow every `frequency`
+    pub start_date: NaiveDate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarEventKind {
+    Earnings,
+    ExDividend,
+    Rebalance,
+    GoalMilestone,
+}
+
+impl CalendarEventKind {
+    /// iCal CATEGORIES value
+    pub fn category(self) -> &'static str {
+        match self {
+            CalendarEventKind::Earnings => "Earnings",
+            CalendarEventKind::ExDividend => "Ex-dividend",
+            CalendarEventKind::Rebalance => "Rebalance",
+            CalendarEventKind::GoalMilestone => "Goal",
+        }
+    }
+}
+
+/// One all-day event of a portfolio's financial calendar
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalendarEvent {
+    pub kind: CalendarEventKind,
+    pub date: NaiveDate,
+    /// Stable across refreshes, so calendar apps update an event rather than duplicate it
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CalendarFeedQuery {
+    /// Days ahead included (default 180, at most 730)
+    pub days: Option<i64>,
+}
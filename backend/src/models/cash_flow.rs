@@ -8,6 +8,8 @@ use sqlx::FromRow;
 pub enum FlowType {
     Deposit,
     Withdrawal,
+    /// Advisor or management fee; not counted as a withdrawal
+    Fee,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -19,6 +21,8 @@ pub struct CashFlow {
     pub flow_date: NaiveDate,
     pub description: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Set for fee flows generated from an account fee schedule
+    pub fee_schedule_id: Option<uuid::Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -40,11 +44,13 @@ impl CashFlow {
             flow_type: match data.flow_type {
                 FlowType::Deposit => "DEPOSIT".to_string(),
                 FlowType::Withdrawal => "WITHDRAWAL".to_string(),
+                FlowType::Fee => "FEE".to_string(),
             },
             amount: data.amount,
             flow_date: data.flow_date,
             description: data.description,
             created_at: chrono::Utc::now(),
+            fee_schedule_id: None,
         }
     }
 }
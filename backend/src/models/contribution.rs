@@ -0,0 +1,36 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// One position's contribution to portfolio return over a period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PositionContribution {
+    pub ticker: String,
+    /// Average daily weight over the period (0-1)
+    pub average_weight: f64,
+    /// Position's own price return over the period, in percent
+    pub position_return: f64,
+    /// Linked daily weight × return, in percentage points of portfolio return
+    pub contribution: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioContributions {
+    pub portfolio_id: Uuid,
+    pub period: String,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// Chained daily portfolio return, in percent; equals the sum of contributions
+    pub portfolio_return: f64,
+    /// Sorted by contribution, largest first
+    pub positions: Vec<PositionContribution>,
+    /// Tickers without price history in the period
+    pub missing_prices: Vec<String>,
+}
+
+/// Query parameters for the contributions endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct ContributionQuery {
+    /// Lookback such as "30d", "6m", or "1y" (default: "90d")
+    pub period: Option<String>,
+}
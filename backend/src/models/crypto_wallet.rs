@@ -0,0 +1,73 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Asset category of holdings imported from wallets
+pub const CRYPTO_ASSET_CATEGORY: &str = "CRYPTO";
+
+/// Blockchains wallet addresses can be imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CryptoChain {
+    Bitcoin,
+    Ethereum,
+}
+
+impl CryptoChain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CryptoChain::Bitcoin => "bitcoin",
+            CryptoChain::Ethereum => "ethereum",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "bitcoin" => Some(CryptoChain::Bitcoin),
+            "ethereum" => Some(CryptoChain::Ethereum),
+            _ => None,
+        }
+    }
+}
+
+/// A public wallet address tracked for an account
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CryptoWallet {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub chain: String,
+    pub address: String,
+    pub label: Option<String>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Error from the last sync attempt, cleared once a sync succeeds
+    pub last_sync_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CryptoWallet {
+    pub fn chain(&self) -> Option<CryptoChain> {
+        CryptoChain::from_str(&self.chain)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCryptoWallet {
+    pub chain: CryptoChain,
+    pub address: String,
+    pub label: Option<String>,
+}
+
+/// Outcome of syncing an account's wallets into its holdings
+#[derive(Debug, Clone, Serialize)]
+pub struct CryptoSyncResult {
+    pub account_id: Uuid,
+    pub snapshot_date: NaiveDate,
+    /// Holdings written, including zeroed ones for tokens no longer held
+    pub holdings: usize,
+    pub total_value: f64,
+    /// Tokens left out because no price was available
+    pub unpriced: Vec<String>,
+    /// Addresses whose balances could not be read; holdings are left unchanged when any fail
+    pub failed_wallets: Vec<String>,
+}
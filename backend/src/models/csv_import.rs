@@ -0,0 +1,207 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Holding snapshot field a CSV column can be mapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldingField {
+    AccountNumber,
+    AccountNickname,
+    ClientId,
+    ClientName,
+    Symbol,
+    Holding,
+    AssetCategory,
+    Industry,
+    Fund,
+    Quantity,
+    Price,
+    AverageCost,
+    BookValue,
+    MarketValue,
+    AccruedInterest,
+    GainLoss,
+    GainLossPct,
+    PercentageOfAssets,
+    Currency,
+}
+
+impl HoldingField {
+    pub const ALL: [HoldingField; 19] = [
+        HoldingField::AccountNumber,
+        HoldingField::AccountNickname,
+        HoldingField::ClientId,
+        HoldingField::ClientName,
+        HoldingField::Symbol,
+        HoldingField::Holding,
+        HoldingField::AssetCategory,
+        HoldingField::Industry,
+        HoldingField::Fund,
+        HoldingField::Quantity,
+        HoldingField::Price,
+        HoldingField::AverageCost,
+        HoldingField::BookValue,
+        HoldingField::MarketValue,
+        HoldingField::AccruedInterest,
+        HoldingField::GainLoss,
+        HoldingField::GainLossPct,
+        HoldingField::PercentageOfAssets,
+        HoldingField::Currency,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HoldingField::AccountNumber => "account_number",
+            HoldingField::AccountNickname => "account_nickname",
+            HoldingField::ClientId => "client_id",
+            HoldingField::ClientName => "client_name",
+            HoldingField::Symbol => "symbol",
+            HoldingField::Holding => "holding",
+            HoldingField::AssetCategory => "asset_category",
+            HoldingField::Industry => "industry",
+            HoldingField::Fund => "fund",
+            HoldingField::Quantity => "quantity",
+            HoldingField::Price => "price",
+            HoldingField::AverageCost => "average_cost",
+            HoldingField::BookValue => "book_value",
+            HoldingField::MarketValue => "market_value",
+            HoldingField::AccruedInterest => "accrued_interest",
+            HoldingField::GainLoss => "gain_loss",
+            HoldingField::GainLossPct => "gain_loss_pct",
+            HoldingField::PercentageOfAssets => "percentage_of_assets",
+            HoldingField::Currency => "currency",
+        }
+    }
+
+    /// Column header for the field in the Raymond James holdings layout
+    pub fn label(&self) -> &'static str {
+        match self {
+            HoldingField::AccountNumber => "Account Number",
+            HoldingField::AccountNickname => "Account Nickname",
+            HoldingField::ClientId => "Client Id",
+            HoldingField::ClientName => "Client Name",
+            HoldingField::Symbol => "Symbol",
+            HoldingField::Holding => "Holding",
+            HoldingField::AssetCategory => "Asset Category",
+            HoldingField::Industry => "Industry",
+            HoldingField::Fund => "Fund",
+            HoldingField::Quantity => "Quantity",
+            HoldingField::Price => "Price",
+            HoldingField::AverageCost => "Average Cost",
+            HoldingField::BookValue => "Book Value",
+            HoldingField::MarketValue => "Market Value",
+            HoldingField::AccruedInterest => "Accrued Interest",
+            HoldingField::GainLoss => "G/L",
+            HoldingField::GainLossPct => "G/L (%)",
+            HoldingField::PercentageOfAssets => "Percentage of Assets",
+            HoldingField::Currency => "Currency",
+        }
+    }
+
+    /// Fields an import cannot proceed without; the rest default or are derived
+    pub fn is_required(&self) -> bool {
+        matches!(
+            self,
+            HoldingField::AccountNumber | HoldingField::Symbol | HoldingField::Quantity | HoldingField::Price
+        )
+    }
+
+    /// Column headers brokers commonly use for the field, normalized (lowercase
+    /// alphanumerics). The first entry is the Raymond James header.
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            HoldingField::AccountNumber => &["accountnumber", "account", "accountno", "acctnumber", "acct", "accountid"],
+            HoldingField::AccountNickname => &["accountnickname", "accountname", "nickname", "accountdescription"],
+            HoldingField::ClientId => &["clientid", "customerid"],
+            HoldingField::ClientName => &["clientname", "customername", "owner", "accountholder"],
+            HoldingField::Symbol => &["symbol", "ticker", "tickersymbol", "securitysymbol", "instrument"],
+            HoldingField::Holding => &["holding", "description", "securitydescription", "securityname", "name", "security"],
+            HoldingField::AssetCategory => &["assetcategory", "assetclass", "securitytype", "type", "assettype"],
+            HoldingField::Industry => &["industry", "sector"],
+            HoldingField::Fund => &["fund", "fundname"],
+            HoldingField::Quantity => &["quantity", "qty", "shares", "units", "position"],
+            HoldingField::Price => &["price", "lastprice", "marketprice", "currentprice", "last", "close"],
+            HoldingField::AverageCost => &["averagecost", "avgcost", "costpershare", "averageprice", "unitcost"],
+            HoldingField::BookValue => &["bookvalue", "costbasis", "totalcost", "cost"],
+            HoldingField::MarketValue => &["marketvalue", "value", "currentvalue", "totalvalue", "mktvalue"],
+            HoldingField::AccruedInterest => &["accruedinterest", "accrued"],
+            HoldingField::GainLoss => &["gl", "gainloss", "unrealizedgainloss", "unrealizedgl", "totalgainloss"],
+            HoldingField::GainLossPct => &["glpct", "gainlosspct", "gainlosspercent", "unrealizedglpct", "return"],
+            HoldingField::PercentageOfAssets => &["percentageofassets", "pctofassets", "percentofaccount", "weight", "allocation"],
+            HoldingField::Currency => &["currency", "ccy", "currencycode", "tradingcurrency", "pricecurrency"],
+        }
+    }
+}
+
+/// Holding field -> source column header
+pub type ColumnMapping = BTreeMap<HoldingField, String>;
+
+/// How a field was matched to a column in an upload preview
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnMatch {
+    pub field: HoldingField,
+    pub column: Option<String>,
+    /// 1.0 for an exact header match or a saved template, lower for fuzzy matches
+    pub confidence: f64,
+    pub required: bool,
+}
+
+/// Result of the first import step: the detected mapping and a sample of
+/// mapped rows, for the user to adjust before confirming
+#[derive(Debug, Clone, Serialize)]
+pub struct CsvImportPreview {
+    pub headers: Vec<String>,
+    pub header_signature: String,
+    pub matches: Vec<ColumnMatch>,
+    pub mapping: ColumnMapping,
+    /// Required fields with no column; the import cannot be confirmed until they are mapped
+    pub missing_required: Vec<HoldingField>,
+    /// Lowest confidence among the mapped required fields
+    pub confidence: f64,
+    /// Saved template the mapping came from, when one matched the headers
+    pub template: Option<CsvImportTemplate>,
+    pub sample_rows: Vec<BTreeMap<HoldingField, String>>,
+    pub total_rows: usize,
+}
+
+/// Holdings read from a PDF statement. They are converted to a holdings CSV
+/// so the rest of the import (preview, confirm, batches) is the CSV flow.
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfImportPreview {
+    /// Statement layout used, e.g. "questrade", or "generic" when no broker was recognized
+    pub broker: String,
+    pub broker_name: String,
+    /// Date the statement reports holdings as of, when it states one
+    pub statement_date: Option<NaiveDate>,
+    pub pages: usize,
+    pub accounts: Vec<String>,
+    /// The holdings as CSV; confirm the import by posting it to `/import/confirm`
+    pub content: String,
+    pub preview: CsvImportPreview,
+    /// Lines that looked like holdings but could not be read
+    pub warnings: Vec<String>,
+}
+
+/// Column mapping saved for a broker's statement format
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CsvImportTemplate {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub broker: String,
+    pub name: String,
+    pub header_signature: String,
+    pub mapping: Json<ColumnMapping>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveCsvImportTemplate {
+    pub broker: String,
+    pub name: Option<String>,
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Body of the datasource's `/search` call
+#[derive(Debug, Default, Deserialize)]
+pub struct DatasourceSearchRequest {
+    /// Text typed in the metric picker; empty lists every metric
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// A metric offered in Grafana's metric picker
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DatasourceMetric {
+    /// Shown in the picker
+    pub text: String,
+    /// Sent back as the query target, e.g. "<portfolio id>:value"
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct DatasourceRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DatasourceTarget {
+    pub target: Option<String>,
+}
+
+/// Body of the datasource's `/query` call
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasourceQueryRequest {
+    pub range: DatasourceRange,
+    #[serde(default)]
+    pub targets: Vec<DatasourceTarget>,
+    pub max_data_points: Option<usize>,
+}
+
+/// One time series in the SimpleJSON response format
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DatasourceSeries {
+    /// Legend name
+    pub target: String,
+    /// [value, unix epoch milliseconds] pairs, oldest first
+    pub datapoints: Vec<(f64, i64)>,
+}
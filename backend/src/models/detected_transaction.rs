@@ -9,6 +9,8 @@ pub enum TransactionType {
     Buy,
     Sell,
     Dividend,
+    /// Dividend reinvested into fractional shares
+    Drip,
     Split,
     Other,
 }
@@ -27,6 +29,21 @@ pub struct DetectedTransaction {
     pub to_snapshot_date: Option<NaiveDate>,
     pub description: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub tags: Vec<String>,
+    /// Markdown notes recorded by the user
+    pub notes: Option<String>,
+    /// Loss disallowed by the wash sale rule; `None` unless this sell is a wash sale
+    pub wash_sale_disallowed_loss: Option<f64>,
+    /// For generated DRIP transactions, the dividend that was reinvested
+    pub source_transaction_id: Option<uuid::Uuid>,
+}
+
+/// Outcome of generating DRIP transactions for an account's dividends
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DripGenerationResult {
+    pub generated: Vec<DetectedTransaction>,
+    /// Dividends that could not be reinvested, with the reason
+    pub skipped: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -81,6 +98,7 @@ impl DetectedTransaction {
                 TransactionType::Buy => "BUY".to_string(),
                 TransactionType::Sell => "SELL".to_string(),
                 TransactionType::Dividend => "DIVIDEND".to_string(),
+                TransactionType::Drip => "DRIP".to_string(),
                 TransactionType::Split => "SPLIT".to_string(),
                 TransactionType::Other => "OTHER".to_string(),
             },
@@ -93,6 +111,10 @@ impl DetectedTransaction {
             to_snapshot_date: data.to_snapshot_date,
             description: data.description,
             created_at: chrono::Utc::now(),
+            tags: Vec::new(),
+            notes: None,
+            wash_sale_disallowed_loss: None,
+            source_transaction_id: None,
         }
     }
 }
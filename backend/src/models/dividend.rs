@@ -0,0 +1,51 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A stored dividend announcement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct DividendEvent {
+    pub ticker: String,
+    pub ex_date: NaiveDate,
+    pub pay_date: Option<NaiveDate>,
+    /// Cash per share
+    pub amount: Option<f64>,
+    /// Annual dividend per share
+    pub annual_rate: Option<f64>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// An upcoming dividend of a held position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncomeCalendarEntry {
+    pub ticker: String,
+    pub ex_date: NaiveDate,
+    pub pay_date: Option<NaiveDate>,
+    pub days_until_ex_date: i64,
+    /// Shares held across the portfolio's accounts
+    pub quantity: f64,
+    pub amount_per_share: Option<f64>,
+    /// quantity × amount, when the amount is known
+    pub expected_income: Option<f64>,
+}
+
+/// Upcoming ex-dividend dates of a portfolio's holdings, soonest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncomeCalendar {
+    pub portfolio_id: String,
+    pub days: i64,
+    pub entries: Vec<IncomeCalendarEntry>,
+    /// Sum of the known expected incomes
+    pub expected_total: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IncomeCalendarQuery {
+    /// Days ahead to include (default: 90)
+    #[serde(default = "default_days")]
+    pub days: i64,
+}
+
+fn default_days() -> i64 {
+    90
+}
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A stored ETF position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct EtfConstituent {
+    pub etf_ticker: String,
+    pub constituent_ticker: Option<String>,
+    pub name: String,
+    /// Percent of the fund (0-100)
+    pub weight: f64,
+    pub sector: Option<String>,
+}
+
+/// A stored ETF sector weight, percent of the fund (0-100)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct EtfSectorWeight {
+    pub etf_ticker: String,
+    pub sector: String,
+    pub weight: f64,
+}
+
+/// When and from where an ETF's constituents were last stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct EtfConstituentRefresh {
+    pub etf_ticker: String,
+    pub source: String,
+    pub constituent_count: i32,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Exposure to one security, held directly and through ETFs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LookThroughExposure {
+    pub ticker: String,
+    pub name: Option<String>,
+    /// Percent of the portfolio held directly
+    pub direct_weight: f64,
+    /// Percent of the portfolio held through ETFs
+    pub etf_weight: f64,
+    pub total_weight: f64,
+    /// ETFs contributing to `etf_weight`
+    pub via: Vec<String>,
+}
+
+/// Holdings two ETFs in the portfolio have in common
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EtfOverlap {
+    pub etf_a: String,
+    pub etf_b: String,
+    /// Sum of the smaller weight of each shared holding, percent of either fund
+    pub overlap_pct: f64,
+    pub common_holdings: usize,
+}
+
+/// Portfolio weight in a sector after looking through ETFs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LookThroughSector {
+    pub sector: String,
+    pub direct_weight: f64,
+    pub etf_weight: f64,
+    pub total_weight: f64,
+}
+
+/// An ETF in the portfolio and how much of it is covered by stored constituents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EtfCoverage {
+    pub ticker: String,
+    /// Percent of the portfolio
+    pub portfolio_weight: f64,
+    pub constituent_count: usize,
+    /// Percent of the fund covered by the stored constituents
+    pub covered_weight: f64,
+    pub source: Option<String>,
+    pub fetched_at: Option<DateTime<Utc>>,
+}
+
+/// Portfolio exposures with ETFs replaced by their constituents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LookThroughAnalysis {
+    pub portfolio_id: String,
+    pub total_value: f64,
+    pub etfs: Vec<EtfCoverage>,
+    /// Largest look-through exposures, by total weight
+    pub exposures: Vec<LookThroughExposure>,
+    pub overlaps: Vec<EtfOverlap>,
+    /// Sector allocation with ETFs split by their sector weights; positions
+    /// without sector data fall under "Unclassified"
+    pub sector_allocation: Vec<LookThroughSector>,
+    /// ETFs held without stored constituents yet
+    pub missing_etfs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LookThroughQuery {
+    /// Number of exposures to return (default: 25)
+    #[serde(default = "default_top")]
+    pub top: usize,
+}
+
+fn default_top() -> usize {
+    25
+}
@@ -1,4 +1,6 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
 
 // ============================================================================
 // Factor Types
@@ -36,6 +38,17 @@ impl FactorType {
         }
     }
 
+    /// Stable key used in storage and query strings
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FactorType::Value => "value",
+            FactorType::Growth => "growth",
+            FactorType::Momentum => "momentum",
+            FactorType::Quality => "quality",
+            FactorType::LowVolatility => "low_volatility",
+        }
+    }
+
     pub fn all() -> Vec<FactorType> {
         vec![
             FactorType::Value,
@@ -183,6 +196,79 @@ pub struct FactorBacktestResult {
     pub cumulative_return: f64,
 }
 
+// ============================================================================
+// Long-Short Factor Spreads
+// ============================================================================
+
+/// One day's long-short return for a factor: the equal-weighted next-day return
+/// of the tracked universe's top quintile minus its bottom quintile, ranked on
+/// the previous close
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct FactorSpread {
+    pub date: NaiveDate,
+    /// `FactorType::as_str` key
+    pub factor: String,
+    pub long_return: f64,
+    pub short_return: f64,
+    pub spread: f64,
+    /// Tickers ranked that day
+    pub universe_size: i32,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Whether top-ranked stocks on a factor have been beating bottom-ranked ones
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FactorTrend {
+    Working,
+    Neutral,
+    NotWorking,
+}
+
+/// Recent performance of one factor alongside the portfolio's tilt to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactorSpreadSummary {
+    pub factor: FactorType,
+    pub label: String,
+    /// Most recent daily spread
+    pub latest_spread: Option<f64>,
+    /// Compounded spread over the requested window
+    pub window_spread: f64,
+    /// Compounded spread over the last 21 trading days
+    pub month_spread: f64,
+    /// Share of days in the window with a positive spread (0-1)
+    pub hit_rate: f64,
+    pub observation_days: usize,
+    pub trend: FactorTrend,
+    /// Portfolio's weighted factor score (0-100), when it holds scoreable positions
+    pub portfolio_score: Option<f64>,
+    pub portfolio_exposure: Option<ExposureLevel>,
+    pub context: String,
+}
+
+/// Response for GET /api/recommendations/factors/:portfolio_id/spreads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactorSpreadsResponse {
+    pub portfolio_id: String,
+    /// Latest date with stored spreads
+    pub as_of: Option<NaiveDate>,
+    pub days: i64,
+    pub factors: Vec<FactorSpreadSummary>,
+    /// Daily spreads for every factor, oldest first
+    pub history: Vec<FactorSpread>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FactorSpreadParams {
+    /// Trading days of spreads to evaluate (default: 63 ~ 3 months)
+    #[serde(default = "default_spread_days")]
+    pub days: i64,
+}
+
+fn default_spread_days() -> i64 {
+    63
+}
+
 // ============================================================================
 // Top-level API Response
 // ============================================================================
@@ -226,3 +312,63 @@ pub struct FactorQueryParams {
     /// Whether to include ETF suggestions (default: true)
     pub include_etfs: Option<bool>,
 }
+
+// ============================================================================
+// Factor Regression
+// ============================================================================
+
+/// A holding's estimated sensitivity to one academic factor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactorLoading {
+    /// "mkt_rf", "smb", "hml" or "mom"
+    pub factor: String,
+    pub label: String,
+    pub beta: f64,
+    pub std_error: f64,
+    /// |t| above about 2 is significant at the 5% level
+    pub t_stat: f64,
+}
+
+/// Daily excess returns of one holding regressed on the factor returns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldingFactorRegression {
+    pub ticker: String,
+    pub holding_name: Option<String>,
+    pub weight: f64,
+    pub observations: usize,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Intercept, annualized (252 trading days)
+    pub alpha_annualized: f64,
+    pub alpha_t_stat: f64,
+    pub r_squared: f64,
+    pub loadings: Vec<FactorLoading>,
+}
+
+/// A holding left out of the regressions, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFactorRegression {
+    pub ticker: String,
+    pub reason: String,
+}
+
+/// Response for GET /api/recommendations/factors/:portfolio_id/regression
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactorRegressionResponse {
+    pub portfolio_id: String,
+    /// Latest date with stored factor returns; the regressions end there
+    pub factors_as_of: Option<NaiveDate>,
+    pub holdings: Vec<HoldingFactorRegression>,
+    pub skipped: Vec<SkippedFactorRegression>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FactorRegressionParams {
+    /// Trading days of returns to regress (default: 756 ~ 3 years)
+    #[serde(default = "default_regression_days")]
+    pub days: i64,
+}
+
+fn default_regression_days() -> i64 {
+    756
+}
@@ -0,0 +1,183 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// An ETF or fund from the fund metadata table
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FundMetadata {
+    pub ticker: String,
+    pub name: String,
+    /// Funds in the same category and currency are treated as interchangeable
+    pub category: String,
+    pub currency: String,
+    /// Annual expense ratio in percent (0.03 = 0.03%)
+    pub expense_ratio: f64,
+}
+
+/// Fees paid by one holding
+#[derive(Debug, Clone, Serialize)]
+pub struct HoldingFee {
+    pub ticker: String,
+    pub name: Option<String>,
+    pub category: Option<String>,
+    pub market_value: f64,
+    /// Share of portfolio value, in percent
+    pub weight: f64,
+    /// `None` for securities not in the fund metadata table (treated as fee-free)
+    pub expense_ratio: Option<f64>,
+    pub annual_fee: f64,
+}
+
+/// A cheaper fund in the same category and currency as a held fund
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeSwapSuggestion {
+    pub ticker: String,
+    pub expense_ratio: f64,
+    pub alternative_ticker: String,
+    pub alternative_name: String,
+    pub alternative_expense_ratio: f64,
+    pub market_value: f64,
+    pub annual_savings: f64,
+    /// Ending value gained by swapping, at the assumed return
+    pub savings_10y: f64,
+    pub savings_20y: f64,
+}
+
+/// Value lost to fees over a horizon, at the assumed return
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeDragProjection {
+    pub years: u32,
+    /// Ending value with no fees minus ending value with current fees
+    pub fee_drag: f64,
+    /// Same, after taking every swap suggestion
+    pub fee_drag_with_swaps: f64,
+    pub savings: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeAnalysis {
+    pub portfolio_id: Uuid,
+    pub total_value: f64,
+    /// Value held in funds from the fund metadata table
+    pub fund_value: f64,
+    /// Value-weighted expense ratio of the whole portfolio, in percent
+    pub weighted_expense_ratio: f64,
+    /// Value-weighted expense ratio of the funds only, in percent
+    pub fund_expense_ratio: f64,
+    pub annual_fees: f64,
+    /// Yearly return before fees assumed for projections, in percent
+    pub assumed_return: f64,
+    pub projections: Vec<FeeDragProjection>,
+    /// Highest annual fee first
+    pub holdings: Vec<HoldingFee>,
+    /// Largest annual savings first
+    pub swaps: Vec<FeeSwapSuggestion>,
+}
+
+/// Query parameters for the fee analysis endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct FeeAnalysisQuery {
+    /// Yearly return before fees in percent (default: 6)
+    pub expected_return: Option<f64>,
+}
+
+/// How a recurring account fee is charged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountFeeType {
+    /// Annual basis points of account value, charged monthly
+    AumBps,
+    /// Fixed dollar amount per month
+    FlatMonthly,
+}
+
+impl AccountFeeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountFeeType::AumBps => "aum_bps",
+            AccountFeeType::FlatMonthly => "flat_monthly",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "aum_bps" => Some(AccountFeeType::AumBps),
+            "flat_monthly" => Some(AccountFeeType::FlatMonthly),
+            _ => None,
+        }
+    }
+}
+
+/// A recurring advisor or management fee on an account
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AccountFeeSchedule {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    /// "aum_bps" or "flat_monthly"
+    pub fee_type: String,
+    /// Basis points per year for "aum_bps", dollars per month for "flat_monthly"
+    pub amount: f64,
+    pub start_date: NaiveDate,
+    /// `None` while the fee is still charged
+    pub end_date: Option<NaiveDate>,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AccountFeeSchedule {
+    pub fn fee_type(&self) -> Option<AccountFeeType> {
+        AccountFeeType::from_str(&self.fee_type)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAccountFeeSchedule {
+    pub fee_type: AccountFeeType,
+    pub amount: f64,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub description: Option<String>,
+}
+
+/// Result of regenerating an account's fee cash flows
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeeFlowGeneration {
+    pub flows: usize,
+    pub total_fees: f64,
+    /// Month ends skipped because no account value was known yet
+    pub skipped_months: usize,
+}
+
+/// Account value at a horizon with and without the account's recurring fees
+#[derive(Debug, Clone, Serialize)]
+pub struct NetOfFeeProjection {
+    pub years: u32,
+    pub gross_value: f64,
+    pub net_value: f64,
+    /// Gross minus net ending value
+    pub fee_drag: f64,
+}
+
+/// Performance of an account before and after its recurring fees
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountFeePerformance {
+    pub account_id: Uuid,
+    pub as_of_date: Option<NaiveDate>,
+    /// Deposits minus withdrawals
+    pub net_contributions: f64,
+    pub gross_value: f64,
+    pub gross_gain: f64,
+    pub gross_gain_pct: f64,
+    /// Fee cash flows charged to date
+    pub total_fees: f64,
+    pub net_value: f64,
+    pub net_gain: f64,
+    pub net_gain_pct: f64,
+    /// Fees for the next year at the current value, across active schedules
+    pub annual_fee_estimate: f64,
+    /// Yearly return before fees assumed for projections, in percent
+    pub assumed_return: f64,
+    pub projections: Vec<NetOfFeeProjection>,
+    pub schedules: Vec<AccountFeeSchedule>,
+}
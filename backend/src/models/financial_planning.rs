@@ -517,6 +517,66 @@ impl From<SurveyGoal> for GoalResponse {
     }
 }
 
+// ==============================================================================
+// Goal Probability Models
+// ==============================================================================
+
+/// One day's Monte Carlo estimate of a goal's chance of success
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GoalProbabilityRecord {
+    pub goal_id: Uuid,
+    pub survey_id: Uuid,
+    pub calculated_on: NaiveDate,
+    pub success_probability: BigDecimal,
+    pub median_outcome: BigDecimal,
+    pub p10_outcome: BigDecimal,
+    pub p90_outcome: BigDecimal,
+    pub target_amount: BigDecimal,
+    pub starting_balance: BigDecimal,
+    pub annual_contribution: BigDecimal,
+    pub horizon_months: i32,
+    pub expected_return: BigDecimal,
+    pub volatility: BigDecimal,
+    pub simulations: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalProbabilityResponse {
+    pub calculated_on: NaiveDate,
+    pub success_probability: f64,
+    pub median_outcome: f64,
+    pub p10_outcome: f64,
+    pub p90_outcome: f64,
+    pub target_amount: f64,
+    pub starting_balance: f64,
+    pub annual_contribution: f64,
+    pub horizon_months: i32,
+    pub expected_return: f64,
+    pub volatility: f64,
+    pub simulations: i32,
+}
+
+impl From<GoalProbabilityRecord> for GoalProbabilityResponse {
+    fn from(r: GoalProbabilityRecord) -> Self {
+        let to_f64 = |v: &BigDecimal| v.to_string().parse().unwrap_or(0.0);
+        Self {
+            calculated_on: r.calculated_on,
+            success_probability: to_f64(&r.success_probability),
+            median_outcome: to_f64(&r.median_outcome),
+            p10_outcome: to_f64(&r.p10_outcome),
+            p90_outcome: to_f64(&r.p90_outcome),
+            target_amount: to_f64(&r.target_amount),
+            starting_balance: to_f64(&r.starting_balance),
+            annual_contribution: to_f64(&r.annual_contribution),
+            horizon_months: r.horizon_months,
+            expected_return: to_f64(&r.expected_return),
+            volatility: to_f64(&r.volatility),
+            simulations: r.simulations,
+        }
+    }
+}
+
 // ==============================================================================
 // Risk Profile Models
 // ==============================================================================
@@ -0,0 +1,78 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Valuation fundamentals and key ratios for a ticker. Percentages are in
+/// percent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundamentalsSnapshot {
+    pub ticker: String,
+    /// Trailing twelve-month price/earnings; negative when loss-making
+    pub pe_ratio: Option<f64>,
+    pub pb_ratio: Option<f64>,
+    /// Market capitalization in dollars
+    pub market_cap: Option<f64>,
+    pub roe: Option<f64>,
+    pub net_margin: Option<f64>,
+    pub debt_to_equity: Option<f64>,
+    pub dividend_yield: Option<f64>,
+    pub payout_ratio: Option<f64>,
+    pub eps_growth_5y: Option<f64>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FundamentalsQuery {
+    /// Bypass the cached fundamentals (default: false)
+    pub force: Option<bool>,
+}
+
+/// A stored quarterly earnings report
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EarningsReport {
+    pub ticker: String,
+    pub period_end: NaiveDate,
+    pub fiscal_year: i32,
+    pub fiscal_quarter: i32,
+    pub eps_actual: Option<f64>,
+    pub eps_estimate: Option<f64>,
+}
+
+/// A stored upcoming earnings announcement
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EarningsDate {
+    pub ticker: String,
+    pub report_date: NaiveDate,
+    /// "bmo" (before open), "amc" (after close) or "dmh" (during market hours)
+    pub hour: Option<String>,
+    pub fiscal_year: Option<i32>,
+    pub fiscal_quarter: Option<i32>,
+    pub eps_estimate: Option<f64>,
+}
+
+/// A stored past dividend payment
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DividendPayment {
+    pub ticker: String,
+    pub ex_date: NaiveDate,
+    pub pay_date: Option<NaiveDate>,
+    /// Cash per share
+    pub amount: f64,
+    pub currency: Option<String>,
+}
+
+/// Stored earnings and dividends of a ticker with what they add up to
+#[derive(Debug, Clone, Serialize)]
+pub struct FundamentalsHistory {
+    pub ticker: String,
+    /// Most recent first
+    pub earnings: Vec<EarningsReport>,
+    /// Year-over-year growth of trailing twelve-month EPS, in percent
+    pub ttm_eps_growth: Option<f64>,
+    /// Most recent first
+    pub dividends: Vec<DividendPayment>,
+    /// Dividends per share with an ex-date in the last twelve months
+    pub trailing_annual_dividend: f64,
+    /// Year-over-year growth of the trailing annual dividend, in percent
+    pub dividend_growth: Option<f64>,
+}
@@ -0,0 +1,63 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Shape of a target-date glide path
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GlidePathSettings {
+    pub goal_date: NaiveDate,
+    /// Equity share while the goal is at least `glide_years` away, in percent
+    pub start_equity_pct: f64,
+    /// Equity share from the goal date on, in percent
+    pub end_equity_pct: f64,
+    /// Years before the goal over which the equity share declines
+    pub glide_years: f64,
+}
+
+/// Glide path parameters, as query parameters for a preview or as the body when
+/// saving. Unset fields fall back to the saved path, then to the defaults. The goal
+/// date may be given directly or as `current_age` and `retirement_age`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GlidePathRequest {
+    pub goal_date: Option<NaiveDate>,
+    pub current_age: Option<u32>,
+    pub retirement_age: Option<u32>,
+    /// Default: 90
+    pub start_equity_pct: Option<f64>,
+    /// Default: 30
+    pub end_equity_pct: Option<f64>,
+    /// Default: 25
+    pub glide_years: Option<f64>,
+}
+
+/// Target mix at one date on the glide path
+#[derive(Debug, Clone, Serialize)]
+pub struct GlidePathPoint {
+    pub date: NaiveDate,
+    pub years_to_goal: f64,
+    pub equity_pct: f64,
+    /// Fixed income and cash
+    pub defensive_pct: f64,
+}
+
+/// The glide path next to the portfolio's current allocation
+#[derive(Debug, Clone, Serialize)]
+pub struct GlidePath {
+    pub portfolio_id: Uuid,
+    pub settings: GlidePathSettings,
+    /// Whether these settings are the saved ones the rebalancer uses
+    pub saved: bool,
+    pub as_of: NaiveDate,
+    pub total_value: f64,
+    pub target_equity_pct: f64,
+    pub current_equity_pct: f64,
+    pub current_fixed_income_pct: f64,
+    pub current_cash_pct: f64,
+    /// Current minus target equity share, in percentage points
+    pub equity_drift: f64,
+    /// Equity to sell (positive) or buy (negative) to be back on the path
+    pub equity_trade_value: f64,
+    /// Yearly points from today to the goal date
+    pub points: Vec<GlidePathPoint>,
+}
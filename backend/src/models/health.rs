@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Outcome of a single health check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthCheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    /// Not enough data to run the check; excluded from the overall score
+    Skipped,
+}
+
+impl HealthCheckStatus {
+    /// Points out of 100 the check contributes, `None` when skipped
+    pub fn score(&self) -> Option<f64> {
+        match self {
+            HealthCheckStatus::Pass => Some(100.0),
+            HealthCheckStatus::Warn => Some(50.0),
+            HealthCheckStatus::Fail => Some(0.0),
+            HealthCheckStatus::Skipped => None,
+        }
+    }
+}
+
+/// One line of the portfolio health checklist
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheckItem {
+    /// Stable identifier, e.g. "concentration"
+    pub check: String,
+    pub title: String,
+    pub status: HealthCheckStatus,
+    pub summary: String,
+    /// Individual findings behind the status
+    pub details: Vec<String>,
+    /// Endpoint with the detailed analysis for this check
+    pub link: String,
+}
+
+/// Scored checklist for a portfolio
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioHealthCheck {
+    pub portfolio_id: Uuid,
+    /// Average of the non-skipped check scores, 0-100
+    pub score: f64,
+    /// Letter grade for `score` (A-F)
+    pub grade: String,
+    pub passed: usize,
+    pub warnings: usize,
+    pub failures: usize,
+    pub skipped: usize,
+    pub checks: Vec<HealthCheckItem>,
+    pub generated_at: DateTime<Utc>,
+}
@@ -2,6 +2,54 @@ use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+/// What a holding's ticker refers to, which decides where its prices come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TickerType {
+    #[default]
+    Equity,
+    /// Priced in USD from CoinGecko; tickered `<SYMBOL>-USD`
+    Crypto,
+}
+
+impl TickerType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TickerType::Equity => "equity",
+            TickerType::Crypto => "crypto",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "equity" => Some(TickerType::Equity),
+            "crypto" => Some(TickerType::Crypto),
+            _ => None,
+        }
+    }
+
+    /// Type implied by the ticker alone: crypto tickers end in `-USD`
+    pub fn of_ticker(ticker: &str) -> Self {
+        match ticker.strip_suffix("-USD") {
+            Some(base) if (2..=10).contains(&base.len()) && base.chars().all(|c| c.is_ascii_alphanumeric()) => {
+                TickerType::Crypto
+            }
+            _ => TickerType::Equity,
+        }
+    }
+
+    /// Type of an imported row. Exchange exports label coins with an asset
+    /// category such as "Crypto" or "Cryptocurrency" and a bare symbol like BTC.
+    pub fn detect(symbol: &str, asset_category: Option<&str>) -> Self {
+        let category = asset_category.unwrap_or_default().to_lowercase();
+        if category.contains("crypto") || category.contains("digital asset") {
+            TickerType::Crypto
+        } else {
+            Self::of_ticker(symbol.trim())
+        }
+    }
+}
+
 // Represents a historical snapshot of a holding at a specific point in time
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct HoldingSnapshot {
@@ -22,6 +70,10 @@ pub struct HoldingSnapshot {
     pub gain_loss: Option<BigDecimal>,
     pub gain_loss_pct: Option<BigDecimal>,
     pub percentage_of_assets: Option<BigDecimal>,
+    /// `TickerType` as stored
+    pub ticker_type: String,
+    /// ISO code of the currency price and values are quoted in
+    pub currency: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -41,6 +93,10 @@ pub struct CreateHoldingSnapshot {
     pub gain_loss: Option<BigDecimal>,
     pub gain_loss_pct: Option<BigDecimal>,
     pub percentage_of_assets: Option<BigDecimal>,
+    #[serde(default)]
+    pub ticker_type: TickerType,
+    /// ISO code of the currency price and values are quoted in
+    pub currency: String,
 }
 
 // View for latest holdings per account
@@ -60,6 +116,14 @@ pub struct LatestAccountHolding {
     pub gain_loss: Option<BigDecimal>,
     pub gain_loss_pct: Option<BigDecimal>,
     pub snapshot_date: chrono::NaiveDate,
+    pub ticker_type: String,
+    pub currency: String,
+}
+
+impl LatestAccountHolding {
+    pub fn ticker_type(&self) -> TickerType {
+        TickerType::from_str(&self.ticker_type).unwrap_or_default()
+    }
 }
 
 // View for account value history over time
@@ -71,6 +135,9 @@ pub struct AccountValueHistory {
     pub total_cost: BigDecimal,
     pub total_gain_loss: Option<BigDecimal>,
     pub total_gain_loss_pct: Option<BigDecimal>,
+    /// "import" for an imported statement, "valuation" for a scheduled
+    /// valuation between imports
+    pub source: String,
 }
 
 impl HoldingSnapshot {
@@ -98,6 +165,8 @@ impl HoldingSnapshot {
             gain_loss: data.gain_loss,
             gain_loss_pct: data.gain_loss_pct,
             percentage_of_assets: data.percentage_of_assets,
+            ticker_type: data.ticker_type.as_str().to_string(),
+            currency: data.currency,
             created_at: chrono::Utc::now(),
         }
     }
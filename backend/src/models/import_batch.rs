@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Tables whose changes are recorded under an import batch and reversed by a rollback
+pub const IMPORT_BATCH_TABLES: &[&str] = &["accounts", "holdings_snapshots", "detected_transactions", "cash_flows"];
+
+/// All rows written by one import, reversible as a unit
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ImportBatch {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub portfolio_id: Uuid,
+    /// Import format, e.g. "rj_holdings" or "mapped_holdings"
+    pub source: String,
+    pub filename: Option<String>,
+    /// "applied" or "rolled_back"
+    pub status: String,
+    pub accounts_created: i32,
+    pub holdings_created: i32,
+    pub transactions_detected: i32,
+    pub error_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub rolled_back_at: Option<DateTime<Utc>>,
+}
+
+/// Rows an import inserted, updated and deleted in one table
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct ImportChangeSummary {
+    pub table_name: String,
+    pub inserted: i64,
+    pub updated: i64,
+    pub deleted: i64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportQuery {
+    /// Run the import and report what it would change, then discard it
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Outcome of rolling back an import batch
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRollback {
+    pub batch: ImportBatch,
+    /// Changes reversed, per table
+    pub reverted: Vec<ImportChangeSummary>,
+}
+
+/// A recorded row change, as stored by the import batch triggers
+#[derive(Debug, Clone, FromRow)]
+pub struct ImportBatchChange {
+    pub table_name: String,
+    pub operation: String,
+    pub row_id: Uuid,
+    pub old_row: Option<serde_json::Value>,
+}
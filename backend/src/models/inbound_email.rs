@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A portfolio's private forwarding address; the token is the part after `+`
+#[derive(Debug, Clone, FromRow)]
+pub struct InboundEmailAddress {
+    pub user_id: Uuid,
+    pub portfolio_id: Uuid,
+    pub token: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentOutcome {
+    Imported,
+    /// Not a statement this pipeline can read (e.g. an unrecognized CSV layout)
+    Skipped,
+    Failed,
+}
+
+/// What happened to one attachment of an inbound email
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboundAttachmentResult {
+    pub filename: String,
+    pub outcome: AttachmentOutcome,
+    /// Import format used, e.g. "rj_activities" or "mapped_holdings"
+    pub source: Option<String>,
+    /// Batch to roll back to undo the import
+    pub import_batch_id: Option<Uuid>,
+    pub accounts_created: usize,
+    pub holdings_created: usize,
+    pub transactions_detected: usize,
+    /// Why the attachment was skipped or failed
+    pub message: Option<String>,
+    /// Rows that could not be imported
+    pub errors: Vec<String>,
+}
+
+/// A statement email received for a portfolio
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct InboundEmail {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub portfolio_id: Uuid,
+    pub message_id: Option<String>,
+    pub sender: String,
+    pub subject: Option<String>,
+    /// "received", "processed", "partial" (some attachments not imported),
+    /// "failed" or "rejected"
+    pub status: String,
+    pub attachments: Json<Vec<InboundAttachmentResult>>,
+    pub error: Option<String>,
+    pub received_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
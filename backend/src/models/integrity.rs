@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Rows failing one integrity check
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityFinding {
+    pub check: String,
+    pub description: String,
+    pub count: i64,
+    /// Whether the offending rows can be deleted without losing user data
+    pub repairable: bool,
+    /// Rows deleted by this run's repair
+    pub repaired: i64,
+}
+
+/// Outcome of an integrity run; checks that found nothing are left out
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub checked_at: DateTime<Utc>,
+    pub checks_run: usize,
+    pub findings: Vec<IntegrityFinding>,
+    /// Whether safe repairs were applied
+    pub repair: bool,
+}
+
+impl IntegrityReport {
+    /// Offending rows left after repair
+    pub fn outstanding(&self) -> i64 {
+        self.findings.iter().map(|f| f.count - f.repaired).sum()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IntegrityCheckRequest {
+    /// Delete orphaned derived rows (snapshots, caches) as well as reporting them
+    #[serde(default)]
+    pub repair: bool,
+}
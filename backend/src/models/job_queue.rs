@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A job in the Postgres work queue
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct QueuedJob {
+    pub id: i64,
+    pub job_name: String,
+    pub dedupe_key: String,
+    /// "queued", "running", "succeeded" or "failed"
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_after: DateTime<Utc>,
+    /// Token of the current claim; only its holder can complete the job
+    pub claim_token: Option<Uuid>,
+    pub claimed_by: Option<String>,
+    /// Lease expiry while running
+    pub visible_until: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub enqueued_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Number of queued jobs per job name and status
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct JobQueueSummary {
+    pub job_name: String,
+    pub status: String,
+    pub jobs: i64,
+    pub oldest_enqueued_at: DateTime<Utc>,
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Rationale and stated thesis recorded for a BUY or SELL, with the trade it belongs to.
+///
+/// For a BUY the thesis metrics describe what the position should do after entry. For
+/// a SELL they describe what the stock should do after the exit: `target_price` is the
+/// level it is expected to fall to, `stop_price` the level that would prove the sale
+/// wrong, and `expected_return_pct` is usually negative.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct JournalEntry {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub account_id: Uuid,
+    pub ticker: String,
+    /// "BUY" or "SELL"
+    pub transaction_type: String,
+    pub transaction_date: NaiveDate,
+    /// Price per share of the trade, when the transaction recorded one
+    pub transaction_price: Option<f64>,
+    /// Markdown notes on why the trade was made
+    pub rationale: String,
+    pub target_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    /// Return expected over the horizon, in percent
+    pub expected_return_pct: Option<f64>,
+    /// Days the thesis was expected to play out over; open-ended when `None`
+    pub horizon_days: Option<i32>,
+    /// Conviction from 1 (low) to 5 (high)
+    pub confidence: Option<i16>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request body for recording or replacing a transaction's journal entry
+#[derive(Debug, Default, Deserialize)]
+pub struct UpsertJournalEntry {
+    pub rationale: String,
+    pub target_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    pub expected_return_pct: Option<f64>,
+    pub horizon_days: Option<i32>,
+    pub confidence: Option<i16>,
+}
+
+/// Query parameters for the journal review endpoint
+#[derive(Debug, Deserialize)]
+pub struct JournalReviewQuery {
+    /// Benchmark the decisions are measured against (default: "SPY")
+    #[serde(default = "default_benchmark")]
+    pub benchmark: String,
+}
+
+fn default_benchmark() -> String {
+    "SPY".to_string()
+}
+
+/// How a reviewed decision turned out relative to the benchmark
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionOutcome {
+    /// The decision beat simply holding the benchmark
+    Outperformed,
+    Underperformed,
+    /// No price history to judge by
+    InsufficientData,
+}
+
+/// Whether one stated thesis metric played out
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThesisCheck {
+    /// "target_price", "stop_price" or "expected_return"
+    pub metric: String,
+    pub expected: f64,
+    /// The close or return the metric was judged against
+    pub actual: Option<f64>,
+    /// `None` when there's no price history to judge by
+    pub met: Option<bool>,
+}
+
+/// A journaled decision scored against what happened since
+#[derive(Debug, Clone, Serialize)]
+pub struct DecisionReview {
+    pub transaction_id: Uuid,
+    pub ticker: String,
+    pub transaction_type: String,
+    pub transaction_date: NaiveDate,
+    pub rationale: String,
+    pub confidence: Option<i16>,
+    /// Trade price, or the first close on or after the trade date when the trade recorded none
+    pub entry_price: Option<f64>,
+    /// Last close used for the review: at the end of the horizon, or the latest one
+    pub review_date: Option<NaiveDate>,
+    pub review_price: Option<f64>,
+    /// Whether the stated horizon has fully elapsed
+    pub horizon_elapsed: bool,
+    /// Percent change in the stock since the trade
+    pub return_since_entry: Option<f64>,
+    /// Percent change in the benchmark over the same dates
+    pub benchmark_return: Option<f64>,
+    /// Percentage points the decision added over the benchmark. For a SELL this is
+    /// the benchmark's return minus the stock's: selling paid off if the stock lagged.
+    pub excess_return: Option<f64>,
+    pub thesis_checks: Vec<ThesisCheck>,
+    pub outcome: DecisionOutcome,
+    /// 0–100 blend of excess return and the share of thesis metrics met
+    pub score: Option<f64>,
+}
+
+/// Review of all journaled decisions in a portfolio
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalReview {
+    pub portfolio_id: Uuid,
+    pub benchmark: String,
+    pub decisions: Vec<DecisionReview>,
+    /// Decisions with enough price history to judge
+    pub reviewed: usize,
+    /// Share of reviewed decisions that outperformed, in percent
+    pub hit_rate: Option<f64>,
+    pub average_excess_return: Option<f64>,
+    /// Share of judged thesis metrics that played out, in percent
+    pub thesis_hit_rate: Option<f64>,
+    pub average_score: Option<f64>,
+}
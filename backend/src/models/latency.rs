@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// What a latency sample measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyKind {
+    /// An HTTP route, named "METHOD /route/:pattern"
+    Endpoint,
+    /// A SQL statement, named by its normalized text
+    Query,
+}
+
+impl LatencyKind {
+    /// Key stored in latency_samples.kind
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LatencyKind::Endpoint => "endpoint",
+            LatencyKind::Query => "query",
+        }
+    }
+}
+
+/// One timed request or query, buffered in memory until the next flush
+#[derive(Debug, Clone)]
+pub struct LatencySample {
+    pub kind: LatencyKind,
+    pub name: String,
+    pub duration_ms: f64,
+    /// HTTP status, endpoints only
+    pub status: Option<i16>,
+    pub request_id: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Latency distribution of one endpoint or query over the stats window
+#[derive(Debug, Clone, FromRow)]
+pub struct LatencyDistribution {
+    pub kind: String,
+    pub name: String,
+    pub sample_count: i32,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Stored statistics row, with the budget it was checked against
+#[derive(Debug, Clone, FromRow)]
+pub struct LatencyStat {
+    pub kind: String,
+    pub name: String,
+    pub sample_count: i32,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub budget_ms: i32,
+    pub breached: bool,
+    pub breached_since: Option<DateTime<Utc>>,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// A slow sample shown as an example of an offender
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct LatencyExample {
+    pub duration_ms: f64,
+    pub status: Option<i16>,
+    pub request_id: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// An endpoint or query ranked by how much of its budget its p95 uses
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyOffender {
+    pub kind: LatencyKind,
+    pub name: String,
+    pub sample_count: i32,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub budget_ms: i32,
+    /// p95 as a percentage of the budget; above 100 means breaching
+    pub budget_used_pct: f64,
+    pub breached: bool,
+    pub breached_since: Option<DateTime<Utc>>,
+    /// Slowest samples in the window
+    pub examples: Vec<LatencyExample>,
+}
+
+/// Top offenders by kind
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyReport {
+    pub computed_at: Option<DateTime<Utc>>,
+    pub window_hours: i64,
+    pub endpoints: Vec<LatencyOffender>,
+    pub queries: Vec<LatencyOffender>,
+}
+
+/// Query parameters for the latency report
+#[derive(Debug, Default, Deserialize)]
+pub struct LatencyReportQuery {
+    /// Offenders per kind (default 10)
+    pub limit: Option<i64>,
+    /// Example samples per offender (default 5)
+    pub examples: Option<i64>,
+    /// Only list endpoints and queries over budget
+    #[serde(default)]
+    pub breached_only: bool,
+}
@@ -48,6 +48,52 @@ pub struct UpdateUserPreferences {
     pub narrative_cache_hours: Option<i32>,
 }
 
+/// Version of the LLM consent text currently shown to users. Bump it when the
+/// text changes so that earlier grants stop counting until the user agrees again.
+pub const CURRENT_LLM_CONSENT_VERSION: i32 = 1;
+
+/// A recorded grant or revocation of LLM consent
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct LlmConsent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub version: i32,
+    pub granted: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LlmConsent {
+    /// Whether this record grants consent to the current consent text
+    pub fn is_current(&self) -> bool {
+        self.granted && self.version >= CURRENT_LLM_CONSENT_VERSION
+    }
+}
+
+/// Input for recording LLM consent; `version` is the consent text the user saw
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordLlmConsent {
+    pub granted: bool,
+    pub version: Option<i32>,
+}
+
+/// The user's latest consent decision and the version currently required
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmConsentStatus {
+    pub current_version: i32,
+    pub consent_required: bool,
+    pub latest: Option<LlmConsent>,
+}
+
+impl LlmConsentStatus {
+    pub fn from_latest(latest: Option<LlmConsent>) -> Self {
+        Self {
+            current_version: CURRENT_LLM_CONSENT_VERSION,
+            consent_required: !latest.as_ref().is_some_and(LlmConsent::is_current),
+            latest,
+        }
+    }
+}
+
 /// LLM usage statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmUsageStats {
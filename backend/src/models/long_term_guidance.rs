@@ -72,13 +72,15 @@ pub struct GrowthMetrics {
 /// Dividend analysis metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DividendMetrics {
-    /// Whether this holding has positive gain/loss suggesting income generation
+    /// Whether this holding pays a dividend (estimated from returns without reported dividends)
     pub has_positive_income: bool,
-    /// Estimated yield based on gain/loss relative to book value
+    /// Trailing dividend yield in percent from reported dividends, or estimated
+    /// from returns without them
     pub estimated_yield: Option<f64>,
-    /// Payout sustainability indicator (0-1)
+    /// Payout sustainability indicator (0-1), from the reported payout ratio when available
     pub payout_sustainability: f64,
-    /// Dividend growth estimated from price trend stability
+    /// Year-over-year dividend growth (as a fraction) from reported dividends,
+    /// or estimated from the price trend
     pub growth_indicator: f64,
 }
 
@@ -87,7 +89,7 @@ pub struct DividendMetrics {
 pub struct MoatIndicators {
     /// Price stability relative to market (lower vol = stronger moat proxy)
     pub price_stability: f64,
-    /// Margin proxy from consistent positive returns
+    /// Reported net margin (20% = 1.0), or a proxy from consistent positive returns
     pub margin_strength: f64,
     /// Relative strength vs benchmark
     pub relative_strength: f64,
@@ -98,7 +100,7 @@ pub struct MoatIndicators {
 /// Management quality proxies
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ManagementMetrics {
-    /// Capital efficiency proxy (return per unit of risk)
+    /// Reported return on equity (15% = 1.5), or return per unit of risk as a proxy
     pub capital_efficiency: f64,
     /// Drawdown recovery speed
     pub recovery_speed: f64,
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Macro factors a what-if scenario can shock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MacroFactor {
+    /// Interest rates; shock in basis points
+    Rates,
+    /// Crude oil; shock in percent
+    Oil,
+    /// US dollar; shock in percent
+    Usd,
+    /// Broad equity market; shock in percent
+    Equity,
+    /// Gold; shock in percent
+    Gold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MacroShock {
+    pub factor: MacroFactor,
+    /// Basis points for rates, percent for every other factor
+    pub shock: f64,
+}
+
+/// Request body for a macro shock scenario.
+///
+/// Shocks can be given as a list, as text such as "rates +100bps, oil +20%, USD +5%", or both.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MacroShockRequest {
+    #[serde(default)]
+    pub shocks: Vec<MacroShock>,
+    pub scenario: Option<String>,
+    /// Trading days of history used to estimate sensitivities (default: 252)
+    pub days: Option<i64>,
+}
+
+/// Estimated sensitivity of a position to one factor, per 1% move in the factor's proxy ETF
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FactorSensitivity {
+    pub factor: MacroFactor,
+    pub proxy: String,
+    pub beta: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionShockImpact {
+    pub ticker: String,
+    pub market_value: f64,
+    pub weight: f64,
+    pub sensitivities: Vec<FactorSensitivity>,
+    /// Regression fit of daily returns on the factor proxies (0-1)
+    pub r_squared: Option<f64>,
+    /// Estimated price change under the scenario, in percent
+    pub estimated_return: f64,
+    pub estimated_pnl: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MacroShockResult {
+    pub portfolio_id: Uuid,
+    pub shocks: Vec<MacroShock>,
+    pub days: i64,
+    pub total_value: f64,
+    pub estimated_pnl: f64,
+    /// Estimated portfolio change, in percent
+    pub estimated_return: f64,
+    pub positions: Vec<PositionShockImpact>,
+    /// Positions without enough aligned history to estimate sensitivities; assumed unaffected
+    pub unmodeled: Vec<String>,
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Breadth and internals of the tracked universe on one trading day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct MarketBreadth {
+    pub date: NaiveDate,
+    /// Tickers with a close on `date` and the previous trading day
+    pub universe_size: i32,
+    pub advancers: i32,
+    pub decliners: i32,
+    pub unchanged: i32,
+    /// Percent of tickers with enough history closing above their 50-day SMA
+    pub pct_above_sma50: Option<f64>,
+    /// Percent of tickers with enough history closing above their 200-day SMA
+    pub pct_above_sma200: Option<f64>,
+    /// Tickers closing at a 52-week high
+    pub new_highs: i32,
+    /// Tickers closing at a 52-week low
+    pub new_lows: i32,
+    pub computed_at: DateTime<Utc>,
+}
+
+impl MarketBreadth {
+    /// Advancers divided by decliners, `None` when nothing declined
+    pub fn advance_decline_ratio(&self) -> Option<f64> {
+        (self.decliners > 0).then(|| self.advancers as f64 / self.decliners as f64)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketBreadthResponse {
+    pub latest: Option<MarketBreadth>,
+    pub advance_decline_ratio: Option<f64>,
+    /// Oldest first
+    pub history: Vec<MarketBreadth>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarketBreadthParams {
+    /// Days of history to include (default: 30)
+    #[serde(default = "default_breadth_days")]
+    pub days: i64,
+}
+
+fn default_breadth_days() -> i64 {
+    30
+}
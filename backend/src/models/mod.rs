@@ -5,6 +5,43 @@ mod account;
 mod holding_snapshot;
 mod cash_flow;
 mod detected_transaction;
+mod annotation;
+mod journal;
+mod pnl;
+mod benchmark_comparison;
+mod behavior;
+mod contribution;
+mod macro_shock;
+mod market_breadth;
+mod risk_budget;
+mod rebalance_simulation;
+mod ownership;
+mod analyst;
+mod fundamentals;
+mod etf;
+mod dividend;
+mod asset_location;
+mod fee;
+mod health;
+mod model_portfolio;
+mod peer_statistics;
+mod user_data;
+mod latency;
+mod glide_path;
+mod crypto_wallet;
+mod rate_limit;
+mod job_queue;
+mod csv_import;
+mod import_batch;
+mod inbound_email;
+mod user_identity;
+mod portfolio_member;
+mod audit;
+mod public_api;
+mod integrity;
+mod widget;
+mod datasource;
+pub mod calendar;
 pub mod risk;
 pub mod risk_snapshot;
 pub mod optimization;
@@ -31,27 +68,121 @@ pub mod financial_planning;
 pub use portfolio::Portfolio;
 pub use portfolio::CreatePortfolio;
 pub use portfolio::UpdatePortfolio;
-pub use price_point::PricePoint;
+pub use portfolio::{ClonePortfolio, ClonePortfolioResponse, PortfolioListQuery};
+pub use price_point::{
+    CorporateAction, DuplicatePriceDate, FiftyTwoWeekRange, IntradayPrice, IntradayPrices, IntradayQuery, InvalidClose,
+    PriceGap, PricePoint, PriceQualityReport, PriceRepairResult, TickerMetadata, TickerSearchQuery, TickerSearchResponse,
+};
 pub use analytics::*;
-pub use account::{Account, CreateAccount};
-pub use holding_snapshot::{HoldingSnapshot, CreateHoldingSnapshot, LatestAccountHolding, AccountValueHistory};
+pub use account::{
+    Account, AccountTaxTreatment, CostBasisMethod, CreateAccount, UpdateCostBasisMethodSetting, UpdateDripSetting,
+    UpdateFractionalShareSetting, UpdateTaxTreatmentSetting,
+};
+pub use holding_snapshot::{HoldingSnapshot, CreateHoldingSnapshot, LatestAccountHolding, AccountValueHistory, TickerType};
 pub use cash_flow::{CashFlow, CreateCashFlow, FlowType};
-pub use detected_transaction::{DetectedTransaction, CreateDetectedTransaction, TransactionType, AccountActivity, AccountTruePerformance};
+pub use annotation::{
+    PositionAnnotation, UpdateAnnotation, TagFilterQuery, AnnotatedHolding, PositionLevelCrossing,
+    MonitoredPosition,
+};
+pub use behavior::{
+    BehaviorQuery, YearTurnover, HoldingPeriodStats, WinRateStats, TradeTiming, TimingStats, BehaviorInsights,
+};
+pub use journal::{
+    JournalEntry, UpsertJournalEntry, JournalReviewQuery, DecisionOutcome, ThesisCheck, DecisionReview,
+    JournalReview,
+};
+pub use pnl::{
+    TaxLot, RealizedLot, LotSelection, UpdateLotSelections, WashSale, OpenLotPnl, PositionPnl, RealizedPeriodPnl,
+    PortfolioPnl, PnlQuery,
+};
+pub use benchmark_comparison::{HoldingComparison, BenchmarkComparison, BenchmarkComparisonQuery};
+pub use contribution::{PositionContribution, PortfolioContributions, ContributionQuery};
+pub use macro_shock::{
+    MacroFactor, MacroShock, MacroShockRequest, FactorSensitivity, PositionShockImpact, MacroShockResult,
+};
+pub use market_breadth::{MarketBreadth, MarketBreadthResponse, MarketBreadthParams};
+pub use risk_budget::{
+    RiskBudgetMetric, SleeveBudget, RiskBudget, SetRiskBudget, SleeveUtilization, RiskBudgetUtilization,
+};
+pub use rebalance_simulation::{
+    RebalancePolicy, RebalancePolicyResult, RebalanceSimulation, RebalanceSimulationQuery,
+};
+pub use ownership::{InsiderActivity, InstitutionalHolder, OwnershipQuery, OwnershipSnapshot};
+pub use analyst::{AnalystConsensus, AnalystConsensusQuery, ConsensusRating, RatingDistribution};
+pub use fundamentals::{DividendPayment, EarningsDate, EarningsReport, FundamentalsHistory, FundamentalsQuery, FundamentalsSnapshot};
+pub use dividend::{DividendEvent, IncomeCalendar, IncomeCalendarEntry, IncomeCalendarQuery};
+pub use etf::{
+    EtfConstituent, EtfConstituentRefresh, EtfCoverage, EtfOverlap, EtfSectorWeight, LookThroughAnalysis,
+    LookThroughExposure, LookThroughQuery, LookThroughSector,
+};
+pub use asset_location::{
+    AccountLocationSummary, AssetLocationAnalysis, AssetLocationQuery, HoldingLocation, IncomeProfile, LocationAmount, LocationMove,
+};
+pub use fee::{
+    AccountFeePerformance, AccountFeeSchedule, AccountFeeType, CreateAccountFeeSchedule, FeeAnalysis, FeeAnalysisQuery,
+    FeeDragProjection, FeeFlowGeneration, FeeSwapSuggestion, FundMetadata, HoldingFee, NetOfFeeProjection,
+};
+pub use health::{HealthCheckItem, HealthCheckStatus, PortfolioHealthCheck};
+pub use model_portfolio::{
+    AllocationGap, FactorGap, ModelAllocation, ModelComparisonQuery, ModelPortfolio, ModelPortfolioComparison, RiskProfile,
+};
+pub use peer_statistics::{
+    PeerCommonHolding, PeerContext, PeerMetric, PeerMetricDistribution, PeerMetricSummary, PeerPercentile, PeerStatistics,
+};
+pub use csv_import::{
+    ColumnMapping, ColumnMatch, CsvImportPreview, CsvImportTemplate, HoldingField, PdfImportPreview,
+    SaveCsvImportTemplate,
+};
+pub use import_batch::{ImportBatch, ImportBatchChange, ImportChangeSummary, ImportQuery, ImportRollback, IMPORT_BATCH_TABLES};
+pub use inbound_email::{AttachmentOutcome, InboundAttachmentResult, InboundEmail, InboundEmailAddress};
+pub use user_identity::{OidcLoginState, OidcProviderInfo, UserIdentity};
+pub use datasource::{
+    DatasourceMetric, DatasourceQueryRequest, DatasourceRange, DatasourceSearchRequest, DatasourceSeries,
+};
+pub use public_api::{
+    CreatePublicApiToken, CreatedPublicApiToken, PublicApiToken, PublicHistoryQuery, PublicPortfolioValue,
+    PublicRiskSummary, PublicValuePoint,
+};
+pub use portfolio_member::{AddPortfolioMember, PortfolioMember, Role, UpdatePortfolioMember};
+pub use widget::{CreateWidgetToken, CreatedWidgetToken, WidgetGrant, WidgetRisk, WidgetToken, WidgetValue};
+pub use integrity::{IntegrityCheckRequest, IntegrityFinding, IntegrityReport};
+pub use audit::{AuditAction, AuditLogEntry, AuditLogQuery, NewAuditEntry};
+pub use crypto_wallet::{
+    CreateCryptoWallet, CryptoChain, CryptoSyncResult, CryptoWallet, CRYPTO_ASSET_CATEGORY,
+};
+pub use glide_path::{GlidePath, GlidePathPoint, GlidePathRequest, GlidePathSettings};
+pub use calendar::{CalendarEvent, CalendarEventKind, CalendarFeedQuery, RebalanceSchedule};
+pub use job_queue::{JobQueueSummary, QueuedJob};
+pub use rate_limit::{
+    ProviderFailureCounts, ProviderStatus, ProviderStatusLevel, ProvidersStatus, QuotaCalibration, QuotaEstimate,
+    RateLimiterMetrics,
+};
+pub use user_data::{DataErasureQuery, DataErasureSummary, UserDataExport};
+pub use latency::{
+    LatencyDistribution, LatencyExample, LatencyKind, LatencyOffender, LatencyReport, LatencyReportQuery, LatencySample,
+    LatencyStat,
+};
+pub use detected_transaction::{DetectedTransaction, CreateDetectedTransaction, DripGenerationResult, TransactionType, AccountActivity, AccountTruePerformance};
 pub use risk::{
     PositionRisk, RiskAssessment, RiskLevel, PortfolioRisk, PositionRiskContribution,
-    CorrelationPair, CorrelationMatrix,
+    PositionRiskBadge, CorrelationPair, CorrelationMatrix,
 };
-pub use risk_snapshot::{RiskSnapshot, RiskAlert, RiskHistoryParams, AlertQueryParams};
+pub use risk_snapshot::{RiskSnapshot, RiskAlert, RiskHistoryParams, AlertQueryParams, AnomalyQueryParams};
 pub use optimization::{
     OptimizationRecommendation, OptimizationAnalysis, PositionAdjustment, ExpectedImpact,
     RecommendationType, Severity, AdjustmentAction, CurrentMetrics, AnalysisSummary,
-    PortfolioHealth, RiskContribution,
+    PortfolioHealth, RiskContribution, OptimizationConstraints, ConstrainedOptimizationRequest,
+    ConstrainedPosition, ConstrainedOptimization, TradeResidual, TradeRoundingReport,
 };
 pub use llm::{
     LlmUsage, CreateLlmUsage, UserPreferences, UpdateUserPreferences, LlmUsageStats,
+    LlmConsent, LlmConsentStatus, RecordLlmConsent, CURRENT_LLM_CONSENT_VERSION,
 };
 pub use narrative::{PortfolioNarrative, GenerateNarrativeRequest};
-pub use news::{NewsArticle, Sentiment, NewsTheme, PortfolioNewsAnalysis, NewsQueryParams};
+pub use news::{
+    NewsArticle, Sentiment, NewsTheme, PortfolioNewsAnalysis, NewsQueryParams, PortfolioNewsFeed,
+    PortfolioNewsFeedQuery, PortfolioNewsItem,
+};
 pub use qa::{PortfolioQuestion, PortfolioAnswer, Confidence};
 pub use forecast::{
     PortfolioForecast, ForecastPoint, ForecastMethod, HistoricalDataPoint,
@@ -76,7 +207,9 @@ pub use hmm_regime::{
 };
 pub use user_preferences::{
     RiskPreferences, UpdateRiskPreferences, RiskPreferencesResponse,
-    RiskAppetite, SignalSensitivity,
+    RiskAppetite, SignalSensitivity, DigestFrequency, NotificationSettings, UpdateNotificationSettings,
+    CurrencySettings, UpdateCurrencySettings, Locale, LocaleSettings, UpdateLocaleSettings,
+    TimezoneSettings, UpdateTimezoneSettings,
 };
 pub use signal::{
     TradingSignal, SignalType, SignalDirection, SignalFactors, SignalFactor,
@@ -0,0 +1,87 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::models::factor::FactorType;
+
+/// One holding of a model portfolio
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ModelAllocation {
+    pub ticker: String,
+    pub asset_class: String,
+    /// Target weight in percent
+    pub weight: f64,
+}
+
+/// A reference portfolio from the model portfolio library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPortfolio {
+    /// Identifier used in queries, e.g. "sixty_forty"
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub allocations: Vec<ModelAllocation>,
+}
+
+/// Backtested risk and return of a set of weights, rebalanced daily
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RiskProfile {
+    pub total_return: f64,
+    pub annualized_return: f64,
+    pub annualized_volatility: f64,
+    /// Largest peak-to-trough decline, as a negative percentage
+    pub max_drawdown: f64,
+    pub sharpe: Option<f64>,
+    /// 0-100 score from the current risk scoring model
+    pub risk_score: f64,
+}
+
+/// Factor score of the portfolio next to the model's
+#[derive(Debug, Clone, Serialize)]
+pub struct FactorGap {
+    pub factor: FactorType,
+    pub label: String,
+    pub portfolio_score: f64,
+    pub model_score: f64,
+    /// Portfolio minus model, in score points
+    pub difference: f64,
+}
+
+/// Portfolio weight in a model ticker next to the model's target
+#[derive(Debug, Clone, Serialize)]
+pub struct AllocationGap {
+    pub ticker: String,
+    pub asset_class: String,
+    pub portfolio_weight: f64,
+    pub model_weight: f64,
+}
+
+/// The user's portfolio compared to a model portfolio
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelPortfolioComparison {
+    pub portfolio_id: uuid::Uuid,
+    pub model: ModelPortfolio,
+    pub period: String,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// Current weights backtested over the period
+    pub portfolio_profile: RiskProfile,
+    pub model_profile: RiskProfile,
+    pub factor_gaps: Vec<FactorGap>,
+    pub allocation_gaps: Vec<AllocationGap>,
+    /// Share of the portfolio that matches the model's holdings, in percent
+    pub overlap_pct: f64,
+    /// Plain-language gap analysis
+    pub findings: Vec<String>,
+    /// Tickers without price history in the period, left out of the profiles
+    pub missing_prices: Vec<String>,
+}
+
+/// Query parameters for the model portfolio comparison endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct ModelComparisonQuery {
+    /// Model portfolio id (default: "sixty_forty")
+    pub model: Option<String>,
+    /// Lookback such as "1y" or "36m" (default: "3y")
+    pub period: Option<String>,
+}
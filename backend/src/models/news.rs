@@ -61,3 +61,50 @@ pub struct NewsQueryParams {
     /// Force refresh, bypassing cache (default: false)
     pub force: Option<bool>,
 }
+
+/// A headline in a portfolio's news feed, scored for relevance to the holdings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioNewsItem {
+    pub ticker: String,
+    pub title: String,
+    pub url: String,
+    pub source: String,
+    pub published_at: DateTime<Utc>,
+    pub snippet: String,
+    /// 0-1; combines how directly the headline names the ticker, recency and position weight
+    pub relevance_score: f64,
+    /// Position weight in the portfolio (0-1)
+    pub position_weight: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<Sentiment>,
+}
+
+/// One page of a portfolio's news feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioNewsFeed {
+    pub portfolio_id: uuid::Uuid,
+    pub days: i32,
+    pub page: usize,
+    pub page_size: usize,
+    pub total: usize,
+    pub has_more: bool,
+    pub items: Vec<PortfolioNewsItem>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Query parameters for a portfolio's news feed
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PortfolioNewsFeedQuery {
+    /// Number of days to look back (default: 7)
+    pub days: Option<i32>,
+    /// Page number, starting at 1 (default: 1)
+    pub page: Option<usize>,
+    /// Items per page (default: 20, max: 100)
+    pub page_size: Option<usize>,
+    /// Restrict to one held ticker
+    pub ticker: Option<String>,
+    /// Include a sentiment label per headline (default: false)
+    pub sentiment: Option<bool>,
+    /// Bypass the cached feed (default: false)
+    pub force: Option<bool>,
+}
@@ -74,6 +74,9 @@ pub struct OptimizationRecommendation {
     pub affected_positions: Vec<PositionAdjustment>,
     pub expected_impact: ExpectedImpact,
     pub suggested_actions: Vec<String>,
+    /// Cash left over after rounding the trades to whole shares
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounding: Option<TradeRoundingReport>,
 }
 
 /// Complete optimization analysis for a portfolio
@@ -135,3 +138,86 @@ pub struct RiskContribution {
     pub is_excessive: bool,       // Contributing >20% of total risk
 }
 
+
+/// User constraints for the rebalancer. Weights are percentages of portfolio value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OptimizationConstraints {
+    /// Maximum weight of any single position
+    #[serde(default)]
+    pub max_position_weight: Option<f64>,
+    /// Maximum combined weight per sector, keyed by the holdings' industry
+    #[serde(default)]
+    pub sector_caps: std::collections::HashMap<String, f64>,
+    /// Minimum weight to hold in cash
+    #[serde(default)]
+    pub min_cash_pct: Option<f64>,
+    /// Tickers that must not be sold (e.g. to avoid realizing gains)
+    #[serde(default)]
+    pub do_not_sell: Vec<String>,
+}
+
+/// Body of a constrained optimization request. Without `constraints` the
+/// portfolio's saved constraints are used.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConstrainedOptimizationRequest {
+    #[serde(default)]
+    pub constraints: Option<OptimizationConstraints>,
+    /// Weight of the volatility penalty against turnover (default: 1.0)
+    #[serde(default)]
+    pub risk_aversion: Option<f64>,
+    /// Steer the equity share toward the saved glide path, if any (default: true)
+    #[serde(default)]
+    pub use_glide_path: Option<bool>,
+}
+
+/// Target weight and trade for one position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstrainedPosition {
+    pub ticker: String,
+    pub holding_name: Option<String>,
+    pub sector: Option<String>,
+    pub current_weight: f64,
+    pub target_weight: f64,
+    pub trade_value: f64,
+    pub action: AdjustmentAction,
+    pub do_not_sell: bool,
+    pub shares_change: Option<f64>,
+}
+
+/// Portfolio weights solved under user constraints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstrainedOptimization {
+    pub portfolio_id: String,
+    pub total_value: f64,
+    pub constraints: OptimizationConstraints,
+    pub target_cash_weight: f64,
+    pub positions: Vec<ConstrainedPosition>,
+    pub turnover: f64,
+    pub iterations: usize,
+    /// False when the constraints could not all be met at once
+    pub converged: bool,
+    pub warnings: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounding: Option<TradeRoundingReport>,
+    /// Equity share targeted from the glide path, in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equity_target: Option<f64>,
+}
+
+/// A trade whose executed amount differs from its target after share rounding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeResidual {
+    pub ticker: String,
+    pub target_amount: f64,
+    pub executed_amount: f64,
+    /// Positive when less is spent than planned, negative when less cash is raised
+    pub residual: f64,
+}
+
+/// Effect of rounding a trade list to whole shares where fractional shares are unsupported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRoundingReport {
+    /// Net cash left over compared with the unrounded trades
+    pub residual_cash: f64,
+    pub trades: Vec<TradeResidual>,
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::InsiderTransaction;
+
+/// An institution's reported stake in a ticker
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InstitutionalHolder {
+    pub name: String,
+    /// % of shares outstanding
+    pub pct_held: Option<f64>,
+    pub shares: Option<i64>,
+    pub report_date: Option<NaiveDate>,
+}
+
+/// Insider buying and selling over a recent window
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct InsiderActivity {
+    pub period_days: i32,
+    pub buys: i32,
+    pub sells: i32,
+    /// Shares bought minus shares sold
+    pub net_shares: i64,
+}
+
+/// Insider and institutional ownership for a ticker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnershipSnapshot {
+    pub ticker: String,
+    /// % of shares outstanding held by insiders
+    pub insider_ownership_pct: Option<f64>,
+    /// % of shares outstanding held by institutions
+    pub institutional_ownership_pct: Option<f64>,
+    pub institution_count: Option<i64>,
+    pub insider_activity: InsiderActivity,
+    /// Insider purchases and sales within the activity window, newest first
+    pub recent_insider_transactions: Vec<InsiderTransaction>,
+    pub top_institutional_holders: Vec<InstitutionalHolder>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OwnershipQuery {
+    /// Insider activity window in days (default: 90)
+    pub days: Option<i32>,
+    /// Bypass the cached snapshot (default: false)
+    pub force: Option<bool>,
+}
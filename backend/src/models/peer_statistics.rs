@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Portfolio-level risk metric tracked across opted-in portfolios
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerMetric {
+    Volatility,
+    MaxDrawdown,
+    Beta,
+    Sharpe,
+    RiskScore,
+}
+
+impl PeerMetric {
+    pub const ALL: [PeerMetric; 5] = [
+        PeerMetric::Volatility,
+        PeerMetric::MaxDrawdown,
+        PeerMetric::Beta,
+        PeerMetric::Sharpe,
+        PeerMetric::RiskScore,
+    ];
+
+    /// Key stored in peer_metric_distributions.metric
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PeerMetric::Volatility => "volatility",
+            PeerMetric::MaxDrawdown => "max_drawdown",
+            PeerMetric::Beta => "beta",
+            PeerMetric::Sharpe => "sharpe",
+            PeerMetric::RiskScore => "risk_score",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_str() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PeerMetric::Volatility => "Volatility",
+            PeerMetric::MaxDrawdown => "Max Drawdown",
+            PeerMetric::Beta => "Beta",
+            PeerMetric::Sharpe => "Sharpe Ratio",
+            PeerMetric::RiskScore => "Risk Score",
+        }
+    }
+
+    /// Whether a higher value means more risk. Drawdowns are negative, so a
+    /// lower value is riskier; a higher Sharpe ratio is better, not riskier.
+    pub fn higher_is_riskier(&self) -> bool {
+        !matches!(self, PeerMetric::MaxDrawdown | PeerMetric::Sharpe)
+    }
+}
+
+/// Stored distribution of one metric: the 1st..99th percentile cut points
+#[derive(Debug, Clone, FromRow)]
+pub struct PeerMetricDistribution {
+    pub metric: String,
+    pub sample_size: i32,
+    pub percentiles: Vec<f64>,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Median and interquartile range of a metric across peers
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerMetricSummary {
+    pub metric: PeerMetric,
+    pub label: String,
+    pub sample_size: i32,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+}
+
+/// A ticker held by many opted-in portfolios
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PeerCommonHolding {
+    pub ticker: String,
+    pub portfolio_count: i32,
+    /// Share of opted-in portfolios holding the ticker, in percent
+    pub portfolio_share: f64,
+}
+
+/// Anonymous aggregate statistics across opted-in portfolios
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerStatistics {
+    pub computed_at: Option<DateTime<Utc>>,
+    pub distributions: Vec<PeerMetricSummary>,
+    pub common_holdings: Vec<PeerCommonHolding>,
+}
+
+/// Where one portfolio metric falls among peers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerPercentile {
+    pub metric: PeerMetric,
+    pub label: String,
+    pub value: f64,
+    pub peer_median: f64,
+    /// Share of peer portfolios with less risk on this metric, 0-100
+    pub riskier_than_pct: f64,
+}
+
+/// Percentile context attached to the portfolio risk response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerContext {
+    /// Smallest sample behind any of the percentiles
+    pub sample_size: i32,
+    pub computed_at: DateTime<Utc>,
+    pub percentiles: Vec<PeerPercentile>,
+    /// e.g. "Your portfolio is riskier than 78% of rustfolio portfolios"
+    pub summary: Option<String>,
+}
@@ -0,0 +1,141 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Shares of one ticker acquired together at a single cost, still held
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaxLot {
+    pub account_id: Uuid,
+    pub ticker: String,
+    pub acquired_date: NaiveDate,
+    pub quantity: f64,
+    pub cost_per_share: f64,
+    /// "opening_balance" for lots seeded from the first holdings snapshot,
+    /// otherwise "transaction"
+    pub source: String,
+    pub transaction_id: Option<Uuid>,
+}
+
+/// The part of a lot closed by a sale
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RealizedLot {
+    pub account_id: Uuid,
+    pub ticker: String,
+    pub acquired_date: NaiveDate,
+    pub sold_date: NaiveDate,
+    pub quantity: f64,
+    pub cost_basis: f64,
+    pub proceeds: f64,
+    pub gain: f64,
+    pub holding_days: i64,
+    /// Held more than one year
+    pub long_term: bool,
+    pub sell_transaction_id: Uuid,
+    /// Transaction that opened the lot; `None` for opening balances
+    pub lot_transaction_id: Option<Uuid>,
+}
+
+/// Shares of one lot designated to be closed by a sale (specific-lot identification)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LotSelection {
+    /// Transaction that opened the lot; `None` for the opening balance lot
+    pub lot_transaction_id: Option<Uuid>,
+    pub quantity: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateLotSelections {
+    pub selections: Vec<LotSelection>,
+}
+
+/// A sale at a loss with replacement shares bought within 30 days before or after it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WashSale {
+    pub sell_transaction_id: Uuid,
+    pub account_id: Uuid,
+    pub ticker: String,
+    pub sold_date: NaiveDate,
+    pub quantity_sold: f64,
+    /// Realized loss of the sale, as a positive amount
+    pub loss: f64,
+    /// Portion of the loss disallowed (proportional to the shares replaced)
+    pub disallowed_loss: f64,
+    pub replacement_quantity: f64,
+    pub replacement_transaction_ids: Vec<Uuid>,
+}
+
+/// Unrealized P&L of one open lot
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenLotPnl {
+    #[serde(flatten)]
+    pub lot: TaxLot,
+    pub current_price: f64,
+    pub cost_basis: f64,
+    pub market_value: f64,
+    pub unrealized_gain: f64,
+    pub unrealized_gain_pct: f64,
+    /// Change in value since the previous close, when two closes are available
+    pub day_change: Option<f64>,
+}
+
+/// Per-position P&L, aggregated across accounts
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionPnl {
+    pub ticker: String,
+    pub quantity: f64,
+    pub cost_basis: f64,
+    pub market_value: f64,
+    pub unrealized_gain: f64,
+    pub unrealized_gain_pct: f64,
+    /// Realized gain on this ticker within the requested date range
+    pub realized_gain: f64,
+    pub day_change: Option<f64>,
+    pub day_change_pct: Option<f64>,
+    /// Share of the portfolio's day change attributable to this position (%)
+    pub day_change_contribution_pct: Option<f64>,
+    pub lots: Vec<OpenLotPnl>,
+}
+
+/// Realized P&L for one reporting period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RealizedPeriodPnl {
+    /// e.g. "2026-03", "2026-Q1", or "2026"
+    pub period: String,
+    pub proceeds: f64,
+    pub cost_basis: f64,
+    pub realized_gain: f64,
+    pub short_term_gain: f64,
+    pub long_term_gain: f64,
+    /// Losses in this period disallowed by the wash sale rule
+    pub wash_sale_disallowed_loss: f64,
+    pub lots_closed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortfolioPnl {
+    pub portfolio_id: Uuid,
+    pub period: String,
+    pub total_cost_basis: f64,
+    pub total_market_value: f64,
+    pub total_unrealized_gain: f64,
+    pub total_realized_gain: f64,
+    pub total_day_change: Option<f64>,
+    pub total_day_change_pct: Option<f64>,
+    pub positions: Vec<PositionPnl>,
+    pub realized_by_period: Vec<RealizedPeriodPnl>,
+    pub realized_lots: Vec<RealizedLot>,
+    pub wash_sales: Vec<WashSale>,
+    /// Tickers whose recorded sells exceed the shares tracked in lots
+    pub unmatched_sells: Vec<String>,
+}
+
+/// Query parameters for the P&L endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct PnlQuery {
+    /// Realized P&L grouping: "month" (default), "quarter", or "year"
+    pub period: Option<String>,
+    /// Only include sales on or after this date
+    pub from: Option<NaiveDate>,
+    /// Only include sales on or before this date
+    pub to: Option<NaiveDate>,
+}
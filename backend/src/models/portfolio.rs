@@ -10,6 +10,10 @@ pub struct Portfolio {
     #[serde(skip_serializing)]
     pub user_id: Uuid,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Set when the portfolio is archived (hidden from lists, skipped by jobs)
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Source portfolio when this one was created via clone
+    pub cloned_from: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,6 +26,25 @@ pub struct UpdatePortfolio {
     pub name: String
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct ClonePortfolio {
+    /// Name for the copy (default: "<source name> (copy)")
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PortfolioListQuery {
+    /// Include archived portfolios (default: false)
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClonePortfolioResponse {
+    pub portfolio: Portfolio,
+    pub holdings_copied: u64,
+}
+
 impl Portfolio {
     pub(crate) fn new(name: String, user_id: Uuid) -> Self {
         Self {
@@ -29,6 +52,8 @@ impl Portfolio {
             name,
             user_id,
             created_at: chrono::Utc::now(),
+            archived_at: None,
+            cloned_from: None,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Access level of a user, either instance-wide or on a shared portfolio.
+///
+/// Roles are ordered: each one can do everything the previous one can.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read-only access
+    Viewer,
+    /// Can change accounts, positions, thresholds and snapshots
+    Editor,
+    /// Can also rename, archive and share the portfolio
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "editor" => Ok(Role::Editor),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("Unknown role '{}'; expected viewer, editor or admin", other)),
+        }
+    }
+}
+
+/// A user with access to a portfolio. The owner is listed with the admin role.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PortfolioMember {
+    pub portfolio_id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub role: Role,
+    pub is_owner: bool,
+    pub invited_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Share a portfolio with an existing user, identified by email
+#[derive(Debug, Deserialize)]
+pub struct AddPortfolioMember {
+    pub email: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePortfolioMember {
+    pub role: Role,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roles_are_ordered_by_privilege() {
+        assert!(Role::Viewer < Role::Editor);
+        assert!(Role::Editor < Role::Admin);
+        assert_eq!([Role::Admin, Role::Viewer, Role::Editor].iter().max(), Some(&Role::Admin));
+    }
+
+    #[test]
+    fn test_role_round_trips_through_text() {
+        for role in [Role::Viewer, Role::Editor, Role::Admin] {
+            assert_eq!(role.as_str().parse::<Role>(), Ok(role));
+            assert_eq!(serde_json::to_value(role).unwrap(), serde_json::json!(role.as_str()));
+        }
+        assert_eq!(" Editor ".parse::<Role>(), Ok(Role::Editor));
+        assert!("owner".parse::<Role>().is_err());
+    }
+}
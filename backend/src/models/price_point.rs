@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::external::price_provider::IntradayInterval;
+
 // Represents a historical price for a given ticker.
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct PricePoint {
@@ -26,3 +28,218 @@ impl PricePoint {
         }
     }
 }
+
+/// One stored intraday bar
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct IntradayPrice {
+    /// Start of the bar
+    pub bar_time: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IntradayQuery {
+    /// Bar size; 5min by default
+    #[serde(default)]
+    pub interval: IntradayInterval,
+}
+
+/// The latest session's intraday bars with the move since the previous close
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntradayPrices {
+    pub ticker: String,
+    pub interval: IntradayInterval,
+    pub currency: String,
+    pub session_date: NaiveDate,
+    /// Last daily close before the session, when stored
+    pub previous_close: Option<f64>,
+    pub last_price: f64,
+    pub change: Option<f64>,
+    pub change_pct: Option<f64>,
+    pub bars: Vec<IntradayPrice>,
+    /// When the bars were last fetched from the provider
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// A stored split or cash dividend
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct CorporateAction {
+    pub ticker: String,
+    /// First session trading without the dividend, or on the split basis
+    pub ex_date: NaiveDate,
+    /// "split" or "dividend"
+    pub action_type: String,
+    /// New shares per old share for a split, cash per share for a dividend
+    pub value: f64,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// 52-week high/low derived from stored closes, with where the latest close sits
+/// in that range. Percentages are in percent.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct FiftyTwoWeekRange {
+    pub high: f64,
+    pub low: f64,
+    pub current: f64,
+    /// % below the 52-week high (0 at the high)
+    pub pct_off_high: f64,
+    /// % above the 52-week low (0 at the low)
+    pub pct_above_low: f64,
+}
+
+impl FiftyTwoWeekRange {
+    pub fn new(high: f64, low: f64, current: f64) -> Option<Self> {
+        if high <= 0.0 || low <= 0.0 || current <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            high,
+            low,
+            current,
+            pct_off_high: (high - current) / high * 100.0,
+            pct_above_low: (current - low) / low * 100.0,
+        })
+    }
+
+    /// Range over the 52 weeks ending at the latest of `points` (in any order)
+    pub fn from_points(points: &[PricePoint]) -> Option<Self> {
+        use bigdecimal::ToPrimitive;
+
+        let latest = points.iter().max_by_key(|p| p.date)?;
+        let cutoff = latest.date - chrono::Duration::days(365);
+        let closes: Vec<f64> = points
+            .iter()
+            .filter(|p| p.date > cutoff)
+            .filter_map(|p| p.close_price.to_f64())
+            .collect();
+        let high = closes.iter().copied().fold(f64::MIN, f64::max);
+        let low = closes.iter().copied().fold(f64::MAX, f64::min);
+        Self::new(high, low, latest.close_price.to_f64()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn point(date: &str, close: &str) -> PricePoint {
+        PricePoint::new(
+            "AAPL".to_string(),
+            NaiveDate::from_str(date).unwrap(),
+            BigDecimal::from_str(close).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_range_percentages() {
+        let range = FiftyTwoWeekRange::new(200.0, 100.0, 150.0).unwrap();
+        assert!((range.pct_off_high - 25.0).abs() < 1e-9);
+        assert!((range.pct_above_low - 50.0).abs() < 1e-9);
+        assert!(FiftyTwoWeekRange::new(200.0, 0.0, 150.0).is_none());
+    }
+
+    #[test]
+    fn test_from_points_ignores_closes_older_than_a_year() {
+        let points = vec![
+            point("2025-01-10", "300"),
+            point("2025-06-01", "120"),
+            point("2025-09-01", "180"),
+            point("2026-03-01", "150"),
+        ];
+        let range = FiftyTwoWeekRange::from_points(&points).unwrap();
+        assert_eq!(range.high, 180.0);
+        assert_eq!(range.low, 120.0);
+        assert_eq!(range.current, 150.0);
+        assert!(FiftyTwoWeekRange::from_points(&[]).is_none());
+    }
+}
+
+/// A listed symbol as reported by the provider's symbol search
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct TickerMetadata {
+    pub ticker: String,
+    pub name: String,
+    /// Listing exchange, when the provider reports it or the symbol suffix implies it
+    pub exchange: Option<String>,
+    /// e.g. "Equity", "ETF", "Crypto"
+    pub asset_type: String,
+    pub region: Option<String>,
+    pub currency: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TickerSearchQuery {
+    pub q: String,
+    /// Most matches returned (default 10, at most 50)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TickerSearchResponse {
+    pub query: String,
+    pub results: Vec<TickerMetadata>,
+    /// Answered from stored metadata without calling the provider
+    pub cached: bool,
+}
+
+/// Trading days missing between two stored closes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PriceGap {
+    /// Last stored close before the gap
+    pub after: NaiveDate,
+    /// First stored close after the gap
+    pub before: NaiveDate,
+    pub missing_days: usize,
+}
+
+/// A date stored under more than one spelling of the ticker, e.g. "aapl" and "AAPL"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicatePriceDate {
+    pub date: NaiveDate,
+    pub tickers: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InvalidClose {
+    pub date: NaiveDate,
+    pub close: f64,
+}
+
+/// Where a ticker's stored history falls short
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceQualityReport {
+    pub ticker: String,
+    pub first_date: Option<NaiveDate>,
+    pub last_date: Option<NaiveDate>,
+    pub points: usize,
+    /// Trading days from `first_date` through `last_date`
+    pub expected_points: usize,
+    pub missing_days: usize,
+    pub gaps: Vec<PriceGap>,
+    pub duplicate_dates: Vec<DuplicatePriceDate>,
+    /// Zero or negative closes
+    pub invalid_closes: Vec<InvalidClose>,
+}
+
+impl PriceQualityReport {
+    pub fn is_clean(&self) -> bool {
+        self.gaps.is_empty() && self.duplicate_dates.is_empty() && self.invalid_closes.is_empty()
+    }
+}
+
+/// Outcome of re-fetching a ticker's gaps and invalid closes
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceRepairResult {
+    /// Missing days now stored
+    pub filled: usize,
+    /// Invalid closes replaced by the provider's
+    pub replaced: usize,
+    /// The history after the repair; days the provider has no close for remain
+    pub report: PriceQualityReport,
+}
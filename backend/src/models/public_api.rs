@@ -0,0 +1,65 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A read-only public API token. The token itself is only returned once, on creation.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PublicApiToken {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePublicApiToken {
+    /// Label shown in the token list, e.g. "Grafana"
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedPublicApiToken {
+    #[serde(flatten)]
+    pub token: PublicApiToken,
+    /// Send as `Authorization: Bearer <token>`; it cannot be shown again
+    pub secret: String,
+}
+
+/// Latest value of a portfolio and its change since the previous valuation
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicPortfolioValue {
+    pub portfolio_id: Uuid,
+    pub name: String,
+    /// None before the portfolio's first valuation
+    pub as_of: Option<NaiveDate>,
+    pub value: f64,
+    pub change: Option<f64>,
+    pub change_pct: Option<f64>,
+}
+
+/// Latest portfolio risk snapshot, reduced to its headline figures
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicRiskSummary {
+    pub portfolio_id: Uuid,
+    pub as_of: NaiveDate,
+    pub risk_score: f64,
+    pub risk_level: String,
+    pub volatility: f64,
+    pub max_drawdown: f64,
+    pub beta: Option<f64>,
+    pub sharpe: Option<f64>,
+    pub var_95: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PublicValuePoint {
+    pub date: NaiveDate,
+    pub value: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublicHistoryQuery {
+    /// Days of history up to the latest valuation (default 365)
+    pub days: Option<i64>,
+}
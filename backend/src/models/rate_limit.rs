@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Outcome of calibrating the price API rate limiter against a provider's
+/// reported quota
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaCalibration {
+    pub provider: String,
+    /// Plan limit the provider reported, in requests per minute
+    pub reported_limit_per_minute: Option<u32>,
+    /// Requests the provider reported left in its current window
+    pub reported_remaining: Option<u32>,
+    pub previous_per_minute: u32,
+    pub applied_per_minute: u32,
+    pub calibrated_at: DateTime<Utc>,
+}
+
+/// Point-in-time state of the price API rate limiter
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimiterMetrics {
+    pub max_concurrent: usize,
+    /// Concurrency permits not currently held
+    pub available_permits: usize,
+    /// Budget the limiter was started with
+    pub configured_per_minute: u32,
+    /// Budget currently enforced, after any calibration
+    pub requests_per_minute: u32,
+    /// Requests still available in the trailing minute
+    pub tokens_remaining: u32,
+    /// Callers waiting for a permit or for the minimum delay to pass
+    pub queue_depth: usize,
+    pub requests_granted: u64,
+    /// Requests that had to wait for the minimum delay
+    pub requests_delayed: u64,
+    /// Requests the provider rejected as rate limited
+    pub rejections: u64,
+    pub last_calibration: Option<QuotaCalibration>,
+    /// Requests granted since midnight UTC
+    pub requests_today: u32,
+    /// Provider's daily allowance (`PRICE_PROVIDER_DAILY_LIMIT`), when configured
+    pub daily_limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderStatusLevel {
+    Ok,
+    /// Recent errors, or no successful fetch for longer than expected
+    Degraded,
+    /// Circuit open: calls are skipping the provider
+    Down,
+    /// No successful fetch on record yet
+    Unknown,
+}
+
+/// Failures still remembered in the in-memory failure cache
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProviderFailureCounts {
+    pub not_found: usize,
+    pub rate_limited: usize,
+    pub api_error: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderStatus {
+    pub name: String,
+    /// "price", "price_chain" (a member of the composite chain), "fundamentals" or "fx"
+    pub role: String,
+    pub status: ProviderStatusLevel,
+    /// Only tracked for members of the composite chain
+    pub circuit_open: Option<bool>,
+    pub consecutive_failures: Option<u32>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub recent_failures: ProviderFailureCounts,
+}
+
+/// Remaining price API budget according to the rate limiter
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaEstimate {
+    pub requests_per_minute: u32,
+    pub tokens_remaining: u32,
+    pub requests_today: u32,
+    pub daily_limit: Option<u32>,
+    /// The daily limit less today's requests, or without one, the most the
+    /// limiter lets through before midnight UTC
+    pub estimated_daily_remaining: u32,
+    pub rejections: u64,
+}
+
+/// Response of GET /api/providers/status
+#[derive(Debug, Clone, Serialize)]
+pub struct ProvidersStatus {
+    pub checked_at: DateTime<Utc>,
+    pub providers: Vec<ProviderStatus>,
+    pub quota: QuotaEstimate,
+    /// Tickers the price refresh is backing off from after repeated failures
+    pub tickers_backing_off: usize,
+}
@@ -0,0 +1,64 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// When a simulated portfolio is brought back to its target weights
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum RebalancePolicy {
+    /// Never rebalance
+    BuyAndHold,
+    /// First trading day of each month
+    Monthly,
+    /// First trading day of each quarter
+    Quarterly,
+    /// Whenever any position drifts more than `band` percentage points from target
+    Threshold { band: f64 },
+}
+
+/// Outcome of one policy over the simulated history
+#[derive(Debug, Clone, Serialize)]
+pub struct RebalancePolicyResult {
+    pub policy: RebalancePolicy,
+    pub rebalances: usize,
+    /// Net of trading costs, in percent
+    pub total_return: f64,
+    pub annualized_return: f64,
+    pub annualized_volatility: f64,
+    /// One-way traded value over the period as a percent of average portfolio value
+    pub turnover: f64,
+    pub annual_turnover: f64,
+    /// Trading costs as a percent of starting value
+    pub costs: f64,
+    /// Annualized return minus that of the portfolio held exactly at target, in points
+    pub tracking_difference: f64,
+    /// Annualized standard deviation of the daily return difference, in percent
+    pub tracking_error: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RebalanceSimulation {
+    pub portfolio_id: Uuid,
+    pub period: String,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// Current weights, used as the targets (0-1)
+    pub target_weights: Vec<(String, f64)>,
+    pub cost_bps: f64,
+    pub results: Vec<RebalancePolicyResult>,
+    /// Policy with the smallest combined tracking difference and tracking error
+    pub recommended_policy: Option<RebalancePolicy>,
+    /// Tickers without price history in the period, left out of the simulation
+    pub missing_prices: Vec<String>,
+}
+
+/// Query parameters for the rebalancing simulation endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct RebalanceSimulationQuery {
+    /// Lookback such as "1y" or "36m" (default: "3y")
+    pub period: Option<String>,
+    /// Drift band for threshold rebalancing, in percentage points (default: 5)
+    pub band: Option<f64>,
+    /// Trading cost per traded dollar, in basis points (default: 10)
+    pub cost_bps: Option<f64>,
+}
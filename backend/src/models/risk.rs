@@ -64,6 +64,37 @@ pub struct RiskAssessment {
 
     /// Risk level classification
     pub risk_level: RiskLevel,
+
+    /// Version of the scoring model that produced `risk_score`
+    #[serde(default = "default_scoring_version")]
+    pub scoring_version: i32,
+}
+
+/// Results cached before scores were versioned were all produced by v1.
+fn default_scoring_version() -> i32 {
+    1
+}
+
+/// One weighted input of a risk scoring model.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskScoreComponent {
+    /// Metric name, e.g. "volatility"
+    pub metric: String,
+    /// Points this metric contributes at or beyond `saturation`
+    pub max_points: f64,
+    /// Absolute metric value that earns the full points, in the metric's unit
+    pub saturation: f64,
+    pub description: String,
+}
+
+/// Description of a risk score methodology version.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskScoringModel {
+    pub version: i32,
+    /// Whether new scores are computed with this version
+    pub current: bool,
+    pub description: String,
+    pub components: Vec<RiskScoreComponent>,
 }
 
 /// Risk level classification based on score.
@@ -105,28 +136,53 @@ pub struct PortfolioRisk {
     /// Total portfolio market value
     pub total_value: f64,
 
-    /// Weighted average volatility across positions
+    /// Annualized volatility of the portfolio's daily returns; the weighted
+    /// average across positions when there is too little history
     pub portfolio_volatility: f64,
 
-    /// Maximum drawdown across portfolio
+    /// Maximum drawdown of the portfolio's daily returns, with the same fallback
     pub portfolio_max_drawdown: f64,
 
-    /// Portfolio beta (weighted average)
+    /// Weighted average of the position betas
     pub portfolio_beta: Option<f64>,
 
-    /// Portfolio Sharpe ratio (weighted average)
+    /// Beta from regressing the portfolio's daily returns on the benchmark's.
+    /// Unlike `portfolio_beta` it reflects cash and weights changing between
+    /// snapshots, and is meant to replace it.
+    #[serde(default)]
+    pub portfolio_regression_beta: Option<f64>,
+
+    /// Annualized alpha of that regression, as a percentage
+    #[serde(default)]
+    pub portfolio_alpha: Option<f64>,
+
+    /// R² of that regression: the share of the portfolio's variance the benchmark explains
+    #[serde(default)]
+    pub portfolio_r_squared: Option<f64>,
+
+    /// Portfolio Sharpe ratio, from the portfolio's daily returns with cash
+    /// earning the risk-free rate
     pub portfolio_sharpe: Option<f64>,
 
-    /// Portfolio VaR at 95% confidence (weighted average)
+    /// Portfolio Sortino ratio, from the same daily returns
+    #[serde(default)]
+    pub portfolio_sortino: Option<f64>,
+
+    /// Weighted average of the position Sharpe ratios. An approximation only:
+    /// it ignores diversification between positions and the cash held.
+    #[serde(default)]
+    pub portfolio_sharpe_weighted_average: Option<f64>,
+
+    /// Portfolio VaR at 95% confidence, from the daily returns (weighted average as fallback)
     pub portfolio_var_95: Option<f64>,
 
-    /// Portfolio VaR at 99% confidence (weighted average)
+    /// Portfolio VaR at 99% confidence, from the daily returns (weighted average as fallback)
     pub portfolio_var_99: Option<f64>,
 
-    /// Portfolio Expected Shortfall at 95% confidence (weighted average)
+    /// Portfolio Expected Shortfall at 95% confidence, from the daily returns (weighted average as fallback)
     pub portfolio_expected_shortfall_95: Option<f64>,
 
-    /// Portfolio Expected Shortfall at 99% confidence (weighted average)
+    /// Portfolio Expected Shortfall at 99% confidence, from the daily returns (weighted average as fallback)
     pub portfolio_expected_shortfall_99: Option<f64>,
 
     /// Overall portfolio risk score
@@ -135,6 +191,10 @@ pub struct PortfolioRisk {
     /// Risk level classification
     pub risk_level: RiskLevel,
 
+    /// Version of the scoring model that produced `portfolio_risk_score`
+    #[serde(default = "default_scoring_version")]
+    pub scoring_version: i32,
+
     /// Individual position risk contributions
     pub position_risks: Vec<PositionRiskContribution>,
 }
@@ -148,6 +208,17 @@ pub struct PositionRiskContribution {
     pub risk_assessment: RiskAssessment,
 }
 
+/// Compact per-position risk for list views, read from caches only
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PositionRiskBadge {
+    pub ticker: String,
+    /// `None` until the risk job has assessed the position
+    pub risk_score: Option<f64>,
+    pub risk_level: Option<RiskLevel>,
+    /// Latest close vs the previous stored close, in percent
+    pub day_change: Option<f64>,
+}
+
 
 /// Correlation pair between two tickers.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -283,6 +354,12 @@ pub struct PortfolioRiskWithViolations {
     pub portfolio_risk: PortfolioRisk,
     pub thresholds: RiskThresholdSettings,
     pub violations: Vec<ThresholdViolation>,
+    /// Utilization of the portfolio's risk budget, when one is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_utilization: Option<crate::models::RiskBudgetUtilization>,
+    /// Percentiles among opted-in portfolios, when the user shares anonymous statistics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_context: Option<crate::models::PeerContext>,
 }
 
 /// Portfolio-level correlation statistics
@@ -385,9 +462,19 @@ pub struct DownsideInterpretation {
 pub struct PortfolioDownsideRisk {
     pub portfolio_id: String,
 
-    /// Aggregated portfolio-level downside metrics
+    /// Portfolio-level downside metrics, from the portfolio's daily returns
+    /// with cash earning the risk-free rate
     pub portfolio_metrics: DownsideRiskMetrics,
 
+    /// Weighted average of the position Sortino ratios, an approximation that
+    /// ignores diversification and cash
+    #[serde(default)]
+    pub weighted_average_sortino: Option<f64>,
+
+    /// Weighted average of the position Sharpe ratios, likewise approximate
+    #[serde(default)]
+    pub weighted_average_sharpe: Option<f64>,
+
     /// Individual position downside risk contributions
     pub position_downside_risks: Vec<PositionDownsideContribution>,
 
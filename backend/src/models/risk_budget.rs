@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Risk measure a budget is expressed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskBudgetMetric {
+    /// Annualized volatility, in percent
+    Volatility,
+    /// 95% value at risk as a positive percent of value
+    Var95,
+}
+
+impl RiskBudgetMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskBudgetMetric::Volatility => "volatility",
+            RiskBudgetMetric::Var95 => "var_95",
+        }
+    }
+
+    pub fn from_string(s: &str) -> Self {
+        match s {
+            "var_95" => RiskBudgetMetric::Var95,
+            _ => RiskBudgetMetric::Volatility,
+        }
+    }
+}
+
+/// Sub-budget for the positions tagged with `tag`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SleeveBudget {
+    pub tag: String,
+    pub budget: f64,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RiskBudget {
+    pub portfolio_id: Uuid,
+    pub metric: String,
+    pub total_budget: f64,
+    /// A position belongs to the first sleeve whose tag it carries
+    pub sleeves: Json<Vec<SleeveBudget>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetRiskBudget {
+    pub metric: RiskBudgetMetric,
+    pub total_budget: f64,
+    #[serde(default)]
+    pub sleeves: Vec<SleeveBudget>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SleeveUtilization {
+    /// Sleeve tag, or "unassigned" for positions in no budgeted sleeve
+    pub sleeve: String,
+    pub budget: Option<f64>,
+    /// Weighted risk of the sleeve's positions, in the budget's metric
+    pub risk: f64,
+    pub utilization_pct: Option<f64>,
+    pub over_budget: bool,
+    pub tickers: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskBudgetUtilization {
+    pub metric: RiskBudgetMetric,
+    pub total_budget: f64,
+    pub total_risk: f64,
+    pub utilization_pct: f64,
+    pub over_budget: bool,
+    pub sleeves: Vec<SleeveUtilization>,
+}
@@ -1,6 +1,7 @@
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 use sqlx::FromRow;
 use uuid::Uuid;
 
@@ -22,11 +23,78 @@ pub struct RiskSnapshot {
     pub expected_shortfall_99: Option<BigDecimal>,
     pub risk_score: BigDecimal,
     pub risk_level: String,
+    /// Risk scoring model version that produced `risk_score`
+    pub scoring_version: i32,
     pub total_value: Option<BigDecimal>,
     pub market_value: Option<BigDecimal>,
+    /// "daily", or "weekly"/"monthly" once compacted by the retention job
+    pub granularity: String,
+    /// Copied from the previous snapshot because none of its inputs changed
+    pub carried_forward: bool,
+    /// Why the portfolio's risk score moved materially since the previous
+    /// snapshot; `None` when it did not or there was nothing to compare against
+    pub change_drivers: Option<Json<Vec<ChangeDriver>>>,
+    /// Average pairwise correlation between positions (portfolio rows only)
+    pub avg_correlation: Option<BigDecimal>,
     pub created_at: DateTime<Utc>,
 }
 
+/// A structural reason for a portfolio's risk score to move between snapshots.
+/// Weights are fractions of the portfolio's market value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChangeDriver {
+    /// A position held now that the previous snapshot did not cover
+    PositionAdded { ticker: String, weight: f64 },
+    /// A position in the previous snapshot that is no longer held
+    PositionRemoved { ticker: String, previous_weight: f64 },
+    /// A large price move in a held position between the two snapshot dates
+    PriceShock { ticker: String, price_change_pct: f64, weight: f64 },
+    /// A position whose share of the portfolio changed noticeably
+    WeightShift { ticker: String, previous_weight: f64, weight: f64 },
+}
+
+impl ChangeDriver {
+    pub fn ticker(&self) -> &str {
+        match self {
+            ChangeDriver::PositionAdded { ticker, .. }
+            | ChangeDriver::PositionRemoved { ticker, .. }
+            | ChangeDriver::PriceShock { ticker, .. }
+            | ChangeDriver::WeightShift { ticker, .. } => ticker,
+        }
+    }
+
+    /// One-line description for history views and narrative prompts
+    pub fn describe(&self) -> String {
+        match self {
+            ChangeDriver::PositionAdded { ticker, weight } => {
+                format!("{} added ({:.1}% of the portfolio)", ticker, weight * 100.0)
+            }
+            ChangeDriver::PositionRemoved { ticker, previous_weight } => {
+                format!("{} removed (was {:.1}% of the portfolio)", ticker, previous_weight * 100.0)
+            }
+            ChangeDriver::PriceShock { ticker, price_change_pct, weight } => {
+                format!("{} price moved {:+.1}% ({:.1}% of the portfolio)", ticker, price_change_pct, weight * 100.0)
+            }
+            ChangeDriver::WeightShift { ticker, previous_weight, weight } => {
+                format!("{} weight {:.1}% -> {:.1}%", ticker, previous_weight * 100.0, weight * 100.0)
+            }
+        }
+    }
+}
+
+/// Latest daily portfolio snapshot, which incremental snapshot runs diff against
+#[derive(Debug, Clone, FromRow)]
+pub struct SnapshotBaseline {
+    pub snapshot_date: NaiveDate,
+    pub created_at: DateTime<Utc>,
+    pub scoring_version: i32,
+    /// Latest date on which no position was carried forward
+    pub last_full_date: Option<NaiveDate>,
+    /// Tickers with a position snapshot on `snapshot_date`
+    pub position_tickers: Vec<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateRiskSnapshot {
     pub portfolio_id: Uuid,
@@ -44,20 +112,109 @@ pub struct CreateRiskSnapshot {
     pub expected_shortfall_99: Option<BigDecimal>,
     pub risk_score: BigDecimal,
     pub risk_level: String,
+    pub scoring_version: i32,
     pub total_value: Option<BigDecimal>,
     pub market_value: Option<BigDecimal>,
+    #[serde(default)]
+    pub change_drivers: Option<Vec<ChangeDriver>>,
+    #[serde(default)]
+    pub avg_correlation: Option<BigDecimal>,
+}
+
+/// Stored metrics a snapshot's risk score is computed from
+#[derive(Debug, Clone, FromRow)]
+pub struct RiskScoringInput {
+    pub id: Uuid,
+    pub volatility: BigDecimal,
+    pub max_drawdown: BigDecimal,
+    pub beta: Option<BigDecimal>,
+    pub value_at_risk: Option<BigDecimal>,
+}
+
+/// A snapshot's live score beside its shadow score
+#[derive(Debug, Clone, FromRow)]
+pub struct ShadowScoreRow {
+    pub id: Uuid,
+    pub portfolio_id: Uuid,
+    pub ticker: Option<String>,
+    pub snapshot_date: NaiveDate,
+    pub risk_score: BigDecimal,
+    pub risk_level: String,
+    pub shadow_risk_score: BigDecimal,
+    pub shadow_risk_level: String,
+}
+
+/// Snapshots a historical recompute covers; unset filters cover all of them
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RiskRecomputeRequest {
+    pub scoring_version: i32,
+    pub portfolio_id: Option<Uuid>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RiskPromoteRequest {
+    pub scoring_version: i32,
+}
+
+/// Number of snapshots whose risk level moves between two levels
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RiskLevelChange {
+    pub from: String,
+    pub to: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RiskScoreDivergence {
+    pub snapshot_id: Uuid,
+    pub portfolio_id: Uuid,
+    pub ticker: Option<String>,
+    pub snapshot_date: NaiveDate,
+    pub risk_score: f64,
+    pub shadow_risk_score: f64,
+    pub difference: f64,
+}
+
+/// How shadow scores differ from the live scores of the same snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RiskRecomputeReport {
+    pub scoring_version: i32,
+    /// Snapshots with a shadow score under `scoring_version`
+    pub snapshots: usize,
+    /// Snapshots whose score moves by more than 0.01 points
+    pub changed: usize,
+    /// Mean of shadow minus live score
+    pub mean_difference: f64,
+    pub mean_abs_difference: f64,
+    pub max_abs_difference: f64,
+    /// Snapshots whose risk level changes, by transition
+    pub level_changes: Vec<RiskLevelChange>,
+    /// Snapshots that move most, largest first
+    pub largest_divergences: Vec<RiskScoreDivergence>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskPromoteResult {
+    pub scoring_version: i32,
+    pub promoted: u64,
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub struct RiskAlert {
     pub portfolio_id: String,
     pub ticker: Option<String>,
-    pub alert_type: String,  // "risk_increase", "threshold_breach"
+    pub alert_type: String,  // "risk_increase", "threshold_breach", "anomaly"
     pub previous_value: f64,
     pub current_value: f64,
     pub change_percent: f64,
     pub date: NaiveDate,
     pub metric_name: String,  // "risk_score", "volatility", etc.
+    /// How many standard deviations the day's jump sits from recent jumps
+    /// (anomaly alerts only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub z_score: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -87,6 +244,18 @@ fn default_threshold() -> f64 {
     20.0
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AnomalyQueryParams {
+    #[serde(default = "default_alert_days")]
+    pub days: i64,
+    #[serde(default = "default_z_threshold")]
+    pub z_threshold: f64,  // Standard deviations from recent jumps (default 3)
+}
+
+fn default_z_threshold() -> f64 {
+    3.0
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum Aggregation {
     Daily,
@@ -144,6 +144,19 @@ pub struct ScreeningFilters {
     /// Geographic filter (e.g. "US", "EU")
     #[serde(default)]
     pub geographies: Vec<String>,
+
+    /// Minimum insider / institutional ownership, % of shares outstanding
+    pub min_insider_ownership_pct: Option<f64>,
+    pub min_institutional_ownership_pct: Option<f64>,
+
+    /// Only tickers whose insiders were net buyers over the last 90 days
+    #[serde(default)]
+    pub insider_net_buying: bool,
+
+    /// Minimum % upside from the current price to the analysts' mean target
+    pub min_analyst_upside_pct: Option<f64>,
+    /// Maximum mean analyst rating, 1 (strong buy) to 5 (strong sell)
+    pub max_analyst_rating: Option<f64>,
 }
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
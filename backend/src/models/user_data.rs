@@ -0,0 +1,38 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Everything stored about a user, keyed by table name
+#[derive(Debug, Clone, Serialize)]
+pub struct UserDataExport {
+    pub exported_at: DateTime<Utc>,
+    /// Profile fields of the user row (never the password hash)
+    pub user: Value,
+    /// Rows of every table holding the user's data, as JSON objects
+    pub tables: BTreeMap<String, Vec<Value>>,
+}
+
+/// Query parameters of `DELETE /api/users/me/data`
+#[derive(Debug, Default, Deserialize)]
+pub struct DataErasureQuery {
+    /// Must be `true`; erasure cannot be undone
+    #[serde(default)]
+    pub confirm: bool,
+    /// Also delete the login itself, not just the data attached to it
+    #[serde(default)]
+    pub delete_account: bool,
+    /// Erase without a recent export from `GET /api/users/me/data/export`
+    #[serde(default)]
+    pub skip_export: bool,
+}
+
+/// Outcome of an erasure, also recorded in the audit log
+#[derive(Debug, Clone, Serialize)]
+pub struct DataErasureSummary {
+    pub erased_at: DateTime<Utc>,
+    pub account_deleted: bool,
+    pub rows_deleted: BTreeMap<String, u64>,
+    pub total_rows_deleted: u64,
+}
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// An external sign-in identity linked to a user
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct UserIdentity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Configured provider id, e.g. "google", "github" or "oidc"
+    pub provider: String,
+    /// The provider's stable id for the person
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: DateTime<Utc>,
+}
+
+/// A sign-in started by the login redirect, awaiting the provider's callback
+#[derive(Debug, Clone, FromRow)]
+pub struct OidcLoginState {
+    pub provider: String,
+    pub code_verifier: String,
+    pub nonce: String,
+    pub redirect_to: String,
+}
+
+/// A sign-in provider offered on the login page
+#[derive(Debug, Clone, Serialize)]
+pub struct OidcProviderInfo {
+    pub id: String,
+    pub name: String,
+}
@@ -111,6 +111,9 @@ pub struct RiskPreferences {
     // Extensible custom settings (JSONB)
     pub custom_settings: Option<sqlx::types::JsonValue>,
 
+    // Contribute to (and see) anonymous aggregate statistics across portfolios
+    pub share_anonymous_stats: bool,
+
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -131,6 +134,7 @@ impl RiskPreferences {
             technical_weight: BigDecimal::from_f64(0.4).unwrap(),
             fundamental_weight: BigDecimal::from_f64(0.3).unwrap(),
             custom_settings: None,
+            share_anonymous_stats: false,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -194,6 +198,9 @@ pub struct UpdateRiskPreferences {
 
     // Custom settings
     pub custom_settings: Option<sqlx::types::JsonValue>,
+
+    // Anonymous aggregate statistics opt-in
+    pub share_anonymous_stats: Option<bool>,
 }
 
 impl UpdateRiskPreferences {
@@ -295,6 +302,7 @@ pub struct RiskPreferencesResponse {
     pub llm_enabled: bool,
     pub narrative_cache_hours: i32,
     pub custom_settings: Option<sqlx::types::JsonValue>,
+    pub share_anonymous_stats: bool,
     pub updated_at: DateTime<Utc>,
 }
 
@@ -311,11 +319,213 @@ impl From<RiskPreferences> for RiskPreferencesResponse {
             llm_enabled: prefs.llm_enabled,
             narrative_cache_hours: prefs.narrative_cache_hours,
             custom_settings: prefs.custom_settings,
+            share_anonymous_stats: prefs.share_anonymous_stats,
             updated_at: prefs.updated_at,
         }
     }
 }
 
+/// How alert emails are delivered
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum DigestFrequency {
+    /// Each alert is emailed as it happens
+    #[default]
+    Immediate,
+    /// Alerts are collected into one email a day
+    Daily,
+    /// Alerts are collected into one email on Mondays
+    Weekly,
+}
+
+/// A user's notification settings
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationSettings {
+    /// Email the owner when a portfolio newly crosses a critical risk threshold
+    pub email_on_critical_violations: bool,
+    pub digest_frequency: DigestFrequency,
+    /// No notifications are dispatched between these times (UTC); the window
+    /// may span midnight
+    pub quiet_hours_start: Option<chrono::NaiveTime>,
+    pub quiet_hours_end: Option<chrono::NaiveTime>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            email_on_critical_violations: true,
+            digest_frequency: DigestFrequency::Immediate,
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+        }
+    }
+}
+
+impl NotificationSettings {
+    /// Whether `now` falls within the quiet hours
+    pub fn is_quiet_at(&self, now: chrono::NaiveTime) -> bool {
+        match (self.quiet_hours_start, self.quiet_hours_end) {
+            (Some(start), Some(end)) if start <= end => now >= start && now < end,
+            (Some(start), Some(end)) => now >= start || now < end,
+            _ => false,
+        }
+    }
+}
+
+/// Input for replacing a user's notification settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateNotificationSettings {
+    pub email_on_critical_violations: bool,
+    pub digest_frequency: DigestFrequency,
+    /// "HH:MM" in UTC; set both or neither
+    pub quiet_hours_start: Option<String>,
+    pub quiet_hours_end: Option<String>,
+}
+
+impl UpdateNotificationSettings {
+    /// Parsed quiet hours, both or neither
+    pub fn quiet_hours(&self) -> Result<Option<(chrono::NaiveTime, chrono::NaiveTime)>, String> {
+        let parse = |value: &str| {
+            chrono::NaiveTime::parse_from_str(value.trim(), "%H:%M")
+                .map_err(|_| format!("Invalid quiet hours time '{}', expected HH:MM", value))
+        };
+        match (self.quiet_hours_start.as_deref(), self.quiet_hours_end.as_deref()) {
+            (None, None) => Ok(None),
+            (Some(start), Some(end)) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start == end {
+                    return Err("Quiet hours must not start and end at the same time".to_string());
+                }
+                Ok(Some((start, end)))
+            }
+            _ => Err("Set both quiet_hours_start and quiet_hours_end, or neither".to_string()),
+        }
+    }
+}
+
+/// The currency a user's portfolio totals, weights and analytics are reported in
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CurrencySettings {
+    /// ISO 4217 code, e.g. "USD" or "CAD"
+    pub base_currency: String,
+}
+
+/// Input for changing a user's base currency
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateCurrencySettings {
+    pub base_currency: String,
+}
+
+/// The time zone a user's "daily" boundaries follow: snapshot dates of their
+/// portfolios and when digests are delivered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimezoneSettings {
+    /// IANA name, e.g. "America/New_York"
+    pub timezone: String,
+    /// Offset from UTC in minutes right now
+    pub utc_offset_minutes: i32,
+}
+
+/// Input for changing a user's time zone
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateTimezoneSettings {
+    pub timezone: String,
+}
+
+/// Language of prose services produce for a user: recommendations, findings
+/// and LLM narratives
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+    Es,
+}
+
+impl Locale {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Fr => "fr",
+            Locale::Es => "es",
+        }
+    }
+
+    /// English name of the language, for LLM instructions
+    pub fn language_name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Fr => "French",
+            Locale::Es => "Spanish",
+        }
+    }
+
+    /// How clients should format numbers and dates for this locale
+    pub fn formatting(&self) -> LocaleFormat {
+        match self {
+            Locale::En => LocaleFormat {
+                tag: "en-US",
+                decimal_separator: ".",
+                thousands_separator: ",",
+                date_format: "MM/DD/YYYY",
+                percent_pattern: "{value}%",
+                currency_pattern: "{symbol}{value}",
+            },
+            Locale::Fr => LocaleFormat {
+                tag: "fr-CA",
+                decimal_separator: ",",
+                thousands_separator: "\u{a0}",
+                date_format: "YYYY-MM-DD",
+                percent_pattern: "{value}\u{a0}%",
+                currency_pattern: "{value}\u{a0}{symbol}",
+            },
+            Locale::Es => LocaleFormat {
+                tag: "es-ES",
+                decimal_separator: ",",
+                thousands_separator: ".",
+                date_format: "DD/MM/YYYY",
+                percent_pattern: "{value}\u{a0}%",
+                currency_pattern: "{value}\u{a0}{symbol}",
+            },
+        }
+    }
+}
+
+/// Number and date formatting conventions of a locale. Services return raw
+/// numbers and ISO dates; clients format them with these.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocaleFormat {
+    /// BCP 47 tag, usable with `Intl` APIs
+    pub tag: &'static str,
+    pub decimal_separator: &'static str,
+    pub thousands_separator: &'static str,
+    pub date_format: &'static str,
+    pub percent_pattern: &'static str,
+    pub currency_pattern: &'static str,
+}
+
+/// A user's locale with its formatting conventions
+#[derive(Debug, Clone, Serialize)]
+pub struct LocaleSettings {
+    pub locale: Locale,
+    pub formatting: LocaleFormat,
+}
+
+impl From<Locale> for LocaleSettings {
+    fn from(locale: Locale) -> Self {
+        Self { locale, formatting: locale.formatting() }
+    }
+}
+
+/// Input for changing a user's locale
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateLocaleSettings {
+    pub locale: Locale,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -373,6 +583,7 @@ mod tests {
             technical_weight: Some(0.4),
             fundamental_weight: Some(0.3),
             custom_settings: None,
+            share_anonymous_stats: None,
         };
 
         assert!(update.validate().is_ok());
@@ -400,6 +611,7 @@ mod tests {
             technical_weight: Some(0.8),
             fundamental_weight: Some(0.6),
             custom_settings: None,
+            share_anonymous_stats: None,
         };
 
         update.normalize_weights();
@@ -425,4 +637,47 @@ mod tests {
         prefs.risk_appetite = RiskAppetite::Aggressive;
         assert_eq!(prefs.default_forecast_horizon_days(), 90);
     }
+
+    fn notification_update(start: Option<&str>, end: Option<&str>) -> UpdateNotificationSettings {
+        UpdateNotificationSettings {
+            email_on_critical_violations: true,
+            digest_frequency: DigestFrequency::Daily,
+            quiet_hours_start: start.map(str::to_string),
+            quiet_hours_end: end.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_notification_quiet_hours_validation() {
+        let time = |h| chrono::NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        assert_eq!(notification_update(None, None).quiet_hours(), Ok(None));
+        assert_eq!(
+            notification_update(Some("22:00"), Some("07:00")).quiet_hours(),
+            Ok(Some((time(22), time(7))))
+        );
+        assert!(notification_update(Some("22:00"), None).quiet_hours().is_err());
+        assert!(notification_update(Some("25:00"), Some("07:00")).quiet_hours().is_err());
+        assert!(notification_update(Some("07:00"), Some("07:00")).quiet_hours().is_err());
+    }
+
+    #[test]
+    fn test_notification_quiet_hours_span_midnight() {
+        let time = |h| chrono::NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        let mut settings = NotificationSettings {
+            quiet_hours_start: Some(time(22)),
+            quiet_hours_end: Some(time(7)),
+            ..NotificationSettings::default()
+        };
+        assert!(settings.is_quiet_at(time(23)));
+        assert!(settings.is_quiet_at(time(3)));
+        assert!(!settings.is_quiet_at(time(7)));
+        assert!(!settings.is_quiet_at(time(12)));
+
+        settings.quiet_hours_start = Some(time(12));
+        settings.quiet_hours_end = Some(time(14));
+        assert!(settings.is_quiet_at(time(13)));
+        assert!(!settings.is_quiet_at(time(15)));
+
+        assert!(!NotificationSettings::default().is_quiet_at(time(3)));
+    }
 }
@@ -1,4 +1,4 @@
-use bigdecimal::BigDecimal;
+use bigdecimal::{BigDecimal, ToPrimitive};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -135,7 +135,7 @@ impl From<WatchlistThreshold> for WatchlistThresholdResponse {
             id: t.id,
             threshold_type: t.threshold_type,
             comparison: t.comparison,
-            value: t.value.to_string().parse::<f64>().unwrap_or(0.0),
+            value: t.value.to_f64().unwrap_or(0.0),
             enabled: t.enabled,
         }
     }
@@ -157,6 +157,10 @@ pub enum ThresholdType {
     RsiOverbought,
     #[serde(rename = "rsi_oversold")]
     RsiOversold,
+    #[serde(rename = "pct_off_52w_high")]
+    PctOff52WeekHigh,
+    #[serde(rename = "pct_above_52w_low")]
+    PctAbove52WeekLow,
 }
 
 impl ThresholdType {
@@ -169,6 +173,8 @@ impl ThresholdType {
             ThresholdType::VolumeSpike => "volume_spike",
             ThresholdType::RsiOverbought => "rsi_overbought",
             ThresholdType::RsiOversold => "rsi_oversold",
+            ThresholdType::PctOff52WeekHigh => "pct_off_52w_high",
+            ThresholdType::PctAbove52WeekLow => "pct_above_52w_low",
         }
     }
 }
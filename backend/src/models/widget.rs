@@ -0,0 +1,59 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A token for an embedded portfolio widget. The token itself is only
+/// returned once, on creation.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct WidgetToken {
+    pub id: Uuid,
+    pub portfolio_id: Uuid,
+    pub name: String,
+    /// The only origin allowed to read the widget from a browser
+    pub allowed_origin: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWidgetToken {
+    /// Label shown in the token list, e.g. "Personal site"
+    pub name: String,
+    /// Origin of the embedding site, e.g. "https://example.com"
+    pub allowed_origin: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedWidgetToken {
+    #[serde(flatten)]
+    pub token: WidgetToken,
+    /// Part of the widget URLs; it cannot be shown again
+    pub secret: String,
+}
+
+/// What a widget token grants: one portfolio, read from one origin
+#[derive(Debug, Clone, FromRow)]
+pub struct WidgetGrant {
+    pub portfolio_id: Uuid,
+    pub allowed_origin: String,
+}
+
+/// Latest value of the portfolio and its change over the last trading day
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WidgetValue {
+    /// None before the portfolio's first valuation
+    pub as_of: Option<NaiveDate>,
+    pub value: f64,
+    pub day_change: Option<f64>,
+    pub day_change_pct: Option<f64>,
+}
+
+/// Latest portfolio risk score, for a gauge
+#[derive(Debug, Clone, Serialize)]
+pub struct WidgetRisk {
+    pub as_of: NaiveDate,
+    /// 0 (lowest) to 100 (highest)
+    pub risk_score: f64,
+    pub risk_level: String,
+}
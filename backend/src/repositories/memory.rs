@@ -0,0 +1,222 @@
+//! In-memory repositories for unit tests.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, FromPrimitive};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{CacheRepo, FundamentalsRepo, HoldingsRepo, PriceRepo, Repositories};
+use crate::db::analytics_queries::AllocationRow;
+use crate::db::holding_snapshot_queries::HoldingDetail;
+use crate::db::risk_cache_queries::RiskCacheEntry;
+use crate::db::tenant::TenantScope;
+use crate::external::fundamentals_provider::ExternalFundamentals;
+use crate::models::long_term_guidance::LongTermGuidanceResponse;
+use crate::models::risk::CorrelationMatrixWithStats;
+use crate::models::{DividendPayment, PricePoint};
+
+#[derive(Default)]
+pub struct MemoryPriceRepo {
+    prices: Mutex<HashMap<String, Vec<PricePoint>>>,
+}
+
+impl MemoryPriceRepo {
+    /// Store daily closes for `ticker`, one per day from `start`
+    pub fn insert_closes(&self, ticker: &str, start: NaiveDate, closes: &[f64]) {
+        let points = closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| PricePoint {
+                id: Uuid::new_v4(),
+                ticker: ticker.to_string(),
+                date: start + chrono::Duration::days(i as i64),
+                close_price: BigDecimal::from_f64(*close).unwrap_or_default(),
+                created_at: Utc::now(),
+            })
+            .collect();
+        self.prices.lock().unwrap().insert(ticker.to_string(), points);
+    }
+}
+
+#[async_trait]
+impl PriceRepo for MemoryPriceRepo {
+    async fn price_history(&self, ticker: &str) -> Result<Vec<PricePoint>, sqlx::Error> {
+        Ok(self.prices.lock().unwrap().get(ticker).cloned().unwrap_or_default())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryHoldingsRepo {
+    holdings: Mutex<HashMap<Uuid, Vec<HoldingDetail>>>,
+}
+
+impl MemoryHoldingsRepo {
+    /// Add a holding to the portfolio's latest snapshot
+    pub fn insert(&self, portfolio_id: Uuid, ticker: &str, industry: Option<&str>, market_value: f64) {
+        self.holdings.lock().unwrap().entry(portfolio_id).or_default().push(HoldingDetail {
+            ticker: ticker.to_string(),
+            holding_name: None,
+            industry: industry.map(str::to_string),
+            asset_category: None,
+            market_value: Some(market_value),
+        });
+    }
+}
+
+#[async_trait]
+impl HoldingsRepo for MemoryHoldingsRepo {
+    async fn latest_allocations(&self, portfolio_id: Uuid) -> Result<Vec<AllocationRow>, sqlx::Error> {
+        let holdings = self.holdings.lock().unwrap();
+        Ok(holdings
+            .get(&portfolio_id)
+            .map(|rows| {
+                rows.iter()
+                    .map(|h| AllocationRow {
+                        ticker: h.ticker.clone(),
+                        value: h.market_value.unwrap_or(0.0),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn latest_holding_details(&self, portfolio_id: Uuid) -> Result<Vec<HoldingDetail>, sqlx::Error> {
+        Ok(self.holdings.lock().unwrap().get(&portfolio_id).cloned().unwrap_or_default())
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryFundamentalsRepo {
+    ratios: Mutex<HashMap<String, ExternalFundamentals>>,
+    dividends: Mutex<HashMap<String, Vec<DividendPayment>>>,
+}
+
+impl MemoryFundamentalsRepo {
+    pub fn insert_ratios(&self, ticker: &str, ratios: ExternalFundamentals) {
+        self.ratios.lock().unwrap().insert(ticker.to_string(), ratios);
+    }
+
+    /// Store a dividend of `amount` per share on each ex-date
+    pub fn insert_dividends(&self, ticker: &str, ex_dates: &[NaiveDate], amount: f64) {
+        let payments = ex_dates
+            .iter()
+            .map(|&ex_date| DividendPayment {
+                ticker: ticker.to_string(),
+                ex_date,
+                pay_date: None,
+                amount,
+                currency: None,
+            })
+            .collect();
+        self.dividends.lock().unwrap().insert(ticker.to_string(), payments);
+    }
+}
+
+#[async_trait]
+impl FundamentalsRepo for MemoryFundamentalsRepo {
+    async fn ratios(&self, ticker: &str) -> Result<Option<ExternalFundamentals>, sqlx::Error> {
+        Ok(self.ratios.lock().unwrap().get(ticker).cloned())
+    }
+
+    async fn dividends(&self, ticker: &str, since: NaiveDate) -> Result<Vec<DividendPayment>, sqlx::Error> {
+        let mut payments: Vec<DividendPayment> = self
+            .dividends
+            .lock()
+            .unwrap()
+            .get(ticker)
+            .map(|p| p.iter().filter(|p| p.ex_date >= since).cloned().collect())
+            .unwrap_or_default();
+        payments.sort_by_key(|p| std::cmp::Reverse(p.ex_date));
+        Ok(payments)
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryCacheRepo {
+    risk: Mutex<HashMap<(TenantScope, i32, String), RiskCacheEntry>>,
+    correlations: Mutex<HashMap<(TenantScope, i32), CorrelationMatrixWithStats>>,
+    guidance: Mutex<HashMap<(Uuid, String, i32, String), LongTermGuidanceResponse>>,
+}
+
+#[async_trait]
+impl CacheRepo for MemoryCacheRepo {
+    async fn risk(&self, tenant: &TenantScope, days: i32, benchmark: &str) -> Result<Option<RiskCacheEntry>, sqlx::Error> {
+        Ok(self.risk.lock().unwrap().get(&(*tenant, days, benchmark.to_string())).cloned())
+    }
+
+    async fn store_risk(
+        &self,
+        tenant: &TenantScope,
+        days: i32,
+        benchmark: &str,
+        risk_data: &Value,
+        expires_at: DateTime<Utc>,
+        scoring_version: i32,
+    ) -> Result<(), sqlx::Error> {
+        let entry = RiskCacheEntry {
+            risk_data: risk_data.clone(),
+            calculation_status: "fresh".to_string(),
+            expires_at,
+            last_error: None,
+            scoring_version,
+        };
+        self.risk.lock().unwrap().insert((*tenant, days, benchmark.to_string()), entry);
+        Ok(())
+    }
+
+    async fn fresh_correlations(
+        &self,
+        tenant: &TenantScope,
+        days: i32,
+    ) -> Result<Option<CorrelationMatrixWithStats>, sqlx::Error> {
+        Ok(self.correlations.lock().unwrap().get(&(*tenant, days)).cloned())
+    }
+
+    async fn guidance(
+        &self,
+        portfolio_id: Uuid,
+        goal: &str,
+        horizon_years: i32,
+        risk_tolerance: &str,
+    ) -> Result<Option<LongTermGuidanceResponse>, sqlx::Error> {
+        let key = (portfolio_id, goal.to_string(), horizon_years, risk_tolerance.to_string());
+        Ok(self.guidance.lock().unwrap().get(&key).cloned())
+    }
+
+    async fn store_guidance(
+        &self,
+        portfolio_id: Uuid,
+        goal: &str,
+        horizon_years: i32,
+        risk_tolerance: &str,
+        response: &LongTermGuidanceResponse,
+    ) -> Result<(), sqlx::Error> {
+        let key = (portfolio_id, goal.to_string(), horizon_years, risk_tolerance.to_string());
+        self.guidance.lock().unwrap().insert(key, response.clone());
+        Ok(())
+    }
+}
+
+/// In-memory repositories, with handles to seed them
+#[derive(Default)]
+pub struct MemoryRepositories {
+    pub prices: Arc<MemoryPriceRepo>,
+    pub holdings: Arc<MemoryHoldingsRepo>,
+    pub fundamentals: Arc<MemoryFundamentalsRepo>,
+    pub cache: Arc<MemoryCacheRepo>,
+}
+
+impl MemoryRepositories {
+    pub fn repositories(&self) -> Repositories {
+        Repositories {
+            prices: self.prices.clone(),
+            holdings: self.holdings.clone(),
+            fundamentals: self.fundamentals.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+}
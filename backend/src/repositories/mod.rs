@@ -0,0 +1,119 @@
+//! Repository traits between services and the database.
+//!
+//! Services that take a [`Repositories`] instead of a `PgPool` can be unit
+//! tested against the in-memory fakes in [`memory`] without a database. The
+//! Postgres implementations in [`postgres`] delegate to the `db::*_queries`
+//! modules, so SQL stays where it is.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::analytics_queries::AllocationRow;
+use crate::db::holding_snapshot_queries::HoldingDetail;
+use crate::db::risk_cache_queries::RiskCacheEntry;
+use crate::db::tenant::TenantScope;
+use crate::external::fundamentals_provider::ExternalFundamentals;
+use crate::models::long_term_guidance::LongTermGuidanceResponse;
+use crate::models::risk::CorrelationMatrixWithStats;
+use crate::models::{DividendPayment, PricePoint};
+
+#[cfg(test)]
+pub mod memory;
+pub mod postgres;
+
+/// Stored daily closes
+#[async_trait]
+pub trait PriceRepo: Send + Sync {
+    /// All closes for a ticker, oldest first
+    async fn price_history(&self, ticker: &str) -> Result<Vec<PricePoint>, sqlx::Error>;
+}
+
+/// Holdings from a portfolio's latest snapshot
+#[async_trait]
+pub trait HoldingsRepo: Send + Sync {
+    /// Market value per ticker, summed across accounts
+    async fn latest_allocations(&self, portfolio_id: Uuid) -> Result<Vec<AllocationRow>, sqlx::Error>;
+
+    /// Name, industry and category per ticker
+    async fn latest_holding_details(&self, portfolio_id: Uuid) -> Result<Vec<HoldingDetail>, sqlx::Error>;
+}
+
+/// Reported fundamentals stored by the fundamentals job
+#[async_trait]
+pub trait FundamentalsRepo: Send + Sync {
+    /// Key ratios, `None` for a ticker never fetched
+    async fn ratios(&self, ticker: &str) -> Result<Option<ExternalFundamentals>, sqlx::Error>;
+
+    /// Dividends with an ex-date on or after `since`, most recent first
+    async fn dividends(&self, ticker: &str, since: NaiveDate) -> Result<Vec<DividendPayment>, sqlx::Error>;
+}
+
+/// Cached analytics results
+#[async_trait]
+pub trait CacheRepo: Send + Sync {
+    /// Cached portfolio risk, whatever its calculation status
+    async fn risk(&self, tenant: &TenantScope, days: i32, benchmark: &str) -> Result<Option<RiskCacheEntry>, sqlx::Error>;
+
+    /// Store freshly calculated portfolio risk
+    async fn store_risk(
+        &self,
+        tenant: &TenantScope,
+        days: i32,
+        benchmark: &str,
+        risk_data: &Value,
+        expires_at: DateTime<Utc>,
+        scoring_version: i32,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Cached correlation matrix, only if fresh and unexpired
+    async fn fresh_correlations(
+        &self,
+        tenant: &TenantScope,
+        days: i32,
+    ) -> Result<Option<CorrelationMatrixWithStats>, sqlx::Error>;
+
+    /// Unexpired long-term guidance
+    async fn guidance(
+        &self,
+        portfolio_id: Uuid,
+        goal: &str,
+        horizon_years: i32,
+        risk_tolerance: &str,
+    ) -> Result<Option<LongTermGuidanceResponse>, sqlx::Error>;
+
+    /// Store long-term guidance for an hour
+    async fn store_guidance(
+        &self,
+        portfolio_id: Uuid,
+        goal: &str,
+        horizon_years: i32,
+        risk_tolerance: &str,
+        response: &LongTermGuidanceResponse,
+    ) -> Result<(), sqlx::Error>;
+}
+
+/// The repositories handed to services, shared through `AppState`
+#[derive(Clone)]
+pub struct Repositories {
+    pub prices: Arc<dyn PriceRepo>,
+    pub holdings: Arc<dyn HoldingsRepo>,
+    pub fundamentals: Arc<dyn FundamentalsRepo>,
+    pub cache: Arc<dyn CacheRepo>,
+}
+
+impl Repositories {
+    /// Postgres-backed repositories sharing one pool
+    pub fn postgres(pool: PgPool) -> Self {
+        Self {
+            prices: Arc::new(postgres::PgPriceRepo::new(pool.clone())),
+            holdings: Arc::new(postgres::PgHoldingsRepo::new(pool.clone())),
+            fundamentals: Arc::new(postgres::PgFundamentalsRepo::new(pool.clone())),
+            cache: Arc::new(postgres::PgCacheRepo::new(pool)),
+        }
+    }
+}
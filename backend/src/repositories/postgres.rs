@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{CacheRepo, FundamentalsRepo, HoldingsRepo, PriceRepo};
+use crate::db::analytics_queries::{self, AllocationRow};
+use crate::db::holding_snapshot_queries::{self, HoldingDetail};
+use crate::db::risk_cache_queries::{self, RiskCacheEntry};
+use crate::db::tenant::TenantScope;
+use crate::db::{fundamentals_queries, long_term_guidance_queries, price_queries};
+use crate::external::fundamentals_provider::ExternalFundamentals;
+use crate::models::long_term_guidance::LongTermGuidanceResponse;
+use crate::models::risk::CorrelationMatrixWithStats;
+use crate::models::{DividendPayment, PricePoint};
+
+pub struct PgPriceRepo {
+    pool: PgPool,
+}
+
+impl PgPriceRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PriceRepo for PgPriceRepo {
+    async fn price_history(&self, ticker: &str) -> Result<Vec<PricePoint>, sqlx::Error> {
+        price_queries::fetch_all(&self.pool, ticker).await
+    }
+}
+
+pub struct PgHoldingsRepo {
+    pool: PgPool,
+}
+
+impl PgHoldingsRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl HoldingsRepo for PgHoldingsRepo {
+    async fn latest_allocations(&self, portfolio_id: Uuid) -> Result<Vec<AllocationRow>, sqlx::Error> {
+        analytics_queries::fetch_allocations_at_latest_date(&self.pool, portfolio_id).await
+    }
+
+    async fn latest_holding_details(&self, portfolio_id: Uuid) -> Result<Vec<HoldingDetail>, sqlx::Error> {
+        holding_snapshot_queries::fetch_latest_holding_details(&self.pool, portfolio_id).await
+    }
+}
+
+pub struct PgFundamentalsRepo {
+    pool: PgPool,
+}
+
+impl PgFundamentalsRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl FundamentalsRepo for PgFundamentalsRepo {
+    async fn ratios(&self, ticker: &str) -> Result<Option<ExternalFundamentals>, sqlx::Error> {
+        // An unreadable stored copy counts as never fetched
+        Ok(fundamentals_queries::fetch(&self.pool, ticker)
+            .await?
+            .and_then(|(data, _)| serde_json::from_value(data).ok()))
+    }
+
+    async fn dividends(&self, ticker: &str, since: NaiveDate) -> Result<Vec<DividendPayment>, sqlx::Error> {
+        fundamentals_queries::fetch_dividends(&self.pool, ticker, since).await
+    }
+}
+
+pub struct PgCacheRepo {
+    pool: PgPool,
+}
+
+impl PgCacheRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CacheRepo for PgCacheRepo {
+    async fn risk(&self, tenant: &TenantScope, days: i32, benchmark: &str) -> Result<Option<RiskCacheEntry>, sqlx::Error> {
+        risk_cache_queries::fetch_risk_cache(&self.pool, tenant, days, benchmark).await
+    }
+
+    async fn store_risk(
+        &self,
+        tenant: &TenantScope,
+        days: i32,
+        benchmark: &str,
+        risk_data: &Value,
+        expires_at: DateTime<Utc>,
+        scoring_version: i32,
+    ) -> Result<(), sqlx::Error> {
+        risk_cache_queries::store_risk_cache(
+            &self.pool,
+            tenant,
+            days,
+            benchmark,
+            risk_data,
+            expires_at,
+            scoring_version,
+        )
+        .await
+    }
+
+    async fn fresh_correlations(
+        &self,
+        tenant: &TenantScope,
+        days: i32,
+    ) -> Result<Option<CorrelationMatrixWithStats>, sqlx::Error> {
+        risk_cache_queries::fetch_fresh_correlations(&self.pool, tenant, days).await
+    }
+
+    async fn guidance(
+        &self,
+        portfolio_id: Uuid,
+        goal: &str,
+        horizon_years: i32,
+        risk_tolerance: &str,
+    ) -> Result<Option<LongTermGuidanceResponse>, sqlx::Error> {
+        long_term_guidance_queries::get_cached_guidance(&self.pool, portfolio_id, goal, horizon_years, risk_tolerance)
+            .await
+    }
+
+    async fn store_guidance(
+        &self,
+        portfolio_id: Uuid,
+        goal: &str,
+        horizon_years: i32,
+        risk_tolerance: &str,
+        response: &LongTermGuidanceResponse,
+    ) -> Result<(), sqlx::Error> {
+        long_term_guidance_queries::cache_guidance(
+            &self.pool,
+            portfolio_id,
+            goal,
+            horizon_years,
+            risk_tolerance,
+            response,
+        )
+        .await
+    }
+}
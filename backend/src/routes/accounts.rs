@@ -1,17 +1,28 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
 use axum::{Json, Router};
-use axum::routing::{get, post};
+use axum::routing::{delete, get, post, put};
 use bigdecimal::BigDecimal;
 use serde::Deserialize;
 use std::str::FromStr;
 use tracing::{info, error};
 use uuid::Uuid;
 
-use crate::db::{account_queries, holding_snapshot_queries, portfolio_queries};
+use crate::db::{account_fee_queries, account_queries, annotation_queries, crypto_wallet_queries, holding_snapshot_queries, portfolio_queries};
 use crate::errors::AppError;
 use crate::middleware::auth::AuthUser;
-use crate::models::{Account, AccountValueHistory, CreateAccount, CreateHoldingSnapshot, HoldingSnapshot, LatestAccountHolding};
+use crate::middleware::permissions::{CanEdit, PortfolioAccess};
+use crate::models::{
+    Account, AccountFeePerformance, AuditAction, AccountFeeSchedule, AccountValueHistory, AnnotatedHolding, CreateAccount,
+    CreateAccountFeeSchedule, CreateCryptoWallet, CreateHoldingSnapshot, CryptoSyncResult, CryptoWallet, DripGenerationResult, FeeAnalysisQuery, HoldingSnapshot, PositionAnnotation, TagFilterQuery, UpdateAnnotation, UpdateDripSetting, UpdateFractionalShareSetting,
+    NewAuditEntry, TickerType, UpdateCostBasisMethodSetting, UpdateTaxTreatmentSetting,
+};
+use crate::services::{
+    advisor_fee_service, annotation_service, audit_service, crypto_wallet_service, drip_service, fee_service, fx_service,
+    wash_sale_service,
+};
 use crate::state::AppState;
+use crate::services::clock;
 
 pub fn router() -> Router<AppState> {
     Router::new()
@@ -19,6 +30,18 @@ pub fn router() -> Router<AppState> {
         .route("/accounts/:account_id", get(get_account))
         .route("/accounts/:account_id/holdings", get(get_latest_holdings).post(add_holding))
         .route("/accounts/:account_id/history", get(get_account_history))
+        .route("/accounts/:account_id/positions/:ticker/annotations", put(set_position_annotation))
+        .route("/accounts/:account_id/drip", put(set_drip_setting))
+        .route("/accounts/:account_id/drip/generate", post(generate_drip_transactions))
+        .route("/accounts/:account_id/fractional-shares", put(set_fractional_share_setting))
+        .route("/accounts/:account_id/tax-treatment", put(set_tax_treatment_setting))
+        .route("/accounts/:account_id/cost-basis-method", put(set_cost_basis_method_setting))
+        .route("/accounts/:account_id/fee-schedules", get(list_fee_schedules).post(create_fee_schedule))
+        .route("/accounts/:account_id/fee-schedules/:schedule_id", delete(delete_fee_schedule))
+        .route("/accounts/:account_id/fee-performance", get(get_fee_performance))
+        .route("/accounts/:account_id/crypto-wallets", get(list_crypto_wallets).post(add_crypto_wallet))
+        .route("/accounts/:account_id/crypto-wallets/sync", post(sync_crypto_wallets))
+        .route("/accounts/:account_id/crypto-wallets/:wallet_id", delete(delete_crypto_wallet))
         .route("/portfolios/:portfolio_id/history", get(get_portfolio_history))
 }
 
@@ -40,6 +63,8 @@ pub struct AddHoldingRequest {
     pub price: f64,
     pub average_cost: f64,
     pub snapshot_date: Option<String>,
+    /// ISO currency code; defaults to the listing's currency
+    pub currency: Option<String>,
 }
 
 fn to_decimal(v: f64) -> BigDecimal {
@@ -94,7 +119,8 @@ pub async fn get_latest_holdings(
     State(state): State<AppState>,
     AuthUser(user_id): AuthUser,
     Path(account_id): Path<Uuid>,
-) -> Result<Json<Vec<LatestAccountHolding>>, AppError> {
+    Query(params): Query<TagFilterQuery>,
+) -> Result<Json<Vec<AnnotatedHolding>>, AppError> {
     info!("GET /accounts/{}/holdings - Fetching latest holdings", account_id);
     if !account_queries::belongs_to_user(&state.pool, account_id, user_id)
         .await
@@ -102,15 +128,424 @@ pub async fn get_latest_holdings(
     {
         return Err(AppError::NotFound(format!("Account {} not found", account_id)));
     }
-    let holdings = holding_snapshot_queries::fetch_latest_holdings(&state.pool, account_id)
+    let tag = annotation_service::normalize_tag_filter(params.tag);
+    let holdings = annotation_service::fetch_account_holdings(&state.pool, account_id, tag.as_deref())
         .await
         .map_err(|e| {
             error!("Failed to fetch latest holdings for account {}: {}", account_id, e);
-            AppError::Db(e)
+            e
         })?;
     Ok(Json(holdings))
 }
 
+/// PUT /api/accounts/:account_id/positions/:ticker/annotations
+///
+/// Set free-form tags, markdown notes, a target price, a stop-loss, and an investment
+/// thesis on a position. Omitted fields are left unchanged; `clear_levels: true` removes
+/// the target and stop-loss. The watchlist monitoring job alerts when either is crossed.
+pub async fn set_position_annotation(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path((account_id, ticker)): Path<(Uuid, String)>,
+    Json(data): Json<UpdateAnnotation>,
+) -> Result<Json<PositionAnnotation>, AppError> {
+    info!("PUT /accounts/{}/positions/{}/annotations - Updating position annotation", account_id, ticker);
+    let previous = annotation_queries::fetch_for_account(&state.pool, account_id)
+        .await
+        .map_err(AppError::Db)?
+        .into_iter()
+        .find(|a| a.ticker.eq_ignore_ascii_case(ticker.trim()));
+    let annotation = annotation_service::set_position_annotation(&state.pool, account_id, &ticker, data)
+        .await
+        .map_err(|e| {
+            error!("Failed to update annotation for {} in account {}: {}", ticker, account_id, e);
+            e
+        })?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::saved(access.user_id, "position_annotation", annotation.id, previous.as_ref())
+            .portfolio(access.portfolio_id)
+            .after(&annotation),
+    )
+    .await;
+    Ok(Json(annotation))
+}
+
+/// PUT /api/accounts/:account_id/drip
+///
+/// Turn automatic dividend reinvestment on or off for an account. Enabling it
+/// immediately generates DRIP transactions for dividends already recorded; later
+/// activity imports generate them automatically.
+pub async fn set_drip_setting(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(account_id): Path<Uuid>,
+    Json(data): Json<UpdateDripSetting>,
+) -> Result<Json<Account>, AppError> {
+    info!("PUT /accounts/{}/drip - Setting DRIP enabled = {}", account_id, data.enabled);
+    let previous = account_queries::fetch_one(&state.pool, account_id).await.map_err(AppError::Db)?;
+    let account = account_queries::set_drip_enabled(&state.pool, account_id, data.enabled)
+        .await
+        .map_err(|e| {
+            error!("Failed to update DRIP setting for account {}: {}", account_id, e);
+            AppError::Db(e)
+        })?
+        .ok_or_else(|| AppError::NotFound(format!("Account {} not found", account_id)))?;
+    audit_account_update(&state, &access, previous.as_ref(), &account).await;
+
+    if account.drip_enabled {
+        let mut conn = state.pool.acquire().await.map_err(AppError::Db)?;
+        drip_service::generate_for_account(&mut conn, account_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to generate DRIP transactions for account {}: {}", account_id, e);
+                e
+            })?;
+    }
+    Ok(Json(account))
+}
+
+/// PUT /api/accounts/:account_id/fractional-shares
+///
+/// Record whether the account's broker supports fractional shares. Trade lists
+/// from the optimizers are rounded to whole shares for accounts that do not.
+pub async fn set_fractional_share_setting(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(account_id): Path<Uuid>,
+    Json(data): Json<UpdateFractionalShareSetting>,
+) -> Result<Json<Account>, AppError> {
+    info!("PUT /accounts/{}/fractional-shares - Setting fractional shares = {}", account_id, data.enabled);
+    let previous = account_queries::fetch_one(&state.pool, account_id).await.map_err(AppError::Db)?;
+    let account = account_queries::set_fractional_shares(&state.pool, account_id, data.enabled)
+        .await
+        .map_err(|e| {
+            error!("Failed to update fractional share setting for account {}: {}", account_id, e);
+            AppError::Db(e)
+        })?
+        .ok_or_else(|| AppError::NotFound(format!("Account {} not found", account_id)))?;
+    audit_account_update(&state, &access, previous.as_ref(), &account).await;
+    Ok(Json(account))
+}
+
+/// PUT /api/accounts/:account_id/tax-treatment
+///
+/// Set how the account is taxed ("taxable", "tax_deferred" or "tax_free"), used by
+/// the asset location analysis. `null` goes back to inferring it from the nickname.
+pub async fn set_tax_treatment_setting(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(account_id): Path<Uuid>,
+    Json(data): Json<UpdateTaxTreatmentSetting>,
+) -> Result<Json<Account>, AppError> {
+    info!("PUT /accounts/{}/tax-treatment - Setting tax treatment = {:?}", account_id, data.tax_treatment);
+    let tax_treatment = data.tax_treatment.map(|t| t.as_str());
+    let previous = account_queries::fetch_one(&state.pool, account_id).await.map_err(AppError::Db)?;
+    let account = account_queries::set_tax_treatment(&state.pool, account_id, tax_treatment)
+        .await
+        .map_err(|e| {
+            error!("Failed to update tax treatment for account {}: {}", account_id, e);
+            AppError::Db(e)
+        })?
+        .ok_or_else(|| AppError::NotFound(format!("Account {} not found", account_id)))?;
+    audit_account_update(&state, &access, previous.as_ref(), &account).await;
+    Ok(Json(account))
+}
+
+/// PUT /api/accounts/:account_id/cost-basis-method
+///
+/// Choose how sales in the account are matched to tax lots ("fifo", "average_cost"
+/// or "specific_lot") for realized gains and the tax report. Wash sales are
+/// re-scanned since realized losses can change.
+pub async fn set_cost_basis_method_setting(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(account_id): Path<Uuid>,
+    Json(data): Json<UpdateCostBasisMethodSetting>,
+) -> Result<Json<Account>, AppError> {
+    info!(
+        "PUT /accounts/{}/cost-basis-method - Setting cost basis method = {:?}",
+        account_id, data.cost_basis_method
+    );
+    let previous = account_queries::fetch_one(&state.pool, account_id).await.map_err(AppError::Db)?;
+    let account = account_queries::set_cost_basis_method(&state.pool, account_id, data.cost_basis_method.as_str())
+        .await
+        .map_err(|e| {
+            error!("Failed to update cost basis method for account {}: {}", account_id, e);
+            AppError::Db(e)
+        })?
+        .ok_or_else(|| AppError::NotFound(format!("Account {} not found", account_id)))?;
+    audit_account_update(&state, &access, previous.as_ref(), &account).await;
+    if let Err(e) = wash_sale_service::refresh_portfolio_wash_sales(&state.pool, account.portfolio_id).await {
+        error!("Failed to check wash sales for portfolio {}: {}", account.portfolio_id, e);
+    }
+    Ok(Json(account))
+}
+
+/// Record a change to an account's settings
+async fn audit_account_update(
+    state: &AppState,
+    access: &PortfolioAccess<CanEdit>,
+    previous: Option<&Account>,
+    account: &Account,
+) {
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::saved(access.user_id, "account", account.id, previous)
+            .portfolio(access.portfolio_id)
+            .after(account),
+    )
+    .await;
+}
+
+/// POST /api/accounts/:account_id/drip/generate
+///
+/// Generate DRIP transactions for every dividend in the account that has not been
+/// reinvested yet, using the close on (or up to a week before) the dividend date.
+/// Dividends without a usable price are listed under `skipped`.
+pub async fn generate_drip_transactions(
+    State(state): State<AppState>,
+    _access: PortfolioAccess<CanEdit>,
+    Path(account_id): Path<Uuid>,
+) -> Result<Json<DripGenerationResult>, AppError> {
+    info!("POST /accounts/{}/drip/generate - Generating DRIP transactions", account_id);
+    let mut conn = state.pool.acquire().await.map_err(AppError::Db)?;
+    let result = drip_service::generate_for_account(&mut conn, account_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to generate DRIP transactions for account {}: {}", account_id, e);
+            e
+        })?;
+    Ok(Json(result))
+}
+
+/// GET /api/accounts/:account_id/fee-schedules
+pub async fn list_fee_schedules(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(account_id): Path<Uuid>,
+) -> Result<Json<Vec<AccountFeeSchedule>>, AppError> {
+    info!("GET /accounts/{}/fee-schedules - Listing fee schedules", account_id);
+    if !account_queries::belongs_to_user(&state.pool, account_id, user_id)
+        .await
+        .map_err(AppError::Db)?
+    {
+        return Err(AppError::NotFound(format!("Account {} not found", account_id)));
+    }
+    let schedules = account_fee_queries::fetch_schedules(&state.pool, account_id)
+        .await
+        .map_err(AppError::Db)?;
+    Ok(Json(schedules))
+}
+
+/// POST /api/accounts/:account_id/fee-schedules
+///
+/// Add a recurring fee (`aum_bps` in annual basis points, or `flat_monthly` in
+/// dollars) and regenerate the account's fee cash flows.
+pub async fn create_fee_schedule(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(account_id): Path<Uuid>,
+    Json(data): Json<CreateAccountFeeSchedule>,
+) -> Result<Json<AccountFeeSchedule>, AppError> {
+    info!("POST /accounts/{}/fee-schedules - Adding {:?} fee", account_id, data.fee_type);
+    if !data.amount.is_finite() || data.amount < 0.0 {
+        return Err(AppError::Validation("amount must be a non-negative number".to_string()));
+    }
+    if data.end_date.is_some_and(|end| end < data.start_date) {
+        return Err(AppError::Validation("end_date must not be before start_date".to_string()));
+    }
+    let schedule = account_fee_queries::create_schedule(&state.pool, account_id, &data)
+        .await
+        .map_err(|e| {
+            error!("Failed to create fee schedule for account {}: {}", account_id, e);
+            AppError::Db(e)
+        })?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(access.user_id, AuditAction::Create, "fee_schedule", schedule.id)
+            .portfolio(access.portfolio_id)
+            .after(&schedule),
+    )
+    .await;
+    if let Err(e) = advisor_fee_service::generate_for_account(&state.pool, account_id).await {
+        error!("Failed to generate fee cash flows for account {}: {}", account_id, e);
+    }
+    Ok(Json(schedule))
+}
+
+/// DELETE /api/accounts/:account_id/fee-schedules/:schedule_id
+///
+/// Removes the schedule together with the fee cash flows generated from it.
+pub async fn delete_fee_schedule(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path((account_id, schedule_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    info!("DELETE /accounts/{}/fee-schedules/{} - Deleting fee schedule", account_id, schedule_id);
+    let previous = account_fee_queries::fetch_schedules(&state.pool, account_id)
+        .await
+        .map_err(AppError::Db)?
+        .into_iter()
+        .find(|s| s.id == schedule_id);
+    let deleted = account_fee_queries::delete_schedule(&state.pool, account_id, schedule_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete fee schedule {}: {}", schedule_id, e);
+            AppError::Db(e)
+        })?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Fee schedule {} not found", schedule_id)));
+    }
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::deleted(access.user_id, "fee_schedule", schedule_id, previous.as_ref())
+            .portfolio(access.portfolio_id),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/accounts/:account_id/fee-performance?expected_return=6
+///
+/// Gross versus net-of-fee gain, plus projections of the account value with and
+/// without its active fee schedules.
+pub async fn get_fee_performance(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(account_id): Path<Uuid>,
+    Query(query): Query<FeeAnalysisQuery>,
+) -> Result<Json<AccountFeePerformance>, AppError> {
+    info!("GET /accounts/{}/fee-performance - Computing net-of-fee performance", account_id);
+    if !account_queries::belongs_to_user(&state.pool, account_id, user_id)
+        .await
+        .map_err(AppError::Db)?
+    {
+        return Err(AppError::NotFound(format!("Account {} not found", account_id)));
+    }
+    let expected_return = query.expected_return.unwrap_or(fee_service::DEFAULT_EXPECTED_RETURN);
+    let performance = advisor_fee_service::account_fee_performance(&state.pool, account_id, expected_return)
+        .await
+        .map_err(|e| {
+            error!("Failed to compute fee performance for account {}: {}", account_id, e);
+            e
+        })?;
+    Ok(Json(performance))
+}
+
+/// GET /api/accounts/:account_id/crypto-wallets
+pub async fn list_crypto_wallets(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(account_id): Path<Uuid>,
+) -> Result<Json<Vec<CryptoWallet>>, AppError> {
+    info!("GET /accounts/{}/crypto-wallets - Listing crypto wallets", account_id);
+    if !account_queries::belongs_to_user(&state.pool, account_id, user_id)
+        .await
+        .map_err(AppError::Db)?
+    {
+        return Err(AppError::NotFound(format!("Account {} not found", account_id)));
+    }
+    let wallets = crypto_wallet_queries::fetch_by_account(&state.pool, account_id)
+        .await
+        .map_err(AppError::Db)?;
+    Ok(Json(wallets))
+}
+
+/// POST /api/accounts/:account_id/crypto-wallets
+///
+/// Track a public `bitcoin` or `ethereum` address and import its balances right
+/// away. Only the address is stored; nothing can be signed or moved.
+pub async fn add_crypto_wallet(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(account_id): Path<Uuid>,
+    Json(data): Json<CreateCryptoWallet>,
+) -> Result<Json<CryptoWallet>, AppError> {
+    info!("POST /accounts/{}/crypto-wallets - Adding {:?} wallet", account_id, data.chain);
+    let wallet = crypto_wallet_service::add_wallet(&state.pool, account_id, data)
+        .await
+        .map_err(|e| {
+            error!("Failed to add crypto wallet for account {}: {}", account_id, e);
+            e
+        })?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(access.user_id, AuditAction::Create, "crypto_wallet", wallet.id)
+            .portfolio(access.portfolio_id)
+            .after(&wallet),
+    )
+    .await;
+    if let Err(e) = crypto_wallet_service::sync_account(
+        &state.pool,
+        account_id,
+        state.chain_provider.as_ref(),
+        state.price_provider.as_ref(),
+    )
+    .await
+    {
+        error!("Failed to sync crypto wallets for account {}: {}", account_id, e);
+    }
+    Ok(Json(wallet))
+}
+
+/// DELETE /api/accounts/:account_id/crypto-wallets/:wallet_id
+///
+/// Stops tracking the address; holdings already imported stay in the history.
+pub async fn delete_crypto_wallet(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path((account_id, wallet_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    info!("DELETE /accounts/{}/crypto-wallets/{} - Removing crypto wallet", account_id, wallet_id);
+    let previous = crypto_wallet_queries::fetch_by_account(&state.pool, account_id)
+        .await
+        .map_err(AppError::Db)?
+        .into_iter()
+        .find(|w| w.id == wallet_id);
+    let deleted = crypto_wallet_queries::delete(&state.pool, account_id, wallet_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete crypto wallet {}: {}", wallet_id, e);
+            AppError::Db(e)
+        })?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Crypto wallet {} not found", wallet_id)));
+    }
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::deleted(access.user_id, "crypto_wallet", wallet_id, previous.as_ref())
+            .portfolio(access.portfolio_id),
+    )
+    .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/accounts/:account_id/crypto-wallets/sync
+///
+/// Re-read the account's wallets now and write today's crypto holdings. When any
+/// address cannot be read, holdings are left unchanged and it is listed under
+/// `failed_wallets`.
+pub async fn sync_crypto_wallets(
+    State(state): State<AppState>,
+    _access: PortfolioAccess<CanEdit>,
+    Path(account_id): Path<Uuid>,
+) -> Result<Json<CryptoSyncResult>, AppError> {
+    info!("POST /accounts/{}/crypto-wallets/sync - Syncing crypto wallets", account_id);
+    let result = crypto_wallet_service::sync_account(
+        &state.pool,
+        account_id,
+        state.chain_provider.as_ref(),
+        state.price_provider.as_ref(),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to sync crypto wallets for account {}: {}", account_id, e);
+        e
+    })?;
+    Ok(Json(result))
+}
+
 pub async fn get_account_history(
     State(state): State<AppState>,
     AuthUser(user_id): AuthUser,
@@ -154,15 +589,11 @@ pub async fn get_portfolio_history(
 
 pub async fn create_account(
     State(state): State<AppState>,
-    AuthUser(user_id): AuthUser,
+    access: PortfolioAccess<CanEdit>,
     Path(portfolio_id): Path<Uuid>,
     Json(body): Json<CreateAccountRequest>,
 ) -> Result<Json<Account>, AppError> {
     info!("POST /portfolios/{}/accounts - Creating account manually", portfolio_id);
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
-        .await
-        .map_err(AppError::Db)?
-        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
     let account = account_queries::create(&state.pool, portfolio_id, CreateAccount {
         account_number: body.account_number,
         account_nickname: body.account_nickname,
@@ -174,27 +605,28 @@ pub async fn create_account(
         error!("Failed to create account for portfolio {}: {}", portfolio_id, e);
         AppError::Db(e)
     })?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(access.user_id, AuditAction::Create, "account", account.id)
+            .portfolio(portfolio_id)
+            .after(&account),
+    )
+    .await;
     Ok(Json(account))
 }
 
 pub async fn add_holding(
     State(state): State<AppState>,
-    AuthUser(user_id): AuthUser,
+    access: PortfolioAccess<CanEdit>,
     Path(account_id): Path<Uuid>,
     Json(body): Json<AddHoldingRequest>,
 ) -> Result<Json<HoldingSnapshot>, AppError> {
     info!("POST /accounts/{}/holdings - Adding holding manually", account_id);
-    if !account_queries::belongs_to_user(&state.pool, account_id, user_id)
-        .await
-        .map_err(AppError::Db)?
-    {
-        return Err(AppError::NotFound(format!("Account {} not found", account_id)));
-    }
     let snapshot_date = if let Some(date_str) = &body.snapshot_date {
         chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
             .map_err(|_| AppError::Validation("Invalid snapshot_date format, expected YYYY-MM-DD".to_string()))?
     } else {
-        chrono::Local::now().date_naive()
+        clock::today()
     };
     let quantity = to_decimal(body.quantity);
     let price = to_decimal(body.price);
@@ -207,8 +639,17 @@ pub async fn add_holding(
     } else {
         Some(BigDecimal::from(0))
     };
+    let ticker_type = TickerType::detect(&body.ticker, body.asset_category.as_deref());
+    let ticker = match ticker_type {
+        TickerType::Crypto => crypto_wallet_service::crypto_ticker(&body.ticker),
+        TickerType::Equity => body.ticker,
+    };
+    let currency = match body.currency.as_deref() {
+        Some(code) => fx_service::normalize_currency(code).map_err(AppError::Validation)?,
+        None => fx_service::currency_for_ticker(&ticker).to_string(),
+    };
     let holding = holding_snapshot_queries::upsert(&state.pool, account_id, snapshot_date, CreateHoldingSnapshot {
-        ticker: body.ticker,
+        ticker,
         holding_name: body.holding_name,
         asset_category: body.asset_category,
         industry: body.industry,
@@ -222,11 +663,20 @@ pub async fn add_holding(
         gain_loss: Some(gain_loss),
         gain_loss_pct,
         percentage_of_assets: None,
+        ticker_type,
+        currency,
     })
     .await
     .map_err(|e| {
         error!("Failed to add holding for account {}: {}", account_id, e);
         AppError::Db(e)
     })?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(access.user_id, AuditAction::Create, "holding", holding.id)
+            .portfolio(access.portfolio_id)
+            .after(&holding),
+    )
+    .await;
     Ok(Json(holding))
 }
@@ -1,4 +1,4 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::{Json, Router};
 use axum::routing::{get, post};
 use chrono::{DateTime, Utc};
@@ -7,12 +7,22 @@ use tracing::{info, error, warn};
 use uuid::Uuid;
 
 use crate::errors::AppError;
+use crate::middleware::permissions::AdminUser;
+use crate::models::risk_snapshot::{RiskPromoteRequest, RiskPromoteResult, RiskRecomputeReport, RiskRecomputeRequest};
+use crate::models::{IntegrityCheckRequest, IntegrityReport, LatencyReport, LatencyReportQuery, RateLimiterMetrics};
+use crate::services::{integrity_service, latency_monitor_service, risk_memo, risk_recompute_service};
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/admin/reset-all-data", post(reset_all_data))
         .route("/admin/cache-health", get(get_cache_health))
+        .route("/admin/latency", get(get_latency_report))
+        .route("/admin/rate-limiter", get(get_rate_limiter_metrics))
+        .route("/admin/risk-recompute", get(get_risk_recompute_report).post(recompute_risk_history))
+        .route("/admin/risk-recompute/promote", post(promote_risk_scores))
+        .route("/admin/integrity", get(get_integrity_report))
+        .route("/admin/integrity/check", post(run_integrity_check))
         // Note: Job-related routes are in routes/jobs.rs and mounted at /api/admin/jobs
 }
 
@@ -24,8 +34,9 @@ pub struct ResetResponse {
 
 pub async fn reset_all_data(
     State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
 ) -> Result<Json<ResetResponse>, AppError> {
-    info!("POST /admin/reset-all-data - Resetting all data");
+    info!("POST /admin/reset-all-data - Resetting all data (requested by {})", user_id);
 
     let tables = vec![
         "detected_transactions",
@@ -49,6 +60,7 @@ pub async fn reset_all_data(
         }
     }
 
+    risk_memo::clear();
     info!("Successfully reset all data");
 
     Ok(Json(ResetResponse {
@@ -57,6 +69,52 @@ pub async fn reset_all_data(
     }))
 }
 
+/// POST /api/admin/risk-recompute
+///
+/// Rescore stored risk snapshots under another scoring model version into
+/// their shadow columns, leaving the live scores untouched, and report how the
+/// new scores diverge. Body: { "scoring_version": 2, "portfolio_id"?, "from"?, "to"? }
+pub async fn recompute_risk_history(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    Json(request): Json<RiskRecomputeRequest>,
+) -> Result<Json<RiskRecomputeReport>, AppError> {
+    info!(
+        "POST /admin/risk-recompute - Rescoring history under version {} (requested by {})",
+        request.scoring_version, user_id
+    );
+    Ok(Json(risk_recompute_service::recompute(&state.pool, &request).await?))
+}
+
+/// GET /api/admin/risk-recompute?scoring_version=2
+///
+/// Divergence report of the shadow scores from an earlier recompute. Takes the
+/// same filters as the POST body as query parameters.
+pub async fn get_risk_recompute_report(
+    State(state): State<AppState>,
+    AdminUser(_): AdminUser,
+    Query(request): Query<RiskRecomputeRequest>,
+) -> Result<Json<RiskRecomputeReport>, AppError> {
+    info!("GET /admin/risk-recompute - Divergence report for version {}", request.scoring_version);
+    Ok(Json(risk_recompute_service::report(&state.pool, &request).await?))
+}
+
+/// POST /api/admin/risk-recompute/promote
+///
+/// Replace the live scores with the shadow scores of the current scoring version.
+/// Body: { "scoring_version": 2 }
+pub async fn promote_risk_scores(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    Json(request): Json<RiskPromoteRequest>,
+) -> Result<Json<RiskPromoteResult>, AppError> {
+    warn!(
+        "POST /admin/risk-recompute/promote - Promoting shadow scores of version {} (requested by {})",
+        request.scoring_version, user_id
+    );
+    Ok(Json(risk_recompute_service::promote(&state.pool, request.scoring_version).await?))
+}
+
 // ============================================================================
 // Cache Health Monitoring Models
 // ============================================================================
@@ -822,4 +880,65 @@ pub async fn invalidate_cache(
     }))
 }
 
+/// GET /api/admin/latency
+///
+/// Endpoints and SQL queries using the most of their p95 latency budget over the
+/// last 24 hours, each with its slowest recent samples (including request IDs
+/// for finding the matching log lines). Statistics are refreshed by the
+/// `check_latency_budgets` job every five minutes.
+///
+/// Query parameters: `limit` (per kind, default 10), `examples` (per offender,
+/// default 5), `breached_only` (default false).
+pub async fn get_latency_report(
+    State(state): State<AppState>,
+    Query(query): Query<LatencyReportQuery>,
+) -> Result<Json<LatencyReport>, AppError> {
+    info!("GET /api/admin/latency");
+
+    let report = latency_monitor_service::latency_report(&state.pool, &query).await?;
+    Ok(Json(report))
+}
+
+/// GET /api/admin/rate-limiter
+///
+/// Current state of the price API rate limiter: per-minute budget and tokens
+/// left in the trailing minute, queued callers, grant/delay/rejection counters
+/// since startup, and the last calibration against the provider's reported
+/// quota (run by the `calibrate_rate_limits` job every 15 minutes).
+pub async fn get_rate_limiter_metrics(
+    State(state): State<AppState>,
+) -> Result<Json<RateLimiterMetrics>, AppError> {
+    info!("GET /api/admin/rate-limiter");
+
+    Ok(Json(state.rate_limiter.metrics()))
+}
+
 // Note: Job-related admin endpoints are in routes/jobs.rs
+
+/// GET /api/admin/integrity
+///
+/// Findings of the last integrity check on this instance, run at startup
+/// unless INTEGRITY_CHECK_ON_STARTUP=false.
+pub async fn get_integrity_report(AdminUser(_): AdminUser) -> Result<Json<IntegrityReport>, AppError> {
+    info!("GET /admin/integrity - Last integrity report");
+    integrity_service::last_report()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("No integrity check has run yet".to_string()))
+}
+
+/// POST /api/admin/integrity/check
+///
+/// Run the integrity checks now. With { "repair": true }, orphaned snapshots
+/// and cache rows are deleted; other findings are only reported.
+pub async fn run_integrity_check(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+    Json(request): Json<IntegrityCheckRequest>,
+) -> Result<Json<IntegrityReport>, AppError> {
+    if request.repair {
+        warn!("POST /admin/integrity/check - Checking and repairing (requested by {})", user_id);
+    } else {
+        info!("POST /admin/integrity/check - Checking (requested by {})", user_id);
+    }
+    Ok(Json(integrity_service::run(&state.pool, request.repair).await?))
+}
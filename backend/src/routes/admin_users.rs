@@ -0,0 +1,97 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use serde::Deserialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::middleware::permissions::AdminUser;
+use crate::models::alert::User;
+use crate::models::Role;
+use crate::services::admin_user_service;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_users))
+        .route("/:user_id/disable", post(disable_user))
+        .route("/:user_id/enable", post(enable_user))
+        .route("/:user_id/reset-password", post(reset_password))
+        .route("/:user_id/role", put(set_role))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRoleRequest {
+    pub role: Role,
+}
+
+/// GET /api/admin/users
+///
+/// Every account on the instance, oldest first, with its role and whether it
+/// is disabled.
+pub async fn list_users(
+    State(state): State<AppState>,
+    AdminUser(admin_id): AdminUser,
+) -> Result<Json<Vec<User>>, AppError> {
+    info!("GET /api/admin/users (requested by {})", admin_id);
+
+    Ok(Json(admin_user_service::list(&state.pool).await?))
+}
+
+/// POST /api/admin/users/:user_id/disable
+///
+/// Blocks sign-in and ends the user's sessions; their data is kept.
+pub async fn disable_user(
+    State(state): State<AppState>,
+    AdminUser(admin_id): AdminUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<User>, AppError> {
+    info!("POST /api/admin/users/{}/disable (requested by {})", user_id, admin_id);
+
+    Ok(Json(admin_user_service::set_disabled(&state.pool, admin_id, user_id, true).await?))
+}
+
+/// POST /api/admin/users/:user_id/enable
+pub async fn enable_user(
+    State(state): State<AppState>,
+    AdminUser(admin_id): AdminUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<User>, AppError> {
+    info!("POST /api/admin/users/{}/enable (requested by {})", user_id, admin_id);
+
+    Ok(Json(admin_user_service::set_disabled(&state.pool, admin_id, user_id, false).await?))
+}
+
+/// POST /api/admin/users/:user_id/reset-password
+///
+/// Sets a new password and signs the user out everywhere.
+pub async fn reset_password(
+    State(state): State<AppState>,
+    AdminUser(admin_id): AdminUser,
+    Path(user_id): Path<Uuid>,
+    Json(req): Json<ResetPasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    info!("POST /api/admin/users/{}/reset-password (requested by {})", user_id, admin_id);
+
+    admin_user_service::reset_password(&state.pool, admin_id, user_id, &req.new_password).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /api/admin/users/:user_id/role
+pub async fn set_role(
+    State(state): State<AppState>,
+    AdminUser(admin_id): AdminUser,
+    Path(user_id): Path<Uuid>,
+    Json(req): Json<SetRoleRequest>,
+) -> Result<Json<User>, AppError> {
+    info!("PUT /api/admin/users/{}/role -> {} (requested by {})", user_id, req.role, admin_id);
+
+    Ok(Json(admin_user_service::set_role(&state.pool, admin_id, user_id, req.role).await?))
+}
@@ -75,6 +75,17 @@ async fn create_alert_rule(
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let pool = &state.pool;
 
+    if let AlertType::HoldingMove { percentage, gap_percentage } = &req.rule_type {
+        if *percentage <= 0.0 || gap_percentage.is_some_and(|gap| gap <= 0.0) {
+            return Err((StatusCode::BAD_REQUEST, "Holding move thresholds must be positive".to_string()));
+        }
+    }
+    if let AlertType::ExDividend { days_before } = &req.rule_type {
+        if !(0..=30).contains(days_before) {
+            return Err((StatusCode::BAD_REQUEST, "Ex-dividend reminders must be 0 to 30 days ahead".to_string()));
+        }
+    }
+
     let rule_type_json = serde_json::to_string(&req.rule_type)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to serialize rule_type: {}", e)))?;
 
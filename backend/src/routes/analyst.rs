@@ -0,0 +1,40 @@
+use axum::extract::{Path, Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use tracing::{error, info};
+
+use crate::errors::AppError;
+use crate::models::{AnalystConsensus, AnalystConsensusQuery};
+use crate::services::analyst_service;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/positions/:ticker/analyst-ratings", get(get_position_analyst_ratings))
+}
+
+/// GET /api/positions/:ticker/analyst-ratings
+///
+/// Analyst rating distribution, consensus and price targets, with upside/downside
+/// from the latest close to the mean target
+///
+/// Query parameters:
+/// - `force`: Refresh from the provider, bypassing the daily cache (default: false)
+async fn get_position_analyst_ratings(
+    Path(ticker): Path<String>,
+    Query(params): Query<AnalystConsensusQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<AnalystConsensus>, AppError> {
+    info!("GET /api/positions/{}/analyst-ratings - Fetching analyst consensus", ticker);
+    let consensus = analyst_service::get_consensus(
+        &state.pool,
+        state.analyst_provider.as_ref(),
+        &ticker,
+        params.force.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch analyst consensus for {}: {}", ticker, e);
+        e
+    })?;
+    Ok(Json(consensus))
+}
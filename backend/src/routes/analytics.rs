@@ -30,7 +30,7 @@ async fn get_analytics(
     portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
         .await.map_err(AppError::Db)?
         .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
-    services::analytics_service::get_analytics(&state.pool, portfolio_id)
+    services::analytics_service::get_analytics(&state.pool, state.fx_provider.as_ref(), portfolio_id)
         .await
         .map(Json)
 }
@@ -0,0 +1,33 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json,
+    Router,
+};
+use tracing::info;
+
+use crate::errors::AppError;
+use crate::middleware::auth::AuthUser;
+use crate::models::{AuditLogEntry, AuditLogQuery};
+use crate::services::audit_service;
+use crate::state::AppState;
+
+/// Create the audit log router
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(list_audit_log))
+}
+
+/// GET /api/audit
+///
+/// Recorded changes, newest first: the user's own and those to portfolios
+/// shared with them. Filter with `portfolio_id`, `from` and `to` (dates,
+/// inclusive), `entity_type` and `limit`.
+pub async fn list_audit_log(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<Vec<AuditLogEntry>>, AppError> {
+    info!("GET /api/audit for user {} ({:?})", user_id, query);
+
+    Ok(Json(audit_service::list(&state.pool, user_id, &query).await?))
+}
@@ -1,7 +1,7 @@
 use axum::{
-    extract::State,
-    http::{header, StatusCode},
-    response::IntoResponse,
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{AppendHeaders, IntoResponse, Redirect},
     routing::{get, post, put},
     Json, Router,
 };
@@ -10,20 +10,28 @@ use serde::{Deserialize, Serialize};
 use crate::auth;
 use crate::db::auth_queries;
 use crate::errors::AppError;
-use crate::middleware::auth::AuthUser;
+use crate::middleware::auth::{cookie_value, AuthSession, AuthUser};
+use crate::models::{OidcProviderInfo, Role};
+use crate::services::auth_service::{self, OidcConfig};
 use crate::services::notification_service;
+use crate::services::session_service::{self, SessionTokens};
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/register", post(register))
         .route("/login", post(login))
+        .route("/refresh", post(refresh))
         .route("/logout", post(logout))
+        .route("/logout-all", post(logout_all))
         .route("/me", get(me))
         .route("/profile", put(update_profile))
         .route("/change-password", put(change_password))
         .route("/request-password-reset", post(request_password_reset))
         .route("/reset-password", post(reset_password))
+        .route("/oidc/providers", get(oidc_providers))
+        .route("/oidc/login", get(oidc_login))
+        .route("/oidc/callback", get(oidc_callback))
 }
 
 // ==============================================================================
@@ -66,11 +74,27 @@ struct ResetPasswordRequest {
     new_password: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct OidcLoginQuery {
+    provider: String,
+    /// Local path to return to after signing in
+    redirect_to: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OidcCallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    /// Set by the provider when sign-in was denied or cancelled
+    error: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 struct UserResponse {
     id: uuid::Uuid,
     email: String,
     name: Option<String>,
+    role: Role,
 }
 
 #[derive(Debug, Serialize)]
@@ -123,7 +147,7 @@ async fn register(
         // Check BEFORE inserting so we know if this is the first real user
         let is_first_user = auth_queries::count_non_default_users(&state.pool).await? == 0;
 
-        let new_user = auth_queries::create_user_with_password(
+        let mut new_user = auth_queries::create_user_with_password(
             &state.pool,
             &email,
             req.name.as_deref(),
@@ -133,10 +157,9 @@ async fn register(
 
         // Migrate existing default-user data to the first real registrant
         if is_first_user {
-            if let Err(e) =
-                auth_queries::migrate_default_user_data(&state.pool, new_user.id).await
-            {
-                tracing::warn!("Data migration for first user failed: {}", e);
+            match auth_queries::migrate_default_user_data(&state.pool, new_user.id).await {
+                Ok(()) => new_user.role = Role::Admin,
+                Err(e) => tracing::warn!("Data migration for first user failed: {}", e),
             }
         }
 
@@ -147,6 +170,7 @@ async fn register(
         id: user.id,
         email: user.email,
         name: user.name,
+        role: user.role,
     };
 
     Ok((StatusCode::CREATED, Json(response)))
@@ -154,6 +178,7 @@ async fn register(
 
 async fn login(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(req): Json<LoginRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let email = req.email.trim().to_lowercase();
@@ -171,33 +196,86 @@ async fn login(
         return Err(AppError::Unauthorized);
     }
 
-    let token = auth::create_jwt(user.id, &state.jwt_secret)
-        .map_err(|e| AppError::External(format!("Token creation failed: {}", e)))?;
-
-    let cookie = format!(
-        "auth_token={}; HttpOnly; SameSite=Strict; Path=/; Max-Age=86400",
-        token
-    );
+    let tokens = session_service::start(&state.pool, &state.jwt_secret, user.id, user_agent(&headers)).await?;
 
     let response = UserResponse {
         id: user.id,
         email: user.email,
         name: user.name,
+        role: user.role,
     };
 
     Ok((
         StatusCode::OK,
-        [(header::SET_COOKIE, cookie)],
+        session_cookies(&tokens),
         Json(response),
     ))
 }
 
-async fn logout() -> impl IntoResponse {
-    let clear_cookie = "auth_token=; HttpOnly; SameSite=Strict; Path=/; Max-Age=0";
-    (
-        StatusCode::NO_CONTENT,
-        [(header::SET_COOKIE, clear_cookie)],
-    )
+fn user_agent(headers: &HeaderMap) -> Option<&str> {
+    headers.get(header::USER_AGENT).and_then(|v| v.to_str().ok())
+}
+
+/// The access token goes to every API call; the refresh token only to the
+/// auth routes that need it
+fn session_cookies(tokens: &SessionTokens) -> AppendHeaders<[(HeaderName, String); 2]> {
+    AppendHeaders([
+        (
+            header::SET_COOKIE,
+            format!(
+                "auth_token={}; HttpOnly; SameSite=Strict; Path=/; Max-Age={}",
+                tokens.access_token,
+                auth::ACCESS_TOKEN_TTL_MINUTES * 60
+            ),
+        ),
+        (
+            header::SET_COOKIE,
+            format!(
+                "refresh_token={}; HttpOnly; SameSite=Strict; Path=/api/auth; Max-Age={}",
+                tokens.refresh_token,
+                auth::REFRESH_TOKEN_TTL_DAYS * 24 * 60 * 60
+            ),
+        ),
+    ])
+}
+
+fn cleared_cookies() -> AppendHeaders<[(HeaderName, &'static str); 2]> {
+    AppendHeaders([
+        (header::SET_COOKIE, "auth_token=; HttpOnly; SameSite=Strict; Path=/; Max-Age=0"),
+        (header::SET_COOKIE, "refresh_token=; HttpOnly; SameSite=Strict; Path=/api/auth; Max-Age=0"),
+    ])
+}
+
+/// Renew the access token with the refresh token cookie. The refresh token is
+/// replaced too, so each one works only once.
+async fn refresh(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let refresh_token = cookie_value(&headers, "refresh_token").ok_or(AppError::Unauthorized)?;
+    let tokens = session_service::refresh(&state.pool, &state.jwt_secret, &refresh_token).await?;
+    Ok((StatusCode::NO_CONTENT, session_cookies(&tokens)))
+}
+
+/// End this browser's session
+async fn logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    if let Some(refresh_token) = cookie_value(&headers, "refresh_token") {
+        session_service::end(&state.pool, &refresh_token).await?;
+    }
+    Ok((StatusCode::NO_CONTENT, cleared_cookies()))
+}
+
+/// End every session of the user, signing out all their browsers and devices
+async fn logout_all(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let ended = session_service::end_all(&state.pool, user_id, None).await?;
+    tracing::info!("Ended {} sessions for user {}", ended, user_id);
+    Ok((StatusCode::NO_CONTENT, cleared_cookies()))
 }
 
 async fn me(
@@ -210,6 +288,7 @@ async fn me(
         id: user.id,
         email: user.email,
         name: user.name,
+        role: user.role,
     };
 
     Ok(Json(response))
@@ -244,12 +323,13 @@ async fn update_profile(
         id: user.id,
         email: user.email,
         name: user.name,
+        role: user.role,
     }))
 }
 
 async fn change_password(
     State(state): State<AppState>,
-    AuthUser(user_id): AuthUser,
+    AuthSession { user_id, session_id }: AuthSession,
     Json(req): Json<ChangePasswordRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     if req.new_password.len() < 8 {
@@ -272,6 +352,8 @@ async fn change_password(
         .map_err(|e| AppError::External(format!("Password hashing failed: {}", e)))?;
 
     auth_queries::update_user_password(&state.pool, user_id, &new_hash).await?;
+    // Sign out everywhere else; this browser stays signed in
+    session_service::end_all(&state.pool, user_id, Some(session_id)).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -322,6 +404,65 @@ async fn reset_password(
         .map_err(|e| AppError::External(format!("Password hashing failed: {}", e)))?;
 
     auth_queries::update_user_password(&state.pool, user_id, &new_hash).await?;
+    session_service::end_all(&state.pool, user_id, None).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+// ==============================================================================
+// Single sign-on
+// ==============================================================================
+
+async fn oidc_providers() -> Json<Vec<OidcProviderInfo>> {
+    Json(OidcConfig::from_env().provider_infos())
+}
+
+/// Redirect the browser to the provider's sign-in page
+async fn oidc_login(
+    State(state): State<AppState>,
+    Query(query): Query<OidcLoginQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let config = OidcConfig::from_env();
+    let url = auth_service::begin_login(&state.pool, &config, &query.provider, query.redirect_to.as_deref()).await?;
+    Ok(Redirect::to(url.as_str()))
+}
+
+/// The provider sends the browser back here. Success sets the session cookies
+/// like a password login; failures go back to the app with `sso_error` set.
+async fn oidc_callback(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<OidcCallbackQuery>,
+) -> impl IntoResponse {
+    let failure = |message: &str| {
+        let encoded: String = url::form_urlencoded::byte_serialize(message.as_bytes()).collect();
+        Redirect::to(&format!("/?sso_error={}", encoded)).into_response()
+    };
+
+    let (code, login_state) = match (query.code, query.state, query.error) {
+        (_, _, Some(error)) => {
+            tracing::info!("Sign-in cancelled at the provider: {}", error);
+            return failure("Sign-in was cancelled");
+        }
+        (Some(code), Some(login_state), None) => (code, login_state),
+        _ => return failure("Sign-in failed"),
+    };
+
+    let config = OidcConfig::from_env();
+    let (user, redirect_to) = match auth_service::complete_login(&state.pool, &config, &code, &login_state).await {
+        Ok(result) => result,
+        Err(AppError::Validation(message)) => return failure(&message),
+        Err(e) => {
+            tracing::warn!("Sign-in failed: {}", e);
+            return failure("Sign-in failed");
+        }
+    };
+
+    match session_service::start(&state.pool, &state.jwt_secret, user.id, user_agent(&headers)).await {
+        Ok(tokens) => (session_cookies(&tokens), Redirect::to(&redirect_to)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to start session: {}", e);
+            failure("Sign-in failed")
+        }
+    }
+}
@@ -7,6 +7,7 @@ use uuid::Uuid;
 use crate::db::{account_queries, cash_flow_queries};
 use crate::errors::AppError;
 use crate::middleware::auth::AuthUser;
+use crate::middleware::permissions::{CanEdit, PortfolioAccess};
 use crate::models::{CashFlow, CreateCashFlow};
 use crate::state::AppState;
 
@@ -18,17 +19,11 @@ pub fn router() -> Router<AppState> {
 
 pub async fn create_cash_flow(
     State(state): State<AppState>,
-    AuthUser(user_id): AuthUser,
+    _access: PortfolioAccess<CanEdit>,
     Path(account_id): Path<Uuid>,
     Json(data): Json<CreateCashFlow>,
 ) -> Result<Json<CashFlow>, AppError> {
     info!("POST /accounts/{}/cash-flows - Creating cash flow", account_id);
-    if !account_queries::belongs_to_user(&state.pool, account_id, user_id)
-        .await
-        .map_err(AppError::Db)?
-    {
-        return Err(AppError::NotFound(format!("Account {} not found", account_id)));
-    }
     let cash_flow = cash_flow_queries::create(&state.pool, account_id, data)
         .await
         .map_err(|e| {
@@ -0,0 +1,43 @@
+use axum::routing::get;
+use axum::{Json, Router};
+use tracing::{info, warn};
+
+use crate::errors::AppError;
+use crate::middleware::permissions::AdminUser;
+use crate::services::chaos::{self, Chaos, ChaosConfig};
+use crate::state::AppState;
+
+/// Only mounted when `CHAOS_ENABLED=true`
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(get_config).put(update_config))
+}
+
+fn installed() -> Result<std::sync::Arc<Chaos>, AppError> {
+    chaos::installed().ok_or_else(|| AppError::NotFound("Chaos injection is not enabled".to_string()))
+}
+
+/// GET /api/admin/chaos
+///
+/// Faults currently injected into price provider and database calls.
+pub async fn get_config(
+    AdminUser(admin_id): AdminUser,
+) -> Result<Json<ChaosConfig>, AppError> {
+    info!("GET /api/admin/chaos (requested by {})", admin_id);
+
+    Ok(Json(installed()?.config()))
+}
+
+/// PUT /api/admin/chaos
+///
+/// Replaces the injected faults, e.g. to fail every provider call for the
+/// duration of one test. Omitted fields are set to zero.
+pub async fn update_config(
+    AdminUser(admin_id): AdminUser,
+    Json(config): Json<ChaosConfig>,
+) -> Result<Json<ChaosConfig>, AppError> {
+    info!("PUT /api/admin/chaos (requested by {})", admin_id);
+
+    let config = installed()?.update(config)?;
+    warn!("Chaos faults changed by {}: {:?}", admin_id, config);
+    Ok(Json(config))
+}
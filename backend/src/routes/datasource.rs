@@ -0,0 +1,59 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use tracing::info;
+
+use crate::errors::AppError;
+use crate::middleware::auth::PublicApiUser;
+use crate::models::{DatasourceMetric, DatasourceQueryRequest, DatasourceSearchRequest, DatasourceSeries};
+use crate::services::datasource_service;
+use crate::state::AppState;
+
+/// Grafana SimpleJSON datasource, mounted at /api/datasource alongside the
+/// public API and authenticated by the same tokens
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(test_connection))
+        .route("/search", post(search_metrics))
+        .route("/query", post(query_metrics))
+}
+
+/// GET /api/datasource - Grafana's "Save & test"
+pub async fn test_connection(PublicApiUser(_user_id): PublicApiUser) -> StatusCode {
+    StatusCode::OK
+}
+
+/// POST /api/datasource/search
+pub async fn search_metrics(
+    State(state): State<AppState>,
+    PublicApiUser(user_id): PublicApiUser,
+    body: Option<Json<DatasourceSearchRequest>>,
+) -> Result<Json<Vec<DatasourceMetric>>, AppError> {
+    let filter = body.and_then(|Json(b)| b.target).unwrap_or_default();
+    info!("POST /api/datasource/search '{}' for user {}", filter, user_id);
+    Ok(Json(datasource_service::search(&state.pool, user_id, &filter).await?))
+}
+
+/// POST /api/datasource/query
+pub async fn query_metrics(
+    State(state): State<AppState>,
+    PublicApiUser(user_id): PublicApiUser,
+    Json(body): Json<DatasourceQueryRequest>,
+) -> Result<Json<Vec<DatasourceSeries>>, AppError> {
+    info!("POST /api/datasource/query with {} targets for user {}", body.targets.len(), user_id);
+    if body.range.from > body.range.to {
+        return Err(AppError::Validation("range.from must not be after range.to".to_string()));
+    }
+    let series = datasource_service::query(
+        &state.pool,
+        state.price_provider.as_ref(),
+        &state.failure_cache,
+        state.rate_limiter.as_ref(),
+        state.risk_free_rate,
+        user_id,
+        &body,
+    )
+    .await?;
+    Ok(Json(series))
+}
@@ -68,6 +68,7 @@ pub fn router() -> Router<AppState> {
         .route("/surveys/:id/goals", get(get_goals))
         .route("/surveys/:survey_id/goals/:goal_id", put(update_goal))
         .route("/surveys/:survey_id/goals/:goal_id", delete(delete_goal))
+        .route("/surveys/:survey_id/goals/:goal_id/probability-history", get(get_goal_probability_history))
         // Risk profile
         .route("/surveys/:id/risk-profile", put(upsert_risk_profile))
         .route("/surveys/:id/risk-profile", get(get_risk_profile))
@@ -742,6 +743,21 @@ async fn delete_goal(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Daily Monte Carlo success probabilities of a goal, oldest first
+async fn get_goal_probability_history(
+    State(state): State<AppState>,
+    Path((survey_id, goal_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let pool = &state.pool;
+
+    let history = financial_planning_queries::get_goal_probability_history(pool, survey_id, goal_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let responses: Vec<GoalProbabilityResponse> = history.into_iter().map(GoalProbabilityResponse::from).collect();
+    Ok(Json(responses))
+}
+
 // ==============================================================================
 // Risk Profile Handlers
 // ==============================================================================
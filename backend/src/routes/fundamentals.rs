@@ -0,0 +1,57 @@
+use axum::extract::{Path, Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use tracing::{error, info};
+
+use crate::errors::AppError;
+use crate::models::{FundamentalsHistory, FundamentalsQuery, FundamentalsSnapshot};
+use crate::services::fundamentals_service;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/positions/:ticker/fundamentals", get(get_position_fundamentals))
+        .route("/positions/:ticker/fundamentals/history", get(get_position_fundamentals_history))
+}
+
+/// GET /api/positions/:ticker/fundamentals
+///
+/// P/E, P/B, market cap and key ratios. Fetching stores them for screening and
+/// factor scoring.
+///
+/// Query parameters:
+/// - `force`: Refresh from the provider, bypassing the daily cache (default: false)
+async fn get_position_fundamentals(
+    Path(ticker): Path<String>,
+    Query(params): Query<FundamentalsQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<FundamentalsSnapshot>, AppError> {
+    info!("GET /api/positions/{}/fundamentals - Fetching fundamentals", ticker);
+    let provider = state.fundamentals_provider.as_ref().ok_or_else(|| {
+        AppError::ServiceUnavailable("No fundamentals provider configured (set FINNHUB_API_KEY)".to_string())
+    })?;
+    let snapshot = fundamentals_service::get_snapshot(
+        &state.pool,
+        provider.as_ref(),
+        &ticker,
+        params.force.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch fundamentals for {}: {}", ticker, e);
+        e
+    })?;
+    Ok(Json(snapshot))
+}
+
+/// GET /api/positions/:ticker/fundamentals/history
+///
+/// Stored quarterly earnings and dividends (last five years) with TTM EPS and
+/// dividend growth. The daily fundamentals job keeps held tickers current.
+async fn get_position_fundamentals_history(
+    Path(ticker): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<FundamentalsHistory>, AppError> {
+    info!("GET /api/positions/{}/fundamentals/history - Fetching earnings and dividends", ticker);
+    Ok(Json(fundamentals_service::history(&state.pool, &ticker).await?))
+}
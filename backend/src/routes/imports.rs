@@ -1,23 +1,52 @@
-use axum::extract::{Path, State};
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::StatusCode;
 use axum::{Json, Router};
-use axum::routing::{get, post};
-use chrono::Local;
+use axum::middleware::map_response;
+use axum::response::Response;
+use axum::routing::{delete, get, post};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
 use tracing::{info, error};
 use uuid::Uuid;
 use std::path::PathBuf;
 
-use crate::db::portfolio_queries;
+use crate::db::{csv_import_template_queries, import_batch_queries, portfolio_queries};
 use crate::errors::AppError;
 use crate::middleware::auth::AuthUser;
-use crate::services::{csv_import_service, activity_import_service};
+use crate::middleware::permissions::{CanEdit, PortfolioAccess};
+use crate::middleware::body_limit;
+use crate::models::{
+    AuditAction, ColumnMapping, CsvImportPreview, CsvImportTemplate, ImportBatch, ImportChangeSummary, ImportQuery, ImportRollback,
+    NewAuditEntry, PdfImportPreview, SaveCsvImportTemplate,
+};
+use crate::services::{
+    audit_service, csv_import_service, activity_import_service, import_batch_service, pdf_statement_service, wash_sale_service,
+};
 use crate::state::AppState;
+use crate::services::clock;
 
 pub fn router() -> Router<AppState> {
+    // Uploads carry the whole file inline, so they get a larger (configurable)
+    // limit than axum's 2 MB default, with a 413 that says what the limit is
+    let limit = body_limit::import_body_limit();
     Router::new()
         .route("/portfolios/:portfolio_id/import", post(import_csv))
         .route("/portfolios/:portfolio_id/import/upload", post(upload_import))
+        .route("/portfolios/:portfolio_id/import/preview", post(preview_import))
+        .route("/portfolios/:portfolio_id/import/pdf/preview", post(preview_pdf_import))
+        .route("/import/pdf/brokers", get(list_pdf_brokers))
+        .route("/portfolios/:portfolio_id/import/confirm", post(confirm_import))
+        .route("/import/templates", get(list_import_templates))
+        .route("/import/templates/:template_id", delete(delete_import_template))
+        .route("/portfolios/:portfolio_id/import/batches", get(list_import_batches))
+        .route("/import/batches/:batch_id/rollback", post(rollback_import_batch))
         .route("/import/files", get(list_csv_files))
+        .layer(DefaultBodyLimit::max(limit))
+        .layer(map_response(move |response: Response| async move {
+            body_limit::explain_payload_too_large(response, limit)
+        }))
 }
 
 #[derive(Debug, Deserialize)]
@@ -34,13 +63,99 @@ pub struct UploadImportRequest {
     pub snapshot_date: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PreviewImportRequest {
+    pub content: String,
+    /// Limits the saved templates considered to one broker
+    pub broker: Option<String>,
+}
+
+/// First step of a PDF statement import
+#[derive(Debug, Deserialize)]
+pub struct PdfPreviewRequest {
+    pub filename: Option<String>,
+    /// The statement, base64 encoded
+    pub content_base64: String,
+    /// Statement layout to read it with (see `/import/pdf/brokers`); detected when absent
+    pub broker: Option<String>,
+    /// Account the holdings belong to, for statements that do not print one
+    pub account_number: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PdfBrokerResponse {
+    pub id: &'static str,
+    pub name: &'static str,
+}
+
+/// Second step of a mapped import. The mapping comes from `mapping` when given,
+/// otherwise from the saved template `template_id`.
+#[derive(Debug, Deserialize)]
+pub struct ConfirmImportRequest {
+    pub content: String,
+    pub snapshot_date: Option<String>,
+    pub mapping: Option<ColumnMapping>,
+    pub template_id: Option<Uuid>,
+    /// Save the mapping as a template for this broker's statements
+    pub save_template: Option<SaveCsvImportTemplate>,
+    /// "pdf_holdings" when the content came from a PDF preview; defaults to "mapped_holdings"
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfirmImportResponse {
+    #[serde(flatten)]
+    pub import: ImportResponse,
+    pub template: Option<CsvImportTemplate>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ImportResponse {
     pub accounts_created: usize,
     pub holdings_created: usize,
     pub transactions_detected: usize,
     pub errors: Vec<String>,
+    /// Non-fatal issues with the imported data, such as newly detected wash sales
+    pub warnings: Vec<String>,
     pub snapshot_date: String,
+    /// True when nothing was written and `changes` describes what the import would do
+    pub dry_run: bool,
+    /// Batch to roll back to undo the import; absent for a dry run
+    pub import_batch_id: Option<Uuid>,
+    /// Rows inserted, updated and deleted per table
+    pub changes: Vec<ImportChangeSummary>,
+}
+
+impl From<csv_import_service::ImportResult> for ImportResponse {
+    fn from(result: csv_import_service::ImportResult) -> Self {
+        ImportResponse {
+            accounts_created: result.accounts_created,
+            holdings_created: result.holdings_created,
+            transactions_detected: result.transactions_detected,
+            errors: result.errors,
+            warnings: Vec::new(),
+            snapshot_date: result.snapshot_date.to_string(),
+            dry_run: false,
+            import_batch_id: None,
+            changes: Vec::new(),
+        }
+    }
+}
+
+impl From<activity_import_service::ActivityImportResult> for ImportResponse {
+    fn from(result: activity_import_service::ActivityImportResult) -> Self {
+        ImportResponse {
+            accounts_created: 0,
+            holdings_created: 0,
+            transactions_detected: result.transactions_imported,
+            errors: result.errors,
+            warnings: Vec::new(),
+            snapshot_date: "N/A".to_string(), // Activities don't have a snapshot date
+            dry_run: false,
+            import_batch_id: None,
+            changes: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -144,10 +259,109 @@ fn extract_activity_date_from_filename(filename: &str) -> Option<String> {
     None
 }
 
+/// Re-scan the portfolio for wash sales after new transactions were recorded and
+/// describe the newly flagged ones. Failures are logged; the import itself succeeded.
+async fn wash_sale_warnings(state: &AppState, portfolio_id: Uuid, transactions: usize) -> Vec<String> {
+    if transactions == 0 {
+        return Vec::new();
+    }
+    match wash_sale_service::refresh_portfolio_wash_sales(&state.pool, portfolio_id).await {
+        Ok(scan) => {
+            if !scan.wash_sales.is_empty() {
+                info!(
+                    "Portfolio {} has {} wash sales ({} new)",
+                    portfolio_id,
+                    scan.wash_sales.len(),
+                    scan.newly_flagged.len()
+                );
+            }
+            scan.newly_flagged.iter().map(wash_sale_service::warning_message).collect()
+        }
+        Err(e) => {
+            error!("Failed to check wash sales for portfolio {}: {}", portfolio_id, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Open the transaction an import runs in, with every row it writes recorded
+/// under a new batch
+async fn begin_import(
+    state: &AppState,
+    user_id: Uuid,
+    portfolio_id: Uuid,
+    source: &str,
+    filename: Option<&str>,
+) -> Result<(Transaction<'static, Postgres>, ImportBatch), AppError> {
+    let mut tx = state.pool.begin().await.map_err(AppError::Db)?;
+    let batch = import_batch_queries::begin(&mut tx, user_id, portfolio_id, source, filename)
+        .await
+        .map_err(AppError::Db)?;
+    Ok((tx, batch))
+}
+
+/// Commit an import, or for a dry run report what it changed and discard it
+async fn finish_import(
+    state: &AppState,
+    mut tx: Transaction<'static, Postgres>,
+    batch: &ImportBatch,
+    dry_run: bool,
+    mut response: ImportResponse,
+) -> Result<ImportResponse, AppError> {
+    response.changes = import_batch_queries::change_summary(&mut *tx, batch.id)
+        .await
+        .map_err(AppError::Db)?;
+
+    if dry_run {
+        tx.rollback().await.map_err(AppError::Db)?;
+        response.dry_run = true;
+        info!("Dry run of {} import into portfolio {} discarded", batch.source, batch.portfolio_id);
+        return Ok(response);
+    }
+
+    import_batch_queries::record_counts(
+        &mut *tx,
+        batch.id,
+        response.accounts_created,
+        response.holdings_created,
+        response.transactions_detected,
+        response.errors.len(),
+    )
+    .await
+    .map_err(AppError::Db)?;
+    tx.commit().await.map_err(AppError::Db)?;
+
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(batch.user_id, AuditAction::Create, "import_batch", batch.id)
+            .portfolio(batch.portfolio_id)
+            .after(&serde_json::json!({
+                "source": batch.source,
+                "filename": batch.filename,
+                "changes": response.changes,
+            })),
+    )
+    .await;
+
+    response.import_batch_id = Some(batch.id);
+    response.warnings = wash_sale_warnings(state, batch.portfolio_id, response.transactions_detected).await;
+    Ok(response)
+}
+
+/// Snapshot date of an uploaded holdings file, defaulting to today
+fn parse_snapshot_date(date_str: Option<&str>) -> Result<chrono::NaiveDate, AppError> {
+    match date_str {
+        Some(date_str) => chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+            .map_err(|e| AppError::Validation(format!("Invalid snapshot_date format: {}", e))),
+        None => Ok(clock::today()),
+    }
+}
+
 pub async fn upload_import(
     State(state): State<AppState>,
-    AuthUser(user_id): AuthUser,
+    access: PortfolioAccess<CanEdit>,
     Path(portfolio_id): Path<Uuid>,
+    Query(query): Query<ImportQuery>,
     Json(data): Json<UploadImportRequest>,
 ) -> Result<Json<ImportResponse>, AppError> {
     info!(
@@ -156,10 +370,6 @@ pub async fn upload_import(
     );
 
     // Validate portfolio ownership
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
-        .await
-        .map_err(AppError::Db)?
-        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
 
     match data.format.as_str() {
         "rj_activities" => {
@@ -167,8 +377,10 @@ pub async fn upload_import(
                 AppError::Validation("account_id is required for rj_activities format".to_string())
             })?;
 
+            let (mut tx, batch) =
+                begin_import(&state, access.user_id, portfolio_id, &data.format, Some(&data.filename)).await?;
             let result = activity_import_service::import_activities_content(
-                &state.pool,
+                &mut tx,
                 account_id,
                 &data.content,
             )
@@ -184,25 +396,16 @@ pub async fn upload_import(
                 result.errors.len()
             );
 
-            Ok(Json(ImportResponse {
-                accounts_created: 0,
-                holdings_created: 0,
-                transactions_detected: result.transactions_imported,
-                errors: result.errors,
-                snapshot_date: "N/A".to_string(),
-            }))
+            let response = finish_import(&state, tx, &batch, query.dry_run, result.into()).await?;
+            Ok(Json(response))
         }
         "rj_holdings" => {
-            let snapshot_date = if let Some(date_str) = data.snapshot_date {
-                chrono::NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").map_err(|e| {
-                    AppError::Validation(format!("Invalid snapshot_date format: {}", e))
-                })?
-            } else {
-                Local::now().date_naive()
-            };
+            let snapshot_date = parse_snapshot_date(data.snapshot_date.as_deref())?;
 
+            let (mut tx, batch) =
+                begin_import(&state, access.user_id, portfolio_id, &data.format, Some(&data.filename)).await?;
             let result = csv_import_service::import_csv_content(
-                &state.pool,
+                &mut tx,
                 portfolio_id,
                 &data.content,
                 snapshot_date,
@@ -221,29 +424,218 @@ pub async fn upload_import(
                 result.errors.len()
             );
 
-            Ok(Json(ImportResponse {
-                accounts_created: result.accounts_created,
-                holdings_created: result.holdings_created,
-                transactions_detected: result.transactions_detected,
-                errors: result.errors,
-                snapshot_date: result.snapshot_date.to_string(),
-            }))
+            let response = finish_import(&state, tx, &batch, query.dry_run, result.into()).await?;
+            Ok(Json(response))
         }
         _ => Err(AppError::Validation("Unknown format".to_string())),
     }
 }
 
-pub async fn import_csv(
+/// First step of a mapped holdings import: detect how the file's columns map
+/// to holding fields, using a saved template when one matches its headers
+pub async fn preview_import(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+    Json(data): Json<PreviewImportRequest>,
+) -> Result<Json<CsvImportPreview>, AppError> {
+    info!("POST /portfolios/{}/import/preview - Previewing CSV mapping", portfolio_id);
+
+    let preview = csv_import_service::preview_csv_content(
+        &state.pool,
+        access.user_id,
+        &data.content,
+        data.broker.as_deref(),
+    )
+    .await
+    .map_err(|e| AppError::Validation(format!("Failed to read CSV: {}", e)))?;
+
+    info!(
+        "CSV preview: {} columns, {} rows, confidence {:.2}, template {:?}",
+        preview.headers.len(),
+        preview.total_rows,
+        preview.confidence,
+        preview.template.as_ref().map(|t| t.id)
+    );
+    Ok(Json(preview))
+}
+
+/// First step of a PDF statement import: read the holdings table and convert it
+/// to a holdings CSV, previewed like an upload. The import is confirmed by
+/// posting the returned `content` to `/import/confirm`.
+pub async fn preview_pdf_import(
+    _access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+    Json(data): Json<PdfPreviewRequest>,
+) -> Result<Json<PdfImportPreview>, AppError> {
+    info!(
+        "POST /portfolios/{}/import/pdf/preview - Reading PDF statement {}",
+        portfolio_id,
+        data.filename.as_deref().unwrap_or("(unnamed)")
+    );
+
+    let pdf = STANDARD
+        .decode(data.content_base64.trim())
+        .map_err(|e| AppError::Validation(format!("content_base64 is not valid base64: {}", e)))?;
+    // Parsing is CPU-bound; keep it off the async workers
+    let preview = tokio::task::spawn_blocking(move || {
+        pdf_statement_service::preview_statement(&pdf, data.broker.as_deref(), data.account_number.as_deref())
+    })
+    .await
+    .map_err(|e| AppError::External(format!("PDF reader failed: {}", e)))?
+    .map_err(|e| AppError::Validation(format!("Failed to read PDF statement: {}", e)))?;
+
+    info!(
+        "PDF preview: {} statement, {} pages, {} holdings, {} warnings",
+        preview.broker,
+        preview.pages,
+        preview.preview.total_rows,
+        preview.warnings.len()
+    );
+    Ok(Json(preview))
+}
+
+/// Statement layouts the PDF import can read
+pub async fn list_pdf_brokers() -> Json<Vec<PdfBrokerResponse>> {
+    let brokers = pdf_statement_service::BROKERS
+        .iter()
+        .chain(std::iter::once(&pdf_statement_service::GENERIC_BROKER))
+        .map(|b| PdfBrokerResponse { id: b.id, name: b.name })
+        .collect();
+    Json(brokers)
+}
+
+/// Second step of a mapped holdings import: import with the confirmed mapping
+/// and optionally save it as a template
+pub async fn confirm_import(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+    Query(query): Query<ImportQuery>,
+    Json(data): Json<ConfirmImportRequest>,
+) -> Result<Json<ConfirmImportResponse>, AppError> {
+    info!("POST /portfolios/{}/import/confirm - Importing mapped CSV", portfolio_id);
+
+    let snapshot_date = parse_snapshot_date(data.snapshot_date.as_deref())?;
+
+    let mut template = match data.template_id {
+        Some(template_id) => Some(
+            csv_import_template_queries::fetch_one(&state.pool, access.user_id, template_id)
+                .await
+                .map_err(AppError::Db)?
+                .ok_or_else(|| AppError::NotFound(format!("Import template {} not found", template_id)))?,
+        ),
+        None => None,
+    };
+    let mapping = match (data.mapping, &template) {
+        (Some(mapping), _) => mapping,
+        (None, Some(template)) => template.mapping.0.clone(),
+        (None, None) => {
+            return Err(AppError::Validation("Either mapping or template_id is required".to_string()));
+        }
+    };
+
+    let save_broker = match &data.save_template {
+        Some(save) if save.broker.trim().is_empty() => {
+            return Err(AppError::Validation("Template broker must not be empty".to_string()));
+        }
+        Some(save) => Some(save.broker.trim().to_string()),
+        None => None,
+    };
+
+    let source = match data.source.as_deref() {
+        None | Some("mapped_holdings") => "mapped_holdings",
+        Some("pdf_holdings") => "pdf_holdings",
+        Some(other) => return Err(AppError::Validation(format!("Unknown import source '{}'", other))),
+    };
+
+    let (mut tx, batch) = begin_import(&state, access.user_id, portfolio_id, source, None).await?;
+    let result = csv_import_service::import_mapped_csv_content(
+        &mut tx,
+        portfolio_id,
+        &data.content,
+        snapshot_date,
+        &mapping,
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to import mapped holdings content: {}", e);
+        AppError::Validation(format!("Failed to import holdings: {}", e))
+    })?;
+
+    info!(
+        "Mapped holdings import completed: {} accounts, {} holdings, {} transactions, {} errors",
+        result.accounts_created,
+        result.holdings_created,
+        result.transactions_detected,
+        result.errors.len()
+    );
+
+    let response = finish_import(&state, tx, &batch, query.dry_run, result.into()).await?;
+
+    // A dry run leaves templates alone, like everything else
+    if !query.dry_run {
+        if let Some(broker) = save_broker {
+            let name = data
+                .save_template
+                .as_ref()
+                .and_then(|t| t.name.as_deref())
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .unwrap_or(&broker);
+            let signature = csv_import_service::csv_header_signature(&data.content)
+                .map_err(|e| AppError::Validation(format!("Failed to read CSV: {}", e)))?;
+            template = Some(
+                csv_import_template_queries::upsert(&state.pool, access.user_id, &broker, name, &signature, &mapping)
+                    .await
+                    .map_err(AppError::Db)?,
+            );
+        } else if let Some(used) = &template {
+            template = Some(
+                csv_import_template_queries::mark_used(&state.pool, used.id)
+                    .await
+                    .map_err(AppError::Db)?,
+            );
+        }
+    }
+
+    Ok(Json(ConfirmImportResponse { import: response, template }))
+}
+
+pub async fn list_import_templates(
     State(state): State<AppState>,
     AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<CsvImportTemplate>>, AppError> {
+    info!("GET /import/templates - Listing CSV import templates");
+    let templates = csv_import_template_queries::fetch_by_user(&state.pool, user_id)
+        .await
+        .map_err(AppError::Db)?;
+    Ok(Json(templates))
+}
+
+pub async fn delete_import_template(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(template_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("DELETE /import/templates/{} - Deleting CSV import template", template_id);
+    let deleted = csv_import_template_queries::delete(&state.pool, user_id, template_id)
+        .await
+        .map_err(AppError::Db)?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Import template {} not found", template_id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn import_csv(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
     Path(portfolio_id): Path<Uuid>,
+    Query(query): Query<ImportQuery>,
     Json(data): Json<ImportRequest>,
 ) -> Result<Json<ImportResponse>, AppError> {
     info!("POST /portfolios/{}/import - Importing CSV file: {}", portfolio_id, data.file_path);
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
-        .await
-        .map_err(AppError::Db)?
-        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
 
     let file_path = PathBuf::from(&data.file_path);
 
@@ -261,7 +653,8 @@ pub async fn import_csv(
     if filename.starts_with("AccountActivities") {
         // Import activity/transaction file
         info!("Importing AccountActivities file");
-        let result = activity_import_service::import_activities_file(&state.pool, portfolio_id, &file_path)
+        let (mut tx, batch) = begin_import(&state, access.user_id, portfolio_id, "activities_file", Some(filename)).await?;
+        let result = activity_import_service::import_activities_file(&mut tx, portfolio_id, &file_path)
             .await
             .map_err(|e| {
                 error!("Failed to import activities file: {}", e);
@@ -274,17 +667,13 @@ pub async fn import_csv(
             result.errors.len()
         );
 
-        Ok(Json(ImportResponse {
-            accounts_created: 0,
-            holdings_created: 0,
-            transactions_detected: result.transactions_imported,
-            errors: result.errors,
-            snapshot_date: "N/A".to_string(), // Activities don't have a snapshot date
-        }))
+        let response = finish_import(&state, tx, &batch, query.dry_run, result.into()).await?;
+        Ok(Json(response))
     } else {
         // Import holdings snapshot file
         info!("Importing AccountsHoldings file");
-        let result = csv_import_service::import_csv_file(&state.pool, portfolio_id, &file_path)
+        let (mut tx, batch) = begin_import(&state, access.user_id, portfolio_id, "holdings_file", Some(filename)).await?;
+        let result = csv_import_service::import_csv_file(&mut tx, portfolio_id, &file_path)
             .await
             .map_err(|e| {
                 error!("Failed to import CSV file: {}", e);
@@ -299,12 +688,49 @@ pub async fn import_csv(
             result.errors.len()
         );
 
-        Ok(Json(ImportResponse {
-            accounts_created: result.accounts_created,
-            holdings_created: result.holdings_created,
-            transactions_detected: result.transactions_detected,
-            errors: result.errors,
-            snapshot_date: result.snapshot_date.to_string(),
-        }))
+        let response = finish_import(&state, tx, &batch, query.dry_run, result.into()).await?;
+        Ok(Json(response))
+    }
+}
+
+pub async fn list_import_batches(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(portfolio_id): Path<Uuid>,
+) -> Result<Json<Vec<ImportBatch>>, AppError> {
+    info!("GET /portfolios/{}/import/batches - Listing import batches", portfolio_id);
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+
+    let batches = import_batch_queries::fetch_by_portfolio(&state.pool, portfolio_id)
+        .await
+        .map_err(AppError::Db)?;
+    Ok(Json(batches))
+}
+
+/// Undo an import atomically: rows it created are deleted, rows it changed or
+/// removed are restored
+pub async fn rollback_import_batch(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Json<ImportRollback>, AppError> {
+    info!("POST /import/batches/{}/rollback - Rolling back import", batch_id);
+    let rollback = import_batch_service::rollback_batch(&state.pool, user_id, batch_id).await?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(user_id, AuditAction::Update, "import_batch", batch_id)
+            .portfolio(rollback.batch.portfolio_id)
+            .before(&serde_json::json!({ "status": "applied" }))
+            .after(&serde_json::json!({ "status": rollback.batch.status, "reverted": rollback.reverted })),
+    )
+    .await;
+
+    // Refresh wash sale flags now that the import's transactions are gone
+    if let Err(e) = wash_sale_service::refresh_portfolio_wash_sales(&state.pool, rollback.batch.portfolio_id).await {
+        error!("Failed to refresh wash sales after rolling back batch {}: {}", batch_id, e);
     }
+    Ok(Json(rollback))
 }
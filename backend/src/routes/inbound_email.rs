@@ -0,0 +1,187 @@
+use axum::extract::{DefaultBodyLimit, Multipart, Path, State};
+use axum::http::StatusCode;
+use axum::middleware::map_response;
+use axum::response::Response;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::{inbound_email_queries, portfolio_queries};
+use crate::errors::AppError;
+use crate::middleware::auth::AuthUser;
+use crate::middleware::permissions::{CanEdit, PortfolioAccess};
+use crate::middleware::body_limit;
+use crate::models::{InboundEmail, InboundEmailAddress};
+use crate::services::inbound_email_service::{self, InboundEmailConfig, Receipt};
+use crate::state::AppState;
+
+/// Most recent emails listed for a portfolio
+const RECENT_EMAILS: i64 = 50;
+
+pub fn router() -> Router<AppState> {
+    // The webhook carries the attachments inline, so it shares the import limit
+    let limit = body_limit::import_body_limit();
+    Router::new()
+        .route(
+            "/portfolios/:portfolio_id/inbound-email",
+            get(get_inbound_address).post(create_inbound_address).delete(delete_inbound_address),
+        )
+        .route("/portfolios/:portfolio_id/inbound-email/messages", get(list_inbound_emails))
+        .route("/inbound-email/mailgun", post(mailgun_webhook))
+        .layer(DefaultBodyLimit::max(limit))
+        .layer(map_response(move |response: Response| async move {
+            body_limit::explain_payload_too_large(response, limit)
+        }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct InboundAddressResponse {
+    pub portfolio_id: Uuid,
+    /// Forward statement emails here to import their attachments
+    pub address: String,
+    pub created_at: DateTime<Utc>,
+}
+
+fn address_response(config: &InboundEmailConfig, address: InboundEmailAddress) -> Result<InboundAddressResponse, AppError> {
+    let email = config
+        .address(&address.token)
+        .ok_or_else(|| AppError::ServiceUnavailable("Inbound email is not configured".to_string()))?;
+    Ok(InboundAddressResponse {
+        portfolio_id: address.portfolio_id,
+        address: email,
+        created_at: address.created_at,
+    })
+}
+
+async fn ensure_portfolio(state: &AppState, portfolio_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+    Ok(())
+}
+
+pub async fn get_inbound_address(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(portfolio_id): Path<Uuid>,
+) -> Result<Json<InboundAddressResponse>, AppError> {
+    info!("GET /portfolios/{}/inbound-email - Fetching forwarding address", portfolio_id);
+    ensure_portfolio(&state, portfolio_id, user_id).await?;
+
+    let address = inbound_email_queries::fetch_address(&state.pool, portfolio_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} has no forwarding address", portfolio_id)))?;
+    Ok(Json(address_response(&InboundEmailConfig::from_env(), address)?))
+}
+
+/// Create the portfolio's forwarding address, or replace it with a new one so
+/// mail to the old address is no longer imported
+pub async fn create_inbound_address(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+) -> Result<Json<InboundAddressResponse>, AppError> {
+    info!("POST /portfolios/{}/inbound-email - Creating forwarding address", portfolio_id);
+    let config = InboundEmailConfig::from_env();
+    if config.domain.is_none() {
+        return Err(AppError::ServiceUnavailable("Inbound email is not configured".to_string()));
+    }
+    let token = inbound_email_service::generate_token();
+    let address = inbound_email_queries::upsert_address(&state.pool, access.user_id, portfolio_id, &token)
+        .await
+        .map_err(AppError::Db)?;
+    Ok(Json(address_response(&config, address)?))
+}
+
+pub async fn delete_inbound_address(
+    State(state): State<AppState>,
+    _access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("DELETE /portfolios/{}/inbound-email - Removing forwarding address", portfolio_id);
+    let deleted = inbound_email_queries::delete_address(&state.pool, portfolio_id)
+        .await
+        .map_err(AppError::Db)?;
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Portfolio {} has no forwarding address", portfolio_id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_inbound_emails(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(portfolio_id): Path<Uuid>,
+) -> Result<Json<Vec<InboundEmail>>, AppError> {
+    info!("GET /portfolios/{}/inbound-email/messages - Listing received emails", portfolio_id);
+    ensure_portfolio(&state, portfolio_id, user_id).await?;
+
+    let emails = inbound_email_queries::fetch_emails(&state.pool, portfolio_id, RECENT_EMAILS)
+        .await
+        .map_err(AppError::Db)?;
+    Ok(Json(emails))
+}
+
+/// Mailgun route forwarding a parsed message (`forward("https://.../api/inbound-email/mailgun")`).
+/// Authenticated by the webhook signature rather than a user session, and each
+/// signature is accepted once. Accepted emails are imported in the background
+/// so the provider gets a prompt 200; a 406 for an unknown address tells
+/// Mailgun not to retry.
+pub async fn mailgun_webhook(
+    State(state): State<AppState>,
+    multipart: Multipart,
+) -> Result<StatusCode, AppError> {
+    let config = InboundEmailConfig::from_env();
+    let signing_key = match (&config.domain, &config.mailgun_signing_key) {
+        (Some(_), Some(key)) => key.clone(),
+        _ => return Err(AppError::ServiceUnavailable("Inbound email is not configured".to_string())),
+    };
+
+    let parts = inbound_email_service::read_form(multipart).await.map_err(|e| {
+        if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            AppError::PayloadTooLarge(e.body_text())
+        } else {
+            AppError::Validation(format!("Invalid multipart body: {}", e.body_text()))
+        }
+    })?;
+    let (signature, message) = inbound_email_service::parse_mailgun(parts)
+        .map_err(|e| AppError::Validation(format!("Invalid Mailgun payload: {}", e)))?;
+
+    let now = Utc::now();
+    if !inbound_email_service::verify_mailgun_signature(&signing_key, &signature, now) {
+        warn!("Rejected inbound email webhook with an invalid signature");
+        return Err(AppError::Unauthorized);
+    }
+    if !inbound_email_service::claim_signature(&state.pool, &signature, now).await? {
+        warn!("Rejected replayed inbound email webhook");
+        return Err(AppError::Unauthorized);
+    }
+
+    match inbound_email_service::receive(&state.pool, &config, &message).await? {
+        Receipt::Accepted(email) => {
+            info!(
+                "Inbound email {} for portfolio {} with {} attachments",
+                email.id,
+                email.portfolio_id,
+                message.attachments.len()
+            );
+            let pool = state.pool.clone();
+            tokio::spawn(async move {
+                if let Err(e) = inbound_email_service::process(&pool, &email, &message.attachments).await {
+                    error!("Failed to process inbound email {}: {}", email.id, e);
+                }
+            });
+            Ok(StatusCode::OK)
+        }
+        Receipt::Rejected | Receipt::Duplicate => Ok(StatusCode::OK),
+        Receipt::UnknownRecipient => {
+            warn!("Inbound email to unknown address {}", message.recipients);
+            Ok(StatusCode::NOT_ACCEPTABLE)
+        }
+    }
+}
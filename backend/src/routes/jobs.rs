@@ -1,3 +1,4 @@
+use bigdecimal::ToPrimitive;
 use axum::{
     extract::{Path, State},
     routing::{get, post},
@@ -12,6 +13,7 @@ pub fn router() -> Router<AppState> {
     Router::new()
         .route("/", get(list_jobs))
         .route("/runs/recent", get(recent_job_runs))
+        .route("/queue", get(job_queue))
         .route("/trigger-all", post(trigger_all_jobs))
         .route("/:job_name/history", get(job_history))
         .route("/:job_name/stats", get(job_stats))
@@ -91,12 +93,31 @@ async fn list_jobs(
         ("calculate_portfolio_correlations", "0 45 */2 * * *", "Every 2 hours at :45"),
         ("create_daily_risk_snapshots", "0 0 17 * * *", "Daily at 5:00 PM ET"),
         ("update_market_regime", "0 5 17 * * *", "Daily at 5:05 PM ET"),
+        ("update_market_breadth", "0 10 17 * * *", "Daily at 5:10 PM ET"),
+        ("update_factor_spreads", "0 15 17 * * *", "Daily at 5:15 PM ET"),
+        ("refresh_peer_statistics", "0 20 17 * * *", "Daily at 5:20 PM ET"),
         ("train_hmm_model", "0 0 0 1 * *", "Monthly on 1st at midnight"),
         ("populate_optimization_cache", if test_mode { "0 */15 * * * *" } else { "0 0 */6 * * *" }, if test_mode { "Every 15 minutes (TEST MODE)" } else { "Every 6 hours" }),
         ("populate_rolling_beta_cache", "0 30 */6 * * *", "Every 6 hours at :30"),
         ("populate_downside_risk_cache", "0 45 */6 * * *", "Every 6 hours at :45"),
+        ("populate_sentiment_cache", "0 0 */4 * * *", "Every 4 hours at :00"),
         ("cleanup_cache", if test_mode { "0 */3 * * * *" } else { "0 0 3 * * SUN" }, if test_mode { "Every 3 minutes (TEST MODE)" } else { "Every Sunday at 3:00 AM" }),
-        ("archive_snapshots", "0 30 3 * * SUN", "Every Sunday at 3:30 AM"),
+        ("holding_move_alerts", "0 */15 14-21 * * MON-FRI", "Every 15 minutes during market hours"),
+        ("compact_snapshots", "0 30 3 * * SUN", "Every Sunday at 3:30 AM"),
+        ("refresh_etf_constituents", "0 0 5 * * SUN", "Every Sunday at 5:00 AM"),
+        ("check_latency_budgets", "0 */5 * * * *", "Every 5 minutes"),
+        ("record_portfolio_valuations", "0 25 17 * * *", "Daily at 5:25 PM ET"),
+        ("refresh_dividend_calendar", "0 30 6 * * *", "Daily at 6:30 AM"),
+        ("refresh_fx_rates", "0 50 16 * * *", "Daily at 4:50 PM ET"),
+        ("refresh_fundamentals", "0 0 6 * * *", "Daily at 6:00 AM"),
+        ("refresh_corporate_actions", "0 30 5 * * *", "Daily at 5:30 AM"),
+        ("backfill_price_history", "0 15 3 * * *", "Daily at 3:15 AM"),
+        ("refresh_factor_returns", "0 0 7 * * SAT", "Every Saturday at 7:00 AM"),
+        ("send_notification_digests", "0 5 * * * *", "Every hour at :05"),
+        ("generate_account_fees", "0 30 17 * * *", "Daily at 5:30 PM ET"),
+        ("recalculate_goal_probabilities", "0 40 17 * * *", "Daily at 5:40 PM ET"),
+        ("sync_crypto_wallets", "0 20 */4 * * *", "Every 4 hours at :20"),
+        ("calibrate_rate_limits", "0 */15 * * * *", "Every 15 minutes"),
     ];
 
     let mut jobs_info = Vec::new();
@@ -164,6 +185,25 @@ async fn recent_job_runs(
     Ok(Json(runs))
 }
 
+#[derive(Debug, Serialize)]
+struct JobQueueResponse {
+    summary: Vec<crate::models::JobQueueSummary>,
+    pending: Vec<crate::models::QueuedJob>,
+}
+
+/// GET /api/admin/jobs/queue - Work queue counts per job and status, and the
+/// jobs waiting or running (with their worker and lease expiry)
+async fn job_queue(
+    State(state): State<AppState>,
+) -> Result<Json<JobQueueResponse>, AppError> {
+    info!("GET /api/admin/jobs/queue");
+
+    let summary = crate::db::job_queue_queries::summary(&state.pool).await?;
+    let pending = crate::db::job_queue_queries::fetch_pending(&state.pool, 100).await?;
+
+    Ok(Json(JobQueueResponse { summary, pending }))
+}
+
 /// GET /api/admin/jobs/:job_name/history - Get history for a specific job
 async fn job_history(
     Path(job_name): Path<String>,
@@ -225,8 +265,8 @@ async fn job_stats(
         total_runs: stats.total_runs,
         successful_runs: stats.successful_runs,
         failed_runs: stats.failed_runs,
-        avg_duration_ms: stats.avg_duration_ms.and_then(|d| d.to_string().parse::<f64>().ok()),
-        avg_items_processed: stats.avg_items_processed.and_then(|d| d.to_string().parse::<f64>().ok()),
+        avg_duration_ms: stats.avg_duration_ms.and_then(|d| d.to_f64()),
+        avg_items_processed: stats.avg_items_processed.and_then(|d| d.to_f64()),
         last_run: stats.last_run,
         last_status: stats.last_status,
     }))
@@ -267,7 +307,7 @@ async fn trigger_job(
     Path(job_name): Path<String>,
     State(state): State<AppState>,
 ) -> Result<Json<TriggerJobResponse>, AppError> {
-    info!("🎯 Manual trigger requested for job: {}", job_name);
+    info!("Manual trigger requested for job: {}", job_name);
 
     // Validate that the job exists in our known jobs list
     let known_jobs = vec![
@@ -275,9 +315,13 @@ async fn trigger_job(
         "check_thresholds", "warm_caches", "calculate_portfolio_risks",
         "calculate_portfolio_correlations", "populate_rolling_beta_cache",
         "create_daily_risk_snapshots", "populate_optimization_cache",
-        "update_market_regime", "train_hmm_model",
-        "populate_downside_risk_cache",
-        "cleanup_cache", "archive_snapshots"
+        "update_market_regime", "update_market_breadth", "update_factor_spreads", "refresh_peer_statistics", "train_hmm_model",
+        "populate_downside_risk_cache", "populate_sentiment_cache", "holding_move_alerts",
+        "cleanup_cache", "compact_snapshots", "check_latency_budgets", "generate_account_fees",
+        "sync_crypto_wallets", "calibrate_rate_limits", "record_portfolio_valuations",
+        "send_notification_digests", "recalculate_goal_probabilities", "refresh_etf_constituents",
+        "refresh_dividend_calendar", "refresh_fx_rates", "refresh_fundamentals",
+        "refresh_corporate_actions", "backfill_price_history", "refresh_factor_returns"
     ];
 
     if !known_jobs.contains(&job_name.as_str()) {
@@ -300,7 +344,7 @@ async fn trigger_job(
     .await?
     .id;
 
-    info!("📝 Started job run with ID: {}", job_id);
+    info!("Started job run with ID: {}", job_id);
 
     let started_at = chrono::Utc::now();
 
@@ -308,81 +352,157 @@ async fn trigger_job(
     let job_context = crate::services::job_scheduler_service::JobContext {
         pool: Arc::new(state.pool.clone()),
         price_provider: state.price_provider.clone(),
+        chain_provider: state.chain_provider.clone(),
+        etf_holdings_provider: state.etf_holdings_provider.clone(),
+        dividend_provider: state.dividend_provider.clone(),
+        fundamentals_provider: state.fundamentals_provider.clone(),
+        news_provider: state.news_provider.clone(),
+        fx_provider: state.fx_provider.clone(),
         failure_cache: Arc::new(state.failure_cache.clone()),
         rate_limiter: state.rate_limiter.clone(),
-        news_service: state.news_service.clone(),
-        llm_service: state.llm_service.clone(),
     };
 
     // Execute the appropriate job function
     let result = match job_name.as_str() {
         "refresh_prices" => {
-            info!("💰 Executing refresh prices job...");
+            info!("Executing refresh prices job...");
             crate::services::job_scheduler_service::refresh_all_prices(job_context).await
         }
         "fetch_news" => {
-            info!("📰 Executing fetch news job...");
+            info!("Executing fetch news job...");
             crate::services::job_scheduler_service::fetch_all_news(job_context).await
         }
         "generate_forecasts" => {
-            info!("🔮 Executing generate forecasts job...");
+            info!("Executing generate forecasts job...");
             crate::services::job_scheduler_service::generate_all_forecasts(job_context).await
         }
         "analyze_sec_filings" => {
-            info!("📄 Executing analyze SEC filings job...");
+            info!("Executing analyze SEC filings job...");
             crate::services::job_scheduler_service::analyze_all_sec_filings(job_context).await
         }
         "check_thresholds" => {
-            info!("⚠️ Executing check thresholds job...");
+            info!("Executing check thresholds job...");
             crate::services::job_scheduler_service::check_all_thresholds(job_context).await
         }
         "warm_caches" => {
-            info!("🔥 Executing warm caches job...");
+            info!("Executing warm caches job...");
             crate::services::job_scheduler_service::warm_popular_caches(job_context).await
         }
         "calculate_portfolio_risks" => {
-            info!("🔍 Executing portfolio risk calculation job...");
+            info!("Executing portfolio risk calculation job...");
             crate::jobs::portfolio_risk_job::calculate_all_portfolio_risks(job_context).await
         }
         "calculate_portfolio_correlations" => {
-            info!("🔗 Executing portfolio correlations calculation job...");
+            info!("Executing portfolio correlations calculation job...");
             crate::jobs::portfolio_correlations_job::calculate_all_portfolio_correlations(job_context).await
         }
         "populate_rolling_beta_cache" => {
-            info!("📊 Executing rolling beta cache population job...");
+            info!("Executing rolling beta cache population job...");
             crate::jobs::rolling_beta_cache_job::populate_rolling_beta_caches(job_context).await
         }
         "create_daily_risk_snapshots" => {
-            info!("📸 Executing daily risk snapshots job...");
+            info!("Executing daily risk snapshots job...");
             crate::jobs::daily_risk_snapshots_job::create_all_daily_risk_snapshots(job_context).await
         }
         "populate_optimization_cache" => {
-            info!("🎯 Executing optimization cache population job...");
+            info!("Executing optimization cache population job...");
             crate::jobs::populate_optimization_cache_job::populate_all_optimization_caches(job_context).await
         }
         "update_market_regime" => {
-            info!("📊 Executing market regime update job...");
+            info!("Executing market regime update job...");
             crate::jobs::market_regime_update_job::update_market_regime(job_context).await
         }
+        "update_market_breadth" => {
+            info!("Executing market breadth update job...");
+            crate::jobs::market_breadth_job::update_market_breadth(job_context).await
+        }
+        "update_factor_spreads" => {
+            info!("Executing factor spread job...");
+            crate::jobs::factor_spread_job::update_factor_spreads(job_context).await
+        }
+        "refresh_etf_constituents" => {
+            info!("Executing ETF constituent job...");
+            crate::jobs::etf_constituent_job::refresh_etf_constituents(job_context).await
+        }
+        "refresh_dividend_calendar" => {
+            info!("Executing dividend calendar job...");
+            crate::jobs::dividend_calendar_job::refresh_dividend_calendar(job_context).await
+        }
+        "refresh_fx_rates" => {
+            info!("Executing FX rates job...");
+            crate::jobs::fx_rates_job::refresh_fx_rates(job_context).await
+        }
+        "refresh_fundamentals" => {
+            info!("Executing fundamentals job...");
+            crate::jobs::fundamentals_job::refresh_fundamentals(job_context).await
+        }
+        "refresh_corporate_actions" => {
+            info!("Executing corporate actions job...");
+            crate::jobs::corporate_actions_job::refresh_corporate_actions(job_context).await
+        }
+        "backfill_price_history" => {
+            info!("Executing price backfill job...");
+            crate::jobs::price_backfill_job::backfill_price_history(job_context).await
+        }
+        "refresh_factor_returns" => {
+            info!("Executing factor returns job...");
+            crate::jobs::factor_returns_job::refresh_factor_returns(job_context).await
+        }
+        "refresh_peer_statistics" => {
+            info!("Executing peer statistics job...");
+            crate::jobs::peer_statistics_job::refresh_peer_statistics(job_context).await
+        }
+        "holding_move_alerts" => {
+            info!("Executing holding move alert job...");
+            crate::jobs::holding_move_alert_job::run_holding_move_alerts(job_context).await
+        }
         "train_hmm_model" => {
-            info!("🧠 Executing HMM model training job...");
+            info!("Executing HMM model training job...");
             crate::services::job_scheduler_service::train_hmm_wrapper(job_context).await
         }
         "generate_regime_forecasts" => {
-            info!("🔮 Executing regime forecast generation job...");
+            info!("Executing regime forecast generation job...");
             crate::jobs::regime_forecast_job::generate_all_regime_forecasts(job_context).await
         }
         "populate_downside_risk_cache" => {
-            info!("📉 Executing downside risk cache population job...");
+            info!("Executing downside risk cache population job...");
             crate::jobs::downside_risk_cache_job::populate_downside_risk_caches(job_context).await
         }
         "cleanup_cache" => {
-            info!("🧹 Executing cleanup cache job...");
+            info!("Executing cleanup cache job...");
             crate::services::job_scheduler_service::cleanup_expired_caches(job_context).await
         }
-        "archive_snapshots" => {
-            info!("📦 Executing archive snapshots job...");
-            crate::services::job_scheduler_service::archive_old_snapshots(job_context).await
+        "compact_snapshots" => {
+            info!("Executing snapshot compaction job...");
+            crate::jobs::snapshot_retention_job::compact_snapshots(job_context).await
+        }
+        "check_latency_budgets" => {
+            info!("Executing latency budget job...");
+            crate::jobs::latency_budget_job::check_latency_budgets(job_context).await
+        }
+        "record_portfolio_valuations" => {
+            info!("Executing portfolio valuation job...");
+            crate::jobs::portfolio_valuation_job::record_portfolio_valuations(job_context).await
+        }
+        "send_notification_digests" => {
+            info!("Executing notification digest job...");
+            crate::jobs::notification_digest_job::send_notification_digests(job_context).await
+        }
+        "generate_account_fees" => {
+            info!("Executing account fee job...");
+            crate::jobs::advisor_fee_job::generate_account_fees(job_context).await
+        }
+        "recalculate_goal_probabilities" => {
+            info!("Executing goal probability job...");
+            crate::jobs::goal_probability_job::recalculate_goal_probabilities(job_context).await
+        }
+        "sync_crypto_wallets" => {
+            info!("Executing crypto wallet sync job...");
+            crate::jobs::crypto_wallet_sync_job::sync_crypto_wallets(job_context).await
+        }
+        "calibrate_rate_limits" => {
+            info!("Executing rate limit calibration job...");
+            crate::jobs::rate_limit_calibration_job::calibrate_rate_limits(job_context).await
         }
         _ => {
             // Unknown job
@@ -430,7 +550,7 @@ async fn trigger_job(
     match result {
         Ok(job_result) => {
             info!(
-                "✅ Job '{}' completed successfully: {} processed, {} failed, duration: {}ms",
+                "Job '{}' completed successfully: {} processed, {} failed, duration: {}ms",
                 job_name, job_result.items_processed, job_result.items_failed, duration_ms
             );
 
@@ -467,7 +587,7 @@ async fn trigger_job(
             }))
         }
         Err(e) => {
-            error!("❌ Job '{}' failed: {}", job_name, e);
+            error!("Job '{}' failed: {}", job_name, e);
 
             let error_message = e.to_string();
             sqlx::query!(
@@ -513,39 +633,62 @@ struct TriggerAllJobsResponse {
 async fn trigger_all_jobs(
     State(state): State<AppState>,
 ) -> Result<Json<TriggerAllJobsResponse>, AppError> {
-    info!("🚀 Manual trigger requested for ALL jobs");
+    info!("Manual trigger requested for ALL jobs");
     let overall_start = chrono::Utc::now();
 
     // Define all jobs to run in sequence (order matters for dependencies)
     let jobs_to_run = vec![
+        "calibrate_rate_limits",            // Size the API budget before fetching
         "refresh_prices",                    // Get latest prices first
+        "backfill_price_history",           // Longer history for newly added tickers
+        "refresh_corporate_actions",        // Splits and dividends (before anything reads adjusted closes)
+        "sync_crypto_wallets",              // On-chain balances (before risk and snapshots)
+        "refresh_fx_rates",                 // Exchange rates (before anything values portfolios)
         "fetch_news",                        // Fetch news
+        "populate_sentiment_cache",         // Scored news sentiment (before screening reads it)
         "analyze_sec_filings",              // Analyze SEC filings
         "check_thresholds",                 // Check alert thresholds
+        "holding_move_alerts",              // Holding move alerts
         "generate_forecasts",               // Generate price forecasts
         "calculate_portfolio_risks",        // Calculate risk metrics
         "populate_downside_risk_cache",     // Downside risk analysis
         "calculate_portfolio_correlations", // Correlation analysis
         "populate_rolling_beta_cache",      // Beta calculations
         "update_market_regime",             // Market regime detection
+        "update_market_breadth",            // Market breadth
+        "refresh_fundamentals",             // Ratios, earnings and dividends (before factor scoring)
+        "update_factor_spreads",            // Long-short factor spreads
+        "refresh_factor_returns",           // Published factor returns for the regressions
+        "refresh_etf_constituents",         // ETF constituents for look-through
         "train_hmm_model",                  // Train HMM model
         "populate_optimization_cache",      // Portfolio optimization
         "create_daily_risk_snapshots",      // Risk snapshots
+        "refresh_peer_statistics",          // Anonymous peer statistics (after snapshots)
+        "record_portfolio_valuations",      // Value accounts between imports
+        "generate_account_fees",            // Recurring account fee cash flows (after valuations)
+        "recalculate_goal_probabilities",   // Goal success probabilities (after valuations)
+        "refresh_dividend_calendar",        // Ex-dividend dates and reminders (before digests)
+        "send_notification_digests",        // Email notification digests
         "warm_caches",                      // Warm popular caches
         "cleanup_cache",                    // Clean expired caches
-        "archive_snapshots",                // Archive old snapshots
+        "compact_snapshots",                // Compact old snapshots
+        "check_latency_budgets",            // Latency budgets (last, to include this run)
     ];
 
-    info!("📋 Will execute {} jobs in sequence", jobs_to_run.len());
+    info!("Will execute {} jobs in sequence", jobs_to_run.len());
 
     // Create job context from AppState
     let job_context = crate::services::job_scheduler_service::JobContext {
         pool: Arc::new(state.pool.clone()),
         price_provider: state.price_provider.clone(),
+        chain_provider: state.chain_provider.clone(),
+        etf_holdings_provider: state.etf_holdings_provider.clone(),
+        dividend_provider: state.dividend_provider.clone(),
+        fundamentals_provider: state.fundamentals_provider.clone(),
+        news_provider: state.news_provider.clone(),
+        fx_provider: state.fx_provider.clone(),
         failure_cache: Arc::new(state.failure_cache.clone()),
         rate_limiter: state.rate_limiter.clone(),
-        news_service: state.news_service.clone(),
-        llm_service: state.llm_service.clone(),
     };
 
     let mut job_results = Vec::new();
@@ -554,7 +697,7 @@ async fn trigger_all_jobs(
 
     // Execute each job in sequence
     for job_name in jobs_to_run.iter() {
-        info!("⏳ Executing job: {}", job_name);
+        info!("Executing job: {}", job_name);
         let job_start = chrono::Utc::now();
 
         // Record job start
@@ -593,6 +736,9 @@ async fn trigger_all_jobs(
             "populate_downside_risk_cache" => {
                 crate::jobs::downside_risk_cache_job::populate_downside_risk_caches(job_context.clone()).await
             }
+            "populate_sentiment_cache" => {
+                crate::jobs::populate_sentiment_cache_job::populate_all_sentiment_caches(job_context.clone()).await
+            }
             "calculate_portfolio_correlations" => {
                 crate::jobs::portfolio_correlations_job::calculate_all_portfolio_correlations(job_context.clone()).await
             }
@@ -602,6 +748,36 @@ async fn trigger_all_jobs(
             "update_market_regime" => {
                 crate::jobs::market_regime_update_job::update_market_regime(job_context.clone()).await
             }
+            "update_market_breadth" => {
+                crate::jobs::market_breadth_job::update_market_breadth(job_context.clone()).await
+            }
+            "update_factor_spreads" => {
+                crate::jobs::factor_spread_job::update_factor_spreads(job_context.clone()).await
+            }
+            "refresh_etf_constituents" => {
+                crate::jobs::etf_constituent_job::refresh_etf_constituents(job_context.clone()).await
+            }
+            "refresh_dividend_calendar" => {
+                crate::jobs::dividend_calendar_job::refresh_dividend_calendar(job_context.clone()).await
+            }
+            "refresh_fx_rates" => {
+                crate::jobs::fx_rates_job::refresh_fx_rates(job_context.clone()).await
+            }
+            "refresh_fundamentals" => {
+                crate::jobs::fundamentals_job::refresh_fundamentals(job_context.clone()).await
+            }
+            "refresh_corporate_actions" => {
+                crate::jobs::corporate_actions_job::refresh_corporate_actions(job_context.clone()).await
+            }
+            "backfill_price_history" => {
+                crate::jobs::price_backfill_job::backfill_price_history(job_context.clone()).await
+            }
+            "refresh_factor_returns" => {
+                crate::jobs::factor_returns_job::refresh_factor_returns(job_context.clone()).await
+            }
+            "holding_move_alerts" => {
+                crate::jobs::holding_move_alert_job::run_holding_move_alerts(job_context.clone()).await
+            }
             "train_hmm_model" => {
                 crate::services::job_scheduler_service::train_hmm_wrapper(job_context.clone()).await
             }
@@ -617,8 +793,29 @@ async fn trigger_all_jobs(
             "cleanup_cache" => {
                 crate::services::job_scheduler_service::cleanup_expired_caches(job_context.clone()).await
             }
-            "archive_snapshots" => {
-                crate::services::job_scheduler_service::archive_old_snapshots(job_context.clone()).await
+            "compact_snapshots" => {
+                crate::jobs::snapshot_retention_job::compact_snapshots(job_context.clone()).await
+            }
+            "check_latency_budgets" => {
+                crate::jobs::latency_budget_job::check_latency_budgets(job_context.clone()).await
+            }
+            "record_portfolio_valuations" => {
+                crate::jobs::portfolio_valuation_job::record_portfolio_valuations(job_context.clone()).await
+            }
+            "send_notification_digests" => {
+                crate::jobs::notification_digest_job::send_notification_digests(job_context.clone()).await
+            }
+            "generate_account_fees" => {
+                crate::jobs::advisor_fee_job::generate_account_fees(job_context.clone()).await
+            }
+            "recalculate_goal_probabilities" => {
+                crate::jobs::goal_probability_job::recalculate_goal_probabilities(job_context.clone()).await
+            }
+            "sync_crypto_wallets" => {
+                crate::jobs::crypto_wallet_sync_job::sync_crypto_wallets(job_context.clone()).await
+            }
+            "calibrate_rate_limits" => {
+                crate::jobs::rate_limit_calibration_job::calibrate_rate_limits(job_context.clone()).await
             }
             _ => {
                 error!("Unknown job: {}", job_name);
@@ -632,7 +829,7 @@ async fn trigger_all_jobs(
         let job_response = match result {
             Ok(job_result) => {
                 successful += 1;
-                info!("✅ {} completed successfully ({}ms)", job_name, duration_ms);
+                info!("{} completed successfully ({}ms)", job_name, duration_ms);
 
                 sqlx::query!(
                     r#"
@@ -666,7 +863,7 @@ async fn trigger_all_jobs(
             Err(e) => {
                 failed += 1;
                 let error_msg = e.to_string();
-                error!("❌ {} failed: {} ({}ms)", job_name, error_msg, duration_ms);
+                error!("{} failed: {} ({}ms)", job_name, error_msg, duration_ms);
 
                 sqlx::query!(
                     r#"
@@ -703,7 +900,7 @@ async fn trigger_all_jobs(
     let total_duration_ms = (chrono::Utc::now() - overall_start).num_milliseconds();
 
     info!(
-        "🏁 All jobs completed: {} successful, {} failed, total duration: {}s",
+        "All jobs completed: {} successful, {} failed, total duration: {}s",
         successful, failed, total_duration_ms / 1000
     );
 
@@ -6,7 +6,8 @@ use uuid::Uuid;
 
 use crate::db::{llm_queries, user_preferences_queries};
 use crate::errors::AppError;
-use crate::models::{UpdateUserPreferences, UserPreferences, LlmUsageStats};
+use crate::middleware::auth::AuthUser;
+use crate::models::{UpdateUserPreferences, UserPreferences, LlmUsageStats, CURRENT_LLM_CONSENT_VERSION};
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
@@ -17,14 +18,25 @@ pub fn router() -> Router<AppState> {
         .route("/users/:user_id/usage", get(get_user_usage_stats))
 }
 
+/// These routes are addressed by user id but only ever serve the caller's own
+/// settings; another user's id is reported as not found
+fn ensure_own_user(auth_user_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+    if auth_user_id != user_id {
+        return Err(AppError::NotFound(format!("User {} not found", user_id)));
+    }
+    Ok(())
+}
+
 /// GET /api/llm/users/:user_id/preferences
 /// Get user preferences for AI features
 #[axum::debug_handler]
 pub async fn get_user_preferences(
     State(state): State<AppState>,
+    AuthUser(auth_user_id): AuthUser,
     Path(user_id): Path<Uuid>,
 ) -> Result<Json<UserPreferences>, AppError> {
     info!("GET /api/llm/users/{}/preferences", user_id);
+    ensure_own_user(auth_user_id, user_id)?;
 
     let preferences = user_preferences_queries::get_by_user_id(&state.pool, user_id)
         .await
@@ -56,10 +68,12 @@ pub async fn get_user_preferences(
 #[axum::debug_handler]
 pub async fn update_user_preferences(
     State(state): State<AppState>,
+    AuthUser(auth_user_id): AuthUser,
     Path(user_id): Path<Uuid>,
     Json(data): Json<UpdateUserPreferences>,
 ) -> Result<Json<UserPreferences>, AppError> {
     info!("PUT /api/llm/users/{}/preferences - llm_enabled: {}", user_id, data.llm_enabled);
+    ensure_own_user(auth_user_id, user_id)?;
 
     let preferences = user_preferences_queries::upsert(&state.pool, user_id, data)
         .await
@@ -72,7 +86,7 @@ pub async fn update_user_preferences(
 }
 
 /// POST /api/llm/users/:user_id/llm-consent
-/// Update LLM consent status
+/// Update LLM consent status, recorded against the current consent version
 #[derive(Debug, serde::Deserialize)]
 pub struct ConsentRequest {
     pub consent: bool,
@@ -81,18 +95,24 @@ pub struct ConsentRequest {
 #[axum::debug_handler]
 pub async fn update_llm_consent(
     State(state): State<AppState>,
+    AuthUser(auth_user_id): AuthUser,
     Path(user_id): Path<Uuid>,
     Json(data): Json<ConsentRequest>,
 ) -> Result<Json<UserPreferences>, AppError> {
     info!("POST /api/llm/users/{}/llm-consent - consent: {}", user_id, data.consent);
+    ensure_own_user(auth_user_id, user_id)?;
 
-    let preferences = user_preferences_queries::update_llm_consent(&state.pool, user_id, data.consent)
+    llm_queries::record_consent(&state.pool, user_id, CURRENT_LLM_CONSENT_VERSION, data.consent)
         .await
         .map_err(|e| {
             error!("Failed to update LLM consent for {}: {}", user_id, e);
             AppError::Db(e)
         })?;
 
+    let preferences = user_preferences_queries::get_by_user_id(&state.pool, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Preferences for user {} not found", user_id)))?;
+
     Ok(Json(preferences))
 }
 
@@ -101,9 +121,11 @@ pub async fn update_llm_consent(
 #[axum::debug_handler]
 pub async fn get_user_usage_stats(
     State(state): State<AppState>,
+    AuthUser(auth_user_id): AuthUser,
     Path(user_id): Path<Uuid>,
 ) -> Result<Json<LlmUsageStats>, AppError> {
     info!("GET /api/llm/users/{}/usage", user_id);
+    ensure_own_user(auth_user_id, user_id)?;
 
     let stats = llm_queries::get_user_usage_stats(&state.pool, user_id)
         .await
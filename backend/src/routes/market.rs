@@ -13,7 +13,7 @@ use tracing::warn;
 
 use crate::db::{hmm_queries, market_regime_queries};
 use crate::models::hmm_regime::{RegimeForecastParams, StateProbabilities};
-use crate::models::{RegimeHistoryParams, RegimeType};
+use crate::models::{MarketBreadthParams, RegimeHistoryParams, RegimeType};
 use crate::state::AppState;
 
 // ==============================================================================
@@ -25,6 +25,7 @@ pub fn router() -> Router<AppState> {
         .route("/market/regime", get(get_current_regime_enhanced))
         .route("/market/regime/history", get(get_regime_history))
         .route("/market/regime/forecast", get(get_regime_forecast))
+        .route("/market/breadth", get(get_market_breadth))
 }
 
 // ==============================================================================
@@ -99,6 +100,23 @@ async fn get_regime_history(
     }
 }
 
+/// GET /api/market/breadth?days=30
+///
+/// Latest breadth readings for the tracked universe (advancers/decliners, % above
+/// SMA50/SMA200, 52-week highs/lows) with history, stored daily by the breadth job
+async fn get_market_breadth(
+    State(state): State<AppState>,
+    Query(params): Query<MarketBreadthParams>,
+) -> impl IntoResponse {
+    match crate::services::market_service::get_breadth(&state.pool, params.days).await {
+        Ok(breadth) => (StatusCode::OK, Json(breadth)).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get market breadth: {}", e);
+            e.into_response()
+        }
+    }
+}
+
 /// GET /api/market/regime/forecast?days=10
 ///
 /// Forecast regime N days ahead using HMM
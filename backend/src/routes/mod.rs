@@ -1,9 +1,11 @@
 pub mod portfolios;
 pub mod analytics;
 pub mod prices;
+pub mod tickers;
 pub mod health;
 pub mod accounts;
 pub mod imports;
+pub mod inbound_email;
 pub mod cash_flows;
 pub mod transactions;
 pub mod admin;
@@ -22,4 +24,15 @@ pub mod recommendations;
 pub mod watchlists;
 pub mod financial_planning;
 pub mod auth;
-
+pub mod ownership;
+pub mod fundamentals;
+pub mod analyst;
+pub mod model_portfolios;
+pub mod user_data;
+pub mod audit;
+pub mod admin_users;
+pub mod public_api;
+pub mod datasource;
+pub mod providers;
+pub mod chaos;
+pub mod widgets;
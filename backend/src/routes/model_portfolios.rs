@@ -0,0 +1,26 @@
+use axum::extract::State;
+use axum::routing::get;
+use axum::{Json, Router};
+use tracing::{error, info};
+
+use crate::errors::AppError;
+use crate::models::ModelPortfolio;
+use crate::services::model_portfolio_service;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(list_model_portfolios))
+}
+
+/// GET /api/model-portfolios
+///
+/// The model portfolio library (60/40, three-fund, all-weather, ...) with target
+/// allocations. Use an id with `/api/portfolios/:id/model-comparison?model=<id>`.
+async fn list_model_portfolios(State(state): State<AppState>) -> Result<Json<Vec<ModelPortfolio>>, AppError> {
+    info!("GET /api/model-portfolios - Listing model portfolios");
+    let models = model_portfolio_service::list_models(&state.pool).await.map_err(|e| {
+        error!("Failed to list model portfolios: {}", e);
+        e
+    })?;
+    Ok(Json(models))
+}
@@ -7,7 +7,9 @@ use std::collections::HashMap;
 use sqlx::PgPool;
 use chrono::{Utc, Duration};
 
+use crate::db::portfolio_queries;
 use crate::errors::AppError;
+use crate::middleware::auth::AuthUser;
 use crate::models::{NewsQueryParams, PortfolioNewsAnalysis, NewsTheme, Sentiment};
 use crate::state::AppState;
 
@@ -95,6 +97,7 @@ async fn cache_news(
 ///
 /// Returns themed news analysis with sentiment
 async fn get_portfolio_news(
+    AuthUser(user_id): AuthUser,
     Path(portfolio_id): Path<Uuid>,
     Query(params): Query<NewsQueryParams>,
     State(state): State<AppState>,
@@ -107,6 +110,11 @@ async fn get_portfolio_news(
         portfolio_id, days, force
     );
 
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+
     // Check cache first if not forcing refresh
     if !force {
         if let Some(cached_news) = get_cached_news(&state.pool, portfolio_id, days).await? {
@@ -141,9 +149,6 @@ async fn get_portfolio_news(
     info!("Fetching news for {} tickers", tickers.len());
 
     // 3. Fetch news for each ticker
-    let demo_user_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001")
-        .expect("Invalid demo user UUID");
-
     let mut position_news: HashMap<String, Vec<NewsTheme>> = HashMap::new();
     let mut all_themes: Vec<NewsTheme> = Vec::new();
 
@@ -151,7 +156,7 @@ async fn get_portfolio_news(
         match state.news_service.fetch_ticker_news(ticker, days).await {
             Ok(articles) if !articles.is_empty() => {
                 // Cluster articles into themes
-                match state.news_service.cluster_into_themes(articles, demo_user_id).await {
+                match state.news_service.cluster_into_themes(articles, user_id).await {
                     Ok(themes) => {
                         info!("Clustered {} themes for {}", themes.len(), ticker);
                         all_themes.extend(themes.clone());
@@ -204,6 +209,7 @@ async fn get_portfolio_news(
 /// Query parameters:
 /// - `days`: Number of days to look back (default: 7)
 async fn get_ticker_news(
+    AuthUser(user_id): AuthUser,
     Path(ticker): Path<String>,
     Query(params): Query<NewsQueryParams>,
     State(state): State<AppState>,
@@ -224,12 +230,9 @@ async fn get_ticker_news(
     }
 
     // Cluster into themes
-    let demo_user_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001")
-        .expect("Invalid demo user UUID");
-
     let themes = state
         .news_service
-        .cluster_into_themes(articles, demo_user_id)
+        .cluster_into_themes(articles, user_id)
         .await?;
 
     info!("Clustered {} themes for {}", themes.len(), ticker);
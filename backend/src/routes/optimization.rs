@@ -4,10 +4,13 @@ use axum::routing::get;
 use tracing::{error, info};
 use uuid::Uuid;
 
-use crate::db::portfolio_queries;
+use crate::db::{optimization_constraint_queries, portfolio_queries};
 use crate::errors::AppError;
 use crate::middleware::auth::AuthUser;
+use crate::middleware::permissions::{CanEdit, PortfolioAccess};
 use crate::models::{OptimizationAnalysis, OptimizationRecommendation, CurrentMetrics, AnalysisSummary, PortfolioHealth, Severity};
+use crate::models::{ConstrainedOptimization, ConstrainedOptimizationRequest, NewAuditEntry, OptimizationConstraints};
+use crate::services::{audit_service, constrained_optimization_service};
 use crate::state::AppState;
 use bigdecimal::ToPrimitive;
 
@@ -15,6 +18,11 @@ pub fn router() -> Router<AppState> {
     Router::new()
         .route("/portfolios/:portfolio_id", get(get_portfolio_optimization))
         .route("/portfolios/:portfolio_id/generate", axum::routing::post(generate_portfolio_optimization))
+        .route(
+            "/portfolios/:portfolio_id/constraints",
+            get(get_constraints).put(set_constraints).delete(delete_constraints),
+        )
+        .route("/portfolios/:portfolio_id/constrained", axum::routing::post(run_constrained_optimization))
 }
 
 /// GET /api/optimization/portfolios/:portfolio_id
@@ -59,7 +67,7 @@ pub async fn get_portfolio_optimization(
                 .map_err(|e| AppError::External(format!("Failed to deserialize recommendations: {}", e)))?;
 
         info!(
-            "✅ Returning {} cached recommendations for portfolio {} (cached at {})",
+            "Returning {} cached recommendations for portfolio {} (cached at {})",
             recommendations.len(),
             portfolio_id,
             cache.calculated_at
@@ -154,7 +162,7 @@ pub async fn get_portfolio_optimization(
     if let Some(cache) = expired_cache {
         // We have expired cache - return it with a stale warning
         info!(
-            "⚠️  Returning STALE cache for portfolio {} (expired at {})",
+            "Returning STALE cache for portfolio {} (expired at {})",
             portfolio_id,
             cache.expires_at
         );
@@ -235,7 +243,7 @@ pub async fn get_portfolio_optimization(
 
     // No cache at all - return empty state
     error!(
-        "❌ No optimization cache found (not even expired) for portfolio {}",
+        "No optimization cache found (not even expired) for portfolio {}",
         portfolio_id
     );
 
@@ -307,13 +315,10 @@ pub async fn get_portfolio_optimization(
 /// Manually trigger optimization calculation for a portfolio
 #[axum::debug_handler]
 pub async fn generate_portfolio_optimization(
-    AuthUser(user_id): AuthUser,
+    _access: PortfolioAccess<CanEdit>,
     Path(portfolio_id): Path<Uuid>,
     State(state): State<AppState>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
-        .await.map_err(AppError::Db)?
-        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
     info!(
         "POST /api/optimization/portfolios/{}/generate - Manual optimization trigger",
         portfolio_id
@@ -335,10 +340,14 @@ pub async fn generate_portfolio_optimization(
     let ctx = JobContext {
         pool: Arc::new(state.pool.clone()),
         price_provider: state.price_provider.clone(),
+        chain_provider: state.chain_provider.clone(),
+        etf_holdings_provider: state.etf_holdings_provider.clone(),
+        dividend_provider: state.dividend_provider.clone(),
+        fundamentals_provider: state.fundamentals_provider.clone(),
+        news_provider: state.news_provider.clone(),
+        fx_provider: state.fx_provider.clone(),
         failure_cache: Arc::new(state.failure_cache.clone()),
         rate_limiter: state.rate_limiter.clone(),
-        news_service: state.news_service.clone(),
-        llm_service: state.llm_service.clone(),
     };
 
     // Import the job function
@@ -347,15 +356,106 @@ pub async fn generate_portfolio_optimization(
     // Run the optimization job for this portfolio
     match calculate_single_portfolio_optimization(&ctx, portfolio_id).await {
         Ok(_) => {
-            info!("✅ Successfully generated optimization for portfolio {}", portfolio_id);
+            info!("Successfully generated optimization for portfolio {}", portfolio_id);
             Ok(Json(serde_json::json!({
                 "message": "Optimization analysis generated successfully",
                 "portfolio_id": portfolio_id.to_string()
             })))
         }
         Err(e) => {
-            error!("❌ Failed to generate optimization for portfolio {}: {:?}", portfolio_id, e);
+            error!("Failed to generate optimization for portfolio {}: {:?}", portfolio_id, e);
             Err(AppError::External(format!("Failed to generate optimization: {}", e)))
         }
     }
 }
+
+/// GET /api/optimization/portfolios/:portfolio_id/constraints
+///
+/// Retrieve the portfolio's saved rebalancing constraints.
+pub async fn get_constraints(
+    AuthUser(user_id): AuthUser,
+    Path(portfolio_id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<OptimizationConstraints>, AppError> {
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await.map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+    info!("GET /api/optimization/portfolios/{}/constraints - Fetching constraints", portfolio_id);
+
+    Ok(Json(constrained_optimization_service::get_constraints(&state.pool, portfolio_id).await?))
+}
+
+/// PUT /api/optimization/portfolios/:portfolio_id/constraints
+///
+/// Save rebalancing constraints, e.g.
+/// `{"max_position_weight": 20, "sector_caps": {"Technology": 35}, "min_cash_pct": 2, "do_not_sell": ["AAPL"]}`.
+/// Weights are percentages; sector caps match the holdings' industry. Saved constraints
+/// apply to generated recommendations and to constrained optimization requests without a body.
+pub async fn set_constraints(
+    access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(request): Json<OptimizationConstraints>,
+) -> Result<Json<OptimizationConstraints>, AppError> {
+    info!("PUT /api/optimization/portfolios/{}/constraints - Saving constraints", portfolio_id);
+
+    let previous = optimization_constraint_queries::fetch(&state.pool, portfolio_id).await?;
+    let constraints = constrained_optimization_service::set_constraints(&state.pool, portfolio_id, request)
+        .await
+        .map_err(|e| {
+            error!("Failed to save optimization constraints for portfolio {}: {}", portfolio_id, e);
+            e
+        })?;
+
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::saved(access.user_id, "optimization_constraints", portfolio_id, previous.as_ref())
+            .portfolio(portfolio_id)
+            .after(&constraints),
+    )
+    .await;
+    Ok(Json(constraints))
+}
+
+/// DELETE /api/optimization/portfolios/:portfolio_id/constraints
+pub async fn delete_constraints(
+    access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<axum::http::StatusCode, AppError> {
+    info!("DELETE /api/optimization/portfolios/{}/constraints - Removing constraints", portfolio_id);
+
+    let previous = optimization_constraint_queries::fetch(&state.pool, portfolio_id).await?;
+    constrained_optimization_service::delete_constraints(&state.pool, portfolio_id).await?;
+
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::deleted(access.user_id, "optimization_constraints", portfolio_id, previous.as_ref())
+            .portfolio(portfolio_id),
+    )
+    .await;
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+/// POST /api/optimization/portfolios/:portfolio_id/constrained
+///
+/// Solve target weights and trades under user constraints. The body may carry
+/// `constraints` (otherwise the saved ones are used) and `risk_aversion` (default: 1.0),
+/// which trades turnover against a tilt toward lower-volatility positions.
+pub async fn run_constrained_optimization(
+    _access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+    State(state): State<AppState>,
+    request: Option<Json<ConstrainedOptimizationRequest>>,
+) -> Result<Json<ConstrainedOptimization>, AppError> {
+    info!("POST /api/optimization/portfolios/{}/constrained - Running constrained optimization", portfolio_id);
+
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let result = constrained_optimization_service::optimize(&state.pool, portfolio_id, request, state.risk_free_rate)
+        .await
+        .map_err(|e| {
+            error!("Constrained optimization failed for portfolio {}: {}", portfolio_id, e);
+            e
+        })?;
+    Ok(Json(result))
+}
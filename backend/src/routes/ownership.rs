@@ -0,0 +1,41 @@
+use axum::extract::{Path, Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use tracing::{error, info};
+
+use crate::errors::AppError;
+use crate::models::{OwnershipQuery, OwnershipSnapshot};
+use crate::services::ownership_service;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/positions/:ticker/ownership", get(get_position_ownership))
+}
+
+/// GET /api/positions/:ticker/ownership
+///
+/// Insider ownership %, recent insider buys/sells and top institutional holders
+///
+/// Query parameters:
+/// - `days`: Insider activity window in days (default: 90, max: 365)
+/// - `force`: Refresh from the provider, bypassing the daily cache (default: false)
+async fn get_position_ownership(
+    Path(ticker): Path<String>,
+    Query(params): Query<OwnershipQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<OwnershipSnapshot>, AppError> {
+    info!("GET /api/positions/{}/ownership - Fetching ownership snapshot", ticker);
+    let snapshot = ownership_service::get_snapshot(
+        &state.pool,
+        state.ownership_provider.as_ref(),
+        &ticker,
+        params.days.unwrap_or(ownership_service::DEFAULT_ACTIVITY_DAYS),
+        params.force.unwrap_or(false),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to fetch ownership for {}: {}", ticker, e);
+        e
+    })?;
+    Ok(Json(snapshot))
+}
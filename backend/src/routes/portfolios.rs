@@ -1,14 +1,23 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::{Json, Router};
 use axum::routing::{delete, get, post, put};
 use tracing::{info, error};
 use uuid::Uuid;
 
+use crate::db::{auth_queries, glide_path_queries, portfolio_member_queries, rebalance_schedule_queries, user_preferences_queries};
+use crate::db::tenant::TenantScope;
 use crate::services;
+use crate::services::audit_service;
 
 use crate::errors::AppError;
-use crate::middleware::auth::AuthUser;
-use crate::models::{CreatePortfolio, Portfolio, UpdatePortfolio, LatestAccountHolding};
+use crate::middleware::auth::{AuthUser, FeedUser};
+use crate::middleware::permissions::{require_role, CanAdmin, CanEdit, CanView, PortfolioAccess};
+use crate::models::{
+    AddPortfolioMember, AnnotatedHolding, CalendarFeedQuery, AssetLocationAnalysis, AssetLocationQuery, BehaviorInsights, BehaviorQuery, BenchmarkComparison, BenchmarkComparisonQuery, ClonePortfolio, ClonePortfolioResponse,
+    ContributionQuery, CreatePortfolio, FeeAnalysis, FeeAnalysisQuery, GlidePath, GlidePathRequest, IncomeCalendar, IncomeCalendarQuery, LookThroughAnalysis, LookThroughQuery, ModelComparisonQuery, ModelPortfolioComparison, PnlQuery, PortfolioContributions, Portfolio, PortfolioHealthCheck, PortfolioListQuery, PortfolioMember,
+    PortfolioNewsFeed, PortfolioNewsFeedQuery, PortfolioPnl, PositionRiskBadge, RebalanceSimulation, RebalanceSimulationQuery, TagFilterQuery, UpdatePortfolio,
+    RebalanceSchedule, Role, UpdatePortfolioMember, AuditAction, NewAuditEntry,
+};
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
@@ -18,6 +27,30 @@ pub fn router() -> Router<AppState> {
         .route("/:id", put(update_portfolio))
         .route("/:id", delete(delete_portfolio))
         .route("/:id/latest-holdings", get(get_portfolio_latest_holdings))
+        .route("/:id/pnl", get(get_portfolio_pnl))
+        .route("/:id/behavior", get(get_behavior_insights))
+        .route("/:id/benchmark-comparison", get(get_benchmark_comparison))
+        .route("/:id/contributions", get(get_contributions))
+        .route("/:id/look-through", get(get_look_through))
+        .route("/:id/income-calendar", get(get_income_calendar))
+        .route("/:id/rebalance-simulation", get(get_rebalance_simulation))
+        .route("/:id/news", get(get_portfolio_news_feed))
+        .route("/:id/asset-location", get(get_asset_location))
+        .route("/:id/fees", get(get_portfolio_fees))
+        .route("/:id/health", get(get_portfolio_health))
+        .route("/:id/positions/risk-badges", get(get_position_risk_badges))
+        .route("/:id/model-comparison", get(get_model_comparison))
+        .route("/:id/glide-path", get(get_glide_path).put(save_glide_path).delete(delete_glide_path))
+        .route(
+            "/:id/rebalance-schedule",
+            get(get_rebalance_schedule).put(save_rebalance_schedule).delete(delete_rebalance_schedule),
+        )
+        .route("/:id/calendar.ics", get(get_calendar_feed))
+        .route("/:id/clone", post(clone_portfolio))
+        .route("/:id/archive", post(archive_portfolio))
+        .route("/:id/unarchive", post(unarchive_portfolio))
+        .route("/:id/members", get(list_members).post(add_member))
+        .route("/:id/members/:user_id", put(update_member).delete(remove_member))
 }
 
 #[axum::debug_handler]
@@ -33,15 +66,23 @@ pub async fn create_portfolio(
             error!("Failed to create portfolio: {}", e);
             e
         })?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(user_id, AuditAction::Create, "portfolio", portfolio.id)
+            .portfolio(portfolio.id)
+            .after(&portfolio),
+    )
+    .await;
     Ok(Json(portfolio))
 }
 
 pub async fn fetch_portfolios(
     State(state): State<AppState>,
     AuthUser(user_id): AuthUser,
+    Query(params): Query<PortfolioListQuery>,
 ) -> Result<Json<Vec<Portfolio>>, AppError> {
-    info!("GET /portfolios - Fetching all portfolios");
-    let portfolios = services::portfolio_service::fetch_all(&state.pool, user_id)
+    info!("GET /portfolios - Fetching all portfolios (include_archived={})", params.include_archived);
+    let portfolios = services::portfolio_service::fetch_all(&state.pool, user_id, params.include_archived)
         .await
         .map_err(|e| {
             error!("Failed to fetch portfolios: {}", e);
@@ -67,32 +108,52 @@ pub async fn get_portfolio(
 
 pub async fn update_portfolio(
     State(state): State<AppState>,
-    AuthUser(user_id): AuthUser,
+    access: PortfolioAccess<CanAdmin>,
     Path(id): Path<Uuid>,
     Json(data): Json<UpdatePortfolio>,
 ) -> Result<Json<Portfolio>, AppError> {
     info!("PUT /portfolios/{} - Updating portfolio", id);
-    let portfolio = services::portfolio_service::update(&state.pool, id, user_id, data)
+    let previous = services::portfolio_service::fetch_one(&state.pool, id, access.user_id).await?;
+    let portfolio = services::portfolio_service::update(&state.pool, id, access.owner_id, data)
         .await
         .map_err(|e| {
             error!("Failed to update portfolio {}: {}", id, e);
             e
         })?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(access.user_id, AuditAction::Update, "portfolio", id)
+            .portfolio(id)
+            .before(&previous)
+            .after(&portfolio),
+    )
+    .await;
     Ok(Json(portfolio))
 }
 
 pub async fn delete_portfolio(
     State(state): State<AppState>,
-    AuthUser(user_id): AuthUser,
+    access: PortfolioAccess<CanAdmin>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<()>, AppError> {
     info!("DELETE /portfolios/{} - Deleting portfolio", id);
-    match services::portfolio_service::delete(&state.pool, id, user_id).await {
+    if !access.is_owner() {
+        return Err(AppError::Forbidden("Only the owner can delete a portfolio".into()));
+    }
+    let previous = services::portfolio_service::fetch_one(&state.pool, id, access.user_id).await?;
+    match services::portfolio_service::delete(&state.pool, id, access.owner_id).await {
         Ok(0) => {
             error!("Portfolio {} not found for deletion", id);
             Err(AppError::NotFound(format!("Portfolio {} not found", id)))
         },
-        Ok(_) => Ok(Json(())),
+        Ok(_) => {
+            audit_service::record(
+                &state.pool,
+                NewAuditEntry::deleted(access.user_id, "portfolio", id, Some(&previous)).portfolio(id),
+            )
+            .await;
+            Ok(Json(()))
+        },
         Err(e) => {
             error!("Failed to delete portfolio {}: {}", id, e);
             Err(e)
@@ -104,16 +165,672 @@ pub async fn get_portfolio_latest_holdings(
     State(state): State<AppState>,
     AuthUser(user_id): AuthUser,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<LatestAccountHolding>>, AppError> {
-    use crate::db::holding_snapshot_queries;
-
+    Query(params): Query<TagFilterQuery>,
+) -> Result<Json<Vec<AnnotatedHolding>>, AppError> {
     info!("GET /portfolios/{}/latest-holdings - Fetching latest holdings", id);
     services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
-    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(&state.pool, id)
+    let tag = services::annotation_service::normalize_tag_filter(params.tag);
+    let holdings = services::annotation_service::fetch_portfolio_holdings(&state.pool, id, tag.as_deref())
         .await
         .map_err(|e| {
             error!("Failed to fetch holdings for portfolio {}: {}", id, e);
-            AppError::Db(e)
+            e
         })?;
     Ok(Json(holdings))
 }
+
+/// GET /api/portfolios/:id/pnl
+///
+/// Realized P&L by period and unrealized P&L per open tax lot, with day-change attribution.
+///
+/// Query parameters:
+/// - period: "month" (default), "quarter", or "year" grouping for realized P&L
+/// - from / to: optional sale-date range (YYYY-MM-DD) for realized P&L
+pub async fn get_portfolio_pnl(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PnlQuery>,
+) -> Result<Json<PortfolioPnl>, AppError> {
+    info!("GET /portfolios/{}/pnl - Computing realized and unrealized P&L", id);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let pnl = services::pnl_service::portfolio_pnl(&state.pool, id, &params)
+        .await
+        .map_err(|e| {
+            error!("Failed to compute P&L for portfolio {}: {}", id, e);
+            e
+        })?;
+    Ok(Json(pnl))
+}
+
+/// GET /api/portfolios/:id/behavior
+///
+/// How the portfolio is traded: annual turnover, average holding period, win rate
+/// of sales, and whether buys and sells were well timed (where the price sat in its
+/// trailing 90-day range and what the stock did afterwards), with findings in the
+/// user's locale.
+///
+/// Query parameters:
+/// - forward_days: calendar days after a trade its timing is judged over (default: 30)
+pub async fn get_behavior_insights(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<BehaviorQuery>,
+) -> Result<Json<BehaviorInsights>, AppError> {
+    info!("GET /portfolios/{}/behavior - Analyzing trading behavior", id);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let locale = user_preferences_queries::get_locale(&state.pool, user_id).await?;
+    let insights = services::behavioral_analytics_service::analyze_portfolio(&state.pool, id, params.forward_days, locale)
+        .await
+        .map_err(|e| {
+            error!("Failed to analyze trading behavior of portfolio {}: {}", id, e);
+            e
+        })?;
+    Ok(Json(insights))
+}
+
+/// GET /api/portfolios/:id/benchmark-comparison
+///
+/// Each holding's period return compared with its sector index ETF and the portfolio
+/// benchmark, with leaders and laggards ranked by contribution (weight × return).
+///
+/// Query parameters:
+/// - days: number of trading days to compare over (default: 90)
+/// - benchmark: benchmark ticker (default: "SPY")
+pub async fn get_benchmark_comparison(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<BenchmarkComparisonQuery>,
+) -> Result<Json<BenchmarkComparison>, AppError> {
+    info!("GET /portfolios/{}/benchmark-comparison - Comparing holdings to {} over {} days", id, params.benchmark, params.days);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let comparison = services::benchmark_comparison_service::compare_portfolio(&state.pool, id, params.days, &params.benchmark)
+        .await
+        .map_err(|e| {
+            error!("Failed to compare portfolio {} to benchmark: {}", id, e);
+            e
+        })?;
+    Ok(Json(comparison))
+}
+
+/// GET /api/portfolios/:id/contributions
+///
+/// Each position's contribution to total portfolio return (weight × return, chained
+/// daily), showing which holdings drove performance over the period.
+///
+/// Query parameters:
+/// - period: lookback such as "30d", "6m", or "1y" (default: "90d")
+pub async fn get_contributions(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ContributionQuery>,
+) -> Result<Json<PortfolioContributions>, AppError> {
+    info!("GET /portfolios/{}/contributions - Computing contribution to return", id);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let contributions = services::contribution_service::portfolio_contributions(&state.pool, id, params.period.as_deref())
+        .await
+        .map_err(|e| {
+            error!("Failed to compute contributions for portfolio {}: {}", id, e);
+            e
+        })?;
+    Ok(Json(contributions))
+}
+
+/// GET /api/portfolios/:id/look-through
+///
+/// Exposures with each ETF replaced by its stored constituents: the largest
+/// single-security exposures held directly and through funds, holdings overlap
+/// between ETF pairs, and the true sector allocation. ETFs the weekly
+/// constituent job hasn't fetched yet are listed as missing.
+///
+/// Query parameters:
+/// - top: number of exposures to return (default: 25)
+pub async fn get_look_through(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanView>,
+    Query(params): Query<LookThroughQuery>,
+) -> Result<Json<LookThroughAnalysis>, AppError> {
+    info!("GET /portfolios/{}/look-through - Looking through ETF holdings", access.portfolio_id);
+    let analysis = services::etf_constituent_service::look_through(&state.pool, access.portfolio_id, params.top)
+        .await
+        .map_err(|e| {
+            error!("Failed to build look-through for portfolio {}: {}", access.portfolio_id, e);
+            e
+        })?;
+    Ok(Json(analysis))
+}
+
+/// GET /api/portfolios/:id/income-calendar
+///
+/// Upcoming ex-dividend dates of the portfolio's holdings, soonest first, with
+/// the income expected from the shares held. Dates are refreshed every morning
+/// by the dividend calendar job.
+///
+/// Query parameters:
+/// - days: days ahead to include (default: 90)
+pub async fn get_income_calendar(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanView>,
+    Query(params): Query<IncomeCalendarQuery>,
+) -> Result<Json<IncomeCalendar>, AppError> {
+    info!("GET /portfolios/{}/income-calendar - Listing upcoming dividends", access.portfolio_id);
+    let calendar = services::dividend_calendar_service::income_calendar(&state.pool, access.portfolio_id, params.days)
+        .await
+        .map_err(|e| {
+            error!("Failed to build income calendar for portfolio {}: {}", access.portfolio_id, e);
+            e
+        })?;
+    Ok(Json(calendar))
+}
+
+/// GET /api/portfolios/:id/rebalance-simulation
+///
+/// Replay the portfolio's history with its current weights as targets under
+/// buy-and-hold, monthly, quarterly and drift-band rebalancing, reporting turnover,
+/// trading costs and tracking difference against holding exactly at target.
+///
+/// Query parameters:
+/// - period: lookback such as "1y" or "36m" (default: "3y")
+/// - band: drift band for threshold rebalancing, in percentage points (default: 5)
+/// - cost_bps: trading cost in basis points of traded value (default: 10)
+pub async fn get_rebalance_simulation(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<RebalanceSimulationQuery>,
+) -> Result<Json<RebalanceSimulation>, AppError> {
+    info!("GET /portfolios/{}/rebalance-simulation - Simulating rebalancing policies", id);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let simulation = services::rebalance_simulation_service::simulate_portfolio(
+        &state.pool,
+        id,
+        params.period.as_deref(),
+        params.band,
+        params.cost_bps,
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to simulate rebalancing for portfolio {}: {}", id, e);
+        e
+    })?;
+    Ok(Json(simulation))
+}
+
+/// GET /api/portfolios/:id/news
+///
+/// Recent headlines for the portfolio's holdings, ordered by relevance
+///
+/// Query parameters:
+/// - `days`: Number of days to look back (default: 7, max: 90)
+/// - `page`, `page_size`: Pagination (default: page 1 of 20 items, max 100)
+/// - `ticker`: Restrict to one held ticker
+/// - `sentiment`: Include a sentiment label per headline (default: false)
+/// - `force`: Bypass the cached feed (default: false)
+pub async fn get_portfolio_news_feed(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PortfolioNewsFeedQuery>,
+) -> Result<Json<PortfolioNewsFeed>, AppError> {
+    info!("GET /portfolios/{}/news - Fetching portfolio news feed", id);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let feed = services::portfolio_news_service::get_feed(&state.pool, &state.news_service, id, &params)
+        .await
+        .map_err(|e| {
+            error!("Failed to build news feed for portfolio {}: {}", id, e);
+            e
+        })?;
+    Ok(Json(feed))
+}
+
+/// GET /api/portfolios/:id/asset-location
+///
+/// Suggest which account type (taxable, tax-deferred, tax-free) should hold each
+/// holding to minimize tax drag, based on its income: bonds and REITs go to sheltered
+/// accounts first, low-yield growth holdings to tax-free or taxable ones. Account
+/// types come from each account's tax treatment, or are inferred from its nickname.
+///
+/// Query parameters:
+/// - marginal_tax_rate: marginal tax rate in percent used for the drag estimates (default: 40)
+pub async fn get_asset_location(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<AssetLocationQuery>,
+) -> Result<Json<AssetLocationAnalysis>, AppError> {
+    info!("GET /portfolios/{}/asset-location - Analyzing asset location", id);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let analysis = services::asset_location_service::portfolio_asset_location(&state.pool, id, params.marginal_tax_rate)
+        .await
+        .map_err(|e| {
+            error!("Failed to analyze asset location for portfolio {}: {}", id, e);
+            e
+        })?;
+    Ok(Json(analysis))
+}
+
+/// GET /api/portfolios/:id/fees
+///
+/// Weighted expense ratio from the fund metadata table, the fee drag projected over
+/// 10 and 20 years, and cheaper funds in the same category to swap into. Holdings
+/// not in the table (e.g. individual stocks) are treated as fee-free.
+///
+/// Query parameters:
+/// - expected_return: yearly return before fees in percent (default: 6)
+pub async fn get_portfolio_fees(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<FeeAnalysisQuery>,
+) -> Result<Json<FeeAnalysis>, AppError> {
+    info!("GET /portfolios/{}/fees - Analyzing fees", id);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let analysis = services::fee_service::portfolio_fees(&state.pool, id, params.expected_return)
+        .await
+        .map_err(|e| {
+            error!("Failed to analyze fees for portfolio {}: {}", id, e);
+            e
+        })?;
+    Ok(Json(analysis))
+}
+
+/// GET /api/portfolios/:id/health
+///
+/// Scored checklist covering concentration, correlation, fee drag, stale data,
+/// missing price history, risk threshold violations and drift from the saved
+/// optimization constraints. Each check links to the endpoint with the details;
+/// checks without data are skipped and left out of the score.
+pub async fn get_portfolio_health(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PortfolioHealthCheck>, AppError> {
+    info!("GET /portfolios/{}/health - Running health checks", id);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let health = services::health_check_service::portfolio_health(&state.pool, id)
+        .await
+        .map_err(|e| {
+            error!("Failed to run health checks for portfolio {}: {}", id, e);
+            e
+        })?;
+    Ok(Json(health))
+}
+
+/// GET /api/portfolios/:id/positions/risk-badges
+///
+/// Compact `{ticker, risk_score, risk_level, day_change}` per held position for
+/// list views. Served from the risk cache and stored prices only, never
+/// triggering a risk calculation; unassessed positions have no score yet.
+pub async fn get_position_risk_badges(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanView>,
+) -> Result<Json<Vec<PositionRiskBadge>>, AppError> {
+    info!("GET /portfolios/{}/positions/risk-badges - Reading cached risk badges", access.portfolio_id);
+    // Cache rows belong to the owner, also when a member is viewing
+    let tenant = TenantScope::for_job(&state.pool, access.portfolio_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Portfolio not found".to_string()))?;
+    let badges = services::risk_badge_service::position_risk_badges(&state.pool, &tenant).await?;
+    Ok(Json(badges))
+}
+
+/// GET /api/portfolios/:id/model-comparison
+///
+/// Compares the portfolio's current weights with a model portfolio over the same
+/// period: backtested return, volatility and drawdown, factor profile, overlap
+/// with the model's holdings, and a plain-language gap analysis.
+///
+/// Query parameters:
+/// - model: model portfolio id from /api/model-portfolios (default: sixty_forty)
+/// - period: lookback such as 1y or 36m (default: 3y)
+pub async fn get_model_comparison(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ModelComparisonQuery>,
+) -> Result<Json<ModelPortfolioComparison>, AppError> {
+    info!("GET /portfolios/{}/model-comparison - Comparing with model {:?}", id, params.model);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let comparison = services::model_portfolio_service::compare_to_model(
+        &state.pool,
+        id,
+        &params,
+        state.price_provider.as_ref(),
+        &state.failure_cache,
+        &state.rate_limiter,
+        state.risk_free_rate,
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to compare portfolio {} with model: {}", id, e);
+        e
+    })?;
+    Ok(Json(comparison))
+}
+
+/// GET /api/portfolios/:id/glide-path
+///
+/// Design a target-date glide path and compare it with the current allocation.
+/// Parameters left out fall back to the saved path, then to the defaults.
+///
+/// Query parameters:
+/// - goal_date, or current_age and retirement_age
+/// - start_equity_pct: equity share far from the goal (default: 90)
+/// - end_equity_pct: equity share at and after the goal (default: 30)
+/// - glide_years: years before the goal over which equity declines (default: 25)
+pub async fn get_glide_path(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<GlidePathRequest>,
+) -> Result<Json<GlidePath>, AppError> {
+    info!("GET /portfolios/{}/glide-path - Generating glide path", id);
+    services::portfolio_service::fetch_one(&state.pool, id, user_id).await?;
+    let glide_path = services::glide_path_service::glide_path(&state.pool, id, &params)
+        .await
+        .map_err(|e| {
+            error!("Failed to generate glide path for portfolio {}: {}", id, e);
+            e
+        })?;
+    Ok(Json(glide_path))
+}
+
+/// PUT /api/portfolios/:id/glide-path
+///
+/// Save the glide path; the constrained rebalancer then targets its equity share
+/// for the current date. Takes the same fields as the GET query parameters.
+pub async fn save_glide_path(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<GlidePathRequest>,
+) -> Result<Json<GlidePath>, AppError> {
+    info!("PUT /portfolios/{}/glide-path - Saving glide path", id);
+    let previous = glide_path_queries::fetch(&state.pool, id).await?;
+    let glide_path = services::glide_path_service::save(&state.pool, id, &request)
+        .await
+        .map_err(|e| {
+            error!("Failed to save glide path for portfolio {}: {}", id, e);
+            e
+        })?;
+    if let Some(saved) = glide_path_queries::fetch(&state.pool, id).await? {
+        audit_service::record(
+            &state.pool,
+            NewAuditEntry::saved(access.user_id, "glide_path", id, previous.as_ref())
+                .portfolio(id)
+                .after(&saved),
+        )
+        .await;
+    }
+    Ok(Json(glide_path))
+}
+
+/// DELETE /api/portfolios/:id/glide-path
+pub async fn delete_glide_path(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(id): Path<Uuid>,
+) -> Result<axum::http::StatusCode, AppError> {
+    info!("DELETE /portfolios/{}/glide-path - Removing glide path", id);
+    let previous = glide_path_queries::fetch(&state.pool, id).await?;
+    services::glide_path_service::delete(&state.pool, id).await?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::deleted(access.user_id, "glide_path", id, previous.as_ref()).portfolio(id),
+    )
+    .await;
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+/// GET /api/portfolios/:id/rebalance-schedule
+pub async fn get_rebalance_schedule(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanView>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RebalanceSchedule>, AppError> {
+    info!("GET /portfolios/{}/rebalance-schedule for user {}", id, access.user_id);
+    let schedule = rebalance_schedule_queries::fetch(&state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No rebalance schedule set for portfolio {}", id)))?;
+    Ok(Json(schedule))
+}
+
+/// PUT /api/portfolios/:id/rebalance-schedule
+///
+/// Schedule recurring rebalances, shown in the portfolio's calendar feed.
+/// Body: { "frequency": "monthly" | "quarterly" | "semiannual" | "annual", "start_date": "YYYY-MM-DD" }
+pub async fn save_rebalance_schedule(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(id): Path<Uuid>,
+    Json(schedule): Json<RebalanceSchedule>,
+) -> Result<Json<RebalanceSchedule>, AppError> {
+    info!("PUT /portfolios/{}/rebalance-schedule - Saving rebalance schedule", id);
+    let previous = rebalance_schedule_queries::fetch(&state.pool, id).await?;
+    let saved = services::calendar_service::save_rebalance_schedule(&state.pool, id, &schedule).await?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::saved(access.user_id, "rebalance_schedule", id, previous.as_ref())
+            .portfolio(id)
+            .after(&saved),
+    )
+    .await;
+    Ok(Json(saved))
+}
+
+/// DELETE /api/portfolios/:id/rebalance-schedule
+pub async fn delete_rebalance_schedule(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanEdit>,
+    Path(id): Path<Uuid>,
+) -> Result<axum::http::StatusCode, AppError> {
+    info!("DELETE /portfolios/{}/rebalance-schedule - Removing rebalance schedule", id);
+    let previous = rebalance_schedule_queries::fetch(&state.pool, id).await?;
+    services::calendar_service::delete_rebalance_schedule(&state.pool, id).await?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::deleted(access.user_id, "rebalance_schedule", id, previous.as_ref()).portfolio(id),
+    )
+    .await;
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+/// GET /api/portfolios/:id/calendar.ics
+///
+/// Upcoming earnings, ex-dividend dates, scheduled rebalances and goal dates as
+/// an iCal feed. Calendar apps subscribe with a public API token in the URL:
+/// `/api/portfolios/:id/calendar.ics?token=<token>`; a signed-in browser needs none.
+///
+/// Query parameters:
+/// - days: days ahead included (default: 180, max: 730)
+pub async fn get_calendar_feed(
+    State(state): State<AppState>,
+    FeedUser(user_id): FeedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<CalendarFeedQuery>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    info!("GET /portfolios/{}/calendar.ics for user {}", id, user_id);
+    let days = query.days.unwrap_or(services::calendar_service::DEFAULT_DAYS);
+    let ics = services::calendar_service::feed(&state.pool, id, user_id, days).await?;
+    Ok((
+        [
+            (axum::http::header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (axum::http::header::CONTENT_DISPOSITION, "inline; filename=\"calendar.ics\""),
+        ],
+        ics,
+    ))
+}
+
+pub async fn clone_portfolio(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(id): Path<Uuid>,
+    body: Option<Json<ClonePortfolio>>,
+) -> Result<Json<ClonePortfolioResponse>, AppError> {
+    info!("POST /portfolios/{}/clone - Cloning portfolio", id);
+    let input = body.map(|Json(b)| b).unwrap_or_default();
+    let cloned = services::portfolio_service::clone(&state.pool, id, user_id, input)
+        .await
+        .map_err(|e| {
+            error!("Failed to clone portfolio {}: {}", id, e);
+            e
+        })?;
+    info!(
+        "Cloned portfolio {} into {} ({} holdings)",
+        id, cloned.portfolio.id, cloned.holdings_copied
+    );
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(user_id, AuditAction::Create, "portfolio", cloned.portfolio.id)
+            .portfolio(cloned.portfolio.id)
+            .after(&cloned.portfolio),
+    )
+    .await;
+    Ok(Json(cloned))
+}
+
+pub async fn archive_portfolio(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanAdmin>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Portfolio>, AppError> {
+    info!("POST /portfolios/{}/archive - Archiving portfolio", id);
+    let previous = services::portfolio_service::fetch_one(&state.pool, id, access.user_id).await?;
+    let portfolio = services::portfolio_service::set_archived(&state.pool, id, access.owner_id, true)
+        .await
+        .map_err(|e| {
+            error!("Failed to archive portfolio {}: {}", id, e);
+            e
+        })?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(access.user_id, AuditAction::Update, "portfolio", id)
+            .portfolio(id)
+            .before(&previous)
+            .after(&portfolio),
+    )
+    .await;
+    Ok(Json(portfolio))
+}
+
+pub async fn unarchive_portfolio(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanAdmin>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Portfolio>, AppError> {
+    info!("POST /portfolios/{}/unarchive - Unarchiving portfolio", id);
+    let previous = services::portfolio_service::fetch_one(&state.pool, id, access.user_id).await?;
+    let portfolio = services::portfolio_service::set_archived(&state.pool, id, access.owner_id, false)
+        .await
+        .map_err(|e| {
+            error!("Failed to unarchive portfolio {}: {}", id, e);
+            e
+        })?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(access.user_id, AuditAction::Update, "portfolio", id)
+            .portfolio(id)
+            .before(&previous)
+            .after(&portfolio),
+    )
+    .await;
+    Ok(Json(portfolio))
+}
+
+/// GET /api/portfolios/:id/members
+///
+/// The owner and every user the portfolio is shared with, with their roles.
+pub async fn list_members(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanView>,
+) -> Result<Json<Vec<PortfolioMember>>, AppError> {
+    info!("GET /portfolios/{}/members - Listing members", access.portfolio_id);
+    let members = services::portfolio_member_service::list(&state.pool, access.portfolio_id).await?;
+    Ok(Json(members))
+}
+
+/// POST /api/portfolios/:id/members
+///
+/// Share the portfolio with a registered user: `{"email": "...", "role": "viewer"}`.
+/// Viewers can read it, editors can also change its accounts, positions,
+/// thresholds and snapshots, and admins can also rename, archive and share it.
+pub async fn add_member(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanAdmin>,
+    Json(data): Json<AddPortfolioMember>,
+) -> Result<Json<PortfolioMember>, AppError> {
+    info!("POST /portfolios/{}/members - Sharing portfolio as {}", access.portfolio_id, data.role);
+    let previous = match auth_queries::get_user_by_email(&state.pool, &data.email.trim().to_lowercase()).await? {
+        Some(user) => portfolio_member_queries::fetch_one(&state.pool, access.portfolio_id, user.id).await?,
+        None => None,
+    };
+    let member = services::portfolio_member_service::add(
+        &state.pool,
+        access.portfolio_id,
+        access.owner_id,
+        access.user_id,
+        data,
+    )
+    .await?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::saved(access.user_id, "portfolio_member", member.user_id, previous.as_ref())
+            .portfolio(access.portfolio_id)
+            .after(&member),
+    )
+    .await;
+    Ok(Json(member))
+}
+
+/// PUT /api/portfolios/:id/members/:user_id
+pub async fn update_member(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanAdmin>,
+    Path((_, member_id)): Path<(Uuid, Uuid)>,
+    Json(data): Json<UpdatePortfolioMember>,
+) -> Result<Json<PortfolioMember>, AppError> {
+    info!("PUT /portfolios/{}/members/{} - Changing role to {}", access.portfolio_id, member_id, data.role);
+    let previous = portfolio_member_queries::fetch_one(&state.pool, access.portfolio_id, member_id).await?;
+    let member = services::portfolio_member_service::update_role(
+        &state.pool,
+        access.portfolio_id,
+        access.owner_id,
+        member_id,
+        data.role,
+    )
+    .await?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::saved(access.user_id, "portfolio_member", member_id, previous.as_ref())
+            .portfolio(access.portfolio_id)
+            .after(&member),
+    )
+    .await;
+    Ok(Json(member))
+}
+
+/// DELETE /api/portfolios/:id/members/:user_id
+///
+/// Stop sharing the portfolio with a user. Members may also remove themselves.
+pub async fn remove_member(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanView>,
+    Path((_, member_id)): Path<(Uuid, Uuid)>,
+) -> Result<axum::http::StatusCode, AppError> {
+    info!("DELETE /portfolios/{}/members/{} - Removing member", access.portfolio_id, member_id);
+    if member_id != access.user_id {
+        require_role(access.role, Role::Admin)?;
+    }
+    let previous = portfolio_member_queries::fetch_one(&state.pool, access.portfolio_id, member_id).await?;
+    services::portfolio_member_service::remove(&state.pool, access.portfolio_id, access.owner_id, member_id).await?;
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::deleted(access.user_id, "portfolio_member", member_id, previous.as_ref())
+            .portfolio(access.portfolio_id),
+    )
+    .await;
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
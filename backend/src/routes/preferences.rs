@@ -9,10 +9,15 @@ use axum::{
 use serde_json::json;
 use tracing::info;
 
+use crate::db::{llm_queries, user_preferences_queries};
 use crate::errors::AppError;
 use crate::middleware::auth::AuthUser;
-use crate::models::{RiskPreferencesResponse, UpdateRiskPreferences};
-use crate::services::user_preference_service;
+use crate::models::{
+    LlmConsentStatus, LocaleSettings, RecordLlmConsent, RiskPreferencesResponse, UpdateCurrencySettings,
+    TimezoneSettings, UpdateLocaleSettings, UpdateNotificationSettings, UpdateRiskPreferences,
+    UpdateTimezoneSettings, CURRENT_LLM_CONSENT_VERSION,
+};
+use crate::services::{clock, fx_service, timezone, user_preference_service};
 use crate::state::AppState;
 
 /// Create the preferences router
@@ -21,6 +26,23 @@ pub fn router() -> Router<AppState> {
         .route("/users/me/preferences", get(get_preferences))
         .route("/users/me/preferences", put(update_preferences))
         .route("/users/me/preferences/reset", post(reset_preferences))
+        .route(
+            "/users/me/preferences/notifications",
+            get(get_notification_settings).put(update_notification_settings),
+        )
+        .route(
+            "/users/me/preferences/currency",
+            get(get_currency_settings).put(update_currency_settings),
+        )
+        .route(
+            "/users/me/preferences/locale",
+            get(get_locale_settings).put(update_locale_settings),
+        )
+        .route(
+            "/users/me/preferences/timezone",
+            get(get_timezone_settings).put(update_timezone_settings),
+        )
+        .route("/users/me/llm-consent", get(get_llm_consent).post(record_llm_consent))
         .route("/users/me/risk-profile", get(get_risk_profile))
 }
 
@@ -69,6 +91,175 @@ pub async fn reset_preferences(
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// GET /api/users/me/preferences/notifications
+pub async fn get_notification_settings(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    info!("GET /api/users/me/preferences/notifications for user {}", user_id);
+
+    let settings = user_preferences_queries::get_notification_settings(&state.pool, user_id).await?;
+
+    Ok((StatusCode::OK, Json(settings)))
+}
+
+/// PUT /api/users/me/preferences/notifications
+pub async fn update_notification_settings(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(update): Json<UpdateNotificationSettings>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("PUT /api/users/me/preferences/notifications for user {}", user_id);
+
+    let quiet_hours = update.quiet_hours().map_err(AppError::Validation)?;
+    let settings =
+        user_preferences_queries::upsert_notification_settings(&state.pool, user_id, &update, quiet_hours).await?;
+
+    Ok((StatusCode::OK, Json(settings)))
+}
+
+/// GET /api/users/me/preferences/currency
+pub async fn get_currency_settings(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    info!("GET /api/users/me/preferences/currency for user {}", user_id);
+
+    let settings = user_preferences_queries::get_currency_settings(&state.pool, user_id).await?;
+
+    Ok((StatusCode::OK, Json(settings)))
+}
+
+/// PUT /api/users/me/preferences/currency
+///
+/// Portfolio totals, weights and analytics are reported in this currency.
+pub async fn update_currency_settings(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(update): Json<UpdateCurrencySettings>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("PUT /api/users/me/preferences/currency for user {} - {}", user_id, update.base_currency);
+
+    let base_currency = fx_service::normalize_currency(&update.base_currency).map_err(AppError::Validation)?;
+    let settings =
+        user_preferences_queries::upsert_currency_settings(&state.pool, user_id, &base_currency).await?;
+
+    Ok((StatusCode::OK, Json(settings)))
+}
+
+/// GET /api/users/me/preferences/locale
+pub async fn get_locale_settings(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    info!("GET /api/users/me/preferences/locale for user {}", user_id);
+
+    let locale = user_preferences_queries::get_locale(&state.pool, user_id).await?;
+
+    Ok((StatusCode::OK, Json(LocaleSettings::from(locale))))
+}
+
+/// PUT /api/users/me/preferences/locale
+///
+/// Recommendations, factor findings and narratives are written in this
+/// language; the returned formatting tells clients how to render numbers and
+/// dates.
+pub async fn update_locale_settings(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(update): Json<UpdateLocaleSettings>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("PUT /api/users/me/preferences/locale for user {} - {}", user_id, update.locale.as_str());
+
+    let locale = user_preferences_queries::upsert_locale(&state.pool, user_id, update.locale).await?;
+
+    Ok((StatusCode::OK, Json(LocaleSettings::from(locale))))
+}
+
+/// GET /api/users/me/preferences/timezone
+pub async fn get_timezone_settings(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    info!("GET /api/users/me/preferences/timezone for user {}", user_id);
+
+    let name = user_preferences_queries::get_timezone(&state.pool, user_id).await?;
+
+    Ok((StatusCode::OK, Json(timezone_settings(timezone::resolve_or_default(&name)))))
+}
+
+/// PUT /api/users/me/preferences/timezone
+///
+/// Daily risk snapshots of the user's portfolios are dated by this zone's
+/// calendar day, and digests go out in its morning.
+pub async fn update_timezone_settings(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(update): Json<UpdateTimezoneSettings>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("PUT /api/users/me/preferences/timezone for user {} - {}", user_id, update.timezone);
+
+    let tz = timezone::resolve(&update.timezone).map_err(AppError::Validation)?;
+    user_preferences_queries::upsert_timezone(&state.pool, user_id, tz.name()).await?;
+
+    Ok((StatusCode::OK, Json(timezone_settings(tz))))
+}
+
+fn timezone_settings(tz: timezone::Tz) -> TimezoneSettings {
+    TimezoneSettings {
+        timezone: tz.name().to_string(),
+        utc_offset_minutes: tz.offset_minutes(clock::now()),
+    }
+}
+
+/// GET /api/users/me/llm-consent
+pub async fn get_llm_consent(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    info!("GET /api/users/me/llm-consent for user {}", user_id);
+
+    let latest = llm_queries::fetch_latest_consent(&state.pool, user_id).await?;
+
+    Ok((StatusCode::OK, Json(LlmConsentStatus::from_latest(latest))))
+}
+
+/// POST /api/users/me/llm-consent
+///
+/// Records a grant or revocation. A grant must name the current consent
+/// version; a revocation defaults to it.
+pub async fn record_llm_consent(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(request): Json<RecordLlmConsent>,
+) -> Result<impl IntoResponse, AppError> {
+    info!(
+        "POST /api/users/me/llm-consent for user {} - granted: {}, version: {:?}",
+        user_id, request.granted, request.version
+    );
+
+    let version = consent_version(&request)?;
+    let consent = llm_queries::record_consent(&state.pool, user_id, version, request.granted).await?;
+
+    Ok((StatusCode::CREATED, Json(LlmConsentStatus::from_latest(Some(consent)))))
+}
+
+/// The version a consent request applies to; grants must be for the current text
+fn consent_version(request: &RecordLlmConsent) -> Result<i32, AppError> {
+    match (request.granted, request.version) {
+        (true, Some(CURRENT_LLM_CONSENT_VERSION)) => Ok(CURRENT_LLM_CONSENT_VERSION),
+        (true, Some(v)) => Err(AppError::Validation(format!(
+            "Consent version {} is not current; the current version is {}",
+            v, CURRENT_LLM_CONSENT_VERSION
+        ))),
+        (true, None) => Err(AppError::Validation(format!(
+            "version is required when granting consent (current version is {})",
+            CURRENT_LLM_CONSENT_VERSION
+        ))),
+        (false, _) => Ok(CURRENT_LLM_CONSENT_VERSION),
+    }
+}
+
 /// GET /api/users/me/risk-profile
 pub async fn get_risk_profile(
     State(state): State<AppState>,
@@ -111,6 +302,7 @@ mod tests {
             technical_weight: Some(0.3),
             fundamental_weight: Some(0.3),
             custom_settings: None,
+            share_anonymous_stats: None,
         };
 
         let json = serde_json::to_string(&update).unwrap();
@@ -130,8 +322,23 @@ mod tests {
             technical_weight: None,
             fundamental_weight: None,
             custom_settings: None,
+            share_anonymous_stats: None,
         };
 
         assert!(update.validate().is_ok());
     }
+
+    #[test]
+    fn test_consent_version() {
+        let grant = |version| RecordLlmConsent { granted: true, version };
+        assert_eq!(consent_version(&grant(Some(CURRENT_LLM_CONSENT_VERSION))).unwrap(), CURRENT_LLM_CONSENT_VERSION);
+        assert!(matches!(consent_version(&grant(None)), Err(AppError::Validation(_))));
+        assert!(matches!(
+            consent_version(&grant(Some(CURRENT_LLM_CONSENT_VERSION + 1))),
+            Err(AppError::Validation(_))
+        ));
+
+        let revoke = RecordLlmConsent { granted: false, version: None };
+        assert_eq!(consent_version(&revoke).unwrap(), CURRENT_LLM_CONSENT_VERSION);
+    }
 }
@@ -1,5 +1,5 @@
 
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::{Json, Router};
 use axum::http::StatusCode;
 use axum::routing::{get, post};
@@ -7,7 +7,7 @@ use tracing::{info, error, warn};
 
 use crate::errors::AppError;
 use crate::external::price_provider::ExternalTickerMatch;
-use crate::models::PricePoint;
+use crate::models::{IntradayPrices, IntradayQuery, PricePoint, PriceQualityReport, PriceRepairResult};
 use crate::services;
 use crate::state::AppState;
 
@@ -15,7 +15,10 @@ pub fn router() -> Router<AppState> {
     Router::new()
         .route("/:ticker", get(get_prices))
         .route("/:ticker/latest", get(get_latest_price))
+        .route("/:ticker/intraday", get(get_intraday_prices))
         .route("/:ticker/update", post(update_prices))
+        .route("/:ticker/quality", get(get_price_quality))
+        .route("/:ticker/quality/repair", post(repair_price_quality))
         .route("/:ticker/mock", post(generate_mock_prices))
         .route("/search/:keyword", get(search_for_ticker_by_keyword))
 }
@@ -62,6 +65,33 @@ pub async fn get_latest_price(
     Ok(Json(price))
 }
 
+/// GET /api/prices/:ticker/intraday?interval=5min
+///
+/// Bars of the latest trading session with the move since the previous daily
+/// close. Intervals: 1min, 5min (default), 15min, 30min, 60min.
+pub async fn get_intraday_prices(
+    Path(ticker): Path<String>,
+    Query(query): Query<IntradayQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<IntradayPrices>, AppError> {
+    info!("GET /prices/{}/intraday - Getting {} bars", ticker, query.interval.as_str());
+    let prices = services::price_service::get_intraday(
+        &state.pool,
+        state.price_provider.as_ref(),
+        &state.rate_limiter,
+        &ticker,
+        query.interval,
+    ).await
+        .map_err(|e| {
+            match &e {
+                AppError::RateLimited => warn!("Rate limited when getting intraday prices for {}", ticker),
+                _ => error!("Failed to get intraday prices for {}: {}", ticker, e),
+            }
+            e
+        })?;
+    Ok(Json(prices))
+}
+
 pub async fn update_prices(
     Path(ticker): Path<String>,
     State(state): State<AppState>,
@@ -84,6 +114,43 @@ pub async fn update_prices(
     Ok(StatusCode::OK)
 }
 
+/// GET /api/prices/:ticker/quality
+///
+/// Missing trading days, dates stored under another spelling of the ticker,
+/// and zero or negative closes in the stored history.
+pub async fn get_price_quality(
+    Path(ticker): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<PriceQualityReport>, AppError> {
+    info!("GET /prices/{}/quality - Checking stored history", ticker);
+    Ok(Json(services::data_quality_service::check(&state.pool, &ticker).await?))
+}
+
+/// POST /api/prices/:ticker/quality/repair
+///
+/// Re-fetch the range covering the gaps and invalid closes and store the
+/// provider's closes for those days. Duplicates are only reported.
+pub async fn repair_price_quality(
+    Path(ticker): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<PriceRepairResult>, AppError> {
+    info!("POST /prices/{}/quality/repair - Re-fetching gaps", ticker);
+    let result = services::data_quality_service::repair(
+        &state.pool,
+        state.price_provider.as_ref(),
+        &state.rate_limiter,
+        &ticker,
+    ).await
+        .map_err(|e| {
+            match &e {
+                AppError::RateLimited => warn!("Rate limited when repairing prices for {}", ticker),
+                _ => error!("Failed to repair prices for {}: {}", ticker, e),
+            }
+            e
+        })?;
+    Ok(Json(result))
+}
+
 pub async fn generate_mock_prices(
     Path(ticker): Path<String>,
     State(state): State<AppState>
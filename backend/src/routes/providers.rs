@@ -0,0 +1,35 @@
+use axum::extract::State;
+use axum::routing::get;
+use axum::{Json, Router};
+use tracing::info;
+
+use crate::errors::AppError;
+use crate::middleware::permissions::AdminUser;
+use crate::models::ProvidersStatus;
+use crate::services::{clock, provider_status_service};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/status", get(get_status))
+}
+
+/// GET /api/providers/status - Health, remaining daily quota estimate, recent
+/// failures and last successful fetch of each configured data provider
+pub async fn get_status(
+    State(state): State<AppState>,
+    AdminUser(user_id): AdminUser,
+) -> Result<Json<ProvidersStatus>, AppError> {
+    info!("GET /api/providers/status (requested by {})", user_id);
+    let status = provider_status_service::status(
+        &state.pool,
+        &state.price_provider_name,
+        state.price_provider.as_ref(),
+        state.fundamentals_provider.is_some(),
+        &state.fx_provider_name,
+        &state.failure_cache,
+        state.rate_limiter.as_ref(),
+        clock::now(),
+    )
+    .await?;
+    Ok(Json(status))
+}
@@ -0,0 +1,109 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db::public_api_token_queries;
+use crate::errors::AppError;
+use crate::middleware::auth::{AuthUser, PublicApiUser};
+use crate::models::{
+    CreatePublicApiToken, CreatedPublicApiToken, PublicApiToken, PublicHistoryQuery, PublicPortfolioValue,
+    PublicRiskSummary, PublicValuePoint,
+};
+use crate::services::public_api_service::{self, DEFAULT_HISTORY_DAYS};
+use crate::state::AppState;
+
+/// Token management, for the signed-in user
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/users/me/public-api-tokens", get(list_tokens).post(create_token))
+        .route("/users/me/public-api-tokens/:token_id", delete(delete_token))
+}
+
+/// The read-only public API, mounted at /api/public only when
+/// `PUBLIC_API_ENABLED` is set. GET routes only.
+pub fn public_router() -> Router<AppState> {
+    Router::new()
+        .route("/portfolios", get(list_portfolio_values))
+        .route("/portfolios/:id/value", get(get_portfolio_value))
+        .route("/portfolios/:id/risk", get(get_risk_summary))
+        .route("/portfolios/:id/history", get(get_value_history))
+}
+
+/// GET /api/users/me/public-api-tokens
+pub async fn list_tokens(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<Vec<PublicApiToken>>, AppError> {
+    info!("GET /api/users/me/public-api-tokens for user {}", user_id);
+    Ok(Json(public_api_token_queries::fetch_for_user(&state.pool, user_id).await?))
+}
+
+/// POST /api/users/me/public-api-tokens - The token is only returned in this response
+pub async fn create_token(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(body): Json<CreatePublicApiToken>,
+) -> Result<Json<CreatedPublicApiToken>, AppError> {
+    info!("POST /api/users/me/public-api-tokens for user {}", user_id);
+    Ok(Json(public_api_service::create_token(&state.pool, user_id, &body.name).await?))
+}
+
+/// DELETE /api/users/me/public-api-tokens/:token_id
+pub async fn delete_token(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path(token_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("DELETE /api/users/me/public-api-tokens/{} for user {}", token_id, user_id);
+    if public_api_token_queries::delete(&state.pool, token_id, user_id).await? == 0 {
+        return Err(AppError::NotFound(format!("Public API token {} not found", token_id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/public/portfolios
+pub async fn list_portfolio_values(
+    State(state): State<AppState>,
+    PublicApiUser(user_id): PublicApiUser,
+) -> Result<Json<Vec<PublicPortfolioValue>>, AppError> {
+    info!("GET /api/public/portfolios for user {}", user_id);
+    Ok(Json(public_api_service::portfolio_values_for_user(&state.pool, user_id).await?))
+}
+
+/// GET /api/public/portfolios/:id/value
+pub async fn get_portfolio_value(
+    State(state): State<AppState>,
+    PublicApiUser(user_id): PublicApiUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PublicPortfolioValue>, AppError> {
+    info!("GET /api/public/portfolios/{}/value", id);
+    Ok(Json(public_api_service::portfolio_value(&state.pool, id, user_id).await?))
+}
+
+/// GET /api/public/portfolios/:id/risk
+pub async fn get_risk_summary(
+    State(state): State<AppState>,
+    PublicApiUser(user_id): PublicApiUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PublicRiskSummary>, AppError> {
+    info!("GET /api/public/portfolios/{}/risk", id);
+    Ok(Json(public_api_service::risk_summary(&state.pool, id, user_id).await?))
+}
+
+/// GET /api/public/portfolios/:id/history?days=365
+pub async fn get_value_history(
+    State(state): State<AppState>,
+    PublicApiUser(user_id): PublicApiUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<PublicHistoryQuery>,
+) -> Result<Json<Vec<PublicValuePoint>>, AppError> {
+    info!("GET /api/public/portfolios/{}/history", id);
+    let days = query.days.unwrap_or(DEFAULT_HISTORY_DAYS);
+    if days <= 0 {
+        return Err(AppError::Validation("days must be positive".to_string()));
+    }
+    Ok(Json(public_api_service::value_history(&state.pool, id, user_id, days).await?))
+}
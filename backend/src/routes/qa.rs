@@ -4,7 +4,9 @@ use axum::routing::post;
 use tracing::{error, info};
 use uuid::Uuid;
 
+use crate::db::portfolio_queries;
 use crate::errors::AppError;
+use crate::middleware::auth::AuthUser;
 use crate::models::{PortfolioQuestion, PortfolioAnswer};
 use crate::services::qa_service;
 use crate::state::AppState;
@@ -26,6 +28,7 @@ pub fn router() -> Router<AppState> {
 ///
 /// Returns: PortfolioAnswer with answer, sources, confidence, and follow-up questions
 async fn ask_question(
+    AuthUser(user_id): AuthUser,
     Path(portfolio_id): Path<Uuid>,
     State(state): State<AppState>,
     Json(question): Json<PortfolioQuestion>,
@@ -35,14 +38,15 @@ async fn ask_question(
         portfolio_id, question.question
     );
 
-    // Use demo user ID (in production, extract from auth token)
-    let demo_user_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001")
-        .expect("Invalid demo user UUID");
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
 
     let answer = qa_service::answer_portfolio_question(
         state.llm_service.clone(),
         &state.pool,
-        demo_user_id,
+        user_id,
         portfolio_id,
         question,
     )
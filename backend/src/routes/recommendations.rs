@@ -1,20 +1,28 @@
 use axum::extract::{Path, Query, State};
 use axum::{Json, Router};
 use axum::routing::{get, post};
+use axum::response::Response;
+use axum::http::HeaderMap;
 use tracing::{error, info};
 use uuid::Uuid;
 
 use crate::errors::AppError;
-use crate::models::factor::{FactorAnalysisResponse, FactorQueryParams};
+use crate::models::factor::{
+    FactorAnalysisResponse, FactorQueryParams, FactorRegressionParams, FactorRegressionResponse, FactorSpreadParams,
+    FactorSpreadsResponse,
+};
 use crate::models::long_term_guidance::{
     LongTermGuidanceResponse, LongTermGuidanceQuery,
     InvestmentGoal, RiskTolerance,
 };
 use crate::models::{ExplanationQuery, NarrativeType, RecommendationExplanation};
 use crate::models::screening::{ScreeningRequest, ScreeningResponse};
-use crate::db::portfolio_queries;
+use crate::db::{portfolio_queries, user_preferences_queries};
 use crate::middleware::auth::AuthUser;
-use crate::services::factor_service;
+use crate::services::{factor_regression_service, factor_service, factor_spread_service};
+use crate::services::export_service::{
+    export_response, portfolio_export_name, ExportCell, ExportDocument, ExportFormat, ExportQuery, ExportTable,
+};
 use crate::services::explanation_service;
 use crate::services::long_term_guidance_service::LongTermGuidanceService;
 use crate::services::screening_service::ScreeningService;
@@ -24,6 +32,10 @@ pub fn router() -> Router<AppState> {
     Router::new()
         .route("/screen", post(screen_stocks))
         .route("/factors/:portfolio_id", get(get_factor_recommendations))
+        .route("/factors/:portfolio_id/spreads", get(get_factor_spreads))
+        .route("/factors/:portfolio_id/regression", get(get_factor_regression))
+        .route("/factors/:portfolio_id/export", get(export_factor_analysis))
+        .route("/factors/:portfolio_id/export/csv", get(export_factor_analysis))
         .route("/long-term/:portfolio_id", get(get_long_term_guidance))
         .route("/:symbol/explanation", get(get_recommendation_explanation))
 }
@@ -35,7 +47,7 @@ pub fn router() -> Router<AppState> {
 /// filtering by sector, market cap, price range, geography, and liquidity.
 /// Results are ranked by composite score and paginated.
 ///
-/// Screening results are cached for 15 minutes unless `refresh: true`.
+/// Screening results are cached per user for 15 minutes unless `refresh: true`.
 ///
 /// # Request Body
 /// ```json
@@ -50,6 +62,7 @@ pub fn router() -> Router<AppState> {
 /// ```
 #[axum::debug_handler]
 pub async fn screen_stocks(
+    AuthUser(user_id): AuthUser,
     State(state): State<AppState>,
     Json(req): Json<ScreeningRequest>,
 ) -> Result<Json<ScreeningResponse>, AppError> {
@@ -96,7 +109,7 @@ pub async fn screen_stocks(
 
     let service = ScreeningService::new(state.pool.clone());
 
-    let response = service.screen(&req).await.map_err(|e| {
+    let response = service.screen(user_id, &req).await.map_err(|e| {
         error!("Screening failed: {}", e);
         AppError::External(format!("Screening failed: {}", e))
     })?;
@@ -154,7 +167,7 @@ pub async fn get_factor_recommendations(
         .await
         .map_err(|_| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
 
-    let analysis = factor_service::analyze_portfolio_factors(
+    let mut analysis = factor_service::analyze_portfolio_factors(
         &state.pool,
         portfolio_id,
         state.price_provider.as_ref(),
@@ -183,9 +196,175 @@ pub async fn get_factor_recommendations(
         analysis.backtest_results.len(),
     );
 
+    let locale = user_preferences_queries::get_locale(&state.pool, user_id).await?;
+    factor_service::localize(&mut analysis, locale);
+
     Ok(Json(analysis))
 }
 
+/// GET /api/recommendations/factors/:portfolio_id/regression?days=756
+///
+/// Each holding's daily excess returns regressed on the published market, size,
+/// value and momentum factor returns: loadings and annualized alpha with their
+/// t-statistics. Holdings with too little overlapping history are listed as skipped.
+pub async fn get_factor_regression(
+    AuthUser(user_id): AuthUser,
+    Path(portfolio_id): Path<Uuid>,
+    Query(params): Query<FactorRegressionParams>,
+    State(state): State<AppState>,
+) -> Result<Json<FactorRegressionResponse>, AppError> {
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await.map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+    info!("GET /api/recommendations/factors/{}/regression - days={}", portfolio_id, params.days);
+    Ok(Json(factor_regression_service::regress_portfolio(&state.pool, portfolio_id, params.days).await?))
+}
+
+/// GET /api/recommendations/factors/:portfolio_id/spreads?days=63
+///
+/// Whether value, growth, momentum, quality and low volatility are currently
+/// "working": compounded daily long-short spreads (top vs bottom quintile of the
+/// tracked universe) stored by the factor spread job, next to the portfolio's
+/// exposure to each factor.
+pub async fn get_factor_spreads(
+    AuthUser(user_id): AuthUser,
+    Path(portfolio_id): Path<Uuid>,
+    Query(params): Query<FactorSpreadParams>,
+    State(state): State<AppState>,
+) -> Result<Json<FactorSpreadsResponse>, AppError> {
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await.map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+    info!("GET /api/recommendations/factors/{}/spreads - days={}", portfolio_id, params.days);
+
+    // Exposures only; a portfolio without holdings still gets the factor readings
+    let exposures = match factor_service::analyze_portfolio_factors(
+        &state.pool,
+        portfolio_id,
+        state.price_provider.as_ref(),
+        &state.failure_cache,
+        &state.rate_limiter,
+        state.risk_free_rate,
+        252,
+        false,
+        false,
+    )
+    .await
+    {
+        Ok(analysis) => analysis.factor_exposures,
+        Err(AppError::Validation(_)) => vec![],
+        Err(e) => {
+            error!("Factor exposures failed for portfolio {}: {:?}", portfolio_id, e);
+            return Err(e);
+        }
+    };
+
+    let spreads = factor_spread_service::get_spreads(&state.pool, portfolio_id, &exposures, params.days).await?;
+    Ok(Json(spreads))
+}
+
+/// GET /api/recommendations/factors/:portfolio_id/export
+///
+/// Export the factor analysis (also served at `/export/csv`): per-holding
+/// factor scores plus the portfolio-level factor exposures.
+///
+/// # Query Parameters
+/// - `days`: Price history window in trading days (default: 252)
+/// - `format`: csv, xlsx, or json (default: negotiated from `Accept`, else csv)
+///
+/// Back-tests and ETF suggestions are not part of the export and are skipped.
+pub async fn export_factor_analysis(
+    AuthUser(user_id): AuthUser,
+    Path(portfolio_id): Path<Uuid>,
+    Query(params): Query<FactorQueryParams>,
+    Query(export): Query<ExportQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Response, AppError> {
+    let format = ExportFormat::negotiate(export.format.as_deref(), &headers)?;
+    let portfolio = portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await.map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+    let days = params.days.unwrap_or(252);
+
+    info!(
+        "GET /api/recommendations/factors/{}/export - Exporting factor analysis (days={}, {:?})",
+        portfolio_id, days, format
+    );
+
+    let mut analysis = factor_service::analyze_portfolio_factors(
+        &state.pool,
+        portfolio_id,
+        state.price_provider.as_ref(),
+        &state.failure_cache,
+        &state.rate_limiter,
+        state.risk_free_rate,
+        days,
+        false,
+        false,
+    )
+    .await
+    .map_err(|e| {
+        error!(
+            "Factor analysis export failed for portfolio {}: {:?}",
+            portfolio_id, e
+        );
+        e
+    })?;
+    let locale = user_preferences_queries::get_locale(&state.pool, user_id).await?;
+    factor_service::localize(&mut analysis, locale);
+
+    let mut scores = ExportTable::new("Holding Factor Scores", &[
+        "Ticker",
+        "Holding Name",
+        "Weight %",
+        "Value",
+        "Growth",
+        "Momentum",
+        "Quality",
+        "Low Volatility",
+        "Composite",
+    ]);
+    for holding in &analysis.holdings_scores {
+        scores.push_row(vec![
+            ExportCell::text(holding.ticker.clone()),
+            ExportCell::text(holding.holding_name.clone().unwrap_or_else(|| "—".to_string())),
+            ExportCell::num(holding.weight * 100.0, 2),
+            ExportCell::num(holding.value_score, 1),
+            ExportCell::num(holding.growth_score, 1),
+            ExportCell::num(holding.momentum_score, 1),
+            ExportCell::num(holding.quality_score, 1),
+            ExportCell::num(holding.low_volatility_score, 1),
+            ExportCell::num(holding.composite_score, 1),
+        ]);
+    }
+
+    let mut exposures = ExportTable::new("Factor Exposures", &[
+        "Factor",
+        "Exposure Score",
+        "Exposure Level",
+        "Expected Risk Premium %",
+        "Recommendation",
+    ]);
+    for exposure in &analysis.factor_exposures {
+        exposures.push_row(vec![
+            ExportCell::text(exposure.label.clone()),
+            ExportCell::num(exposure.score, 1),
+            ExportCell::text(format!("{:?}", exposure.exposure_level).to_uppercase()),
+            ExportCell::num(exposure.expected_risk_premium, 2),
+            ExportCell::text(exposure.recommendation.clone()),
+        ]);
+    }
+
+    info!(
+        "Successfully exported factor analysis for {} holdings",
+        analysis.holdings_scores.len()
+    );
+
+    let filename = portfolio_export_name("portfolio_factors", &portfolio.name, portfolio_id);
+    export_response(&ExportDocument::new(vec![scores, exposures]), format, &filename)
+}
+
 /// GET /api/recommendations/long-term/:portfolio_id
 ///
 /// Generate long-term investment guidance for a portfolio, including:
@@ -273,8 +452,7 @@ pub async fn get_long_term_guidance(
 
     // Check cache first (unless refresh requested)
     if !query.refresh {
-        match crate::db::long_term_guidance_queries::get_cached_guidance(
-            &state.pool,
+        match state.repos.cache.guidance(
             portfolio_id,
             &goal.to_string(),
             horizon_years,
@@ -294,7 +472,7 @@ pub async fn get_long_term_guidance(
     }
 
     // Generate fresh guidance
-    let service = LongTermGuidanceService::new(state.pool.clone(), state.risk_free_rate);
+    let service = LongTermGuidanceService::new(state.repos.clone(), state.risk_free_rate);
 
     let response = service
         .generate_guidance(
@@ -311,8 +489,7 @@ pub async fn get_long_term_guidance(
         })?;
 
     // Cache the result
-    if let Err(e) = crate::db::long_term_guidance_queries::cache_guidance(
-        &state.pool,
+    if let Err(e) = state.repos.cache.store_guidance(
         portfolio_id,
         &goal.to_string(),
         horizon_years,
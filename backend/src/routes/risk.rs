@@ -1,24 +1,35 @@
+use bigdecimal::ToPrimitive;
 use axum::extract::{Path, Query, State};
 use axum::{Json, Router};
 use axum::routing::{get, post};
 use axum::response::Response;
-use axum::http::{header, StatusCode};
+use axum::http::HeaderMap;
 use serde::Deserialize;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 use sqlx::PgPool;
 use chrono::{Utc, Duration};
 
-use crate::db::portfolio_queries;
+use crate::db::{portfolio_queries, user_preferences_queries};
+use crate::db::risk_cache_queries::{self, RiskCacheEntry};
+use crate::db::tenant::TenantScope;
+use crate::repositories::CacheRepo;
 use crate::errors::AppError;
 use crate::middleware::auth::AuthUser;
-use crate::models::{RiskAssessment, CorrelationMatrix, CorrelationPair, RiskSnapshot, RiskAlert, RiskHistoryParams, AlertQueryParams, PortfolioNarrative, GenerateNarrativeRequest};
-use crate::models::risk::{RiskThresholdSettings, UpdateRiskThresholds, PortfolioRiskWithViolations, ThresholdViolation, ViolationSeverity};
-use crate::services::{risk_service, risk_snapshot_service, narrative_service};
+use crate::middleware::permissions::{CanEdit, CanView, PortfolioAccess};
+use crate::models::{AuditAction, NewAuditEntry, RiskAssessment, CorrelationMatrix, CorrelationPair, RiskSnapshot, RiskAlert, RiskHistoryParams, AlertQueryParams, AnomalyQueryParams, PortfolioNarrative, GenerateNarrativeRequest, PeerStatistics, TickerType};
+use crate::models::risk::{RiskScoringModel, RiskThresholdSettings, UpdateRiskThresholds, PortfolioRiskWithViolations, ThresholdViolation, ViolationSeverity};
+use crate::services::{audit_service, clock, timezone, portfolio_return_service, risk_service, risk_snapshot_service, narrative_service, macro_shock_service, risk_budget_service, peer_statistics_service};
+use crate::services::fx_service::PortfolioFx;
+use crate::services::export_service::{
+    export_response, portfolio_export_name, ExportCell, ExportDocument, ExportFormat, ExportQuery, ExportTable,
+};
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
+        .route("/scoring-models", get(get_scoring_models))
+        .route("/peer-statistics", get(get_peer_statistics))
         .route("/positions/:ticker", get(get_position_risk))
         .route("/positions/:ticker/rolling-beta", get(get_rolling_beta))
         .route("/positions/:ticker/beta-forecast", get(get_beta_forecast))
@@ -29,10 +40,19 @@ pub fn router() -> Router<AppState> {
         .route("/portfolios/:portfolio_id/snapshot", post(create_portfolio_snapshot))
         .route("/portfolios/:portfolio_id/history", get(get_risk_history))
         .route("/portfolios/:portfolio_id/alerts", get(get_risk_alerts))
+        .route("/portfolios/:portfolio_id/anomalies", get(get_risk_anomalies))
         .route("/portfolios/:portfolio_id/thresholds", get(get_thresholds))
         .route("/portfolios/:portfolio_id/thresholds", post(set_thresholds))
         .route("/portfolios/:portfolio_id/narrative", get(get_portfolio_narrative))
-        .route("/portfolios/:portfolio_id/export/csv", get(export_portfolio_risk_csv))
+        .route("/portfolios/:portfolio_id/macro-shock", post(run_macro_shock))
+        .route(
+            "/portfolios/:portfolio_id/budget",
+            get(get_risk_budget).put(set_risk_budget).delete(delete_risk_budget),
+        )
+        .route("/portfolios/:portfolio_id/export", get(export_portfolio_risk))
+        .route("/portfolios/:portfolio_id/export/csv", get(export_portfolio_risk))
+        .route("/portfolios/:portfolio_id/correlations/export", get(export_correlations))
+        .route("/portfolios/:portfolio_id/correlations/export/csv", get(export_correlations))
         .route("/portfolios/:portfolio_id/cache-status", get(crate::routes::admin::get_portfolio_cache_status))
         .route("/portfolios/:portfolio_id/invalidate-cache", post(crate::routes::admin::invalidate_cache))
 }
@@ -66,26 +86,17 @@ fn default_benchmark() -> String {
 /// DEPRECATED: Use `get_cached_portfolio_risk_with_status` instead for status-aware caching
 #[allow(dead_code)]
 async fn get_cached_portfolio_risk(
-    pool: &PgPool,
-    portfolio_id: Uuid,
+    cache: &dyn CacheRepo,
+    tenant: &TenantScope,
     days: i64,
     benchmark: &str,
 ) -> Result<Option<PortfolioRiskWithViolations>, AppError> {
-    let result = sqlx::query_scalar::<_, serde_json::Value>(
-        r#"
-        SELECT risk_data
-        FROM portfolio_risk_cache
-        WHERE portfolio_id = $1 AND days = $2 AND benchmark = $3 AND expires_at > NOW()
-        "#
-    )
-    .bind(portfolio_id)
-    .bind(days as i32)
-    .bind(benchmark)
-    .fetch_optional(pool)
-    .await
-    .map_err(AppError::Db)?;
+    let portfolio_id = tenant.portfolio_id();
+    let result = cache.risk(tenant, days as i32, benchmark)
+        .await?
+        .filter(|entry| entry.expires_at > Utc::now());
 
-    if let Some(risk_data) = result {
+    if let Some(RiskCacheEntry { risk_data, .. }) = result {
         info!("Found cached risk data for portfolio {} ({}d, {})", portfolio_id, days, benchmark);
         let risk_result: PortfolioRiskWithViolations = serde_json::from_value(risk_data)
             .map_err(|e| AppError::External(format!("Failed to deserialize cached risk: {}", e)))?;
@@ -98,8 +109,8 @@ async fn get_cached_portfolio_risk(
 
 /// Store portfolio risk data in cache with 4-hour expiration
 async fn cache_portfolio_risk(
-    pool: &PgPool,
-    portfolio_id: Uuid,
+    cache: &dyn CacheRepo,
+    tenant: &TenantScope,
     days: i64,
     benchmark: &str,
     risk_data: &PortfolioRiskWithViolations,
@@ -107,54 +118,121 @@ async fn cache_portfolio_risk(
     let risk_json = serde_json::to_value(risk_data)
         .map_err(|e| AppError::External(format!("Failed to serialize risk for cache: {}", e)))?;
 
-    let calculated_at = Utc::now();
-    let expires_at = calculated_at + Duration::hours(4);
-
-    sqlx::query(
-        r#"
-        INSERT INTO portfolio_risk_cache (portfolio_id, days, benchmark, risk_data, calculated_at, expires_at)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        ON CONFLICT (portfolio_id, days, benchmark)
-        DO UPDATE SET
-            risk_data = $4,
-            calculated_at = $5,
-            expires_at = $6,
-            updated_at = NOW()
-        "#
+    let expires_at = Utc::now() + Duration::hours(4);
+
+    cache.store_risk(
+        tenant,
+        days as i32,
+        benchmark,
+        &risk_json,
+        expires_at,
+        risk_data.portfolio_risk.scoring_version,
     )
-    .bind(portfolio_id)
-    .bind(days as i32)
-    .bind(benchmark)
-    .bind(risk_json)
-    .bind(calculated_at)
-    .bind(expires_at)
-    .execute(pool)
-    .await
-    .map_err(AppError::Db)?;
+    .await?;
 
-    info!("Cached risk data for portfolio {} (expires at {})", portfolio_id, expires_at);
+    info!("Cached risk data for portfolio {} (expires at {})", tenant.portfolio_id(), expires_at);
     Ok(())
 }
 
+/// POST /api/risk/portfolios/:portfolio_id/macro-shock
+///
+/// Estimate per-position and portfolio P&L under macro shocks, e.g.
+/// `{"scenario": "rates +100bps, oil +20%, USD +5%"}` or
+/// `{"shocks": [{"factor": "oil", "shock": 20}]}`. Sensitivities come from a regression
+/// of each position's daily returns on factor proxy ETFs over `days` (default: 252).
+pub async fn run_macro_shock(
+    _access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(request): Json<crate::models::MacroShockRequest>,
+) -> Result<Json<crate::models::MacroShockResult>, AppError> {
+    info!("POST /api/risk/portfolios/{}/macro-shock - Running macro shock scenario", portfolio_id);
+
+    let result = macro_shock_service::run_scenario(&state.pool, portfolio_id, &request)
+        .await
+        .map_err(|e| {
+            error!("Failed to run macro shock scenario for portfolio {}: {}", portfolio_id, e);
+            e
+        })?;
+    Ok(Json(result))
+}
+
+/// GET /api/risk/portfolios/:portfolio_id/budget
+///
+/// Retrieve the portfolio's risk budget and per-sleeve sub-budgets.
+pub async fn get_risk_budget(
+    AuthUser(user_id): AuthUser,
+    Path(portfolio_id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<Json<crate::models::RiskBudget>, AppError> {
+    info!("GET /api/risk/portfolios/{}/budget - Retrieving risk budget", portfolio_id);
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await.map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+
+    Ok(Json(risk_budget_service::get_budget(&state.pool, portfolio_id).await?))
+}
+
+/// PUT /api/risk/portfolios/:portfolio_id/budget
+///
+/// Set a total volatility or VaR budget with optional sleeve sub-budgets, e.g.
+/// `{"metric": "volatility", "total_budget": 18, "sleeves": [{"tag": "growth", "budget": 10}]}`.
+/// Sleeves are position annotation tags. Utilization is reported on the portfolio risk
+/// endpoint and the rebalancer keeps suggested trades within budget.
+pub async fn set_risk_budget(
+    access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+    State(state): State<AppState>,
+    Json(request): Json<crate::models::SetRiskBudget>,
+) -> Result<Json<crate::models::RiskBudget>, AppError> {
+    info!("PUT /api/risk/portfolios/{}/budget - Setting risk budget", portfolio_id);
+
+    let previous = crate::db::risk_budget_queries::fetch(&state.pool, portfolio_id).await?;
+    let budget = risk_budget_service::set_budget(&state.pool, portfolio_id, request)
+        .await
+        .map_err(|e| {
+            error!("Failed to set risk budget for portfolio {}: {}", portfolio_id, e);
+            e
+        })?;
+
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::saved(access.user_id, "risk_budget", portfolio_id, previous.as_ref())
+            .portfolio(portfolio_id)
+            .after(&budget),
+    )
+    .await;
+    Ok(Json(budget))
+}
+
+/// DELETE /api/risk/portfolios/:portfolio_id/budget
+pub async fn delete_risk_budget(
+    access: PortfolioAccess<CanEdit>,
+    Path(portfolio_id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<axum::http::StatusCode, AppError> {
+    info!("DELETE /api/risk/portfolios/{}/budget - Removing risk budget", portfolio_id);
+
+    let previous = crate::db::risk_budget_queries::fetch(&state.pool, portfolio_id).await?;
+    risk_budget_service::delete_budget(&state.pool, portfolio_id).await?;
+
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::deleted(access.user_id, "risk_budget", portfolio_id, previous.as_ref()).portfolio(portfolio_id),
+    )
+    .await;
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
 /// Check if cached narrative exists and is still fresh
 async fn get_cached_narrative(
     pool: &PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     time_period: &str,
     _cache_hours: i32,
 ) -> Result<Option<PortfolioNarrative>, AppError> {
-    let result = sqlx::query_scalar::<_, serde_json::Value>(
-        r#"
-        SELECT narrative_data
-        FROM portfolio_narrative_cache
-        WHERE portfolio_id = $1 AND time_period = $2 AND expires_at > NOW()
-        "#
-    )
-    .bind(portfolio_id)
-    .bind(time_period)
-    .fetch_optional(pool)
-    .await
-    .map_err(AppError::Db)?;
+    let portfolio_id = tenant.portfolio_id();
+    let result = risk_cache_queries::fetch_narrative_cache(pool, tenant, time_period).await?;
 
     if let Some(narrative_data) = result {
         info!("Found cached narrative for portfolio {} ({})", portfolio_id, time_period);
@@ -170,7 +248,7 @@ async fn get_cached_narrative(
 /// Store portfolio narrative in cache with configurable expiration
 async fn cache_narrative(
     pool: &PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     time_period: &str,
     narrative: &PortfolioNarrative,
     cache_hours: i32,
@@ -181,31 +259,35 @@ async fn cache_narrative(
     let generated_at = Utc::now();
     let expires_at = generated_at + Duration::hours(cache_hours as i64);
 
-    sqlx::query(
-        r#"
-        INSERT INTO portfolio_narrative_cache (portfolio_id, time_period, narrative_data, generated_at, expires_at)
-        VALUES ($1, $2, $3, $4, $5)
-        ON CONFLICT (portfolio_id, time_period)
-        DO UPDATE SET
-            narrative_data = $3,
-            generated_at = $4,
-            expires_at = $5,
-            updated_at = NOW()
-        "#
-    )
-    .bind(portfolio_id)
-    .bind(time_period)
-    .bind(narrative_json)
-    .bind(generated_at)
-    .bind(expires_at)
-    .execute(pool)
-    .await
-    .map_err(AppError::Db)?;
+    risk_cache_queries::store_narrative_cache(pool, tenant, time_period, &narrative_json, generated_at, expires_at).await?;
 
-    info!("Cached narrative for portfolio {} (expires at {})", portfolio_id, expires_at);
+    info!("Cached narrative for portfolio {} (expires at {})", tenant.portfolio_id(), expires_at);
     Ok(())
 }
 
+/// GET /api/risk/scoring-models
+///
+/// Weights of every risk score methodology version. Snapshots and cached risk
+/// results record the `scoring_version` they were computed with, so history charts
+/// can annotate where the methodology changed.
+pub async fn get_scoring_models() -> Json<Vec<RiskScoringModel>> {
+    Json(risk_service::scoring_models())
+}
+
+/// GET /api/risk/peer-statistics
+///
+/// Anonymous aggregate statistics across portfolios of users who opted in with
+/// `share_anonymous_stats`: median and interquartile range of each portfolio risk
+/// metric, and the most commonly held tickers. Only available to opted-in users.
+pub async fn get_peer_statistics(
+    AuthUser(user_id): AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<PeerStatistics>, AppError> {
+    info!("GET /api/risk/peer-statistics - Fetching anonymous aggregate statistics");
+    let statistics = peer_statistics_service::get_peer_statistics(&state.pool, user_id).await?;
+    Ok(Json(statistics))
+}
+
 /// GET /api/risk/positions/:ticker
 ///
 /// Calculate and return risk metrics for a specific ticker.
@@ -223,7 +305,7 @@ pub async fn get_position_risk(
 ) -> Result<Json<RiskAssessment>, AppError> {
     // Check failure cache first - return 404 immediately for known-bad tickers
     if state.failure_cache.is_failed(&ticker).is_some() {
-        info!("⚠️  Ticker {} in failure cache, returning 404 without computation", ticker);
+        info!("Ticker {} in failure cache, returning 404 without computation", ticker);
         return Err(AppError::NotFound(format!(
             "Ticker {} is not available. It may be an invalid ticker, mutual fund code, or unsupported security type.",
             ticker
@@ -264,19 +346,19 @@ pub async fn get_position_risk(
         // Log with detailed context for debugging
         match &e {
             AppError::External(msg) if msg.contains("failure cache") => {
-                info!("⚠️  Ticker {} in failure cache: {}", ticker, msg);
+                info!("Ticker {} in failure cache: {}", ticker, msg);
             }
             AppError::External(msg) if msg.contains("No price data") => {
-                warn!("📊 No price data available for {}: {}", ticker, msg);
+                warn!("No price data available for {}: {}", ticker, msg);
             }
             AppError::NotFound(msg) => {
-                info!("🔍 No cached data for {}: {}", ticker, msg);
+                info!("No cached data for {}: {}", ticker, msg);
             }
             AppError::RateLimited => {
-                warn!("⏳ Rate limited when fetching {}", ticker);
+                warn!("Rate limited when fetching {}", ticker);
             }
             _ => {
-                error!("❌ Failed to get risk metrics for {}: {:?}", ticker, e);
+                error!("Failed to get risk metrics for {}: {:?}", ticker, e);
             }
         }
         e
@@ -385,52 +467,25 @@ async fn get_cached_rolling_beta(
     benchmark: &str,
     days: i64,
 ) -> Result<Option<(crate::models::risk::RollingBetaAnalysis, chrono::DateTime<Utc>, chrono::DateTime<Utc>)>, AppError> {
-    let result = sqlx::query!(
-        r#"
-        SELECT
-            beta_30d, beta_60d, beta_90d,
-            current_beta, beta_volatility,
-            calculated_at, expires_at
-        FROM rolling_beta_cache
-        WHERE ticker = $1 AND benchmark = $2 AND total_days = $3
-        "#,
-        ticker,
-        benchmark,
-        days as i32
-    )
-    .fetch_optional(pool)
-    .await?;
-
-    match result {
-        Some(row) => {
-            use crate::models::risk::{RollingBetaAnalysis, BetaPoint};
-
-            // Deserialize JSONB arrays
-            let beta_30d: Vec<BetaPoint> = serde_json::from_value(row.beta_30d)
-                .map_err(|e| AppError::External(format!("Failed to parse beta_30d: {}", e)))?;
-            let beta_60d: Vec<BetaPoint> = serde_json::from_value(row.beta_60d)
-                .map_err(|e| AppError::External(format!("Failed to parse beta_60d: {}", e)))?;
-            let beta_90d: Vec<BetaPoint> = serde_json::from_value(row.beta_90d)
-                .map_err(|e| AppError::External(format!("Failed to parse beta_90d: {}", e)))?;
-
-            let analysis = RollingBetaAnalysis {
-                ticker: ticker.to_string(),
-                benchmark: benchmark.to_string(),
-                beta_30d,
-                beta_60d,
-                beta_90d,
-                current_beta: row.current_beta,
-                beta_volatility: row.beta_volatility,
-            };
+    let result = risk_cache_queries::fetch_rolling_beta_cache(pool, ticker, benchmark, days as i32).await?;
+
+    Ok(result.map(|row| {
+        let analysis = crate::models::risk::RollingBetaAnalysis {
+            ticker: ticker.to_string(),
+            benchmark: benchmark.to_string(),
+            beta_30d: row.beta_30d.0,
+            beta_60d: row.beta_60d.0,
+            beta_90d: row.beta_90d.0,
+            current_beta: row.current_beta,
+            beta_volatility: row.beta_volatility,
+        };
 
-            // Convert NaiveDateTime to DateTime<Utc>
-            let calculated_at_utc = chrono::DateTime::<Utc>::from_naive_utc_and_offset(row.calculated_at, Utc);
-            let expires_at_utc = chrono::DateTime::<Utc>::from_naive_utc_and_offset(row.expires_at, Utc);
+        // Convert NaiveDateTime to DateTime<Utc>
+        let calculated_at_utc = chrono::DateTime::<Utc>::from_naive_utc_and_offset(row.calculated_at, Utc);
+        let expires_at_utc = chrono::DateTime::<Utc>::from_naive_utc_and_offset(row.expires_at, Utc);
 
-            Ok(Some((analysis, calculated_at_utc, expires_at_utc)))
-        }
-        None => Ok(None),
-    }
+        (analysis, calculated_at_utc, expires_at_utc)
+    }))
 }
 
 /// Query parameters for beta forecast
@@ -590,22 +645,23 @@ pub async fn get_volatility_forecast(
 ///
 /// Example: GET /api/risk/portfolios/{uuid}/downside?days=90&benchmark=SPY
 pub async fn get_portfolio_downside_risk(
-    AuthUser(user_id): AuthUser,
+    _access: PortfolioAccess<CanView>,
     Path(portfolio_id): Path<Uuid>,
     Query(params): Query<RiskQueryParams>,
     State(state): State<AppState>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
-        .await.map_err(AppError::Db)?
+    // Cache rows belong to the owner, also when a member is viewing
+    let tenant = TenantScope::for_job(&state.pool, portfolio_id)
+        .await?
         .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
     info!(
-        "🌐 [ENDPOINT] GET /api/risk/portfolios/{}/downside - days={}, benchmark={}, force={}",
+        "[ENDPOINT] GET /api/risk/portfolios/{}/downside - days={}, benchmark={}, force={}",
         portfolio_id, params.days, params.benchmark, params.force
     );
 
     // If force refresh requested, compute directly
     if params.force {
-        info!("🔄 [ENDPOINT] Force refresh requested for portfolio {}", portfolio_id);
+        info!("[ENDPOINT] Force refresh requested for portfolio {}", portfolio_id);
         match risk_service::compute_portfolio_downside_risk(
             &state.pool,
             portfolio_id,
@@ -619,7 +675,7 @@ pub async fn get_portfolio_downside_risk(
         .await
         {
             Ok(risk) => {
-                info!("✅ [ENDPOINT] Force computation succeeded for portfolio {}", portfolio_id);
+                info!("[ENDPOINT] Force computation succeeded for portfolio {}", portfolio_id);
                 return Ok(Json(serde_json::json!({
                     "data": risk,
                     "cache_status": {
@@ -630,15 +686,15 @@ pub async fn get_portfolio_downside_risk(
                 })));
             }
             Err(e) => {
-                warn!("❌ [ENDPOINT] Force computation failed: {}, trying cache", e);
+                warn!("[ENDPOINT] Force computation failed: {}, trying cache", e);
                 // Fall through to cache check
             }
         }
     }
 
     // Try to get from cache
-    info!("🔍 [ENDPOINT] Looking for cached downside risk for portfolio {}", portfolio_id);
-    let cached = get_cached_downside_risk(&state.pool, portfolio_id, params.days, &params.benchmark).await?;
+    info!("[ENDPOINT] Looking for cached downside risk for portfolio {}", portfolio_id);
+    let cached = get_cached_downside_risk(&state.pool, &tenant, params.days, &params.benchmark).await?;
 
     match cached {
         Some((risk_data, calculated_at_utc, expires_at_utc)) => {
@@ -647,7 +703,7 @@ pub async fn get_portfolio_downside_risk(
             let is_stale = expires_at_utc < Utc::now();
 
             info!(
-                "✅ [ENDPOINT] Serving downside risk for portfolio {} from cache (age: {} hours, stale: {})",
+                "[ENDPOINT] Serving downside risk for portfolio {} from cache (age: {} hours, stale: {})",
                 portfolio_id, age_hours, is_stale
             );
 
@@ -673,8 +729,8 @@ pub async fn get_portfolio_downside_risk(
             })))
         }
         None => {
-            warn!("⚠️ [ENDPOINT] No cached downside risk found for portfolio {}", portfolio_id);
-            warn!("💡 [ENDPOINT] User should trigger populate_downside_risk_cache job in Admin panel");
+            warn!("[ENDPOINT] No cached downside risk found for portfolio {}", portfolio_id);
+            warn!("[ENDPOINT] User should trigger populate_downside_risk_cache job in Admin panel");
             Err(AppError::NotFound(format!(
                 "Downside risk data not available for this portfolio. The background job will calculate it within 6 hours, or you can request immediate calculation using force=true. Note: Forced calculation may take 30-60 seconds."
             )))
@@ -685,38 +741,19 @@ pub async fn get_portfolio_downside_risk(
 /// Get cached downside risk from database
 async fn get_cached_downside_risk(
     pool: &PgPool,
-    portfolio_id: Uuid,
+    tenant: &TenantScope,
     days: i64,
     benchmark: &str,
 ) -> Result<Option<(crate::models::risk::PortfolioDownsideRisk, chrono::DateTime<Utc>, chrono::DateTime<Utc>)>, AppError> {
-    let result = sqlx::query!(
-        r#"
-        SELECT
-            risk_data, calculated_at, expires_at
-        FROM downside_risk_cache
-        WHERE portfolio_id = $1 AND days = $2 AND benchmark = $3
-        "#,
-        portfolio_id,
-        days as i32,
-        benchmark
-    )
-    .fetch_optional(pool)
-    .await?;
-
-    match result {
-        Some(row) => {
-            // Deserialize JSONB
-            let risk_data: crate::models::risk::PortfolioDownsideRisk = serde_json::from_value(row.risk_data)
-                .map_err(|e| AppError::External(format!("Failed to parse cached risk data: {}", e)))?;
+    let result = risk_cache_queries::fetch_downside_risk_cache(pool, tenant, days as i32, benchmark).await?;
 
-            // Convert NaiveDateTime to DateTime<Utc>
-            let calculated_at_utc = chrono::DateTime::<Utc>::from_naive_utc_and_offset(row.calculated_at, Utc);
-            let expires_at_utc = chrono::DateTime::<Utc>::from_naive_utc_and_offset(row.expires_at, Utc);
+    Ok(result.map(|row| {
+        // Convert NaiveDateTime to DateTime<Utc>
+        let calculated_at_utc = chrono::DateTime::<Utc>::from_naive_utc_and_offset(row.calculated_at, Utc);
+        let expires_at_utc = chrono::DateTime::<Utc>::from_naive_utc_and_offset(row.expires_at, Utc);
 
-            Ok(Some((risk_data, calculated_at_utc, expires_at_utc)))
-        }
-        None => Ok(None),
-    }
+        (row.risk_data.0, calculated_at_utc, expires_at_utc)
+    }))
 }
 
 /// Represents the state of cached portfolio risk data
@@ -744,38 +781,23 @@ enum CacheResult {
 /// - `Some(CacheResult::Error)` if status='error'
 /// - `None` if no cache entry exists
 async fn get_cached_portfolio_risk_with_status(
-    pool: &PgPool,
-    portfolio_id: Uuid,
+    cache: &dyn CacheRepo,
+    tenant: &TenantScope,
     days: i64,
     benchmark: &str,
 ) -> Result<Option<CacheResult>, AppError> {
-    // Use raw query to handle case where status columns might not exist yet
-    let result = sqlx::query_as::<_, (serde_json::Value, Option<String>, chrono::DateTime<Utc>, Option<String>)>(
-        r#"
-        SELECT risk_data,
-               COALESCE(calculation_status, 'stale') as calculation_status,
-               expires_at,
-               last_error
-        FROM portfolio_risk_cache
-        WHERE portfolio_id = $1 AND days = $2 AND benchmark = $3
-        "#
-    )
-    .bind(portfolio_id)
-    .bind(days as i32)
-    .bind(benchmark)
-    .fetch_optional(pool)
-    .await
-    .map_err(AppError::Db)?;
+    let portfolio_id = tenant.portfolio_id();
+    let result = cache.risk(tenant, days as i32, benchmark).await?;
 
     match result {
         None => {
             info!("No cache entry found for portfolio {} ({}d, {})", portfolio_id, days, benchmark);
             Ok(None)
         }
-        Some((risk_data_json, calculation_status_opt, expires_at, last_error)) => {
+        Some(RiskCacheEntry { risk_data: risk_data_json, calculation_status, expires_at, last_error, scoring_version }) => {
             let now = Utc::now();
-            let is_expired = expires_at <= now;
-            let calculation_status = calculation_status_opt.unwrap_or_else(|| "stale".to_string());
+            // Scores from an older methodology are served as stale until recalculated
+            let is_expired = expires_at <= now || scoring_version != risk_service::CURRENT_SCORING_VERSION;
 
             match calculation_status.as_str() {
                 "fresh" => {
@@ -829,13 +851,14 @@ async fn get_cached_portfolio_risk_with_status(
 ///
 /// Example: GET /api/risk/portfolios/{uuid}?days=60
 pub async fn get_portfolio_risk(
-    AuthUser(user_id): AuthUser,
+    access: PortfolioAccess<CanView>,
     Path(portfolio_id): Path<Uuid>,
     Query(params): Query<RiskQueryParams>,
     State(state): State<AppState>,
 ) -> Result<Json<PortfolioRiskWithViolations>, AppError> {
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
-        .await.map_err(AppError::Db)?
+    // Cache rows belong to the owner, also when a member is viewing
+    let tenant = TenantScope::for_job(&state.pool, portfolio_id)
+        .await?
         .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
     use crate::db::holding_snapshot_queries;
     use crate::models::PositionRiskContribution;
@@ -851,19 +874,19 @@ pub async fn get_portfolio_risk(
     // This significantly reduces API response time and prevents duplicate calculations
     if !params.force {
         // Query the cache with status information
-        match get_cached_portfolio_risk_with_status(&state.pool, portfolio_id, params.days, &params.benchmark).await? {
+        match get_cached_portfolio_risk_with_status(state.repos.cache.as_ref(), &tenant, params.days, &params.benchmark).await? {
             Some(CacheResult::Fresh(data)) => {
-                info!("✓ Returning fresh cached risk data for portfolio {}", portfolio_id);
-                return Ok(Json(data));
+                info!("Returning fresh cached risk data for portfolio {}", portfolio_id);
+                return Ok(Json(with_live_sections(&state.pool, access.user_id, portfolio_id, data).await));
             }
             Some(CacheResult::Stale(data)) => {
                 // Return stale data but log a warning
                 // Background job will refresh this automatically
                 warn!(
-                    "⚠ Returning stale cache data for portfolio {} ({}d, {}). Background job will refresh soon.",
+                    "Returning stale cache data for portfolio {} ({}d, {}). Background job will refresh soon.",
                     portfolio_id, params.days, params.benchmark
                 );
-                return Ok(Json(data));
+                return Ok(Json(with_live_sections(&state.pool, access.user_id, portfolio_id, data).await));
             }
             Some(CacheResult::Calculating) => {
                 // Calculation is in progress, ask client to retry
@@ -890,7 +913,7 @@ pub async fn get_portfolio_risk(
     // LEGACY BEHAVIOR: force=true triggers synchronous calculation
     // This is preserved for manual refresh and debugging purposes
     // In production, this should rarely be used as it can cause timeouts
-    info!("🔄 Force refresh requested - performing synchronous calculation for portfolio {}", portfolio_id);
+    info!("Force refresh requested - performing synchronous calculation for portfolio {}", portfolio_id);
 
     // 1. Fetch all latest holdings for the portfolio
     let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(
@@ -901,12 +924,15 @@ pub async fn get_portfolio_risk(
         AppError::Db(e)
     })?;
 
+    // Values from accounts in different currencies are summed in the owner's base currency
+    let fx = PortfolioFx::load(&state.pool, state.fx_provider.as_ref(), portfolio_id, &holdings).await?;
+
     // 2. Aggregate holdings by ticker (same ticker across multiple accounts)
     let mut ticker_aggregates: HashMap<String, (f64, f64)> = HashMap::new(); // (quantity, market_value)
 
     for holding in &holdings {
-        let market_value = holding.market_value.to_string().parse::<f64>().unwrap_or(0.0);
-        let quantity = holding.quantity.to_string().parse::<f64>().unwrap_or(0.0);
+        let market_value = fx.market_value(holding);
+        let quantity = holding.quantity.to_f64().unwrap_or(0.0);
 
         ticker_aggregates
             .entry(holding.ticker.clone())
@@ -943,6 +969,15 @@ pub async fn get_portfolio_risk(
     let mut es_95_count = 0;
     let mut es_99_count = 0;
 
+
+    // Refresh every position's closes in one batch up front
+    let tickers: Vec<String> = ticker_aggregates
+        .iter()
+        .filter(|(_, (_, market_value))| market_value / total_value >= 0.001)
+        .map(|(ticker, _)| ticker.clone())
+        .collect();
+    risk_service::prefetch_prices(&state.pool, &tickers, &params.benchmark, state.price_provider.as_ref(), &state.failure_cache, &state.rate_limiter).await;
+
     for (ticker, (_quantity, market_value)) in ticker_aggregates {
         // Skip positions with negligible value (< 0.1% of portfolio)
         let weight = market_value / total_value;
@@ -1016,17 +1051,29 @@ pub async fn get_portfolio_risk(
         ));
     }
 
+    // Volatility, drawdown, Sharpe and VaR come from the portfolio's own daily
+    // returns; averaging the position metrics ignores diversification and cash.
+    // The weighted averages are kept as a fallback for too little history.
+    let returns = portfolio_return_service::compute_metrics(&state.pool, portfolio_id, params.days, &params.benchmark, state.risk_free_rate)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Could not build the return series for portfolio {}: {}", portfolio_id, e);
+            Default::default()
+        });
+    let portfolio_volatility = returns.volatility.unwrap_or(weighted_volatility);
+    let portfolio_max_drawdown = returns.max_drawdown.unwrap_or(weighted_max_drawdown);
+
     // 4. Calculate portfolio-level risk score
     let portfolio_risk_score = risk_service::score_risk(&crate::models::PositionRisk {
-        volatility: weighted_volatility,
-        max_drawdown: weighted_max_drawdown,
+        volatility: portfolio_volatility,
+        max_drawdown: portfolio_max_drawdown,
         beta: if beta_count > 0 { Some(weighted_beta) } else { None },
         beta_spy: if beta_count > 0 { Some(weighted_beta) } else { None },
         beta_qqq: None,
         beta_iwm: None,
         risk_decomposition: None,
-        sharpe: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
-        sortino: None,
+        sharpe: returns.sharpe,
+        sortino: returns.sortino,
         annualized_return: None,
         value_at_risk: None, // VaR not meaningful at portfolio level without correlations
         var_95: None,
@@ -1045,16 +1092,22 @@ pub async fn get_portfolio_risk(
     let portfolio_risk = crate::models::PortfolioRisk {
         portfolio_id: portfolio_id.to_string(),
         total_value,
-        portfolio_volatility: weighted_volatility,
-        portfolio_max_drawdown: weighted_max_drawdown,
+        portfolio_volatility,
+        portfolio_max_drawdown,
         portfolio_beta: if beta_count > 0 { Some(weighted_beta) } else { None },
-        portfolio_sharpe: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
-        portfolio_var_95: if var_95_count > 0 { Some(weighted_var_95) } else { None },
-        portfolio_var_99: if var_99_count > 0 { Some(weighted_var_99) } else { None },
-        portfolio_expected_shortfall_95: if es_95_count > 0 { Some(weighted_es_95) } else { None },
-        portfolio_expected_shortfall_99: if es_99_count > 0 { Some(weighted_es_99) } else { None },
+        portfolio_regression_beta: returns.beta,
+        portfolio_alpha: returns.alpha,
+        portfolio_r_squared: returns.r_squared,
+        portfolio_sharpe: returns.sharpe,
+        portfolio_sortino: returns.sortino,
+        portfolio_sharpe_weighted_average: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
+        portfolio_var_95: returns.var_95.or(if var_95_count > 0 { Some(weighted_var_95) } else { None }),
+        portfolio_var_99: returns.var_99.or(if var_99_count > 0 { Some(weighted_var_99) } else { None }),
+        portfolio_expected_shortfall_95: returns.expected_shortfall_95.or(if es_95_count > 0 { Some(weighted_es_95) } else { None }),
+        portfolio_expected_shortfall_99: returns.expected_shortfall_99.or(if es_99_count > 0 { Some(weighted_es_99) } else { None }),
         portfolio_risk_score,
         risk_level,
+        scoring_version: risk_service::CURRENT_SCORING_VERSION,
         position_risks: position_risks.clone(),
     };
 
@@ -1091,15 +1144,37 @@ pub async fn get_portfolio_risk(
         portfolio_risk,
         thresholds,
         violations,
+        budget_utilization: None,
+        peer_context: None,
     };
 
     // Cache the results for future requests
-    if let Err(e) = cache_portfolio_risk(&state.pool, portfolio_id, params.days, &params.benchmark, &risk_with_violations).await {
+    if let Err(e) = cache_portfolio_risk(state.repos.cache.as_ref(), &tenant, params.days, &params.benchmark, &risk_with_violations).await {
         error!("Failed to cache risk data for portfolio {}: {}", portfolio_id, e);
         // Continue even if caching fails - don't fail the request
     }
 
-    Ok(Json(risk_with_violations))
+    Ok(Json(with_live_sections(&state.pool, access.user_id, portfolio_id, risk_with_violations).await))
+}
+
+/// Attach live risk budget utilization and peer percentiles. Neither is cached with
+/// the risk data, so budget edits and opt-in changes show up immediately; failures
+/// only drop the affected section.
+async fn with_live_sections(
+    pool: &PgPool,
+    user_id: Uuid,
+    portfolio_id: Uuid,
+    mut data: PortfolioRiskWithViolations,
+) -> PortfolioRiskWithViolations {
+    match risk_budget_service::utilization_for_risk(pool, portfolio_id, &data.portfolio_risk).await {
+        Ok(utilization) => data.budget_utilization = utilization,
+        Err(e) => warn!("Failed to compute risk budget utilization for portfolio {}: {}", portfolio_id, e),
+    }
+    match peer_statistics_service::peer_context_for(pool, user_id, &data.portfolio_risk).await {
+        Ok(context) => data.peer_context = context,
+        Err(e) => warn!("Failed to compute peer context for portfolio {}: {}", portfolio_id, e),
+    }
+    data
 }
 
 /// Detect threshold violations in portfolio risk data
@@ -1258,16 +1333,14 @@ pub async fn get_thresholds(
 ///
 /// Request body: UpdateRiskThresholds with optional fields
 pub async fn set_thresholds(
-    AuthUser(user_id): AuthUser,
+    access: PortfolioAccess<CanEdit>,
     Path(portfolio_id): Path<Uuid>,
     State(state): State<AppState>,
     Json(request): Json<UpdateRiskThresholds>,
 ) -> Result<Json<RiskThresholdSettings>, AppError> {
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
-        .await.map_err(AppError::Db)?
-        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
     info!("POST /api/risk/portfolios/{}/thresholds - Updating risk thresholds", portfolio_id);
 
+    let previous = crate::db::risk_threshold_queries::get_thresholds(&state.pool, portfolio_id).await?;
     let settings = crate::db::risk_threshold_queries::upsert_thresholds(&state.pool, portfolio_id, &request)
         .await
         .map_err(|e| {
@@ -1275,35 +1348,28 @@ pub async fn set_thresholds(
             AppError::Db(e)
         })?;
 
+    audit_service::record(
+        &state.pool,
+        NewAuditEntry::new(access.user_id, AuditAction::Update, "risk_thresholds", portfolio_id)
+            .portfolio(portfolio_id)
+            .before(&previous)
+            .after(&settings),
+    )
+    .await;
     Ok(Json(settings))
 }
 
 /// Get cached correlation matrix if available and fresh
 async fn get_cached_correlations(
-    pool: &PgPool,
-    portfolio_id: Uuid,
+    cache: &dyn CacheRepo,
+    tenant: &TenantScope,
     days: i64,
 ) -> Result<Option<crate::models::risk::CorrelationMatrixWithStats>, AppError> {
-    let result = sqlx::query_scalar::<_, serde_json::Value>(
-        r#"
-        SELECT correlations_data
-        FROM portfolio_correlations_cache
-        WHERE portfolio_id = $1
-          AND days = $2
-          AND calculation_status = 'fresh'
-          AND expires_at > NOW()
-        "#
-    )
-    .bind(portfolio_id)
-    .bind(days as i32)
-    .fetch_optional(pool)
-    .await
-    .map_err(AppError::Db)?;
+    let portfolio_id = tenant.portfolio_id();
+    let result = cache.fresh_correlations(tenant, days as i32).await?;
 
-    if let Some(correlations_data) = result {
+    if let Some(correlation_result) = result {
         info!("Found cached correlation data for portfolio {} ({}d)", portfolio_id, days);
-        let correlation_result: crate::models::risk::CorrelationMatrixWithStats = serde_json::from_value(correlations_data)
-            .map_err(|e| AppError::External(format!("Failed to deserialize cached correlations: {}", e)))?;
         Ok(Some(correlation_result))
     } else {
         info!("No valid correlation cache found for portfolio {} ({}d)", portfolio_id, days);
@@ -1321,13 +1387,14 @@ async fn get_cached_correlations(
 ///
 /// Example: GET /api/risk/portfolios/{uuid}/correlations?days=90
 pub async fn get_portfolio_correlations(
-    AuthUser(user_id): AuthUser,
+    _access: PortfolioAccess<CanView>,
     Path(portfolio_id): Path<Uuid>,
     Query(params): Query<RiskQueryParams>,
     State(state): State<AppState>,
 ) -> Result<Json<crate::models::risk::CorrelationMatrixWithStats>, AppError> {
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
-        .await.map_err(AppError::Db)?
+    // Cache rows belong to the owner, also when a member is viewing
+    let tenant = TenantScope::for_job(&state.pool, portfolio_id)
+        .await?
         .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
     use crate::db::{holding_snapshot_queries, price_queries};
     use std::collections::HashMap;
@@ -1340,7 +1407,7 @@ pub async fn get_portfolio_correlations(
 
     // Check cache first if not forcing refresh
     if !params.force {
-        if let Some(cached_correlations) = get_cached_correlations(&state.pool, portfolio_id, params.days).await? {
+        if let Some(cached_correlations) = get_cached_correlations(state.repos.cache.as_ref(), &tenant, params.days).await? {
             info!("Returning cached correlation data for portfolio {}", portfolio_id);
             return Ok(Json(cached_correlations));
         }
@@ -1382,6 +1449,9 @@ pub async fn get_portfolio_correlations(
         }
     };
 
+    // Values from accounts in different currencies are summed in the owner's base currency
+    let fx = PortfolioFx::load(&state.pool, state.fx_provider.as_ref(), portfolio_id, &holdings).await?;
+
     // 2. Aggregate holdings by ticker and filter out mutual funds and negligible positions
     info!("Step 2: Aggregating holdings by ticker...");
     let mut ticker_aggregates: HashMap<String, f64> = HashMap::new(); // ticker -> market_value
@@ -1389,7 +1459,7 @@ pub async fn get_portfolio_correlations(
     let mut filtered_mutual_funds = Vec::new();
 
     for holding in &holdings {
-        let market_value = holding.market_value.to_string().parse::<f64>().unwrap_or(0.0);
+        let market_value = fx.market_value(holding);
         total_value += market_value;
 
         // Skip mutual funds and other securities that won't have price data
@@ -1405,7 +1475,10 @@ pub async fn get_portfolio_correlations(
             || holding.ticker.starts_with("EDG")
             || holding.ticker.len() > 5; // Most proprietary tickers are longer
 
-        if is_mutual_fund || is_proprietary_ticker {
+        // Crypto tickers (BTC-USD) are long but priced from CoinGecko
+        let is_crypto = holding.ticker_type() == TickerType::Crypto;
+
+        if !is_crypto && (is_mutual_fund || is_proprietary_ticker) {
             filtered_mutual_funds.push(holding.ticker.clone());
             continue;
         }
@@ -1473,7 +1546,15 @@ pub async fn get_portfolio_correlations(
 
     info!("Computing correlations for {} tickers: {:?}", tickers.len(), tickers);
 
-    // 3. Fetch price data for all tickers in one batch query (much faster!)
+    // 3. Refresh stale closes concurrently, then read them all in one batch query (much faster!)
+    crate::services::price_service::refresh_many_from_api(
+        &state.pool,
+        state.price_provider.as_ref(),
+        &tickers,
+        &state.failure_cache,
+        &state.rate_limiter,
+    )
+    .await;
     info!("Step 3: Fetching price data for {} tickers (last {} days)...", tickers.len(), params.days);
     let fetch_start = Instant::now();
     let price_data = match price_queries::fetch_window_batch(&state.pool, &tickers, params.days).await {
@@ -1629,19 +1710,17 @@ pub async fn get_portfolio_correlations(
 ///
 /// Example: POST /api/risk/portfolios/{uuid}/snapshot
 pub async fn create_portfolio_snapshot(
-    AuthUser(user_id): AuthUser,
+    _access: PortfolioAccess<CanEdit>,
     Path(portfolio_id): Path<Uuid>,
     State(state): State<AppState>,
 ) -> Result<Json<Vec<RiskSnapshot>>, AppError> {
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
-        .await.map_err(AppError::Db)?
-        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
     info!(
         "POST /api/risk/portfolios/{}/snapshot - Creating risk snapshots",
         portfolio_id
     );
 
-    let today = chrono::Utc::now().date_naive();
+    let owner_timezone = user_preferences_queries::fetch_portfolio_timezone(&state.pool, portfolio_id).await?;
+    let today = timezone::resolve_or_default(&owner_timezone).local_date(clock::now());
 
     let snapshots = risk_snapshot_service::create_daily_snapshots(
         &state.pool,
@@ -1744,42 +1823,81 @@ pub async fn get_risk_alerts(
     Ok(Json(alerts))
 }
 
-/// GET /api/risk/portfolios/:portfolio_id/export/csv
+/// GET /api/risk/portfolios/:portfolio_id/anomalies
 ///
-/// Export portfolio risk analysis to CSV format
+/// Get statistically unusual jumps in portfolio volatility, beta and average
+/// correlation, judged against each metric's own recent day-over-day changes
+/// rather than a fixed threshold
+///
+/// Query parameters:
+/// - `days`: Lookback period in days (default: 30)
+/// - `z_threshold`: Standard deviations a jump must sit from recent jumps (default: 3.0)
+///
+/// Example: GET /api/risk/portfolios/{uuid}/anomalies?days=60&z_threshold=2.5
+pub async fn get_risk_anomalies(
+    AuthUser(user_id): AuthUser,
+    Path(portfolio_id): Path<Uuid>,
+    Query(params): Query<AnomalyQueryParams>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<RiskAlert>>, AppError> {
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await.map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+    if !params.z_threshold.is_finite() || params.z_threshold <= 0.0 {
+        return Err(AppError::Validation("z_threshold must be positive".to_string()));
+    }
+    info!(
+        "GET /api/risk/portfolios/{}/anomalies - Detecting metric anomalies (days={}, z_threshold={})",
+        portfolio_id, params.days, params.z_threshold
+    );
+
+    let alerts = risk_snapshot_service::detect_metric_anomalies(
+        &state.pool,
+        portfolio_id,
+        params.days,
+        params.z_threshold,
+    )
+    .await?;
+
+    info!(
+        "Found {} metric anomalies for portfolio {}",
+        alerts.len(),
+        portfolio_id
+    );
+
+    Ok(Json(alerts))
+}
+
+/// GET /api/risk/portfolios/:portfolio_id/export
+///
+/// Export portfolio risk analysis (also served at `/export/csv`)
 ///
 /// Query parameters:
 /// - `days`: Rolling window in days (default: 90)
 /// - `benchmark`: Benchmark ticker for beta (default: "SPY")
+/// - `format`: csv, xlsx, or json (default: negotiated from `Accept`, else csv)
 ///
-/// Returns CSV file with portfolio summary and position-level risk metrics
-pub async fn export_portfolio_risk_csv(
+/// Returns a file with position-level risk metrics
+pub async fn export_portfolio_risk(
     AuthUser(user_id): AuthUser,
     Path(portfolio_id): Path<Uuid>,
     Query(params): Query<RiskQueryParams>,
+    Query(export): Query<ExportQuery>,
+    headers: HeaderMap,
     State(state): State<AppState>,
 ) -> Result<Response, AppError> {
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+    let format = ExportFormat::negotiate(export.format.as_deref(), &headers)?;
+    let portfolio = portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
         .await.map_err(AppError::Db)?
         .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
     info!(
-        "GET /api/risk/portfolios/{}/export/csv - Exporting risk data to CSV",
-        portfolio_id
+        "GET /api/risk/portfolios/{}/export - Exporting risk data ({:?})",
+        portfolio_id, format
     );
 
-    // Get portfolio risk data (same as get_portfolio_risk)
-    use crate::db::{holding_snapshot_queries, portfolio_queries};
+    use crate::db::holding_snapshot_queries;
     use std::collections::HashMap;
 
-    // Fetch portfolio name
-    let portfolio = portfolio_queries::fetch_one_unchecked(&state.pool, portfolio_id)
-        .await
-        .map_err(|e| {
-            error!("Failed to fetch portfolio: {}", e);
-            AppError::Db(e)
-        })?
-        .ok_or_else(|| AppError::External("Portfolio not found".to_string()))?;
-
     // Fetch holdings
     let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(
         &state.pool,
@@ -1795,12 +1913,15 @@ pub async fn export_portfolio_risk_csv(
         ));
     }
 
+    // Values from accounts in different currencies are summed in the owner's base currency
+    let fx = PortfolioFx::load(&state.pool, state.fx_provider.as_ref(), portfolio_id, &holdings).await?;
+
     // Aggregate holdings by ticker
     let mut ticker_aggregates: HashMap<String, (f64, Option<String>)> = HashMap::new();
     let mut total_value = 0.0;
 
     for holding in &holdings {
-        let market_value = holding.market_value.to_string().parse::<f64>().unwrap_or(0.0);
+        let market_value = fx.market_value(holding);
         total_value += market_value;
 
         ticker_aggregates
@@ -1809,15 +1930,23 @@ pub async fn export_portfolio_risk_csv(
             .or_insert((market_value, holding.holding_name.clone()));
     }
 
-    // Build CSV
-    let mut csv_writer = csv::Writer::from_writer(vec![]);
+    let tags_by_ticker = crate::db::annotation_queries::fetch_for_portfolio(&state.pool, portfolio_id)
+        .await
+        .map(|a| crate::services::annotation_service::tags_by_ticker(&a))
+        .map_err(|e| {
+            error!("Failed to fetch position annotations: {}", e);
+            AppError::Db(e)
+        })?;
 
-    // Write header
-    csv_writer.write_record(&[
+    let mut table = ExportTable::new("Position Risk", &[
         "Ticker",
         "Holding Name",
         "Market Value",
         "Portfolio Weight %",
+        "52W High",
+        "52W Low",
+        "% Off 52W High",
+        "% Above 52W Low",
         "Volatility %",
         "Max Drawdown %",
         "Beta",
@@ -1829,15 +1958,32 @@ pub async fn export_portfolio_risk_csv(
         "Expected Shortfall 99% %",
         "Risk Score",
         "Risk Level",
-    ]).map_err(|e| {
-        error!("Failed to write CSV header: {}", e);
-        AppError::External(format!("CSV generation error: {}", e))
-    })?;
+        "Tags",
+    ]);
+
+    let tickers: Vec<String> = ticker_aggregates.keys().cloned().collect();
+    let ranges = crate::db::price_queries::fetch_52_week_range_batch(&state.pool, &tickers)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch 52-week ranges: {}", e);
+            AppError::Db(e)
+        })?;
 
     // Process each ticker
     let mut rows_written = 0;
     for (ticker, (market_value, holding_name)) in ticker_aggregates {
         let weight = (market_value / total_value) * 100.0;
+        let range = ranges.get(&ticker);
+        let range_cells = vec![
+            ExportCell::opt_num(range.map(|r| r.high), 2),
+            ExportCell::opt_num(range.map(|r| r.low), 2),
+            ExportCell::opt_num(range.map(|r| r.pct_off_high), 2),
+            ExportCell::opt_num(range.map(|r| r.pct_above_low), 2),
+        ];
+        let name_cell = ExportCell::Text(holding_name.unwrap_or_else(|| "—".to_string()));
+        let tags_cell = ExportCell::Text(
+            tags_by_ticker.get(&ticker).map(|t| t.join("; ")).unwrap_or_default()
+        );
 
         // Compute risk metrics
         match risk_service::compute_risk_metrics(
@@ -1851,80 +1997,104 @@ pub async fn export_portfolio_risk_csv(
             state.risk_free_rate,
         ).await {
             Ok(assessment) => {
-                csv_writer.write_record(&[
-                    ticker,
-                    holding_name.unwrap_or_else(|| "—".to_string()),
-                    format!("{:.2}", market_value),
-                    format!("{:.2}", weight),
-                    format!("{:.2}", assessment.metrics.volatility),
-                    format!("{:.2}", assessment.metrics.max_drawdown),
-                    assessment.metrics.beta.map(|b| format!("{:.2}", b)).unwrap_or_else(|| "—".to_string()),
-                    assessment.metrics.sharpe.map(|s| format!("{:.2}", s)).unwrap_or_else(|| "—".to_string()),
-                    assessment.metrics.value_at_risk.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "—".to_string()),
-                    assessment.metrics.var_95.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "—".to_string()),
-                    assessment.metrics.var_99.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "—".to_string()),
-                    assessment.metrics.expected_shortfall_95.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "—".to_string()),
-                    assessment.metrics.expected_shortfall_99.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "—".to_string()),
-                    format!("{:.2}", assessment.risk_score),
-                    assessment.risk_level.to_string().to_uppercase(),
-                ]).map_err(|e| {
-                    error!("Failed to write CSV row: {}", e);
-                    AppError::External(format!("CSV generation error: {}", e))
-                })?;
+                let m = &assessment.metrics;
+                let mut row = vec![
+                    ExportCell::Text(ticker),
+                    name_cell,
+                    ExportCell::num(market_value, 2),
+                    ExportCell::num(weight, 2),
+                ];
+                row.extend(range_cells);
+                row.extend([
+                    ExportCell::num(m.volatility, 2),
+                    ExportCell::num(m.max_drawdown, 2),
+                    ExportCell::opt_num(m.beta, 2),
+                    ExportCell::opt_num(m.sharpe, 2),
+                    ExportCell::opt_num(m.value_at_risk, 2),
+                    ExportCell::opt_num(m.var_95, 2),
+                    ExportCell::opt_num(m.var_99, 2),
+                    ExportCell::opt_num(m.expected_shortfall_95, 2),
+                    ExportCell::opt_num(m.expected_shortfall_99, 2),
+                    ExportCell::num(assessment.risk_score, 2),
+                    ExportCell::text(assessment.risk_level.to_string().to_uppercase()),
+                    tags_cell,
+                ]);
+                table.push_row(row);
                 rows_written += 1;
             },
             Err(e) => {
                 warn!("Skipping {} due to error: {}", ticker, e);
                 // Write row with error indication
-                csv_writer.write_record(&[
-                    ticker,
-                    holding_name.unwrap_or_else(|| "—".to_string()),
-                    format!("{:.2}", market_value),
-                    format!("{:.2}", weight),
-                    "N/A".to_string(),
-                    "N/A".to_string(),
-                    "N/A".to_string(),
-                    "N/A".to_string(),
-                    "N/A".to_string(),
-                    "N/A".to_string(),
-                    "N/A".to_string(),
-                    "N/A".to_string(),
-                    "N/A".to_string(),
-                    "N/A".to_string(),
-                    "ERROR".to_string(),
-                ]).map_err(|e| {
-                    error!("Failed to write CSV row: {}", e);
-                    AppError::External(format!("CSV generation error: {}", e))
-                })?;
+                let mut row = vec![
+                    ExportCell::Text(ticker),
+                    name_cell,
+                    ExportCell::num(market_value, 2),
+                    ExportCell::num(weight, 2),
+                ];
+                row.extend(range_cells);
+                row.extend(std::iter::repeat_n(ExportCell::text("N/A"), 10));
+                row.push(ExportCell::text("ERROR"));
+                row.push(tags_cell);
+                table.push_row(row);
             }
         }
     }
 
-    let csv_data = csv_writer.into_inner().map_err(|e| {
-        error!("Failed to finalize CSV: {}", e);
-        AppError::External(format!("CSV generation error: {}", e))
-    })?;
+    info!("Successfully exported {} positions", rows_written);
 
-    info!("Successfully exported {} positions to CSV", rows_written);
+    let filename = portfolio_export_name("portfolio_risk", &portfolio.name, portfolio_id);
+    export_response(&ExportDocument::single(table), format, &filename)
+}
 
-    // Generate filename with date
-    let filename = format!(
-        "portfolio_risk_{}_{}_{}.csv",
-        portfolio.name.replace(' ', "_"),
-        portfolio_id,
-        chrono::Utc::now().format("%Y%m%d")
+/// GET /api/risk/portfolios/:portfolio_id/correlations/export
+///
+/// Export the portfolio correlation matrix as a grid (also served at `/export/csv`)
+///
+/// Query parameters:
+/// - `days`: Rolling window in days (default: 90)
+/// - `force`: Compute on demand instead of reading the cache (default: false)
+/// - `format`: csv, xlsx, or json (default: negotiated from `Accept`, else csv)
+///
+/// The first column holds the tickers; each cell is the pairwise correlation.
+pub async fn export_correlations(
+    access: PortfolioAccess<CanView>,
+    Path(portfolio_id): Path<Uuid>,
+    Query(params): Query<RiskQueryParams>,
+    Query(export): Query<ExportQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Response, AppError> {
+    let format = ExportFormat::negotiate(export.format.as_deref(), &headers)?;
+    let portfolio = portfolio_queries::fetch_one(&state.pool, portfolio_id, access.user_id)
+        .await.map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+    info!(
+        "GET /api/risk/portfolios/{}/correlations/export - Exporting correlation matrix (days={}, {:?})",
+        portfolio_id, params.days, format
     );
 
-    // Build response with proper headers
-    Ok(Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "text/csv; charset=utf-8")
-        .header(
-            header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", filename)
-        )
-        .body(csv_data.into())
-        .unwrap())
+    let Json(result) = get_portfolio_correlations(
+        access,
+        Path(portfolio_id),
+        Query(params),
+        State(state),
+    ).await?;
+    let matrix = result.matrix;
+
+    let mut columns = vec!["Ticker"];
+    columns.extend(matrix.tickers.iter().map(String::as_str));
+    let mut table = ExportTable::new("Correlations", &columns);
+
+    for (ticker, row) in matrix.tickers.iter().zip(matrix.matrix_2d.iter()) {
+        let mut cells = vec![ExportCell::text(ticker.clone())];
+        cells.extend(row.iter().map(|c| ExportCell::num(*c, 4)));
+        table.push_row(cells);
+    }
+
+    info!("Successfully exported {}x{} correlation matrix", matrix.tickers.len(), matrix.tickers.len());
+
+    let filename = portfolio_export_name("portfolio_correlations", &portfolio.name, portfolio_id);
+    export_response(&ExportDocument::single(table), format, &filename)
 }
 
 /// GET /api/risk/portfolios/:portfolio_id/narrative
@@ -1934,18 +2104,20 @@ pub async fn export_portfolio_risk_csv(
 /// Query parameters:
 /// - `time_period`: Optional time period for analysis (e.g., "30d", "90d", "1y")
 ///
-/// Requires LLM to be enabled and user consent granted.
+/// Requires LLM to be enabled and consent to the current version recorded via
+/// `POST /api/users/me/llm-consent`; otherwise generation fails with 403 `consent_required`.
 /// Returns a structured narrative with summary, performance explanation, risk highlights, and top contributors.
 ///
 /// Example: GET /api/risk/portfolios/{uuid}/narrative?time_period=30d
 pub async fn get_portfolio_narrative(
-    AuthUser(user_id): AuthUser,
+    access: PortfolioAccess<CanView>,
     Path(portfolio_id): Path<Uuid>,
     Query(params): Query<GenerateNarrativeRequest>,
     State(state): State<AppState>,
 ) -> Result<Json<PortfolioNarrative>, AppError> {
-    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
-        .await.map_err(AppError::Db)?
+    // Cache rows belong to the owner, also when a member is viewing
+    let tenant = TenantScope::for_job(&state.pool, portfolio_id)
+        .await?
         .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
     use crate::db::holding_snapshot_queries;
     use std::collections::HashMap;
@@ -1958,10 +2130,8 @@ pub async fn get_portfolio_narrative(
     // Use provided time_period or default to "90 days"
     let time_period = params.time_period.as_deref().unwrap_or("90 days");
 
-    // Get user preferences for cache duration (demo user for now)
-    let demo_user_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001")
-        .expect("Invalid demo user UUID");
-    let user_prefs = crate::db::user_preferences_queries::get_by_user_id(&state.pool, demo_user_id)
+    // The user's preferences set how long a narrative stays cached
+    let user_prefs = crate::db::user_preferences_queries::get_by_user_id(&state.pool, access.user_id)
         .await
         .map_err(|e| {
             error!("Failed to fetch user preferences: {}", e);
@@ -1971,7 +2141,7 @@ pub async fn get_portfolio_narrative(
 
     // Check cache first if not forcing refresh
     if !params.force {
-        if let Some(cached_narrative) = get_cached_narrative(&state.pool, portfolio_id, time_period, cache_hours).await? {
+        if let Some(cached_narrative) = get_cached_narrative(&state.pool, &tenant, time_period, cache_hours).await? {
             info!("Returning cached narrative for portfolio {}", portfolio_id);
             return Ok(Json(cached_narrative));
         }
@@ -2003,12 +2173,15 @@ pub async fn get_portfolio_narrative(
         ));
     }
 
+    // Values from accounts in different currencies are summed in the owner's base currency
+    let fx = PortfolioFx::load(&state.pool, state.fx_provider.as_ref(), portfolio_id, &holdings).await?;
+
     // 2. Aggregate holdings by ticker
     let mut ticker_aggregates: HashMap<String, (f64, f64)> = HashMap::new();
 
     for holding in &holdings {
-        let market_value = holding.market_value.to_string().parse::<f64>().unwrap_or(0.0);
-        let quantity = holding.quantity.to_string().parse::<f64>().unwrap_or(0.0);
+        let market_value = fx.market_value(holding);
+        let quantity = holding.quantity.to_f64().unwrap_or(0.0);
 
         ticker_aggregates
             .entry(holding.ticker.clone())
@@ -2044,6 +2217,7 @@ pub async fn get_portfolio_narrative(
     let mut es_95_count = 0;
     let mut es_99_count = 0;
 
+
     for (ticker, (_quantity, market_value)) in ticker_aggregates {
         let weight = market_value / total_value;
         if weight < 0.001 {
@@ -2113,17 +2287,29 @@ pub async fn get_portfolio_narrative(
         ));
     }
 
+    // Volatility, drawdown, Sharpe and VaR come from the portfolio's own daily
+    // returns; averaging the position metrics ignores diversification and cash.
+    // The weighted averages are kept as a fallback for too little history.
+    let returns = portfolio_return_service::compute_metrics(&state.pool, portfolio_id, days, "SPY", state.risk_free_rate)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Could not build the return series for portfolio {}: {}", portfolio_id, e);
+            Default::default()
+        });
+    let portfolio_volatility = returns.volatility.unwrap_or(weighted_volatility);
+    let portfolio_max_drawdown = returns.max_drawdown.unwrap_or(weighted_max_drawdown);
+
     // 4. Calculate portfolio-level risk score
     let portfolio_risk_score = risk_service::score_risk(&crate::models::PositionRisk {
-        volatility: weighted_volatility,
-        max_drawdown: weighted_max_drawdown,
+        volatility: portfolio_volatility,
+        max_drawdown: portfolio_max_drawdown,
         beta: if beta_count > 0 { Some(weighted_beta) } else { None },
         beta_spy: if beta_count > 0 { Some(weighted_beta) } else { None },
         beta_qqq: None,
         beta_iwm: None,
         risk_decomposition: None,
-        sharpe: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
-        sortino: None,
+        sharpe: returns.sharpe,
+        sortino: returns.sortino,
         annualized_return: None,
         value_at_risk: None,
         var_95: None,
@@ -2141,28 +2327,85 @@ pub async fn get_portfolio_narrative(
     let portfolio_risk = crate::models::PortfolioRisk {
         portfolio_id: portfolio_id.to_string(),
         total_value,
-        portfolio_volatility: weighted_volatility,
-        portfolio_max_drawdown: weighted_max_drawdown,
+        portfolio_volatility,
+        portfolio_max_drawdown,
         portfolio_beta: if beta_count > 0 { Some(weighted_beta) } else { None },
-        portfolio_sharpe: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
-        portfolio_var_95: if var_95_count > 0 { Some(weighted_var_95) } else { None },
-        portfolio_var_99: if var_99_count > 0 { Some(weighted_var_99) } else { None },
-        portfolio_expected_shortfall_95: if es_95_count > 0 { Some(weighted_es_95) } else { None },
-        portfolio_expected_shortfall_99: if es_99_count > 0 { Some(weighted_es_99) } else { None },
+        portfolio_regression_beta: returns.beta,
+        portfolio_alpha: returns.alpha,
+        portfolio_r_squared: returns.r_squared,
+        portfolio_sharpe: returns.sharpe,
+        portfolio_sortino: returns.sortino,
+        portfolio_sharpe_weighted_average: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
+        portfolio_var_95: returns.var_95.or(if var_95_count > 0 { Some(weighted_var_95) } else { None }),
+        portfolio_var_99: returns.var_99.or(if var_99_count > 0 { Some(weighted_var_99) } else { None }),
+        portfolio_expected_shortfall_95: returns.expected_shortfall_95.or(if es_95_count > 0 { Some(weighted_es_95) } else { None }),
+        portfolio_expected_shortfall_99: returns.expected_shortfall_99.or(if es_99_count > 0 { Some(weighted_es_99) } else { None }),
         portfolio_risk_score,
         risk_level,
+        scoring_version: risk_service::CURRENT_SCORING_VERSION,
         position_risks,
     };
 
     // 5. Generate narrative using LLM service
-    // Use a demo user ID (in production, extract from auth token)
-    let demo_user_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001")
-        .expect("Invalid demo user UUID");
+    // Stated theses give the narrative the investor's own rationale; optional context
+    let theses = crate::db::annotation_queries::fetch_for_portfolio(&state.pool, portfolio_id)
+        .await
+        .map(|a| crate::services::annotation_service::theses_by_ticker(&a))
+        .unwrap_or_else(|e| {
+            warn!("Failed to fetch position theses for narrative: {}", e);
+            Vec::new()
+        });
+
+    // Leader/laggard attribution for the top contributors section; optional context
+    let contributors = crate::services::benchmark_comparison_service::compare_portfolio(
+        &state.pool,
+        portfolio_id,
+        days,
+        "SPY",
+    )
+    .await
+    .map(|c| crate::services::benchmark_comparison_service::contributor_lines(&c))
+    .unwrap_or_else(|e| {
+        warn!("Failed to compute benchmark comparison for narrative: {}", e);
+        Vec::new()
+    });
+
+    // Most relevant recent headlines for held tickers; optional context
+    let headlines = if state.news_service.is_enabled() {
+        crate::services::portfolio_news_service::feed_items(
+            &state.pool,
+            &state.news_service,
+            portfolio_id,
+            crate::services::portfolio_news_service::DEFAULT_DAYS,
+            false,
+        )
+        .await
+        .map(|(items, _)| crate::services::portfolio_news_service::narrative_headlines(&items, 8))
+        .unwrap_or_else(|e| {
+            warn!("Failed to fetch headlines for narrative: {}", e);
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+
+    // Why the risk score moved recently, from the snapshot change drivers; optional context
+    let risk_changes = risk_snapshot_service::recent_change_lines(&state.pool, portfolio_id, days, 5)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Failed to fetch risk score changes for narrative: {}", e);
+            Vec::new()
+        });
 
     let narrative = narrative_service::generate_portfolio_narrative(
+        &state.pool,
         state.llm_service.clone(),
-        demo_user_id,
+        access.user_id,
         &portfolio_risk,
+        &theses,
+        &contributors,
+        &headlines,
+        &risk_changes,
         time_period,
     ).await?;
 
@@ -2172,7 +2415,7 @@ pub async fn get_portfolio_narrative(
     );
 
     // Cache the narrative for future requests
-    if let Err(e) = cache_narrative(&state.pool, portfolio_id, time_period, &narrative, cache_hours).await {
+    if let Err(e) = cache_narrative(&state.pool, &tenant, time_period, &narrative, cache_hours).await {
         error!("Failed to cache narrative for portfolio {}: {}", portfolio_id, e);
         // Continue even if caching fails - don't fail the request
     }
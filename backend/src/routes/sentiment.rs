@@ -5,7 +5,9 @@ use serde::Deserialize;
 use tracing::info;
 use uuid::Uuid;
 
+use crate::db::portfolio_queries;
 use crate::errors::AppError;
+use crate::middleware::auth::AuthUser;
 use crate::models::{SentimentSignal, PortfolioSentimentAnalysis, DivergenceType, EnhancedSentimentSignal, SentimentTrend, MomentumTrend, SentimentDataPoint, SentimentAwareForecast};
 use crate::services::{sentiment_service, price_service, enhanced_sentiment_service, sec_edgar_service, sentiment_forecasting_service};
 use crate::state::AppState;
@@ -37,6 +39,7 @@ fn default_forecast_days() -> i32 {
 /// GET /api/sentiment/positions/:ticker/sentiment
 /// Query params: days (default: 30)
 pub async fn get_position_sentiment(
+    AuthUser(user_id): AuthUser,
     Path(ticker): Path<String>,
     Query(params): Query<SentimentQueryParams>,
     State(state): State<AppState>,
@@ -62,11 +65,7 @@ pub async fn get_position_sentiment(
     info!("Found {} news articles for {}", articles.len(), ticker);
 
     // 2. Cluster articles into themes using LLM
-    // Use demo user ID (same pattern as news routes)
-    let demo_user_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001")
-        .expect("Failed to parse demo user UUID");
-
-    let themes = state.news_service.cluster_into_themes(articles, demo_user_id).await?;
+    let themes = state.news_service.cluster_into_themes(articles, user_id).await?;
 
     if themes.is_empty() {
         return Err(AppError::Validation(
@@ -146,10 +145,15 @@ pub async fn get_enhanced_position_sentiment(
 /// FAST VERSION: Reads from sentiment_signal_cache instead of fetching fresh data.
 /// Returns cached data even if expired, with warnings about staleness.
 pub async fn get_portfolio_sentiment(
+    AuthUser(user_id): AuthUser,
     Path(portfolio_id): Path<Uuid>,
     State(state): State<AppState>,
 ) -> Result<Json<PortfolioSentimentAnalysis>, AppError> {
     info!("Fetching portfolio sentiment from cache for portfolio_id: {}", portfolio_id);
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
 
     // 1. Get all positions in the portfolio
     let positions = sqlx::query!(
@@ -357,10 +361,15 @@ pub async fn clear_sentiment_cache(
 /// GET /api/sentiment/portfolios/:portfolio_id/cache-status
 /// Get cache status for portfolio sentiment data
 pub async fn get_portfolio_sentiment_cache_status(
+    AuthUser(user_id): AuthUser,
     Path(portfolio_id): Path<Uuid>,
     State(state): State<AppState>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     info!("Checking sentiment cache status for portfolio_id: {}", portfolio_id);
+    portfolio_queries::fetch_one(&state.pool, portfolio_id, user_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
 
     // Get all tickers in the portfolio
     let tickers = sqlx::query!(
@@ -474,6 +483,7 @@ pub async fn get_portfolio_sentiment_cache_status(
 #[allow(dead_code)]
 async fn fetch_ticker_sentiment(
     state: &AppState,
+    user_id: Uuid,
     ticker: &str,
     days: i32,
 ) -> Result<SentimentSignal, AppError> {
@@ -487,9 +497,7 @@ async fn fetch_ticker_sentiment(
     }
 
     // Cluster into themes
-    let demo_user_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001")
-        .expect("Failed to parse demo user UUID");
-    let themes = state.news_service.cluster_into_themes(articles, demo_user_id).await?;
+    let themes = state.news_service.cluster_into_themes(articles, user_id).await?;
 
     if themes.is_empty() {
         return Err(AppError::Validation(
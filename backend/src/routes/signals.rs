@@ -1,3 +1,4 @@
+use bigdecimal::ToPrimitive;
 use axum::extract::{Path, Query, State};
 use axum::{Json, Router};
 use axum::routing::get;
@@ -8,6 +9,7 @@ use crate::errors::AppError;
 use crate::models::{SignalType, SignalGenerationParams, SignalResponse, TradingSignal};
 use crate::services::{price_service, signal_service::SignalService};
 use crate::state::AppState;
+use crate::services::clock;
 
 pub fn router() -> Router<AppState> {
     Router::new()
@@ -201,7 +203,7 @@ pub async fn get_stock_signals(
     // Note: PricePoint only has close_price, no volume data
     let mut prices: Vec<f64> = price_data
         .iter()
-        .filter_map(|p| p.close_price.to_string().parse::<f64>().ok())
+        .filter_map(|p| p.close_price.to_f64())
         .collect();
 
     // Reverse to get chronological order (oldest first) as expected by indicators
@@ -293,7 +295,7 @@ pub async fn get_signals_history(
             AppError::External(format!("Failed to fetch signal history: {}", e))
         })?;
 
-    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+    let cutoff = clock::now() - chrono::Duration::days(days);
     let filtered: Vec<_> = signals
         .into_iter()
         .filter(|s| s.generated_at >= cutoff)
@@ -0,0 +1,31 @@
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use tracing::info;
+
+use crate::errors::AppError;
+use crate::models::{TickerSearchQuery, TickerSearchResponse};
+use crate::services::price_service;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/search", get(search_tickers))
+}
+
+/// GET /api/tickers/search?q=appl - Name, exchange, type and currency of
+/// matching symbols, for validating a ticker before adding it
+pub async fn search_tickers(
+    State(state): State<AppState>,
+    Query(query): Query<TickerSearchQuery>,
+) -> Result<Json<TickerSearchResponse>, AppError> {
+    info!("GET /api/tickers/search?q={}", query.q);
+    let response = price_service::search_tickers(
+        &state.pool,
+        state.price_provider.as_ref(),
+        state.rate_limiter.as_ref(),
+        &query.q,
+        query.limit,
+    )
+    .await?;
+    Ok(Json(response))
+}
@@ -1,18 +1,32 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::{Json, Router};
-use axum::routing::get;
+use axum::routing::{get, put};
+use axum::http::StatusCode;
 use tracing::{info, error};
 use uuid::Uuid;
 
-use crate::db::{account_queries, detected_transaction_queries, portfolio_queries};
+use crate::db::{account_queries, detected_transaction_queries, journal_queries, portfolio_queries};
 use crate::errors::AppError;
 use crate::middleware::auth::AuthUser;
-use crate::models::{AccountActivity, AccountTruePerformance, DetectedTransaction};
+use crate::middleware::permissions::{CanEdit, CanView, PortfolioAccess};
+use crate::models::{
+    AccountActivity, AccountTruePerformance, DetectedTransaction, JournalEntry, JournalReview, JournalReviewQuery,
+    LotSelection, TagFilterQuery, UpdateAnnotation, UpdateLotSelections, UpsertJournalEntry,
+};
+use crate::services::{annotation_service, lot_service, trade_journal_service, wash_sale_service};
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/accounts/:account_id/transactions", get(list_transactions))
+        .route("/transactions/:transaction_id/annotations", put(set_transaction_annotation))
+        .route("/transactions/:transaction_id/lot-selections", put(set_lot_selections))
+        .route(
+            "/transactions/:transaction_id/journal",
+            get(get_journal_entry).put(set_journal_entry).delete(delete_journal_entry),
+        )
+        .route("/portfolios/:portfolio_id/journal", get(list_journal_entries))
+        .route("/portfolios/:portfolio_id/journal/review", get(review_journal))
         .route("/accounts/:account_id/activity", get(get_activity))
         .route("/accounts/:account_id/true-performance", get(get_true_performance))
         .route("/portfolios/:portfolio_id/true-performance", get(get_portfolio_true_performance))
@@ -22,6 +36,7 @@ pub async fn list_transactions(
     State(state): State<AppState>,
     AuthUser(user_id): AuthUser,
     Path(account_id): Path<Uuid>,
+    Query(params): Query<TagFilterQuery>,
 ) -> Result<Json<Vec<DetectedTransaction>>, AppError> {
     info!("GET /accounts/{}/transactions - Listing transactions", account_id);
     if !account_queries::belongs_to_user(&state.pool, account_id, user_id)
@@ -30,7 +45,8 @@ pub async fn list_transactions(
     {
         return Err(AppError::NotFound(format!("Account {} not found", account_id)));
     }
-    let transactions = detected_transaction_queries::fetch_by_account(&state.pool, account_id)
+    let tag = annotation_service::normalize_tag_filter(params.tag);
+    let transactions = detected_transaction_queries::fetch_by_account(&state.pool, account_id, tag.as_deref())
         .await
         .map_err(|e| {
             error!("Failed to fetch transactions: {}", e);
@@ -39,6 +55,151 @@ pub async fn list_transactions(
     Ok(Json(transactions))
 }
 
+/// PUT /api/transactions/:transaction_id/annotations
+///
+/// Set free-form tags and markdown notes on a transaction. Omitted fields are left unchanged.
+pub async fn set_transaction_annotation(
+    State(state): State<AppState>,
+    _access: PortfolioAccess<CanEdit>,
+    Path(transaction_id): Path<Uuid>,
+    Json(data): Json<UpdateAnnotation>,
+) -> Result<Json<DetectedTransaction>, AppError> {
+    info!("PUT /transactions/{}/annotations - Updating transaction annotation", transaction_id);
+    let updated = annotation_service::set_transaction_annotation(&state.pool, transaction_id, data)
+        .await
+        .map_err(|e| {
+            error!("Failed to update annotation for transaction {}: {}", transaction_id, e);
+            e
+        })?;
+    Ok(Json(updated))
+}
+
+/// PUT /api/transactions/:transaction_id/lot-selections
+///
+/// Designate which lots a SELL closes, for accounts using specific-lot
+/// identification. Replaces any earlier designation; an empty list reverts the
+/// sale to FIFO. Wash sales are re-scanned since realized losses can change.
+pub async fn set_lot_selections(
+    State(state): State<AppState>,
+    _access: PortfolioAccess<CanEdit>,
+    Path(transaction_id): Path<Uuid>,
+    Json(data): Json<UpdateLotSelections>,
+) -> Result<Json<Vec<LotSelection>>, AppError> {
+    info!("PUT /transactions/{}/lot-selections - Designating {} lots", transaction_id, data.selections.len());
+    let transaction = detected_transaction_queries::fetch_one(&state.pool, transaction_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {} not found", transaction_id)))?;
+    lot_service::set_lot_selections(&state.pool, &transaction, &data.selections)
+        .await
+        .map_err(|e| {
+            error!("Failed to designate lots for transaction {}: {}", transaction_id, e);
+            e
+        })?;
+
+    if let Some(account) = account_queries::fetch_one(&state.pool, transaction.account_id)
+        .await
+        .map_err(AppError::Db)?
+    {
+        if let Err(e) = wash_sale_service::refresh_portfolio_wash_sales(&state.pool, account.portfolio_id).await {
+            error!("Failed to check wash sales for portfolio {}: {}", account.portfolio_id, e);
+        }
+    }
+    Ok(Json(data.selections))
+}
+
+/// GET /api/transactions/:transaction_id/journal
+pub async fn get_journal_entry(
+    State(state): State<AppState>,
+    _access: PortfolioAccess<CanView>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<Json<JournalEntry>, AppError> {
+    info!("GET /transactions/{}/journal - Getting journal entry", transaction_id);
+    let entry = journal_queries::fetch_for_transaction(&state.pool, transaction_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("No journal entry for transaction {}", transaction_id)))?;
+    Ok(Json(entry))
+}
+
+/// PUT /api/transactions/:transaction_id/journal
+///
+/// Record why a BUY or SELL was made and, optionally, the thesis it was expected
+/// to play out: target and stop prices, expected return, horizon and confidence.
+/// Replaces any earlier entry.
+pub async fn set_journal_entry(
+    State(state): State<AppState>,
+    _access: PortfolioAccess<CanEdit>,
+    Path(transaction_id): Path<Uuid>,
+    Json(data): Json<UpsertJournalEntry>,
+) -> Result<Json<JournalEntry>, AppError> {
+    info!("PUT /transactions/{}/journal - Recording journal entry", transaction_id);
+    let transaction = detected_transaction_queries::fetch_one(&state.pool, transaction_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {} not found", transaction_id)))?;
+    let entry = trade_journal_service::record_entry(&state.pool, &transaction, data)
+        .await
+        .map_err(|e| {
+            error!("Failed to record journal entry for transaction {}: {}", transaction_id, e);
+            e
+        })?;
+    Ok(Json(entry))
+}
+
+/// DELETE /api/transactions/:transaction_id/journal
+pub async fn delete_journal_entry(
+    State(state): State<AppState>,
+    _access: PortfolioAccess<CanEdit>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("DELETE /transactions/{}/journal - Deleting journal entry", transaction_id);
+    if !journal_queries::delete(&state.pool, transaction_id).await.map_err(AppError::Db)? {
+        return Err(AppError::NotFound(format!("No journal entry for transaction {}", transaction_id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/portfolios/:portfolio_id/journal
+///
+/// Journal entries across the portfolio's accounts, most recent trade first.
+pub async fn list_journal_entries(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanView>,
+) -> Result<Json<Vec<JournalEntry>>, AppError> {
+    info!("GET /portfolios/{}/journal - Listing journal entries", access.portfolio_id);
+    let entries = journal_queries::fetch_for_portfolio(&state.pool, access.portfolio_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch journal entries: {}", e);
+            AppError::Db(e)
+        })?;
+    Ok(Json(entries))
+}
+
+/// GET /api/portfolios/:portfolio_id/journal/review
+///
+/// Score each journaled decision: the stock's return since the trade against the
+/// benchmark's, up to the end of the stated horizon, and whether the stated target,
+/// stop and expected return played out. Summarizes hit rates across decisions.
+///
+/// Query parameters:
+/// - benchmark: benchmark ticker (default: "SPY")
+pub async fn review_journal(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanView>,
+    Query(params): Query<JournalReviewQuery>,
+) -> Result<Json<JournalReview>, AppError> {
+    info!("GET /portfolios/{}/journal/review - Reviewing decisions against {}", access.portfolio_id, params.benchmark);
+    let review = trade_journal_service::review_portfolio(&state.pool, access.portfolio_id, &params.benchmark)
+        .await
+        .map_err(|e| {
+            error!("Failed to review journal of portfolio {}: {}", access.portfolio_id, e);
+            e
+        })?;
+    Ok(Json(review))
+}
+
 pub async fn get_activity(
     State(state): State<AppState>,
     AuthUser(user_id): AuthUser,
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{delete, get},
+    Json,
+    Router,
+};
+use tracing::info;
+
+use crate::errors::AppError;
+use crate::middleware::auth::AuthUser;
+use crate::models::DataErasureQuery;
+use crate::services::user_data_service;
+use crate::state::AppState;
+
+/// Create the personal data router
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/users/me/data", delete(erase_data))
+        .route("/users/me/data/export", get(export_data))
+}
+
+/// GET /api/users/me/data/export
+///
+/// Download everything stored about the current user as one JSON document.
+pub async fn export_data(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    info!("GET /api/users/me/data/export for user {}", user_id);
+
+    let export = user_data_service::export_user_data(&state.pool, user_id).await?;
+    let filename = format!("rustfolio-data-{}.json", export.exported_at.format("%Y%m%d"));
+
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))],
+        Json(export),
+    ))
+}
+
+/// DELETE /api/users/me/data?confirm=true[&delete_account=true][&skip_export=true]
+///
+/// Permanently erase the current user's portfolios, transactions, snapshots,
+/// caches and LLM artifacts. Requires a recent export unless `skip_export=true`.
+pub async fn erase_data(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<DataErasureQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    info!(
+        "DELETE /api/users/me/data for user {} (delete_account: {})",
+        user_id, query.delete_account
+    );
+
+    let summary = user_data_service::erase_user_data(&state.pool, user_id, &query).await?;
+
+    Ok((StatusCode::OK, Json(summary)))
+}
@@ -1,3 +1,4 @@
+use bigdecimal::ToPrimitive;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
@@ -64,7 +65,7 @@ struct PaginationParams {
 // ==============================================================================
 
 async fn list_templates() -> Result<impl IntoResponse, (StatusCode, String)> {
-    info!("📋 Listing all index templates");
+    info!("Listing all index templates");
 
     let templates = index_templates::get_all_templates();
     let template_list: Vec<IndexTemplateListItem> = templates
@@ -72,19 +73,19 @@ async fn list_templates() -> Result<impl IntoResponse, (StatusCode, String)> {
         .map(IndexTemplateListItem::from)
         .collect();
 
-    info!("📋 Returning {} index templates", template_list.len());
+    info!("Returning {} index templates", template_list.len());
     Ok(Json(template_list))
 }
 
 async fn get_template(
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    info!("📋 Getting template details for: {}", id);
+    info!("Getting template details for: {}", id);
 
     let template = index_templates::get_template_by_id(&id)
         .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Template '{}' not found", id)))?;
 
-    info!("📋 Returning template '{}' with {} tickers", template.name, template.ticker_count);
+    info!("Returning template '{}' with {} tickers", template.name, template.ticker_count);
     Ok(Json(template))
 }
 
@@ -95,7 +96,7 @@ async fn create_from_template(
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let pool = &state.pool;
 
-    info!("🎯 Creating watchlist from template: {}", req.template_id);
+    info!("Creating watchlist from template: {}", req.template_id);
 
     // Get the template
     let template = index_templates::get_template_by_id(&req.template_id)
@@ -115,7 +116,7 @@ async fn create_from_template(
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    info!("✅ Created watchlist '{}' (id: {})", watchlist_name, watchlist.id);
+    info!("Created watchlist '{}' (id: {})", watchlist_name, watchlist.id);
 
     // Determine which tickers to add (use selected_tickers if provided, otherwise all from template)
     let tickers_to_add = req.selected_tickers.as_ref().unwrap_or(&template.tickers);
@@ -125,7 +126,7 @@ async fn create_from_template(
     let mut failed_count = 0;
     let mut failed_tickers = Vec::new();
 
-    info!("📊 Adding {} tickers to watchlist...", tickers_to_add.len());
+    info!("Adding {} tickers to watchlist...", tickers_to_add.len());
 
     for (idx, ticker) in tickers_to_add.iter().enumerate() {
         let ticker_upper = ticker.to_uppercase();
@@ -147,14 +148,14 @@ async fn create_from_template(
                 added_count += 1;
             }
             Err(e) => {
-                warn!("⚠️ Failed to add ticker {}: {}", ticker_upper, e);
+                warn!("Failed to add ticker {}: {}", ticker_upper, e);
                 failed_count += 1;
                 failed_tickers.push(ticker_upper);
             }
         }
     }
 
-    info!("✅ Watchlist created: {} added, {} failed", added_count, failed_count);
+    info!("Watchlist created: {} added, {} failed", added_count, failed_count);
 
     let response = CreateWatchlistFromTemplateResponse {
         watchlist_id: watchlist.id.to_string(),
@@ -418,18 +419,18 @@ async fn add_item(
                 state.rate_limiter.as_ref(),
             ).await {
                 Ok(()) => {
-                    info!("✓ Successfully fetched price data from API for {}", ticker_upper);
+                    info!("Successfully fetched price data from API for {}", ticker_upper);
                     // Now fetch the latest price from database
                     match price_queries::fetch_latest(pool, &ticker_upper).await {
                         Ok(Some(pp)) => {
-                            info!("✓ Cached price now available for {}: ${}", ticker_upper, pp.close_price);
+                            info!("Cached price now available for {}: ${}", ticker_upper, pp.close_price);
                             Some(pp.close_price)
                         }
                         _ => None,
                     }
                 }
                 Err(e) => {
-                    warn!("⚠️ Could not fetch price from API for {}: {} - will retry later", ticker_upper, e);
+                    warn!("Could not fetch price from API for {}: {} - will retry later", ticker_upper, e);
                     None
                 }
             }
@@ -483,7 +484,7 @@ async fn add_item(
     };
 
     // Log the full response for debugging
-    info!("✅ Watchlist item response: ticker={}, company_name={:?}, current_price={:?}, added_price={:?}, change={:?}, risk={:?}",
+    info!("Watchlist item response: ticker={}, company_name={:?}, current_price={:?}, added_price={:?}, change={:?}, risk={:?}",
         response.ticker, response.company_name, response.current_price, response.added_price, response.price_change_pct, response.risk_level);
 
     Ok((StatusCode::CREATED, Json(response)))
@@ -495,14 +496,14 @@ async fn get_items(
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let pool = &state.pool;
 
-    info!("📋 Fetching watchlist items for watchlist {}", watchlist_id);
+    info!("Fetching watchlist items for watchlist {}", watchlist_id);
     let start = std::time::Instant::now();
 
     let items = watchlist_queries::get_watchlist_items(pool, watchlist_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    info!("📋 Fetched {} items in {:?}", items.len(), start.elapsed());
+    info!("Fetched {} items in {:?}", items.len(), start.elapsed());
 
     if items.is_empty() {
         return Ok(Json(vec![]));
@@ -514,7 +515,7 @@ async fn get_items(
     let all_thresholds = watchlist_queries::get_thresholds_for_items(pool, &item_ids)
         .await
         .unwrap_or_default();
-    info!("📋 Batch fetched thresholds in {:?}", batch_start.elapsed());
+    info!("Batch fetched thresholds in {:?}", batch_start.elapsed());
 
     // Batch fetch all prices
     let batch_start = std::time::Instant::now();
@@ -522,7 +523,7 @@ async fn get_items(
     let prices_map = price_queries::fetch_latest_batch(pool, &tickers)
         .await
         .unwrap_or_default();
-    info!("📋 Batch fetched prices in {:?}", batch_start.elapsed());
+    info!("Batch fetched prices in {:?}", batch_start.elapsed());
 
     // Fetch company names in parallel (limit concurrency to avoid overwhelming the API)
     let batch_start = std::time::Instant::now();
@@ -551,7 +552,7 @@ async fn get_items(
         .into_iter()
         .filter_map(|(ticker, name): (String, Option<String>)| name.map(|n| (ticker, n)))
         .collect();
-    info!("📋 Fetched {} company names in {:?}", company_names.len(), batch_start.elapsed());
+    info!("Fetched {} company names in {:?}", company_names.len(), batch_start.elapsed());
 
     // Build responses
     let mut responses = Vec::new();
@@ -559,8 +560,8 @@ async fn get_items(
         let thresholds = all_thresholds.get(&item.id).cloned().unwrap_or_default();
 
         let (current_price, price_change_pct) = if let Some(price_point) = prices_map.get(&item.ticker) {
-            let current_price = Some(price_point.close_price.to_string().parse::<f64>().unwrap_or(0.0));
-            let added_price_f64 = item.added_price.as_ref().and_then(|p| p.to_string().parse::<f64>().ok());
+            let current_price = Some(price_point.close_price.to_f64().unwrap_or(0.0));
+            let added_price_f64 = item.added_price.as_ref().and_then(|p| p.to_f64());
             let price_change_pct = match (current_price, added_price_f64) {
                 (Some(current), Some(added)) if added > 0.0 => {
                     Some(((current - added) / added) * 100.0)
@@ -596,7 +597,7 @@ async fn get_items(
         responses.push(response);
     }
 
-    info!("📋 Returning {} watchlist items in total {:?}", responses.len(), start.elapsed());
+    info!("Returning {} watchlist items in total {:?}", responses.len(), start.elapsed());
     Ok(Json(responses))
 }
 
@@ -682,7 +683,7 @@ async fn refresh_watchlist_prices(
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let pool = &state.pool;
 
-    info!("🔄 Force refresh prices for watchlist {}", watchlist_id);
+    info!("Force refresh prices for watchlist {}", watchlist_id);
 
     // Get all items in the watchlist
     let items = watchlist_queries::get_watchlist_items(pool, watchlist_id)
@@ -704,12 +705,12 @@ async fn refresh_watchlist_prices(
             .is_some();
 
         if has_price {
-            info!("⏭️ Skipping {} - already has price data", ticker);
+            info!("Skipping {} - already has price data", ticker);
             skipped += 1;
             continue;
         }
 
-        info!("🔄 Fetching price for {}", ticker);
+        info!("Fetching price for {}", ticker);
 
         // Try to fetch from API
         match crate::services::price_service::refresh_from_api(
@@ -720,17 +721,17 @@ async fn refresh_watchlist_prices(
             state.rate_limiter.as_ref(),
         ).await {
             Ok(()) => {
-                info!("✅ Successfully refreshed price for {}", ticker);
+                info!("Successfully refreshed price for {}", ticker);
                 refreshed += 1;
             }
             Err(e) => {
-                warn!("❌ Failed to refresh price for {}: {}", ticker, e);
+                warn!("Failed to refresh price for {}: {}", ticker, e);
                 failed += 1;
             }
         }
     }
 
-    info!("🔄 Refresh complete: {} refreshed, {} skipped, {} failed", refreshed, skipped, failed);
+    info!("Refresh complete: {} refreshed, {} skipped, {} failed", refreshed, skipped, failed);
 
     Ok(Json(serde_json::json!({
         "refreshed": refreshed,
@@ -751,7 +752,7 @@ async fn set_threshold(
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let pool = &state.pool;
 
-    info!("🎯 SET_THRESHOLD REQUEST RECEIVED");
+    info!("SET_THRESHOLD REQUEST RECEIVED");
     info!("   item_id: {}", item_id);
     info!("   threshold_type: {:?} (as_str: {})", req.threshold_type, req.threshold_type.as_str());
     info!("   comparison: {:?} (as_str: {})", req.comparison, req.comparison.as_str());
@@ -759,18 +760,18 @@ async fn set_threshold(
     info!("   enabled: {:?}", req.enabled);
 
     // Verify item exists
-    info!("🔍 Verifying watchlist item exists...");
+    info!("Verifying watchlist item exists...");
     match watchlist_queries::get_watchlist_item(pool, item_id).await {
         Ok(item) => {
-            info!("✅ Found watchlist item: ticker={}, watchlist_id={}", item.ticker, item.watchlist_id);
+            info!("Found watchlist item: ticker={}, watchlist_id={}", item.ticker, item.watchlist_id);
         }
         Err(e) => {
-            warn!("❌ Watchlist item not found: {}", e);
+            warn!("Watchlist item not found: {}", e);
             return Err((StatusCode::NOT_FOUND, format!("Watchlist item not found: {}", e)));
         }
     }
 
-    info!("💾 Saving threshold to database...");
+    info!("Saving threshold to database...");
     let threshold = watchlist_queries::set_threshold(
         pool,
         item_id,
@@ -781,11 +782,11 @@ async fn set_threshold(
     )
     .await
     .map_err(|e| {
-        warn!("❌ Failed to save threshold: {}", e);
+        warn!("Failed to save threshold: {}", e);
         (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to save threshold: {}", e))
     })?;
 
-    info!("✅ Threshold saved successfully: id={}", threshold.id);
+    info!("Threshold saved successfully: id={}", threshold.id);
     Ok((StatusCode::CREATED, Json(WatchlistThresholdResponse::from(threshold))))
 }
 
@@ -795,16 +796,16 @@ async fn delete_all_thresholds(
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let pool = &state.pool;
 
-    info!("🗑️  DELETE_ALL_THRESHOLDS - item_id: {}", item_id);
+    info!("DELETE_ALL_THRESHOLDS - item_id: {}", item_id);
 
     watchlist_queries::delete_all_thresholds_for_item(pool, item_id)
         .await
         .map_err(|e| {
-            warn!("❌ Failed to delete thresholds: {}", e);
+            warn!("Failed to delete thresholds: {}", e);
             (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to delete thresholds: {}", e))
         })?;
 
-    info!("✅ All thresholds deleted for item {}", item_id);
+    info!("All thresholds deleted for item {}", item_id);
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -881,7 +882,7 @@ async fn mark_alert_read(
 
 async fn get_current_price_data(pool: &PgPool, item: &WatchlistItem) -> (Option<f64>, Option<f64>) {
     let current_price = match price_queries::fetch_latest(pool, &item.ticker).await {
-        Ok(Some(pp)) => Some(pp.close_price.to_string().parse::<f64>().unwrap_or(0.0)),
+        Ok(Some(pp)) => Some(pp.close_price.to_f64().unwrap_or(0.0)),
         _ => None,
     };
 
@@ -889,8 +890,8 @@ async fn get_current_price_data(pool: &PgPool, item: &WatchlistItem) -> (Option<
     let price_change_pct = match price_queries::fetch_window(pool, &item.ticker, 2).await {
         Ok(prices) if prices.len() >= 2 => {
             // prices are sorted by date DESC, so [0] is most recent, [1] is previous
-            let current = prices[0].close_price.to_string().parse::<f64>().ok();
-            let previous = prices[1].close_price.to_string().parse::<f64>().ok();
+            let current = prices[0].close_price.to_f64();
+            let previous = prices[1].close_price.to_f64();
 
             match (current, previous) {
                 (Some(curr), Some(prev)) if prev > 0.0 => {
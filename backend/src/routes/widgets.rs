@@ -0,0 +1,118 @@
+use axum::extract::{Path, State};
+use axum::http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, CACHE_CONTROL, ORIGIN, VARY};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use serde::Serialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db::widget_token_queries;
+use crate::errors::AppError;
+use crate::middleware::permissions::{CanAdmin, PortfolioAccess};
+use crate::models::{CreateWidgetToken, CreatedWidgetToken, WidgetGrant, WidgetToken};
+use crate::services::widget_service::{self, CACHE_MAX_AGE_SECS};
+use crate::state::AppState;
+
+/// Path prefix of the embeddable widget routes, which set their own CORS
+/// headers instead of the app-wide ones
+pub const WIDGET_PATH_PREFIX: &str = "/api/widgets/";
+
+/// Token management, for portfolio admins
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/portfolios/:portfolio_id/widget-tokens", get(list_tokens).post(create_token))
+        .route("/portfolios/:portfolio_id/widget-tokens/:token_id", delete(delete_token))
+}
+
+/// The widget payloads, mounted at /api/widgets only when
+/// `WIDGET_API_ENABLED` is set
+pub fn widget_router() -> Router<AppState> {
+    Router::new()
+        .route("/:token/value", get(get_widget_value))
+        .route("/:token/risk", get(get_widget_risk))
+}
+
+/// GET /api/portfolios/:portfolio_id/widget-tokens
+pub async fn list_tokens(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanAdmin>,
+) -> Result<Json<Vec<WidgetToken>>, AppError> {
+    info!("GET /api/portfolios/{}/widget-tokens", access.portfolio_id);
+    Ok(Json(widget_token_queries::fetch_for_portfolio(&state.pool, access.portfolio_id).await?))
+}
+
+/// POST /api/portfolios/:portfolio_id/widget-tokens
+///
+/// Body: { "name": "Personal site", "allowed_origin": "https://example.com" }.
+/// The token is only returned in this response.
+pub async fn create_token(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanAdmin>,
+    Json(body): Json<CreateWidgetToken>,
+) -> Result<Json<CreatedWidgetToken>, AppError> {
+    info!("POST /api/portfolios/{}/widget-tokens for origin {}", access.portfolio_id, body.allowed_origin);
+    Ok(Json(widget_service::create_token(&state.pool, access.portfolio_id, access.user_id, &body).await?))
+}
+
+/// DELETE /api/portfolios/:portfolio_id/widget-tokens/:token_id
+pub async fn delete_token(
+    State(state): State<AppState>,
+    access: PortfolioAccess<CanAdmin>,
+    Path((_, token_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    info!("DELETE /api/portfolios/{}/widget-tokens/{}", access.portfolio_id, token_id);
+    if widget_token_queries::delete(&state.pool, token_id, access.portfolio_id).await? == 0 {
+        return Err(AppError::NotFound(format!("Widget token {} not found", token_id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Authenticate the token and check the request's origin against it
+async fn grant(state: &AppState, token: &str, headers: &HeaderMap) -> Result<WidgetGrant, AppError> {
+    let grant = widget_service::authenticate(&state.pool, token).await?;
+    let origin = headers.get(ORIGIN).and_then(|v| v.to_str().ok());
+    if !widget_service::origin_permitted(&grant, origin) {
+        return Err(AppError::Forbidden("This widget is not enabled for this site".to_string()));
+    }
+    Ok(grant)
+}
+
+/// The payload, readable by the token's origin only and cacheable for a few minutes
+fn widget_response(grant: &WidgetGrant, payload: impl Serialize) -> Response {
+    let mut response = Json(payload).into_response();
+    let headers = response.headers_mut();
+    if let Ok(origin) = HeaderValue::from_str(&grant.allowed_origin) {
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    }
+    headers.insert(VARY, HeaderValue::from_static("Origin"));
+    if let Ok(cache) = HeaderValue::from_str(&format!("public, max-age={}", CACHE_MAX_AGE_SECS)) {
+        headers.insert(CACHE_CONTROL, cache);
+    }
+    response
+}
+
+/// GET /api/widgets/:token/value - Latest value and day change
+pub async fn get_widget_value(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let grant = grant(&state, &token, &headers).await?;
+    info!("GET /api/widgets/:token/value for portfolio {}", grant.portfolio_id);
+    let value = widget_service::value(&state.pool, &grant).await?;
+    Ok(widget_response(&grant, value))
+}
+
+/// GET /api/widgets/:token/risk - Latest risk score, for a gauge
+pub async fn get_widget_risk(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let grant = grant(&state, &token, &headers).await?;
+    info!("GET /api/widgets/:token/risk for portfolio {}", grant.portfolio_id);
+    let risk = widget_service::risk(&state.pool, &grant).await?;
+    Ok(widget_response(&grant, risk))
+}
@@ -3,7 +3,7 @@ use bigdecimal::BigDecimal;
 use chrono::NaiveDate;
 use csv::ReaderBuilder;
 use serde::Deserialize;
-use sqlx::PgPool;
+use sqlx::{Connection, PgConnection};
 use std::path::Path;
 use std::str::FromStr;
 use tracing::info;
@@ -11,6 +11,7 @@ use uuid::Uuid;
 
 use crate::db::{account_queries, detected_transaction_queries};
 use crate::models::{CreateDetectedTransaction, TransactionType};
+use crate::services::drip_service;
 
 #[derive(Debug, Deserialize)]
 struct ActivityRow {
@@ -95,7 +96,7 @@ fn map_transaction_type(tran_type: &str) -> Option<TransactionType> {
 }
 
 pub async fn import_activities_content(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     account_id: Uuid,
     content: &str,
 ) -> Result<ActivityImportResult> {
@@ -109,13 +110,17 @@ pub async fn import_activities_content(
     for (line_num, result) in reader.deserialize::<ActivityRow>().enumerate() {
         match result {
             Ok(row) => {
-                match process_activity_row(pool, account_id, row).await {
+                // Savepoint per row so a failed row does not abort the import's transaction
+                let mut savepoint = conn.begin().await?;
+                match process_activity_row(&mut savepoint, account_id, row).await {
                     Ok(imported) => {
+                        savepoint.commit().await?;
                         if imported {
                             transactions_imported += 1;
                         }
                     }
                     Err(e) => {
+                        savepoint.rollback().await?;
                         errors.push(format!("Line {}: {}", line_num + 2, e));
                     }
                 }
@@ -131,6 +136,15 @@ pub async fn import_activities_content(
         account_id, transactions_imported, errors.len()
     );
 
+    // Reinvest newly imported dividends for accounts with DRIP enabled
+    let drip_enabled = account_queries::fetch_one(&mut *conn, account_id)
+        .await?
+        .is_some_and(|a| a.drip_enabled);
+    if drip_enabled && transactions_imported > 0 {
+        let drips = drip_service::generate_for_account(&mut *conn, account_id).await?;
+        errors.extend(drips.skipped.into_iter().map(|s| format!("DRIP skipped: {}", s)));
+    }
+
     Ok(ActivityImportResult {
         transactions_imported,
         errors,
@@ -138,7 +152,7 @@ pub async fn import_activities_content(
 }
 
 pub async fn import_activities_file(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     portfolio_id: Uuid,
     file_path: &Path,
 ) -> Result<ActivityImportResult> {
@@ -147,23 +161,34 @@ pub async fn import_activities_file(
         .and_then(|n| n.to_str())
         .context("Invalid filename")?;
 
+    let file_content = std::fs::read_to_string(file_path)
+        .with_context(|| format!("Failed to read file: {:?}", file_path))?;
+
+    import_named_activities_content(&mut *conn, portfolio_id, filename, &file_content).await
+}
+
+/// Import an `AccountActivities-{account_number}-{date}.csv` export into the
+/// portfolio's account named by the filename
+pub async fn import_named_activities_content(
+    conn: &mut PgConnection,
+    portfolio_id: Uuid,
+    filename: &str,
+    content: &str,
+) -> Result<ActivityImportResult> {
     let account_number = extract_account_number_from_filename(filename)?;
 
     info!("Importing activities for account: {}", account_number);
 
     // Find the account
-    let account = account_queries::find_by_account_number(pool, portfolio_id, &account_number)
+    let account = account_queries::find_by_account_number(&mut *conn, portfolio_id, &account_number)
         .await?
         .context(format!("Account not found: {}", account_number))?;
 
-    let file_content = std::fs::read_to_string(file_path)
-        .with_context(|| format!("Failed to read file: {:?}", file_path))?;
-
-    import_activities_content(pool, account.id, &file_content).await
+    import_activities_content(&mut *conn, account.id, content).await
 }
 
 async fn process_activity_row(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     account_id: Uuid,
     row: ActivityRow,
 ) -> Result<bool> {
@@ -203,7 +228,7 @@ async fn process_activity_row(
         description: Some(format!("{}: {}", row.tran_types, row.description)),
     };
 
-    detected_transaction_queries::create(pool, account_id, settled_date, transaction).await?;
+    detected_transaction_queries::create(&mut *conn, account_id, settled_date, transaction).await?;
 
     Ok(true)
 }
@@ -0,0 +1,152 @@
+//! Instance administration of user accounts.
+//!
+//! Administrators list users, disable and re-enable accounts, reset passwords
+//! and change instance-wide roles. Disabling an account or resetting its
+//! password ends all of the user's sessions. An administrator cannot disable or
+//! demote their own account, so the instance always keeps the admin acting on
+//! it. Every change is recorded in the audit log under the acting admin.
+//!
+//! A fresh self-hosted instance gets its first administrator from
+//! `ADMIN_EMAIL` (and `ADMIN_PASSWORD`) at startup; see [`bootstrap_from_env`].
+
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::auth_queries;
+use crate::errors::AppError;
+use crate::models::alert::User;
+use crate::models::{AuditAction, NewAuditEntry, Role};
+use crate::services::{audit_service, session_service};
+
+/// Shortest password an administrator may set
+const MIN_PASSWORD_LEN: usize = 8;
+
+pub async fn list(pool: &PgPool) -> Result<Vec<User>, AppError> {
+    Ok(auth_queries::list_users(pool).await?)
+}
+
+/// Disable or re-enable an account; disabling also signs the user out everywhere
+pub async fn set_disabled(pool: &PgPool, admin_id: Uuid, user_id: Uuid, disabled: bool) -> Result<User, AppError> {
+    if disabled && admin_id == user_id {
+        return Err(AppError::Validation("You cannot disable your own account".into()));
+    }
+    let before = find(pool, user_id).await?;
+    let user = auth_queries::set_user_disabled(pool, user_id, disabled)
+        .await?
+        .ok_or_else(|| not_found(user_id))?;
+    if disabled {
+        session_service::end_all(pool, user_id, None).await?;
+    }
+
+    info!("Admin {} {} user {}", admin_id, if disabled { "disabled" } else { "enabled" }, user_id);
+    audit_service::record(
+        pool,
+        NewAuditEntry::new(admin_id, AuditAction::Update, "user", user_id).before(&before).after(&user),
+    )
+    .await;
+    Ok(user)
+}
+
+/// Set a new password for a user and end their sessions
+pub async fn reset_password(pool: &PgPool, admin_id: Uuid, user_id: Uuid, new_password: &str) -> Result<(), AppError> {
+    validate_password(new_password)?;
+    find(pool, user_id).await?;
+
+    auth_queries::update_user_password(pool, user_id, &hash(new_password)?).await?;
+    session_service::end_all(pool, user_id, None).await?;
+
+    info!("Admin {} reset the password of user {}", admin_id, user_id);
+    audit_service::record(pool, NewAuditEntry::new(admin_id, AuditAction::Update, "user_password", user_id)).await;
+    Ok(())
+}
+
+/// Change a user's instance-wide role
+pub async fn set_role(pool: &PgPool, admin_id: Uuid, user_id: Uuid, role: Role) -> Result<User, AppError> {
+    if admin_id == user_id && role != Role::Admin {
+        return Err(AppError::Validation("You cannot remove your own administrator role".into()));
+    }
+    let before = find(pool, user_id).await?;
+    let user = auth_queries::set_user_role(pool, user_id, role)
+        .await?
+        .ok_or_else(|| not_found(user_id))?;
+
+    info!("Admin {} set the role of user {} to {}", admin_id, user_id, role);
+    audit_service::record(
+        pool,
+        NewAuditEntry::new(admin_id, AuditAction::Update, "user", user_id).before(&before).after(&user),
+    )
+    .await;
+    Ok(user)
+}
+
+/// Make the account named by `ADMIN_EMAIL` an administrator while the instance
+/// has no enabled admin who can sign in.
+///
+/// An existing account is promoted and re-enabled; if it has no password yet it
+/// gets `ADMIN_PASSWORD`. Otherwise the account is created with `ADMIN_PASSWORD`.
+/// Once an admin exists the variables are ignored, so an existing admin's
+/// password is never overwritten from the environment.
+pub async fn bootstrap_from_env(pool: &PgPool) -> Result<(), AppError> {
+    let Some(email) = std::env::var("ADMIN_EMAIL")
+        .ok()
+        .map(|e| e.trim().to_lowercase())
+        .filter(|e| !e.is_empty())
+    else {
+        return Ok(());
+    };
+    if auth_queries::count_active_admins(pool).await? > 0 {
+        info!("ADMIN_EMAIL is set but an administrator already exists; skipping admin bootstrap");
+        return Ok(());
+    }
+    let password = std::env::var("ADMIN_PASSWORD").ok().filter(|p| !p.is_empty());
+
+    let user = match auth_queries::get_user_by_email(pool, &email).await? {
+        Some(user) => match (&user.password_hash, password) {
+            (None, Some(password)) => {
+                validate_password(&password)?;
+                auth_queries::set_user_password(pool, user.id, &hash(&password)?, None).await?
+            }
+            _ => user,
+        },
+        None => {
+            let password = password.ok_or_else(|| {
+                AppError::Validation(format!("No account for ADMIN_EMAIL {}; set ADMIN_PASSWORD to create it", email))
+            })?;
+            validate_password(&password)?;
+            auth_queries::create_user_with_password(pool, &email, None, &hash(&password)?).await?
+        }
+    };
+
+    auth_queries::set_user_role(pool, user.id, Role::Admin).await?;
+    auth_queries::set_user_disabled(pool, user.id, false).await?;
+    info!("Bootstrapped administrator {} from ADMIN_EMAIL", email);
+
+    if user.password_hash.is_none() {
+        warn!("Administrator {} has no password; sign in with a linked provider or set ADMIN_PASSWORD", email);
+    }
+    Ok(())
+}
+
+async fn find(pool: &PgPool, user_id: Uuid) -> Result<User, AppError> {
+    auth_queries::find_user(pool, user_id).await?.ok_or_else(|| not_found(user_id))
+}
+
+fn not_found(user_id: Uuid) -> AppError {
+    AppError::NotFound(format!("User {} not found", user_id))
+}
+
+fn hash(password: &str) -> Result<String, AppError> {
+    auth::hash_password(password).map_err(|e| AppError::External(format!("Password hashing failed: {}", e)))
+}
+
+fn validate_password(password: &str) -> Result<(), AppError> {
+    if password.len() < MIN_PASSWORD_LEN {
+        return Err(AppError::Validation(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LEN
+        )));
+    }
+    Ok(())
+}
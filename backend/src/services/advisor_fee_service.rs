@@ -0,0 +1,258 @@
+//! Recurring account fees: advisor and management fees defined per account.
+//!
+//! Each schedule charges on month ends between its start and end dates, either a
+//! flat amount or a share of the account value (annual basis points, one twelfth a
+//! month). Charges are stored as `FEE` cash flows, which the true-performance view
+//! ignores, so gross and net-of-fee numbers can be compared side by side.
+
+use bigdecimal::ToPrimitive;
+use chrono::{Datelike, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{account_fee_queries, detected_transaction_queries, holding_snapshot_queries};
+use crate::errors::AppError;
+use crate::models::{
+    AccountFeePerformance, AccountFeeSchedule, AccountFeeType, FeeFlowGeneration, NetOfFeeProjection,
+};
+use crate::services::clock;
+use crate::services::fee_service::PROJECTION_YEARS;
+
+/// Last day of the month containing `date`
+fn month_end(date: NaiveDate) -> NaiveDate {
+    let (year, month) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+    NaiveDate::from_ymd_opt(year, month, 1).and_then(|d| d.pred_opt()).unwrap_or(date)
+}
+
+/// Month ends falling within `start..=through`
+pub fn month_ends(start: NaiveDate, through: NaiveDate) -> Vec<NaiveDate> {
+    let mut ends = Vec::new();
+    let mut current = month_end(start);
+    while current <= through {
+        ends.push(current);
+        match current.succ_opt() {
+            Some(next) => current = month_end(next),
+            None => break,
+        }
+    }
+    ends
+}
+
+/// Latest value on or before `date`; `values` must be sorted by date
+pub fn value_on(values: &[(NaiveDate, f64)], date: NaiveDate) -> Option<f64> {
+    let idx = values.partition_point(|(d, _)| *d <= date);
+    idx.checked_sub(1).map(|i| values[i].1)
+}
+
+/// Monthly charges for `schedule` through `through`, plus the number of months
+/// skipped because no account value was known yet
+pub fn schedule_fees(
+    schedule: &AccountFeeSchedule,
+    values: &[(NaiveDate, f64)],
+    through: NaiveDate,
+) -> (Vec<(NaiveDate, f64)>, usize) {
+    let last = schedule.end_date.map_or(through, |end| end.min(through));
+    let mut fees = Vec::new();
+    let mut skipped = 0;
+
+    for date in month_ends(schedule.start_date, last) {
+        let amount = match schedule.fee_type() {
+            Some(AccountFeeType::FlatMonthly) => Some(schedule.amount),
+            Some(AccountFeeType::AumBps) => value_on(values, date).map(|v| v * schedule.amount / 10_000.0 / 12.0),
+            None => None,
+        };
+        match amount {
+            Some(a) if a > 0.0 => fees.push((date, a)),
+            Some(_) => {}
+            None => skipped += 1,
+        }
+    }
+
+    (fees, skipped)
+}
+
+/// Fees over the next year for schedules still active on `today`
+pub fn annual_fee_estimate(schedules: &[AccountFeeSchedule], value: f64, today: NaiveDate) -> f64 {
+    schedules
+        .iter()
+        .filter(|s| s.end_date.is_none_or(|end| end >= today))
+        .map(|s| match s.fee_type() {
+            Some(AccountFeeType::AumBps) => value * s.amount / 10_000.0,
+            Some(AccountFeeType::FlatMonthly) => s.amount * 12.0,
+            None => 0.0,
+        })
+        .sum()
+}
+
+/// Grow `value` for `years` at `expected_return` percent, with and without the fees;
+/// basis-point fees are charged on the grown value each year
+pub fn project(value: f64, expected_return: f64, aum_bps: f64, flat_annual: f64, years: u32) -> NetOfFeeProjection {
+    let growth = 1.0 + expected_return / 100.0;
+    let gross_value = value * growth.powi(years as i32);
+    let mut net_value = value;
+    for _ in 0..years {
+        net_value *= growth;
+        net_value -= net_value * aum_bps / 10_000.0 + flat_annual;
+        net_value = net_value.max(0.0);
+    }
+    NetOfFeeProjection {
+        years,
+        gross_value,
+        net_value,
+        fee_drag: gross_value - net_value,
+    }
+}
+
+/// Regenerate the fee cash flows of every schedule on the account
+pub async fn generate_for_account(pool: &PgPool, account_id: Uuid) -> Result<FeeFlowGeneration, AppError> {
+    let schedules = account_fee_queries::fetch_schedules(pool, account_id).await.map_err(AppError::Db)?;
+    let values: Vec<(NaiveDate, f64)> = holding_snapshot_queries::fetch_account_value_history(pool, account_id)
+        .await
+        .map_err(AppError::Db)?
+        .into_iter()
+        .map(|h| (h.snapshot_date, h.total_value.to_f64().unwrap_or(0.0)))
+        .collect();
+    let today = clock::today();
+
+    let mut flows = Vec::new();
+    let mut result = FeeFlowGeneration::default();
+    for schedule in &schedules {
+        let (fees, skipped) = schedule_fees(schedule, &values, today);
+        result.skipped_months += skipped;
+        flows.extend(fees.into_iter().map(|(date, amount)| (schedule.id, date, amount)));
+    }
+    result.flows = flows.len();
+    // Amounts are stored rounded to cents
+    result.total_fees = flows.iter().map(|f| (f.2 * 100.0).round() / 100.0).sum();
+
+    account_fee_queries::replace_fee_flows(pool, account_id, &flows).await.map_err(AppError::Db)?;
+    Ok(result)
+}
+
+/// Gross versus net-of-fee performance and projections for the account
+pub async fn account_fee_performance(
+    pool: &PgPool,
+    account_id: Uuid,
+    expected_return: f64,
+) -> Result<AccountFeePerformance, AppError> {
+    let schedules = account_fee_queries::fetch_schedules(pool, account_id).await.map_err(AppError::Db)?;
+    let total_fees = account_fee_queries::fetch_total_fees(pool, account_id).await.map_err(AppError::Db)?;
+    let performance = detected_transaction_queries::fetch_true_performance(pool, account_id)
+        .await
+        .map_err(AppError::Db)?;
+
+    let (net_contributions, gross_value, as_of_date) = match &performance {
+        Some(p) => (
+            (p.total_deposits.to_f64().unwrap_or(0.0) - p.total_withdrawals.to_f64().unwrap_or(0.0)),
+            p.current_value.to_f64().unwrap_or(0.0),
+            p.as_of_date,
+        ),
+        None => (0.0, 0.0, None),
+    };
+    let pct = |gain: f64| if net_contributions > 0.0 { gain / net_contributions * 100.0 } else { 0.0 };
+    let gross_gain = gross_value - net_contributions;
+    let net_value = gross_value - total_fees;
+    let net_gain = net_value - net_contributions;
+
+    let today = clock::today();
+    let active: Vec<&AccountFeeSchedule> =
+        schedules.iter().filter(|s| s.end_date.is_none_or(|end| end >= today)).collect();
+    let aum_bps: f64 =
+        active.iter().filter(|s| s.fee_type() == Some(AccountFeeType::AumBps)).map(|s| s.amount).sum();
+    let flat_annual: f64 =
+        active.iter().filter(|s| s.fee_type() == Some(AccountFeeType::FlatMonthly)).map(|s| s.amount * 12.0).sum();
+
+    Ok(AccountFeePerformance {
+        account_id,
+        as_of_date,
+        net_contributions,
+        gross_value,
+        gross_gain,
+        gross_gain_pct: pct(gross_gain),
+        total_fees,
+        net_value,
+        net_gain,
+        net_gain_pct: pct(net_gain),
+        annual_fee_estimate: annual_fee_estimate(&schedules, gross_value, today),
+        assumed_return: expected_return,
+        projections: PROJECTION_YEARS
+            .iter()
+            .map(|&years| project(gross_value, expected_return, aum_bps, flat_annual, years))
+            .collect(),
+        schedules,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    fn schedule(fee_type: AccountFeeType, amount: f64, start: NaiveDate, end: Option<NaiveDate>) -> AccountFeeSchedule {
+        AccountFeeSchedule {
+            id: Uuid::new_v4(),
+            account_id: Uuid::new_v4(),
+            fee_type: fee_type.as_str().to_string(),
+            amount,
+            start_date: start,
+            end_date: end,
+            description: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_month_ends_cover_range() {
+        let ends = month_ends(d(2024, 1, 15), d(2024, 3, 31));
+        assert_eq!(ends, vec![d(2024, 1, 31), d(2024, 2, 29), d(2024, 3, 31)]);
+        assert!(month_ends(d(2024, 1, 15), d(2024, 1, 30)).is_empty());
+        assert_eq!(month_ends(d(2024, 12, 1), d(2025, 1, 31)), vec![d(2024, 12, 31), d(2025, 1, 31)]);
+    }
+
+    #[test]
+    fn test_aum_fee_uses_latest_value_and_skips_unknown_months() {
+        let values = vec![(d(2024, 2, 10), 120_000.0), (d(2024, 3, 20), 240_000.0)];
+        let s = schedule(AccountFeeType::AumBps, 100.0, d(2024, 1, 1), None);
+        let (fees, skipped) = schedule_fees(&s, &values, d(2024, 3, 31));
+
+        assert_eq!(skipped, 1);
+        assert_eq!(fees.len(), 2);
+        assert_eq!(fees[0].0, d(2024, 2, 29));
+        assert!((fees[0].1 - 100.0).abs() < 1e-9);
+        assert!((fees[1].1 - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_flat_fee_stops_at_end_date() {
+        let s = schedule(AccountFeeType::FlatMonthly, 25.0, d(2024, 1, 1), Some(d(2024, 4, 15)));
+        let (fees, skipped) = schedule_fees(&s, &[], d(2024, 12, 31));
+
+        assert_eq!(skipped, 0);
+        assert_eq!(fees.iter().map(|f| f.0).collect::<Vec<_>>(), vec![d(2024, 1, 31), d(2024, 2, 29), d(2024, 3, 31)]);
+        assert!(fees.iter().all(|f| f.1 == 25.0));
+    }
+
+    #[test]
+    fn test_projection_fee_drag() {
+        let none = project(100_000.0, 6.0, 0.0, 0.0, 10);
+        assert!(none.fee_drag.abs() < 1e-6);
+
+        let with_fees = project(100_000.0, 6.0, 100.0, 120.0, 10);
+        assert!((with_fees.gross_value - none.gross_value).abs() < 1e-6);
+        assert!(with_fees.net_value < with_fees.gross_value);
+        assert!(with_fees.fee_drag > 0.0);
+
+        let est = annual_fee_estimate(
+            &[
+                schedule(AccountFeeType::AumBps, 50.0, d(2024, 1, 1), None),
+                schedule(AccountFeeType::FlatMonthly, 10.0, d(2024, 1, 1), Some(d(2024, 6, 30))),
+            ],
+            200_000.0,
+            d(2025, 1, 1),
+        );
+        assert!((est - 1_000.0).abs() < 1e-9);
+    }
+}
@@ -2,9 +2,12 @@
 // Full implementation would integrate deeply with existing risk and sentiment services
 
 use crate::db::alert_queries::*;
-use crate::db::price_queries;
+use crate::db::{dividend_queries, holding_snapshot_queries, price_queries};
+use crate::external::price_provider::{ExternalQuote, PriceProvider};
 use crate::models::alert::*;
-use chrono::{DateTime, Duration, Utc};
+use crate::models::DividendEvent;
+use crate::services::clock;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde_json::json;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -70,7 +73,7 @@ pub async fn evaluate_alert_rule_simple(
                         (false, 0.0, format!("{}: No recent price data available", ticker), percentage)
                     }
                     Err(e) => {
-                        eprintln!("Error calculating price change for {}: {:?}", ticker, e);
+                        tracing::warn!(ticker = %ticker, error = ?e, "Failed to calculate price change");
                         (false, 0.0, format!("{}: Error fetching price data", ticker), percentage)
                     }
                 }
@@ -119,6 +122,23 @@ pub async fn evaluate_alert_rule_simple(
             let message = "No divergence detected".to_string();
             (triggered, 0.0, message, 0.0)
         }
+        AlertType::HoldingMove { percentage, gap_percentage } => {
+            // On-demand evaluation uses stored closes; the intraday job uses live quotes
+            let tickers = holding_snapshot_queries::fetch_user_held_tickers(pool, rule.user_id, rule.portfolio_id).await?;
+            let mut moves = Vec::new();
+            for ticker in tickers {
+                if let Some(change_pct) = calculate_price_change(pool, &ticker).await? {
+                    moves.push(HoldingMove { ticker, change_pct, gap_pct: None });
+                }
+            }
+            let (triggered, actual_value, message) = summarize_holding_moves(&moves, percentage, gap_percentage);
+            (triggered, actual_value, message, percentage)
+        }
+        AlertType::ExDividend { days_before } => {
+            let events = upcoming_ex_dividends(pool, rule, days_before).await?;
+            let (triggered, actual_value, message) = summarize_ex_dividends(&events, clock::today());
+            (triggered, actual_value, message, days_before as f64)
+        }
     };
 
     if triggered {
@@ -142,6 +162,190 @@ pub async fn evaluate_alert_rule_simple(
     }
 }
 
+// ==============================================================================
+// Holding Moves
+// ==============================================================================
+
+/// A held ticker's move on the day and its opening gap, in percent of the previous close
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoldingMove {
+    pub ticker: String,
+    pub change_pct: f64,
+    pub gap_pct: Option<f64>,
+}
+
+impl HoldingMove {
+    pub fn from_quote(ticker: &str, quote: &ExternalQuote) -> Option<Self> {
+        if quote.previous_close <= 0.0 {
+            return None;
+        }
+        let pct = |price: f64| (price - quote.previous_close) / quote.previous_close * 100.0;
+        Some(HoldingMove {
+            ticker: ticker.to_string(),
+            change_pct: pct(quote.price),
+            gap_pct: quote.open.map(pct),
+        })
+    }
+
+    fn breaches(&self, percentage: f64, gap_percentage: Option<f64>) -> bool {
+        let gapped = matches!((self.gap_pct, gap_percentage), (Some(gap), Some(limit)) if gap.abs() >= limit);
+        self.change_pct.abs() >= percentage || gapped
+    }
+
+    /// Largest absolute move, intraday or at the open
+    fn magnitude(&self) -> f64 {
+        self.change_pct.abs().max(self.gap_pct.map_or(0.0, f64::abs))
+    }
+}
+
+/// Latest move for a ticker from a live quote, falling back to the last two stored closes
+pub async fn latest_holding_move(
+    pool: &PgPool,
+    provider: &dyn PriceProvider,
+    ticker: &str,
+) -> Result<Option<HoldingMove>, sqlx::Error> {
+    match provider.fetch_quote(ticker).await {
+        Ok(quote) => Ok(HoldingMove::from_quote(ticker, &quote)),
+        Err(e) => {
+            tracing::warn!("Quote unavailable for {}, using stored closes: {}", ticker, e);
+            Ok(calculate_price_change(pool, ticker).await?.map(|change_pct| HoldingMove {
+                ticker: ticker.to_string(),
+                change_pct,
+                gap_pct: None,
+            }))
+        }
+    }
+}
+
+/// Whether any holding breached the thresholds, the largest breaching move and a message
+pub fn summarize_holding_moves(
+    moves: &[HoldingMove],
+    percentage: f64,
+    gap_percentage: Option<f64>,
+) -> (bool, f64, String) {
+    let mut breached: Vec<&HoldingMove> = moves.iter().filter(|m| m.breaches(percentage, gap_percentage)).collect();
+    if breached.is_empty() {
+        let largest = moves.iter().map(|m| m.magnitude()).fold(0.0, f64::max);
+        return (false, largest, format!("No holding moved more than {:.2}%", percentage));
+    }
+    breached.sort_by(|a, b| b.magnitude().total_cmp(&a.magnitude()));
+
+    let details: Vec<String> = breached
+        .iter()
+        .map(|m| match m.gap_pct {
+            Some(gap) if gap_percentage.is_some_and(|limit| gap.abs() >= limit) => {
+                format!("{} {:+.2}% (gapped {:+.2}% at the open)", m.ticker, m.change_pct, gap)
+            }
+            _ => format!("{} {:+.2}%", m.ticker, m.change_pct),
+        })
+        .collect();
+    let message = format!(
+        "{} holding(s) moved beyond the threshold ({:.2}%): {}",
+        breached.len(),
+        percentage,
+        details.join(", ")
+    );
+    (true, breached[0].magnitude(), message)
+}
+
+/// Evaluate a holding-move rule against moves computed by the caller
+pub fn holding_move_result(
+    rule: &AlertRule,
+    percentage: f64,
+    gap_percentage: Option<f64>,
+    moves: &[HoldingMove],
+) -> Option<AlertEvaluationResult> {
+    let (triggered, actual_value, message) = summarize_holding_moves(moves, percentage, gap_percentage);
+    if !triggered {
+        return None;
+    }
+    let movers: Vec<_> = moves
+        .iter()
+        .filter(|m| m.breaches(percentage, gap_percentage))
+        .map(|m| json!({ "ticker": m.ticker, "change_pct": m.change_pct, "gap_pct": m.gap_pct }))
+        .collect();
+
+    Some(AlertEvaluationResult {
+        rule_id: rule.id,
+        triggered: true,
+        actual_value,
+        threshold: percentage,
+        message,
+        severity: calculate_severity("holding_move", percentage, actual_value),
+        metadata: json!({
+            "rule_type": rule.rule_type,
+            "portfolio_id": rule.portfolio_id,
+            "movers": movers,
+        }),
+    })
+}
+
+// ==============================================================================
+// Ex-Dividend Reminders
+// ==============================================================================
+
+/// Stored dividends of the rule's held tickers going ex within `days_before` days
+pub async fn upcoming_ex_dividends(
+    pool: &PgPool,
+    rule: &AlertRule,
+    days_before: i64,
+) -> Result<Vec<DividendEvent>, sqlx::Error> {
+    let tickers = holding_snapshot_queries::fetch_user_held_tickers(pool, rule.user_id, rule.portfolio_id).await?;
+    let today = clock::today();
+    dividend_queries::fetch_upcoming(pool, &tickers, today, today + Duration::days(days_before)).await
+}
+
+/// Whether any dividend goes ex soon, days until the nearest one and a message
+pub fn summarize_ex_dividends(events: &[DividendEvent], today: NaiveDate) -> (bool, f64, String) {
+    let Some(nearest) = events.iter().map(|e| (e.ex_date - today).num_days()).min() else {
+        return (false, 0.0, "No upcoming ex-dividend dates".to_string());
+    };
+    let details: Vec<String> = events
+        .iter()
+        .map(|e| {
+            let amount = e.amount.map(|a| format!(" (${:.4}/share)", a)).unwrap_or_default();
+            format!("{} on {}{}", e.ticker, e.ex_date.format("%b %-d"), amount)
+        })
+        .collect();
+    let message = format!(
+        "{} holding(s) go ex-dividend soon: {}. Buy before the ex-date to receive the dividend.",
+        events.len(),
+        details.join(", ")
+    );
+    (true, nearest as f64, message)
+}
+
+/// Evaluate an ex-dividend rule against the dividends not yet reminded of
+pub fn ex_dividend_result(
+    rule: &AlertRule,
+    days_before: i64,
+    events: &[DividendEvent],
+    today: NaiveDate,
+) -> Option<AlertEvaluationResult> {
+    let (triggered, actual_value, message) = summarize_ex_dividends(events, today);
+    if !triggered {
+        return None;
+    }
+    let dividends: Vec<_> = events
+        .iter()
+        .map(|e| json!({ "ticker": e.ticker, "ex_date": e.ex_date, "pay_date": e.pay_date, "amount": e.amount }))
+        .collect();
+
+    Some(AlertEvaluationResult {
+        rule_id: rule.id,
+        triggered: true,
+        actual_value,
+        threshold: days_before as f64,
+        message,
+        severity: calculate_severity("ex_dividend", days_before as f64, actual_value),
+        metadata: json!({
+            "rule_type": rule.rule_type,
+            "portfolio_id": rule.portfolio_id,
+            "dividends": dividends,
+        }),
+    })
+}
+
 // ==============================================================================
 // Helper Functions
 // ==============================================================================
@@ -191,7 +395,7 @@ pub fn calculate_severity(rule_type: &str, threshold: f64, actual_value: f64) ->
     };
 
     match rule_type {
-        "price_change" => {
+        "price_change" | "holding_move" => {
             if ratio >= 2.0 {
                 AlertSeverity::Critical
             } else if ratio >= 1.5 {
@@ -230,6 +434,7 @@ pub fn calculate_severity(rule_type: &str, threshold: f64, actual_value: f64) ->
             }
         }
         "sentiment_change" => AlertSeverity::Medium,
+        "ex_dividend" => AlertSeverity::Low,
         "divergence" => AlertSeverity::High,
         _ => AlertSeverity::Medium,
     }
@@ -290,4 +495,71 @@ mod tests {
             AlertSeverity::High
         );
     }
+
+    fn holding_move(ticker: &str, change_pct: f64, gap_pct: Option<f64>) -> HoldingMove {
+        HoldingMove { ticker: ticker.to_string(), change_pct, gap_pct }
+    }
+
+    #[test]
+    fn test_holding_move_from_quote() {
+        let quote = ExternalQuote { price: 95.0, open: Some(92.0), previous_close: 100.0 };
+        let m = HoldingMove::from_quote("AAPL", &quote).unwrap();
+        assert!((m.change_pct + 5.0).abs() < 1e-9);
+        assert!((m.gap_pct.unwrap() + 8.0).abs() < 1e-9);
+
+        let no_close = ExternalQuote { price: 95.0, open: None, previous_close: 0.0 };
+        assert!(HoldingMove::from_quote("AAPL", &no_close).is_none());
+    }
+
+    #[test]
+    fn test_summarize_holding_moves() {
+        let moves = vec![
+            holding_move("AAPL", 1.0, Some(-4.0)),
+            holding_move("MSFT", -6.0, Some(-1.0)),
+            holding_move("NVDA", 2.0, None),
+        ];
+
+        // Day move only
+        let (triggered, actual, message) = summarize_holding_moves(&moves, 5.0, None);
+        assert!(triggered);
+        assert_eq!(actual, 6.0);
+        assert!(message.contains("MSFT -6.00%"));
+        assert!(!message.contains("AAPL"));
+
+        // Gap threshold catches AAPL too, ordered by magnitude
+        let (_, _, message) = summarize_holding_moves(&moves, 5.0, Some(3.0));
+        assert!(message.starts_with("2 holding(s)"));
+        assert!(message.find("MSFT").unwrap() < message.find("AAPL").unwrap());
+        assert!(message.contains("gapped -4.00% at the open"));
+
+        let (triggered, actual, _) = summarize_holding_moves(&moves, 10.0, Some(10.0));
+        assert!(!triggered);
+        assert_eq!(actual, 6.0);
+    }
+
+    fn dividend(ticker: &str, ex_date: &str, amount: Option<f64>) -> DividendEvent {
+        DividendEvent {
+            ticker: ticker.to_string(),
+            ex_date: ex_date.parse().unwrap(),
+            pay_date: None,
+            amount,
+            annual_rate: None,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_summarize_ex_dividends() {
+        let today: NaiveDate = "2026-03-09".parse().unwrap();
+        let events = vec![dividend("KO", "2026-03-12", Some(0.51)), dividend("JNJ", "2026-03-16", None)];
+
+        let (triggered, days, message) = summarize_ex_dividends(&events, today);
+        assert!(triggered);
+        assert_eq!(days, 3.0);
+        assert!(message.starts_with("2 holding(s)"));
+        assert!(message.contains("KO on Mar 12 ($0.5100/share)"));
+        assert!(message.contains("JNJ on Mar 16."));
+
+        assert!(!summarize_ex_dividends(&[], today).0);
+    }
 }
@@ -0,0 +1,180 @@
+//! Analyst rating distribution and price target consensus per ticker.
+//!
+//! Raw provider data is stored per ticker and refreshed once a day; the consensus
+//! bucket and upside are derived on read against the latest stored close.
+
+use bigdecimal::ToPrimitive;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::db::{analyst_queries, price_queries};
+use crate::errors::AppError;
+use crate::external::analyst_provider::{AnalystProvider, ExternalAnalystConsensus};
+use crate::external::price_provider::PriceProviderError;
+use crate::models::{AnalystConsensus, ConsensusRating, RatingDistribution};
+
+const CACHE_HOURS: i64 = 24;
+
+/// Mean rating on a 1 (strong buy) to 5 (strong sell) scale, weighted by analyst count
+pub fn mean_rating(ratings: &RatingDistribution) -> Option<f64> {
+    let total = ratings.total();
+    if total == 0 {
+        return None;
+    }
+    let weighted = ratings.strong_buy + 2 * ratings.buy + 3 * ratings.hold + 4 * ratings.sell + 5 * ratings.strong_sell;
+    Some(weighted as f64 / total as f64)
+}
+
+pub fn consensus_rating(mean: f64) -> ConsensusRating {
+    match mean {
+        m if m < 1.5 => ConsensusRating::StrongBuy,
+        m if m < 2.5 => ConsensusRating::Buy,
+        m if m < 3.5 => ConsensusRating::Hold,
+        m if m < 4.5 => ConsensusRating::Sell,
+        _ => ConsensusRating::StrongSell,
+    }
+}
+
+/// % move from `current_price` to `target`
+pub fn upside_pct(target: Option<f64>, current_price: Option<f64>) -> Option<f64> {
+    match (target, current_price) {
+        (Some(target), Some(price)) if price > 0.0 => Some((target - price) / price * 100.0),
+        _ => None,
+    }
+}
+
+/// Summarize raw analyst data against the current price. The provider's mean
+/// recommendation is preferred; otherwise it is derived from the distribution.
+pub fn summarize(
+    ticker: &str,
+    raw: &ExternalAnalystConsensus,
+    current_price: Option<f64>,
+    fetched_at: DateTime<Utc>,
+) -> AnalystConsensus {
+    let recommendation_mean = raw.recommendation_mean.or_else(|| mean_rating(&raw.ratings));
+    AnalystConsensus {
+        ticker: ticker.to_string(),
+        ratings: raw.ratings,
+        recommendation_mean,
+        consensus: recommendation_mean.map(consensus_rating),
+        analyst_count: raw.analyst_count,
+        target_mean: raw.target_mean,
+        target_high: raw.target_high,
+        target_low: raw.target_low,
+        current_price,
+        upside_pct: upside_pct(raw.target_mean, current_price),
+        fetched_at,
+    }
+}
+
+/// Stored analyst data for a ticker, regardless of age
+pub async fn stored(pool: &PgPool, ticker: &str) -> Result<Option<(ExternalAnalystConsensus, DateTime<Utc>)>, AppError> {
+    let Some((data, fetched_at)) = analyst_queries::fetch(pool, ticker).await? else {
+        return Ok(None);
+    };
+    match serde_json::from_value(data) {
+        Ok(raw) => Ok(Some((raw, fetched_at))),
+        Err(e) => {
+            warn!("Discarding unreadable analyst data for {}: {}", ticker, e);
+            Ok(None)
+        }
+    }
+}
+
+/// Analyst consensus for a ticker, refreshed from the provider when the stored
+/// copy is older than a day (or `force` is set). A stale copy is served if the
+/// provider fails.
+pub async fn get_consensus(
+    pool: &PgPool,
+    provider: &dyn AnalystProvider,
+    ticker: &str,
+    force: bool,
+) -> Result<AnalystConsensus, AppError> {
+    let ticker = ticker.to_uppercase();
+    let current_price = price_queries::fetch_latest(pool, &ticker)
+        .await?
+        .and_then(|p| p.close_price.to_f64());
+    let existing = stored(pool, &ticker).await?;
+
+    if let Some((raw, fetched_at)) = &existing {
+        if !force && Utc::now() - *fetched_at < Duration::hours(CACHE_HOURS) {
+            return Ok(summarize(&ticker, raw, current_price, *fetched_at));
+        }
+    }
+
+    match provider.fetch_analyst_consensus(&ticker).await {
+        Ok(raw) => {
+            let data = serde_json::to_value(&raw)
+                .map_err(|e| AppError::External(format!("Failed to serialize analyst data: {}", e)))?;
+            let fetched_at = analyst_queries::upsert(pool, &ticker, data).await?;
+            info!(
+                "Stored analyst consensus for {}: {} ratings, mean target {:?}",
+                ticker,
+                raw.ratings.total(),
+                raw.target_mean
+            );
+            Ok(summarize(&ticker, &raw, current_price, fetched_at))
+        }
+        Err(e) => match existing {
+            Some((raw, fetched_at)) => {
+                warn!("Analyst refresh failed for {}, serving data from {}: {}", ticker, fetched_at, e);
+                Ok(summarize(&ticker, &raw, current_price, fetched_at))
+            }
+            None => match e {
+                PriceProviderError::NotFound => Err(AppError::NotFound(format!("No analyst coverage for {}", ticker))),
+                other => Err(AppError::External(format!("Failed to fetch analyst data for {}: {}", ticker, other))),
+            },
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratings(strong_buy: i32, buy: i32, hold: i32, sell: i32, strong_sell: i32) -> RatingDistribution {
+        RatingDistribution { strong_buy, buy, hold, sell, strong_sell }
+    }
+
+    #[test]
+    fn test_mean_rating_weights_distribution() {
+        assert_eq!(mean_rating(&ratings(0, 0, 0, 0, 0)), None);
+        assert_eq!(mean_rating(&ratings(2, 0, 0, 0, 0)), Some(1.0));
+        let mean = mean_rating(&ratings(1, 1, 1, 1, 1)).unwrap();
+        assert!((mean - 3.0).abs() < 1e-9);
+        assert_eq!(consensus_rating(mean), ConsensusRating::Hold);
+        assert_eq!(consensus_rating(1.8), ConsensusRating::Buy);
+        assert_eq!(consensus_rating(4.9), ConsensusRating::StrongSell);
+    }
+
+    #[test]
+    fn test_summarize_computes_upside_and_downside() {
+        let raw = ExternalAnalystConsensus {
+            ratings: ratings(5, 10, 5, 0, 0),
+            target_mean: Some(120.0),
+            ..Default::default()
+        };
+        let up = summarize("AAPL", &raw, Some(100.0), Utc::now());
+        assert!((up.upside_pct.unwrap() - 20.0).abs() < 1e-9);
+        assert_eq!(up.consensus, Some(ConsensusRating::Buy));
+
+        let down = summarize("AAPL", &raw, Some(150.0), Utc::now());
+        assert!((down.upside_pct.unwrap() + 20.0).abs() < 1e-9);
+
+        let unpriced = summarize("AAPL", &raw, None, Utc::now());
+        assert_eq!(unpriced.upside_pct, None);
+    }
+
+    #[test]
+    fn test_summarize_prefers_provider_mean() {
+        let raw = ExternalAnalystConsensus {
+            ratings: ratings(10, 0, 0, 0, 0),
+            recommendation_mean: Some(2.6),
+            ..Default::default()
+        };
+        let consensus = summarize("AAPL", &raw, None, Utc::now());
+        assert_eq!(consensus.recommendation_mean, Some(2.6));
+        assert_eq!(consensus.consensus, Some(ConsensusRating::Hold));
+    }
+}
@@ -1,39 +1,76 @@
+use std::collections::BTreeMap;
+
 use crate::db;
 use crate::errors::AppError;
+use crate::external::fx_provider::FxProvider;
 use crate::models::{AllocationPoint, AnalyticsMeta, AnalyticsResponse, ChartPoint};
+use crate::services::fx_service::{self, FxRates};
 use crate::services::indicators;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-pub async fn get_analytics(pool: &PgPool, portfolio_id: Uuid) -> Result<AnalyticsResponse, AppError> {
+pub async fn get_analytics(
+    pool: &PgPool,
+    fx_provider: &dyn FxProvider,
+    portfolio_id: Uuid,
+) -> Result<AnalyticsResponse, AppError> {
     let rows = db::analytics_queries::fetch_portfolio_value_series(pool, portfolio_id).await?;
-    let values: Vec<f64> = rows.iter().map(|r| r.value).collect();
+    let allocation_rows = db::analytics_queries::fetch_currency_allocations_at_latest_date(pool, portfolio_id).await?;
+
+    // Each snapshot date is converted at that day's rates
+    let base_currency = db::fx_queries::fetch_portfolio_base_currency(pool, portfolio_id).await?;
+    let mut currencies: Vec<String> = rows.iter().map(|r| r.currency.clone()).collect();
+    currencies.push(base_currency.clone());
+    currencies.sort();
+    currencies.dedup();
+    let rates = match (rows.first(), rows.last()) {
+        (Some(first), Some(last)) if currencies.len() > 1 => {
+            fx_service::load_rates(pool, fx_provider, &currencies, first.date, last.date).await?
+        }
+        _ => FxRates::default(),
+    };
+    let convert = |value: f64, currency: &str, date| {
+        rates.convert(value, currency, &base_currency, date).unwrap_or(value)
+    };
+
+    let mut by_date: BTreeMap<chrono::NaiveDate, f64> = BTreeMap::new();
+    for r in &rows {
+        *by_date.entry(r.date).or_insert(0.0) += convert(r.value, &r.currency, r.date);
+    }
+    let values: Vec<f64> = by_date.values().copied().collect();
 
     let sma20 = indicators::sma(&values, 20);
     let ema20 = indicators::ema(&values, 20);
     let (m, b) = indicators::regression_trend(&values);
 
     // Build chart series with a single iterator pipeline
-    let series: Vec<ChartPoint> = rows
+    let series: Vec<ChartPoint> = by_date
         .iter()
         .zip(sma20.into_iter())
         .zip(ema20.into_iter())
         .enumerate()
-        .map(|(i, ((r, sma), ema))| ChartPoint {
-            date: r.date,
-            value: r.value,
+        .map(|(i, (((date, value), sma), ema))| ChartPoint {
+            date: *date,
+            value: *value,
             sma20: sma,
             ema20: ema,
             trend: Some(m * i as f64 + b),
         })
         .collect();
 
-    let allocations = compute_allocations(pool, portfolio_id).await?;
+    // Allocations are at the latest snapshot date, the end of the series
+    let latest = by_date.keys().next_back().copied().unwrap_or_default();
+    let mut allocation_values: BTreeMap<String, f64> = BTreeMap::new();
+    for r in allocation_rows {
+        *allocation_values.entry(r.ticker).or_insert(0.0) += convert(r.value, &r.currency, latest);
+    }
+    let allocations = compute_allocations(allocation_values);
 
     let meta = AnalyticsMeta {
         points: series.len(),
         start: series.first().map(|p| p.date),
         end: series.last().map(|p| p.date),
+        base_currency,
     };
 
     Ok(AnalyticsResponse {
@@ -43,18 +80,17 @@ pub async fn get_analytics(pool: &PgPool, portfolio_id: Uuid) -> Result<Analytic
     })
 }
 
-/// Keep allocation calculation separated (pure-ish mapping + DB call).
-async fn compute_allocations(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<AllocationPoint>, AppError> {
-    let rows = db::analytics_queries::fetch_allocations_at_latest_date(pool, portfolio_id).await?;
-    let total: f64 = rows.iter().map(|r| r.value).sum();
+/// Keep allocation calculation separated (pure mapping of base-currency values).
+fn compute_allocations(values: BTreeMap<String, f64>) -> Vec<AllocationPoint> {
+    let total: f64 = values.values().sum();
 
-    Ok(rows
+    values
         .into_iter()
-        .filter(|r| r.value.is_finite() && r.value > 0.0)
-        .map(|r| AllocationPoint {
-            ticker: r.ticker,
-            value: r.value,
-            weight: if total > 0.0 { r.value / total } else { 0.0 },
+        .filter(|(_, value)| value.is_finite() && *value > 0.0)
+        .map(|(ticker, value)| AllocationPoint {
+            ticker,
+            value,
+            weight: if total > 0.0 { value / total } else { 0.0 },
         })
-        .collect())
-}
\ No newline at end of file
+        .collect()
+}
@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{annotation_queries, detected_transaction_queries, holding_snapshot_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{
+    AnnotatedHolding, DetectedTransaction, LatestAccountHolding, PositionAnnotation, UpdateAnnotation,
+};
+
+const MAX_TAGS: usize = 20;
+const MAX_TAG_LEN: usize = 50;
+const MAX_NOTES_LEN: usize = 10_000;
+const MAX_THESIS_LEN: usize = 5_000;
+
+/// Normalize user-supplied tags: trimmed, lowercased, de-duplicated (first
+/// occurrence wins) and with empty entries dropped.
+pub fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || normalized.contains(&tag) {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_LEN {
+            return Err(AppError::Validation(format!(
+                "Tag '{}' exceeds {} characters",
+                tag, MAX_TAG_LEN
+            )));
+        }
+        normalized.push(tag);
+    }
+
+    if normalized.len() > MAX_TAGS {
+        return Err(AppError::Validation(format!("At most {} tags are allowed", MAX_TAGS)));
+    }
+    Ok(normalized)
+}
+
+/// Normalize a `?tag=` filter the same way stored tags are normalized
+pub fn normalize_tag_filter(tag: Option<String>) -> Option<String> {
+    tag.map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty())
+}
+
+fn validate_update(mut input: UpdateAnnotation) -> Result<UpdateAnnotation, AppError> {
+    input.tags = input.tags.map(normalize_tags).transpose()?;
+    if let Some(notes) = &input.notes {
+        if notes.chars().count() > MAX_NOTES_LEN {
+            return Err(AppError::Validation(format!(
+                "Notes exceed {} characters",
+                MAX_NOTES_LEN
+            )));
+        }
+    }
+    Ok(input)
+}
+
+/// Validate the position-only fields: positive, finite levels with the stop below the
+/// target, a trailing stop percentage between 0 and 100, and a bounded thesis.
+/// `existing` supplies levels not being changed.
+fn validate_position_levels(
+    input: &UpdateAnnotation,
+    existing: Option<&PositionAnnotation>,
+) -> Result<(), AppError> {
+    for (name, level) in [("Target price", input.target_price), ("Stop-loss", input.stop_loss)] {
+        if let Some(value) = level {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(AppError::Validation(format!("{} must be a positive number", name)));
+            }
+        }
+    }
+
+    if let Some(pct) = input.trailing_stop_pct {
+        if !pct.is_finite() || pct <= 0.0 || pct >= 100.0 {
+            return Err(AppError::Validation(
+                "Trailing stop must be a percentage between 0 and 100".to_string(),
+            ));
+        }
+    }
+
+    if !input.clear_levels {
+        let target = input.target_price.or(existing.and_then(|a| a.target_price));
+        let stop = input.stop_loss.or(existing.and_then(|a| a.stop_loss));
+        if let (Some(target), Some(stop)) = (target, stop) {
+            if stop >= target {
+                return Err(AppError::Validation(format!(
+                    "Stop-loss ({:.2}) must be below the target price ({:.2})",
+                    stop, target
+                )));
+            }
+        }
+    }
+
+    if let Some(thesis) = &input.thesis {
+        if thesis.chars().count() > MAX_THESIS_LEN {
+            return Err(AppError::Validation(format!(
+                "Thesis exceeds {} characters",
+                MAX_THESIS_LEN
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Join holdings with their annotations, keeping only holdings carrying `tag` when given
+pub fn annotate_holdings(
+    holdings: Vec<LatestAccountHolding>,
+    annotations: Vec<PositionAnnotation>,
+    tag: Option<&str>,
+) -> Vec<AnnotatedHolding> {
+    let mut by_position: HashMap<(Uuid, String), PositionAnnotation> = annotations
+        .into_iter()
+        .map(|a| ((a.account_id, a.ticker.clone()), a))
+        .collect();
+
+    holdings
+        .into_iter()
+        .map(|holding| {
+            let annotation = by_position.remove(&(holding.account_id, holding.ticker.clone()));
+            match annotation {
+                Some(a) => AnnotatedHolding {
+                    holding,
+                    trailing_stop: a.trailing_stop(),
+                    tags: a.tags,
+                    notes: a.notes,
+                    target_price: a.target_price,
+                    stop_loss: a.stop_loss,
+                    trailing_stop_pct: a.trailing_stop_pct,
+                    thesis: a.thesis,
+                    fifty_two_week: None,
+                },
+                None => AnnotatedHolding {
+                    holding,
+                    tags: Vec::new(),
+                    notes: None,
+                    target_price: None,
+                    stop_loss: None,
+                    trailing_stop_pct: None,
+                    trailing_stop: None,
+                    thesis: None,
+                    fifty_two_week: None,
+                },
+            }
+        })
+        .filter(|h| tag.is_none_or(|t| h.tags.iter().any(|ht| ht == t)))
+        .collect()
+}
+
+pub async fn set_position_annotation(
+    pool: &PgPool,
+    account_id: Uuid,
+    ticker: &str,
+    input: UpdateAnnotation,
+) -> Result<PositionAnnotation, AppError> {
+    let ticker = ticker.trim().to_uppercase();
+    if ticker.is_empty() {
+        return Err(AppError::Validation("Ticker cannot be empty".into()));
+    }
+    let input = validate_update(input)?;
+    let existing = annotation_queries::fetch_for_account(pool, account_id)
+        .await?
+        .into_iter()
+        .find(|a| a.ticker == ticker);
+    validate_position_levels(&input, existing.as_ref())?;
+
+    let annotation =
+        annotation_queries::upsert_position_annotation(pool, account_id, &ticker, &input).await?;
+    Ok(annotation)
+}
+
+pub async fn set_transaction_annotation(
+    pool: &PgPool,
+    transaction_id: Uuid,
+    input: UpdateAnnotation,
+) -> Result<DetectedTransaction, AppError> {
+    if input.target_price.is_some() || input.stop_loss.is_some() || input.thesis.is_some() || input.clear_levels {
+        return Err(AppError::Validation(
+            "Target price, stop-loss and thesis can only be set on positions".into(),
+        ));
+    }
+    let input = validate_update(input)?;
+    detected_transaction_queries::update_annotation(pool, transaction_id, input.tags.as_deref(), input.notes.as_deref())
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {} not found", transaction_id)))
+}
+
+pub async fn fetch_portfolio_holdings(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    tag: Option<&str>,
+) -> Result<Vec<AnnotatedHolding>, AppError> {
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let annotations = annotation_queries::fetch_for_portfolio(pool, portfolio_id).await?;
+    with_52_week_ranges(pool, annotate_holdings(holdings, annotations, tag)).await
+}
+
+pub async fn fetch_account_holdings(
+    pool: &PgPool,
+    account_id: Uuid,
+    tag: Option<&str>,
+) -> Result<Vec<AnnotatedHolding>, AppError> {
+    let holdings = holding_snapshot_queries::fetch_latest_holdings(pool, account_id).await?;
+    let annotations = annotation_queries::fetch_for_account(pool, account_id).await?;
+    with_52_week_ranges(pool, annotate_holdings(holdings, annotations, tag)).await
+}
+
+/// Attach each holding's 52-week range from stored prices
+async fn with_52_week_ranges(
+    pool: &PgPool,
+    mut holdings: Vec<AnnotatedHolding>,
+) -> Result<Vec<AnnotatedHolding>, AppError> {
+    let mut tickers: Vec<String> = holdings.iter().map(|h| h.holding.ticker.clone()).collect();
+    tickers.sort();
+    tickers.dedup();
+    let ranges = price_queries::fetch_52_week_range_batch(pool, &tickers).await?;
+    for holding in &mut holdings {
+        holding.fifty_two_week = ranges.get(&holding.holding.ticker).copied();
+    }
+    Ok(holdings)
+}
+
+/// Stated theses per ticker across all accounts of a portfolio, in ticker order
+pub fn theses_by_ticker(annotations: &[PositionAnnotation]) -> Vec<(String, String)> {
+    let mut theses: Vec<(String, String)> = Vec::new();
+    for annotation in annotations {
+        let Some(thesis) = annotation.thesis.as_deref().map(str::trim).filter(|t| !t.is_empty()) else {
+            continue;
+        };
+        if !theses.iter().any(|(ticker, _)| ticker == &annotation.ticker) {
+            theses.push((annotation.ticker.clone(), thesis.to_string()));
+        }
+    }
+    theses
+}
+
+/// Tags per ticker across all accounts of a portfolio (for exports)
+pub fn tags_by_ticker(annotations: &[PositionAnnotation]) -> HashMap<String, Vec<String>> {
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for annotation in annotations {
+        let entry = map.entry(annotation.ticker.clone()).or_default();
+        for tag in &annotation.tags {
+            if !entry.contains(tag) {
+                entry.push(tag.clone());
+            }
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use chrono::{NaiveDate, Utc};
+
+    fn holding(account_id: Uuid, ticker: &str) -> LatestAccountHolding {
+        LatestAccountHolding {
+            id: Uuid::new_v4(),
+            account_id,
+            account_nickname: "TFSA".to_string(),
+            account_number: "123".to_string(),
+            ticker: ticker.to_string(),
+            holding_name: None,
+            asset_category: None,
+            industry: None,
+            quantity: BigDecimal::from(10),
+            price: BigDecimal::from(100),
+            market_value: BigDecimal::from(1000),
+            gain_loss: None,
+            gain_loss_pct: None,
+            snapshot_date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            ticker_type: "equity".to_string(),
+            currency: "USD".to_string(),
+        }
+    }
+
+    fn annotation(account_id: Uuid, ticker: &str, tags: &[&str]) -> PositionAnnotation {
+        PositionAnnotation {
+            id: Uuid::new_v4(),
+            account_id,
+            ticker: ticker.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            notes: Some("Long-term hold".to_string()),
+            target_price: None,
+            stop_loss: None,
+            thesis: None,
+            trailing_stop_pct: None,
+            trailing_high: None,
+            target_alerted_at: None,
+            stop_alerted_at: None,
+            trailing_alerted_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_tags_trims_lowercases_and_dedupes() {
+        let tags = normalize_tags(vec![
+            " Core ".to_string(),
+            "core".to_string(),
+            "".to_string(),
+            "Tax-Lot-To-Sell-2025".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(tags, vec!["core", "tax-lot-to-sell-2025"]);
+    }
+
+    #[test]
+    fn test_normalize_tags_rejects_long_tag() {
+        assert!(normalize_tags(vec!["x".repeat(MAX_TAG_LEN + 1)]).is_err());
+    }
+
+    #[test]
+    fn test_annotate_holdings_filters_by_tag() {
+        let account = Uuid::new_v4();
+        let holdings = vec![holding(account, "AAPL"), holding(account, "TSLA")];
+        let annotations = vec![
+            annotation(account, "AAPL", &["core"]),
+            annotation(account, "TSLA", &["speculative"]),
+        ];
+
+        let all = annotate_holdings(holdings.clone(), annotations.clone(), None);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].tags, vec!["core"]);
+
+        let core = annotate_holdings(holdings, annotations, Some("core"));
+        assert_eq!(core.len(), 1);
+        assert_eq!(core[0].holding.ticker, "AAPL");
+    }
+
+    #[test]
+    fn test_tags_by_ticker_merges_accounts() {
+        let annotations = vec![
+            annotation(Uuid::new_v4(), "AAPL", &["core"]),
+            annotation(Uuid::new_v4(), "AAPL", &["core", "dividend"]),
+        ];
+        let map = tags_by_ticker(&annotations);
+        assert_eq!(map["AAPL"], vec!["core", "dividend"]);
+    }
+
+    #[test]
+    fn test_validate_position_levels_requires_stop_below_target() {
+        let input = UpdateAnnotation {
+            stop_loss: Some(120.0),
+            ..Default::default()
+        };
+        let mut existing = annotation(Uuid::new_v4(), "AAPL", &[]);
+        existing.target_price = Some(110.0);
+        assert!(validate_position_levels(&input, Some(&existing)).is_err());
+
+        let input = UpdateAnnotation {
+            stop_loss: Some(90.0),
+            ..Default::default()
+        };
+        assert!(validate_position_levels(&input, Some(&existing)).is_ok());
+
+        let input = UpdateAnnotation {
+            target_price: Some(-5.0),
+            ..Default::default()
+        };
+        assert!(validate_position_levels(&input, None).is_err());
+
+        let input = UpdateAnnotation {
+            trailing_stop_pct: Some(100.0),
+            ..Default::default()
+        };
+        assert!(validate_position_levels(&input, None).is_err());
+    }
+
+    #[test]
+    fn test_theses_by_ticker_skips_blank() {
+        let mut with_thesis = annotation(Uuid::new_v4(), "AAPL", &[]);
+        with_thesis.thesis = Some("Services margin expansion".to_string());
+        let mut blank = annotation(Uuid::new_v4(), "MSFT", &[]);
+        blank.thesis = Some("  ".to_string());
+        let theses = theses_by_ticker(&[with_thesis, blank]);
+        assert_eq!(theses, vec![("AAPL".to_string(), "Services margin expansion".to_string())]);
+    }
+}
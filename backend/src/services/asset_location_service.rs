@@ -0,0 +1,387 @@
+//! Asset location: which account type should hold what.
+//!
+//! Holdings are ranked by the yearly tax they cost in a taxable account (their
+//! income yield times how heavily that income is taxed). The highest-drag holdings
+//! are placed first into the account type that suits them, up to the value each
+//! account type holds today, so the suggestion never changes how much sits in each
+//! account type.
+
+use bigdecimal::ToPrimitive;
+use std::collections::HashMap;
+
+use chrono::Duration;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{account_queries, detected_transaction_queries, holding_snapshot_queries};
+use crate::errors::AppError;
+use crate::models::{
+    Account, AccountLocationSummary, AccountTaxTreatment, AssetLocationAnalysis, HoldingLocation, IncomeProfile,
+    LatestAccountHolding, LocationAmount, LocationMove,
+};
+use crate::services::clock;
+
+pub const DEFAULT_MARGINAL_TAX_RATE: f64 = 40.0;
+/// Yield at or above which an equity counts as a dividend payer, in percent
+const DIVIDEND_YIELD_PCT: f64 = 2.0;
+/// Yields assumed when no income has been recorded for a bond or REIT holding
+const ASSUMED_BOND_YIELD_PCT: f64 = 4.0;
+const ASSUMED_REIT_YIELD_PCT: f64 = 5.0;
+/// Moves smaller than this are not worth the trades
+const MIN_MOVE_VALUE: f64 = 100.0;
+
+const ALL_TREATMENTS: [AccountTaxTreatment; 3] =
+    [AccountTaxTreatment::TaxDeferred, AccountTaxTreatment::TaxFree, AccountTaxTreatment::Taxable];
+
+/// A ticker's value per account type with its income characteristics
+struct RankedHolding<'a> {
+    ticker: String,
+    holding: &'a LatestAccountHolding,
+    current: HashMap<AccountTaxTreatment, f64>,
+    profile: IncomeProfile,
+    yield_pct: f64,
+    drag_pct: f64,
+}
+
+fn has_word(text: &str, word: &str) -> bool {
+    text.split(|c: char| !c.is_ascii_alphanumeric()).any(|w| w == word)
+}
+
+/// Classify a holding from its asset category, name and industry
+pub fn income_profile(holding: &LatestAccountHolding, yield_pct: f64) -> IncomeProfile {
+    let category = holding.asset_category.as_deref().unwrap_or("").to_uppercase();
+    let text = format!(
+        "{} {}",
+        holding.holding_name.as_deref().unwrap_or(""),
+        holding.industry.as_deref().unwrap_or("")
+    )
+    .to_uppercase();
+
+    if category == "FIXED INCOME" || has_word(&text, "BOND") || has_word(&text, "BONDS") || has_word(&text, "TREASURY") {
+        IncomeProfile::Interest
+    } else if has_word(&text, "REIT") || text.contains("REAL ESTATE INVESTMENT TRUST") {
+        IncomeProfile::ReitDistributions
+    } else if yield_pct >= DIVIDEND_YIELD_PCT {
+        IncomeProfile::Dividends
+    } else {
+        IncomeProfile::Growth
+    }
+}
+
+/// Share of the income yield lost to tax at the marginal rate. Eligible/qualified
+/// dividends are taxed at roughly half the ordinary rate.
+fn income_tax_factor(profile: IncomeProfile) -> f64 {
+    match profile {
+        IncomeProfile::Interest | IncomeProfile::ReitDistributions => 1.0,
+        IncomeProfile::Dividends | IncomeProfile::Growth => 0.5,
+    }
+}
+
+/// Account types to fill, best first. Income belongs in sheltered accounts; growth
+/// is best tax-free, and in a taxable account its gains are at least deferred.
+fn preferred_treatments(profile: IncomeProfile) -> [AccountTaxTreatment; 3] {
+    match profile {
+        IncomeProfile::Growth => {
+            [AccountTaxTreatment::TaxFree, AccountTaxTreatment::Taxable, AccountTaxTreatment::TaxDeferred]
+        }
+        _ => [AccountTaxTreatment::TaxDeferred, AccountTaxTreatment::TaxFree, AccountTaxTreatment::Taxable],
+    }
+}
+
+fn amounts(by_treatment: &HashMap<AccountTaxTreatment, f64>) -> Vec<LocationAmount> {
+    ALL_TREATMENTS
+        .iter()
+        .filter_map(|t| {
+            by_treatment
+                .get(t)
+                .filter(|v| **v > 0.0)
+                .map(|v| LocationAmount { tax_treatment: *t, market_value: *v })
+        })
+        .collect()
+}
+
+/// Pair the value a holding has too much of in one account type with what it lacks in another
+fn location_moves(
+    ticker: &str,
+    current: &HashMap<AccountTaxTreatment, f64>,
+    suggested: &HashMap<AccountTaxTreatment, f64>,
+    drag_rate: f64,
+) -> Vec<LocationMove> {
+    let diff = |t: &AccountTaxTreatment| {
+        suggested.get(t).copied().unwrap_or(0.0) - current.get(t).copied().unwrap_or(0.0)
+    };
+    let mut excess: Vec<(AccountTaxTreatment, f64)> =
+        ALL_TREATMENTS.iter().filter(|t| diff(t) < 0.0).map(|t| (*t, -diff(t))).collect();
+    let mut deficit: Vec<(AccountTaxTreatment, f64)> =
+        ALL_TREATMENTS.iter().filter(|t| diff(t) > 0.0).map(|t| (*t, diff(t))).collect();
+
+    let mut moves = Vec::new();
+    for (from, available) in excess.iter_mut() {
+        for (to, needed) in deficit.iter_mut() {
+            let value = available.min(*needed);
+            if value <= 0.0 {
+                continue;
+            }
+            *available -= value;
+            *needed -= value;
+            if value < MIN_MOVE_VALUE {
+                continue;
+            }
+            let taxable = |t: AccountTaxTreatment| if t == AccountTaxTreatment::Taxable { 1.0 } else { 0.0 };
+            moves.push(LocationMove {
+                ticker: ticker.to_string(),
+                from: *from,
+                to: *to,
+                market_value: value,
+                estimated_annual_savings: value * drag_rate * (taxable(*from) - taxable(*to)),
+            });
+        }
+    }
+    moves
+}
+
+/// Suggest account types for each holding. `dividends` is trailing 12-month income
+/// per ticker; `marginal_tax_rate` is in percent.
+pub fn analyze(
+    portfolio_id: Uuid,
+    accounts: &[Account],
+    holdings: &[LatestAccountHolding],
+    dividends: &HashMap<String, f64>,
+    marginal_tax_rate: f64,
+) -> AssetLocationAnalysis {
+    let treatments: HashMap<Uuid, AccountTaxTreatment> =
+        accounts.iter().map(|a| (a.id, a.effective_tax_treatment())).collect();
+
+    let mut capacity: HashMap<AccountTaxTreatment, f64> = HashMap::new();
+    let mut account_values: HashMap<Uuid, f64> = HashMap::new();
+    // ticker -> (first holding seen, current value per account type)
+    let mut positions: HashMap<String, (&LatestAccountHolding, HashMap<AccountTaxTreatment, f64>)> = HashMap::new();
+
+    for holding in holdings {
+        let Some(treatment) = treatments.get(&holding.account_id).copied() else {
+            continue;
+        };
+        let value = holding.market_value.to_f64().unwrap_or(0.0);
+        if value <= 0.0 || holding.ticker.is_empty() {
+            continue;
+        }
+        *capacity.entry(treatment).or_default() += value;
+        *account_values.entry(holding.account_id).or_default() += value;
+        let (_, current) = positions.entry(holding.ticker.clone()).or_insert_with(|| (holding, HashMap::new()));
+        *current.entry(treatment).or_default() += value;
+    }
+
+    let rate = marginal_tax_rate / 100.0;
+    let mut ranked: Vec<RankedHolding> = positions
+        .into_iter()
+        .map(|(ticker, (holding, current))| {
+            let value: f64 = current.values().sum();
+            let recorded_yield = dividends.get(&ticker).map(|d| d / value * 100.0).unwrap_or(0.0);
+            let profile = income_profile(holding, recorded_yield);
+            let yield_pct = match profile {
+                IncomeProfile::Interest if recorded_yield == 0.0 => ASSUMED_BOND_YIELD_PCT,
+                IncomeProfile::ReitDistributions if recorded_yield == 0.0 => ASSUMED_REIT_YIELD_PCT,
+                _ => recorded_yield,
+            };
+            let drag_pct = yield_pct * income_tax_factor(profile) * rate;
+            RankedHolding { ticker, holding, current, profile, yield_pct, drag_pct }
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.drag_pct.total_cmp(&a.drag_pct).then_with(|| a.ticker.cmp(&b.ticker)));
+
+    let mut remaining = capacity.clone();
+    let mut locations = Vec::new();
+    let mut moves = Vec::new();
+    let mut current_drag = 0.0;
+    let mut optimized_drag = 0.0;
+
+    for RankedHolding { ticker, holding, current, profile, yield_pct, drag_pct } in ranked {
+        let value: f64 = current.values().sum();
+        let mut left = value;
+        let mut suggested: HashMap<AccountTaxTreatment, f64> = HashMap::new();
+        for treatment in preferred_treatments(profile) {
+            let room = remaining.entry(treatment).or_default();
+            let placed = left.min(*room);
+            if placed > 0.0 {
+                *room -= placed;
+                *suggested.entry(treatment).or_default() += placed;
+                left -= placed;
+            }
+        }
+
+        let drag_rate = drag_pct / 100.0;
+        current_drag += current.get(&AccountTaxTreatment::Taxable).copied().unwrap_or(0.0) * drag_rate;
+        optimized_drag += suggested.get(&AccountTaxTreatment::Taxable).copied().unwrap_or(0.0) * drag_rate;
+        moves.extend(location_moves(&ticker, &current, &suggested, drag_rate));
+
+        locations.push(HoldingLocation {
+            ticker,
+            holding_name: holding.holding_name.clone(),
+            income_profile: profile,
+            yield_pct,
+            tax_drag_pct: drag_pct,
+            market_value: value,
+            current: amounts(&current),
+            suggested: amounts(&suggested),
+        });
+    }
+    moves.sort_by(|a, b| b.estimated_annual_savings.total_cmp(&a.estimated_annual_savings));
+
+    let summaries: Vec<AccountLocationSummary> = accounts
+        .iter()
+        .map(|a| AccountLocationSummary {
+            account_id: a.id,
+            account_nickname: a.account_nickname.clone(),
+            tax_treatment: a.effective_tax_treatment(),
+            inferred: a.tax_treatment.is_none(),
+            market_value: account_values.get(&a.id).copied().unwrap_or(0.0),
+        })
+        .collect();
+
+    let mut notes = Vec::new();
+    let funded: Vec<_> = ALL_TREATMENTS.iter().filter(|t| capacity.get(t).copied().unwrap_or(0.0) > 0.0).collect();
+    if funded.len() < 2 {
+        notes.push("All holdings sit in accounts with the same tax treatment, so location cannot reduce tax drag.".to_string());
+    }
+    if summaries.iter().any(|s| s.inferred) {
+        notes.push(
+            "Some account tax treatments were inferred from their nicknames; set them explicitly for accurate results."
+                .to_string(),
+        );
+    }
+    if !moves.is_empty() {
+        notes.push(
+            "Moving a holding means selling it in one account and buying it in another; selling in a taxable account may realize capital gains."
+                .to_string(),
+        );
+    }
+
+    AssetLocationAnalysis {
+        portfolio_id,
+        marginal_tax_rate,
+        accounts: summaries,
+        holdings: locations,
+        moves,
+        current_annual_tax_drag: current_drag,
+        optimized_annual_tax_drag: optimized_drag,
+        estimated_annual_savings: current_drag - optimized_drag,
+        notes,
+    }
+}
+
+pub async fn portfolio_asset_location(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    marginal_tax_rate: Option<f64>,
+) -> Result<AssetLocationAnalysis, AppError> {
+    let marginal_tax_rate = marginal_tax_rate.unwrap_or(DEFAULT_MARGINAL_TAX_RATE);
+    if !(0.0..=100.0).contains(&marginal_tax_rate) {
+        return Err(AppError::Validation("marginal_tax_rate must be between 0 and 100".to_string()));
+    }
+
+    let accounts = account_queries::fetch_all(pool, portfolio_id).await?;
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let since = clock::today() - Duration::days(365);
+    let dividends: HashMap<String, f64> =
+        detected_transaction_queries::fetch_dividend_totals_for_portfolio(pool, portfolio_id, since)
+            .await?
+            .into_iter()
+            .collect();
+
+    Ok(analyze(portfolio_id, &accounts, &holdings, &dividends, marginal_tax_rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    fn account(nickname: &str) -> Account {
+        Account::new(Uuid::nil(), nickname.to_string(), nickname.to_string(), None, None)
+    }
+
+    fn holding(account: &Account, ticker: &str, name: &str, category: &str, value: f64) -> LatestAccountHolding {
+        LatestAccountHolding {
+            id: Uuid::new_v4(),
+            account_id: account.id,
+            account_nickname: account.account_nickname.clone(),
+            account_number: account.account_number.clone(),
+            ticker: ticker.to_string(),
+            holding_name: Some(name.to_string()),
+            asset_category: Some(category.to_string()),
+            industry: None,
+            quantity: BigDecimal::from(1),
+            price: BigDecimal::from_str(&value.to_string()).unwrap(),
+            market_value: BigDecimal::from_str(&value.to_string()).unwrap(),
+            gain_loss: None,
+            gain_loss_pct: None,
+            snapshot_date: chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            ticker_type: "equity".to_string(),
+            currency: "USD".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_income_profile_classification() {
+        let a = account("Investment");
+        assert_eq!(income_profile(&holding(&a, "XBB", "iShares Core Canadian Bond", "EQUITIES", 1.0), 0.0), IncomeProfile::Interest);
+        assert_eq!(income_profile(&holding(&a, "ZAG", "Aggregate", "FIXED INCOME", 1.0), 0.0), IncomeProfile::Interest);
+        assert_eq!(income_profile(&holding(&a, "REI.UN", "RioCan REIT", "EQUITIES", 1.0), 0.0), IncomeProfile::ReitDistributions);
+        assert_eq!(income_profile(&holding(&a, "ENB", "Enbridge", "EQUITIES", 1.0), 6.5), IncomeProfile::Dividends);
+        assert_eq!(income_profile(&holding(&a, "SHOP", "Shopify", "EQUITIES", 1.0), 0.0), IncomeProfile::Growth);
+    }
+
+    #[test]
+    fn test_bonds_move_to_sheltered_and_growth_to_taxable() {
+        let taxable = account("Investment Account");
+        let rrsp = account("RRSP");
+        let holdings = vec![
+            holding(&taxable, "ZAG", "BMO Aggregate Bond", "FIXED INCOME", 10_000.0),
+            holding(&rrsp, "SHOP", "Shopify", "EQUITIES", 10_000.0),
+        ];
+        let analysis = analyze(Uuid::nil(), &[taxable, rrsp], &holdings, &HashMap::new(), 40.0);
+
+        assert_eq!(analysis.holdings[0].ticker, "ZAG");
+        assert_eq!(analysis.holdings[0].suggested[0].tax_treatment, AccountTaxTreatment::TaxDeferred);
+        assert_eq!(analysis.moves.len(), 2);
+        let bond_move = analysis.moves.iter().find(|m| m.ticker == "ZAG").unwrap();
+        assert_eq!(bond_move.from, AccountTaxTreatment::Taxable);
+        assert_eq!(bond_move.to, AccountTaxTreatment::TaxDeferred);
+        // 4% assumed yield, fully taxed at 40%
+        assert!((analysis.current_annual_tax_drag - 160.0).abs() < 1e-6);
+        assert!(analysis.optimized_annual_tax_drag.abs() < 1e-6);
+        assert!((analysis.estimated_annual_savings - 160.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_single_account_type_suggests_nothing() {
+        let a = account("Margin");
+        let b = account("Cash");
+        let holdings = vec![
+            holding(&a, "ZAG", "Bond", "FIXED INCOME", 5_000.0),
+            holding(&b, "SHOP", "Shopify", "EQUITIES", 5_000.0),
+        ];
+        let analysis = analyze(Uuid::nil(), &[a, b], &holdings, &HashMap::new(), 40.0);
+        assert!(analysis.moves.is_empty());
+        assert!(analysis.estimated_annual_savings.abs() < 1e-9);
+        assert!(analysis.notes.iter().any(|n| n.contains("same tax treatment")));
+    }
+
+    #[test]
+    fn test_recorded_dividends_set_yield() {
+        let taxable = account("Joint");
+        let tfsa = account("TFSA");
+        let holdings = vec![
+            holding(&taxable, "ENB", "Enbridge", "EQUITIES", 10_000.0),
+            holding(&tfsa, "SHOP", "Shopify", "EQUITIES", 10_000.0),
+        ];
+        let dividends = HashMap::from([("ENB".to_string(), 700.0)]);
+        let analysis = analyze(Uuid::nil(), &[taxable, tfsa], &holdings, &dividends, 40.0);
+
+        let enb = analysis.holdings.iter().find(|h| h.ticker == "ENB").unwrap();
+        assert_eq!(enb.income_profile, IncomeProfile::Dividends);
+        assert!((enb.yield_pct - 7.0).abs() < 1e-9);
+        assert_eq!(enb.suggested[0].tax_treatment, AccountTaxTreatment::TaxFree);
+    }
+}
@@ -0,0 +1,121 @@
+//! Audit log of data-changing operations.
+//!
+//! Handlers record a [`NewAuditEntry`] after a change succeeds, with the entity
+//! as it was before and after. Updates also store just the fields that
+//! changed, so the log reads as a list of edits; an update that changed
+//! nothing is not recorded. Recording is best-effort: a failure is logged and
+//! never fails the change itself.
+
+use serde_json::{Map, Value};
+use sqlx::{PgExecutor, PgPool};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::{audit_queries, portfolio_member_queries};
+use crate::errors::AppError;
+use crate::models::{AuditAction, AuditLogEntry, AuditLogQuery, NewAuditEntry};
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+
+/// Bookkeeping fields that change on every write and say nothing about the edit
+const IGNORED_FIELDS: &[&str] = &["updated_at"];
+
+pub async fn record<'e>(executor: impl PgExecutor<'e>, entry: NewAuditEntry) {
+    let changes = match (entry.action, &entry.before, &entry.after) {
+        (AuditAction::Update, Some(before), Some(after)) => {
+            let changes = diff(before, after);
+            if changes.is_empty() {
+                return;
+            }
+            Some(Value::Object(changes))
+        }
+        _ => None,
+    };
+
+    if let Err(e) = audit_queries::insert(executor, &entry, changes.as_ref()).await {
+        warn!(
+            "Failed to record audit entry ({:?} {} {}): {}",
+            entry.action, entry.entity_type, entry.entity_id, e
+        );
+    }
+}
+
+/// Entries the user may see, newest first. Filtering by a portfolio requires
+/// access to it.
+pub async fn list(pool: &PgPool, user_id: Uuid, query: &AuditLogQuery) -> Result<Vec<AuditLogEntry>, AppError> {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return Err(AppError::Validation("'from' must not be after 'to'".into()));
+        }
+    }
+    if let Some(portfolio_id) = query.portfolio_id {
+        if portfolio_member_queries::role_for(pool, portfolio_id, user_id).await?.is_none() {
+            return Err(AppError::NotFound(format!("Portfolio {} not found", portfolio_id)));
+        }
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    Ok(audit_queries::fetch_visible(pool, user_id, query, limit).await?)
+}
+
+/// Fields that differ between two JSON values, as `{"field": {"before", "after"}}`.
+/// Nested objects are compared field by field, with dotted paths; arrays and
+/// scalars are compared whole.
+fn diff(before: &Value, after: &Value) -> Map<String, Value> {
+    let mut changes = Map::new();
+    diff_into(&mut changes, "", before, after);
+    changes
+}
+
+fn diff_into(changes: &mut Map<String, Value>, path: &str, before: &Value, after: &Value) {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            let keys = before.keys().chain(after.keys().filter(|k| !before.contains_key(*k)));
+            for key in keys {
+                if IGNORED_FIELDS.contains(&key.as_str()) {
+                    continue;
+                }
+                let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                diff_into(
+                    changes,
+                    &field,
+                    before.get(key).unwrap_or(&Value::Null),
+                    after.get(key).unwrap_or(&Value::Null),
+                );
+            }
+        }
+        _ if before != after => {
+            let field = if path.is_empty() { "value".to_string() } else { path.to_string() };
+            changes.insert(field, serde_json::json!({ "before": before, "after": after }));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_reports_changed_fields_only() {
+        let before = json!({"name": "Growth", "limits": {"max": 10, "min": 0}, "tags": ["a"], "updated_at": "t1"});
+        let after = json!({"name": "Growth", "limits": {"max": 15, "min": 0}, "tags": ["a", "b"], "updated_at": "t2"});
+        assert_eq!(
+            Value::Object(diff(&before, &after)),
+            json!({
+                "limits.max": {"before": 10, "after": 15},
+                "tags": {"before": ["a"], "after": ["a", "b"]},
+            })
+        );
+    }
+
+    #[test]
+    fn test_diff_of_added_and_removed_fields() {
+        let changes = diff(&json!({"old": 1}), &json!({"new": 2}));
+        assert_eq!(changes["old"], json!({"before": 1, "after": null}));
+        assert_eq!(changes["new"], json!({"before": null, "after": 2}));
+        assert!(diff(&json!({"a": 1}), &json!({"a": 1})).is_empty());
+        assert_eq!(diff(&json!(1), &json!(2))["value"], json!({"before": 1, "after": 2}));
+    }
+}
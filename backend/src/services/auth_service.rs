@@ -0,0 +1,550 @@
+//! Single sign-on through OIDC/OAuth2 providers.
+//!
+//! The login redirect ([`begin_login`]) stores a one-time state with a PKCE
+//! verifier and nonce. The provider sends the browser back to the callback,
+//! and [`complete_login`] exchanges the authorization code there. It maps the
+//! external subject to a local user: an identity already linked, else the
+//! account with the same verified email, else a new passwordless account.
+//!
+//! Google and any discovery-capable issuer (Keycloak, Authentik, ...) use
+//! OpenID Connect ID tokens. GitHub has no OIDC login, so its identity comes
+//! from the user API instead.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::{decode, decode_header, DecodingKey, Validation};
+use reqwest::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use url::Url;
+use uuid::Uuid;
+
+use crate::db::auth_queries;
+use crate::errors::AppError;
+use crate::models::alert::User;
+use crate::models::OidcProviderInfo;
+
+const GOOGLE_ISSUER: &str = "https://accounts.google.com";
+const GITHUB_API: &str = "https://api.github.com";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProviderKind {
+    /// OpenID Connect: the identity comes from the ID token
+    Oidc,
+    /// GitHub OAuth2: the identity comes from the user API
+    GitHub,
+}
+
+#[derive(Debug, Clone)]
+pub struct OidcProvider {
+    pub id: String,
+    pub name: String,
+    pub kind: ProviderKind,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Issuer whose discovery document lists the endpoints (OIDC only)
+    pub issuer: Option<String>,
+    pub authorization_endpoint: Option<String>,
+    pub token_endpoint: Option<String>,
+    pub scopes: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub providers: Vec<OidcProvider>,
+    /// Callback URL registered with every provider
+    pub redirect_url: String,
+}
+
+fn env_pair(id_var: &str, secret_var: &str) -> Option<(String, String)> {
+    let id = std::env::var(id_var).ok().filter(|v| !v.is_empty())?;
+    let secret = std::env::var(secret_var).ok().filter(|v| !v.is_empty())?;
+    Some((id, secret))
+}
+
+impl OidcConfig {
+    /// Providers with a client id and secret configured; none means SSO is off
+    pub fn from_env() -> Self {
+        let mut providers = Vec::new();
+        if let Some((client_id, client_secret)) = env_pair("OIDC_GOOGLE_CLIENT_ID", "OIDC_GOOGLE_CLIENT_SECRET") {
+            providers.push(OidcProvider {
+                id: "google".to_string(),
+                name: "Google".to_string(),
+                kind: ProviderKind::Oidc,
+                client_id,
+                client_secret,
+                issuer: Some(GOOGLE_ISSUER.to_string()),
+                authorization_endpoint: Some("https://accounts.google.com/o/oauth2/v2/auth".to_string()),
+                token_endpoint: Some("https://oauth2.googleapis.com/token".to_string()),
+                scopes: "openid email profile".to_string(),
+            });
+        }
+        if let Some((client_id, client_secret)) = env_pair("OIDC_GITHUB_CLIENT_ID", "OIDC_GITHUB_CLIENT_SECRET") {
+            providers.push(OidcProvider {
+                id: "github".to_string(),
+                name: "GitHub".to_string(),
+                kind: ProviderKind::GitHub,
+                client_id,
+                client_secret,
+                issuer: None,
+                authorization_endpoint: Some("https://github.com/login/oauth/authorize".to_string()),
+                token_endpoint: Some("https://github.com/login/oauth/access_token".to_string()),
+                scopes: "read:user user:email".to_string(),
+            });
+        }
+        if let (Some(issuer), Some((client_id, client_secret))) = (
+            std::env::var("OIDC_ISSUER_URL").ok().filter(|v| !v.is_empty()),
+            env_pair("OIDC_CLIENT_ID", "OIDC_CLIENT_SECRET"),
+        ) {
+            providers.push(OidcProvider {
+                id: "oidc".to_string(),
+                name: std::env::var("OIDC_PROVIDER_NAME").unwrap_or_else(|_| "Single sign-on".to_string()),
+                kind: ProviderKind::Oidc,
+                client_id,
+                client_secret,
+                issuer: Some(issuer.trim_end_matches('/').to_string()),
+                // Discovered at sign-in
+                authorization_endpoint: None,
+                token_endpoint: None,
+                scopes: "openid email profile".to_string(),
+            });
+        }
+
+        Self {
+            providers,
+            redirect_url: std::env::var("OIDC_REDIRECT_URL")
+                .unwrap_or_else(|_| "http://localhost:5173/api/auth/oidc/callback".to_string()),
+        }
+    }
+
+    pub fn provider(&self, id: &str) -> Result<&OidcProvider, AppError> {
+        self.providers
+            .iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| AppError::Validation(format!("Sign-in provider '{}' is not configured", id)))
+    }
+
+    pub fn provider_infos(&self) -> Vec<OidcProviderInfo> {
+        self.providers
+            .iter()
+            .map(|p| OidcProviderInfo { id: p.id.clone(), name: p.name.clone() })
+            .collect()
+    }
+}
+
+/// Who the provider says signed in
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalIdentity {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiscoveryDocument {
+    authorization_endpoint: String,
+    token_endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: EmailVerified,
+    name: Option<String>,
+}
+
+/// Some issuers send `email_verified` as a string
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum EmailVerified {
+    Bool(bool),
+    Text(String),
+    #[default]
+    Missing,
+}
+
+impl EmailVerified {
+    fn is_true(&self) -> bool {
+        match self {
+            EmailVerified::Bool(b) => *b,
+            EmailVerified::Text(s) => s.eq_ignore_ascii_case("true"),
+            EmailVerified::Missing => false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubUser {
+    id: i64,
+    name: Option<String>,
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+fn http_client() -> Client {
+    Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .user_agent("Rustfolio")
+        .build()
+        .expect("Failed to build HTTP client")
+}
+
+/// A random URL-safe token: two UUIDs of hex, 64 characters (a valid PKCE verifier)
+fn random_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// PKCE S256 challenge for a verifier (RFC 7636)
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Where to send the browser after sign-in: a local path only, so the login
+/// link cannot be used to redirect to another site
+pub fn sanitize_redirect(redirect_to: Option<&str>) -> String {
+    match redirect_to {
+        Some(path) if path.starts_with('/') && !path.starts_with("//") && !path.contains('\\') => path.to_string(),
+        _ => "/".to_string(),
+    }
+}
+
+/// Authorization and token endpoints, from the issuer's discovery document
+/// when they are not configured
+async fn endpoints(client: &Client, provider: &OidcProvider) -> Result<(String, String), AppError> {
+    if let (Some(authorization), Some(token)) = (&provider.authorization_endpoint, &provider.token_endpoint) {
+        return Ok((authorization.clone(), token.clone()));
+    }
+    let issuer = provider
+        .issuer
+        .as_deref()
+        .ok_or_else(|| AppError::External(format!("Provider {} has no endpoints", provider.id)))?;
+    let document: DiscoveryDocument = client
+        .get(format!("{}/.well-known/openid-configuration", issuer))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::External(format!("OIDC discovery failed for {}: {}", issuer, e)))?
+        .json()
+        .await
+        .map_err(|e| AppError::External(format!("Invalid OIDC discovery document from {}: {}", issuer, e)))?;
+    Ok((document.authorization_endpoint, document.token_endpoint))
+}
+
+pub fn authorization_url(
+    authorization_endpoint: &str,
+    provider: &OidcProvider,
+    redirect_url: &str,
+    state: &str,
+    code_verifier: &str,
+    nonce: &str,
+) -> Result<Url, AppError> {
+    let mut url = Url::parse(authorization_endpoint)
+        .map_err(|e| AppError::External(format!("Invalid authorization endpoint: {}", e)))?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &provider.client_id)
+            .append_pair("redirect_uri", redirect_url)
+            .append_pair("scope", &provider.scopes)
+            .append_pair("state", state)
+            .append_pair("code_challenge", &pkce_challenge(code_verifier))
+            .append_pair("code_challenge_method", "S256");
+        if provider.kind == ProviderKind::Oidc {
+            query.append_pair("nonce", nonce);
+        }
+    }
+    Ok(url)
+}
+
+/// Start a sign-in: remember its state and return the provider URL to redirect to
+pub async fn begin_login(
+    pool: &sqlx::PgPool,
+    config: &OidcConfig,
+    provider_id: &str,
+    redirect_to: Option<&str>,
+) -> Result<Url, AppError> {
+    let provider = config.provider(provider_id)?;
+    let (authorization_endpoint, _) = endpoints(&http_client(), provider).await?;
+
+    let state = random_token();
+    let code_verifier = random_token();
+    let nonce = random_token();
+    auth_queries::create_oidc_login_state(
+        pool,
+        &state,
+        &provider.id,
+        &code_verifier,
+        &nonce,
+        &sanitize_redirect(redirect_to),
+    )
+    .await?;
+
+    authorization_url(&authorization_endpoint, provider, &config.redirect_url, &state, &code_verifier, &nonce)
+}
+
+/// Read the identity from an ID token. The token came straight from the
+/// provider's token endpoint over TLS, which OIDC Core 3.1.3.7 accepts in
+/// place of checking its signature; issuer, audience, expiry and nonce are
+/// still checked.
+pub fn identity_from_id_token(
+    id_token: &str,
+    issuer: &str,
+    client_id: &str,
+    nonce: &str,
+) -> Result<ExternalIdentity, AppError> {
+    let header = decode_header(id_token).map_err(|e| AppError::External(format!("Invalid ID token: {}", e)))?;
+    let mut validation = Validation::new(header.alg);
+    validation.insecure_disable_signature_validation();
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[client_id]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    let claims = decode::<IdTokenClaims>(id_token, &DecodingKey::from_secret(&[]), &validation)
+        .map_err(|e| AppError::External(format!("ID token rejected: {}", e)))?
+        .claims;
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(AppError::External("ID token nonce does not match the sign-in".to_string()));
+    }
+
+    Ok(ExternalIdentity {
+        subject: claims.sub,
+        email: claims.email.map(|e| e.trim().to_lowercase()),
+        email_verified: claims.email_verified.is_true(),
+        name: claims.name,
+    })
+}
+
+async fn github_identity(client: &Client, access_token: &str) -> Result<ExternalIdentity, AppError> {
+    let user: GitHubUser = client
+        .get(format!("{}/user", GITHUB_API))
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::External(format!("GitHub user lookup failed: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| AppError::External(format!("Invalid GitHub user response: {}", e)))?;
+    let emails: Vec<GitHubEmail> = client
+        .get(format!("{}/user/emails", GITHUB_API))
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::External(format!("GitHub email lookup failed: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| AppError::External(format!("Invalid GitHub email response: {}", e)))?;
+
+    let primary = emails.into_iter().find(|e| e.primary && e.verified);
+    Ok(ExternalIdentity {
+        subject: user.id.to_string(),
+        email_verified: primary.is_some(),
+        email: primary.map(|e| e.email.trim().to_lowercase()),
+        name: user.name.or(Some(user.login)),
+    })
+}
+
+/// Finish a sign-in from the provider's callback: returns the local user and
+/// the path to send the browser to
+pub async fn complete_login(
+    pool: &sqlx::PgPool,
+    config: &OidcConfig,
+    code: &str,
+    state: &str,
+) -> Result<(User, String), AppError> {
+    let login = auth_queries::consume_oidc_login_state(pool, state)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+    let provider = config.provider(&login.provider)?;
+    let client = http_client();
+    let (_, token_endpoint) = endpoints(&client, provider).await?;
+
+    let tokens: TokenResponse = client
+        .post(&token_endpoint)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", config.redirect_url.as_str()),
+            ("client_id", provider.client_id.as_str()),
+            ("client_secret", provider.client_secret.as_str()),
+            ("code_verifier", login.code_verifier.as_str()),
+        ])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| AppError::External(format!("Code exchange with {} failed: {}", provider.id, e)))?
+        .json()
+        .await
+        .map_err(|e| AppError::External(format!("Invalid token response from {}: {}", provider.id, e)))?;
+
+    let identity = match provider.kind {
+        ProviderKind::Oidc => {
+            let id_token = tokens
+                .id_token
+                .as_deref()
+                .ok_or_else(|| AppError::External(format!("{} returned no ID token", provider.id)))?;
+            let issuer = provider.issuer.as_deref().unwrap_or_default();
+            identity_from_id_token(id_token, issuer, &provider.client_id, &login.nonce)?
+        }
+        ProviderKind::GitHub => github_identity(&client, &tokens.access_token).await?,
+    };
+
+    let user = resolve_user(pool, &provider.id, &identity).await?;
+    info!("User {} signed in with {}", user.id, provider.id);
+    Ok((user, login.redirect_to))
+}
+
+/// The local user for an external identity, linking or creating one on first sign-in
+async fn resolve_user(pool: &sqlx::PgPool, provider: &str, identity: &ExternalIdentity) -> Result<User, AppError> {
+    let verified_email = identity.email.as_deref().filter(|_| identity.email_verified);
+
+    if let Some(linked) = auth_queries::find_user_identity(pool, provider, &identity.subject).await? {
+        auth_queries::touch_user_identity(pool, linked.id, verified_email).await?;
+        return Ok(auth_queries::get_user(pool, linked.user_id).await?);
+    }
+
+    // An unverified address could claim someone else's account
+    let email = verified_email.ok_or_else(|| {
+        warn!("{} sign-in for subject {} has no verified email", provider, identity.subject);
+        AppError::Validation("Your account with this provider has no verified email address".to_string())
+    })?;
+
+    let user = match auth_queries::get_user_by_email(pool, email).await? {
+        Some(user) => user,
+        None => {
+            let is_first_user = auth_queries::count_non_default_users(pool).await? == 0;
+            let user = auth_queries::create_user_without_password(pool, email, identity.name.as_deref()).await?;
+
+            // Same as registering: the first real user takes over the default user's data
+            if is_first_user {
+                if let Err(e) = auth_queries::migrate_default_user_data(pool, user.id).await {
+                    warn!("Data migration for first user failed: {}", e);
+                }
+            }
+            user
+        }
+    };
+
+    auth_queries::create_user_identity(pool, user.id, provider, &identity.subject, Some(email)).await?;
+    Ok(user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    fn provider(kind: ProviderKind) -> OidcProvider {
+        OidcProvider {
+            id: "google".to_string(),
+            name: "Google".to_string(),
+            kind,
+            client_id: "client-1".to_string(),
+            client_secret: "secret".to_string(),
+            issuer: Some(GOOGLE_ISSUER.to_string()),
+            authorization_endpoint: None,
+            token_endpoint: None,
+            scopes: "openid email".to_string(),
+        }
+    }
+
+    fn id_token(claims: serde_json::Value) -> String {
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(b"unused")).unwrap()
+    }
+
+    #[test]
+    fn test_pkce_challenge_rfc7636_vector() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_authorization_url() {
+        let url = authorization_url(
+            "https://idp.example.com/authorize?prompt=select_account",
+            &provider(ProviderKind::Oidc),
+            "http://localhost:5173/api/auth/oidc/callback",
+            "st",
+            "verifier",
+            "nn",
+        )
+        .unwrap();
+        let query: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(query["prompt"], "select_account");
+        assert_eq!(query["client_id"], "client-1");
+        assert_eq!(query["redirect_uri"], "http://localhost:5173/api/auth/oidc/callback");
+        assert_eq!(query["state"], "st");
+        assert_eq!(query["code_challenge"], pkce_challenge("verifier"));
+        assert_eq!(query["nonce"], "nn");
+
+        let github = authorization_url(
+            "https://github.com/login/oauth/authorize",
+            &provider(ProviderKind::GitHub),
+            "http://localhost/cb",
+            "st",
+            "verifier",
+            "nn",
+        )
+        .unwrap();
+        assert!(!github.query_pairs().any(|(k, _)| k == "nonce"));
+    }
+
+    #[test]
+    fn test_identity_from_id_token() {
+        let exp = chrono::Utc::now().timestamp() + 600;
+        let valid = json!({
+            "iss": GOOGLE_ISSUER, "aud": "client-1", "exp": exp, "sub": "1234",
+            "nonce": "nn", "email": "Person@Example.com", "email_verified": "true", "name": "Person",
+        });
+
+        let identity = identity_from_id_token(&id_token(valid.clone()), GOOGLE_ISSUER, "client-1", "nn").unwrap();
+        assert_eq!(
+            identity,
+            ExternalIdentity {
+                subject: "1234".to_string(),
+                email: Some("person@example.com".to_string()),
+                email_verified: true,
+                name: Some("Person".to_string()),
+            }
+        );
+
+        // Replayed from another sign-in, meant for another client, or expired
+        assert!(identity_from_id_token(&id_token(valid.clone()), GOOGLE_ISSUER, "client-1", "other").is_err());
+        assert!(identity_from_id_token(&id_token(valid.clone()), GOOGLE_ISSUER, "client-2", "nn").is_err());
+        let mut expired = valid;
+        expired["exp"] = json!(exp - 7200);
+        assert!(identity_from_id_token(&id_token(expired), GOOGLE_ISSUER, "client-1", "nn").is_err());
+    }
+
+    #[test]
+    fn test_sanitize_redirect() {
+        assert_eq!(sanitize_redirect(Some("/portfolios/1")), "/portfolios/1");
+        assert_eq!(sanitize_redirect(None), "/");
+        assert_eq!(sanitize_redirect(Some("https://evil.example")), "/");
+        assert_eq!(sanitize_redirect(Some("//evil.example")), "/");
+        assert_eq!(sanitize_redirect(Some("/\\evil.example")), "/");
+    }
+}
@@ -0,0 +1,453 @@
+//! Behavioral analytics of the transaction ledger.
+//!
+//! Looks at how a portfolio is traded rather than what it holds: how much of it
+//! turns over each year, how long positions are kept, how often sales close at a
+//! gain, and whether buys and sells were well timed — where the price sat in its
+//! trailing range and what the stock did in the weeks after.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bigdecimal::ToPrimitive;
+use chrono::{Datelike, Duration, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{detected_transaction_queries, holding_snapshot_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{
+    AccountValueHistory, BehaviorInsights, HoldingPeriodStats, Locale, RealizedLot, TaxLot, TimingStats,
+    TradeTiming, WinRateStats, YearTurnover,
+};
+use crate::services::lot_service::{self, LotTrade, TradeSide};
+use crate::services::{clock, i18n};
+
+/// Calendar days of closes before a trade that form its trailing range
+const RANGE_LOOKBACK_DAYS: i64 = 90;
+/// Range percentile at or above which a buy counts as "high", and at or below
+/// which (mirrored) a sell counts as "low"
+const MISTIMED_PERCENTILE: f64 = 80.0;
+const HIGH_TURNOVER_PCT: f64 = 100.0;
+const SHORT_HOLDING_DAYS: f64 = 90.0;
+const LOW_WIN_RATE_PCT: f64 = 40.0;
+/// Closed trades needed before the win rate is worth commenting on
+const MIN_CLOSED_TRADES: usize = 5;
+/// Losers held this many times longer than winners signals the disposition effect
+const DISPOSITION_RATIO: f64 = 1.5;
+const GOOD_TIMING_SCORE: f64 = 60.0;
+const POOR_TIMING_SCORE: f64 = 40.0;
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(s, n), v| (s + v, n + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+fn weighted_mean(pairs: impl Iterator<Item = (f64, f64)>) -> Option<f64> {
+    let (sum, weight) = pairs.fold((0.0, 0.0), |(s, w), (value, weight)| (s + value * weight, w + weight));
+    (weight > 0.0).then(|| sum / weight)
+}
+
+/// Total portfolio value per date, carrying each account's last recorded value forward
+pub fn portfolio_values(history: &[AccountValueHistory]) -> Vec<(NaiveDate, f64)> {
+    let mut by_date: BTreeMap<NaiveDate, Vec<(Uuid, f64)>> = BTreeMap::new();
+    for row in history {
+        by_date
+            .entry(row.snapshot_date)
+            .or_default()
+            .push((row.account_id, row.total_value.to_f64().unwrap_or(0.0)));
+    }
+    let mut latest: HashMap<Uuid, f64> = HashMap::new();
+    by_date
+        .into_iter()
+        .map(|(date, rows)| {
+            latest.extend(rows);
+            (date, latest.values().sum())
+        })
+        .collect()
+}
+
+/// Purchases, sales and turnover per calendar year. `trades` should hold
+/// discretionary trades only: no opening balances or reinvested dividends.
+pub fn annual_turnover(trades: &[LotTrade], values: &[(NaiveDate, f64)]) -> Vec<YearTurnover> {
+    let mut years: BTreeMap<i32, (f64, f64)> = BTreeMap::new();
+    for trade in trades {
+        let flows = years.entry(trade.date.year()).or_default();
+        match trade.side {
+            TradeSide::Buy => flows.0 += trade.quantity * trade.price,
+            TradeSide::Sell => flows.1 += trade.quantity * trade.price,
+        }
+    }
+    years
+        .into_iter()
+        .map(|(year, (purchases, sales))| {
+            let average_value = mean(values.iter().filter(|(d, _)| d.year() == year).map(|(_, v)| *v))
+                .filter(|v| *v > 0.0);
+            YearTurnover {
+                year,
+                purchases,
+                sales,
+                average_value,
+                turnover_pct: average_value.map(|v| purchases.min(sales) / v * 100.0),
+            }
+        })
+        .collect()
+}
+
+pub fn holding_periods(realized: &[RealizedLot], open_lots: &[TaxLot], today: NaiveDate) -> HoldingPeriodStats {
+    HoldingPeriodStats {
+        average_realized_days: weighted_mean(realized.iter().map(|l| (l.holding_days as f64, l.cost_basis))),
+        average_open_days: weighted_mean(open_lots.iter().map(|l| {
+            ((today - l.acquired_date).num_days() as f64, l.quantity * l.cost_per_share)
+        })),
+    }
+}
+
+/// Win rate over sales; the lots a sale closed are netted into one trade
+pub fn win_rate(realized: &[RealizedLot]) -> WinRateStats {
+    let mut gains: HashMap<Uuid, f64> = HashMap::new();
+    for lot in realized {
+        *gains.entry(lot.sell_transaction_id).or_default() += lot.gain;
+    }
+    let wins: Vec<f64> = gains.values().copied().filter(|g| *g > 0.0).collect();
+    let losses: Vec<f64> = gains.values().copied().filter(|g| *g <= 0.0).collect();
+    let days = |winners: bool| {
+        weighted_mean(
+            realized
+                .iter()
+                .filter(|l| (gains[&l.sell_transaction_id] > 0.0) == winners)
+                .map(|l| (l.holding_days as f64, l.cost_basis)),
+        )
+    };
+
+    WinRateStats {
+        closed_trades: gains.len(),
+        winning_trades: wins.len(),
+        win_rate: (!gains.is_empty()).then(|| wins.len() as f64 / gains.len() as f64 * 100.0),
+        average_gain: mean(wins.iter().copied()),
+        average_loss: mean(losses.iter().map(|l| -l)),
+        average_winner_days: days(true),
+        average_loser_days: days(false),
+    }
+}
+
+/// Judge a trade against `closes` (oldest first) of its ticker. The forward
+/// return is left out until a close at least `forward_days` after the trade exists.
+pub fn trade_timing(trade: &LotTrade, closes: &[(NaiveDate, f64)], forward_days: i64) -> Option<TradeTiming> {
+    let transaction_id = trade.transaction_id?;
+    let range_start = trade.date - Duration::days(RANGE_LOOKBACK_DAYS);
+    let trailing: Vec<f64> = closes
+        .iter()
+        .filter(|(d, _)| *d >= range_start && *d <= trade.date)
+        .map(|(_, c)| *c)
+        .collect();
+    let low = trailing.iter().copied().reduce(f64::min);
+    let high = trailing.iter().copied().reduce(f64::max);
+    let range_percentile = match (low, high) {
+        (Some(low), Some(high)) if high > low => Some(((trade.price - low) / (high - low) * 100.0).clamp(0.0, 100.0)),
+        _ => None,
+    };
+
+    let forward_date = trade.date + Duration::days(forward_days);
+    let forward_close = closes
+        .last()
+        .filter(|(latest, _)| *latest >= forward_date)
+        .and_then(|_| closes.iter().rev().find(|(d, _)| *d <= forward_date))
+        .map(|(_, c)| *c);
+    let forward_return = forward_close
+        .filter(|_| trade.price > 0.0)
+        .map(|close| (close / trade.price - 1.0) * 100.0);
+
+    let is_buy = trade.side == TradeSide::Buy;
+    Some(TradeTiming {
+        transaction_id,
+        ticker: trade.ticker.clone(),
+        side: if is_buy { "BUY" } else { "SELL" }.to_string(),
+        date: trade.date,
+        price: trade.price,
+        range_percentile,
+        forward_return,
+        well_timed: forward_return.map(|r| if is_buy { r > 0.0 } else { r < 0.0 }),
+    })
+}
+
+pub fn timing_stats(timings: &[&TradeTiming]) -> TimingStats {
+    let judged: Vec<bool> = timings.iter().filter_map(|t| t.well_timed).collect();
+    let mistimed = timings
+        .iter()
+        .filter(|t| match (t.range_percentile, t.well_timed) {
+            (Some(p), Some(false)) if t.side == "BUY" => p >= MISTIMED_PERCENTILE,
+            (Some(p), Some(false)) => p <= 100.0 - MISTIMED_PERCENTILE,
+            _ => false,
+        })
+        .count();
+
+    TimingStats {
+        trades: timings.len(),
+        judged: judged.len(),
+        average_range_percentile: mean(timings.iter().filter_map(|t| t.range_percentile)),
+        average_forward_return: mean(timings.iter().filter_map(|t| t.forward_return)),
+        well_timed_pct: mean(judged.iter().map(|w| if *w { 100.0 } else { 0.0 })),
+        mistimed,
+    }
+}
+
+/// Findings worth pointing out, in `locale`
+pub fn build_insights(locale: Locale, analysis: &BehaviorInsights) -> Vec<String> {
+    let number = |value: f64| i18n::format_decimal(locale, value, 0);
+    let mut insights = Vec::new();
+    if analysis.annual_turnover.is_empty() && analysis.win_rate.closed_trades == 0 {
+        insights.push(i18n::message(locale, "behavior.no_trades", &[]));
+        return insights;
+    }
+
+    if let Some(turnover) = analysis.average_turnover_pct.filter(|t| *t > HIGH_TURNOVER_PCT) {
+        insights.push(i18n::message(locale, "behavior.turnover_high", &[("turnover", &number(turnover))]));
+    }
+    if let Some(days) = analysis.holding_period.average_realized_days.filter(|d| *d < SHORT_HOLDING_DAYS) {
+        insights.push(i18n::message(locale, "behavior.short_holding", &[("days", &number(days))]));
+    }
+
+    let wins = &analysis.win_rate;
+    if wins.closed_trades >= MIN_CLOSED_TRADES {
+        if let Some(rate) = wins.win_rate.filter(|r| *r < LOW_WIN_RATE_PCT) {
+            insights.push(i18n::message(locale, "behavior.low_win_rate", &[("win_rate", &number(rate))]));
+        }
+        if let (Some(winner), Some(loser)) = (wins.average_winner_days, wins.average_loser_days) {
+            if winner > 0.0 && loser >= winner * DISPOSITION_RATIO {
+                insights.push(i18n::message(
+                    locale,
+                    "behavior.disposition_effect",
+                    &[("loser_days", &number(loser)), ("winner_days", &number(winner))],
+                ));
+            }
+        }
+    }
+
+    if analysis.buy_timing.mistimed > 0 {
+        insights.push(i18n::message(
+            locale,
+            "behavior.bought_high",
+            &[("count", &analysis.buy_timing.mistimed.to_string())],
+        ));
+    }
+    if analysis.sell_timing.mistimed > 0 {
+        insights.push(i18n::message(
+            locale,
+            "behavior.sold_low",
+            &[("count", &analysis.sell_timing.mistimed.to_string())],
+        ));
+    }
+    match analysis.timing_score {
+        Some(score) if score >= GOOD_TIMING_SCORE => {
+            insights.push(i18n::message(locale, "behavior.timing_good", &[("score", &number(score))]));
+        }
+        Some(score) if score < POOR_TIMING_SCORE => {
+            insights.push(i18n::message(locale, "behavior.timing_poor", &[("score", &number(score))]));
+        }
+        _ => {}
+    }
+    insights
+}
+
+/// Behavioral analysis of a portfolio's trades, with findings in `locale`
+pub async fn analyze_portfolio(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    forward_days: i64,
+    locale: Locale,
+) -> Result<BehaviorInsights, AppError> {
+    if !(1..=365).contains(&forward_days) {
+        return Err(AppError::Validation("forward_days must be between 1 and 365".to_string()));
+    }
+    let today = clock::today();
+
+    let ledger = lot_service::build_ledger(
+        lot_service::load_portfolio_trades(pool, portfolio_id).await?,
+        &lot_service::load_lot_matching(pool, portfolio_id).await?,
+    );
+    // Reinvested dividends aren't decisions
+    let trades: Vec<LotTrade> = detected_transaction_queries::fetch_trades_for_portfolio(pool, portfolio_id)
+        .await?
+        .iter()
+        .filter(|tx| tx.transaction_type != "DRIP")
+        .filter_map(lot_service::transaction_trade)
+        .filter(|t| t.price > 0.0)
+        .collect();
+    let values = portfolio_values(&holding_snapshot_queries::fetch_portfolio_value_history(pool, portfolio_id).await?);
+
+    let mut first_trade: HashMap<&str, NaiveDate> = HashMap::new();
+    for trade in &trades {
+        let date = first_trade.entry(trade.ticker.as_str()).or_insert(trade.date);
+        *date = (*date).min(trade.date);
+    }
+    let mut closes: HashMap<&str, Vec<(NaiveDate, f64)>> = HashMap::new();
+    for (ticker, first) in first_trade {
+        let points =
+            price_queries::fetch_range(pool, ticker, first - Duration::days(RANGE_LOOKBACK_DAYS), today).await?;
+        closes.insert(
+            ticker,
+            points.into_iter().filter_map(|p| p.close_price.to_f64().map(|c| (p.date, c))).collect(),
+        );
+    }
+    let timings: Vec<TradeTiming> = trades
+        .iter()
+        .filter_map(|t| trade_timing(t, closes.get(t.ticker.as_str()).map(Vec::as_slice).unwrap_or_default(), forward_days))
+        .collect();
+
+    let buys: Vec<&TradeTiming> = timings.iter().filter(|t| t.side == "BUY").collect();
+    let sells: Vec<&TradeTiming> = timings.iter().filter(|t| t.side == "SELL").collect();
+    let annual_turnover = annual_turnover(&trades, &values);
+
+    let mut analysis = BehaviorInsights {
+        portfolio_id,
+        forward_days,
+        average_turnover_pct: mean(annual_turnover.iter().filter_map(|y| y.turnover_pct)),
+        annual_turnover,
+        holding_period: holding_periods(&ledger.realized, &ledger.open_lots, today),
+        win_rate: win_rate(&ledger.realized),
+        buy_timing: timing_stats(&buys),
+        sell_timing: timing_stats(&sells),
+        timing_score: mean(timings.iter().filter_map(|t| t.well_timed).map(|w| if w { 100.0 } else { 0.0 })),
+        trades: timings,
+        insights: Vec::new(),
+    };
+    analysis.insights = build_insights(locale, &analysis);
+    Ok(analysis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+    }
+
+    fn trade(side: TradeSide, on: NaiveDate, quantity: f64, price: f64) -> LotTrade {
+        LotTrade {
+            account_id: Uuid::nil(),
+            ticker: "AAPL".to_string(),
+            date: on,
+            side,
+            quantity,
+            price,
+            transaction_id: Some(Uuid::new_v4()),
+        }
+    }
+
+    fn realized(sell: Uuid, gain: f64, holding_days: i64) -> RealizedLot {
+        RealizedLot {
+            account_id: Uuid::nil(),
+            ticker: "AAPL".to_string(),
+            acquired_date: date(1, 2),
+            sold_date: date(1, 2) + Duration::days(holding_days),
+            quantity: 10.0,
+            cost_basis: 1000.0,
+            proceeds: 1000.0 + gain,
+            gain,
+            holding_days,
+            long_term: holding_days > 365,
+            sell_transaction_id: sell,
+            lot_transaction_id: None,
+        }
+    }
+
+    #[test]
+    fn test_turnover_uses_carried_forward_values() {
+        let account = |id: u128, on: NaiveDate, value: i64| AccountValueHistory {
+            account_id: Uuid::from_u128(id),
+            snapshot_date: on,
+            total_value: BigDecimal::from(value),
+            total_cost: BigDecimal::from(value),
+            total_gain_loss: None,
+            total_gain_loss_pct: None,
+            source: "import".to_string(),
+        };
+        let values = portfolio_values(&[
+            account(1, date(1, 31), 6_000),
+            account(2, date(1, 31), 4_000),
+            account(1, date(2, 28), 8_000),
+        ]);
+        assert_eq!(values, vec![(date(1, 31), 10_000.0), (date(2, 28), 12_000.0)]);
+
+        let trades = vec![
+            trade(TradeSide::Buy, date(1, 5), 30.0, 100.0),
+            trade(TradeSide::Sell, date(3, 5), 20.0, 110.0),
+        ];
+        let years = annual_turnover(&trades, &values);
+        assert_eq!(years.len(), 1);
+        assert_eq!(years[0].average_value, Some(11_000.0));
+        // min(3,000 bought, 2,200 sold) / 11,000
+        assert!((years[0].turnover_pct.unwrap() - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_win_rate_nets_lots_per_sale() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let stats = win_rate(&[
+            realized(a, 150.0, 20),
+            realized(a, -50.0, 20),
+            realized(b, -200.0, 200),
+            realized(c, 80.0, 40),
+        ]);
+        assert_eq!(stats.closed_trades, 3);
+        assert_eq!(stats.winning_trades, 2);
+        assert!((stats.win_rate.unwrap() - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.average_loss, Some(200.0));
+        assert_eq!(stats.average_winner_days, Some(80.0 / 3.0));
+        assert_eq!(stats.average_loser_days, Some(200.0));
+    }
+
+    #[test]
+    fn test_trade_timing_flags_buying_high() {
+        let closes: Vec<(NaiveDate, f64)> = vec![
+            (date(1, 5), 90.0),
+            (date(1, 20), 100.0),
+            (date(2, 2), 110.0),
+            (date(3, 2), 99.0),
+            (date(3, 10), 95.0),
+        ];
+        let buy = trade(TradeSide::Buy, date(2, 2), 10.0, 110.0);
+        let timing = trade_timing(&buy, &closes, 30).unwrap();
+        assert_eq!(timing.range_percentile, Some(100.0));
+        assert!((timing.forward_return.unwrap() + 10.0).abs() < 1e-9);
+        assert_eq!(timing.well_timed, Some(false));
+
+        let sell = trade(TradeSide::Sell, date(3, 2), 10.0, 99.0);
+        let pending = trade_timing(&sell, &closes, 30).unwrap();
+        assert_eq!(pending.forward_return, None);
+
+        let stats = timing_stats(&[&timing, &pending]);
+        assert_eq!(stats.judged, 1);
+        assert_eq!(stats.mistimed, 1);
+        assert_eq!(stats.well_timed_pct, Some(0.0));
+    }
+
+    #[test]
+    fn test_insights_in_locale() {
+        let analysis = BehaviorInsights {
+            portfolio_id: Uuid::nil(),
+            forward_days: 30,
+            annual_turnover: vec![YearTurnover {
+                year: 2026,
+                purchases: 18_000.0,
+                sales: 18_000.0,
+                average_value: Some(10_000.0),
+                turnover_pct: Some(180.0),
+            }],
+            average_turnover_pct: Some(180.0),
+            holding_period: HoldingPeriodStats { average_realized_days: Some(45.0), average_open_days: None },
+            win_rate: WinRateStats::default(),
+            buy_timing: TimingStats { mistimed: 2, ..Default::default() },
+            sell_timing: TimingStats::default(),
+            timing_score: Some(50.0),
+            trades: Vec::new(),
+            insights: Vec::new(),
+        };
+        let insights = build_insights(Locale::En, &analysis);
+        assert_eq!(insights.len(), 3);
+        assert!(insights[0].contains("180%"));
+        assert!(insights[1].contains("45 days"));
+        assert!(build_insights(Locale::Fr, &analysis)[2].contains('2'));
+    }
+}
@@ -0,0 +1,252 @@
+//! Constituent-level benchmark comparison ("what the index did").
+//!
+//! Each holding's return over the period is compared with the SPDR sector ETF for
+//! its industry and with the portfolio benchmark. Contribution is weight × return
+//! using current market-value weights, which ranks holdings into leaders and
+//! laggards; the narrative uses those as its top contributors.
+
+use std::collections::HashMap;
+
+use bigdecimal::ToPrimitive;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{holding_snapshot_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{BenchmarkComparison, HoldingComparison, PricePoint};
+
+const LEADER_COUNT: usize = 5;
+
+/// Industry keywords mapped to SPDR sector ETFs; the first match wins
+const SECTOR_ETFS: &[(&[&str], &str)] = &[
+    (&["semiconductor", "software", "technology", "computer", "electronic", "it services"], "XLK"),
+    (&["bank", "insurance", "financial", "capital markets", "asset management", "credit"], "XLF"),
+    (&["oil", "gas", "energy", "petroleum", "coal"], "XLE"),
+    (&["pharma", "biotech", "health", "medical", "drug", "life sciences"], "XLV"),
+    (&["utilit", "electric", "water"], "XLU"),
+    (&["reit", "real estate"], "XLRE"),
+    (&["telecom", "media", "entertainment", "interactive", "communication"], "XLC"),
+    (&["beverage", "food", "household", "tobacco", "personal products", "consumer staples"], "XLP"),
+    (&["retail", "auto", "apparel", "restaurant", "leisure", "hotel", "consumer discretionary"], "XLY"),
+    (&["chemical", "metal", "mining", "steel", "paper", "materials"], "XLB"),
+    (&["aerospace", "defense", "machinery", "industrial", "transport", "airline", "construction"], "XLI"),
+];
+
+/// Sector ETF for a holding's industry, if it maps to one
+pub fn sector_etf_for_industry(industry: &str) -> Option<&'static str> {
+    let industry = industry.to_lowercase();
+    SECTOR_ETFS
+        .iter()
+        .find(|(keywords, _)| keywords.iter().any(|k| industry.contains(k)))
+        .map(|(_, etf)| *etf)
+}
+
+/// Percent return from the first to the last close of an ascending price series
+pub fn period_return(points: &[PricePoint]) -> Option<f64> {
+    let first = points.first()?.close_price.to_f64()?;
+    let last = points.last()?.close_price.to_f64()?;
+    if points.len() < 2 || first <= 0.0 {
+        return None;
+    }
+    Some((last / first - 1.0) * 100.0)
+}
+
+/// A held position: (ticker, industry, market value)
+pub type HeldPosition = (String, Option<String>, f64);
+
+/// Build the comparison from positions and per-ticker period returns
+pub fn build_comparison(
+    portfolio_id: Uuid,
+    days: i64,
+    benchmark: &str,
+    positions: &[HeldPosition],
+    returns: &HashMap<String, f64>,
+) -> BenchmarkComparison {
+    let benchmark_return = returns.get(benchmark).copied();
+    let total_value: f64 = positions
+        .iter()
+        .filter(|(ticker, _, _)| returns.contains_key(ticker))
+        .map(|(_, _, value)| value)
+        .sum();
+
+    let mut holdings = Vec::new();
+    let mut missing_prices = Vec::new();
+    for (ticker, industry, value) in positions {
+        let Some(&holding_return) = returns.get(ticker) else {
+            missing_prices.push(ticker.clone());
+            continue;
+        };
+        let weight = if total_value > 0.0 { value / total_value } else { 0.0 };
+        let sector_etf = industry.as_deref().and_then(sector_etf_for_industry);
+        let sector_return = sector_etf.and_then(|etf| returns.get(etf).copied());
+
+        holdings.push(HoldingComparison {
+            ticker: ticker.clone(),
+            industry: industry.clone(),
+            sector_etf: sector_etf.map(String::from),
+            weight,
+            holding_return,
+            sector_return,
+            benchmark_return,
+            excess_vs_sector: sector_return.map(|r| holding_return - r),
+            excess_vs_benchmark: benchmark_return.map(|r| holding_return - r),
+            contribution: weight * holding_return,
+        });
+    }
+
+    holdings.sort_by(|a, b| b.contribution.total_cmp(&a.contribution));
+    let portfolio_return = holdings.iter().map(|h| h.contribution).sum();
+
+    let leaders = holdings
+        .iter()
+        .filter(|h| h.contribution > 0.0)
+        .take(LEADER_COUNT)
+        .cloned()
+        .collect();
+    let laggards = holdings
+        .iter()
+        .rev()
+        .filter(|h| h.contribution < 0.0)
+        .take(LEADER_COUNT)
+        .cloned()
+        .collect();
+
+    BenchmarkComparison {
+        portfolio_id,
+        days,
+        benchmark: benchmark.to_string(),
+        benchmark_return,
+        portfolio_return,
+        holdings,
+        leaders,
+        laggards,
+        missing_prices,
+    }
+}
+
+/// Compare a portfolio's current holdings against their sector ETFs and `benchmark`
+pub async fn compare_portfolio(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    days: i64,
+    benchmark: &str,
+) -> Result<BenchmarkComparison, AppError> {
+    if !(2..=3650).contains(&days) {
+        return Err(AppError::Validation("days must be between 2 and 3650".to_string()));
+    }
+    let benchmark = benchmark.trim().to_uppercase();
+
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+
+    // Aggregate across accounts; cash rows have no ticker
+    let mut positions: Vec<HeldPosition> = Vec::new();
+    for holding in holdings.iter().filter(|h| !h.ticker.is_empty()) {
+        let value = holding.market_value.to_f64().unwrap_or(0.0);
+        match positions.iter_mut().find(|(t, _, _)| *t == holding.ticker) {
+            Some(position) => {
+                position.2 += value;
+                if position.1.is_none() {
+                    position.1 = holding.industry.clone();
+                }
+            }
+            None => positions.push((holding.ticker.clone(), holding.industry.clone(), value)),
+        }
+    }
+
+    let mut tickers: Vec<String> = positions.iter().map(|(t, _, _)| t.clone()).collect();
+    tickers.extend(
+        positions
+            .iter()
+            .filter_map(|(_, industry, _)| industry.as_deref().and_then(sector_etf_for_industry))
+            .map(String::from),
+    );
+    tickers.push(benchmark.clone());
+    tickers.sort();
+    tickers.dedup();
+
+    let windows = price_queries::fetch_window_batch(pool, &tickers, days).await?;
+    let returns: HashMap<String, f64> = windows
+        .iter()
+        .filter_map(|(ticker, points)| period_return(points).map(|r| (ticker.clone(), r)))
+        .collect();
+
+    Ok(build_comparison(portfolio_id, days, &benchmark, &positions, &returns))
+}
+
+/// Leaders and laggards as prompt lines for the narrative's top contributors
+pub fn contributor_lines(comparison: &BenchmarkComparison) -> Vec<String> {
+    comparison
+        .leaders
+        .iter()
+        .chain(comparison.laggards.iter())
+        .map(|h| {
+            let mut line = format!(
+                "{}: {:+.2} pts of portfolio return ({:+.1}% return, {:.1}% weight)",
+                h.ticker,
+                h.contribution,
+                h.holding_return,
+                h.weight * 100.0
+            );
+            if let (Some(etf), Some(r)) = (&h.sector_etf, h.sector_return) {
+                line.push_str(&format!(", sector {} {:+.1}%", etf, r));
+            }
+            if let Some(r) = h.benchmark_return {
+                line.push_str(&format!(", {} {:+.1}%", comparison.benchmark, r));
+            }
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sector_etf_for_industry() {
+        assert_eq!(sector_etf_for_industry("Semiconductors"), Some("XLK"));
+        assert_eq!(sector_etf_for_industry("Regional Banks"), Some("XLF"));
+        assert_eq!(sector_etf_for_industry("Oil & Gas E&P"), Some("XLE"));
+        assert_eq!(sector_etf_for_industry("Unknown"), None);
+    }
+
+    #[test]
+    fn test_build_comparison_leaders_and_laggards() {
+        let positions = vec![
+            ("AAPL".to_string(), Some("Technology Hardware".to_string()), 6000.0),
+            ("XOM".to_string(), Some("Oil & Gas".to_string()), 4000.0),
+            ("NEW".to_string(), None, 1000.0),
+        ];
+        let returns: HashMap<String, f64> = [
+            ("AAPL", 10.0),
+            ("XOM", -5.0),
+            ("XLK", 8.0),
+            ("XLE", -2.0),
+            ("SPY", 3.0),
+        ]
+        .into_iter()
+        .map(|(t, r)| (t.to_string(), r))
+        .collect();
+
+        let c = build_comparison(Uuid::nil(), 90, "SPY", &positions, &returns);
+
+        assert_eq!(c.missing_prices, vec!["NEW".to_string()]);
+        assert_eq!(c.holdings.len(), 2);
+        assert!((c.portfolio_return - 4.0).abs() < 1e-9);
+
+        assert_eq!(c.leaders.len(), 1);
+        assert_eq!(c.leaders[0].ticker, "AAPL");
+        assert!((c.leaders[0].contribution - 6.0).abs() < 1e-9);
+        assert_eq!(c.leaders[0].excess_vs_sector, Some(2.0));
+        assert_eq!(c.leaders[0].excess_vs_benchmark, Some(7.0));
+
+        assert_eq!(c.laggards.len(), 1);
+        assert_eq!(c.laggards[0].ticker, "XOM");
+        assert_eq!(c.laggards[0].sector_etf.as_deref(), Some("XLE"));
+
+        let lines = contributor_lines(&c);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("AAPL: +6.00 pts"));
+        assert!(lines[0].contains("sector XLK +8.0%"));
+    }
+}
@@ -0,0 +1,166 @@
+//! Benchmark price history pre-seeding.
+//!
+//! Beta, rolling beta and the regime jobs read benchmark prices straight from
+//! `price_points`, so on a fresh install they fail until someone happens to look
+//! up SPY. At startup every configured benchmark with too little stored history
+//! is backfilled from the price provider; benchmarks that already have it are
+//! left alone, so later restarts make no API calls.
+
+use std::sync::Arc;
+
+use chrono::Duration;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::db::price_queries;
+use crate::errors::AppError;
+use crate::external::price_provider::{PriceProvider, PriceProviderError};
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::risk_service::BETA_BENCHMARKS;
+use crate::services::{clock, risk_memo};
+
+/// Five years, enough for every beta and regime lookback
+const DEFAULT_HISTORY_DAYS: u32 = 1825;
+
+/// Share of the expected trading days a benchmark must already have to be skipped
+const COVERAGE_THRESHOLD: f64 = 0.9;
+
+/// Benchmarks to seed, read from `BENCHMARK_TICKERS` (comma-separated, defaults
+/// to SPY, QQQ and IWM) and `BENCHMARK_HISTORY_DAYS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchmarkSeedConfig {
+    pub tickers: Vec<String>,
+    pub history_days: u32,
+}
+
+impl Default for BenchmarkSeedConfig {
+    fn default() -> Self {
+        Self {
+            tickers: BETA_BENCHMARKS.iter().map(|t| t.to_string()).collect(),
+            history_days: DEFAULT_HISTORY_DAYS,
+        }
+    }
+}
+
+impl BenchmarkSeedConfig {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let tickers = std::env::var("BENCHMARK_TICKERS")
+            .map(|s| parse_tickers(&s))
+            .unwrap_or(default.tickers);
+        let history_days = std::env::var("BENCHMARK_HISTORY_DAYS")
+            .ok()
+            .and_then(|s| s.parse::<u32>().ok())
+            .filter(|d| *d > 0)
+            .unwrap_or(default.history_days);
+        Self { tickers, history_days }
+    }
+}
+
+fn parse_tickers(value: &str) -> Vec<String> {
+    let mut tickers: Vec<String> = Vec::new();
+    for ticker in value.split(',').map(|t| t.trim().to_uppercase()).filter(|t| !t.is_empty()) {
+        if !tickers.contains(&ticker) {
+            tickers.push(ticker);
+        }
+    }
+    tickers
+}
+
+/// Whether `stored_points` over the last `history_days` falls short of the
+/// trading days in that span
+fn needs_backfill(stored_points: i64, history_days: u32) -> bool {
+    let expected_trading_days = history_days as f64 * 252.0 / 365.0;
+    (stored_points as f64) < expected_trading_days * COVERAGE_THRESHOLD
+}
+
+/// Benchmarks handled by one seeding run
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SeedResult {
+    pub seeded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Backfill every configured benchmark that lacks history. A benchmark that
+/// fails is logged and left for the next start or an on-demand refresh.
+pub async fn seed_benchmarks(
+    pool: &PgPool,
+    provider: &dyn PriceProvider,
+    rate_limiter: &RateLimiter,
+    config: &BenchmarkSeedConfig,
+) -> Result<SeedResult, AppError> {
+    let since = clock::today() - Duration::days(config.history_days as i64);
+    let counts = price_queries::fetch_point_counts(pool, &config.tickers, since).await?;
+
+    let mut result = SeedResult::default();
+    for ticker in &config.tickers {
+        let stored = counts.get(ticker).copied().unwrap_or(0);
+        if !needs_backfill(stored, config.history_days) {
+            result.skipped += 1;
+            continue;
+        }
+
+        info!("Seeding {} days of history for benchmark {} ({} points stored)", config.history_days, ticker, stored);
+        let fetched = {
+            let _guard = rate_limiter.acquire().await;
+            provider.fetch_daily_history(ticker, config.history_days).await
+        };
+        if let Err(PriceProviderError::RateLimited) = &fetched {
+            rate_limiter.record_rejection();
+        }
+
+        match fetched {
+            Ok(points) => {
+                price_queries::upsert_external_points(pool, ticker, &points).await?;
+                risk_memo::invalidate(ticker);
+                info!("Seeded {} price points for benchmark {}", points.len(), ticker);
+                result.seeded += 1;
+            }
+            Err(e) => {
+                error!("Failed to seed history for benchmark {}: {}", ticker, e);
+                result.failed += 1;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Seed benchmarks in the background so startup isn't held up by the provider
+pub fn spawn(pool: PgPool, provider: Arc<dyn PriceProvider>, rate_limiter: Arc<RateLimiter>) {
+    let config = BenchmarkSeedConfig::from_env();
+    if config.tickers.is_empty() {
+        info!("Benchmark seeding disabled (BENCHMARK_TICKERS is empty)");
+        return;
+    }
+    tokio::spawn(async move {
+        match seed_benchmarks(&pool, provider.as_ref(), &rate_limiter, &config).await {
+            Ok(result) => info!(
+                "Benchmark seeding finished: {} seeded, {} already present, {} failed",
+                result.seeded, result.skipped, result.failed
+            ),
+            Err(e) => error!("Benchmark seeding failed: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tickers() {
+        assert_eq!(parse_tickers(" spy, QQQ ,,iwm,SPY "), vec!["SPY", "QQQ", "IWM"]);
+        assert!(parse_tickers("").is_empty());
+    }
+
+    #[test]
+    fn test_needs_backfill() {
+        // Five years is about 1260 trading days
+        assert!(needs_backfill(0, 1825));
+        assert!(needs_backfill(250, 1825));
+        assert!(!needs_backfill(1250, 1825));
+        assert!(!needs_backfill(240, 365));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::errors::AppError;
 use crate::models::forecast::{BetaForecast, BetaForecastPoint, BetaRegimeChange, ForecastMethod};
 use crate::models::risk::BetaPoint;
+use crate::services::clock;
 use chrono::{Duration, NaiveDate, Utc};
 use sqlx::PgPool;
 
@@ -98,7 +99,7 @@ pub async fn generate_beta_forecast(
             .iter()
             .filter(|rc| {
                 if let Ok(date) = NaiveDate::parse_from_str(&rc.date, "%Y-%m-%d") {
-                    (Utc::now().naive_utc().date() - date).num_days() < 30
+                    (clock::today() - date).num_days() < 30
                 } else {
                     false
                 }
@@ -146,7 +147,7 @@ fn mean_reversion_forecast(
 ) -> Vec<BetaForecastPoint> {
     let mut forecast_points = Vec::new();
     let decay_rate = 0.005; // Half-life ~140 days
-    let start_date = Utc::now().naive_utc().date();
+    let start_date = clock::today();
 
     for day in 1..=days_ahead {
         let alpha = (-decay_rate * day as f64).exp();
@@ -178,7 +179,7 @@ fn exponential_smoothing_forecast(
     days_ahead: i32,
 ) -> Vec<BetaForecastPoint> {
     let mut forecast_points = Vec::new();
-    let start_date = Utc::now().naive_utc().date();
+    let start_date = clock::today();
 
     if historical_beta.is_empty() {
         return forecast_points;
@@ -234,7 +235,7 @@ fn linear_regression_forecast(
     days_ahead: i32,
 ) -> Vec<BetaForecastPoint> {
     let mut forecast_points = Vec::new();
-    let start_date = Utc::now().naive_utc().date();
+    let start_date = clock::today();
 
     if historical_beta.len() < 2 {
         return forecast_points;
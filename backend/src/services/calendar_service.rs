@@ -0,0 +1,360 @@
+//! Financial calendar of a portfolio as an iCal (RFC 5545) feed.
+//!
+//! Lists upcoming earnings releases and ex-dividend dates of the held tickers,
+//! the portfolio's scheduled rebalances and, for its owner, the target dates of
+//! their financial planning goals and glide path. Every event is an all-day
+//! event with a UID derived from what it describes, so a subscribed calendar
+//! updates events in place when the feed is refreshed.
+
+use std::collections::HashSet;
+
+use bigdecimal::ToPrimitive;
+use chrono::{DateTime, Duration, Months, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{
+    dividend_queries, financial_planning_queries, fundamentals_queries, glide_path_queries,
+    holding_snapshot_queries, portfolio_member_queries, portfolio_queries, rebalance_schedule_queries,
+};
+use crate::errors::AppError;
+use crate::models::financial_planning::SurveyGoal;
+use crate::models::{
+    CalendarEvent, CalendarEventKind, DividendEvent, EarningsDate, GlidePathSettings, RebalanceSchedule,
+};
+use crate::services::clock;
+
+/// Days ahead included when the request doesn't say
+pub const DEFAULT_DAYS: i64 = 180;
+const MAX_DAYS: i64 = 730;
+/// Furthest a rebalance schedule may start from today
+const MAX_SCHEDULE_YEARS: u32 = 10;
+const PRODUCT_ID: &str = "-//Rustfolio//Financial Calendar//EN";
+/// Longest content line allowed by RFC 5545, in octets
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Scheduled rebalances between `from` and `to`. Each date is counted from
+/// the start date, so a schedule starting on the 31st stays at month end.
+pub fn rebalance_dates(schedule: &RebalanceSchedule, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+    let months = schedule.frequency.months();
+    (0u32..)
+        .map_while(|i| schedule.start_date.checked_add_months(Months::new(i * months)))
+        .take_while(|date| *date <= to)
+        .filter(|date| *date >= from)
+        .collect()
+}
+
+fn uid(portfolio_id: Uuid, kind: &str, key: &str, date: NaiveDate) -> String {
+    format!("{}-{}-{}-{}@rustfolio", portfolio_id, kind, key, date.format("%Y%m%d"))
+}
+
+fn earnings_event(portfolio_id: Uuid, earnings: &EarningsDate) -> CalendarEvent {
+    let session = match earnings.hour.as_deref() {
+        Some("bmo") => Some("before market open"),
+        Some("amc") => Some("after market close"),
+        Some("dmh") => Some("during market hours"),
+        _ => None,
+    };
+    let quarter = match (earnings.fiscal_quarter, earnings.fiscal_year) {
+        (Some(q), Some(y)) => Some(format!("Q{} {}", q, y)),
+        _ => None,
+    };
+    let details: Vec<String> = [
+        quarter.map(|q| format!("Fiscal {}", q)),
+        session.map(|s| format!("Reported {}", s)),
+        earnings.eps_estimate.map(|e| format!("EPS estimate {:.2}", e)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    CalendarEvent {
+        kind: CalendarEventKind::Earnings,
+        date: earnings.report_date,
+        uid: uid(portfolio_id, "earnings", &earnings.ticker, earnings.report_date),
+        summary: format!("{} earnings", earnings.ticker),
+        description: (!details.is_empty()).then(|| details.join("\n")),
+    }
+}
+
+fn dividend_event(portfolio_id: Uuid, dividend: &DividendEvent) -> CalendarEvent {
+    let details: Vec<String> = [
+        dividend.amount.map(|a| format!("Dividend {:.4} per share", a)),
+        dividend.pay_date.map(|d| format!("Paid {}", d)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    CalendarEvent {
+        kind: CalendarEventKind::ExDividend,
+        date: dividend.ex_date,
+        uid: uid(portfolio_id, "exdiv", &dividend.ticker, dividend.ex_date),
+        summary: format!("{} ex-dividend", dividend.ticker),
+        description: (!details.is_empty()).then(|| details.join("\n")),
+    }
+}
+
+fn rebalance_event(portfolio_id: Uuid, portfolio_name: &str, date: NaiveDate) -> CalendarEvent {
+    CalendarEvent {
+        kind: CalendarEventKind::Rebalance,
+        date,
+        uid: uid(portfolio_id, "rebalance", "scheduled", date),
+        summary: format!("Rebalance {}", portfolio_name),
+        description: Some("Scheduled rebalance back to target weights".to_string()),
+    }
+}
+
+fn goal_event(portfolio_id: Uuid, goal: &SurveyGoal, date: NaiveDate) -> CalendarEvent {
+    let name = goal
+        .description
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+        .unwrap_or(&goal.goal_type);
+    CalendarEvent {
+        kind: CalendarEventKind::GoalMilestone,
+        date,
+        uid: uid(portfolio_id, "goal", &goal.id.to_string(), date),
+        summary: format!("Goal: {}", name),
+        description: goal
+            .target_amount
+            .as_ref()
+            .and_then(|a| a.to_f64())
+            .map(|a| format!("Target amount {:.2}", a)),
+    }
+}
+
+fn glide_path_event(portfolio_id: Uuid, portfolio_name: &str, settings: &GlidePathSettings) -> CalendarEvent {
+    CalendarEvent {
+        kind: CalendarEventKind::GoalMilestone,
+        date: settings.goal_date,
+        uid: uid(portfolio_id, "glidepath", "goal", settings.goal_date),
+        summary: format!("{} glide path goal date", portfolio_name),
+        description: Some(format!("Equity share reaches {:.0}%", settings.end_equity_pct)),
+    }
+}
+
+/// Escape a TEXT value (RFC 5545 3.3.11)
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fold a content line longer than 75 octets onto continuation lines starting
+/// with a space, never splitting a UTF-8 character (RFC 5545 3.1)
+pub fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    // Continuation lines count their leading space
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+/// The events as an iCal document
+pub fn to_ics(calendar_name: &str, events: &[CalendarEvent], now: DateTime<Utc>) -> String {
+    let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(calendar_name)),
+    ];
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", event.date.format("%Y%m%d")));
+        lines.push(format!("DTEND;VALUE=DATE:{}", (event.date + Duration::days(1)).format("%Y%m%d")));
+        lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        lines.push(format!("CATEGORIES:{}", event.kind.category()));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut ics = String::new();
+    for line in lines {
+        ics.push_str(&fold_line(&line));
+        ics.push_str("\r\n");
+    }
+    ics
+}
+
+/// Events of the next `days` days, soonest first
+pub async fn events(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    user_id: Uuid,
+    days: i64,
+) -> Result<(String, Vec<CalendarEvent>), AppError> {
+    if !(1..=MAX_DAYS).contains(&days) {
+        return Err(AppError::Validation(format!("days must be between 1 and {}", MAX_DAYS)));
+    }
+    let not_found = || AppError::NotFound(format!("Portfolio {} not found", portfolio_id));
+    let (_, owner_id) = portfolio_member_queries::role_for(pool, portfolio_id, user_id)
+        .await?
+        .ok_or_else(not_found)?;
+    let portfolio = portfolio_queries::fetch_one(pool, portfolio_id, owner_id)
+        .await?
+        .ok_or_else(not_found)?;
+
+    let from = clock::today();
+    let to = from + Duration::days(days);
+
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let tickers: Vec<String> = holdings
+        .iter()
+        .filter(|h| h.quantity.to_f64().unwrap_or(0.0) > 0.0)
+        .map(|h| h.ticker.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut events: Vec<CalendarEvent> = Vec::new();
+    for earnings in fundamentals_queries::fetch_earnings_dates(pool, &tickers, from, to).await? {
+        events.push(earnings_event(portfolio_id, &earnings));
+    }
+    for dividend in dividend_queries::fetch_upcoming(pool, &tickers, from, to).await? {
+        events.push(dividend_event(portfolio_id, &dividend));
+    }
+    if let Some(schedule) = rebalance_schedule_queries::fetch(pool, portfolio_id).await? {
+        for date in rebalance_dates(&schedule, from, to) {
+            events.push(rebalance_event(portfolio_id, &portfolio.name, date));
+        }
+    }
+    if let Some(settings) = glide_path_queries::fetch(pool, portfolio_id).await? {
+        if settings.goal_date >= from && settings.goal_date <= to {
+            events.push(glide_path_event(portfolio_id, &portfolio.name, &settings));
+        }
+    }
+    // Planning goals are the owner's own; members the portfolio is shared with don't see them
+    if user_id == owner_id {
+        for goal in financial_planning_queries::fetch_goals_due(pool, owner_id, from, to).await? {
+            if let Some(date) = goal.target_date {
+                events.push(goal_event(portfolio_id, &goal, date));
+            }
+        }
+    }
+
+    events.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.summary.cmp(&b.summary)));
+    Ok((portfolio.name, events))
+}
+
+/// The portfolio's calendar of the next `days` days as an iCal feed
+pub async fn feed(pool: &PgPool, portfolio_id: Uuid, user_id: Uuid, days: i64) -> Result<String, AppError> {
+    let (name, events) = events(pool, portfolio_id, user_id, days).await?;
+    Ok(to_ics(&format!("{} (Rustfolio)", name), &events, clock::now()))
+}
+
+/// Validate and save a portfolio's rebalance schedule
+pub async fn save_rebalance_schedule(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    schedule: &RebalanceSchedule,
+) -> Result<RebalanceSchedule, AppError> {
+    let today = clock::today();
+    let bound = Months::new(12 * MAX_SCHEDULE_YEARS);
+    let in_range = today.checked_sub_months(bound).is_some_and(|min| schedule.start_date >= min)
+        && today.checked_add_months(bound).is_some_and(|max| schedule.start_date <= max);
+    if !in_range {
+        return Err(AppError::Validation(format!(
+            "start_date must be within {} years of today",
+            MAX_SCHEDULE_YEARS
+        )));
+    }
+    Ok(rebalance_schedule_queries::upsert(pool, portfolio_id, schedule).await?)
+}
+
+pub async fn delete_rebalance_schedule(pool: &PgPool, portfolio_id: Uuid) -> Result<(), AppError> {
+    if !rebalance_schedule_queries::delete(pool, portfolio_id).await? {
+        return Err(AppError::NotFound(format!("No rebalance schedule set for portfolio {}", portfolio_id)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::calendar::RebalanceFrequency;
+    use chrono::TimeZone;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_rebalance_dates_follow_start_date() {
+        let schedule = RebalanceSchedule { frequency: RebalanceFrequency::Quarterly, start_date: date("2025-01-31") };
+        assert_eq!(
+            rebalance_dates(&schedule, date("2025-06-01"), date("2026-02-15")),
+            vec![date("2025-07-31"), date("2025-10-31"), date("2026-01-31")]
+        );
+
+        let monthly = RebalanceSchedule { frequency: RebalanceFrequency::Monthly, start_date: date("2026-01-31") };
+        assert_eq!(
+            rebalance_dates(&monthly, date("2026-01-01"), date("2026-03-31")),
+            vec![date("2026-01-31"), date("2026-02-28"), date("2026-03-31")]
+        );
+        assert!(rebalance_dates(&monthly, date("2025-01-01"), date("2025-12-31")).is_empty());
+    }
+
+    #[test]
+    fn test_escape_and_fold() {
+        assert_eq!(escape_text("Q1, 2026; EPS\\est\nnext"), "Q1\\, 2026\\; EPS\\\\est\\nnext");
+
+        let long = format!("DESCRIPTION:{}", "é".repeat(60));
+        let folded = fold_line(&long);
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| l.len() <= MAX_LINE_OCTETS));
+        assert!(lines[1..].iter().all(|l| l.starts_with(' ')));
+        let unfolded: String = lines.iter().enumerate().map(|(i, l)| if i == 0 { *l } else { &l[1..] }).collect();
+        assert_eq!(unfolded, long);
+    }
+
+    #[test]
+    fn test_to_ics_all_day_events() {
+        let portfolio_id = Uuid::nil();
+        let earnings = EarningsDate {
+            ticker: "AAPL".to_string(),
+            report_date: date("2026-04-30"),
+            hour: Some("amc".to_string()),
+            fiscal_year: Some(2026),
+            fiscal_quarter: Some(2),
+            eps_estimate: Some(1.5),
+        };
+        let events = vec![earnings_event(portfolio_id, &earnings)];
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 8, 30, 0).unwrap();
+
+        let ics = to_ics("Retirement", &events, now);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains(&format!("UID:{}-earnings-AAPL-20260430@rustfolio\r\n", portfolio_id)));
+        assert!(ics.contains("DTSTAMP:20260302T083000Z\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20260430\r\nDTEND;VALUE=DATE:20260501\r\n"));
+        assert!(ics.contains("SUMMARY:AAPL earnings\r\n"));
+        assert!(ics.contains("DESCRIPTION:Fiscal Q2 2026\\nReported after market close\\nEPS estimate 1.50\r\n"));
+        assert!(ics.contains("CATEGORIES:Earnings\r\n"));
+    }
+}
@@ -0,0 +1,276 @@
+//! Fault injection for resilience testing.
+//!
+//! With `CHAOS_ENABLED=true`, calls to the price provider and database
+//! connection checkouts can be delayed or made to fail at configured rates, so
+//! circuit breakers, stale-data serving and retries can be exercised against a
+//! running service in integration tests. Never enable it in production.
+//!
+//! - Provider calls wait `CHAOS_PROVIDER_LATENCY_MS`, then fail as rate limited
+//!   with probability `CHAOS_PROVIDER_RATE_LIMIT_RATE`, or with a network
+//!   error with probability `CHAOS_PROVIDER_FAILURE_RATE`.
+//! - Connection checkouts wait `CHAOS_DB_LATENCY_MS`. With probability
+//!   `CHAOS_DB_FAILURE_RATE` the connection's `search_path` is pointed at an
+//!   empty schema, so every statement run on it fails like a real database
+//!   error until the connection is checked out again.
+//! - `CHAOS_SEED` makes the sequence of injected faults reproducible.
+//!
+//! The settings can be changed at runtime through `/api/admin/chaos`, which is
+//! only mounted while chaos is enabled.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgConnection;
+
+use crate::errors::AppError;
+use crate::external::price_provider::PriceProviderError;
+
+/// Schema that doesn't exist; statements on a poisoned connection find no tables
+const UNAVAILABLE_SCHEMA: &str = "chaos_unavailable";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChaosConfig {
+    #[serde(default)]
+    pub provider_latency_ms: u64,
+    /// Share of provider calls rejected as rate limited (0-1)
+    #[serde(default)]
+    pub provider_rate_limit_rate: f64,
+    /// Share of provider calls failing with a network error (0-1)
+    #[serde(default)]
+    pub provider_failure_rate: f64,
+    #[serde(default)]
+    pub db_latency_ms: u64,
+    /// Share of connection checkouts whose statements fail (0-1)
+    #[serde(default)]
+    pub db_failure_rate: f64,
+    /// Seed of the fault sequence; random when unset
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl ChaosConfig {
+    /// The configuration when `CHAOS_ENABLED=true`, `None` otherwise
+    pub fn from_env() -> Option<Self> {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let enabled = var("CHAOS_ENABLED").and_then(|v| v.parse::<bool>().ok()).unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let number = |key: &str| var(key).and_then(|v| v.trim().parse::<f64>().ok()).unwrap_or(0.0);
+        let millis = |key: &str| var(key).and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(0);
+        Some(Self {
+            provider_latency_ms: millis("CHAOS_PROVIDER_LATENCY_MS"),
+            provider_rate_limit_rate: number("CHAOS_PROVIDER_RATE_LIMIT_RATE").clamp(0.0, 1.0),
+            provider_failure_rate: number("CHAOS_PROVIDER_FAILURE_RATE").clamp(0.0, 1.0),
+            db_latency_ms: millis("CHAOS_DB_LATENCY_MS"),
+            db_failure_rate: number("CHAOS_DB_FAILURE_RATE").clamp(0.0, 1.0),
+            seed: var("CHAOS_SEED").and_then(|v| v.trim().parse().ok()),
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), AppError> {
+        let rates = [
+            ("provider_rate_limit_rate", self.provider_rate_limit_rate),
+            ("provider_failure_rate", self.provider_failure_rate),
+            ("db_failure_rate", self.db_failure_rate),
+        ];
+        for (name, rate) in rates {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(AppError::Validation(format!("{} must be between 0 and 1", name)));
+            }
+        }
+        if self.provider_rate_limit_rate + self.provider_failure_rate > 1.0 {
+            return Err(AppError::Validation(
+                "provider_rate_limit_rate and provider_failure_rate must not add up to more than 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+struct ChaosState {
+    config: ChaosConfig,
+    rng: StdRng,
+}
+
+/// Injects the configured faults. Shared by the provider wrapper, the pool
+/// hook and the admin routes.
+pub struct Chaos {
+    state: Mutex<ChaosState>,
+    /// Database faults wait until startup migrations have run
+    db_armed: AtomicBool,
+    /// Set once a connection was poisoned, after which healthy checkouts reset it
+    db_poisoned: AtomicBool,
+}
+
+impl Chaos {
+    pub fn new(config: ChaosConfig) -> Self {
+        let rng = StdRng::seed_from_u64(config.seed.unwrap_or_else(rand::random));
+        Self {
+            state: Mutex::new(ChaosState { config, rng }),
+            db_armed: AtomicBool::new(false),
+            db_poisoned: AtomicBool::new(false),
+        }
+    }
+
+    pub fn config(&self) -> ChaosConfig {
+        self.state.lock().unwrap().config.clone()
+    }
+
+    /// Replace the configuration; a new seed restarts the fault sequence
+    pub fn update(&self, config: ChaosConfig) -> Result<ChaosConfig, AppError> {
+        config.validate()?;
+        let mut state = self.state.lock().unwrap();
+        if let Some(seed) = config.seed.filter(|s| state.config.seed != Some(*s)) {
+            state.rng = StdRng::seed_from_u64(seed);
+        }
+        state.config = config.clone();
+        Ok(config)
+    }
+
+    /// Start injecting database faults
+    pub fn arm_db(&self) {
+        self.db_armed.store(true, Ordering::Relaxed);
+    }
+
+    /// Delay and outcome of the next provider call
+    pub fn next_provider_fault(&self) -> (Duration, Option<PriceProviderError>) {
+        let mut state = self.state.lock().unwrap();
+        let latency = Duration::from_millis(state.config.provider_latency_ms);
+        let (rate_limit_rate, failure_rate) = (state.config.provider_rate_limit_rate, state.config.provider_failure_rate);
+        if rate_limit_rate + failure_rate <= 0.0 {
+            return (latency, None);
+        }
+        let roll: f64 = state.rng.random();
+        let fault = if roll < rate_limit_rate {
+            Some(PriceProviderError::RateLimited)
+        } else if roll < rate_limit_rate + failure_rate {
+            Some(PriceProviderError::Network("injected failure".to_string()))
+        } else {
+            None
+        };
+        (latency, fault)
+    }
+
+    /// Wait out the injected latency and return the injected error, if any
+    pub async fn before_provider_call(&self) -> Result<(), PriceProviderError> {
+        let (latency, fault) = self.next_provider_fault();
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        fault.map_or(Ok(()), Err)
+    }
+
+    /// Delay and whether to poison the next connection checkout
+    pub fn next_db_fault(&self) -> (Duration, bool) {
+        let mut state = self.state.lock().unwrap();
+        let latency = Duration::from_millis(state.config.db_latency_ms);
+        let rate = state.config.db_failure_rate;
+        let fail = rate > 0.0 && state.rng.random::<f64>() < rate;
+        (latency, fail)
+    }
+
+    async fn before_db_checkout(&self, conn: &mut PgConnection) -> Result<bool, sqlx::Error> {
+        if !self.db_armed.load(Ordering::Relaxed) {
+            return Ok(true);
+        }
+        let (latency, fail) = self.next_db_fault();
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        if fail {
+            self.db_poisoned.store(true, Ordering::Relaxed);
+            sqlx::query(&format!("SET search_path TO {}", UNAVAILABLE_SCHEMA)).execute(&mut *conn).await?;
+        } else if self.db_poisoned.load(Ordering::Relaxed) {
+            sqlx::query("RESET search_path").execute(&mut *conn).await?;
+        }
+        Ok(true)
+    }
+}
+
+static INSTALLED: OnceLock<Arc<Chaos>> = OnceLock::new();
+
+/// Make `chaos` the process-wide fault injector served by `/api/admin/chaos`
+pub fn install(chaos: Arc<Chaos>) {
+    let _ = INSTALLED.set(chaos);
+}
+
+/// The installed fault injector, when chaos is enabled
+pub fn installed() -> Option<Arc<Chaos>> {
+    INSTALLED.get().cloned()
+}
+
+/// Pool options that inject database faults on connection checkout
+pub fn with_db_faults(options: PgPoolOptions, chaos: Arc<Chaos>) -> PgPoolOptions {
+    options.before_acquire(move |conn, _meta| {
+        let chaos = chaos.clone();
+        Box::pin(async move { chaos.before_db_checkout(conn).await })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |key| map.get(key).cloned()
+    }
+
+    #[test]
+    fn test_config_only_when_enabled() {
+        assert_eq!(ChaosConfig::from_vars(vars(&[("CHAOS_PROVIDER_FAILURE_RATE", "0.5")])), None);
+        let config = ChaosConfig::from_vars(vars(&[
+            ("CHAOS_ENABLED", "true"),
+            ("CHAOS_PROVIDER_LATENCY_MS", "250"),
+            ("CHAOS_PROVIDER_RATE_LIMIT_RATE", "0.2"),
+            ("CHAOS_DB_FAILURE_RATE", "3"),
+            ("CHAOS_SEED", "7"),
+        ]))
+        .unwrap();
+        assert_eq!(config.provider_latency_ms, 250);
+        assert_eq!(config.provider_rate_limit_rate, 0.2);
+        assert_eq!(config.provider_failure_rate, 0.0);
+        assert_eq!(config.db_failure_rate, 1.0);
+        assert_eq!(config.seed, Some(7));
+        assert!(config.validate().is_ok());
+
+        let invalid = ChaosConfig { provider_rate_limit_rate: 0.7, provider_failure_rate: 0.5, ..Default::default() };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_provider_faults_follow_rates_and_seed() {
+        let never = Chaos::new(ChaosConfig { seed: Some(1), ..Default::default() });
+        assert!((0..100).all(|_| never.next_provider_fault().1.is_none()));
+
+        let always = Chaos::new(ChaosConfig { provider_rate_limit_rate: 1.0, seed: Some(1), ..Default::default() });
+        assert!((0..100).all(|_| matches!(always.next_provider_fault().1, Some(PriceProviderError::RateLimited))));
+
+        let config = ChaosConfig { provider_failure_rate: 0.3, seed: Some(42), ..Default::default() };
+        let sequence = |chaos: &Chaos| (0..200).map(|_| chaos.next_provider_fault().1.is_some()).collect::<Vec<_>>();
+        let first = sequence(&Chaos::new(config.clone()));
+        assert_eq!(first, sequence(&Chaos::new(config)));
+        let failures = first.iter().filter(|f| **f).count();
+        assert!((30..90).contains(&failures), "{} failures", failures);
+    }
+
+    #[test]
+    fn test_update_validates_and_applies() {
+        let chaos = Chaos::new(ChaosConfig::default());
+        assert!(chaos.update(ChaosConfig { db_failure_rate: 1.5, ..Default::default() }).is_err());
+        assert_eq!(chaos.config(), ChaosConfig::default());
+
+        chaos.update(ChaosConfig { db_failure_rate: 1.0, db_latency_ms: 5, ..Default::default() }).unwrap();
+        assert_eq!(chaos.next_db_fault(), (Duration::from_millis(5), true));
+    }
+}
@@ -0,0 +1,91 @@
+//! Wall clock for date-window calculations.
+//!
+//! Code that decides "today" for price windows, regime history or refresh
+//! checks reads it through [`now`]/[`today`] instead of `Utc::now()`, so demo
+//! mode can pin the whole service to a fixed as-of time with [`install`].
+
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+}
+
+/// The real wall clock
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock frozen at a seed instant; it only moves when advanced
+pub struct FixedClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    pub fn new(at: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(at) }
+    }
+
+    #[allow(dead_code)]
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+static CLOCK: OnceLock<RwLock<Arc<dyn Clock>>> = OnceLock::new();
+
+fn global() -> &'static RwLock<Arc<dyn Clock>> {
+    CLOCK.get_or_init(|| RwLock::new(Arc::new(SystemClock)))
+}
+
+/// Replace the process-wide clock
+pub fn install(clock: Arc<dyn Clock>) {
+    *global().write().unwrap() = clock;
+}
+
+/// The process-wide clock
+pub fn current() -> Arc<dyn Clock> {
+    global().read().unwrap().clone()
+}
+
+pub fn now() -> DateTime<Utc> {
+    current().now()
+}
+
+pub fn today() -> NaiveDate {
+    current().today()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_fixed_clock_only_moves_when_advanced() {
+        let start = Utc.with_ymd_and_hms(2025, 6, 30, 21, 0, 0).unwrap();
+        let clock = FixedClock::new(start);
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::hours(4));
+        assert_eq!(clock.now(), start + Duration::hours(4));
+        assert_eq!(clock.today(), NaiveDate::from_ymd_opt(2025, 7, 1).unwrap());
+    }
+}
@@ -0,0 +1,594 @@
+//! Constraint-aware rebalancing.
+//!
+//! Finds the portfolio closest to the current one that satisfies the user's
+//! constraints: a maximum single-position weight, per-sector caps, a cash floor
+//! and a do-not-sell list. Weight freed up by the caps is spread over the other
+//! positions, tilted toward lower volatility. With a saved glide path the solver
+//! starts from the current weights rescaled to the path's equity share for today.
+//! The weights are solved by `quant::solve_weights`; trades are then sized in
+//! shares per account broker support.
+
+use bigdecimal::ToPrimitive;
+use std::collections::{HashMap, HashSet};
+
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::{holding_snapshot_queries, optimization_constraint_queries};
+use crate::errors::AppError;
+use crate::models::{
+    AdjustmentAction, ConstrainedOptimization, ConstrainedOptimizationRequest, ConstrainedPosition, CurrentMetrics,
+    ExpectedImpact, LatestAccountHolding, OptimizationConstraints, OptimizationRecommendation, PositionAdjustment, RecommendationType,
+    Severity,
+};
+use crate::services::quant::{solve_weights, WeightProblem};
+use crate::services::{glide_path_service, risk_service};
+use crate::services::trade_rounding_service::{self, RoundingContext, RoundingTally};
+
+pub const DEFAULT_RISK_AVERSION: f64 = 1.0;
+/// Trades smaller than this share of portfolio value are reported as HOLD
+const MIN_TRADE_WEIGHT: f64 = 0.0005;
+/// One-way turnover (percent) below which no rebalance is recommended
+const MIN_RECOMMENDED_TURNOVER: f64 = 0.5;
+
+/// One aggregated position going into the solver
+#[derive(Debug, Clone)]
+pub struct PositionInput {
+    pub ticker: String,
+    pub holding_name: Option<String>,
+    pub sector: Option<String>,
+    pub market_value: f64,
+    /// Annualized volatility in percent, if known
+    pub volatility: Option<f64>,
+    /// Counts toward the glide path's equity share
+    pub equity: bool,
+}
+
+fn validate(mut constraints: OptimizationConstraints) -> Result<OptimizationConstraints, AppError> {
+    if let Some(max) = constraints.max_position_weight {
+        if !(max > 0.0 && max <= 100.0) {
+            return Err(AppError::Validation("max_position_weight must be between 0 and 100".to_string()));
+        }
+    }
+    if let Some(cash) = constraints.min_cash_pct {
+        if !(0.0..100.0).contains(&cash) {
+            return Err(AppError::Validation("min_cash_pct must be at least 0 and below 100".to_string()));
+        }
+    }
+
+    let mut caps = HashMap::new();
+    for (sector, cap) in constraints.sector_caps {
+        let sector = sector.trim().to_lowercase();
+        if sector.is_empty() {
+            return Err(AppError::Validation("Sector names must not be empty".to_string()));
+        }
+        if !(cap > 0.0 && cap <= 100.0) {
+            return Err(AppError::Validation(format!("Cap for sector '{}' must be between 0 and 100", sector)));
+        }
+        if caps.insert(sector.clone(), cap).is_some() {
+            return Err(AppError::Validation(format!("Sector '{}' is listed more than once", sector)));
+        }
+    }
+    constraints.sector_caps = caps;
+
+    let mut seen = HashSet::new();
+    constraints.do_not_sell = constraints
+        .do_not_sell
+        .into_iter()
+        .map(|t| t.trim().to_uppercase())
+        .filter(|t| !t.is_empty() && seen.insert(t.clone()))
+        .collect();
+    Ok(constraints)
+}
+
+pub async fn get_constraints(pool: &PgPool, portfolio_id: Uuid) -> Result<OptimizationConstraints, AppError> {
+    optimization_constraint_queries::fetch(pool, portfolio_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No optimization constraints set for portfolio {}", portfolio_id)))
+}
+
+pub async fn set_constraints(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    constraints: OptimizationConstraints,
+) -> Result<OptimizationConstraints, AppError> {
+    let constraints = validate(constraints)?;
+    Ok(optimization_constraint_queries::upsert(pool, portfolio_id, &constraints).await?)
+}
+
+pub async fn delete_constraints(pool: &PgPool, portfolio_id: Uuid) -> Result<(), AppError> {
+    if !optimization_constraint_queries::delete(pool, portfolio_id).await? {
+        return Err(AppError::NotFound(format!("No optimization constraints set for portfolio {}", portfolio_id)));
+    }
+    Ok(())
+}
+
+/// Current weights with equity and non-equity positions each scaled so equity makes
+/// up `equity_target` (0-1); unchanged when either side has no positions
+fn glide_anchor(positions: &[PositionInput], current: &[f64], equity_target: f64, warnings: &mut Vec<String>) -> Vec<f64> {
+    let equity: f64 = positions.iter().zip(current).filter(|(p, _)| p.equity).map(|(_, w)| w).sum();
+    let defensive = 1.0 - equity;
+    if (equity <= 0.0 && equity_target > 0.0) || (defensive <= 0.0 && equity_target < 1.0) {
+        warnings.push(format!(
+            "The glide path targets {:.1}% equity but the portfolio holds no {} positions to shift into",
+            equity_target * 100.0,
+            if equity <= 0.0 { "equity" } else { "fixed income or cash" }
+        ));
+        return current.to_vec();
+    }
+    positions
+        .iter()
+        .zip(current)
+        .map(|(p, w)| if p.equity { w * equity_target / equity } else { w * (1.0 - equity_target) / defensive })
+        .collect()
+}
+
+/// Solve target weights for `positions` under `constraints`, optionally steering
+/// the equity share toward `equity_target` (0-1)
+pub fn solve(
+    portfolio_id: Uuid,
+    positions: &[PositionInput],
+    constraints: OptimizationConstraints,
+    risk_aversion: f64,
+    equity_target: Option<f64>,
+) -> ConstrainedOptimization {
+    let total_value: f64 = positions.iter().map(|p| p.market_value).sum();
+    let mut warnings = Vec::new();
+    if positions.is_empty() || total_value <= 0.0 {
+        return ConstrainedOptimization {
+            portfolio_id: portfolio_id.to_string(),
+            total_value,
+            constraints,
+            target_cash_weight: 0.0,
+            positions: vec![],
+            turnover: 0.0,
+            iterations: 0,
+            converged: true,
+            warnings: vec!["Portfolio has no holdings with market value".to_string()],
+            rounding: None,
+            equity_target: equity_target.map(|t| t * 100.0),
+        };
+    }
+
+    let do_not_sell: HashSet<&str> = constraints.do_not_sell.iter().map(String::as_str).collect();
+    let max_weight = constraints.max_position_weight.map_or(1.0, |m| m / 100.0);
+    let current: Vec<f64> = positions.iter().map(|p| p.market_value / total_value).collect();
+    let anchor = match equity_target {
+        Some(target) => glide_anchor(positions, &current, target, &mut warnings),
+        None => current.clone(),
+    };
+
+    let mut lower = vec![0.0; positions.len()];
+    let mut upper = vec![max_weight; positions.len()];
+    for (i, position) in positions.iter().enumerate() {
+        if do_not_sell.contains(position.ticker.as_str()) {
+            lower[i] = current[i];
+            if current[i] > max_weight {
+                upper[i] = current[i];
+                warnings.push(format!(
+                    "{} is above the {:.1}% position cap but is on the do-not-sell list",
+                    position.ticker,
+                    max_weight * 100.0
+                ));
+            }
+        }
+    }
+
+    let mut groups = Vec::new();
+    let mut sectors: Vec<(&String, &f64)> = constraints.sector_caps.iter().collect();
+    sectors.sort_by(|a, b| a.0.cmp(b.0));
+    for (sector, cap) in sectors {
+        let members: Vec<usize> = positions
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.sector.as_deref().is_some_and(|s| s.trim().to_lowercase() == *sector))
+            .map(|(i, _)| i)
+            .collect();
+        if members.is_empty() {
+            continue;
+        }
+        let mut cap = cap / 100.0;
+        let locked: f64 = members.iter().map(|&i| lower[i]).sum();
+        if locked > cap {
+            warnings.push(format!(
+                "Do-not-sell positions alone exceed the {:.1}% cap for sector '{}'",
+                cap * 100.0,
+                sector
+            ));
+            cap = locked;
+        }
+        groups.push((members, cap));
+    }
+
+    let mut invested = 1.0 - constraints.min_cash_pct.unwrap_or(0.0) / 100.0;
+    let locked: f64 = lower.iter().sum();
+    if locked > invested {
+        warnings.push("Do-not-sell positions leave less cash than the requested minimum".to_string());
+        invested = locked;
+    }
+    // Sectors are disjoint, so the most the caps allow is easy to total up
+    let grouped: HashSet<usize> = groups.iter().flat_map(|(members, _)| members.iter().copied()).collect();
+    let capacity = groups
+        .iter()
+        .map(|(members, cap)| members.iter().map(|&i| upper[i]).sum::<f64>().min(*cap))
+        .sum::<f64>()
+        + (0..positions.len()).filter(|i| !grouped.contains(i)).map(|i| upper[i]).sum::<f64>();
+    if capacity < invested {
+        warnings.push(format!(
+            "Position and sector caps only allow {:.1}% to be invested; the rest is held as cash",
+            capacity * 100.0
+        ));
+        invested = capacity;
+    }
+
+    let problem = WeightProblem {
+        current: anchor,
+        variance: positions.iter().map(|p| p.volatility.map_or(0.0, |v| (v / 100.0).powi(2))).collect(),
+        lower,
+        upper,
+        groups,
+        invested,
+        risk_aversion,
+    };
+    let solution = solve_weights(&problem);
+    if !solution.converged {
+        warnings.push("Constraints could not all be satisfied; target weights are approximate".to_string());
+    }
+
+    let mut results: Vec<ConstrainedPosition> = positions
+        .iter()
+        .zip(current.iter().zip(&solution.weights))
+        .map(|(position, (w0, w))| {
+            let change = w - w0;
+            let action = if change.abs() < MIN_TRADE_WEIGHT {
+                AdjustmentAction::Hold
+            } else if change > 0.0 {
+                AdjustmentAction::Buy
+            } else {
+                AdjustmentAction::Sell
+            };
+            ConstrainedPosition {
+                ticker: position.ticker.clone(),
+                holding_name: position.holding_name.clone(),
+                sector: position.sector.clone(),
+                current_weight: w0 * 100.0,
+                target_weight: w * 100.0,
+                trade_value: if action == AdjustmentAction::Hold { 0.0 } else { change * total_value },
+                action,
+                do_not_sell: do_not_sell.contains(position.ticker.as_str()),
+                shares_change: None,
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| b.trade_value.abs().total_cmp(&a.trade_value.abs()));
+
+    let turnover = current
+        .iter()
+        .zip(&solution.weights)
+        .map(|(a, b)| (a - b).abs())
+        .sum::<f64>()
+        / 2.0
+        * 100.0;
+
+    ConstrainedOptimization {
+        portfolio_id: portfolio_id.to_string(),
+        total_value,
+        constraints,
+        target_cash_weight: (1.0 - solution.weights.iter().sum::<f64>()) * 100.0,
+        positions: results,
+        turnover,
+        iterations: solution.iterations,
+        converged: solution.converged,
+        warnings,
+        rounding: None,
+        equity_target: equity_target.map(|t| t * 100.0),
+    }
+}
+
+/// Convert target trades to share counts, resizing them to what can be executed
+pub fn apply_rounding(result: &mut ConstrainedOptimization, context: &RoundingContext) {
+    if result.total_value <= 0.0 {
+        return;
+    }
+    let mut tally = RoundingTally::default();
+    for position in &mut result.positions {
+        if position.action == AdjustmentAction::Hold {
+            continue;
+        }
+        let sell_all = position.action == AdjustmentAction::Sell && position.target_weight <= 0.0;
+        let Some((shares, executed)) = context.round_trade(&position.ticker, position.trade_value, sell_all) else {
+            continue;
+        };
+        tally.record(&position.ticker, position.trade_value, executed);
+        position.shares_change = Some(shares);
+        position.trade_value = executed;
+        position.target_weight = position.current_weight + executed / result.total_value * 100.0;
+        if shares == 0.0 {
+            position.action = AdjustmentAction::Hold;
+        }
+    }
+    result.target_cash_weight = 100.0 - result.positions.iter().map(|p| p.target_weight).sum::<f64>();
+    result.rounding = tally.report();
+}
+
+/// Solve under the request's constraints, falling back to the saved ones.
+/// Volatilities come from cached price history only, so no provider calls are made.
+pub async fn optimize(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    request: ConstrainedOptimizationRequest,
+    risk_free_rate: f64,
+) -> Result<ConstrainedOptimization, AppError> {
+    let constraints = match request.constraints {
+        Some(constraints) => validate(constraints)?,
+        None => optimization_constraint_queries::fetch(pool, portfolio_id).await?.unwrap_or_default(),
+    };
+    let risk_aversion = request.risk_aversion.unwrap_or(DEFAULT_RISK_AVERSION);
+    if !(0.0..=100.0).contains(&risk_aversion) {
+        return Err(AppError::Validation("risk_aversion must be between 0 and 100".to_string()));
+    }
+
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let mut positions = aggregate_positions(&holdings);
+    if positions.is_empty() {
+        return Err(AppError::Validation("Portfolio has no holdings to optimize".to_string()));
+    }
+    if risk_aversion > 0.0 {
+        for position in &mut positions {
+            match risk_service::compute_risk_metrics_from_cache(pool, &position.ticker, 90, "SPY", risk_free_rate).await {
+                Ok(assessment) => position.volatility = Some(assessment.metrics.volatility),
+                Err(e) => warn!("No cached volatility for {}: {}", position.ticker, e),
+            }
+        }
+    }
+
+    let equity_target = if request.use_glide_path.unwrap_or(true) {
+        glide_path_service::equity_target(pool, portfolio_id).await?
+    } else {
+        None
+    };
+
+    let mut result = solve(portfolio_id, &positions, constraints, risk_aversion, equity_target);
+    let rounding = trade_rounding_service::load_context(pool, portfolio_id, &holdings).await?;
+    apply_rounding(&mut result, &rounding);
+    info!(
+        "Constrained optimization for portfolio {}: {:.1}% turnover, {} iterations (converged: {})",
+        portfolio_id, result.turnover, result.iterations, result.converged
+    );
+    Ok(result)
+}
+
+fn aggregate_positions(holdings: &[LatestAccountHolding]) -> Vec<PositionInput> {
+    let mut by_ticker: HashMap<String, PositionInput> = HashMap::new();
+    for holding in holdings {
+        let market_value = holding.market_value.to_f64().unwrap_or(0.0);
+        let entry = by_ticker.entry(holding.ticker.clone()).or_insert_with(|| PositionInput {
+            ticker: holding.ticker.clone(),
+            holding_name: holding.holding_name.clone(),
+            sector: None,
+            market_value: 0.0,
+            volatility: None,
+            equity: glide_path_service::is_equity(holding),
+        });
+        entry.market_value += market_value;
+        if entry.sector.is_none() {
+            entry.sector = holding.industry.clone();
+        }
+    }
+    let mut positions: Vec<PositionInput> = by_ticker.into_values().filter(|p| p.market_value > 0.0).collect();
+    positions.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+    positions
+}
+
+/// Load the saved constraints for the background analysis, if any
+pub async fn saved_constraints(pool: &PgPool, portfolio_id: Uuid) -> Result<Option<OptimizationConstraints>, AppError> {
+    Ok(optimization_constraint_queries::fetch(pool, portfolio_id).await?)
+}
+
+/// Turn sells of do-not-sell tickers into holds
+pub fn enforce_do_not_sell(constraints: &OptimizationConstraints, recommendations: &mut [OptimizationRecommendation]) {
+    for recommendation in recommendations.iter_mut() {
+        let mut notes = Vec::new();
+        for adjustment in &mut recommendation.affected_positions {
+            if adjustment.action == AdjustmentAction::Sell
+                && constraints.do_not_sell.iter().any(|t| t.eq_ignore_ascii_case(&adjustment.ticker))
+            {
+                adjustment.action = AdjustmentAction::Hold;
+                adjustment.recommended_value = adjustment.current_value;
+                adjustment.recommended_weight = adjustment.current_weight;
+                adjustment.amount_change = 0.0;
+                adjustment.shares_change = None;
+                notes.push(format!("{} is on your do-not-sell list and is left unchanged", adjustment.ticker));
+            }
+        }
+        recommendation.suggested_actions.extend(notes);
+    }
+}
+
+/// Recommendation to move to the constrained target weights, if it requires real trades
+pub fn constraint_recommendation(
+    result: &ConstrainedOptimization,
+    current_metrics: &CurrentMetrics,
+) -> Option<OptimizationRecommendation> {
+    if result.turnover < MIN_RECOMMENDED_TURNOVER {
+        return None;
+    }
+
+    let affected_positions: Vec<PositionAdjustment> = result
+        .positions
+        .iter()
+        .filter(|p| p.action != AdjustmentAction::Hold)
+        .map(|p| {
+            let current_value = p.current_weight / 100.0 * result.total_value;
+            PositionAdjustment {
+                ticker: p.ticker.clone(),
+                holding_name: p.holding_name.clone(),
+                current_value,
+                current_weight: p.current_weight,
+                recommended_value: current_value + p.trade_value,
+                recommended_weight: p.target_weight,
+                action: p.action.clone(),
+                amount_change: p.trade_value,
+                shares_change: None,
+            }
+        })
+        .collect();
+
+    let largest_after = result.positions.iter().map(|p| p.target_weight).fold(0.0, f64::max);
+    let mut suggested_actions: Vec<String> = affected_positions
+        .iter()
+        .take(5)
+        .map(|a| {
+            let verb = if a.action == AdjustmentAction::Buy { "Buy" } else { "Sell" };
+            format!("{} ${:.0} of {} ({:.1}% → {:.1}%)", verb, a.amount_change.abs(), a.ticker, a.current_weight, a.recommended_weight)
+        })
+        .collect();
+    if result.target_cash_weight > 0.05 {
+        suggested_actions.push(format!("Hold {:.1}% of the portfolio in cash", result.target_cash_weight));
+    }
+    if largest_after > 0.0 {
+        suggested_actions.push(format!("Largest position after rebalancing: {:.1}%", largest_after));
+    }
+    suggested_actions.extend(result.warnings.iter().cloned());
+
+    Some(OptimizationRecommendation {
+        id: "constraints-1".to_string(),
+        recommendation_type: RecommendationType::RebalanceSectors,
+        severity: Severity::Warning,
+        title: "Rebalance to Meet Your Portfolio Constraints".to_string(),
+        rationale: format!(
+            "Your holdings break one or more of the limits you set. Moving to the target weights trades \
+             {:.1}% of the portfolio, the smallest change that keeps every position and sector within its cap{}.",
+            result.turnover,
+            if result.constraints.do_not_sell.is_empty() { "" } else { " without selling any do-not-sell position" }
+        ),
+        affected_positions,
+        expected_impact: ExpectedImpact {
+            risk_score_before: current_metrics.risk_score,
+            risk_score_after: current_metrics.risk_score,
+            risk_score_change: 0.0,
+            volatility_before: current_metrics.volatility,
+            volatility_after: current_metrics.volatility,
+            volatility_change: 0.0,
+            sharpe_before: current_metrics.sharpe_ratio,
+            sharpe_after: current_metrics.sharpe_ratio,
+            sharpe_change: None,
+            diversification_before: current_metrics.diversification_score,
+            diversification_after: current_metrics.diversification_score,
+            diversification_change: 0.0,
+            max_drawdown_before: current_metrics.max_drawdown,
+            max_drawdown_after: current_metrics.max_drawdown,
+        },
+        suggested_actions,
+        rounding: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(ticker: &str, sector: &str, value: f64) -> PositionInput {
+        PositionInput {
+            ticker: ticker.to_string(),
+            holding_name: None,
+            sector: Some(sector.to_string()),
+            market_value: value,
+            volatility: None,
+            equity: sector != "Fixed Income",
+        }
+    }
+
+    fn weight(result: &ConstrainedOptimization, ticker: &str) -> f64 {
+        result.positions.iter().find(|p| p.ticker == ticker).unwrap().target_weight
+    }
+
+    #[test]
+    fn test_validate_normalizes() {
+        let constraints = validate(OptimizationConstraints {
+            sector_caps: [(" Technology ".to_string(), 40.0)].into_iter().collect(),
+            do_not_sell: vec!["aapl".to_string(), "AAPL ".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(constraints.sector_caps.get("technology"), Some(&40.0));
+        assert_eq!(constraints.do_not_sell, vec!["AAPL".to_string()]);
+
+        assert!(validate(OptimizationConstraints { max_position_weight: Some(0.0), ..Default::default() }).is_err());
+        assert!(validate(OptimizationConstraints { min_cash_pct: Some(100.0), ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn test_solve_caps_and_cash() {
+        let positions = vec![
+            position("AAPL", "Technology", 5000.0),
+            position("MSFT", "Technology", 3000.0),
+            position("JNJ", "Health Care", 2000.0),
+        ];
+        let constraints = OptimizationConstraints {
+            max_position_weight: Some(40.0),
+            sector_caps: [("technology".to_string(), 60.0)].into_iter().collect(),
+            min_cash_pct: Some(5.0),
+            do_not_sell: vec![],
+        };
+        let result = solve(Uuid::nil(), &positions, constraints, 0.0, None);
+
+        assert!(result.converged);
+        assert!(weight(&result, "AAPL") <= 40.0 + 1e-4);
+        assert!(weight(&result, "AAPL") + weight(&result, "MSFT") <= 60.0 + 1e-4);
+        assert!((result.target_cash_weight - 5.0).abs() < 1e-4);
+        assert!((weight(&result, "JNJ") - 35.0).abs() < 1e-4);
+        assert_eq!(result.positions.iter().find(|p| p.ticker == "JNJ").unwrap().action, AdjustmentAction::Buy);
+    }
+
+    #[test]
+    fn test_do_not_sell_is_never_sold() {
+        let positions = vec![position("AAPL", "Technology", 6000.0), position("JNJ", "Health Care", 4000.0)];
+        let constraints = OptimizationConstraints {
+            max_position_weight: Some(50.0),
+            do_not_sell: vec!["AAPL".to_string()],
+            ..Default::default()
+        };
+        let result = solve(Uuid::nil(), &positions, constraints, 0.0, None);
+
+        assert!((weight(&result, "AAPL") - 60.0).abs() < 1e-4);
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.positions.iter().all(|p| p.action != AdjustmentAction::Sell || !p.do_not_sell));
+    }
+
+    #[test]
+    fn test_excess_capacity_goes_to_cash() {
+        let positions = vec![position("AAPL", "Technology", 7000.0), position("MSFT", "Technology", 3000.0)];
+        let constraints = OptimizationConstraints {
+            sector_caps: [("technology".to_string(), 80.0)].into_iter().collect(),
+            ..Default::default()
+        };
+        let result = solve(Uuid::nil(), &positions, constraints, 0.0, None);
+
+        assert!(result.converged);
+        assert!((result.target_cash_weight - 20.0).abs() < 1e-4);
+        assert!(!result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_glide_path_target_shifts_equity() {
+        let positions = vec![
+            position("VFV", "Equity", 6000.0),
+            position("XIC", "Equity", 2000.0),
+            position("ZAG", "Fixed Income", 2000.0),
+        ];
+        let result = solve(Uuid::nil(), &positions, OptimizationConstraints::default(), 0.0, Some(0.6));
+
+        assert!(result.converged);
+        assert!((weight(&result, "VFV") + weight(&result, "XIC") - 60.0).abs() < 1e-4);
+        assert!((weight(&result, "VFV") / weight(&result, "XIC") - 3.0).abs() < 1e-4);
+        assert!((weight(&result, "ZAG") - 40.0).abs() < 1e-4);
+        assert!((result.turnover - 20.0).abs() < 1e-4);
+        assert_eq!(result.equity_target, Some(60.0));
+
+        let all_equity = vec![position("VFV", "Equity", 10000.0)];
+        let result = solve(Uuid::nil(), &all_equity, OptimizationConstraints::default(), 0.0, Some(0.6));
+        assert_eq!(result.warnings.len(), 1);
+    }
+}
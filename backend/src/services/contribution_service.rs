@@ -0,0 +1,234 @@
+//! Contribution-to-return analysis.
+//!
+//! Each day a position contributes its start-of-day weight × its daily return.
+//! Daily contributions are linked geometrically (scaled by the portfolio's
+//! cumulative growth before that day) so they sum exactly to the chained
+//! portfolio return. Weights come from current share counts at each day's
+//! closing prices, so trades within the period are not reflected.
+
+use std::collections::{BTreeSet, HashMap};
+
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{holding_snapshot_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{PortfolioContributions, PositionContribution};
+use crate::services::clock;
+
+pub const DEFAULT_PERIOD: &str = "90d";
+
+/// Parse a lookback such as "30d", "6m", or "1y" into calendar days
+pub fn parse_period(period: &str) -> Result<i64, AppError> {
+    let period = period.trim().to_lowercase();
+    let invalid = || AppError::Validation(format!("Invalid period '{}'; use e.g. 30d, 6m, or 1y", period));
+    let (count, unit) = period.split_at(period.len().saturating_sub(1));
+    let count: i64 = count.parse().map_err(|_| invalid())?;
+    let days = match unit {
+        "d" => count,
+        "w" => count * 7,
+        "m" => count * 30,
+        "y" => count * 365,
+        _ => return Err(invalid()),
+    };
+    if !(2..=3650).contains(&days) {
+        return Err(AppError::Validation("period must be between 2 days and 10 years".to_string()));
+    }
+    Ok(days)
+}
+
+/// Linked contributions from share counts and ascending (date, close) series per ticker.
+///
+/// Returns the positions (sorted by contribution) and the chained portfolio return in percent.
+pub fn compute_contributions(
+    quantities: &[(String, f64)],
+    prices: &HashMap<String, Vec<(NaiveDate, f64)>>,
+) -> (Vec<PositionContribution>, f64) {
+    let dates: Vec<NaiveDate> = prices
+        .values()
+        .flat_map(|series| series.iter().map(|(d, _)| *d))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    // Closes forward-filled onto the common calendar; None before a ticker's first price
+    let filled: Vec<(&str, f64, Vec<Option<f64>>)> = quantities
+        .iter()
+        .filter_map(|(ticker, qty)| {
+            let series = prices.get(ticker)?;
+            let by_date: HashMap<NaiveDate, f64> = series.iter().copied().collect();
+            let mut last = None;
+            let closes = dates
+                .iter()
+                .map(|d| {
+                    if let Some(p) = by_date.get(d) {
+                        last = Some(*p);
+                    }
+                    last
+                })
+                .collect();
+            Some((ticker.as_str(), *qty, closes))
+        })
+        .collect();
+
+    let n = filled.len();
+    let mut linked = vec![0.0; n];
+    let mut weight_sum = vec![0.0; n];
+    let mut growth = 1.0;
+    let mut days = 0usize;
+
+    for t in 1..dates.len() {
+        let start_values: Vec<Option<(f64, f64)>> = filled
+            .iter()
+            .map(|(_, qty, closes)| match (closes[t - 1], closes[t]) {
+                (Some(prev), Some(cur)) if prev > 0.0 => Some((qty * prev, cur / prev - 1.0)),
+                _ => None,
+            })
+            .collect();
+        let total: f64 = start_values.iter().flatten().map(|(v, _)| v).sum();
+        if total <= 0.0 {
+            continue;
+        }
+
+        let mut day_return = 0.0;
+        for (i, entry) in start_values.iter().enumerate() {
+            if let Some((value, r)) = entry {
+                let weight = value / total;
+                let contribution = weight * r;
+                linked[i] += contribution * growth;
+                weight_sum[i] += weight;
+                day_return += contribution;
+            }
+        }
+        growth *= 1.0 + day_return;
+        days += 1;
+    }
+
+    let mut positions: Vec<PositionContribution> = filled
+        .iter()
+        .enumerate()
+        .map(|(i, (ticker, _, closes))| {
+            let first = closes.iter().flatten().next().copied();
+            let last = closes.last().copied().flatten();
+            let position_return = match (first, last) {
+                (Some(f), Some(l)) if f > 0.0 => (l / f - 1.0) * 100.0,
+                _ => 0.0,
+            };
+            PositionContribution {
+                ticker: ticker.to_string(),
+                average_weight: if days > 0 { weight_sum[i] / days as f64 } else { 0.0 },
+                position_return,
+                contribution: linked[i] * 100.0,
+            }
+        })
+        .collect();
+    positions.sort_by(|a, b| b.contribution.total_cmp(&a.contribution));
+
+    (positions, (growth - 1.0) * 100.0)
+}
+
+/// Contribution of each current holding to the portfolio's return over `period`
+pub async fn portfolio_contributions(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    period: Option<&str>,
+) -> Result<PortfolioContributions, AppError> {
+    let period = period.unwrap_or(DEFAULT_PERIOD);
+    let days = parse_period(period)?;
+    let start = clock::today() - Duration::days(days);
+
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let mut quantities: Vec<(String, f64)> = Vec::new();
+    for holding in holdings.iter().filter(|h| !h.ticker.is_empty()) {
+        let qty = holding.quantity.to_f64().unwrap_or(0.0);
+        match quantities.iter_mut().find(|(t, _)| *t == holding.ticker) {
+            Some(entry) => entry.1 += qty,
+            None => quantities.push((holding.ticker.clone(), qty)),
+        }
+    }
+    quantities.retain(|(_, qty)| *qty > 0.0);
+
+    let tickers: Vec<String> = quantities.iter().map(|(t, _)| t.clone()).collect();
+    // Calendar days bound the number of trading days, so this window covers the period
+    let windows = price_queries::fetch_window_batch(pool, &tickers, days + 1).await?;
+    let prices: HashMap<String, Vec<(NaiveDate, f64)>> = windows
+        .into_iter()
+        .map(|(ticker, points)| {
+            let series: Vec<(NaiveDate, f64)> = points
+                .iter()
+                .filter(|p| p.date >= start)
+                .filter_map(|p| p.close_price.to_f64().map(|c| (p.date, c)))
+                .collect();
+            (ticker, series)
+        })
+        .filter(|(_, series)| !series.is_empty())
+        .collect();
+
+    let missing_prices = tickers.iter().filter(|t| !prices.contains_key(*t)).cloned().collect();
+    let (positions, portfolio_return) = compute_contributions(&quantities, &prices);
+
+    let all_dates = || prices.values().flat_map(|s| s.iter().map(|(d, _)| *d));
+    Ok(PortfolioContributions {
+        portfolio_id,
+        period: period.to_string(),
+        start_date: all_dates().min(),
+        end_date: all_dates().max(),
+        portfolio_return,
+        positions,
+        missing_prices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_period() {
+        assert_eq!(parse_period("90d").unwrap(), 90);
+        assert_eq!(parse_period("6m").unwrap(), 180);
+        assert_eq!(parse_period("1Y").unwrap(), 365);
+        assert!(parse_period("abc").is_err());
+        assert!(parse_period("0d").is_err());
+    }
+
+    #[test]
+    fn test_contributions_sum_to_chained_return() {
+        let quantities = vec![("AAA".to_string(), 10.0), ("BBB".to_string(), 5.0)];
+        let prices: HashMap<String, Vec<(NaiveDate, f64)>> = [
+            ("AAA".to_string(), vec![(day(2), 100.0), (day(3), 110.0), (day(4), 99.0)]),
+            ("BBB".to_string(), vec![(day(2), 200.0), (day(3), 190.0), (day(4), 209.0)]),
+        ]
+        .into_iter()
+        .collect();
+
+        let (positions, total) = compute_contributions(&quantities, &prices);
+
+        // Portfolio value: 2000 -> 2050 -> 2035
+        assert!((total - 1.75).abs() < 1e-9);
+        let sum: f64 = positions.iter().map(|p| p.contribution).sum();
+        assert!((sum - total).abs() < 1e-9);
+
+        let aaa = positions.iter().find(|p| p.ticker == "AAA").unwrap();
+        assert!((aaa.position_return - -1.0).abs() < 1e-9);
+        assert!((aaa.average_weight - 0.5182926829).abs() < 1e-6);
+        assert_eq!(positions[0].ticker, "BBB");
+    }
+
+    #[test]
+    fn test_position_without_prices_is_ignored() {
+        let quantities = vec![("AAA".to_string(), 1.0), ("NONE".to_string(), 1.0)];
+        let prices: HashMap<String, Vec<(NaiveDate, f64)>> =
+            [("AAA".to_string(), vec![(day(2), 10.0), (day(3), 11.0)])].into_iter().collect();
+
+        let (positions, total) = compute_contributions(&quantities, &prices);
+        assert_eq!(positions.len(), 1);
+        assert!((total - 10.0).abs() < 1e-9);
+    }
+}
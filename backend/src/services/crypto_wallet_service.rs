@@ -0,0 +1,275 @@
+//! Read-only crypto wallet import.
+//!
+//! Accounts can track public wallet addresses. A sync reads each address's
+//! balances from the chain provider, adds them up per token across the account's
+//! wallets and writes today's holdings snapshots with asset category `CRYPTO`,
+//! priced in USD as `<SYMBOL>-USD`. Tokens no longer held get a zero row so the
+//! latest-holdings view stops showing them. Average cost starts at the price on
+//! first import and blends in later additions at the price when they appear.
+
+use std::collections::{HashMap, HashSet};
+
+use bigdecimal::{BigDecimal, FromPrimitive};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::{crypto_wallet_queries, holding_snapshot_queries};
+use crate::errors::AppError;
+use crate::external::chain_provider::{ChainProvider, ExternalTokenBalance};
+use crate::external::price_provider::PriceProvider;
+use crate::models::{
+    CreateCryptoWallet, CreateHoldingSnapshot, CryptoChain, CryptoSyncResult, CryptoWallet, TickerType, CRYPTO_ASSET_CATEGORY,
+};
+use crate::services::clock;
+
+const BASE58: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Check the address format and normalize its case
+pub fn normalize_address(chain: CryptoChain, address: &str) -> Result<String, AppError> {
+    let address = address.trim();
+    let valid = match chain {
+        CryptoChain::Bitcoin => {
+            let lower = address.to_lowercase();
+            if lower.starts_with("bc1") {
+                (14..=74).contains(&lower.len()) && lower.chars().all(|c| c.is_ascii_alphanumeric())
+            } else {
+                (address.starts_with('1') || address.starts_with('3'))
+                    && (26..=35).contains(&address.len())
+                    && address.chars().all(|c| BASE58.contains(c))
+            }
+        }
+        CryptoChain::Ethereum => {
+            address.len() == 42
+                && (address.starts_with("0x") || address.starts_with("0X"))
+                && address[2..].chars().all(|c| c.is_ascii_hexdigit())
+        }
+    };
+    if !valid {
+        return Err(AppError::Validation(format!("'{}' is not a valid {} address", address, chain.as_str())));
+    }
+    Ok(match chain {
+        CryptoChain::Bitcoin if address.to_lowercase().starts_with("bc1") => address.to_lowercase(),
+        CryptoChain::Bitcoin => address.to_string(),
+        CryptoChain::Ethereum => address.to_lowercase(),
+    })
+}
+
+/// Holding ticker for a token symbol, also accepting one already in `<SYMBOL>-USD` form
+pub fn crypto_ticker(symbol: &str) -> String {
+    let symbol = symbol.trim().to_uppercase();
+    format!("{}-USD", symbol.strip_suffix("-USD").unwrap_or(&symbol))
+}
+
+/// Add up balances of the same token, keeping the first reported price
+pub fn aggregate(balances: Vec<ExternalTokenBalance>) -> Vec<ExternalTokenBalance> {
+    let mut by_symbol: Vec<ExternalTokenBalance> = Vec::new();
+    for balance in balances {
+        match by_symbol.iter_mut().find(|b| b.symbol == balance.symbol) {
+            Some(existing) => {
+                existing.quantity += balance.quantity;
+                existing.price_usd = existing.price_usd.or(balance.price_usd);
+            }
+            None => by_symbol.push(balance),
+        }
+    }
+    by_symbol.retain(|b| b.quantity > 0.0);
+    by_symbol.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    by_symbol
+}
+
+/// Average cost after moving from `previous` (quantity, average cost) to `quantity`;
+/// units added since are costed at `price`
+pub fn blended_average_cost(previous: Option<(f64, f64)>, quantity: f64, price: f64) -> f64 {
+    match previous {
+        Some((prev_qty, prev_cost)) if prev_qty > 0.0 && prev_cost > 0.0 => {
+            if quantity <= prev_qty {
+                prev_cost
+            } else {
+                (prev_qty * prev_cost + (quantity - prev_qty) * price) / quantity
+            }
+        }
+        _ => price,
+    }
+}
+
+fn decimal(value: f64, scale: i64) -> BigDecimal {
+    let factor = 10f64.powi(scale as i32);
+    BigDecimal::from_f64((value * factor).round() / factor).unwrap_or_default().with_scale(scale)
+}
+
+fn snapshot(ticker: &str, name: Option<String>, quantity: f64, price: f64, average_cost: f64) -> CreateHoldingSnapshot {
+    let market_value = quantity * price;
+    let book_value = quantity * average_cost;
+    CreateHoldingSnapshot {
+        ticker: ticker.to_string(),
+        holding_name: name,
+        asset_category: Some(CRYPTO_ASSET_CATEGORY.to_string()),
+        industry: None,
+        quantity: decimal(quantity, 8),
+        price: decimal(price, 8),
+        average_cost: decimal(average_cost, 8),
+        book_value: decimal(book_value, 2),
+        market_value: decimal(market_value, 2),
+        fund: None,
+        accrued_interest: None,
+        gain_loss: Some(decimal(market_value - book_value, 2)),
+        gain_loss_pct: (book_value > 0.0).then(|| decimal((market_value - book_value) / book_value * 100.0, 4)),
+        percentage_of_assets: None,
+        ticker_type: TickerType::Crypto,
+        currency: "USD".to_string(),
+    }
+}
+
+pub async fn add_wallet(pool: &PgPool, account_id: Uuid, data: CreateCryptoWallet) -> Result<CryptoWallet, AppError> {
+    let address = normalize_address(data.chain, &data.address)?;
+    let label = data.label.as_deref().map(str::trim).filter(|l| !l.is_empty());
+    crypto_wallet_queries::create(pool, account_id, data.chain.as_str(), &address, label)
+        .await?
+        .ok_or_else(|| AppError::Validation(format!("Wallet {} is already tracked for this account", address)))
+}
+
+/// Read every wallet of the account and write today's crypto holdings
+pub async fn sync_account(
+    pool: &PgPool,
+    account_id: Uuid,
+    chain_provider: &dyn ChainProvider,
+    price_provider: &dyn PriceProvider,
+) -> Result<CryptoSyncResult, AppError> {
+    let wallets = crypto_wallet_queries::fetch_by_account(pool, account_id).await?;
+    if wallets.is_empty() {
+        return Err(AppError::Validation(format!("Account {} has no crypto wallets", account_id)));
+    }
+    let snapshot_date = clock::today();
+    let mut result = CryptoSyncResult {
+        account_id,
+        snapshot_date,
+        holdings: 0,
+        total_value: 0.0,
+        unpriced: Vec::new(),
+        failed_wallets: Vec::new(),
+    };
+
+    let mut balances = Vec::new();
+    for wallet in &wallets {
+        let Some(chain) = wallet.chain() else { continue };
+        match chain_provider.fetch_balances(chain, &wallet.address).await {
+            Ok(wallet_balances) => balances.extend(wallet_balances),
+            Err(e) => {
+                warn!("Failed to read {} wallet {}: {}", wallet.chain, wallet.address, e);
+                crypto_wallet_queries::record_sync(pool, wallet.id, Some(&e.to_string())).await?;
+                result.failed_wallets.push(wallet.address.clone());
+            }
+        }
+    }
+    // A partial read would look like a sale of whatever the failed wallet holds
+    if !result.failed_wallets.is_empty() {
+        return Ok(result);
+    }
+
+    let previous: HashMap<String, (f64, f64)> = crypto_wallet_queries::fetch_crypto_positions(pool, account_id)
+        .await?
+        .into_iter()
+        .map(|(ticker, quantity, cost)| (ticker, (quantity, cost)))
+        .collect();
+
+    let mut seen = HashSet::new();
+    for balance in aggregate(balances) {
+        let ticker = crypto_ticker(&balance.symbol);
+        seen.insert(ticker.clone());
+        let price = match balance.price_usd {
+            Some(price) => price,
+            None => match price_provider.fetch_quote(&ticker).await {
+                Ok(quote) if quote.price > 0.0 => quote.price,
+                Ok(_) | Err(_) => {
+                    result.unpriced.push(balance.symbol.clone());
+                    continue;
+                }
+            },
+        };
+        let average_cost = blended_average_cost(previous.get(&ticker).copied(), balance.quantity, price);
+        let input = snapshot(&ticker, Some(balance.name.clone()), balance.quantity, price, average_cost);
+        holding_snapshot_queries::upsert(pool, account_id, snapshot_date, input).await?;
+        result.holdings += 1;
+        result.total_value += balance.quantity * price;
+    }
+
+    for (ticker, (quantity, cost)) in &previous {
+        if *quantity > 0.0 && !seen.contains(ticker) {
+            holding_snapshot_queries::upsert(pool, account_id, snapshot_date, snapshot(ticker, None, 0.0, 0.0, *cost)).await?;
+            result.holdings += 1;
+        }
+    }
+
+    for wallet in &wallets {
+        crypto_wallet_queries::record_sync(pool, wallet.id, None).await?;
+    }
+    info!(
+        "Synced {} crypto wallets for account {}: {} holdings worth {:.2}",
+        wallets.len(),
+        account_id,
+        result.holdings,
+        result.total_value
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(symbol: &str, quantity: f64, price: Option<f64>) -> ExternalTokenBalance {
+        ExternalTokenBalance {
+            symbol: symbol.to_string(),
+            name: symbol.to_string(),
+            quantity,
+            price_usd: price,
+        }
+    }
+
+    #[test]
+    fn test_normalize_address() {
+        assert_eq!(
+            normalize_address(CryptoChain::Ethereum, " 0xDE0B295669A9FD93D5F28D9EC85E40F4CB697BAE ").unwrap(),
+            "0xde0b295669a9fd93d5f28d9ec85e40f4cb697bae"
+        );
+        assert!(normalize_address(CryptoChain::Ethereum, "0x1234").is_err());
+        assert!(normalize_address(CryptoChain::Bitcoin, "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").is_ok());
+        assert_eq!(
+            normalize_address(CryptoChain::Bitcoin, "BC1QAR0SRRR7XFKVY5L643LYDNW9RE59GTZZWF5MDQ").unwrap(),
+            "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"
+        );
+        assert!(normalize_address(CryptoChain::Bitcoin, "0OIl-not-an-address").is_err());
+    }
+
+    #[test]
+    fn test_exchange_rows_map_to_crypto_tickers() {
+        assert_eq!(TickerType::detect("BTC", Some("Cryptocurrency")), TickerType::Crypto);
+        assert_eq!(TickerType::detect("eth-usd", None), TickerType::Equity);
+        assert_eq!(TickerType::detect("ETH-USD", None), TickerType::Crypto);
+        assert_eq!(TickerType::detect("BRK-B", Some("Equity")), TickerType::Equity);
+        assert_eq!(crypto_ticker(" btc "), "BTC-USD");
+        assert_eq!(crypto_ticker("ETH-USD"), "ETH-USD");
+    }
+
+    #[test]
+    fn test_aggregate_sums_tokens_across_wallets() {
+        let merged = aggregate(vec![
+            balance("ETH", 1.5, None),
+            balance("USDC", 100.0, Some(1.0)),
+            balance("ETH", 0.5, Some(3000.0)),
+            balance("DUST", 0.0, None),
+        ]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].symbol, "ETH");
+        assert_eq!(merged[0].quantity, 2.0);
+        assert_eq!(merged[0].price_usd, Some(3000.0));
+    }
+
+    #[test]
+    fn test_blended_average_cost() {
+        assert_eq!(blended_average_cost(None, 2.0, 100.0), 100.0);
+        assert_eq!(blended_average_cost(Some((2.0, 100.0)), 1.0, 300.0), 100.0);
+        assert_eq!(blended_average_cost(Some((1.0, 100.0)), 2.0, 300.0), 200.0);
+    }
+}
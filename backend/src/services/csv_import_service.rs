@@ -1,17 +1,20 @@
 use anyhow::{Context, Result};
 use bigdecimal::BigDecimal;
 use chrono::NaiveDate;
-use csv::ReaderBuilder;
+use csv::{ReaderBuilder, StringRecord};
 use serde::Deserialize;
-use sqlx::PgPool;
-use std::collections::HashSet;
+use sqlx::{Connection, PgConnection, PgPool};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 use std::str::FromStr;
 use uuid::Uuid;
 
-use crate::db::{account_queries, holding_snapshot_queries};
-use crate::models::{CreateAccount, CreateHoldingSnapshot};
-use crate::services::transaction_detection_service;
+use crate::db::{account_queries, csv_import_template_queries, holding_snapshot_queries};
+use crate::models::{
+    ColumnMapping, ColumnMatch, CreateAccount, CreateHoldingSnapshot, CsvImportPreview, CsvImportTemplate, HoldingField,
+    TickerType,
+};
+use crate::services::{crypto_wallet_service, fx_service, transaction_detection_service};
 
 #[derive(Debug, Deserialize)]
 struct CsvRow {
@@ -51,6 +54,17 @@ struct CsvRow {
     gain_loss_pct: String,
     #[serde(rename = "Percentage of Assets")]
     percentage_of_assets: String,
+    /// Optional; blank means the listing's currency
+    #[serde(rename = "Currency", default)]
+    currency: String,
+}
+
+/// The row's currency, or the listing's currency when the column is blank
+fn row_currency(row: &CsvRow, ticker: &str) -> Result<String> {
+    if row.currency.trim().is_empty() {
+        return Ok(fx_service::currency_for_ticker(ticker).to_string());
+    }
+    fx_service::normalize_currency(&row.currency).map_err(anyhow::Error::msg)
 }
 
 fn parse_money_string(s: &str) -> Result<BigDecimal> {
@@ -69,7 +83,7 @@ fn parse_money_string(s: &str) -> Result<BigDecimal> {
         .with_context(|| format!("Failed to parse money string: {}", s))
 }
 
-fn extract_date_from_filename(filename: &str) -> Result<NaiveDate> {
+pub fn extract_date_from_filename(filename: &str) -> Result<NaiveDate> {
     // Expected format: AccountsHoldings-YYYYMMDD.csv
     let parts: Vec<&str> = filename.split('-').collect();
     if parts.len() < 2 {
@@ -85,26 +99,336 @@ fn extract_date_from_filename(filename: &str) -> Result<NaiveDate> {
         .with_context(|| format!("Failed to parse date from filename: {}", filename))
 }
 
-pub async fn import_csv_content(
+/// Number of mapped rows returned in an import preview
+const PREVIEW_ROWS: usize = 5;
+/// Fuzzy matches scoring below this are left for the user to map
+const MIN_MATCH_CONFIDENCE: f64 = 0.5;
+
+/// Lowercase alphanumerics of a header, with `%` spelled out so "G/L" and
+/// "G/L (%)" stay distinct
+pub fn normalize_header(header: &str) -> String {
+    header
+        .replace('%', "pct")
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Identifies a statement layout: its normalized headers in order
+pub fn header_signature(headers: &[String]) -> String {
+    headers.iter().map(|h| normalize_header(h)).collect::<Vec<_>>().join(",")
+}
+
+/// How well a header names a field: 1.0 for its primary name, 0.9 for another
+/// exact alias, and up to 0.8 when an alias is contained in the header
+fn match_confidence(field: HoldingField, header: &str) -> f64 {
+    let normalized = normalize_header(header);
+    if normalized.is_empty() {
+        return 0.0;
+    }
+    let mut best: f64 = 0.0;
+    for (i, alias) in field.aliases().iter().enumerate() {
+        let score = if normalized == *alias {
+            if i == 0 { 1.0 } else { 0.9 }
+        } else if alias.len() >= 4 && normalized.contains(alias) {
+            0.5 + 0.3 * alias.len() as f64 / normalized.len() as f64
+        } else {
+            0.0
+        };
+        best = best.max(score);
+    }
+    best
+}
+
+/// Assign each field the header that names it best, strongest matches first so
+/// an exact "Account Nickname" is not taken by a fuzzy "account" match
+pub fn detect_mapping(headers: &[String]) -> Vec<ColumnMatch> {
+    let mut candidates = Vec::new();
+    for field in HoldingField::ALL {
+        for (index, header) in headers.iter().enumerate() {
+            let confidence = match_confidence(field, header);
+            if confidence >= MIN_MATCH_CONFIDENCE {
+                candidates.push((confidence, field, index));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.2.cmp(&b.2)));
+
+    let mut assigned: HashMap<HoldingField, (usize, f64)> = HashMap::new();
+    let mut used_columns = HashSet::new();
+    for (confidence, field, index) in candidates {
+        if assigned.contains_key(&field) || used_columns.contains(&index) {
+            continue;
+        }
+        assigned.insert(field, (index, confidence));
+        used_columns.insert(index);
+    }
+
+    HoldingField::ALL
+        .iter()
+        .map(|field| {
+            let matched = assigned.get(field);
+            ColumnMatch {
+                field: *field,
+                column: matched.map(|(index, _)| headers[*index].clone()),
+                confidence: matched.map(|(_, c)| *c).unwrap_or(0.0),
+                required: field.is_required(),
+            }
+        })
+        .collect()
+}
+
+/// Column index of every mapped field; errors when a required field is
+/// unmapped or a mapped column is not in the file
+fn column_indices(headers: &[String], mapping: &ColumnMapping) -> Result<BTreeMap<HoldingField, usize>> {
+    let missing: Vec<&str> = HoldingField::ALL
+        .iter()
+        .filter(|f| f.is_required() && !mapping.contains_key(f))
+        .map(|f| f.as_str())
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!("Required fields are not mapped: {}", missing.join(", "));
+    }
+
+    let mut columns = BTreeMap::new();
+    for (field, column) in mapping {
+        let index = headers
+            .iter()
+            .position(|h| h.trim() == column.trim())
+            .with_context(|| format!("Column '{}' mapped to {} is not in the file", column, field.as_str()))?;
+        columns.insert(*field, index);
+    }
+    Ok(columns)
+}
+
+fn mapped_values(record: &StringRecord, columns: &BTreeMap<HoldingField, usize>) -> BTreeMap<HoldingField, String> {
+    columns
+        .iter()
+        .map(|(field, index)| (*field, record.get(*index).unwrap_or("").trim().to_string()))
+        .collect()
+}
+
+/// Build a row in the Raymond James layout from a mapped record, deriving
+/// values the statement does not carry
+fn mapped_row(record: &StringRecord, columns: &BTreeMap<HoldingField, usize>) -> CsvRow {
+    let values = mapped_values(record, columns);
+    let get = |field: HoldingField| values.get(&field).cloned().unwrap_or_default();
+    let product = |a: &str, b: &str| -> String {
+        if a.is_empty() || b.is_empty() {
+            return String::new();
+        }
+        match (parse_money_string(a), parse_money_string(b)) {
+            (Ok(a), Ok(b)) => (a * b).to_string(),
+            _ => String::new(),
+        }
+    };
+
+    let account_number = get(HoldingField::AccountNumber);
+    let quantity = get(HoldingField::Quantity);
+    let price = get(HoldingField::Price);
+    let mut account_nickname = get(HoldingField::AccountNickname);
+    if account_nickname.is_empty() {
+        account_nickname = account_number.clone();
+    }
+    let mut average_cost = get(HoldingField::AverageCost);
+    if average_cost.is_empty() {
+        average_cost = price.clone();
+    }
+    let mut market_value = get(HoldingField::MarketValue);
+    if market_value.is_empty() {
+        market_value = product(&quantity, &price);
+    }
+    let mut book_value = get(HoldingField::BookValue);
+    if book_value.is_empty() {
+        book_value = product(&quantity, &average_cost);
+    }
+
+    CsvRow {
+        client_name: get(HoldingField::ClientName),
+        client_id: get(HoldingField::ClientId),
+        account_nickname,
+        account_number,
+        asset_category: get(HoldingField::AssetCategory),
+        industry: get(HoldingField::Industry),
+        symbol: get(HoldingField::Symbol),
+        holding: get(HoldingField::Holding),
+        quantity,
+        price,
+        fund: get(HoldingField::Fund),
+        average_cost,
+        book_value,
+        market_value,
+        accrued_interest: get(HoldingField::AccruedInterest),
+        gain_loss: get(HoldingField::GainLoss),
+        gain_loss_pct: get(HoldingField::GainLossPct),
+        percentage_of_assets: get(HoldingField::PercentageOfAssets),
+        currency: get(HoldingField::Currency),
+    }
+}
+
+/// Build the preview for an upload. A saved template for the same headers
+/// supplies the mapping at full confidence; otherwise it is auto-detected.
+pub fn build_preview(content: &str, template: Option<CsvImportTemplate>) -> Result<CsvImportPreview> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_string()).collect();
+    if headers.iter().all(|h| h.trim().is_empty()) {
+        anyhow::bail!("The file has no header row");
+    }
+
+    let template = template.filter(|t| column_indices(&headers, &t.mapping.0).is_ok());
+    let matches = match &template {
+        Some(t) => HoldingField::ALL
+            .iter()
+            .map(|field| {
+                let column = t.mapping.0.get(field).cloned();
+                ColumnMatch {
+                    field: *field,
+                    confidence: if column.is_some() { 1.0 } else { 0.0 },
+                    column,
+                    required: field.is_required(),
+                }
+            })
+            .collect(),
+        None => detect_mapping(&headers),
+    };
+
+    let mapping: ColumnMapping = matches
+        .iter()
+        .filter_map(|m| m.column.clone().map(|c| (m.field, c)))
+        .collect();
+    let missing_required: Vec<HoldingField> = matches
+        .iter()
+        .filter(|m| m.required && m.column.is_none())
+        .map(|m| m.field)
+        .collect();
+    let confidence = if missing_required.is_empty() {
+        matches
+            .iter()
+            .filter(|m| m.required)
+            .map(|m| m.confidence)
+            .fold(1.0, f64::min)
+    } else {
+        0.0
+    };
+
+    let columns: BTreeMap<HoldingField, usize> = mapping
+        .iter()
+        .filter_map(|(field, column)| headers.iter().position(|h| h == column).map(|i| (*field, i)))
+        .collect();
+    let mut sample_rows = Vec::new();
+    let mut total_rows = 0;
+    for record in reader.records() {
+        let record = record?;
+        if sample_rows.len() < PREVIEW_ROWS {
+            sample_rows.push(mapped_values(&record, &columns));
+        }
+        total_rows += 1;
+    }
+
+    Ok(CsvImportPreview {
+        header_signature: header_signature(&headers),
+        headers,
+        matches,
+        mapping,
+        missing_required,
+        confidence,
+        template,
+        sample_rows,
+        total_rows,
+    })
+}
+
+/// Preview an upload for `user_id`, applying their most recently used template
+/// for the same headers (and broker, when given)
+pub async fn preview_csv_content(
     pool: &PgPool,
+    user_id: Uuid,
+    content: &str,
+    broker: Option<&str>,
+) -> Result<CsvImportPreview> {
+    let signature = csv_header_signature(content)?;
+    let template = csv_import_template_queries::find_by_signature(pool, user_id, &signature, broker).await?;
+    build_preview(content, template)
+}
+
+pub fn csv_header_signature(content: &str) -> Result<String> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_string()).collect();
+    Ok(header_signature(&headers))
+}
+
+pub async fn import_csv_content(
+    conn: &mut PgConnection,
+    portfolio_id: Uuid,
+    content: &str,
+    snapshot_date: NaiveDate,
+) -> Result<ImportResult> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(content.as_bytes());
+
+    let rows = reader
+        .deserialize::<CsvRow>()
+        .map(|result| result.map_err(|e| format!("Failed to parse CSV row: {}", e)));
+
+    import_rows(&mut *conn, portfolio_id, snapshot_date, rows).await
+}
+
+/// Import a holdings CSV in any broker's layout, reading each field from the
+/// column `mapping` assigns it. Required fields must be mapped; unmapped
+/// values are derived where possible (market value from quantity and price,
+/// book value from quantity and average cost) and left empty otherwise.
+pub async fn import_mapped_csv_content(
+    conn: &mut PgConnection,
     portfolio_id: Uuid,
     content: &str,
     snapshot_date: NaiveDate,
+    mapping: &ColumnMapping,
 ) -> Result<ImportResult> {
     let mut reader = ReaderBuilder::new()
         .has_headers(true)
+        .flexible(true)
         .from_reader(content.as_bytes());
 
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_string()).collect();
+    let columns = column_indices(&headers, mapping)?;
+
+    let rows = reader.records().map(|result| {
+        result
+            .map(|record| mapped_row(&record, &columns))
+            .map_err(|e| format!("Failed to parse CSV row: {}", e))
+    });
+
+    import_rows(&mut *conn, portfolio_id, snapshot_date, rows).await
+}
+
+async fn import_rows(
+    conn: &mut PgConnection,
+    portfolio_id: Uuid,
+    snapshot_date: NaiveDate,
+    rows: impl Iterator<Item = std::result::Result<CsvRow, String>>,
+) -> Result<ImportResult> {
     let mut accounts_created = 0;
     let mut holdings_created = 0;
     let mut errors = Vec::new();
     let mut accounts_with_holdings = HashSet::new();
 
-    for (line_num, result) in reader.deserialize::<CsvRow>().enumerate() {
+    // Imports run in a transaction; each row gets a savepoint so a failed row
+    // is reported without aborting the rest
+    for (line_num, result) in rows.enumerate() {
         match result {
             Ok(row) => {
-                match process_row(pool, portfolio_id, snapshot_date, row).await {
+                let mut savepoint = conn.begin().await?;
+                match process_row(&mut savepoint, portfolio_id, snapshot_date, row).await {
                     Ok((account_new, holding_new, account_id)) => {
+                        savepoint.commit().await?;
                         if account_new {
                             accounts_created += 1;
                         }
@@ -116,12 +440,13 @@ pub async fn import_csv_content(
                         }
                     }
                     Err(e) => {
+                        savepoint.rollback().await?;
                         errors.push(format!("Line {}: {}", line_num + 2, e));
                     }
                 }
             }
             Err(e) => {
-                errors.push(format!("Line {}: Failed to parse CSV row: {}", line_num + 2, e));
+                errors.push(format!("Line {}: {}", line_num + 2, e));
             }
         }
     }
@@ -129,15 +454,20 @@ pub async fn import_csv_content(
     // Detect transactions for all accounts that received holdings in this snapshot
     let mut transactions_detected = 0;
     for account_id in accounts_with_holdings {
+        let mut savepoint = conn.begin().await?;
         match transaction_detection_service::detect_transactions_for_new_snapshot(
-            pool,
+            &mut savepoint,
             account_id,
             snapshot_date,
         )
         .await
         {
-            Ok(count) => transactions_detected += count,
+            Ok(count) => {
+                savepoint.commit().await?;
+                transactions_detected += count;
+            }
             Err(e) => {
+                savepoint.rollback().await?;
                 errors.push(format!("Transaction detection failed for account {}: {}", account_id, e));
             }
         }
@@ -153,7 +483,7 @@ pub async fn import_csv_content(
 }
 
 pub async fn import_csv_file(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     portfolio_id: Uuid,
     file_path: &Path,
 ) -> Result<ImportResult> {
@@ -167,11 +497,11 @@ pub async fn import_csv_file(
     let file_content = std::fs::read_to_string(file_path)
         .with_context(|| format!("Failed to read file: {:?}", file_path))?;
 
-    import_csv_content(pool, portfolio_id, &file_content, snapshot_date).await
+    import_csv_content(&mut *conn, portfolio_id, &file_content, snapshot_date).await
 }
 
 async fn process_row(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     portfolio_id: Uuid,
     snapshot_date: NaiveDate,
     row: CsvRow,
@@ -186,13 +516,13 @@ async fn process_row(
         };
 
         let existing_account = account_queries::find_by_account_number(
-            pool,
+            &mut *conn,
             portfolio_id,
             &row.account_number,
         ).await?;
 
         let account_new = existing_account.is_none();
-        let account = account_queries::upsert(pool, portfolio_id, account_data).await?;
+        let account = account_queries::upsert(&mut *conn, portfolio_id, account_data).await?;
 
         // Create holding snapshot for cash with empty ticker
         let quantity = parse_money_string(&row.quantity)?;
@@ -214,18 +544,20 @@ async fn process_row(
             gain_loss: None,
             gain_loss_pct: None,
             percentage_of_assets: None,
+            ticker_type: TickerType::Equity,
+            currency: row_currency(&row, "")?,
         };
 
         // Check if cash holding already exists for this snapshot
         let existing_holdings = holding_snapshot_queries::fetch_by_account_and_date(
-            pool,
+            &mut *conn,
             account.id,
             snapshot_date,
         ).await?;
 
         let holding_new = !existing_holdings.iter().any(|h| h.ticker.is_empty());
 
-        holding_snapshot_queries::upsert(pool, account.id, snapshot_date, holding_data).await?;
+        holding_snapshot_queries::upsert(&mut *conn, account.id, snapshot_date, holding_data).await?;
 
         return Ok((account_new, holding_new, Some(account.id)));
     }
@@ -244,14 +576,14 @@ async fn process_row(
     };
 
     let existing_account = account_queries::find_by_account_number(
-        pool,
+        &mut *conn,
         portfolio_id,
         &row.account_number,
     ).await?;
 
     let account_new = existing_account.is_none();
 
-    let account = account_queries::upsert(pool, portfolio_id, account_data).await?;
+    let account = account_queries::upsert(&mut *conn, portfolio_id, account_data).await?;
 
     // Parse holding data
     let holding_name = if row.holding.trim().is_empty() {
@@ -308,8 +640,17 @@ async fn process_row(
         Some(parse_money_string(&row.percentage_of_assets)?)
     };
 
+    // Exchange exports list coins by bare symbol; store them under the same
+    // `<SYMBOL>-USD` ticker wallet imports use so they get priced
+    let ticker_type = TickerType::detect(&row.symbol, asset_category.as_deref());
+    let ticker = match ticker_type {
+        TickerType::Crypto => crypto_wallet_service::crypto_ticker(&row.symbol),
+        TickerType::Equity => row.symbol.clone(),
+    };
+    let currency = row_currency(&row, &ticker)?;
+
     let holding_data = CreateHoldingSnapshot {
-        ticker: row.symbol.clone(),
+        ticker,
         holding_name,
         asset_category,
         industry,
@@ -323,18 +664,20 @@ async fn process_row(
         gain_loss,
         gain_loss_pct,
         percentage_of_assets,
+        ticker_type,
+        currency,
     };
 
     // Check if holding already exists
     let existing_holdings = holding_snapshot_queries::fetch_by_account_and_date(
-        pool,
+        &mut *conn,
         account.id,
         snapshot_date,
     ).await?;
 
     let holding_new = !existing_holdings.iter().any(|h| h.ticker == row.symbol);
 
-    holding_snapshot_queries::upsert(pool, account.id, snapshot_date, holding_data).await?;
+    holding_snapshot_queries::upsert(&mut *conn, account.id, snapshot_date, holding_data).await?;
 
     Ok((account_new, holding_new, Some(account.id)))
 }
@@ -347,3 +690,98 @@ pub struct ImportResult {
     pub errors: Vec<String>,
     pub snapshot_date: NaiveDate,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|h| h.to_string()).collect()
+    }
+
+    fn column_for(matches: &[ColumnMatch], field: HoldingField) -> Option<&str> {
+        matches.iter().find(|m| m.field == field).and_then(|m| m.column.as_deref())
+    }
+
+    #[test]
+    fn test_detect_mapping_raymond_james_headers() {
+        let content = "Client Name,Client Id,Account Nickname,Account Number,Asset Category,Industry,Symbol,Holding,Quantity,Price,Fund,Average Cost,Book Value,Market Value,Accrued Interest,G/L,G/L (%),Percentage of Assets\n";
+        let preview = build_preview(content, None).unwrap();
+
+        // The layout has no currency column; the listing's currency is used
+        assert!(preview
+            .matches
+            .iter()
+            .filter(|m| m.field != HoldingField::Currency)
+            .all(|m| m.column.is_some() && m.confidence == 1.0));
+        assert_eq!(column_for(&preview.matches, HoldingField::Currency), None);
+        assert_eq!(column_for(&preview.matches, HoldingField::GainLoss), Some("G/L"));
+        assert_eq!(column_for(&preview.matches, HoldingField::GainLossPct), Some("G/L (%)"));
+        assert!(preview.missing_required.is_empty());
+        assert_eq!(preview.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_detect_mapping_other_broker_headers() {
+        let matches = detect_mapping(&headers(&[
+            "Account", "Ticker", "Description", "Shares", "Last Price", "Cost Basis", "Market Value (CAD)", "Ccy",
+        ]));
+
+        assert_eq!(column_for(&matches, HoldingField::AccountNumber), Some("Account"));
+        assert_eq!(column_for(&matches, HoldingField::Symbol), Some("Ticker"));
+        assert_eq!(column_for(&matches, HoldingField::Quantity), Some("Shares"));
+        assert_eq!(column_for(&matches, HoldingField::Price), Some("Last Price"));
+        assert_eq!(column_for(&matches, HoldingField::BookValue), Some("Cost Basis"));
+        let market_value = matches.iter().find(|m| m.field == HoldingField::MarketValue).unwrap();
+        assert_eq!(market_value.column.as_deref(), Some("Market Value (CAD)"));
+        assert!(market_value.confidence > MIN_MATCH_CONFIDENCE && market_value.confidence < 0.9);
+        assert_eq!(column_for(&matches, HoldingField::AverageCost), None);
+        assert_eq!(column_for(&matches, HoldingField::Currency), Some("Ccy"));
+    }
+
+    #[test]
+    fn test_preview_reports_missing_required_fields() {
+        let preview = build_preview("Ticker,Shares\nAAPL,10\n", None).unwrap();
+
+        assert_eq!(preview.missing_required, vec![HoldingField::AccountNumber, HoldingField::Price]);
+        assert_eq!(preview.confidence, 0.0);
+        assert_eq!(preview.total_rows, 1);
+        assert_eq!(preview.sample_rows[0].get(&HoldingField::Symbol).map(String::as_str), Some("AAPL"));
+    }
+
+    #[test]
+    fn test_mapped_row_derives_missing_values() {
+        let headers = headers(&["Acct", "Ticker", "Qty", "Last"]);
+        let mapping: ColumnMapping = [
+            (HoldingField::AccountNumber, "Acct"),
+            (HoldingField::Symbol, "Ticker"),
+            (HoldingField::Quantity, "Qty"),
+            (HoldingField::Price, "Last"),
+        ]
+        .into_iter()
+        .map(|(f, c)| (f, c.to_string()))
+        .collect();
+        let columns = column_indices(&headers, &mapping).unwrap();
+
+        let row = mapped_row(&StringRecord::from(vec!["123", "VFV", "10", "$1,250.50"]), &columns);
+
+        assert_eq!(row.account_nickname, "123");
+        assert_eq!(row.average_cost, "$1,250.50");
+        assert_eq!(parse_money_string(&row.market_value).unwrap(), BigDecimal::from_str("12505.00").unwrap());
+        assert_eq!(parse_money_string(&row.book_value).unwrap(), BigDecimal::from_str("12505.00").unwrap());
+    }
+
+    #[test]
+    fn test_column_indices_rejects_incomplete_mapping() {
+        let mapping: ColumnMapping = [(HoldingField::Symbol, "Ticker".to_string())].into_iter().collect();
+        let err = column_indices(&headers(&["Ticker"]), &mapping).unwrap_err().to_string();
+        assert!(err.contains("account_number"));
+
+        let mut mapping = mapping;
+        mapping.insert(HoldingField::AccountNumber, "Account".to_string());
+        mapping.insert(HoldingField::Quantity, "Ticker".to_string());
+        mapping.insert(HoldingField::Price, "Ticker".to_string());
+        let err = column_indices(&headers(&["Ticker"]), &mapping).unwrap_err().to_string();
+        assert!(err.contains("'Account'"));
+    }
+}
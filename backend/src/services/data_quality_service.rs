@@ -0,0 +1,255 @@
+//! Price history quality checks.
+//!
+//! Analytics read `price_points` as if every trading day had one positive
+//! close. Provider outages, partial refreshes and symbol changes break that
+//! quietly: a missing week flattens volatility, a zero close shows up as a
+//! -100% return. The scan reports missing trading days, dates stored under
+//! more than one spelling of the ticker, and zero or negative closes; repair
+//! re-fetches the affected range and stores only the closes that fill a gap or
+//! replace an invalid one.
+//!
+//! Trading days come from the benchmark's stored closes, the closest thing to
+//! an exchange calendar the database has. Outside the benchmark's history, or
+//! for the benchmark itself, every weekday counts.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::db::price_queries;
+use crate::errors::AppError;
+use crate::external::price_provider::{PriceProvider, PriceProviderError};
+use crate::models::{DuplicatePriceDate, InvalidClose, PriceGap, PriceQualityReport, PriceRepairResult};
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::risk_service::BETA_BENCHMARKS;
+use crate::services::{clock, price_service, risk_memo};
+
+/// Margin added to the fetch window so the oldest target day is covered
+const FETCH_MARGIN_DAYS: i64 = 7;
+
+fn is_weekday(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Trading days from `first` through `last`: the reference calendar's dates
+/// where it has history, weekdays elsewhere
+fn trading_days(first: NaiveDate, last: NaiveDate, calendar: &BTreeSet<NaiveDate>) -> Vec<NaiveDate> {
+    let covered = calendar.first().zip(calendar.last());
+    first
+        .iter_days()
+        .take_while(|d| *d <= last)
+        .filter(|d| match covered {
+            Some((start, end)) if d >= start && d <= end => calendar.contains(d),
+            _ => is_weekday(*d),
+        })
+        .collect()
+}
+
+/// Runs of trading days without a close between consecutive stored dates
+fn find_gaps(stored: &BTreeSet<NaiveDate>, trading_days: &[NaiveDate]) -> Vec<PriceGap> {
+    let mut gaps = Vec::new();
+    let mut previous: Option<NaiveDate> = None;
+    let mut missing = 0;
+    for day in trading_days {
+        if stored.contains(day) {
+            if let Some(after) = previous.filter(|_| missing > 0) {
+                gaps.push(PriceGap { after, before: *day, missing_days: missing });
+            }
+            previous = Some(*day);
+            missing = 0;
+        } else if previous.is_some() {
+            missing += 1;
+        }
+    }
+    gaps
+}
+
+/// Report over every stored row for any spelling of `ticker`
+pub fn assess(ticker: &str, rows: &[(String, NaiveDate, f64)], calendar: &BTreeSet<NaiveDate>) -> PriceQualityReport {
+    let mut by_date: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+    for (symbol, date, _) in rows {
+        by_date.entry(*date).or_default().push(symbol.clone());
+    }
+    let stored: BTreeSet<NaiveDate> = by_date.keys().copied().collect();
+    let first_date = stored.first().copied();
+    let last_date = stored.last().copied();
+    let days = match first_date.zip(last_date) {
+        Some((first, last)) => trading_days(first, last, calendar),
+        None => Vec::new(),
+    };
+    let gaps = find_gaps(&stored, &days);
+
+    PriceQualityReport {
+        ticker: ticker.to_string(),
+        first_date,
+        last_date,
+        points: rows.len(),
+        expected_points: days.len(),
+        missing_days: gaps.iter().map(|g| g.missing_days).sum(),
+        gaps,
+        duplicate_dates: by_date
+            .into_iter()
+            .filter(|(_, symbols)| symbols.len() > 1)
+            .map(|(date, tickers)| DuplicatePriceDate { date, tickers })
+            .collect(),
+        invalid_closes: rows
+            .iter()
+            .filter(|(symbol, _, close)| symbol == ticker && *close <= 0.0)
+            .map(|(_, date, close)| InvalidClose { date: *date, close: *close })
+            .collect(),
+    }
+}
+
+/// Dates a repair should fetch: the missing trading days and the invalid closes
+fn repair_targets(report: &PriceQualityReport, calendar: &BTreeSet<NaiveDate>) -> BTreeSet<NaiveDate> {
+    let mut targets: BTreeSet<NaiveDate> = report.invalid_closes.iter().map(|c| c.date).collect();
+    for gap in &report.gaps {
+        let inside = trading_days(gap.after + Duration::days(1), gap.before - Duration::days(1), calendar);
+        targets.extend(inside);
+    }
+    targets
+}
+
+async fn calendar(pool: &PgPool, ticker: &str, rows: &[(String, NaiveDate, f64)]) -> Result<BTreeSet<NaiveDate>, AppError> {
+    let reference = BETA_BENCHMARKS[0];
+    let (Some((_, first, _)), Some((_, last, _))) = (rows.first(), rows.last()) else {
+        return Ok(BTreeSet::new());
+    };
+    if ticker == reference {
+        return Ok(BTreeSet::new());
+    }
+    Ok(price_queries::fetch_dates(pool, reference, *first, *last).await?.into_iter().collect())
+}
+
+/// The report and the trading calendar it was measured against
+async fn scan(pool: &PgPool, ticker: &str) -> Result<(PriceQualityReport, BTreeSet<NaiveDate>), AppError> {
+    let ticker = ticker.trim().to_uppercase();
+    if !price_service::is_valid_ticker(&ticker) {
+        return Err(AppError::Validation(format!("Invalid ticker: {}", ticker)));
+    }
+    let rows = price_queries::fetch_closes_any_case(pool, &ticker).await?;
+    if rows.is_empty() {
+        return Err(AppError::NotFound(format!("No prices stored for {}", ticker)));
+    }
+    let calendar = calendar(pool, &ticker, &rows).await?;
+    Ok((assess(&ticker, &rows, &calendar), calendar))
+}
+
+pub async fn check(pool: &PgPool, ticker: &str) -> Result<PriceQualityReport, AppError> {
+    Ok(scan(pool, ticker).await?.0)
+}
+
+/// Re-fetch the range covering the ticker's gaps and invalid closes and store
+/// the closes for those days. Stored valid closes are left as they are.
+pub async fn repair(
+    pool: &PgPool,
+    provider: &dyn PriceProvider,
+    rate_limiter: &RateLimiter,
+    ticker: &str,
+) -> Result<PriceRepairResult, AppError> {
+    let (before, calendar) = scan(pool, ticker).await?;
+    let targets = repair_targets(&before, &calendar);
+    let Some(oldest) = targets.first() else {
+        return Ok(PriceRepairResult { filled: 0, replaced: 0, report: before });
+    };
+
+    let days = (clock::today() - *oldest).num_days() + FETCH_MARGIN_DAYS;
+    let fetched = {
+        let _guard = rate_limiter.acquire().await;
+        provider.fetch_daily_history(&before.ticker, days.max(1) as u32).await
+    };
+    let points = match fetched {
+        Ok(points) => points,
+        Err(PriceProviderError::RateLimited) => {
+            rate_limiter.record_rejection();
+            return Err(AppError::RateLimited);
+        }
+        Err(e) => return Err(AppError::External(e.to_string())),
+    };
+
+    let repairs: Vec<_> = points
+        .into_iter()
+        .filter(|p| targets.contains(&p.date) && p.close > bigdecimal::BigDecimal::from(0))
+        .collect();
+    if !repairs.is_empty() {
+        price_queries::upsert_external_points(pool, &before.ticker, &repairs).await?;
+        price_service::apply_corporate_actions(pool, &before.ticker).await?;
+        risk_memo::invalidate(&before.ticker);
+    }
+
+    let replaced = repairs
+        .iter()
+        .filter(|p| before.invalid_closes.iter().any(|c| c.date == p.date))
+        .count();
+    let filled = repairs.len() - replaced;
+    let report = check(pool, &before.ticker).await?;
+    if !report.is_clean() {
+        warn!(
+            "{} still has {} missing days, {} duplicate dates and {} invalid closes after repair",
+            report.ticker,
+            report.missing_days,
+            report.duplicate_dates.len(),
+            report.invalid_closes.len()
+        );
+    }
+    info!("Repaired {}: {} days filled, {} closes replaced", report.ticker, filled, replaced);
+    Ok(PriceRepairResult { filled, replaced, report })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, d).unwrap()
+    }
+
+    fn rows(ticker: &str, closes: &[(NaiveDate, f64)]) -> Vec<(String, NaiveDate, f64)> {
+        closes.iter().map(|(d, c)| (ticker.to_string(), *d, *c)).collect()
+    }
+
+    #[test]
+    fn test_gaps_skip_weekends() {
+        // Fri 6 Mar, then Mon 9, Wed 11 and Mon 16: the 10th, 12th and 13th are missing
+        let stored = rows("AAPL", &[(day(3, 6), 1.0), (day(3, 9), 1.0), (day(3, 11), 1.0), (day(3, 16), 1.0)]);
+        let report = assess("AAPL", &stored, &BTreeSet::new());
+        assert_eq!(report.expected_points, 7);
+        assert_eq!(
+            report.gaps,
+            vec![
+                PriceGap { after: day(3, 9), before: day(3, 11), missing_days: 1 },
+                PriceGap { after: day(3, 11), before: day(3, 16), missing_days: 2 },
+            ]
+        );
+        assert_eq!(report.missing_days, 3);
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn test_holidays_follow_the_reference_calendar() {
+        // Good Friday, 3 Apr 2026: the benchmark has no close either
+        let calendar: BTreeSet<NaiveDate> = [day(4, 1), day(4, 2), day(4, 6), day(4, 7)].into_iter().collect();
+        let stored = rows("AAPL", &[(day(4, 1), 1.0), (day(4, 2), 1.0), (day(4, 6), 1.0), (day(4, 7), 1.0)]);
+        let report = assess("AAPL", &stored, &calendar);
+        assert!(report.is_clean());
+        assert_eq!(report.expected_points, 4);
+    }
+
+    #[test]
+    fn test_duplicates_and_invalid_closes() {
+        let mut stored = rows("AAPL", &[(day(3, 9), 0.0), (day(3, 10), 2.0), (day(3, 11), -1.0)]);
+        stored.push(("aapl".to_string(), day(3, 10), 2.0));
+        let report = assess("AAPL", &stored, &BTreeSet::new());
+        assert_eq!(
+            report.duplicate_dates,
+            vec![DuplicatePriceDate { date: day(3, 10), tickers: vec!["AAPL".to_string(), "aapl".to_string()] }]
+        );
+        assert_eq!(report.invalid_closes.len(), 2);
+        assert!(report.gaps.is_empty());
+
+        let targets = repair_targets(&report, &BTreeSet::new());
+        assert_eq!(targets.into_iter().collect::<Vec<_>>(), vec![day(3, 9), day(3, 11)]);
+    }
+}
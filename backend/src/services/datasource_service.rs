@@ -0,0 +1,246 @@
+//! Grafana SimpleJSON datasource.
+//!
+//! Implements the SimpleJSON contract (also understood by the Infinity
+//! plugin): `/search` lists the metrics a user can chart and `/query` returns
+//! them as `[value, epoch ms]` time series. Each metric is a target of the
+//! form `<portfolio id>:<metric>`:
+//!
+//! - `value` - total portfolio value per valuation date
+//! - `risk_score` - risk score of each daily risk snapshot
+//! - `factor:<factor>` - current factor exposure (0-100), e.g. `factor:momentum`.
+//!   Exposures are not stored historically, so this series has a single point
+//!   at the time of the query.
+
+use bigdecimal::ToPrimitive;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{holding_snapshot_queries, portfolio_queries, risk_snapshot_queries};
+use crate::errors::AppError;
+use crate::external::price_provider::PriceProvider;
+use crate::models::factor::FactorType;
+use crate::models::{DatasourceMetric, DatasourceQueryRequest, DatasourceRange, DatasourceSeries, Portfolio};
+use crate::services::behavioral_analytics_service::portfolio_values;
+use crate::services::failure_cache::FailureCache;
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::{clock, factor_service};
+
+/// Days of closes used to score factor exposures, as in factor analysis
+const FACTOR_LOOKBACK_DAYS: i64 = 252;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Metric {
+    Value,
+    RiskScore,
+    Factor(FactorType),
+}
+
+impl Metric {
+    pub fn all() -> Vec<Metric> {
+        let mut metrics = vec![Metric::Value, Metric::RiskScore];
+        metrics.extend(FactorType::all().into_iter().map(Metric::Factor));
+        metrics
+    }
+
+    pub fn key(&self) -> String {
+        match self {
+            Metric::Value => "value".to_string(),
+            Metric::RiskScore => "risk_score".to_string(),
+            Metric::Factor(factor) => format!("factor:{}", factor.as_str()),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Metric::Value => "value".to_string(),
+            Metric::RiskScore => "risk score".to_string(),
+            Metric::Factor(factor) => format!("{} exposure", factor.label().to_lowercase()),
+        }
+    }
+}
+
+/// Portfolio and metric named by a target such as "<portfolio id>:factor:value"
+pub fn parse_target(target: &str) -> Option<(Uuid, Metric)> {
+    let (portfolio_id, metric) = target.trim().split_once(':')?;
+    let portfolio_id = Uuid::parse_str(portfolio_id).ok()?;
+    let metric = Metric::all().into_iter().find(|m| m.key() == metric)?;
+    Some((portfolio_id, metric))
+}
+
+/// At most `max_points` points spread evenly over the series, always keeping the latest
+pub fn downsample(points: Vec<(f64, i64)>, max_points: usize) -> Vec<(f64, i64)> {
+    if max_points == 0 || points.len() <= max_points {
+        return points;
+    }
+    let last = points.len() - 1;
+    let step = last as f64 / (max_points - 1).max(1) as f64;
+    let mut indices: Vec<usize> = (0..max_points).map(|i| (i as f64 * step).round() as usize).collect();
+    indices.dedup();
+    indices.into_iter().map(|i| points[i.min(last)]).collect()
+}
+
+fn epoch_ms(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp_millis()
+}
+
+/// Daily points of the series falling within the range, as [value, epoch ms]
+pub fn daily_points(series: &[(NaiveDate, f64)], range: &DatasourceRange) -> Vec<(f64, i64)> {
+    let (from, to) = (range.from.date_naive(), range.to.date_naive());
+    series
+        .iter()
+        .filter(|(date, _)| *date >= from && *date <= to)
+        .map(|(date, value)| (*value, epoch_ms(*date)))
+        .collect()
+}
+
+/// Every metric of the user's active portfolios whose name matches `filter`
+pub async fn search(pool: &PgPool, user_id: Uuid, filter: &str) -> Result<Vec<DatasourceMetric>, AppError> {
+    let filter = filter.trim().to_lowercase();
+    let portfolios = portfolio_queries::fetch_all(pool, user_id, false).await?;
+    Ok(portfolios
+        .iter()
+        .flat_map(|portfolio| {
+            Metric::all().into_iter().map(move |metric| DatasourceMetric {
+                text: format!("{} {}", portfolio.name, metric.label()),
+                value: format!("{}:{}", portfolio.id, metric.key()),
+            })
+        })
+        .filter(|m| filter.is_empty() || m.text.to_lowercase().contains(&filter))
+        .collect())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn metric_points(
+    pool: &PgPool,
+    price_provider: &dyn PriceProvider,
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
+    risk_free_rate: f64,
+    portfolio: &Portfolio,
+    metric: &Metric,
+    range: &DatasourceRange,
+) -> Result<Vec<(f64, i64)>, AppError> {
+    match metric {
+        Metric::Value => {
+            let history = holding_snapshot_queries::fetch_portfolio_value_history(pool, portfolio.id).await?;
+            Ok(daily_points(&portfolio_values(&history), range))
+        }
+        Metric::RiskScore => {
+            let snapshots = risk_snapshot_queries::fetch_history(
+                pool,
+                portfolio.id,
+                None,
+                range.from.date_naive(),
+                range.to.date_naive(),
+            )
+            .await?;
+            let series: Vec<(NaiveDate, f64)> = snapshots
+                .iter()
+                .map(|s| (s.snapshot_date, s.risk_score.to_f64().unwrap_or(0.0)))
+                .collect();
+            Ok(daily_points(&series, range))
+        }
+        Metric::Factor(factor) => {
+            let now: DateTime<Utc> = clock::now();
+            if now < range.from || now > range.to {
+                return Ok(Vec::new());
+            }
+            let exposures = factor_service::current_exposures(
+                pool,
+                portfolio.id,
+                price_provider,
+                failure_cache,
+                rate_limiter,
+                risk_free_rate,
+                FACTOR_LOOKBACK_DAYS,
+            )
+            .await?;
+            Ok(exposures
+                .iter()
+                .find(|e| e.factor == *factor)
+                .map(|e| vec![(e.score, now.timestamp_millis())])
+                .unwrap_or_default())
+        }
+    }
+}
+
+/// One series per target, in the order requested
+pub async fn query(
+    pool: &PgPool,
+    price_provider: &dyn PriceProvider,
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
+    risk_free_rate: f64,
+    user_id: Uuid,
+    request: &DatasourceQueryRequest,
+) -> Result<Vec<DatasourceSeries>, AppError> {
+    let mut series = Vec::new();
+    for target in request.targets.iter().filter_map(|t| t.target.as_deref()) {
+        if target.trim().is_empty() {
+            continue;
+        }
+        let (portfolio_id, metric) = parse_target(target)
+            .ok_or_else(|| AppError::Validation(format!("Unknown target '{}'", target)))?;
+        let portfolio = portfolio_queries::fetch_one(pool, portfolio_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))?;
+
+        let points = metric_points(
+            pool,
+            price_provider,
+            failure_cache,
+            rate_limiter,
+            risk_free_rate,
+            &portfolio,
+            &metric,
+            &request.range,
+        )
+        .await?;
+        series.push(DatasourceSeries {
+            target: format!("{} {}", portfolio.name, metric.label()),
+            datapoints: downsample(points, request.max_data_points.unwrap_or(0)),
+        });
+    }
+    Ok(series)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        let id = Uuid::new_v4();
+        assert_eq!(parse_target(&format!("{}:value", id)), Some((id, Metric::Value)));
+        assert_eq!(parse_target(&format!("{}:risk_score", id)), Some((id, Metric::RiskScore)));
+        assert_eq!(
+            parse_target(&format!("{}:factor:low_volatility", id)),
+            Some((id, Metric::Factor(FactorType::LowVolatility)))
+        );
+        assert_eq!(parse_target(&format!("{}:factor:size", id)), None);
+        assert_eq!(parse_target("not-a-uuid:value"), None);
+    }
+
+    #[test]
+    fn test_downsample_keeps_latest_point() {
+        let points: Vec<(f64, i64)> = (0..10).map(|i| (i as f64, i)).collect();
+        let sampled = downsample(points.clone(), 4);
+        assert_eq!(sampled.len(), 4);
+        assert_eq!(sampled.first(), Some(&(0.0, 0)));
+        assert_eq!(sampled.last(), Some(&(9.0, 9)));
+        assert_eq!(downsample(points.clone(), 0).len(), 10);
+        assert_eq!(downsample(points, 20).len(), 10);
+    }
+
+    #[test]
+    fn test_daily_points_within_range() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let range = DatasourceRange {
+            from: day(2).and_hms_opt(12, 0, 0).unwrap().and_utc(),
+            to: day(4).and_hms_opt(0, 0, 0).unwrap().and_utc(),
+        };
+        let points = daily_points(&[(day(1), 1.0), (day(2), 2.0), (day(4), 4.0), (day(5), 5.0)], &range);
+        assert_eq!(points, vec![(2.0, epoch_ms(day(2))), (4.0, epoch_ms(day(4)))]);
+    }
+}
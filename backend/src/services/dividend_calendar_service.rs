@@ -0,0 +1,155 @@
+//! Upcoming dividends of held tickers.
+//!
+//! A daily job stores each held ticker's next ex-dividend date from the dividend
+//! provider. The income calendar, ex-dividend alert rules and notification
+//! digests all read the stored dates.
+
+use std::collections::HashMap;
+
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, NaiveDate};
+use sqlx::PgPool;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::db::{dividend_queries, holding_snapshot_queries};
+use crate::errors::AppError;
+use crate::external::dividend_provider::DividendProvider;
+use crate::external::price_provider::PriceProviderError;
+use crate::models::{DividendEvent, IncomeCalendar, IncomeCalendarEntry};
+use crate::services::clock;
+
+const INTER_TICKER_DELAY_MS: u64 = 200;
+
+/// Days of upcoming ex-dates listed in daily and weekly digests
+pub const DIGEST_DAILY_DAYS: i64 = 3;
+pub const DIGEST_WEEKLY_DAYS: i64 = 10;
+
+/// Result of a refresh run
+#[derive(Debug, Default)]
+pub struct DividendRefresh {
+    pub stored: usize,
+    /// Tickers the provider reports no dividend for
+    pub non_payers: usize,
+    pub failed: usize,
+}
+
+/// Store the next dividend of every held ticker
+pub async fn refresh_held_tickers(pool: &PgPool, provider: &dyn DividendProvider) -> Result<DividendRefresh, AppError> {
+    let tickers = dividend_queries::fetch_held_tickers(pool).await?;
+    let mut refresh = DividendRefresh::default();
+
+    for ticker in &tickers {
+        match provider.fetch_dividend_event(ticker).await {
+            Ok(event) => {
+                dividend_queries::upsert(pool, ticker, &event).await?;
+                refresh.stored += 1;
+            }
+            Err(PriceProviderError::NotFound) => {
+                debug!("No dividend reported for {}", ticker);
+                refresh.non_payers += 1;
+            }
+            Err(e) => {
+                warn!("Failed to fetch dividend for {}: {}", ticker, e);
+                refresh.failed += 1;
+            }
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(INTER_TICKER_DELAY_MS)).await;
+    }
+
+    Ok(refresh)
+}
+
+/// Income calendar from held quantities per ticker and their upcoming dividends
+pub fn build_income_calendar(
+    portfolio_id: Uuid,
+    quantities: &HashMap<String, f64>,
+    events: &[DividendEvent],
+    today: NaiveDate,
+    days: i64,
+) -> IncomeCalendar {
+    let mut entries: Vec<IncomeCalendarEntry> = events
+        .iter()
+        .filter_map(|e| {
+            let quantity = *quantities.get(&e.ticker)?;
+            Some(IncomeCalendarEntry {
+                ticker: e.ticker.clone(),
+                ex_date: e.ex_date,
+                pay_date: e.pay_date,
+                days_until_ex_date: (e.ex_date - today).num_days(),
+                quantity,
+                amount_per_share: e.amount,
+                expected_income: e.amount.map(|a| a * quantity),
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| a.ex_date.cmp(&b.ex_date).then_with(|| a.ticker.cmp(&b.ticker)));
+    let expected_total = entries.iter().filter_map(|e| e.expected_income).sum();
+
+    IncomeCalendar {
+        portfolio_id: portfolio_id.to_string(),
+        days,
+        entries,
+        expected_total,
+    }
+}
+
+/// Upcoming ex-dividend dates of a portfolio's holdings over the next `days` days
+pub async fn income_calendar(pool: &PgPool, portfolio_id: Uuid, days: i64) -> Result<IncomeCalendar, AppError> {
+    if !(1..=365).contains(&days) {
+        return Err(AppError::Validation("days must be between 1 and 365".to_string()));
+    }
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let mut quantities: HashMap<String, f64> = HashMap::new();
+    for h in &holdings {
+        *quantities.entry(h.ticker.clone()).or_insert(0.0) += h.quantity.to_f64().unwrap_or(0.0);
+    }
+    quantities.retain(|_, q| *q > 0.0);
+
+    let tickers: Vec<String> = quantities.keys().cloned().collect();
+    let today = clock::today();
+    let events = dividend_queries::fetch_upcoming(pool, &tickers, today, today + Duration::days(days)).await?;
+
+    Ok(build_income_calendar(portfolio_id, &quantities, &events, today, days))
+}
+
+/// Upcoming ex-dividend dates of everything a user holds, for digests
+pub async fn upcoming_for_user(pool: &PgPool, user_id: Uuid, days: i64) -> Result<Vec<DividendEvent>, sqlx::Error> {
+    let tickers = holding_snapshot_queries::fetch_user_held_tickers(pool, user_id, None).await?;
+    let today = clock::today();
+    dividend_queries::fetch_upcoming(pool, &tickers, today, today + Duration::days(days)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_income_calendar_multiplies_held_quantity() {
+        let today: NaiveDate = "2026-03-09".parse().unwrap();
+        let event = |ticker: &str, ex_date: &str, amount: Option<f64>| DividendEvent {
+            ticker: ticker.to_string(),
+            ex_date: ex_date.parse().unwrap(),
+            pay_date: None,
+            amount,
+            annual_rate: None,
+            fetched_at: Utc::now(),
+        };
+        let events = vec![
+            event("KO", "2026-03-20", Some(0.51)),
+            event("JNJ", "2026-03-12", None),
+            event("PG", "2026-03-15", Some(1.0)),
+        ];
+        let quantities = HashMap::from([("KO".to_string(), 100.0), ("JNJ".to_string(), 10.0)]);
+
+        let calendar = build_income_calendar(Uuid::nil(), &quantities, &events, today, 30);
+
+        // PG isn't held in this portfolio
+        let tickers: Vec<&str> = calendar.entries.iter().map(|e| e.ticker.as_str()).collect();
+        assert_eq!(tickers, vec!["JNJ", "KO"]);
+        assert_eq!(calendar.entries[0].days_until_ex_date, 3);
+        assert_eq!(calendar.entries[0].expected_income, None);
+        assert!((calendar.expected_total - 51.0).abs() < 1e-9);
+    }
+}
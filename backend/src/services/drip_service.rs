@@ -0,0 +1,97 @@
+//! Dividend reinvestment (DRIP).
+//!
+//! For accounts that reinvest dividends automatically, each DIVIDEND transaction
+//! is matched with a DRIP transaction buying `amount / close` fractional shares
+//! at the close on (or just before) the dividend date, so lot quantities and cost
+//! basis include the reinvested shares.
+
+use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
+use sqlx::PgConnection;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::{detected_transaction_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::DripGenerationResult;
+
+/// A close older than this relative to the dividend date is not used for reinvestment
+const MAX_PRICE_AGE_DAYS: i64 = 7;
+const SHARE_DECIMALS: i32 = 6;
+
+/// Fractional shares bought by reinvesting `amount` at `price`, rounded to 6 decimals
+pub fn reinvestment_quantity(amount: f64, price: f64) -> Option<f64> {
+    if !amount.is_finite() || !price.is_finite() || amount <= 0.0 || price <= 0.0 {
+        return None;
+    }
+    let factor = 10f64.powi(SHARE_DECIMALS);
+    let quantity = (amount / price * factor).round() / factor;
+    (quantity > 0.0).then_some(quantity)
+}
+
+/// Generate DRIP transactions for every dividend of the account that has not been reinvested yet
+pub async fn generate_for_account(conn: &mut PgConnection, account_id: Uuid) -> Result<DripGenerationResult, AppError> {
+    let dividends = detected_transaction_queries::fetch_unreinvested_dividends(&mut *conn, account_id).await?;
+    let mut result = DripGenerationResult::default();
+
+    for dividend in dividends {
+        let amount = dividend.amount.as_ref().and_then(|a| a.to_f64()).unwrap_or(0.0).abs();
+        let close = price_queries::fetch_close_on_or_before(&mut *conn, &dividend.ticker, dividend.transaction_date).await?;
+
+        let Some(close) = close.filter(|p| (dividend.transaction_date - p.date).num_days() <= MAX_PRICE_AGE_DAYS) else {
+            result.skipped.push(format!(
+                "{} dividend on {}: no price within {} days",
+                dividend.ticker, dividend.transaction_date, MAX_PRICE_AGE_DAYS
+            ));
+            continue;
+        };
+        let price = close.close_price.to_f64().unwrap_or(0.0);
+        let Some(quantity) = reinvestment_quantity(amount, price) else {
+            result.skipped.push(format!(
+                "{} dividend on {}: invalid amount or price",
+                dividend.ticker, dividend.transaction_date
+            ));
+            continue;
+        };
+
+        let to_decimal = |v: f64| BigDecimal::from_f64(v).unwrap_or_default();
+        let created = detected_transaction_queries::create_drip(
+            &mut *conn,
+            &dividend,
+            &to_decimal(quantity),
+            &close.close_price,
+            &to_decimal(amount),
+        )
+        .await?;
+
+        match created {
+            Some(drip) => result.generated.push(drip),
+            None => warn!("Dividend {} was reinvested concurrently; skipping", dividend.id),
+        }
+    }
+
+    info!(
+        "DRIP generation for account {}: {} generated, {} skipped",
+        account_id,
+        result.generated.len(),
+        result.skipped.len()
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reinvestment_quantity_rounds_to_six_decimals() {
+        assert_eq!(reinvestment_quantity(25.0, 150.0), Some(0.166667));
+        assert_eq!(reinvestment_quantity(100.0, 50.0), Some(2.0));
+    }
+
+    #[test]
+    fn test_reinvestment_quantity_rejects_invalid_inputs() {
+        assert_eq!(reinvestment_quantity(0.0, 150.0), None);
+        assert_eq!(reinvestment_quantity(25.0, 0.0), None);
+        assert_eq!(reinvestment_quantity(f64::NAN, 10.0), None);
+    }
+}
@@ -4,6 +4,7 @@ use crate::models::{
     ConfidenceLevel, EventImportance, InsiderConfidence,
 };
 use crate::services::sec_edgar_service;
+use crate::services::clock;
 use chrono::Utc;
 use sqlx::PgPool;
 use tracing::{info, warn};
@@ -18,25 +19,25 @@ pub async fn generate_enhanced_sentiment(
     ticker: &str,
     days: i32,
 ) -> Result<EnhancedSentimentSignal, AppError> {
-    info!("🚀 [ENHANCED SENTIMENT] ========== STARTING ENHANCED SENTIMENT GENERATION FOR {} ==========", ticker);
-    info!("🚀 [ENHANCED SENTIMENT] Parameters: ticker={}, days={}", ticker, days);
+    info!("[ENHANCED SENTIMENT] ========== STARTING ENHANCED SENTIMENT GENERATION FOR {} ==========", ticker);
+    info!("[ENHANCED SENTIMENT] Parameters: ticker={}, days={}", ticker, days);
 
     // Check cache first
-    info!("🔍 [ENHANCED SENTIMENT] Checking cache...");
+    info!("[ENHANCED SENTIMENT] Checking cache...");
     if let Some(cached) = get_enhanced_sentiment_from_cache(pool, ticker).await? {
-        info!("✅ [ENHANCED SENTIMENT] Using cached enhanced sentiment for {}", ticker);
+        info!("[ENHANCED SENTIMENT] Using cached enhanced sentiment for {}", ticker);
         return Ok(cached);
     }
-    info!("⚠️ [ENHANCED SENTIMENT] No cache found, generating fresh sentiment...");
+    info!("[ENHANCED SENTIMENT] No cache found, generating fresh sentiment...");
 
     // 1. Get base news sentiment (existing implementation)
-    info!("📰 [ENHANCED SENTIMENT] === PHASE 1: NEWS SENTIMENT ===");
+    info!("[ENHANCED SENTIMENT] === PHASE 1: NEWS SENTIMENT ===");
     let (news_signal, news_articles) = get_or_create_news_sentiment(pool, news_service, ticker, days).await?;
-    info!("📰 [ENHANCED SENTIMENT] News sentiment result: score={:.2}, articles={}",
+    info!("[ENHANCED SENTIMENT] News sentiment result: score={:.2}, articles={}",
         news_signal.current_sentiment, news_signal.news_articles_analyzed);
 
     // 2. Fetch and analyze material events (8-K filings)
-    info!("📄 [ENHANCED SENTIMENT] === PHASE 2: SEC FILINGS ===");
+    info!("[ENHANCED SENTIMENT] === PHASE 2: SEC FILINGS ===");
     let material_events = fetch_and_analyze_material_events(
         pool,
         edgar_service,
@@ -44,54 +45,54 @@ pub async fn generate_enhanced_sentiment(
         ticker,
         days,
     ).await?;
-    info!("📄 [ENHANCED SENTIMENT] SEC filings result: {} material events found", material_events.len());
+    info!("[ENHANCED SENTIMENT] SEC filings result: {} material events found", material_events.len());
 
     let sec_score = calculate_sec_filing_score(&material_events);
-    info!("📄 [ENHANCED SENTIMENT] SEC filing score calculated: {:?}", sec_score);
+    info!("[ENHANCED SENTIMENT] SEC filing score calculated: {:?}", sec_score);
 
     // 3. Fetch and analyze insider transactions (Form 4)
-    info!("👤 [ENHANCED SENTIMENT] === PHASE 3: INSIDER ACTIVITY ===");
+    info!("[ENHANCED SENTIMENT] === PHASE 3: INSIDER ACTIVITY ===");
     let insider_sentiment = fetch_and_analyze_insider_activity(
         pool,
         edgar_service,
         ticker,
         days,
     ).await?;
-    info!("👤 [ENHANCED SENTIMENT] Insider activity result: score={:.2}, transactions={}, buys={}, sells={}",
+    info!("[ENHANCED SENTIMENT] Insider activity result: score={:.2}, transactions={}, buys={}, sells={}",
         insider_sentiment.sentiment_score, insider_sentiment.total_transactions,
         insider_sentiment.buying_transactions, insider_sentiment.selling_transactions);
 
     // 4. Calculate combined sentiment with weights
     // News: 40%, SEC: 30%, Insider: 30%
-    info!("🧮 [ENHANCED SENTIMENT] === PHASE 4: COMBINING SENTIMENTS ===");
-    info!("🧮 [ENHANCED SENTIMENT] Input scores: news={:.2}, sec={:?}, insider={:.2}",
+    info!("[ENHANCED SENTIMENT] === PHASE 4: COMBINING SENTIMENTS ===");
+    info!("[ENHANCED SENTIMENT] Input scores: news={:.2}, sec={:?}, insider={:.2}",
         news_signal.current_sentiment, sec_score, insider_sentiment.sentiment_score);
     let combined = calculate_weighted_sentiment(
         news_signal.current_sentiment,
         sec_score,
         insider_sentiment.sentiment_score,
     );
-    info!("🧮 [ENHANCED SENTIMENT] Combined sentiment calculated: {:.2}", combined);
+    info!("[ENHANCED SENTIMENT] Combined sentiment calculated: {:.2}", combined);
 
     // 5. Detect divergences between sources
-    info!("🔍 [ENHANCED SENTIMENT] Detecting divergences between sources...");
+    info!("[ENHANCED SENTIMENT] Detecting divergences between sources...");
     let divergence_flags = detect_multi_source_divergences(
         news_signal.current_sentiment,
         sec_score,
         insider_sentiment.sentiment_score,
         &material_events,
     );
-    info!("🔍 [ENHANCED SENTIMENT] Divergences detected: {}", divergence_flags.len());
+    info!("[ENHANCED SENTIMENT] Divergences detected: {}", divergence_flags.len());
 
     // 6. Determine overall confidence level
-    info!("🎯 [ENHANCED SENTIMENT] Determining confidence level...");
+    info!("[ENHANCED SENTIMENT] Determining confidence level...");
     let confidence = determine_confidence_level(
         &news_signal,
         &material_events,
         &insider_sentiment,
         &divergence_flags,
     );
-    info!("🎯 [ENHANCED SENTIMENT] Confidence level: {:?}", confidence);
+    info!("[ENHANCED SENTIMENT] Confidence level: {:?}", confidence);
 
     // Log final results before moving values
     let insider_score = insider_sentiment.sentiment_score;
@@ -112,12 +113,12 @@ pub async fn generate_enhanced_sentiment(
     };
 
     // Cache the result (12-hour TTL)
-    info!("💾 [ENHANCED SENTIMENT] Caching result with 12-hour TTL...");
+    info!("[ENHANCED SENTIMENT] Caching result with 12-hour TTL...");
     save_enhanced_sentiment_to_cache(pool, &enhanced_signal).await?;
-    info!("✅ [ENHANCED SENTIMENT] Result cached successfully");
+    info!("[ENHANCED SENTIMENT] Result cached successfully");
 
     info!(
-        "🎉 [ENHANCED SENTIMENT] ========== COMPLETED: combined={:.2}, confidence={:?}, news={:.2}, sec={:?}, insider={:.2} ==========",
+        "[ENHANCED SENTIMENT] ========== COMPLETED: combined={:.2}, confidence={:?}, news={:.2}, sec={:?}, insider={:.2} ==========",
         combined, &confidence, news_score, sec_score, insider_score
     );
 
@@ -131,17 +132,17 @@ async fn get_or_create_news_sentiment(
     ticker: &str,
     days: i32,
 ) -> Result<(SentimentSignal, Vec<crate::models::NewsArticle>), AppError> {
-    info!("🔍 [NEWS SENTIMENT] Starting news sentiment fetch for {} (days={})", ticker, days);
+    info!("[NEWS SENTIMENT] Starting news sentiment fetch for {} (days={})", ticker, days);
 
     // 1. Fetch news articles
-    info!("🔍 [NEWS SENTIMENT] Step 1: Fetching news articles...");
+    info!("[NEWS SENTIMENT] Step 1: Fetching news articles...");
     let articles = match news_service.fetch_ticker_news(ticker, days).await {
         Ok(articles) => {
-            info!("✅ [NEWS SENTIMENT] Successfully fetched {} articles for {}", articles.len(), ticker);
+            info!("[NEWS SENTIMENT] Successfully fetched {} articles for {}", articles.len(), ticker);
             articles
         },
         Err(e) => {
-            warn!("❌ [NEWS SENTIMENT] Failed to fetch news for {}: {}", ticker, e);
+            warn!("[NEWS SENTIMENT] Failed to fetch news for {}: {}", ticker, e);
             return Ok((SentimentSignal {
                 ticker: ticker.to_string(),
                 current_sentiment: 0.0,
@@ -160,7 +161,7 @@ async fn get_or_create_news_sentiment(
     };
 
     if articles.is_empty() {
-        warn!("⚠️ [NEWS SENTIMENT] No news articles found for {} in last {} days", ticker, days);
+        warn!("[NEWS SENTIMENT] No news articles found for {} in last {} days", ticker, days);
         return Ok((SentimentSignal {
             ticker: ticker.to_string(),
             current_sentiment: 0.0,
@@ -177,23 +178,23 @@ async fn get_or_create_news_sentiment(
         }, Vec::new()));
     }
 
-    info!("✅ [NEWS SENTIMENT] Found {} news articles for {}", articles.len(), ticker);
+    info!("[NEWS SENTIMENT] Found {} news articles for {}", articles.len(), ticker);
 
     // Clone articles for return value before consuming them
     let articles_clone = articles.clone();
 
     // 2. Cluster articles into themes using LLM
-    info!("🔍 [NEWS SENTIMENT] Step 2: Clustering articles into themes using LLM...");
+    info!("[NEWS SENTIMENT] Step 2: Clustering articles into themes using LLM...");
     let demo_user_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001")
         .expect("Failed to parse demo user UUID");
 
     let themes = match news_service.cluster_into_themes(articles, demo_user_id).await {
         Ok(themes) => {
-            info!("✅ [NEWS SENTIMENT] Successfully extracted {} themes for {}", themes.len(), ticker);
+            info!("[NEWS SENTIMENT] Successfully extracted {} themes for {}", themes.len(), ticker);
             themes
         },
         Err(e) => {
-            warn!("❌ [NEWS SENTIMENT] Failed to cluster themes for {}: {}", ticker, e);
+            warn!("[NEWS SENTIMENT] Failed to cluster themes for {}: {}", ticker, e);
             return Ok((SentimentSignal {
                 ticker: ticker.to_string(),
                 current_sentiment: 0.0,
@@ -212,7 +213,7 @@ async fn get_or_create_news_sentiment(
     };
 
     if themes.is_empty() {
-        warn!("⚠️ [NEWS SENTIMENT] No themes extracted for {} (empty result from LLM)", ticker);
+        warn!("[NEWS SENTIMENT] No themes extracted for {} (empty result from LLM)", ticker);
         return Ok((SentimentSignal {
             ticker: ticker.to_string(),
             current_sentiment: 0.0,
@@ -229,17 +230,17 @@ async fn get_or_create_news_sentiment(
         }, articles_clone));
     }
 
-    info!("✅ [NEWS SENTIMENT] Extracted {} themes for {}", themes.len(), ticker);
+    info!("[NEWS SENTIMENT] Extracted {} themes for {}", themes.len(), ticker);
 
     // 3. Fetch price history for correlation analysis
-    info!("🔍 [NEWS SENTIMENT] Step 3: Fetching price history for correlation analysis...");
+    info!("[NEWS SENTIMENT] Step 3: Fetching price history for correlation analysis...");
     let prices = match crate::services::price_service::get_history(pool, ticker).await {
         Ok(prices) => {
-            info!("✅ [NEWS SENTIMENT] Successfully fetched {} price points for {}", prices.len(), ticker);
+            info!("[NEWS SENTIMENT] Successfully fetched {} price points for {}", prices.len(), ticker);
             prices
         },
         Err(e) => {
-            warn!("❌ [NEWS SENTIMENT] Failed to fetch price history for {}: {}", ticker, e);
+            warn!("[NEWS SENTIMENT] Failed to fetch price history for {}: {}", ticker, e);
             return Ok((SentimentSignal {
                 ticker: ticker.to_string(),
                 current_sentiment: 0.0,
@@ -258,7 +259,7 @@ async fn get_or_create_news_sentiment(
     };
 
     if prices.is_empty() {
-        warn!("⚠️ [NEWS SENTIMENT] No price history found for {} (empty result from DB)", ticker);
+        warn!("[NEWS SENTIMENT] No price history found for {} (empty result from DB)", ticker);
         return Ok((SentimentSignal {
             ticker: ticker.to_string(),
             current_sentiment: 0.0,
@@ -275,10 +276,10 @@ async fn get_or_create_news_sentiment(
         }, articles_clone));
     }
 
-    info!("✅ [NEWS SENTIMENT] Found {} price points for {}", prices.len(), ticker);
+    info!("[NEWS SENTIMENT] Found {} price points for {}", prices.len(), ticker);
 
     // 4. Generate sentiment signal using existing service
-    info!("🔍 [NEWS SENTIMENT] Step 4: Generating sentiment signal with correlation analysis...");
+    info!("[NEWS SENTIMENT] Step 4: Generating sentiment signal with correlation analysis...");
     let signal = match crate::services::sentiment_service::generate_sentiment_signal(
         pool,
         ticker,
@@ -286,12 +287,12 @@ async fn get_or_create_news_sentiment(
         prices,
     ).await {
         Ok(signal) => {
-            info!("✅ [NEWS SENTIMENT] Successfully generated sentiment signal for {}: score={:.2}, trend={:?}, articles={}",
+            info!("[NEWS SENTIMENT] Successfully generated sentiment signal for {}: score={:.2}, trend={:?}, articles={}",
                 ticker, signal.current_sentiment, signal.sentiment_trend, signal.news_articles_analyzed);
             signal
         },
         Err(e) => {
-            warn!("❌ [NEWS SENTIMENT] Failed to generate sentiment signal for {}: {}", ticker, e);
+            warn!("[NEWS SENTIMENT] Failed to generate sentiment signal for {}: {}", ticker, e);
             return Ok((SentimentSignal {
                 ticker: ticker.to_string(),
                 current_sentiment: 0.0,
@@ -310,7 +311,7 @@ async fn get_or_create_news_sentiment(
     };
 
     info!(
-        "🎉 [NEWS SENTIMENT] COMPLETED news sentiment for {}: score={:.2}, trend={:?}, articles={}",
+        "[NEWS SENTIMENT] COMPLETED news sentiment for {}: score={:.2}, trend={:?}, articles={}",
         ticker, signal.current_sentiment, signal.sentiment_trend, signal.news_articles_analyzed
     );
 
@@ -325,26 +326,26 @@ async fn fetch_and_analyze_material_events(
     ticker: &str,
     days: i32,
 ) -> Result<Vec<MaterialEvent>, AppError> {
-    info!("🔍 [SEC] Checking database cache for material events...");
+    info!("[SEC] Checking database cache for material events...");
     // Check database cache first
     let cached_events = fetch_material_events_from_db(pool, ticker, days).await?;
 
     if !cached_events.is_empty() {
-        info!("✅ [SEC] Found {} cached material events for {}", cached_events.len(), ticker);
+        info!("[SEC] Found {} cached material events for {}", cached_events.len(), ticker);
         return Ok(cached_events);
     }
-    info!("⚠️ [SEC] No cached events found");
+    info!("[SEC] No cached events found");
 
     // Fetch fresh 8-K filings from SEC Edgar
-    info!("🔍 [SEC] Fetching fresh 8-K filings from SEC Edgar API...");
+    info!("[SEC] Fetching fresh 8-K filings from SEC Edgar API...");
     let filings = edgar_service.fetch_8k_filings(ticker, days).await?;
 
     if filings.is_empty() {
-        info!("⚠️ [SEC] No 8-K filings found for {} in last {} days", ticker, days);
+        info!("[SEC] No 8-K filings found for {} in last {} days", ticker, days);
         return Ok(Vec::new());
     }
 
-    info!("✅ [SEC] Found {} 8-K filings for {}, analyzing with LLM", filings.len(), ticker);
+    info!("[SEC] Found {} 8-K filings for {}, analyzing with LLM", filings.len(), ticker);
 
     // Demo user ID for LLM rate limiting
     let demo_user_id = uuid::Uuid::parse_str("00000000-0000-0000-0000-000000000001")
@@ -354,17 +355,17 @@ async fn fetch_and_analyze_material_events(
 
     // Analyze up to 3 most recent filings (to avoid timeouts)
     for filing in filings.iter().take(3) {
-        info!("🔍 [SEC] Analyzing 8-K filing from {} for {}", filing.filing_date, ticker);
+        info!("[SEC] Analyzing 8-K filing from {} for {}", filing.filing_date, ticker);
 
         // Try to fetch and analyze filing content
         match edgar_service.fetch_filing_content(&filing.filing_url).await {
             Ok(content) => {
-                info!("✅ [SEC] Fetched filing content ({} chars)", content.len());
+                info!("[SEC] Fetched filing content ({} chars)", content.len());
 
                 // Analyze with LLM
                 match sec_edgar_service::analyze_material_event(filing, &content, llm_service, demo_user_id).await {
                     Ok(event) => {
-                        info!("✅ [SEC] LLM analysis complete: sentiment={:.2}, importance={:?}",
+                        info!("[SEC] LLM analysis complete: sentiment={:.2}, importance={:?}",
                               event.sentiment_score, event.importance);
 
                         // Save to database
@@ -375,7 +376,7 @@ async fn fetch_and_analyze_material_events(
                         events.push(event);
                     }
                     Err(e) => {
-                        warn!("⚠️ [SEC] LLM analysis failed for {}: {}", filing.filing_date, e);
+                        warn!("[SEC] LLM analysis failed for {}: {}", filing.filing_date, e);
                         // Create placeholder event as fallback
                         let event = MaterialEvent {
                             ticker: ticker.to_string(),
@@ -391,7 +392,7 @@ async fn fetch_and_analyze_material_events(
                 }
             }
             Err(e) => {
-                warn!("⚠️ [SEC] Failed to fetch filing content for {}: {}", filing.filing_date, e);
+                warn!("[SEC] Failed to fetch filing content for {}: {}", filing.filing_date, e);
                 // Create placeholder event as fallback
                 let event = MaterialEvent {
                     ticker: ticker.to_string(),
@@ -419,23 +420,23 @@ async fn fetch_and_analyze_insider_activity(
     ticker: &str,
     days: i32,
 ) -> Result<InsiderSentiment, AppError> {
-    info!("🔍 [INSIDER] Checking database cache for insider transactions...");
+    info!("[INSIDER] Checking database cache for insider transactions...");
     // Check database cache first
     let cached_transactions = fetch_insider_transactions_from_db(pool, ticker, days).await?;
 
     let transactions = if !cached_transactions.is_empty() {
-        info!("✅ [INSIDER] Found {} cached insider transactions for {}", cached_transactions.len(), ticker);
+        info!("[INSIDER] Found {} cached insider transactions for {}", cached_transactions.len(), ticker);
         cached_transactions
     } else {
-        info!("⚠️ [INSIDER] No cached transactions found");
+        info!("[INSIDER] No cached transactions found");
         // Fetch fresh Form 4 filings
-        info!("🔍 [INSIDER] Fetching fresh Form 4 filings from SEC Edgar API...");
+        info!("[INSIDER] Fetching fresh Form 4 filings from SEC Edgar API...");
         let txns = edgar_service.fetch_form4_transactions(ticker, days).await?;
 
         if txns.is_empty() {
-            info!("⚠️ [INSIDER] No Form 4 filings found for {} in last {} days", ticker, days);
+            info!("[INSIDER] No Form 4 filings found for {} in last {} days", ticker, days);
         } else {
-            info!("✅ [INSIDER] Found {} Form 4 transactions for {}", txns.len(), ticker);
+            info!("[INSIDER] Found {} Form 4 transactions for {}", txns.len(), ticker);
             // Save to database
             for txn in &txns {
                 if let Err(e) = save_insider_transaction_to_db(pool, txn).await {
@@ -743,7 +744,7 @@ async fn fetch_material_events_from_db(
     ticker: &str,
     days: i32,
 ) -> Result<Vec<MaterialEvent>, AppError> {
-    let cutoff_date = Utc::now().date_naive() - chrono::Duration::days(days as i64);
+    let cutoff_date = clock::today() - chrono::Duration::days(days as i64);
 
     #[derive(sqlx::FromRow)]
     struct MaterialEventRow {
@@ -852,7 +853,7 @@ async fn fetch_insider_transactions_from_db(
     ticker: &str,
     days: i32,
 ) -> Result<Vec<crate::models::InsiderTransaction>, AppError> {
-    let cutoff_date = Utc::now().date_naive() - chrono::Duration::days(days as i64);
+    let cutoff_date = clock::today() - chrono::Duration::days(days as i64);
 
     #[derive(sqlx::FromRow)]
     struct InsiderTransactionRow {
@@ -0,0 +1,418 @@
+//! ETF constituents and look-through analysis.
+//!
+//! A weekly job stores the constituents and sector weights of every ETF held in
+//! a portfolio, preferring issuer holdings files and falling back to the ETF
+//! holdings provider (which usually reports only the top ten positions). The
+//! look-through analysis replaces each ETF with its constituents to show true
+//! single-stock exposure, overlap between funds, and sector allocation.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::{etf_constituent_queries, fund_metadata_queries, holding_snapshot_queries};
+use crate::errors::AppError;
+use crate::external::etf_holdings_provider::EtfHoldingsProvider;
+use crate::models::*;
+use crate::services::benchmark_comparison_service::sector_etf_for_industry;
+
+/// ETFs refreshed more recently than this are skipped by the weekly job
+const REFRESH_DAYS: i64 = 6;
+
+pub const UNCLASSIFIED: &str = "Unclassified";
+const OTHER: &str = "Other";
+
+/// Provider sector keys that don't contain an industry keyword
+const SECTOR_KEYS: &[(&str, &str)] = &[
+    ("consumer_cyclical", "Consumer Discretionary"),
+    ("consumer_defensive", "Consumer Staples"),
+    ("realestate", "Real Estate"),
+];
+
+/// GICS sector names for the sector ETFs industries map to
+const SECTOR_NAMES: &[(&str, &str)] = &[
+    ("XLK", "Technology"),
+    ("XLF", "Financials"),
+    ("XLE", "Energy"),
+    ("XLV", "Health Care"),
+    ("XLU", "Utilities"),
+    ("XLRE", "Real Estate"),
+    ("XLC", "Communication Services"),
+    ("XLP", "Consumer Staples"),
+    ("XLY", "Consumer Discretionary"),
+    ("XLB", "Materials"),
+    ("XLI", "Industrials"),
+];
+
+/// Map a provider sector key, issuer sector name or brokerage industry to a
+/// GICS sector name, so ETFs and direct holdings land in the same buckets
+pub fn normalize_sector(raw: &str) -> &'static str {
+    let key = raw.trim().to_lowercase();
+    if let Some((_, name)) = SECTOR_KEYS.iter().find(|(k, _)| *k == key) {
+        return name;
+    }
+    sector_etf_for_industry(&key)
+        .and_then(|etf| SECTOR_NAMES.iter().find(|(e, _)| *e == etf))
+        .map(|(_, name)| *name)
+        .unwrap_or(OTHER)
+}
+
+/// Whether a holding is an ETF: listed in the fund metadata or described as one
+pub fn is_etf(asset_category: Option<&str>, holding_name: Option<&str>, in_fund_metadata: bool) -> bool {
+    let says_etf = |s: Option<&str>| s.is_some_and(|s| s.to_lowercase().contains("etf"));
+    in_fund_metadata || says_etf(asset_category) || says_etf(holding_name)
+}
+
+/// Result of a refresh run
+#[derive(Debug, Default)]
+pub struct EtfRefresh {
+    pub refreshed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Fetch and store constituents for every held ETF not refreshed in the last
+/// `REFRESH_DAYS` days (all of them when `force` is set)
+pub async fn refresh_held_etfs(
+    pool: &PgPool,
+    provider: &dyn EtfHoldingsProvider,
+    force: bool,
+) -> Result<EtfRefresh, AppError> {
+    let etfs = etf_constituent_queries::fetch_held_etfs(pool).await?;
+    let cutoff = Utc::now() - Duration::days(REFRESH_DAYS);
+    let fresh: HashSet<String> = etf_constituent_queries::fetch_refreshes(pool, &etfs)
+        .await?
+        .into_iter()
+        .filter(|r| r.fetched_at > cutoff)
+        .map(|r| r.etf_ticker)
+        .collect();
+
+    let mut refresh = EtfRefresh::default();
+    for etf in &etfs {
+        if !force && fresh.contains(etf) {
+            refresh.skipped += 1;
+            continue;
+        }
+        match provider.fetch_etf_holdings(etf).await {
+            Ok(data) => {
+                etf_constituent_queries::replace(pool, etf, &data).await?;
+                info!(
+                    "Stored {} constituents and {} sector weights for {} from {}",
+                    data.holdings.len(),
+                    data.sector_weights.len(),
+                    etf,
+                    data.source
+                );
+                refresh.refreshed += 1;
+            }
+            Err(e) => {
+                warn!("Failed to fetch constituents for {}: {}", etf, e);
+                refresh.failed += 1;
+            }
+        }
+    }
+
+    Ok(refresh)
+}
+
+/// A position aggregated across accounts
+#[derive(Debug, Clone)]
+pub struct LookThroughPosition {
+    pub ticker: String,
+    pub name: Option<String>,
+    pub industry: Option<String>,
+    pub market_value: f64,
+    pub is_etf: bool,
+}
+
+/// Look-through analysis from positions and the stored data of their ETFs.
+/// Weights are percent of the portfolio.
+pub fn build_look_through(
+    portfolio_id: Uuid,
+    positions: &[LookThroughPosition],
+    constituents: &[EtfConstituent],
+    sector_weights: &[EtfSectorWeight],
+    refreshes: &[EtfConstituentRefresh],
+    top: usize,
+) -> LookThroughAnalysis {
+    let total_value: f64 = positions.iter().map(|p| p.market_value.max(0.0)).sum();
+    let weight_of = |value: f64| if total_value > 0.0 { value.max(0.0) / total_value * 100.0 } else { 0.0 };
+
+    let mut by_etf: HashMap<&str, Vec<&EtfConstituent>> = HashMap::new();
+    for c in constituents {
+        by_etf.entry(c.etf_ticker.as_str()).or_default().push(c);
+    }
+    let mut sectors_by_etf: HashMap<&str, Vec<&EtfSectorWeight>> = HashMap::new();
+    for s in sector_weights {
+        sectors_by_etf.entry(s.etf_ticker.as_str()).or_default().push(s);
+    }
+
+    let mut exposures: HashMap<String, LookThroughExposure> = HashMap::new();
+    let mut sectors: BTreeMap<&'static str, (f64, f64)> = BTreeMap::new();
+    let mut etfs = Vec::new();
+    let mut missing_etfs = Vec::new();
+
+    for position in positions {
+        let weight = weight_of(position.market_value);
+        let holdings = by_etf.get(position.ticker.as_str());
+        let etf_sectors = sectors_by_etf.get(position.ticker.as_str());
+
+        if !position.is_etf && holdings.is_none() {
+            let exposure = exposures.entry(position.ticker.clone()).or_insert_with(|| LookThroughExposure {
+                ticker: position.ticker.clone(),
+                name: position.name.clone(),
+                direct_weight: 0.0,
+                etf_weight: 0.0,
+                total_weight: 0.0,
+                via: vec![],
+            });
+            exposure.direct_weight += weight;
+            exposure.name = exposure.name.take().or_else(|| position.name.clone());
+            let sector = position.industry.as_deref().map(normalize_sector).unwrap_or(UNCLASSIFIED);
+            sectors.entry(sector).or_default().0 += weight;
+            continue;
+        }
+
+        if holdings.is_none() && etf_sectors.is_none() {
+            missing_etfs.push(position.ticker.clone());
+            sectors.entry(UNCLASSIFIED).or_default().1 += weight;
+            continue;
+        }
+
+        let holdings = holdings.cloned().unwrap_or_default();
+        for c in &holdings {
+            let Some(ticker) = &c.constituent_ticker else { continue };
+            let exposure = exposures.entry(ticker.clone()).or_insert_with(|| LookThroughExposure {
+                ticker: ticker.clone(),
+                name: Some(c.name.clone()),
+                direct_weight: 0.0,
+                etf_weight: 0.0,
+                total_weight: 0.0,
+                via: vec![],
+            });
+            exposure.etf_weight += weight * c.weight / 100.0;
+            if !exposure.via.contains(&position.ticker) {
+                exposure.via.push(position.ticker.clone());
+            }
+        }
+
+        // Sector split: the fund's sector weights when reported, else its
+        // constituents' sectors; whatever neither covers is "Other"
+        let split: Vec<(&'static str, f64)> = match etf_sectors {
+            Some(weights) => weights.iter().map(|s| (normalize_sector(&s.sector), s.weight)).collect(),
+            None => holdings
+                .iter()
+                .map(|c| (c.sector.as_deref().map(normalize_sector).unwrap_or(OTHER), c.weight))
+                .collect(),
+        };
+        let covered: f64 = split.iter().map(|(_, w)| w).sum();
+        for (sector, fund_weight) in split {
+            sectors.entry(sector).or_default().1 += weight * fund_weight / 100.0;
+        }
+        if covered < 100.0 {
+            sectors.entry(OTHER).or_default().1 += weight * (100.0 - covered) / 100.0;
+        }
+
+        let refresh = refreshes.iter().find(|r| r.etf_ticker == position.ticker);
+        etfs.push(EtfCoverage {
+            ticker: position.ticker.clone(),
+            portfolio_weight: weight,
+            constituent_count: holdings.len(),
+            covered_weight: holdings.iter().map(|c| c.weight).sum(),
+            source: refresh.map(|r| r.source.clone()),
+            fetched_at: refresh.map(|r| r.fetched_at),
+        });
+    }
+
+    let mut exposures: Vec<LookThroughExposure> = exposures
+        .into_values()
+        .map(|mut e| {
+            e.total_weight = e.direct_weight + e.etf_weight;
+            e
+        })
+        .collect();
+    exposures.sort_by(|a, b| b.total_weight.total_cmp(&a.total_weight).then_with(|| a.ticker.cmp(&b.ticker)));
+    exposures.truncate(top);
+
+    let etf_tickers: Vec<&str> = etfs.iter().map(|e| e.ticker.as_str()).collect();
+    let mut overlaps = Vec::new();
+    for (i, a) in etf_tickers.iter().enumerate() {
+        for b in &etf_tickers[i + 1..] {
+            if let Some(overlap) = etf_overlap(a, b, &by_etf) {
+                overlaps.push(overlap);
+            }
+        }
+    }
+    overlaps.sort_by(|a, b| b.overlap_pct.total_cmp(&a.overlap_pct));
+
+    let mut sector_allocation: Vec<LookThroughSector> = sectors
+        .into_iter()
+        .map(|(sector, (direct_weight, etf_weight))| LookThroughSector {
+            sector: sector.to_string(),
+            direct_weight,
+            etf_weight,
+            total_weight: direct_weight + etf_weight,
+        })
+        .filter(|s| s.total_weight > 0.0)
+        .collect();
+    sector_allocation.sort_by(|a, b| b.total_weight.total_cmp(&a.total_weight));
+
+    LookThroughAnalysis {
+        portfolio_id: portfolio_id.to_string(),
+        total_value,
+        etfs,
+        exposures,
+        overlaps,
+        sector_allocation,
+        missing_etfs,
+    }
+}
+
+/// Overlap of two ETFs' stored constituents, `None` when they share nothing
+fn etf_overlap(a: &str, b: &str, by_etf: &HashMap<&str, Vec<&EtfConstituent>>) -> Option<EtfOverlap> {
+    let weights = |etf: &str| -> HashMap<&str, f64> {
+        let mut map = HashMap::new();
+        for c in by_etf.get(etf).into_iter().flatten() {
+            if let Some(ticker) = &c.constituent_ticker {
+                *map.entry(ticker.as_str()).or_insert(0.0) += c.weight;
+            }
+        }
+        map
+    };
+    let (wa, wb) = (weights(a), weights(b));
+    let shared: Vec<f64> = wa
+        .iter()
+        .filter_map(|(ticker, weight)| wb.get(ticker).map(|other| weight.min(*other)))
+        .collect();
+    if shared.is_empty() {
+        return None;
+    }
+    Some(EtfOverlap {
+        etf_a: a.to_string(),
+        etf_b: b.to_string(),
+        overlap_pct: shared.iter().sum(),
+        common_holdings: shared.len(),
+    })
+}
+
+/// Look-through analysis of a portfolio's latest holdings
+pub async fn look_through(pool: &PgPool, portfolio_id: Uuid, top: usize) -> Result<LookThroughAnalysis, AppError> {
+    if !(1..=500).contains(&top) {
+        return Err(AppError::Validation("top must be between 1 and 500".to_string()));
+    }
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let tickers: Vec<String> = holdings.iter().map(|h| h.ticker.clone()).collect();
+    let funds: HashSet<String> = fund_metadata_queries::fetch_for_tickers(pool, &tickers)
+        .await?
+        .into_iter()
+        .map(|f| f.ticker)
+        .collect();
+
+    let mut positions: Vec<LookThroughPosition> = Vec::new();
+    for h in &holdings {
+        let value = h.market_value.to_f64().unwrap_or(0.0);
+        let etf = is_etf(h.asset_category.as_deref(), h.holding_name.as_deref(), funds.contains(&h.ticker));
+        match positions.iter_mut().find(|p| p.ticker == h.ticker) {
+            Some(p) => {
+                p.market_value += value;
+                p.is_etf |= etf;
+            }
+            None => positions.push(LookThroughPosition {
+                ticker: h.ticker.clone(),
+                name: h.holding_name.clone(),
+                industry: h.industry.clone(),
+                market_value: value,
+                is_etf: etf,
+            }),
+        }
+    }
+
+    let constituents = etf_constituent_queries::fetch_constituents(pool, &tickers).await?;
+    let sector_weights = etf_constituent_queries::fetch_sector_weights(pool, &tickers).await?;
+    let refreshes = etf_constituent_queries::fetch_refreshes(pool, &tickers).await?;
+
+    Ok(build_look_through(portfolio_id, &positions, &constituents, &sector_weights, &refreshes, top))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(ticker: &str, industry: Option<&str>, value: f64, is_etf: bool) -> LookThroughPosition {
+        LookThroughPosition {
+            ticker: ticker.to_string(),
+            name: None,
+            industry: industry.map(str::to_string),
+            market_value: value,
+            is_etf,
+        }
+    }
+
+    fn constituent(etf: &str, ticker: &str, weight: f64, sector: Option<&str>) -> EtfConstituent {
+        EtfConstituent {
+            etf_ticker: etf.to_string(),
+            constituent_ticker: Some(ticker.to_string()),
+            name: ticker.to_string(),
+            weight,
+            sector: sector.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_normalize_sector_across_sources() {
+        assert_eq!(normalize_sector("technology"), "Technology");
+        assert_eq!(normalize_sector("Information Technology"), "Technology");
+        assert_eq!(normalize_sector("Semiconductors"), "Technology");
+        assert_eq!(normalize_sector("financial_services"), "Financials");
+        assert_eq!(normalize_sector("consumer_cyclical"), "Consumer Discretionary");
+        assert_eq!(normalize_sector("realestate"), "Real Estate");
+        assert_eq!(normalize_sector("Widgets"), "Other");
+    }
+
+    #[test]
+    fn test_look_through_combines_direct_and_etf_exposure() {
+        let positions = vec![
+            position("AAPL", Some("Technology Hardware"), 10_000.0, false),
+            position("QQQ", None, 60_000.0, true),
+            position("VOO", None, 30_000.0, true),
+            position("XBB", None, 0.0, true),
+        ];
+        let constituents = vec![
+            constituent("QQQ", "AAPL", 10.0, None),
+            constituent("QQQ", "MSFT", 8.0, None),
+            constituent("VOO", "AAPL", 7.0, None),
+            constituent("VOO", "JPM", 1.0, None),
+        ];
+        let sector_weights = vec![
+            EtfSectorWeight { etf_ticker: "QQQ".to_string(), sector: "technology".to_string(), weight: 50.0 },
+            EtfSectorWeight { etf_ticker: "QQQ".to_string(), sector: "healthcare".to_string(), weight: 50.0 },
+        ];
+
+        let analysis = build_look_through(Uuid::nil(), &positions, &constituents, &sector_weights, &[], 10);
+
+        // AAPL: 10% direct, 60% x 10% via QQQ and 30% x 7% via VOO
+        let aapl = &analysis.exposures[0];
+        assert_eq!(aapl.ticker, "AAPL");
+        assert!((aapl.direct_weight - 10.0).abs() < 1e-9);
+        assert!((aapl.etf_weight - 8.1).abs() < 1e-9);
+        assert_eq!(aapl.via, vec!["QQQ", "VOO"]);
+
+        assert_eq!(analysis.overlaps.len(), 1);
+        assert!((analysis.overlaps[0].overlap_pct - 7.0).abs() < 1e-9);
+        assert_eq!(analysis.overlaps[0].common_holdings, 1);
+        assert_eq!(analysis.missing_etfs, vec!["XBB"]);
+
+        // QQQ splits by sector weights; VOO's unreported sectors fall under Other
+        let sector = |name: &str| analysis.sector_allocation.iter().find(|s| s.sector == name).unwrap().clone();
+        assert!((sector("Technology").total_weight - 40.0).abs() < 1e-9);
+        assert!((sector("Technology").direct_weight - 10.0).abs() < 1e-9);
+        assert!((sector("Health Care").etf_weight - 30.0).abs() < 1e-9);
+        assert!((sector("Other").etf_weight - 30.0).abs() < 1e-9);
+        let total: f64 = analysis.sector_allocation.iter().map(|s| s.total_weight).sum();
+        assert!((total - 100.0).abs() < 1e-9);
+    }
+}
@@ -1,3 +1,4 @@
+use bigdecimal::ToPrimitive;
 use std::sync::Arc;
 use chrono::{Duration, Utc};
 use sqlx::PgPool;
@@ -99,7 +100,7 @@ async fn build_explanation_context(
     // Extract prices (oldest first for indicators)
     let mut prices: Vec<f64> = price_data
         .iter()
-        .filter_map(|p| p.close_price.to_string().parse::<f64>().ok())
+        .filter_map(|p| p.close_price.to_f64())
         .collect();
     prices.reverse();
 
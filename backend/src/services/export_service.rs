@@ -0,0 +1,365 @@
+//! Tabular export rendering shared by all export endpoints.
+//!
+//! Route handlers build an [`ExportDocument`] (one or more named tables) and
+//! hand it to [`export_response`], which renders it as CSV, XLSX, or JSON.
+//! The format is chosen from the `?format=` query parameter when present,
+//! otherwise from the request's `Accept` header, falling back to CSV.
+
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::Response;
+use rust_xlsxwriter::{Format, Workbook};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+
+const CSV_MIME: &str = "text/csv";
+const XLSX_MIME: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+const JSON_MIME: &str = "application/json";
+
+/// Query parameters accepted by export endpoints
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportQuery {
+    /// Output format: "csv", "xlsx", or "json". Overrides the `Accept` header.
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn from_param(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "xlsx" | "excel" => Some(ExportFormat::Xlsx),
+            "json" => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+
+    fn from_mime(mime: &str) -> Option<Self> {
+        match mime {
+            CSV_MIME => Some(ExportFormat::Csv),
+            XLSX_MIME => Some(ExportFormat::Xlsx),
+            JSON_MIME => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Pick the output format for a request.
+    ///
+    /// An explicit `?format=` wins; otherwise the first supported media type in
+    /// the `Accept` header is used (wildcards and a missing header mean CSV).
+    pub fn negotiate(format_param: Option<&str>, headers: &HeaderMap) -> Result<Self, AppError> {
+        if let Some(param) = format_param {
+            return Self::from_param(param).ok_or_else(|| {
+                AppError::Validation(format!(
+                    "Unsupported export format '{}'. Use csv, xlsx, or json",
+                    param
+                ))
+            });
+        }
+
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+
+        let format = accept
+            .split(',')
+            .filter_map(|part| part.split(';').next())
+            .map(|mime| mime.trim().to_lowercase())
+            .find_map(|mime| Self::from_mime(&mime))
+            .unwrap_or(ExportFormat::Csv);
+
+        Ok(format)
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Xlsx => XLSX_MIME,
+            ExportFormat::Json => JSON_MIME,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// A single cell in an export table
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportCell {
+    Text(String),
+    /// Numeric value rendered with a fixed number of decimals in text formats
+    Number { value: f64, decimals: usize },
+    Empty,
+}
+
+impl ExportCell {
+    pub fn text(value: impl Into<String>) -> Self {
+        ExportCell::Text(value.into())
+    }
+
+    pub fn num(value: f64, decimals: usize) -> Self {
+        ExportCell::Number { value, decimals }
+    }
+
+    /// Numeric cell for an optional metric, empty when missing
+    pub fn opt_num(value: Option<f64>, decimals: usize) -> Self {
+        value.map(|v| Self::num(v, decimals)).unwrap_or(ExportCell::Empty)
+    }
+
+    fn to_text(&self) -> String {
+        match self {
+            ExportCell::Text(s) => s.clone(),
+            ExportCell::Number { value, decimals } => format!("{:.*}", *decimals, value),
+            ExportCell::Empty => "—".to_string(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            ExportCell::Text(s) => json!(s),
+            ExportCell::Number { value, decimals } => {
+                let factor = 10f64.powi(*decimals as i32);
+                json!((value * factor).round() / factor)
+            }
+            ExportCell::Empty => Value::Null,
+        }
+    }
+}
+
+/// A named table with a header row
+#[derive(Debug, Clone)]
+pub struct ExportTable {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<ExportCell>>,
+}
+
+impl ExportTable {
+    pub fn new(name: &str, columns: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push_row(&mut self, row: Vec<ExportCell>) {
+        self.rows.push(row);
+    }
+}
+
+/// One or more tables exported together (rendered as CSV sections,
+/// XLSX worksheets, or JSON arrays keyed by table name).
+#[derive(Debug, Clone)]
+pub struct ExportDocument {
+    pub tables: Vec<ExportTable>,
+}
+
+impl ExportDocument {
+    pub fn single(table: ExportTable) -> Self {
+        Self { tables: vec![table] }
+    }
+
+    pub fn new(tables: Vec<ExportTable>) -> Self {
+        Self { tables }
+    }
+}
+
+fn render_error(kind: &str, e: impl std::fmt::Display) -> AppError {
+    error!("{} generation error: {}", kind, e);
+    AppError::External(format!("{} generation error: {}", kind, e))
+}
+
+pub fn render_csv(doc: &ExportDocument) -> Result<Vec<u8>, AppError> {
+    // Multi-table documents have sections with differing column counts
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(vec![]);
+    let multi = doc.tables.len() > 1;
+
+    for (idx, table) in doc.tables.iter().enumerate() {
+        if multi {
+            if idx > 0 {
+                writer.write_record([""]).map_err(|e| render_error("CSV", e))?;
+            }
+            writer.write_record([&table.name]).map_err(|e| render_error("CSV", e))?;
+        }
+        writer.write_record(&table.columns).map_err(|e| render_error("CSV", e))?;
+        for row in &table.rows {
+            writer
+                .write_record(row.iter().map(|c| c.to_text()))
+                .map_err(|e| render_error("CSV", e))?;
+        }
+    }
+
+    writer.into_inner().map_err(|e| render_error("CSV", e))
+}
+
+pub fn render_xlsx(doc: &ExportDocument) -> Result<Vec<u8>, AppError> {
+    let mut workbook = Workbook::new();
+    let bold = Format::new().set_bold();
+
+    for table in &doc.tables {
+        let sheet = workbook.add_worksheet();
+        // Excel limits sheet names to 31 characters
+        let sheet_name: String = table.name.chars().take(31).collect();
+        sheet.set_name(&sheet_name).map_err(|e| render_error("XLSX", e))?;
+
+        for (col, name) in table.columns.iter().enumerate() {
+            sheet
+                .write_string_with_format(0, col as u16, name, &bold)
+                .map_err(|e| render_error("XLSX", e))?;
+        }
+
+        for (r, row) in table.rows.iter().enumerate() {
+            let row_idx = (r + 1) as u32;
+            for (col, cell) in row.iter().enumerate() {
+                let col_idx = col as u16;
+                match cell {
+                    ExportCell::Text(s) => {
+                        sheet.write_string(row_idx, col_idx, s).map(|_| ())
+                    }
+                    ExportCell::Number { value, .. } => {
+                        sheet.write_number(row_idx, col_idx, *value).map(|_| ())
+                    }
+                    ExportCell::Empty => Ok(()),
+                }
+                .map_err(|e| render_error("XLSX", e))?;
+            }
+        }
+    }
+
+    workbook.save_to_buffer().map_err(|e| render_error("XLSX", e))
+}
+
+pub fn render_json(doc: &ExportDocument) -> Result<Vec<u8>, AppError> {
+    let mut out = Map::new();
+    for table in &doc.tables {
+        let rows: Vec<Value> = table
+            .rows
+            .iter()
+            .map(|row| {
+                let obj: Map<String, Value> = table
+                    .columns
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(col, cell)| (col.clone(), cell.to_json()))
+                    .collect();
+                Value::Object(obj)
+            })
+            .collect();
+        out.insert(table.name.clone(), Value::Array(rows));
+    }
+    Ok(serde_json::to_vec_pretty(&Value::Object(out))?)
+}
+
+pub fn render(doc: &ExportDocument, format: ExportFormat) -> Result<Vec<u8>, AppError> {
+    match format {
+        ExportFormat::Csv => render_csv(doc),
+        ExportFormat::Xlsx => render_xlsx(doc),
+        ExportFormat::Json => render_json(doc),
+    }
+}
+
+/// Build the attachment filename stem used by portfolio exports,
+/// e.g. `portfolio_risk_Retirement_<uuid>_20260214`.
+pub fn portfolio_export_name(kind: &str, portfolio_name: &str, portfolio_id: Uuid) -> String {
+    format!(
+        "{}_{}_{}_{}",
+        kind,
+        portfolio_name.replace(' ', "_"),
+        portfolio_id,
+        chrono::Utc::now().format("%Y%m%d")
+    )
+}
+
+/// Render a document in the requested format and wrap it in a downloadable response.
+pub fn export_response(
+    doc: &ExportDocument,
+    format: ExportFormat,
+    filename_stem: &str,
+) -> Result<Response, AppError> {
+    let body = render(doc, format)?;
+    let filename = format!("{}.{}", filename_stem, format.extension());
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename)
+        )
+        .body(body.into())
+        .unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn sample_doc() -> ExportDocument {
+        let mut table = ExportTable::new("Positions", &["Ticker", "Weight %", "Beta"]);
+        table.push_row(vec![ExportCell::text("AAPL"), ExportCell::num(12.3456, 2), ExportCell::Empty]);
+        ExportDocument::single(table)
+    }
+
+    #[test]
+    fn test_negotiate_prefers_query_param() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        let format = ExportFormat::negotiate(Some("xlsx"), &headers).unwrap();
+        assert_eq!(format, ExportFormat::Xlsx);
+    }
+
+    #[test]
+    fn test_negotiate_uses_accept_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("text/html;q=0.9, application/json;q=0.8"),
+        );
+        assert_eq!(ExportFormat::negotiate(None, &headers).unwrap(), ExportFormat::Json);
+        assert_eq!(ExportFormat::negotiate(None, &HeaderMap::new()).unwrap(), ExportFormat::Csv);
+    }
+
+    #[test]
+    fn test_negotiate_rejects_unknown_format() {
+        assert!(ExportFormat::negotiate(Some("pdf"), &HeaderMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_render_csv_formats_cells() {
+        let csv = String::from_utf8(render_csv(&sample_doc()).unwrap()).unwrap();
+        assert_eq!(csv, "Ticker,Weight %,Beta\nAAPL,12.35,—\n");
+    }
+
+    #[test]
+    fn test_render_json_keys_rows_by_column() {
+        let value: Value = serde_json::from_slice(&render_json(&sample_doc()).unwrap()).unwrap();
+        assert_eq!(value["Positions"][0]["Ticker"], "AAPL");
+        assert_eq!(value["Positions"][0]["Weight %"], 12.35);
+        assert!(value["Positions"][0]["Beta"].is_null());
+    }
+
+    #[test]
+    fn test_render_xlsx_produces_zip() {
+        let bytes = render_xlsx(&sample_doc()).unwrap();
+        assert_eq!(&bytes[..2], b"PK");
+    }
+}
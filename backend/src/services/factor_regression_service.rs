@@ -0,0 +1,357 @@
+//! Per-holding factor regressions.
+//!
+//! The factor scores in `factor_service` are heuristics computed from each
+//! holding's own prices. This regresses each holding's daily excess returns on
+//! published factor returns instead, the Carhart four-factor model:
+//!
+//! r - rf = alpha + b_mkt (Mkt-RF) + b_smb SMB + b_hml HML + b_mom MOM + e
+//!
+//! by ordinary least squares, reporting each loading with its standard error
+//! and t-statistic so users can tell a real tilt from noise. Factor returns
+//! come from Kenneth French's data library, refreshed weekly by the
+//! `refresh_factor_returns` job; the library publishes with a lag of a few
+//! weeks, so the regressions end at its latest date.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{factor_return_queries, holding_snapshot_queries, price_queries};
+use crate::errors::AppError;
+use crate::external::ken_french::KenFrenchProvider;
+use crate::models::factor::{
+    FactorLoading, FactorRegressionResponse, HoldingFactorRegression, SkippedFactorRegression,
+};
+use crate::services::factor_service;
+
+/// Regressors in model order, with their labels
+const FACTORS: [(&str, &str); 4] = [
+    ("mkt_rf", "Market"),
+    ("smb", "Size (small minus big)"),
+    ("hml", "Value (high minus low book-to-market)"),
+    ("mom", "Momentum"),
+];
+const RISK_FREE: &str = "rf";
+/// Fewest daily returns a regression is run on
+pub const MIN_OBSERVATIONS: usize = 60;
+/// Longest window accepted, ten years of trading days
+pub const MAX_DAYS: i64 = 2520;
+const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// Coefficients of an OLS fit with their standard errors
+#[derive(Debug, Clone, PartialEq)]
+pub struct OlsFit {
+    pub coefficients: Vec<f64>,
+    pub std_errors: Vec<f64>,
+    pub r_squared: f64,
+}
+
+impl OlsFit {
+    pub fn t_stat(&self, i: usize) -> f64 {
+        if self.std_errors[i] > 0.0 {
+            self.coefficients[i] / self.std_errors[i]
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Inverse of a small square matrix by Gauss-Jordan elimination with partial
+/// pivoting, None when it is (numerically) singular
+fn invert(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut a: Vec<Vec<f64>> = matrix
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut augmented = row.clone();
+            augmented.extend((0..n).map(|j| if i == j { 1.0 } else { 0.0 }));
+            augmented
+        })
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        let p = a[col][col];
+        a[col].iter_mut().for_each(|v| *v /= p);
+        for row in 0..n {
+            if row != col {
+                let factor = a[row][col];
+                if factor != 0.0 {
+                    let pivot_row = a[col].clone();
+                    a[row].iter_mut().zip(pivot_row).for_each(|(v, pv)| *v -= factor * pv);
+                }
+            }
+        }
+    }
+    Some(a.into_iter().map(|row| row[n..].to_vec()).collect())
+}
+
+/// Regress `y` on the rows of `x` (each starting with 1.0 for the intercept).
+/// None with too few observations or collinear regressors.
+pub fn ols(y: &[f64], x: &[Vec<f64>]) -> Option<OlsFit> {
+    let k = x.first()?.len();
+    let n = y.len();
+    if n != x.len() || n <= k {
+        return None;
+    }
+
+    let mut xtx = vec![vec![0.0; k]; k];
+    let mut xty = vec![0.0; k];
+    for (row, yi) in x.iter().zip(y) {
+        for i in 0..k {
+            xty[i] += row[i] * yi;
+            for j in 0..k {
+                xtx[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    let inverse = invert(&xtx)?;
+    let coefficients: Vec<f64> = (0..k).map(|i| (0..k).map(|j| inverse[i][j] * xty[j]).sum()).collect();
+
+    let mean = y.iter().sum::<f64>() / n as f64;
+    let (mut ssr, mut sst) = (0.0, 0.0);
+    for (row, yi) in x.iter().zip(y) {
+        let fitted: f64 = row.iter().zip(&coefficients).map(|(xi, b)| xi * b).sum();
+        ssr += (yi - fitted).powi(2);
+        sst += (yi - mean).powi(2);
+    }
+    let sigma2 = ssr / (n - k) as f64;
+    Some(OlsFit {
+        std_errors: (0..k).map(|i| (sigma2 * inverse[i][i]).max(0.0).sqrt()).collect(),
+        r_squared: if sst > 0.0 { 1.0 - ssr / sst } else { 0.0 },
+        coefficients,
+    })
+}
+
+/// Factor returns by date, keeping only days with every regressor and the risk-free rate
+pub fn factor_table(rows: &[(NaiveDate, String, f64)]) -> BTreeMap<NaiveDate, [f64; 5]> {
+    let mut by_date: BTreeMap<NaiveDate, HashMap<&str, f64>> = BTreeMap::new();
+    for (date, factor, value) in rows {
+        by_date.entry(*date).or_default().insert(factor.as_str(), *value);
+    }
+    by_date
+        .into_iter()
+        .filter_map(|(date, values)| {
+            let mut day = [0.0; 5];
+            for (i, (factor, _)) in FACTORS.iter().enumerate() {
+                day[i] = *values.get(factor)?;
+            }
+            day[4] = *values.get(RISK_FREE)?;
+            Some((date, day))
+        })
+        .collect()
+}
+
+/// Regress one holding's closes (ascending) on the factor table. Only returns
+/// between consecutive factor dates are used, so a missing close never turns
+/// into a multi-day return.
+pub fn regress_holding(
+    closes: &[(NaiveDate, f64)],
+    factors: &BTreeMap<NaiveDate, [f64; 5]>,
+) -> Result<(OlsFit, usize, NaiveDate, NaiveDate), String> {
+    let previous_day: HashMap<NaiveDate, NaiveDate> =
+        factors.keys().zip(factors.keys().skip(1)).map(|(prev, day)| (*day, *prev)).collect();
+
+    let mut y = Vec::new();
+    let mut x = Vec::new();
+    let mut dates = Vec::new();
+    for pair in closes.windows(2) {
+        let ((prev_date, prev_close), (date, close)) = (pair[0], pair[1]);
+        if prev_close <= 0.0 || previous_day.get(&date) != Some(&prev_date) {
+            continue;
+        }
+        let day = &factors[&date];
+        y.push(close / prev_close - 1.0 - day[4]);
+        x.push(vec![1.0, day[0], day[1], day[2], day[3]]);
+        dates.push(date);
+    }
+
+    if y.len() < MIN_OBSERVATIONS {
+        return Err(format!(
+            "{} daily returns overlap the factor data; at least {} are needed",
+            y.len(),
+            MIN_OBSERVATIONS
+        ));
+    }
+    let fit = ols(&y, &x).ok_or_else(|| "the factor returns are collinear over this window".to_string())?;
+    Ok((fit, y.len(), dates[0], dates[dates.len() - 1]))
+}
+
+fn holding_regression(
+    ticker: String,
+    holding_name: Option<String>,
+    weight: f64,
+    (fit, observations, from, to): (OlsFit, usize, NaiveDate, NaiveDate),
+) -> HoldingFactorRegression {
+    HoldingFactorRegression {
+        ticker,
+        holding_name,
+        weight,
+        observations,
+        from,
+        to,
+        alpha_annualized: fit.coefficients[0] * TRADING_DAYS_PER_YEAR,
+        alpha_t_stat: fit.t_stat(0),
+        r_squared: fit.r_squared,
+        loadings: FACTORS
+            .iter()
+            .enumerate()
+            .map(|(i, (factor, label))| FactorLoading {
+                factor: factor.to_string(),
+                label: label.to_string(),
+                beta: fit.coefficients[i + 1],
+                std_error: fit.std_errors[i + 1],
+                t_stat: fit.t_stat(i + 1),
+            })
+            .collect(),
+    }
+}
+
+/// Regress every holding of the portfolio over the last `days` factor dates
+pub async fn regress_portfolio(pool: &PgPool, portfolio_id: Uuid, days: i64) -> Result<FactorRegressionResponse, AppError> {
+    if !(MIN_OBSERVATIONS as i64..=MAX_DAYS).contains(&days) {
+        return Err(AppError::Validation(format!(
+            "days must be between {} and {}",
+            MIN_OBSERVATIONS, MAX_DAYS
+        )));
+    }
+    let factors_as_of = factor_return_queries::fetch_latest_date(pool).await?.ok_or_else(|| {
+        AppError::ServiceUnavailable("No factor returns stored yet; run the refresh_factor_returns job".to_string())
+    })?;
+
+    // Calendar days comfortably covering `days` trading days
+    let since = factors_as_of - Duration::days(days * 7 / 5 + 14);
+    let mut factors = factor_table(&factor_return_queries::fetch_since(pool, since).await?);
+    let excess = factors.len().saturating_sub(days as usize + 1);
+    if let Some(first_kept) = factors.keys().nth(excess).copied() {
+        factors = factors.split_off(&first_kept);
+    }
+    let Some(from) = factors.keys().next().copied() else {
+        return Err(AppError::ServiceUnavailable("No complete factor returns in the window".to_string()));
+    };
+
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let (aggregates, total_value) = factor_service::aggregate_holdings(&holdings);
+    if total_value <= 0.0 {
+        return Err(AppError::Validation("Portfolio has no holdings to analyze".to_string()));
+    }
+    let mut weighted = factor_service::holding_weights(&aggregates, total_value);
+    weighted.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    let mut regressions = Vec::new();
+    let mut skipped = Vec::new();
+    for (ticker, holding_name, weight) in weighted {
+        let closes: Vec<(NaiveDate, f64)> = price_queries::fetch_adjusted_range(pool, &ticker, from, factors_as_of)
+            .await?
+            .into_iter()
+            .filter_map(|p| Some((p.date, p.close_price.to_f64()?)))
+            .collect();
+        match regress_holding(&closes, &factors) {
+            Ok(result) => regressions.push(holding_regression(ticker, holding_name, weight, result)),
+            Err(reason) => skipped.push(SkippedFactorRegression { ticker, reason }),
+        }
+    }
+
+    Ok(FactorRegressionResponse {
+        portfolio_id: portfolio_id.to_string(),
+        factors_as_of: Some(factors_as_of),
+        holdings: regressions,
+        skipped,
+    })
+}
+
+/// Download the factor library and store its returns, returning the rows
+/// that were new or changed
+pub async fn refresh_factor_returns(pool: &PgPool) -> Result<u64, AppError> {
+    let returns = KenFrenchProvider::from_env()
+        .fetch_daily_returns()
+        .await
+        .map_err(|e| AppError::External(format!("Factor library download failed: {}", e)))?;
+    Ok(factor_return_queries::upsert_returns(pool, &returns).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random series in [-0.01, 0.01)
+    fn noise(seed: u64, n: usize) -> Vec<f64> {
+        let mut state = seed;
+        (0..n)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                ((state >> 33) as f64 / (1u64 << 31) as f64 - 0.5) * 0.02
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ols_recovers_known_loadings() {
+        let n = 250;
+        let (mkt, smb, hml, mom, eps) = (noise(1, n), noise(2, n), noise(3, n), noise(4, n), noise(5, n));
+        let x: Vec<Vec<f64>> = (0..n).map(|i| vec![1.0, mkt[i], smb[i], hml[i], mom[i]]).collect();
+        let y: Vec<f64> = (0..n)
+            .map(|i| 0.0002 + 1.2 * mkt[i] + 0.5 * smb[i] - 0.3 * hml[i] + 0.0 * mom[i] + 0.1 * eps[i])
+            .collect();
+
+        let fit = ols(&y, &x).unwrap();
+        assert!((fit.coefficients[1] - 1.2).abs() < 0.05);
+        assert!((fit.coefficients[2] - 0.5).abs() < 0.05);
+        assert!((fit.coefficients[3] + 0.3).abs() < 0.05);
+        assert!(fit.t_stat(1) > 20.0);
+        assert!(fit.t_stat(4).abs() < 3.0);
+        assert!(fit.r_squared > 0.95);
+    }
+
+    #[test]
+    fn test_ols_rejects_collinear_regressors() {
+        let mkt = noise(7, 100);
+        let x: Vec<Vec<f64>> = mkt.iter().map(|m| vec![1.0, *m, 2.0 * m]).collect();
+        assert!(ols(&mkt, &x).is_none());
+        assert!(ols(&[0.1, 0.2], &[vec![1.0, 0.1], vec![1.0, 0.2]]).is_none());
+    }
+
+    #[test]
+    fn test_regress_holding_aligns_returns_with_factor_days() {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let n = 120;
+        let (mkt, smb, hml, mom) = (noise(11, n), noise(12, n), noise(13, n), noise(14, n));
+        let rows: Vec<(NaiveDate, String, f64)> = (0..n)
+            .flat_map(|i| {
+                let date = start + Duration::days(i as i64);
+                [("mkt_rf", mkt[i]), ("smb", smb[i]), ("hml", hml[i]), ("mom", mom[i]), ("rf", 0.0001)]
+                    .into_iter()
+                    .map(move |(f, v)| (date, f.to_string(), v))
+            })
+            .collect();
+        let factors = factor_table(&rows);
+        assert_eq!(factors.len(), n);
+
+        // Tracks the market one for one, with every tenth close missing
+        let mut close = 100.0;
+        let mut closes = vec![(start, close)];
+        for (i, market) in mkt.iter().enumerate().skip(1) {
+            close *= 1.0 + 0.0001 + market;
+            if i % 10 != 0 {
+                closes.push((start + Duration::days(i as i64), close));
+            }
+        }
+
+        let (fit, observations, from, _) = regress_holding(&closes, &factors).unwrap();
+        // Returns spanning a missing close are dropped, not merged
+        assert!(observations < n - 1 - 10);
+        assert_eq!(from, start + Duration::days(1));
+        assert!((fit.coefficients[1] - 1.0).abs() < 1e-6);
+        assert!(fit.coefficients[0].abs() < 1e-9);
+
+        assert!(regress_holding(&closes[..30], &factors).is_err());
+    }
+}
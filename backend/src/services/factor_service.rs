@@ -7,10 +7,12 @@ use uuid::Uuid;
 
 use crate::db::holding_snapshot_queries;
 use crate::errors::AppError;
+use crate::external::fundamentals_provider::ExternalFundamentals;
 use crate::external::price_provider::PriceProvider;
 use crate::models::factor::*;
+use crate::models::{LatestAccountHolding, Locale};
 use crate::services::failure_cache::FailureCache;
-use crate::services::price_service;
+use crate::services::{fundamentals_service, i18n, price_service};
 use crate::services::rate_limiter::RateLimiter;
 
 // ============================================================================
@@ -43,21 +45,7 @@ pub async fn analyze_portfolio_factors(
     }
 
     // 2. Aggregate holdings by ticker
-    let mut ticker_aggregates: HashMap<String, (f64, f64, Option<String>)> = HashMap::new();
-    let mut total_value = 0.0;
-
-    for h in &holdings {
-        let mv = h.market_value.to_string().parse::<f64>().unwrap_or(0.0);
-        total_value += mv;
-        let qty = h.quantity.to_string().parse::<f64>().unwrap_or(0.0);
-        ticker_aggregates
-            .entry(h.ticker.clone())
-            .and_modify(|(q, v, _)| {
-                *q += qty;
-                *v += mv;
-            })
-            .or_insert((qty, mv, h.holding_name.clone()));
-    }
+    let (ticker_aggregates, total_value) = aggregate_holdings(&holdings);
 
     if total_value <= 0.0 {
         return Err(AppError::Validation(
@@ -66,55 +54,17 @@ pub async fn analyze_portfolio_factors(
     }
 
     // 3. Score each holding on every factor
-    let mut holdings_scores = Vec::new();
-    for (ticker, (_qty, mv, name)) in &ticker_aggregates {
-        // Pre-check: Skip tickers without sufficient price data to avoid slow API calls
-        let has_data = match price_service::get_history(pool, ticker).await {
-            Ok(p) if p.len() >= 20 => true,
-            _ => {
-                info!("Skipping {} - insufficient price data for factor analysis", ticker);
-                false
-            }
-        };
-
-        if !has_data {
-            continue;
-        }
-
-        let weight = *mv / total_value;
-        let scores = score_ticker(
-            pool,
-            ticker,
-            price_provider,
-            failure_cache,
-            rate_limiter,
-            risk_free_rate,
-            days,
-        )
-        .await;
-        let composite = FactorWeights::default().composite(&TickerFactorScores {
-            ticker: ticker.clone(),
-            holding_name: name.clone(),
-            weight,
-            value_score: scores.0,
-            growth_score: scores.1,
-            momentum_score: scores.2,
-            quality_score: scores.3,
-            low_volatility_score: scores.4,
-            composite_score: 0.0,
-        });
-        holdings_scores.push(TickerFactorScores {
-            ticker: ticker.clone(),
-            holding_name: name.clone(),
-            weight,
-            value_score: scores.0,
-            growth_score: scores.1,
-            momentum_score: scores.2,
-            quality_score: scores.3,
-            low_volatility_score: scores.4,
-            composite_score: composite,
-        });
-    }
+    let weighted = holding_weights(&ticker_aggregates, total_value);
+    let mut holdings_scores = score_holdings(
+        pool,
+        &weighted,
+        price_provider,
+        failure_cache,
+        rate_limiter,
+        risk_free_rate,
+        days,
+    )
+    .await;
     holdings_scores.sort_by(|a, b| b.composite_score.partial_cmp(&a.composite_score).unwrap_or(std::cmp::Ordering::Equal));
 
     // 4. Aggregate portfolio-level factor exposures
@@ -138,7 +88,7 @@ pub async fn analyze_portfolio_factors(
     };
 
     // 8. Summary
-    let summary = build_summary(&factor_exposures, &holdings_scores);
+    let summary = build_summary(&factor_exposures, &holdings_scores, Locale::En);
 
     // 9. Portfolio name
     let portfolio_name = sqlx::query!("SELECT name FROM portfolios WHERE id = $1", portfolio_id)
@@ -160,10 +110,114 @@ pub async fn analyze_portfolio_factors(
     })
 }
 
+/// Quantity, market value and name per ticker
+type TickerAggregates = HashMap<String, (f64, f64, Option<String>)>;
+
+/// Holdings aggregated by ticker across accounts, and the total market value
+pub fn aggregate_holdings(holdings: &[LatestAccountHolding]) -> (TickerAggregates, f64) {
+    let mut ticker_aggregates: TickerAggregates = HashMap::new();
+    let mut total_value = 0.0;
+
+    for h in holdings {
+        let mv = h.market_value.to_f64().unwrap_or(0.0);
+        total_value += mv;
+        let qty = h.quantity.to_f64().unwrap_or(0.0);
+        ticker_aggregates
+            .entry(h.ticker.clone())
+            .and_modify(|(q, v, _)| {
+                *q += qty;
+                *v += mv;
+            })
+            .or_insert((qty, mv, h.holding_name.clone()));
+    }
+    (ticker_aggregates, total_value)
+}
+
+/// (ticker, name, weight) of each aggregated holding
+pub fn holding_weights(ticker_aggregates: &TickerAggregates, total_value: f64) -> Vec<(String, Option<String>, f64)> {
+    ticker_aggregates
+        .iter()
+        .map(|(ticker, (_qty, mv, name))| (ticker.clone(), name.clone(), *mv / total_value))
+        .collect()
+}
+
+/// Current portfolio-level factor exposures, without the rest of the analysis.
+/// Empty for a portfolio without holdings.
+pub async fn current_exposures(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    price_provider: &dyn PriceProvider,
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
+    risk_free_rate: f64,
+    days: i64,
+) -> Result<Vec<PortfolioFactorExposure>, AppError> {
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let (ticker_aggregates, total_value) = aggregate_holdings(&holdings);
+    if total_value <= 0.0 {
+        return Ok(Vec::new());
+    }
+    let weighted = holding_weights(&ticker_aggregates, total_value);
+    let scores = score_holdings(pool, &weighted, price_provider, failure_cache, rate_limiter, risk_free_rate, days).await;
+    Ok(compute_portfolio_exposures(&scores))
+}
+
 // ============================================================================
 // Factor scoring for individual tickers
 // ============================================================================
 
+/// Score each (ticker, name, weight) on every factor. Tickers with fewer than 20
+/// stored prices are skipped to avoid slow API calls.
+pub async fn score_holdings(
+    pool: &PgPool,
+    holdings: &[(String, Option<String>, f64)],
+    price_provider: &dyn PriceProvider,
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
+    risk_free_rate: f64,
+    days: i64,
+) -> Vec<TickerFactorScores> {
+    let mut holdings_scores = Vec::new();
+    for (ticker, name, weight) in holdings {
+        let has_data = match price_service::get_history(pool, ticker).await {
+            Ok(p) if p.len() >= 20 => true,
+            _ => {
+                info!("Skipping {} - insufficient price data for factor analysis", ticker);
+                false
+            }
+        };
+
+        if !has_data {
+            continue;
+        }
+
+        let scores = score_ticker(
+            pool,
+            ticker,
+            price_provider,
+            failure_cache,
+            rate_limiter,
+            risk_free_rate,
+            days,
+        )
+        .await;
+        let mut ticker_scores = TickerFactorScores {
+            ticker: ticker.clone(),
+            holding_name: name.clone(),
+            weight: *weight,
+            value_score: scores.0,
+            growth_score: scores.1,
+            momentum_score: scores.2,
+            quality_score: scores.3,
+            low_volatility_score: scores.4,
+            composite_score: 0.0,
+        };
+        ticker_scores.composite_score = FactorWeights::default().composite(&ticker_scores);
+        holdings_scores.push(ticker_scores);
+    }
+    holdings_scores
+}
+
 /// Returns (value, growth, momentum, quality, low_vol) scores in 0-100.
 async fn score_ticker(
     pool: &PgPool,
@@ -199,10 +253,16 @@ async fn score_ticker(
         return (50.0, 50.0, 50.0, 50.0, 50.0);
     }
 
-    let value_score = compute_value_score(&closes);
+    // Reported P/E, P/B, profitability and leverage when stored, the price-shape
+    // proxies otherwise
+    let fundamentals = match fundamentals_service::stored(pool, ticker).await {
+        Ok(Some((raw, _))) => Some(raw),
+        _ => None,
+    };
+    let value_score = score_closes(&FactorType::Value, &closes, fundamentals.as_ref());
     let growth_score = compute_growth_score(&closes);
     let momentum_score = compute_momentum_score(&closes);
-    let quality_score = compute_quality_score(&closes);
+    let quality_score = score_closes(&FactorType::Quality, &closes, fundamentals.as_ref());
     let low_vol_score = compute_low_volatility_score(
         pool,
         ticker,
@@ -223,8 +283,8 @@ async fn score_ticker(
     )
 }
 
-/// Value factor: uses price-to-moving-average ratio as a proxy for valuation
-/// (lower ratio = more "value"). Also considers mean reversion.
+/// Value factor for tickers without stored fundamentals: uses price-to-moving-average
+/// ratio as a proxy for valuation (lower ratio = more "value"). Also considers mean reversion.
 fn compute_value_score(closes: &[f64]) -> f64 {
     let n = closes.len();
     if n < 50 {
@@ -335,8 +395,9 @@ fn compute_momentum_score(closes: &[f64]) -> f64 {
     (momentum_score * 0.6 + trend_score * 0.4).clamp(0.0, 100.0)
 }
 
-/// Quality factor: uses return consistency (R-squared of trend) and
-/// low drawdown as proxies for earnings stability and profitability.
+/// Quality factor for tickers without stored fundamentals: uses return
+/// consistency (R-squared of trend) and low drawdown as proxies for earnings
+/// stability and profitability.
 fn compute_quality_score(closes: &[f64]) -> f64 {
     let n = closes.len();
     if n < 30 {
@@ -430,6 +491,11 @@ async fn compute_low_volatility_score(
         .filter_map(|p| p.close_price.to_f64())
         .collect();
 
+    volatility_score(&closes)
+}
+
+/// Annualized volatility of daily returns mapped to 0-100 (lower volatility = higher score)
+fn volatility_score(closes: &[f64]) -> f64 {
     if closes.len() < 20 {
         return 50.0;
     }
@@ -458,17 +524,32 @@ async fn compute_low_volatility_score(
 
     // Lower volatility = higher score
     // Vol of 10% => 90, vol of 50% => 10
-    let vol_score = ((50.0 - annualized_vol) / 40.0 * 100.0).clamp(0.0, 100.0);
-
     // For factor analysis, we only use volatility score (no beta calculation to avoid external calls)
-    vol_score
+    ((50.0 - annualized_vol) / 40.0 * 100.0).clamp(0.0, 100.0)
+}
+
+/// Score a ticker on one factor from its closes (oldest first), with stored
+/// fundamentals for value and quality when available. Also used to rank the
+/// tracked universe without per-ticker queries.
+pub fn score_closes(factor: &FactorType, closes: &[f64], fundamentals: Option<&ExternalFundamentals>) -> f64 {
+    match factor {
+        FactorType::Value => fundamentals
+            .and_then(fundamentals_service::value_score)
+            .unwrap_or_else(|| compute_value_score(closes)),
+        FactorType::Growth => compute_growth_score(closes),
+        FactorType::Momentum => compute_momentum_score(closes),
+        FactorType::Quality => fundamentals
+            .and_then(fundamentals_service::quality_score)
+            .unwrap_or_else(|| compute_quality_score(closes)),
+        FactorType::LowVolatility => volatility_score(closes),
+    }
 }
 
 // ============================================================================
 // Portfolio-level aggregation
 // ============================================================================
 
-fn compute_portfolio_exposures(scores: &[TickerFactorScores]) -> Vec<PortfolioFactorExposure> {
+pub fn compute_portfolio_exposures(scores: &[TickerFactorScores]) -> Vec<PortfolioFactorExposure> {
     let total_weight: f64 = scores.iter().map(|s| s.weight).sum();
     if total_weight <= 0.0 {
         return vec![];
@@ -493,7 +574,7 @@ fn compute_portfolio_exposures(scores: &[TickerFactorScores]) -> Vec<PortfolioFa
 
             let exposure = ExposureLevel::from_score(weighted_score);
             let premium = expected_risk_premium(ft);
-            let recommendation = factor_recommendation(ft, &exposure, weighted_score);
+            let recommendation = factor_recommendation(Locale::En, ft, &exposure, weighted_score);
 
             PortfolioFactorExposure {
                 factor: ft.clone(),
@@ -520,29 +601,39 @@ fn expected_risk_premium(factor: &FactorType) -> f64 {
     }
 }
 
-fn factor_recommendation(factor: &FactorType, exposure: &ExposureLevel, score: f64) -> String {
-    match exposure {
-        ExposureLevel::Underweight => format!(
-            "Your portfolio has low {} exposure ({:.0}/100). Consider adding {} stocks or ETFs to capture the estimated {:.1}% annual risk premium.",
-            factor.label().to_lowercase(),
-            score,
-            factor.label().to_lowercase(),
-            expected_risk_premium(factor),
-        ),
-        ExposureLevel::Neutral => format!(
-            "Your {} exposure is balanced ({:.0}/100). This factor has a historical risk premium of ~{:.1}% per year.",
-            factor.label().to_lowercase(),
-            score,
-            expected_risk_premium(factor),
-        ),
-        ExposureLevel::Overweight => format!(
-            "Your portfolio is heavily tilted toward {} ({:.0}/100). This may concentrate risk, though {} stocks have historically earned a {:.1}% annual premium.",
-            factor.label().to_lowercase(),
-            score,
-            factor.label().to_lowercase(),
-            expected_risk_premium(factor),
-        ),
+fn factor_recommendation(locale: Locale, factor: &FactorType, exposure: &ExposureLevel, score: f64) -> String {
+    let key = match exposure {
+        ExposureLevel::Underweight => "factor.recommendation.underweight",
+        ExposureLevel::Neutral => "factor.recommendation.neutral",
+        ExposureLevel::Overweight => "factor.recommendation.overweight",
+    };
+    let name = factor_label(locale, factor).to_lowercase();
+    let score = format!("{:.0}", score);
+    let premium = i18n::format_decimal(locale, expected_risk_premium(factor), 1);
+    i18n::message(
+        locale,
+        key,
+        &[("factor", name.as_str()), ("score", score.as_str()), ("premium", premium.as_str())],
+    )
+}
+
+fn factor_label(locale: Locale, factor: &FactorType) -> String {
+    i18n::message(locale, &format!("factor.{}.label", factor.as_str()), &[])
+}
+
+/// Rewrite the prose of an analysis (factor labels, descriptions,
+/// recommendations, summary) in `locale`. The analysis is built in English.
+pub fn localize(analysis: &mut FactorAnalysisResponse, locale: Locale) {
+    if locale == Locale::En {
+        return;
+    }
+    for exposure in &mut analysis.factor_exposures {
+        exposure.label = factor_label(locale, &exposure.factor);
+        exposure.description = i18n::message(locale, &format!("factor.{}.description", exposure.factor.as_str()), &[]);
+        exposure.recommendation =
+            factor_recommendation(locale, &exposure.factor, &exposure.exposure_level, exposure.score);
     }
+    analysis.summary = build_summary(&analysis.factor_exposures, &analysis.holdings_scores, locale);
 }
 
 // ============================================================================
@@ -951,18 +1042,20 @@ fn backtest_single_factor(
 fn build_summary(
     exposures: &[PortfolioFactorExposure],
     scores: &[TickerFactorScores],
+    locale: Locale,
 ) -> FactorAnalysisSummary {
+    let not_available = || i18n::message(locale, "factor.summary.not_available", &[]);
     let dominant = exposures
         .iter()
         .max_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(std::cmp::Ordering::Equal))
         .map(|e| e.label.clone())
-        .unwrap_or_else(|| "N/A".to_string());
+        .unwrap_or_else(not_available);
 
     let weakest = exposures
         .iter()
         .min_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(std::cmp::Ordering::Equal))
         .map(|e| e.label.clone())
-        .unwrap_or_else(|| "N/A".to_string());
+        .unwrap_or_else(not_available);
 
     let total_weight: f64 = scores.iter().map(|s| s.weight).sum();
     let overall_composite = if total_weight > 0.0 {
@@ -973,52 +1066,39 @@ fn build_summary(
 
     let mut findings = Vec::new();
 
-    // Identify underweight factors
-    let underweight: Vec<_> = exposures
-        .iter()
-        .filter(|e| e.exposure_level == ExposureLevel::Underweight)
-        .collect();
-    if !underweight.is_empty() {
-        findings.push(format!(
-            "Portfolio is underweight in: {}",
-            underweight
-                .iter()
-                .map(|e| e.label.as_str())
-                .collect::<Vec<_>>()
-                .join(", ")
-        ));
-    }
-
-    // Identify overweight factors
-    let overweight: Vec<_> = exposures
-        .iter()
-        .filter(|e| e.exposure_level == ExposureLevel::Overweight)
-        .collect();
-    if !overweight.is_empty() {
-        findings.push(format!(
-            "Portfolio is overweight in: {}",
-            overweight
-                .iter()
-                .map(|e| e.label.as_str())
-                .collect::<Vec<_>>()
-                .join(", ")
-        ));
+    // Identify underweight and overweight factors
+    for (level, key) in [
+        (ExposureLevel::Underweight, "factor.summary.underweight_in"),
+        (ExposureLevel::Overweight, "factor.summary.overweight_in"),
+    ] {
+        let factors = exposures
+            .iter()
+            .filter(|e| e.exposure_level == level)
+            .map(|e| e.label.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !factors.is_empty() {
+            findings.push(i18n::message(locale, key, &[("factors", factors.as_str())]));
+        }
     }
 
     // Composite assessment
-    if overall_composite >= 65.0 {
-        findings.push("Overall multi-factor composite is strong, indicating good factor diversification.".to_string());
+    let composite_key = if overall_composite >= 65.0 {
+        "factor.summary.composite_strong"
     } else if overall_composite >= 40.0 {
-        findings.push("Overall multi-factor composite is moderate. Consider rebalancing toward underweight factors.".to_string());
+        "factor.summary.composite_moderate"
     } else {
-        findings.push("Overall multi-factor composite is weak. Significant factor rebalancing is recommended.".to_string());
-    }
+        "factor.summary.composite_weak"
+    };
+    findings.push(i18n::message(locale, composite_key, &[]));
 
     // Best individual stock
     if let Some(best) = scores.first() {
-        findings.push(format!(
-            "Highest composite score: {} ({:.0}/100)",
-            best.ticker, best.composite_score
+        let score = format!("{:.0}", best.composite_score);
+        findings.push(i18n::message(
+            locale,
+            "factor.summary.best_composite",
+            &[("ticker", best.ticker.as_str()), ("score", score.as_str())],
         ));
     }
 
@@ -1086,6 +1166,25 @@ mod tests {
         assert!(score > 50.0, "Stable uptrend should score well on quality, got {}", score);
     }
 
+    #[test]
+    fn test_quality_prefers_reported_ratios() {
+        let prices: Vec<f64> = (0..200).map(|i| 100.0 + i as f64 * 0.1).collect();
+        let leveraged = ExternalFundamentals {
+            roe: Some(2.0),
+            net_margin: Some(1.0),
+            debt_to_equity: Some(3.5),
+            ..Default::default()
+        };
+        let score = score_closes(&FactorType::Quality, &prices, Some(&leveraged));
+        assert!(score < 20.0, "Weak reported ratios should override the price proxy, got {}", score);
+
+        let valuation_only = ExternalFundamentals { pe_ratio: Some(15.0), ..Default::default() };
+        assert_eq!(
+            score_closes(&FactorType::Quality, &prices, Some(&valuation_only)),
+            compute_quality_score(&prices)
+        );
+    }
+
     #[test]
     fn test_r_squared_perfect_line() {
         let values: Vec<f64> = (0..50).map(|i| 10.0 + i as f64 * 2.0).collect();
@@ -1197,4 +1296,16 @@ mod tests {
         let value_count = suggestions.iter().filter(|e| e.factor == FactorType::Value).count();
         assert!(value_count >= 2, "Should suggest multiple ETFs for underweight factor, got {}", value_count);
     }
+
+    #[test]
+    fn test_factor_recommendation_in_locale() {
+        let en = factor_recommendation(Locale::En, &FactorType::LowVolatility, &ExposureLevel::Underweight, 20.4);
+        assert_eq!(
+            en,
+            "Your portfolio has low low volatility exposure (20/100). Consider adding low volatility stocks or ETFs to capture the estimated 2.5% annual risk premium."
+        );
+        let fr = factor_recommendation(Locale::Fr, &FactorType::Value, &ExposureLevel::Neutral, 50.0);
+        assert!(fr.contains("facteur valeur est équilibrée (50/100)"), "{}", fr);
+        assert!(fr.contains("4,5 %"), "{}", fr);
+    }
 }
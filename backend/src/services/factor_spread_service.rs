@@ -0,0 +1,334 @@
+//! Long-short factor spreads over the tracked universe.
+//!
+//! Each trading day, every ticker with closes on that day and the one before is
+//! scored on each factor from its history up to the previous close, then the
+//! universe is split into quintiles. The factor's spread for the day is the
+//! equal-weighted return of the top quintile minus the bottom quintile. Compounded
+//! over a few months, the spreads show which factors are currently being rewarded,
+//! which gives context for a portfolio's factor tilts.
+
+use std::collections::HashMap;
+
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db::{factor_spread_queries, price_queries};
+use crate::errors::AppError;
+use crate::external::fundamentals_provider::ExternalFundamentals;
+use crate::models::factor::*;
+use crate::models::PricePoint;
+use crate::services::factor_service;
+use crate::services::fundamentals_service;
+use crate::services::clock;
+
+/// Closes a ticker needs before it can be ranked (the growth score needs 60)
+const MIN_HISTORY: usize = 61;
+/// Closes used for scoring, matching the portfolio factor analysis default
+const SCORING_WINDOW: usize = 252;
+/// Smallest universe split into quintiles (two tickers per bucket)
+const MIN_UNIVERSE: usize = 10;
+/// Tickers without a close in this many calendar days are not tracked
+const UNIVERSE_STALENESS_DAYS: i64 = 10;
+/// Trading days in the "last month" spread
+const MONTH_TRADING_DAYS: usize = 21;
+/// Compounded window spread beyond which a factor counts as working (or not)
+const TREND_THRESHOLD: f64 = 0.02;
+
+/// Equal-weighted mean returns of the top and bottom quintiles of `(score, return)`
+/// pairs, `None` for universes too small to split
+pub fn quintile_returns(ranked: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if ranked.len() < MIN_UNIVERSE {
+        return None;
+    }
+    let mut sorted = ranked.to_vec();
+    sorted.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let bucket = sorted.len() / 5;
+    let mean = |slice: &[(f64, f64)]| slice.iter().map(|(_, r)| r).sum::<f64>() / slice.len() as f64;
+    Some((mean(&sorted[..bucket]), mean(&sorted[sorted.len() - bucket..])))
+}
+
+/// Spreads for every factor on the latest date in `series` (ascending closes per
+/// ticker). Only tickers that closed on that date are ranked.
+pub fn compute_spreads(
+    series: &HashMap<String, Vec<PricePoint>>,
+    fundamentals: &HashMap<String, ExternalFundamentals>,
+) -> Vec<FactorSpread> {
+    let Some(date) = series.values().filter_map(|points| points.last().map(|p| p.date)).max() else {
+        return vec![];
+    };
+
+    // (formation closes, return on `date`) per eligible ticker
+    let universe: Vec<(&str, Vec<f64>, f64)> = series
+        .iter()
+        .filter(|(_, points)| points.last().map(|p| p.date) == Some(date) && points.len() >= MIN_HISTORY)
+        .filter_map(|(ticker, points)| {
+            let closes: Vec<f64> = points.iter().filter_map(|p| p.close_price.to_f64()).collect();
+            if closes.len() != points.len() {
+                return None;
+            }
+            let (last, formation) = closes.split_last()?;
+            let prev = *formation.last()?;
+            if prev <= 0.0 {
+                return None;
+            }
+            let window = formation[formation.len().saturating_sub(SCORING_WINDOW)..].to_vec();
+            Some((ticker.as_str(), window, last / prev - 1.0))
+        })
+        .collect();
+
+    FactorType::all()
+        .into_iter()
+        .filter_map(|factor| {
+            let ranked: Vec<(f64, f64)> = universe
+                .iter()
+                .map(|(ticker, closes, ret)| {
+                    (factor_service::score_closes(&factor, closes, fundamentals.get(*ticker)), *ret)
+                })
+                .collect();
+            let (long_return, short_return) = quintile_returns(&ranked)?;
+            Some(FactorSpread {
+                date,
+                factor: factor.as_str().to_string(),
+                long_return,
+                short_return,
+                spread: long_return - short_return,
+                universe_size: ranked.len() as i32,
+                computed_at: Utc::now(),
+            })
+        })
+        .collect()
+}
+
+/// Compute and store factor spreads for the latest trading day. Returns the
+/// stored rows, empty when the universe is too small to rank.
+pub async fn update_spreads(pool: &PgPool) -> Result<Vec<FactorSpread>, AppError> {
+    let since = clock::today() - Duration::days(UNIVERSE_STALENESS_DAYS);
+    let tickers = price_queries::fetch_tickers_with_prices_since(pool, since).await?;
+    if tickers.len() < MIN_UNIVERSE {
+        info!("Only {} tracked tickers with recent prices; skipping factor spreads", tickers.len());
+        return Ok(vec![]);
+    }
+
+    let series = price_queries::fetch_window_batch(pool, &tickers, SCORING_WINDOW as i64 + 1).await?;
+    let fundamentals = fundamentals_service::stored_all(pool).await?;
+
+    let mut stored = Vec::new();
+    for spread in compute_spreads(&series, &fundamentals) {
+        stored.push(factor_spread_queries::upsert(pool, &spread).await?);
+    }
+    if let Some(first) = stored.first() {
+        info!(
+            "Factor spreads for {} over {} tickers: {}",
+            first.date,
+            first.universe_size,
+            stored
+                .iter()
+                .map(|s| format!("{} {:+.2}%", s.factor, s.spread * 100.0))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    Ok(stored)
+}
+
+/// Compounded return of a run of daily returns
+fn compound(returns: &[f64]) -> f64 {
+    returns.iter().fold(1.0, |acc, r| acc * (1.0 + r)) - 1.0
+}
+
+fn trend(window_spread: f64) -> FactorTrend {
+    if window_spread > TREND_THRESHOLD {
+        FactorTrend::Working
+    } else if window_spread < -TREND_THRESHOLD {
+        FactorTrend::NotWorking
+    } else {
+        FactorTrend::Neutral
+    }
+}
+
+fn context(label: &str, trend: FactorTrend, window_spread: f64, days: usize, exposure: Option<&ExposureLevel>) -> String {
+    let performance = match trend {
+        FactorTrend::Working => format!(
+            "{} is working: top-quintile stocks beat the bottom quintile by {:.1}% over the last {} trading days",
+            label, window_spread * 100.0, days
+        ),
+        FactorTrend::NotWorking => format!(
+            "{} is not working: top-quintile stocks trailed the bottom quintile by {:.1}% over the last {} trading days",
+            label, -window_spread * 100.0, days
+        ),
+        FactorTrend::Neutral => format!(
+            "{} has been flat: top and bottom quintiles are within {:.1}% over the last {} trading days",
+            label, window_spread.abs() * 100.0, days
+        ),
+    };
+    let tilt = match (exposure, trend) {
+        (Some(ExposureLevel::Overweight), FactorTrend::Working) => "Your overweight tilt has been a tailwind.",
+        (Some(ExposureLevel::Overweight), FactorTrend::NotWorking) => "Your overweight tilt has been a headwind.",
+        (Some(ExposureLevel::Underweight), FactorTrend::Working) => "Your underweight tilt has missed this.",
+        (Some(ExposureLevel::Underweight), FactorTrend::NotWorking) => "Your underweight tilt has helped.",
+        (Some(ExposureLevel::Neutral), _) => "Your portfolio is neutral on this factor.",
+        (Some(_), FactorTrend::Neutral) => "Your tilt has made little difference.",
+        (None, _) => "",
+    };
+    if tilt.is_empty() {
+        format!("{}.", performance)
+    } else {
+        format!("{}. {}", performance, tilt)
+    }
+}
+
+/// Summarize one factor's daily spreads (oldest first) against the portfolio's exposure
+pub fn summarize(
+    factor: FactorType,
+    spreads: &[FactorSpread],
+    exposure: Option<&PortfolioFactorExposure>,
+) -> FactorSpreadSummary {
+    let daily: Vec<f64> = spreads.iter().map(|s| s.spread).collect();
+    let window_spread = compound(&daily);
+    let month_spread = compound(&daily[daily.len().saturating_sub(MONTH_TRADING_DAYS)..]);
+    let hit_rate = if daily.is_empty() {
+        0.0
+    } else {
+        daily.iter().filter(|&&s| s > 0.0).count() as f64 / daily.len() as f64
+    };
+    let trend = trend(window_spread);
+    let label = factor.label().to_string();
+
+    FactorSpreadSummary {
+        context: context(&label, trend, window_spread, daily.len(), exposure.map(|e| &e.exposure_level)),
+        factor,
+        label,
+        latest_spread: daily.last().copied(),
+        window_spread,
+        month_spread,
+        hit_rate,
+        observation_days: daily.len(),
+        trend,
+        portfolio_score: exposure.map(|e| e.score),
+        portfolio_exposure: exposure.map(|e| e.exposure_level.clone()),
+    }
+}
+
+/// Factor spreads over the last `days` trading days alongside the portfolio's
+/// factor exposures
+pub async fn get_spreads(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    exposures: &[PortfolioFactorExposure],
+    days: i64,
+) -> Result<FactorSpreadsResponse, AppError> {
+    if !(5..=756).contains(&days) {
+        return Err(AppError::Validation("days must be between 5 and 756".to_string()));
+    }
+    let history = factor_spread_queries::fetch_recent(pool, days).await?;
+
+    let factors = FactorType::all()
+        .into_iter()
+        .map(|factor| {
+            let spreads: Vec<FactorSpread> =
+                history.iter().filter(|s| s.factor == factor.as_str()).cloned().collect();
+            let exposure = exposures.iter().find(|e| e.factor == factor);
+            summarize(factor, &spreads, exposure)
+        })
+        .collect();
+
+    Ok(FactorSpreadsResponse {
+        portfolio_id: portfolio_id.to_string(),
+        as_of: history.last().map(|s| s.date),
+        days,
+        factors,
+        history,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::{BigDecimal, FromPrimitive};
+    use chrono::NaiveDate;
+
+    fn series(ticker: &str, closes: &[f64]) -> Vec<PricePoint> {
+        let end = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+        let n = closes.len() as i64;
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, c)| PricePoint {
+                id: Uuid::nil(),
+                ticker: ticker.to_string(),
+                date: end - Duration::days(n - 1 - i as i64),
+                close_price: BigDecimal::from_f64(*c).unwrap(),
+                created_at: Utc::now(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_quintile_returns_split_top_and_bottom() {
+        // Score i earns i% the next day: top quintile {9, 8}, bottom {1, 0}
+        let ranked: Vec<(f64, f64)> = (0..10).map(|i| (i as f64, i as f64 / 100.0)).collect();
+        let (long, short) = quintile_returns(&ranked).unwrap();
+        assert!((long - 0.085).abs() < 1e-12);
+        assert!((short - 0.005).abs() < 1e-12);
+        assert_eq!(quintile_returns(&ranked[..9]), None);
+    }
+
+    #[test]
+    fn test_momentum_spread_positive_when_trends_continue() {
+        // Trending tickers keep rising on the last day, falling ones keep falling
+        let mut map = HashMap::new();
+        for i in 0..10 {
+            let slope = (i as f64 - 4.5) * 0.002;
+            let closes: Vec<f64> = (0..120).map(|d| 100.0 * (1.0 + slope).powi(d)).collect();
+            map.insert(format!("T{}", i), series(&format!("T{}", i), &closes));
+        }
+        // A stale ticker is left out of the universe
+        let mut stale = series("STALE", &[100.0; 120]);
+        stale.pop();
+        map.insert("STALE".to_string(), stale);
+
+        let spreads = compute_spreads(&map, &HashMap::new());
+        assert_eq!(spreads.len(), FactorType::all().len());
+        let momentum = spreads.iter().find(|s| s.factor == "momentum").unwrap();
+        assert_eq!(momentum.universe_size, 10);
+        assert!(momentum.spread > 0.0, "momentum spread {}", momentum.spread);
+    }
+
+    #[test]
+    fn test_summarize_compounds_and_reads_tilt() {
+        let spreads: Vec<FactorSpread> = (0..30)
+            .map(|i| FactorSpread {
+                date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap() + Duration::days(i),
+                factor: "value".to_string(),
+                long_return: 0.002,
+                short_return: 0.0,
+                spread: 0.002,
+                universe_size: 50,
+                computed_at: Utc::now(),
+            })
+            .collect();
+        let exposure = PortfolioFactorExposure {
+            factor: FactorType::Value,
+            label: "Value".to_string(),
+            description: String::new(),
+            score: 72.0,
+            exposure_level: ExposureLevel::Overweight,
+            expected_risk_premium: 0.0,
+            recommendation: String::new(),
+        };
+
+        let summary = summarize(FactorType::Value, &spreads, Some(&exposure));
+        assert!((summary.window_spread - (1.002f64.powi(30) - 1.0)).abs() < 1e-12);
+        assert!((summary.month_spread - (1.002f64.powi(21) - 1.0)).abs() < 1e-12);
+        assert_eq!(summary.hit_rate, 1.0);
+        assert_eq!(summary.trend, FactorTrend::Working);
+        assert!(summary.context.contains("tailwind"));
+
+        let empty = summarize(FactorType::Quality, &[], None);
+        assert_eq!(empty.trend, FactorTrend::Neutral);
+        assert_eq!(empty.latest_spread, None);
+    }
+}
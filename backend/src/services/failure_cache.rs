@@ -84,6 +84,16 @@ impl FailureCache {
         });
     }
 
+    /// Failures still within their TTL, keyed by cache key
+    pub fn active(&self) -> Vec<(String, FailureInfo)> {
+        let now = Utc::now();
+        self.cache
+            .iter()
+            .filter(|entry| now < entry.value().failed_at + Duration::hours(entry.value().ttl_hours))
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+
     /// Get the number of cached failures
     #[allow(dead_code)]
     pub fn len(&self) -> usize {
@@ -0,0 +1,245 @@
+//! Fee analysis: weighted expense ratio and fee drag projections.
+//!
+//! Expense ratios come from the fund metadata table; holdings not in it (individual
+//! stocks, unknown funds) are treated as fee-free. Swap suggestions only consider
+//! funds of the same category and currency.
+
+use bigdecimal::ToPrimitive;
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{fund_metadata_queries, holding_snapshot_queries};
+use crate::errors::AppError;
+use crate::models::{FeeAnalysis, FeeDragProjection, FeeSwapSuggestion, FundMetadata, HoldingFee};
+
+pub const DEFAULT_EXPECTED_RETURN: f64 = 6.0;
+pub const PROJECTION_YEARS: [u32; 2] = [10, 20];
+/// Alternatives must be at least this much cheaper, in percentage points
+const MIN_EXPENSE_RATIO_SAVING: f64 = 0.05;
+
+/// Fund metadata key for a holding ticker: exchange suffixes such as `.TO` are dropped
+pub fn fund_key(ticker: &str) -> String {
+    let upper = ticker.trim().to_uppercase();
+    for suffix in [".TO", ".V", ".NE", ".CN"] {
+        if let Some(base) = upper.strip_suffix(suffix) {
+            return base.to_string();
+        }
+    }
+    upper
+}
+
+/// Ending value lost to an expense ratio over `years`, both rates in percent
+pub fn fee_drag(value: f64, expected_return: f64, expense_ratio: f64, years: u32) -> f64 {
+    let gross = 1.0 + expected_return / 100.0;
+    let net = gross - expense_ratio / 100.0;
+    value * (gross.powi(years as i32) - net.powi(years as i32))
+}
+
+/// Cheapest fund in the same category and currency, if it is meaningfully cheaper
+fn cheaper_alternative<'a>(fund: &FundMetadata, candidates: &'a [FundMetadata]) -> Option<&'a FundMetadata> {
+    candidates
+        .iter()
+        .filter(|c| c.category == fund.category && c.currency == fund.currency && c.ticker != fund.ticker)
+        .filter(|c| c.expense_ratio <= fund.expense_ratio - MIN_EXPENSE_RATIO_SAVING)
+        .min_by(|a, b| a.expense_ratio.total_cmp(&b.expense_ratio).then_with(|| a.ticker.cmp(&b.ticker)))
+}
+
+/// Analyze fees for `positions` (ticker, holding name, market value). `funds` maps
+/// [`fund_key`]s to metadata; `candidates` are possible swap targets.
+pub fn analyze(
+    portfolio_id: Uuid,
+    positions: &[(String, Option<String>, f64)],
+    funds: &HashMap<String, FundMetadata>,
+    candidates: &[FundMetadata],
+    expected_return: f64,
+) -> FeeAnalysis {
+    let total_value: f64 = positions.iter().map(|p| p.2).sum();
+    let mut holdings = Vec::new();
+    let mut swaps = Vec::new();
+    let mut fund_value = 0.0;
+    let mut annual_fees = 0.0;
+    // (value, current expense ratio, expense ratio after swaps)
+    let mut fee_paths: Vec<(f64, f64, f64)> = Vec::new();
+
+    for (ticker, name, value) in positions {
+        let fund = funds.get(&fund_key(ticker));
+        let annual_fee = fund.map(|f| value * f.expense_ratio / 100.0).unwrap_or(0.0);
+        annual_fees += annual_fee;
+
+        if let Some(fund) = fund {
+            fund_value += value;
+            let alternative = cheaper_alternative(fund, candidates);
+            let swapped_ratio = alternative.map(|a| a.expense_ratio).unwrap_or(fund.expense_ratio);
+            fee_paths.push((*value, fund.expense_ratio, swapped_ratio));
+
+            if let Some(alt) = alternative {
+                let saving = |years| {
+                    fee_drag(*value, expected_return, fund.expense_ratio, years)
+                        - fee_drag(*value, expected_return, alt.expense_ratio, years)
+                };
+                swaps.push(FeeSwapSuggestion {
+                    ticker: ticker.clone(),
+                    expense_ratio: fund.expense_ratio,
+                    alternative_ticker: alt.ticker.clone(),
+                    alternative_name: alt.name.clone(),
+                    alternative_expense_ratio: alt.expense_ratio,
+                    market_value: *value,
+                    annual_savings: value * (fund.expense_ratio - alt.expense_ratio) / 100.0,
+                    savings_10y: saving(10),
+                    savings_20y: saving(20),
+                });
+            }
+        }
+
+        holdings.push(HoldingFee {
+            ticker: ticker.clone(),
+            name: name.clone().or_else(|| fund.map(|f| f.name.clone())),
+            category: fund.map(|f| f.category.clone()),
+            market_value: *value,
+            weight: if total_value > 0.0 { value / total_value * 100.0 } else { 0.0 },
+            expense_ratio: fund.map(|f| f.expense_ratio),
+            annual_fee,
+        });
+    }
+
+    holdings.sort_by(|a, b| b.annual_fee.total_cmp(&a.annual_fee).then_with(|| a.ticker.cmp(&b.ticker)));
+    swaps.sort_by(|a, b| b.annual_savings.total_cmp(&a.annual_savings));
+
+    let projections = PROJECTION_YEARS
+        .iter()
+        .map(|&years| {
+            let fee_drag_now: f64 = fee_paths.iter().map(|(v, er, _)| fee_drag(*v, expected_return, *er, years)).sum();
+            let fee_drag_with_swaps: f64 =
+                fee_paths.iter().map(|(v, _, er)| fee_drag(*v, expected_return, *er, years)).sum();
+            FeeDragProjection {
+                years,
+                fee_drag: fee_drag_now,
+                fee_drag_with_swaps,
+                savings: fee_drag_now - fee_drag_with_swaps,
+            }
+        })
+        .collect();
+
+    FeeAnalysis {
+        portfolio_id,
+        total_value,
+        fund_value,
+        weighted_expense_ratio: if total_value > 0.0 { annual_fees / total_value * 100.0 } else { 0.0 },
+        fund_expense_ratio: if fund_value > 0.0 { annual_fees / fund_value * 100.0 } else { 0.0 },
+        annual_fees,
+        assumed_return: expected_return,
+        projections,
+        holdings,
+        swaps,
+    }
+}
+
+pub async fn portfolio_fees(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    expected_return: Option<f64>,
+) -> Result<FeeAnalysis, AppError> {
+    let expected_return = expected_return.unwrap_or(DEFAULT_EXPECTED_RETURN);
+    if !(-50.0..=50.0).contains(&expected_return) {
+        return Err(AppError::Validation("expected_return must be between -50 and 50".to_string()));
+    }
+
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let mut positions: Vec<(String, Option<String>, f64)> = Vec::new();
+    for holding in holdings {
+        if holding.ticker.is_empty() {
+            continue;
+        }
+        let value = holding.market_value.to_f64().unwrap_or(0.0);
+        match positions.iter_mut().find(|p| p.0 == holding.ticker) {
+            Some(position) => position.2 += value,
+            None => positions.push((holding.ticker, holding.holding_name, value)),
+        }
+    }
+
+    let keys: Vec<String> = positions.iter().map(|p| fund_key(&p.0)).collect();
+    let funds: HashMap<String, FundMetadata> = fund_metadata_queries::fetch_for_tickers(pool, &keys)
+        .await?
+        .into_iter()
+        .map(|f| (f.ticker.clone(), f))
+        .collect();
+    let mut categories: Vec<String> = funds.values().map(|f| f.category.clone()).collect();
+    categories.sort();
+    categories.dedup();
+    let candidates = fund_metadata_queries::fetch_for_categories(pool, &categories).await?;
+
+    Ok(analyze(portfolio_id, &positions, &funds, &candidates, expected_return))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fund(ticker: &str, category: &str, currency: &str, expense_ratio: f64) -> FundMetadata {
+        FundMetadata {
+            ticker: ticker.to_string(),
+            name: format!("{} Fund", ticker),
+            category: category.to_string(),
+            currency: currency.to_string(),
+            expense_ratio,
+        }
+    }
+
+    #[test]
+    fn test_fund_key_strips_exchange_suffix() {
+        assert_eq!(fund_key("xeqt.to"), "XEQT");
+        assert_eq!(fund_key("VTI"), "VTI");
+    }
+
+    #[test]
+    fn test_fee_drag_compounds() {
+        assert_eq!(fee_drag(10_000.0, 6.0, 0.0, 10), 0.0);
+        let drag = fee_drag(10_000.0, 6.0, 1.0, 10);
+        let expected = 10_000.0 * (1.06f64.powi(10) - 1.05f64.powi(10));
+        assert!((drag - expected).abs() < 1e-6);
+        assert!(fee_drag(10_000.0, 6.0, 1.0, 20) > 2.0 * drag);
+    }
+
+    #[test]
+    fn test_weighted_expense_ratio_counts_stocks_as_free() {
+        let funds = HashMap::from([("SPY".to_string(), fund("SPY", "us_large_cap", "USD", 0.10))]);
+        let positions = vec![
+            ("SPY".to_string(), None, 5_000.0),
+            ("AAPL".to_string(), Some("Apple".to_string()), 5_000.0),
+        ];
+        let analysis = analyze(Uuid::nil(), &positions, &funds, &[], 6.0);
+
+        assert!((analysis.annual_fees - 5.0).abs() < 1e-9);
+        assert!((analysis.weighted_expense_ratio - 0.05).abs() < 1e-9);
+        assert!((analysis.fund_expense_ratio - 0.10).abs() < 1e-9);
+        assert_eq!(analysis.holdings[0].ticker, "SPY");
+        assert!(analysis.holdings[1].expense_ratio.is_none());
+        assert!(analysis.swaps.is_empty());
+    }
+
+    #[test]
+    fn test_swaps_stay_within_category_and_currency() {
+        let eem = fund("EEM", "emerging_markets", "USD", 0.70);
+        let funds = HashMap::from([("EEM".to_string(), eem.clone())]);
+        let candidates = vec![
+            eem,
+            fund("VWO", "emerging_markets", "USD", 0.08),
+            fund("XEC", "emerging_markets", "CAD", 0.05),
+            fund("SPLG", "us_large_cap", "USD", 0.02),
+        ];
+        let positions = vec![("EEM".to_string(), None, 100_000.0)];
+        let analysis = analyze(Uuid::nil(), &positions, &funds, &candidates, 6.0);
+
+        assert_eq!(analysis.swaps.len(), 1);
+        let swap = &analysis.swaps[0];
+        assert_eq!(swap.alternative_ticker, "VWO");
+        assert!((swap.annual_savings - 620.0).abs() < 1e-6);
+        assert!(swap.savings_20y > swap.savings_10y);
+
+        let ten = &analysis.projections[0];
+        assert_eq!(ten.years, 10);
+        assert!((ten.savings - swap.savings_10y).abs() < 1e-6);
+    }
+}
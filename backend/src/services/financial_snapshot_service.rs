@@ -1,4 +1,4 @@
-use bigdecimal::BigDecimal;
+use bigdecimal::{BigDecimal, ToPrimitive};
 use std::str::FromStr;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -7,6 +7,7 @@ use uuid::Uuid;
 
 use crate::db::financial_planning_queries;
 use crate::models::financial_planning::*;
+use crate::services::clock;
 
 // ==============================================================================
 // Snapshot calculation constants
@@ -337,25 +338,25 @@ fn calculate_household(
     // Income attribution — employment income
     let primary_employment = income_info.as_ref()
         .and_then(|i| i.gross_annual_income.as_ref())
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .map(|annual| annual / 12.0)
         .unwrap_or(0.0);
 
     let spouse_employment = income_info.as_ref()
         .and_then(|i| i.spouse_gross_annual_income.as_ref())
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .map(|annual| annual / 12.0)
         .unwrap_or(0.0);
 
     // Additional income attribution
     let primary_additional: f64 = additional_income.iter()
         .filter(|i| i.is_recurring.unwrap_or(true) && i.owner == "mine")
-        .filter_map(|i| i.monthly_amount.to_string().parse::<f64>().ok())
+        .filter_map(|i| i.monthly_amount.to_f64())
         .sum();
 
     let spouse_additional: f64 = additional_income.iter()
         .filter(|i| i.is_recurring.unwrap_or(true) && i.owner == "spouse")
-        .filter_map(|i| i.monthly_amount.to_string().parse::<f64>().ok())
+        .filter_map(|i| i.monthly_amount.to_f64())
         .sum();
 
     let primary_monthly_income = primary_employment + primary_additional;
@@ -369,7 +370,7 @@ fn calculate_household(
     let mut spouse_individual = 0.0_f64;
 
     for expense in household_expenses {
-        let amount = expense.monthly_amount.to_string().parse::<f64>().unwrap_or(0.0);
+        let amount = expense.monthly_amount.to_f64().unwrap_or(0.0);
         match expense.expense_type.as_str() {
             "shared" => shared_expenses += amount,
             "mine" => primary_individual += amount,
@@ -422,14 +423,14 @@ fn attribute_assets(assets: &[SurveyAsset]) -> (f64, f64) {
     let mut spouse = 0.0_f64;
 
     for asset in assets {
-        let value = asset.current_value.to_string().parse::<f64>().unwrap_or(0.0);
+        let value = asset.current_value.to_f64().unwrap_or(0.0);
         match asset.ownership.as_str() {
             "mine" => primary += value,
             "spouse" => spouse += value,
             "joint" => {
                 let split = asset.joint_split_percentage
                     .as_ref()
-                    .and_then(|v| v.to_string().parse::<f64>().ok())
+                    .and_then(|v| v.to_f64())
                     .unwrap_or(50.0);
                 let primary_share = value * split / 100.0;
                 primary += primary_share;
@@ -448,14 +449,14 @@ fn attribute_liabilities(liabilities: &[SurveyLiability]) -> (f64, f64) {
     let mut spouse = 0.0_f64;
 
     for liability in liabilities {
-        let value = liability.balance.to_string().parse::<f64>().unwrap_or(0.0);
+        let value = liability.balance.to_f64().unwrap_or(0.0);
         match liability.ownership.as_str() {
             "mine" => primary += value,
             "spouse" => spouse += value,
             "joint" => {
                 let split = liability.joint_split_percentage
                     .as_ref()
-                    .and_then(|v| v.to_string().parse::<f64>().ok())
+                    .and_then(|v| v.to_f64())
                     .unwrap_or(50.0);
                 let primary_share = value * split / 100.0;
                 primary += primary_share;
@@ -478,25 +479,25 @@ fn calculate_net_worth(
 ) -> NetWorthBreakdown {
     let total_assets: f64 = assets
         .iter()
-        .map(|a| a.current_value.to_string().parse::<f64>().unwrap_or(0.0))
+        .map(|a| a.current_value.to_f64().unwrap_or(0.0))
         .sum();
 
     let total_liabilities: f64 = liabilities
         .iter()
-        .map(|l| l.balance.to_string().parse::<f64>().unwrap_or(0.0))
+        .map(|l| l.balance.to_f64().unwrap_or(0.0))
         .sum();
 
     // Group assets by type
     let mut assets_by_type: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
     for asset in assets {
-        let value = asset.current_value.to_string().parse::<f64>().unwrap_or(0.0);
+        let value = asset.current_value.to_f64().unwrap_or(0.0);
         *assets_by_type.entry(asset.asset_type.clone()).or_insert(0.0) += value;
     }
 
     // Group liabilities by type
     let mut liabilities_by_type: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
     for liability in liabilities {
-        let value = liability.balance.to_string().parse::<f64>().unwrap_or(0.0);
+        let value = liability.balance.to_f64().unwrap_or(0.0);
         *liabilities_by_type.entry(liability.liability_type.clone()).or_insert(0.0) += value;
     }
 
@@ -517,7 +518,7 @@ fn calculate_net_worth(
 
 fn parse_rate(bd: &Option<bigdecimal::BigDecimal>) -> f64 {
     bd.as_ref()
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .unwrap_or(0.0) / 100.0
 }
 
@@ -562,7 +563,7 @@ fn estimate_monthly_cash_flow(
     // Primary salary (always annual)
     let primary_salary = income.gross_annual_income
         .as_ref()
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .map(|a| a / 12.0)
         .unwrap_or(0.0);
 
@@ -570,7 +571,7 @@ fn estimate_monthly_cash_flow(
     let spouse_salary = if has_spouse {
         income.spouse_gross_annual_income
             .as_ref()
-            .and_then(|v| v.to_string().parse::<f64>().ok())
+            .and_then(|v| v.to_f64())
             .map(|a| a / 12.0)
             .unwrap_or(0.0)
     } else {
@@ -586,7 +587,7 @@ fn estimate_monthly_cash_flow(
             (i.owner == "mine" || (has_spouse && i.owner == "spouse"))
         })
         .fold((0.0_f64, 0.0_f64), |(gross_acc, tax_acc), i| {
-            let amount = i.monthly_amount.to_string().parse::<f64>().unwrap_or(0.0);
+            let amount = i.monthly_amount.to_f64().unwrap_or(0.0);
             let rate = if i.owner == "spouse" {
                 if is_investment_income(&i.income_type) { spouse_invest_rate } else { spouse_salary_rate }
             } else {
@@ -601,12 +602,12 @@ fn estimate_monthly_cash_flow(
     // Payroll deductions (CPP, EI, benefit premiums etc.) — monthly dollar amounts
     let primary_deductions = income.monthly_deductions
         .as_ref()
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .unwrap_or(0.0);
     let spouse_deductions = if has_spouse {
         income.spouse_monthly_deductions
             .as_ref()
-            .and_then(|v| v.to_string().parse::<f64>().ok())
+            .and_then(|v| v.to_f64())
             .unwrap_or(0.0)
     } else {
         0.0
@@ -624,7 +625,7 @@ fn estimate_monthly_cash_flow(
             let payment = liability
                 .monthly_payment
                 .as_ref()
-                .and_then(|v| v.to_string().parse::<f64>().ok())
+                .and_then(|v| v.to_f64())
                 .unwrap_or(0.0);
 
             if payment == 0.0 {
@@ -649,7 +650,7 @@ fn estimate_monthly_cash_flow(
         l.liability_type == "mortgage" &&
         l.monthly_payment
             .as_ref()
-            .and_then(|v| v.to_string().parse::<f64>().ok())
+            .and_then(|v| v.to_f64())
             .unwrap_or(0.0) > 0.0
     });
 
@@ -662,13 +663,13 @@ fn estimate_monthly_cash_flow(
     if !expenses.is_empty() {
         for e in expenses.iter().filter(|e| e.is_recurring.unwrap_or(true)) {
             if has_mortgage_payment && e.expense_category == "housing" { continue; }
-            let amount = e.monthly_amount.to_string().parse::<f64>().unwrap_or(0.0);
+            let amount = e.monthly_amount.to_f64().unwrap_or(0.0);
             *expense_map.entry(e.expense_category.clone()).or_insert(0.0) += amount;
         }
     } else if !household_expenses.is_empty() {
         for e in household_expenses.iter() {
             if has_mortgage_payment && e.expense_category == "housing" { continue; }
-            let amount = e.monthly_amount.to_string().parse::<f64>().unwrap_or(0.0);
+            let amount = e.monthly_amount.to_f64().unwrap_or(0.0);
             // When household mode: use full amounts (income is already household-combined).
             // When individual mode: primary's share only (mine + half shared).
             let counted = if has_spouse {
@@ -743,33 +744,70 @@ fn project_retirement_income(
         return None;
     }
 
+    let annual_contribution = annual_retirement_contribution(income);
+    let current_retirement_savings = current_retirement_savings(goals, assets);
+
+    // Future value of current savings: FV = PV * (1 + r)^n
+    let fv_current = current_retirement_savings
+        * (1.0 + ASSUMED_RETURN_RATE).powi(years_to_retirement);
+
+    // Future value of annual contributions (annuity): FV = PMT * [((1+r)^n - 1) / r]
+    let fv_contributions = if ASSUMED_RETURN_RATE > 0.0 {
+        annual_contribution
+            * (((1.0 + ASSUMED_RETURN_RATE).powi(years_to_retirement) - 1.0) / ASSUMED_RETURN_RATE)
+    } else {
+        annual_contribution * years_to_retirement as f64
+    };
+
+    let total_at_retirement = fv_current + fv_contributions;
+
+    // 4% withdrawal rule for monthly income
+    let monthly_income = (total_at_retirement * WITHDRAWAL_RATE) / 12.0;
+
+    Some(RetirementProjection {
+        current_retirement_savings,
+        annual_contribution,
+        years_to_retirement,
+        projected_total_at_retirement: total_at_retirement,
+        estimated_monthly_income: monthly_income,
+        assumed_return_rate: ASSUMED_RETURN_RATE,
+        assumed_withdrawal_rate: WITHDRAWAL_RATE,
+    })
+}
+
+/// Yearly retirement contribution from payroll: the employee's contribution
+/// rate plus the employer match, applied to gross salary.
+pub(crate) fn annual_retirement_contribution(income: &SurveyIncomeInfo) -> f64 {
     let gross_annual = income.gross_annual_income
         .as_ref()
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .unwrap_or(0.0);
 
     let contribution_rate = income.retirement_contribution_rate
         .as_ref()
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .unwrap_or(0.0) / 100.0;
 
     let employer_match = income.employer_match_rate
         .as_ref()
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .unwrap_or(0.0) / 100.0;
 
-    let annual_contribution = gross_annual * (contribution_rate + employer_match);
+    gross_annual * (contribution_rate + employer_match)
+}
 
+/// What the user has saved towards retirement so far.
+pub(crate) fn current_retirement_savings(goals: &[SurveyGoal], assets: &[SurveyAsset]) -> f64 {
     // Prefer the retirement goal's current_savings if explicitly set by the user,
     // because that represents what the user considers their retirement savings.
     // Fall back to summing retirement-type assets if no goal is set.
     let retirement_goal_savings = goals.iter()
         .find(|g| g.goal_type == "retirement")
         .and_then(|g| g.current_savings.as_ref())
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .filter(|&v| v > 0.0);
 
-    let current_retirement_savings: f64 = retirement_goal_savings.unwrap_or_else(|| {
+    retirement_goal_savings.unwrap_or_else(|| {
         assets
             .iter()
             .filter(|a| {
@@ -778,47 +816,20 @@ fn project_retirement_income(
                     "retirement" | "rrsp" | "lira" | "rrif" | "tfsa"
                 )
             })
-            .map(|a| a.current_value.to_string().parse::<f64>().unwrap_or(0.0))
+            .map(|a| a.current_value.to_f64().unwrap_or(0.0))
             .sum()
-    });
-
-    // Future value of current savings: FV = PV * (1 + r)^n
-    let fv_current = current_retirement_savings
-        * (1.0 + ASSUMED_RETURN_RATE).powi(years_to_retirement);
-
-    // Future value of annual contributions (annuity): FV = PMT * [((1+r)^n - 1) / r]
-    let fv_contributions = if ASSUMED_RETURN_RATE > 0.0 {
-        annual_contribution
-            * (((1.0 + ASSUMED_RETURN_RATE).powi(years_to_retirement) - 1.0) / ASSUMED_RETURN_RATE)
-    } else {
-        annual_contribution * years_to_retirement as f64
-    };
-
-    let total_at_retirement = fv_current + fv_contributions;
-
-    // 4% withdrawal rule for monthly income
-    let monthly_income = (total_at_retirement * WITHDRAWAL_RATE) / 12.0;
-
-    Some(RetirementProjection {
-        current_retirement_savings,
-        annual_contribution,
-        years_to_retirement,
-        projected_total_at_retirement: total_at_retirement,
-        estimated_monthly_income: monthly_income,
-        assumed_return_rate: ASSUMED_RETURN_RATE,
-        assumed_withdrawal_rate: WITHDRAWAL_RATE,
     })
 }
 
 fn calculate_goal_progress(goal: &SurveyGoal) -> GoalProgress {
     let target = goal.target_amount
         .as_ref()
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .unwrap_or(0.0);
 
     let current = goal.current_savings
         .as_ref()
-        .and_then(|v| v.to_string().parse::<f64>().ok())
+        .and_then(|v| v.to_f64())
         .unwrap_or(0.0);
 
     let progress_percentage = if target > 0.0 {
@@ -828,7 +839,7 @@ fn calculate_goal_progress(goal: &SurveyGoal) -> GoalProgress {
     };
 
     let months_remaining = goal.target_date.map(|target_date| {
-        let today = Utc::now().date_naive();
+        let today = clock::today();
         let days = (target_date - today).num_days();
         (days as f64 / 30.44).ceil() as i64
     });
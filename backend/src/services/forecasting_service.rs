@@ -1,3 +1,4 @@
+use bigdecimal::ToPrimitive;
 use chrono::{Duration, NaiveDate, Utc};
 use std::collections::HashMap;
 use tracing::{info, warn};
@@ -8,6 +9,7 @@ use crate::errors::AppError;
 use crate::external::price_provider::PriceProvider;
 use crate::models::{ForecastMethod, ForecastPoint, HistoricalDataPoint, LatestAccountHolding, PortfolioForecast, RiskPreferences};
 use crate::services::failure_cache::FailureCache;
+use crate::services::{clock, risk_memo};
 use crate::services::user_preference_service;
 use sqlx::PgPool;
 
@@ -107,7 +109,7 @@ async fn fetch_current_portfolio_value(
     let total: f64 = history
         .iter()
         .filter(|r| r.snapshot_date == latest_date)
-        .map(|r| r.total_value.to_string().parse::<f64>().unwrap_or(0.0))
+        .map(|r| r.total_value.to_f64().unwrap_or(0.0))
         .sum();
 
     Ok(total)
@@ -161,7 +163,7 @@ async fn fetch_historical_portfolio_values(
 
     for record in history {
         let date_str = record.snapshot_date.to_string();
-        let value = record.total_value.to_string().parse::<f64>().unwrap_or(0.0);
+        let value = record.total_value.to_f64().unwrap_or(0.0);
 
         *date_values.entry(date_str).or_insert(0.0) += value;
     }
@@ -203,7 +205,7 @@ async fn fetch_portfolio_cash_flows(
 
     Ok(records
         .into_iter()
-        .map(|r| (r.flow_date, r.amount.to_string().parse::<f64>().unwrap_or(0.0), r.flow_type))
+        .map(|r| (r.flow_date, r.amount.to_f64().unwrap_or(0.0), r.flow_type))
         .collect())
 }
 
@@ -745,7 +747,7 @@ pub async fn generate_benchmark_based_forecast(
     // Calculate current portfolio value
     let current_value: f64 = holdings
         .iter()
-        .map(|h| h.market_value.to_string().parse::<f64>().unwrap_or(0.0))
+        .map(|h| h.market_value.to_f64().unwrap_or(0.0))
         .sum();
 
     if current_value <= 0.0 {
@@ -915,7 +917,7 @@ fn categorize_holdings_by_benchmark(holdings: &[LatestAccountHolding]) -> Vec<Be
     holdings
         .iter()
         .map(|h| {
-            let market_value = h.market_value.to_string().parse::<f64>().unwrap_or(0.0);
+            let market_value = h.market_value.to_f64().unwrap_or(0.0);
             let benchmark = map_asset_category_to_benchmark(&h.asset_category);
 
             BenchmarkedHolding {
@@ -936,7 +938,7 @@ async fn fetch_benchmark_history(
     _failure_cache: &FailureCache,
 ) -> Result<Vec<(NaiveDate, f64)>, AppError> {
     // First try to get from database
-    let cutoff_date = Utc::now().date_naive() - Duration::days(days);
+    let cutoff_date = clock::today() - Duration::days(days);
 
     let prices = sqlx::query!(
         "SELECT date, close_price FROM price_points
@@ -953,7 +955,7 @@ async fn fetch_benchmark_history(
     if prices.len() >= (days as usize / 2) {
         return Ok(prices
             .into_iter()
-            .map(|p| (p.date, p.close_price.to_string().parse::<f64>().unwrap_or(0.0)))
+            .map(|p| (p.date, p.close_price.to_f64().unwrap_or(0.0)))
             .collect());
     }
 
@@ -970,7 +972,7 @@ async fn fetch_benchmark_history(
             let mut result = Vec::new();
 
             for point in &api_prices {
-                let price_f64 = point.close.to_string().parse::<f64>().unwrap_or(0.0);
+                let price_f64 = point.close.to_f64().unwrap_or(0.0);
                 result.push((point.date, price_f64));
 
                 // Store in database for future use
@@ -985,6 +987,7 @@ async fn fetch_benchmark_history(
                 .execute(pool)
                 .await;
             }
+            risk_memo::invalidate(ticker);
 
             Ok(result)
         }
@@ -0,0 +1,395 @@
+//! Reported fundamentals per ticker: valuation and key ratios, quarterly
+//! earnings, announced earnings dates and dividend history.
+//!
+//! Provider data is stored per ticker; a daily job refreshes held tickers and
+//! the ratios are refetched at most once a day. Screening, factor scoring and
+//! long-term guidance read only the stored copy and keep their price-based
+//! proxies for tickers that have never been fetched.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sqlx::PgPool;
+use tracing::{debug, info, warn};
+
+use crate::db::{dividend_queries, fundamentals_queries};
+use crate::errors::AppError;
+use crate::external::fundamentals_provider::{ExternalFundamentals, FundamentalsProvider};
+use crate::external::price_provider::PriceProviderError;
+use crate::models::{DividendPayment, EarningsReport, FundamentalsHistory, FundamentalsSnapshot};
+use crate::services::clock;
+
+const CACHE_HOURS: i64 = 24;
+
+/// Years of dividends fetched for a ticker without stored history
+const DIVIDEND_HISTORY_YEARS: i64 = 5;
+
+/// Days ahead searched for announced earnings releases
+const EARNINGS_CALENDAR_DAYS: i64 = 120;
+
+/// Four provider calls per ticker; keeps a refresh under 60 calls a minute
+const INTER_TICKER_DELAY_MS: u64 = 4000;
+
+/// Score assigned to loss-making companies and negative book values
+const NEGATIVE_RATIO_SCORE: f64 = 10.0;
+
+fn snapshot(ticker: &str, raw: &ExternalFundamentals, fetched_at: DateTime<Utc>) -> FundamentalsSnapshot {
+    FundamentalsSnapshot {
+        ticker: ticker.to_string(),
+        pe_ratio: raw.pe_ratio,
+        pb_ratio: raw.pb_ratio,
+        market_cap: raw.market_cap,
+        roe: raw.roe,
+        net_margin: raw.net_margin,
+        debt_to_equity: raw.debt_to_equity,
+        dividend_yield: raw.dividend_yield,
+        payout_ratio: raw.payout_ratio,
+        eps_growth_5y: raw.eps_growth_5y,
+        fetched_at,
+    }
+}
+
+/// Value score (0-100) for a P/E ratio: 10x scores 90, 40x scores 10
+pub fn pe_ratio_score(pe: f64) -> f64 {
+    if pe <= 0.0 {
+        return NEGATIVE_RATIO_SCORE;
+    }
+    ((40.0 - pe) / 30.0 * 80.0 + 10.0).clamp(5.0, 95.0)
+}
+
+/// Value score (0-100) for a P/B ratio: 1x scores 90, 6x scores 10
+pub fn pb_ratio_score(pb: f64) -> f64 {
+    if pb <= 0.0 {
+        return NEGATIVE_RATIO_SCORE;
+    }
+    ((6.0 - pb) / 5.0 * 80.0 + 10.0).clamp(5.0, 95.0)
+}
+
+/// Mean of the P/E and P/B scores that are available, `None` without either
+pub fn value_score(raw: &ExternalFundamentals) -> Option<f64> {
+    let scores: Vec<f64> = [raw.pe_ratio.map(pe_ratio_score), raw.pb_ratio.map(pb_ratio_score)]
+        .into_iter()
+        .flatten()
+        .collect();
+    if scores.is_empty() {
+        return None;
+    }
+    Some(scores.iter().sum::<f64>() / scores.len() as f64)
+}
+
+/// Score (0-100) of a ratio that is better the higher it is: 0 scores 10,
+/// `good` scores 90
+fn higher_is_better(value: f64, good: f64) -> f64 {
+    (value / good * 80.0 + 10.0).clamp(5.0, 95.0)
+}
+
+/// Quality score (0-100) from profitability and leverage: the mean of the ROE
+/// (25% scores 90), net margin (20% scores 90) and debt/equity (0x scores 90,
+/// 2x scores 10) scores available, `None` without any
+pub fn quality_score(raw: &ExternalFundamentals) -> Option<f64> {
+    let leverage_score = |de: f64| {
+        if de < 0.0 {
+            // Negative equity
+            NEGATIVE_RATIO_SCORE
+        } else {
+            ((2.0 - de) / 2.0 * 80.0 + 10.0).clamp(5.0, 95.0)
+        }
+    };
+    let scores: Vec<f64> = [
+        raw.roe.map(|roe| higher_is_better(roe, 25.0)),
+        raw.net_margin.map(|margin| higher_is_better(margin, 20.0)),
+        raw.debt_to_equity.map(leverage_score),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if scores.is_empty() {
+        return None;
+    }
+    Some(scores.iter().sum::<f64>() / scores.len() as f64)
+}
+
+/// Dividends per share with an ex-date in the twelve months up to `as_of`
+pub fn trailing_annual_dividend(payments: &[DividendPayment], as_of: NaiveDate) -> f64 {
+    let start = as_of - Duration::days(365);
+    payments
+        .iter()
+        .filter(|p| p.ex_date > start && p.ex_date <= as_of)
+        .map(|p| p.amount)
+        .sum()
+}
+
+/// Year-over-year growth of the trailing annual dividend in percent, `None`
+/// without payments in the prior year
+pub fn dividend_growth(payments: &[DividendPayment], as_of: NaiveDate) -> Option<f64> {
+    let prior = trailing_annual_dividend(payments, as_of - Duration::days(365));
+    if prior <= 0.0 {
+        return None;
+    }
+    Some((trailing_annual_dividend(payments, as_of) / prior - 1.0) * 100.0)
+}
+
+/// Year-over-year growth of trailing twelve-month EPS in percent, from quarters
+/// most recent first. Needs eight consecutive reported quarters and positive
+/// prior-year earnings.
+pub fn ttm_eps_growth(earnings: &[EarningsReport]) -> Option<f64> {
+    let eps: Vec<f64> = earnings.iter().map_while(|e| e.eps_actual).take(8).collect();
+    if eps.len() < 8 {
+        return None;
+    }
+    let recent: f64 = eps[..4].iter().sum();
+    let prior: f64 = eps[4..].iter().sum();
+    if prior <= 0.0 {
+        return None;
+    }
+    Some((recent / prior - 1.0) * 100.0)
+}
+
+/// Stored fundamentals for a ticker, regardless of age
+pub async fn stored(pool: &PgPool, ticker: &str) -> Result<Option<(ExternalFundamentals, DateTime<Utc>)>, AppError> {
+    let Some((data, fetched_at)) = fundamentals_queries::fetch(pool, ticker).await? else {
+        return Ok(None);
+    };
+    match serde_json::from_value(data) {
+        Ok(raw) => Ok(Some((raw, fetched_at))),
+        Err(e) => {
+            warn!("Discarding unreadable fundamentals for {}: {}", ticker, e);
+            Ok(None)
+        }
+    }
+}
+
+/// Every ticker's stored fundamentals, regardless of age; unreadable rows are skipped
+pub async fn stored_all(pool: &PgPool) -> Result<HashMap<String, ExternalFundamentals>, AppError> {
+    let rows = fundamentals_queries::fetch_all(pool).await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(ticker, data)| serde_json::from_value(data).ok().map(|raw| (ticker, raw)))
+        .collect())
+}
+
+/// Fundamentals for a ticker, refreshed from the provider when the stored copy
+/// is older than a day (or `force` is set). A stale copy is served if the
+/// provider fails.
+pub async fn get_snapshot(
+    pool: &PgPool,
+    provider: &dyn FundamentalsProvider,
+    ticker: &str,
+    force: bool,
+) -> Result<FundamentalsSnapshot, AppError> {
+    let ticker = ticker.to_uppercase();
+    let existing = stored(pool, &ticker).await?;
+
+    if let Some((raw, fetched_at)) = &existing {
+        if !force && Utc::now() - *fetched_at < Duration::hours(CACHE_HOURS) {
+            return Ok(snapshot(&ticker, raw, *fetched_at));
+        }
+    }
+
+    match provider.fetch_fundamentals(&ticker).await {
+        Ok(raw) => {
+            let data = serde_json::to_value(&raw)
+                .map_err(|e| AppError::External(format!("Failed to serialize fundamentals: {}", e)))?;
+            let fetched_at = fundamentals_queries::upsert(pool, &ticker, data).await?;
+            info!("Stored fundamentals for {}: P/E {:?}, P/B {:?}", ticker, raw.pe_ratio, raw.pb_ratio);
+            Ok(snapshot(&ticker, &raw, fetched_at))
+        }
+        Err(e) => match existing {
+            Some((raw, fetched_at)) => {
+                warn!("Fundamentals refresh failed for {}, serving data from {}: {}", ticker, fetched_at, e);
+                Ok(snapshot(&ticker, &raw, fetched_at))
+            }
+            None => match e {
+                PriceProviderError::NotFound => Err(AppError::NotFound(format!("No fundamentals for {}", ticker))),
+                other => Err(AppError::External(format!("Failed to fetch fundamentals for {}: {}", ticker, other))),
+            },
+        },
+    }
+}
+
+/// Stored earnings and dividends of a ticker
+pub async fn history(pool: &PgPool, ticker: &str) -> Result<FundamentalsHistory, AppError> {
+    let ticker = ticker.to_uppercase();
+    let today = clock::today();
+    let earnings = fundamentals_queries::fetch_earnings(pool, &ticker).await?;
+    let dividends =
+        fundamentals_queries::fetch_dividends(pool, &ticker, today - Duration::days(365 * DIVIDEND_HISTORY_YEARS)).await?;
+
+    Ok(FundamentalsHistory {
+        ticker,
+        ttm_eps_growth: ttm_eps_growth(&earnings),
+        trailing_annual_dividend: trailing_annual_dividend(&dividends, today),
+        dividend_growth: dividend_growth(&dividends, today),
+        earnings,
+        dividends,
+    })
+}
+
+/// Result of a refresh run
+#[derive(Debug, Default)]
+pub struct FundamentalsRefresh {
+    pub refreshed: usize,
+    pub failed: usize,
+}
+
+/// Refresh the ratios, earnings and dividends of every held ticker
+pub async fn refresh_held_tickers(
+    pool: &PgPool,
+    provider: &dyn FundamentalsProvider,
+) -> Result<FundamentalsRefresh, AppError> {
+    let tickers = dividend_queries::fetch_held_tickers(pool).await?;
+    let mut refresh = FundamentalsRefresh::default();
+
+    for ticker in &tickers {
+        if refresh_ticker(pool, provider, ticker).await? {
+            refresh.refreshed += 1;
+        } else {
+            refresh.failed += 1;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(INTER_TICKER_DELAY_MS)).await;
+    }
+
+    Ok(refresh)
+}
+
+/// Refresh one ticker: ratios when older than a day, all reported earnings,
+/// announced earnings dates and dividends since the latest stored one. Returns false if a provider call
+/// failed; tickers the provider has no data for (ETFs, funds) are not failures.
+async fn refresh_ticker(pool: &PgPool, provider: &dyn FundamentalsProvider, ticker: &str) -> Result<bool, AppError> {
+    let mut ok = true;
+
+    match get_snapshot(pool, provider, ticker, false).await {
+        Ok(_) | Err(AppError::NotFound(_)) => {}
+        Err(AppError::Db(e)) => return Err(AppError::Db(e)),
+        Err(e) => {
+            warn!("Failed to refresh ratios for {}: {}", ticker, e);
+            ok = false;
+        }
+    }
+
+    match provider.fetch_earnings(ticker).await {
+        Ok(reports) => fundamentals_queries::upsert_earnings(pool, ticker, &reports).await?,
+        Err(PriceProviderError::NotFound) => debug!("No earnings reported for {}", ticker),
+        Err(e) => {
+            warn!("Failed to fetch earnings for {}: {}", ticker, e);
+            ok = false;
+        }
+    }
+
+    let today = clock::today();
+    match provider.fetch_upcoming_earnings(ticker, today, today + Duration::days(EARNINGS_CALENDAR_DAYS)).await {
+        Ok(dates) => fundamentals_queries::replace_earnings_dates(pool, ticker, today, &dates).await?,
+        Err(PriceProviderError::NotFound) => debug!("No earnings dates announced for {}", ticker),
+        Err(e) => {
+            warn!("Failed to fetch earnings dates for {}: {}", ticker, e);
+            ok = false;
+        }
+    }
+
+    let from = match fundamentals_queries::fetch_latest_dividend_date(pool, ticker).await? {
+        Some(latest) => latest + Duration::days(1),
+        None => today - Duration::days(365 * DIVIDEND_HISTORY_YEARS),
+    };
+    match provider.fetch_dividend_history(ticker, from).await {
+        Ok(payments) => fundamentals_queries::upsert_dividends(pool, ticker, &payments).await?,
+        Err(PriceProviderError::NotFound) => debug!("No dividends reported for {}", ticker),
+        Err(e) => {
+            warn!("Failed to fetch dividends for {}: {}", ticker, e);
+            ok = false;
+        }
+    }
+
+    Ok(ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ratio_scores_favor_cheap_valuations() {
+        assert!((pe_ratio_score(10.0) - 90.0).abs() < 1e-9);
+        assert!((pe_ratio_score(40.0) - 10.0).abs() < 1e-9);
+        assert!(pe_ratio_score(15.0) > pe_ratio_score(25.0));
+        assert_eq!(pe_ratio_score(-12.0), NEGATIVE_RATIO_SCORE);
+        assert_eq!(pe_ratio_score(200.0), 5.0);
+
+        assert!((pb_ratio_score(1.0) - 90.0).abs() < 1e-9);
+        assert!((pb_ratio_score(6.0) - 10.0).abs() < 1e-9);
+        assert_eq!(pb_ratio_score(0.3), 95.0);
+    }
+
+    #[test]
+    fn test_value_score_uses_available_ratios() {
+        let both = ExternalFundamentals { pe_ratio: Some(10.0), pb_ratio: Some(6.0), ..Default::default() };
+        assert!((value_score(&both).unwrap() - 50.0).abs() < 1e-9);
+
+        let pe_only = ExternalFundamentals { pe_ratio: Some(10.0), ..Default::default() };
+        assert!((value_score(&pe_only).unwrap() - 90.0).abs() < 1e-9);
+
+        let cap_only = ExternalFundamentals { market_cap: Some(1e9), ..Default::default() };
+        assert_eq!(value_score(&cap_only), None);
+    }
+
+    #[test]
+    fn test_quality_score_from_profitability_and_leverage() {
+        let strong = ExternalFundamentals {
+            roe: Some(25.0),
+            net_margin: Some(20.0),
+            debt_to_equity: Some(0.0),
+            ..Default::default()
+        };
+        assert!((quality_score(&strong).unwrap() - 90.0).abs() < 1e-9);
+
+        let weak = ExternalFundamentals { roe: Some(-5.0), debt_to_equity: Some(3.0), ..Default::default() };
+        assert!(quality_score(&weak).unwrap() < 10.0);
+        assert_eq!(quality_score(&ExternalFundamentals { pe_ratio: Some(15.0), ..Default::default() }), None);
+    }
+
+    fn payment(ex_date: &str, amount: f64) -> DividendPayment {
+        DividendPayment {
+            ticker: "KO".to_string(),
+            ex_date: ex_date.parse().unwrap(),
+            pay_date: None,
+            amount,
+            currency: None,
+        }
+    }
+
+    #[test]
+    fn test_trailing_dividend_and_growth() {
+        let payments = vec![
+            payment("2024-11-29", 0.485),
+            payment("2024-09-13", 0.485),
+            payment("2024-06-14", 0.485),
+            payment("2024-03-14", 0.485),
+            payment("2023-11-30", 0.46),
+            payment("2023-09-14", 0.46),
+            payment("2023-06-15", 0.46),
+            payment("2023-03-16", 0.46),
+        ];
+        let as_of: NaiveDate = "2024-12-31".parse().unwrap();
+        assert!((trailing_annual_dividend(&payments, as_of) - 1.94).abs() < 1e-9);
+        assert!((dividend_growth(&payments, as_of).unwrap() - (1.94 / 1.84 - 1.0) * 100.0).abs() < 1e-9);
+        assert_eq!(dividend_growth(&payments[..4], as_of), None);
+    }
+
+    #[test]
+    fn test_ttm_eps_growth_needs_eight_quarters() {
+        let report = |eps: Option<f64>| EarningsReport {
+            ticker: "AAPL".to_string(),
+            period_end: "2024-12-31".parse().unwrap(),
+            fiscal_year: 2025,
+            fiscal_quarter: 1,
+            eps_actual: eps,
+            eps_estimate: None,
+        };
+        let quarters: Vec<EarningsReport> = [1.5, 1.5, 1.5, 1.5, 1.0, 1.0, 1.0, 1.0].iter().map(|&e| report(Some(e))).collect();
+        assert!((ttm_eps_growth(&quarters).unwrap() - 50.0).abs() < 1e-9);
+        assert_eq!(ttm_eps_growth(&quarters[..7]), None);
+
+        let mut gap = quarters.clone();
+        gap[2] = report(None);
+        assert_eq!(ttm_eps_growth(&gap), None);
+    }
+}
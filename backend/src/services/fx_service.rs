@@ -0,0 +1,273 @@
+//! Exchange rates and currency conversion.
+//!
+//! Holdings and prices are stored in the currency they are quoted in. A daily
+//! job stores ECB reference rates as units per US dollar; portfolio totals,
+//! weights and analytics convert market values into the base currency the
+//! portfolio's owner picked.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, NaiveDate};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::fx_queries;
+use crate::errors::AppError;
+use crate::external::fx_provider::FxProvider;
+use crate::models::LatestAccountHolding;
+use crate::services::clock;
+
+/// Currencies with ECB reference rates
+pub const SUPPORTED_CURRENCIES: &[&str] = &[
+    "USD", "EUR", "JPY", "BGN", "CZK", "DKK", "GBP", "HUF", "PLN", "RON", "SEK", "CHF", "ISK", "NOK", "TRY",
+    "AUD", "BRL", "CAD", "CNY", "HKD", "IDR", "ILS", "INR", "KRW", "MXN", "MYR", "NZD", "PHP", "SGD", "THB",
+    "ZAR",
+];
+
+/// Quote currency by exchange suffix. London listings are left out: they are
+/// quoted in pence, which no reference rate covers.
+const SUFFIX_CURRENCIES: &[(&str, &str)] = &[
+    (".TO", "CAD"),
+    (".V", "CAD"),
+    (".CN", "CAD"),
+    (".NE", "CAD"),
+    (".AX", "AUD"),
+    (".HK", "HKD"),
+    (".T", "JPY"),
+    (".SW", "CHF"),
+    (".DE", "EUR"),
+    (".F", "EUR"),
+    (".PA", "EUR"),
+    (".AS", "EUR"),
+    (".MI", "EUR"),
+    (".MC", "EUR"),
+];
+
+/// Days without a new publication before stored rates count as stale; covers
+/// a weekend plus a holiday
+const STALE_AFTER_DAYS: i64 = 4;
+
+/// Look back this far for a rate when a range starts on a non-publication day
+const LOOKBACK_DAYS: i64 = 7;
+
+/// Upper-cased ISO code, if it has reference rates
+pub fn normalize_currency(code: &str) -> Result<String, String> {
+    let code = code.trim().to_uppercase();
+    if SUPPORTED_CURRENCIES.contains(&code.as_str()) {
+        Ok(code)
+    } else {
+        Err(format!("Unsupported currency '{}'", code))
+    }
+}
+
+/// Currency a ticker is quoted in, from its exchange suffix; USD otherwise
+pub fn currency_for_ticker(ticker: &str) -> &'static str {
+    let ticker = ticker.to_uppercase();
+    SUFFIX_CURRENCIES
+        .iter()
+        .find(|(suffix, _)| ticker.ends_with(suffix))
+        .map(|(_, currency)| *currency)
+        .unwrap_or("USD")
+}
+
+/// Daily rates as units of each currency per US dollar
+#[derive(Debug, Clone, Default)]
+pub struct FxRates {
+    by_currency: HashMap<String, BTreeMap<NaiveDate, f64>>,
+}
+
+impl FxRates {
+    pub fn insert(&mut self, date: NaiveDate, currency: &str, units_per_usd: f64) {
+        self.by_currency
+            .entry(currency.to_uppercase())
+            .or_default()
+            .insert(date, units_per_usd);
+    }
+
+    /// Rate published on `date`, or the last one before it (weekends, holidays).
+    /// Dates before the first stored rate use the first one.
+    fn units_per_usd(&self, currency: &str, date: NaiveDate) -> Option<f64> {
+        if currency.eq_ignore_ascii_case("USD") {
+            return Some(1.0);
+        }
+        let series = self.by_currency.get(&currency.to_uppercase())?;
+        series
+            .range(..=date)
+            .next_back()
+            .or_else(|| series.iter().next())
+            .map(|(_, rate)| *rate)
+    }
+
+    fn latest_date(&self, currency: &str) -> Option<NaiveDate> {
+        self.by_currency.get(currency)?.keys().next_back().copied()
+    }
+
+    /// `amount` in `from` converted into `to` at the rates of `date`
+    pub fn convert(&self, amount: f64, from: &str, to: &str, date: NaiveDate) -> Option<f64> {
+        if from.eq_ignore_ascii_case(to) {
+            return Some(amount);
+        }
+        let from_rate = self.units_per_usd(from, date)?;
+        let to_rate = self.units_per_usd(to, date)?;
+        Some(amount / from_rate * to_rate)
+    }
+}
+
+/// Fetch and store rates published between `start` and `end`; returns the
+/// number of publication days stored
+pub async fn refresh_rates(
+    pool: &PgPool,
+    provider: &dyn FxProvider,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<usize, AppError> {
+    let days = provider
+        .fetch_rates(start, end)
+        .await
+        .map_err(|e| AppError::External(format!("Failed to fetch exchange rates: {}", e)))?;
+    for rates in &days {
+        fx_queries::upsert_rates(pool, rates).await?;
+    }
+    Ok(days.len())
+}
+
+/// Rates of `currencies` between `start` and `end`. Stored rates are used when
+/// they cover the range; otherwise the missing days are fetched and stored
+/// first. Fails when a currency has no rate at all.
+pub async fn load_rates(
+    pool: &PgPool,
+    provider: &dyn FxProvider,
+    currencies: &[String],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<FxRates, AppError> {
+    let needed: Vec<&String> = currencies.iter().filter(|c| !c.eq_ignore_ascii_case("USD")).collect();
+    if needed.is_empty() {
+        return Ok(FxRates::default());
+    }
+
+    let mut rates = stored_rates(pool, start, end).await?;
+    let refresh_from = needed
+        .iter()
+        .filter_map(|c| match rates.latest_date(c) {
+            Some(latest) if (end - latest).num_days() < STALE_AFTER_DAYS => None,
+            Some(latest) => Some(latest + Duration::days(1)),
+            None => Some(start - Duration::days(LOOKBACK_DAYS)),
+        })
+        .min();
+
+    if let Some(from) = refresh_from {
+        match refresh_rates(pool, provider, from, end).await {
+            Ok(days) => {
+                info!("Stored {} days of exchange rates from {} to {}", days, from, end);
+                rates = stored_rates(pool, start, end).await?;
+            }
+            Err(e) => warn!("{}; using stored rates", e),
+        }
+    }
+
+    if let Some(missing) = needed.iter().find(|c| rates.latest_date(c).is_none()) {
+        return Err(AppError::External(format!("No exchange rate available for {}", missing)));
+    }
+    Ok(rates)
+}
+
+async fn stored_rates(pool: &PgPool, start: NaiveDate, end: NaiveDate) -> Result<FxRates, sqlx::Error> {
+    let mut rates = FxRates::default();
+    for (date, currency, units_per_usd) in fx_queries::fetch_rates_between(pool, start, end).await? {
+        rates.insert(date, &currency, units_per_usd);
+    }
+    Ok(rates)
+}
+
+/// Converts a portfolio's market values into its owner's base currency at
+/// today's rates
+#[derive(Debug, Clone)]
+pub struct PortfolioFx {
+    pub base_currency: String,
+    rates: FxRates,
+    as_of: NaiveDate,
+}
+
+impl PortfolioFx {
+    /// Loads the rates needed for `holdings` of the portfolio
+    pub async fn load(
+        pool: &PgPool,
+        provider: &dyn FxProvider,
+        portfolio_id: Uuid,
+        holdings: &[LatestAccountHolding],
+    ) -> Result<Self, AppError> {
+        let base_currency = fx_queries::fetch_portfolio_base_currency(pool, portfolio_id).await?;
+        let mut currencies: Vec<String> = holdings.iter().map(|h| h.currency.clone()).collect();
+        currencies.push(base_currency.clone());
+        currencies.sort();
+        currencies.dedup();
+
+        let as_of = clock::today();
+        let rates = if currencies.len() == 1 {
+            FxRates::default()
+        } else {
+            load_rates(pool, provider, &currencies, as_of - Duration::days(LOOKBACK_DAYS), as_of).await?
+        };
+
+        Ok(Self { base_currency, rates, as_of })
+    }
+
+    /// `amount` in `currency` converted into the base currency
+    pub fn convert(&self, amount: f64, currency: &str) -> f64 {
+        // `load` made sure every holding currency has a rate
+        self.rates
+            .convert(amount, currency, &self.base_currency, self.as_of)
+            .unwrap_or(amount)
+    }
+
+    /// Market value of a holding in the base currency
+    pub fn market_value(&self, holding: &LatestAccountHolding) -> f64 {
+        self.convert(holding.market_value.to_f64().unwrap_or(0.0), &holding.currency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_convert_between_non_usd_currencies() {
+        let mut rates = FxRates::default();
+        rates.insert(date("2026-03-05"), "CAD", 1.40);
+        rates.insert(date("2026-03-05"), "EUR", 0.90);
+        rates.insert(date("2026-03-06"), "CAD", 1.50);
+
+        let usd = rates.convert(140.0, "CAD", "USD", date("2026-03-05")).unwrap();
+        assert!((usd - 100.0).abs() < 1e-9);
+        let eur = rates.convert(140.0, "cad", "EUR", date("2026-03-05")).unwrap();
+        assert!((eur - 90.0).abs() < 1e-9);
+        // Saturday uses Friday's rate
+        let cad = rates.convert(100.0, "USD", "CAD", date("2026-03-07")).unwrap();
+        assert!((cad - 150.0).abs() < 1e-9);
+        // Before the first publication, the first rate applies
+        let cad = rates.convert(100.0, "USD", "CAD", date("2026-01-02")).unwrap();
+        assert!((cad - 140.0).abs() < 1e-9);
+        assert_eq!(rates.convert(1.0, "USD", "JPY", date("2026-03-05")), None);
+        assert_eq!(rates.convert(5.0, "JPY", "JPY", date("2026-03-05")), Some(5.0));
+    }
+
+    #[test]
+    fn test_currency_for_ticker_by_exchange_suffix() {
+        assert_eq!(currency_for_ticker("RY.TO"), "CAD");
+        assert_eq!(currency_for_ticker("ABC.v"), "CAD");
+        assert_eq!(currency_for_ticker("BHP.AX"), "AUD");
+        assert_eq!(currency_for_ticker("AAPL"), "USD");
+        assert_eq!(currency_for_ticker("BTC-USD"), "USD");
+        // Not a Tokyo listing just because it ends in T
+        assert_eq!(currency_for_ticker("T"), "USD");
+        assert_eq!(normalize_currency(" cad ").as_deref(), Ok("CAD"));
+        assert!(normalize_currency("GBX").is_err());
+    }
+}
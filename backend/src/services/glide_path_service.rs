@@ -0,0 +1,276 @@
+//! Target-date glide paths.
+//!
+//! A glide path holds the equity share at `start_equity_pct` until the goal is
+//! `glide_years` away, then lowers it linearly to `end_equity_pct` at the goal date
+//! and keeps it there. Holdings are split into equity, fixed income and cash from
+//! their asset category and name; fixed income and cash together make up the
+//! defensive share. A saved path gives the constrained rebalancer its equity target.
+
+use bigdecimal::ToPrimitive;
+use chrono::{Months, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{glide_path_queries, holding_snapshot_queries};
+use crate::errors::AppError;
+use crate::models::{GlidePath, GlidePathPoint, GlidePathRequest, GlidePathSettings, LatestAccountHolding};
+use crate::services::clock;
+
+pub const DEFAULT_START_EQUITY_PCT: f64 = 90.0;
+pub const DEFAULT_END_EQUITY_PCT: f64 = 30.0;
+pub const DEFAULT_GLIDE_YEARS: f64 = 25.0;
+const MAX_GLIDE_YEARS: f64 = 60.0;
+const MAX_HORIZON_YEARS: u32 = 100;
+const DAYS_PER_YEAR: f64 = 365.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetClass {
+    Equity,
+    FixedIncome,
+    Cash,
+}
+
+fn has_word(text: &str, word: &str) -> bool {
+    text.split(|c: char| !c.is_ascii_alphanumeric()).any(|w| w == word)
+}
+
+/// Classify a holding from its asset category and name; anything that is not
+/// recognizably fixed income or cash counts as equity
+pub fn asset_class(asset_category: Option<&str>, holding_name: Option<&str>) -> AssetClass {
+    let category = asset_category.unwrap_or("").to_uppercase();
+    let name = holding_name.unwrap_or("").to_uppercase();
+
+    if category.contains("CASH") || name.contains("MONEY MARKET") || has_word(&name, "SAVINGS") {
+        AssetClass::Cash
+    } else if category == "FIXED INCOME"
+        || ["BOND", "BONDS", "TREASURY", "GIC"].iter().any(|w| has_word(&name, w))
+    {
+        AssetClass::FixedIncome
+    } else {
+        AssetClass::Equity
+    }
+}
+
+pub fn is_equity(holding: &LatestAccountHolding) -> bool {
+    asset_class(holding.asset_category.as_deref(), holding.holding_name.as_deref()) == AssetClass::Equity
+}
+
+/// Target equity share on `date`, in percent
+pub fn equity_share(settings: &GlidePathSettings, date: NaiveDate) -> f64 {
+    let years_to_goal = (settings.goal_date - date).num_days() as f64 / DAYS_PER_YEAR;
+    let progress = (years_to_goal / settings.glide_years).clamp(0.0, 1.0);
+    settings.end_equity_pct + (settings.start_equity_pct - settings.end_equity_pct) * progress
+}
+
+/// Yearly points from `today` through the goal date
+pub fn path_points(settings: &GlidePathSettings, today: NaiveDate) -> Vec<GlidePathPoint> {
+    let point = |date: NaiveDate| {
+        let equity_pct = equity_share(settings, date);
+        GlidePathPoint {
+            date,
+            years_to_goal: ((settings.goal_date - date).num_days() as f64 / DAYS_PER_YEAR).max(0.0),
+            equity_pct,
+            defensive_pct: 100.0 - equity_pct,
+        }
+    };
+
+    let mut points = Vec::new();
+    for year in 0..MAX_HORIZON_YEARS {
+        match today.checked_add_months(Months::new(12 * year)) {
+            Some(date) if date < settings.goal_date => points.push(point(date)),
+            _ => break,
+        }
+    }
+    points.push(point(settings.goal_date.max(today)));
+    points
+}
+
+/// Fill unset request fields from the saved path, then the defaults, and validate
+pub fn resolve(
+    request: &GlidePathRequest,
+    saved: Option<&GlidePathSettings>,
+    today: NaiveDate,
+) -> Result<GlidePathSettings, AppError> {
+    let goal_date = match (request.goal_date, request.current_age, request.retirement_age) {
+        (Some(date), _, _) => date,
+        (None, Some(age), Some(retirement)) => {
+            if retirement <= age {
+                return Err(AppError::Validation("retirement_age must be greater than current_age".to_string()));
+            }
+            today
+                .checked_add_months(Months::new(12 * (retirement - age)))
+                .ok_or_else(|| AppError::Validation("retirement_age is too far away".to_string()))?
+        }
+        (None, Some(_), None) | (None, None, Some(_)) => {
+            return Err(AppError::Validation(
+                "current_age and retirement_age must be given together".to_string(),
+            ));
+        }
+        (None, None, None) => saved.map(|s| s.goal_date).ok_or_else(|| {
+            AppError::Validation("goal_date, or current_age and retirement_age, is required".to_string())
+        })?,
+    };
+
+    let settings = GlidePathSettings {
+        goal_date,
+        start_equity_pct: request
+            .start_equity_pct
+            .or(saved.map(|s| s.start_equity_pct))
+            .unwrap_or(DEFAULT_START_EQUITY_PCT),
+        end_equity_pct: request
+            .end_equity_pct
+            .or(saved.map(|s| s.end_equity_pct))
+            .unwrap_or(DEFAULT_END_EQUITY_PCT),
+        glide_years: request.glide_years.or(saved.map(|s| s.glide_years)).unwrap_or(DEFAULT_GLIDE_YEARS),
+    };
+
+    for (name, value) in [("start_equity_pct", settings.start_equity_pct), ("end_equity_pct", settings.end_equity_pct)] {
+        if !(0.0..=100.0).contains(&value) {
+            return Err(AppError::Validation(format!("{} must be between 0 and 100", name)));
+        }
+    }
+    if settings.end_equity_pct > settings.start_equity_pct {
+        return Err(AppError::Validation("end_equity_pct must not exceed start_equity_pct".to_string()));
+    }
+    if !(settings.glide_years > 0.0 && settings.glide_years <= MAX_GLIDE_YEARS) {
+        return Err(AppError::Validation(format!("glide_years must be above 0 and at most {}", MAX_GLIDE_YEARS)));
+    }
+    Ok(settings)
+}
+
+/// Compare the path with the current holdings
+pub fn compare(
+    portfolio_id: Uuid,
+    settings: GlidePathSettings,
+    saved: bool,
+    holdings: &[LatestAccountHolding],
+    today: NaiveDate,
+) -> GlidePath {
+    let (mut equity, mut fixed_income, mut cash) = (0.0, 0.0, 0.0);
+    for holding in holdings {
+        let value = holding.market_value.to_f64().unwrap_or(0.0);
+        match asset_class(holding.asset_category.as_deref(), holding.holding_name.as_deref()) {
+            AssetClass::Equity => equity += value,
+            AssetClass::FixedIncome => fixed_income += value,
+            AssetClass::Cash => cash += value,
+        }
+    }
+    let total_value = equity + fixed_income + cash;
+    let pct = |v: f64| if total_value > 0.0 { v / total_value * 100.0 } else { 0.0 };
+
+    let target_equity_pct = equity_share(&settings, today);
+    let current_equity_pct = pct(equity);
+    GlidePath {
+        portfolio_id,
+        points: path_points(&settings, today),
+        settings,
+        saved,
+        as_of: today,
+        total_value,
+        target_equity_pct,
+        current_equity_pct,
+        current_fixed_income_pct: pct(fixed_income),
+        current_cash_pct: pct(cash),
+        equity_drift: if total_value > 0.0 { current_equity_pct - target_equity_pct } else { 0.0 },
+        equity_trade_value: if total_value > 0.0 { equity - total_value * target_equity_pct / 100.0 } else { 0.0 },
+    }
+}
+
+/// Preview a glide path; unset parameters fall back to the saved path
+pub async fn glide_path(pool: &PgPool, portfolio_id: Uuid, request: &GlidePathRequest) -> Result<GlidePath, AppError> {
+    let saved = glide_path_queries::fetch(pool, portfolio_id).await?;
+    let today = clock::today();
+    let settings = resolve(request, saved.as_ref(), today)?;
+    let is_saved = saved.as_ref().is_some_and(|s| {
+        s.goal_date == settings.goal_date
+            && s.start_equity_pct == settings.start_equity_pct
+            && s.end_equity_pct == settings.end_equity_pct
+            && s.glide_years == settings.glide_years
+    });
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    Ok(compare(portfolio_id, settings, is_saved, &holdings, today))
+}
+
+/// Save the glide path the rebalancer should follow
+pub async fn save(pool: &PgPool, portfolio_id: Uuid, request: &GlidePathRequest) -> Result<GlidePath, AppError> {
+    let saved = glide_path_queries::fetch(pool, portfolio_id).await?;
+    let today = clock::today();
+    let settings = resolve(request, saved.as_ref(), today)?;
+    let settings = glide_path_queries::upsert(pool, portfolio_id, &settings).await?;
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    Ok(compare(portfolio_id, settings, true, &holdings, today))
+}
+
+pub async fn delete(pool: &PgPool, portfolio_id: Uuid) -> Result<(), AppError> {
+    if !glide_path_queries::delete(pool, portfolio_id).await? {
+        return Err(AppError::NotFound(format!("No glide path set for portfolio {}", portfolio_id)));
+    }
+    Ok(())
+}
+
+/// Today's equity target from the saved glide path (0-1), if one is set
+pub async fn equity_target(pool: &PgPool, portfolio_id: Uuid) -> Result<Option<f64>, AppError> {
+    let saved = glide_path_queries::fetch(pool, portfolio_id).await?;
+    Ok(saved.map(|s| equity_share(&s, clock::today()) / 100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(y: i32, m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, day).unwrap()
+    }
+
+    fn settings(goal: NaiveDate) -> GlidePathSettings {
+        GlidePathSettings {
+            goal_date: goal,
+            start_equity_pct: 90.0,
+            end_equity_pct: 30.0,
+            glide_years: 20.0,
+        }
+    }
+
+    #[test]
+    fn test_equity_share_declines_toward_goal() {
+        let s = settings(d(2050, 1, 1));
+        assert_eq!(equity_share(&s, d(2020, 1, 1)), 90.0);
+        assert!((equity_share(&s, d(2040, 1, 1)) - 60.0).abs() < 0.1);
+        assert_eq!(equity_share(&s, d(2050, 1, 1)), 30.0);
+        assert_eq!(equity_share(&s, d(2060, 1, 1)), 30.0);
+
+        let points = path_points(&s, d(2045, 1, 1));
+        assert_eq!(points.len(), 6);
+        assert_eq!(points.last().unwrap().date, d(2050, 1, 1));
+        assert!(points.windows(2).all(|w| w[1].equity_pct <= w[0].equity_pct));
+    }
+
+    #[test]
+    fn test_resolve_from_ages_and_saved_path() {
+        let today = d(2025, 6, 30);
+        let request = GlidePathRequest { current_age: Some(40), retirement_age: Some(65), ..Default::default() };
+        let resolved = resolve(&request, None, today).unwrap();
+        assert_eq!(resolved.goal_date, d(2050, 6, 30));
+        assert_eq!(resolved.start_equity_pct, DEFAULT_START_EQUITY_PCT);
+
+        let saved = settings(d(2040, 1, 1));
+        let request = GlidePathRequest { end_equity_pct: Some(40.0), ..Default::default() };
+        let resolved = resolve(&request, Some(&saved), today).unwrap();
+        assert_eq!(resolved.goal_date, d(2040, 1, 1));
+        assert_eq!(resolved.end_equity_pct, 40.0);
+        assert_eq!(resolved.glide_years, 20.0);
+
+        assert!(resolve(&GlidePathRequest::default(), None, today).is_err());
+        let rising = GlidePathRequest { goal_date: Some(d(2040, 1, 1)), end_equity_pct: Some(95.0), ..Default::default() };
+        assert!(resolve(&rising, None, today).is_err());
+    }
+
+    #[test]
+    fn test_asset_class() {
+        assert_eq!(asset_class(Some("EQUITIES"), Some("Shopify")), AssetClass::Equity);
+        assert_eq!(asset_class(Some("EQUITIES"), Some("iShares Core Canadian Bond")), AssetClass::FixedIncome);
+        assert_eq!(asset_class(Some("FIXED INCOME"), Some("Aggregate")), AssetClass::FixedIncome);
+        assert_eq!(asset_class(Some("CASH AND CASH EQUIVALENTS"), None), AssetClass::Cash);
+        assert_eq!(asset_class(None, Some("CI High Interest Savings")), AssetClass::Cash);
+    }
+}
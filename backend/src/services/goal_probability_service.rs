@@ -0,0 +1,283 @@
+//! Monte Carlo success probabilities for financial planning goals.
+//!
+//! Each goal's savings are simulated month by month to its target date under
+//! lognormal market returns sized by the survey's risk tolerance, with planned
+//! contributions added along the way. The share of paths that reach the target
+//! is the goal's success probability. Results are stored per goal per day so
+//! the trend can be charted as markets move and contributions arrive.
+
+use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
+use chrono::{Datelike, NaiveDate};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sqlx::PgPool;
+use std::f64::consts::PI;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::financial_planning_queries;
+use crate::models::financial_planning::*;
+use crate::models::long_term_guidance::RiskTolerance;
+use crate::services::financial_snapshot_service;
+
+/// Simulated paths per goal
+pub const SIMULATIONS: usize = 2000;
+
+/// Annual expected return and volatility for each risk tolerance. Moderate
+/// matches the fixed rate the financial snapshot projects with.
+fn return_assumptions(tolerance: Option<&RiskTolerance>) -> (f64, f64) {
+    match tolerance {
+        Some(RiskTolerance::Conservative) => (0.04, 0.06),
+        Some(RiskTolerance::Aggressive) => (0.075, 0.15),
+        Some(RiskTolerance::Moderate) | None => (0.06, 0.10),
+    }
+}
+
+/// Inputs for simulating one goal
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalSimulation {
+    pub target_amount: f64,
+    pub starting_balance: f64,
+    pub annual_contribution: f64,
+    pub horizon_months: u32,
+    pub expected_return: f64,
+    pub volatility: f64,
+}
+
+/// Distribution of simulated balances at the goal's horizon
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalOutcome {
+    pub success_probability: f64,
+    pub median: f64,
+    pub p10: f64,
+    pub p90: f64,
+}
+
+/// Run `paths` monthly simulations of a goal's balance.
+///
+/// Yearly log returns are normal with mean `ln(1 + r) - σ²/2`, so the
+/// arithmetic mean return is `expected_return`; contributions are added in
+/// equal monthly amounts after each month's return.
+pub fn simulate_goal(sim: &GoalSimulation, paths: usize, rng: &mut StdRng) -> GoalOutcome {
+    let drift = ((1.0 + sim.expected_return).ln() - sim.volatility.powi(2) / 2.0) / 12.0;
+    let shock = sim.volatility / 12f64.sqrt();
+    let monthly_contribution = sim.annual_contribution / 12.0;
+
+    let mut outcomes: Vec<f64> = (0..paths.max(1))
+        .map(|_| {
+            (0..sim.horizon_months).fold(sim.starting_balance, |balance, _| {
+                balance * (drift + shock * standard_normal(rng)).exp() + monthly_contribution
+            })
+        })
+        .collect();
+    outcomes.sort_by(|a, b| a.total_cmp(b));
+
+    let successes = outcomes.iter().filter(|&&b| b >= sim.target_amount).count();
+    let quantile = |q: f64| outcomes[((outcomes.len() - 1) as f64 * q).round() as usize];
+
+    GoalOutcome {
+        success_probability: successes as f64 / outcomes.len() as f64,
+        median: quantile(0.5),
+        p10: quantile(0.1),
+        p90: quantile(0.9),
+    }
+}
+
+/// Box-Muller draw from N(0, 1)
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1: f64 = rng.random::<f64>().max(f64::MIN_POSITIVE);
+    let u2: f64 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+/// Months until a goal is due: its target date, or for a retirement goal
+/// without one, the planned retirement age. `None` when neither is known.
+fn horizon_months(
+    goal: &SurveyGoal,
+    personal_info: Option<&SurveyPersonalInfo>,
+    income_info: Option<&SurveyIncomeInfo>,
+    today: NaiveDate,
+) -> Option<i64> {
+    if let Some(target_date) = goal.target_date {
+        let days = (target_date - today).num_days();
+        return Some((days as f64 / 30.44).ceil() as i64);
+    }
+    if goal.goal_type != "retirement" {
+        return None;
+    }
+    let current_age = today.year() - personal_info?.birth_year?;
+    let retirement_age = income_info?.planned_retirement_age.unwrap_or(65);
+    Some(((retirement_age - current_age) * 12) as i64)
+}
+
+/// Simulation inputs for a goal, or `None` if it has no target or is already due.
+///
+/// Retirement goals start from the same savings the financial snapshot uses
+/// and receive payroll contributions; other goals start from their recorded
+/// savings without planned contributions, so money added to them shows up as
+/// a higher starting balance on the next run.
+pub fn goal_simulation(
+    goal: &SurveyGoal,
+    goals: &[SurveyGoal],
+    assets: &[SurveyAsset],
+    personal_info: Option<&SurveyPersonalInfo>,
+    income_info: Option<&SurveyIncomeInfo>,
+    risk_profile: Option<&SurveyRiskProfile>,
+    today: NaiveDate,
+) -> Option<GoalSimulation> {
+    let target_amount = goal.target_amount.as_ref()?.to_f64().filter(|&t| t > 0.0)?;
+    let horizon_months = horizon_months(goal, personal_info, income_info, today).filter(|&m| m > 0)?;
+
+    let (starting_balance, annual_contribution) = if goal.goal_type == "retirement" {
+        (
+            financial_snapshot_service::current_retirement_savings(goals, assets),
+            income_info.map(financial_snapshot_service::annual_retirement_contribution).unwrap_or(0.0),
+        )
+    } else {
+        (goal.current_savings.as_ref().and_then(|v| v.to_f64()).unwrap_or(0.0), 0.0)
+    };
+
+    let tolerance = risk_profile
+        .and_then(|r| r.risk_tolerance.as_deref())
+        .and_then(RiskTolerance::from_str_opt);
+    let (expected_return, volatility) = return_assumptions(tolerance.as_ref());
+
+    Some(GoalSimulation {
+        target_amount,
+        starting_balance,
+        annual_contribution,
+        horizon_months: horizon_months as u32,
+        expected_return,
+        volatility,
+    })
+}
+
+/// Result of a recalculation run
+#[derive(Debug, Default)]
+pub struct GoalProbabilityRefresh {
+    pub goals: usize,
+    pub failed_surveys: usize,
+}
+
+/// Recalculate and store the success probability of every goal with a target.
+///
+/// Survey assets linked to portfolio accounts are refreshed first so starting
+/// balances follow the market. Paths are seeded from the goal and the date, so
+/// re-running a day reproduces its result.
+pub async fn recalculate_goal_probabilities(
+    pool: &PgPool,
+    calculated_on: NaiveDate,
+) -> Result<GoalProbabilityRefresh, sqlx::Error> {
+    let mut refresh = GoalProbabilityRefresh::default();
+
+    for survey_id in financial_planning_queries::get_surveys_with_targeted_goals(pool).await? {
+        match recalculate_survey(pool, survey_id, calculated_on).await {
+            Ok(goals) => refresh.goals += goals,
+            Err(e) => {
+                warn!("Failed to recalculate goal probabilities for survey {}: {}", survey_id, e);
+                refresh.failed_surveys += 1;
+            }
+        }
+    }
+
+    Ok(refresh)
+}
+
+async fn recalculate_survey(
+    pool: &PgPool,
+    survey_id: Uuid,
+    calculated_on: NaiveDate,
+) -> Result<usize, sqlx::Error> {
+    let mut assets = financial_planning_queries::get_assets(pool, survey_id).await?;
+    for asset in assets.iter_mut().filter(|a| a.linked_account_id.is_some()) {
+        match financial_planning_queries::refresh_asset_value(pool, asset.id).await {
+            Ok(refreshed) => *asset = refreshed,
+            Err(e) => warn!("Could not refresh linked asset {}: {}", asset.id, e),
+        }
+    }
+
+    let goals = financial_planning_queries::get_goals(pool, survey_id).await?;
+    let personal_info = financial_planning_queries::get_personal_info(pool, survey_id).await?;
+    let income_info = financial_planning_queries::get_income_info(pool, survey_id).await?;
+    let risk_profile = financial_planning_queries::get_risk_profile(pool, survey_id).await?;
+
+    let mut recorded = 0;
+    for goal in &goals {
+        let Some(sim) = goal_simulation(
+            goal,
+            &goals,
+            &assets,
+            personal_info.as_ref(),
+            income_info.as_ref(),
+            risk_profile.as_ref(),
+            calculated_on,
+        ) else {
+            continue;
+        };
+
+        let (hi, lo) = goal.id.as_u64_pair();
+        let mut rng = StdRng::seed_from_u64(hi ^ lo ^ calculated_on.num_days_from_ce() as u64);
+        let outcome = simulate_goal(&sim, SIMULATIONS, &mut rng);
+
+        let decimal = |v: f64| BigDecimal::from_f64(v).unwrap_or_default();
+        let record = GoalProbabilityRecord {
+            goal_id: goal.id,
+            survey_id,
+            calculated_on,
+            success_probability: decimal(outcome.success_probability),
+            median_outcome: decimal(outcome.median),
+            p10_outcome: decimal(outcome.p10),
+            p90_outcome: decimal(outcome.p90),
+            target_amount: decimal(sim.target_amount),
+            starting_balance: decimal(sim.starting_balance),
+            annual_contribution: decimal(sim.annual_contribution),
+            horizon_months: sim.horizon_months as i32,
+            expected_return: decimal(sim.expected_return),
+            volatility: decimal(sim.volatility),
+            simulations: SIMULATIONS as i32,
+            created_at: chrono::Utc::now(),
+        };
+        financial_planning_queries::upsert_goal_probability(pool, &record).await?;
+        recorded += 1;
+    }
+
+    Ok(recorded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sim(starting_balance: f64, annual_contribution: f64, volatility: f64) -> GoalSimulation {
+        GoalSimulation {
+            target_amount: 100_000.0,
+            starting_balance,
+            annual_contribution,
+            horizon_months: 120,
+            expected_return: 0.06,
+            volatility,
+        }
+    }
+
+    #[test]
+    fn test_simulate_goal_without_volatility_is_deterministic() {
+        let mut rng = StdRng::seed_from_u64(7);
+        // 50k at 6% for 10 years grows to ~89.5k: short of the target
+        let short = simulate_goal(&sim(50_000.0, 0.0, 0.0), 100, &mut rng);
+        assert_eq!(short.success_probability, 0.0);
+        assert!((short.median - 50_000.0 * 1.06f64.powi(10)).abs() < 1.0);
+        assert_eq!(short.p10, short.p90);
+
+        let reached = simulate_goal(&sim(50_000.0, 2_000.0, 0.0), 100, &mut rng);
+        assert_eq!(reached.success_probability, 1.0);
+    }
+
+    #[test]
+    fn test_simulate_goal_probability_rises_with_contributions() {
+        let low = simulate_goal(&sim(50_000.0, 500.0, 0.15), SIMULATIONS, &mut StdRng::seed_from_u64(1));
+        let high = simulate_goal(&sim(50_000.0, 3_000.0, 0.15), SIMULATIONS, &mut StdRng::seed_from_u64(1));
+        assert!(low.success_probability > 0.0 && low.success_probability < 1.0);
+        assert!(high.success_probability > low.success_probability);
+        assert!(low.p10 < low.median && low.median < low.p90);
+    }
+}
@@ -0,0 +1,658 @@
+//! Portfolio health check: a battery of quick checks scored into a checklist.
+//!
+//! Each check reads data that other endpoints already compute or cache and links
+//! to that endpoint for the details. A check whose inputs are unavailable (no
+//! cached correlations, no saved constraints, ...) or whose query fails is
+//! reported as skipped rather than failing the whole request.
+
+use bigdecimal::ToPrimitive;
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::{
+    holding_snapshot_queries, optimization_constraint_queries, price_queries, risk_snapshot_queries,
+    risk_threshold_queries,
+};
+use crate::errors::AppError;
+use crate::models::risk::{CorrelationStatistics, RiskThresholdSettings};
+use crate::models::{
+    FeeAnalysis, HealthCheckItem, HealthCheckStatus, OptimizationConstraints, PortfolioHealthCheck, RiskSnapshot,
+};
+use crate::services::fee_service;
+use crate::services::clock;
+
+const CONCENTRATION_WARN_WEIGHT: f64 = 15.0;
+const CONCENTRATION_FAIL_WEIGHT: f64 = 25.0;
+const CORRELATION_WARN_AVERAGE: f64 = 0.5;
+const CORRELATION_FAIL_AVERAGE: f64 = 0.7;
+/// Weighted expense ratios in percent
+const FEE_WARN_RATIO: f64 = 0.4;
+const FEE_FAIL_RATIO: f64 = 1.0;
+const HOLDINGS_WARN_AGE_DAYS: i64 = 45;
+const HOLDINGS_FAIL_AGE_DAYS: i64 = 90;
+const PRICE_STALE_DAYS: i64 = 7;
+/// Roughly a quarter of trading days; risk metrics need at least this much history
+const MIN_PRICE_POINTS: i64 = 60;
+const HISTORY_LOOKBACK_DAYS: i64 = 365;
+/// Share of portfolio value without usable history above which the check fails
+const MISSING_HISTORY_FAIL_WEIGHT: f64 = 20.0;
+/// Constraint breaches larger than this many percentage points fail the drift check
+const DRIFT_FAIL_POINTS: f64 = 5.0;
+
+/// A ticker's aggregated value across accounts
+#[derive(Debug, Clone)]
+pub struct HealthPosition {
+    pub ticker: String,
+    pub industry: Option<String>,
+    pub market_value: f64,
+}
+
+fn weights(positions: &[HealthPosition]) -> Vec<(&HealthPosition, f64)> {
+    let total: f64 = positions.iter().map(|p| p.market_value).sum();
+    positions
+        .iter()
+        .map(|p| (p, if total > 0.0 { p.market_value / total * 100.0 } else { 0.0 }))
+        .collect()
+}
+
+fn item(
+    check: &str,
+    title: &str,
+    status: HealthCheckStatus,
+    summary: String,
+    details: Vec<String>,
+    link: String,
+) -> HealthCheckItem {
+    HealthCheckItem {
+        check: check.to_string(),
+        title: title.to_string(),
+        status,
+        summary,
+        details,
+        link,
+    }
+}
+
+fn skipped(check: &str, title: &str, summary: &str, link: String) -> HealthCheckItem {
+    item(check, title, HealthCheckStatus::Skipped, summary.to_string(), Vec::new(), link)
+}
+
+/// Largest single-position weight and the effective number of holdings (1 / HHI)
+pub fn check_concentration(portfolio_id: Uuid, positions: &[HealthPosition]) -> HealthCheckItem {
+    let link = format!("/api/portfolios/{}/latest-holdings", portfolio_id);
+    let securities: Vec<HealthPosition> = positions.iter().filter(|p| !p.ticker.is_empty()).cloned().collect();
+    if securities.is_empty() {
+        return skipped("concentration", "Concentration", "No holdings to analyze", link);
+    }
+
+    let weighted = weights(&securities);
+    let hhi: f64 = weighted.iter().map(|(_, w)| (w / 100.0).powi(2)).sum();
+    let effective_holdings = if hhi > 0.0 { 1.0 / hhi } else { 0.0 };
+    let (largest, largest_weight) = weighted
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(p, w)| (p.ticker.clone(), *w))
+        .unwrap_or_default();
+
+    let details = weighted
+        .iter()
+        .filter(|(_, w)| *w > CONCENTRATION_WARN_WEIGHT)
+        .map(|(p, w)| format!("{} is {:.1}% of the portfolio", p.ticker, w))
+        .collect();
+    let status = if largest_weight > CONCENTRATION_FAIL_WEIGHT {
+        HealthCheckStatus::Fail
+    } else if largest_weight > CONCENTRATION_WARN_WEIGHT {
+        HealthCheckStatus::Warn
+    } else {
+        HealthCheckStatus::Pass
+    };
+    let summary = format!(
+        "Largest position {} at {:.1}%; {:.1} effective holdings",
+        largest, largest_weight, effective_holdings
+    );
+    item("concentration", "Concentration", status, summary, details, link)
+}
+
+/// Average pairwise correlation from the cached correlation matrix
+pub fn check_correlation(portfolio_id: Uuid, statistics: Option<&CorrelationStatistics>) -> HealthCheckItem {
+    let link = format!("/api/risk/portfolios/{}/correlations", portfolio_id);
+    let Some(stats) = statistics else {
+        return skipped("correlation", "Correlation", "No fresh correlation data; open the correlation view to compute it", link);
+    };
+
+    let mut details = Vec::new();
+    if stats.high_correlation_pairs > 0 {
+        details.push(format!("{} position pairs are correlated above 0.7", stats.high_correlation_pairs));
+    }
+    let status = if stats.average_correlation >= CORRELATION_FAIL_AVERAGE {
+        HealthCheckStatus::Fail
+    } else if stats.average_correlation >= CORRELATION_WARN_AVERAGE || stats.high_correlation_pairs > 0 {
+        HealthCheckStatus::Warn
+    } else {
+        HealthCheckStatus::Pass
+    };
+    let summary = format!(
+        "Average correlation {:.2}; diversification score {:.1}/10",
+        stats.average_correlation, stats.adjusted_diversification_score
+    );
+    item("correlation", "Correlation", status, summary, details, link)
+}
+
+/// Weighted expense ratio and available cheaper swaps
+pub fn check_fees(portfolio_id: Uuid, analysis: &FeeAnalysis) -> HealthCheckItem {
+    let link = format!("/api/portfolios/{}/fees", portfolio_id);
+    if analysis.total_value <= 0.0 {
+        return skipped("fee_drag", "Fee drag", "No holdings to analyze", link);
+    }
+
+    let details = analysis
+        .swaps
+        .iter()
+        .map(|s| {
+            format!(
+                "{} ({:.2}%) could be swapped for {} ({:.2}%), saving ${:.0}/yr",
+                s.ticker, s.expense_ratio, s.alternative_ticker, s.alternative_expense_ratio, s.annual_savings
+            )
+        })
+        .collect();
+    let status = if analysis.weighted_expense_ratio >= FEE_FAIL_RATIO {
+        HealthCheckStatus::Fail
+    } else if analysis.weighted_expense_ratio >= FEE_WARN_RATIO || !analysis.swaps.is_empty() {
+        HealthCheckStatus::Warn
+    } else {
+        HealthCheckStatus::Pass
+    };
+    let drag = analysis
+        .projections
+        .last()
+        .map(|p| format!("; ${:.0} fee drag over {} years", p.fee_drag, p.years))
+        .unwrap_or_default();
+    let summary = format!("Weighted expense ratio {:.2}%{}", analysis.weighted_expense_ratio, drag);
+    item("fee_drag", "Fee drag", status, summary, details, link)
+}
+
+/// Age of the latest holdings snapshot and of each ticker's latest price
+pub fn check_stale_data(
+    portfolio_id: Uuid,
+    today: NaiveDate,
+    snapshot_date: Option<NaiveDate>,
+    latest_prices: &HashMap<String, NaiveDate>,
+) -> HealthCheckItem {
+    let link = format!("/api/portfolios/{}/latest-holdings", portfolio_id);
+    let Some(snapshot_date) = snapshot_date else {
+        return skipped("stale_data", "Stale data", "No holdings snapshots imported", link);
+    };
+
+    let snapshot_age = (today - snapshot_date).num_days();
+    let mut stale: Vec<(&String, i64)> = latest_prices
+        .iter()
+        .map(|(ticker, date)| (ticker, (today - *date).num_days()))
+        .filter(|(_, age)| *age > PRICE_STALE_DAYS)
+        .collect();
+    stale.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    let mut details = Vec::new();
+    if snapshot_age > HOLDINGS_WARN_AGE_DAYS {
+        details.push(format!("Holdings were last imported {} days ago ({})", snapshot_age, snapshot_date));
+    }
+    details.extend(stale.iter().map(|(ticker, age)| format!("Latest {} price is {} days old", ticker, age)));
+
+    let status = if snapshot_age > HOLDINGS_FAIL_AGE_DAYS {
+        HealthCheckStatus::Fail
+    } else if snapshot_age > HOLDINGS_WARN_AGE_DAYS || !stale.is_empty() {
+        HealthCheckStatus::Warn
+    } else {
+        HealthCheckStatus::Pass
+    };
+    let summary = format!(
+        "Holdings as of {} ({} days ago); {} tickers with stale prices",
+        snapshot_date,
+        snapshot_age,
+        stale.len()
+    );
+    item("stale_data", "Stale data", status, summary, details, link)
+}
+
+/// Positions without enough price history for risk metrics
+pub fn check_price_history(
+    portfolio_id: Uuid,
+    positions: &[HealthPosition],
+    point_counts: &HashMap<String, i64>,
+) -> HealthCheckItem {
+    let link = format!("/api/risk/portfolios/{}", portfolio_id);
+    let securities: Vec<HealthPosition> = positions.iter().filter(|p| !p.ticker.is_empty()).cloned().collect();
+    if securities.is_empty() {
+        return skipped("price_history", "Price history", "No holdings to analyze", link);
+    }
+
+    let mut missing_weight = 0.0;
+    let mut details = Vec::new();
+    for (position, weight) in weights(&securities) {
+        let count = point_counts.get(&position.ticker).copied().unwrap_or(0);
+        if count < MIN_PRICE_POINTS {
+            missing_weight += weight;
+            details.push(if count == 0 {
+                format!("{} has no price history", position.ticker)
+            } else {
+                format!("{} has only {} price points in the last year", position.ticker, count)
+            });
+        }
+    }
+
+    let status = if missing_weight > MISSING_HISTORY_FAIL_WEIGHT {
+        HealthCheckStatus::Fail
+    } else if !details.is_empty() {
+        HealthCheckStatus::Warn
+    } else {
+        HealthCheckStatus::Pass
+    };
+    let summary = format!(
+        "{} of {} positions ({:.1}% of value) lack sufficient price history",
+        details.len(),
+        securities.len(),
+        missing_weight
+    );
+    item("price_history", "Price history", status, summary, details, link)
+}
+
+/// Latest position risk snapshots against the portfolio's risk thresholds
+pub fn check_threshold_violations(
+    portfolio_id: Uuid,
+    snapshots: &[RiskSnapshot],
+    thresholds: &RiskThresholdSettings,
+) -> HealthCheckItem {
+    let link = format!("/api/risk/portfolios/{}/alerts", portfolio_id);
+    if snapshots.is_empty() {
+        return skipped("threshold_violations", "Risk thresholds", "No risk snapshots recorded yet", link);
+    }
+
+    let to_f64 = |v: &bigdecimal::BigDecimal| v.to_f64().unwrap_or(0.0);
+    let mut critical = 0;
+    let mut warnings = 0;
+    let mut details = Vec::new();
+    for snapshot in snapshots {
+        let ticker = snapshot.ticker.as_deref().unwrap_or("portfolio");
+        // (metric, value, warning, critical, higher is worse)
+        let mut metrics = vec![
+            ("volatility", to_f64(&snapshot.volatility), thresholds.volatility_warning_threshold, thresholds.volatility_critical_threshold, true),
+            ("max drawdown", to_f64(&snapshot.max_drawdown), thresholds.drawdown_warning_threshold, thresholds.drawdown_critical_threshold, false),
+            ("risk score", to_f64(&snapshot.risk_score), thresholds.risk_score_warning_threshold, thresholds.risk_score_critical_threshold, true),
+        ];
+        if let Some(beta) = &snapshot.beta {
+            metrics.push(("beta", to_f64(beta), thresholds.beta_warning_threshold, thresholds.beta_critical_threshold, true));
+        }
+        if let Some(var) = &snapshot.var_95 {
+            metrics.push(("VaR (95%)", to_f64(var), thresholds.var_warning_threshold, thresholds.var_critical_threshold, false));
+        }
+
+        for (metric, value, warning, critical_threshold, higher_is_worse) in metrics {
+            let breaches = |threshold: f64| if higher_is_worse { value >= threshold } else { value <= threshold };
+            if breaches(critical_threshold) {
+                critical += 1;
+                details.push(format!("{} {} {:.2} breaches the critical threshold {:.2}", ticker, metric, value, critical_threshold));
+            } else if breaches(warning) {
+                warnings += 1;
+                details.push(format!("{} {} {:.2} breaches the warning threshold {:.2}", ticker, metric, value, warning));
+            }
+        }
+    }
+
+    let status = if critical > 0 {
+        HealthCheckStatus::Fail
+    } else if warnings > 0 {
+        HealthCheckStatus::Warn
+    } else {
+        HealthCheckStatus::Pass
+    };
+    let summary = format!(
+        "{} critical and {} warning violations across {} positions",
+        critical,
+        warnings,
+        snapshots.len()
+    );
+    item("threshold_violations", "Risk thresholds", status, summary, details, link)
+}
+
+/// Current weights against the saved optimization constraints
+pub fn check_drift(
+    portfolio_id: Uuid,
+    positions: &[HealthPosition],
+    constraints: Option<&OptimizationConstraints>,
+) -> HealthCheckItem {
+    let link = format!("/api/optimization/portfolios/{}/constraints", portfolio_id);
+    let Some(constraints) = constraints else {
+        return skipped("drift", "Drift from constraints", "No optimization constraints saved", link);
+    };
+    if positions.is_empty() {
+        return skipped("drift", "Drift from constraints", "No holdings to analyze", link);
+    }
+
+    let weighted = weights(positions);
+    // (description, percentage points over or under the limit)
+    let mut breaches: Vec<(String, f64)> = Vec::new();
+
+    if let Some(max_weight) = constraints.max_position_weight {
+        for (position, weight) in weighted.iter().filter(|(p, _)| !p.ticker.is_empty()) {
+            if *weight > max_weight {
+                breaches.push((
+                    format!("{} is {:.1}%, above the {:.1}% position cap", position.ticker, weight, max_weight),
+                    weight - max_weight,
+                ));
+            }
+        }
+    }
+
+    let mut sector_weights: HashMap<&str, f64> = HashMap::new();
+    for (position, weight) in &weighted {
+        if let Some(industry) = position.industry.as_deref() {
+            *sector_weights.entry(industry).or_insert(0.0) += weight;
+        }
+    }
+    let mut caps: Vec<(&String, &f64)> = constraints.sector_caps.iter().collect();
+    caps.sort_by(|a, b| a.0.cmp(b.0));
+    for (sector, cap) in caps {
+        let weight = sector_weights.get(sector.as_str()).copied().unwrap_or(0.0);
+        if weight > *cap {
+            breaches.push((format!("{} is {:.1}%, above its {:.1}% cap", sector, weight, cap), weight - cap));
+        }
+    }
+
+    if let Some(min_cash) = constraints.min_cash_pct {
+        // Cash rows are imported with an empty ticker
+        let cash: f64 = weighted.iter().filter(|(p, _)| p.ticker.is_empty()).map(|(_, w)| w).sum();
+        if cash < min_cash {
+            breaches.push((format!("Cash is {:.1}%, below the {:.1}% minimum", cash, min_cash), min_cash - cash));
+        }
+    }
+
+    let largest = breaches.iter().map(|b| b.1).fold(0.0, f64::max);
+    let status = if largest > DRIFT_FAIL_POINTS {
+        HealthCheckStatus::Fail
+    } else if !breaches.is_empty() {
+        HealthCheckStatus::Warn
+    } else {
+        HealthCheckStatus::Pass
+    };
+    let summary = if breaches.is_empty() {
+        "All positions are within the saved constraints".to_string()
+    } else {
+        format!("{} constraint breaches; largest {:.1} points", breaches.len(), largest)
+    };
+    let details = breaches.into_iter().map(|b| b.0).collect();
+    item("drift", "Drift from constraints", status, summary, details, link)
+}
+
+pub fn grade(score: f64) -> &'static str {
+    match score {
+        s if s >= 90.0 => "A",
+        s if s >= 80.0 => "B",
+        s if s >= 70.0 => "C",
+        s if s >= 60.0 => "D",
+        _ => "F",
+    }
+}
+
+/// Score the checklist: the average of the non-skipped checks, 100 when all are skipped
+pub fn summarize(portfolio_id: Uuid, checks: Vec<HealthCheckItem>) -> PortfolioHealthCheck {
+    let scores: Vec<f64> = checks.iter().filter_map(|c| c.status.score()).collect();
+    let score = if scores.is_empty() { 100.0 } else { scores.iter().sum::<f64>() / scores.len() as f64 };
+    let count = |status: HealthCheckStatus| checks.iter().filter(|c| c.status == status).count();
+
+    PortfolioHealthCheck {
+        portfolio_id,
+        score,
+        grade: grade(score).to_string(),
+        passed: count(HealthCheckStatus::Pass),
+        warnings: count(HealthCheckStatus::Warn),
+        failures: count(HealthCheckStatus::Fail),
+        skipped: count(HealthCheckStatus::Skipped),
+        checks,
+        generated_at: Utc::now(),
+    }
+}
+
+async fn cached_correlation_statistics(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+) -> Result<Option<CorrelationStatistics>, AppError> {
+    let data = sqlx::query_scalar::<_, serde_json::Value>(
+        r#"
+        SELECT correlations_data
+        FROM portfolio_correlations_cache
+        WHERE portfolio_id = $1
+          AND calculation_status = 'fresh'
+          AND expires_at > NOW()
+        ORDER BY calculated_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(portfolio_id)
+    .fetch_optional(pool)
+    .await?;
+
+    match data {
+        Some(data) => {
+            let matrix: crate::models::risk::CorrelationMatrixWithStats = serde_json::from_value(data)
+                .map_err(|e| AppError::External(format!("Failed to deserialize cached correlations: {}", e)))?;
+            Ok(Some(matrix.statistics))
+        }
+        None => Ok(None),
+    }
+}
+
+async fn latest_position_snapshots(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<RiskSnapshot>, AppError> {
+    match risk_snapshot_queries::fetch_latest(pool, portfolio_id, None).await? {
+        Some(latest) => {
+            Ok(risk_snapshot_queries::fetch_portfolio_positions_by_date(pool, portfolio_id, latest.snapshot_date).await?)
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Log a failed check's error and report it as skipped
+fn or_skipped(
+    result: Result<HealthCheckItem, AppError>,
+    check: &str,
+    title: &str,
+    link: String,
+) -> HealthCheckItem {
+    result.unwrap_or_else(|e| {
+        warn!("Health check '{}' could not run: {}", check, e);
+        skipped(check, title, "Check could not be run", link)
+    })
+}
+
+pub async fn portfolio_health(pool: &PgPool, portfolio_id: Uuid) -> Result<PortfolioHealthCheck, AppError> {
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let snapshot_date = holdings.iter().map(|h| h.snapshot_date).max();
+    let mut positions: Vec<HealthPosition> = Vec::new();
+    for holding in &holdings {
+        let value = holding.market_value.to_f64().unwrap_or(0.0);
+        match positions.iter_mut().find(|p| p.ticker == holding.ticker) {
+            Some(position) => position.market_value += value,
+            None => positions.push(HealthPosition {
+                ticker: holding.ticker.clone(),
+                industry: holding.industry.clone(),
+                market_value: value,
+            }),
+        }
+    }
+    positions.retain(|p| p.market_value > 0.0);
+    let tickers: Vec<String> = positions.iter().filter(|p| !p.ticker.is_empty()).map(|p| p.ticker.clone()).collect();
+    let today = clock::today();
+
+    let mut checks = vec![check_concentration(portfolio_id, &positions)];
+
+    checks.push(or_skipped(
+        cached_correlation_statistics(pool, portfolio_id)
+            .await
+            .map(|stats| check_correlation(portfolio_id, stats.as_ref())),
+        "correlation",
+        "Correlation",
+        format!("/api/risk/portfolios/{}/correlations", portfolio_id),
+    ));
+
+    checks.push(or_skipped(
+        fee_service::portfolio_fees(pool, portfolio_id, None)
+            .await
+            .map(|analysis| check_fees(portfolio_id, &analysis)),
+        "fee_drag",
+        "Fee drag",
+        format!("/api/portfolios/{}/fees", portfolio_id),
+    ));
+
+    checks.push(or_skipped(
+        price_queries::fetch_latest_batch(pool, &tickers)
+            .await
+            .map(|latest| {
+                let dates = latest.into_iter().map(|(ticker, point)| (ticker, point.date)).collect();
+                check_stale_data(portfolio_id, today, snapshot_date, &dates)
+            })
+            .map_err(AppError::Db),
+        "stale_data",
+        "Stale data",
+        format!("/api/portfolios/{}/latest-holdings", portfolio_id),
+    ));
+
+    let since = today - chrono::Duration::days(HISTORY_LOOKBACK_DAYS);
+    checks.push(or_skipped(
+        price_queries::fetch_point_counts(pool, &tickers, since)
+            .await
+            .map(|counts| check_price_history(portfolio_id, &positions, &counts))
+            .map_err(AppError::Db),
+        "price_history",
+        "Price history",
+        format!("/api/risk/portfolios/{}", portfolio_id),
+    ));
+
+    let violations = async {
+        let snapshots = latest_position_snapshots(pool, portfolio_id).await?;
+        let thresholds = risk_threshold_queries::get_thresholds(pool, portfolio_id).await?;
+        Ok(check_threshold_violations(portfolio_id, &snapshots, &thresholds))
+    };
+    checks.push(or_skipped(
+        violations.await,
+        "threshold_violations",
+        "Risk thresholds",
+        format!("/api/risk/portfolios/{}/alerts", portfolio_id),
+    ));
+
+    checks.push(or_skipped(
+        optimization_constraint_queries::fetch(pool, portfolio_id)
+            .await
+            .map(|constraints| check_drift(portfolio_id, &positions, constraints.as_ref()))
+            .map_err(AppError::from),
+        "drift",
+        "Drift from constraints",
+        format!("/api/optimization/portfolios/{}/constraints", portfolio_id),
+    ));
+
+    Ok(summarize(portfolio_id, checks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(ticker: &str, industry: Option<&str>, market_value: f64) -> HealthPosition {
+        HealthPosition {
+            ticker: ticker.to_string(),
+            industry: industry.map(str::to_string),
+            market_value,
+        }
+    }
+
+    #[test]
+    fn test_concentration_flags_large_positions() {
+        let positions = vec![
+            position("AAPL", None, 30.0),
+            position("MSFT", None, 20.0),
+            position("VTI", None, 50.0),
+        ];
+        let check = check_concentration(Uuid::nil(), &positions);
+        assert_eq!(check.status, HealthCheckStatus::Fail);
+        assert_eq!(check.details.len(), 3);
+
+        let even: Vec<HealthPosition> = (0..10).map(|i| position(&format!("T{}", i), None, 10.0)).collect();
+        assert_eq!(check_concentration(Uuid::nil(), &even).status, HealthCheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_concentration_ignores_cash() {
+        let mut positions: Vec<HealthPosition> = (0..10).map(|i| position(&format!("T{}", i), None, 10.0)).collect();
+        positions.push(position("", Some("Cash"), 500.0));
+        assert_eq!(check_concentration(Uuid::nil(), &positions).status, HealthCheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_stale_data_uses_snapshot_and_price_age() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+        let fresh = HashMap::from([("VTI".to_string(), today)]);
+        let check = check_stale_data(Uuid::nil(), today, Some(today - chrono::Duration::days(10)), &fresh);
+        assert_eq!(check.status, HealthCheckStatus::Pass);
+
+        let stale = HashMap::from([("VTI".to_string(), today - chrono::Duration::days(20))]);
+        let check = check_stale_data(Uuid::nil(), today, Some(today), &stale);
+        assert_eq!(check.status, HealthCheckStatus::Warn);
+
+        let check = check_stale_data(Uuid::nil(), today, Some(today - chrono::Duration::days(120)), &fresh);
+        assert_eq!(check.status, HealthCheckStatus::Fail);
+        assert_eq!(check_stale_data(Uuid::nil(), today, None, &fresh).status, HealthCheckStatus::Skipped);
+    }
+
+    #[test]
+    fn test_price_history_weights_missing_positions() {
+        let positions = vec![position("VTI", None, 90.0), position("NEW", None, 10.0)];
+        let counts = HashMap::from([("VTI".to_string(), 250)]);
+        let check = check_price_history(Uuid::nil(), &positions, &counts);
+        assert_eq!(check.status, HealthCheckStatus::Warn);
+        assert_eq!(check.details, vec!["NEW has no price history".to_string()]);
+
+        let counts = HashMap::from([("NEW".to_string(), 250), ("VTI".to_string(), 12)]);
+        assert_eq!(check_price_history(Uuid::nil(), &positions, &counts).status, HealthCheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_drift_checks_position_sector_and_cash() {
+        let positions = vec![
+            position("AAPL", Some("Technology"), 40.0),
+            position("MSFT", Some("Technology"), 30.0),
+            position("XOM", Some("Energy"), 28.0),
+            position("", Some("Cash"), 2.0),
+        ];
+        let constraints = OptimizationConstraints {
+            max_position_weight: Some(38.0),
+            sector_caps: HashMap::from([("Technology".to_string(), 80.0)]),
+            min_cash_pct: Some(5.0),
+            do_not_sell: Vec::new(),
+        };
+        let check = check_drift(Uuid::nil(), &positions, Some(&constraints));
+        assert_eq!(check.status, HealthCheckStatus::Warn);
+        assert_eq!(check.details.len(), 2);
+
+        let tight = OptimizationConstraints { max_position_weight: Some(30.0), ..constraints };
+        assert_eq!(check_drift(Uuid::nil(), &positions, Some(&tight)).status, HealthCheckStatus::Fail);
+        assert_eq!(check_drift(Uuid::nil(), &positions, None).status, HealthCheckStatus::Skipped);
+    }
+
+    #[test]
+    fn test_summary_skips_unscored_checks() {
+        let make = |status| item("x", "X", status, String::new(), Vec::new(), String::new());
+        let health = summarize(
+            Uuid::nil(),
+            vec![
+                make(HealthCheckStatus::Pass),
+                make(HealthCheckStatus::Warn),
+                make(HealthCheckStatus::Skipped),
+            ],
+        );
+        assert_eq!(health.score, 75.0);
+        assert_eq!(health.grade, "C");
+        assert_eq!((health.passed, health.warnings, health.failures, health.skipped), (1, 1, 0, 1));
+        assert_eq!(summarize(Uuid::nil(), Vec::new()).score, 100.0);
+    }
+}
@@ -310,6 +310,7 @@ async fn fetch_prices_from_db(
         .map(|p| ExternalPricePoint {
             date: p.date,
             close: p.close_price,
+            adjusted_close: None,
         })
         .collect();
 
@@ -395,6 +396,7 @@ mod tests {
             prices.push(ExternalPricePoint {
                 date,
                 close: BigDecimal::from_str(&price.to_string()).unwrap(),
+                adjusted_close: None,
             });
         }
 
@@ -0,0 +1,240 @@
+//! Message catalog for prose services produce for users.
+//!
+//! Messages are looked up by key in the user's locale, falling back to
+//! English, and `{name}` placeholders are filled from the arguments. Numbers
+//! placed in messages use the locale's decimal separator; everything else
+//! services return stays raw for clients to format with [`LocaleFormat`].
+//!
+//! [`LocaleFormat`]: crate::models::user_preferences::LocaleFormat
+
+use crate::models::Locale;
+
+struct Entry {
+    key: &'static str,
+    en: &'static str,
+    fr: &'static str,
+    es: &'static str,
+}
+
+const CATALOG: &[Entry] = &[
+    // Factor names and descriptions
+    Entry { key: "factor.value.label", en: "Value", fr: "Valeur", es: "Valor" },
+    Entry {
+        key: "factor.value.description",
+        en: "Stocks trading below intrinsic value based on fundamental ratios",
+        fr: "Actions se négociant sous leur valeur intrinsèque selon les ratios fondamentaux",
+        es: "Acciones que cotizan por debajo de su valor intrínseco según ratios fundamentales",
+    },
+    Entry { key: "factor.growth.label", en: "Growth", fr: "Croissance", es: "Crecimiento" },
+    Entry {
+        key: "factor.growth.description",
+        en: "Companies with above-average revenue and earnings growth",
+        fr: "Entreprises dont la croissance du chiffre d'affaires et des bénéfices dépasse la moyenne",
+        es: "Empresas con un crecimiento de ingresos y beneficios superior a la media",
+    },
+    Entry { key: "factor.momentum.label", en: "Momentum", fr: "Momentum", es: "Momentum" },
+    Entry {
+        key: "factor.momentum.description",
+        en: "Securities exhibiting strong recent price performance",
+        fr: "Titres affichant une forte performance récente",
+        es: "Valores con un fuerte rendimiento reciente",
+    },
+    Entry { key: "factor.quality.label", en: "Quality", fr: "Qualité", es: "Calidad" },
+    Entry {
+        key: "factor.quality.description",
+        en: "Profitable companies with low debt and stable earnings",
+        fr: "Entreprises rentables, peu endettées et aux bénéfices stables",
+        es: "Empresas rentables con poca deuda y beneficios estables",
+    },
+    Entry { key: "factor.low_volatility.label", en: "Low Volatility", fr: "Faible volatilité", es: "Baja volatilidad" },
+    Entry {
+        key: "factor.low_volatility.description",
+        en: "Securities with below-average price fluctuations",
+        fr: "Titres dont les fluctuations de prix sont inférieures à la moyenne",
+        es: "Valores con fluctuaciones de precio inferiores a la media",
+    },
+    // Factor recommendations
+    Entry {
+        key: "factor.recommendation.underweight",
+        en: "Your portfolio has low {factor} exposure ({score}/100). Consider adding {factor} stocks or ETFs to capture the estimated {premium}% annual risk premium.",
+        fr: "Votre portefeuille est peu exposé au facteur {factor} ({score}/100). Envisagez d'ajouter des actions ou des FNB axés sur ce facteur pour capter la prime de risque annuelle estimée de {premium} %.",
+        es: "Su cartera tiene poca exposición al factor {factor} ({score}/100). Considere añadir acciones o ETF de este factor para capturar la prima de riesgo anual estimada del {premium} %.",
+    },
+    Entry {
+        key: "factor.recommendation.neutral",
+        en: "Your {factor} exposure is balanced ({score}/100). This factor has a historical risk premium of ~{premium}% per year.",
+        fr: "Votre exposition au facteur {factor} est équilibrée ({score}/100). Ce facteur a offert historiquement une prime de risque d'environ {premium} % par an.",
+        es: "Su exposición al factor {factor} está equilibrada ({score}/100). Este factor ha tenido históricamente una prima de riesgo de ~{premium} % anual.",
+    },
+    Entry {
+        key: "factor.recommendation.overweight",
+        en: "Your portfolio is heavily tilted toward {factor} ({score}/100). This may concentrate risk, though {factor} stocks have historically earned a {premium}% annual premium.",
+        fr: "Votre portefeuille est fortement orienté vers le facteur {factor} ({score}/100). Cela peut concentrer le risque, même si ces actions ont rapporté historiquement une prime annuelle de {premium} %.",
+        es: "Su cartera está muy inclinada hacia el factor {factor} ({score}/100). Esto puede concentrar el riesgo, aunque estas acciones han obtenido históricamente una prima anual del {premium} %.",
+    },
+    // Factor analysis findings
+    Entry { key: "factor.summary.not_available", en: "N/A", fr: "N/D", es: "N/D" },
+    Entry {
+        key: "factor.summary.underweight_in",
+        en: "Portfolio is underweight in: {factors}",
+        fr: "Le portefeuille est sous-pondéré en : {factors}",
+        es: "La cartera está infraponderada en: {factors}",
+    },
+    Entry {
+        key: "factor.summary.overweight_in",
+        en: "Portfolio is overweight in: {factors}",
+        fr: "Le portefeuille est surpondéré en : {factors}",
+        es: "La cartera está sobreponderada en: {factors}",
+    },
+    Entry {
+        key: "factor.summary.composite_strong",
+        en: "Overall multi-factor composite is strong, indicating good factor diversification.",
+        fr: "Le score composite multifactoriel global est élevé, signe d'une bonne diversification des facteurs.",
+        es: "El compuesto multifactorial global es sólido, lo que indica una buena diversificación de factores.",
+    },
+    Entry {
+        key: "factor.summary.composite_moderate",
+        en: "Overall multi-factor composite is moderate. Consider rebalancing toward underweight factors.",
+        fr: "Le score composite multifactoriel global est modéré. Envisagez un rééquilibrage vers les facteurs sous-pondérés.",
+        es: "El compuesto multifactorial global es moderado. Considere reequilibrar hacia los factores infraponderados.",
+    },
+    Entry {
+        key: "factor.summary.composite_weak",
+        en: "Overall multi-factor composite is weak. Significant factor rebalancing is recommended.",
+        fr: "Le score composite multifactoriel global est faible. Un rééquilibrage important des facteurs est recommandé.",
+        es: "El compuesto multifactorial global es débil. Se recomienda un reequilibrio significativo de factores.",
+    },
+    Entry {
+        key: "factor.summary.best_composite",
+        en: "Highest composite score: {ticker} ({score}/100)",
+        fr: "Meilleur score composite : {ticker} ({score}/100)",
+        es: "Mayor puntuación compuesta: {ticker} ({score}/100)",
+    },
+    // Behavioral insights
+    Entry {
+        key: "behavior.no_trades",
+        en: "No buys or sells recorded yet, so there is no trading behavior to analyze.",
+        fr: "Aucun achat ni aucune vente enregistré pour l'instant : il n'y a pas encore de comportement à analyser.",
+        es: "Aún no hay compras ni ventas registradas, así que no hay comportamiento que analizar.",
+    },
+    Entry {
+        key: "behavior.turnover_high",
+        en: "Your portfolio turns over about {turnover}% a year. Frequent trading adds costs and taxes that eat into returns.",
+        fr: "Votre portefeuille se renouvelle d'environ {turnover} % par an. Des transactions fréquentes ajoutent des frais et des impôts qui réduisent le rendement.",
+        es: "Su cartera rota alrededor de un {turnover} % al año. Operar con frecuencia añade costes e impuestos que reducen la rentabilidad.",
+    },
+    Entry {
+        key: "behavior.short_holding",
+        en: "Positions are sold after {days} days on average. Short holding periods make results depend on timing rather than on the businesses you own.",
+        fr: "Les positions sont vendues après {days} jours en moyenne. Des durées de détention courtes rendent les résultats dépendants du moment choisi plutôt que des entreprises détenues.",
+        es: "Las posiciones se venden tras {days} días de media. Los periodos de tenencia cortos hacen que los resultados dependan del momento elegido más que de las empresas que posee.",
+    },
+    Entry {
+        key: "behavior.low_win_rate",
+        en: "Only {win_rate}% of your sales closed at a gain.",
+        fr: "Seulement {win_rate} % de vos ventes ont été réalisées avec un gain.",
+        es: "Solo el {win_rate} % de sus ventas se cerraron con ganancia.",
+    },
+    Entry {
+        key: "behavior.disposition_effect",
+        en: "Losing positions were held {loser_days} days on average versus {winner_days} for winners. Selling winners early while riding losers is a common bias.",
+        fr: "Les positions perdantes ont été conservées {loser_days} jours en moyenne contre {winner_days} pour les gagnantes. Vendre trop tôt les gagnantes en gardant les perdantes est un biais courant.",
+        es: "Las posiciones perdedoras se mantuvieron {loser_days} días de media frente a {winner_days} las ganadoras. Vender pronto las ganadoras y aguantar las perdedoras es un sesgo habitual.",
+    },
+    Entry {
+        key: "behavior.bought_high",
+        en: "{count} buys were made near the top of their recent range and fell afterwards.",
+        fr: "{count} achats ont été effectués près du haut de leur fourchette récente et ont baissé ensuite.",
+        es: "{count} compras se hicieron cerca del máximo de su rango reciente y bajaron después.",
+    },
+    Entry {
+        key: "behavior.sold_low",
+        en: "{count} sales were made near the bottom of their recent range and rose afterwards.",
+        fr: "{count} ventes ont été effectuées près du bas de leur fourchette récente et ont monté ensuite.",
+        es: "{count} ventas se hicieron cerca del mínimo de su rango reciente y subieron después.",
+    },
+    Entry {
+        key: "behavior.timing_good",
+        en: "{score}% of your trades moved in your favor afterwards. Your timing has added value.",
+        fr: "{score} % de vos transactions ont évolué en votre faveur ensuite. Votre choix du moment a créé de la valeur.",
+        es: "El {score} % de sus operaciones evolucionaron a su favor después. Su elección del momento ha aportado valor.",
+    },
+    Entry {
+        key: "behavior.timing_poor",
+        en: "Only {score}% of your trades moved in your favor afterwards. Regular, rules-based investing may serve you better than timing trades.",
+        fr: "Seulement {score} % de vos transactions ont évolué en votre faveur ensuite. Investir régulièrement selon des règles pourrait vous servir mieux que de chercher le bon moment.",
+        es: "Solo el {score} % de sus operaciones evolucionaron a su favor después. Invertir de forma periódica y con reglas puede servirle mejor que buscar el momento.",
+    },
+];
+
+/// `key` in `locale` with `{name}` placeholders filled from `args`. Falls back
+/// to English, then to the key itself for an unknown key.
+pub fn message(locale: Locale, key: &str, args: &[(&str, &str)]) -> String {
+    let Some(entry) = CATALOG.iter().find(|e| e.key == key) else {
+        return key.to_string();
+    };
+    let template = match locale {
+        Locale::En => entry.en,
+        Locale::Fr => entry.fr,
+        Locale::Es => entry.es,
+    };
+    let template = if template.is_empty() { entry.en } else { template };
+
+    args.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// `value` with `decimals` decimal places and the locale's decimal separator
+pub fn format_decimal(locale: Locale, value: f64, decimals: usize) -> String {
+    let text = format!("{:.*}", decimals, value);
+    match locale.formatting().decimal_separator {
+        "." => text,
+        separator => text.replace('.', separator),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(template: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = template
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    #[test]
+    fn test_catalog_translations_are_complete() {
+        for entry in CATALOG {
+            assert!(!entry.fr.is_empty() && !entry.es.is_empty(), "{} is missing a translation", entry.key);
+            assert_eq!(placeholders(entry.en), placeholders(entry.fr), "{} placeholders differ in fr", entry.key);
+            assert_eq!(placeholders(entry.en), placeholders(entry.es), "{} placeholders differ in es", entry.key);
+        }
+        let mut keys: Vec<&str> = CATALOG.iter().map(|e| e.key).collect();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), CATALOG.len(), "duplicate catalog keys");
+    }
+
+    #[test]
+    fn test_message_fills_placeholders_in_locale() {
+        let args = [("ticker", "RY.TO"), ("score", "82")];
+        assert_eq!(
+            message(Locale::Fr, "factor.summary.best_composite", &args),
+            "Meilleur score composite : RY.TO (82/100)"
+        );
+        assert_eq!(
+            message(Locale::En, "factor.summary.best_composite", &args),
+            "Highest composite score: RY.TO (82/100)"
+        );
+        assert_eq!(message(Locale::Es, "no.such.key", &[]), "no.such.key");
+        assert_eq!(format_decimal(Locale::Fr, 4.5, 1), "4,5");
+        assert_eq!(format_decimal(Locale::En, 4.5, 1), "4.5");
+    }
+}
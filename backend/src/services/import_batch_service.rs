@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db::{import_batch_queries, portfolio_member_queries};
+use crate::errors::AppError;
+use crate::models::{ImportRollback, Role};
+
+/// Reverse every change an import made, in one transaction. Refused when a
+/// later import that is still applied touched the same rows, since undoing
+/// this one first would clobber that import's data.
+pub async fn rollback_batch(pool: &PgPool, user_id: Uuid, batch_id: Uuid) -> Result<ImportRollback, AppError> {
+    let mut tx = pool.begin().await.map_err(AppError::Db)?;
+
+    let batch = import_batch_queries::lock(&mut *tx, user_id, batch_id)
+        .await
+        .map_err(AppError::Db)?
+        .ok_or_else(|| AppError::NotFound(format!("Import batch {} not found", batch_id)))?;
+    let role = portfolio_member_queries::role_for(&mut *tx, batch.portfolio_id, user_id)
+        .await
+        .map_err(AppError::Db)?;
+    if role.is_none_or(|(role, _)| role < Role::Editor) {
+        return Err(AppError::Forbidden("You can no longer edit the portfolio this batch was imported into".into()));
+    }
+    if batch.status != "applied" {
+        return Err(AppError::Validation(format!("Import batch {} was already rolled back", batch_id)));
+    }
+    if let Some(later) = import_batch_queries::find_later_overlapping_batch(&mut *tx, batch_id)
+        .await
+        .map_err(AppError::Db)?
+    {
+        return Err(AppError::Validation(format!(
+            "Import batch {} changed the same rows later; roll it back first",
+            later
+        )));
+    }
+
+    let reverted = import_batch_queries::change_summary(&mut *tx, batch_id)
+        .await
+        .map_err(AppError::Db)?;
+    let changes = import_batch_queries::fetch_changes(&mut *tx, batch_id)
+        .await
+        .map_err(AppError::Db)?;
+
+    let mut columns: HashMap<String, Vec<String>> = HashMap::new();
+    for change in &changes {
+        if !columns.contains_key(&change.table_name) {
+            let table_columns = import_batch_queries::fetch_table_columns(&mut *tx, &change.table_name)
+                .await
+                .map_err(AppError::Db)?;
+            columns.insert(change.table_name.clone(), table_columns);
+        }
+        import_batch_queries::revert_change(&mut *tx, change, &columns[&change.table_name])
+            .await
+            .map_err(AppError::Db)?;
+    }
+
+    let batch = import_batch_queries::mark_rolled_back(&mut *tx, batch_id)
+        .await
+        .map_err(AppError::Db)?;
+    tx.commit().await.map_err(AppError::Db)?;
+
+    info!("Rolled back import batch {}: {} changes reverted", batch_id, changes.len());
+    Ok(ImportRollback { batch, reverted })
+}
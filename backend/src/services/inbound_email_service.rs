@@ -0,0 +1,664 @@
+//! Email-in statement ingestion.
+//!
+//! Each portfolio can have a private forwarding address,
+//! `import+<token>@INBOUND_EMAIL_DOMAIN`. Broker statement emails sent there
+//! reach the mail provider's parsing webhook. [`parse_mailgun`] turns Mailgun's
+//! multipart form into an [`InboundMessage`]; another provider plugs in by
+//! producing the same message. [`receive`] resolves the portfolio and records
+//! the email. [`process`] then imports each CSV or PDF statement attachment
+//! through the regular import pipeline, under its own import batch, so an
+//! emailed statement can be rolled back like an upload.
+
+use anyhow::Result;
+use axum::extract::multipart::{Multipart, MultipartError};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::{auth_queries, csv_import_template_queries, import_batch_queries, inbound_email_queries, portfolio_member_queries};
+use crate::errors::AppError;
+use crate::models::{AttachmentOutcome, ColumnMapping, InboundAttachmentResult, InboundEmail, Role};
+use crate::services::{activity_import_service, csv_import_service, pdf_statement_service, wash_sale_service};
+
+/// Webhooks signed longer ago than this are refused as replays
+const MAX_SIGNATURE_AGE_SECS: i64 = 15 * 60;
+/// Holdings files are only imported unattended when every required column
+/// matched at least an exact alias (or a saved template covers the layout)
+const AUTO_IMPORT_CONFIDENCE: f64 = 0.9;
+
+#[derive(Debug, Clone)]
+pub struct InboundEmailConfig {
+    /// Domain the provider receives mail for; inbound email is off when unset
+    pub domain: Option<String>,
+    pub local_part: String,
+    pub mailgun_signing_key: Option<String>,
+    /// Only accept mail sent from the portfolio owner's account email
+    pub require_owner_sender: bool,
+}
+
+impl InboundEmailConfig {
+    pub fn from_env() -> Self {
+        Self {
+            domain: std::env::var("INBOUND_EMAIL_DOMAIN").ok().filter(|d| !d.trim().is_empty()),
+            local_part: std::env::var("INBOUND_EMAIL_LOCAL_PART").unwrap_or_else(|_| "import".to_string()),
+            mailgun_signing_key: std::env::var("MAILGUN_WEBHOOK_SIGNING_KEY").ok().filter(|k| !k.is_empty()),
+            require_owner_sender: std::env::var("INBOUND_EMAIL_REQUIRE_OWNER_SENDER")
+                .ok()
+                .and_then(|s| s.parse::<bool>().ok())
+                .unwrap_or(true),
+        }
+    }
+
+    /// Forwarding address for a token, when inbound email is configured
+    pub fn address(&self, token: &str) -> Option<String> {
+        self.domain
+            .as_ref()
+            .map(|domain| format!("{}+{}@{}", self.local_part, token, domain))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InboundAttachment {
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// A received email, independent of the provider that delivered it
+#[derive(Debug, Clone)]
+pub struct InboundMessage {
+    /// Envelope recipients, comma separated
+    pub recipients: String,
+    /// Envelope sender
+    pub sender: String,
+    /// From header
+    pub from: Option<String>,
+    pub subject: Option<String>,
+    pub message_id: Option<String>,
+    pub attachments: Vec<InboundAttachment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MailgunSignature {
+    pub timestamp: String,
+    pub token: String,
+    pub signature: String,
+}
+
+/// What became of a delivered email
+#[derive(Debug)]
+pub enum Receipt {
+    /// Recorded; its attachments still need to be processed
+    Accepted(Box<InboundEmail>),
+    /// Recorded as rejected, e.g. from a sender other than the owner
+    Rejected,
+    /// Already received under the same Message-Id
+    Duplicate,
+    /// No portfolio has the address it was sent to
+    UnknownRecipient,
+}
+
+/// A new address token: a random UUID without dashes
+pub fn generate_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// Check Mailgun's webhook signature: hex HMAC-SHA256 of timestamp + token
+/// under the webhook signing key
+pub fn verify_mailgun_signature(signing_key: &str, signature: &MailgunSignature, now: DateTime<Utc>) -> bool {
+    let Ok(timestamp) = signature.timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (now.timestamp() - timestamp).abs() > MAX_SIGNATURE_AGE_SECS {
+        return false;
+    }
+    let Ok(expected) = hex::decode(&signature.signature) else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(signing_key.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(signature.timestamp.as_bytes());
+    mac.update(signature.token.as_bytes());
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Record the token of a webhook whose signature verified. A signature stays
+/// valid for the whole age window, so a repeated token is a replay; returns
+/// false for one. Tokens past the window are dropped as new ones arrive.
+pub async fn claim_signature(pool: &PgPool, signature: &MailgunSignature, now: DateTime<Utc>) -> Result<bool, AppError> {
+    let signed_at = signature
+        .timestamp
+        .parse::<i64>()
+        .ok()
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        .ok_or_else(|| AppError::Validation("Invalid webhook timestamp".to_string()))?;
+    inbound_email_queries::prune_webhook_tokens(pool, now - Duration::seconds(MAX_SIGNATURE_AGE_SECS))
+        .await
+        .map_err(AppError::Db)?;
+    inbound_email_queries::claim_webhook_token(pool, &signature.token, signed_at)
+        .await
+        .map_err(AppError::Db)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormPart {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// Collect a multipart/form-data body into its named parts
+pub async fn read_form(mut multipart: Multipart) -> Result<Vec<FormPart>, MultipartError> {
+    let mut parts = Vec::new();
+    while let Some(field) = multipart.next_field().await? {
+        let Some(name) = field.name().map(str::to_string) else {
+            continue;
+        };
+        let filename = field.file_name().map(str::to_string);
+        let content_type = field.content_type().map(str::to_string);
+        let data = field.bytes().await?.to_vec();
+        parts.push(FormPart { name, filename, content_type, data });
+    }
+    Ok(parts)
+}
+
+/// Read Mailgun's parsed-message form: text fields plus one file part per
+/// attachment (`attachment-1`, `attachment-2`, ...)
+pub fn parse_mailgun(parts: Vec<FormPart>) -> Result<(MailgunSignature, InboundMessage)> {
+    let field = |name: &str| {
+        parts
+            .iter()
+            .find(|p| p.filename.is_none() && p.name.eq_ignore_ascii_case(name))
+            .map(|p| String::from_utf8_lossy(&p.data).trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let required = |name: &str| field(name).ok_or_else(|| anyhow::anyhow!("Missing field: {}", name));
+
+    let signature = MailgunSignature {
+        timestamp: required("timestamp")?,
+        token: required("token")?,
+        signature: required("signature")?,
+    };
+    let message = InboundMessage {
+        recipients: required("recipient")?,
+        sender: required("sender")?,
+        from: field("from"),
+        subject: field("subject"),
+        message_id: field("Message-Id"),
+        attachments: Vec::new(),
+    };
+    let attachments = parts
+        .into_iter()
+        .filter_map(|p| {
+            Some(InboundAttachment {
+                filename: p.filename?,
+                content_type: p.content_type,
+                data: p.data,
+            })
+        })
+        .collect();
+
+    Ok((signature, InboundMessage { attachments, ..message }))
+}
+
+/// Bare lowercase address from `Name <user@example.com>` or `user@example.com`
+fn email_address(value: &str) -> String {
+    let address = match (value.rfind('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &value[start + 1..end],
+        _ => value,
+    };
+    address.trim().to_ascii_lowercase()
+}
+
+/// Token of the first recipient addressed to `local_part+<token>@domain`
+pub fn recipient_token(recipients: &str, local_part: &str, domain: &str) -> Option<String> {
+    recipients.split(',').find_map(|recipient| {
+        let address = email_address(recipient);
+        let (local, host) = address.rsplit_once('@')?;
+        if !host.eq_ignore_ascii_case(domain) {
+            return None;
+        }
+        let (prefix, token) = local.split_once('+')?;
+        (prefix.eq_ignore_ascii_case(local_part) && !token.is_empty()).then(|| token.to_string())
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AttachmentKind {
+    /// Raymond James `AccountActivities-{account}-{date}.csv` export
+    Activities,
+    /// Holdings CSV in any layout a saved template or the column detection covers
+    Holdings,
+    /// Holdings statement, read from its text layer
+    Pdf,
+    /// Logos and other inline images; not reported
+    Image,
+    Other,
+}
+
+fn classify_attachment(attachment: &InboundAttachment) -> AttachmentKind {
+    let filename = attachment.filename.to_ascii_lowercase();
+    let content_type = attachment.content_type.as_deref().unwrap_or("").to_ascii_lowercase();
+    if filename.ends_with(".pdf") || content_type.starts_with("application/pdf") {
+        AttachmentKind::Pdf
+    } else if filename.ends_with(".csv")
+        || content_type.starts_with("text/csv")
+        || content_type.starts_with("application/csv")
+        || content_type.starts_with("text/comma-separated-values")
+    {
+        if attachment.filename.starts_with("AccountActivities") {
+            AttachmentKind::Activities
+        } else {
+            AttachmentKind::Holdings
+        }
+    } else if content_type.starts_with("image/") {
+        AttachmentKind::Image
+    } else {
+        AttachmentKind::Other
+    }
+}
+
+fn attachment_result(filename: &str, outcome: AttachmentOutcome, message: Option<String>) -> InboundAttachmentResult {
+    InboundAttachmentResult {
+        filename: filename.to_string(),
+        outcome,
+        source: None,
+        import_batch_id: None,
+        accounts_created: 0,
+        holdings_created: 0,
+        transactions_detected: 0,
+        message,
+        errors: Vec::new(),
+    }
+}
+
+/// Resolve the portfolio an email was sent to and record it. Only accepted
+/// emails go on to [`process`].
+pub async fn receive(
+    pool: &PgPool,
+    config: &InboundEmailConfig,
+    message: &InboundMessage,
+) -> Result<Receipt, AppError> {
+    let domain = config
+        .domain
+        .as_deref()
+        .ok_or_else(|| AppError::ServiceUnavailable("Inbound email is not configured".to_string()))?;
+
+    let Some(token) = recipient_token(&message.recipients, &config.local_part, domain) else {
+        return Ok(Receipt::UnknownRecipient);
+    };
+    let Some(address) = inbound_email_queries::find_address_by_token(pool, &token)
+        .await
+        .map_err(AppError::Db)?
+    else {
+        return Ok(Receipt::UnknownRecipient);
+    };
+
+    let mut rejection = None;
+    // The address imports as the user who created it, who may since have lost edit access
+    let role = portfolio_member_queries::role_for(pool, address.portfolio_id, address.user_id)
+        .await
+        .map_err(AppError::Db)?;
+    if role.is_none_or(|(role, _)| role < Role::Editor) {
+        rejection = Some("The forwarding address's creator can no longer edit the portfolio".to_string());
+    }
+    if rejection.is_none() && config.require_owner_sender {
+        let owner = auth_queries::get_user(pool, address.user_id).await.map_err(AppError::Db)?;
+        let owner_email = owner.email.trim().to_ascii_lowercase();
+        let from_owner = std::iter::once(message.sender.as_str())
+            .chain(message.from.as_deref())
+            .any(|sender| email_address(sender) == owner_email);
+        if !from_owner {
+            rejection = Some(format!(
+                "Sender {} is not the portfolio owner's email address",
+                email_address(&message.sender)
+            ));
+        }
+    }
+    if rejection.is_none() && message.attachments.is_empty() {
+        rejection = Some("The email has no attachments".to_string());
+    }
+
+    let status = if rejection.is_some() { "rejected" } else { "received" };
+    let email = inbound_email_queries::create_email(
+        pool,
+        address.user_id,
+        address.portfolio_id,
+        message.message_id.as_deref(),
+        &email_address(&message.sender),
+        message.subject.as_deref(),
+        status,
+        rejection.as_deref(),
+    )
+    .await
+    .map_err(AppError::Db)?;
+
+    Ok(match (email, rejection) {
+        (None, _) => Receipt::Duplicate,
+        (Some(email), Some(reason)) => {
+            warn!("Rejected inbound email {} for portfolio {}: {}", email.id, email.portfolio_id, reason);
+            Receipt::Rejected
+        }
+        (Some(email), None) => Receipt::Accepted(Box::new(email)),
+    })
+}
+
+/// Import an accepted email's attachments into its portfolio and record the
+/// outcome of each
+pub async fn process(
+    pool: &PgPool,
+    email: &InboundEmail,
+    attachments: &[InboundAttachment],
+) -> Result<InboundEmail, AppError> {
+    let received_on = email.received_at.with_timezone(&Local).date_naive();
+
+    let mut results = Vec::new();
+    for attachment in attachments {
+        let kind = classify_attachment(attachment);
+        let result = match kind {
+            AttachmentKind::Image => continue,
+            AttachmentKind::Other => attachment_result(
+                &attachment.filename,
+                AttachmentOutcome::Skipped,
+                Some("Only CSV and PDF statements are imported".to_string()),
+            ),
+            AttachmentKind::Activities | AttachmentKind::Holdings | AttachmentKind::Pdf => {
+                let imported = if kind == AttachmentKind::Pdf {
+                    import_pdf_attachment(pool, email, attachment, received_on).await
+                } else {
+                    import_csv_attachment(pool, email, attachment, kind, received_on).await
+                };
+                match imported {
+                    Ok(result) => result,
+                    Err(e) => {
+                        error!("Failed to import {} from inbound email {}: {}", attachment.filename, email.id, e);
+                        attachment_result(&attachment.filename, AttachmentOutcome::Failed, Some(e.to_string()))
+                    }
+                }
+            }
+        };
+        results.push(result);
+    }
+
+    let transactions: usize = results.iter().map(|r| r.transactions_detected).sum();
+    if transactions > 0 {
+        if let Err(e) = wash_sale_service::refresh_portfolio_wash_sales(pool, email.portfolio_id).await {
+            error!("Failed to check wash sales for portfolio {}: {}", email.portfolio_id, e);
+        }
+    }
+
+    let imported = results.iter().filter(|r| r.outcome == AttachmentOutcome::Imported).count();
+    let (status, error) = if results.is_empty() {
+        ("failed", Some("The email has no statement attachments"))
+    } else if imported == results.len() {
+        ("processed", None)
+    } else if imported > 0 {
+        ("partial", None)
+    } else {
+        ("failed", None)
+    };
+
+    info!(
+        "Inbound email {} for portfolio {}: {} of {} attachments imported",
+        email.id,
+        email.portfolio_id,
+        imported,
+        results.len()
+    );
+    inbound_email_queries::finish_email(pool, email.id, status, &results, error)
+        .await
+        .map_err(AppError::Db)
+}
+
+/// Import one CSV attachment in its own transaction and import batch
+async fn import_csv_attachment(
+    pool: &PgPool,
+    email: &InboundEmail,
+    attachment: &InboundAttachment,
+    kind: AttachmentKind,
+    received_on: NaiveDate,
+) -> Result<InboundAttachmentResult> {
+    let content = String::from_utf8_lossy(&attachment.data);
+    let content = content.trim_start_matches('\u{feff}');
+    let filename = attachment.filename.as_str();
+
+    if kind == AttachmentKind::Activities {
+        let mut tx = pool.begin().await?;
+        let batch =
+            import_batch_queries::begin(&mut tx, email.user_id, email.portfolio_id, "rj_activities", Some(filename))
+                .await?;
+        let result =
+            activity_import_service::import_named_activities_content(&mut tx, email.portfolio_id, filename, content)
+                .await?;
+        import_batch_queries::record_counts(&mut *tx, batch.id, 0, 0, result.transactions_imported, result.errors.len())
+            .await?;
+        tx.commit().await?;
+
+        return Ok(InboundAttachmentResult {
+            source: Some(batch.source),
+            import_batch_id: Some(batch.id),
+            transactions_detected: result.transactions_imported,
+            errors: result.errors,
+            ..attachment_result(filename, AttachmentOutcome::Imported, None)
+        });
+    }
+
+    // Unattended, so only a saved template or a near-exact header match will do
+    let signature = csv_import_service::csv_header_signature(content)?;
+    let template = csv_import_template_queries::find_by_signature(pool, email.user_id, &signature, None).await?;
+    let preview = csv_import_service::build_preview(content, template)?;
+    if !preview.missing_required.is_empty() || preview.confidence < AUTO_IMPORT_CONFIDENCE {
+        return Ok(attachment_result(
+            filename,
+            AttachmentOutcome::Skipped,
+            Some(
+                "The columns could not be matched with confidence; import this file once through the upload \
+                 preview and save a template for its layout"
+                    .to_string(),
+            ),
+        ));
+    }
+
+    let snapshot_date = csv_import_service::extract_date_from_filename(filename).unwrap_or(received_on);
+    let result = import_holdings(pool, email, filename, "mapped_holdings", content, snapshot_date, &preview.mapping).await?;
+    if let Some(template) = &preview.template {
+        csv_import_template_queries::mark_used(pool, template.id).await?;
+    }
+    Ok(result)
+}
+
+/// Import the holdings of a PDF statement, dated as the statement states
+async fn import_pdf_attachment(
+    pool: &PgPool,
+    email: &InboundEmail,
+    attachment: &InboundAttachment,
+    received_on: NaiveDate,
+) -> Result<InboundAttachmentResult> {
+    let filename = attachment.filename.as_str();
+    // Parsing is CPU-bound; keep it off the async workers
+    let data = attachment.data.clone();
+    let statement = tokio::task::spawn_blocking(move || pdf_statement_service::parse_statement(&data, None, None)).await??;
+    let preview = csv_import_service::build_preview(&statement.content, None)?;
+    if !preview.missing_required.is_empty() {
+        return Ok(attachment_result(
+            filename,
+            AttachmentOutcome::Skipped,
+            Some(
+                "The statement does not show an account number; import it once through the PDF upload and enter \
+                 the account"
+                    .to_string(),
+            ),
+        ));
+    }
+
+    let snapshot_date = statement
+        .statement_date
+        .or_else(|| csv_import_service::extract_date_from_filename(filename).ok())
+        .unwrap_or(received_on);
+    let mut result = import_holdings(
+        pool,
+        email,
+        filename,
+        "pdf_holdings",
+        &statement.content,
+        snapshot_date,
+        &preview.mapping,
+    )
+    .await?;
+    result.errors.extend(statement.warnings);
+    Ok(result)
+}
+
+/// Import a holdings CSV with a confirmed mapping in its own transaction and import batch
+async fn import_holdings(
+    pool: &PgPool,
+    email: &InboundEmail,
+    filename: &str,
+    source: &str,
+    content: &str,
+    snapshot_date: NaiveDate,
+    mapping: &ColumnMapping,
+) -> Result<InboundAttachmentResult> {
+    let mut tx = pool.begin().await?;
+    let batch = import_batch_queries::begin(&mut tx, email.user_id, email.portfolio_id, source, Some(filename)).await?;
+    let result =
+        csv_import_service::import_mapped_csv_content(&mut tx, email.portfolio_id, content, snapshot_date, mapping)
+            .await?;
+    import_batch_queries::record_counts(
+        &mut *tx,
+        batch.id,
+        result.accounts_created,
+        result.holdings_created,
+        result.transactions_detected,
+        result.errors.len(),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(InboundAttachmentResult {
+        source: Some(batch.source),
+        import_batch_id: Some(batch.id),
+        accounts_created: result.accounts_created,
+        holdings_created: result.holdings_created,
+        transactions_detected: result.transactions_detected,
+        errors: result.errors,
+        ..attachment_result(filename, AttachmentOutcome::Imported, None)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::FromRequest;
+    use chrono::TimeZone;
+
+    fn sign(key: &str, timestamp: &str, token: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).unwrap();
+        mac.update(timestamp.as_bytes());
+        mac.update(token.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn test_verify_mailgun_signature() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap();
+        let timestamp = now.timestamp().to_string();
+        let valid = MailgunSignature {
+            timestamp: timestamp.clone(),
+            token: "abc123".to_string(),
+            signature: sign("key-secret", &timestamp, "abc123"),
+        };
+        assert!(verify_mailgun_signature("key-secret", &valid, now));
+        assert!(!verify_mailgun_signature("other-key", &valid, now));
+
+        let tampered = MailgunSignature { token: "abc124".to_string(), ..valid.clone() };
+        assert!(!verify_mailgun_signature("key-secret", &tampered, now));
+
+        // A captured request cannot be replayed later
+        let later = now + chrono::Duration::seconds(MAX_SIGNATURE_AGE_SECS + 1);
+        assert!(!verify_mailgun_signature("key-secret", &valid, later));
+    }
+
+    #[tokio::test]
+    async fn test_parse_mailgun_multipart() {
+        let body = "--XyZ\r\n\
+            Content-Disposition: form-data; name=\"recipient\"\r\n\r\n\
+            import+0123abcd@in.example.com\r\n\
+            --XyZ\r\n\
+            Content-Disposition: form-data; name=\"sender\"\r\n\r\n\
+            owner@example.com\r\n\
+            --XyZ\r\n\
+            Content-Disposition: form-data; name=\"subject\"\r\n\r\n\
+            Fwd: Your statement\r\n\
+            --XyZ\r\n\
+            Content-Disposition: form-data; name=\"timestamp\"\r\n\r\n\
+            1772625600\r\n\
+            --XyZ\r\n\
+            Content-Disposition: form-data; name=\"token\"\r\n\r\n\
+            tok\r\n\
+            --XyZ\r\n\
+            Content-Disposition: form-data; name=\"signature\"\r\n\r\n\
+            00ff\r\n\
+            --XyZ\r\n\
+            Content-Disposition: form-data; name=\"attachment-1\"; filename=\"AccountsHoldings-20260301.csv\"\r\n\
+            Content-Type: text/csv\r\n\r\n\
+            Symbol,Quantity\r\nAAPL,10\r\n\
+            --XyZ--\r\n";
+
+        let request = axum::http::Request::builder()
+            .header(axum::http::header::CONTENT_TYPE, "multipart/form-data; boundary=\"XyZ\"")
+            .body(axum::body::Body::from(body))
+            .unwrap();
+        let multipart = Multipart::from_request(request, &()).await.unwrap();
+        let parts = read_form(multipart).await.unwrap();
+        assert_eq!(parts.len(), 7);
+
+        let (signature, message) = parse_mailgun(parts).unwrap();
+        assert_eq!(signature.timestamp, "1772625600");
+        assert_eq!(message.recipients, "import+0123abcd@in.example.com");
+        assert_eq!(message.subject.as_deref(), Some("Fwd: Your statement"));
+        assert_eq!(message.attachments.len(), 1);
+        assert_eq!(message.attachments[0].filename, "AccountsHoldings-20260301.csv");
+        assert_eq!(message.attachments[0].content_type.as_deref(), Some("text/csv"));
+        // The part's own line breaks are kept; only the one before the boundary is not
+        assert_eq!(message.attachments[0].data, b"Symbol,Quantity\r\nAAPL,10");
+    }
+
+    #[test]
+    fn test_recipient_token() {
+        assert_eq!(
+            recipient_token("Import <Import+0123abcd@In.Example.com>", "import", "in.example.com"),
+            Some("0123abcd".to_string())
+        );
+        assert_eq!(
+            recipient_token("someone@example.com, import+ff00@in.example.com", "import", "in.example.com"),
+            Some("ff00".to_string())
+        );
+        assert_eq!(recipient_token("import+ff00@other.com", "import", "in.example.com"), None);
+        assert_eq!(recipient_token("import@in.example.com", "import", "in.example.com"), None);
+        assert_eq!(recipient_token("statements+ff00@in.example.com", "import", "in.example.com"), None);
+    }
+
+    #[test]
+    fn test_classify_attachment() {
+        let attachment = |filename: &str, content_type: Option<&str>| InboundAttachment {
+            filename: filename.to_string(),
+            content_type: content_type.map(str::to_string),
+            data: Vec::new(),
+        };
+        assert_eq!(
+            classify_attachment(&attachment("AccountActivities-12345-20260301.csv", Some("text/csv"))),
+            AttachmentKind::Activities
+        );
+        assert_eq!(
+            classify_attachment(&attachment("positions.CSV", Some("application/octet-stream"))),
+            AttachmentKind::Holdings
+        );
+        assert_eq!(classify_attachment(&attachment("statement.pdf", None)), AttachmentKind::Pdf);
+        assert_eq!(classify_attachment(&attachment("logo.png", Some("image/png"))), AttachmentKind::Image);
+        assert_eq!(classify_attachment(&attachment("notes.txt", Some("text/plain"))), AttachmentKind::Other);
+    }
+}
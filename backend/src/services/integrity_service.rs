@@ -0,0 +1,166 @@
+//! Data integrity checks.
+//!
+//! Foreign keys cover most relations, but restores, manual fixes and the few
+//! caches created without one can still leave rows pointing at deleted
+//! accounts or portfolios, and imports have written negative quantities
+//! before. At startup, and on demand through the admin API, every rule in
+//! [`integrity_queries::CHECKS`] is counted; derived rows that are safe to
+//! drop are deleted when repair is requested, everything else is only
+//! reported for an administrator to look at.
+
+use std::sync::{OnceLock, RwLock};
+
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use crate::db::integrity_queries::{self, IntegrityCheck};
+use crate::errors::AppError;
+use crate::models::{IntegrityFinding, IntegrityReport};
+use crate::services::clock;
+
+/// `INTEGRITY_CHECK_ON_STARTUP` (default true) and `INTEGRITY_AUTO_REPAIR`
+/// (default false)
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegrityConfig {
+    pub on_startup: bool,
+    pub auto_repair: bool,
+}
+
+impl Default for IntegrityConfig {
+    fn default() -> Self {
+        Self { on_startup: true, auto_repair: false }
+    }
+}
+
+impl IntegrityConfig {
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let flag = |key: &str, default: bool| {
+            var(key).map(|v| v.trim().eq_ignore_ascii_case("true")).unwrap_or(default)
+        };
+        let defaults = Self::default();
+        Self {
+            on_startup: flag("INTEGRITY_CHECK_ON_STARTUP", defaults.on_startup),
+            auto_repair: flag("INTEGRITY_AUTO_REPAIR", defaults.auto_repair),
+        }
+    }
+}
+
+static LAST_REPORT: OnceLock<RwLock<Option<IntegrityReport>>> = OnceLock::new();
+
+fn last_report_slot() -> &'static RwLock<Option<IntegrityReport>> {
+    LAST_REPORT.get_or_init(|| RwLock::new(None))
+}
+
+/// Report of the most recent run on this instance
+pub fn last_report() -> Option<IntegrityReport> {
+    last_report_slot().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Finding for `check`, or None when no rows violate it
+fn finding(check: &IntegrityCheck, count: i64, repaired: i64) -> Option<IntegrityFinding> {
+    (count > 0).then(|| IntegrityFinding {
+        check: check.name.to_string(),
+        description: check.description.to_string(),
+        count,
+        repairable: check.repairable,
+        repaired,
+    })
+}
+
+/// Run every check, deleting repairable violations when `repair` is set
+pub async fn run(pool: &PgPool, repair: bool) -> Result<IntegrityReport, AppError> {
+    let mut findings = Vec::new();
+    for check in integrity_queries::CHECKS {
+        let count = integrity_queries::count_violations(pool, check).await?;
+        let repaired = if repair && check.repairable && count > 0 {
+            integrity_queries::delete_violations(pool, check).await?
+        } else {
+            0
+        };
+        findings.extend(finding(check, count, repaired));
+    }
+
+    let report = IntegrityReport {
+        checked_at: clock::now(),
+        checks_run: integrity_queries::CHECKS.len(),
+        findings,
+        repair,
+    };
+    *last_report_slot().write().unwrap_or_else(|e| e.into_inner()) = Some(report.clone());
+    Ok(report)
+}
+
+/// Check in the background after migrations so startup isn't held up by the
+/// table scans
+pub fn spawn_startup_check(pool: PgPool) {
+    let config = IntegrityConfig::from_env();
+    if !config.on_startup {
+        info!("Startup integrity check disabled (INTEGRITY_CHECK_ON_STARTUP=false)");
+        return;
+    }
+    tokio::spawn(async move {
+        match run(&pool, config.auto_repair).await {
+            Ok(report) if report.findings.is_empty() => {
+                info!("Integrity check passed ({} checks)", report.checks_run)
+            }
+            Ok(report) => {
+                for f in &report.findings {
+                    warn!("Integrity check {}: {} rows ({}), {} repaired", f.check, f.count, f.description, f.repaired);
+                }
+                warn!(
+                    "Integrity check found {} outstanding rows; see GET /api/admin/integrity",
+                    report.outstanding()
+                );
+            }
+            Err(e) => error!("Integrity check failed: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_vars() {
+        assert_eq!(IntegrityConfig::from_vars(|_| None), IntegrityConfig::default());
+        let config = IntegrityConfig::from_vars(|key| match key {
+            "INTEGRITY_CHECK_ON_STARTUP" => Some("false".to_string()),
+            "INTEGRITY_AUTO_REPAIR" => Some("TRUE".to_string()),
+            _ => None,
+        });
+        assert_eq!(config, IntegrityConfig { on_startup: false, auto_repair: true });
+    }
+
+    #[test]
+    fn test_checks_only_repair_derived_rows() {
+        let mut names: Vec<&str> = integrity_queries::CHECKS.iter().map(|c| c.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), integrity_queries::CHECKS.len());
+
+        for check in integrity_queries::CHECKS {
+            let derived = ["cache", "snapshots", "history"].iter().any(|kind| check.table.contains(kind));
+            assert!(!check.repairable || derived, "{} would delete user data", check.name);
+        }
+        let negative = integrity_queries::CHECKS.iter().find(|c| c.name == "negative_snapshot_quantities").unwrap();
+        assert!(!negative.repairable);
+    }
+
+    #[test]
+    fn test_finding_skips_clean_checks() {
+        let check = &integrity_queries::CHECKS[0];
+        assert!(finding(check, 0, 0).is_none());
+        let report = IntegrityReport {
+            checked_at: clock::now(),
+            checks_run: 1,
+            findings: finding(check, 3, 1).into_iter().collect(),
+            repair: true,
+        };
+        assert_eq!(report.outstanding(), 2);
+    }
+}
@@ -1,40 +1,131 @@
+//! Scheduled background jobs on a Postgres-backed work queue.
+//!
+//! Every instance runs the cron ticker, but a tick only enqueues the due job
+//! into `job_queue`, keyed by the job name and tick minute, so the tick is
+//! queued once however many instances are running. Workers (`JOB_WORKER_COUNT`
+//! per instance, 0 for an enqueue-only instance) claim jobs with a lease they
+//! extend while the job runs, subject to per-job concurrency limits
+//! (`JOB_CONCURRENCY_LIMITS`, default 1). A job whose worker dies is claimed
+//! again once its lease expires, and only the current claim can complete it, so
+//! each tick of a cache population job completes exactly once. Failed jobs are
+//! retried with backoff up to `JOB_MAX_ATTEMPTS` times.
+
+use crate::db::job_queue_queries;
 use crate::errors::AppError;
+use crate::external::chain_provider::ChainProvider;
+use crate::external::dividend_provider::DividendProvider;
+use crate::external::fundamentals_provider::FundamentalsProvider;
+use crate::external::news::NewsProvider;
+use crate::external::fx_provider::FxProvider;
+use crate::external::etf_holdings_provider::EtfHoldingsProvider;
 use crate::external::price_provider::PriceProvider;
-use crate::jobs::{portfolio_risk_job, portfolio_correlations_job, daily_risk_snapshots_job, market_regime_update_job, hmm_training_job, regime_forecast_job, populate_optimization_cache_job, rolling_beta_cache_job, downside_risk_cache_job, watchlist_monitoring_job, populate_sentiment_cache_job};
+use crate::jobs::{portfolio_risk_job, portfolio_correlations_job, daily_risk_snapshots_job, market_regime_update_job, hmm_training_job, regime_forecast_job, populate_optimization_cache_job, rolling_beta_cache_job, downside_risk_cache_job, watchlist_monitoring_job, populate_sentiment_cache_job, market_breadth_job, holding_move_alert_job, peer_statistics_job, snapshot_retention_job, latency_budget_job, advisor_fee_job, crypto_wallet_sync_job, rate_limit_calibration_job, portfolio_valuation_job, notification_digest_job, goal_probability_job, factor_spread_job, etf_constituent_job, dividend_calendar_job, fx_rates_job, fundamentals_job, corporate_actions_job, price_backfill_job, factor_returns_job};
 use crate::services::failure_cache::FailureCache;
 use crate::services::rate_limiter::RateLimiter;
-use crate::services::llm_service::LlmService;
-use crate::services::news_service::NewsService;
 use sqlx::PgPool;
 use tokio_cron_scheduler::{JobScheduler, Job};
-use tracing::{info, error, warn};
+use tracing::{debug, field, info, info_span, error, warn, Instrument, Span};
 use chrono::Utc;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
+
+type JobFuture = Pin<Box<dyn Future<Output = Result<JobResult, AppError>> + Send>>;
+type JobHandler = Arc<dyn Fn(JobContext) -> JobFuture + Send + Sync>;
+
+/// Concurrent runs allowed for jobs without an entry in `JOB_CONCURRENCY_LIMITS`
+const DEFAULT_JOB_CONCURRENCY: i32 = 1;
+/// A failed job is retried after this many seconds times its attempt count
+const RETRY_DELAY_SECS: i64 = 60;
+/// Finished queue entries are deleted by the cache cleanup job after this long
+const QUEUE_RETENTION_DAYS: i32 = 14;
+
+/// Work queue settings, from the environment
+#[derive(Debug, Clone)]
+pub struct QueueSettings {
+    /// Workers claiming jobs on this instance
+    pub worker_count: usize,
+    /// Lease length of a claimed job; workers renew it at a third of this
+    pub visibility_secs: i64,
+    /// Idle workers check the queue this often
+    pub poll_interval: Duration,
+    pub max_attempts: i32,
+    /// Maximum concurrent runs per job name, across all instances
+    pub concurrency_limits: Vec<(String, i32)>,
+}
+
+impl QueueSettings {
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+            std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        }
+
+        Self {
+            worker_count: env_or("JOB_WORKER_COUNT", 2),
+            visibility_secs: env_or("JOB_VISIBILITY_TIMEOUT_SECS", 900_i64).max(30),
+            poll_interval: Duration::from_secs(env_or("JOB_POLL_INTERVAL_SECS", 5_u64).max(1)),
+            max_attempts: env_or("JOB_MAX_ATTEMPTS", 3_i32).max(1),
+            concurrency_limits: parse_concurrency_limits(&std::env::var("JOB_CONCURRENCY_LIMITS").unwrap_or_default()),
+        }
+    }
+}
+
+/// Parse "job_a=2,job_b=1"; malformed entries are skipped with a warning
+fn parse_concurrency_limits(spec: &str) -> Vec<(String, i32)> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry
+                .split_once('=')
+                .and_then(|(name, limit)| Some((name.trim().to_string(), limit.trim().parse::<i32>().ok()?)))
+                .filter(|(name, limit)| !name.is_empty() && *limit >= 1);
+            if parsed.is_none() {
+                warn!("Ignoring malformed JOB_CONCURRENCY_LIMITS entry '{}'", entry);
+            }
+            parsed
+        })
+        .collect()
+}
 
 // Context passed to job functions
 #[derive(Clone)]
 pub struct JobContext {
     pub pool: Arc<PgPool>,
     pub price_provider: Arc<dyn PriceProvider>,
+    pub chain_provider: Arc<dyn ChainProvider>,
+    pub etf_holdings_provider: Arc<dyn EtfHoldingsProvider>,
+    pub dividend_provider: Arc<dyn DividendProvider>,
+    /// `None` without a fundamentals API key
+    pub fundamentals_provider: Option<Arc<dyn FundamentalsProvider>>,
+    /// `None` without a news provider key
+    pub news_provider: Option<Arc<dyn NewsProvider>>,
+    pub fx_provider: Arc<dyn FxProvider>,
     pub failure_cache: Arc<FailureCache>,
     pub rate_limiter: Arc<RateLimiter>,
-    pub news_service: Arc<NewsService>,
-    pub llm_service: Arc<LlmService>,
 }
 
 pub struct JobSchedulerService {
     scheduler: JobScheduler,
     context: JobContext,
+    handlers: HashMap<&'static str, JobHandler>,
+    settings: QueueSettings,
 }
 
 impl JobSchedulerService {
     pub async fn new(
         pool: Arc<PgPool>,
         price_provider: Arc<dyn PriceProvider>,
+        chain_provider: Arc<dyn ChainProvider>,
+        etf_holdings_provider: Arc<dyn EtfHoldingsProvider>,
+        dividend_provider: Arc<dyn DividendProvider>,
+        fundamentals_provider: Option<Arc<dyn FundamentalsProvider>>,
+        news_provider: Option<Arc<dyn NewsProvider>>,
+        fx_provider: Arc<dyn FxProvider>,
         failure_cache: Arc<FailureCache>,
         rate_limiter: Arc<RateLimiter>,
-        news_service: Arc<NewsService>,
-        llm_service: Arc<LlmService>,
     ) -> Result<Self, AppError> {
         let scheduler = JobScheduler::new()
             .await
@@ -43,21 +134,27 @@ impl JobSchedulerService {
         let context = JobContext {
             pool,
             price_provider,
+            chain_provider,
+            etf_holdings_provider,
+            dividend_provider,
+            fundamentals_provider,
+            news_provider,
+            fx_provider,
             failure_cache,
             rate_limiter,
-            news_service,
-            llm_service,
         };
 
         Ok(Self {
             scheduler,
             context,
+            handlers: HashMap::new(),
+            settings: QueueSettings::from_env(),
         })
     }
 
     /// Start all scheduled jobs
     pub async fn start(&mut self) -> Result<(), AppError> {
-        info!("🚀 Starting job scheduler...");
+        info!("Starting job scheduler...");
 
         // Check if we're in test mode (runs jobs every minute for testing)
         let test_mode = std::env::var("JOB_SCHEDULER_TEST_MODE")
@@ -66,7 +163,7 @@ impl JobSchedulerService {
             .unwrap_or(false);
 
         if test_mode {
-            info!("⚠️  JOB SCHEDULER IN TEST MODE - Jobs will run every minute!");
+            info!("JOB SCHEDULER IN TEST MODE - Jobs will run every minute!");
         }
 
         // Nightly jobs (format: sec min hour day month weekday)
@@ -149,6 +246,28 @@ impl JobSchedulerService {
             market_regime_update_job::update_market_regime
         ).await?;
 
+        self.schedule_job(
+            "0 10 17 * * *",
+            "update_market_breadth",
+            "Daily at 5:10 PM ET",
+            market_breadth_job::update_market_breadth
+        ).await?;
+
+        // Factor spreads - daily after breadth, once closes are in
+        self.schedule_job(
+            "0 15 17 * * *",
+            "update_factor_spreads",
+            "Daily at 5:15 PM ET",
+            factor_spread_job::update_factor_spreads
+        ).await?;
+
+        self.schedule_job(
+            "0 20 17 * * *",
+            "refresh_peer_statistics",
+            "Daily at 5:20 PM ET",
+            peer_statistics_job::refresh_peer_statistics
+        ).await?;
+
         // HMM training job - monthly
         self.schedule_job(
             "0 0 0 1 * *",
@@ -205,6 +324,14 @@ impl JobSchedulerService {
             watchlist_monitoring_job::run_watchlist_monitoring
         ).await?;
 
+        // Holding move alerts - every 15 minutes during market hours (UTC, weekdays)
+        self.schedule_job(
+            "0 */15 14-21 * * MON-FRI",
+            "holding_move_alerts",
+            "Every 15 minutes during market hours",
+            holding_move_alert_job::run_holding_move_alerts
+        ).await?;
+
         // Weekly jobs (SUN = Sunday)
         let cleanup_schedule = if test_mode { "0 */3 * * * *" } else { "0 0 3 * * SUN" };
         let cleanup_desc = if test_mode { "Every 3 minutes (TEST MODE)" } else { "Every Sunday at 3:00 AM" };
@@ -218,9 +345,123 @@ impl JobSchedulerService {
 
         self.schedule_job(
             "0 30 3 * * SUN",
-            "archive_snapshots",
+            "compact_snapshots",
             "Every Sunday at 3:30 AM",
-            archive_old_snapshots
+            snapshot_retention_job::compact_snapshots
+        ).await?;
+
+        // ETF constituents - weekly, funds rebalance slowly
+        self.schedule_job(
+            "0 0 5 * * SUN",
+            "refresh_etf_constituents",
+            "Every Sunday at 5:00 AM",
+            etf_constituent_job::refresh_etf_constituents
+        ).await?;
+
+        self.schedule_job(
+            "0 */5 * * * *",
+            "check_latency_budgets",
+            "Every 5 minutes",
+            latency_budget_job::check_latency_budgets
+        ).await?;
+
+        // Portfolio valuations - daily after the close, so value history has a
+        // point per trading day between statement imports
+        self.schedule_job(
+            "0 25 17 * * *",
+            "record_portfolio_valuations",
+            "Daily at 5:25 PM ET",
+            portfolio_valuation_job::record_portfolio_valuations
+        ).await?;
+
+        // Exchange rates - daily after the ECB publishes, before the evening
+        // snapshots value portfolios in their base currency
+        self.schedule_job(
+            "0 50 16 * * *",
+            "refresh_fx_rates",
+            "Daily at 4:50 PM ET",
+            fx_rates_job::refresh_fx_rates
+        ).await?;
+
+        // Goal probabilities - nightly, after valuations and account fees so
+        // linked survey assets pick up the day's values
+        self.schedule_job(
+            "0 40 17 * * *",
+            "recalculate_goal_probabilities",
+            "Daily at 5:40 PM ET",
+            goal_probability_job::recalculate_goal_probabilities
+        ).await?;
+
+        // Dividend calendar - daily before the digests, which list upcoming ex-dates
+        self.schedule_job(
+            "0 30 6 * * *",
+            "refresh_dividend_calendar",
+            "Daily at 6:30 AM",
+            dividend_calendar_job::refresh_dividend_calendar
+        ).await?;
+
+        // Fundamentals - daily before the dividend calendar and the market open
+        self.schedule_job(
+            "0 0 6 * * *",
+            "refresh_fundamentals",
+            "Daily at 6:00 AM",
+            fundamentals_job::refresh_fundamentals
+        ).await?;
+
+        // Corporate actions - daily before fundamentals, so risk runs on adjusted closes
+        self.schedule_job(
+            "0 30 5 * * *",
+            "refresh_corporate_actions",
+            "Daily at 5:30 AM",
+            corporate_actions_job::refresh_corporate_actions
+        ).await?;
+
+        // Price backfill - nightly after the price refresh, a few tickers per run
+        self.schedule_job(
+            "0 15 3 * * *",
+            "backfill_price_history",
+            "Daily at 3:15 AM",
+            price_backfill_job::backfill_price_history
+        ).await?;
+
+        // Factor returns - weekly; the library publishes monthly with a lag
+        self.schedule_job(
+            "0 0 7 * * SAT",
+            "refresh_factor_returns",
+            "Every Saturday at 7:00 AM",
+            factor_returns_job::refresh_factor_returns
+        ).await?;
+
+        // Notification digests - hourly, so each user's goes out in their local morning
+        self.schedule_job(
+            "0 5 * * * *",
+            "send_notification_digests",
+            "Every hour at :05",
+            notification_digest_job::send_notification_digests
+        ).await?;
+
+        // Account fees - daily, after the snapshot-based value history is updated
+        self.schedule_job(
+            "0 30 17 * * *",
+            "generate_account_fees",
+            "Daily at 5:30 PM ET",
+            advisor_fee_job::generate_account_fees
+        ).await?;
+
+        // Crypto wallets - every 4 hours at :20
+        self.schedule_job(
+            "0 20 */4 * * *",
+            "sync_crypto_wallets",
+            "Every 4 hours at :20",
+            crypto_wallet_sync_job::sync_crypto_wallets
+        ).await?;
+
+        // Rate limiter budget - every 15 minutes from the provider's reported quota
+        self.schedule_job(
+            "0 */15 * * * *",
+            "calibrate_rate_limits",
+            "Every 15 minutes",
+            rate_limit_calibration_job::calibrate_rate_limits
         ).await?;
 
         // Start the scheduler
@@ -228,22 +469,34 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::External(format!("Failed to start scheduler: {}", e)))?;
 
-        info!("✅ Job scheduler started successfully with 17 jobs");
+        info!("Job scheduler started successfully with 26 jobs");
+
+        let handlers = Arc::new(self.handlers.clone());
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string());
+        for index in 0..self.settings.worker_count {
+            let worker = format!("{}:{}:{}", host, std::process::id(), index);
+            tokio::spawn(run_worker(worker, self.context.clone(), handlers.clone(), self.settings.clone()));
+        }
+        info!(
+            "Started {} job queue workers (lease {}s, limits {:?}, default {})",
+            self.settings.worker_count, self.settings.visibility_secs, self.settings.concurrency_limits,
+            DEFAULT_JOB_CONCURRENCY
+        );
         Ok(())
     }
 
     /// Stop the scheduler gracefully
     #[allow(dead_code)]
     pub async fn stop(&mut self) -> Result<(), AppError> {
-        info!("🛑 Stopping job scheduler...");
+        info!("Stopping job scheduler...");
         self.scheduler.shutdown()
             .await
             .map_err(|e| AppError::External(format!("Failed to stop scheduler: {}", e)))?;
-        info!("✅ Job scheduler stopped");
+        info!("Job scheduler stopped");
         Ok(())
     }
 
-    /// Helper to schedule a job with tracking
+    /// Register a job handler and enqueue the job on its cron schedule
     async fn schedule_job<F, Fut>(
         &mut self,
         schedule: &str,
@@ -255,14 +508,22 @@ impl JobSchedulerService {
         F: Fn(JobContext) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = Result<JobResult, AppError>> + Send + 'static,
     {
-        let context = self.context.clone();
-        let job_fn = Arc::new(job_fn);
+        let handler: JobHandler = Arc::new(move |ctx| Box::pin(job_fn(ctx)));
+        self.handlers.insert(job_name, handler);
+
+        let pool = self.context.pool.clone();
+        let max_attempts = self.settings.max_attempts;
 
         let job = Job::new_async(schedule, move |_uuid, _l| {
-            let context = context.clone();
-            let job_fn = job_fn.clone();
+            let pool = pool.clone();
             Box::pin(async move {
-                execute_job_with_tracking(&context.pool, job_name, context.clone(), job_fn).await;
+                // Every instance ticks; keying on the minute queues the tick once
+                let tick = Utc::now().format("%Y-%m-%dT%H:%M").to_string();
+                match job_queue_queries::enqueue(&pool, job_name, &tick, max_attempts).await {
+                    Ok(Some(id)) => info!("Queued job {} for {} (queue id {})", job_name, tick, id),
+                    Ok(None) => debug!("Job {} for {} was already queued", job_name, tick),
+                    Err(e) => error!("Failed to queue job {}: {}", job_name, e),
+                }
             })
         })
         .map_err(|e| AppError::External(format!("Failed to create job {}: {}", job_name, e)))?;
@@ -271,30 +532,120 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::External(format!("Failed to add job {}: {}", job_name, e)))?;
 
-        info!("📅 Scheduled: {} - {} [cron: {}]", job_name, description, schedule);
+        info!("Scheduled: {} - {} [cron: {}]", job_name, description, schedule);
         Ok(())
     }
 }
 
-// Job tracking wrapper
-async fn execute_job_with_tracking<F, Fut>(
+/// Claim and run queued jobs until the process exits
+async fn run_worker(
+    worker: String,
+    context: JobContext,
+    handlers: Arc<HashMap<&'static str, JobHandler>>,
+    settings: QueueSettings,
+) {
+    loop {
+        let claimed = job_queue_queries::claim(
+            &context.pool,
+            &worker,
+            &settings.concurrency_limits,
+            DEFAULT_JOB_CONCURRENCY,
+            settings.visibility_secs,
+        )
+        .await;
+
+        match claimed {
+            Ok(Some(job)) => run_claimed_job(&worker, job, &context, &handlers, &settings).await,
+            Ok(None) => tokio::time::sleep(settings.poll_interval).await,
+            Err(e) => {
+                error!("Job worker {} failed to claim a job: {}", worker, e);
+                tokio::time::sleep(settings.poll_interval).await;
+            }
+        }
+    }
+}
+
+async fn run_claimed_job(
+    worker: &str,
+    job: crate::models::QueuedJob,
+    context: &JobContext,
+    handlers: &HashMap<&'static str, JobHandler>,
+    settings: &QueueSettings,
+) {
+    let Some(token) = job.claim_token else {
+        return;
+    };
+    let retry_delay = RETRY_DELAY_SECS * job.attempts as i64;
+
+    let Some(handler) = handlers.get(job.job_name.as_str()).cloned() else {
+        warn!("Worker {} has no handler for job {}", worker, job.job_name);
+        if let Err(e) = job_queue_queries::fail(&context.pool, job.id, token, "No handler registered for job", retry_delay).await {
+            error!("Failed to release job {}: {}", job.id, e);
+        }
+        return;
+    };
+
+    info!(
+        "Worker {} claimed job {} (queue id {}, attempt {}/{})",
+        worker, job.job_name, job.id, job.attempts, job.max_attempts
+    );
+
+    // Keep the lease alive while the job runs
+    let heartbeat = {
+        let pool = context.pool.clone();
+        let visibility_secs = settings.visibility_secs;
+        let job_id = job.id;
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs((visibility_secs / 3) as u64)).await;
+                match job_queue_queries::extend_lease(&pool, job_id, token, visibility_secs).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        warn!("Lost the lease on queue job {}", job_id);
+                        break;
+                    }
+                    Err(e) => warn!("Failed to extend lease on queue job {}: {}", job_id, e),
+                }
+            }
+        })
+    };
+
+    let span = info_span!("job", job_name = %job.job_name, queue_job_id = job.id, job_run_id = field::Empty);
+    let outcome = execute_job_with_tracking(&context.pool, &job.job_name, context.clone(), handler)
+        .instrument(span)
+        .await;
+    heartbeat.abort();
+
+    let acked = match outcome {
+        Ok(()) => job_queue_queries::complete(&context.pool, job.id, token).await.map(|done| done.then(|| "succeeded".to_string())),
+        Err(message) => job_queue_queries::fail(&context.pool, job.id, token, &message, retry_delay).await,
+    };
+    match acked {
+        Ok(Some(status)) => info!("Queue job {} ({}) {}", job.id, job.job_name, status),
+        Ok(None) => warn!("Queue job {} ({}) was claimed by another worker before it finished", job.id, job.job_name),
+        Err(e) => error!("Failed to record queue job {} outcome: {}", job.id, e),
+    }
+}
+
+// Job tracking wrapper; returns the error message of a failed job
+async fn execute_job_with_tracking(
     pool: &PgPool,
     job_name: &str,
     context: JobContext,
-    job_fn: Arc<F>,
-) where
-    F: Fn(JobContext) -> Fut,
-    Fut: std::future::Future<Output = Result<JobResult, AppError>>,
-{
-    info!("🏃 Starting job: {}", job_name);
+    job_fn: JobHandler,
+) -> Result<(), String> {
+    info!("Starting job: {}", job_name);
     let started_at = Utc::now();
 
     // Record job start
     let job_id = match record_job_start(pool, job_name).await {
-        Ok(id) => id,
+        Ok(id) => {
+            Span::current().record("job_run_id", id);
+            id
+        }
         Err(e) => {
             error!("Failed to record job start: {}", e);
-            return;
+            return Err(format!("Failed to record job start: {}", e));
         }
     };
 
@@ -307,8 +658,10 @@ async fn execute_job_with_tracking<F, Fut>(
     match result {
         Ok(job_result) => {
             info!(
-                "✅ Job completed: {} (processed: {}, failed: {}, duration: {}ms)",
-                job_name, job_result.items_processed, job_result.items_failed, duration_ms
+                items_processed = job_result.items_processed,
+                items_failed = job_result.items_failed,
+                duration_ms,
+                "Job completed: {}", job_name
             );
 
             if let Err(e) = record_job_success(
@@ -320,13 +673,15 @@ async fn execute_job_with_tracking<F, Fut>(
             ).await {
                 error!("Failed to record job success: {}", e);
             }
+            Ok(())
         }
         Err(e) => {
-            error!("❌ Job failed: {} - {}", job_name, e);
+            error!(duration_ms, error = %e, "Job failed: {}", job_name);
 
             if let Err(e) = record_job_failure(pool, job_id, &e.to_string(), duration_ms).await {
                 error!("Failed to record job failure: {}", e);
             }
+            Err(e.to_string())
         }
     }
 }
@@ -408,43 +763,43 @@ async fn record_job_failure(
 
 // Job implementation functions
 pub async fn refresh_all_prices(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("💰 Refreshing all prices...");
+    info!("Refreshing all prices...");
 
     // Get all unique tickers from positions
     let tickers = sqlx::query!("SELECT DISTINCT ticker FROM positions")
         .fetch_all(ctx.pool.as_ref())
         .await?;
 
+    let tickers: Vec<String> = tickers.into_iter().map(|record| record.ticker).collect();
+
     let mut processed = 0;
     let mut failed = 0;
 
-    for record in tickers {
-        match crate::services::price_service::refresh_from_api(
-            ctx.pool.as_ref(),
-            ctx.price_provider.as_ref(),
-            &record.ticker,
-            &ctx.failure_cache,
-            ctx.rate_limiter.as_ref(),
-        ).await {
+    // Fetched concurrently within the rate limiter's budget
+    for (ticker, result) in crate::services::price_service::refresh_many_from_api(
+        ctx.pool.as_ref(),
+        ctx.price_provider.as_ref(),
+        &tickers,
+        &ctx.failure_cache,
+        ctx.rate_limiter.as_ref(),
+    ).await {
+        match result {
             Ok(_) => {
                 processed += 1;
-                info!("✅ Refreshed prices for {}", record.ticker);
+                info!("Refreshed prices for {}", ticker);
             }
             Err(e) => {
                 failed += 1;
-                warn!("❌ Failed to refresh prices for {}: {}", record.ticker, e);
+                warn!("Failed to refresh prices for {}: {}", ticker, e);
             }
         }
-
-        // Small delay to avoid rate limiting
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
     }
 
     Ok(JobResult { items_processed: processed, items_failed: failed })
 }
 
 pub async fn fetch_all_news(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("📰 Fetching all news...");
+    info!("Fetching all news...");
 
     // Clear all news cache to force fresh fetch on next request
     let result = sqlx::query!("DELETE FROM portfolio_news_cache")
@@ -452,13 +807,13 @@ pub async fn fetch_all_news(ctx: JobContext) -> Result<JobResult, AppError> {
         .await?;
 
     let processed = result.rows_affected() as i32;
-    info!("🗑️ Cleared {} news cache entries", processed);
+    info!("Cleared {} news cache entries", processed);
 
     Ok(JobResult { items_processed: processed, items_failed: 0 })
 }
 
 pub async fn generate_all_forecasts(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🔮 Generating all forecasts...");
+    info!("Generating all forecasts...");
 
     // Get popular tickers (top 20 by position count)
     let tickers = sqlx::query!(
@@ -485,7 +840,7 @@ pub async fn generate_all_forecasts(ctx: JobContext) -> Result<JobResult, AppErr
         .await;
 
         processed += 1;
-        info!("🗑️ Cleared forecast cache for {}", record.ticker);
+        info!("Cleared forecast cache for {}", record.ticker);
 
         tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
     }
@@ -494,7 +849,7 @@ pub async fn generate_all_forecasts(ctx: JobContext) -> Result<JobResult, AppErr
 }
 
 pub async fn analyze_all_sec_filings(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("📄 Analyzing SEC filings...");
+    info!("Analyzing SEC filings...");
 
     // Get top 20 tickers
     let tickers = sqlx::query!("SELECT DISTINCT ticker FROM positions LIMIT 20")
@@ -513,7 +868,7 @@ pub async fn analyze_all_sec_filings(ctx: JobContext) -> Result<JobResult, AppEr
         .await;
 
         processed += 1;
-        info!("🗑️ Cleared SEC analysis cache for {}", record.ticker);
+        info!("Cleared SEC analysis cache for {}", record.ticker);
 
         tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
     }
@@ -522,10 +877,15 @@ pub async fn analyze_all_sec_filings(ctx: JobContext) -> Result<JobResult, AppEr
 }
 
 pub async fn check_all_thresholds(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("⚠️ Checking thresholds...");
+    info!("Checking thresholds...");
 
     // Get all portfolios with threshold settings
-    let portfolios = sqlx::query!("SELECT DISTINCT portfolio_id FROM risk_threshold_settings")
+    let portfolios = sqlx::query!(
+        "SELECT DISTINCT rts.portfolio_id
+         FROM risk_threshold_settings rts
+         JOIN portfolios p ON p.id = rts.portfolio_id
+         WHERE p.archived_at IS NULL"
+    )
         .fetch_all(ctx.pool.as_ref())
         .await?;
 
@@ -539,7 +899,7 @@ pub async fn check_all_thresholds(ctx: JobContext) -> Result<JobResult, AppError
 }
 
 pub async fn warm_popular_caches(_ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🔥 Warming popular caches...");
+    info!("Warming popular caches...");
 
     // Nothing to pre-warm yet, caches fill on-demand
     // This job is a placeholder for future optimization
@@ -548,7 +908,7 @@ pub async fn warm_popular_caches(_ctx: JobContext) -> Result<JobResult, AppError
 }
 
 pub async fn cleanup_expired_caches(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🧹 Cleaning up expired caches...");
+    info!("Cleaning up expired caches...");
 
     let mut processed = 0;
 
@@ -569,38 +929,39 @@ pub async fn cleanup_expired_caches(ctx: JobContext) -> Result<JobResult, AppErr
         .await?;
 
         processed += result.rows_affected() as i32;
-        info!("🗑️ Deleted {} expired rows from {}", result.rows_affected(), table);
+        info!("Deleted {} expired rows from {}", result.rows_affected(), table);
     }
 
-    Ok(JobResult { items_processed: processed, items_failed: 0 })
-}
-
-pub async fn archive_old_snapshots(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("📦 Archiving old snapshots...");
-
-    // Delete risk snapshots older than 1 year
-    let result = sqlx::query!(
-        "DELETE FROM risk_snapshots WHERE snapshot_date < NOW() - INTERVAL '1 year'"
-    )
-    .execute(ctx.pool.as_ref())
-    .await?;
-
-    info!("📦 Archived {} old snapshots", result.rows_affected());
+    let pruned = job_queue_queries::prune_finished(ctx.pool.as_ref(), QUEUE_RETENTION_DAYS).await?;
+    processed += pruned as i32;
+    info!("Deleted {} finished job queue entries", pruned);
 
-    Ok(JobResult {
-        items_processed: result.rows_affected() as i32,
-        items_failed: 0,
-    })
+    Ok(JobResult { items_processed: processed, items_failed: 0 })
 }
 
 pub async fn train_hmm_wrapper(ctx: JobContext) -> Result<JobResult, AppError> {
-    info!("🧠 Training HMM model...");
+    info!("Training HMM model...");
 
     // Run the HMM training job
-    hmm_training_job::run_hmm_training_job(ctx.pool.as_ref()).await?;
+    hmm_training_job::run_hmm_training_job(ctx.pool.as_ref(), ctx.price_provider.as_ref()).await?;
 
     Ok(JobResult {
         items_processed: 1,
         items_failed: 0,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_concurrency_limits() {
+        let limits = parse_concurrency_limits(" warm_caches=2, refresh_prices = 1,bad,zero=0,=3,");
+        assert_eq!(
+            limits,
+            vec![("warm_caches".to_string(), 2), ("refresh_prices".to_string(), 1)]
+        );
+        assert!(parse_concurrency_limits("").is_empty());
+    }
+}
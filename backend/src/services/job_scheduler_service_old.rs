@@ -21,7 +21,7 @@ impl JobSchedulerService {
 
     /// Start all scheduled jobs
     pub async fn start(&self) -> Result<(), AppError> {
-        info!("🚀 Starting job scheduler...");
+        info!("Starting job scheduler...");
 
         // Nightly jobs
         self.schedule_nightly_price_refresh().await?;
@@ -42,17 +42,17 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::External(format!("Failed to start scheduler: {}", e)))?;
 
-        info!("✅ Job scheduler started successfully with 8 jobs");
+        info!("Job scheduler started successfully with 8 jobs");
         Ok(())
     }
 
     /// Stop the scheduler gracefully
     pub async fn stop(&self) -> Result<(), AppError> {
-        info!("🛑 Stopping job scheduler...");
+        info!("Stopping job scheduler...");
         self.scheduler.shutdown()
             .await
             .map_err(|e| AppError::External(format!("Failed to stop scheduler: {}", e)))?;
-        info!("✅ Job scheduler stopped");
+        info!("Job scheduler stopped");
         Ok(())
     }
 
@@ -74,7 +74,7 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::External(format!("Failed to add job: {}", e)))?;
 
-        info!("📅 Scheduled: refresh_prices (daily 2:00 AM UTC)");
+        info!("Scheduled: refresh_prices (daily 2:00 AM UTC)");
         Ok(())
     }
 
@@ -95,7 +95,7 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::Internal(format!("Failed to add job: {}", e)))?;
 
-        info!("📅 Scheduled: fetch_news (daily 2:30 AM UTC)");
+        info!("Scheduled: fetch_news (daily 2:30 AM UTC)");
         Ok(())
     }
 
@@ -116,7 +116,7 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::Internal(format!("Failed to add job: {}", e)))?;
 
-        info!("📅 Scheduled: generate_forecasts (daily 4:00 AM UTC)");
+        info!("Scheduled: generate_forecasts (daily 4:00 AM UTC)");
         Ok(())
     }
 
@@ -137,7 +137,7 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::Internal(format!("Failed to add job: {}", e)))?;
 
-        info!("📅 Scheduled: analyze_sec_filings (daily 4:30 AM UTC)");
+        info!("Scheduled: analyze_sec_filings (daily 4:30 AM UTC)");
         Ok(())
     }
 
@@ -158,7 +158,7 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::Internal(format!("Failed to add job: {}", e)))?;
 
-        info!("📅 Scheduled: check_thresholds (hourly)");
+        info!("Scheduled: check_thresholds (hourly)");
         Ok(())
     }
 
@@ -179,7 +179,7 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::Internal(format!("Failed to add job: {}", e)))?;
 
-        info!("📅 Scheduled: warm_caches (hourly at :30)");
+        info!("Scheduled: warm_caches (hourly at :30)");
         Ok(())
     }
 
@@ -200,7 +200,7 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::Internal(format!("Failed to add job: {}", e)))?;
 
-        info!("📅 Scheduled: cleanup_cache (weekly Sunday 3:00 AM UTC)");
+        info!("Scheduled: cleanup_cache (weekly Sunday 3:00 AM UTC)");
         Ok(())
     }
 
@@ -221,7 +221,7 @@ impl JobSchedulerService {
             .await
             .map_err(|e| AppError::Internal(format!("Failed to add job: {}", e)))?;
 
-        info!("📅 Scheduled: archive_snapshots (weekly Sunday 3:30 AM UTC)");
+        info!("Scheduled: archive_snapshots (weekly Sunday 3:30 AM UTC)");
         Ok(())
     }
 }
@@ -235,7 +235,7 @@ async fn run_job_with_tracking<F, Fut>(
     F: FnOnce() -> Fut,
     Fut: std::future::Future<Output = Result<JobResult, AppError>>,
 {
-    info!("🏃 Starting job: {}", job_name);
+    info!("Starting job: {}", job_name);
     let started_at = Utc::now();
 
     // Record job start
@@ -256,7 +256,7 @@ async fn run_job_with_tracking<F, Fut>(
     match result {
         Ok(job_result) => {
             info!(
-                "✅ Job completed: {} (processed: {}, failed: {}, duration: {}ms)",
+                "Job completed: {} (processed: {}, failed: {}, duration: {}ms)",
                 job_name, job_result.items_processed, job_result.items_failed, duration_ms
             );
 
@@ -271,7 +271,7 @@ async fn run_job_with_tracking<F, Fut>(
             }
         }
         Err(e) => {
-            error!("❌ Job failed: {} - {}", job_name, e);
+            error!("Job failed: {} - {}", job_name, e);
 
             if let Err(e) = record_job_failure(pool, job_id, &e.to_string(), duration_ms).await {
                 error!("Failed to record job failure: {}", e);
@@ -357,7 +357,7 @@ async fn record_job_failure(
 
 // Job implementation functions
 async fn refresh_all_prices(pool: &PgPool) -> Result<JobResult, AppError> {
-    info!("💰 Refreshing all prices...");
+    info!("Refreshing all prices...");
 
     // Get all unique tickers from positions
     let tickers = sqlx::query!("SELECT DISTINCT ticker FROM positions")
@@ -371,11 +371,11 @@ async fn refresh_all_prices(pool: &PgPool) -> Result<JobResult, AppError> {
         match crate::services::price_service::refresh_latest_price(pool, &record.ticker).await {
             Ok(_) => {
                 processed += 1;
-                info!("✅ Refreshed prices for {}", record.ticker);
+                info!("Refreshed prices for {}", record.ticker);
             }
             Err(e) => {
                 failed += 1;
-                warn!("❌ Failed to refresh prices for {}: {}", record.ticker, e);
+                warn!("Failed to refresh prices for {}: {}", record.ticker, e);
             }
         }
 
@@ -387,7 +387,7 @@ async fn refresh_all_prices(pool: &PgPool) -> Result<JobResult, AppError> {
 }
 
 async fn fetch_all_news(pool: &PgPool) -> Result<JobResult, AppError> {
-    info!("📰 Fetching all news...");
+    info!("Fetching all news...");
 
     // Get all unique tickers from positions
     let tickers = sqlx::query!("SELECT DISTINCT ticker FROM positions LIMIT 20")
@@ -407,7 +407,7 @@ async fn fetch_all_news(pool: &PgPool) -> Result<JobResult, AppError> {
         .await;
 
         processed += 1;
-        info!("🗑️ Cleared news cache for {}", record.ticker);
+        info!("Cleared news cache for {}", record.ticker);
 
         // Small delay
         tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
@@ -417,7 +417,7 @@ async fn fetch_all_news(pool: &PgPool) -> Result<JobResult, AppError> {
 }
 
 async fn generate_all_forecasts(pool: &PgPool) -> Result<JobResult, AppError> {
-    info!("🔮 Generating all forecasts...");
+    info!("Generating all forecasts...");
 
     // Get popular tickers (top 20 by position count)
     let tickers = sqlx::query!(
@@ -445,7 +445,7 @@ async fn generate_all_forecasts(pool: &PgPool) -> Result<JobResult, AppError> {
         .await;
 
         processed += 1;
-        info!("🗑️ Cleared forecast cache for {}", record.ticker);
+        info!("Cleared forecast cache for {}", record.ticker);
 
         tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
     }
@@ -454,7 +454,7 @@ async fn generate_all_forecasts(pool: &PgPool) -> Result<JobResult, AppError> {
 }
 
 async fn analyze_all_sec_filings(pool: &PgPool) -> Result<JobResult, AppError> {
-    info!("📄 Analyzing SEC filings...");
+    info!("Analyzing SEC filings...");
 
     // Get all unique tickers from positions
     let tickers = sqlx::query!("SELECT DISTINCT ticker FROM positions LIMIT 20")
@@ -474,7 +474,7 @@ async fn analyze_all_sec_filings(pool: &PgPool) -> Result<JobResult, AppError> {
         .await;
 
         processed += 1;
-        info!("🗑️ Cleared SEC analysis cache for {}", record.ticker);
+        info!("Cleared SEC analysis cache for {}", record.ticker);
 
         tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
     }
@@ -483,7 +483,7 @@ async fn analyze_all_sec_filings(pool: &PgPool) -> Result<JobResult, AppError> {
 }
 
 async fn check_all_thresholds(pool: &PgPool) -> Result<JobResult, AppError> {
-    info!("⚠️ Checking thresholds...");
+    info!("Checking thresholds...");
 
     // Get all portfolios with threshold settings
     let portfolios = sqlx::query!("SELECT DISTINCT portfolio_id FROM risk_threshold_settings")
@@ -500,7 +500,7 @@ async fn check_all_thresholds(pool: &PgPool) -> Result<JobResult, AppError> {
 }
 
 async fn warm_popular_caches(pool: &PgPool) -> Result<JobResult, AppError> {
-    info!("🔥 Warming popular caches...");
+    info!("Warming popular caches...");
 
     // Nothing to pre-warm yet, caches fill on-demand
     // This job is a placeholder for future optimization
@@ -509,7 +509,7 @@ async fn warm_popular_caches(pool: &PgPool) -> Result<JobResult, AppError> {
 }
 
 async fn cleanup_expired_caches(pool: &PgPool) -> Result<JobResult, AppError> {
-    info!("🧹 Cleaning up expired caches...");
+    info!("Cleaning up expired caches...");
 
     let mut processed = 0;
 
@@ -530,14 +530,14 @@ async fn cleanup_expired_caches(pool: &PgPool) -> Result<JobResult, AppError> {
         .await?;
 
         processed += result.rows_affected() as i32;
-        info!("🗑️ Deleted {} expired rows from {}", result.rows_affected(), table);
+        info!("Deleted {} expired rows from {}", result.rows_affected(), table);
     }
 
     Ok(JobResult { items_processed: processed, items_failed: 0 })
 }
 
 async fn archive_old_snapshots(pool: &PgPool) -> Result<JobResult, AppError> {
-    info!("📦 Archiving old snapshots...");
+    info!("Archiving old snapshots...");
 
     // Delete risk snapshots older than 1 year
     let result = sqlx::query!(
@@ -546,7 +546,7 @@ async fn archive_old_snapshots(pool: &PgPool) -> Result<JobResult, AppError> {
     .execute(pool)
     .await?;
 
-    info!("📦 Archived {} old snapshots", result.rows_affected());
+    info!("Archived {} old snapshots", result.rows_affected());
 
     Ok(JobResult {
         items_processed: result.rows_affected() as i32,
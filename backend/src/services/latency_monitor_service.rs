@@ -0,0 +1,426 @@
+//! Endpoint and query latency budgets.
+//!
+//! Every matched HTTP route is timed by the `record_latency` middleware, and
+//! every SQL statement by [`QueryTimingLayer`], which listens to the timing
+//! events sqlx emits for each query. Samples are buffered in memory and flushed
+//! to `latency_samples` by the latency budget job, which then recomputes p50/p95
+//! per endpoint and query over the trailing window and flags those whose p95
+//! exceeds their budget. Budgets come from `latency_budgets`, falling back to
+//! `ENDPOINT_LATENCY_BUDGET_MS` / `QUERY_LATENCY_BUDGET_MS`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{Duration as ChronoDuration, Utc};
+use sqlx::PgPool;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{info, warn, Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use crate::db::latency_queries;
+use crate::errors::AppError;
+use crate::models::{LatencyKind, LatencyOffender, LatencyReport, LatencyReportQuery, LatencySample, LatencyStat};
+
+/// Trailing window the statistics cover
+pub const STATS_WINDOW_HOURS: i64 = 24;
+/// Raw samples are kept this long for examples
+const SAMPLE_RETENTION_DAYS: i64 = 7;
+/// Names with fewer samples in the window get no statistics
+const MIN_SAMPLES: i64 = 5;
+/// Samples beyond this are dropped until the next flush
+const MAX_BUFFERED_SAMPLES: usize = 100_000;
+/// Normalized statements are cut to this many characters
+const MAX_QUERY_NAME_CHARS: usize = 200;
+
+const DEFAULT_ENDPOINT_BUDGET_MS: i32 = 1000;
+const DEFAULT_QUERY_BUDGET_MS: i32 = 250;
+
+static SAMPLES: Mutex<Vec<LatencySample>> = Mutex::new(Vec::new());
+
+fn record_sample(sample: LatencySample) {
+    if let Ok(mut samples) = SAMPLES.lock() {
+        if samples.len() < MAX_BUFFERED_SAMPLES {
+            samples.push(sample);
+        }
+    }
+}
+
+fn drain_samples() -> Vec<LatencySample> {
+    SAMPLES.lock().map(|mut samples| std::mem::take(&mut *samples)).unwrap_or_default()
+}
+
+/// Record the duration of one request to a matched route
+pub fn record_endpoint(method: &str, route: &str, status: u16, elapsed: Duration, request_id: Option<String>) {
+    record_sample(LatencySample {
+        kind: LatencyKind::Endpoint,
+        name: format!("{} {}", method, route),
+        duration_ms: elapsed.as_secs_f64() * 1000.0,
+        status: Some(status as i16),
+        request_id,
+        recorded_at: Utc::now(),
+    });
+}
+
+/// Collapse whitespace and cut the statement so the same query always gets
+/// the same name
+pub fn normalize_statement(sql: &str) -> String {
+    let collapsed = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    match collapsed.char_indices().nth(MAX_QUERY_NAME_CHARS) {
+        Some((end, _)) => format!("{}…", &collapsed[..end]),
+        None => collapsed,
+    }
+}
+
+/// Request ID of a `request` span, stored in the span's extensions
+struct SpanRequestId(String);
+
+#[derive(Default)]
+struct QueryEventVisitor {
+    summary: Option<String>,
+    statement: Option<String>,
+    elapsed_secs: Option<f64>,
+    request_id: Option<String>,
+}
+
+impl Visit for QueryEventVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "summary" => self.summary = Some(value.to_string()),
+            "db.statement" => self.statement = Some(value.to_string()),
+            "request_id" => self.request_id = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "request_id" {
+            self.request_id = Some(format!("{:?}", value).trim_matches('"').to_string());
+        }
+    }
+}
+
+/// Tracing layer turning sqlx's per-statement `sqlx::query` events into query
+/// latency samples, tagged with the request ID of the enclosing request span.
+/// Install it with a filter enabling `sqlx::query` at debug level.
+pub struct QueryTimingLayer;
+
+impl<S> Layer<S> for QueryTimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != "request" {
+            return;
+        }
+        let mut visitor = QueryEventVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(request_id), Some(span)) = (visitor.request_id, ctx.span(id)) {
+            span.extensions_mut().insert(SpanRequestId(request_id));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if event.metadata().target() != "sqlx::query" {
+            return;
+        }
+        let mut visitor = QueryEventVisitor::default();
+        event.record(&mut visitor);
+        let Some(elapsed_secs) = visitor.elapsed_secs else {
+            return;
+        };
+        // Short statements are logged whole as the summary, longer ones in db.statement
+        let sql = visitor
+            .statement
+            .filter(|s| !s.trim().is_empty())
+            .or(visitor.summary)
+            .unwrap_or_default();
+        let request_id = ctx.event_scope(event).and_then(|scope| {
+            scope
+                .from_root()
+                .find_map(|span| span.extensions().get::<SpanRequestId>().map(|id| id.0.clone()))
+        });
+
+        record_sample(LatencySample {
+            kind: LatencyKind::Query,
+            name: normalize_statement(&sql),
+            duration_ms: elapsed_secs * 1000.0,
+            status: None,
+            request_id,
+            recorded_at: Utc::now(),
+        });
+    }
+}
+
+/// Default budgets per kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetDefaults {
+    pub endpoint_ms: i32,
+    pub query_ms: i32,
+}
+
+impl Default for BudgetDefaults {
+    fn default() -> Self {
+        Self {
+            endpoint_ms: DEFAULT_ENDPOINT_BUDGET_MS,
+            query_ms: DEFAULT_QUERY_BUDGET_MS,
+        }
+    }
+}
+
+impl BudgetDefaults {
+    pub fn from_env() -> Self {
+        let budget = |key: &str, default: i32| {
+            std::env::var(key)
+                .ok()
+                .and_then(|s| s.parse::<i32>().ok())
+                .filter(|ms| *ms > 0)
+                .unwrap_or(default)
+        };
+        Self {
+            endpoint_ms: budget("ENDPOINT_LATENCY_BUDGET_MS", DEFAULT_ENDPOINT_BUDGET_MS),
+            query_ms: budget("QUERY_LATENCY_BUDGET_MS", DEFAULT_QUERY_BUDGET_MS),
+        }
+    }
+
+    fn for_kind(&self, kind: &str) -> i32 {
+        if kind == LatencyKind::Query.as_str() {
+            self.query_ms
+        } else {
+            self.endpoint_ms
+        }
+    }
+}
+
+/// Budget for one endpoint or query: its override, else the default for its kind
+pub fn resolve_budget(
+    kind: &str,
+    name: &str,
+    overrides: &HashMap<(String, String), i32>,
+    defaults: BudgetDefaults,
+) -> i32 {
+    overrides
+        .get(&(kind.to_string(), name.to_string()))
+        .copied()
+        .unwrap_or_else(|| defaults.for_kind(kind))
+}
+
+/// Outcome of one latency budget check
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyRefresh {
+    pub samples_flushed: u64,
+    pub endpoints_breaching: usize,
+    pub queries_breaching: usize,
+    pub new_breaches: usize,
+}
+
+/// Flush buffered samples, recompute statistics over the window and flag
+/// endpoints and queries whose p95 exceeds their budget
+pub async fn refresh_latency_stats(pool: &PgPool, defaults: BudgetDefaults) -> Result<LatencyRefresh, AppError> {
+    let samples = drain_samples();
+    let samples_flushed = latency_queries::insert_samples(pool, &samples).await?;
+    latency_queries::delete_samples_before(pool, Utc::now() - ChronoDuration::days(SAMPLE_RETENTION_DAYS)).await?;
+
+    let now = Utc::now();
+    let since = now - ChronoDuration::hours(STATS_WINDOW_HOURS);
+    let distributions = latency_queries::fetch_distributions(pool, since, MIN_SAMPLES).await?;
+    let overrides = latency_queries::fetch_budgets(pool).await?;
+    let previous: HashMap<(String, String), LatencyStat> = latency_queries::fetch_stats(pool)
+        .await?
+        .into_iter()
+        .map(|stat| ((stat.kind.clone(), stat.name.clone()), stat))
+        .collect();
+
+    let mut new_breaches = 0;
+    let stats: Vec<LatencyStat> = distributions
+        .into_iter()
+        .map(|d| {
+            let budget_ms = resolve_budget(&d.kind, &d.name, &overrides, defaults);
+            let breached = d.p95_ms > budget_ms as f64;
+            let was_breached = previous
+                .get(&(d.kind.clone(), d.name.clone()))
+                .filter(|stat| stat.breached)
+                .and_then(|stat| stat.breached_since);
+            let breached_since = match (breached, was_breached) {
+                (false, _) => None,
+                (true, Some(since)) => Some(since),
+                (true, None) => {
+                    new_breaches += 1;
+                    warn!(
+                        kind = %d.kind,
+                        name = %d.name,
+                        p95_ms = d.p95_ms,
+                        budget_ms,
+                        sample_count = d.sample_count,
+                        "Latency budget breached"
+                    );
+                    Some(now)
+                }
+            };
+            LatencyStat {
+                kind: d.kind,
+                name: d.name,
+                sample_count: d.sample_count,
+                p50_ms: d.p50_ms,
+                p95_ms: d.p95_ms,
+                max_ms: d.max_ms,
+                budget_ms,
+                breached,
+                breached_since,
+                computed_at: now,
+            }
+        })
+        .collect();
+
+    latency_queries::replace_stats(pool, &stats).await?;
+
+    let breaching = |kind: LatencyKind| stats.iter().filter(|s| s.breached && s.kind == kind.as_str()).count();
+    let refresh = LatencyRefresh {
+        samples_flushed,
+        endpoints_breaching: breaching(LatencyKind::Endpoint),
+        queries_breaching: breaching(LatencyKind::Query),
+        new_breaches,
+    };
+    info!(
+        samples_flushed = refresh.samples_flushed,
+        endpoints_breaching = refresh.endpoints_breaching,
+        queries_breaching = refresh.queries_breaching,
+        "Latency statistics refreshed"
+    );
+    Ok(refresh)
+}
+
+fn budget_used_pct(p95_ms: f64, budget_ms: i32) -> f64 {
+    p95_ms / budget_ms.max(1) as f64 * 100.0
+}
+
+/// Rank stats of one kind by budget usage, worst first
+fn rank_offenders(stats: &[LatencyStat], kind: LatencyKind, breached_only: bool, limit: usize) -> Vec<&LatencyStat> {
+    let mut ranked: Vec<&LatencyStat> = stats
+        .iter()
+        .filter(|s| s.kind == kind.as_str() && (!breached_only || s.breached))
+        .collect();
+    ranked.sort_by(|a, b| {
+        budget_used_pct(b.p95_ms, b.budget_ms)
+            .partial_cmp(&budget_used_pct(a.p95_ms, a.budget_ms))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Endpoints and queries using the most of their latency budget, with their
+/// slowest recent samples
+pub async fn latency_report(pool: &PgPool, query: &LatencyReportQuery) -> Result<LatencyReport, AppError> {
+    let limit = query.limit.unwrap_or(10).clamp(1, 100) as usize;
+    let examples = query.examples.unwrap_or(5).clamp(0, 50);
+    let stats = latency_queries::fetch_stats(pool).await?;
+    let since = Utc::now() - ChronoDuration::hours(STATS_WINDOW_HOURS);
+
+    let mut sections = Vec::with_capacity(2);
+    for kind in [LatencyKind::Endpoint, LatencyKind::Query] {
+        let mut offenders = Vec::new();
+        for stat in rank_offenders(&stats, kind, query.breached_only, limit) {
+            let samples = if examples > 0 {
+                latency_queries::fetch_slowest_samples(pool, &stat.kind, &stat.name, since, examples).await?
+            } else {
+                Vec::new()
+            };
+            offenders.push(LatencyOffender {
+                kind,
+                name: stat.name.clone(),
+                sample_count: stat.sample_count,
+                p50_ms: stat.p50_ms,
+                p95_ms: stat.p95_ms,
+                max_ms: stat.max_ms,
+                budget_ms: stat.budget_ms,
+                budget_used_pct: budget_used_pct(stat.p95_ms, stat.budget_ms),
+                breached: stat.breached,
+                breached_since: stat.breached_since,
+                examples: samples,
+            });
+        }
+        sections.push(offenders);
+    }
+    let queries = sections.pop().unwrap_or_default();
+    let endpoints = sections.pop().unwrap_or_default();
+
+    Ok(LatencyReport {
+        computed_at: stats.iter().map(|s| s.computed_at).max(),
+        window_hours: STATS_WINDOW_HOURS,
+        endpoints,
+        queries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(kind: LatencyKind, name: &str, p95_ms: f64, budget_ms: i32) -> LatencyStat {
+        LatencyStat {
+            kind: kind.as_str().to_string(),
+            name: name.to_string(),
+            sample_count: 20,
+            p50_ms: p95_ms / 2.0,
+            p95_ms,
+            max_ms: p95_ms * 1.5,
+            budget_ms,
+            breached: p95_ms > budget_ms as f64,
+            breached_since: None,
+            computed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_statement_collapses_whitespace() {
+        let sql = "SELECT id,\n       name\n  FROM portfolios\n WHERE user_id = $1";
+        assert_eq!(normalize_statement(sql), "SELECT id, name FROM portfolios WHERE user_id = $1");
+    }
+
+    #[test]
+    fn test_normalize_statement_truncates_long_queries() {
+        let sql = format!("SELECT {} FROM t", "a, ".repeat(200));
+        let name = normalize_statement(&sql);
+        assert_eq!(name.chars().count(), MAX_QUERY_NAME_CHARS + 1);
+        assert!(name.ends_with('…'));
+    }
+
+    #[test]
+    fn test_resolve_budget_prefers_override() {
+        let mut overrides = HashMap::new();
+        overrides.insert(("endpoint".to_string(), "GET /api/slow".to_string()), 5000);
+        let defaults = BudgetDefaults::default();
+
+        assert_eq!(resolve_budget("endpoint", "GET /api/slow", &overrides, defaults), 5000);
+        assert_eq!(resolve_budget("endpoint", "GET /api/fast", &overrides, defaults), DEFAULT_ENDPOINT_BUDGET_MS);
+        assert_eq!(resolve_budget("query", "SELECT 1", &overrides, defaults), DEFAULT_QUERY_BUDGET_MS);
+    }
+
+    #[test]
+    fn test_rank_offenders_orders_by_budget_usage() {
+        let stats = vec![
+            stat(LatencyKind::Endpoint, "GET /a", 900.0, 1000),
+            stat(LatencyKind::Endpoint, "GET /b", 4000.0, 3000),
+            stat(LatencyKind::Endpoint, "GET /c", 100.0, 1000),
+            stat(LatencyKind::Query, "SELECT 1", 2000.0, 250),
+        ];
+
+        let ranked = rank_offenders(&stats, LatencyKind::Endpoint, false, 2);
+        let names: Vec<&str> = ranked.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["GET /b", "GET /a"]);
+
+        let breached = rank_offenders(&stats, LatencyKind::Endpoint, true, 10);
+        assert_eq!(breached.len(), 1);
+        assert_eq!(breached[0].name, "GET /b");
+    }
+}
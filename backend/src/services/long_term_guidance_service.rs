@@ -1,20 +1,33 @@
-use sqlx::PgPool;
+use bigdecimal::ToPrimitive;
+use chrono::Duration;
 use tracing::warn;
 use uuid::Uuid;
 
-use crate::db;
+use crate::db::analytics_queries::AllocationRow;
+use crate::db::holding_snapshot_queries::HoldingDetail;
+use crate::external::fundamentals_provider::ExternalFundamentals;
 use crate::models::long_term_guidance::*;
-use crate::services::price_service;
+use crate::models::DividendPayment;
+use crate::repositories::Repositories;
+use crate::services::{clock, fundamentals_service};
+
+/// Reported fundamentals of a ticker; empty for one never fetched
+#[derive(Default)]
+struct ReportedFundamentals {
+    ratios: Option<ExternalFundamentals>,
+    /// Last two years, most recent first
+    dividends: Vec<DividendPayment>,
+}
 
 /// Service for computing long-term investment quality scores and recommendations
 pub struct LongTermGuidanceService {
-    pool: PgPool,
+    repos: Repositories,
     risk_free_rate: f64,
 }
 
 impl LongTermGuidanceService {
-    pub fn new(pool: PgPool, risk_free_rate: f64) -> Self {
-        Self { pool, risk_free_rate }
+    pub fn new(repos: Repositories, risk_free_rate: f64) -> Self {
+        Self { repos, risk_free_rate }
     }
 
     /// Generate long-term guidance for a portfolio
@@ -27,7 +40,7 @@ impl LongTermGuidanceService {
         min_quality: Option<f64>,
     ) -> Result<LongTermGuidanceResponse, String> {
         // 1. Fetch current holdings for the portfolio
-        let allocations = db::analytics_queries::fetch_allocations_at_latest_date(&self.pool, portfolio_id)
+        let allocations = self.repos.holdings.latest_allocations(portfolio_id)
             .await
             .map_err(|e| format!("Failed to fetch portfolio allocations: {}", e))?;
 
@@ -36,7 +49,9 @@ impl LongTermGuidanceService {
         }
 
         // Fetch holding details (names, industries) from latest snapshot
-        let holding_details = self.fetch_holding_details(portfolio_id).await?;
+        let holding_details = self.repos.holdings.latest_holding_details(portfolio_id)
+            .await
+            .map_err(|e| format!("Failed to fetch holding details: {}", e))?;
 
         let total_value: f64 = allocations.iter().map(|a| a.value).sum();
 
@@ -110,7 +125,7 @@ impl LongTermGuidanceService {
         industry: Option<&str>,
     ) -> Result<QualityScore, String> {
         // Fetch price history
-        let price_data = price_service::get_history(&self.pool, ticker)
+        let price_data = self.repos.prices.price_history(ticker)
             .await
             .map_err(|e| format!("Failed to fetch price data for {}: {}", ticker, e))?;
 
@@ -121,7 +136,7 @@ impl LongTermGuidanceService {
         // Convert to f64 prices in chronological order (oldest first)
         let mut prices: Vec<f64> = price_data
             .iter()
-            .filter_map(|p| p.close_price.to_string().parse::<f64>().ok())
+            .filter_map(|p| p.close_price.to_f64())
             .collect();
         prices.reverse();
 
@@ -135,11 +150,12 @@ impl LongTermGuidanceService {
             .map(|w| (w[1] - w[0]) / w[0])
             .collect();
 
-        // Compute component scores
+        // Compute component scores, from reported fundamentals where stored
+        let reported = self.reported_fundamentals(ticker).await;
         let growth_metrics = self.compute_growth_metrics(&prices, &returns);
-        let dividend_metrics = self.compute_dividend_metrics(&prices, &returns, ticker).await;
-        let moat_indicators = self.compute_moat_indicators(&prices, &returns);
-        let management_metrics = self.compute_management_metrics(&prices, &returns);
+        let dividend_metrics = self.compute_dividend_metrics(&prices, &returns, &reported);
+        let moat_indicators = self.compute_moat_indicators(&prices, &returns, reported.ratios.as_ref());
+        let management_metrics = self.compute_management_metrics(&prices, &returns, reported.ratios.as_ref());
 
         // Score each component (0-100)
         let growth_score = self.score_growth(&growth_metrics);
@@ -226,14 +242,79 @@ impl LongTermGuidanceService {
         }
     }
 
+    // ── Reported Fundamentals ────────────────────────────────────────
+
+    async fn reported_fundamentals(&self, ticker: &str) -> ReportedFundamentals {
+        let ratios = self.repos.fundamentals.ratios(ticker).await.unwrap_or_else(|e| {
+            warn!("Could not read fundamentals for {}: {}", ticker, e);
+            None
+        });
+        let since = clock::today() - Duration::days(2 * 365);
+        let dividends = self.repos.fundamentals.dividends(ticker, since).await.unwrap_or_else(|e| {
+            warn!("Could not read dividend history for {}: {}", ticker, e);
+            Vec::new()
+        });
+        ReportedFundamentals { ratios, dividends }
+    }
+
     // ── Dividend Metrics ─────────────────────────────────────────────
 
-    async fn compute_dividend_metrics(
+    fn compute_dividend_metrics(
         &self,
         prices: &[f64],
         returns: &[f64],
-        _ticker: &str,
+        reported: &ReportedFundamentals,
     ) -> DividendMetrics {
+        let price = prices.last().copied().unwrap_or(0.0);
+        self.reported_dividend_metrics(price, reported)
+            .unwrap_or_else(|| self.estimated_dividend_metrics(prices, returns))
+    }
+
+    /// Dividend metrics from reported dividends and payout ratio. `None` without
+    /// reported dividend data, including for tickers never fetched.
+    fn reported_dividend_metrics(&self, price: f64, reported: &ReportedFundamentals) -> Option<DividendMetrics> {
+        let today = clock::today();
+        let trailing = fundamentals_service::trailing_annual_dividend(&reported.dividends, today);
+        let reported_yield = reported.ratios.as_ref().and_then(|r| r.dividend_yield);
+        let dividend_yield = if trailing > 0.0 && price > 0.0 {
+            trailing / price * 100.0
+        } else {
+            reported_yield?
+        };
+
+        if dividend_yield <= 0.0 {
+            // Reported non-payer
+            return Some(DividendMetrics {
+                has_positive_income: false,
+                estimated_yield: None,
+                payout_sustainability: 0.0,
+                growth_indicator: 0.0,
+            });
+        }
+
+        // Paying out more than earnings can't last
+        let payout_sustainability = match reported.ratios.as_ref().and_then(|r| r.payout_ratio) {
+            Some(payout) if payout > 0.0 && payout <= 60.0 => 0.9,
+            Some(payout) if payout > 0.0 && payout <= 80.0 => 0.7,
+            Some(payout) if payout > 0.0 && payout <= 100.0 => 0.5,
+            Some(_) => 0.3,
+            None => 0.5,
+        };
+        let growth_indicator = fundamentals_service::dividend_growth(&reported.dividends, today)
+            .map(|growth| (growth / 100.0).clamp(-0.5, 0.5))
+            .unwrap_or(0.0);
+
+        Some(DividendMetrics {
+            has_positive_income: true,
+            estimated_yield: Some(dividend_yield),
+            payout_sustainability,
+            growth_indicator,
+        })
+    }
+
+    /// Dividend metrics estimated from price behavior, for tickers without
+    /// reported dividend data
+    fn estimated_dividend_metrics(&self, prices: &[f64], returns: &[f64]) -> DividendMetrics {
         // Without direct dividend data, we estimate from price behavior:
         // - Stocks with stable, positive returns and low volatility are likely dividend payers
         // - We look for patterns consistent with income-generating securities
@@ -291,17 +372,24 @@ impl LongTermGuidanceService {
 
     // ── Moat Indicators ──────────────────────────────────────────────
 
-    fn compute_moat_indicators(&self, prices: &[f64], returns: &[f64]) -> MoatIndicators {
+    fn compute_moat_indicators(
+        &self,
+        prices: &[f64],
+        returns: &[f64],
+        ratios: Option<&ExternalFundamentals>,
+    ) -> MoatIndicators {
         let volatility = self.compute_volatility(returns);
 
         // Price stability: inverse of volatility, normalized to 0-1
         let price_stability = (1.0 - (volatility / 50.0)).max(0.0).min(1.0);
 
-        // Margin strength: proportion of positive returns (higher = more consistent profits)
-        let positive_ratio = if returns.is_empty() { 0.0 } else {
-            returns.iter().filter(|&&r| r > 0.0).count() as f64 / returns.len() as f64
+        // Margin strength: reported net margin (20% or more is full strength),
+        // otherwise the proportion of positive returns as a proxy
+        let margin_strength = match ratios.and_then(|r| r.net_margin) {
+            Some(net_margin) => (net_margin / 20.0).clamp(0.0, 1.0),
+            None if returns.is_empty() => 0.0,
+            None => returns.iter().filter(|&&r| r > 0.0).count() as f64 / returns.len() as f64,
         };
-        let margin_strength = positive_ratio;
 
         // Relative strength: overall trend direction and magnitude
         let n = prices.len();
@@ -338,18 +426,24 @@ impl LongTermGuidanceService {
 
     // ── Management Metrics ───────────────────────────────────────────
 
-    fn compute_management_metrics(&self, prices: &[f64], returns: &[f64]) -> ManagementMetrics {
+    fn compute_management_metrics(
+        &self,
+        prices: &[f64],
+        returns: &[f64],
+        ratios: Option<&ExternalFundamentals>,
+    ) -> ManagementMetrics {
         let mean_return = if returns.is_empty() { 0.0 } else {
             returns.iter().sum::<f64>() / returns.len() as f64
         };
         let volatility = self.compute_volatility(returns);
 
-        // Capital efficiency: risk-adjusted return (Sharpe-like)
+        // Capital efficiency: reported return on equity on the same scale (15%
+        // ROE rates like a 1.5 Sharpe ratio), otherwise risk-adjusted return
         let daily_rf = self.risk_free_rate / 252.0;
-        let capital_efficiency = if volatility > 0.0 {
-            ((mean_return - daily_rf) / (volatility / 100.0)).max(-2.0).min(2.0)
-        } else {
-            0.0
+        let capital_efficiency = match ratios.and_then(|r| r.roe) {
+            Some(roe) => (roe / 10.0).clamp(-2.0, 2.0),
+            None if volatility > 0.0 => ((mean_return - daily_rf) / (volatility / 100.0)).clamp(-2.0, 2.0),
+            None => 0.0,
         };
 
         // Recovery speed: how quickly the stock recovers from drawdowns
@@ -655,7 +749,7 @@ impl LongTermGuidanceService {
         &self,
         recommendations: &[LongTermRecommendation],
         total_value: f64,
-        allocations: &[db::analytics_queries::AllocationRow],
+        allocations: &[AllocationRow],
         _holding_details: &[HoldingDetail],
     ) -> PortfolioGuidanceSummary {
         let dividend_aristocrat_count = recommendations.iter()
@@ -906,54 +1000,16 @@ impl LongTermGuidanceService {
 
         monthly_returns
     }
-
-    /// Fetch holding details (name, industry) from latest snapshot
-    async fn fetch_holding_details(&self, portfolio_id: Uuid) -> Result<Vec<HoldingDetail>, String> {
-        let rows = sqlx::query_as::<_, HoldingDetail>(
-            r#"
-            WITH latest AS (
-                SELECT MAX(h.snapshot_date) as snapshot_date
-                FROM holdings_snapshots h
-                JOIN accounts a ON h.account_id = a.id
-                WHERE a.portfolio_id = $1
-            )
-            SELECT DISTINCT ON (h.ticker)
-                h.ticker,
-                h.holding_name,
-                h.industry,
-                h.asset_category,
-                h.market_value::double precision as market_value
-            FROM holdings_snapshots h
-            JOIN accounts a ON h.account_id = a.id
-            JOIN latest l ON h.snapshot_date = l.snapshot_date
-            WHERE a.portfolio_id = $1
-              AND h.ticker != ''
-            ORDER BY h.ticker, h.market_value DESC
-            "#,
-        )
-        .bind(portfolio_id)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| format!("Failed to fetch holding details: {}", e))?;
-
-        Ok(rows)
-    }
-}
-
-/// Internal struct for holding details
-#[derive(Debug, Clone, sqlx::FromRow)]
-#[allow(dead_code)]
-pub struct HoldingDetail {
-    pub ticker: String,
-    pub holding_name: Option<String>,
-    pub industry: Option<String>,
-    pub asset_category: Option<String>,
-    pub market_value: Option<f64>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::repositories::memory::MemoryRepositories;
+
+    fn test_service() -> LongTermGuidanceService {
+        LongTermGuidanceService::new(MemoryRepositories::default().repositories(), 0.045)
+    }
 
     #[test]
     fn test_quality_tier_from_score() {
@@ -1030,10 +1086,7 @@ mod tests {
 
     #[test]
     fn test_r_squared() {
-        let service = LongTermGuidanceService {
-            pool: unsafe { std::mem::zeroed() }, // Not used in this test
-            risk_free_rate: 0.045,
-        };
+        let service = test_service();
 
         // Perfect linear data should have R-squared near 1.0
         let linear: Vec<f64> = (0..100).map(|i| i as f64 * 2.0 + 1.0).collect();
@@ -1043,10 +1096,7 @@ mod tests {
 
     #[test]
     fn test_compute_volatility() {
-        let service = LongTermGuidanceService {
-            pool: unsafe { std::mem::zeroed() },
-            risk_free_rate: 0.045,
-        };
+        let service = test_service();
 
         // Zero returns should give zero volatility
         let returns = vec![0.0; 100];
@@ -1060,10 +1110,7 @@ mod tests {
 
     #[test]
     fn test_recovery_speed() {
-        let service = LongTermGuidanceService {
-            pool: unsafe { std::mem::zeroed() },
-            risk_free_rate: 0.045,
-        };
+        let service = test_service();
 
         // Steadily increasing prices: no drawdowns
         let prices: Vec<f64> = (1..=100).map(|i| i as f64).collect();
@@ -1073,10 +1120,7 @@ mod tests {
 
     #[test]
     fn test_monthly_returns() {
-        let service = LongTermGuidanceService {
-            pool: unsafe { std::mem::zeroed() },
-            risk_free_rate: 0.045,
-        };
+        let service = test_service();
 
         let prices: Vec<f64> = (0..100).map(|i| 100.0 + i as f64).collect();
         let monthly = service.compute_monthly_returns(&prices);
@@ -1088,10 +1132,7 @@ mod tests {
 
     #[test]
     fn test_growth_scoring() {
-        let service = LongTermGuidanceService {
-            pool: unsafe { std::mem::zeroed() },
-            risk_free_rate: 0.045,
-        };
+        let service = test_service();
 
         let high_growth = GrowthMetrics {
             annualized_return: 25.0,
@@ -1113,4 +1154,76 @@ mod tests {
         let score = service.score_growth(&negative_growth);
         assert!(score < 50.0, "Negative growth should score below 50, got {}", score);
     }
+
+    #[tokio::test]
+    async fn test_generate_guidance_from_repositories() {
+        let memory = MemoryRepositories::default();
+        let portfolio_id = Uuid::new_v4();
+        memory.holdings.insert(portfolio_id, "AAA", Some("Technology"), 6000.0);
+        memory.holdings.insert(portfolio_id, "BBB", Some("Utilities"), 4000.0);
+
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let steady: Vec<f64> = (0..300).map(|i| 100.0 * 1.0005f64.powi(i)).collect();
+        let choppy: Vec<f64> = (0..300).map(|i| 50.0 + i as f64 * 0.01 + if i % 2 == 0 { 2.0 } else { -2.0 }).collect();
+        memory.prices.insert_closes("AAA", start, &steady);
+        memory.prices.insert_closes("BBB", start, &choppy);
+
+        let service = LongTermGuidanceService::new(memory.repositories(), 0.045);
+        let response = service
+            .generate_guidance(portfolio_id, &InvestmentGoal::Wealth, &RiskTolerance::Moderate, 10, None)
+            .await
+            .unwrap();
+
+        let mut tickers: Vec<&str> = response.recommendations.iter().map(|r| r.ticker.as_str()).collect();
+        tickers.sort();
+        assert_eq!(tickers, vec!["AAA", "BBB"]);
+        let aaa = response.recommendations.iter().find(|r| r.ticker == "AAA").unwrap();
+        assert_eq!(aaa.industry.as_deref(), Some("Technology"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_guidance_requires_holdings() {
+        let result = test_service()
+            .generate_guidance(Uuid::new_v4(), &InvestmentGoal::Retirement, &RiskTolerance::Conservative, 20, None)
+            .await;
+        assert_eq!(result.unwrap_err(), "No holdings found in portfolio");
+    }
+
+    #[tokio::test]
+    async fn test_reported_fundamentals_replace_proxies() {
+        let memory = MemoryRepositories::default();
+        let portfolio_id = Uuid::new_v4();
+        memory.holdings.insert(portfolio_id, "KO", Some("Consumer Staples"), 5000.0);
+        memory.holdings.insert(portfolio_id, "NEW", None, 5000.0);
+
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let choppy: Vec<f64> = (0..300).map(|i| 50.0 + if i % 2 == 0 { 0.5 } else { -0.5 }).collect();
+        memory.prices.insert_closes("KO", start, &[50.0; 300]);
+        memory.prices.insert_closes("NEW", start, &choppy);
+
+        memory.fundamentals.insert_ratios(
+            "KO",
+            ExternalFundamentals { roe: Some(40.0), net_margin: Some(23.0), payout_ratio: Some(70.0), ..Default::default() },
+        );
+        let today = clock::today();
+        let ex_dates: Vec<chrono::NaiveDate> = [30, 120, 210, 300].iter().map(|&d| today - Duration::days(d)).collect();
+        memory.fundamentals.insert_dividends("KO", &ex_dates, 0.5);
+
+        let service = LongTermGuidanceService::new(memory.repositories(), 0.045);
+        let response = service
+            .generate_guidance(portfolio_id, &InvestmentGoal::Retirement, &RiskTolerance::Conservative, 20, None)
+            .await
+            .unwrap();
+
+        let ko = &response.recommendations.iter().find(|r| r.ticker == "KO").unwrap().quality_score;
+        assert!((ko.dividend_metrics.estimated_yield.unwrap() - 4.0).abs() < 1e-9);
+        assert_eq!(ko.dividend_metrics.payout_sustainability, 0.7);
+        assert_eq!(ko.moat_indicators.margin_strength, 1.0);
+        assert_eq!(ko.management_metrics.capital_efficiency, 2.0);
+
+        // A ticker never fetched keeps the price-based estimates
+        let new = &response.recommendations.iter().find(|r| r.ticker == "NEW").unwrap().quality_score;
+        assert!(new.management_metrics.capital_efficiency < 2.0);
+        assert!(new.dividend_score < ko.dividend_score);
+    }
 }
@@ -0,0 +1,472 @@
+//! Tax-lot tracking.
+//!
+//! Lots are derived rather than stored: each account's first holdings snapshot
+//! seeds one opening lot per ticker at its average cost, and the BUY/SELL/DRIP
+//! transactions recorded after that snapshot open and close lots. Each account
+//! picks how sales are matched to lots: first-in first-out, average cost (every
+//! lot carries the pooled adjusted cost base), or specific lots designated per
+//! sale with FIFO for any shares left undesignated.
+
+use std::collections::{HashMap, VecDeque};
+
+use bigdecimal::ToPrimitive;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{account_queries, detected_transaction_queries, holding_snapshot_queries};
+use crate::errors::AppError;
+use crate::models::{CostBasisMethod, DetectedTransaction, HoldingSnapshot, LotSelection, RealizedLot, TaxLot};
+
+/// Quantities below this are treated as fully closed
+const QUANTITY_EPSILON: f64 = 1e-6;
+const LONG_TERM_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// A normalized trade fed into the lot ledger
+#[derive(Debug, Clone)]
+pub struct LotTrade {
+    pub account_id: Uuid,
+    pub ticker: String,
+    pub date: NaiveDate,
+    pub side: TradeSide,
+    pub quantity: f64,
+    pub price: f64,
+    /// `None` for opening balances
+    pub transaction_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LotLedger {
+    pub open_lots: Vec<TaxLot>,
+    pub realized: Vec<RealizedLot>,
+    /// (account_id, ticker, quantity) sold beyond the shares tracked in lots
+    pub unmatched_sells: Vec<(Uuid, String, f64)>,
+}
+
+/// How each account matches sales to lots
+#[derive(Debug, Clone, Default)]
+pub struct LotMatching {
+    /// Accounts not listed match FIFO
+    pub methods: HashMap<Uuid, CostBasisMethod>,
+    /// Designated lots per sell transaction, honoured in specific-lot accounts
+    pub selections: HashMap<Uuid, Vec<LotSelection>>,
+}
+
+impl LotMatching {
+    fn method(&self, account_id: Uuid) -> CostBasisMethod {
+        self.methods.get(&account_id).copied().unwrap_or_default()
+    }
+}
+
+/// Turn an opening snapshot holding into a trade; cash rows and empty positions are skipped
+pub fn opening_trade(holding: &HoldingSnapshot) -> Option<LotTrade> {
+    let quantity = holding.quantity.to_f64().unwrap_or(0.0);
+    if holding.ticker.is_empty() || quantity <= QUANTITY_EPSILON {
+        return None;
+    }
+    Some(LotTrade {
+        account_id: holding.account_id,
+        ticker: holding.ticker.clone(),
+        date: holding.snapshot_date,
+        side: TradeSide::Buy,
+        quantity,
+        price: holding.average_cost.to_f64().unwrap_or(0.0),
+        transaction_id: None,
+    })
+}
+
+/// Turn a BUY/SELL/DRIP transaction into a trade. The price falls back to amount / quantity.
+///
+/// Reinvested dividends open a lot like any other purchase.
+pub fn transaction_trade(tx: &DetectedTransaction) -> Option<LotTrade> {
+    let side = match tx.transaction_type.as_str() {
+        "BUY" | "DRIP" => TradeSide::Buy,
+        "SELL" => TradeSide::Sell,
+        _ => return None,
+    };
+    let quantity = tx.quantity.as_ref().and_then(|q| q.to_f64())?.abs();
+    if quantity <= QUANTITY_EPSILON {
+        return None;
+    }
+    let price = tx
+        .price
+        .as_ref()
+        .and_then(|p| p.to_f64())
+        .or_else(|| tx.amount.as_ref().and_then(|a| a.to_f64()).map(|a| a.abs() / quantity))
+        .unwrap_or(0.0);
+
+    Some(LotTrade {
+        account_id: tx.account_id,
+        ticker: tx.ticker.clone(),
+        date: tx.transaction_date,
+        side,
+        quantity,
+        price,
+        transaction_id: Some(tx.id),
+    })
+}
+
+/// Match trades into lots per (account, ticker), using each account's cost basis method.
+///
+/// Trades are processed in date order; on the same date buys are applied before sells.
+pub fn build_ledger(mut trades: Vec<LotTrade>, matching: &LotMatching) -> LotLedger {
+    trades.sort_by(|a, b| {
+        a.date
+            .cmp(&b.date)
+            .then_with(|| (a.side == TradeSide::Sell).cmp(&(b.side == TradeSide::Sell)))
+    });
+
+    let mut queues: HashMap<(Uuid, String), VecDeque<TaxLot>> = HashMap::new();
+    let mut ledger = LotLedger::default();
+
+    for trade in trades {
+        let method = matching.method(trade.account_id);
+        let queue = queues
+            .entry((trade.account_id, trade.ticker.clone()))
+            .or_default();
+
+        match trade.side {
+            TradeSide::Buy => {
+                queue.push_back(TaxLot {
+                    account_id: trade.account_id,
+                    ticker: trade.ticker.clone(),
+                    acquired_date: trade.date,
+                    quantity: trade.quantity,
+                    cost_per_share: trade.price,
+                    source: if trade.transaction_id.is_some() { "transaction" } else { "opening_balance" }
+                        .to_string(),
+                    transaction_id: trade.transaction_id,
+                });
+                if method == CostBasisMethod::AverageCost {
+                    pool_cost(queue);
+                }
+            }
+            TradeSide::Sell => {
+                let mut remaining = trade.quantity;
+
+                if method == CostBasisMethod::SpecificLot {
+                    let designated = trade.transaction_id.and_then(|id| matching.selections.get(&id));
+                    for selection in designated.into_iter().flatten() {
+                        let Some(lot) = queue
+                            .iter_mut()
+                            .find(|lot| lot.transaction_id == selection.lot_transaction_id)
+                        else {
+                            continue;
+                        };
+                        remaining -= close_lot(&mut ledger.realized, &trade, lot, selection.quantity.min(remaining));
+                    }
+                    queue.retain(|lot| lot.quantity > QUANTITY_EPSILON);
+                }
+
+                while remaining > QUANTITY_EPSILON {
+                    let Some(lot) = queue.front_mut() else { break };
+                    remaining -= close_lot(&mut ledger.realized, &trade, lot, remaining);
+                    if lot.quantity <= QUANTITY_EPSILON {
+                        queue.pop_front();
+                    }
+                }
+                if remaining > QUANTITY_EPSILON {
+                    ledger
+                        .unmatched_sells
+                        .push((trade.account_id, trade.ticker.clone(), remaining));
+                }
+            }
+        }
+    }
+
+    let mut open_lots: Vec<TaxLot> = queues.into_values().flatten().collect();
+    open_lots.sort_by(|a, b| {
+        a.ticker
+            .cmp(&b.ticker)
+            .then(a.acquired_date.cmp(&b.acquired_date))
+    });
+    ledger.open_lots = open_lots;
+    ledger
+}
+
+/// Close up to `quantity` shares of `lot` at the sale's price; returns the shares closed
+fn close_lot(realized: &mut Vec<RealizedLot>, trade: &LotTrade, lot: &mut TaxLot, quantity: f64) -> f64 {
+    let closed = quantity.min(lot.quantity);
+    if closed <= 0.0 {
+        return 0.0;
+    }
+    let holding_days = (trade.date - lot.acquired_date).num_days();
+    let cost_basis = closed * lot.cost_per_share;
+    let proceeds = closed * trade.price;
+
+    realized.push(RealizedLot {
+        account_id: trade.account_id,
+        ticker: trade.ticker.clone(),
+        acquired_date: lot.acquired_date,
+        sold_date: trade.date,
+        quantity: closed,
+        cost_basis,
+        proceeds,
+        gain: proceeds - cost_basis,
+        holding_days,
+        long_term: holding_days > LONG_TERM_DAYS,
+        // Sells always come from transactions
+        sell_transaction_id: trade.transaction_id.unwrap_or_default(),
+        lot_transaction_id: lot.transaction_id,
+    });
+
+    lot.quantity -= closed;
+    closed
+}
+
+/// Re-price every open lot at the pooled average cost per share
+fn pool_cost(queue: &mut VecDeque<TaxLot>) {
+    let quantity: f64 = queue.iter().map(|lot| lot.quantity).sum();
+    if quantity <= QUANTITY_EPSILON {
+        return;
+    }
+    let cost: f64 = queue.iter().map(|lot| lot.quantity * lot.cost_per_share).sum();
+    for lot in queue.iter_mut() {
+        lot.cost_per_share = cost / quantity;
+    }
+}
+
+/// Load the opening balances and trades of every account in a portfolio.
+///
+/// Transactions dated on or before an account's opening snapshot are already
+/// reflected in its opening balances and are skipped.
+pub async fn load_portfolio_trades(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<LotTrade>, AppError> {
+    let opening = holding_snapshot_queries::fetch_portfolio_opening_holdings(pool, portfolio_id).await?;
+    let transactions = detected_transaction_queries::fetch_trades_for_portfolio(pool, portfolio_id).await?;
+
+    let opening_dates: HashMap<Uuid, NaiveDate> = opening
+        .iter()
+        .map(|h| (h.account_id, h.snapshot_date))
+        .collect();
+
+    let mut trades: Vec<LotTrade> = opening.iter().filter_map(opening_trade).collect();
+    trades.extend(
+        transactions
+            .iter()
+            .filter(|tx| {
+                opening_dates
+                    .get(&tx.account_id)
+                    .is_none_or(|opened| tx.transaction_date > *opened)
+            })
+            .filter_map(transaction_trade),
+    );
+    Ok(trades)
+}
+
+/// Load the cost basis method of every account in a portfolio and the lots designated for its sales
+pub async fn load_lot_matching(pool: &PgPool, portfolio_id: Uuid) -> Result<LotMatching, AppError> {
+    let accounts = account_queries::fetch_all(pool, portfolio_id).await?;
+    let mut selections: HashMap<Uuid, Vec<LotSelection>> = HashMap::new();
+    for (sell_transaction_id, selection) in
+        detected_transaction_queries::fetch_lot_selections_for_portfolio(pool, portfolio_id).await?
+    {
+        selections.entry(sell_transaction_id).or_default().push(selection);
+    }
+    Ok(LotMatching {
+        methods: accounts.iter().map(|a| (a.id, a.cost_basis_method())).collect(),
+        selections,
+    })
+}
+
+/// Designate the lots a sell transaction closes. Each lot must be a BUY/DRIP of the same
+/// ticker in the same account, or `None` for its opening balance, and the designated
+/// quantities may not exceed the shares sold.
+pub async fn set_lot_selections(
+    pool: &PgPool,
+    sell: &DetectedTransaction,
+    selections: &[LotSelection],
+) -> Result<(), AppError> {
+    if sell.transaction_type != "SELL" {
+        return Err(AppError::Validation("Lots can only be designated for SELL transactions".into()));
+    }
+    if selections.iter().any(|s| s.quantity <= QUANTITY_EPSILON) {
+        return Err(AppError::Validation("Designated quantities must be positive".into()));
+    }
+    let sold = sell.quantity.as_ref().and_then(|q| q.to_f64()).unwrap_or(0.0).abs();
+    let designated: f64 = selections.iter().map(|s| s.quantity).sum();
+    if designated > sold + QUANTITY_EPSILON {
+        return Err(AppError::Validation(format!(
+            "Designated {} shares but only {} were sold",
+            designated, sold
+        )));
+    }
+
+    for lot_id in selections.iter().filter_map(|s| s.lot_transaction_id) {
+        let lot = detected_transaction_queries::fetch_one(pool, lot_id).await?;
+        let valid = lot.is_some_and(|lot| {
+            lot.account_id == sell.account_id
+                && lot.ticker == sell.ticker
+                && matches!(lot.transaction_type.as_str(), "BUY" | "DRIP")
+        });
+        if !valid {
+            return Err(AppError::Validation(format!(
+                "Transaction {} is not a purchase of {} in this account",
+                lot_id, sell.ticker
+            )));
+        }
+    }
+
+    detected_transaction_queries::replace_lot_selections(pool, sell.id, selections).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, m, d).unwrap()
+    }
+
+    fn trade(account: Uuid, side: TradeSide, d: NaiveDate, qty: f64, price: f64) -> LotTrade {
+        LotTrade {
+            account_id: account,
+            ticker: "AAPL".to_string(),
+            date: d,
+            side,
+            quantity: qty,
+            price,
+            transaction_id: Some(Uuid::new_v4()),
+        }
+    }
+
+    #[test]
+    fn test_build_ledger_fifo_partial_close() {
+        let account = Uuid::new_v4();
+        let ledger = build_ledger(
+            vec![
+                trade(account, TradeSide::Sell, date(3, 1), 15.0, 130.0),
+                trade(account, TradeSide::Buy, date(1, 1), 10.0, 100.0),
+                trade(account, TradeSide::Buy, date(2, 1), 10.0, 110.0),
+            ],
+            &LotMatching::default(),
+        );
+
+        assert_eq!(ledger.realized.len(), 2);
+        assert!((ledger.realized[0].gain - 300.0).abs() < 1e-9);
+        assert!((ledger.realized[1].quantity - 5.0).abs() < 1e-9);
+        assert!((ledger.realized[1].gain - 100.0).abs() < 1e-9);
+
+        assert_eq!(ledger.open_lots.len(), 1);
+        assert!((ledger.open_lots[0].quantity - 5.0).abs() < 1e-9);
+        assert_eq!(ledger.open_lots[0].acquired_date, date(2, 1));
+        assert!(ledger.unmatched_sells.is_empty());
+    }
+
+    #[test]
+    fn test_build_ledger_keeps_accounts_separate() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        let ledger = build_ledger(
+            vec![
+                trade(a, TradeSide::Buy, date(1, 1), 10.0, 100.0),
+                trade(b, TradeSide::Sell, date(2, 1), 4.0, 120.0),
+            ],
+            &LotMatching::default(),
+        );
+
+        assert!(ledger.realized.is_empty());
+        assert_eq!(ledger.unmatched_sells.len(), 1);
+        assert_eq!(ledger.unmatched_sells[0].0, b);
+        assert_eq!(ledger.open_lots.len(), 1);
+    }
+
+    #[test]
+    fn test_long_term_classification() {
+        let account = Uuid::new_v4();
+        let ledger = build_ledger(
+            vec![
+                trade(account, TradeSide::Buy, date(1, 1), 1.0, 100.0),
+                trade(
+                    account,
+                    TradeSide::Sell,
+                    NaiveDate::from_ymd_opt(2026, 1, 2).unwrap(),
+                    1.0,
+                    90.0,
+                ),
+            ],
+            &LotMatching::default(),
+        );
+        assert!(ledger.realized[0].long_term);
+        assert!(ledger.realized[0].gain < 0.0);
+    }
+
+    #[test]
+    fn test_average_cost_pools_lots() {
+        let account = Uuid::new_v4();
+        let matching = LotMatching {
+            methods: HashMap::from([(account, CostBasisMethod::AverageCost)]),
+            ..Default::default()
+        };
+        let ledger = build_ledger(
+            vec![
+                trade(account, TradeSide::Buy, date(1, 1), 10.0, 100.0),
+                trade(account, TradeSide::Buy, date(2, 1), 30.0, 120.0),
+                trade(account, TradeSide::Sell, date(3, 1), 20.0, 130.0),
+                trade(account, TradeSide::Buy, date(4, 1), 20.0, 145.0),
+            ],
+            &matching,
+        );
+
+        // Sold at the pooled cost of 115/share, not the oldest lot's 100
+        assert_eq!(ledger.realized.len(), 2);
+        let gain: f64 = ledger.realized.iter().map(|r| r.gain).sum();
+        assert!((gain - 300.0).abs() < 1e-9);
+
+        // The later buy re-pools: (20 * 115 + 20 * 145) / 40 = 130
+        assert_eq!(ledger.open_lots.len(), 2);
+        assert!(ledger.open_lots.iter().all(|l| (l.cost_per_share - 130.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_specific_lot_closes_designated_lot_then_fifo() {
+        let account = Uuid::new_v4();
+        let first = trade(account, TradeSide::Buy, date(1, 1), 10.0, 100.0);
+        let second = trade(account, TradeSide::Buy, date(2, 1), 10.0, 150.0);
+        let sell = trade(account, TradeSide::Sell, date(3, 1), 12.0, 140.0);
+        let matching = LotMatching {
+            methods: HashMap::from([(account, CostBasisMethod::SpecificLot)]),
+            selections: HashMap::from([(
+                sell.transaction_id.unwrap(),
+                vec![LotSelection { lot_transaction_id: second.transaction_id, quantity: 10.0 }],
+            )]),
+        };
+        let ledger = build_ledger(vec![first.clone(), second.clone(), sell], &matching);
+
+        assert_eq!(ledger.realized.len(), 2);
+        assert_eq!(ledger.realized[0].lot_transaction_id, second.transaction_id);
+        assert!((ledger.realized[0].gain + 100.0).abs() < 1e-9);
+        // The 2 undesignated shares come from the oldest lot
+        assert_eq!(ledger.realized[1].lot_transaction_id, first.transaction_id);
+        assert!((ledger.realized[1].quantity - 2.0).abs() < 1e-9);
+
+        assert_eq!(ledger.open_lots.len(), 1);
+        assert_eq!(ledger.open_lots[0].transaction_id, first.transaction_id);
+        assert!((ledger.open_lots[0].quantity - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_selections_ignored_outside_specific_lot_accounts() {
+        let account = Uuid::new_v4();
+        let first = trade(account, TradeSide::Buy, date(1, 1), 10.0, 100.0);
+        let second = trade(account, TradeSide::Buy, date(2, 1), 10.0, 150.0);
+        let sell = trade(account, TradeSide::Sell, date(3, 1), 5.0, 140.0);
+        let matching = LotMatching {
+            selections: HashMap::from([(
+                sell.transaction_id.unwrap(),
+                vec![LotSelection { lot_transaction_id: second.transaction_id, quantity: 5.0 }],
+            )]),
+            ..Default::default()
+        };
+        let ledger = build_ledger(vec![first.clone(), second, sell], &matching);
+
+        assert_eq!(ledger.realized.len(), 1);
+        assert_eq!(ledger.realized[0].lot_transaction_id, first.transaction_id);
+    }
+}
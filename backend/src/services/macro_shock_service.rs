@@ -0,0 +1,334 @@
+//! Macro shock what-if scenarios.
+//!
+//! Each macro factor is represented by a proxy ETF. A position's sensitivities are
+//! the coefficients of a multiple regression of its daily returns on the proxies'
+//! daily returns; the broad market (SPY) is always included so that market beta is
+//! not misattributed to the shocked factors. A shock is translated into a proxy move
+//! (a rate shock through the proxy's duration) and applied through the betas.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use bigdecimal::ToPrimitive;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{holding_snapshot_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{
+    FactorSensitivity, MacroFactor, MacroShock, MacroShockRequest, MacroShockResult, PositionShockImpact, PricePoint,
+};
+
+const DEFAULT_DAYS: i64 = 252;
+const MIN_OBSERVATIONS: usize = 30;
+/// Approximate modified duration of the rates proxy (TLT)
+const RATES_PROXY_DURATION: f64 = 17.0;
+
+impl MacroFactor {
+    pub fn proxy(&self) -> &'static str {
+        match self {
+            MacroFactor::Rates => "TLT",
+            MacroFactor::Oil => "USO",
+            MacroFactor::Usd => "UUP",
+            MacroFactor::Equity => "SPY",
+            MacroFactor::Gold => "GLD",
+        }
+    }
+
+    /// Percent move of the proxy implied by a shock to the factor
+    pub fn proxy_move(&self, shock: f64) -> f64 {
+        match self {
+            // Bond prices fall by roughly duration × yield change
+            MacroFactor::Rates => -RATES_PROXY_DURATION * shock / 100.0,
+            _ => shock,
+        }
+    }
+
+    fn from_keyword(word: &str) -> Option<Self> {
+        match word {
+            "rates" | "rate" | "yields" | "yield" | "interest" => Some(MacroFactor::Rates),
+            "oil" | "crude" => Some(MacroFactor::Oil),
+            "usd" | "dollar" => Some(MacroFactor::Usd),
+            "equity" | "equities" | "stocks" | "market" | "spy" => Some(MacroFactor::Equity),
+            "gold" => Some(MacroFactor::Gold),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a scenario such as "rates +100bps, oil +20%, USD +5%".
+///
+/// Rate shocks may be given in bps or percent ("rates +1%" is 100 bps).
+pub fn parse_scenario(scenario: &str) -> Result<Vec<MacroShock>, AppError> {
+    let mut shocks = Vec::new();
+    for part in scenario.split([',', ';']).map(str::trim).filter(|p| !p.is_empty()) {
+        let invalid = || AppError::Validation(format!("Cannot parse shock '{}'; expected e.g. 'oil +20%'", part));
+        let lower = part.to_lowercase();
+        let (name, amount) = lower.split_once(char::is_whitespace).ok_or_else(invalid)?;
+        let factor = MacroFactor::from_keyword(name.trim()).ok_or_else(|| {
+            AppError::Validation(format!("Unknown factor '{}'; use rates, oil, usd, equity, or gold", name))
+        })?;
+
+        let amount = amount.replace(' ', "");
+        let (number, in_bps) = if let Some(n) = amount.strip_suffix("bps").or_else(|| amount.strip_suffix("bp")) {
+            (n, true)
+        } else {
+            (amount.strip_suffix('%').unwrap_or(&amount), false)
+        };
+        let value: f64 = number.parse().map_err(|_| invalid())?;
+
+        let shock = match (factor, in_bps) {
+            (MacroFactor::Rates, true) => value,
+            (MacroFactor::Rates, false) => value * 100.0,
+            (_, true) => return Err(invalid()),
+            (_, false) => value,
+        };
+        shocks.push(MacroShock { factor, shock });
+    }
+    Ok(shocks)
+}
+
+/// Merge listed and parsed shocks; a factor given twice is rejected
+fn collect_shocks(request: &MacroShockRequest) -> Result<Vec<MacroShock>, AppError> {
+    let mut shocks = request.shocks.clone();
+    if let Some(scenario) = &request.scenario {
+        shocks.extend(parse_scenario(scenario)?);
+    }
+    if shocks.is_empty() {
+        return Err(AppError::Validation("Specify at least one shock".to_string()));
+    }
+    let mut seen = HashSet::new();
+    for shock in &shocks {
+        if !shock.shock.is_finite() {
+            return Err(AppError::Validation("Shock values must be finite".to_string()));
+        }
+        if !seen.insert(shock.factor) {
+            return Err(AppError::Validation(format!("Factor {:?} is shocked more than once", shock.factor)));
+        }
+    }
+    Ok(shocks)
+}
+
+/// Daily returns keyed by the later date of each pair
+fn daily_returns(points: &[PricePoint]) -> BTreeMap<NaiveDate, f64> {
+    points
+        .windows(2)
+        .filter_map(|w| {
+            let prev = w[0].close_price.to_f64()?;
+            let cur = w[1].close_price.to_f64()?;
+            (prev > 0.0).then(|| (w[1].date, cur / prev - 1.0))
+        })
+        .collect()
+}
+
+/// Ordinary least squares with an intercept.
+///
+/// Returns the slope coefficients and R², or `None` with too few observations or
+/// collinear regressors.
+pub fn regress(y: &[f64], xs: &[Vec<f64>]) -> Option<(Vec<f64>, f64)> {
+    let n = y.len();
+    let k = xs.len() + 1;
+    if n < MIN_OBSERVATIONS.max(k + 1) || xs.iter().any(|x| x.len() != n) {
+        return None;
+    }
+
+    // Normal equations (X'X) b = X'y as an augmented matrix
+    let row = |i: usize| -> Vec<f64> { std::iter::once(1.0).chain(xs.iter().map(|x| x[i])).collect() };
+    let mut m = vec![vec![0.0; k + 1]; k];
+    for (i, yi) in y.iter().enumerate() {
+        let r = row(i);
+        for (a, ra) in r.iter().enumerate() {
+            for (b, rb) in r.iter().enumerate() {
+                m[a][b] += ra * rb;
+            }
+            m[a][k] += ra * yi;
+        }
+    }
+
+    // Gaussian elimination with partial pivoting
+    for col in 0..k {
+        let pivot = (col..k).max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))?;
+        if m[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        m.swap(col, pivot);
+        let pivot_row = m[col].clone();
+        for (r, current) in m.iter_mut().enumerate() {
+            if r != col {
+                let factor = current[col] / pivot_row[col];
+                for (value, p) in current.iter_mut().zip(&pivot_row).skip(col) {
+                    *value -= factor * p;
+                }
+            }
+        }
+    }
+    let coefficients: Vec<f64> = (0..k).map(|i| m[i][k] / m[i][i]).collect();
+
+    let mean = y.iter().sum::<f64>() / n as f64;
+    let (mut ss_res, mut ss_tot) = (0.0, 0.0);
+    for (i, yi) in y.iter().enumerate() {
+        let fitted: f64 = row(i).iter().zip(&coefficients).map(|(x, b)| x * b).sum();
+        ss_res += (yi - fitted).powi(2);
+        ss_tot += (yi - mean).powi(2);
+    }
+    let r_squared = if ss_tot > 0.0 { (1.0 - ss_res / ss_tot).max(0.0) } else { 0.0 };
+
+    Some((coefficients[1..].to_vec(), r_squared))
+}
+
+/// Estimate the P&L impact of macro shocks on a portfolio's current holdings
+pub async fn run_scenario(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    request: &MacroShockRequest,
+) -> Result<MacroShockResult, AppError> {
+    let shocks = collect_shocks(request)?;
+    let days = request.days.unwrap_or(DEFAULT_DAYS);
+    if !(MIN_OBSERVATIONS as i64 + 1..=2520).contains(&days) {
+        return Err(AppError::Validation(format!("days must be between {} and 2520", MIN_OBSERVATIONS + 1)));
+    }
+
+    // The market factor is always a regressor, shocked or not
+    let mut factors: Vec<MacroFactor> = shocks.iter().map(|s| s.factor).collect();
+    if !factors.contains(&MacroFactor::Equity) {
+        factors.push(MacroFactor::Equity);
+    }
+    let proxy_moves: Vec<f64> = factors
+        .iter()
+        .map(|f| shocks.iter().find(|s| s.factor == *f).map_or(0.0, |s| f.proxy_move(s.shock)))
+        .collect();
+
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let mut positions: Vec<(String, f64)> = Vec::new();
+    for holding in holdings.iter().filter(|h| !h.ticker.is_empty()) {
+        let value = holding.market_value.to_f64().unwrap_or(0.0);
+        match positions.iter_mut().find(|(t, _)| *t == holding.ticker) {
+            Some(entry) => entry.1 += value,
+            None => positions.push((holding.ticker.clone(), value)),
+        }
+    }
+    let total_value: f64 = positions.iter().map(|(_, v)| v).sum();
+    if total_value <= 0.0 {
+        return Err(AppError::Validation("Portfolio has no holdings to analyze".to_string()));
+    }
+
+    let mut tickers: Vec<String> = positions.iter().map(|(t, _)| t.clone()).collect();
+    tickers.extend(factors.iter().map(|f| f.proxy().to_string()));
+    tickers.sort();
+    tickers.dedup();
+    let windows = price_queries::fetch_window_batch(pool, &tickers, days + 1).await?;
+    let returns: HashMap<&str, BTreeMap<NaiveDate, f64>> = windows
+        .iter()
+        .map(|(ticker, points)| (ticker.as_str(), daily_returns(points)))
+        .collect();
+
+    let proxy_returns: Vec<&BTreeMap<NaiveDate, f64>> = factors
+        .iter()
+        .map(|f| {
+            returns
+                .get(f.proxy())
+                .filter(|r| r.len() >= MIN_OBSERVATIONS)
+                .ok_or_else(|| {
+                    AppError::Validation(format!(
+                        "Not enough price history for {} (proxy for {:?}); refresh its prices first",
+                        f.proxy(),
+                        f
+                    ))
+                })
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut impacts = Vec::new();
+    let mut unmodeled = Vec::new();
+    for (ticker, value) in &positions {
+        let fit = returns.get(ticker.as_str()).and_then(|series| {
+            // Dates on which the position and every proxy traded
+            let dates: Vec<&NaiveDate> = series
+                .keys()
+                .filter(|d| proxy_returns.iter().all(|p| p.contains_key(*d)))
+                .collect();
+            let y: Vec<f64> = dates.iter().map(|d| series[*d]).collect();
+            let xs: Vec<Vec<f64>> = proxy_returns
+                .iter()
+                .map(|p| dates.iter().map(|d| p[*d]).collect())
+                .collect();
+            regress(&y, &xs)
+        });
+
+        let Some((betas, r_squared)) = fit else {
+            unmodeled.push(ticker.clone());
+            continue;
+        };
+        let estimated_return: f64 = betas.iter().zip(&proxy_moves).map(|(b, m)| b * m).sum();
+        impacts.push(PositionShockImpact {
+            ticker: ticker.clone(),
+            market_value: *value,
+            weight: value / total_value,
+            sensitivities: factors
+                .iter()
+                .zip(&betas)
+                .map(|(f, b)| FactorSensitivity { factor: *f, proxy: f.proxy().to_string(), beta: *b })
+                .collect(),
+            r_squared: Some(r_squared),
+            estimated_return,
+            estimated_pnl: value * estimated_return / 100.0,
+        });
+    }
+    impacts.sort_by(|a, b| a.estimated_pnl.total_cmp(&b.estimated_pnl));
+
+    let estimated_pnl: f64 = impacts.iter().map(|i| i.estimated_pnl).sum();
+    Ok(MacroShockResult {
+        portfolio_id,
+        shocks,
+        days,
+        total_value,
+        estimated_pnl,
+        estimated_return: estimated_pnl / total_value * 100.0,
+        positions: impacts,
+        unmodeled,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scenario() {
+        let shocks = parse_scenario("rates +100bps, oil +20%, USD -5%").unwrap();
+        assert_eq!(
+            shocks,
+            vec![
+                MacroShock { factor: MacroFactor::Rates, shock: 100.0 },
+                MacroShock { factor: MacroFactor::Oil, shock: 20.0 },
+                MacroShock { factor: MacroFactor::Usd, shock: -5.0 },
+            ]
+        );
+        assert_eq!(parse_scenario("rates +0.5%").unwrap()[0].shock, 50.0);
+        assert!(parse_scenario("bitcoin +10%").is_err());
+        assert!(parse_scenario("oil +20bps").is_err());
+        assert!(parse_scenario("oil lots").is_err());
+    }
+
+    #[test]
+    fn test_rates_shock_moves_proxy_through_duration() {
+        assert!((MacroFactor::Rates.proxy_move(100.0) - -17.0).abs() < 1e-9);
+        assert_eq!(MacroFactor::Oil.proxy_move(20.0), 20.0);
+    }
+
+    #[test]
+    fn test_regress_recovers_coefficients() {
+        let x1: Vec<f64> = (0..60).map(|i| ((i * 7 % 13) as f64 - 6.0) / 100.0).collect();
+        let x2: Vec<f64> = (0..60).map(|i| ((i * 5 % 11) as f64 - 5.0) / 100.0).collect();
+        let y: Vec<f64> = x1.iter().zip(&x2).map(|(a, b)| 0.001 + 1.2 * a - 0.4 * b).collect();
+
+        let (betas, r_squared) = regress(&y, &[x1.clone(), x2]).unwrap();
+        assert!((betas[0] - 1.2).abs() < 1e-9);
+        assert!((betas[1] - -0.4).abs() < 1e-9);
+        assert!((r_squared - 1.0).abs() < 1e-9);
+
+        // Too few observations or collinear regressors
+        assert!(regress(&y[..10], &[x1[..10].to_vec()]).is_none());
+        assert!(regress(&y, &[x1.clone(), x1]).is_none());
+    }
+}
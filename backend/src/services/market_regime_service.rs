@@ -1,5 +1,5 @@
 use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
-use chrono::{Duration, NaiveDate, Utc};
+use chrono::{Duration, NaiveDate};
 use sqlx::PgPool;
 use tracing::info;
 
@@ -11,6 +11,7 @@ use crate::models::{
     RegimeDetectionParams, RegimeType, RiskPreferences,
 };
 use crate::models::risk::RiskThresholdSettings;
+use crate::services::clock;
 
 /// Detect the current market regime based on recent market data
 ///
@@ -248,7 +249,7 @@ pub async fn get_regime_history(
     pool: &PgPool,
     days: i64,
 ) -> Result<Vec<MarketRegime>, AppError> {
-    let end_date = Utc::now().date_naive();
+    let end_date = clock::today();
     let start_date = end_date - Duration::days(days);
 
     market_regime_queries::get_regime_history(pool, start_date, end_date)
@@ -0,0 +1,187 @@
+//! Market breadth and internals.
+//!
+//! Breadth is computed over the tracked universe: every ticker with recent closes
+//! in `price_points` (holdings, watchlists, benchmarks and screened tickers). A
+//! ticker only counts on a date if it closed on that date, so stale series do not
+//! skew the readings.
+
+use std::collections::HashMap;
+
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, NaiveDate, Utc};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::db::{market_breadth_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{MarketBreadth, MarketBreadthResponse, PricePoint};
+use crate::services::clock;
+
+/// Trading days in the 52-week high/low window
+const YEAR_TRADING_DAYS: usize = 252;
+/// Minimum history before a ticker can register a 52-week high or low
+const MIN_HIGH_LOW_HISTORY: usize = 50;
+/// Tickers without a close in this many calendar days are not tracked
+const UNIVERSE_STALENESS_DAYS: i64 = 10;
+
+fn sma(closes: &[f64], period: usize) -> Option<f64> {
+    (closes.len() >= period).then(|| closes[closes.len() - period..].iter().sum::<f64>() / period as f64)
+}
+
+fn percent(count: usize, of: usize) -> Option<f64> {
+    (of > 0).then(|| count as f64 / of as f64 * 100.0)
+}
+
+/// Breadth on the latest date present in `series` (ascending closes per ticker)
+pub fn compute_breadth(series: &HashMap<String, Vec<PricePoint>>) -> Option<MarketBreadth> {
+    let date = series.values().filter_map(|points| points.last().map(|p| p.date)).max()?;
+
+    let (mut advancers, mut decliners, mut unchanged) = (0, 0, 0);
+    let (mut above50, mut eligible50, mut above200, mut eligible200) = (0, 0, 0, 0);
+    let (mut new_highs, mut new_lows) = (0, 0);
+
+    for points in series.values() {
+        if points.last().map(|p| p.date) != Some(date) || points.len() < 2 {
+            continue;
+        }
+        let closes: Vec<f64> = points.iter().filter_map(|p| p.close_price.to_f64()).collect();
+        if closes.len() != points.len() {
+            continue;
+        }
+        let last = closes[closes.len() - 1];
+        let prev = closes[closes.len() - 2];
+
+        match last.partial_cmp(&prev) {
+            Some(std::cmp::Ordering::Greater) => advancers += 1,
+            Some(std::cmp::Ordering::Less) => decliners += 1,
+            _ => unchanged += 1,
+        }
+
+        if let Some(avg) = sma(&closes, 50) {
+            eligible50 += 1;
+            if last > avg {
+                above50 += 1;
+            }
+        }
+        if let Some(avg) = sma(&closes, 200) {
+            eligible200 += 1;
+            if last > avg {
+                above200 += 1;
+            }
+        }
+
+        if closes.len() >= MIN_HIGH_LOW_HISTORY {
+            let window = &closes[closes.len().saturating_sub(YEAR_TRADING_DAYS)..];
+            let high = window.iter().copied().fold(f64::MIN, f64::max);
+            let low = window.iter().copied().fold(f64::MAX, f64::min);
+            if last >= high {
+                new_highs += 1;
+            } else if last <= low {
+                new_lows += 1;
+            }
+        }
+    }
+
+    Some(MarketBreadth {
+        date,
+        universe_size: advancers + decliners + unchanged,
+        advancers,
+        decliners,
+        unchanged,
+        pct_above_sma50: percent(above50 as usize, eligible50),
+        pct_above_sma200: percent(above200 as usize, eligible200),
+        new_highs,
+        new_lows,
+        computed_at: Utc::now(),
+    })
+}
+
+/// Compute breadth for the latest trading day and store it
+pub async fn update_breadth(pool: &PgPool) -> Result<Option<MarketBreadth>, AppError> {
+    let since = clock::today() - Duration::days(UNIVERSE_STALENESS_DAYS);
+    let tickers = price_queries::fetch_tickers_with_prices_since(pool, since).await?;
+    if tickers.is_empty() {
+        info!("No tracked tickers with recent prices; skipping breadth update");
+        return Ok(None);
+    }
+
+    let series = price_queries::fetch_window_batch(pool, &tickers, YEAR_TRADING_DAYS as i64).await?;
+    let Some(breadth) = compute_breadth(&series) else {
+        return Ok(None);
+    };
+    let stored = market_breadth_queries::upsert(pool, &breadth).await?;
+    info!(
+        "Market breadth for {}: {} advancers, {} decliners, {} new highs, {} new lows ({} tickers)",
+        stored.date, stored.advancers, stored.decliners, stored.new_highs, stored.new_lows, stored.universe_size
+    );
+    Ok(Some(stored))
+}
+
+/// Latest breadth plus `days` of history
+pub async fn get_breadth(pool: &PgPool, days: i64) -> Result<MarketBreadthResponse, AppError> {
+    if !(1..=3650).contains(&days) {
+        return Err(AppError::Validation("days must be between 1 and 3650".to_string()));
+    }
+    let since: NaiveDate = clock::today() - Duration::days(days);
+    let history = market_breadth_queries::fetch_since(pool, since).await?;
+    let latest = match history.last() {
+        Some(latest) => Some(latest.clone()),
+        None => market_breadth_queries::fetch_latest(pool).await?,
+    };
+    Ok(MarketBreadthResponse {
+        advance_decline_ratio: latest.as_ref().and_then(|b| b.advance_decline_ratio()),
+        latest,
+        history,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::{BigDecimal, FromPrimitive};
+    use uuid::Uuid;
+
+    fn series(ticker: &str, closes: &[f64], last_day: u32) -> Vec<PricePoint> {
+        let end = NaiveDate::from_ymd_opt(2026, 3, last_day).unwrap();
+        let n = closes.len() as i64;
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, c)| PricePoint {
+                id: Uuid::nil(),
+                ticker: ticker.to_string(),
+                date: end - Duration::days(n - 1 - i as i64),
+                close_price: BigDecimal::from_f64(*c).unwrap(),
+                created_at: Utc::now(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compute_breadth() {
+        let rising: Vec<f64> = (1..=60).map(|i| i as f64).collect();
+        let falling: Vec<f64> = (1..=60).rev().map(|i| i as f64).collect();
+        let map: HashMap<String, Vec<PricePoint>> = [
+            ("UP".to_string(), series("UP", &rising, 20)),
+            ("DOWN".to_string(), series("DOWN", &falling, 20)),
+            ("FLAT".to_string(), series("FLAT", &[5.0, 5.0], 20)),
+            // Stale: last close before the latest date
+            ("OLD".to_string(), series("OLD", &rising, 19)),
+        ]
+        .into_iter()
+        .collect();
+
+        let b = compute_breadth(&map).unwrap();
+        assert_eq!(b.date, NaiveDate::from_ymd_opt(2026, 3, 20).unwrap());
+        assert_eq!((b.universe_size, b.advancers, b.decliners, b.unchanged), (3, 1, 1, 1));
+        assert_eq!(b.pct_above_sma50, Some(50.0));
+        assert_eq!(b.pct_above_sma200, None);
+        assert_eq!((b.new_highs, b.new_lows), (1, 1));
+        assert_eq!(b.advance_decline_ratio(), Some(1.0));
+    }
+
+    #[test]
+    fn test_compute_breadth_empty() {
+        assert!(compute_breadth(&HashMap::new()).is_none());
+    }
+}
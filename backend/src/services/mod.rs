@@ -1,15 +1,55 @@
 pub mod analytics_service;
+pub mod export_service;
+pub mod annotation_service;
+pub mod lot_service;
+pub mod pnl_service;
+pub mod wash_sale_service;
+pub mod drip_service;
+pub mod benchmark_comparison_service;
+pub mod contribution_service;
+pub mod macro_shock_service;
+pub mod market_service;
+pub mod risk_budget_service;
+pub mod constrained_optimization_service;
+pub mod trade_rounding_service;
+pub mod trade_journal_service;
+pub mod behavioral_analytics_service;
+pub mod rebalance_simulation_service;
+pub mod portfolio_news_service;
+pub mod ownership_service;
+pub mod fundamentals_service;
+pub mod etf_constituent_service;
+pub mod dividend_calendar_service;
+pub mod fx_service;
+pub mod i18n;
+pub mod analyst_service;
+pub mod asset_location_service;
+pub mod fee_service;
+pub mod advisor_fee_service;
+pub mod glide_path_service;
+pub mod crypto_wallet_service;
+pub mod health_check_service;
+pub mod model_portfolio_service;
+pub mod peer_statistics_service;
+pub mod snapshot_retention_service;
+pub mod user_data_service;
+pub mod latency_monitor_service;
 pub mod price_service;
 pub mod portfolio_service;
+pub mod portfolio_member_service;
 pub mod csv_import_service;
 pub mod activity_import_service;
 pub mod transaction_detection_service;
 pub mod risk_service;
+pub mod risk_memo;
 pub mod risk_snapshot_service;
 pub mod optimization_service;
 pub mod portfolio_risk_cache_service;
+pub mod risk_badge_service;
 pub mod failure_cache;
 pub mod rate_limiter;
+pub mod clock;
+pub mod timezone;
 pub mod llm_service;
 pub mod narrative_service;
 pub mod news_service;
@@ -31,9 +71,33 @@ pub mod user_preference_service;
 pub mod clustering;
 pub mod signal_service;
 pub mod factor_service;
+pub mod factor_spread_service;
 pub mod explanation_service;
 pub mod watchlist_monitoring_service;
 pub mod long_term_guidance_service;
 pub mod screening_service;
 pub(crate) mod indicators;
-pub mod financial_snapshot_service;
\ No newline at end of file
+pub(crate) mod quant;
+pub mod financial_snapshot_service;
+pub mod goal_probability_service;
+pub mod import_batch_service;
+pub mod inbound_email_service;
+pub mod auth_service;
+pub(crate) mod pdf_text;
+pub mod pdf_statement_service;
+pub mod benchmark_seed_service;
+pub mod session_service;
+pub mod public_api_service;
+pub mod datasource_service;
+pub mod audit_service;
+pub mod portfolio_return_service;
+pub mod admin_user_service;
+pub mod provider_status_service;
+pub mod calendar_service;
+pub mod risk_recompute_service;
+pub mod chaos;
+pub mod price_backfill_service;
+pub mod integrity_service;
+pub mod widget_service;
+pub mod data_quality_service;
+pub mod factor_regression_service;
@@ -0,0 +1,375 @@
+//! Model portfolio benchmarking.
+//!
+//! Compares the portfolio's current weights with a model from the library: both
+//! are backtested over the same dates with daily rebalancing, scored on the
+//! usual factors, and the differences are summarized as a gap analysis.
+
+use std::collections::HashMap;
+
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{holding_snapshot_queries, model_portfolio_queries, price_queries};
+use crate::errors::AppError;
+use crate::external::price_provider::PriceProvider;
+use crate::models::factor::PortfolioFactorExposure;
+use crate::models::{
+    AllocationGap, FactorGap, ModelComparisonQuery, ModelPortfolio, ModelPortfolioComparison, PositionRisk,
+    RiskProfile,
+};
+use crate::services::contribution_service::parse_period;
+use crate::services::failure_cache::FailureCache;
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::{factor_service, rebalance_simulation_service, risk_service};
+use crate::services::clock;
+
+pub const DEFAULT_MODEL: &str = "sixty_forty";
+pub const DEFAULT_PERIOD: &str = "3y";
+const TRADING_DAYS: f64 = 252.0;
+/// Lookback for factor scores, matching the factor analysis default
+const FACTOR_DAYS: i64 = 252;
+/// Differences smaller than these are not called out in the findings
+const VOLATILITY_GAP_POINTS: f64 = 2.0;
+const DRAWDOWN_GAP_POINTS: f64 = 5.0;
+const RETURN_GAP_POINTS: f64 = 1.0;
+const FACTOR_GAP_POINTS: f64 = 10.0;
+
+/// Backtest `weights` (0-1, one per column of `closes`) rebalanced daily.
+/// `risk_free_rate` is annual, e.g. 0.045.
+pub fn risk_profile(closes: &[Vec<f64>], weights: &[f64], risk_free_rate: f64) -> RiskProfile {
+    if closes.len() < 2 || weights.is_empty() {
+        return RiskProfile::default();
+    }
+
+    let returns: Vec<f64> = closes
+        .windows(2)
+        .map(|w| weights.iter().zip(w[0].iter().zip(&w[1])).map(|(wt, (a, b))| wt * (b / a - 1.0)).sum())
+        .collect();
+
+    let mut value = 1.0;
+    let mut peak = 1.0;
+    let mut max_drawdown: f64 = 0.0;
+    for r in &returns {
+        value *= 1.0 + r;
+        peak = f64::max(peak, value);
+        max_drawdown = max_drawdown.min((value / peak - 1.0) * 100.0);
+    }
+
+    let days = returns.len() as f64;
+    let annualized_return = if value > 0.0 { (value.powf(TRADING_DAYS / days) - 1.0) * 100.0 } else { -100.0 };
+    let mean = returns.iter().sum::<f64>() / days;
+    let annualized_volatility = if returns.len() > 1 {
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (days - 1.0);
+        variance.sqrt() * TRADING_DAYS.sqrt() * 100.0
+    } else {
+        0.0
+    };
+    let sharpe = (annualized_volatility > 0.0)
+        .then(|| (annualized_return - risk_free_rate * 100.0) / annualized_volatility);
+
+    let risk_score = risk_service::score_risk(&PositionRisk {
+        volatility: annualized_volatility,
+        max_drawdown,
+        beta: None,
+        beta_spy: None,
+        beta_qqq: None,
+        beta_iwm: None,
+        risk_decomposition: None,
+        sharpe,
+        sortino: None,
+        annualized_return: Some(annualized_return),
+        value_at_risk: None,
+        var_95: None,
+        var_99: None,
+        expected_shortfall_95: None,
+        expected_shortfall_99: None,
+    });
+
+    RiskProfile {
+        total_return: (value - 1.0) * 100.0,
+        annualized_return,
+        annualized_volatility,
+        max_drawdown,
+        sharpe,
+        risk_score,
+    }
+}
+
+/// Pair up factor exposures by factor
+pub fn factor_gaps(portfolio: &[PortfolioFactorExposure], model: &[PortfolioFactorExposure]) -> Vec<FactorGap> {
+    portfolio
+        .iter()
+        .filter_map(|p| {
+            let m = model.iter().find(|m| m.factor == p.factor)?;
+            Some(FactorGap {
+                factor: p.factor.clone(),
+                label: p.label.clone(),
+                portfolio_score: p.score,
+                model_score: m.score,
+                difference: p.score - m.score,
+            })
+        })
+        .collect()
+}
+
+/// Portfolio weight in each model ticker, and the overlap with the model in percent
+pub fn allocation_gaps(model: &ModelPortfolio, portfolio_weights: &HashMap<String, f64>) -> (Vec<AllocationGap>, f64) {
+    let mut overlap = 0.0;
+    let gaps = model
+        .allocations
+        .iter()
+        .map(|a| {
+            let held = portfolio_weights.get(&a.ticker).copied().unwrap_or(0.0);
+            overlap += held.min(a.weight);
+            AllocationGap {
+                ticker: a.ticker.clone(),
+                asset_class: a.asset_class.clone(),
+                portfolio_weight: held,
+                model_weight: a.weight,
+            }
+        })
+        .collect();
+    (gaps, overlap)
+}
+
+/// Plain-language differences worth calling out
+pub fn findings(model_name: &str, portfolio: &RiskProfile, model: &RiskProfile, factors: &[FactorGap]) -> Vec<String> {
+    let mut findings = Vec::new();
+
+    let volatility_gap = portfolio.annualized_volatility - model.annualized_volatility;
+    if volatility_gap.abs() >= VOLATILITY_GAP_POINTS {
+        findings.push(format!(
+            "Volatility is {:.1} points {} than the {} model ({:.1}% vs {:.1}%)",
+            volatility_gap.abs(),
+            if volatility_gap > 0.0 { "higher" } else { "lower" },
+            model_name,
+            portfolio.annualized_volatility,
+            model.annualized_volatility
+        ));
+    }
+
+    let drawdown_gap = portfolio.max_drawdown - model.max_drawdown;
+    if drawdown_gap.abs() >= DRAWDOWN_GAP_POINTS {
+        findings.push(format!(
+            "Worst drawdown was {:.1} points {} than the {} model ({:.1}% vs {:.1}%)",
+            drawdown_gap.abs(),
+            if drawdown_gap < 0.0 { "deeper" } else { "shallower" },
+            model_name,
+            portfolio.max_drawdown,
+            model.max_drawdown
+        ));
+    }
+
+    let return_gap = portfolio.annualized_return - model.annualized_return;
+    if return_gap.abs() >= RETURN_GAP_POINTS {
+        findings.push(format!(
+            "Annualized return {} the {} model by {:.1} points",
+            if return_gap > 0.0 { "beat" } else { "trailed" },
+            model_name,
+            return_gap.abs()
+        ));
+    }
+
+    for gap in factors.iter().filter(|g| g.difference.abs() >= FACTOR_GAP_POINTS) {
+        findings.push(format!(
+            "{} tilt is {} than the model ({:.0} vs {:.0})",
+            gap.label,
+            if gap.difference > 0.0 { "stronger" } else { "weaker" },
+            gap.portfolio_score,
+            gap.model_score
+        ));
+    }
+
+    if findings.is_empty() {
+        findings.push(format!("Risk, drawdown and factor profile are close to the {} model", model_name));
+    }
+    findings
+}
+
+pub async fn list_models(pool: &PgPool) -> Result<Vec<ModelPortfolio>, AppError> {
+    Ok(model_portfolio_queries::fetch_all(pool).await?)
+}
+
+pub async fn compare_to_model(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    query: &ModelComparisonQuery,
+    price_provider: &dyn PriceProvider,
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
+    risk_free_rate: f64,
+) -> Result<ModelPortfolioComparison, AppError> {
+    let model_id = query.model.as_deref().unwrap_or(DEFAULT_MODEL);
+    let model = model_portfolio_queries::fetch_one(pool, model_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Model portfolio '{}' not found", model_id)))?;
+    let period = query.period.as_deref().unwrap_or(DEFAULT_PERIOD);
+    let days = parse_period(period)?;
+
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let mut values: HashMap<String, f64> = HashMap::new();
+    for holding in holdings.iter().filter(|h| !h.ticker.is_empty()) {
+        *values.entry(holding.ticker.clone()).or_insert(0.0) += holding.market_value.to_f64().unwrap_or(0.0);
+    }
+    values.retain(|_, v| *v > 0.0);
+    let total: f64 = values.values().sum();
+    if total <= 0.0 {
+        return Err(AppError::Validation("Portfolio has no holdings to compare".to_string()));
+    }
+    let portfolio_weights: HashMap<String, f64> = values.iter().map(|(t, v)| (t.clone(), v / total * 100.0)).collect();
+
+    let mut tickers: Vec<String> = portfolio_weights.keys().cloned().collect();
+    tickers.extend(model.allocations.iter().map(|a| a.ticker.clone()));
+    tickers.sort();
+    tickers.dedup();
+
+    let start = clock::today() - Duration::days(days);
+    let windows = price_queries::fetch_window_batch(pool, &tickers, days + 1).await?;
+    let prices: HashMap<String, Vec<(NaiveDate, f64)>> = windows
+        .into_iter()
+        .map(|(ticker, points)| {
+            let series: Vec<(NaiveDate, f64)> = points
+                .iter()
+                .filter(|p| p.date >= start)
+                .filter_map(|p| p.close_price.to_f64().map(|c| (p.date, c)))
+                .collect();
+            (ticker, series)
+        })
+        .filter(|(_, series)| !series.is_empty())
+        .collect();
+    let missing_prices: Vec<String> = tickers.iter().filter(|t| !prices.contains_key(*t)).cloned().collect();
+    tickers.retain(|t| prices.contains_key(t));
+
+    // Both weight vectors are renormalized over the tickers that have prices
+    let column_weights = |weights: &HashMap<String, f64>| -> Vec<f64> {
+        let raw: Vec<f64> = tickers.iter().map(|t| weights.get(t).copied().unwrap_or(0.0)).collect();
+        let sum: f64 = raw.iter().sum();
+        raw.iter().map(|w| if sum > 0.0 { w / sum } else { 0.0 }).collect()
+    };
+    let model_weights: HashMap<String, f64> = model.allocations.iter().map(|a| (a.ticker.clone(), a.weight)).collect();
+    let (dates, closes) = rebalance_simulation_service::align(&tickers, &prices);
+    let portfolio_profile = risk_profile(&closes, &column_weights(&portfolio_weights), risk_free_rate);
+    let model_profile = risk_profile(&closes, &column_weights(&model_weights), risk_free_rate);
+
+    let as_holdings = |weights: &HashMap<String, f64>| -> Vec<(String, Option<String>, f64)> {
+        let mut list: Vec<(String, Option<String>, f64)> =
+            weights.iter().map(|(t, w)| (t.clone(), None, w / 100.0)).collect();
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        list
+    };
+    let portfolio_scores = factor_service::score_holdings(
+        pool,
+        &as_holdings(&portfolio_weights),
+        price_provider,
+        failure_cache,
+        rate_limiter,
+        risk_free_rate,
+        FACTOR_DAYS,
+    )
+    .await;
+    let model_scores = factor_service::score_holdings(
+        pool,
+        &as_holdings(&model_weights),
+        price_provider,
+        failure_cache,
+        rate_limiter,
+        risk_free_rate,
+        FACTOR_DAYS,
+    )
+    .await;
+    let factor_gaps = factor_gaps(
+        &factor_service::compute_portfolio_exposures(&portfolio_scores),
+        &factor_service::compute_portfolio_exposures(&model_scores),
+    );
+
+    let (allocation_gaps, overlap_pct) = allocation_gaps(&model, &portfolio_weights);
+    let findings = findings(&model.name, &portfolio_profile, &model_profile, &factor_gaps);
+
+    Ok(ModelPortfolioComparison {
+        portfolio_id,
+        model,
+        period: period.to_string(),
+        start_date: dates.first().copied(),
+        end_date: dates.last().copied(),
+        portfolio_profile,
+        model_profile,
+        factor_gaps,
+        allocation_gaps,
+        overlap_pct,
+        findings,
+        missing_prices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ModelAllocation;
+    use crate::models::factor::FactorType;
+
+    #[test]
+    fn test_risk_profile_tracks_drawdown_and_volatility() {
+        let closes = vec![vec![100.0, 50.0], vec![110.0, 50.0], vec![88.0, 50.0], vec![99.0, 50.0]];
+        let all_stock = risk_profile(&closes, &[1.0, 0.0], 0.0);
+        assert!((all_stock.total_return + 1.0).abs() < 1e-9);
+        assert!((all_stock.max_drawdown + 20.0).abs() < 1e-9);
+
+        let flat = risk_profile(&closes, &[0.0, 1.0], 0.0);
+        assert_eq!(flat.annualized_volatility, 0.0);
+        assert_eq!(flat.max_drawdown, 0.0);
+        assert!(flat.sharpe.is_none());
+
+        let balanced = risk_profile(&closes, &[0.5, 0.5], 0.0);
+        assert!(balanced.annualized_volatility < all_stock.annualized_volatility);
+        assert!(balanced.risk_score < all_stock.risk_score);
+    }
+
+    #[test]
+    fn test_allocation_gaps_measure_overlap() {
+        let model = ModelPortfolio {
+            id: "sixty_forty".to_string(),
+            name: "60/40".to_string(),
+            description: String::new(),
+            allocations: vec![
+                ModelAllocation { ticker: "VTI".to_string(), asset_class: "us_equity".to_string(), weight: 60.0 },
+                ModelAllocation { ticker: "BND".to_string(), asset_class: "us_bonds".to_string(), weight: 40.0 },
+            ],
+        };
+        let weights = HashMap::from([("VTI".to_string(), 80.0), ("AAPL".to_string(), 20.0)]);
+        let (gaps, overlap) = allocation_gaps(&model, &weights);
+        assert_eq!(overlap, 60.0);
+        assert_eq!(gaps[1].portfolio_weight, 0.0);
+    }
+
+    #[test]
+    fn test_findings_call_out_material_gaps() {
+        let portfolio = RiskProfile {
+            annualized_volatility: 22.0,
+            max_drawdown: -30.0,
+            annualized_return: 9.0,
+            ..Default::default()
+        };
+        let model = RiskProfile {
+            annualized_volatility: 11.0,
+            max_drawdown: -18.0,
+            annualized_return: 8.5,
+            ..Default::default()
+        };
+        let factors = vec![FactorGap {
+            factor: FactorType::Momentum,
+            label: "Momentum".to_string(),
+            portfolio_score: 72.0,
+            model_score: 50.0,
+            difference: 22.0,
+        }];
+        let lines = findings("60/40", &portfolio, &model, &factors);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Volatility is 11.0 points higher"));
+        assert!(lines[1].contains("deeper"));
+        assert!(lines[2].starts_with("Momentum tilt is stronger"));
+
+        assert_eq!(findings("60/40", &model, &model, &[]).len(), 1);
+    }
+}
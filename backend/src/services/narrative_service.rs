@@ -1,17 +1,35 @@
 use chrono::Utc;
+use sqlx::PgPool;
 use tracing::{info, warn};
 use uuid::Uuid;
 
+use crate::db::{llm_queries, user_preferences_queries};
 use crate::errors::{AppError, LlmError};
-use crate::models::{PortfolioNarrative, PortfolioRisk};
+use crate::models::{LlmConsent, Locale, PortfolioNarrative, PortfolioRisk, CURRENT_LLM_CONSENT_VERSION};
 use crate::services::llm_service::LlmService;
 use std::sync::Arc;
 
-/// Generate a narrative summary for a portfolio
+/// Generate a narrative summary for a portfolio.
+///
+/// `theses` are the user's stated investment theses as (ticker, thesis) pairs; the
+/// narrative relates position risk back to them. `contributors` are leader/laggard
+/// attribution lines from the benchmark comparison, used for the top contributors.
+/// `headlines` are the most relevant recent headlines for held tickers.
+/// `risk_changes` explain recent material moves in the risk score, from the
+/// change drivers stored with risk snapshots.
+///
+/// Refuses with [`AppError::ConsentRequired`] unless the user's latest consent
+/// record grants the current consent version.
+#[allow(clippy::too_many_arguments)]
 pub async fn generate_portfolio_narrative(
+    pool: &PgPool,
     llm_service: Arc<LlmService>,
     user_id: Uuid,
     portfolio_risk: &PortfolioRisk,
+    theses: &[(String, String)],
+    contributors: &[String],
+    headlines: &[String],
+    risk_changes: &[String],
     time_period: &str,
 ) -> Result<PortfolioNarrative, AppError> {
     info!("Generating narrative for portfolio (time_period: {})", time_period);
@@ -21,8 +39,12 @@ pub async fn generate_portfolio_narrative(
         return Err(AppError::Llm(LlmError::Disabled));
     }
 
-    // Build the prompt
-    let prompt = build_narrative_prompt(portfolio_risk, time_period);
+    let consent = llm_queries::fetch_latest_consent(pool, user_id).await?;
+    ensure_consent(consent.as_ref())?;
+
+    // Build the prompt, asking for prose in the user's language
+    let locale = user_preferences_queries::get_locale(pool, user_id).await?;
+    let prompt = build_narrative_prompt(portfolio_risk, theses, contributors, headlines, risk_changes, time_period, locale);
 
     // Generate completion with rate limiting
     let response = llm_service
@@ -30,11 +52,27 @@ pub async fn generate_portfolio_narrative(
         .await?;
 
     // Parse the response
-    parse_narrative_response(&response, portfolio_risk)
+    parse_narrative_response(&response, portfolio_risk, contributors)
+}
+
+/// Check that the latest consent record grants the current consent version
+fn ensure_consent(consent: Option<&LlmConsent>) -> Result<(), AppError> {
+    match consent {
+        Some(c) if c.is_current() => Ok(()),
+        _ => Err(AppError::ConsentRequired { required_version: CURRENT_LLM_CONSENT_VERSION }),
+    }
 }
 
 /// Build a detailed prompt for portfolio narrative generation
-fn build_narrative_prompt(portfolio_risk: &PortfolioRisk, time_period: &str) -> String {
+fn build_narrative_prompt(
+    portfolio_risk: &PortfolioRisk,
+    theses: &[(String, String)],
+    contributors: &[String],
+    headlines: &[String],
+    risk_changes: &[String],
+    time_period: &str,
+    locale: Locale,
+) -> String {
     let position_count = portfolio_risk.position_risks.len();
     let avg_volatility = if !portfolio_risk.position_risks.is_empty() {
         portfolio_risk.position_risks.iter()
@@ -70,6 +108,64 @@ fn build_narrative_prompt(portfolio_risk: &PortfolioRisk, time_period: &str) ->
         .map(|p| format!("{} ({:.1}% volatility)", p.ticker, p.risk_assessment.metrics.volatility))
         .collect();
 
+    // Stated theses for positions still held
+    let held_theses: Vec<String> = theses
+        .iter()
+        .filter(|(ticker, _)| portfolio_risk.position_risks.iter().any(|p| &p.ticker == ticker))
+        .map(|(ticker, thesis)| format!("- {}: {}", ticker, thesis))
+        .collect();
+    let thesis_section = if held_theses.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\nSTATED INVESTMENT THESES (the investor's own reasons for holding):\n{}\n\nWhere a thesis is stated, refer to it when discussing that position and note whether its risk profile is consistent with the stated rationale.\n",
+            held_theses.join("\n")
+        )
+    };
+
+    // Measured attribution replaces guessing contributors from value and volatility
+    let (attribution_section, contributor_instruction) = if contributors.is_empty() {
+        (
+            String::new(),
+            "List 3 positions that most impact the portfolio (positive or negative)",
+        )
+    } else {
+        (
+            format!(
+                "\nPERFORMANCE ATTRIBUTION (contribution = weight x period return, with sector ETF and benchmark returns):\n{}\n",
+                contributors.iter().map(|c| format!("- {}", c)).collect::<Vec<_>>().join("\n")
+            ),
+            "List the 3 most significant leaders or laggards from the PERFORMANCE ATTRIBUTION data, citing their contribution and how they did versus their sector and the benchmark",
+        )
+    };
+
+    let news_section = if headlines.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\nRECENT HEADLINES FOR HOLDINGS (most relevant first; mention only where they help explain the portfolio's state):\n{}\n",
+            headlines.iter().map(|h| format!("- {}", h)).collect::<Vec<_>>().join("\n")
+        )
+    };
+
+    let risk_change_section = if risk_changes.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\nRECENT RISK SCORE CHANGES (what changed in the portfolio when the score moved; use these to explain the current risk level):\n{}\n",
+            risk_changes.iter().map(|c| format!("- {}", c)).collect::<Vec<_>>().join("\n")
+        )
+    };
+
+    let language_requirement = if locale == Locale::En {
+        String::new()
+    } else {
+        format!(
+            "- Write every text value in {}; keep the JSON keys in English\n",
+            locale.language_name()
+        )
+    };
+
     format!(
         r#"Analyze this investment portfolio's {} performance and provide educational insights:
 
@@ -85,7 +181,7 @@ TOP HOLDINGS:
 
 HIGHEST RISK POSITIONS:
 {}
-
+{}{}{}{}
 INSTRUCTIONS:
 Generate a concise portfolio analysis with the following sections. Use clear, educational language suitable for retail investors.
 
@@ -105,7 +201,7 @@ Generate a concise portfolio analysis with the following sections. Use clear, ed
    - Include both position-level and portfolio-level risks
 
 4. TOP CONTRIBUTORS (3 items):
-   - List 3 positions that most impact the portfolio (positive or negative)
+   - {}
    - Brief explanation of why each matters
    - Focus on their influence on overall portfolio metrics
 
@@ -116,7 +212,7 @@ IMPORTANT REQUIREMENTS:
 - Focus on risk analysis and portfolio construction insights
 - Be objective and factual based on the metrics provided
 - Keep the tone professional but accessible
-
+{}
 Format your response as valid JSON with this structure:
 {{
   "summary": "...",
@@ -132,7 +228,13 @@ Format your response as valid JSON with this structure:
         avg_volatility,
         top_positions.join("\n"),
         high_risk_positions.join("\n"),
-        time_period
+        attribution_section,
+        thesis_section,
+        news_section,
+        risk_change_section,
+        time_period,
+        contributor_instruction,
+        language_requirement
     )
 }
 
@@ -140,6 +242,7 @@ Format your response as valid JSON with this structure:
 fn parse_narrative_response(
     response: &str,
     portfolio_risk: &PortfolioRisk,
+    contributors: &[String],
 ) -> Result<PortfolioNarrative, AppError> {
     // Try to parse as JSON first
     if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(response) {
@@ -198,12 +301,16 @@ fn parse_narrative_response(
             format!("Portfolio max drawdown: {:.2}%", portfolio_risk.portfolio_max_drawdown),
             "Monitor individual position volatility regularly".to_string(),
         ],
-        top_contributors: portfolio_risk
-            .position_risks
-            .iter()
-            .take(3)
-            .map(|p| format!("{}: ${:.2} ({:.1}% volatility)", p.ticker, p.market_value, p.risk_assessment.metrics.volatility))
-            .collect(),
+        top_contributors: if contributors.is_empty() {
+            portfolio_risk
+                .position_risks
+                .iter()
+                .take(3)
+                .map(|p| format!("{}: ${:.2} ({:.1}% volatility)", p.ticker, p.market_value, p.risk_assessment.metrics.volatility))
+                .collect()
+        } else {
+            contributors.iter().take(3).cloned().collect()
+        },
         generated_at: Utc::now(),
     })
 }
@@ -213,6 +320,25 @@ mod tests {
     use super::*;
     use crate::models::{PositionRisk, PositionRiskContribution, RiskAssessment, RiskLevel};
 
+    #[test]
+    fn test_ensure_consent() {
+        let consent = |version, granted| LlmConsent {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            version,
+            granted,
+            created_at: Utc::now(),
+        };
+
+        assert!(ensure_consent(Some(&consent(CURRENT_LLM_CONSENT_VERSION, true))).is_ok());
+        for missing in [None, Some(consent(CURRENT_LLM_CONSENT_VERSION, false)), Some(consent(CURRENT_LLM_CONSENT_VERSION - 1, true))] {
+            assert!(matches!(
+                ensure_consent(missing.as_ref()),
+                Err(AppError::ConsentRequired { required_version: CURRENT_LLM_CONSENT_VERSION })
+            ));
+        }
+    }
+
     #[test]
     fn test_build_narrative_prompt() {
         let portfolio_risk = PortfolioRisk {
@@ -221,13 +347,19 @@ mod tests {
             portfolio_volatility: 15.5,
             portfolio_max_drawdown: -12.0,
             portfolio_beta: Some(1.1),
+            portfolio_regression_beta: None,
+            portfolio_alpha: None,
+            portfolio_r_squared: None,
             portfolio_sharpe: Some(1.3),
+            portfolio_sortino: Some(1.8),
+            portfolio_sharpe_weighted_average: Some(1.1),
             portfolio_var_95: Some(-4.5),
             portfolio_var_99: Some(-7.0),
             portfolio_expected_shortfall_95: Some(-5.5),
             portfolio_expected_shortfall_99: Some(-8.5),
             portfolio_risk_score: 65.0,
             risk_level: RiskLevel::Moderate,
+            scoring_version: 1,
             position_risks: vec![
                 PositionRiskContribution {
                     ticker: "AAPL".to_string(),
@@ -254,16 +386,49 @@ mod tests {
                         },
                         risk_score: 60.0,
                         risk_level: RiskLevel::Moderate,
+                        scoring_version: 1,
                     },
                 },
             ],
         };
 
-        let prompt = build_narrative_prompt(&portfolio_risk, "30 days");
+        let prompt = build_narrative_prompt(&portfolio_risk, &[], &[], &[], &[], "30 days", Locale::En);
 
         assert!(prompt.contains("Total Value: $100000.00"));
         assert!(prompt.contains("Portfolio Risk Score: 65.0/100"));
         assert!(prompt.contains("AAPL"));
         assert!(prompt.contains("valid JSON"));
+        assert!(!prompt.contains("STATED INVESTMENT THESES"));
+
+        let theses = vec![
+            ("AAPL".to_string(), "Services revenue keeps compounding".to_string()),
+            ("TSLA".to_string(), "Not held anymore".to_string()),
+        ];
+        let prompt = build_narrative_prompt(&portfolio_risk, &theses, &[], &[], &[], "30 days", Locale::En);
+        assert!(prompt.contains("- AAPL: Services revenue keeps compounding"));
+        assert!(!prompt.contains("TSLA"));
+        assert!(!prompt.contains("PERFORMANCE ATTRIBUTION"));
+
+        let contributors = vec!["AAPL: +6.00 pts of portfolio return (+12.0% return, 50.0% weight)".to_string()];
+        let prompt = build_narrative_prompt(&portfolio_risk, &[], &contributors, &[], &[], "30 days", Locale::En);
+        assert!(prompt.contains("PERFORMANCE ATTRIBUTION"));
+        assert!(prompt.contains("- AAPL: +6.00 pts of portfolio return"));
+        assert!(prompt.contains("leaders or laggards"));
+        assert!(!prompt.contains("RECENT HEADLINES"));
+
+        let headlines = vec!["AAPL: Apple beats estimates (Wire, 2026-03-01, positive)".to_string()];
+        let prompt = build_narrative_prompt(&portfolio_risk, &[], &[], &headlines, &[], "30 days", Locale::En);
+        assert!(prompt.contains("RECENT HEADLINES FOR HOLDINGS"));
+        assert!(prompt.contains("- AAPL: Apple beats estimates"));
+        assert!(!prompt.contains("RECENT RISK SCORE CHANGES"));
+
+        let risk_changes = vec!["2026-03-02: risk score 52.0 -> 61.5 (TSLA added (30.0% of the portfolio))".to_string()];
+        let prompt = build_narrative_prompt(&portfolio_risk, &[], &[], &[], &risk_changes, "30 days", Locale::En);
+        assert!(prompt.contains("RECENT RISK SCORE CHANGES"));
+        assert!(prompt.contains("- 2026-03-02: risk score 52.0 -> 61.5"));
+        assert!(!prompt.contains("Write every text value"));
+
+        let prompt = build_narrative_prompt(&portfolio_risk, &[], &[], &[], &[], "30 days", Locale::Fr);
+        assert!(prompt.contains("- Write every text value in French; keep the JSON keys in English"));
     }
 }
@@ -8,6 +8,7 @@ use tracing::{error, info, warn};
 use crate::errors::AppError;
 use crate::models::{NewsArticle, NewsTheme, Sentiment};
 use crate::services::llm_service::LlmService;
+use crate::services::clock;
 
 /// Configuration for news service
 #[derive(Debug, Clone)]
@@ -114,7 +115,7 @@ impl NewsProvider for SerperProvider {
             AppError::External(format!("Failed to parse news response: {}", e))
         })?;
 
-        let cutoff_date = Utc::now() - Duration::days(days as i64);
+        let cutoff_date = clock::now() - Duration::days(days as i64);
 
         let articles: Vec<NewsArticle> = serper_response
             .news
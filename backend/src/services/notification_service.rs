@@ -1,6 +1,10 @@
 use crate::db::alert_queries::*;
+use crate::db::user_preferences_queries;
 use crate::models::alert::*;
-use chrono::Utc;
+use crate::models::risk::ThresholdViolation;
+use crate::models::{DigestFrequency, DividendEvent};
+use crate::services::dividend_calendar_service;
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 use lettre::{
@@ -9,6 +13,7 @@ use lettre::{
     Message, SmtpTransport, Transport,
 };
 use std::env;
+use tracing::{debug, error, info};
 
 // ==============================================================================
 // Notification Service
@@ -23,6 +28,8 @@ pub async fn send_notification(
     // Get user preferences
     let prefs = get_or_create_notification_preferences(pool, user_id).await?;
 
+    let settings = user_preferences_queries::get_notification_settings(pool, user_id).await?;
+
     // Get user info for email
     let user = get_user(pool, user_id).await?;
 
@@ -33,10 +40,12 @@ pub async fn send_notification(
         }
     }
 
-    if prefs.email_enabled {
-        if should_send_email_notification(pool, user_id, &prefs).await? {
-            send_email_notification(pool, &user.email, alert, &prefs).await?;
-        }
+    // Digest users get this alert in their next digest instead
+    if prefs.email_enabled
+        && settings.digest_frequency == DigestFrequency::Immediate
+        && should_send_email_notification(pool, user_id, &prefs).await?
+    {
+        send_email_notification(pool, &user.email, alert, &prefs).await?;
     }
 
     if prefs.webhook_enabled {
@@ -129,10 +138,10 @@ pub async fn send_email_notification(
         // Try to send actual email via SMTP
         match send_email_via_smtp(to_email, alert).await {
             Ok(_) => {
-                println!("✅ Email sent successfully to {} (#{}) via SMTP", to_email, new_count);
+                info!(user_id = %user.id, alert_id = %alert.id, email_count = new_count, "Email sent via SMTP");
             }
             Err(e) => {
-                eprintln!("❌ Failed to send email via SMTP: {}", e);
+                error!(user_id = %user.id, alert_id = %alert.id, error = %e, "Failed to send email via SMTP");
                 log_email_notification(to_email, alert, new_count);
             }
         }
@@ -172,17 +181,18 @@ fn log_email_notification(to_email: &str, alert: &AlertHistory, count: i32) {
         format!("{} Alert", format_rule_type(&alert.rule_type))
     };
 
-    println!("📧 Email notification #{} would be sent:", count);
-    println!("   To: {}", to_email);
-    println!("   Subject: {}", subject);
-    if let Some(ticker) = &alert.ticker {
-        println!("   Ticker: {}", ticker);
-    }
-    println!("   Message: {}", alert.message);
-    println!("   Threshold: {:.2}% | Actual: {:.2}%", alert.threshold, alert.actual_value);
-    println!("   Severity: {}", alert.severity);
-    println!("   Triggered: {}", alert.triggered_at);
-    println!();
+    info!(
+        alert_id = %alert.id,
+        email_count = count,
+        to = to_email,
+        subject = %subject,
+        ticker = alert.ticker.as_deref(),
+        threshold = alert.threshold,
+        actual_value = alert.actual_value,
+        severity = %alert.severity,
+        triggered_at = %alert.triggered_at,
+        "Email notification would be sent (SMTP disabled)"
+    );
 }
 
 /// Send email via SMTP using lettre
@@ -258,8 +268,7 @@ async fn send_email_via_smtp(to_email: &str, alert: &AlertHistory) -> Result<(),
     // Create SMTP transport
     let creds = Credentials::new(smtp_username.clone(), smtp_password.clone());
 
-    println!("🔌 Connecting to SMTP server: {}:{}", smtp_host, smtp_port);
-    println!("👤 Username: {}", smtp_username);
+    debug!(smtp_host = %smtp_host, smtp_port, smtp_username = %smtp_username, "Connecting to SMTP server");
 
     let mailer = SmtpTransport::starttls_relay(&smtp_host)
         .map_err(|e| format!("Failed to create SMTP transport: {}", e))?
@@ -268,14 +277,10 @@ async fn send_email_via_smtp(to_email: &str, alert: &AlertHistory) -> Result<(),
         .build();
 
     // Send email
-    println!("📤 Sending email to {}...", to_email);
     match mailer.send(&email) {
-        Ok(_) => {
-            println!("✅ Email sent successfully!");
-            Ok(())
-        }
+        Ok(_) => Ok(()),
         Err(e) => {
-            eprintln!("❌ SMTP Error: {:?}", e);
+            error!(error = ?e, "SMTP send failed");
             Err(format!("SMTP send failed: {}. Check your Gmail App Password and ensure 2FA is enabled.", e).into())
         }
     }
@@ -318,6 +323,171 @@ pub async fn send_test_email(pool: &PgPool, user_id: Uuid) -> Result<(), Box<dyn
     Ok(())
 }
 
+// ==============================================================================
+// Risk Violations and Digests
+// ==============================================================================
+
+/// Tell a portfolio's owner about critical threshold violations that are new
+/// since the last risk calculation. Nothing is sent during quiet hours; the
+/// email goes out only if the owner asked for one and doesn't use a digest.
+pub async fn notify_critical_violations(
+    pool: &PgPool,
+    user_id: Uuid,
+    portfolio_id: Uuid,
+    portfolio_name: &str,
+    violations: &[ThresholdViolation],
+) -> Result<(), sqlx::Error> {
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    let settings = user_preferences_queries::get_notification_settings(pool, user_id).await?;
+    if settings.is_quiet_at(Utc::now().time()) {
+        debug!(%user_id, %portfolio_id, "Skipping critical violation notification during quiet hours");
+        return Ok(());
+    }
+    let prefs = get_or_create_notification_preferences(pool, user_id).await?;
+
+    let title = format!("🚨 Critical risk in {}", portfolio_name);
+    let message = format_violations_message(violations);
+    let link = format!("/portfolios/{}", portfolio_id);
+
+    if prefs.in_app_enabled {
+        create_notification(pool, user_id, None, &title, &message, "alert", Some(&link), None).await?;
+    }
+
+    if settings.email_on_critical_violations
+        && prefs.email_enabled
+        && settings.digest_frequency == DigestFrequency::Immediate
+        && get_daily_email_count(pool, user_id).await? < prefs.max_daily_emails
+    {
+        let user = get_user(pool, user_id).await?;
+        increment_daily_email_count(pool, user_id).await?;
+        send_text_email(&user.email, &title, &message).await;
+    }
+
+    Ok(())
+}
+
+/// Email a user a summary of the notifications created since `since`.
+/// Returns how many were summarised; nothing is sent when there are none.
+pub async fn send_digest(
+    pool: &PgPool,
+    user_id: Uuid,
+    frequency: DigestFrequency,
+    since: DateTime<Utc>,
+) -> Result<usize, sqlx::Error> {
+    let prefs = get_or_create_notification_preferences(pool, user_id).await?;
+    if !prefs.email_enabled {
+        return Ok(0);
+    }
+    let notifications = get_notifications_since(pool, user_id, since).await?;
+    let dividend_days = match frequency {
+        DigestFrequency::Weekly => dividend_calendar_service::DIGEST_WEEKLY_DAYS,
+        _ => dividend_calendar_service::DIGEST_DAILY_DAYS,
+    };
+    let dividends = dividend_calendar_service::upcoming_for_user(pool, user_id, dividend_days).await?;
+    if notifications.is_empty() && dividends.is_empty() {
+        return Ok(0);
+    }
+
+    let user = get_user(pool, user_id).await?;
+    let period = match frequency {
+        DigestFrequency::Weekly => "weekly",
+        _ => "daily",
+    };
+    let subject = if notifications.is_empty() {
+        format!("Your Rustfolio {} digest: upcoming dividends", period)
+    } else {
+        format!(
+            "Your Rustfolio {} digest: {} notification{}",
+            period,
+            notifications.len(),
+            if notifications.len() == 1 { "" } else { "s" }
+        )
+    };
+    send_text_email(&user.email, &subject, &format_digest(&notifications, &dividends)).await;
+
+    Ok(notifications.len())
+}
+
+fn format_violations_message(violations: &[ThresholdViolation]) -> String {
+    let lines: Vec<String> = violations
+        .iter()
+        .map(|v| format!("{} {} is {:.2} (critical threshold {:.2})", v.ticker, v.metric_name, v.metric_value, v.threshold_value))
+        .collect();
+    lines.join("\n")
+}
+
+fn format_digest(notifications: &[Notification], dividends: &[DividendEvent]) -> String {
+    let mut body = String::new();
+    for notification in notifications {
+        body.push_str(&format!(
+            "{}  {}\n{}\n\n",
+            notification.created_at.format("%Y-%m-%d %H:%M UTC"),
+            notification.title,
+            notification.message
+        ));
+    }
+    if !dividends.is_empty() {
+        body.push_str("Upcoming ex-dividend dates:\n");
+        for dividend in dividends {
+            let amount = dividend.amount.map(|a| format!("  ${:.4}/share", a)).unwrap_or_default();
+            let paid = dividend.pay_date.map(|d| format!("  paid {}", d.format("%Y-%m-%d"))).unwrap_or_default();
+            body.push_str(&format!("{}  {}{}{}\n", dividend.ex_date.format("%Y-%m-%d"), dividend.ticker, amount, paid));
+        }
+        body.push('\n');
+    }
+    body.push_str("View details at: http://localhost:5173");
+    body
+}
+
+/// Send a plain-text email, or log it when SMTP is disabled. Failures are
+/// logged rather than returned so one bad address doesn't stop a job.
+async fn send_text_email(to_email: &str, subject: &str, body: &str) {
+    let smtp_enabled = env::var("SMTP_ENABLED")
+        .unwrap_or_else(|_| "false".to_string())
+        .to_lowercase()
+        == "true";
+
+    if !smtp_enabled {
+        info!(to = to_email, subject, "Email notification would be sent (SMTP disabled)");
+        return;
+    }
+
+    if let Err(e) = send_text_email_via_smtp(to_email, subject, body) {
+        error!(to = to_email, subject, error = %e, "Failed to send email via SMTP");
+    }
+}
+
+fn send_text_email_via_smtp(
+    to_email: &str,
+    subject: &str,
+    body: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let smtp_host = env::var("SMTP_HOST")?;
+    let smtp_port = env::var("SMTP_PORT")?.parse::<u16>()?;
+    let smtp_username = env::var("SMTP_USERNAME")?;
+    let smtp_password = env::var("SMTP_PASSWORD")?;
+    let smtp_from_email = env::var("SMTP_FROM_EMAIL")?;
+    let smtp_from_name = env::var("SMTP_FROM_NAME").unwrap_or_else(|_| "Rustfolio".to_string());
+
+    let email = Message::builder()
+        .from(format!("{} <{}>", smtp_from_name, smtp_from_email).parse()?)
+        .to(to_email.parse()?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(body.to_string())?;
+
+    let mailer = SmtpTransport::starttls_relay(&smtp_host)?
+        .port(smtp_port)
+        .credentials(Credentials::new(smtp_username, smtp_password))
+        .build();
+
+    mailer.send(&email)?;
+    Ok(())
+}
+
 // ==============================================================================
 // Password Reset Email
 // ==============================================================================
@@ -431,11 +601,12 @@ async fn send_webhook_notification(
 
 /// Log webhook notification (placeholder for actual HTTP POST)
 fn log_webhook_notification(webhook_url: &str, alert: &AlertHistory) {
-    println!("🔔 Webhook notification would be sent:");
-    println!("   URL: {}", webhook_url);
-    println!("   Alert ID: {}", alert.id);
-    println!("   Message: {}", alert.message);
-    println!();
+    info!(
+        alert_id = %alert.id,
+        webhook_url,
+        message = %alert.message,
+        "Webhook notification would be sent"
+    );
 }
 
 // ==============================================================================
@@ -615,10 +786,43 @@ mod tests {
         assert!(!is_in_quiet_hours(&prefs));
     }
 
+    #[test]
+    fn test_format_violations_message() {
+        use crate::models::risk::ViolationSeverity;
+
+        let violation = ThresholdViolation {
+            ticker: "TSLA".to_string(),
+            holding_name: None,
+            metric_name: "Volatility".to_string(),
+            metric_value: 72.456,
+            threshold_value: 60.0,
+            threshold_type: ViolationSeverity::Critical,
+        };
+        assert_eq!(
+            format_violations_message(&[violation]),
+            "TSLA Volatility is 72.46 (critical threshold 60.00)"
+        );
+    }
+
     #[test]
     fn test_format_rule_type() {
         assert_eq!(format_rule_type("price_change"), "Price Change");
         assert_eq!(format_rule_type("volatility_spike"), "Volatility Spike");
         assert_eq!(format_rule_type("unknown"), "Alert");
     }
+
+    #[test]
+    fn test_format_digest_lists_upcoming_dividends() {
+        let dividend = DividendEvent {
+            ticker: "KO".to_string(),
+            ex_date: "2026-03-12".parse().unwrap(),
+            pay_date: Some("2026-04-01".parse().unwrap()),
+            amount: Some(0.51),
+            annual_rate: Some(2.04),
+            fetched_at: Utc::now(),
+        };
+        let body = format_digest(&[], &[dividend]);
+        assert!(body.starts_with("Upcoming ex-dividend dates:\n2026-03-12  KO  $0.5100/share  paid 2026-04-01\n"));
+        assert!(!format_digest(&[], &[]).contains("ex-dividend"));
+    }
 }
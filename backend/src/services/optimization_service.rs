@@ -1,3 +1,4 @@
+use bigdecimal::ToPrimitive;
 use sqlx::PgPool;
 use std::collections::HashMap;
 use tracing::{info, warn};
@@ -7,7 +8,11 @@ use crate::db::holding_snapshot_queries;
 use crate::errors::AppError;
 use crate::external::price_provider::PriceProvider;
 use crate::models::*;
-use crate::services::{failure_cache::FailureCache, rate_limiter::RateLimiter, risk_service};
+use crate::services::constrained_optimization_service::{self, PositionInput};
+use crate::services::{
+    failure_cache::FailureCache, glide_path_service, portfolio_return_service, rate_limiter::RateLimiter, risk_budget_service, risk_service,
+    trade_rounding_service,
+};
 
 /// Analyze portfolio and generate optimization recommendations
 pub async fn analyze_portfolio(
@@ -36,8 +41,8 @@ pub async fn analyze_portfolio(
     let mut total_value = 0.0;
 
     for holding in &holdings {
-        let market_value = holding.market_value.to_string().parse::<f64>().unwrap_or(0.0);
-        let quantity = holding.quantity.to_string().parse::<f64>().unwrap_or(0.0);
+        let market_value = holding.market_value.to_f64().unwrap_or(0.0);
+        let quantity = holding.quantity.to_f64().unwrap_or(0.0);
         total_value += market_value;
 
         ticker_aggregates
@@ -52,8 +57,8 @@ pub async fn analyze_portfolio(
     // 3. Calculate current metrics
     let current_metrics = calculate_current_metrics(
         pool,
+        portfolio_id,
         &ticker_aggregates,
-        total_value,
         price_provider,
         failure_cache,
         rate_limiter,
@@ -70,7 +75,7 @@ pub async fn analyze_portfolio(
     }
 
     // Check risk contributors
-    let risk_contributions = calculate_risk_contributions(
+    let (risk_contributions, position_metrics) = calculate_risk_contributions(
         pool,
         &ticker_aggregates,
         total_value,
@@ -90,6 +95,71 @@ pub async fn analyze_portfolio(
         recommendations.push(rec);
     }
 
+    // Keep suggested trades within the portfolio's risk budget, if one is set
+    let weights: HashMap<String, f64> = ticker_aggregates
+        .iter()
+        .map(|(ticker, (_, market_value, _))| (ticker.clone(), market_value / total_value))
+        .collect();
+    match risk_budget_service::context_for_positions(pool, portfolio_id, &weights, &position_metrics).await {
+        Ok(Some(budget)) => risk_budget_service::apply_budget_constraints(&budget, total_value, &mut recommendations),
+        Ok(None) => {}
+        Err(e) => warn!("Failed to load risk budget for portfolio {}: {}", portfolio_id, e),
+    }
+
+    // Respect the user's rebalancing constraints and suggest a rebalance when they are broken
+    match constrained_optimization_service::saved_constraints(pool, portfolio_id).await {
+        Ok(Some(constraints)) => {
+            constrained_optimization_service::enforce_do_not_sell(&constraints, &mut recommendations);
+            let positions: Vec<PositionInput> = ticker_aggregates
+                .iter()
+                .map(|(ticker, (_, market_value, name))| {
+                    let holding = holdings.iter().find(|h| &h.ticker == ticker);
+                    PositionInput {
+                        ticker: ticker.clone(),
+                        holding_name: name.clone(),
+                        sector: holding.and_then(|h| h.industry.clone()),
+                        market_value: *market_value,
+                        volatility: position_metrics.get(ticker).map(|m| m.volatility),
+                        equity: holding.is_none_or(glide_path_service::is_equity),
+                    }
+                })
+                .collect();
+            let equity_target = glide_path_service::equity_target(pool, portfolio_id).await.unwrap_or_else(|e| {
+                warn!("Failed to load glide path for portfolio {}: {}", portfolio_id, e);
+                None
+            });
+            let result = constrained_optimization_service::solve(
+                portfolio_id,
+                &positions,
+                constraints,
+                constrained_optimization_service::DEFAULT_RISK_AVERSION,
+                equity_target,
+            );
+            if let Some(rec) = constrained_optimization_service::constraint_recommendation(&result, &current_metrics) {
+                recommendations.push(rec);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => warn!("Failed to load optimization constraints for portfolio {}: {}", portfolio_id, e),
+    }
+
+    // Size trades in shares, rounding to whole shares where the broker requires it
+    match trade_rounding_service::load_context(pool, portfolio_id, &holdings).await {
+        Ok(rounding) => {
+            for rec in &mut recommendations {
+                rec.rounding = trade_rounding_service::round_adjustments(&rounding, total_value, &mut rec.affected_positions);
+                if let Some(report) = rec.rounding.as_ref().filter(|r| r.residual_cash.abs() >= 1.0) {
+                    rec.suggested_actions.push(format!(
+                        "Rounding to whole shares leaves ${:.0} {} than planned",
+                        report.residual_cash.abs(),
+                        if report.residual_cash > 0.0 { "more cash" } else { "less cash" }
+                    ));
+                }
+            }
+        }
+        Err(e) => warn!("Failed to load share rounding context for portfolio {}: {}", portfolio_id, e),
+    }
+
     // 5. Calculate summary
     let summary = calculate_summary(&recommendations, &current_metrics);
 
@@ -110,17 +180,16 @@ pub async fn analyze_portfolio(
 /// Calculate current portfolio metrics
 async fn calculate_current_metrics(
     pool: &PgPool,
+    portfolio_id: Uuid,
     ticker_aggregates: &HashMap<String, (f64, f64, Option<String>)>,
-    total_value: f64,
     price_provider: &dyn PriceProvider,
     failure_cache: &FailureCache,
     rate_limiter: &RateLimiter,
     risk_free_rate: f64,
 ) -> Result<CurrentMetrics, AppError> {
+    let total_value: f64 = ticker_aggregates.values().map(|(_, mv, _)| mv).sum();
     let mut weighted_volatility = 0.0;
     let mut weighted_max_drawdown = 0.0;
-    let mut weighted_sharpe = 0.0;
-    let mut sharpe_count = 0;
     let mut risk_score_sum = 0.0;
     let mut risk_count = 0;
 
@@ -145,11 +214,6 @@ async fn calculate_current_metrics(
                 weighted_volatility += assessment.metrics.volatility * weight;
                 weighted_max_drawdown += assessment.metrics.max_drawdown.abs() * weight;
 
-                if let Some(sharpe) = assessment.metrics.sharpe {
-                    weighted_sharpe += sharpe * weight;
-                    sharpe_count += 1;
-                }
-
                 risk_score_sum += assessment.risk_score * weight;
                 risk_count += 1;
             }
@@ -159,6 +223,15 @@ async fn calculate_current_metrics(
         }
     }
 
+    // Volatility, drawdown and Sharpe from the portfolio's own daily returns,
+    // falling back to the weighted averages for too little history
+    let returns = portfolio_return_service::compute_metrics(pool, portfolio_id, 90, "SPY", risk_free_rate)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Could not build the return series for portfolio {}: {}", portfolio_id, e);
+            Default::default()
+        });
+
     // Calculate diversification score
     let diversification_score = calculate_diversification_score(ticker_aggregates, total_value);
 
@@ -195,13 +268,9 @@ async fn calculate_current_metrics(
 
     Ok(CurrentMetrics {
         risk_score: if risk_count > 0 { risk_score_sum } else { 0.0 },
-        volatility: weighted_volatility,
-        max_drawdown: weighted_max_drawdown,
-        sharpe_ratio: if sharpe_count > 0 {
-            Some(weighted_sharpe)
-        } else {
-            None
-        },
+        volatility: returns.volatility.unwrap_or(weighted_volatility),
+        max_drawdown: returns.max_drawdown.map(f64::abs).unwrap_or(weighted_max_drawdown),
+        sharpe_ratio: returns.sharpe,
         diversification_score,
         correlation_adjusted_diversification_score: correlation_adjusted_score,
         average_correlation,
@@ -296,10 +365,11 @@ fn detect_concentration_risk(
             "Reinvest proceeds into diversified assets (index funds or other low-correlation positions)".to_string(),
             format!("This will reduce {} position to 15% of portfolio", ticker),
         ],
+        rounding: None,
     })
 }
 
-/// Calculate risk contributions for each position
+/// Calculate risk contributions for each position, along with the per-ticker metrics
 async fn calculate_risk_contributions(
     pool: &PgPool,
     ticker_aggregates: &HashMap<String, (f64, f64, Option<String>)>,
@@ -308,9 +378,10 @@ async fn calculate_risk_contributions(
     failure_cache: &FailureCache,
     rate_limiter: &RateLimiter,
     risk_free_rate: f64,
-) -> Result<Vec<RiskContribution>, AppError> {
+) -> Result<(Vec<RiskContribution>, HashMap<String, PositionRisk>), AppError> {
     let mut contributions = Vec::new();
     let mut total_risk = 0.0;
+    let mut metrics = HashMap::new();
 
     // First pass: calculate individual volatilities
     let mut ticker_volatilities = HashMap::new();
@@ -332,6 +403,7 @@ async fn calculate_risk_contributions(
             let vol = assessment.metrics.volatility;
             ticker_volatilities.insert(ticker.clone(), vol);
             total_risk += weight * vol;
+            metrics.insert(ticker.clone(), assessment.metrics);
         }
     }
 
@@ -356,7 +428,7 @@ async fn calculate_risk_contributions(
     }
 
     contributions.sort_by(|a, b| b.risk_contribution.partial_cmp(&a.risk_contribution).unwrap());
-    Ok(contributions)
+    Ok((contributions, metrics))
 }
 
 /// Detect positions with excessive risk contribution
@@ -438,6 +510,7 @@ fn detect_excessive_risk_contributors(
             ),
             "Consider replacing with lower-volatility alternatives in the same sector".to_string(),
         ],
+        rounding: None,
     })
 }
 
@@ -618,6 +691,7 @@ fn assess_diversification(
             max_drawdown_after: current_metrics.max_drawdown * 0.85,
         },
         suggested_actions,
+        rounding: None,
     })
 }
 
@@ -0,0 +1,193 @@
+//! Insider and institutional ownership per ticker.
+//!
+//! Raw provider data is stored per ticker and refreshed once a day; the insider
+//! activity window and the top-holder list are derived on read so one stored copy
+//! serves any window.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::db::ownership_queries;
+use crate::errors::AppError;
+use crate::external::ownership_provider::{ExternalOwnership, OwnershipProvider};
+use crate::external::price_provider::PriceProviderError;
+use crate::models::{InsiderActivity, InsiderTransactionType, OwnershipSnapshot};
+use crate::services::clock;
+
+pub const DEFAULT_ACTIVITY_DAYS: i32 = 90;
+const TOP_HOLDERS: usize = 10;
+const CACHE_HOURS: i64 = 24;
+
+/// Summarize raw ownership data: insider purchases and sales within `days` of
+/// `today`, and the largest institutional holders
+pub fn summarize(
+    ticker: &str,
+    raw: &ExternalOwnership,
+    days: i32,
+    today: NaiveDate,
+    fetched_at: DateTime<Utc>,
+) -> OwnershipSnapshot {
+    let cutoff = today - Duration::days(days as i64);
+    let mut recent: Vec<_> = raw
+        .insider_transactions
+        .iter()
+        .filter(|t| t.transaction_date >= cutoff)
+        .filter(|t| matches!(t.transaction_type, InsiderTransactionType::Purchase | InsiderTransactionType::Sale))
+        .cloned()
+        .collect();
+    recent.sort_by_key(|t| std::cmp::Reverse(t.transaction_date));
+
+    let mut activity = InsiderActivity { period_days: days, ..Default::default() };
+    for transaction in &recent {
+        if transaction.transaction_type == InsiderTransactionType::Purchase {
+            activity.buys += 1;
+            activity.net_shares += transaction.shares;
+        } else {
+            activity.sells += 1;
+            activity.net_shares -= transaction.shares;
+        }
+    }
+
+    let mut holders = raw.institutional_holders.clone();
+    holders.sort_by(|a, b| b.pct_held.unwrap_or(0.0).total_cmp(&a.pct_held.unwrap_or(0.0)));
+    holders.truncate(TOP_HOLDERS);
+
+    OwnershipSnapshot {
+        ticker: ticker.to_string(),
+        insider_ownership_pct: raw.insider_pct,
+        institutional_ownership_pct: raw.institutional_pct,
+        institution_count: raw.institution_count,
+        insider_activity: activity,
+        recent_insider_transactions: recent,
+        top_institutional_holders: holders,
+        fetched_at,
+    }
+}
+
+/// Stored ownership data for a ticker, regardless of age
+pub async fn stored(pool: &PgPool, ticker: &str) -> Result<Option<(ExternalOwnership, DateTime<Utc>)>, AppError> {
+    let Some((data, fetched_at)) = ownership_queries::fetch(pool, ticker).await? else {
+        return Ok(None);
+    };
+    match serde_json::from_value(data) {
+        Ok(raw) => Ok(Some((raw, fetched_at))),
+        Err(e) => {
+            warn!("Discarding unreadable ownership data for {}: {}", ticker, e);
+            Ok(None)
+        }
+    }
+}
+
+/// Ownership snapshot for a ticker, refreshed from the provider when the stored
+/// copy is older than a day (or `force` is set). A stale copy is served if the
+/// provider fails.
+pub async fn get_snapshot(
+    pool: &PgPool,
+    provider: &dyn OwnershipProvider,
+    ticker: &str,
+    days: i32,
+    force: bool,
+) -> Result<OwnershipSnapshot, AppError> {
+    if !(1..=365).contains(&days) {
+        return Err(AppError::Validation("days must be between 1 and 365".to_string()));
+    }
+    let ticker = ticker.to_uppercase();
+    let today = clock::today();
+    let existing = stored(pool, &ticker).await?;
+
+    if let Some((raw, fetched_at)) = &existing {
+        if !force && Utc::now() - *fetched_at < Duration::hours(CACHE_HOURS) {
+            return Ok(summarize(&ticker, raw, days, today, *fetched_at));
+        }
+    }
+
+    match provider.fetch_ownership(&ticker).await {
+        Ok(raw) => {
+            let data = serde_json::to_value(&raw)
+                .map_err(|e| AppError::External(format!("Failed to serialize ownership data: {}", e)))?;
+            let fetched_at = ownership_queries::upsert(pool, &ticker, data).await?;
+            info!(
+                "Stored ownership for {}: {} institutional holders, {} insider transactions",
+                ticker,
+                raw.institutional_holders.len(),
+                raw.insider_transactions.len()
+            );
+            Ok(summarize(&ticker, &raw, days, today, fetched_at))
+        }
+        Err(e) => match existing {
+            Some((raw, fetched_at)) => {
+                warn!("Ownership refresh failed for {}, serving data from {}: {}", ticker, fetched_at, e);
+                Ok(summarize(&ticker, &raw, days, today, fetched_at))
+            }
+            None => match e {
+                PriceProviderError::NotFound => Err(AppError::NotFound(format!("No ownership data for {}", ticker))),
+                other => Err(AppError::External(format!("Failed to fetch ownership for {}: {}", ticker, other))),
+            },
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InsiderTransaction, InstitutionalHolder};
+
+    fn transaction(days_ago: i64, transaction_type: InsiderTransactionType, shares: i64, today: NaiveDate) -> InsiderTransaction {
+        InsiderTransaction {
+            ticker: "AAPL".to_string(),
+            transaction_date: today - Duration::days(days_ago),
+            reporting_person: "Insider".to_string(),
+            title: Some("Director".to_string()),
+            transaction_type,
+            shares,
+            price_per_share: None,
+            ownership_after: None,
+        }
+    }
+
+    fn holder(name: &str, pct: Option<f64>) -> InstitutionalHolder {
+        InstitutionalHolder { name: name.to_string(), pct_held: pct, shares: None, report_date: None }
+    }
+
+    #[test]
+    fn test_summarize_counts_recent_buys_and_sells() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let raw = ExternalOwnership {
+            insider_pct: Some(0.5),
+            institutional_pct: Some(61.0),
+            institution_count: Some(5000),
+            institutional_holders: vec![],
+            insider_transactions: vec![
+                transaction(10, InsiderTransactionType::Sale, 5_000, today),
+                transaction(20, InsiderTransactionType::Purchase, 1_000, today),
+                transaction(30, InsiderTransactionType::Grant, 9_000, today),
+                transaction(200, InsiderTransactionType::Purchase, 50_000, today),
+            ],
+        };
+        let snapshot = summarize("AAPL", &raw, 90, today, Utc::now());
+
+        assert_eq!(snapshot.insider_activity.buys, 1);
+        assert_eq!(snapshot.insider_activity.sells, 1);
+        assert_eq!(snapshot.insider_activity.net_shares, -4_000);
+        assert_eq!(snapshot.recent_insider_transactions.len(), 2);
+        assert_eq!(snapshot.recent_insider_transactions[0].transaction_type, InsiderTransactionType::Sale);
+
+        let longer = summarize("AAPL", &raw, 365, today, Utc::now());
+        assert_eq!(longer.insider_activity.buys, 2);
+        assert_eq!(longer.insider_activity.net_shares, 46_000);
+    }
+
+    #[test]
+    fn test_summarize_orders_top_holders() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let mut holders: Vec<_> = (0..12).map(|i| holder(&format!("Fund {}", i), Some(i as f64))).collect();
+        holders.push(holder("Unknown", None));
+        let raw = ExternalOwnership { institutional_holders: holders, ..Default::default() };
+
+        let snapshot = summarize("AAPL", &raw, 90, today, Utc::now());
+        assert_eq!(snapshot.top_institutional_holders.len(), TOP_HOLDERS);
+        assert_eq!(snapshot.top_institutional_holders[0].name, "Fund 11");
+        assert!(snapshot.top_institutional_holders.iter().all(|h| h.name != "Unknown"));
+    }
+}
@@ -0,0 +1,700 @@
+//! Holdings statements delivered as PDF.
+//!
+//! Several Canadian brokers only provide statements as PDF. The text layer is
+//! extracted (see `pdf_text`), the holdings table is found by its header row,
+//! and each row is read into holding fields by the column it sits under. The
+//! result is written out as a holdings CSV in the Raymond James layout, so
+//! from there the import is the CSV flow: the same preview, confirmation,
+//! validation and import batches.
+
+use anyhow::{bail, Result};
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use csv::Writer;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use crate::models::{HoldingField, PdfImportPreview};
+use crate::services::csv_import_service;
+use crate::services::pdf_text::{self, TextCell, TextLine};
+
+/// How a broker's holdings statements are laid out
+#[derive(Debug)]
+pub struct BrokerLayout {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Lowercase text that identifies the broker's statements
+    markers: &'static [&'static str],
+    /// Holdings table headers (normalized) the generic aliases do not cover
+    header_aliases: &'static [(&'static str, HoldingField)],
+}
+
+pub const BROKERS: &[BrokerLayout] = &[
+    BrokerLayout {
+        id: "raymond_james",
+        name: "Raymond James",
+        markers: &["raymond james"],
+        header_aliases: &[
+            ("estmarketvalue", HoldingField::MarketValue),
+            ("quantityheld", HoldingField::Quantity),
+        ],
+    },
+    BrokerLayout {
+        id: "questrade",
+        name: "Questrade",
+        markers: &["questrade"],
+        header_aliases: &[("averagecostpershare", HoldingField::AverageCost)],
+    },
+    BrokerLayout {
+        id: "td_direct_investing",
+        name: "TD Direct Investing",
+        markers: &["td direct investing", "td waterhouse"],
+        header_aliases: &[
+            ("closingprice", HoldingField::Price),
+            ("closingmarketvalue", HoldingField::MarketValue),
+        ],
+    },
+    BrokerLayout {
+        id: "rbc_direct_investing",
+        name: "RBC Direct Investing",
+        markers: &["rbc direct investing"],
+        header_aliases: &[
+            ("averageunitcost", HoldingField::AverageCost),
+            ("quantityparvalue", HoldingField::Quantity),
+        ],
+    },
+    BrokerLayout {
+        id: "bmo_investorline",
+        name: "BMO InvestorLine",
+        markers: &["bmo investorline"],
+        header_aliases: &[("unitsheld", HoldingField::Quantity)],
+    },
+    BrokerLayout {
+        id: "cibc_investors_edge",
+        name: "CIBC Investor's Edge",
+        markers: &["investor's edge", "investors edge"],
+        header_aliases: &[("quantityowned", HoldingField::Quantity)],
+    },
+    BrokerLayout {
+        id: "wealthsimple",
+        name: "Wealthsimple",
+        markers: &["wealthsimple"],
+        header_aliases: &[("marketpricecad", HoldingField::Price)],
+    },
+];
+
+/// Used when no broker is recognized; relies on the generic header aliases
+pub const GENERIC_BROKER: BrokerLayout = BrokerLayout {
+    id: "generic",
+    name: "Other broker",
+    markers: &[],
+    header_aliases: &[],
+};
+
+/// Headers common on Canadian statements that the CSV aliases do not cover
+const COMMON_HEADER_ALIASES: &[(&str, HoldingField)] = &[
+    ("bookcost", HoldingField::BookValue),
+    ("totalbookcost", HoldingField::BookValue),
+    ("averagebookcost", HoldingField::AverageCost),
+    ("unrealizedgain", HoldingField::GainLoss),
+    ("securitydescription", HoldingField::Holding),
+];
+
+/// "Account No. 12345-678" and its variants
+static ACCOUNT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\baccount\s*(?:number|no\.?|num\.?|#)?\s*[:#]?\s*([0-9A-Z][0-9A-Z-]{3,})").unwrap()
+});
+static TICKER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Z][A-Z0-9]{0,5}(?:[.\-][A-Z0-9]{1,3})?$").unwrap());
+/// "Royal Bank of Canada (RY)"
+static TICKER_IN_PARENTHESES_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(([A-Z][A-Z0-9]{0,5}(?:[.\-][A-Z0-9]{1,3})?)\)").unwrap());
+/// "RY - Royal Bank of Canada"
+static LEADING_TICKER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([A-Z][A-Z0-9]{0,5}(?:[.\-][A-Z0-9]{1,3})?)\s+-\s+(.+)$").unwrap());
+/// "March 1, 2026", "1 Mar 2026" or "2026-03-01"
+static DATE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    let month = r"(Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec)[a-z]*\.?";
+    Regex::new(&format!(
+        r"(?i)\b(?:{month}\s+(\d{{1,2}}),?\s+(\d{{4}})|(\d{{1,2}})\s+{month},?\s+(\d{{4}})|(\d{{4}})[-/](\d{{1,2}})[-/](\d{{1,2}}))\b"
+    ))
+    .unwrap()
+});
+
+/// Largest baseline distance, in points, between a row and a wrapped line of its description
+const WRAPPED_LINE_GAP: f64 = 16.0;
+
+/// Fields holding amounts, cleaned to plain decimals
+const NUMERIC_FIELDS: [HoldingField; 9] = [
+    HoldingField::Quantity,
+    HoldingField::Price,
+    HoldingField::AverageCost,
+    HoldingField::BookValue,
+    HoldingField::MarketValue,
+    HoldingField::AccruedInterest,
+    HoldingField::GainLoss,
+    HoldingField::GainLossPct,
+    HoldingField::PercentageOfAssets,
+];
+
+/// A column of the holdings table, spanning its header cell
+#[derive(Debug, Clone)]
+struct Column {
+    field: Option<HoldingField>,
+    x: f64,
+    end_x: f64,
+}
+
+#[derive(Debug)]
+struct StatementRow {
+    page: usize,
+    account: Option<String>,
+    values: BTreeMap<HoldingField, String>,
+}
+
+/// Holdings read from a statement, as a holdings CSV
+#[derive(Debug)]
+pub struct ParsedStatement {
+    pub broker: &'static BrokerLayout,
+    pub statement_date: Option<NaiveDate>,
+    pub pages: usize,
+    pub accounts: Vec<String>,
+    pub content: String,
+    pub warnings: Vec<String>,
+}
+
+/// Read the holdings of a PDF statement. `broker` selects a layout by id and
+/// is detected from the text when absent; `account_number` applies to every
+/// holding, for statements that do not print the account.
+pub fn parse_statement(data: &[u8], broker: Option<&str>, account_number: Option<&str>) -> Result<ParsedStatement> {
+    let text = pdf_text::extract_text(data)?;
+    let mut statement = parse_lines(&text.lines, broker, account_number)?;
+    statement.pages = text.pages;
+    Ok(statement)
+}
+
+/// Parse a statement and run its holdings through the CSV import preview
+pub fn preview_statement(data: &[u8], broker: Option<&str>, account_number: Option<&str>) -> Result<PdfImportPreview> {
+    let statement = parse_statement(data, broker, account_number)?;
+    let preview = csv_import_service::build_preview(&statement.content, None)?;
+    Ok(PdfImportPreview {
+        broker: statement.broker.id.to_string(),
+        broker_name: statement.broker.name.to_string(),
+        statement_date: statement.statement_date,
+        pages: statement.pages,
+        accounts: statement.accounts,
+        content: statement.content,
+        preview,
+        warnings: statement.warnings,
+    })
+}
+
+fn find_broker(id: &str) -> Result<&'static BrokerLayout> {
+    if id == GENERIC_BROKER.id {
+        return Ok(&GENERIC_BROKER);
+    }
+    match BROKERS.iter().find(|b| b.id == id) {
+        Some(broker) => Ok(broker),
+        None => bail!(
+            "Unknown broker '{}'; expected one of: {}, {}",
+            id,
+            BROKERS.iter().map(|b| b.id).collect::<Vec<_>>().join(", "),
+            GENERIC_BROKER.id
+        ),
+    }
+}
+
+/// The broker whose name appears in the statement, falling back to the generic layout
+fn detect_broker(lines: &[TextLine]) -> &'static BrokerLayout {
+    let text = lines.iter().map(|l| l.text()).collect::<Vec<_>>().join("\n").to_lowercase();
+    BROKERS
+        .iter()
+        .find(|b| b.markers.iter().any(|m| text.contains(m)))
+        .unwrap_or(&GENERIC_BROKER)
+}
+
+pub fn parse_lines(lines: &[TextLine], broker: Option<&str>, account_number: Option<&str>) -> Result<ParsedStatement> {
+    let layout = match broker {
+        Some(id) => find_broker(id)?,
+        None => detect_broker(lines),
+    };
+    let account_number = account_number.map(str::trim).filter(|a| !a.is_empty());
+
+    let mut accounts: Vec<String> = Vec::new();
+    let mut current_account: Option<String> = None;
+    let mut columns: Option<Vec<Column>> = None;
+    let mut section: Option<String> = None;
+    let mut last_row_line: Option<usize> = None;
+    let mut rows: Vec<StatementRow> = Vec::new();
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = &lines[i];
+        if account_number.is_none() {
+            if let Some(account) = find_account(&line.text()) {
+                if !accounts.contains(&account) {
+                    accounts.push(account.clone());
+                }
+                current_account = Some(account);
+            }
+        }
+
+        if let Some((header, consumed)) = find_header(layout, lines, i) {
+            columns = Some(header);
+            section = None;
+            last_row_line = None;
+            i += consumed;
+            continue;
+        }
+        let Some(table) = &columns else {
+            i += 1;
+            continue;
+        };
+        if line.cells.first().is_some_and(|c| c.text.to_lowercase().starts_with("total")) {
+            columns = None;
+            i += 1;
+            continue;
+        }
+
+        let values = assign_cells(table, &line.cells);
+        match values.get(&HoldingField::Quantity).and_then(|q| clean_number(q)) {
+            Some(_) => {
+                rows.push(StatementRow {
+                    page: line.page,
+                    account: account_number.map(String::from).or_else(|| current_account.clone()),
+                    values: holding_values(values, section.as_deref()),
+                });
+                last_row_line = Some(i);
+            }
+            None if line.cells.iter().all(|c| clean_number(&c.text).is_none()) => {
+                let descriptive = values
+                    .keys()
+                    .all(|f| matches!(f, HoldingField::Holding | HoldingField::Symbol));
+                // Only directly below the row: a page header after the last row is not part of it
+                let continues = last_row_line
+                    .is_some_and(|l| l + 1 == i && lines[l].page == line.page && lines[l].y - line.y < WRAPPED_LINE_GAP)
+                    && descriptive
+                    && !values.is_empty();
+                if let (true, Some(row)) = (continues, rows.last_mut()) {
+                    // A description wrapped onto the next line
+                    for (field, text) in values {
+                        let value = row.values.entry(field).or_default();
+                        if !value.is_empty() {
+                            value.push(' ');
+                        }
+                        value.push_str(&text);
+                    }
+                    last_row_line = Some(i);
+                } else if line.cells.len() == 1
+                    && !line.cells[0].text.chars().any(|c| c.is_ascii_digit())
+                    && (line.cells[0].x - table[0].x).abs() < 12.0
+                {
+                    // A heading such as "Canadian Equities" groups the rows below it
+                    section = Some(line.cells[0].text.clone());
+                }
+            }
+            // Subtotals and other figures that are not holdings
+            None => {}
+        }
+        i += 1;
+    }
+
+    let statement_date = statement_date(lines);
+    if account_number.is_none() && accounts.len() == 1 {
+        for row in rows.iter_mut().filter(|r| r.account.is_none()) {
+            row.account = accounts.first().cloned();
+        }
+    }
+
+    let mut warnings = Vec::new();
+    let mut holdings = Vec::new();
+    let unassigned = !accounts.is_empty() || account_number.is_some();
+    for mut row in rows {
+        let description = row.values.get(&HoldingField::Holding).cloned().unwrap_or_default();
+        let Some(symbol) = resolve_symbol(&mut row.values) else {
+            warnings.push(format!("Page {}: no symbol for '{}'; the holding was skipped", row.page, description));
+            continue;
+        };
+        match row.account.take() {
+            Some(account) => {
+                row.values.insert(HoldingField::AccountNumber, account);
+            }
+            None if unassigned => {
+                warnings.push(format!(
+                    "Page {}: {} is listed before any account number; the holding was skipped",
+                    row.page, symbol
+                ));
+                continue;
+            }
+            None => {}
+        }
+        derive_price(&mut row.values);
+        holdings.push(row.values);
+    }
+
+    if holdings.is_empty() {
+        bail!("No holdings table was found in the statement");
+    }
+    if !unassigned {
+        warnings.push("No account number was found in the statement; provide account_number to import it".to_string());
+    }
+
+    Ok(ParsedStatement {
+        broker: layout,
+        statement_date,
+        pages: lines.last().map(|l| l.page).unwrap_or(0),
+        accounts: account_number.map(|a| vec![a.to_string()]).unwrap_or(accounts),
+        content: holdings_csv(&holdings)?,
+        warnings,
+    })
+}
+
+fn find_account(text: &str) -> Option<String> {
+    ACCOUNT_RE
+        .captures_iter(text)
+        .filter_map(|caps| caps.get(1).map(|m| m.as_str().trim_end_matches('-').to_uppercase()))
+        .find(|account| account.chars().any(|c| c.is_ascii_digit()))
+}
+
+/// Columns of a holdings table header starting at `lines[start]`, with the
+/// number of lines it spans. Headers wrapped over two lines ("Market" above
+/// "Value") are merged when that names more fields.
+fn find_header(layout: &BrokerLayout, lines: &[TextLine], start: usize) -> Option<(Vec<Column>, usize)> {
+    let single = header_columns(layout, &lines[start].cells);
+    let double = lines
+        .get(start + 1)
+        .filter(|next| next.page == lines[start].page && lines[start].y - next.y < 24.0)
+        .and_then(|next| header_columns(layout, &merge_header_cells(&lines[start].cells, &next.cells)));
+
+    let mapped = |columns: &Option<Vec<Column>>| {
+        columns
+            .as_ref()
+            .map(|c| c.iter().filter(|c| c.field.is_some()).count())
+            .unwrap_or(0)
+    };
+    if mapped(&double) > mapped(&single) {
+        double.map(|c| (c, 2))
+    } else {
+        single.map(|c| (c, 1))
+    }
+}
+
+fn merge_header_cells(top: &[TextCell], bottom: &[TextCell]) -> Vec<TextCell> {
+    let mut cells = top.to_vec();
+    for cell in bottom {
+        match cells.iter_mut().find(|c| overlap(c.x, c.end_x, cell.x, cell.end_x) > 0.0) {
+            Some(above) => {
+                above.text = format!("{} {}", above.text, cell.text);
+                above.x = above.x.min(cell.x);
+                above.end_x = above.end_x.max(cell.end_x);
+            }
+            None => cells.push(cell.clone()),
+        }
+    }
+    cells.sort_by(|a, b| a.x.total_cmp(&b.x));
+    cells
+}
+
+/// The line's cells as table columns, when they name at least a security, a
+/// quantity and a price or value
+fn header_columns(layout: &BrokerLayout, cells: &[TextCell]) -> Option<Vec<Column>> {
+    let headers: Vec<String> = cells
+        .iter()
+        .map(|cell| {
+            let normalized = csv_import_service::normalize_header(&cell.text);
+            layout
+                .header_aliases
+                .iter()
+                .chain(COMMON_HEADER_ALIASES)
+                .find(|(alias, _)| *alias == normalized)
+                .map(|(_, field)| field.label().to_string())
+                .unwrap_or_else(|| cell.text.clone())
+        })
+        .collect();
+
+    let mut fields: Vec<Option<HoldingField>> = vec![None; cells.len()];
+    for matched in csv_import_service::detect_mapping(&headers) {
+        let Some(column) = &matched.column else { continue };
+        if let Some(index) = (0..headers.len()).find(|i| headers[*i] == *column && fields[*i].is_none()) {
+            fields[index] = Some(matched.field);
+        }
+    }
+
+    let has = |field: HoldingField| fields.contains(&Some(field));
+    let is_table = has(HoldingField::Quantity)
+        && (has(HoldingField::Symbol) || has(HoldingField::Holding))
+        && (has(HoldingField::Price) || has(HoldingField::MarketValue));
+    is_table.then(|| {
+        cells
+            .iter()
+            .zip(fields)
+            .map(|(cell, field)| Column {
+                field,
+                x: cell.x,
+                end_x: cell.end_x,
+            })
+            .collect()
+    })
+}
+
+fn overlap(a_start: f64, a_end: f64, b_start: f64, b_end: f64) -> f64 {
+    a_end.min(b_end) - a_start.max(b_start)
+}
+
+/// Place each cell under the column it overlaps most, or the nearest one.
+/// Text under a column the import does not use is dropped.
+fn assign_cells(columns: &[Column], cells: &[TextCell]) -> BTreeMap<HoldingField, String> {
+    let mut values: BTreeMap<HoldingField, String> = BTreeMap::new();
+    for cell in cells {
+        let distance = |column: &Column| {
+            let shared = overlap(column.x, column.end_x, cell.x, cell.end_x);
+            if shared > 0.0 {
+                -shared
+            } else {
+                (column.x + column.end_x - cell.x - cell.end_x).abs() / 2.0
+            }
+        };
+        let nearest = columns.iter().min_by(|a, b| distance(a).total_cmp(&distance(b)));
+        if let Some(field) = nearest.and_then(|c| c.field) {
+            let value = values.entry(field).or_default();
+            if !value.is_empty() {
+                value.push(' ');
+            }
+            value.push_str(&cell.text);
+        }
+    }
+    values
+}
+
+/// Clean a row's figures, dropping the ones that are not numbers ("N/A")
+fn holding_values(mut values: BTreeMap<HoldingField, String>, section: Option<&str>) -> BTreeMap<HoldingField, String> {
+    for field in NUMERIC_FIELDS {
+        if let Some(value) = values.remove(&field) {
+            if let Some(number) = clean_number(&value) {
+                values.insert(field, number);
+            }
+        }
+    }
+    if let Some(section) = section {
+        values.entry(HoldingField::AssetCategory).or_insert_with(|| section.to_string());
+    }
+    values
+}
+
+/// A statement figure as a plain decimal: "$1,234.50" becomes "1234.50", and
+/// "(12.00)" or "12.00-" become "-12.00"
+fn clean_number(text: &str) -> Option<String> {
+    let mut value: String = text
+        .trim()
+        .trim_start_matches("CAD")
+        .trim_start_matches("USD")
+        .trim_end_matches("CAD")
+        .trim_end_matches("USD")
+        .chars()
+        .filter(|c| !matches!(c, '$' | ',' | '%' | ' ' | '\u{a0}'))
+        .collect();
+    let mut negative = false;
+    if value.starts_with('(') && value.ends_with(')') {
+        value = value[1..value.len() - 1].to_string();
+        negative = true;
+    } else if value.len() > 1 && value.ends_with('-') {
+        value.pop();
+        negative = true;
+    }
+    BigDecimal::from_str(&value).ok()?;
+    Some(if negative { format!("-{}", value) } else { value })
+}
+
+fn is_ticker(symbol: &str) -> bool {
+    TICKER_RE.is_match(symbol)
+}
+
+/// The row's symbol: from its symbol column, or else from its description as
+/// "Royal Bank of Canada (RY)" or "RY - Royal Bank of Canada"
+fn resolve_symbol(values: &mut BTreeMap<HoldingField, String>) -> Option<String> {
+    let from_column = values
+        .get(&HoldingField::Symbol)
+        .map(|s| s.trim().trim_end_matches('*').to_uppercase())
+        .filter(|s| is_ticker(s));
+
+    let symbol = match from_column {
+        Some(symbol) => symbol,
+        None => {
+            let description = values.get(&HoldingField::Holding)?.clone();
+            if let Some(caps) = TICKER_IN_PARENTHESES_RE.captures(&description) {
+                caps[1].to_string()
+            } else if let Some(caps) = LEADING_TICKER_RE.captures(&description) {
+                values.insert(HoldingField::Holding, caps[2].to_string());
+                caps[1].to_string()
+            } else {
+                return None;
+            }
+        }
+    };
+    values.insert(HoldingField::Symbol, symbol.clone());
+    values.entry(HoldingField::Holding).or_insert_with(|| symbol.clone());
+    Some(symbol)
+}
+
+/// Statements that list only market value get the price it implies
+fn derive_price(values: &mut BTreeMap<HoldingField, String>) {
+    if values.contains_key(&HoldingField::Price) {
+        return;
+    }
+    let number = |field: HoldingField| values.get(&field).and_then(|v| BigDecimal::from_str(v).ok());
+    if let (Some(quantity), Some(market_value)) = (number(HoldingField::Quantity), number(HoldingField::MarketValue)) {
+        if quantity != BigDecimal::from(0) {
+            let price = (market_value / quantity).round(4);
+            values.insert(HoldingField::Price, price.normalized().to_string());
+        }
+    }
+}
+
+fn holdings_csv(holdings: &[BTreeMap<HoldingField, String>]) -> Result<String> {
+    let fields: Vec<HoldingField> = HoldingField::ALL
+        .into_iter()
+        .filter(|f| holdings.iter().any(|h| h.contains_key(f)))
+        .collect();
+    let mut writer = Writer::from_writer(Vec::new());
+    writer.write_record(fields.iter().map(|f| f.label()))?;
+    for holding in holdings {
+        writer.write_record(fields.iter().map(|f| holding.get(f).map(String::as_str).unwrap_or("")))?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let months = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    let name = name.to_lowercase();
+    months.iter().position(|m| name.starts_with(m)).map(|i| i as u32 + 1)
+}
+
+/// Dates written out in a line of text
+fn dates_in(text: &str) -> Vec<NaiveDate> {
+    DATE_RE
+        .captures_iter(text)
+        .filter_map(|caps| {
+            let number = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<u32>().ok());
+            if let Some(name) = caps.get(1) {
+                NaiveDate::from_ymd_opt(number(3)? as i32, month_number(name.as_str())?, number(2)?)
+            } else if let Some(name) = caps.get(5) {
+                NaiveDate::from_ymd_opt(number(6)? as i32, month_number(name.as_str())?, number(4)?)
+            } else {
+                NaiveDate::from_ymd_opt(number(7)? as i32, number(8)?, number(9)?)
+            }
+        })
+        .collect()
+}
+
+/// The date holdings are reported as of: the latest date on the first line
+/// that introduces one ("As of", "Statement period", ...)
+pub fn statement_date(lines: &[TextLine]) -> Option<NaiveDate> {
+    const KEYWORDS: [&str; 6] = ["as of", "as at", "statement date", "statement period", "period ending", "ending"];
+    lines
+        .iter()
+        .map(|l| l.text())
+        .filter(|text| {
+            let lower = text.to_lowercase();
+            KEYWORDS.iter().any(|k| lower.contains(k))
+        })
+        .find_map(|text| dates_in(&text).into_iter().max())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(page: usize, y: f64, cells: &[(f64, f64, &str)]) -> TextLine {
+        TextLine {
+            page,
+            y,
+            cells: cells
+                .iter()
+                .map(|(x, end_x, text)| TextCell {
+                    x: *x,
+                    end_x: *end_x,
+                    text: text.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn statement_lines() -> Vec<TextLine> {
+        vec![
+            line(1, 760.0, &[(40.0, 200.0, "RBC Direct Investing Inc.")]),
+            line(1, 745.0, &[(40.0, 260.0, "Statement period: January 1, 2026 to January 31, 2026")]),
+            line(1, 730.0, &[(40.0, 200.0, "Account number: 123-45678-1-2")]),
+            line(1, 700.0, &[(40.0, 110.0, "Security"), (250.0, 290.0, "Quantity"), (330.0, 370.0, "Book"), (420.0, 460.0, "Market")]),
+            line(1, 690.0, &[(40.0, 110.0, "Description"), (330.0, 370.0, "Cost"), (420.0, 460.0, "Value")]),
+            line(1, 670.0, &[(40.0, 120.0, "Canadian Equities")]),
+            line(1, 655.0, &[(40.0, 180.0, "ROYAL BANK OF CANADA"), (260.0, 290.0, "100"), (320.0, 370.0, "$12,000.00"), (410.0, 460.0, "$14,250.00")]),
+            line(1, 645.0, &[(40.0, 180.0, "COMMON (RY)")]),
+            line(1, 630.0, &[(40.0, 180.0, "XEQT - ISHARES CORE EQUITY"), (260.0, 290.0, "50.5"), (320.0, 370.0, "1,500.00"), (410.0, 460.0, "(1,616.00)")]),
+            line(1, 615.0, &[(40.0, 180.0, "MYSTERY FUND"), (260.0, 290.0, "10"), (320.0, 370.0, "100.00"), (410.0, 460.0, "120.00")]),
+            line(2, 760.0, &[(40.0, 200.0, "RBC Direct Investing Inc.")]),
+            line(2, 700.0, &[(40.0, 120.0, "Total Canadian Equities"), (410.0, 460.0, "$12,754.00")]),
+            line(2, 680.0, &[(40.0, 180.0, "Page 2 of 2"), (260.0, 290.0, "2")]),
+        ]
+    }
+
+    #[test]
+    fn test_parse_lines_reads_holdings_table() {
+        let statement = parse_lines(&statement_lines(), None, None).unwrap();
+        assert_eq!(statement.broker.id, "rbc_direct_investing");
+        assert_eq!(statement.statement_date, NaiveDate::from_ymd_opt(2026, 1, 31));
+        assert_eq!(statement.accounts, vec!["123-45678-1-2".to_string()]);
+        assert_eq!(statement.warnings.len(), 1);
+        assert_eq!(statement.warnings[0], "Page 1: no symbol for 'MYSTERY FUND'; the holding was skipped");
+
+        let preview = csv_import_service::build_preview(&statement.content, None).unwrap();
+        assert!(preview.missing_required.is_empty());
+        assert_eq!(preview.confidence, 1.0);
+        assert_eq!(preview.total_rows, 2);
+        let ry = &preview.sample_rows[0];
+        assert_eq!(ry[&HoldingField::Symbol], "RY");
+        assert_eq!(ry[&HoldingField::Holding], "ROYAL BANK OF CANADA COMMON (RY)");
+        assert_eq!(ry[&HoldingField::BookValue], "12000.00");
+        assert_eq!(ry[&HoldingField::Price], "142.5");
+        assert_eq!(ry[&HoldingField::AssetCategory], "Canadian Equities");
+        let xeqt = &preview.sample_rows[1];
+        assert_eq!(xeqt[&HoldingField::Symbol], "XEQT");
+        assert_eq!(xeqt[&HoldingField::Holding], "ISHARES CORE EQUITY");
+        assert_eq!(xeqt[&HoldingField::MarketValue], "-1616.00");
+    }
+
+    #[test]
+    fn test_parse_lines_without_account_asks_for_one() {
+        let mut lines = statement_lines();
+        lines.remove(2);
+        let statement = parse_lines(&lines, Some("generic"), None).unwrap();
+        assert_eq!(statement.broker.id, "generic");
+        assert!(statement.warnings.iter().any(|w| w.contains("account_number")));
+        let preview = csv_import_service::build_preview(&statement.content, None).unwrap();
+        assert_eq!(preview.missing_required, vec![HoldingField::AccountNumber]);
+
+        let statement = parse_lines(&lines, None, Some("TFSA-1")).unwrap();
+        assert_eq!(statement.accounts, vec!["TFSA-1".to_string()]);
+        assert!(parse_lines(&lines, Some("unknown"), None).is_err());
+    }
+
+    #[test]
+    fn test_clean_number() {
+        assert_eq!(clean_number("$1,234.50").as_deref(), Some("1234.50"));
+        assert_eq!(clean_number("(12.00)").as_deref(), Some("-12.00"));
+        assert_eq!(clean_number("12.00-").as_deref(), Some("-12.00"));
+        assert_eq!(clean_number("4.5%").as_deref(), Some("4.5"));
+        assert_eq!(clean_number("USD 10").as_deref(), Some("10"));
+        assert_eq!(clean_number("N/A"), None);
+        assert_eq!(clean_number("-"), None);
+    }
+
+    #[test]
+    fn test_dates_in_reads_common_formats() {
+        assert_eq!(dates_in("As of Dec. 31, 2025"), vec![NaiveDate::from_ymd_opt(2025, 12, 31).unwrap()]);
+        assert_eq!(dates_in("as at 28 February 2026"), vec![NaiveDate::from_ymd_opt(2026, 2, 28).unwrap()]);
+        assert_eq!(dates_in("Period ending 2026/03/31"), vec![NaiveDate::from_ymd_opt(2026, 3, 31).unwrap()]);
+        assert!(dates_in("Page 2 of 5").is_empty());
+    }
+}
@@ -0,0 +1,562 @@
+//! Text extraction from the text layer of a PDF.
+//!
+//! Broker statements are generated by reporting systems, so every figure is
+//! real text in a content stream. Parsing, fonts and text positioning are left
+//! to `lopdf` and `pdf-extract`; this module places each glyph on the page and
+//! groups the placed text into lines of cells, which is what the statement
+//! parsers detect tables in. Encrypted files and scanned statements (images
+//! without text) are rejected.
+//!
+//! Statements arrive as uploads and email attachments, so the decoded size of
+//! every stream is checked against a budget before the document is parsed; a
+//! small compressed stream cannot expand into gigabytes.
+
+use anyhow::{bail, Result};
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use lopdf::Document;
+use pdf_extract::{MediaBox, OutputDev, OutputError, Transform};
+use regex::bytes::Regex;
+use std::borrow::Cow;
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::LazyLock;
+
+/// Gap, in ems, above which adjacent text is treated as separate cells
+/// rather than words of the same cell
+const CELL_GAP: f64 = 0.5;
+/// Gap, in ems, above which words of a cell are joined with a space
+const WORD_GAP: f64 = 0.15;
+/// Largest decoded size of any one stream
+const MAX_STREAM_BYTES: usize = 32 * 1024 * 1024;
+/// Largest decoded size of all streams of a document together
+const MAX_DOCUMENT_BYTES: usize = 128 * 1024 * 1024;
+/// Filters decoded in sequence per stream, e.g. ASCII85 over Flate
+const MAX_FILTER_LAYERS: usize = 4;
+
+/// Start of stream data: the `stream` keyword and its end of line
+static STREAM_START: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"stream\r?\n").unwrap());
+/// Name objects, which may spell characters as `#xx`
+static NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"/[^\s/<>\[\]()%{}]+").unwrap());
+
+/// A string drawn on a page, in page coordinates (origin bottom left)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    pub page: usize,
+    /// Start of the first visible glyph
+    pub x: f64,
+    pub y: f64,
+    /// End of the last visible glyph
+    pub end_x: f64,
+    /// Font size on the page
+    pub size: f64,
+    pub text: String,
+}
+
+/// Text that sits together on a line, such as one table cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextCell {
+    pub x: f64,
+    pub end_x: f64,
+    pub text: String,
+}
+
+/// Cells sharing a baseline, left to right
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    pub page: usize,
+    pub y: f64,
+    pub cells: Vec<TextCell>,
+}
+
+impl TextLine {
+    pub fn text(&self) -> String {
+        self.cells.iter().map(|c| c.text.as_str()).collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Text of a document, in reading order
+#[derive(Debug, Clone)]
+pub struct PdfText {
+    pub pages: usize,
+    pub lines: Vec<TextLine>,
+}
+
+/// Extract the text layer of a PDF as lines of cells. CPU-bound; async callers
+/// run it on the blocking pool.
+pub fn extract_text(data: &[u8]) -> Result<PdfText> {
+    check_decoded_size(data)?;
+    let document = Document::load_mem(data).map_err(|e| anyhow::anyhow!("Could not read the PDF: {}", e))?;
+    if document.trailer.get(b"Encrypt").is_ok() {
+        bail!("Encrypted PDFs are not supported; export the statement without a password");
+    }
+    let pages = document.get_pages().len();
+    if pages == 0 {
+        bail!("The PDF has no pages");
+    }
+
+    let mut collector = RunCollector::default();
+    // pdf-extract panics on some malformed fonts; treat that like a parse error
+    match panic::catch_unwind(AssertUnwindSafe(|| pdf_extract::output_doc(&document, &mut collector))) {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => bail!("Could not read the PDF's text: {:?}", e),
+        Err(_) => bail!("Could not read the PDF's text"),
+    }
+    collector.flush();
+
+    if collector.runs.iter().all(|r| r.text.trim().is_empty()) {
+        bail!("The PDF has no text layer; scanned statements cannot be read");
+    }
+    Ok(PdfText {
+        pages,
+        lines: group_lines(collector.runs),
+    })
+}
+
+/// Group runs into lines by baseline, then join runs on a line into cells
+/// wherever the gap between them is narrower than a column gutter
+pub fn group_lines(mut runs: Vec<TextRun>) -> Vec<TextLine> {
+    runs.retain(|r| !r.text.trim().is_empty());
+    runs.sort_by(|a, b| a.page.cmp(&b.page).then(b.y.total_cmp(&a.y)).then(a.x.total_cmp(&b.x)));
+
+    let mut grouped: Vec<(usize, f64, f64, Vec<TextRun>)> = Vec::new();
+    for run in runs {
+        match grouped.last_mut() {
+            Some((page, y, size, line))
+                if *page == run.page && (*y - run.y).abs() <= 0.4 * size.min(run.size).max(1.0) =>
+            {
+                *size = size.max(run.size);
+                line.push(run);
+            }
+            _ => grouped.push((run.page, run.y, run.size, vec![run])),
+        }
+    }
+
+    grouped
+        .into_iter()
+        .map(|(page, y, _, mut line)| {
+            line.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let mut cells: Vec<(TextCell, f64)> = Vec::new();
+            for run in line {
+                let text = run.text.trim();
+                match cells.last_mut() {
+                    Some((cell, size)) if run.x - cell.end_x < CELL_GAP * size.max(run.size) => {
+                        if run.x - cell.end_x > WORD_GAP * size.max(run.size) {
+                            cell.text.push(' ');
+                        }
+                        cell.text.push_str(text);
+                        cell.end_x = cell.end_x.max(run.end_x);
+                        *size = size.max(run.size);
+                    }
+                    _ => cells.push((
+                        TextCell {
+                            x: run.x,
+                            end_x: run.end_x,
+                            text: text.to_string(),
+                        },
+                        run.size,
+                    )),
+                }
+            }
+            TextLine {
+                page,
+                y,
+                cells: cells.into_iter().map(|(cell, _)| cell).collect(),
+            }
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Decoded size budget
+
+/// Decode every stream the way the parser would, keeping only the size, and
+/// fail once a stream or the whole document decodes past its budget. Streams
+/// are found by their `stream` keyword and decoded from there to the end of
+/// the file; a decoder stops at the end of its own data, so a wrong or
+/// indirect /Length cannot hide part of a stream.
+fn check_decoded_size(data: &[u8]) -> Result<()> {
+    if NAME.find_iter(data).any(|name| matches!(decode_name(name.as_bytes()).as_slice(), b"/LZWDecode" | b"/LZW")) {
+        bail!("LZW-compressed PDFs are not supported");
+    }
+
+    let mut total = 0;
+    for start in STREAM_START.find_iter(data) {
+        if data[..start.start()].ends_with(b"end") {
+            continue;
+        }
+        let mut layer = Cow::Borrowed(&data[start.end()..]);
+        let mut largest = 0;
+        for _ in 0..MAX_FILTER_LAYERS {
+            let Some(decoded) = decode_layer(&layer)? else { break };
+            largest = largest.max(decoded.len());
+            layer = Cow::Owned(decoded);
+        }
+        total += largest;
+        if total > MAX_DOCUMENT_BYTES {
+            bail!("The PDF decompresses to more than {} MB", MAX_DOCUMENT_BYTES / (1024 * 1024));
+        }
+    }
+    Ok(())
+}
+
+/// Name with its `#xx` escapes resolved
+fn decode_name(name: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(name.len());
+    let mut i = 0;
+    while i < name.len() {
+        let escaped = (name[i] == b'#')
+            .then(|| name.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(name[i]);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// One filter's worth of decoding: zlib, else ASCII85, else raw deflate past
+/// a damaged zlib header, which the parser falls back to. None when the data
+/// is none of these.
+fn decode_layer(data: &[u8]) -> Result<Option<Vec<u8>>> {
+    let inflated = inflate_capped(ZlibDecoder::new(data))?;
+    if !inflated.is_empty() {
+        return Ok(Some(inflated));
+    }
+    if let Some(decoded) = ascii85_decode(data) {
+        return Ok(Some(decoded));
+    }
+    if data.len() > 2 {
+        let inflated = inflate_capped(DeflateDecoder::new(&data[2..]))?;
+        if !inflated.is_empty() {
+            return Ok(Some(inflated));
+        }
+    }
+    Ok(None)
+}
+
+/// Read a decoder to its end, or fail once it passes the per-stream budget.
+/// Corrupt data yields whatever decoded before the error.
+fn inflate_capped(decoder: impl Read) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let _ = decoder.take(MAX_STREAM_BYTES as u64 + 1).read_to_end(&mut output);
+    if output.len() > MAX_STREAM_BYTES {
+        bail!("A PDF stream decompresses to more than {} MB", MAX_STREAM_BYTES / (1024 * 1024));
+    }
+    Ok(output)
+}
+
+/// ASCII85 data up to its `~>` terminator; None when the stream is not ASCII85
+fn ascii85_decode(data: &[u8]) -> Option<Vec<u8>> {
+    let end = data.windows(2).position(|w| w == b"~>")?;
+    let mut output = Vec::new();
+    let mut group = Vec::with_capacity(5);
+    for &b in &data[..end] {
+        match b {
+            b'z' if group.is_empty() => output.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => {
+                group.push(b - b'!');
+                if group.len() == 5 {
+                    let value = group.iter().fold(0u64, |acc, &d| acc * 85 + d as u64);
+                    output.extend_from_slice(&(value as u32).to_be_bytes());
+                    group.clear();
+                }
+            }
+            b if b.is_ascii_whitespace() => {}
+            _ => return None,
+        }
+        if output.len() > MAX_STREAM_BYTES {
+            return None;
+        }
+    }
+    if !group.is_empty() {
+        let kept = group.len() - 1;
+        group.resize(5, 84);
+        let value = group.iter().fold(0u64, |acc, &d| acc * 85 + d as u64);
+        output.extend_from_slice(&(value as u32).to_be_bytes()[..kept]);
+    }
+    (!output.is_empty()).then_some(output)
+}
+
+// ---------------------------------------------------------------------------
+// Glyph placement
+
+/// Run being built from consecutive glyphs
+struct PendingRun {
+    run: TextRun,
+    /// Whitespace was shown since the last visible glyph
+    space: bool,
+}
+
+/// Receives each glyph with its text rendering matrix and joins glyphs that
+/// sit next to each other on a baseline into runs
+#[derive(Default)]
+struct RunCollector {
+    page: usize,
+    runs: Vec<TextRun>,
+    pending: Option<PendingRun>,
+}
+
+impl RunCollector {
+    fn flush(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            self.runs.push(pending.run);
+        }
+    }
+}
+
+impl OutputDev for RunCollector {
+    fn begin_page(&mut self, page_num: u32, _media_box: &MediaBox, _art_box: Option<(f64, f64, f64, f64)>) -> Result<(), OutputError> {
+        self.flush();
+        self.page = page_num as usize;
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        self.flush();
+        Ok(())
+    }
+
+    fn output_character(&mut self, trm: &Transform, width: f64, _spacing: f64, font_size: f64, text: &str) -> Result<(), OutputError> {
+        let scale_x = trm.m11.hypot(trm.m12);
+        let scale_y = trm.m21.hypot(trm.m22);
+        let (x, y) = (trm.m31, trm.m32);
+        let size = (font_size * scale_y).abs();
+        let end_x = x + width * font_size * scale_x;
+
+        if text.trim().is_empty() {
+            if let Some(pending) = self.pending.as_mut() {
+                pending.space = true;
+            }
+            return Ok(());
+        }
+
+        let continues = self.pending.as_ref().is_some_and(|p| {
+            let gap = x - p.run.end_x;
+            p.run.page == self.page
+                && (p.run.y - y).abs() < 0.01 * size.max(1.0)
+                && gap > -0.5 * size
+                && gap < CELL_GAP * size
+        });
+        if continues {
+            if let Some(pending) = self.pending.as_mut() {
+                if pending.space || x - pending.run.end_x > WORD_GAP * size {
+                    pending.run.text.push(' ');
+                }
+                pending.run.text.push_str(text);
+                pending.run.end_x = end_x;
+                pending.space = false;
+            }
+        } else {
+            self.flush();
+            self.pending = Some(PendingRun {
+                run: TextRun {
+                    page: self.page,
+                    x,
+                    y,
+                    end_x,
+                    size,
+                    text: text.to_string(),
+                },
+                space: false,
+            });
+        }
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use lopdf::{dictionary, Object, Stream};
+    use std::io::Write;
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// A one-page PDF with a compressed content stream, a simple font and a
+    /// Type0 font mapped through a ToUnicode CMap
+    fn sample_pdf(content: &str, encrypted: bool) -> Vec<u8> {
+        let cmap = "/CIDInit /ProcSet findresource begin 12 dict begin begincmap\n\
+                    1 begincodespacerange <0000> <FFFF> endcodespacerange\n\
+                    2 beginbfchar <0001> <0041> <0002> <0050> endbfchar\n\
+                    1 beginbfrange <0010> <0019> <0030> endbfrange\n\
+                    endcmap end end";
+
+        let mut doc = Document::with_version("1.4");
+        let pages_id = doc.new_object_id();
+        let simple = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let to_unicode = doc.add_object(Stream::new(dictionary! {}, cmap.as_bytes().to_vec()));
+        let descendant = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "CIDFontType2",
+            "BaseFont" => "Custom",
+            "CIDSystemInfo" => dictionary! {
+                "Registry" => Object::string_literal("Adobe"),
+                "Ordering" => Object::string_literal("Identity"),
+                "Supplement" => 0,
+            },
+            "FontDescriptor" => dictionary! {
+                "Type" => "FontDescriptor",
+                "FontName" => "Custom",
+                "Flags" => 32,
+            },
+            "DW" => 600,
+        });
+        let composite = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => "Custom",
+            "Encoding" => "Identity-H",
+            "DescendantFonts" => vec![descendant.into()],
+            "ToUnicode" => to_unicode,
+        });
+        let contents = doc.add_object(Stream::new(
+            dictionary! { "Filter" => "FlateDecode" },
+            zlib(content.as_bytes()),
+        ));
+        let page = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            "Contents" => contents,
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page.into()],
+                "Count" => 1,
+                "Resources" => dictionary! {
+                    "Font" => dictionary! { "F1" => simple, "F2" => composite },
+                },
+            }),
+        );
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog);
+        if encrypted {
+            let encrypt = doc.add_object(dictionary! { "Filter" => "Standard", "V" => 1, "R" => 2 });
+            doc.trailer.set("Encrypt", encrypt);
+        }
+
+        let mut pdf = Vec::new();
+        doc.save_to(&mut pdf).unwrap();
+        pdf
+    }
+
+    #[test]
+    fn test_extract_text_places_cells_on_lines() {
+        let pdf = sample_pdf(
+            "BT /F1 10 Tf 50 700 Td (Symbol) Tj 150 0 Td (Quantity) Tj ET\n\
+             BT /F2 10 Tf 1 0 0 1 50 686 Tm <00010001000200010001> Tj 150 0 Td <00110010> Tj ET\n\
+             BT /F1 10 Tf 50 672 Td [(Royal) -250 (Bank)] TJ ET",
+            false,
+        );
+        let text = extract_text(&pdf).unwrap();
+        assert_eq!(text.pages, 1);
+        let lines: Vec<Vec<&str>> = text
+            .lines
+            .iter()
+            .map(|l| l.cells.iter().map(|c| c.text.as_str()).collect())
+            .collect();
+        assert_eq!(lines, vec![vec!["Symbol", "Quantity"], vec!["AAPAA", "10"], vec!["Royal Bank"]]);
+        assert!((text.lines[0].cells[1].x - 200.0).abs() < 0.01);
+        assert!(text.lines[0].y > text.lines[1].y);
+    }
+
+    #[test]
+    fn test_group_lines_splits_cells_on_wide_gaps() {
+        let run = |x: f64, end_x: f64, y: f64, text: &str| TextRun {
+            page: 1,
+            x,
+            y,
+            end_x,
+            size: 10.0,
+            text: text.to_string(),
+        };
+        let lines = group_lines(vec![
+            run(300.0, 320.0, 500.0, "12.50"),
+            run(50.0, 80.0, 500.4, "TD"),
+            run(82.0, 110.0, 500.0, "BANK"),
+            run(50.0, 70.0, 480.0, "Next"),
+        ]);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text(), "TD BANK 12.50");
+        assert_eq!(lines[0].cells.len(), 2);
+        assert_eq!(lines[1].cells[0].text, "Next");
+    }
+
+    #[test]
+    fn test_rejects_encrypted_and_textless_documents() {
+        let encrypted = sample_pdf("BT /F1 10 Tf (x) Tj ET", true);
+        assert!(extract_text(&encrypted).unwrap_err().to_string().contains("Encrypted"));
+
+        let scanned = sample_pdf("q 612 0 0 792 0 0 cm BI /W 1 /H 1 /BPC 8 /CS /G ID \x01 EI Q", false);
+        assert!(extract_text(&scanned).unwrap_err().to_string().contains("no text layer"));
+        assert!(extract_text(b"hello").is_err());
+    }
+
+    #[test]
+    fn test_rejects_streams_that_decompress_past_the_budget() {
+        // About 32 KB of zlib data that expands past the per-stream budget
+        let bomb = zlib(&vec![0u8; MAX_STREAM_BYTES + 1]);
+        let mut pdf = b"%PDF-1.4\n1 0 obj << /Filter /FlateDecode >>\nstream\n".to_vec();
+        pdf.extend_from_slice(&bomb);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n%%EOF\n");
+        assert!(extract_text(&pdf).unwrap_err().to_string().contains("decompresses to more than"));
+
+        // The same data wrapped in ASCII85 is caught after both layers
+        let mut wrapped = b"%PDF-1.4\n1 0 obj << /Filter [/ASCII85Decode /FlateDecode] >>\nstream\n".to_vec();
+        wrapped.extend_from_slice(&ascii85_encode(&bomb));
+        wrapped.extend_from_slice(b"~>\nendstream\nendobj\n%%EOF\n");
+        assert!(check_decoded_size(&wrapped).is_err());
+
+        assert!(check_decoded_size(b"1 0 obj << /Filter /LZW#44ecode >> stream\n").is_err());
+        assert!(check_decoded_size(&sample_pdf("BT /F1 10 Tf (x) Tj ET", false)).is_ok());
+    }
+
+    fn ascii85_encode(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for chunk in data.chunks(4) {
+            let mut group = [0u8; 4];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let mut value = u32::from_be_bytes(group) as u64;
+            let mut digits = [0u8; 5];
+            for digit in digits.iter_mut().rev() {
+                *digit = (value % 85) as u8 + b'!';
+                value /= 85;
+            }
+            out.extend_from_slice(&digits[..chunk.len() + 1]);
+        }
+        out
+    }
+}
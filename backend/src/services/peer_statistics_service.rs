@@ -0,0 +1,344 @@
+//! Anonymous aggregate statistics across portfolios.
+//!
+//! Only portfolios of users who opted in (`share_anonymous_stats`) are read. The
+//! daily refresh stores the 1st..99th percentile cut points of each portfolio-level
+//! risk metric and the most commonly held tickers. Nothing is published until
+//! enough portfolios take part, and rarely held tickers are never stored, so no
+//! individual portfolio can be read back out of the tables.
+
+use chrono::Duration;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db::{peer_statistics_queries, risk_preferences_queries};
+use crate::db::peer_statistics_queries::PeerPortfolioMetrics;
+use crate::errors::AppError;
+use crate::models::{
+    PeerCommonHolding, PeerContext, PeerMetric, PeerMetricDistribution, PeerMetricSummary, PeerPercentile,
+    PeerStatistics, PortfolioRisk,
+};
+use crate::services::risk_service;
+use crate::services::clock;
+
+/// Fewest portfolios a distribution is published for
+pub const MIN_PEER_PORTFOLIOS: usize = 10;
+/// Fewest portfolios that must hold a ticker before it is listed
+pub const MIN_HOLDING_PORTFOLIOS: i64 = 5;
+/// Snapshots older than this are left out, so abandoned portfolios age out
+const SNAPSHOT_MAX_AGE_DAYS: i64 = 30;
+const COMMON_HOLDINGS_LIMIT: usize = 20;
+
+/// Outcome of a statistics refresh
+#[derive(Debug, Clone, Copy)]
+pub struct PeerRefresh {
+    pub portfolios: usize,
+    pub metrics_published: usize,
+    pub holdings_published: usize,
+}
+
+/// 1st..99th percentile cut points, linearly interpolated. Empty input gives none.
+pub fn percentile_cuts(values: &[f64]) -> Vec<f64> {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    if sorted.is_empty() {
+        return Vec::new();
+    }
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let last = (sorted.len() - 1) as f64;
+    (1..=99)
+        .map(|p| {
+            let rank = last * p as f64 / 100.0;
+            let lower = rank.floor() as usize;
+            let upper = rank.ceil() as usize;
+            sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+        })
+        .collect()
+}
+
+/// Share of peers (0-100) taking less risk than `value` on `metric`
+pub fn riskier_than_pct(metric: PeerMetric, cuts: &[f64], value: f64) -> f64 {
+    if cuts.is_empty() {
+        return 0.0;
+    }
+    let below = if metric.higher_is_riskier() {
+        cuts.iter().filter(|c| **c < value).count()
+    } else {
+        cuts.iter().filter(|c| **c > value).count()
+    };
+    below as f64 * 100.0 / (cuts.len() + 1) as f64
+}
+
+fn metric_value(metric: PeerMetric, metrics: &PeerPortfolioMetrics) -> Option<f64> {
+    match metric {
+        PeerMetric::Volatility => Some(metrics.volatility),
+        PeerMetric::MaxDrawdown => Some(metrics.max_drawdown),
+        PeerMetric::Beta => metrics.beta,
+        PeerMetric::Sharpe => metrics.sharpe,
+        PeerMetric::RiskScore => Some(metrics.risk_score),
+    }
+}
+
+fn portfolio_value(metric: PeerMetric, risk: &PortfolioRisk) -> Option<f64> {
+    match metric {
+        PeerMetric::Volatility => Some(risk.portfolio_volatility),
+        PeerMetric::MaxDrawdown => Some(risk.portfolio_max_drawdown),
+        PeerMetric::Beta => risk.portfolio_beta,
+        PeerMetric::Sharpe => risk.portfolio_sharpe,
+        // Scores from another scoring model are not comparable with the distribution
+        PeerMetric::RiskScore => {
+            (risk.scoring_version == risk_service::CURRENT_SCORING_VERSION).then_some(risk.portfolio_risk_score)
+        }
+    }
+}
+
+/// Distributions for every metric with enough non-missing values
+pub fn build_distributions(portfolios: &[PeerPortfolioMetrics]) -> Vec<(String, i32, Vec<f64>)> {
+    PeerMetric::ALL
+        .into_iter()
+        .filter_map(|metric| {
+            let values: Vec<f64> = portfolios.iter().filter_map(|p| metric_value(metric, p)).collect();
+            if values.len() < MIN_PEER_PORTFOLIOS {
+                return None;
+            }
+            Some((metric.as_str().to_string(), values.len() as i32, percentile_cuts(&values)))
+        })
+        .collect()
+}
+
+/// Percentile context for a portfolio, or `None` when no distribution applies
+pub fn build_context(distributions: &[PeerMetricDistribution], risk: &PortfolioRisk) -> Option<PeerContext> {
+    let mut sample_size = i32::MAX;
+    let mut computed_at = None;
+    let mut percentiles = Vec::new();
+
+    for distribution in distributions {
+        let Some(metric) = PeerMetric::from_key(&distribution.metric) else {
+            continue;
+        };
+        let Some(value) = portfolio_value(metric, risk) else {
+            continue;
+        };
+        if distribution.percentiles.len() != 99 {
+            continue;
+        }
+        sample_size = sample_size.min(distribution.sample_size);
+        computed_at = computed_at.max(Some(distribution.computed_at));
+        percentiles.push(PeerPercentile {
+            metric,
+            label: metric.label().to_string(),
+            value,
+            peer_median: distribution.percentiles[49],
+            riskier_than_pct: riskier_than_pct(metric, &distribution.percentiles, value),
+        });
+    }
+
+    let computed_at = computed_at?;
+    let summary = [PeerMetric::RiskScore, PeerMetric::Volatility]
+        .iter()
+        .find_map(|metric| percentiles.iter().find(|p| p.metric == *metric))
+        .map(|p| format!("Your portfolio is riskier than {:.0}% of rustfolio portfolios", p.riskier_than_pct));
+
+    Some(PeerContext {
+        sample_size,
+        computed_at,
+        percentiles,
+        summary,
+    })
+}
+
+/// Recompute and store the aggregate statistics from opted-in portfolios
+pub async fn refresh_peer_statistics(pool: &PgPool) -> Result<PeerRefresh, AppError> {
+    let since = clock::today() - Duration::days(SNAPSHOT_MAX_AGE_DAYS);
+    let portfolios =
+        peer_statistics_queries::fetch_opted_in_portfolio_metrics(pool, since, risk_service::CURRENT_SCORING_VERSION)
+            .await?;
+
+    // Below the minimum everything is withdrawn rather than left stale
+    let distributions = if portfolios.len() >= MIN_PEER_PORTFOLIOS {
+        build_distributions(&portfolios)
+    } else {
+        info!(
+            "Only {} opted-in portfolios with recent snapshots (minimum {}); publishing no distributions",
+            portfolios.len(),
+            MIN_PEER_PORTFOLIOS
+        );
+        Vec::new()
+    };
+    peer_statistics_queries::replace_distributions(pool, &distributions).await?;
+
+    let (total, counts) = peer_statistics_queries::fetch_opted_in_holding_counts(pool, MIN_HOLDING_PORTFOLIOS).await?;
+    let holdings: Vec<PeerCommonHolding> = if total as usize >= MIN_PEER_PORTFOLIOS {
+        counts
+            .into_iter()
+            .take(COMMON_HOLDINGS_LIMIT)
+            .map(|(ticker, count)| PeerCommonHolding {
+                ticker,
+                portfolio_count: count as i32,
+                portfolio_share: count as f64 * 100.0 / total as f64,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    peer_statistics_queries::replace_common_holdings(pool, &holdings).await?;
+
+    Ok(PeerRefresh {
+        portfolios: portfolios.len(),
+        metrics_published: distributions.len(),
+        holdings_published: holdings.len(),
+    })
+}
+
+async fn is_opted_in(pool: &PgPool, user_id: Uuid) -> Result<bool, AppError> {
+    Ok(risk_preferences_queries::get_preferences_by_user_id(pool, user_id)
+        .await?
+        .is_some_and(|prefs| prefs.share_anonymous_stats))
+}
+
+/// Published statistics. Only users who share their own data can see them.
+pub async fn get_peer_statistics(pool: &PgPool, user_id: Uuid) -> Result<PeerStatistics, AppError> {
+    if !is_opted_in(pool, user_id).await? {
+        return Err(AppError::Validation(
+            "Anonymous statistics are only available after opting in with share_anonymous_stats in /api/users/me/preferences"
+                .to_string(),
+        ));
+    }
+
+    let distributions = peer_statistics_queries::fetch_distributions(pool).await?;
+    let computed_at = distributions.iter().map(|d| d.computed_at).max();
+    let distributions = distributions
+        .into_iter()
+        .filter_map(|d| {
+            let metric = PeerMetric::from_key(&d.metric)?;
+            (d.percentiles.len() == 99).then(|| PeerMetricSummary {
+                metric,
+                label: metric.label().to_string(),
+                sample_size: d.sample_size,
+                p25: d.percentiles[24],
+                median: d.percentiles[49],
+                p75: d.percentiles[74],
+            })
+        })
+        .collect();
+
+    Ok(PeerStatistics {
+        computed_at,
+        distributions,
+        common_holdings: peer_statistics_queries::fetch_common_holdings(pool).await?,
+    })
+}
+
+/// Percentile context for the risk response, `None` unless the user opted in
+pub async fn peer_context_for(
+    pool: &PgPool,
+    user_id: Uuid,
+    risk: &PortfolioRisk,
+) -> Result<Option<PeerContext>, AppError> {
+    if !is_opted_in(pool, user_id).await? {
+        return Ok(None);
+    }
+    let distributions = peer_statistics_queries::fetch_distributions(pool).await?;
+    Ok(build_context(&distributions, risk))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::RiskLevel;
+
+    fn peer(volatility: f64, max_drawdown: f64, risk_score: f64) -> PeerPortfolioMetrics {
+        PeerPortfolioMetrics {
+            volatility,
+            max_drawdown,
+            beta: None,
+            sharpe: None,
+            risk_score,
+        }
+    }
+
+    fn risk(volatility: f64, max_drawdown: f64, risk_score: f64) -> PortfolioRisk {
+        PortfolioRisk {
+            portfolio_id: Uuid::nil().to_string(),
+            total_value: 100_000.0,
+            portfolio_volatility: volatility,
+            portfolio_max_drawdown: max_drawdown,
+            portfolio_beta: None,
+            portfolio_regression_beta: None,
+            portfolio_alpha: None,
+            portfolio_r_squared: None,
+            portfolio_sharpe: None,
+            portfolio_sortino: None,
+            portfolio_sharpe_weighted_average: None,
+            portfolio_var_95: None,
+            portfolio_var_99: None,
+            portfolio_expected_shortfall_95: None,
+            portfolio_expected_shortfall_99: None,
+            portfolio_risk_score: risk_score,
+            risk_level: RiskLevel::from_score(risk_score),
+            scoring_version: risk_service::CURRENT_SCORING_VERSION,
+            position_risks: vec![],
+        }
+    }
+
+    #[test]
+    fn test_percentile_cuts_interpolate() {
+        let values: Vec<f64> = (0..=100).map(|v| v as f64).collect();
+        let cuts = percentile_cuts(&values);
+        assert_eq!(cuts.len(), 99);
+        assert_eq!(cuts[0], 1.0);
+        assert_eq!(cuts[49], 50.0);
+        assert_eq!(cuts[98], 99.0);
+
+        let cuts = percentile_cuts(&[10.0, 20.0]);
+        assert!((cuts[49] - 15.0).abs() < 1e-9);
+        assert!(percentile_cuts(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_riskier_than_respects_metric_direction() {
+        let cuts = percentile_cuts(&(0..=100).map(|v| v as f64).collect::<Vec<_>>());
+        assert_eq!(riskier_than_pct(PeerMetric::Volatility, &cuts, 78.5), 78.0);
+        // A drawdown of -90% is deeper than almost every peer's
+        let drawdowns = percentile_cuts(&(0..=100).map(|v| -(v as f64)).collect::<Vec<_>>());
+        assert_eq!(riskier_than_pct(PeerMetric::MaxDrawdown, &drawdowns, -90.5), 90.0);
+    }
+
+    #[test]
+    fn test_distributions_need_minimum_sample() {
+        let few: Vec<PeerPortfolioMetrics> = (0..MIN_PEER_PORTFOLIOS - 1).map(|i| peer(i as f64, -5.0, 40.0)).collect();
+        assert!(build_distributions(&few).is_empty());
+
+        let enough: Vec<PeerPortfolioMetrics> = (0..MIN_PEER_PORTFOLIOS).map(|i| peer(i as f64, -5.0, 40.0)).collect();
+        let metrics: Vec<String> = build_distributions(&enough).into_iter().map(|d| d.0).collect();
+        // Beta and Sharpe are missing for every peer
+        assert_eq!(metrics, vec!["volatility", "max_drawdown", "risk_score"]);
+    }
+
+    #[test]
+    fn test_build_context_summarizes_risk_score() {
+        let peers: Vec<PeerPortfolioMetrics> = (0..=100).map(|i| peer(i as f64 / 4.0, -(i as f64) / 2.0, i as f64)).collect();
+        let distributions: Vec<PeerMetricDistribution> = build_distributions(&peers)
+            .into_iter()
+            .map(|(metric, sample_size, percentiles)| PeerMetricDistribution {
+                metric,
+                sample_size,
+                percentiles,
+                computed_at: chrono::Utc::now(),
+            })
+            .collect();
+
+        let context = build_context(&distributions, &risk(10.0, -20.0, 78.5)).unwrap();
+        assert_eq!(context.sample_size, 101);
+        assert_eq!(context.percentiles.len(), 3);
+        assert_eq!(context.summary.as_deref(), Some("Your portfolio is riskier than 78% of rustfolio portfolios"));
+
+        let mut old_model = risk(10.1, -20.0, 78.5);
+        old_model.scoring_version = risk_service::CURRENT_SCORING_VERSION - 1;
+        let context = build_context(&distributions, &old_model).unwrap();
+        assert!(context.percentiles.iter().all(|p| p.metric != PeerMetric::RiskScore));
+        assert!(context.summary.unwrap().starts_with("Your portfolio is riskier than 40%"));
+
+        assert!(build_context(&[], &old_model).is_none());
+    }
+}
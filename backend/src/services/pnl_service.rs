@@ -0,0 +1,369 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bigdecimal::ToPrimitive;
+use chrono::{Datelike, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{holding_snapshot_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{
+    OpenLotPnl, PnlQuery, PortfolioPnl, PositionPnl, RealizedLot, RealizedPeriodPnl, TaxLot, WashSale,
+};
+use crate::services::{lot_service, wash_sale_service};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PnlPeriod {
+    Month,
+    Quarter,
+    Year,
+}
+
+impl PnlPeriod {
+    pub fn from_param(value: Option<&str>) -> Result<Self, AppError> {
+        match value.map(|v| v.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("month") => Ok(PnlPeriod::Month),
+            Some("quarter") => Ok(PnlPeriod::Quarter),
+            Some("year") => Ok(PnlPeriod::Year),
+            Some(other) => Err(AppError::Validation(format!(
+                "Unsupported period '{}'. Use month, quarter, or year",
+                other
+            ))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PnlPeriod::Month => "month",
+            PnlPeriod::Quarter => "quarter",
+            PnlPeriod::Year => "year",
+        }
+    }
+
+    /// Sortable period label for a date, e.g. "2026-03", "2026-Q1", "2026"
+    pub fn label(&self, date: NaiveDate) -> String {
+        match self {
+            PnlPeriod::Month => format!("{}-{:02}", date.year(), date.month()),
+            PnlPeriod::Quarter => format!("{}-Q{}", date.year(), (date.month() - 1) / 3 + 1),
+            PnlPeriod::Year => date.year().to_string(),
+        }
+    }
+}
+
+fn empty_period(label: String) -> RealizedPeriodPnl {
+    RealizedPeriodPnl {
+        period: label,
+        proceeds: 0.0,
+        cost_basis: 0.0,
+        realized_gain: 0.0,
+        short_term_gain: 0.0,
+        long_term_gain: 0.0,
+        wash_sale_disallowed_loss: 0.0,
+        lots_closed: 0,
+    }
+}
+
+/// Group realized lots and wash sale adjustments into reporting periods by sale date,
+/// oldest period first
+pub fn realized_by_period(
+    realized: &[RealizedLot],
+    wash_sales: &[WashSale],
+    period: PnlPeriod,
+) -> Vec<RealizedPeriodPnl> {
+    let mut periods: BTreeMap<String, RealizedPeriodPnl> = BTreeMap::new();
+    for lot in realized {
+        let label = period.label(lot.sold_date);
+        let entry = periods
+            .entry(label.clone())
+            .or_insert_with(|| empty_period(label));
+        entry.proceeds += lot.proceeds;
+        entry.cost_basis += lot.cost_basis;
+        entry.realized_gain += lot.gain;
+        if lot.long_term {
+            entry.long_term_gain += lot.gain;
+        } else {
+            entry.short_term_gain += lot.gain;
+        }
+        entry.lots_closed += 1;
+    }
+    for sale in wash_sales {
+        let label = period.label(sale.sold_date);
+        periods
+            .entry(label.clone())
+            .or_insert_with(|| empty_period(label))
+            .wash_sale_disallowed_loss += sale.disallowed_loss;
+    }
+    periods.into_values().collect()
+}
+
+fn pct(numerator: f64, denominator: f64) -> f64 {
+    if denominator.abs() > f64::EPSILON {
+        numerator / denominator * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Value open lots at `current` prices and attribute the day change
+/// (current vs `previous` close) to each position.
+pub fn position_pnl(
+    open_lots: &[TaxLot],
+    realized: &[RealizedLot],
+    current: &HashMap<String, f64>,
+    previous: &HashMap<String, f64>,
+) -> Vec<PositionPnl> {
+    let mut by_ticker: BTreeMap<String, Vec<&TaxLot>> = BTreeMap::new();
+    for lot in open_lots {
+        by_ticker.entry(lot.ticker.clone()).or_default().push(lot);
+    }
+
+    let mut realized_by_ticker: HashMap<&str, f64> = HashMap::new();
+    for lot in realized {
+        *realized_by_ticker.entry(lot.ticker.as_str()).or_default() += lot.gain;
+    }
+
+    let mut positions: Vec<PositionPnl> = by_ticker
+        .into_iter()
+        .map(|(ticker, lots)| {
+            let price = current.get(&ticker).copied();
+            let prev = previous.get(&ticker).copied();
+
+            let lots: Vec<OpenLotPnl> = lots
+                .into_iter()
+                .map(|lot| {
+                    let current_price = price.unwrap_or(lot.cost_per_share);
+                    let cost_basis = lot.quantity * lot.cost_per_share;
+                    let market_value = lot.quantity * current_price;
+                    OpenLotPnl {
+                        lot: lot.clone(),
+                        current_price,
+                        cost_basis,
+                        market_value,
+                        unrealized_gain: market_value - cost_basis,
+                        unrealized_gain_pct: pct(market_value - cost_basis, cost_basis),
+                        day_change: price.zip(prev).map(|(c, p)| lot.quantity * (c - p)),
+                    }
+                })
+                .collect();
+
+            let quantity: f64 = lots.iter().map(|l| l.lot.quantity).sum();
+            let cost_basis: f64 = lots.iter().map(|l| l.cost_basis).sum();
+            let market_value: f64 = lots.iter().map(|l| l.market_value).sum();
+            let day_change = price.zip(prev).map(|(c, p)| quantity * (c - p));
+            let prev_value = prev.map(|p| quantity * p);
+
+            PositionPnl {
+                realized_gain: realized_by_ticker.remove(ticker.as_str()).unwrap_or(0.0),
+                ticker,
+                quantity,
+                cost_basis,
+                market_value,
+                unrealized_gain: market_value - cost_basis,
+                unrealized_gain_pct: pct(market_value - cost_basis, cost_basis),
+                day_change,
+                day_change_pct: day_change.zip(prev_value).map(|(d, v)| pct(d, v)),
+                day_change_contribution_pct: None,
+                lots,
+            }
+        })
+        .collect();
+
+    // Fully closed positions still report their realized gain
+    let mut closed: Vec<(&str, f64)> = realized_by_ticker.into_iter().collect();
+    closed.sort_by(|a, b| a.0.cmp(b.0));
+    for (ticker, gain) in closed {
+        positions.push(PositionPnl {
+            ticker: ticker.to_string(),
+            quantity: 0.0,
+            cost_basis: 0.0,
+            market_value: 0.0,
+            unrealized_gain: 0.0,
+            unrealized_gain_pct: 0.0,
+            realized_gain: gain,
+            day_change: None,
+            day_change_pct: None,
+            day_change_contribution_pct: None,
+            lots: Vec::new(),
+        });
+    }
+
+    let total_day_change: f64 = positions.iter().filter_map(|p| p.day_change).sum();
+    if total_day_change.abs() > f64::EPSILON {
+        for position in &mut positions {
+            position.day_change_contribution_pct = position.day_change.map(|d| d / total_day_change * 100.0);
+        }
+    }
+
+    positions
+}
+
+/// Realized and unrealized P&L for a portfolio, built from its tax lots.
+///
+/// Realized P&L doubles as the portfolio's tax report, so wash sales are listed and
+/// their disallowed losses totalled per period.
+pub async fn portfolio_pnl(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    query: &PnlQuery,
+) -> Result<PortfolioPnl, AppError> {
+    let period = PnlPeriod::from_param(query.period.as_deref())?;
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return Err(AppError::Validation("'from' must not be after 'to'".into()));
+        }
+    }
+
+    let trades = lot_service::load_portfolio_trades(pool, portfolio_id).await?;
+    let matching = lot_service::load_lot_matching(pool, portfolio_id).await?;
+    let ledger = lot_service::build_ledger(trades.clone(), &matching);
+    let in_range = |date: NaiveDate| {
+        query.from.is_none_or(|from| date >= from) && query.to.is_none_or(|to| date <= to)
+    };
+    let wash_sales: Vec<WashSale> = wash_sale_service::detect_wash_sales(&trades, &ledger)
+        .into_iter()
+        .filter(|s| in_range(s.sold_date))
+        .collect();
+
+    let mut tickers: Vec<String> = ledger.open_lots.iter().map(|l| l.ticker.clone()).collect();
+    tickers.sort();
+    tickers.dedup();
+
+    // Latest close from price history, falling back to the latest snapshot price
+    let mut current: HashMap<String, f64> = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id)
+        .await?
+        .into_iter()
+        .filter_map(|h| h.price.to_f64().map(|p| (h.ticker, p)))
+        .collect();
+    let mut previous: HashMap<String, f64> = HashMap::new();
+    let windows = price_queries::fetch_window_batch(pool, &tickers, 2).await?;
+    for (ticker, points) in windows {
+        let closes: Vec<f64> = points.iter().filter_map(|p| p.close_price.to_f64()).collect();
+        if let Some(last) = closes.last() {
+            current.insert(ticker.clone(), *last);
+        }
+        if closes.len() == 2 {
+            previous.insert(ticker, closes[0]);
+        }
+    }
+
+    let realized: Vec<RealizedLot> = ledger
+        .realized
+        .into_iter()
+        .filter(|l| in_range(l.sold_date))
+        .collect();
+
+    let positions = position_pnl(&ledger.open_lots, &realized, &current, &previous);
+
+    let total_cost_basis: f64 = positions.iter().map(|p| p.cost_basis).sum();
+    let total_market_value: f64 = positions.iter().map(|p| p.market_value).sum();
+    let day_changes: Vec<f64> = positions.iter().filter_map(|p| p.day_change).collect();
+    let total_day_change = if day_changes.is_empty() {
+        None
+    } else {
+        Some(day_changes.iter().sum::<f64>())
+    };
+
+    let mut unmatched_sells: Vec<String> = ledger.unmatched_sells.into_iter().map(|(_, t, _)| t).collect();
+    unmatched_sells.sort();
+    unmatched_sells.dedup();
+
+    Ok(PortfolioPnl {
+        portfolio_id,
+        period: period.as_str().to_string(),
+        total_cost_basis,
+        total_market_value,
+        total_unrealized_gain: total_market_value - total_cost_basis,
+        total_realized_gain: realized.iter().map(|l| l.gain).sum(),
+        total_day_change,
+        total_day_change_pct: total_day_change.map(|d| pct(d, total_market_value - d)),
+        positions,
+        realized_by_period: realized_by_period(&realized, &wash_sales, period),
+        realized_lots: realized,
+        wash_sales,
+        unmatched_sells,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn realized(sold: NaiveDate, gain: f64, long_term: bool) -> RealizedLot {
+        RealizedLot {
+            account_id: Uuid::nil(),
+            ticker: "AAPL".to_string(),
+            acquired_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            sold_date: sold,
+            quantity: 1.0,
+            cost_basis: 100.0,
+            proceeds: 100.0 + gain,
+            gain,
+            holding_days: 10,
+            long_term,
+            sell_transaction_id: Uuid::nil(),
+            lot_transaction_id: None,
+        }
+    }
+
+    fn lot(ticker: &str, qty: f64, cost: f64) -> TaxLot {
+        TaxLot {
+            account_id: Uuid::nil(),
+            ticker: ticker.to_string(),
+            acquired_date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            quantity: qty,
+            cost_per_share: cost,
+            source: "transaction".to_string(),
+            transaction_id: None,
+        }
+    }
+
+    #[test]
+    fn test_period_labels() {
+        let d = NaiveDate::from_ymd_opt(2026, 5, 14).unwrap();
+        assert_eq!(PnlPeriod::Month.label(d), "2026-05");
+        assert_eq!(PnlPeriod::Quarter.label(d), "2026-Q2");
+        assert_eq!(PnlPeriod::Year.label(d), "2026");
+        assert!(PnlPeriod::from_param(Some("week")).is_err());
+    }
+
+    #[test]
+    fn test_realized_by_period_splits_terms() {
+        let lots = vec![
+            realized(NaiveDate::from_ymd_opt(2026, 1, 10).unwrap(), 50.0, true),
+            realized(NaiveDate::from_ymd_opt(2026, 2, 10).unwrap(), -20.0, false),
+            realized(NaiveDate::from_ymd_opt(2025, 12, 10).unwrap(), 5.0, false),
+        ];
+        let periods = realized_by_period(&lots, &[], PnlPeriod::Quarter);
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[0].period, "2025-Q4");
+        assert_eq!(periods[1].lots_closed, 2);
+        assert!((periods[1].long_term_gain - 50.0).abs() < 1e-9);
+        assert!((periods[1].short_term_gain + 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_position_pnl_attributes_day_change() {
+        let lots = vec![lot("AAPL", 10.0, 100.0), lot("AAPL", 10.0, 120.0), lot("MSFT", 5.0, 300.0)];
+        let current = HashMap::from([("AAPL".to_string(), 110.0), ("MSFT".to_string(), 310.0)]);
+        let previous = HashMap::from([("AAPL".to_string(), 108.0), ("MSFT".to_string(), 314.0)]);
+
+        let positions = position_pnl(&lots, &[], &current, &previous);
+        let aapl = &positions[0];
+        assert_eq!(aapl.lots.len(), 2);
+        assert!((aapl.unrealized_gain - 0.0).abs() < 1e-9);
+        assert!((aapl.lots[0].unrealized_gain - 100.0).abs() < 1e-9);
+        assert_eq!(aapl.day_change, Some(40.0));
+        assert_eq!(positions[1].day_change, Some(-20.0));
+        // Total day change is +20: AAPL contributed 200%, MSFT -100%
+        assert!((aapl.day_change_contribution_pct.unwrap() - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_position_pnl_reports_closed_positions() {
+        let sold = realized(NaiveDate::from_ymd_opt(2026, 1, 10).unwrap(), 25.0, false);
+        let positions = position_pnl(&[], &[sold], &HashMap::new(), &HashMap::new());
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].quantity, 0.0);
+        assert_eq!(positions[0].realized_gain, 25.0);
+    }
+}
@@ -0,0 +1,66 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{auth_queries, portfolio_member_queries};
+use crate::errors::AppError;
+use crate::models::{AddPortfolioMember, PortfolioMember, Role};
+
+pub async fn list(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<PortfolioMember>, AppError> {
+    Ok(portfolio_member_queries::fetch_all(pool, portfolio_id).await?)
+}
+
+/// Share a portfolio with an existing user. Sharing again with the same user
+/// changes their role.
+pub async fn add(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    owner_id: Uuid,
+    invited_by: Uuid,
+    input: AddPortfolioMember,
+) -> Result<PortfolioMember, AppError> {
+    let email = input.email.trim().to_lowercase();
+    if email.is_empty() {
+        return Err(AppError::Validation("Email is required".into()));
+    }
+    let user = auth_queries::get_user_by_email(pool, &email)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No user is registered with {}", email)))?;
+    if user.id == owner_id {
+        return Err(AppError::Validation("The owner already has full access to the portfolio".into()));
+    }
+
+    portfolio_member_queries::upsert(pool, portfolio_id, user.id, input.role, invited_by).await?;
+    fetch_member(pool, portfolio_id, user.id).await
+}
+
+pub async fn update_role(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    owner_id: Uuid,
+    user_id: Uuid,
+    role: Role,
+) -> Result<PortfolioMember, AppError> {
+    if user_id == owner_id {
+        return Err(AppError::Validation("The owner's role cannot be changed".into()));
+    }
+    if !portfolio_member_queries::update_role(pool, portfolio_id, user_id, role).await? {
+        return Err(AppError::NotFound(format!("User {} is not a member of the portfolio", user_id)));
+    }
+    fetch_member(pool, portfolio_id, user_id).await
+}
+
+pub async fn remove(pool: &PgPool, portfolio_id: Uuid, owner_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+    if user_id == owner_id {
+        return Err(AppError::Validation("The owner cannot be removed from the portfolio".into()));
+    }
+    if !portfolio_member_queries::delete(pool, portfolio_id, user_id).await? {
+        return Err(AppError::NotFound(format!("User {} is not a member of the portfolio", user_id)));
+    }
+    Ok(())
+}
+
+async fn fetch_member(pool: &PgPool, portfolio_id: Uuid, user_id: Uuid) -> Result<PortfolioMember, AppError> {
+    portfolio_member_queries::fetch_one(pool, portfolio_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("User {} is not a member of the portfolio", user_id)))
+}
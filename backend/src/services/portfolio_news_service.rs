@@ -0,0 +1,328 @@
+//! Headline feed for a portfolio's holdings.
+//!
+//! Collects recent articles for every held ticker and scores each one for
+//! relevance: how directly it names the ticker, how recent it is and how large the
+//! position is. Sentiment labels come from a small finance lexicon, so labelling a
+//! page of headlines costs no LLM calls. The scored feed is cached per lookback
+//! window so paging does not re-query the news provider.
+
+use std::collections::HashMap;
+
+use bigdecimal::ToPrimitive;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::db::{holding_snapshot_queries, news_feed_queries};
+use crate::errors::AppError;
+use crate::models::{NewsArticle, PortfolioNewsFeed, PortfolioNewsFeedQuery, PortfolioNewsItem, Sentiment};
+use crate::services::news_service::NewsService;
+
+pub const DEFAULT_DAYS: i32 = 7;
+const DEFAULT_PAGE_SIZE: usize = 20;
+const MAX_PAGE_SIZE: usize = 100;
+const CACHE_HOURS: i64 = 1;
+
+const POSITIVE_TERMS: &[&str] = &[
+    "beat", "beats", "surge", "surges", "soar", "soars", "rally", "rallies", "gain", "gains", "upgrade",
+    "upgraded", "record", "growth", "raises", "outperform", "strong", "profit", "bullish", "jumps",
+];
+const NEGATIVE_TERMS: &[&str] = &[
+    "miss", "misses", "plunge", "plunges", "fall", "falls", "drop", "drops", "downgrade", "downgraded",
+    "lawsuit", "probe", "recall", "loss", "losses", "cuts", "weak", "bearish", "slump", "layoffs",
+];
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+}
+
+/// Whether `text` names the ticker as a standalone word (exchange suffix ignored)
+fn mentions(text: &str, ticker: &str) -> bool {
+    let symbol = ticker.split('.').next().unwrap_or(ticker).to_lowercase();
+    words(text).any(|w| w == symbol)
+}
+
+/// Relevance in 0-1: half from naming the ticker (title over snippet), 30% from
+/// recency within the lookback window, 20% from position weight relative to the
+/// largest position
+pub fn relevance_score(
+    article: &NewsArticle,
+    ticker: &str,
+    weight: f64,
+    max_weight: f64,
+    now: DateTime<Utc>,
+    days: i32,
+) -> f64 {
+    let mention = if mentions(&article.title, ticker) {
+        1.0
+    } else if mentions(&article.snippet, ticker) {
+        0.6
+    } else {
+        0.3
+    };
+    let age_days = (now - article.published_at).num_minutes() as f64 / (24.0 * 60.0);
+    let recency = (1.0 - age_days / days.max(1) as f64).clamp(0.0, 1.0);
+    let size = if max_weight > 0.0 { (weight / max_weight).clamp(0.0, 1.0) } else { 0.0 };
+    0.5 * mention + 0.3 * recency + 0.2 * size
+}
+
+/// Lexicon-based sentiment of a headline and its snippet
+pub fn label_sentiment(article: &NewsArticle) -> Sentiment {
+    let (mut positive, mut negative) = (0, 0);
+    for word in words(&article.title).chain(words(&article.snippet)) {
+        if POSITIVE_TERMS.contains(&word.as_str()) {
+            positive += 1;
+        } else if NEGATIVE_TERMS.contains(&word.as_str()) {
+            negative += 1;
+        }
+    }
+    match positive.cmp(&negative) {
+        std::cmp::Ordering::Greater => Sentiment::Positive,
+        std::cmp::Ordering::Less => Sentiment::Negative,
+        std::cmp::Ordering::Equal => Sentiment::Neutral,
+    }
+}
+
+/// Score and label articles per (ticker, weight), keep the best-scoring copy of
+/// articles returned for several tickers, and order by relevance then recency
+pub fn build_items(
+    articles: Vec<(String, f64, Vec<NewsArticle>)>,
+    now: DateTime<Utc>,
+    days: i32,
+) -> Vec<PortfolioNewsItem> {
+    let max_weight = articles.iter().map(|(_, w, _)| *w).fold(0.0, f64::max);
+    let mut by_url: HashMap<String, PortfolioNewsItem> = HashMap::new();
+    for (ticker, weight, ticker_articles) in articles {
+        for article in ticker_articles {
+            let item = PortfolioNewsItem {
+                relevance_score: relevance_score(&article, &ticker, weight, max_weight, now, days),
+                sentiment: Some(label_sentiment(&article)),
+                ticker: ticker.clone(),
+                position_weight: weight,
+                title: article.title,
+                url: article.url,
+                source: article.source,
+                published_at: article.published_at,
+                snippet: article.snippet,
+            };
+            match by_url.get(&item.url) {
+                Some(existing) if existing.relevance_score >= item.relevance_score => {}
+                _ => {
+                    by_url.insert(item.url.clone(), item);
+                }
+            }
+        }
+    }
+    let mut items: Vec<PortfolioNewsItem> = by_url.into_values().collect();
+    items.sort_by(|a, b| {
+        b.relevance_score
+            .total_cmp(&a.relevance_score)
+            .then(b.published_at.cmp(&a.published_at))
+    });
+    items
+}
+
+/// Fetch and score headlines for every held ticker
+async fn collect_items(
+    pool: &PgPool,
+    news_service: &NewsService,
+    portfolio_id: Uuid,
+    days: i32,
+) -> Result<Vec<PortfolioNewsItem>, AppError> {
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let mut values: HashMap<String, f64> = HashMap::new();
+    for holding in &holdings {
+        *values.entry(holding.ticker.clone()).or_insert(0.0) += holding.market_value.to_f64().unwrap_or(0.0);
+    }
+    let total: f64 = values.values().sum();
+
+    let mut tickers: Vec<(String, f64)> = values.into_iter().collect();
+    tickers.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut articles = Vec::new();
+    for (ticker, value) in tickers {
+        match news_service.fetch_ticker_news(&ticker, days).await {
+            Ok(found) => {
+                let weight = if total > 0.0 { value / total } else { 0.0 };
+                articles.push((ticker, weight, found));
+            }
+            Err(e) => warn!("Failed to fetch news for {}: {}", ticker, e),
+        }
+    }
+    Ok(build_items(articles, Utc::now(), days))
+}
+
+/// Scored feed items for a portfolio, from the cache unless stale or `force` is set
+pub async fn feed_items(
+    pool: &PgPool,
+    news_service: &NewsService,
+    portfolio_id: Uuid,
+    days: i32,
+    force: bool,
+) -> Result<(Vec<PortfolioNewsItem>, DateTime<Utc>), AppError> {
+    if !force {
+        if let Some((items, fetched_at)) = news_feed_queries::fetch_fresh(pool, portfolio_id, days).await? {
+            match serde_json::from_value(items) {
+                Ok(items) => return Ok((items, fetched_at)),
+                Err(e) => warn!("Discarding unreadable news feed cache for portfolio {}: {}", portfolio_id, e),
+            }
+        }
+    }
+
+    if !news_service.is_enabled() {
+        return Err(AppError::ServiceUnavailable("News service is not enabled".to_string()));
+    }
+
+    let items = collect_items(pool, news_service, portfolio_id, days).await?;
+    let fetched_at = Utc::now();
+    info!("Collected {} headlines for portfolio {} (days={})", items.len(), portfolio_id, days);
+
+    let cached = serde_json::to_value(&items)
+        .map_err(|e| AppError::External(format!("Failed to serialize news feed: {}", e)))?;
+    if let Err(e) =
+        news_feed_queries::upsert(pool, portfolio_id, days, cached, fetched_at, fetched_at + Duration::hours(CACHE_HOURS)).await
+    {
+        warn!("Failed to cache news feed for portfolio {}: {}", portfolio_id, e);
+    }
+    Ok((items, fetched_at))
+}
+
+/// One page of `items` (pages start at 1), with the total and whether more follow
+pub fn paginate(items: Vec<PortfolioNewsItem>, page: usize, page_size: usize) -> (Vec<PortfolioNewsItem>, usize, bool) {
+    let total = items.len();
+    let start = (page - 1).saturating_mul(page_size);
+    let page_items: Vec<PortfolioNewsItem> = items.into_iter().skip(start).take(page_size).collect();
+    let has_more = start + page_items.len() < total;
+    (page_items, total, has_more)
+}
+
+pub async fn get_feed(
+    pool: &PgPool,
+    news_service: &NewsService,
+    portfolio_id: Uuid,
+    query: &PortfolioNewsFeedQuery,
+) -> Result<PortfolioNewsFeed, AppError> {
+    let days = query.days.unwrap_or(DEFAULT_DAYS);
+    if !(1..=90).contains(&days) {
+        return Err(AppError::Validation("days must be between 1 and 90".to_string()));
+    }
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    let (mut items, fetched_at) = feed_items(pool, news_service, portfolio_id, days, query.force.unwrap_or(false)).await?;
+    if let Some(ticker) = &query.ticker {
+        items.retain(|item| item.ticker.eq_ignore_ascii_case(ticker));
+    }
+    if !query.sentiment.unwrap_or(false) {
+        for item in &mut items {
+            item.sentiment = None;
+        }
+    }
+
+    let (items, total, has_more) = paginate(items, page, page_size);
+    Ok(PortfolioNewsFeed {
+        portfolio_id,
+        days,
+        page,
+        page_size,
+        total,
+        has_more,
+        items,
+        fetched_at,
+    })
+}
+
+/// The most relevant headlines as one-line summaries for the narrative prompt
+pub fn narrative_headlines(items: &[PortfolioNewsItem], limit: usize) -> Vec<String> {
+    items
+        .iter()
+        .take(limit)
+        .map(|item| {
+            let sentiment = item.sentiment.map(|s| format!(", {}", s)).unwrap_or_default();
+            format!(
+                "{}: {} ({}, {}{})",
+                item.ticker,
+                item.title,
+                item.source,
+                item.published_at.format("%Y-%m-%d"),
+                sentiment
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(title: &str, snippet: &str, url: &str, hours_ago: i64, now: DateTime<Utc>) -> NewsArticle {
+        NewsArticle {
+            title: title.to_string(),
+            url: url.to_string(),
+            source: "Wire".to_string(),
+            published_at: now - Duration::hours(hours_ago),
+            snippet: snippet.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_relevance_prefers_title_mentions_recency_and_weight() {
+        let now = Utc::now();
+        let in_title = article("AAPL beats estimates", "", "a", 2, now);
+        let in_snippet = article("Tech earnings roundup", "Apple (AAPL) reported", "b", 2, now);
+        let elsewhere = article("Markets wrap", "Stocks rose", "c", 2, now);
+
+        let title_score = relevance_score(&in_title, "AAPL", 0.5, 0.5, now, 7);
+        let snippet_score = relevance_score(&in_snippet, "AAPL", 0.5, 0.5, now, 7);
+        let other_score = relevance_score(&elsewhere, "AAPL", 0.5, 0.5, now, 7);
+        assert!(title_score > snippet_score && snippet_score > other_score);
+
+        let old = article("AAPL beats estimates", "", "d", 6 * 24, now);
+        assert!(relevance_score(&old, "AAPL", 0.5, 0.5, now, 7) < title_score);
+        assert!(relevance_score(&in_title, "AAPL", 0.1, 0.5, now, 7) < title_score);
+
+        // Exchange suffixes are ignored and partial words do not count
+        assert!(mentions("Enbridge (ENB) raises dividend", "ENB.TO"));
+        assert!(!mentions("ENBRIDGE update", "ENB"));
+    }
+
+    #[test]
+    fn test_label_sentiment() {
+        let now = Utc::now();
+        assert_eq!(label_sentiment(&article("MSFT beats, shares surge", "", "a", 1, now)), Sentiment::Positive);
+        assert_eq!(label_sentiment(&article("MSFT faces probe", "Shares fall", "b", 1, now)), Sentiment::Negative);
+        assert_eq!(label_sentiment(&article("MSFT annual meeting", "", "c", 1, now)), Sentiment::Neutral);
+    }
+
+    #[test]
+    fn test_build_items_dedupes_and_paginates() {
+        let now = Utc::now();
+        let shared = article("AAPL and MSFT partner", "", "shared", 1, now);
+        let items = build_items(
+            vec![
+                ("AAPL".to_string(), 0.6, vec![shared.clone(), article("Apple event", "", "a", 30, now)]),
+                ("MSFT".to_string(), 0.4, vec![shared, article("MSFT cloud growth", "", "m", 10, now)]),
+            ],
+            now,
+            7,
+        );
+        assert_eq!(items.len(), 3);
+        let shared_item = items.iter().find(|i| i.url == "shared").unwrap();
+        assert_eq!(shared_item.ticker, "AAPL");
+        assert!(items.windows(2).all(|w| w[0].relevance_score >= w[1].relevance_score));
+
+        let (page, total, has_more) = paginate(items.clone(), 1, 2);
+        assert_eq!((page.len(), total, has_more), (2, 3, true));
+        let lines = narrative_headlines(&page, 1);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("AAPL: AAPL and MSFT partner (Wire, "));
+        assert!(lines[0].ends_with(", neutral)"));
+        let (page, _, has_more) = paginate(items.clone(), 2, 2);
+        assert_eq!((page.len(), has_more), (1, false));
+        let (page, _, has_more) = paginate(items, 5, 2);
+        assert!(page.is_empty() && !has_more);
+    }
+}
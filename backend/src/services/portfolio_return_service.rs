@@ -0,0 +1,379 @@
+//! Portfolio daily return series.
+//!
+//! Portfolio-level volatility, drawdown, Sharpe and VaR are computed from the
+//! portfolio's own daily returns rather than averaged over its positions, which
+//! ignores diversification and cash. The series is rebuilt from each account's
+//! holdings snapshots and stored prices: each day's return is that of the
+//! holdings in effect at the previous close, so weights drift with prices
+//! between imports and change at each snapshot boundary. Cash earns the
+//! risk-free rate.
+//!
+//! Days before an account's first snapshot use that snapshot's holdings, so a
+//! newly imported portfolio still gets a full window of history. Holdings with
+//! no stored price are left out of both the value and the return.
+//!
+//! Portfolio beta, alpha and R² come from regressing the series on the
+//! benchmark's daily returns over the same days.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use bigdecimal::ToPrimitive;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{holding_snapshot_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{HoldingSnapshot, PricePoint};
+use crate::services::risk_service;
+
+/// Fewer days of returns paired with the benchmark than this give no beta
+const MIN_REGRESSION_DAYS: usize = 20;
+
+/// Daily returns of a portfolio, oldest first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PortfolioReturnSeries {
+    pub dates: Vec<NaiveDate>,
+    pub returns: Vec<f64>,
+}
+
+/// Risk metrics of a portfolio return series. All are `None` when there is too
+/// little history.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PortfolioReturnMetrics {
+    /// Annualized volatility, as a percentage
+    pub volatility: Option<f64>,
+    /// Maximum drawdown, as a (negative) percentage
+    pub max_drawdown: Option<f64>,
+    pub sharpe: Option<f64>,
+    pub sortino: Option<f64>,
+    /// Annualized downside deviation, as a percentage
+    pub downside_deviation: Option<f64>,
+    pub var_95: Option<f64>,
+    pub var_99: Option<f64>,
+    pub expected_shortfall_95: Option<f64>,
+    pub expected_shortfall_99: Option<f64>,
+    /// Beta from regressing the returns on the benchmark's
+    pub beta: Option<f64>,
+    /// Annualized alpha of that regression, as a percentage
+    pub alpha: Option<f64>,
+    pub r_squared: Option<f64>,
+}
+
+/// A holding as of one snapshot of an account
+#[derive(Debug, Clone)]
+struct Holding {
+    ticker: String,
+    quantity: f64,
+    market_value: f64,
+}
+
+/// Holdings by account, then by snapshot date
+type Books = HashMap<Uuid, BTreeMap<NaiveDate, Vec<Holding>>>;
+
+/// Rebuild a portfolio's daily returns over its last `days` trading days
+pub async fn build_series(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    days: i64,
+    risk_free_rate: f64,
+) -> Result<PortfolioReturnSeries, AppError> {
+    // Calendar days comfortably covering `days` trading days
+    let since = crate::services::clock::today() - chrono::Duration::days(days * 7 / 5 + 7);
+    let snapshots = holding_snapshot_queries::fetch_portfolio_holdings_since(pool, portfolio_id, since).await?;
+    let books = books_from_snapshots(&snapshots);
+
+    let tickers: Vec<String> = snapshots
+        .iter()
+        .filter(|h| !h.ticker.is_empty())
+        .map(|h| h.ticker.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let prices: HashMap<String, BTreeMap<NaiveDate, f64>> = price_queries::fetch_window_batch(pool, &tickers, days)
+        .await?
+        .into_iter()
+        .map(|(ticker, points)| (ticker, closes_by_date(&points)))
+        .collect();
+
+    Ok(daily_returns(&books, &prices, days, risk_free_rate))
+}
+
+/// Risk metrics of a portfolio's daily returns over its last `days` trading
+/// days, with beta, alpha and R² against `benchmark`
+pub async fn compute_metrics(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    days: i64,
+    benchmark: &str,
+    risk_free_rate: f64,
+) -> Result<PortfolioReturnMetrics, AppError> {
+    let series = build_series(pool, portfolio_id, days, risk_free_rate).await?;
+    let mut metrics = metrics(&series.returns, risk_free_rate);
+
+    let benchmark_closes = price_queries::fetch_window_batch(pool, &[benchmark.to_string()], days + 1)
+        .await?
+        .remove(benchmark)
+        .map(|points| closes_by_date(&points))
+        .unwrap_or_default();
+    let (returns, benchmark_returns) = pair_with_benchmark(&series, &benchmark_closes);
+    if returns.len() >= MIN_REGRESSION_DAYS {
+        if let Some((beta, alpha, r_squared)) = risk_service::beta_regression_from_returns(&returns, &benchmark_returns) {
+            metrics.beta = Some(beta);
+            metrics.alpha = Some(alpha);
+            metrics.r_squared = Some(r_squared);
+        }
+    }
+    Ok(metrics)
+}
+
+/// Risk metrics of a series of daily returns
+pub fn metrics(returns: &[f64], risk_free_rate: f64) -> PortfolioReturnMetrics {
+    let Some((volatility, max_drawdown)) = risk_service::vol_drawdown_from_returns(returns) else {
+        return PortfolioReturnMetrics::default();
+    };
+    let (var_95, var_99) = risk_service::var_from_returns(returns);
+    let (expected_shortfall_95, expected_shortfall_99) = risk_service::expected_shortfall_from_returns(returns);
+    PortfolioReturnMetrics {
+        volatility: Some(volatility),
+        max_drawdown: Some(max_drawdown),
+        sharpe: risk_service::sharpe_from_returns(returns, risk_free_rate),
+        sortino: risk_service::sortino_from_returns(returns, risk_free_rate),
+        downside_deviation: risk_service::downside_deviation_from_returns(returns, risk_free_rate),
+        var_95,
+        var_99,
+        expected_shortfall_95,
+        expected_shortfall_99,
+        ..Default::default()
+    }
+}
+
+fn books_from_snapshots(snapshots: &[HoldingSnapshot]) -> Books {
+    let mut books: Books = HashMap::new();
+    for snapshot in snapshots {
+        books
+            .entry(snapshot.account_id)
+            .or_default()
+            .entry(snapshot.snapshot_date)
+            .or_default()
+            .push(Holding {
+                ticker: snapshot.ticker.clone(),
+                quantity: snapshot.quantity.to_f64().unwrap_or(0.0),
+                market_value: snapshot.market_value.to_f64().unwrap_or(0.0),
+            });
+    }
+    books
+}
+
+/// Positive closes by date
+fn closes_by_date(points: &[PricePoint]) -> BTreeMap<NaiveDate, f64> {
+    points
+        .iter()
+        .filter_map(|p| p.close_price.to_f64().filter(|c| *c > 0.0).map(|c| (p.date, c)))
+        .collect()
+}
+
+/// The series' returns alongside the benchmark's over the same days. The first
+/// day is dropped, as its previous date isn't in the series.
+fn pair_with_benchmark(series: &PortfolioReturnSeries, closes: &BTreeMap<NaiveDate, f64>) -> (Vec<f64>, Vec<f64>) {
+    let mut returns = Vec::new();
+    let mut benchmark_returns = Vec::new();
+    for (i, pair) in series.dates.windows(2).enumerate() {
+        if let (Some(start), Some(end)) = (close_on_or_before(closes, pair[0]), close_on_or_before(closes, pair[1])) {
+            returns.push(series.returns[i + 1]);
+            benchmark_returns.push(end / start - 1.0);
+        }
+    }
+    (returns, benchmark_returns)
+}
+
+/// Last close on or before `date`
+fn close_on_or_before(closes: &BTreeMap<NaiveDate, f64>, date: NaiveDate) -> Option<f64> {
+    closes.range(..=date).next_back().map(|(_, close)| *close)
+}
+
+/// Daily returns over the last `days` price dates. Each day's return is the
+/// change in value of the holdings in effect at the previous close, over their
+/// value then.
+fn daily_returns(
+    books: &Books,
+    prices: &HashMap<String, BTreeMap<NaiveDate, f64>>,
+    days: i64,
+    risk_free_rate: f64,
+) -> PortfolioReturnSeries {
+    let all_dates: BTreeSet<NaiveDate> = prices.values().flat_map(|closes| closes.keys().copied()).collect();
+    let skip = all_dates.len().saturating_sub(days.max(0) as usize);
+    let dates: Vec<NaiveDate> = all_dates.into_iter().skip(skip).collect();
+
+    let mut series = PortfolioReturnSeries::default();
+    for pair in dates.windows(2) {
+        let (prev, date) = (pair[0], pair[1]);
+        let mut start_value = 0.0;
+        let mut end_value = 0.0;
+        let mut cash = 0.0;
+
+        for book in books.values() {
+            // Holdings at the previous close, or the first import for earlier days
+            let Some(holdings) = book.range(..=prev).next_back().or_else(|| book.iter().next()).map(|(_, h)| h)
+            else {
+                continue;
+            };
+            for holding in holdings {
+                if holding.ticker.is_empty() {
+                    cash += holding.market_value;
+                    continue;
+                }
+                let Some(closes) = prices.get(&holding.ticker) else {
+                    continue;
+                };
+                if let (Some(start), Some(end)) = (close_on_or_before(closes, prev), close_on_or_before(closes, date)) {
+                    start_value += holding.quantity * start;
+                    end_value += holding.quantity * end;
+                }
+            }
+        }
+
+        let total = start_value + cash;
+        if total <= 0.0 {
+            continue;
+        }
+        let cash_income = cash * risk_free_rate / 252.0;
+        series.dates.push(date);
+        series.returns.push((end_value - start_value + cash_income) / total);
+    }
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn closes(points: &[(u32, f64)]) -> BTreeMap<NaiveDate, f64> {
+        points.iter().map(|(day, close)| (date(*day), *close)).collect()
+    }
+
+    fn holding(ticker: &str, quantity: f64, market_value: f64) -> Holding {
+        Holding { ticker: ticker.to_string(), quantity, market_value }
+    }
+
+    #[test]
+    fn test_daily_returns_use_holdings_at_previous_close() {
+        let account = Uuid::new_v4();
+        let mut book = BTreeMap::new();
+        book.insert(date(1), vec![holding("AAA", 10.0, 1000.0)]);
+        // Sold half and moved it to cash at the close of the 3rd
+        book.insert(date(3), vec![holding("AAA", 5.0, 500.0), holding("", 0.0, 550.0)]);
+        let books: Books = HashMap::from([(account, book)]);
+        let prices = HashMap::from([(
+            "AAA".to_string(),
+            closes(&[(1, 100.0), (2, 110.0), (3, 110.0), (4, 121.0)]),
+        )]);
+
+        let series = daily_returns(&books, &prices, 90, 0.0);
+        assert_eq!(series.dates, vec![date(2), date(3), date(4)]);
+        assert!((series.returns[0] - 0.10).abs() < 1e-12);
+        assert!(series.returns[1].abs() < 1e-12);
+        // Half in AAA (+10%), half in cash
+        assert!((series.returns[2] - 0.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_daily_returns_backfill_and_carry_prices_forward() {
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let books: Books = HashMap::from([
+            // First imported after the window starts: its holdings are used for earlier days
+            (first, BTreeMap::from([(date(3), vec![holding("AAA", 1.0, 100.0)])])),
+            (second, BTreeMap::from([(date(1), vec![holding("BBB", 2.0, 100.0), holding("ZZZ", 5.0, 50.0)])])),
+        ]);
+        let prices = HashMap::from([
+            ("AAA".to_string(), closes(&[(1, 100.0), (2, 110.0), (3, 121.0)])),
+            // No price on the 2nd: unchanged that day
+            ("BBB".to_string(), closes(&[(1, 50.0), (3, 55.0)])),
+        ]);
+
+        let series = daily_returns(&books, &prices, 90, 0.0);
+        // Unpriced ZZZ is left out; AAA +10% on 100 of 200
+        assert!((series.returns[0] - 0.05).abs() < 1e-12);
+        // AAA +10% on 110, BBB +10% on 100
+        assert!((series.returns[1] - (11.0 + 10.0) / 210.0).abs() < 1e-12);
+
+        // Only the last `days` dates
+        assert_eq!(daily_returns(&books, &prices, 2, 0.0).dates, vec![date(3)]);
+    }
+
+    #[test]
+    fn test_cash_earns_risk_free_rate() {
+        let books: Books = HashMap::from([(
+            Uuid::new_v4(),
+            BTreeMap::from([(date(1), vec![holding("AAA", 1.0, 100.0), holding("", 0.0, 100.0)])]),
+        )]);
+        let prices = HashMap::from([("AAA".to_string(), closes(&[(1, 100.0), (2, 100.0)]))]);
+        let series = daily_returns(&books, &prices, 90, 0.0504);
+        assert!((series.returns[0] - 0.5 * 0.0504 / 252.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_metrics_reflect_diversification() {
+        // Two positions swinging in opposite directions around the same drift
+        let (mut pa, mut pb) = (100.0, 100.0);
+        let mut a = vec![(1, pa)];
+        let mut b = vec![(1, pb)];
+        for day in 2..=21 {
+            let swing = if day % 2 == 0 { 0.02 } else { -0.02 };
+            pa *= 1.001 + swing;
+            pb *= 1.001 - swing;
+            a.push((day, pa));
+            b.push((day, pb));
+        }
+        let single: Books = HashMap::from([(Uuid::new_v4(), BTreeMap::from([(date(1), vec![holding("A", 1.0, 100.0)])]))]);
+        let pair: Books = HashMap::from([(
+            Uuid::new_v4(),
+            BTreeMap::from([(date(1), vec![holding("A", 1.0, 100.0), holding("B", 1.0, 100.0)])]),
+        )]);
+        let prices = HashMap::from([("A".to_string(), closes(&a)), ("B".to_string(), closes(&b))]);
+
+        let alone = metrics(&daily_returns(&single, &prices, 90, 0.0).returns, 0.0);
+        let together = metrics(&daily_returns(&pair, &prices, 90, 0.0).returns, 0.0);
+        assert!(together.volatility.unwrap() < alone.volatility.unwrap() / 5.0);
+        assert!(together.max_drawdown.unwrap() > alone.max_drawdown.unwrap());
+        assert!(together.var_95.unwrap() > alone.var_95.unwrap());
+        assert!(together.sharpe.unwrap() > alone.sharpe.unwrap());
+
+        assert_eq!(metrics(&[0.01], 0.0), PortfolioReturnMetrics::default());
+    }
+
+    #[test]
+    fn test_pair_with_benchmark_for_regression() {
+        let mut close = 100.0;
+        let mut points = vec![(1, close)];
+        for day in 2..=25 {
+            close *= if day % 3 == 0 { 0.98 } else { 1.015 };
+            points.push((day, close));
+        }
+        // Half in a fund tracking the benchmark, half in cash
+        let books: Books = HashMap::from([(
+            Uuid::new_v4(),
+            BTreeMap::from([(date(1), vec![holding("FUND", 1.0, 100.0), holding("", 0.0, 100.0)])]),
+        )]);
+        let prices = HashMap::from([("FUND".to_string(), closes(&points))]);
+        let series = daily_returns(&books, &prices, 90, 0.0);
+
+        let mut benchmark = closes(&points);
+        // A missing benchmark close carries the previous one forward
+        benchmark.remove(&date(10));
+        let (returns, benchmark_returns) = pair_with_benchmark(&series, &benchmark);
+        assert_eq!(returns.len(), series.returns.len() - 1);
+        assert_eq!(returns[0], series.returns[1]);
+        assert_eq!(benchmark_returns[7], 0.0);
+
+        let (beta, _, r_squared) =
+            risk_service::beta_regression_from_returns(&returns[9..], &benchmark_returns[9..]).unwrap();
+        assert!((0.4..0.6).contains(&beta), "beta {}", beta);
+        assert!(r_squared > 0.95);
+    }
+}
@@ -2,7 +2,7 @@ use sqlx::PgPool;
 use uuid::Uuid;
 use crate::db;
 use crate::errors::AppError;
-use crate::models::{CreatePortfolio, Portfolio, UpdatePortfolio};
+use crate::models::{ClonePortfolio, ClonePortfolioResponse, CreatePortfolio, Portfolio, UpdatePortfolio};
 
 pub async fn create(
     pool: &PgPool,
@@ -32,11 +32,40 @@ pub async fn update(
     Ok(portfolio)
 }
 
-pub async fn fetch_all(pool: &PgPool, user_id: Uuid) -> Result<Vec<Portfolio>, AppError> {
-    let portfolios = db::portfolio_queries::fetch_all(pool, user_id).await?;
+pub async fn fetch_all(pool: &PgPool, user_id: Uuid, include_archived: bool) -> Result<Vec<Portfolio>, AppError> {
+    let portfolios = db::portfolio_queries::fetch_all(pool, user_id, include_archived).await?;
     Ok(portfolios)
 }
 
+/// Copy a portfolio (accounts, latest holdings, positions, thresholds) into a new
+/// portfolio owned by the same user, for what-if experimentation.
+pub async fn clone(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: Uuid,
+    input: ClonePortfolio,
+) -> Result<ClonePortfolioResponse, AppError> {
+    let source = fetch_one(pool, id, user_id).await?;
+    let name = match input.name {
+        Some(name) if name.trim().is_empty() => {
+            return Err(AppError::Validation("Portfolio name cannot be empty".into()));
+        }
+        Some(name) => name,
+        None => format!("{} (copy)", source.name),
+    };
+
+    let target = Portfolio::new(name, user_id);
+    let (portfolio, holdings_copied) = db::portfolio_queries::clone_contents(pool, source.id, target).await?;
+    Ok(ClonePortfolioResponse { portfolio, holdings_copied })
+}
+
+pub async fn set_archived(pool: &PgPool, id: Uuid, user_id: Uuid, archived: bool) -> Result<Portfolio, AppError> {
+    let portfolio = db::portfolio_queries::set_archived(pool, id, user_id, archived)
+        .await?
+        .ok_or(AppError::NotFound("Portfolio not found".to_string()))?;
+    Ok(portfolio)
+}
+
 pub(crate) async fn fetch_one(pool: &PgPool, id: Uuid, user_id: Uuid) -> Result<Portfolio, AppError> {
     let portfolio = db::portfolio_queries::fetch_one(pool, id, user_id)
         .await?
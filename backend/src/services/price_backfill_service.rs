@@ -0,0 +1,249 @@
+//! Backfill of long price histories.
+//!
+//! Regular refreshes fetch a year of closes, and newly added tickers start
+//! with whatever the first refresh returned, too little for rolling beta and
+//! other long-window analytics. The backfill compares each tracked ticker's
+//! stored history with the configured target and fetches the provider's full
+//! history for those falling short, a few tickers per run so the rest of the
+//! day's API budget is left for regular refreshes.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::db::{price_queries, ticker_fetch_failure_queries};
+use crate::errors::AppError;
+use crate::external::price_provider::{PriceProvider, PriceProviderError};
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::{clock, price_service, risk_memo};
+
+/// Years of history to keep for every tracked ticker
+const DEFAULT_YEARS: u32 = 5;
+const MAX_YEARS: u32 = 30;
+/// Tickers backfilled per run
+const DEFAULT_MAX_TICKERS: usize = 20;
+/// A history starting this close to the target counts as complete; the
+/// target itself may fall on a weekend or holiday
+const START_GRACE_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackfillConfig {
+    pub years: u32,
+    pub max_tickers: usize,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self { years: DEFAULT_YEARS, max_tickers: DEFAULT_MAX_TICKERS }
+    }
+}
+
+impl BackfillConfig {
+    /// `PRICE_BACKFILL_YEARS` and `PRICE_BACKFILL_MAX_TICKERS`
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        Self {
+            years: var("PRICE_BACKFILL_YEARS")
+                .and_then(|v| v.trim().parse::<u32>().ok())
+                .map(|y| y.clamp(1, MAX_YEARS))
+                .unwrap_or(defaults.years),
+            max_tickers: var("PRICE_BACKFILL_MAX_TICKERS")
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.max_tickers),
+        }
+    }
+
+    /// Earliest date the stored history should reach
+    pub fn target_start(&self, today: NaiveDate) -> NaiveDate {
+        today - Duration::days(365 * self.years as i64)
+    }
+
+    /// Days requested from the provider: the whole target window
+    pub fn fetch_days(&self) -> u32 {
+        365 * self.years + START_GRACE_DAYS as u32
+    }
+}
+
+/// How much history is stored for a ticker
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryCoverage {
+    pub ticker: String,
+    pub earliest: Option<NaiveDate>,
+    pub points: i64,
+    /// Where the provider's history begins, when a backfill found it short
+    pub history_starts: Option<NaiveDate>,
+}
+
+impl HistoryCoverage {
+    /// Whether older closes are still to be fetched
+    pub fn needs_backfill(&self, target_start: NaiveDate) -> bool {
+        let Some(earliest) = self.earliest else {
+            return true;
+        };
+        if earliest <= target_start + Duration::days(START_GRACE_DAYS) {
+            return false;
+        }
+        self.history_starts.is_none_or(|starts| earliest > starts)
+    }
+}
+
+/// Tickers short of the target, those with the least history first so newly
+/// added tickers get usable analytics soonest
+pub fn backfill_order(coverage: &[HistoryCoverage], target_start: NaiveDate) -> Vec<String> {
+    let mut pending: Vec<&HistoryCoverage> = coverage.iter().filter(|c| c.needs_backfill(target_start)).collect();
+    pending.sort_by(|a, b| a.points.cmp(&b.points).then_with(|| a.ticker.cmp(&b.ticker)));
+    pending.into_iter().map(|c| c.ticker.clone()).collect()
+}
+
+/// Result of a backfill run
+#[derive(Debug, Default, PartialEq)]
+pub struct BackfillRun {
+    pub backfilled: usize,
+    pub failed: usize,
+    /// Tickers still short of the target after this run
+    pub remaining: usize,
+}
+
+/// Stored history of every tracked ticker with a symbol the provider can price
+pub async fn coverage(pool: &PgPool) -> Result<Vec<HistoryCoverage>, AppError> {
+    let tickers: Vec<String> = price_queries::fetch_tracked_tickers(pool)
+        .await?
+        .into_iter()
+        .filter(|t| price_service::is_valid_ticker(t))
+        .collect();
+    let stored = price_queries::fetch_history_coverage(pool, &tickers).await?;
+    let starts = price_queries::fetch_history_starts(pool, &tickers).await?;
+    Ok(merge_coverage(tickers, &stored, &starts))
+}
+
+fn merge_coverage(
+    tickers: Vec<String>,
+    stored: &HashMap<String, (NaiveDate, i64)>,
+    starts: &HashMap<String, NaiveDate>,
+) -> Vec<HistoryCoverage> {
+    tickers
+        .into_iter()
+        .map(|ticker| {
+            let (earliest, points) = stored.get(&ticker).map_or((None, 0), |(e, n)| (Some(*e), *n));
+            let history_starts = starts.get(&ticker).copied();
+            HistoryCoverage { ticker, earliest, points, history_starts }
+        })
+        .collect()
+}
+
+/// Backfill up to `config.max_tickers` tickers to the target history. Stops
+/// early once the provider rate limits, leaving the rest for the next run.
+pub async fn backfill(
+    pool: &PgPool,
+    provider: &dyn PriceProvider,
+    rate_limiter: &RateLimiter,
+    config: &BackfillConfig,
+) -> Result<BackfillRun, AppError> {
+    let target_start = config.target_start(clock::today());
+    let pending = backfill_order(&coverage(pool).await?, target_start);
+    let mut run = BackfillRun { remaining: pending.len(), ..Default::default() };
+
+    for ticker in pending.iter().take(config.max_tickers) {
+        if !ticker_fetch_failure_queries::should_retry_ticker(pool, ticker).await? {
+            continue;
+        }
+
+        let result = {
+            let _guard = rate_limiter.acquire().await;
+            provider.fetch_daily_history(ticker, config.fetch_days()).await
+        };
+        let points = match result {
+            Ok(points) if !points.is_empty() => points,
+            Ok(_) | Err(PriceProviderError::NotFound) => {
+                warn!("No price history available to backfill {}", ticker);
+                run.failed += 1;
+                continue;
+            }
+            Err(PriceProviderError::RateLimited) => {
+                rate_limiter.record_rejection();
+                warn!("Rate limited while backfilling {}, resuming next run", ticker);
+                break;
+            }
+            Err(e) => {
+                warn!("Failed to backfill {}: {}", ticker, e);
+                run.failed += 1;
+                continue;
+            }
+        };
+
+        price_queries::upsert_external_points(pool, ticker, &points).await?;
+        // Closes older than the stored splits and dividends need adjusting too
+        price_service::apply_corporate_actions(pool, ticker).await?;
+        risk_memo::invalidate(ticker);
+
+        let earliest = points.iter().map(|p| p.date).min().unwrap_or(target_start);
+        if earliest > target_start + Duration::days(START_GRACE_DAYS) {
+            price_queries::upsert_history_start(pool, ticker, earliest).await?;
+        }
+        info!("Backfilled {} closes for {} back to {}", points.len(), ticker, earliest);
+        run.backfilled += 1;
+        run.remaining -= 1;
+    }
+
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn covered(ticker: &str, earliest: Option<NaiveDate>, points: i64, history_starts: Option<NaiveDate>) -> HistoryCoverage {
+        HistoryCoverage { ticker: ticker.to_string(), earliest, points, history_starts }
+    }
+
+    #[test]
+    fn test_config_from_vars() {
+        let config = BackfillConfig::from_vars(|key| match key {
+            "PRICE_BACKFILL_YEARS" => Some("50".to_string()),
+            "PRICE_BACKFILL_MAX_TICKERS" => Some("0".to_string()),
+            _ => None,
+        });
+        assert_eq!(config, BackfillConfig { years: MAX_YEARS, max_tickers: DEFAULT_MAX_TICKERS });
+        assert_eq!(BackfillConfig::from_vars(|_| None), BackfillConfig::default());
+        assert_eq!(BackfillConfig::default().target_start(date(2026, 3, 4)), date(2021, 3, 5));
+    }
+
+    #[test]
+    fn test_needs_backfill() {
+        let target = date(2021, 3, 5);
+        assert!(covered("NEW", None, 0, None).needs_backfill(target));
+        assert!(covered("AAPL", Some(date(2025, 3, 4)), 250, None).needs_backfill(target));
+        // Starts on the first trading day after the target
+        assert!(!covered("AAPL", Some(date(2021, 3, 8)), 1_250, None).needs_backfill(target));
+        // Listed in 2023: the provider has nothing older
+        assert!(!covered("IPO", Some(date(2023, 6, 1)), 700, Some(date(2023, 6, 1))).needs_backfill(target));
+        // Older closes have since appeared in a refresh
+        assert!(covered("IPO", Some(date(2024, 1, 2)), 500, Some(date(2023, 6, 1))).needs_backfill(target));
+    }
+
+    #[test]
+    fn test_backfill_order_puts_shortest_history_first() {
+        let target = date(2021, 3, 5);
+        let coverage = merge_coverage(
+            vec!["MSFT".to_string(), "AAPL".to_string(), "NEW".to_string(), "SPY".to_string()],
+            &HashMap::from([
+                ("MSFT".to_string(), (date(2025, 3, 4), 250)),
+                ("AAPL".to_string(), (date(2025, 9, 2), 125)),
+                ("SPY".to_string(), (date(2020, 1, 2), 1_550)),
+            ]),
+            &HashMap::new(),
+        );
+        assert_eq!(backfill_order(&coverage, target), vec!["NEW", "AAPL", "MSFT"]);
+    }
+}
@@ -1,14 +1,18 @@
 
-use bigdecimal::BigDecimal;
+use bigdecimal::{BigDecimal, ToPrimitive};
 use sqlx::PgPool;
 use tracing::{error, warn, info};
 use tokio::time::{sleep as async_sleep, Duration};
 use crate::db;
 use crate::errors::AppError;
-use crate::external::price_provider::{ExternalPricePoint, ExternalTickerMatch, PriceProvider, PriceProviderError};
-use crate::models::PricePoint;
+use crate::external::price_provider::{
+    ExternalPricePoint, ExternalTickerMatch, IntradayInterval, PriceProvider, PriceProviderError,
+};
+use crate::models::{CorporateAction, IntradayPrices, PricePoint, TickerMetadata, TickerSearchResponse};
 use crate::services::failure_cache::{FailureCache, FailureType};
-use chrono::{Utc, Duration as ChronoDuration, Datelike, Timelike};
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::{clock, fx_service, risk_memo, timezone};
+use chrono::{Duration as ChronoDuration, Datelike, Timelike};
 
 pub async fn get_history(pool: &PgPool, ticker: &str)
                          -> Result<Vec<PricePoint>, AppError> {
@@ -33,6 +37,161 @@ pub async fn get_latest(pool: &PgPool, ticker: &str)
         })
 }
 
+/// Intraday bars are kept this long, covering a long weekend
+const INTRADAY_RETENTION_DAYS: i64 = 5;
+
+/// The latest session's intraday bars for a ticker. Stored bars are served
+/// until they are one interval old, then the session is fetched again; when
+/// that fails, the stored session is served if there is one.
+pub async fn get_intraday(
+    pool: &PgPool,
+    provider: &dyn PriceProvider,
+    rate_limiter: &RateLimiter,
+    ticker: &str,
+    interval: IntradayInterval,
+) -> Result<IntradayPrices, AppError> {
+    let now = clock::now();
+    let mut fetched_at = db::intraday_price_queries::fetch_last_fetched_at(pool, ticker, interval).await?;
+
+    if fetched_at.is_none_or(|at| now - at >= ChronoDuration::minutes(interval.minutes())) {
+        let result = {
+            let _guard = rate_limiter.acquire().await;
+            provider.fetch_intraday(ticker, interval).await
+        };
+        match result {
+            Ok(bars) => {
+                db::intraday_price_queries::upsert_bars(pool, ticker, interval, &bars, now).await?;
+                db::intraday_price_queries::delete_before(pool, ticker, now - ChronoDuration::days(INTRADAY_RETENTION_DAYS))
+                    .await?;
+                fetched_at = Some(now);
+            }
+            Err(e) => {
+                if let PriceProviderError::RateLimited = e {
+                    rate_limiter.record_rejection();
+                }
+                if fetched_at.is_none() {
+                    return Err(match e {
+                        PriceProviderError::RateLimited => AppError::RateLimited,
+                        PriceProviderError::NotFound => {
+                            AppError::NotFound(format!("No intraday prices found for ticker {}", ticker))
+                        }
+                        _ => AppError::External(e.to_string()),
+                    });
+                }
+                warn!("Failed to refresh intraday prices for {}, serving stored bars: {}", ticker, e);
+            }
+        }
+    }
+
+    let bars = db::intraday_price_queries::fetch_latest_session(pool, ticker, interval).await?;
+    let (Some(first), Some(last), Some(fetched_at)) = (bars.first(), bars.last(), fetched_at) else {
+        return Err(AppError::NotFound(format!("No intraday prices found for ticker {}", ticker)));
+    };
+    let session_date = first.bar_time.date_naive();
+    let last_price = last.close;
+
+    let previous_close = db::price_queries::fetch_close_on_or_before(pool, ticker, session_date - ChronoDuration::days(1))
+        .await?
+        .and_then(|p| p.close_price.to_f64());
+    let change = previous_close.map(|prev| last_price - prev);
+    let change_pct = previous_close.filter(|prev| *prev > 0.0).map(|prev| (last_price - prev) / prev * 100.0);
+
+    Ok(IntradayPrices {
+        ticker: ticker.to_string(),
+        interval,
+        currency: fx_service::currency_for_ticker(ticker).to_string(),
+        session_date,
+        previous_close,
+        last_price,
+        change,
+        change_pct,
+        bars,
+        fetched_at,
+    })
+}
+
+/// Days of splits and dividends fetched per ticker, covering the stored history
+const CORPORATE_ACTION_DAYS: u32 = 1825;
+
+/// Closes adjusted for the splits and dividends after them, oldest first.
+///
+/// Each action scales every close before its ex-date: a split by 1 / ratio, a
+/// dividend by 1 - amount / the last close before the ex-date. Some providers
+/// already report split-adjusted closes, so a split is only applied when the
+/// raw series actually jumps by about the split ratio across the ex-date.
+pub fn adjusted_closes(closes: &[(chrono::NaiveDate, f64)], actions: &[CorporateAction]) -> Vec<(chrono::NaiveDate, f64)> {
+    let mut factors: Vec<(chrono::NaiveDate, f64)> = Vec::new();
+    for action in actions {
+        let before = closes.iter().rev().find(|(d, _)| *d < action.ex_date).map(|(_, c)| *c);
+        let after = closes.iter().find(|(d, _)| *d >= action.ex_date).map(|(_, c)| *c);
+        let factor = match (action.action_type.as_str(), before, after) {
+            ("split", Some(before), Some(after)) if action.value > 0.0 && before > 0.0 && after > 0.0 => {
+                let jump = (before / after).ln();
+                ((jump - action.value.ln()).abs() < jump.abs()).then(|| 1.0 / action.value)
+            }
+            ("dividend", Some(before), _) if action.value < before => Some(1.0 - action.value / before),
+            _ => None,
+        };
+        if let Some(factor) = factor {
+            factors.push((action.ex_date, factor));
+        }
+    }
+
+    closes
+        .iter()
+        .map(|(date, close)| {
+            let factor: f64 = factors.iter().filter(|(ex, _)| ex > date).map(|(_, f)| f).product();
+            (*date, close * factor)
+        })
+        .collect()
+}
+
+/// Rebuild a ticker's adjusted closes from its stored corporate actions.
+/// Tickers without any are left alone. Returns the number of closes rewritten.
+pub async fn apply_corporate_actions(pool: &PgPool, ticker: &str) -> Result<u64, AppError> {
+    let actions = db::corporate_action_queries::fetch_for_ticker(pool, ticker).await?;
+    if actions.is_empty() {
+        return Ok(0);
+    }
+    let closes = db::corporate_action_queries::fetch_raw_closes(pool, ticker).await?;
+    let updated =
+        db::corporate_action_queries::update_adjusted_closes(pool, ticker, &adjusted_closes(&closes, &actions)).await?;
+    risk_memo::invalidate(ticker);
+    Ok(updated)
+}
+
+/// Fetch a ticker's splits and dividends and, when any are new or changed,
+/// re-run the adjustment pass. Returns whether the adjusted closes were rebuilt.
+/// Providers without corporate action data leave the ticker unchanged.
+pub async fn refresh_corporate_actions(
+    pool: &PgPool,
+    provider: &dyn PriceProvider,
+    rate_limiter: &RateLimiter,
+    ticker: &str,
+) -> Result<bool, AppError> {
+    let result = {
+        let _guard = rate_limiter.acquire().await;
+        provider.fetch_corporate_actions(ticker, CORPORATE_ACTION_DAYS).await
+    };
+    let actions = match result {
+        Ok(actions) => actions,
+        Err(PriceProviderError::NotFound) | Err(PriceProviderError::Unsupported(_)) => return Ok(false),
+        Err(PriceProviderError::RateLimited) => {
+            rate_limiter.record_rejection();
+            return Err(AppError::RateLimited);
+        }
+        Err(e) => return Err(AppError::External(e.to_string())),
+    };
+
+    let changed = db::corporate_action_queries::upsert_actions(pool, ticker, &actions, clock::now()).await?;
+    if changed == 0 {
+        return Ok(false);
+    }
+    let rewritten = apply_corporate_actions(pool, ticker).await?;
+    info!("Stored {} new corporate actions for {}, rewrote {} adjusted closes", changed, ticker, rewritten);
+    Ok(true)
+}
+
 /*pub async fn refresh_from_api(pool: &PgPool, ticker: &str)
                               -> Result<(), AppError> {
     let api_prices = external::price_provider::fetch_daily(ticker).await?;
@@ -41,7 +200,7 @@ pub async fn get_latest(pool: &PgPool, ticker: &str)
 }*/
 
 pub async fn generate_mock(pool: &PgPool, ticker: &str) -> Result<(), AppError> {
-    let today = Utc::now().date_naive();
+    let today = clock::today();
     let mut points: Vec<ExternalPricePoint> = Vec::new();
 
     let mut current = 100.0_f64;
@@ -52,6 +211,7 @@ pub async fn generate_mock(pool: &PgPool, ticker: &str) -> Result<(), AppError>
         points.push(ExternalPricePoint {
             date: today - ChronoDuration::days(i),
             close: current.to_string().parse::<BigDecimal>().unwrap(),
+            adjusted_close: None,
         });
     }
 
@@ -60,6 +220,7 @@ pub async fn generate_mock(pool: &PgPool, ticker: &str) -> Result<(), AppError>
             error!("Failed to generate mock prices for ticker {}: {}", ticker, e);
             AppError::Db(e)
         })?;
+    risk_memo::invalidate(ticker);
     Ok(())
 }
 
@@ -75,6 +236,104 @@ pub async fn search_for_ticker_from_api(
     }
 }
 
+/// Matches returned by ticker search when the request doesn't say
+const DEFAULT_SEARCH_LIMIT: i64 = 10;
+const MAX_SEARCH_LIMIT: i64 = 50;
+const MAX_SEARCH_QUERY_LEN: usize = 50;
+/// Stored metadata younger than this answers an exact symbol lookup without the provider
+const TICKER_METADATA_TTL_DAYS: i64 = 30;
+
+/// Exchange implied by a symbol's suffix, for providers whose search doesn't report one
+fn exchange_from_symbol(symbol: &str) -> Option<&'static str> {
+    let (_, suffix) = symbol.rsplit_once('.')?;
+    match suffix {
+        "TO" => Some("TSX"),
+        "V" => Some("TSXV"),
+        "NE" => Some("NEO"),
+        "CN" => Some("CSE"),
+        "L" => Some("LSE"),
+        _ => None,
+    }
+}
+
+/// Provider matches as ticker metadata, best match first, one entry per symbol
+pub fn metadata_from_matches(mut matches: Vec<ExternalTickerMatch>, now: chrono::DateTime<chrono::Utc>) -> Vec<TickerMetadata> {
+    matches.sort_by(|a, b| b.match_score.total_cmp(&a.match_score));
+    let mut seen = std::collections::HashSet::new();
+    matches
+        .into_iter()
+        .filter(|m| !m.symbol.trim().is_empty() && seen.insert(m.symbol.to_uppercase()))
+        .map(|m| {
+            let known = |v: String| Some(v).filter(|v| !v.is_empty() && v != "Unknown");
+            let ticker = m.symbol.trim().to_uppercase();
+            TickerMetadata {
+                exchange: m.exchange.or_else(|| exchange_from_symbol(&ticker).map(str::to_string)),
+                name: m.name,
+                asset_type: m._type,
+                region: known(m.region),
+                currency: known(m.currency).map(|c| c.to_uppercase()).unwrap_or_else(|| "USD".to_string()),
+                updated_at: now,
+                ticker,
+            }
+        })
+        .collect()
+}
+
+/// Search symbols by ticker or company name.
+///
+/// An exact symbol already stored recently is answered from `ticker_metadata`;
+/// anything else goes to the provider and its matches are stored. When the
+/// provider is unavailable, stored matches are returned instead of an error.
+pub async fn search_tickers(
+    pool: &PgPool,
+    provider: &dyn PriceProvider,
+    rate_limiter: &RateLimiter,
+    query: &str,
+    limit: Option<i64>,
+) -> Result<TickerSearchResponse, AppError> {
+    let query = query.trim();
+    if query.is_empty() || query.len() > MAX_SEARCH_QUERY_LEN {
+        return Err(AppError::Validation(format!(
+            "Search query must be between 1 and {} characters",
+            MAX_SEARCH_QUERY_LEN
+        )));
+    }
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+
+    let cached = db::ticker_metadata_queries::search(pool, query, limit).await?;
+    let fresh_after = clock::now() - ChronoDuration::days(TICKER_METADATA_TTL_DAYS);
+    if cached.iter().any(|m| m.ticker.eq_ignore_ascii_case(query) && m.updated_at > fresh_after) {
+        return Ok(TickerSearchResponse { query: query.to_string(), results: cached, cached: true });
+    }
+
+    let result = {
+        let _guard = rate_limiter.acquire().await;
+        provider.search_ticker_by_keyword(query).await
+    };
+    let matches = match result {
+        Ok(matches) => matches,
+        Err(PriceProviderError::NotFound) => Vec::new(),
+        Err(e) => {
+            if matches!(e, PriceProviderError::RateLimited) {
+                rate_limiter.record_rejection();
+            }
+            if !cached.is_empty() {
+                warn!("Ticker search for '{}' failed ({}), returning stored matches", query, e);
+                return Ok(TickerSearchResponse { query: query.to_string(), results: cached, cached: true });
+            }
+            return Err(match e {
+                PriceProviderError::RateLimited => AppError::RateLimited,
+                e => AppError::External(e.to_string()),
+            });
+        }
+    };
+
+    let mut results = metadata_from_matches(matches, clock::now());
+    db::ticker_metadata_queries::upsert_many(pool, &results).await?;
+    results.truncate(limit as usize);
+    Ok(TickerSearchResponse { query: query.to_string(), results, cached: false })
+}
+
 /// Determines if we should refresh price data based on market hours and data age
 ///
 /// Strategy:
@@ -85,22 +344,22 @@ pub async fn search_for_ticker_from_api(
 fn should_refresh_price_data(latest_price_date: chrono::NaiveDate) -> bool {
     use chrono::Weekday;
 
-    let now = Utc::now();
-    let today = now.date_naive();
-
-    // Convert to Eastern Time (approximate - 5 hours behind UTC, or 4 during DST)
-    // For simplicity, we'll use a fixed offset. In production, consider using chrono_tz.
-    let et_hour = if now.hour() >= 5 { now.hour() - 5 } else { now.hour() + 19 };
+    // Market hours are in Eastern Time, daylight saving included
+    let market_now = timezone::market().local_datetime(clock::now());
+    let today = market_now.date();
+    let et_minutes = market_now.hour() * 60 + market_now.minute();
+    let market_open = 9 * 60 + 30;
+    let market_close = 16 * 60;
 
     // Weekend: No need to refresh
-    if matches!(now.weekday(), Weekday::Sat | Weekday::Sun) {
+    if matches!(market_now.weekday(), Weekday::Sat | Weekday::Sun) {
         return false;
     }
 
     // If data is from today, check based on market hours
     if latest_price_date == today {
-        // During market hours (9:30 AM - 4:00 PM ET ≈ 14:30 - 21:00 UTC)
-        if et_hour >= 9 && et_hour < 16 {
+        // During market hours (9:30 AM - 4:00 PM ET)
+        if (market_open..market_close).contains(&et_minutes) {
             // Refresh every 15 minutes during market hours
             // Since we can't track exact time, we'll refresh on each request during market hours
             // but the rate limiter will prevent too many concurrent requests
@@ -111,7 +370,7 @@ fn should_refresh_price_data(latest_price_date: chrono::NaiveDate) -> bool {
     }
 
     // If data is from yesterday and it's before market open, that's recent enough
-    if latest_price_date == today - ChronoDuration::days(1) && et_hour < 9 {
+    if latest_price_date == today - ChronoDuration::days(1) && et_minutes < market_open {
         return false;
     }
 
@@ -121,7 +380,7 @@ fn should_refresh_price_data(latest_price_date: chrono::NaiveDate) -> bool {
 
 /// Validates whether a ticker symbol is valid for API calls
 /// Returns false for empty strings, non-alphabetic symbols, and known mutual fund codes
-fn is_valid_ticker(ticker: &str) -> bool {
+pub fn is_valid_ticker(ticker: &str) -> bool {
     let ticker = ticker.trim();
 
     // Must not be empty
@@ -157,13 +416,14 @@ fn is_valid_ticker(ticker: &str) -> bool {
     true
 }
 
-pub async fn refresh_from_api(
-    pool: &PgPool,
-    provider: &dyn PriceProvider,
-    ticker: &str,
-    failure_cache: &FailureCache,
-    rate_limiter: &crate::services::rate_limiter::RateLimiter,
-) -> Result<(), AppError> {
+/// Days of history fetched per refresh, enough for rolling beta analysis
+/// (180 days plus a 90-day window)
+const REFRESH_HISTORY_DAYS: u32 = 365;
+
+/// Whether a ticker's closes need fetching: false when the stored ones are
+/// recent enough, an error for invalid tickers and those still backing off
+/// after a failed fetch
+async fn needs_refresh(pool: &PgPool, ticker: &str) -> Result<bool, AppError> {
     // Validate ticker before attempting any API calls
     if !is_valid_ticker(ticker) {
         info!("⊘ Skipping invalid ticker: '{}' (empty, non-alphabetic, or mutual fund code)", ticker);
@@ -183,7 +443,7 @@ pub async fn refresh_from_api(
 
     if !should_retry {
         if let Ok(Some(failure)) = db::ticker_fetch_failure_queries::get_active_failure(pool, ticker).await {
-            info!("⚠️ Skipping API call for {} - ticker is in failure cache ({}). Will retry after {}",
+            info!("Skipping API call for {} - ticker is in failure cache ({}). Will retry after {}",
                   ticker,
                   failure.failure_type,
                   failure.retry_after);
@@ -197,10 +457,83 @@ pub async fn refresh_from_api(
     // Check if we already have recent data in database (smart caching)
     if let Some(latest) = db::price_queries::fetch_latest(pool, ticker).await? {
         if !should_refresh_price_data(latest.date) {
-            info!("✓ Skipping API call for {} - data is recent enough ({})", ticker, latest.date);
-            return Ok(());
+            info!("Skipping API call for {} - data is recent enough ({})", ticker, latest.date);
+            return Ok(false);
         }
     }
+    Ok(true)
+}
+
+async fn store_fetched(
+    pool: &PgPool,
+    ticker: &str,
+    external_points: &[ExternalPricePoint],
+    failure_cache: &FailureCache,
+) -> Result<(), AppError> {
+    db::price_queries::upsert_external_points(pool, ticker, external_points).await
+        .map_err(|e| {
+            error!("Failed to refresh prices from API for ticker {}: {}", ticker, e);
+            AppError::Db(e)
+        })?;
+    risk_memo::invalidate(ticker);
+
+    // Clear from failure cache on success
+    failure_cache.clear(ticker);
+    if let Err(e) = db::ticker_fetch_failure_queries::clear_fetch_failure(pool, ticker).await {
+        warn!("Failed to clear failure cache for ticker {}: {}", ticker, e);
+    }
+
+    info!("Successfully fetched price data for {}", ticker);
+    Ok(())
+}
+
+/// Record a failed fetch in both memory and database cache to avoid retrying
+async fn record_fetch_error(
+    pool: &PgPool,
+    ticker: &str,
+    e: PriceProviderError,
+    failure_cache: &FailureCache,
+) -> AppError {
+    let failure_type_str = match &e {
+        PriceProviderError::RateLimited => "rate_limited",
+        PriceProviderError::NotFound => "not_found",
+        _ => "api_error",
+    };
+
+    let failure_type_mem = match &e {
+        PriceProviderError::RateLimited => FailureType::RateLimited,
+        PriceProviderError::NotFound => FailureType::NotFound,
+        _ => FailureType::ApiError,
+    };
+
+    failure_cache.record_failure(ticker, failure_type_mem);
+
+    if let Err(db_err) = db::ticker_fetch_failure_queries::record_fetch_failure(
+        pool,
+        ticker,
+        failure_type_str,
+        Some(&e.to_string())
+    ).await {
+        error!("Failed to record failure in database for ticker {}: {}", ticker, db_err);
+    }
+
+    error!("Failed to fetch price data for {}: {}", ticker, e);
+    match e {
+        PriceProviderError::RateLimited => AppError::RateLimited,
+        _ => AppError::External(e.to_string()),
+    }
+}
+
+pub async fn refresh_from_api(
+    pool: &PgPool,
+    provider: &dyn PriceProvider,
+    ticker: &str,
+    failure_cache: &FailureCache,
+    rate_limiter: &crate::services::rate_limiter::RateLimiter,
+) -> Result<(), AppError> {
+    if !needs_refresh(pool, ticker).await? {
+        return Ok(());
+    }
 
     // Retry logic with exponential backoff
     let mut retry_count = 0;
@@ -210,24 +543,13 @@ pub async fn refresh_from_api(
         // Acquire rate limiter permit before making API call
         let _guard = rate_limiter.acquire().await;
 
-        // Fetch 365 days of history to support rolling beta analysis (needs 180 days + 90-day window)
-        match provider.fetch_daily_history(ticker, 365).await {
-            Ok(external_points) => {
-                db::price_queries::upsert_external_points(pool, ticker, &external_points).await
-                    .map_err(|e| {
-                        error!("Failed to refresh prices from API for ticker {}: {}", ticker, e);
-                        AppError::Db(e)
-                    })?;
-
-                // Clear from failure cache on success
-                failure_cache.clear(ticker);
-                if let Err(e) = db::ticker_fetch_failure_queries::clear_fetch_failure(pool, ticker).await {
-                    warn!("Failed to clear failure cache for ticker {}: {}", ticker, e);
-                }
+        let result = provider.fetch_daily_history(ticker, REFRESH_HISTORY_DAYS).await;
+        if let Err(PriceProviderError::RateLimited) = &result {
+            rate_limiter.record_rejection();
+        }
 
-                info!("✓ Successfully fetched price data for {}", ticker);
-                return Ok(());
-            },
+        match result {
+            Ok(external_points) => return store_fetched(pool, ticker, &external_points, failure_cache).await,
             Err(PriceProviderError::RateLimited) if retry_count < max_retries => {
                 retry_count += 1;
                 let delay = Duration::from_secs(5 * retry_count as u64); // 5, 10, 15 seconds
@@ -235,37 +557,133 @@ pub async fn refresh_from_api(
                       ticker, delay.as_secs(), retry_count, max_retries);
                 async_sleep(delay).await;
             },
-            Err(e) => {
-                // Record failure in both memory and database cache to avoid retrying
-                let failure_type_str = match &e {
-                    PriceProviderError::RateLimited => "rate_limited",
-                    PriceProviderError::NotFound => "not_found",
-                    _ => "api_error",
-                };
-
-                let failure_type_mem = match &e {
-                    PriceProviderError::RateLimited => FailureType::RateLimited,
-                    PriceProviderError::NotFound => FailureType::NotFound,
-                    _ => FailureType::ApiError,
-                };
-
-                failure_cache.record_failure(ticker, failure_type_mem);
-
-                if let Err(db_err) = db::ticker_fetch_failure_queries::record_fetch_failure(
-                    pool,
-                    ticker,
-                    failure_type_str,
-                    Some(&e.to_string())
-                ).await {
-                    error!("Failed to record failure in database for ticker {}: {}", ticker, db_err);
-                }
+            Err(e) => return Err(record_fetch_error(pool, ticker, e, failure_cache).await),
+        }
+    }
+}
 
-                error!("✗ Failed to fetch price data for {}: {}", ticker, e);
-                return Err(match e {
-                    PriceProviderError::RateLimited => AppError::RateLimited,
-                    _ => AppError::External(e.to_string()),
-                });
+/// Refresh several tickers at once, fetching the stale ones concurrently with
+/// `PriceProvider::fetch_many`. Tickers the provider rate limited go through
+/// `refresh_from_api`, which retries with backoff. Results are in input order
+/// with duplicates dropped.
+pub async fn refresh_many_from_api(
+    pool: &PgPool,
+    provider: &dyn PriceProvider,
+    tickers: &[String],
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
+) -> Vec<(String, Result<(), AppError>)> {
+    let mut results: Vec<(String, Result<(), AppError>)> = Vec::new();
+    let mut to_fetch: Vec<&str> = Vec::new();
+    for ticker in tickers {
+        if results.iter().any(|(t, _)| t == ticker) {
+            continue;
+        }
+        let result = match needs_refresh(pool, ticker).await {
+            Ok(true) => {
+                to_fetch.push(ticker);
+                Ok(())
+            }
+            Ok(false) => Ok(()),
+            Err(e) => Err(e),
+        };
+        results.push((ticker.clone(), result));
+    }
+    if to_fetch.is_empty() {
+        return results;
+    }
+
+    info!("Fetching price data for {} tickers", to_fetch.len());
+    let mut rate_limited = Vec::new();
+    for (ticker, fetched) in provider.fetch_many(&to_fetch, REFRESH_HISTORY_DAYS, rate_limiter).await {
+        let outcome = match fetched {
+            Ok(external_points) => store_fetched(pool, &ticker, &external_points, failure_cache).await,
+            Err(PriceProviderError::RateLimited) => {
+                rate_limited.push(ticker);
+                continue;
             }
+            Err(e) => Err(record_fetch_error(pool, &ticker, e, failure_cache).await),
+        };
+        if let Some((_, result)) = results.iter_mut().find(|(t, _)| *t == ticker) {
+            *result = outcome;
         }
     }
+
+    for ticker in rate_limited {
+        let outcome = refresh_from_api(pool, provider, &ticker, failure_cache, rate_limiter).await;
+        if let Some((_, result)) = results.iter_mut().find(|(t, _)| *t == ticker) {
+            *result = outcome;
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+
+    fn action(ex_date: NaiveDate, action_type: &str, value: f64) -> CorporateAction {
+        CorporateAction {
+            ticker: "AAPL".to_string(),
+            ex_date,
+            action_type: action_type.to_string(),
+            value,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2020, 8, d).unwrap()
+    }
+
+    #[test]
+    fn test_adjusted_closes_apply_split_and_dividend() {
+        let closes = vec![(day(6), 455.0), (day(7), 444.0), (day(28), 500.0), (day(31), 129.0)];
+        let actions = vec![action(day(7), "dividend", 0.82), action(day(31), "split", 4.0)];
+
+        let adjusted = adjusted_closes(&closes, &actions);
+        let dividend = 1.0 - 0.82 / 455.0;
+        assert!((adjusted[0].1 - 455.0 / 4.0 * dividend).abs() < 1e-9);
+        assert!((adjusted[1].1 - 444.0 / 4.0).abs() < 1e-9);
+        assert!((adjusted[2].1 - 125.0).abs() < 1e-9);
+        assert_eq!(adjusted[3].1, 129.0);
+    }
+
+    fn ticker_match(symbol: &str, exchange: Option<&str>, match_score: f64) -> ExternalTickerMatch {
+        ExternalTickerMatch {
+            symbol: symbol.to_string(),
+            name: format!("{} Inc", symbol),
+            _type: "Equity".to_string(),
+            region: "Unknown".to_string(),
+            currency: "cad".to_string(),
+            exchange: exchange.map(str::to_string),
+            match_score,
+        }
+    }
+
+    #[test]
+    fn test_metadata_from_matches_ranks_and_dedupes() {
+        let matches = vec![
+            ticker_match("shop", Some("NYSE"), 0.6),
+            ticker_match("SHOP.TO", None, 0.9),
+            ticker_match("SHOP", None, 0.5),
+        ];
+        let metadata = metadata_from_matches(matches, Utc::now());
+
+        let tickers: Vec<&str> = metadata.iter().map(|m| m.ticker.as_str()).collect();
+        assert_eq!(tickers, vec!["SHOP.TO", "SHOP"]);
+        assert_eq!(metadata[0].exchange.as_deref(), Some("TSX"));
+        assert_eq!(metadata[1].exchange.as_deref(), Some("NYSE"));
+        assert_eq!(metadata[0].region, None);
+        assert_eq!(metadata[0].currency, "CAD");
+    }
+
+    #[test]
+    fn test_adjusted_closes_skip_already_adjusted_split() {
+        // The provider already reported split-adjusted closes: no jump on the ex-date
+        let closes = vec![(day(28), 125.0), (day(31), 129.0)];
+        let adjusted = adjusted_closes(&closes, &[action(day(31), "split", 4.0)]);
+        assert_eq!(adjusted, closes);
+    }
 }
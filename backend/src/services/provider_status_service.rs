@@ -0,0 +1,243 @@
+//! Health of the configured market data providers.
+//!
+//! Combines what is already tracked in memory and in the database: the
+//! composite chain's circuit breakers, the rate limiter's counters, live
+//! entries in the `FailureCache` and the time data from each provider was last
+//! stored. Nothing here calls a provider, so checking status spends no quota.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+use crate::db::{fundamentals_queries, fx_queries, price_queries, ticker_fetch_failure_queries};
+use crate::errors::AppError;
+use crate::external::price_provider::{PriceProvider, ProviderHealth};
+use crate::models::{
+    ProviderFailureCounts, ProviderStatus, ProviderStatusLevel, ProvidersStatus, QuotaEstimate, RateLimiterMetrics,
+};
+use crate::services::failure_cache::{FailureCache, FailureInfo, FailureType};
+use crate::services::rate_limiter::RateLimiter;
+
+/// Closes are refreshed daily; a long weekend is the longest expected gap
+const PRICE_STALE_AFTER_DAYS: i64 = 4;
+/// Fundamentals are refreshed weekly per ticker
+const FUNDAMENTALS_STALE_AFTER_DAYS: i64 = 8;
+/// ECB rates are published on business days
+const FX_STALE_AFTER_DAYS: i64 = 4;
+/// Fundamentals always come from Finnhub when configured
+const FUNDAMENTALS_PROVIDER_NAME: &str = "finnhub";
+
+/// Failures in the cache belonging to a provider. Members of the composite
+/// chain key their misses "<member>:<ticker>"; the price refresh keys its own
+/// by bare ticker, which are counted against the top-level provider (`None`).
+pub fn failure_counts(entries: &[(String, FailureInfo)], member: Option<&str>) -> ProviderFailureCounts {
+    let mut counts = ProviderFailureCounts::default();
+    for (key, info) in entries {
+        let belongs = match (member, key.split_once(':')) {
+            (Some(name), Some((prefix, _))) => prefix == name,
+            (None, None) => true,
+            _ => false,
+        };
+        if !belongs {
+            continue;
+        }
+        match info.error_type {
+            FailureType::NotFound => counts.not_found += 1,
+            FailureType::RateLimited => counts.rate_limited += 1,
+            FailureType::ApiError => counts.api_error += 1,
+        }
+    }
+    counts
+}
+
+/// Requests left today: the daily limit less today's requests when one is
+/// configured, otherwise what the per-minute budget allows before midnight UTC
+pub fn estimated_daily_remaining(metrics: &RateLimiterMetrics, now: DateTime<Utc>) -> u32 {
+    if let Some(limit) = metrics.daily_limit {
+        return limit.saturating_sub(metrics.requests_today);
+    }
+    let midnight = (now.date_naive() + Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    let minutes_left = (midnight - now).num_minutes().max(0) as u64;
+    let remaining = metrics.tokens_remaining as u64 + metrics.requests_per_minute as u64 * minutes_left;
+    remaining.min(u32::MAX as u64) as u32
+}
+
+pub fn level(
+    circuit_open: bool,
+    consecutive_failures: u32,
+    last_success_at: Option<DateTime<Utc>>,
+    stale_after: Duration,
+    now: DateTime<Utc>,
+) -> ProviderStatusLevel {
+    if circuit_open {
+        return ProviderStatusLevel::Down;
+    }
+    match last_success_at {
+        None if consecutive_failures > 0 => ProviderStatusLevel::Degraded,
+        None => ProviderStatusLevel::Unknown,
+        Some(at) if consecutive_failures > 0 || now - at > stale_after => ProviderStatusLevel::Degraded,
+        Some(_) => ProviderStatusLevel::Ok,
+    }
+}
+
+fn quota(metrics: &RateLimiterMetrics, now: DateTime<Utc>) -> QuotaEstimate {
+    QuotaEstimate {
+        requests_per_minute: metrics.requests_per_minute,
+        tokens_remaining: metrics.tokens_remaining,
+        requests_today: metrics.requests_today,
+        daily_limit: metrics.daily_limit,
+        estimated_daily_remaining: estimated_daily_remaining(metrics, now),
+        rejections: metrics.rejections,
+    }
+}
+
+fn chain_member_status(
+    member: &ProviderHealth,
+    failures: &[(String, FailureInfo)],
+    now: DateTime<Utc>,
+) -> ProviderStatus {
+    ProviderStatus {
+        name: member.name.clone(),
+        role: "price_chain".to_string(),
+        status: level(
+            member.circuit_open,
+            member.consecutive_failures,
+            member.last_success_at,
+            Duration::days(PRICE_STALE_AFTER_DAYS),
+            now,
+        ),
+        circuit_open: Some(member.circuit_open),
+        consecutive_failures: Some(member.consecutive_failures),
+        last_success_at: member.last_success_at,
+        recent_failures: failure_counts(failures, Some(&member.name)),
+    }
+}
+
+/// Status of the price provider (and each member of a composite chain), the
+/// fundamentals provider when configured, and the FX provider
+#[allow(clippy::too_many_arguments)]
+pub async fn status(
+    pool: &PgPool,
+    price_provider_name: &str,
+    price_provider: &dyn PriceProvider,
+    fundamentals_configured: bool,
+    fx_provider_name: &str,
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
+    now: DateTime<Utc>,
+) -> Result<ProvidersStatus, AppError> {
+    let failures = failure_cache.active();
+    let chain = price_provider.health();
+
+    // A chain is down only when every member is; otherwise it is as fresh as
+    // its most recent success or the last close stored
+    let last_stored = price_queries::fetch_last_stored_at(pool).await?;
+    let last_price = chain.iter().filter_map(|m| m.last_success_at).chain(last_stored).max();
+    let chain_down = !chain.is_empty() && chain.iter().all(|m| m.circuit_open);
+    let mut providers = vec![ProviderStatus {
+        name: price_provider_name.to_string(),
+        role: "price".to_string(),
+        status: level(chain_down, 0, last_price, Duration::days(PRICE_STALE_AFTER_DAYS), now),
+        circuit_open: None,
+        consecutive_failures: None,
+        last_success_at: last_price,
+        recent_failures: failure_counts(&failures, None),
+    }];
+    providers.extend(chain.iter().map(|member| chain_member_status(member, &failures, now)));
+
+    if fundamentals_configured {
+        let last = fundamentals_queries::fetch_last_fetched_at(pool).await?;
+        providers.push(ProviderStatus {
+            name: FUNDAMENTALS_PROVIDER_NAME.to_string(),
+            role: "fundamentals".to_string(),
+            status: level(false, 0, last, Duration::days(FUNDAMENTALS_STALE_AFTER_DAYS), now),
+            circuit_open: None,
+            consecutive_failures: None,
+            last_success_at: last,
+            recent_failures: ProviderFailureCounts::default(),
+        });
+    }
+
+    let last_fx = fx_queries::fetch_last_fetched_at(pool).await?;
+    providers.push(ProviderStatus {
+        name: fx_provider_name.to_string(),
+        role: "fx".to_string(),
+        status: level(false, 0, last_fx, Duration::days(FX_STALE_AFTER_DAYS), now),
+        circuit_open: None,
+        consecutive_failures: None,
+        last_success_at: last_fx,
+        recent_failures: ProviderFailureCounts::default(),
+    });
+
+    Ok(ProvidersStatus {
+        checked_at: now,
+        providers,
+        quota: quota(&rate_limiter.metrics(), now),
+        tickers_backing_off: ticker_fetch_failure_queries::get_all_active_failures(pool).await?.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn failure(error_type: FailureType) -> FailureInfo {
+        FailureInfo { failed_at: Utc::now(), error_type, ttl_hours: 1 }
+    }
+
+    fn metrics(daily_limit: Option<u32>) -> RateLimiterMetrics {
+        RateLimiterMetrics {
+            max_concurrent: 3,
+            available_permits: 3,
+            configured_per_minute: 8,
+            requests_per_minute: 8,
+            tokens_remaining: 5,
+            queue_depth: 0,
+            requests_granted: 40,
+            requests_delayed: 0,
+            rejections: 0,
+            last_calibration: None,
+            requests_today: 40,
+            daily_limit,
+        }
+    }
+
+    #[test]
+    fn test_failure_counts_split_by_member_prefix() {
+        let entries = vec![
+            ("AAPL".to_string(), failure(FailureType::RateLimited)),
+            ("MSFT".to_string(), failure(FailureType::ApiError)),
+            ("twelvedata:XYZ".to_string(), failure(FailureType::NotFound)),
+            ("polygon:XYZ".to_string(), failure(FailureType::NotFound)),
+        ];
+        let top = failure_counts(&entries, None);
+        assert_eq!(top, ProviderFailureCounts { not_found: 0, rate_limited: 1, api_error: 1 });
+        let member = failure_counts(&entries, Some("twelvedata"));
+        assert_eq!(member, ProviderFailureCounts { not_found: 1, rate_limited: 0, api_error: 0 });
+        assert_eq!(failure_counts(&entries, Some("yahoo")), ProviderFailureCounts::default());
+    }
+
+    #[test]
+    fn test_estimated_daily_remaining() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 23, 50, 0).unwrap();
+        assert_eq!(estimated_daily_remaining(&metrics(Some(500)), now), 460);
+        assert_eq!(estimated_daily_remaining(&metrics(Some(25)), now), 0);
+        // 5 tokens now plus 8 a minute for the last 10 minutes of the day
+        assert_eq!(estimated_daily_remaining(&metrics(None), now), 85);
+    }
+
+    #[test]
+    fn test_level() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 12, 0, 0).unwrap();
+        let stale_after = Duration::days(4);
+        let recent = Some(now - Duration::hours(6));
+        assert_eq!(level(false, 0, recent, stale_after, now), ProviderStatusLevel::Ok);
+        assert_eq!(level(false, 2, recent, stale_after, now), ProviderStatusLevel::Degraded);
+        assert_eq!(level(false, 0, Some(now - Duration::days(5)), stale_after, now), ProviderStatusLevel::Degraded);
+        assert_eq!(level(true, 3, recent, stale_after, now), ProviderStatusLevel::Down);
+        assert_eq!(level(false, 0, None, stale_after, now), ProviderStatusLevel::Unknown);
+    }
+}
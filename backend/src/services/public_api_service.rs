@@ -0,0 +1,210 @@
+//! Read-only public API for self-hosted dashboards.
+//!
+//! With `PUBLIC_API_ENABLED=true`, `/api/public` serves a small read-only
+//! subset of the API (portfolio values, risk summary and value history) to
+//! dashboards such as Grafana or Home Assistant. They authenticate with a
+//! per-user token sent as `Authorization: Bearer <token>` instead of the
+//! browser session cookie. No mutating route is mounted under `/api/public`,
+//! so a leaked token exposes figures but can change nothing. The same tokens
+//! authenticate the Grafana datasource at `/api/datasource`.
+
+use bigdecimal::ToPrimitive;
+use chrono::Duration;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::{holding_snapshot_queries, portfolio_queries, public_api_token_queries, risk_snapshot_queries};
+use crate::errors::AppError;
+use crate::models::{
+    CreatedPublicApiToken, Portfolio, PublicPortfolioValue, PublicRiskSummary, PublicValuePoint,
+};
+use crate::services::behavioral_analytics_service::portfolio_values;
+
+/// Days of value history returned when the request doesn't say
+pub const DEFAULT_HISTORY_DAYS: i64 = 365;
+/// Longest token label accepted
+const MAX_TOKEN_NAME_LEN: usize = 100;
+/// Public API tokens are prefixed so they are recognizable in dashboard configs
+const TOKEN_PREFIX: &str = "rfp_";
+
+#[derive(Debug, Clone)]
+pub struct PublicApiConfig {
+    /// `/api/public` and `/api/datasource` are only mounted when enabled
+    pub enabled: bool,
+}
+
+impl PublicApiConfig {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("PUBLIC_API_ENABLED")
+                .ok()
+                .and_then(|s| s.parse::<bool>().ok())
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Tokens are stored hashed like refresh tokens, so a database leak doesn't expose them
+fn hash_token(token: &str) -> String {
+    auth::hash_refresh_token(token)
+}
+
+pub async fn create_token(pool: &PgPool, user_id: Uuid, name: &str) -> Result<CreatedPublicApiToken, AppError> {
+    if !PublicApiConfig::from_env().enabled {
+        return Err(AppError::ServiceUnavailable("The public API is not enabled".to_string()));
+    }
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_TOKEN_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Token name must be between 1 and {} characters",
+            MAX_TOKEN_NAME_LEN
+        )));
+    }
+
+    let secret = format!("{}{}", TOKEN_PREFIX, auth::generate_refresh_token());
+    let token = public_api_token_queries::create(pool, user_id, name, &hash_token(&secret)).await?;
+    Ok(CreatedPublicApiToken { token, secret })
+}
+
+/// The user a bearer token belongs to
+pub async fn authenticate(pool: &PgPool, token: &str) -> Result<Uuid, AppError> {
+    if !token.starts_with(TOKEN_PREFIX) {
+        return Err(AppError::Unauthorized);
+    }
+    public_api_token_queries::authenticate(pool, &hash_token(token))
+        .await?
+        .ok_or(AppError::Unauthorized)
+}
+
+/// Latest value and the change since the valuation before it
+pub fn value_summary(portfolio: &Portfolio, values: &[(chrono::NaiveDate, f64)]) -> PublicPortfolioValue {
+    let latest = values.last();
+    let previous = values.len().checked_sub(2).map(|i| values[i].1);
+    let value = latest.map(|(_, v)| *v).unwrap_or(0.0);
+    let change = previous.map(|p| value - p);
+    PublicPortfolioValue {
+        portfolio_id: portfolio.id,
+        name: portfolio.name.clone(),
+        as_of: latest.map(|(d, _)| *d),
+        value,
+        change,
+        change_pct: previous.filter(|p| *p > 0.0).map(|p| (value - p) / p * 100.0),
+    }
+}
+
+/// Values of the last `days` days up to the latest valuation, oldest first
+pub fn recent_history(values: &[(chrono::NaiveDate, f64)], days: i64) -> Vec<PublicValuePoint> {
+    let Some((latest, _)) = values.last() else {
+        return Vec::new();
+    };
+    let since = *latest - Duration::days(days.max(0));
+    values
+        .iter()
+        .filter(|(date, _)| *date >= since)
+        .map(|(date, value)| PublicValuePoint { date: *date, value: *value })
+        .collect()
+}
+
+async fn portfolio(pool: &PgPool, portfolio_id: Uuid, user_id: Uuid) -> Result<Portfolio, AppError> {
+    portfolio_queries::fetch_one(pool, portfolio_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} not found", portfolio_id)))
+}
+
+async fn values(pool: &PgPool, portfolio_id: Uuid) -> Result<Vec<(chrono::NaiveDate, f64)>, AppError> {
+    Ok(portfolio_values(&holding_snapshot_queries::fetch_portfolio_value_history(pool, portfolio_id).await?))
+}
+
+/// Values of the user's active portfolios
+pub async fn portfolio_values_for_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<PublicPortfolioValue>, AppError> {
+    let mut summaries = Vec::new();
+    for portfolio in portfolio_queries::fetch_all(pool, user_id, false).await? {
+        let values = values(pool, portfolio.id).await?;
+        summaries.push(value_summary(&portfolio, &values));
+    }
+    Ok(summaries)
+}
+
+pub async fn portfolio_value(pool: &PgPool, portfolio_id: Uuid, user_id: Uuid) -> Result<PublicPortfolioValue, AppError> {
+    let portfolio = portfolio(pool, portfolio_id, user_id).await?;
+    Ok(value_summary(&portfolio, &values(pool, portfolio_id).await?))
+}
+
+pub async fn value_history(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    user_id: Uuid,
+    days: i64,
+) -> Result<Vec<PublicValuePoint>, AppError> {
+    portfolio(pool, portfolio_id, user_id).await?;
+    Ok(recent_history(&values(pool, portfolio_id).await?, days))
+}
+
+/// Headline figures of the latest portfolio risk snapshot
+pub async fn risk_summary(pool: &PgPool, portfolio_id: Uuid, user_id: Uuid) -> Result<PublicRiskSummary, AppError> {
+    portfolio(pool, portfolio_id, user_id).await?;
+    let snapshot = risk_snapshot_queries::fetch_latest(pool, portfolio_id, None)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Portfolio {} has no risk snapshot yet", portfolio_id)))?;
+    Ok(PublicRiskSummary {
+        portfolio_id,
+        as_of: snapshot.snapshot_date,
+        risk_score: snapshot.risk_score.to_f64().unwrap_or(0.0),
+        risk_level: snapshot.risk_level,
+        volatility: snapshot.volatility.to_f64().unwrap_or(0.0),
+        max_drawdown: snapshot.max_drawdown.to_f64().unwrap_or(0.0),
+        beta: snapshot.beta.and_then(|v| v.to_f64()),
+        sharpe: snapshot.sharpe.and_then(|v| v.to_f64()),
+        var_95: snapshot.var_95.and_then(|v| v.to_f64()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    fn portfolio() -> Portfolio {
+        Portfolio {
+            id: Uuid::new_v4(),
+            name: "Retirement".to_string(),
+            user_id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            archived_at: None,
+            cloned_from: None,
+        }
+    }
+
+    #[test]
+    fn test_value_summary_reports_change_since_previous_valuation() {
+        let summary = value_summary(&portfolio(), &[(day(2), 1000.0), (day(3), 1000.0), (day(4), 1050.0)]);
+        assert_eq!(summary.as_of, Some(day(4)));
+        assert_eq!(summary.value, 1050.0);
+        assert_eq!(summary.change, Some(50.0));
+        assert!((summary.change_pct.unwrap() - 5.0).abs() < 1e-9);
+
+        let empty = value_summary(&portfolio(), &[]);
+        assert_eq!(empty.as_of, None);
+        assert_eq!(empty.value, 0.0);
+        assert_eq!(empty.change, None);
+    }
+
+    #[test]
+    fn test_recent_history_counts_back_from_latest_valuation() {
+        let values = vec![(day(1), 10.0), (day(5), 11.0), (day(9), 12.0)];
+        let history = recent_history(&values, 4);
+        assert_eq!(
+            history,
+            vec![
+                PublicValuePoint { date: day(5), value: 11.0 },
+                PublicValuePoint { date: day(9), value: 12.0 },
+            ]
+        );
+        assert!(recent_history(&[], 30).is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use bigdecimal::ToPrimitive;
 use std::sync::Arc;
 use sqlx::PgPool;
 use tracing::{info, warn};
@@ -30,7 +31,7 @@ pub async fn build_portfolio_context(
 
     let total_value: f64 = holdings
         .iter()
-        .filter_map(|h| h.market_value.to_string().parse::<f64>().ok())
+        .filter_map(|h| h.market_value.to_f64())
         .sum();
 
     let position_count = holdings.len();
@@ -49,7 +50,7 @@ pub async fn build_portfolio_context(
     if !holdings.is_empty() {
         context.push_str("TOP HOLDINGS:\n");
         for (i, holding) in holdings.iter().take(10).enumerate() {
-            let value = holding.market_value.to_string().parse::<f64>().unwrap_or(0.0);
+            let value = holding.market_value.to_f64().unwrap_or(0.0);
             let weight = if total_value > 0.0 {
                 (value / total_value) * 100.0
             } else {
@@ -0,0 +1,223 @@
+//! Constrained portfolio weight solver.
+//!
+//! Solves the quadratic program
+//!
+//!   minimize   ½ Σ (wᵢ − w0ᵢ)² + ½ λ Σ σᵢ² wᵢ²
+//!   subject to lᵢ ≤ wᵢ ≤ uᵢ,  Σ wᵢ = invested,  Σ_{i∈G} wᵢ ≤ cap_G for each group G
+//!
+//! with projected gradient descent. The first term keeps turnover low, the second
+//! tilts freed-up weight toward lower-volatility positions. The projection onto the
+//! feasible set uses Dykstra's algorithm over the box-and-budget set and the group
+//! half-spaces, which converges to the exact Euclidean projection.
+
+const MAX_ITERATIONS: usize = 500;
+const MAX_PROJECTION_ROUNDS: usize = 200;
+const TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone)]
+pub struct WeightProblem {
+    /// Weights to stay close to (fractions of portfolio value), normally the current ones
+    pub current: Vec<f64>,
+    /// Variance per unit weight (σ², σ as a fraction); 0 when unknown
+    pub variance: Vec<f64>,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+    /// Index sets whose combined weight is capped
+    pub groups: Vec<(Vec<usize>, f64)>,
+    /// Total weight to hold in the positions; the rest is cash
+    pub invested: f64,
+    pub risk_aversion: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct WeightSolution {
+    pub weights: Vec<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+/// Project `v` onto {l ≤ w ≤ u, Σw = target} by bisecting on a uniform shift
+fn project_box_budget(v: &[f64], lower: &[f64], upper: &[f64], target: f64) -> Vec<f64> {
+    let shifted = |tau: f64| -> f64 {
+        v.iter()
+            .zip(lower.iter().zip(upper))
+            .map(|(x, (l, u))| (x - tau).clamp(*l, *u))
+            .sum()
+    };
+    let mut lo = v.iter().zip(upper).map(|(x, u)| x - u).fold(f64::INFINITY, f64::min);
+    let mut hi = v.iter().zip(lower).map(|(x, l)| x - l).fold(f64::NEG_INFINITY, f64::max);
+    for _ in 0..100 {
+        let mid = (lo + hi) / 2.0;
+        if shifted(mid) > target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+        if hi - lo < 1e-14 {
+            break;
+        }
+    }
+    let tau = (lo + hi) / 2.0;
+    v.iter()
+        .zip(lower.iter().zip(upper))
+        .map(|(x, (l, u))| (x - tau).clamp(*l, *u))
+        .collect()
+}
+
+/// Project `w` onto the half-space Σ_{i∈group} wᵢ ≤ cap in place
+fn project_group(w: &mut [f64], group: &[usize], cap: f64) {
+    let sum: f64 = group.iter().map(|&i| w[i]).sum();
+    if sum > cap && !group.is_empty() {
+        let excess = (sum - cap) / group.len() as f64;
+        for &i in group {
+            w[i] -= excess;
+        }
+    }
+}
+
+/// Euclidean projection onto the feasible set via Dykstra's algorithm
+fn project(v: &[f64], problem: &WeightProblem) -> Vec<f64> {
+    let n = v.len();
+    let sets = problem.groups.len() + 1;
+    let mut increments = vec![vec![0.0; n]; sets];
+    let mut x = v.to_vec();
+
+    for _ in 0..MAX_PROJECTION_ROUNDS {
+        let previous = x.clone();
+        for (k, increment) in increments.iter_mut().enumerate() {
+            let y: Vec<f64> = x.iter().zip(increment.iter()).map(|(a, b)| a + b).collect();
+            let projected = if k == 0 {
+                project_box_budget(&y, &problem.lower, &problem.upper, problem.invested)
+            } else {
+                let (group, cap) = &problem.groups[k - 1];
+                let mut p = y.clone();
+                project_group(&mut p, group, *cap);
+                p
+            };
+            for (inc, (yi, pi)) in increment.iter_mut().zip(y.iter().zip(&projected)) {
+                *inc = yi - pi;
+            }
+            x = projected;
+        }
+        let change = x.iter().zip(&previous).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+        if change < TOLERANCE {
+            break;
+        }
+    }
+    x
+}
+
+/// True when `w` satisfies every constraint within `tolerance`
+pub fn is_feasible(w: &[f64], problem: &WeightProblem, tolerance: f64) -> bool {
+    let in_box = w
+        .iter()
+        .zip(problem.lower.iter().zip(&problem.upper))
+        .all(|(x, (l, u))| *x >= l - tolerance && *x <= u + tolerance);
+    let budget = (w.iter().sum::<f64>() - problem.invested).abs() <= tolerance;
+    let groups = problem
+        .groups
+        .iter()
+        .all(|(group, cap)| group.iter().map(|&i| w[i]).sum::<f64>() <= cap + tolerance);
+    in_box && budget && groups
+}
+
+pub fn solve_weights(problem: &WeightProblem) -> WeightSolution {
+    let curvature = problem
+        .variance
+        .iter()
+        .map(|v| 1.0 + problem.risk_aversion * v)
+        .fold(1.0, f64::max);
+    let step = 1.0 / curvature;
+
+    let mut w = project(&problem.current, problem);
+    for iteration in 1..=MAX_ITERATIONS {
+        let candidate: Vec<f64> = w
+            .iter()
+            .zip(problem.current.iter().zip(&problem.variance))
+            .map(|(wi, (w0, var))| wi - step * ((wi - w0) + problem.risk_aversion * var * wi))
+            .collect();
+        let next = project(&candidate, problem);
+        let change = next.iter().zip(&w).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+        w = next;
+        if change < TOLERANCE {
+            let converged = is_feasible(&w, problem, 1e-6);
+            return WeightSolution { weights: w, iterations: iteration, converged };
+        }
+    }
+    let converged = is_feasible(&w, problem, 1e-6);
+    WeightSolution { weights: w, iterations: MAX_ITERATIONS, converged }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(current: Vec<f64>) -> WeightProblem {
+        let n = current.len();
+        WeightProblem {
+            current,
+            variance: vec![0.0; n],
+            lower: vec![0.0; n],
+            upper: vec![1.0; n],
+            groups: vec![],
+            invested: 1.0,
+            risk_aversion: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_unconstrained_keeps_current_weights() {
+        let p = problem(vec![0.5, 0.3, 0.2]);
+        let s = solve_weights(&p);
+        assert!(s.converged);
+        for (a, b) in s.weights.iter().zip(&p.current) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_max_weight_redistributes_excess() {
+        let mut p = problem(vec![0.6, 0.2, 0.2]);
+        p.upper = vec![0.4; 3];
+        let s = solve_weights(&p);
+        assert!(s.converged);
+        assert!((s.weights[0] - 0.4).abs() < 1e-6);
+        assert!((s.weights[1] - 0.3).abs() < 1e-6);
+        assert!((s.weights[2] - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_group_cap_and_cash() {
+        let mut p = problem(vec![0.4, 0.4, 0.2]);
+        p.groups = vec![(vec![0, 1], 0.5)];
+        p.invested = 0.9;
+        let s = solve_weights(&p);
+        assert!(s.converged);
+        assert!(s.weights[0] + s.weights[1] <= 0.5 + 1e-6);
+        assert!((s.weights.iter().sum::<f64>() - 0.9).abs() < 1e-6);
+        assert!((s.weights[2] - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_lower_bound_is_respected() {
+        // Position 0 may not be sold even though the budget shrinks
+        let mut p = problem(vec![0.5, 0.5]);
+        p.lower = vec![0.5, 0.0];
+        p.invested = 0.8;
+        let s = solve_weights(&p);
+        assert!(s.converged);
+        assert!((s.weights[0] - 0.5).abs() < 1e-6);
+        assert!((s.weights[1] - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_risk_aversion_tilts_to_low_volatility() {
+        let mut p = problem(vec![0.6, 0.2, 0.2]);
+        p.upper = vec![0.4; 3];
+        p.variance = vec![0.0, 0.25, 0.01];
+        p.risk_aversion = 1.0;
+        let s = solve_weights(&p);
+        assert!(s.converged);
+        assert!(s.weights[2] > s.weights[1]);
+    }
+}
@@ -1,19 +1,68 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::Semaphore;
 use tokio::time::{sleep, Duration, Instant};
+use chrono::{NaiveDate, Utc};
 use parking_lot::Mutex;
 
+use crate::external::price_provider::ProviderQuota;
+use crate::models::{QuotaCalibration, RateLimiterMetrics};
+
+/// Share of a provider's reported limit the limiter budgets for, leaving room
+/// for calibration probes and other clients of the same API key
+const QUOTA_HEADROOM: f64 = 0.9;
+const MIN_REQUESTS_PER_MINUTE: u32 = 1;
+const MAX_REQUESTS_PER_MINUTE: u32 = 600;
+
 /// Rate limiter to control API request frequency
 ///
 /// This prevents exhausting the free tier quotas of price providers like Twelve Data (8 req/min)
-/// and Alpha Vantage (5 req/min).
+/// and Alpha Vantage (5 req/min). The per-minute budget starts at the configured value and is
+/// recalibrated from the provider's reported quota by the `calibrate_rate_limits` job.
 pub struct RateLimiter {
     /// Semaphore to limit concurrent requests
     semaphore: Arc<Semaphore>,
+    max_concurrent: usize,
     /// Last request timestamp to enforce minimum delay between requests
     last_request: Arc<Mutex<Instant>>,
-    /// Minimum delay between requests in milliseconds
-    min_delay: Duration,
+    /// Budget the limiter was created with
+    configured_per_minute: u32,
+    /// Budget currently enforced; the minimum delay between requests derives from it
+    requests_per_minute: AtomicU32,
+    /// Grant times within the trailing minute
+    recent: Mutex<VecDeque<Instant>>,
+    waiting: AtomicUsize,
+    granted: AtomicU64,
+    delayed: AtomicU64,
+    rejections: AtomicU64,
+    last_calibration: Mutex<Option<QuotaCalibration>>,
+    /// Requests per day the provider plan allows, when known
+    daily_limit: Option<u32>,
+    /// Requests granted on the current UTC day
+    today: Mutex<(NaiveDate, u32)>,
+}
+
+/// Counts a caller as queued until it is granted a permit or gives up
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl<'a> QueueSlot<'a> {
+    fn enter(waiting: &'a AtomicUsize) -> Self {
+        waiting.fetch_add(1, Ordering::Relaxed);
+        Self(waiting)
+    }
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Per-minute budget for a provider-reported limit
+fn budget_for_limit(limit_per_minute: u32) -> u32 {
+    ((limit_per_minute as f64 * QUOTA_HEADROOM).floor() as u32)
+        .clamp(MIN_REQUESTS_PER_MINUTE, MAX_REQUESTS_PER_MINUTE)
 }
 
 impl RateLimiter {
@@ -29,14 +78,52 @@ impl RateLimiter {
     /// let limiter = RateLimiter::new(3, 8);
     /// ```
     pub fn new(max_concurrent: usize, requests_per_minute: u32) -> Self {
-        let min_delay_ms = 60_000 / requests_per_minute as u64;
+        let requests_per_minute = requests_per_minute.max(MIN_REQUESTS_PER_MINUTE);
         Self {
             semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent,
             last_request: Arc::new(Mutex::new(Instant::now() - Duration::from_secs(60))),
-            min_delay: Duration::from_millis(min_delay_ms),
+            configured_per_minute: requests_per_minute,
+            requests_per_minute: AtomicU32::new(requests_per_minute),
+            recent: Mutex::new(VecDeque::new()),
+            waiting: AtomicUsize::new(0),
+            granted: AtomicU64::new(0),
+            delayed: AtomicU64::new(0),
+            rejections: AtomicU64::new(0),
+            last_calibration: Mutex::new(None),
+            daily_limit: None,
+            today: Mutex::new((Utc::now().date_naive(), 0)),
         }
     }
 
+    /// Track usage against a provider's daily request allowance
+    pub fn with_daily_limit(mut self, daily_limit: Option<u32>) -> Self {
+        self.daily_limit = daily_limit;
+        self
+    }
+
+    /// Requests granted so far on the current UTC day. Reads the wall clock
+    /// rather than `clock::today()`: provider quotas reset on the real day even
+    /// when demo mode pins the service clock.
+    fn requests_today(&self) -> u32 {
+        let today = self.today.lock();
+        if today.0 == Utc::now().date_naive() { today.1 } else { 0 }
+    }
+
+    fn count_today(&self) {
+        let date = Utc::now().date_naive();
+        let mut today = self.today.lock();
+        if today.0 != date {
+            *today = (date, 0);
+        }
+        today.1 += 1;
+    }
+
+    /// Minimum delay between requests in the current budget
+    fn min_delay(&self) -> Duration {
+        Duration::from_millis(60_000 / self.requests_per_minute.load(Ordering::Relaxed) as u64)
+    }
+
     /// Acquire permission to make a request
     ///
     /// This will block until:
@@ -45,16 +132,19 @@ impl RateLimiter {
     ///
     /// Returns a guard that releases the permit when dropped.
     pub async fn acquire(&self) -> RateLimitGuard {
+        let _slot = QueueSlot::enter(&self.waiting);
+
         // Wait for a semaphore permit
         let permit = self.semaphore.clone().acquire_owned().await.unwrap();
 
         // Enforce minimum delay between requests
+        let min_delay = self.min_delay();
         let wait_time = {
             let last = self.last_request.lock();
             let elapsed = last.elapsed();
 
-            if elapsed < self.min_delay {
-                Some(self.min_delay - elapsed)
+            if elapsed < min_delay {
+                Some(min_delay - elapsed)
             } else {
                 None
             }
@@ -62,20 +152,78 @@ impl RateLimiter {
 
         // Sleep outside the lock if needed
         if let Some(delay) = wait_time {
+            self.delayed.fetch_add(1, Ordering::Relaxed);
             sleep(delay).await;
         }
 
         // Update last request time
-        *self.last_request.lock() = Instant::now();
+        let now = Instant::now();
+        *self.last_request.lock() = now;
+        self.granted.fetch_add(1, Ordering::Relaxed);
+        self.recent.lock().push_back(now);
+        self.count_today();
 
         RateLimitGuard { _permit: permit }
     }
 
+    /// Record a request the provider rejected as rate limited
+    pub fn record_rejection(&self) {
+        self.rejections.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Get the current utilization (for monitoring)
-    #[allow(dead_code)]
     pub fn available_permits(&self) -> usize {
         self.semaphore.available_permits()
     }
+
+    pub fn requests_per_minute(&self) -> u32 {
+        self.requests_per_minute.load(Ordering::Relaxed)
+    }
+
+    /// Requests still available in the trailing minute
+    fn tokens_remaining(&self) -> u32 {
+        let mut recent = self.recent.lock();
+        while recent.front().is_some_and(|t| t.elapsed() >= Duration::from_secs(60)) {
+            recent.pop_front();
+        }
+        self.requests_per_minute().saturating_sub(recent.len() as u32)
+    }
+
+    /// Adjust the per-minute budget to a provider's reported quota. A quota
+    /// without a plan limit keeps the current budget but is still recorded.
+    pub fn calibrate(&self, quota: &ProviderQuota) -> QuotaCalibration {
+        let previous = self.requests_per_minute();
+        let applied = quota.requests_per_minute.map(budget_for_limit).unwrap_or(previous);
+        self.requests_per_minute.store(applied, Ordering::Relaxed);
+
+        let calibration = QuotaCalibration {
+            provider: quota.provider.clone(),
+            reported_limit_per_minute: quota.requests_per_minute,
+            reported_remaining: quota.remaining,
+            previous_per_minute: previous,
+            applied_per_minute: applied,
+            calibrated_at: Utc::now(),
+        };
+        *self.last_calibration.lock() = Some(calibration.clone());
+        calibration
+    }
+
+    pub fn metrics(&self) -> RateLimiterMetrics {
+        RateLimiterMetrics {
+            max_concurrent: self.max_concurrent,
+            available_permits: self.available_permits(),
+            configured_per_minute: self.configured_per_minute,
+            requests_per_minute: self.requests_per_minute(),
+            tokens_remaining: self.tokens_remaining(),
+            queue_depth: self.waiting.load(Ordering::Relaxed),
+            requests_granted: self.granted.load(Ordering::Relaxed),
+            requests_delayed: self.delayed.load(Ordering::Relaxed),
+            rejections: self.rejections.load(Ordering::Relaxed),
+            last_calibration: self.last_calibration.lock().clone(),
+            requests_today: self.requests_today(),
+            daily_limit: self.daily_limit,
+        }
+    }
 }
 
 /// Guard that holds a rate limit permit
@@ -136,4 +284,47 @@ mod tests {
         // All should complete (third waits for first two)
         tokio::try_join!(handle1, handle2, handle3).unwrap();
     }
+
+    #[tokio::test]
+    async fn test_metrics_track_grants_and_tokens() {
+        let limiter = RateLimiter::new(2, 600); // 100ms delay
+
+        drop(limiter.acquire().await);
+        drop(limiter.acquire().await);
+        limiter.record_rejection();
+
+        let metrics = limiter.metrics();
+        assert_eq!(metrics.requests_granted, 2);
+        assert_eq!(metrics.requests_delayed, 1, "Second request waits for the minimum delay");
+        assert_eq!(metrics.tokens_remaining, 598);
+        assert_eq!(metrics.queue_depth, 0);
+        assert_eq!(metrics.rejections, 1);
+        assert_eq!(metrics.available_permits, 2);
+    }
+
+    #[test]
+    fn test_calibrate_applies_reported_limit_with_headroom() {
+        let limiter = RateLimiter::new(3, 8);
+        let quota = |limit| ProviderQuota {
+            provider: "twelvedata".to_string(),
+            requests_per_minute: limit,
+            remaining: Some(4),
+        };
+
+        let calibration = limiter.calibrate(&quota(Some(55)));
+        assert_eq!(calibration.previous_per_minute, 8);
+        assert_eq!(calibration.applied_per_minute, 49);
+        assert_eq!(limiter.min_delay(), Duration::from_millis(60_000 / 49));
+
+        // No reported limit keeps the current budget
+        assert_eq!(limiter.calibrate(&quota(None)).applied_per_minute, 49);
+
+        // A tiny plan never drops the budget to zero
+        assert_eq!(limiter.calibrate(&quota(Some(1))).applied_per_minute, 1);
+
+        let metrics = limiter.metrics();
+        assert_eq!(metrics.configured_per_minute, 8);
+        assert_eq!(metrics.requests_per_minute, 1);
+        assert_eq!(metrics.last_calibration.unwrap().reported_remaining, Some(4));
+    }
 }
@@ -0,0 +1,300 @@
+//! Rebalancing policy simulation.
+//!
+//! Replays the portfolio's history with today's weights as targets under several
+//! rebalancing policies (buy-and-hold, monthly, quarterly and a drift band) and
+//! compares them with a reference portfolio held exactly at target every day.
+//! Each rebalance trades back to target and pays `cost_bps` on the traded value.
+
+use std::collections::{BTreeSet, HashMap};
+
+use bigdecimal::ToPrimitive;
+use chrono::{Datelike, Duration, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{holding_snapshot_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{RebalancePolicy, RebalancePolicyResult, RebalanceSimulation};
+use crate::services::contribution_service::parse_period;
+use crate::services::clock;
+
+pub const DEFAULT_PERIOD: &str = "3y";
+pub const DEFAULT_BAND: f64 = 5.0;
+pub const DEFAULT_COST_BPS: f64 = 10.0;
+const TRADING_DAYS: f64 = 252.0;
+
+fn annualize(total_growth: f64, days: usize) -> f64 {
+    if days == 0 || total_growth <= 0.0 {
+        return 0.0;
+    }
+    (total_growth.powf(TRADING_DAYS / days as f64) - 1.0) * 100.0
+}
+
+fn annualized_std(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    variance.sqrt() * TRADING_DAYS.sqrt() * 100.0
+}
+
+fn should_rebalance(policy: RebalancePolicy, prev: NaiveDate, date: NaiveDate, drift: f64) -> bool {
+    match policy {
+        RebalancePolicy::BuyAndHold => false,
+        RebalancePolicy::Monthly => date.month() != prev.month() || date.year() != prev.year(),
+        RebalancePolicy::Quarterly => (date.month() - 1) / 3 != (prev.month() - 1) / 3 || date.year() != prev.year(),
+        RebalancePolicy::Threshold { band } => drift > band / 100.0,
+    }
+}
+
+/// Daily values of a policy starting from 1.0, plus rebalance count, traded value and costs
+struct PolicyPath {
+    values: Vec<f64>,
+    rebalances: usize,
+    traded: f64,
+    costs: f64,
+}
+
+fn run_policy(dates: &[NaiveDate], closes: &[Vec<f64>], targets: &[f64], policy: RebalancePolicy, cost_bps: f64) -> PolicyPath {
+    let mut units: Vec<f64> = targets.iter().zip(&closes[0]).map(|(w, p)| w / p).collect();
+    let mut values = vec![1.0];
+    let (mut rebalances, mut traded, mut costs) = (0, 0.0, 0.0);
+
+    for t in 1..dates.len() {
+        let position_values: Vec<f64> = units.iter().zip(&closes[t]).map(|(u, p)| u * p).collect();
+        let mut value: f64 = position_values.iter().sum();
+        let drift = position_values
+            .iter()
+            .zip(targets)
+            .map(|(v, w)| (v / value - w).abs())
+            .fold(0.0, f64::max);
+
+        if should_rebalance(policy, dates[t - 1], dates[t], drift) {
+            let trade: f64 = position_values.iter().zip(targets).map(|(v, w)| (w * value - v).abs()).sum::<f64>() / 2.0;
+            let cost = trade * 2.0 * cost_bps / 10_000.0;
+            value -= cost;
+            units = targets.iter().zip(&closes[t]).map(|(w, p)| w * value / p).collect();
+            rebalances += 1;
+            traded += trade;
+            costs += cost;
+        }
+        values.push(value);
+    }
+    PolicyPath { values, rebalances, traded, costs }
+}
+
+fn daily_returns(values: &[f64]) -> Vec<f64> {
+    values.windows(2).map(|w| w[1] / w[0] - 1.0).collect()
+}
+
+/// Simulate `policies` over aligned closes (one row per date, one column per target)
+pub fn simulate(
+    dates: &[NaiveDate],
+    closes: &[Vec<f64>],
+    targets: &[f64],
+    policies: &[RebalancePolicy],
+    cost_bps: f64,
+) -> Vec<RebalancePolicyResult> {
+    if dates.len() < 2 || targets.is_empty() {
+        return vec![];
+    }
+    let days = dates.len() - 1;
+
+    // Reference: held exactly at target each day, without costs
+    let reference: Vec<f64> = closes
+        .windows(2)
+        .map(|w| targets.iter().zip(w[0].iter().zip(&w[1])).map(|(t, (a, b))| t * (b / a - 1.0)).sum())
+        .collect();
+    let reference_growth: f64 = reference.iter().map(|r| 1.0 + r).product();
+    let reference_annual = annualize(reference_growth, days);
+
+    policies
+        .iter()
+        .map(|&policy| {
+            let path = run_policy(dates, closes, targets, policy, cost_bps);
+            let returns = daily_returns(&path.values);
+            let differences: Vec<f64> = returns.iter().zip(&reference).map(|(a, b)| a - b).collect();
+            let growth = *path.values.last().unwrap_or(&1.0);
+            let average_value = path.values.iter().sum::<f64>() / path.values.len() as f64;
+            let turnover = path.traded / average_value * 100.0;
+            let annualized_return = annualize(growth, days);
+            RebalancePolicyResult {
+                policy,
+                rebalances: path.rebalances,
+                total_return: (growth - 1.0) * 100.0,
+                annualized_return,
+                annualized_volatility: annualized_std(&returns),
+                turnover,
+                annual_turnover: turnover * TRADING_DAYS / days as f64,
+                costs: path.costs * 100.0,
+                tracking_difference: annualized_return - reference_annual,
+                tracking_error: annualized_std(&differences),
+            }
+        })
+        .collect()
+}
+
+/// Closes forward-filled onto the union of dates, starting once every ticker has a price
+pub fn align(tickers: &[String], prices: &HashMap<String, Vec<(NaiveDate, f64)>>) -> (Vec<NaiveDate>, Vec<Vec<f64>>) {
+    let all_dates: BTreeSet<NaiveDate> = tickers
+        .iter()
+        .filter_map(|t| prices.get(t))
+        .flat_map(|s| s.iter().map(|(d, _)| *d))
+        .collect();
+    let lookups: Vec<HashMap<NaiveDate, f64>> =
+        tickers.iter().map(|t| prices.get(t).map(|s| s.iter().copied().collect()).unwrap_or_default()).collect();
+
+    let mut last: Vec<Option<f64>> = vec![None; tickers.len()];
+    let mut dates = Vec::new();
+    let mut rows = Vec::new();
+    for date in all_dates {
+        for (i, lookup) in lookups.iter().enumerate() {
+            if let Some(p) = lookup.get(&date).filter(|p| **p > 0.0) {
+                last[i] = Some(*p);
+            }
+        }
+        if let Some(row) = last.iter().copied().collect::<Option<Vec<f64>>>() {
+            dates.push(date);
+            rows.push(row);
+        }
+    }
+    (dates, rows)
+}
+
+pub async fn simulate_portfolio(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    period: Option<&str>,
+    band: Option<f64>,
+    cost_bps: Option<f64>,
+) -> Result<RebalanceSimulation, AppError> {
+    let period = period.unwrap_or(DEFAULT_PERIOD);
+    let days = parse_period(period)?;
+    let band = band.unwrap_or(DEFAULT_BAND);
+    if !(band > 0.0 && band <= 50.0) {
+        return Err(AppError::Validation("band must be between 0 and 50 percentage points".to_string()));
+    }
+    let cost_bps = cost_bps.unwrap_or(DEFAULT_COST_BPS);
+    if !(0.0..=500.0).contains(&cost_bps) {
+        return Err(AppError::Validation("cost_bps must be between 0 and 500".to_string()));
+    }
+
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id).await?;
+    let mut values: HashMap<String, f64> = HashMap::new();
+    for holding in holdings.iter().filter(|h| !h.ticker.is_empty()) {
+        *values.entry(holding.ticker.clone()).or_insert(0.0) += holding.market_value.to_f64().unwrap_or(0.0);
+    }
+    values.retain(|_, v| *v > 0.0);
+    let mut tickers: Vec<String> = values.keys().cloned().collect();
+    tickers.sort();
+
+    let start = clock::today() - Duration::days(days);
+    let windows = price_queries::fetch_window_batch(pool, &tickers, days + 1).await?;
+    let prices: HashMap<String, Vec<(NaiveDate, f64)>> = windows
+        .into_iter()
+        .map(|(ticker, points)| {
+            let series: Vec<(NaiveDate, f64)> = points
+                .iter()
+                .filter(|p| p.date >= start)
+                .filter_map(|p| p.close_price.to_f64().map(|c| (p.date, c)))
+                .collect();
+            (ticker, series)
+        })
+        .filter(|(_, series)| !series.is_empty())
+        .collect();
+
+    let missing_prices: Vec<String> = tickers.iter().filter(|t| !prices.contains_key(*t)).cloned().collect();
+    tickers.retain(|t| prices.contains_key(t));
+    let total: f64 = tickers.iter().map(|t| values[t]).sum();
+    let target_weights: Vec<(String, f64)> = tickers.iter().map(|t| (t.clone(), values[t] / total)).collect();
+    let targets: Vec<f64> = target_weights.iter().map(|(_, w)| *w).collect();
+
+    let (dates, closes) = align(&tickers, &prices);
+    let policies = [
+        RebalancePolicy::BuyAndHold,
+        RebalancePolicy::Monthly,
+        RebalancePolicy::Quarterly,
+        RebalancePolicy::Threshold { band },
+    ];
+    let results = simulate(&dates, &closes, &targets, &policies, cost_bps);
+    let recommended_policy = results
+        .iter()
+        .min_by(|a, b| {
+            let score = |r: &RebalancePolicyResult| r.tracking_difference.abs() + r.tracking_error;
+            score(a).total_cmp(&score(b))
+        })
+        .map(|r| r.policy);
+
+    Ok(RebalanceSimulation {
+        portfolio_id,
+        period: period.to_string(),
+        start_date: dates.first().copied(),
+        end_date: dates.last().copied(),
+        target_weights,
+        cost_bps,
+        results,
+        recommended_policy,
+        missing_prices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dates(n: usize) -> Vec<NaiveDate> {
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        (0..n).map(|i| start + Duration::days(i as i64 * 10)).collect()
+    }
+
+    #[test]
+    fn test_constant_prices_never_drift() {
+        let closes = vec![vec![100.0, 50.0]; 40];
+        let results = simulate(&dates(40), &closes, &[0.6, 0.4], &[RebalancePolicy::Threshold { band: 5.0 }], 10.0);
+        assert_eq!(results[0].rebalances, 0);
+        assert!(results[0].total_return.abs() < 1e-12);
+        assert!(results[0].tracking_error.abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_policies_trade_as_expected() {
+        // A trends up, B stays flat, so A's weight drifts steadily
+        let closes: Vec<Vec<f64>> = (0..40).map(|i| vec![100.0 * (1.0 + 0.02 * i as f64), 100.0]).collect();
+        let policies = [
+            RebalancePolicy::BuyAndHold,
+            RebalancePolicy::Monthly,
+            RebalancePolicy::Threshold { band: 5.0 },
+        ];
+        let results = simulate(&dates(40), &closes, &[0.5, 0.5], &policies, 10.0);
+
+        let buy_and_hold = &results[0];
+        assert_eq!(buy_and_hold.rebalances, 0);
+        assert_eq!(buy_and_hold.costs, 0.0);
+        // 0.5 × 1.78 + 0.5 × 1.0
+        assert!((buy_and_hold.total_return - 39.0).abs() < 1e-9);
+
+        let monthly = &results[1];
+        assert!(monthly.rebalances >= 10);
+        assert!(monthly.costs > 0.0);
+        assert!(monthly.tracking_error < buy_and_hold.tracking_error);
+
+        let threshold = &results[2];
+        assert!(threshold.rebalances > 0 && threshold.rebalances < monthly.rebalances);
+        assert!(threshold.turnover > 0.0);
+    }
+
+    #[test]
+    fn test_align_waits_for_every_ticker() {
+        let d = dates(3);
+        let prices: HashMap<String, Vec<(NaiveDate, f64)>> = [
+            ("A".to_string(), vec![(d[0], 1.0), (d[1], 2.0), (d[2], 3.0)]),
+            ("B".to_string(), vec![(d[1], 10.0)]),
+        ]
+        .into_iter()
+        .collect();
+        let (aligned, rows) = align(&["A".to_string(), "B".to_string()], &prices);
+        assert_eq!(aligned, vec![d[1], d[2]]);
+        assert_eq!(rows, vec![vec![2.0, 10.0], vec![3.0, 10.0]]);
+    }
+}
@@ -0,0 +1,118 @@
+//! Position risk badges for list views.
+//!
+//! Badges are assembled from what is already stored: the portfolio risk cache
+//! kept warm by the risk job and the stored daily closes. Nothing here calls a
+//! price provider or runs a risk assessment, so list screens render instantly;
+//! positions the risk job hasn't assessed yet get a badge without a score.
+
+use std::collections::HashMap;
+
+use bigdecimal::ToPrimitive;
+use sqlx::PgPool;
+
+use crate::db::risk_cache_queries;
+use crate::db::tenant::TenantScope;
+use crate::db::{analytics_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{PositionRiskBadge, PositionRiskContribution, PricePoint};
+
+/// Window and benchmark of the risk cache entry the risk job keeps warm
+const CACHED_RISK_DAYS: i32 = 90;
+const CACHED_RISK_BENCHMARK: &str = "SPY";
+
+/// Badges for the positions in the portfolio's latest holdings, largest first
+pub async fn position_risk_badges(pool: &PgPool, tenant: &TenantScope) -> Result<Vec<PositionRiskBadge>, AppError> {
+    let holdings = analytics_queries::fetch_allocations_at_latest_date(pool, tenant.portfolio_id()).await?;
+    let tickers: Vec<String> = holdings.iter().map(|h| h.ticker.clone()).collect();
+
+    // Any cached assessment will do, stale or not; placeholder rows hold `{}`
+    let assessed: Vec<PositionRiskContribution> =
+        risk_cache_queries::fetch_risk_cache(pool, tenant, CACHED_RISK_DAYS, CACHED_RISK_BENCHMARK)
+            .await?
+            .and_then(|entry| entry.risk_data.get("position_risks").cloned())
+            .and_then(|risks| serde_json::from_value(risks).ok())
+            .unwrap_or_default();
+
+    let closes = price_queries::fetch_last_two_batch(pool, &tickers).await?;
+
+    Ok(risk_badges(&tickers, &assessed, &closes))
+}
+
+/// One badge per ticker, in the order given
+pub fn risk_badges(
+    tickers: &[String],
+    assessed: &[PositionRiskContribution],
+    closes: &HashMap<String, Vec<PricePoint>>,
+) -> Vec<PositionRiskBadge> {
+    let by_ticker: HashMap<&str, &PositionRiskContribution> =
+        assessed.iter().map(|p| (p.ticker.as_str(), p)).collect();
+
+    tickers
+        .iter()
+        .map(|ticker| {
+            let assessment = by_ticker.get(ticker.as_str()).map(|p| &p.risk_assessment);
+            PositionRiskBadge {
+                ticker: ticker.clone(),
+                risk_score: assessment.map(|a| a.risk_score),
+                risk_level: assessment.map(|a| a.risk_level.clone()),
+                day_change: closes.get(ticker).and_then(|points| day_change(points)),
+            }
+        })
+        .collect()
+}
+
+/// Percent change between the last two closes (oldest first)
+fn day_change(points: &[PricePoint]) -> Option<f64> {
+    let [previous, latest] = points else {
+        return None;
+    };
+    let previous = previous.close_price.to_f64()?;
+    let latest = latest.close_price.to_f64()?;
+    (previous > 0.0).then(|| (latest - previous) / previous * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use chrono::{NaiveDate, Utc};
+    use uuid::Uuid;
+
+    fn close(ticker: &str, day: u32, price: &str) -> PricePoint {
+        PricePoint {
+            id: Uuid::new_v4(),
+            ticker: ticker.to_string(),
+            date: NaiveDate::from_ymd_opt(2026, 3, day).unwrap(),
+            close_price: price.parse::<BigDecimal>().unwrap(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_risk_badges_from_cache_and_closes() {
+        let assessed: Vec<PositionRiskContribution> = serde_json::from_value(serde_json::json!([{
+            "ticker": "AAPL", "market_value": 5000.0, "weight": 0.5,
+            "risk_assessment": {
+                "ticker": "AAPL", "risk_score": 42.5, "risk_level": "moderate",
+                "metrics": {"volatility": 24.0, "max_drawdown": -18.0}
+            }
+        }]))
+        .unwrap();
+        let closes = HashMap::from([
+            ("AAPL".to_string(), vec![close("AAPL", 5, "200"), close("AAPL", 6, "205")]),
+            ("NEW".to_string(), vec![close("NEW", 6, "10")]),
+        ]);
+        let tickers = vec!["AAPL".to_string(), "NEW".to_string()];
+
+        let badges = risk_badges(&tickers, &assessed, &closes);
+        assert_eq!(badges[0].risk_score, Some(42.5));
+        assert_eq!(badges[0].risk_level, Some(crate::models::RiskLevel::Moderate));
+        assert!((badges[0].day_change.unwrap() - 2.5).abs() < 1e-9);
+
+        // Not yet assessed, and only one close stored
+        assert_eq!(
+            badges[1],
+            PositionRiskBadge { ticker: "NEW".to_string(), risk_score: None, risk_level: None, day_change: None }
+        );
+    }
+}
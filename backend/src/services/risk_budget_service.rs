@@ -0,0 +1,508 @@
+//! Portfolio risk budgets.
+//!
+//! A budget caps the portfolio's weighted volatility or 95% VaR, optionally with
+//! sub-budgets per sleeve. A sleeve is the set of positions carrying a tag; a
+//! position belongs to the first budgeted sleeve whose tag it carries. Risk is
+//! additive like the rest of the risk module: a position contributes its weight ×
+//! its own volatility (or VaR), so sleeve risks sum to the portfolio figure.
+
+use std::collections::{HashMap, HashSet};
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{annotation_queries, risk_budget_queries};
+use crate::errors::AppError;
+use crate::models::{
+    AdjustmentAction, ExpectedImpact, OptimizationRecommendation, PortfolioRisk, PositionAdjustment, PositionAnnotation,
+    PositionRisk, RecommendationType, RiskBudget, RiskBudgetMetric, RiskBudgetUtilization, SetRiskBudget, Severity,
+    SleeveBudget, SleeveUtilization,
+};
+
+pub const UNASSIGNED_SLEEVE: &str = "unassigned";
+const MAX_SLEEVES: usize = 20;
+
+fn validate(mut input: SetRiskBudget) -> Result<SetRiskBudget, AppError> {
+    if !input.total_budget.is_finite() || input.total_budget <= 0.0 {
+        return Err(AppError::Validation("total_budget must be a positive number".to_string()));
+    }
+    if input.sleeves.len() > MAX_SLEEVES {
+        return Err(AppError::Validation(format!("At most {} sleeves are allowed", MAX_SLEEVES)));
+    }
+    let mut seen = HashSet::new();
+    for sleeve in &mut input.sleeves {
+        sleeve.tag = sleeve.tag.trim().to_lowercase();
+        if sleeve.tag.is_empty() || sleeve.tag == UNASSIGNED_SLEEVE {
+            return Err(AppError::Validation("Sleeve tags must be non-empty and not 'unassigned'".to_string()));
+        }
+        if !seen.insert(sleeve.tag.clone()) {
+            return Err(AppError::Validation(format!("Sleeve '{}' is listed more than once", sleeve.tag)));
+        }
+        if !sleeve.budget.is_finite() || sleeve.budget <= 0.0 || sleeve.budget > input.total_budget {
+            return Err(AppError::Validation(format!(
+                "Budget for sleeve '{}' must be positive and no larger than the total budget",
+                sleeve.tag
+            )));
+        }
+    }
+    Ok(input)
+}
+
+pub async fn set_budget(pool: &PgPool, portfolio_id: Uuid, input: SetRiskBudget) -> Result<RiskBudget, AppError> {
+    let input = validate(input)?;
+    Ok(risk_budget_queries::upsert(pool, portfolio_id, input.metric.as_str(), input.total_budget, &input.sleeves).await?)
+}
+
+/// Risk of one position per unit of weight, in the budget's metric
+pub fn position_metric(metric: RiskBudgetMetric, risk: &PositionRisk) -> Option<f64> {
+    match metric {
+        RiskBudgetMetric::Volatility => Some(risk.volatility),
+        RiskBudgetMetric::Var95 => risk.var_95.map(f64::abs),
+    }
+}
+
+/// Map each tagged ticker to the first budgeted sleeve it belongs to
+pub fn sleeve_assignments(sleeves: &[SleeveBudget], annotations: &[PositionAnnotation]) -> HashMap<String, String> {
+    let mut tags_by_ticker: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for annotation in annotations {
+        tags_by_ticker
+            .entry(annotation.ticker.as_str())
+            .or_default()
+            .extend(annotation.tags.iter().map(String::as_str));
+    }
+    tags_by_ticker
+        .into_iter()
+        .filter_map(|(ticker, tags)| {
+            sleeves
+                .iter()
+                .find(|s| tags.contains(s.tag.as_str()))
+                .map(|s| (ticker.to_string(), s.tag.clone()))
+        })
+        .collect()
+}
+
+/// Everything needed to evaluate a budget against a set of positions
+#[derive(Debug, Clone)]
+pub struct BudgetContext {
+    pub metric: RiskBudgetMetric,
+    pub total_budget: f64,
+    pub sleeves: Vec<SleeveBudget>,
+    /// ticker -> sleeve tag; tickers not listed are unassigned
+    pub assignments: HashMap<String, String>,
+    /// ticker -> (weight, risk per unit weight)
+    pub positions: HashMap<String, (f64, f64)>,
+}
+
+impl BudgetContext {
+    fn sleeve_of(&self, ticker: &str) -> &str {
+        self.assignments.get(ticker).map_or(UNASSIGNED_SLEEVE, String::as_str)
+    }
+
+    fn sleeve_budget(&self, sleeve: &str) -> Option<f64> {
+        self.sleeves.iter().find(|s| s.tag == sleeve).map(|s| s.budget)
+    }
+
+    fn sleeve_risk(&self, sleeve: &str) -> f64 {
+        self.positions
+            .iter()
+            .filter(|(ticker, _)| self.sleeve_of(ticker) == sleeve)
+            .map(|(_, (w, r))| w * r)
+            .sum()
+    }
+
+    fn total_risk(&self) -> f64 {
+        self.positions.values().map(|(w, r)| w * r).sum()
+    }
+
+    pub fn utilization(&self) -> RiskBudgetUtilization {
+        let total_risk = self.total_risk();
+        let mut names: Vec<&str> = self.sleeves.iter().map(|s| s.tag.as_str()).collect();
+        if self.positions.keys().any(|t| self.sleeve_of(t) == UNASSIGNED_SLEEVE) {
+            names.push(UNASSIGNED_SLEEVE);
+        }
+
+        let sleeves = names
+            .into_iter()
+            .map(|name| {
+                let budget = self.sleeve_budget(name);
+                let risk = self.sleeve_risk(name);
+                let mut tickers: Vec<String> = self
+                    .positions
+                    .keys()
+                    .filter(|t| self.sleeve_of(t) == name)
+                    .cloned()
+                    .collect();
+                tickers.sort();
+                SleeveUtilization {
+                    sleeve: name.to_string(),
+                    budget,
+                    risk,
+                    utilization_pct: budget.map(|b| risk / b * 100.0),
+                    over_budget: budget.is_some_and(|b| risk > b),
+                    tickers,
+                }
+            })
+            .collect();
+
+        RiskBudgetUtilization {
+            metric: self.metric,
+            total_budget: self.total_budget,
+            total_risk,
+            utilization_pct: total_risk / self.total_budget * 100.0,
+            over_budget: total_risk > self.total_budget,
+            sleeves,
+        }
+    }
+}
+
+async fn load_context(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    position_risks: impl Fn(RiskBudgetMetric) -> HashMap<String, (f64, f64)>,
+) -> Result<Option<BudgetContext>, AppError> {
+    let Some(budget) = risk_budget_queries::fetch(pool, portfolio_id).await? else {
+        return Ok(None);
+    };
+    let annotations = annotation_queries::fetch_for_portfolio(pool, portfolio_id).await?;
+    let metric = RiskBudgetMetric::from_string(&budget.metric);
+    let sleeves = budget.sleeves.0;
+    Ok(Some(BudgetContext {
+        metric,
+        total_budget: budget.total_budget,
+        assignments: sleeve_assignments(&sleeves, &annotations),
+        sleeves,
+        positions: position_risks(metric),
+    }))
+}
+
+/// Budget utilization for a computed portfolio risk, or `None` without a budget
+pub async fn utilization_for_risk(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    risk: &PortfolioRisk,
+) -> Result<Option<RiskBudgetUtilization>, AppError> {
+    let context = load_context(pool, portfolio_id, |metric| {
+        risk.position_risks
+            .iter()
+            .filter_map(|p| {
+                position_metric(metric, &p.risk_assessment.metrics).map(|r| (p.ticker.clone(), (p.weight, r)))
+            })
+            .collect()
+    })
+    .await?;
+    Ok(context.map(|c| c.utilization()))
+}
+
+/// Load the budget context for the rebalancer from per-ticker weights and metrics
+pub async fn context_for_positions(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    weights: &HashMap<String, f64>,
+    metrics: &HashMap<String, PositionRisk>,
+) -> Result<Option<BudgetContext>, AppError> {
+    load_context(pool, portfolio_id, |metric| {
+        weights
+            .iter()
+            .filter_map(|(ticker, w)| {
+                metrics
+                    .get(ticker)
+                    .and_then(|m| position_metric(metric, m))
+                    .map(|r| (ticker.clone(), (*w, r)))
+            })
+            .collect()
+    })
+    .await
+}
+
+fn scale_down(ticker: &str, weight: f64, factor: f64, total_value: f64) -> PositionAdjustment {
+    let current_value = weight * total_value;
+    let recommended_value = current_value * factor;
+    PositionAdjustment {
+        ticker: ticker.to_string(),
+        holding_name: None,
+        current_value,
+        current_weight: weight * 100.0,
+        recommended_value,
+        recommended_weight: weight * factor * 100.0,
+        action: AdjustmentAction::Sell,
+        amount_change: recommended_value - current_value,
+        shares_change: None,
+    }
+}
+
+fn budget_recommendation(
+    id: String,
+    title: String,
+    rationale: String,
+    risk: f64,
+    budget: f64,
+    adjustments: Vec<PositionAdjustment>,
+) -> OptimizationRecommendation {
+    OptimizationRecommendation {
+        id,
+        recommendation_type: RecommendationType::ReduceRisk,
+        severity: if risk > budget * 1.2 { Severity::High } else { Severity::Warning },
+        title,
+        rationale,
+        affected_positions: adjustments,
+        expected_impact: ExpectedImpact {
+            risk_score_before: 0.0,
+            risk_score_after: 0.0,
+            risk_score_change: 0.0,
+            volatility_before: 0.0,
+            volatility_after: 0.0,
+            volatility_change: budget - risk,
+            sharpe_before: None,
+            sharpe_after: None,
+            sharpe_change: None,
+            diversification_before: 0.0,
+            diversification_after: 0.0,
+            diversification_change: 0.0,
+            max_drawdown_before: 0.0,
+            max_drawdown_after: 0.0,
+        },
+        suggested_actions: vec!["Hold the proceeds in cash or lower-risk positions outside the sleeve".to_string()],
+        rounding: None,
+    }
+}
+
+/// Make suggested trades respect the budget.
+///
+/// Buys are limited to the headroom left in their sleeve and in the total budget
+/// (dropped to HOLD when there is none), and each sleeve or total budget that is
+/// already exceeded gets a recommendation scaling its positions back within budget.
+pub fn apply_budget_constraints(
+    context: &BudgetContext,
+    total_value: f64,
+    recommendations: &mut Vec<OptimizationRecommendation>,
+) {
+    if total_value <= 0.0 {
+        return;
+    }
+    let mut sleeve_risk: HashMap<String, f64> = HashMap::new();
+    for ticker in context.positions.keys() {
+        let sleeve = context.sleeve_of(ticker).to_string();
+        sleeve_risk.entry(sleeve.clone()).or_insert_with(|| context.sleeve_risk(&sleeve));
+    }
+    let mut total_risk = context.total_risk();
+    let metric = context.metric.as_str();
+
+    for recommendation in recommendations.iter_mut() {
+        let mut notes = Vec::new();
+        for adjustment in &mut recommendation.affected_positions {
+            if adjustment.action != AdjustmentAction::Buy {
+                continue;
+            }
+            let Some(&(_, unit_risk)) = context.positions.get(&adjustment.ticker) else { continue };
+            let added_weight = (adjustment.recommended_value - adjustment.current_value) / total_value;
+            if unit_risk <= 0.0 || added_weight <= 0.0 {
+                continue;
+            }
+
+            let sleeve = context.sleeve_of(&adjustment.ticker).to_string();
+            let current_sleeve_risk = sleeve_risk.get(&sleeve).copied().unwrap_or(0.0);
+            let mut headroom = (context.total_budget - total_risk).max(0.0);
+            let mut limit = "total";
+            if let Some(budget) = context.sleeve_budget(&sleeve) {
+                let sleeve_headroom = (budget - current_sleeve_risk).max(0.0);
+                if sleeve_headroom < headroom {
+                    headroom = sleeve_headroom;
+                    limit = "sleeve";
+                }
+            }
+
+            let allowed_weight = added_weight.min(headroom / unit_risk);
+            if allowed_weight < added_weight {
+                adjustment.recommended_value = adjustment.current_value + allowed_weight * total_value;
+                adjustment.recommended_weight = adjustment.recommended_value / total_value * 100.0;
+                adjustment.amount_change = adjustment.recommended_value - adjustment.current_value;
+                adjustment.shares_change = None;
+                if allowed_weight <= 0.0 {
+                    adjustment.action = AdjustmentAction::Hold;
+                }
+                notes.push(if limit == "sleeve" {
+                    format!("Buy of {} limited by the '{}' sleeve {} budget", adjustment.ticker, sleeve, metric)
+                } else {
+                    format!("Buy of {} limited by the portfolio {} budget", adjustment.ticker, metric)
+                });
+            }
+
+            let added_risk = allowed_weight * unit_risk;
+            total_risk += added_risk;
+            *sleeve_risk.entry(sleeve).or_insert(0.0) += added_risk;
+        }
+        recommendation.suggested_actions.extend(notes);
+    }
+
+    let utilization = context.utilization();
+    for sleeve in utilization.sleeves.iter().filter(|s| s.over_budget) {
+        let Some(budget) = sleeve.budget else { continue };
+        let factor = budget / sleeve.risk;
+        let adjustments = sleeve
+            .tickers
+            .iter()
+            .filter_map(|t| context.positions.get(t).map(|(w, _)| scale_down(t, *w, factor, total_value)))
+            .collect();
+        recommendations.push(budget_recommendation(
+            format!("risk-budget-{}", sleeve.sleeve),
+            format!("'{}' Sleeve Exceeds Its Risk Budget", sleeve.sleeve),
+            format!(
+                "The '{}' sleeve carries {:.2} of {} against a budget of {:.2} ({:.0}% utilized). \
+                 Reducing its positions by {:.0}% brings it back within budget.",
+                sleeve.sleeve,
+                sleeve.risk,
+                metric,
+                budget,
+                sleeve.utilization_pct.unwrap_or(0.0),
+                (1.0 - factor) * 100.0
+            ),
+            sleeve.risk,
+            budget,
+            adjustments,
+        ));
+    }
+
+    if utilization.over_budget {
+        let factor = utilization.total_budget / utilization.total_risk;
+        let mut tickers: Vec<&String> = context.positions.keys().collect();
+        tickers.sort();
+        let adjustments = tickers
+            .into_iter()
+            .map(|t| scale_down(t, context.positions[t].0, factor, total_value))
+            .collect();
+        recommendations.push(budget_recommendation(
+            "risk-budget-total".to_string(),
+            "Portfolio Exceeds Its Risk Budget".to_string(),
+            format!(
+                "The portfolio carries {:.2} of {} against a budget of {:.2} ({:.0}% utilized).",
+                utilization.total_risk, metric, utilization.total_budget, utilization.utilization_pct
+            ),
+            utilization.total_risk,
+            utilization.total_budget,
+            adjustments,
+        ));
+    }
+}
+
+pub async fn get_budget(pool: &PgPool, portfolio_id: Uuid) -> Result<RiskBudget, AppError> {
+    risk_budget_queries::fetch(pool, portfolio_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No risk budget set for portfolio {}", portfolio_id)))
+}
+
+pub async fn delete_budget(pool: &PgPool, portfolio_id: Uuid) -> Result<(), AppError> {
+    if !risk_budget_queries::delete(pool, portfolio_id).await? {
+        return Err(AppError::NotFound(format!("No risk budget set for portfolio {}", portfolio_id)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> BudgetContext {
+        BudgetContext {
+            metric: RiskBudgetMetric::Volatility,
+            total_budget: 20.0,
+            sleeves: vec![SleeveBudget { tag: "growth".to_string(), budget: 10.0 }],
+            assignments: [("NVDA".to_string(), "growth".to_string())].into_iter().collect(),
+            positions: [
+                ("NVDA".to_string(), (0.4, 40.0)),
+                ("JNJ".to_string(), (0.6, 10.0)),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    fn buy(ticker: &str, current: f64, recommended: f64) -> PositionAdjustment {
+        PositionAdjustment {
+            ticker: ticker.to_string(),
+            holding_name: None,
+            current_value: current,
+            current_weight: current / 10.0,
+            recommended_value: recommended,
+            recommended_weight: recommended / 10.0,
+            action: AdjustmentAction::Buy,
+            amount_change: recommended - current,
+            shares_change: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_normalizes_and_rejects() {
+        let input = SetRiskBudget {
+            metric: RiskBudgetMetric::Volatility,
+            total_budget: 15.0,
+            sleeves: vec![SleeveBudget { tag: " Growth ".to_string(), budget: 5.0 }],
+        };
+        assert_eq!(validate(input.clone()).unwrap().sleeves[0].tag, "growth");
+
+        let mut too_big = input.clone();
+        too_big.sleeves[0].budget = 20.0;
+        assert!(validate(too_big).is_err());
+
+        let mut duplicate = input;
+        duplicate.sleeves.push(SleeveBudget { tag: "growth".to_string(), budget: 1.0 });
+        assert!(validate(duplicate).is_err());
+    }
+
+    #[test]
+    fn test_utilization_per_sleeve() {
+        let u = context().utilization();
+        assert!((u.total_risk - 22.0).abs() < 1e-9);
+        assert!(u.over_budget);
+
+        let growth = &u.sleeves[0];
+        assert_eq!(growth.sleeve, "growth");
+        assert!((growth.risk - 16.0).abs() < 1e-9);
+        assert_eq!(growth.utilization_pct, Some(160.0));
+        assert!(growth.over_budget);
+
+        let unassigned = &u.sleeves[1];
+        assert_eq!(unassigned.sleeve, UNASSIGNED_SLEEVE);
+        assert_eq!(unassigned.budget, None);
+        assert_eq!(unassigned.tickers, vec!["JNJ".to_string()]);
+    }
+
+    #[test]
+    fn test_buys_respect_budgets() {
+        let mut ctx = context();
+        ctx.total_budget = 30.0;
+        ctx.sleeves[0].budget = 18.0;
+
+        let mut recommendations = vec![budget_recommendation(
+            "test".to_string(),
+            String::new(),
+            String::new(),
+            0.0,
+            0.0,
+            vec![buy("NVDA", 400.0, 600.0), buy("JNJ", 600.0, 1000.0)],
+        )];
+        apply_budget_constraints(&ctx, 1000.0, &mut recommendations);
+
+        // Growth headroom 2.0 at 40 per unit weight: 0.05 of 1000 = 50
+        let nvda = &recommendations[0].affected_positions[0];
+        assert!((nvda.recommended_value - 450.0).abs() < 1e-9);
+        assert_eq!(nvda.action, AdjustmentAction::Buy);
+
+        // Total headroom left 30 - 24 = 6.0 at 10 per unit weight: 0.6 -> capped buy of 400 passes
+        let jnj = &recommendations[0].affected_positions[1];
+        assert!((jnj.recommended_value - 1000.0).abs() < 1e-9);
+        assert_eq!(recommendations.len(), 1);
+    }
+
+    #[test]
+    fn test_over_budget_sleeve_gets_reduction() {
+        let mut recommendations = Vec::new();
+        apply_budget_constraints(&context(), 1000.0, &mut recommendations);
+
+        assert_eq!(recommendations.len(), 2);
+        let sleeve = &recommendations[0];
+        assert_eq!(sleeve.id, "risk-budget-growth");
+        let nvda = &sleeve.affected_positions[0];
+        assert!((nvda.recommended_weight - 25.0).abs() < 1e-9);
+        assert_eq!(recommendations[1].id, "risk-budget-total");
+    }
+}
@@ -0,0 +1,171 @@
+//! Memoized ticker risk assessments, shared by every caller of
+//! `risk_service::compute_risk_metrics`.
+//!
+//! The same ticker and window are assessed by the position and portfolio risk
+//! endpoints, the risk snapshot and portfolio risk jobs, and the optimizer.
+//! Each assessment is remembered with the [`DataVersion`] it was computed from
+//! (the last price date of the ticker and benchmark, and the scoring model
+//! version), and a lookup only hits while that version still matches, so a new
+//! close makes the old entry unreachable. Price refreshes that rewrite existing
+//! closes call [`invalidate`] for the ticker.
+
+use std::sync::OnceLock;
+
+use chrono::NaiveDate;
+use dashmap::DashMap;
+
+use crate::models::risk::RiskAssessment;
+use crate::models::PricePoint;
+use crate::services::risk_service::{BETA_BENCHMARKS, CURRENT_SCORING_VERSION};
+
+/// The memo is cleared when it grows past this many entries
+const MAX_ENTRIES: usize = 20_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MemoKey {
+    ticker: String,
+    days: i64,
+    benchmark: String,
+    risk_free_bits: u64,
+}
+
+impl MemoKey {
+    fn new(ticker: &str, days: i64, benchmark: &str, risk_free_rate: f64) -> Self {
+        Self {
+            ticker: ticker.to_string(),
+            days,
+            benchmark: benchmark.to_string(),
+            risk_free_bits: risk_free_rate.to_bits(),
+        }
+    }
+}
+
+/// Data an assessment was computed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataVersion {
+    pub last_price_date: NaiveDate,
+    pub benchmark_price_date: NaiveDate,
+    pub model_version: i32,
+}
+
+impl DataVersion {
+    /// Version of the given ticker and benchmark windows; `None` when either is empty
+    pub fn of(series: &[PricePoint], bench: &[PricePoint]) -> Option<Self> {
+        Some(Self {
+            last_price_date: series.iter().map(|p| p.date).max()?,
+            benchmark_price_date: bench.iter().map(|p| p.date).max()?,
+            model_version: CURRENT_SCORING_VERSION,
+        })
+    }
+}
+
+fn memo() -> &'static DashMap<MemoKey, (DataVersion, RiskAssessment)> {
+    static MEMO: OnceLock<DashMap<MemoKey, (DataVersion, RiskAssessment)>> = OnceLock::new();
+    MEMO.get_or_init(DashMap::new)
+}
+
+/// Remembered assessment for this ticker and window, if computed from `version`
+pub fn lookup(ticker: &str, days: i64, benchmark: &str, risk_free_rate: f64, version: DataVersion) -> Option<RiskAssessment> {
+    let key = MemoKey::new(ticker, days, benchmark, risk_free_rate);
+    memo()
+        .get(&key)
+        .filter(|entry| entry.0 == version)
+        .map(|entry| entry.1.clone())
+}
+
+/// Remember an assessment, replacing any computed from older data
+pub fn store(ticker: &str, days: i64, benchmark: &str, risk_free_rate: f64, version: DataVersion, assessment: &RiskAssessment) {
+    let memo = memo();
+    if memo.len() >= MAX_ENTRIES {
+        memo.clear();
+    }
+    memo.insert(MemoKey::new(ticker, days, benchmark, risk_free_rate), (version, assessment.clone()));
+}
+
+/// Drop every assessment that used this ticker's prices. The multi-benchmark
+/// betas use the beta benchmarks in every assessment, so new prices for one of
+/// those clear the memo.
+pub fn invalidate(ticker: &str) {
+    if BETA_BENCHMARKS.contains(&ticker) {
+        clear();
+    } else {
+        memo().retain(|key, _| key.ticker != ticker && key.benchmark != ticker);
+    }
+}
+
+/// Drop every assessment, for when price history is deleted wholesale
+pub fn clear() {
+    memo().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::risk::{PositionRisk, RiskLevel};
+
+    fn assessment(ticker: &str, risk_score: f64) -> RiskAssessment {
+        RiskAssessment {
+            ticker: ticker.to_string(),
+            metrics: PositionRisk {
+                volatility: 20.0,
+                max_drawdown: -10.0,
+                beta: Some(1.0),
+                beta_spy: Some(1.0),
+                beta_qqq: None,
+                beta_iwm: None,
+                risk_decomposition: None,
+                sharpe: None,
+                sortino: None,
+                annualized_return: None,
+                value_at_risk: None,
+                var_95: None,
+                var_99: None,
+                expected_shortfall_95: None,
+                expected_shortfall_99: None,
+            },
+            risk_score,
+            risk_level: RiskLevel::from_score(risk_score),
+            scoring_version: CURRENT_SCORING_VERSION,
+        }
+    }
+
+    fn version(day: u32) -> DataVersion {
+        DataVersion {
+            last_price_date: NaiveDate::from_ymd_opt(2025, 6, day).unwrap(),
+            benchmark_price_date: NaiveDate::from_ymd_opt(2025, 6, day).unwrap(),
+            model_version: CURRENT_SCORING_VERSION,
+        }
+    }
+
+    #[test]
+    fn test_lookup_requires_matching_data_version() {
+        store("MEMO_A", 90, "MEMO_BENCH", 0.045, version(27), &assessment("MEMO_A", 40.0));
+
+        let hit = lookup("MEMO_A", 90, "MEMO_BENCH", 0.045, version(27)).unwrap();
+        assert_eq!(hit.risk_score, 40.0);
+
+        assert!(lookup("MEMO_A", 90, "MEMO_BENCH", 0.045, version(30)).is_none(), "A newer close misses");
+        assert!(lookup("MEMO_A", 60, "MEMO_BENCH", 0.045, version(27)).is_none(), "Windows are memoized separately");
+        assert!(lookup("MEMO_A", 90, "MEMO_BENCH", 0.05, version(27)).is_none(), "So are risk-free rates");
+
+        // Recomputing on the new data replaces the old entry
+        store("MEMO_A", 90, "MEMO_BENCH", 0.045, version(30), &assessment("MEMO_A", 45.0));
+        assert!(lookup("MEMO_A", 90, "MEMO_BENCH", 0.045, version(27)).is_none());
+        assert_eq!(lookup("MEMO_A", 90, "MEMO_BENCH", 0.045, version(30)).unwrap().risk_score, 45.0);
+    }
+
+    #[test]
+    fn test_invalidate_drops_ticker_and_benchmark_users() {
+        store("MEMO_B", 90, "MEMO_IDX", 0.045, version(27), &assessment("MEMO_B", 40.0));
+        store("MEMO_C", 90, "MEMO_IDX", 0.045, version(27), &assessment("MEMO_C", 50.0));
+        store("MEMO_D", 90, "MEMO_OTHER", 0.045, version(27), &assessment("MEMO_D", 60.0));
+
+        invalidate("MEMO_B");
+        assert!(lookup("MEMO_B", 90, "MEMO_IDX", 0.045, version(27)).is_none());
+        assert!(lookup("MEMO_C", 90, "MEMO_IDX", 0.045, version(27)).is_some());
+
+        invalidate("MEMO_IDX");
+        assert!(lookup("MEMO_C", 90, "MEMO_IDX", 0.045, version(27)).is_none());
+        assert!(lookup("MEMO_D", 90, "MEMO_OTHER", 0.045, version(27)).is_some());
+    }
+}
@@ -0,0 +1,240 @@
+//! Recompute stored risk snapshots under another scoring model version.
+//!
+//! A snapshot's score is a function of its stored volatility, drawdown, beta
+//! and VaR, so history can be rescored without fetching prices. A recompute
+//! writes the new scores to the snapshots' shadow columns and reports how far
+//! they diverge from the live scores. Once the new version is made current in
+//! [`risk_service::CURRENT_SCORING_VERSION`], promoting it replaces the live
+//! scores, keeping risk history comparable across the methodology change.
+
+use std::collections::BTreeMap;
+
+use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::db::risk_snapshot_queries;
+use crate::errors::AppError;
+use crate::models::risk::RiskLevel;
+use crate::models::risk_snapshot::{
+    RiskLevelChange, RiskPromoteResult, RiskRecomputeRequest, RiskRecomputeReport, RiskScoreDivergence,
+    RiskScoringInput, ShadowScoreRow,
+};
+use crate::models::PositionRisk;
+use crate::services::risk_service::{self, RiskScoringWeights, CURRENT_SCORING_VERSION};
+
+/// Snapshots rescored per query
+const BATCH_SIZE: i64 = 2_000;
+/// Divergences listed in a report
+const LARGEST_DIVERGENCES: usize = 20;
+/// Scores are stored with two decimals; smaller moves are rounding
+const CHANGE_EPSILON: f64 = 0.01;
+
+fn weights(scoring_version: i32) -> Result<&'static RiskScoringWeights, AppError> {
+    risk_service::scoring_weights(scoring_version)
+        .ok_or_else(|| AppError::Validation(format!("Unknown scoring version {}", scoring_version)))
+}
+
+/// Score of a stored snapshot under `weights`
+pub fn rescore(input: &RiskScoringInput, weights: &RiskScoringWeights) -> (f64, RiskLevel) {
+    let score = risk_service::score_risk_with(
+        &PositionRisk {
+            volatility: input.volatility.to_f64().unwrap_or(0.0),
+            max_drawdown: input.max_drawdown.to_f64().unwrap_or(0.0),
+            beta: input.beta.as_ref().and_then(|b| b.to_f64()),
+            beta_spy: None,
+            beta_qqq: None,
+            beta_iwm: None,
+            risk_decomposition: None,
+            sharpe: None,
+            sortino: None,
+            annualized_return: None,
+            value_at_risk: input.value_at_risk.as_ref().and_then(|v| v.to_f64()),
+            var_95: None,
+            var_99: None,
+            expected_shortfall_95: None,
+            expected_shortfall_99: None,
+        },
+        weights,
+    );
+    (score, RiskLevel::from_score(score))
+}
+
+/// Divergence of the shadow scores from the live scores
+pub fn divergence_report(scoring_version: i32, rows: &[ShadowScoreRow]) -> RiskRecomputeReport {
+    let mut divergences: Vec<RiskScoreDivergence> = rows
+        .iter()
+        .map(|row| {
+            let risk_score = row.risk_score.to_f64().unwrap_or(0.0);
+            let shadow_risk_score = row.shadow_risk_score.to_f64().unwrap_or(0.0);
+            RiskScoreDivergence {
+                snapshot_id: row.id,
+                portfolio_id: row.portfolio_id,
+                ticker: row.ticker.clone(),
+                snapshot_date: row.snapshot_date,
+                risk_score,
+                shadow_risk_score,
+                difference: shadow_risk_score - risk_score,
+            }
+        })
+        .collect();
+
+    let mut transitions: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for row in rows.iter().filter(|r| r.risk_level != r.shadow_risk_level) {
+        *transitions.entry((row.risk_level.clone(), row.shadow_risk_level.clone())).or_default() += 1;
+    }
+    let mut level_changes: Vec<RiskLevelChange> = transitions
+        .into_iter()
+        .map(|((from, to), count)| RiskLevelChange { from, to, count })
+        .collect();
+    level_changes.sort_by_key(|c| std::cmp::Reverse(c.count));
+
+    let n = divergences.len();
+    let sum: f64 = divergences.iter().map(|d| d.difference).sum();
+    let abs_sum: f64 = divergences.iter().map(|d| d.difference.abs()).sum();
+    let max_abs = divergences.iter().map(|d| d.difference.abs()).fold(0.0, f64::max);
+    let changed = divergences.iter().filter(|d| d.difference.abs() > CHANGE_EPSILON).count();
+
+    divergences.retain(|d| d.difference.abs() > CHANGE_EPSILON);
+    divergences.sort_by(|a, b| b.difference.abs().total_cmp(&a.difference.abs()));
+    divergences.truncate(LARGEST_DIVERGENCES);
+
+    RiskRecomputeReport {
+        scoring_version,
+        snapshots: n,
+        changed,
+        mean_difference: if n > 0 { sum / n as f64 } else { 0.0 },
+        mean_abs_difference: if n > 0 { abs_sum / n as f64 } else { 0.0 },
+        max_abs_difference: max_abs,
+        level_changes,
+        largest_divergences: divergences,
+    }
+}
+
+/// Rescore the snapshots matching the request into their shadow columns and
+/// report the divergence from the live scores
+pub async fn recompute(pool: &PgPool, request: &RiskRecomputeRequest) -> Result<RiskRecomputeReport, AppError> {
+    let weights = weights(request.scoring_version)?;
+    if let (Some(from), Some(to)) = (request.from, request.to) {
+        if from > to {
+            return Err(AppError::Validation("from must not be after to".to_string()));
+        }
+    }
+
+    let mut after = None;
+    let mut rescored = 0u64;
+    loop {
+        let inputs = risk_snapshot_queries::fetch_scoring_inputs(pool, request, after, BATCH_SIZE).await?;
+        let Some(last) = inputs.last() else {
+            break;
+        };
+        after = Some(last.id);
+
+        let mut ids = Vec::with_capacity(inputs.len());
+        let mut scores = Vec::with_capacity(inputs.len());
+        let mut levels = Vec::with_capacity(inputs.len());
+        for input in &inputs {
+            let (score, level) = rescore(input, weights);
+            ids.push(input.id);
+            scores.push(BigDecimal::from_f64((score * 100.0).round() / 100.0).unwrap_or_else(|| BigDecimal::from(0)));
+            levels.push(level.to_string());
+        }
+        rescored += risk_snapshot_queries::store_shadow_scores(pool, request.scoring_version, &ids, &scores, &levels).await?;
+
+        if (inputs.len() as i64) < BATCH_SIZE {
+            break;
+        }
+    }
+    info!("Rescored {} risk snapshots under scoring version {}", rescored, request.scoring_version);
+
+    report(pool, request).await
+}
+
+/// Divergence of the shadow scores already stored under the request's version
+pub async fn report(pool: &PgPool, request: &RiskRecomputeRequest) -> Result<RiskRecomputeReport, AppError> {
+    weights(request.scoring_version)?;
+    let rows = risk_snapshot_queries::fetch_shadow_scores(pool, request).await?;
+    Ok(divergence_report(request.scoring_version, &rows))
+}
+
+/// Replace the live scores with the shadow scores of the current scoring
+/// version. Other versions can't be promoted: newly computed snapshots would
+/// be scored differently from the promoted history.
+pub async fn promote(pool: &PgPool, scoring_version: i32) -> Result<RiskPromoteResult, AppError> {
+    weights(scoring_version)?;
+    if scoring_version != CURRENT_SCORING_VERSION {
+        return Err(AppError::Validation(format!(
+            "Only the current scoring version ({}) can be promoted",
+            CURRENT_SCORING_VERSION
+        )));
+    }
+    let promoted = risk_snapshot_queries::promote_shadow_scores(pool, scoring_version).await?;
+    info!("Promoted {} shadow risk scores under scoring version {}", promoted, scoring_version);
+    Ok(RiskPromoteResult { scoring_version, promoted })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    fn decimal(v: f64) -> BigDecimal {
+        BigDecimal::from_f64(v).unwrap()
+    }
+
+    fn shadow_row(risk_score: f64, risk_level: &str, shadow_risk_score: f64, shadow_risk_level: &str) -> ShadowScoreRow {
+        ShadowScoreRow {
+            id: Uuid::new_v4(),
+            portfolio_id: Uuid::nil(),
+            ticker: None,
+            snapshot_date: NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(),
+            risk_score: decimal(risk_score),
+            risk_level: risk_level.to_string(),
+            shadow_risk_score: decimal(shadow_risk_score),
+            shadow_risk_level: shadow_risk_level.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rescore_matches_live_scoring() {
+        let input = RiskScoringInput {
+            id: Uuid::new_v4(),
+            volatility: decimal(25.0),
+            max_drawdown: decimal(-20.0),
+            beta: Some(decimal(1.2)),
+            value_at_risk: Some(decimal(-3.0)),
+        };
+        let (score, level) = rescore(&input, weights(CURRENT_SCORING_VERSION).unwrap());
+        // 20 volatility + 12 drawdown + 12 beta + 3 VaR
+        assert!((score - 47.0).abs() < 1e-9);
+        assert_eq!(level, RiskLevel::Moderate);
+        assert!(weights(CURRENT_SCORING_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn test_divergence_report() {
+        let rows = vec![
+            shadow_row(35.0, "low", 45.0, "moderate"),
+            shadow_row(60.0, "moderate", 58.0, "moderate"),
+            shadow_row(20.0, "low", 20.0, "low"),
+            shadow_row(38.0, "low", 41.0, "moderate"),
+        ];
+        let report = divergence_report(2, &rows);
+        assert_eq!(report.snapshots, 4);
+        assert_eq!(report.changed, 3);
+        assert!((report.mean_difference - 2.75).abs() < 1e-9);
+        assert!((report.mean_abs_difference - 3.75).abs() < 1e-9);
+        assert!((report.max_abs_difference - 10.0).abs() < 1e-9);
+        assert_eq!(
+            report.level_changes,
+            vec![RiskLevelChange { from: "low".to_string(), to: "moderate".to_string(), count: 2 }]
+        );
+        let differences: Vec<f64> = report.largest_divergences.iter().map(|d| d.difference).collect();
+        assert_eq!(differences, vec![10.0, 3.0, -2.0]);
+
+        let empty = divergence_report(2, &[]);
+        assert_eq!(empty.snapshots, 0);
+        assert_eq!(empty.mean_abs_difference, 0.0);
+    }
+}
@@ -1,10 +1,11 @@
 use crate::db::price_queries;
 use crate::errors::AppError;
 use crate::external::price_provider::PriceProvider;
-use crate::models::risk::{PositionRisk, RiskAssessment, RiskLevel, RiskDecomposition};
+use crate::models::risk::{PositionRisk, RiskAssessment, RiskLevel, RiskDecomposition, RiskScoreComponent, RiskScoringModel};
 use crate::models::PricePoint;
 use crate::services::price_service;
 use crate::services::failure_cache::FailureCache;
+use crate::services::risk_memo::{self, DataVersion};
 use crate::services::rate_limiter::RateLimiter;
 use bigdecimal::ToPrimitive;
 use sqlx::PgPool;
@@ -37,8 +38,8 @@ pub async fn compute_risk_metrics_from_cache(
     risk_free_rate: f64,
 ) -> Result<RiskAssessment, AppError> {
     // Fetch price history from database only (no API calls)
-    let series = price_queries::fetch_window(pool, ticker, days).await?;
-    let bench = price_queries::fetch_window(pool, benchmark, days).await?;
+    let series = price_queries::fetch_adjusted_window(pool, ticker, days).await?;
+    let bench = price_queries::fetch_adjusted_window(pool, benchmark, days).await?;
 
     if series.is_empty() {
         return Err(AppError::NotFound(format!(
@@ -54,6 +55,14 @@ pub async fn compute_risk_metrics_from_cache(
         )));
     }
 
+    // A full assessment of the same data is at least as complete as one built
+    // from cached benchmarks only
+    if let Some(assessment) = DataVersion::of(&series, &bench)
+        .and_then(|version| risk_memo::lookup(ticker, days, benchmark, risk_free_rate, version))
+    {
+        return Ok(assessment);
+    }
+
     // Compute individual risk metrics
     let (volatility, max_drawdown) = compute_vol_drawdown(&series);
     let beta = compute_beta(&series, &bench);
@@ -66,7 +75,7 @@ pub async fn compute_risk_metrics_from_cache(
 
     // Compute multi-benchmark betas from cache only
     let beta_spy = if benchmark != "SPY" {
-        let spy_data = price_queries::fetch_window(pool, "SPY", days).await.ok();
+        let spy_data = price_queries::fetch_adjusted_window(pool, "SPY", days).await.ok();
         spy_data.and_then(|spy| {
             if spy.len() >= 2 {
                 compute_beta(&series, &spy)
@@ -79,7 +88,7 @@ pub async fn compute_risk_metrics_from_cache(
     };
 
     let beta_qqq = if benchmark != "QQQ" {
-        let qqq_data = price_queries::fetch_window(pool, "QQQ", days).await.ok();
+        let qqq_data = price_queries::fetch_adjusted_window(pool, "QQQ", days).await.ok();
         qqq_data.and_then(|qqq| {
             if qqq.len() >= 2 {
                 compute_beta(&series, &qqq)
@@ -92,7 +101,7 @@ pub async fn compute_risk_metrics_from_cache(
     };
 
     let beta_iwm = if benchmark != "IWM" {
-        let iwm_data = price_queries::fetch_window(pool, "IWM", days).await.ok();
+        let iwm_data = price_queries::fetch_adjusted_window(pool, "IWM", days).await.ok();
         iwm_data.and_then(|iwm| {
             if iwm.len() >= 2 {
                 compute_beta(&series, &iwm)
@@ -138,9 +147,32 @@ pub async fn compute_risk_metrics_from_cache(
         metrics,
         risk_score,
         risk_level,
+        scoring_version: CURRENT_SCORING_VERSION,
     })
 }
 
+/// Refresh the closes of `tickers`, the benchmark and the beta benchmarks in
+/// one concurrent batch before computing risk for each ticker, so the refreshes
+/// in `compute_risk_metrics` find them fresh instead of fetching one at a time.
+/// Failures are left for `compute_risk_metrics` to report per ticker.
+pub async fn prefetch_prices(
+    pool: &PgPool,
+    tickers: &[String],
+    benchmark: &str,
+    price_provider: &dyn PriceProvider,
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
+) {
+    let mut all: Vec<String> = tickers.to_vec();
+    all.push(benchmark.to_string());
+    all.extend(BETA_BENCHMARKS.iter().map(|b| b.to_string()));
+    let results = price_service::refresh_many_from_api(pool, price_provider, &all, failure_cache, rate_limiter).await;
+    let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+    if failed > 0 {
+        warn!("Could not refresh prices for {} of {} tickers", failed, results.len());
+    }
+}
+
 pub async fn compute_risk_metrics(
     pool: &PgPool,
     ticker: &str,
@@ -159,8 +191,8 @@ pub async fn compute_risk_metrics(
     let benchmark_fetch_failed = price_service::refresh_from_api(pool, price_provider, benchmark, failure_cache, rate_limiter).await.is_err();
 
     // Fetch price history for the ticker and benchmark
-    let series = price_queries::fetch_window(pool, ticker, days).await?;
-    let bench = price_queries::fetch_window(pool, benchmark, days).await?;
+    let series = price_queries::fetch_adjusted_window(pool, ticker, days).await?;
+    let bench = price_queries::fetch_adjusted_window(pool, benchmark, days).await?;
 
     if series.is_empty() {
         let error_msg = if ticker_fetch_failed {
@@ -192,6 +224,12 @@ pub async fn compute_risk_metrics(
         return Err(AppError::NotFound(error_msg));
     }
 
+    let version = DataVersion::of(&series, &bench);
+    if let Some(assessment) = version.and_then(|v| risk_memo::lookup(ticker, days, benchmark, risk_free_rate, v)) {
+        info!("Using memoized risk metrics for {} ({} days)", ticker, days);
+        return Ok(assessment);
+    }
+
     // Compute individual risk metrics
     let (volatility, max_drawdown) = compute_vol_drawdown(&series);
     let beta = compute_beta(&series, &bench);
@@ -235,12 +273,18 @@ pub async fn compute_risk_metrics(
     let risk_score = score_risk(&metrics);
     let risk_level = RiskLevel::from_score(risk_score);
 
-    Ok(RiskAssessment {
+    let assessment = RiskAssessment {
         ticker: ticker.to_string(),
         metrics,
         risk_score,
         risk_level,
-    })
+        scoring_version: CURRENT_SCORING_VERSION,
+    };
+    if let Some(version) = version {
+        risk_memo::store(ticker, days, benchmark, risk_free_rate, version, &assessment);
+    }
+
+    Ok(assessment)
 }
 
 /// Compute volatility (annualized) and max drawdown for a price series.
@@ -300,6 +344,60 @@ fn compute_vol_drawdown(series: &[PricePoint]) -> (f64, f64) {
     (volatility, max_dd * 100.0) // Convert to percentage
 }
 
+/// Annualized volatility and maximum drawdown of a series of daily returns, both
+/// as percentages. The drawdown is measured on the value the returns compound to.
+pub fn vol_drawdown_from_returns(returns: &[f64]) -> Option<(f64, f64)> {
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() as f64 - 1.0);
+    let volatility = variance.sqrt() * (252.0_f64).sqrt() * 100.0;
+
+    let mut value = 1.0;
+    let mut peak = 1.0;
+    let mut max_dd = 0.0;
+    for r in returns {
+        value *= 1.0 + r;
+        peak = f64::max(peak, value);
+        max_dd = f64::min(max_dd, (value - peak) / peak);
+    }
+
+    Some((volatility, max_dd * 100.0))
+}
+
+/// Regression of daily returns on a benchmark's over the same days:
+/// `(beta, alpha, r_squared)`, with alpha annualized as a percentage. `None`
+/// when the series don't line up or the benchmark never moves.
+pub fn beta_regression_from_returns(returns: &[f64], benchmark_returns: &[f64]) -> Option<(f64, f64, f64)> {
+    if returns.len() != benchmark_returns.len() || returns.is_empty() {
+        return None;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let mean_bench = benchmark_returns.iter().sum::<f64>() / benchmark_returns.len() as f64;
+
+    let mut covariance = 0.0;
+    let mut var_bench = 0.0;
+    let mut var = 0.0;
+    for (r, b) in returns.iter().zip(benchmark_returns) {
+        covariance += (r - mean) * (b - mean_bench);
+        var_bench += (b - mean_bench).powi(2);
+        var += (r - mean).powi(2);
+    }
+
+    if var_bench.abs() < f64::EPSILON {
+        return None;
+    }
+
+    let beta = covariance / var_bench;
+    // Squared correlation
+    let r_squared = if var.abs() < f64::EPSILON { 0.0 } else { covariance.powi(2) / (var * var_bench) };
+    let alpha = (mean - beta * mean_bench) * 252.0 * 100.0;
+    Some((beta, alpha, r_squared))
+}
+
 /// Compute beta relative to a benchmark return series.
 ///
 /// Beta measures the systematic risk of a security relative to the market (benchmark).
@@ -395,6 +493,20 @@ fn compute_annualized_return(series: &[PricePoint]) -> Option<f64> {
     Some(annualized)
 }
 
+/// Simple daily returns of a price series, skipping non-positive prices
+fn price_returns(series: &[PricePoint]) -> Vec<f64> {
+    let prices: Vec<f64> = series
+        .iter()
+        .filter_map(|p| p.close_price.to_f64())
+        .collect();
+
+    prices
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| (w[1] - w[0]) / w[0])
+        .collect()
+}
+
 /// Compute the annualized Sharpe ratio using the provided risk-free rate.
 ///
 /// The Sharpe ratio measures risk-adjusted return. Higher values indicate better
@@ -404,30 +516,11 @@ fn compute_annualized_return(series: &[PricePoint]) -> Option<f64> {
 /// * `series` - Price history for the asset
 /// * `risk_free_rate` - Annual risk-free rate (e.g., 0.045 for 4.5%)
 fn compute_sharpe(series: &[PricePoint], risk_free_rate: f64) -> Option<f64> {
-    if series.len() < 2 {
-        return None;
-    }
-
-    // Convert to f64 prices
-    let prices: Vec<f64> = series
-        .iter()
-        .filter_map(|p| p.close_price.to_f64())
-        .collect();
-
-    if prices.len() < 2 {
-        return None;
-    }
-
-    // Calculate daily returns
-    let mut returns = Vec::new();
-    for i in 1..prices.len() {
-        let prev = prices[i - 1];
-        let cur = prices[i];
-        if prev > 0.0 {
-            returns.push((cur - prev) / prev);
-        }
-    }
+    sharpe_from_returns(&price_returns(series), risk_free_rate)
+}
 
+/// Annualized Sharpe ratio of a series of daily returns
+pub fn sharpe_from_returns(returns: &[f64], risk_free_rate: f64) -> Option<f64> {
     if returns.is_empty() {
         return None;
     }
@@ -441,7 +534,7 @@ fn compute_sharpe(series: &[PricePoint], risk_free_rate: f64) -> Option<f64> {
         / (returns.len() as f64 - 1.0);
     let volatility = variance.sqrt() * (252.0_f64).sqrt(); // Annualized
 
-    if volatility.abs() < f64::EPSILON {
+    if !volatility.is_finite() || volatility.abs() < f64::EPSILON {
         return None; // Avoid division by zero
     }
 
@@ -462,30 +555,11 @@ fn compute_sharpe(series: &[PricePoint], risk_free_rate: f64) -> Option<f64> {
 /// * `series` - Price history for the asset
 /// * `risk_free_rate` - Annual risk-free rate (e.g., 0.045 for 4.5%)
 fn compute_sortino(series: &[PricePoint], risk_free_rate: f64) -> Option<f64> {
-    if series.len() < 2 {
-        return None;
-    }
-
-    // Convert to f64 prices
-    let prices: Vec<f64> = series
-        .iter()
-        .filter_map(|p| p.close_price.to_f64())
-        .collect();
-
-    if prices.len() < 2 {
-        return None;
-    }
-
-    // Calculate daily returns
-    let mut returns = Vec::new();
-    for i in 1..prices.len() {
-        let prev = prices[i - 1];
-        let cur = prices[i];
-        if prev > 0.0 {
-            returns.push((cur - prev) / prev);
-        }
-    }
+    sortino_from_returns(&price_returns(series), risk_free_rate)
+}
 
+/// Annualized Sortino ratio of a series of daily returns
+pub fn sortino_from_returns(returns: &[f64], risk_free_rate: f64) -> Option<f64> {
     if returns.is_empty() {
         return None;
     }
@@ -517,7 +591,7 @@ fn compute_sortino(series: &[PricePoint], risk_free_rate: f64) -> Option<f64> {
 
     let downside_deviation = downside_variance.sqrt() * (252.0_f64).sqrt(); // Annualized
 
-    if downside_deviation.abs() < f64::EPSILON {
+    if !downside_deviation.is_finite() || downside_deviation.abs() < f64::EPSILON {
         return None; // Avoid division by zero
     }
 
@@ -537,30 +611,11 @@ fn compute_sortino(series: &[PricePoint], risk_free_rate: f64) -> Option<f64> {
 /// # Returns
 /// Annualized downside deviation as a percentage, or None if insufficient data
 pub fn compute_downside_deviation(series: &[PricePoint], risk_free_rate: f64) -> Option<f64> {
-    if series.len() < 2 {
-        return None;
-    }
-
-    // Convert to f64 prices
-    let prices: Vec<f64> = series
-        .iter()
-        .filter_map(|p| p.close_price.to_f64())
-        .collect();
-
-    if prices.len() < 2 {
-        return None;
-    }
-
-    // Calculate daily returns
-    let mut returns = Vec::new();
-    for i in 1..prices.len() {
-        let prev = prices[i - 1];
-        let cur = prices[i];
-        if prev > 0.0 {
-            returns.push((cur - prev) / prev);
-        }
-    }
+    downside_deviation_from_returns(&price_returns(series), risk_free_rate)
+}
 
+/// Annualized downside deviation of a series of daily returns, as a percentage
+pub fn downside_deviation_from_returns(returns: &[f64], risk_free_rate: f64) -> Option<f64> {
     if returns.is_empty() {
         return None;
     }
@@ -732,36 +787,17 @@ fn compute_var(series: &[PricePoint]) -> Option<f64> {
 /// - var_95: 95% confidence (5% chance of exceeding this loss)
 /// - var_99: 99% confidence (1% chance of exceeding this loss)
 fn compute_var_multi(series: &[PricePoint]) -> (Option<f64>, Option<f64>) {
-    if series.len() < 2 {
-        return (None, None);
-    }
-
-    // Convert to f64 prices
-    let prices: Vec<f64> = series
-        .iter()
-        .filter_map(|p| p.close_price.to_f64())
-        .collect();
-
-    if prices.len() < 2 {
-        return (None, None);
-    }
-
-    // Calculate daily returns
-    let mut returns = Vec::new();
-    for i in 1..prices.len() {
-        let prev = prices[i - 1];
-        let cur = prices[i];
-        if prev > 0.0 {
-            returns.push((cur - prev) / prev);
-        }
-    }
+    var_from_returns(&price_returns(series))
+}
 
+/// Historical VaR at 95% and 99% of a series of daily returns, as percentages
+pub fn var_from_returns(returns: &[f64]) -> (Option<f64>, Option<f64>) {
     if returns.is_empty() {
         return (None, None);
     }
 
     // Sort returns to find percentiles
-    let mut sorted_returns = returns.clone();
+    let mut sorted_returns = returns.to_vec();
     sorted_returns.sort_by(|a, b| a.partial_cmp(b).unwrap());
 
     // 95% VaR (5th percentile)
@@ -782,36 +818,17 @@ fn compute_var_multi(series: &[PricePoint]) -> (Option<f64>, Option<f64>) {
 ///
 /// Returns (es_95, es_99) as a tuple of negative percentages.
 fn compute_expected_shortfall(series: &[PricePoint]) -> (Option<f64>, Option<f64>) {
-    if series.len() < 2 {
-        return (None, None);
-    }
-
-    // Convert to f64 prices
-    let prices: Vec<f64> = series
-        .iter()
-        .filter_map(|p| p.close_price.to_f64())
-        .collect();
-
-    if prices.len() < 2 {
-        return (None, None);
-    }
-
-    // Calculate daily returns
-    let mut returns = Vec::new();
-    for i in 1..prices.len() {
-        let prev = prices[i - 1];
-        let cur = prices[i];
-        if prev > 0.0 {
-            returns.push((cur - prev) / prev);
-        }
-    }
+    expected_shortfall_from_returns(&price_returns(series))
+}
 
+/// Expected Shortfall at 95% and 99% of a series of daily returns, as percentages
+pub fn expected_shortfall_from_returns(returns: &[f64]) -> (Option<f64>, Option<f64>) {
     if returns.is_empty() {
         return (None, None);
     }
 
     // Sort returns
-    let mut sorted_returns = returns.clone();
+    let mut sorted_returns = returns.to_vec();
     sorted_returns.sort_by(|a, b| a.partial_cmp(b).unwrap());
 
     // ES at 95% confidence (average of worst 5% returns)
@@ -837,37 +854,107 @@ fn compute_expected_shortfall(series: &[PricePoint]) -> (Option<f64>, Option<f64
     (es_95, es_99)
 }
 
-/// Score a PositionRisk into a 0–100 risk rating.
+/// Version of the scoring model used for newly computed risk scores.
 ///
-/// Higher scores indicate higher risk. The score is calculated as a weighted
-/// combination of various risk metrics.
+/// Bump this (and add an entry to [`SCORING_MODELS`]) whenever the weighting
+/// changes, so stored snapshots and cache entries stay comparable by version.
+/// Stored history is rescored with `risk_recompute_service`: recompute into the
+/// shadow columns, check the divergence report, then promote after the bump.
+pub const CURRENT_SCORING_VERSION: i32 = 1;
+
+/// Points and saturation levels of one risk scoring model version.
 ///
-/// # Weighting
+/// Each metric contributes `*_points * min(|value| / *_saturation, 1)`.
+pub struct RiskScoringWeights {
+    pub version: i32,
+    pub description: &'static str,
+    pub volatility_points: f64,
+    /// Annualized volatility (%) that earns the full volatility points
+    pub volatility_saturation: f64,
+    pub drawdown_points: f64,
+    /// Max drawdown magnitude (%) that earns the full drawdown points
+    pub drawdown_saturation: f64,
+    pub beta_points: f64,
+    pub beta_saturation: f64,
+    pub var_points: f64,
+    /// 1-day VaR magnitude (%) that earns the full VaR points
+    pub var_saturation: f64,
+}
+
+/// All scoring model versions, oldest first.
+pub const SCORING_MODELS: &[RiskScoringWeights] = &[RiskScoringWeights {
+    version: 1,
+    description: "Weighted volatility, drawdown, beta and VaR, each capped at an extreme value",
+    volatility_points: 40.0,
+    volatility_saturation: 50.0,
+    drawdown_points: 30.0,
+    drawdown_saturation: 50.0,
+    beta_points: 20.0,
+    beta_saturation: 2.0,
+    var_points: 10.0,
+    var_saturation: 10.0,
+}];
+
+/// Weights for a scoring model version, if it exists
+pub fn scoring_weights(version: i32) -> Option<&'static RiskScoringWeights> {
+    SCORING_MODELS.iter().find(|m| m.version == version)
+}
+
+/// Describe every scoring model version for clients annotating risk history.
+pub fn scoring_models() -> Vec<RiskScoringModel> {
+    SCORING_MODELS
+        .iter()
+        .map(|m| {
+            let component = |metric: &str, max_points: f64, saturation: f64, description: &str| RiskScoreComponent {
+                metric: metric.to_string(),
+                max_points,
+                saturation,
+                description: description.to_string(),
+            };
+            RiskScoringModel {
+                version: m.version,
+                current: m.version == CURRENT_SCORING_VERSION,
+                description: m.description.to_string(),
+                components: vec![
+                    component("volatility", m.volatility_points, m.volatility_saturation, "Annualized volatility, in percent"),
+                    component("max_drawdown", m.drawdown_points, m.drawdown_saturation, "Maximum peak-to-trough decline, in percent"),
+                    component("beta", m.beta_points, m.beta_saturation, "Absolute beta against the benchmark"),
+                    component("value_at_risk", m.var_points, m.var_saturation, "1-day 95% Value at Risk, in percent"),
+                ],
+            }
+        })
+        .collect()
+}
+
+/// Score a PositionRisk into a 0–100 risk rating with the current scoring model.
+///
+/// Higher scores indicate higher risk. See [`score_risk_with`] for the formula.
+pub fn score_risk(risk: &PositionRisk) -> f64 {
+    let weights = scoring_weights(CURRENT_SCORING_VERSION).expect("current scoring version is defined");
+    score_risk_with(risk, weights)
+}
+
+/// Score a PositionRisk with a specific scoring model version.
+///
+/// # Weighting (v1)
 /// - 40% volatility (normalized to 50% max)
 /// - 30% drawdown severity (normalized to -50% max)
 /// - 20% beta magnitude (normalized to 2.0 max)
 /// - 10% VaR (normalized to -10% max)
-pub fn score_risk(risk: &PositionRisk) -> f64 {
-    // Volatility component (40 points max)
-    // Assume 50% annualized volatility as extreme
-    let vol_score = (risk.volatility / 50.0).min(1.0) * 40.0;
+pub fn score_risk_with(risk: &PositionRisk, weights: &RiskScoringWeights) -> f64 {
+    let vol_score = (risk.volatility / weights.volatility_saturation).min(1.0) * weights.volatility_points;
 
-    // Drawdown component (30 points max)
-    // Assume -50% max drawdown as extreme
-    let dd_score = (-risk.max_drawdown / 50.0).min(1.0) * 30.0;
+    // Drawdown is negative; only declines add risk
+    let dd_score = (-risk.max_drawdown / weights.drawdown_saturation).min(1.0) * weights.drawdown_points;
 
-    // Beta component (20 points max)
-    // Assume beta of 2.0 as extreme
     let beta_score = risk
         .beta
-        .map(|b| (b.abs().min(2.0) / 2.0) * 20.0)
+        .map(|b| (b.abs().min(weights.beta_saturation) / weights.beta_saturation) * weights.beta_points)
         .unwrap_or(0.0);
 
-    // VaR component (10 points max)
-    // Assume -10% VaR as extreme
     let var_score = risk
         .value_at_risk
-        .map(|v| (v.abs().min(10.0) / 10.0) * 10.0)
+        .map(|v| (v.abs().min(weights.var_saturation) / weights.var_saturation) * weights.var_points)
         .unwrap_or(0.0);
 
     (vol_score + dd_score + beta_score + var_score).min(100.0)
@@ -963,10 +1050,9 @@ async fn compute_multi_benchmark_beta(
     failure_cache: &FailureCache,
     rate_limiter: &RateLimiter,
 ) -> (Option<f64>, Option<f64>, Option<f64>) {
-    let benchmarks = ["SPY", "QQQ", "IWM"];
     let mut betas = Vec::new();
 
-    for benchmark in &benchmarks {
+    for benchmark in &BETA_BENCHMARKS {
         // Ensure fresh benchmark data
         if let Err(e) = price_service::refresh_from_api(pool, price_provider, benchmark, failure_cache, rate_limiter).await {
             warn!("Failed to refresh {} data: {}", benchmark, e);
@@ -975,7 +1061,7 @@ async fn compute_multi_benchmark_beta(
         }
 
         // Fetch benchmark price history
-        match price_queries::fetch_window(pool, benchmark, days).await {
+        match price_queries::fetch_adjusted_window(pool, benchmark, days).await {
             Ok(bench_series) => {
                 if bench_series.len() >= 2 {
                     let beta = compute_beta(ticker_series, &bench_series);
@@ -999,6 +1085,9 @@ async fn compute_multi_benchmark_beta(
     )
 }
 
+/// Benchmarks every assessment reports a beta against
+pub const BETA_BENCHMARKS: [&str; 3] = ["SPY", "QQQ", "IWM"];
+
 /// Compute risk decomposition: systematic vs idiosyncratic risk.
 ///
 /// Systematic risk is the portion of total risk explained by market movements (beta),
@@ -1182,34 +1271,34 @@ pub async fn compute_portfolio_downside_risk(
     use crate::db::holding_snapshot_queries;
     use std::collections::HashMap;
 
-    info!("🔍 [DOWNSIDE_RISK] Starting downside risk computation for portfolio {}", portfolio_id);
-    info!("📋 [DOWNSIDE_RISK] Parameters: days={}, benchmark={}, risk_free_rate={}", days, benchmark, risk_free_rate);
+    info!("[DOWNSIDE_RISK] Starting downside risk computation for portfolio {}", portfolio_id);
+    info!("[DOWNSIDE_RISK] Parameters: days={}, benchmark={}, risk_free_rate={}", days, benchmark, risk_free_rate);
 
     // 1. Fetch all latest holdings for the portfolio
-    info!("📊 [DOWNSIDE_RISK] Fetching portfolio holdings...");
+    info!("[DOWNSIDE_RISK] Fetching portfolio holdings...");
     let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id)
         .await
         .map_err(|e| {
-            tracing::error!("❌ [DOWNSIDE_RISK] Failed to fetch portfolio holdings: {}", e);
+            tracing::error!("[DOWNSIDE_RISK] Failed to fetch portfolio holdings: {}", e);
             AppError::Db(e)
         })?;
 
     if holdings.is_empty() {
-        warn!("⚠️ [DOWNSIDE_RISK] Portfolio {} has no holdings", portfolio_id);
+        warn!("[DOWNSIDE_RISK] Portfolio {} has no holdings", portfolio_id);
         return Err(AppError::External(
             "Portfolio has no holdings".to_string()
         ));
     }
 
-    info!("✅ [DOWNSIDE_RISK] Found {} holdings in portfolio {}", holdings.len(), portfolio_id);
+    info!("[DOWNSIDE_RISK] Found {} holdings in portfolio {}", holdings.len(), portfolio_id);
 
     // 2. Aggregate holdings by ticker
-    info!("🔢 [DOWNSIDE_RISK] Aggregating holdings by ticker...");
+    info!("[DOWNSIDE_RISK] Aggregating holdings by ticker...");
     let mut ticker_aggregates: HashMap<String, (f64, f64)> = HashMap::new(); // (quantity, market_value)
 
     for holding in &holdings {
-        let market_value = holding.market_value.to_string().parse::<f64>().unwrap_or(0.0);
-        let quantity = holding.quantity.to_string().parse::<f64>().unwrap_or(0.0);
+        let market_value = holding.market_value.to_f64().unwrap_or(0.0);
+        let quantity = holding.quantity.to_f64().unwrap_or(0.0);
 
         ticker_aggregates
             .entry(holding.ticker.clone())
@@ -1220,20 +1309,20 @@ pub async fn compute_portfolio_downside_risk(
             .or_insert((quantity, market_value));
     }
 
-    info!("✅ [DOWNSIDE_RISK] Aggregated into {} unique tickers", ticker_aggregates.len());
+    info!("[DOWNSIDE_RISK] Aggregated into {} unique tickers", ticker_aggregates.len());
 
     let total_value: f64 = ticker_aggregates.values().map(|(_, mv)| mv).sum();
-    info!("💰 [DOWNSIDE_RISK] Total portfolio value: ${:.2}", total_value);
+    info!("[DOWNSIDE_RISK] Total portfolio value: ${:.2}", total_value);
 
     if total_value == 0.0 {
-        warn!("⚠️ [DOWNSIDE_RISK] Portfolio {} has no holdings with market value", portfolio_id);
+        warn!("[DOWNSIDE_RISK] Portfolio {} has no holdings with market value", portfolio_id);
         return Err(AppError::External(
             "Portfolio has no holdings with market value".to_string()
         ));
     }
 
     // 3. Compute downside metrics for each ticker
-    info!("📈 [DOWNSIDE_RISK] Computing downside metrics for each ticker...");
+    info!("[DOWNSIDE_RISK] Computing downside metrics for each ticker...");
     let mut position_downside_risks = Vec::new();
     let mut weighted_downside_deviation = 0.0;
     let mut weighted_sortino = 0.0;
@@ -1241,30 +1330,38 @@ pub async fn compute_portfolio_downside_risk(
     let mut weighted_sharpe = 0.0;
     let mut sharpe_count = 0;
 
+    // One query for every position's window rather than one per ticker
+    let tickers: Vec<String> = ticker_aggregates
+        .iter()
+        .filter(|(_, (_, market_value))| market_value / total_value >= 0.001)
+        .map(|(ticker, _)| ticker.clone())
+        .collect();
+    info!("[DOWNSIDE_RISK] Fetching {}-day price history for {} tickers...", days, tickers.len());
+    let fetch_start = std::time::Instant::now();
+    let windows = price_queries::fetch_adjusted_window_batch(pool, &tickers, days)
+        .await
+        .map_err(AppError::Db)?;
+    info!("[DOWNSIDE_RISK] Fetched price history for {} tickers in {:.2}s", windows.len(), fetch_start.elapsed().as_secs_f64());
+
     let total_tickers = ticker_aggregates.len();
     let mut ticker_count = 0;
     for (ticker, (_quantity, market_value)) in ticker_aggregates {
         ticker_count += 1;
         let weight = market_value / total_value;
 
-        info!("🎯 [DOWNSIDE_RISK] Processing ticker {}/{}: {} (weight: {:.2}%, value: ${:.2})",
+        info!("[DOWNSIDE_RISK] Processing ticker {}/{}: {} (weight: {:.2}%, value: ${:.2})",
               ticker_count, total_tickers, ticker, weight * 100.0, market_value);
 
         if weight < 0.001 {
-            info!("⏭️ [DOWNSIDE_RISK] Skipping {} - negligible weight (<0.1%)", ticker);
+            info!("[DOWNSIDE_RISK] Skipping {} - negligible weight (<0.1%)", ticker);
             continue; // Skip negligible positions
         }
 
-        // Fetch price data for this ticker
-        info!("📊 [DOWNSIDE_RISK] Fetching {}-day price history for {}...", days, ticker);
-        let fetch_start = std::time::Instant::now();
-        match price_queries::fetch_window(pool, &ticker, days).await {
-            Ok(series) if series.len() >= 2 => {
-                let fetch_elapsed = fetch_start.elapsed();
-                info!("✅ [DOWNSIDE_RISK] Fetched {} price points for {} in {:.2}s", series.len(), ticker, fetch_elapsed.as_secs_f64());
-                let downside_deviation = compute_downside_deviation(&series, risk_free_rate);
-                let sortino = compute_sortino(&series, risk_free_rate);
-                let sharpe = compute_sharpe(&series, risk_free_rate);
+        match windows.get(&ticker) {
+            Some(series) if series.len() >= 2 => {
+                let downside_deviation = compute_downside_deviation(series, risk_free_rate);
+                let sortino = compute_sortino(series, risk_free_rate);
+                let sharpe = compute_sharpe(series, risk_free_rate);
 
                 if let Some(dd) = downside_deviation {
                     weighted_downside_deviation += dd * weight;
@@ -1294,54 +1391,47 @@ pub async fn compute_portfolio_downside_risk(
                     });
                 }
             }
-            Ok(_) => {
-                warn!("⚠️ [DOWNSIDE_RISK] Insufficient price data for {} (< 2 points)", ticker);
-            }
-            Err(e) => {
-                warn!("❌ [DOWNSIDE_RISK] Failed to fetch price data for {}: {}", ticker, e);
+            _ => {
+                warn!("[DOWNSIDE_RISK] Insufficient price data for {} (< 2 points)", ticker);
             }
         }
     }
 
-    info!("📊 [DOWNSIDE_RISK] Processed all tickers. Positions with downside data: {}", position_downside_risks.len());
+    info!("[DOWNSIDE_RISK] Processed all tickers. Positions with downside data: {}", position_downside_risks.len());
 
     if position_downside_risks.is_empty() {
-        warn!("⚠️ [DOWNSIDE_RISK] No positions in portfolio have available downside risk data");
+        warn!("[DOWNSIDE_RISK] No positions in portfolio have available downside risk data");
         return Err(AppError::External(
             "No positions in portfolio have available downside risk data".to_string()
         ));
     }
 
-    // 4. Create portfolio-level aggregated metrics
-    info!("🔢 [DOWNSIDE_RISK] Computing portfolio-level aggregated metrics...");
-    let portfolio_sortino = if sortino_count > 0 {
-        Some(weighted_sortino)
-    } else {
-        None
-    };
-
-    let portfolio_sharpe = if sharpe_count > 0 {
-        Some(weighted_sharpe)
-    } else {
-        None
-    };
+    // 4. Compute portfolio-level metrics from the portfolio's own daily returns
+    info!("[DOWNSIDE_RISK] Computing portfolio-level metrics from the portfolio return series...");
+    let ratios = crate::services::portfolio_return_service::compute_metrics(pool, portfolio_id, days, benchmark, risk_free_rate)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("[DOWNSIDE_RISK] Could not build the return series for portfolio {}: {}", portfolio_id, e);
+            Default::default()
+        });
+    let portfolio_downside_deviation = ratios.downside_deviation.unwrap_or(weighted_downside_deviation);
 
     let portfolio_interpretation = interpret_downside_metrics(
-        weighted_downside_deviation,
-        portfolio_sortino,
-        portfolio_sharpe,
+        portfolio_downside_deviation,
+        ratios.sortino,
+        ratios.sharpe,
     );
 
     let portfolio_metrics = crate::models::risk::DownsideRiskMetrics {
-        downside_deviation: weighted_downside_deviation,
-        sortino_ratio: portfolio_sortino,
+        downside_deviation: portfolio_downside_deviation,
+        sortino_ratio: ratios.sortino,
         mar: risk_free_rate * 100.0,
-        sharpe_ratio: portfolio_sharpe,
+        sharpe_ratio: ratios.sharpe,
         interpretation: portfolio_interpretation,
     };
 
     // 5. Sort positions by downside deviation (highest risk first)
-    info!("🔄 [DOWNSIDE_RISK] Sorting positions by downside deviation...");
+    info!("[DOWNSIDE_RISK] Sorting positions by downside deviation...");
     position_downside_risks.sort_by(|a, b| {
         b.downside_metrics
             .downside_deviation
@@ -1349,13 +1439,15 @@ pub async fn compute_portfolio_downside_risk(
             .unwrap()
     });
 
-    info!("✅ [DOWNSIDE_RISK] Downside risk computation COMPLETED for portfolio {}", portfolio_id);
-    info!("📈 [DOWNSIDE_RISK] Portfolio metrics - Downside Dev: {:.4}, Sortino: {:?}, Sharpe: {:?}",
-          weighted_downside_deviation, portfolio_sortino, portfolio_sharpe);
+    info!("[DOWNSIDE_RISK] Downside risk computation COMPLETED for portfolio {}", portfolio_id);
+    info!("[DOWNSIDE_RISK] Portfolio metrics - Downside Dev: {:.4}, Sortino: {:?}, Sharpe: {:?}",
+          portfolio_downside_deviation, ratios.sortino, ratios.sharpe);
 
     Ok(crate::models::risk::PortfolioDownsideRisk {
         portfolio_id: portfolio_id.to_string(),
         portfolio_metrics,
+        weighted_average_sortino: if sortino_count > 0 { Some(weighted_sortino) } else { None },
+        weighted_average_sharpe: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
         position_downside_risks,
         days,
         benchmark: benchmark.to_string(),
@@ -1434,11 +1526,11 @@ pub async fn compute_rolling_beta(
     }
 
     // Fetch price data for both ticker and benchmark
-    let ticker_prices = price_queries::fetch_window(pool, ticker, total_days)
+    let ticker_prices = price_queries::fetch_adjusted_window(pool, ticker, total_days)
         .await
         .map_err(|e| AppError::Db(e))?;
 
-    let benchmark_prices = price_queries::fetch_window(pool, benchmark, total_days)
+    let benchmark_prices = price_queries::fetch_adjusted_window(pool, benchmark, total_days)
         .await
         .map_err(|e| AppError::Db(e))?;
 
@@ -1588,48 +1680,15 @@ fn calculate_rolling_beta_window(
             .map(|w| (w[1].1 - w[0].1) / w[0].1)
             .collect();
 
-        if ticker_returns.len() != benchmark_returns.len() || ticker_returns.is_empty() {
+        let Some((beta, alpha, r_squared)) = beta_regression_from_returns(&ticker_returns, &benchmark_returns) else {
             continue;
-        }
-
-        // Calculate beta and r-squared for this window
-        let mean_ticker = ticker_returns.iter().sum::<f64>() / ticker_returns.len() as f64;
-        let mean_bench = benchmark_returns.iter().sum::<f64>() / benchmark_returns.len() as f64;
-
-        let mut covariance = 0.0;
-        let mut var_bench = 0.0;
-        let mut var_ticker = 0.0;
-
-        for (tr, br) in ticker_returns.iter().zip(benchmark_returns.iter()) {
-            let diff_ticker = tr - mean_ticker;
-            let diff_bench = br - mean_bench;
-            covariance += diff_ticker * diff_bench;
-            var_bench += diff_bench * diff_bench;
-            var_ticker += diff_ticker * diff_ticker;
-        }
-
-        if var_bench.abs() < f64::EPSILON {
-            continue;
-        }
-
-        let beta = covariance / var_bench;
-
-        // Calculate r-squared (correlation^2)
-        let correlation = if var_ticker.abs() < f64::EPSILON || var_bench.abs() < f64::EPSILON {
-            0.0
-        } else {
-            covariance / (var_ticker.sqrt() * var_bench.sqrt())
         };
-        let r_squared = correlation * correlation;
-
-        // Alpha is mean_ticker - beta * mean_bench (annualized)
-        let alpha = Some((mean_ticker - beta * mean_bench) * 252.0 * 100.0);
 
         beta_points.push(BetaPoint {
             date: ticker_data[i].0.format("%Y-%m-%d").to_string(),
             beta,
             r_squared,
-            alpha,
+            alpha: Some(alpha),
         });
     }
 
@@ -1681,6 +1740,20 @@ mod tests {
         assert!(dd <= -20.0); // At least -20% drawdown
     }
 
+    #[test]
+    fn test_beta_regression_from_returns() {
+        let bench = [0.01, -0.02, 0.015, 0.005, -0.01];
+        let returns: Vec<f64> = bench.iter().map(|b| 2.0 * b + 0.001).collect();
+
+        let (beta, alpha, r_squared) = beta_regression_from_returns(&returns, &bench).unwrap();
+        assert!((beta - 2.0).abs() < 1e-9);
+        assert!((alpha - 25.2).abs() < 1e-6);
+        assert!((r_squared - 1.0).abs() < 1e-9);
+
+        assert!(beta_regression_from_returns(&[0.01, 0.02], &[0.01, 0.01]).is_none());
+        assert!(beta_regression_from_returns(&returns, &bench[1..]).is_none());
+    }
+
     #[test]
     fn test_score_risk_zero_risk() {
         let risk = PositionRisk {
@@ -1729,6 +1802,17 @@ mod tests {
         assert_eq!(score, 100.0); // Should hit max score
     }
 
+    #[test]
+    fn test_scoring_models_describe_current_version() {
+        let models = scoring_models();
+        assert_eq!(models.iter().filter(|m| m.current).count(), 1);
+        let current = models.iter().find(|m| m.current).unwrap();
+        assert_eq!(current.version, CURRENT_SCORING_VERSION);
+        let total: f64 = current.components.iter().map(|c| c.max_points).sum();
+        assert_eq!(total, 100.0);
+        assert!(scoring_weights(0).is_none());
+    }
+
     #[test]
     fn test_risk_level_classification() {
         assert_eq!(RiskLevel::from_score(20.0), RiskLevel::Low);
@@ -1857,3 +1941,111 @@ mod tests {
         assert!(es_95.unwrap() >= 0.0, "CVaR 95% should be non-negative with all positive returns");
     }
 }
+
+#[cfg(test)]
+mod property_tests {
+    use super::*;
+    use bigdecimal::{BigDecimal, FromPrimitive};
+    use chrono::{Duration, NaiveDate, Utc};
+    use proptest::prelude::*;
+    use uuid::Uuid;
+
+    /// Daily closes starting at `start` that realise the given returns
+    fn series_from_returns(start: f64, returns: &[f64]) -> Vec<PricePoint> {
+        let first_day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut price = start;
+        std::iter::once(start)
+            .chain(returns.iter().map(|r| {
+                price *= 1.0 + r;
+                price
+            }))
+            .enumerate()
+            .map(|(i, close)| PricePoint {
+                id: Uuid::new_v4(),
+                ticker: "PROP".to_string(),
+                date: first_day + Duration::days(i as i64),
+                close_price: BigDecimal::from_f64(close).unwrap(),
+                created_at: Utc::now(),
+            })
+            .collect()
+    }
+
+    fn daily_returns(len: std::ops::Range<usize>) -> impl Strategy<Value = Vec<f64>> {
+        prop::collection::vec(-0.15f64..0.15, len)
+    }
+
+    /// Equal-length return series for two assets
+    fn return_pair() -> impl Strategy<Value = (Vec<f64>, Vec<f64>)> {
+        (10usize..120).prop_flat_map(|n| (daily_returns(n..n + 1), daily_returns(n..n + 1)))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_correlation_is_bounded((a, b) in return_pair()) {
+            let corr = compute_correlation(&series_from_returns(100.0, &a), &series_from_returns(50.0, &b));
+            if let Some(corr) = corr {
+                prop_assert!((-1.0..=1.0).contains(&corr), "correlation {} out of range", corr);
+            }
+        }
+
+        #[test]
+        fn prop_self_correlation_is_one(returns in daily_returns(10..120)) {
+            let series = series_from_returns(100.0, &returns);
+            if let Some(corr) = compute_correlation(&series, &series) {
+                prop_assert!((corr - 1.0).abs() < 1e-9);
+            }
+        }
+
+        #[test]
+        fn prop_var_99_is_at_least_var_95(returns in daily_returns(2..400)) {
+            let (var_95, var_99) = compute_var_multi(&series_from_returns(100.0, &returns));
+            let (var_95, var_99) = (var_95.unwrap(), var_99.unwrap());
+            // Both are negative percentages, so the deeper 99% loss is the smaller number
+            prop_assert!(var_95 >= var_99, "VaR95 {} < VaR99 {}", var_95, var_99);
+        }
+
+        #[test]
+        fn prop_portfolio_vol_at_most_weighted_vols(
+            (assets, raw_weights) in (2usize..6, 10usize..120).prop_flat_map(|(k, n)| (
+                prop::collection::vec(daily_returns(n..n + 1), k),
+                prop::collection::vec(0.01f64..1.0, k),
+            ))
+        ) {
+            let total: f64 = raw_weights.iter().sum();
+            let weights: Vec<f64> = raw_weights.iter().map(|w| w / total).collect();
+
+            // Daily-rebalanced portfolio: each day's return is the weighted asset return
+            let portfolio_returns: Vec<f64> = (0..assets[0].len())
+                .map(|t| assets.iter().zip(&weights).map(|(r, w)| w * r[t]).sum())
+                .collect();
+            let (portfolio_vol, _) = compute_vol_drawdown(&series_from_returns(100.0, &portfolio_returns));
+
+            let weighted_vol: f64 = assets
+                .iter()
+                .zip(&weights)
+                .map(|(r, w)| w * compute_vol_drawdown(&series_from_returns(100.0, r)).0)
+                .sum();
+
+            prop_assert!(
+                portfolio_vol <= weighted_vol + 1e-6 * weighted_vol.max(1.0),
+                "portfolio vol {} > weighted vol {}", portfolio_vol, weighted_vol
+            );
+        }
+
+        #[test]
+        fn prop_beta_is_price_scale_invariant(
+            (asset, bench) in return_pair(),
+            asset_scale in 0.01f64..100.0,
+            bench_scale in 0.01f64..100.0,
+        ) {
+            let beta = compute_beta(&series_from_returns(100.0, &asset), &series_from_returns(100.0, &bench));
+            let scaled = compute_beta(
+                &series_from_returns(100.0 * asset_scale, &asset),
+                &series_from_returns(100.0 * bench_scale, &bench),
+            );
+            prop_assume!(beta.is_some());
+            let (beta, scaled) = (beta.unwrap(), scaled.unwrap());
+            prop_assert!((beta - scaled).abs() < 1e-6 * beta.abs().max(1.0), "beta {} vs {}", beta, scaled);
+        }
+    }
+}
@@ -1,18 +1,102 @@
 use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
-use chrono::{Datelike, Duration, NaiveDate, Utc};
+use chrono::{Datelike, Duration, NaiveDate};
 use sqlx::PgPool;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tracing::{info, warn};
 use uuid::Uuid;
 
-use crate::db::{holding_snapshot_queries, risk_snapshot_queries};
+use crate::db::tenant::TenantScope;
+use crate::db::{holding_snapshot_queries, price_queries, risk_cache_queries, risk_snapshot_queries};
 use crate::errors::AppError;
 use crate::external::price_provider::PriceProvider;
-use crate::models::risk_snapshot::{Aggregation, CreateRiskSnapshot, RiskAlert, RiskSnapshot};
+use crate::models::risk_snapshot::{
+    Aggregation, ChangeDriver, CreateRiskSnapshot, RiskAlert, RiskSnapshot, SnapshotBaseline,
+};
 use crate::models::RiskLevel;
 use crate::services::failure_cache::FailureCache;
 use crate::services::rate_limiter::RateLimiter;
-use crate::services::risk_service;
+use crate::services::{portfolio_return_service, risk_service};
+use crate::services::clock;
+
+/// Benchmark position snapshots report their beta against
+const SNAPSHOT_BENCHMARK: &str = "SPY";
+/// Incremental runs fall back to a full recompute once the last one is this old
+const FULL_RECOMPUTE_DAYS: i64 = 7;
+/// Risk score move (in points) from the previous snapshot worth explaining
+const MATERIAL_SCORE_CHANGE: f64 = 5.0;
+/// Change in a position's weight (as a fraction) reported as a weight shift
+const WEIGHT_SHIFT_THRESHOLD: f64 = 0.05;
+/// Price move (in percent) between snapshots reported as a price shock
+const PRICE_SHOCK_PCT: f64 = 10.0;
+/// Positions below this weight are ignored when explaining a change
+const NEGLIGIBLE_WEIGHT: f64 = 0.001;
+/// Correlation lookback the correlations job caches, recorded on snapshots
+const CORRELATION_DAYS: i32 = 90;
+/// Day-over-day jumps a metric's latest jump is compared against
+const ANOMALY_WINDOW: usize = 20;
+/// Fewest prior jumps needed before a jump can be called anomalous
+const ANOMALY_MIN_BASELINE: usize = 10;
+/// Spread of prior jumps below which a metric is treated as flat
+const ANOMALY_MIN_STD: f64 = 1e-6;
+
+/// Reads one anomaly-tracked metric off a snapshot
+type MetricReader = fn(&RiskSnapshot) -> Option<f64>;
+
+/// How much of a portfolio's daily snapshot has to be recomputed
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotPlan {
+    /// Recompute every position and the portfolio
+    Full,
+    /// Recompute these repriced positions and the portfolio; carry the other
+    /// positions forward
+    Incremental(HashSet<String>),
+    /// Nothing changed since the last snapshot; copy it to the new date
+    CarryForward,
+}
+
+/// Outcome of an incremental snapshot run for one portfolio
+#[derive(Debug)]
+pub struct SnapshotRun {
+    pub plan: SnapshotPlan,
+    pub recomputed: usize,
+    pub carried_forward: u64,
+}
+
+/// Decide what to recompute given the previous snapshot and what changed since.
+///
+/// New holdings, positions that are no longer held, a scoring model change or
+/// a new benchmark close (which moves every position's beta) all force a full
+/// recompute, as does a last full recompute `FULL_RECOMPUTE_DAYS` or more ago.
+/// Otherwise only the `repriced` tickers are recomputed.
+pub fn plan_snapshot(
+    baseline: Option<&SnapshotBaseline>,
+    date: NaiveDate,
+    held: &HashSet<String>,
+    holdings_changed: bool,
+    repriced: &HashSet<String>,
+) -> SnapshotPlan {
+    let Some(baseline) = baseline else {
+        return SnapshotPlan::Full;
+    };
+
+    let full_is_recent = baseline
+        .last_full_date
+        .is_some_and(|last| (date - last).num_days() < FULL_RECOMPUTE_DAYS);
+    let dropped_positions = baseline.position_tickers.iter().any(|t| !held.contains(t));
+
+    if baseline.scoring_version != risk_service::CURRENT_SCORING_VERSION
+        || !full_is_recent
+        || holdings_changed
+        || dropped_positions
+        || repriced.contains(SNAPSHOT_BENCHMARK)
+    {
+        SnapshotPlan::Full
+    } else if repriced.is_empty() {
+        SnapshotPlan::CarryForward
+    } else {
+        SnapshotPlan::Incremental(repriced.clone())
+    }
+}
 
 /// Create daily risk snapshots for a portfolio and all its positions
 pub async fn create_daily_snapshots(
@@ -24,25 +108,110 @@ pub async fn create_daily_snapshots(
     rate_limiter: &RateLimiter,
     risk_free_rate: f64,
 ) -> Result<Vec<RiskSnapshot>, AppError> {
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id)
+        .await?;
+
+    let (snapshots, _) = snapshot_holdings(
+        pool, portfolio_id, date, &holdings, None, price_provider, failure_cache, rate_limiter, risk_free_rate,
+    )
+    .await?;
+    Ok(snapshots)
+}
+
+/// Create the daily snapshots for a portfolio, recomputing only what changed
+/// since its previous snapshot (see [`plan_snapshot`])
+pub async fn create_incremental_snapshots(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    date: NaiveDate,
+    price_provider: &dyn PriceProvider,
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
+    risk_free_rate: f64,
+) -> Result<SnapshotRun, AppError> {
+    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id)
+        .await?;
+    let held: HashSet<String> = holdings.iter().map(|h| h.ticker.clone()).collect();
+
+    let baseline = risk_snapshot_queries::fetch_baseline(pool, portfolio_id).await?;
+    let plan = match &baseline {
+        Some(base) => {
+            let holdings_changed =
+                holding_snapshot_queries::holdings_changed_since(pool, portfolio_id, base.created_at).await?;
+            let mut watched: Vec<String> = held.iter().cloned().collect();
+            watched.push(SNAPSHOT_BENCHMARK.to_string());
+            let repriced: HashSet<String> = price_queries::fetch_tickers_added_since(pool, &watched, base.created_at)
+                .await?
+                .into_iter()
+                .collect();
+            plan_snapshot(Some(base), date, &held, holdings_changed, &repriced)
+        }
+        None => SnapshotPlan::Full,
+    };
+
+    let (recomputed, carried_forward) = match (&plan, &baseline) {
+        (SnapshotPlan::CarryForward, Some(base)) => {
+            let carried = risk_snapshot_queries::carry_forward(pool, portfolio_id, base.snapshot_date, date, None).await?;
+            (0, carried)
+        }
+        (SnapshotPlan::Incremental(repriced), Some(base)) => {
+            let (snapshots, carried) = snapshot_holdings(
+                pool,
+                portfolio_id,
+                date,
+                &holdings,
+                Some((base.snapshot_date, repriced)),
+                price_provider,
+                failure_cache,
+                rate_limiter,
+                risk_free_rate,
+            )
+            .await?;
+            (snapshots.len(), carried)
+        }
+        _ => {
+            let (snapshots, _) = snapshot_holdings(
+                pool, portfolio_id, date, &holdings, None, price_provider, failure_cache, rate_limiter, risk_free_rate,
+            )
+            .await?;
+            (snapshots.len(), 0)
+        }
+    };
+
+    Ok(SnapshotRun { plan, recomputed, carried_forward })
+}
+
+/// Snapshot the given holdings. With `carry` set to the previous snapshot date
+/// and the repriced tickers, other positions are carried forward from that date
+/// rather than recomputed. Returns the new snapshots and the number carried.
+#[allow(clippy::too_many_arguments)]
+async fn snapshot_holdings(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    date: NaiveDate,
+    holdings: &[crate::models::LatestAccountHolding],
+    carry: Option<(NaiveDate, &HashSet<String>)>,
+    price_provider: &dyn PriceProvider,
+    failure_cache: &FailureCache,
+    rate_limiter: &RateLimiter,
+    risk_free_rate: f64,
+) -> Result<(Vec<RiskSnapshot>, u64), AppError> {
     info!(
         "Creating risk snapshots for portfolio {} on {}",
         portfolio_id, date
     );
 
-    // Fetch all holdings for the portfolio
-    let holdings = holding_snapshot_queries::fetch_portfolio_latest_holdings(pool, portfolio_id)
-        .await?;
-
     if holdings.is_empty() {
         return Err(AppError::NotFound("No holdings found for portfolio".to_string()));
     }
 
     let mut snapshots = Vec::new();
+    let mut unchanged = Vec::new();
 
     // Aggregate holdings by ticker (same ticker across multiple accounts)
     let mut ticker_aggregates: HashMap<String, (f64, f64)> = HashMap::new(); // (quantity, market_value)
 
-    for holding in &holdings {
+    for holding in holdings {
         let market_value = holding.market_value.to_f64().unwrap_or(0.0);
         let quantity = holding.quantity.to_f64().unwrap_or(0.0);
 
@@ -55,8 +224,16 @@ pub async fn create_daily_snapshots(
             .or_insert((quantity, market_value));
     }
 
+    let tickers: Vec<String> = ticker_aggregates.keys().cloned().collect();
+    risk_service::prefetch_prices(pool, &tickers, SNAPSHOT_BENCHMARK, price_provider, failure_cache, rate_limiter).await;
+
     // Create position-level snapshots for each unique ticker
     for (ticker, (_quantity, market_value)) in &ticker_aggregates {
+        if carry.is_some_and(|(_, repriced)| !repriced.contains(ticker)) {
+            unchanged.push(ticker.clone());
+            continue;
+        }
+
         match create_position_snapshot(
             pool,
             portfolio_id,
@@ -90,13 +267,21 @@ pub async fn create_daily_snapshots(
         }
     }
 
+    let carried = match carry {
+        Some((previous, _)) if !unchanged.is_empty() => {
+            risk_snapshot_queries::carry_forward(pool, portfolio_id, previous, date, Some(&unchanged)).await?
+        }
+        _ => 0,
+    };
+
     info!(
-        "Created {} snapshots for portfolio {}",
+        "Created {} snapshots for portfolio {} ({} positions carried forward)",
         snapshots.len(),
-        portfolio_id
+        portfolio_id,
+        carried
     );
 
-    Ok(snapshots)
+    Ok((snapshots, carried))
 }
 
 /// Create a snapshot for a single position
@@ -134,8 +319,11 @@ async fn create_position_snapshot(
         expected_shortfall_99: position_risk.expected_shortfall_99.and_then(|v| BigDecimal::from_f64(v)),
         risk_score: BigDecimal::from_f64(risk_assessment.risk_score).unwrap_or_else(|| BigDecimal::from(0)),
         risk_level: risk_assessment.risk_level.to_string(),
+        scoring_version: risk_assessment.scoring_version,
         total_value: None,
         market_value: Some(BigDecimal::from_f64(market_value).unwrap_or_else(|| BigDecimal::from(0))),
+        change_drivers: None,
+        avg_correlation: None,
     };
 
     risk_snapshot_queries::upsert_snapshot(pool, snapshot)
@@ -167,13 +355,11 @@ async fn create_portfolio_snapshot(
     let mut weighted_volatility = 0.0;
     let mut weighted_max_drawdown = 0.0;
     let mut weighted_beta = 0.0;
-    let mut weighted_sharpe = 0.0;
     let mut weighted_var_95 = 0.0;
     let mut weighted_var_99 = 0.0;
     let mut weighted_es_95 = 0.0;
     let mut weighted_es_99 = 0.0;
     let mut beta_count = 0;
-    let mut sharpe_count = 0;
     let mut var_95_count = 0;
     let mut var_99_count = 0;
     let mut es_95_count = 0;
@@ -204,11 +390,6 @@ async fn create_portfolio_snapshot(
                     beta_count += 1;
                 }
 
-                if let Some(sharpe) = assessment.metrics.sharpe {
-                    weighted_sharpe += sharpe * weight;
-                    sharpe_count += 1;
-                }
-
                 if let Some(var_95) = assessment.metrics.var_95 {
                     weighted_var_95 += var_95 * weight;
                     var_95_count += 1;
@@ -235,17 +416,28 @@ async fn create_portfolio_snapshot(
         }
     }
 
+    // Volatility, drawdown, Sharpe and VaR come from the portfolio's own daily
+    // returns, falling back to the weighted averages for too little history
+    let returns = portfolio_return_service::compute_metrics(pool, portfolio_id, 90, "SPY", risk_free_rate)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Could not build the return series for portfolio {}: {}", portfolio_id, e);
+            Default::default()
+        });
+    let volatility = returns.volatility.unwrap_or(weighted_volatility);
+    let max_drawdown = returns.max_drawdown.unwrap_or(weighted_max_drawdown);
+
     // Calculate portfolio-level risk score
     let portfolio_risk_score = risk_service::score_risk(&crate::models::PositionRisk {
-        volatility: weighted_volatility,
-        max_drawdown: weighted_max_drawdown,
+        volatility,
+        max_drawdown,
         beta: if beta_count > 0 { Some(weighted_beta) } else { None },
         beta_spy: if beta_count > 0 { Some(weighted_beta) } else { None },
         beta_qqq: None,
         beta_iwm: None,
         risk_decomposition: None,
-        sharpe: if sharpe_count > 0 { Some(weighted_sharpe) } else { None },
-        sortino: None,
+        sharpe: returns.sharpe,
+        sortino: returns.sortino,
         annualized_return: None,
         value_at_risk: None,
         var_95: None,
@@ -256,24 +448,39 @@ async fn create_portfolio_snapshot(
 
     let risk_level = RiskLevel::from_score(portfolio_risk_score);
 
+    let change_drivers = explain_score_change(pool, portfolio_id, date, portfolio_risk_score, ticker_aggregates, total_value)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Could not explain the risk score change for portfolio {}: {}", portfolio_id, e);
+            None
+        });
+
+    let avg_correlation = cached_average_correlation(pool, portfolio_id).await.unwrap_or_else(|e| {
+        warn!("Could not read cached correlations for portfolio {}: {}", portfolio_id, e);
+        None
+    });
+
     let snapshot = CreateRiskSnapshot {
         portfolio_id,
         ticker: None,
         snapshot_date: date,
         snapshot_type: "portfolio".to_string(),
-        volatility: BigDecimal::from_f64(weighted_volatility).unwrap_or_else(|| BigDecimal::from(0)),
-        max_drawdown: BigDecimal::from_f64(weighted_max_drawdown).unwrap_or_else(|| BigDecimal::from(0)),
+        volatility: BigDecimal::from_f64(volatility).unwrap_or_else(|| BigDecimal::from(0)),
+        max_drawdown: BigDecimal::from_f64(max_drawdown).unwrap_or_else(|| BigDecimal::from(0)),
         beta: if beta_count > 0 { BigDecimal::from_f64(weighted_beta) } else { None },
-        sharpe: if sharpe_count > 0 { BigDecimal::from_f64(weighted_sharpe) } else { None },
+        sharpe: returns.sharpe.and_then(BigDecimal::from_f64),
         value_at_risk: None,
-        var_95: if var_95_count > 0 { BigDecimal::from_f64(weighted_var_95) } else { None },
-        var_99: if var_99_count > 0 { BigDecimal::from_f64(weighted_var_99) } else { None },
-        expected_shortfall_95: if es_95_count > 0 { BigDecimal::from_f64(weighted_es_95) } else { None },
-        expected_shortfall_99: if es_99_count > 0 { BigDecimal::from_f64(weighted_es_99) } else { None },
+        var_95: returns.var_95.or(if var_95_count > 0 { Some(weighted_var_95) } else { None }).and_then(BigDecimal::from_f64),
+        var_99: returns.var_99.or(if var_99_count > 0 { Some(weighted_var_99) } else { None }).and_then(BigDecimal::from_f64),
+        expected_shortfall_95: returns.expected_shortfall_95.or(if es_95_count > 0 { Some(weighted_es_95) } else { None }).and_then(BigDecimal::from_f64),
+        expected_shortfall_99: returns.expected_shortfall_99.or(if es_99_count > 0 { Some(weighted_es_99) } else { None }).and_then(BigDecimal::from_f64),
         risk_score: BigDecimal::from_f64(portfolio_risk_score).unwrap_or_else(|| BigDecimal::from(0)),
         risk_level: risk_level.to_string(),
+        scoring_version: risk_service::CURRENT_SCORING_VERSION,
         total_value: Some(BigDecimal::from_f64(total_value).unwrap_or_else(|| BigDecimal::from(0))),
         market_value: None,
+        change_drivers,
+        avg_correlation: avg_correlation.and_then(BigDecimal::from_f64),
     };
 
     risk_snapshot_queries::upsert_snapshot(pool, snapshot)
@@ -281,6 +488,163 @@ async fn create_portfolio_snapshot(
         .map_err(|e| AppError::Db(e))
 }
 
+/// Average pairwise correlation from the portfolio's fresh correlation cache,
+/// if the correlations job has computed one
+async fn cached_average_correlation(pool: &PgPool, portfolio_id: Uuid) -> Result<Option<f64>, sqlx::Error> {
+    let Some(tenant) = TenantScope::for_job(pool, portfolio_id).await? else {
+        return Ok(None);
+    };
+    let cached = risk_cache_queries::fetch_fresh_correlations(pool, &tenant, CORRELATION_DAYS).await?;
+    Ok(cached.map(|c| c.statistics.average_correlation))
+}
+
+/// Work out why the portfolio's risk score moved since its previous snapshot.
+///
+/// Returns `None` unless there is a previous snapshot under the same scoring
+/// version and the score moved by at least `MATERIAL_SCORE_CHANGE` points.
+async fn explain_score_change(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    date: NaiveDate,
+    risk_score: f64,
+    ticker_aggregates: &HashMap<String, (f64, f64)>,
+    total_value: f64,
+) -> Result<Option<Vec<ChangeDriver>>, sqlx::Error> {
+    let Some(previous) = risk_snapshot_queries::fetch_previous(pool, portfolio_id, date).await? else {
+        return Ok(None);
+    };
+    let previous_score = previous.risk_score.to_f64().unwrap_or(0.0);
+    if previous.scoring_version != risk_service::CURRENT_SCORING_VERSION
+        || (risk_score - previous_score).abs() < MATERIAL_SCORE_CHANGE
+    {
+        return Ok(None);
+    }
+
+    let previous_positions =
+        risk_snapshot_queries::fetch_portfolio_positions_by_date(pool, portfolio_id, previous.snapshot_date).await?;
+    let previous_values: HashMap<String, f64> = previous_positions
+        .iter()
+        .filter_map(|p| Some((p.ticker.clone()?, p.market_value.as_ref()?.to_f64()?)))
+        .collect();
+    let previous_total = previous
+        .total_value
+        .as_ref()
+        .and_then(|v| v.to_f64())
+        .filter(|v| *v > 0.0)
+        .unwrap_or_else(|| previous_values.values().sum());
+
+    let previous_weights = weights(&previous_values, previous_total);
+    let current_weights = weights(
+        &ticker_aggregates.iter().map(|(t, (_, mv))| (t.clone(), *mv)).collect(),
+        total_value,
+    );
+
+    let mut price_changes = HashMap::new();
+    for ticker in current_weights.keys().filter(|t| previous_weights.contains_key(*t)) {
+        let before = price_queries::fetch_close_on_or_before(pool, ticker, previous.snapshot_date).await?;
+        let after = price_queries::fetch_close_on_or_before(pool, ticker, date).await?;
+        if let (Some(before), Some(after)) = (before, after) {
+            let (before, after) = (before.close_price.to_f64().unwrap_or(0.0), after.close_price.to_f64().unwrap_or(0.0));
+            if before > 0.0 {
+                price_changes.insert(ticker.clone(), (after / before - 1.0) * 100.0);
+            }
+        }
+    }
+
+    Ok(Some(change_drivers(&previous_weights, &current_weights, &price_changes)))
+}
+
+/// Market values as fractions of `total`, leaving out negligible positions
+fn weights(values: &HashMap<String, f64>, total: f64) -> HashMap<String, f64> {
+    if total <= 0.0 {
+        return HashMap::new();
+    }
+    values
+        .iter()
+        .map(|(ticker, value)| (ticker.clone(), value / total))
+        .filter(|(_, weight)| *weight >= NEGLIGIBLE_WEIGHT)
+        .collect()
+}
+
+/// Structural changes between two sets of position weights: positions added
+/// and removed, price shocks in positions held throughout (`price_changes` in
+/// percent) and weight shifts. Each kind is ordered by its size, largest first.
+pub fn change_drivers(
+    previous: &HashMap<String, f64>,
+    current: &HashMap<String, f64>,
+    price_changes: &HashMap<String, f64>,
+) -> Vec<ChangeDriver> {
+    let mut added = Vec::new();
+    let mut shocks = Vec::new();
+    let mut shifts = Vec::new();
+
+    for (ticker, &weight) in current {
+        let Some(&previous_weight) = previous.get(ticker) else {
+            added.push(ChangeDriver::PositionAdded { ticker: ticker.clone(), weight });
+            continue;
+        };
+        if let Some(&price_change_pct) = price_changes.get(ticker).filter(|p| p.abs() >= PRICE_SHOCK_PCT) {
+            shocks.push(ChangeDriver::PriceShock { ticker: ticker.clone(), price_change_pct, weight });
+        }
+        if (weight - previous_weight).abs() >= WEIGHT_SHIFT_THRESHOLD {
+            shifts.push(ChangeDriver::WeightShift { ticker: ticker.clone(), previous_weight, weight });
+        }
+    }
+
+    let mut removed: Vec<ChangeDriver> = previous
+        .iter()
+        .filter(|(ticker, _)| !current.contains_key(*ticker))
+        .map(|(ticker, &previous_weight)| ChangeDriver::PositionRemoved { ticker: ticker.clone(), previous_weight })
+        .collect();
+
+    let size = |driver: &ChangeDriver| match driver {
+        ChangeDriver::PositionAdded { weight, .. } => *weight,
+        ChangeDriver::PositionRemoved { previous_weight, .. } => *previous_weight,
+        ChangeDriver::PriceShock { price_change_pct, weight, .. } => (price_change_pct * weight).abs(),
+        ChangeDriver::WeightShift { previous_weight, weight, .. } => (weight - previous_weight).abs(),
+    };
+    let mut drivers = Vec::new();
+    for group in [&mut added, &mut removed, &mut shocks, &mut shifts] {
+        group.sort_by(|a, b| {
+            size(b)
+                .partial_cmp(&size(a))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.ticker().cmp(b.ticker()))
+        });
+        drivers.append(group);
+    }
+    drivers
+}
+
+/// Lines explaining the most recent material risk score changes in the last
+/// `days`, newest first, for narrative prompts
+pub async fn recent_change_lines(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    days: i64,
+    limit: usize,
+) -> Result<Vec<String>, AppError> {
+    let end_date = clock::today();
+    let history =
+        risk_snapshot_queries::fetch_history(pool, portfolio_id, None, end_date - Duration::days(days), end_date).await?;
+
+    Ok(history
+        .windows(2)
+        .rev()
+        .filter_map(|pair| {
+            let drivers = pair[1].change_drivers.as_ref().filter(|d| !d.is_empty())?;
+            Some(format!(
+                "{}: risk score {:.1} -> {:.1} ({})",
+                pair[1].snapshot_date,
+                pair[0].risk_score.to_f64().unwrap_or(0.0),
+                pair[1].risk_score.to_f64().unwrap_or(0.0),
+                drivers.iter().map(ChangeDriver::describe).collect::<Vec<_>>().join("; ")
+            ))
+        })
+        .take(limit)
+        .collect())
+}
+
 /// Detect significant risk increases (>threshold% in risk score)
 pub async fn detect_risk_increases(
     pool: &PgPool,
@@ -288,7 +652,7 @@ pub async fn detect_risk_increases(
     lookback_days: i64,
     threshold: f64,
 ) -> Result<Vec<RiskAlert>, AppError> {
-    let end_date = Utc::now().date_naive();
+    let end_date = clock::today();
     let start_date = end_date - Duration::days(lookback_days);
 
     // Fetch portfolio-level history
@@ -303,6 +667,11 @@ pub async fn detect_risk_increases(
         let prev = &history[i - 1];
         let curr = &history[i];
 
+        // Scores from different methodology versions aren't comparable
+        if prev.scoring_version != curr.scoring_version {
+            continue;
+        }
+
         let prev_score = prev.risk_score.to_f64().unwrap_or(0.0);
         let curr_score = curr.risk_score.to_f64().unwrap_or(0.0);
 
@@ -319,6 +688,7 @@ pub async fn detect_risk_increases(
                     change_percent,
                     date: curr.snapshot_date,
                     metric_name: "risk_score".to_string(),
+                    z_score: None,
                 });
             }
         }
@@ -327,6 +697,105 @@ pub async fn detect_risk_increases(
     Ok(alerts)
 }
 
+/// A day-over-day jump in a snapshot metric that stands out from recent jumps
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricAnomaly {
+    pub date: NaiveDate,
+    pub previous: f64,
+    pub current: f64,
+    pub z_score: f64,
+}
+
+/// Rolling z-score control chart over the day-over-day changes of a metric
+/// series (oldest first). Each change is scored against the mean and standard
+/// deviation of up to `ANOMALY_WINDOW` changes before it and flagged when it
+/// lies `z_threshold` or more deviations away. Nothing is flagged until
+/// `ANOMALY_MIN_BASELINE` changes have been seen, nor against a flat baseline.
+pub fn jump_anomalies(series: &[(NaiveDate, f64)], z_threshold: f64) -> Vec<MetricAnomaly> {
+    let jumps: Vec<f64> = series.windows(2).map(|pair| pair[1].1 - pair[0].1).collect();
+    let mut anomalies = Vec::new();
+
+    for (i, &jump) in jumps.iter().enumerate().skip(ANOMALY_MIN_BASELINE) {
+        let baseline = &jumps[i.saturating_sub(ANOMALY_WINDOW)..i];
+        let n = baseline.len() as f64;
+        let mean = baseline.iter().sum::<f64>() / n;
+        let std = (baseline.iter().map(|j| (j - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+        if std < ANOMALY_MIN_STD {
+            continue;
+        }
+
+        let z_score = (jump - mean) / std;
+        if z_score.abs() >= z_threshold {
+            anomalies.push(MetricAnomaly {
+                date: series[i + 1].0,
+                previous: series[i].1,
+                current: series[i + 1].1,
+                z_score,
+            });
+        }
+    }
+    anomalies
+}
+
+/// Flag statistically unusual jumps in portfolio volatility, beta and average
+/// correlation over the last `lookback_days` (see [`jump_anomalies`]).
+///
+/// Unlike [`detect_risk_increases`] there is no fixed threshold: each metric is
+/// judged against its own recent behaviour. Carried-forward and compacted
+/// snapshots are left out so repeated values don't shrink the baseline.
+pub async fn detect_metric_anomalies(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    lookback_days: i64,
+    z_threshold: f64,
+) -> Result<Vec<RiskAlert>, AppError> {
+    let end_date = clock::today();
+    let start_date = end_date - Duration::days(lookback_days);
+    // Reach back far enough that the first days in range have a full baseline
+    let history_start = start_date - Duration::days(2 * ANOMALY_WINDOW as i64);
+
+    let history = risk_snapshot_queries::fetch_history(pool, portfolio_id, None, history_start, end_date).await?;
+    let history: Vec<&RiskSnapshot> = history
+        .iter()
+        .filter(|s| s.granularity == "daily" && !s.carried_forward)
+        .collect();
+
+    let metrics: [(&str, MetricReader); 3] = [
+        ("volatility", |s| s.volatility.to_f64()),
+        ("beta", |s| s.beta.as_ref()?.to_f64()),
+        ("avg_correlation", |s| s.avg_correlation.as_ref()?.to_f64()),
+    ];
+
+    let mut alerts = Vec::new();
+    for (metric_name, value) in metrics {
+        let series: Vec<(NaiveDate, f64)> = history.iter().filter_map(|s| Some((s.snapshot_date, value(s)?))).collect();
+        for anomaly in jump_anomalies(&series, z_threshold) {
+            if anomaly.date < start_date {
+                continue;
+            }
+            let change_percent = if anomaly.previous != 0.0 {
+                (anomaly.current - anomaly.previous) / anomaly.previous.abs() * 100.0
+            } else {
+                0.0
+            };
+            alerts.push(RiskAlert {
+                portfolio_id: portfolio_id.to_string(),
+                ticker: None,
+                alert_type: "anomaly".to_string(),
+                previous_value: anomaly.previous,
+                current_value: anomaly.current,
+                change_percent,
+                date: anomaly.date,
+                metric_name: metric_name.to_string(),
+                z_score: Some(anomaly.z_score),
+            });
+        }
+    }
+    alerts.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.metric_name.cmp(&b.metric_name)));
+
+    Ok(alerts)
+}
+
 /// Get risk trend for visualization with optional aggregation
 pub async fn get_risk_trend(
     pool: &PgPool,
@@ -335,7 +804,7 @@ pub async fn get_risk_trend(
     days: i64,
     aggregation: Aggregation,
 ) -> Result<Vec<RiskSnapshot>, AppError> {
-    let end_date = Utc::now().date_naive();
+    let end_date = clock::today();
     let start_date = end_date - Duration::days(days);
 
     let history = risk_snapshot_queries::fetch_history(pool, portfolio_id, ticker, start_date, end_date)
@@ -403,3 +872,99 @@ fn aggregate_by_month(snapshots: Vec<RiskSnapshot>) -> Vec<RiskSnapshot> {
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+    }
+
+    fn tickers(list: &[&str]) -> HashSet<String> {
+        list.iter().map(|t| t.to_string()).collect()
+    }
+
+    fn baseline(last_full_day: u32) -> SnapshotBaseline {
+        SnapshotBaseline {
+            snapshot_date: date(27),
+            created_at: chrono::Utc::now(),
+            scoring_version: risk_service::CURRENT_SCORING_VERSION,
+            last_full_date: Some(date(last_full_day)),
+            position_tickers: vec!["AAPL".to_string(), "MSFT".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_plan_snapshot_recomputes_only_repriced_tickers() {
+        let held = tickers(&["AAPL", "MSFT"]);
+        let base = baseline(26);
+
+        assert_eq!(plan_snapshot(Some(&base), date(28), &held, false, &tickers(&[])), SnapshotPlan::CarryForward);
+        assert_eq!(
+            plan_snapshot(Some(&base), date(28), &held, false, &tickers(&["AAPL"])),
+            SnapshotPlan::Incremental(tickers(&["AAPL"]))
+        );
+    }
+
+    #[test]
+    fn test_plan_snapshot_falls_back_to_full() {
+        let held = tickers(&["AAPL", "MSFT"]);
+        let base = baseline(26);
+
+        assert_eq!(plan_snapshot(None, date(28), &held, false, &tickers(&[])), SnapshotPlan::Full);
+        // New benchmark close moves every beta
+        assert_eq!(plan_snapshot(Some(&base), date(28), &held, false, &tickers(&["SPY"])), SnapshotPlan::Full);
+        // Holdings were re-imported, or a position was sold
+        assert_eq!(plan_snapshot(Some(&base), date(28), &held, true, &tickers(&[])), SnapshotPlan::Full);
+        assert_eq!(plan_snapshot(Some(&base), date(28), &tickers(&["AAPL"]), false, &tickers(&[])), SnapshotPlan::Full);
+        // Weekly full recompute
+        assert_eq!(plan_snapshot(Some(&baseline(20)), date(27), &held, false, &tickers(&[])), SnapshotPlan::Full);
+        assert_eq!(plan_snapshot(Some(&baseline(21)), date(27), &held, false, &tickers(&[])), SnapshotPlan::CarryForward);
+
+        let mut rescored = baseline(26);
+        rescored.scoring_version += 1;
+        assert_eq!(plan_snapshot(Some(&rescored), date(28), &held, false, &tickers(&[])), SnapshotPlan::Full);
+    }
+
+    #[test]
+    fn test_jump_anomalies() {
+        // Volatility wobbling by +/-0.5 a day, then jumping by 6
+        let mut series: Vec<(NaiveDate, f64)> = (1..=15)
+            .map(|day| (date(day), 20.0 + if day % 2 == 0 { 0.5 } else { 0.0 }))
+            .collect();
+        series.push((date(16), 26.0));
+
+        let anomalies = jump_anomalies(&series, 3.0);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].date, date(16));
+        assert_eq!((anomalies[0].previous, anomalies[0].current), (20.0, 26.0));
+        assert!(anomalies[0].z_score > 3.0);
+
+        // Too little history to judge, and a flat baseline
+        assert!(jump_anomalies(&series[5..], 3.0).is_empty());
+        let flat: Vec<(NaiveDate, f64)> = (1..=15).map(|day| (date(day), 1.0)).chain([(date(16), 2.0)]).collect();
+        assert!(jump_anomalies(&flat, 3.0).is_empty());
+    }
+
+    #[test]
+    fn test_change_drivers() {
+        let weights = |list: &[(&str, f64)]| -> HashMap<String, f64> {
+            list.iter().map(|(t, w)| (t.to_string(), *w)).collect()
+        };
+        let previous = weights(&[("AAPL", 0.5), ("MSFT", 0.3), ("XOM", 0.2)]);
+        let current = weights(&[("AAPL", 0.42), ("MSFT", 0.28), ("TSLA", 0.3)]);
+        let price_changes = weights(&[("AAPL", -15.0), ("MSFT", 2.0)]);
+
+        assert_eq!(
+            change_drivers(&previous, &current, &price_changes),
+            vec![
+                ChangeDriver::PositionAdded { ticker: "TSLA".to_string(), weight: 0.3 },
+                ChangeDriver::PositionRemoved { ticker: "XOM".to_string(), previous_weight: 0.2 },
+                ChangeDriver::PriceShock { ticker: "AAPL".to_string(), price_change_pct: -15.0, weight: 0.42 },
+                ChangeDriver::WeightShift { ticker: "AAPL".to_string(), previous_weight: 0.5, weight: 0.42 },
+            ]
+        );
+        assert!(change_drivers(&previous, &previous, &HashMap::new()).is_empty());
+    }
+}
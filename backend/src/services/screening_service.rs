@@ -4,7 +4,15 @@ use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::models::screening::*;
+use crate::models::{AnalystConsensus, OwnershipSnapshot};
 use crate::services::indicators::{sma, rsi};
+use crate::external::fundamentals_provider::ExternalFundamentals;
+use crate::db::news_sentiment_queries;
+use crate::services::{analyst_service, fundamentals_service, ownership_service};
+use crate::services::clock;
+
+/// News sentiment older than this is treated as missing
+const SENTIMENT_MAX_AGE_DAYS: i32 = 7;
 
 pub struct ScreeningService {
     pool: PgPool,
@@ -19,13 +27,14 @@ impl ScreeningService {
     // Public entry point
     // -----------------------------------------------------------------------
 
-    pub async fn screen(&self, req: &ScreeningRequest) -> Result<ScreeningResponse, String> {
+    /// Screen for `user_id`; cached results are only ever served back to the same user
+    pub async fn screen(&self, user_id: Uuid, req: &ScreeningRequest) -> Result<ScreeningResponse, String> {
         let weights = req.weights.resolve(req.risk_appetite, req.horizon_months);
         let cache_key = self.build_cache_key(req);
 
         // Try cache first
         if !req.refresh {
-            if let Some(cached) = self.get_cached(&cache_key).await {
+            if let Some(cached) = self.get_cached(user_id, &cache_key).await {
                 info!("Screening cache hit for key {}", cache_key);
                 return Ok(cached);
             }
@@ -78,7 +87,7 @@ impl ScreeningService {
         };
 
         // Store in cache (fire-and-forget)
-        if let Err(e) = self.store_cache(&cache_key, &response).await {
+        if let Err(e) = self.store_cache(user_id, &cache_key, &response).await {
             warn!("Failed to store screening cache: {}", e);
         }
 
@@ -144,20 +153,10 @@ impl ScreeningService {
         let prices: Vec<f64> = price_rows.iter().map(|r| r.0).collect();
         let current_price = *prices.last().unwrap_or(&0.0);
 
-        // Fetch latest sentiment score if available
-        let sentiment_row: Option<(f64,)> = sqlx::query_as(
-            r#"SELECT CAST(sentiment_score AS DOUBLE PRECISION)
-               FROM sentiment_signal_cache
-               WHERE ticker = $1
-               ORDER BY generated_at DESC
-               LIMIT 1"#,
-        )
-        .bind(ticker)
-        .fetch_optional(&self.pool)
-        .await
-        .unwrap_or(None);
-
-        let sentiment_score = sentiment_row.map(|r| r.0);
+        // Latest news sentiment recorded by the sentiment cache job, if recent
+        let sentiment_score = news_sentiment_queries::fetch_latest_score(&self.pool, ticker, SENTIMENT_MAX_AGE_DAYS)
+            .await
+            .unwrap_or(None);
 
         // Fetch sector/industry from latest_account_holdings view if available
         let sector_row: Option<(Option<String>,)> = sqlx::query_as(
@@ -173,6 +172,32 @@ impl ScreeningService {
 
         let sector = sector_row.and_then(|r| r.0);
 
+        // Stored ownership data only; screening never calls the ownership provider
+        let ownership = ownership_service::stored(&self.pool, ticker)
+            .await
+            .unwrap_or(None)
+            .map(|(raw, fetched_at)| {
+                ownership_service::summarize(
+                    ticker,
+                    &raw,
+                    ownership_service::DEFAULT_ACTIVITY_DAYS,
+                    clock::today(),
+                    fetched_at,
+                )
+            });
+
+        // Stored analyst data only, priced against the screened close
+        let analyst = analyst_service::stored(&self.pool, ticker)
+            .await
+            .unwrap_or(None)
+            .map(|(raw, fetched_at)| analyst_service::summarize(ticker, &raw, Some(current_price), fetched_at));
+
+        // Stored fundamentals only; tickers never fetched keep the price proxies
+        let fundamentals = fundamentals_service::stored(&self.pool, ticker)
+            .await
+            .unwrap_or(None)
+            .map(|(raw, _)| raw);
+
         Ok(TickerData {
             symbol: ticker.to_string(),
             prices,
@@ -181,8 +206,11 @@ impl ScreeningService {
             sector,
             // We don't have real volume data in this schema, so we'll skip volume-based filters.
             avg_volume: None,
-            market_cap: None,
+            market_cap: fundamentals.as_ref().and_then(|f| f.market_cap),
             geography: None,
+            ownership,
+            analyst,
+            fundamentals,
         })
     }
 
@@ -252,6 +280,37 @@ impl ScreeningService {
             }
         }
 
+        // Ownership filters (when data available)
+        if let Some(ref ownership) = data.ownership {
+            if let (Some(min), Some(pct)) = (filters.min_insider_ownership_pct, ownership.insider_ownership_pct) {
+                if pct < min {
+                    return false;
+                }
+            }
+            if let (Some(min), Some(pct)) = (filters.min_institutional_ownership_pct, ownership.institutional_ownership_pct) {
+                if pct < min {
+                    return false;
+                }
+            }
+            if filters.insider_net_buying && ownership.insider_activity.net_shares <= 0 {
+                return false;
+            }
+        }
+
+        // Analyst filters (when data available)
+        if let Some(ref analyst) = data.analyst {
+            if let (Some(min), Some(upside)) = (filters.min_analyst_upside_pct, analyst.upside_pct) {
+                if upside < min {
+                    return false;
+                }
+            }
+            if let (Some(max), Some(rating)) = (filters.max_analyst_rating, analyst.recommendation_mean) {
+                if rating > max {
+                    return false;
+                }
+            }
+        }
+
         true
     }
 
@@ -292,35 +351,73 @@ impl ScreeningService {
         let mut details = Vec::new();
         let mut scores: Vec<f64> = Vec::new();
 
-        // P/E proxy: We don't have actual earnings data, so we derive a pseudo-valuation
+        let fundamentals = data.fundamentals.as_ref();
+
+        // P/E from stored fundamentals; without them, derive a pseudo-valuation
         // metric from price change stability (low volatility = value-like).
-        let pe_score = self.pseudo_pe_score(prices);
-        details.push(ScoreDetail {
-            metric: "P/E Proxy (volatility-adjusted)".into(),
-            raw_value: Some(pe_score),
-            score: pe_score,
-            interpretation: if pe_score > 60.0 {
-                "Attractively valued (low volatility, steady growth)".into()
-            } else if pe_score > 40.0 {
-                "Fairly valued".into()
-            } else {
-                "Appears expensive or volatile".into()
-            },
-        });
+        let pe_score = if let Some(pe) = fundamentals.and_then(|f| f.pe_ratio) {
+            let score = fundamentals_service::pe_ratio_score(pe);
+            details.push(ScoreDetail {
+                metric: "P/E Ratio (trailing)".into(),
+                raw_value: Some(pe),
+                score,
+                interpretation: if pe <= 0.0 {
+                    "Not profitable over the trailing year".into()
+                } else if score > 60.0 {
+                    "Attractively valued on earnings".into()
+                } else if score > 40.0 {
+                    "Fairly valued on earnings".into()
+                } else {
+                    "Expensive relative to earnings".into()
+                },
+            });
+            score
+        } else {
+            let score = self.pseudo_pe_score(prices);
+            details.push(ScoreDetail {
+                metric: "P/E Proxy (volatility-adjusted)".into(),
+                raw_value: Some(score),
+                score,
+                interpretation: if score > 60.0 {
+                    "Attractively valued (low volatility, steady growth)".into()
+                } else if score > 40.0 {
+                    "Fairly valued".into()
+                } else {
+                    "Appears expensive or volatile".into()
+                },
+            });
+            score
+        };
         scores.push(pe_score);
 
-        // P/B proxy: price relative to long-term average
-        let pb_score = self.price_to_avg_score(prices);
-        details.push(ScoreDetail {
-            metric: "P/B Proxy (price vs long-term avg)".into(),
-            raw_value: Some(pb_score),
-            score: pb_score,
-            interpretation: if pb_score > 60.0 {
-                "Trading below long-term average".into()
-            } else {
-                "Trading near or above long-term average".into()
-            },
-        });
+        // P/B from stored fundamentals; without them, price relative to long-term average
+        let pb_score = if let Some(pb) = fundamentals.and_then(|f| f.pb_ratio) {
+            let score = fundamentals_service::pb_ratio_score(pb);
+            details.push(ScoreDetail {
+                metric: "P/B Ratio".into(),
+                raw_value: Some(pb),
+                score,
+                interpretation: if score > 60.0 {
+                    "Trading close to book value".into()
+                } else {
+                    "Trading at a premium to book value".into()
+                },
+            });
+            score
+        } else {
+            let score = self.price_to_avg_score(prices);
+            details.push(ScoreDetail {
+                metric: "P/B Proxy (price vs long-term avg)".into(),
+                raw_value: Some(score),
+                score,
+                interpretation: if score > 60.0 {
+                    "Trading below long-term average".into()
+                } else {
+                    "Trading near or above long-term average".into()
+                },
+            });
+            score
+        };
         scores.push(pb_score);
 
         // PEG proxy: growth-adjusted valuation
@@ -880,18 +977,25 @@ impl ScreeningService {
         req.horizon_months.hash(&mut h);
         format!("{:?}", req.filters.sectors).hash(&mut h);
         format!("{:?}", req.filters.market_cap).hash(&mut h);
+        format!(
+            "{:?}{:?}{}",
+            req.filters.min_insider_ownership_pct,
+            req.filters.min_institutional_ownership_pct,
+            req.filters.insider_net_buying
+        )
+        .hash(&mut h);
+        format!("{:?}{:?}", req.filters.min_analyst_upside_pct, req.filters.max_analyst_rating).hash(&mut h);
 
         format!("screen_{:x}", h.finish())
     }
 
-    async fn get_cached(&self, cache_key: &str) -> Option<ScreeningResponse> {
+    async fn get_cached(&self, user_id: Uuid, cache_key: &str) -> Option<ScreeningResponse> {
         let row: Option<(serde_json::Value, i32, i32)> = sqlx::query_as(
             r#"SELECT results_json, total_screened, total_passed_filters
                FROM screening_cache
-               WHERE cache_key = $1 AND expires_at > NOW()
-               ORDER BY created_at DESC
-               LIMIT 1"#,
+               WHERE user_id = $1 AND cache_key = $2 AND expires_at > NOW()"#,
         )
+        .bind(user_id)
         .bind(cache_key)
         .fetch_optional(&self.pool)
         .await
@@ -917,14 +1021,14 @@ impl ScreeningService {
         })
     }
 
-    async fn store_cache(&self, cache_key: &str, response: &ScreeningResponse) -> Result<(), String> {
+    async fn store_cache(&self, user_id: Uuid, cache_key: &str, response: &ScreeningResponse) -> Result<(), String> {
         let json_val = serde_json::to_value(&response.results)
             .map_err(|e| format!("JSON serialization: {}", e))?;
 
         sqlx::query(
-            r#"INSERT INTO screening_cache (id, cache_key, results_json, total_screened, total_passed_filters, created_at, expires_at)
-               VALUES ($1, $2, $3, $4, $5, NOW(), NOW() + INTERVAL '15 minutes')
-               ON CONFLICT (cache_key) DO UPDATE
+            r#"INSERT INTO screening_cache (id, user_id, cache_key, results_json, total_screened, total_passed_filters, created_at, expires_at)
+               VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW() + INTERVAL '15 minutes')
+               ON CONFLICT (user_id, cache_key) DO UPDATE
                  SET results_json = EXCLUDED.results_json,
                      total_screened = EXCLUDED.total_screened,
                      total_passed_filters = EXCLUDED.total_passed_filters,
@@ -932,6 +1036,7 @@ impl ScreeningService {
                      expires_at = EXCLUDED.expires_at"#,
         )
         .bind(Uuid::new_v4())
+        .bind(user_id)
         .bind(cache_key)
         .bind(&json_val)
         .bind(response.total_screened as i32)
@@ -957,6 +1062,9 @@ struct TickerData {
     avg_volume: Option<f64>,
     market_cap: Option<f64>,
     geography: Option<String>,
+    ownership: Option<OwnershipSnapshot>,
+    analyst: Option<AnalystConsensus>,
+    fundamentals: Option<ExternalFundamentals>,
 }
 
 // ---------------------------------------------------------------------------
@@ -982,6 +1090,9 @@ mod tests {
             avg_volume: Some(1_000_000.0),
             market_cap: Some(50_000_000_000.0),
             geography: Some("US".into()),
+            ownership: None,
+            analyst: None,
+            fundamentals: None,
         }
     }
 
@@ -1106,6 +1217,68 @@ mod tests {
         assert!(!service.passes_filters(&data, &filters), "50B market cap should not pass Small filter");
     }
 
+    #[test]
+    fn test_filters_ownership() {
+        let service = test_service();
+        let mut data = make_ticker(vec![100.0; 50]);
+
+        let mut filters = ScreeningFilters {
+            min_insider_ownership_pct: Some(5.0),
+            insider_net_buying: true,
+            ..Default::default()
+        };
+        assert!(service.passes_filters(&data, &filters), "Missing ownership data should not exclude");
+
+        data.ownership = Some(OwnershipSnapshot {
+            ticker: "TEST".into(),
+            insider_ownership_pct: Some(8.0),
+            institutional_ownership_pct: Some(40.0),
+            institution_count: None,
+            insider_activity: crate::models::InsiderActivity { period_days: 90, buys: 2, sells: 0, net_shares: 1_000 },
+            recent_insider_transactions: vec![],
+            top_institutional_holders: vec![],
+            fetched_at: Utc::now(),
+        });
+        assert!(service.passes_filters(&data, &filters));
+
+        filters.min_institutional_ownership_pct = Some(50.0);
+        assert!(!service.passes_filters(&data, &filters));
+
+        filters.min_institutional_ownership_pct = None;
+        if let Some(ownership) = data.ownership.as_mut() {
+            ownership.insider_activity.net_shares = -500;
+        }
+        assert!(!service.passes_filters(&data, &filters));
+    }
+
+    #[test]
+    fn test_filters_analyst() {
+        let service = test_service();
+        let mut data = make_ticker(vec![100.0; 50]);
+
+        let mut filters = ScreeningFilters {
+            min_analyst_upside_pct: Some(10.0),
+            max_analyst_rating: Some(2.5),
+            ..Default::default()
+        };
+        assert!(service.passes_filters(&data, &filters), "Missing analyst data should not exclude");
+
+        let raw = crate::external::analyst_provider::ExternalAnalystConsensus {
+            target_mean: Some(115.0),
+            recommendation_mean: Some(2.0),
+            ..Default::default()
+        };
+        data.analyst = Some(analyst_service::summarize("TEST", &raw, Some(100.0), Utc::now()));
+        assert!(service.passes_filters(&data, &filters));
+
+        filters.min_analyst_upside_pct = Some(20.0);
+        assert!(!service.passes_filters(&data, &filters));
+
+        filters.min_analyst_upside_pct = None;
+        filters.max_analyst_rating = Some(1.5);
+        assert!(!service.passes_filters(&data, &filters));
+    }
+
     #[test]
     fn test_scoring_produces_valid_composite() {
         let service = test_service();
@@ -1118,6 +1291,25 @@ mod tests {
         assert!(!result.explanation.is_empty());
     }
 
+    #[test]
+    fn test_fundamentals_replace_valuation_proxies() {
+        let service = test_service();
+        let mut data = make_ticker(make_prices(300, 100.0, 0.1));
+        data.fundamentals = Some(ExternalFundamentals {
+            pe_ratio: Some(10.0),
+            pb_ratio: Some(1.0),
+            market_cap: Some(50_000_000_000.0),
+            ..Default::default()
+        });
+
+        let score = service.score_fundamentals(&data);
+        assert_eq!(score.pe_score, Some(fundamentals_service::pe_ratio_score(10.0)));
+        assert_eq!(score.pb_score, Some(fundamentals_service::pb_ratio_score(1.0)));
+        let metrics: Vec<&str> = score.details.iter().map(|d| d.metric.as_str()).collect();
+        assert!(metrics.contains(&"P/E Ratio (trailing)"));
+        assert!(!metrics.iter().any(|m| m.starts_with("P/E Proxy") || m.starts_with("P/B Proxy")));
+    }
+
     #[test]
     fn test_score_ticker_ranking() {
         let service = test_service();
@@ -1,7 +1,8 @@
 use crate::errors::AppError;
 use crate::models::{SecFiling, InsiderTransaction, InsiderTransactionType, FilingType};
 use crate::services::llm_service::LlmService;
-use chrono::{NaiveDate, Utc, Duration};
+use crate::services::clock;
+use chrono::{NaiveDate, Duration};
 use reqwest::Client;
 use serde::Deserialize;
 use tracing::{info, warn};
@@ -119,7 +120,7 @@ impl SecEdgarService {
         ticker: &str,
         days_back: i32,
     ) -> Result<Vec<SecFiling>, AppError> {
-        let cutoff_date = Utc::now().date_naive() - Duration::days(days_back as i64);
+        let cutoff_date = clock::today() - Duration::days(days_back as i64);
         let mut filings = Vec::new();
 
         // Parse Atom feed entries
@@ -176,7 +177,7 @@ impl SecEdgarService {
         ticker: &str,
         days_back: i32,
     ) -> Result<Vec<InsiderTransaction>, AppError> {
-        let cutoff_date = Utc::now().date_naive() - Duration::days(days_back as i64);
+        let cutoff_date = clock::today() - Duration::days(days_back as i64);
         let mut transactions = Vec::new();
 
         // Parse Atom feed entries (with multiline support using (?s) flag)
@@ -259,7 +260,7 @@ impl SecEdgarService {
         // Text:  /Archives/edgar/data/.../0000320193-26-000005/0000320193-26-000005.txt
         let content_url = if filing_url.contains("-index.htm") {
             let txt_url = filing_url.replace("-index.htm", ".txt");
-            info!("📄 [SEC] Converting index URL to text file: {}", txt_url);
+            info!("[SEC] Converting index URL to text file: {}", txt_url);
             txt_url
         } else {
             filing_url.to_string()
@@ -282,13 +283,13 @@ impl SecEdgarService {
         let content = response.text().await
             .map_err(|e| AppError::External(format!("Failed to read filing content: {}", e)))?;
 
-        info!("📄 [SEC] Downloaded filing content: {} chars", content.len());
+        info!("[SEC] Downloaded filing content: {} chars", content.len());
 
         // Extract meaningful content from the filing
         // Skip XBRL/XML headers and extract exhibits (press releases) or Item sections
         let meaningful_content = self.extract_meaningful_content(&content);
 
-        info!("📄 [SEC] Extracted meaningful content: {} chars", meaningful_content.len());
+        info!("[SEC] Extracted meaningful content: {} chars", meaningful_content.len());
 
         Ok(meaningful_content)
     }
@@ -315,7 +316,7 @@ impl SecEdgarService {
                 // Clean up HTML tags and XBRL
                 let cleaned = self.extract_text_from_html(exhibit_text);
 
-                info!("📄 [SEC] Extracted EX-99 exhibit content");
+                info!("[SEC] Extracted EX-99 exhibit content");
                 return cleaned;
             }
         }
@@ -331,7 +332,7 @@ impl SecEdgarService {
 
             let cleaned = self.extract_text_from_html(item_text);
 
-            info!("📄 [SEC] Extracted Item 2.02 section content");
+            info!("[SEC] Extracted Item 2.02 section content");
             return cleaned;
         }
 
@@ -342,7 +343,7 @@ impl SecEdgarService {
         let text = &content[skip_amount..skip_amount + take_amount];
         let cleaned = self.extract_text_from_html(text);
 
-        info!("📄 [SEC] Using fallback content extraction (skipped first 10KB)");
+        info!("[SEC] Using fallback content extraction (skipped first 10KB)");
         cleaned
     }
 
@@ -413,13 +414,13 @@ pub async fn analyze_material_event(
     let content_for_llm = content.chars().take(8000).collect::<String>();
 
     // Log the actual content being sent to LLM in chunks so we can see what it contains
-    info!("📄 [SEC] Content being sent to LLM - PART 1 (chars 0-2000):\n{}",
+    info!("[SEC] Content being sent to LLM - PART 1 (chars 0-2000):\n{}",
           content_for_llm.chars().take(2000).collect::<String>());
-    info!("📄 [SEC] Content being sent to LLM - PART 2 (chars 2000-4000):\n{}",
+    info!("[SEC] Content being sent to LLM - PART 2 (chars 2000-4000):\n{}",
           content_for_llm.chars().skip(2000).take(2000).collect::<String>());
-    info!("📄 [SEC] Content being sent to LLM - PART 3 (chars 4000-6000):\n{}",
+    info!("[SEC] Content being sent to LLM - PART 3 (chars 4000-6000):\n{}",
           content_for_llm.chars().skip(4000).take(2000).collect::<String>());
-    info!("📄 [SEC] Content being sent to LLM - PART 4 (chars 6000-8000):\n{}",
+    info!("[SEC] Content being sent to LLM - PART 4 (chars 6000-8000):\n{}",
           content_for_llm.chars().skip(6000).take(2000).collect::<String>());
 
     // Create prompt for LLM
@@ -461,57 +462,57 @@ Focus on: What happened, why it matters, and whether it's positive or negative f
         prompt.to_string(),
     ).await?;
 
-    info!("📝 [SEC] Raw LLM response (first 500 chars): {}",
+    info!("[SEC] Raw LLM response (first 500 chars): {}",
           response.chars().take(500).collect::<String>());
 
     // Clean response: remove markdown code blocks if present
     let json_str = response.trim();
     let json_str = if json_str.starts_with("```json") {
-        info!("🔧 [SEC] Removing ```json markdown wrapper");
+        info!("[SEC] Removing ```json markdown wrapper");
         // Remove ```json at start and ``` at end
         json_str.trim_start_matches("```json")
             .trim_end_matches("```")
             .trim()
     } else if json_str.starts_with("```") {
-        info!("🔧 [SEC] Removing ``` markdown wrapper");
+        info!("[SEC] Removing ``` markdown wrapper");
         // Remove ``` at start and end
         json_str.trim_start_matches("```")
             .trim_end_matches("```")
             .trim()
     } else {
-        info!("✅ [SEC] No markdown wrapper detected");
+        info!("[SEC] No markdown wrapper detected");
         json_str
     };
 
-    info!("📝 [SEC] Cleaned JSON string (first 300 chars): {}",
+    info!("[SEC] Cleaned JSON string (first 300 chars): {}",
           json_str.chars().take(300).collect::<String>());
 
     // Parse response
     let analysis: serde_json::Value = serde_json::from_str(json_str)
         .map_err(|e| {
-            warn!("❌ [SEC] JSON parse error: {}", e);
-            warn!("❌ [SEC] Failed string: {}", json_str);
+            warn!("[SEC] JSON parse error: {}", e);
+            warn!("[SEC] Failed string: {}", json_str);
             AppError::Validation(format!("Failed to parse LLM response: {}", e))
         })?;
 
-    info!("📊 [SEC] Parsed JSON: {}", serde_json::to_string_pretty(&analysis).unwrap_or_default());
+    info!("[SEC] Parsed JSON: {}", serde_json::to_string_pretty(&analysis).unwrap_or_default());
 
     let event_type = analysis["event_type"]
         .as_str()
         .unwrap_or("other")
         .to_string();
-    info!("📊 [SEC] Extracted event_type: {}", event_type);
+    info!("[SEC] Extracted event_type: {}", event_type);
 
     let sentiment_score = analysis["sentiment_score"]
         .as_f64()
         .unwrap_or(0.0)
         .clamp(-1.0, 1.0);
-    info!("📊 [SEC] Extracted sentiment_score: {} (raw: {:?})", sentiment_score, analysis["sentiment_score"]);
+    info!("[SEC] Extracted sentiment_score: {} (raw: {:?})", sentiment_score, analysis["sentiment_score"]);
 
     let importance_str = analysis["importance"]
         .as_str()
         .unwrap_or("medium");
-    info!("📊 [SEC] Extracted importance: {}", importance_str);
+    info!("[SEC] Extracted importance: {}", importance_str);
 
     let importance = match importance_str {
         "critical" => crate::models::EventImportance::Critical,
@@ -524,7 +525,7 @@ Focus on: What happened, why it matters, and whether it's positive or negative f
         .as_str()
         .unwrap_or("No summary available")
         .to_string();
-    info!("📊 [SEC] Extracted summary: {}", summary.chars().take(100).collect::<String>());
+    info!("[SEC] Extracted summary: {}", summary.chars().take(100).collect::<String>());
 
     let material_event = crate::models::MaterialEvent {
         ticker: filing.ticker.clone(),
@@ -536,7 +537,7 @@ Focus on: What happened, why it matters, and whether it's positive or negative f
         filing_url: filing.filing_url.clone(),
     };
 
-    info!("✅ [SEC] Created MaterialEvent: ticker={}, sentiment={}, type={}, importance={}",
+    info!("[SEC] Created MaterialEvent: ticker={}, sentiment={}, type={}, importance={}",
           material_event.ticker, material_event.sentiment_score, event_type, importance_str);
 
     Ok(material_event)
@@ -1,9 +1,11 @@
+use bigdecimal::ToPrimitive;
 use chrono::{Duration, Utc};
 use tracing::{info, warn};
 
 use crate::errors::AppError;
 use crate::models::{EnhancedSentimentSignal, ForecastPoint, HistoricalDataPoint, SentimentAwareForecast, SentimentFactors};
 use crate::services::price_service;
+use crate::services::clock;
 use sqlx::PgPool;
 
 /// Sentiment momentum over different time windows
@@ -57,13 +59,13 @@ pub async fn calculate_sentiment_momentum(
     pool: &PgPool,
     ticker: &str,
 ) -> Result<SentimentMomentum, AppError> {
-    info!("🔍 [SENTIMENT MOMENTUM] Calculating momentum for {}", ticker);
+    info!("[SENTIMENT MOMENTUM] Calculating momentum for {}", ticker);
 
     // Fetch historical sentiment data
     let sentiment_history = fetch_sentiment_history(pool, ticker, 30).await?;
 
     if sentiment_history.len() < 7 {
-        warn!("⚠️ [SENTIMENT MOMENTUM] Insufficient data for {}: {} points", ticker, sentiment_history.len());
+        warn!("[SENTIMENT MOMENTUM] Insufficient data for {}: {} points", ticker, sentiment_history.len());
         return Ok(SentimentMomentum {
             seven_day_change: 0.0,
             thirty_day_change: 0.0,
@@ -111,7 +113,7 @@ pub async fn calculate_sentiment_momentum(
     };
 
     info!(
-        "✅ [SENTIMENT MOMENTUM] {} - 7d: {:.3}, 30d: {:.3}, accel: {:.3}",
+        "[SENTIMENT MOMENTUM] {} - 7d: {:.3}, 30d: {:.3}, accel: {:.3}",
         ticker, seven_day_change, thirty_day_change, acceleration
     );
 
@@ -130,7 +132,7 @@ pub async fn detect_sentiment_spike(
     pool: &PgPool,
     ticker: &str,
 ) -> Result<SentimentSpike, AppError> {
-    info!("🔍 [SENTIMENT SPIKE] Detecting spikes for {}", ticker);
+    info!("[SENTIMENT SPIKE] Detecting spikes for {}", ticker);
 
     let sentiment_history = fetch_sentiment_history(pool, ticker, 30).await?;
 
@@ -180,7 +182,7 @@ pub async fn detect_sentiment_spike(
     };
 
     info!(
-        "✅ [SENTIMENT SPIKE] {} - detected: {}, z-score: {:.2}, direction: {:?}",
+        "[SENTIMENT SPIKE] {} - detected: {}, z-score: {:.2}, direction: {:?}",
         ticker, detected, z_score, direction
     );
 
@@ -201,13 +203,13 @@ pub async fn detect_sentiment_price_divergence(
     ticker: &str,
     sentiment: f64,
 ) -> Result<SentimentDivergence, AppError> {
-    info!("🔍 [DIVERGENCE] Detecting divergence for {} (sentiment: {:.2})", ticker, sentiment);
+    info!("[DIVERGENCE] Detecting divergence for {} (sentiment: {:.2})", ticker, sentiment);
 
     // Fetch recent price history
     let prices = price_service::get_history(pool, ticker).await?;
 
     if prices.len() < 30 {
-        warn!("⚠️ [DIVERGENCE] Insufficient price data for {}: {} points", ticker, prices.len());
+        warn!("[DIVERGENCE] Insufficient price data for {}: {} points", ticker, prices.len());
         return Ok(SentimentDivergence {
             detected: false,
             divergence_type: DivergenceType::None,
@@ -219,7 +221,7 @@ pub async fn detect_sentiment_price_divergence(
     // Calculate price trend (30-day)
     let recent_prices: Vec<f64> = prices[..30.min(prices.len())]
         .iter()
-        .map(|p| p.close_price.to_string().parse::<f64>().unwrap_or(0.0))
+        .map(|p| p.close_price.to_f64().unwrap_or(0.0))
         .collect();
 
     let price_change = if !recent_prices.is_empty() {
@@ -228,7 +230,7 @@ pub async fn detect_sentiment_price_divergence(
         0.0
     };
 
-    info!("📊 [DIVERGENCE] {} price change (30d): {:.2}%", ticker, price_change * 100.0);
+    info!("[DIVERGENCE] {} price change (30d): {:.2}%", ticker, price_change * 100.0);
 
     // Detect divergence
     let threshold = 0.3; // Sentiment and price must be > 0.3 in opposite directions
@@ -254,7 +256,7 @@ pub async fn detect_sentiment_price_divergence(
     };
 
     info!(
-        "✅ [DIVERGENCE] {} - detected: {}, type: {:?}, score: {:.2}, reversal prob: {:.2}",
+        "[DIVERGENCE] {} - detected: {}, type: {:?}, score: {:.2}, reversal prob: {:.2}",
         ticker, detected, divergence_type, divergence_score, reversal_probability
     );
 
@@ -356,7 +358,7 @@ pub async fn generate_sentiment_aware_stock_forecast(
     days_ahead: i32,
 ) -> Result<SentimentAwareForecast, AppError> {
     info!(
-        "🚀 [SENTIMENT FORECAST] Generating sentiment-aware forecast for {} ({} days)",
+        "[SENTIMENT FORECAST] Generating sentiment-aware forecast for {} ({} days)",
         ticker, days_ahead
     );
 
@@ -376,7 +378,7 @@ pub async fn generate_sentiment_aware_stock_forecast(
         .iter()
         .map(|p| HistoricalDataPoint {
             date: p.date.to_string(),
-            value: p.close_price.to_string().parse::<f64>().unwrap_or(0.0),
+            value: p.close_price.to_f64().unwrap_or(0.0),
         })
         .collect();
 
@@ -420,7 +422,7 @@ pub async fn generate_sentiment_aware_stock_forecast(
     );
 
     info!(
-        "✅ [SENTIMENT FORECAST] Completed for {}: sentiment={:.2}, momentum={:.3}, divergence={}, reversal_prob={:.2}",
+        "[SENTIMENT FORECAST] Completed for {}: sentiment={:.2}, momentum={:.3}, divergence={}, reversal_prob={:.2}",
         ticker,
         enhanced_sentiment.combined_sentiment,
         momentum.seven_day_change,
@@ -449,7 +451,7 @@ async fn fetch_sentiment_history(
     ticker: &str,
     days: i32,
 ) -> Result<Vec<SentimentHistoryPoint>, AppError> {
-    let cutoff_date = (Utc::now() - Duration::days(days as i64)).naive_utc();
+    let cutoff_date = (clock::now() - Duration::days(days as i64)).naive_utc();
 
     let rows = sqlx::query!(
         r#"
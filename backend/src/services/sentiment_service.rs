@@ -2,7 +2,10 @@ use crate::models::{
     SentimentTrend, MomentumTrend, DivergenceType, SentimentDataPoint,
     SentimentSignal, NewsTheme, PricePoint, Sentiment, NewsArticle,
 };
+use crate::db::news_sentiment_queries;
 use crate::errors::AppError;
+use crate::external::news::ExternalNewsArticle;
+use crate::services::clock;
 use chrono::{Utc, Duration, NaiveDate};
 use sqlx::PgPool;
 use std::collections::HashMap;
@@ -281,6 +284,69 @@ pub async fn generate_sentiment_signal(
     // Calculate current sentiment
     let current_sentiment = calculate_sentiment_score(&themes);
 
+    let signal = assemble_signal(ticker, current_sentiment, historical_sentiment, &prices, themes.len() as i32);
+
+    // Cache the result
+    save_sentiment_to_cache(pool, &signal).await?;
+
+    Ok(signal)
+}
+
+/// Relevance-weighted mean sentiment of provider-scored articles, from -1.0
+/// (very negative) to +1.0 (very positive)
+pub fn scored_news_sentiment(articles: &[ExternalNewsArticle]) -> f64 {
+    let total_weight: f64 = articles.iter().map(|a| a.relevance).sum();
+    if total_weight <= 0.0 {
+        return 0.0;
+    }
+    let weighted_sum: f64 = articles.iter().map(|a| a.sentiment_score * a.relevance).sum();
+    (weighted_sum / total_weight).clamp(-1.0, 1.0)
+}
+
+/// Cache the sentiment signal of articles the news provider already scored,
+/// replacing any cached signal, and record the score for screening. Unlike
+/// [`generate_sentiment_signal`] this needs no LLM.
+pub async fn cache_scored_news(
+    pool: &PgPool,
+    ticker: &str,
+    articles: &[ExternalNewsArticle],
+    prices: &[PricePoint],
+    days: i32,
+    source: &str,
+) -> Result<SentimentSignal, AppError> {
+    if articles.is_empty() {
+        return Err(AppError::Validation(
+            format!("No news data available for {}", ticker)
+        ));
+    }
+
+    let historical_sentiment = build_scored_timeline(articles, prices, clock::today(), days);
+    let current_sentiment = scored_news_sentiment(articles);
+    let signal = assemble_signal(ticker, current_sentiment, historical_sentiment, prices, articles.len() as i32);
+
+    save_sentiment_to_cache(pool, &signal).await?;
+    news_sentiment_queries::insert_score(
+        pool,
+        ticker,
+        current_sentiment,
+        articles.len() as i32,
+        articles.iter().map(|a| a.published_at).max(),
+        source,
+    )
+    .await?;
+
+    Ok(signal)
+}
+
+/// Trends, divergence, correlation and warnings around a ticker's current
+/// sentiment and its daily timeline
+fn assemble_signal(
+    ticker: &str,
+    current_sentiment: f64,
+    historical_sentiment: Vec<SentimentDataPoint>,
+    prices: &[PricePoint],
+    articles_analyzed: i32,
+) -> SentimentSignal {
     // Determine trends
     let sentiment_trend = determine_sentiment_trend(&historical_sentiment);
 
@@ -316,13 +382,13 @@ pub async fn generate_sentiment_signal(
 
     // Generate warnings
     let warnings = generate_warnings(
-        themes.len() as i32,
+        articles_analyzed,
         correlation,
         historical_sentiment.len(),
         sentiment_volatility,
     );
 
-    let signal = SentimentSignal {
+    SentimentSignal {
         ticker: ticker.to_string(),
         current_sentiment,
         sentiment_trend,
@@ -332,15 +398,10 @@ pub async fn generate_sentiment_signal(
         correlation_lag_days: lag,
         correlation_strength,
         historical_sentiment,
-        news_articles_analyzed: themes.len() as i32,
+        news_articles_analyzed: articles_analyzed,
         calculated_at: Utc::now(),
         warnings,
-    };
-
-    // Cache the result
-    save_sentiment_to_cache(pool, &signal).await?;
-
-    Ok(signal)
+    }
 }
 
 /// Build sentiment timeline by aggregating news by date
@@ -371,7 +432,7 @@ fn build_sentiment_timeline(
     }
 
     // Generate timeline with per-day sentiment
-    let end_date = Utc::now().date_naive();
+    let end_date = clock::today();
     let start_date = end_date - Duration::days(days as i64);
 
     let mut timeline = Vec::new();
@@ -424,6 +485,49 @@ fn build_sentiment_timeline(
     timeline
 }
 
+/// Daily timeline of provider-scored articles over the `days` days up to
+/// `end`, on days with a close. Days without news decay toward neutral as in
+/// [`build_sentiment_timeline`].
+fn build_scored_timeline(
+    articles: &[ExternalNewsArticle],
+    prices: &[PricePoint],
+    end: NaiveDate,
+    days: i32,
+) -> Vec<SentimentDataPoint> {
+    use bigdecimal::ToPrimitive;
+
+    let mut articles_by_date: HashMap<NaiveDate, Vec<ExternalNewsArticle>> = HashMap::new();
+    for article in articles {
+        articles_by_date
+            .entry(article.published_at.date_naive())
+            .or_default()
+            .push(article.clone());
+    }
+
+    let overall_sentiment = scored_news_sentiment(articles);
+    let start = end - Duration::days(days as i64);
+
+    let mut timeline: Vec<SentimentDataPoint> = prices
+        .iter()
+        .filter(|p| p.date >= start && p.date <= end)
+        .filter_map(|p| {
+            let price = p.close_price.to_f64()?;
+            let (sentiment_score, news_volume) = match articles_by_date.get(&p.date) {
+                Some(day_articles) => (scored_news_sentiment(day_articles), day_articles.len() as i32),
+                None => (overall_sentiment * 0.5, 0),
+            };
+            Some(SentimentDataPoint {
+                date: p.date.format("%Y-%m-%d").to_string(),
+                sentiment_score,
+                news_volume,
+                price: Some(price),
+            })
+        })
+        .collect();
+    timeline.sort_by(|a, b| a.date.cmp(&b.date));
+    timeline
+}
+
 /// Get sentiment signal from cache
 async fn get_sentiment_from_cache(
     pool: &PgPool,
@@ -622,4 +726,49 @@ mod tests {
         assert_eq!(classify_correlation_strength(0.2), "weak");
         assert_eq!(classify_correlation_strength(-0.75), "strong");
     }
+
+    fn scored_article(date: NaiveDate, sentiment_score: f64, relevance: f64) -> ExternalNewsArticle {
+        ExternalNewsArticle {
+            title: "t".to_string(),
+            url: "https://example.com".to_string(),
+            source: "Wire".to_string(),
+            published_at: date.and_hms_opt(14, 0, 0).unwrap().and_utc(),
+            summary: String::new(),
+            sentiment_score,
+            relevance,
+        }
+    }
+
+    #[test]
+    fn test_scored_news_sentiment_weights_by_relevance() {
+        let day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        assert_eq!(scored_news_sentiment(&[]), 0.0);
+        let articles = vec![scored_article(day, 0.6, 0.9), scored_article(day, -0.4, 0.1)];
+        assert!((scored_news_sentiment(&articles) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_build_scored_timeline() {
+        use bigdecimal::BigDecimal;
+
+        let end = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let prices: Vec<PricePoint> = (0..5)
+            .map(|i| PricePoint {
+                id: uuid::Uuid::new_v4(),
+                ticker: "AAPL".to_string(),
+                date: end - Duration::days(4 - i),
+                close_price: BigDecimal::from(100 + i),
+                created_at: Utc::now(),
+            })
+            .collect();
+        let articles = vec![scored_article(end - Duration::days(1), 0.4, 1.0)];
+
+        let timeline = build_scored_timeline(&articles, &prices, end, 2);
+        let dates: Vec<&str> = timeline.iter().map(|p| p.date.as_str()).collect();
+        assert_eq!(dates, vec!["2026-03-02", "2026-03-03", "2026-03-04"]);
+        assert_eq!(timeline[1].sentiment_score, 0.4);
+        assert_eq!(timeline[1].news_volume, 1);
+        assert_eq!(timeline[0].sentiment_score, 0.2);
+        assert_eq!(timeline[2].price, Some(104.0));
+    }
 }
@@ -0,0 +1,77 @@
+//! Sign-in sessions: short-lived access tokens renewed with a rotating refresh
+//! token.
+//!
+//! Signing in opens a session and issues both tokens. The access token names
+//! its session and is checked against it on every request, so revoking a
+//! session (logout, logout everywhere, a password change) takes effect
+//! immediately rather than when the token expires. Each refresh replaces the
+//! refresh token, so a stolen token stops working once the real browser uses it.
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::{auth_queries, session_queries};
+use crate::errors::AppError;
+
+/// Tokens for a session, set as cookies by the auth routes
+#[derive(Debug)]
+pub struct SessionTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+/// Open a session for a user who has just signed in. Disabled accounts are
+/// refused here, whichever way they signed in.
+pub async fn start(
+    pool: &PgPool,
+    jwt_secret: &str,
+    user_id: Uuid,
+    user_agent: Option<&str>,
+) -> Result<SessionTokens, AppError> {
+    if auth_queries::get_user(pool, user_id).await?.disabled_at.is_some() {
+        return Err(AppError::Forbidden("This account has been disabled".into()));
+    }
+    let refresh_token = auth::generate_refresh_token();
+    let session_id = session_queries::create(
+        pool,
+        user_id,
+        &auth::hash_refresh_token(&refresh_token),
+        user_agent,
+        Utc::now() + Duration::days(auth::REFRESH_TOKEN_TTL_DAYS),
+    )
+    .await?;
+    issue(jwt_secret, user_id, session_id, refresh_token)
+}
+
+/// Trade a refresh token for a new access token and refresh token
+pub async fn refresh(pool: &PgPool, jwt_secret: &str, refresh_token: &str) -> Result<SessionTokens, AppError> {
+    let new_refresh_token = auth::generate_refresh_token();
+    let (session_id, user_id) = session_queries::rotate(
+        pool,
+        &auth::hash_refresh_token(refresh_token),
+        &auth::hash_refresh_token(&new_refresh_token),
+        Utc::now() + Duration::days(auth::REFRESH_TOKEN_TTL_DAYS),
+    )
+    .await?
+    .ok_or(AppError::Unauthorized)?;
+    issue(jwt_secret, user_id, session_id, new_refresh_token)
+}
+
+/// End the session holding `refresh_token`
+pub async fn end(pool: &PgPool, refresh_token: &str) -> Result<(), AppError> {
+    session_queries::revoke_by_token(pool, &auth::hash_refresh_token(refresh_token)).await?;
+    Ok(())
+}
+
+/// End every session of a user, except `keep` when given. Returns the number ended.
+pub async fn end_all(pool: &PgPool, user_id: Uuid, keep: Option<Uuid>) -> Result<u64, AppError> {
+    Ok(session_queries::revoke_all(pool, user_id, keep).await?)
+}
+
+fn issue(jwt_secret: &str, user_id: Uuid, session_id: Uuid, refresh_token: String) -> Result<SessionTokens, AppError> {
+    let access_token = auth::create_jwt(user_id, session_id, jwt_secret)
+        .map_err(|e| AppError::External(format!("Token creation failed: {}", e)))?;
+    Ok(SessionTokens { access_token, refresh_token })
+}
@@ -51,7 +51,7 @@ impl SignalService {
         // Cache signals in database
         for signal in &signals {
             if let Err(e) = self.cache_signal(signal).await {
-                eprintln!("Warning: Failed to cache signal: {}", e);
+                tracing::warn!(ticker = %signal.ticker, error = %e, "Failed to cache signal");
             }
         }
 
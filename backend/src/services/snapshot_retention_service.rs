@@ -0,0 +1,137 @@
+//! Snapshot retention and compaction.
+//!
+//! Risk and holdings snapshots are written daily and would otherwise grow without
+//! bound. Rows older than `weekly_after_months` are compacted to the last snapshot
+//! of each week, and rows older than `monthly_after_months` to the last snapshot of
+//! each month — the same "last of the period" rule the risk history aggregation
+//! uses. History endpoints read the same tables, so compacted tiers simply show up
+//! as sparser points (risk snapshots also carry their `granularity`).
+//! Each account's first holdings snapshot survives compaction, since opening
+//! lots are seeded from it.
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::db::{holding_snapshot_queries, risk_snapshot_queries};
+use crate::errors::AppError;
+use crate::services::clock;
+
+const DEFAULT_WEEKLY_AFTER_MONTHS: u32 = 3;
+const DEFAULT_MONTHLY_AFTER_MONTHS: u32 = 12;
+
+/// Retention horizons, read from `SNAPSHOT_WEEKLY_AFTER_MONTHS` and
+/// `SNAPSHOT_MONTHLY_AFTER_MONTHS`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionConfig {
+    pub weekly_after_months: u32,
+    pub monthly_after_months: u32,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            weekly_after_months: DEFAULT_WEEKLY_AFTER_MONTHS,
+            monthly_after_months: DEFAULT_MONTHLY_AFTER_MONTHS,
+        }
+    }
+}
+
+impl RetentionConfig {
+    pub fn from_env() -> Self {
+        let months = |key: &str, default: u32| {
+            std::env::var(key)
+                .ok()
+                .and_then(|s| s.parse::<u32>().ok())
+                .filter(|m| *m > 0)
+                .unwrap_or(default)
+        };
+        let weekly_after_months = months("SNAPSHOT_WEEKLY_AFTER_MONTHS", DEFAULT_WEEKLY_AFTER_MONTHS);
+        // The monthly tier can't start before the weekly one
+        let monthly_after_months =
+            months("SNAPSHOT_MONTHLY_AFTER_MONTHS", DEFAULT_MONTHLY_AFTER_MONTHS).max(weekly_after_months);
+        Self {
+            weekly_after_months,
+            monthly_after_months,
+        }
+    }
+
+    /// (weekly cutoff, monthly cutoff). Snapshots before the weekly cutoff are
+    /// compacted to weeks, before the monthly cutoff to months. Cutoffs fall on a
+    /// Monday and a 1st of the month so no period is ever split between tiers.
+    pub fn cutoffs(&self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let monthly = month_start(months_before(today, self.monthly_after_months));
+        let weekly = week_start(months_before(today, self.weekly_after_months)).max(monthly);
+        (weekly, monthly)
+    }
+}
+
+fn months_before(date: NaiveDate, months: u32) -> NaiveDate {
+    date.checked_sub_months(Months::new(months)).unwrap_or(NaiveDate::MIN)
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// Rows removed by one compaction run
+#[derive(Debug, Clone, Copy)]
+pub struct CompactionResult {
+    pub risk_rows_removed: u64,
+    pub holding_rows_removed: u64,
+}
+
+/// Compact risk and holdings snapshots according to `config`
+pub async fn compact_snapshots(pool: &PgPool, config: RetentionConfig) -> Result<CompactionResult, AppError> {
+    let (weekly_cutoff, monthly_cutoff) = config.cutoffs(clock::today());
+    info!(
+        "Compacting snapshots: weekly before {}, monthly before {}",
+        weekly_cutoff, monthly_cutoff
+    );
+
+    let risk_rows_removed =
+        risk_snapshot_queries::compact(pool, Some(monthly_cutoff), weekly_cutoff, "week", "weekly").await?
+            + risk_snapshot_queries::compact(pool, None, monthly_cutoff, "month", "monthly").await?;
+    let holding_rows_removed = holding_snapshot_queries::compact(pool, Some(monthly_cutoff), weekly_cutoff, "week").await?
+        + holding_snapshot_queries::compact(pool, None, monthly_cutoff, "month").await?;
+
+    Ok(CompactionResult {
+        risk_rows_removed,
+        holding_rows_removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_cutoffs_align_to_period_starts() {
+        let config = RetentionConfig::default();
+        // 2026-10-17 is a Saturday; three months back is Friday 2026-07-17
+        let (weekly, monthly) = config.cutoffs(date(2026, 10, 17));
+        assert_eq!(weekly, date(2026, 7, 13));
+        assert_eq!(weekly.weekday(), chrono::Weekday::Mon);
+        assert_eq!(monthly, date(2025, 10, 1));
+    }
+
+    #[test]
+    fn test_weekly_cutoff_never_precedes_monthly() {
+        let config = RetentionConfig {
+            weekly_after_months: 12,
+            monthly_after_months: 12,
+        };
+        // A week starting in September would otherwise reach past the monthly cutoff
+        let (weekly, monthly) = config.cutoffs(date(2026, 10, 1));
+        assert_eq!(monthly, date(2025, 10, 1));
+        assert_eq!(weekly, monthly);
+    }
+}
@@ -0,0 +1,100 @@
+//! Time zones users can pick for their "daily" boundaries.
+//!
+//! Snapshot dates, digest delivery and market-close checks depend on the local
+//! calendar day, not the UTC one. Zones are IANA names resolved against the tz
+//! database bundled with chrono-tz, so offsets follow each region's actual
+//! daylight-saving history rather than a hand-maintained rule table.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+
+/// Zone used when a user hasn't chosen one
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+
+/// Zone of the US exchanges, for market open and close
+pub const MARKET_TIMEZONE: &str = "America/New_York";
+
+/// A supported time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tz(chrono_tz::Tz);
+
+/// Look up a zone by IANA name, ignoring case
+pub fn resolve(name: &str) -> Result<Tz, String> {
+    let name = name.trim();
+    chrono_tz::Tz::from_str_insensitive(name)
+        .map(Tz)
+        .map_err(|_| format!("Unsupported time zone '{}'; expected an IANA name such as America/New_York", name))
+}
+
+/// A stored zone name, falling back to UTC for one no longer supported
+pub fn resolve_or_default(name: &str) -> Tz {
+    resolve(name).unwrap_or(Tz(chrono_tz::UTC))
+}
+
+/// The zone of the US exchanges
+pub fn market() -> Tz {
+    resolve_or_default(MARKET_TIMEZONE)
+}
+
+impl Tz {
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    /// Offset from UTC in minutes at the instant `at`
+    pub fn offset_minutes(&self, at: DateTime<Utc>) -> i32 {
+        self.0.offset_from_utc_datetime(&at.naive_utc()).fix().local_minus_utc() / 60
+    }
+
+    /// Wall-clock time in the zone at `at`
+    pub fn local_datetime(&self, at: DateTime<Utc>) -> NaiveDateTime {
+        at.with_timezone(&self.0).naive_local()
+    }
+
+    /// Calendar day in the zone at `at`
+    pub fn local_date(&self, at: DateTime<Utc>) -> NaiveDate {
+        self.local_datetime(at).date()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    #[test]
+    fn test_offsets_follow_daylight_time() {
+        let new_york = resolve("america/new_york").unwrap();
+        assert_eq!(new_york.name(), "America/New_York");
+        // 2026: DST from 8 March 07:00 UTC to 1 November 06:00 UTC
+        assert_eq!(new_york.offset_minutes(Utc.with_ymd_and_hms(2026, 3, 8, 6, 59, 0).unwrap()), -300);
+        assert_eq!(new_york.offset_minutes(Utc.with_ymd_and_hms(2026, 3, 8, 7, 0, 0).unwrap()), -240);
+        assert_eq!(new_york.offset_minutes(Utc.with_ymd_and_hms(2026, 11, 1, 5, 59, 0).unwrap()), -240);
+        assert_eq!(new_york.offset_minutes(Utc.with_ymd_and_hms(2026, 11, 1, 6, 0, 0).unwrap()), -300);
+
+        let london = resolve("Europe/London").unwrap();
+        assert_eq!(london.offset_minutes(Utc.with_ymd_and_hms(2026, 3, 29, 0, 59, 0).unwrap()), 0);
+        assert_eq!(london.offset_minutes(Utc.with_ymd_and_hms(2026, 3, 29, 1, 0, 0).unwrap()), 60);
+
+        let sydney = resolve("Australia/Sydney").unwrap();
+        assert_eq!(sydney.offset_minutes(Utc.with_ymd_and_hms(2026, 1, 15, 0, 0, 0).unwrap()), 660);
+        assert_eq!(sydney.offset_minutes(Utc.with_ymd_and_hms(2026, 7, 15, 0, 0, 0).unwrap()), 600);
+
+        // Zones outside the usual trading hubs, including quarter-hour offsets
+        let kathmandu = resolve("Asia/Kathmandu").unwrap();
+        assert_eq!(kathmandu.offset_minutes(Utc.with_ymd_and_hms(2026, 7, 15, 0, 0, 0).unwrap()), 345);
+
+        assert!(resolve("Mars/Olympus_Mons").is_err());
+        assert_eq!(resolve_or_default("Mars/Olympus_Mons").name(), DEFAULT_TIMEZONE);
+    }
+
+    #[test]
+    fn test_local_date_shifts_day_boundary() {
+        // 02:30 UTC on 10 March is still the evening of 9 March in Los Angeles
+        let at = Utc.with_ymd_and_hms(2026, 3, 10, 2, 30, 0).unwrap();
+        let la = resolve("America/Los_Angeles").unwrap();
+        assert_eq!(la.local_date(at), NaiveDate::from_ymd_opt(2026, 3, 9).unwrap());
+        assert_eq!(la.local_datetime(at).time(), NaiveTime::from_hms_opt(19, 30, 0).unwrap());
+        assert_eq!(resolve("Asia/Kolkata").unwrap().local_date(at), NaiveDate::from_ymd_opt(2026, 3, 10).unwrap());
+        assert_eq!(market().local_date(at), NaiveDate::from_ymd_opt(2026, 3, 9).unwrap());
+    }
+}
@@ -0,0 +1,392 @@
+//! Trade journal: the rationale behind buys and sells, reviewed against what
+//! happened since.
+//!
+//! A decision is judged two ways. Its excess return compares the stock with the
+//! benchmark from the trade date to the end of the stated horizon (or today): a
+//! buy paid off if the stock beat the benchmark, a sell if the stock lagged it.
+//! Its thesis checks test whether the stated target, stop and expected return
+//! played out over the same closes.
+
+use std::collections::HashMap;
+
+use bigdecimal::ToPrimitive;
+use chrono::{Duration, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{journal_queries, price_queries};
+use crate::errors::AppError;
+use crate::models::{
+    DecisionOutcome, DecisionReview, DetectedTransaction, JournalEntry, JournalReview, PricePoint, ThesisCheck,
+    UpsertJournalEntry,
+};
+use crate::services::clock;
+
+const MAX_RATIONALE_LEN: usize = 10_000;
+const MAX_HORIZON_DAYS: i32 = 3650;
+/// Score points per percentage point of excess return, around a neutral 50
+const SCORE_POINTS_PER_PCT: f64 = 2.5;
+/// Weight of the thesis hit rate in the score when any metric could be judged
+const THESIS_WEIGHT: f64 = 0.4;
+/// How far before the earliest trade to look for a benchmark close
+const BENCHMARK_LOOKBACK_DAYS: i64 = 10;
+
+fn validate(mut input: UpsertJournalEntry) -> Result<UpsertJournalEntry, AppError> {
+    input.rationale = input.rationale.trim().to_string();
+    if input.rationale.is_empty() {
+        return Err(AppError::Validation("Rationale cannot be empty".into()));
+    }
+    if input.rationale.chars().count() > MAX_RATIONALE_LEN {
+        return Err(AppError::Validation(format!("Rationale exceeds {} characters", MAX_RATIONALE_LEN)));
+    }
+    for (name, level) in [("Target price", input.target_price), ("Stop price", input.stop_price)] {
+        if let Some(value) = level {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(AppError::Validation(format!("{} must be a positive number", name)));
+            }
+        }
+    }
+    if input.expected_return_pct.is_some_and(|r| !r.is_finite() || r <= -100.0) {
+        return Err(AppError::Validation("Expected return must be above -100%".into()));
+    }
+    if input.horizon_days.is_some_and(|d| !(1..=MAX_HORIZON_DAYS).contains(&d)) {
+        return Err(AppError::Validation(format!("Horizon must be between 1 and {} days", MAX_HORIZON_DAYS)));
+    }
+    if input.confidence.is_some_and(|c| !(1..=5).contains(&c)) {
+        return Err(AppError::Validation("Confidence must be between 1 and 5".into()));
+    }
+    Ok(input)
+}
+
+/// Record or replace the journal entry of a BUY or SELL
+pub async fn record_entry(
+    pool: &PgPool,
+    transaction: &DetectedTransaction,
+    input: UpsertJournalEntry,
+) -> Result<JournalEntry, AppError> {
+    if !matches!(transaction.transaction_type.as_str(), "BUY" | "SELL") {
+        return Err(AppError::Validation(format!(
+            "Only BUY and SELL transactions can be journaled, not {}",
+            transaction.transaction_type
+        )));
+    }
+    let input = validate(input)?;
+    journal_queries::upsert(pool, transaction.id, &input).await?;
+    journal_queries::fetch_for_transaction(pool, transaction.id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {} not found", transaction.id)))
+}
+
+/// Close of the last day on or before `date`, or failing that the first one after
+fn close_near(series: &[(NaiveDate, f64)], date: NaiveDate) -> Option<f64> {
+    series
+        .iter()
+        .rev()
+        .find(|(d, _)| *d <= date)
+        .or_else(|| series.first())
+        .map(|(_, close)| *close)
+}
+
+fn check(metric: &str, expected: f64, actual: Option<f64>, met: impl Fn(f64) -> bool) -> ThesisCheck {
+    ThesisCheck {
+        metric: metric.to_string(),
+        expected,
+        actual,
+        met: actual.map(met),
+    }
+}
+
+/// Judge a journaled decision. `closes` and `benchmark` are oldest first and
+/// should start on or before the trade date.
+pub fn review_decision(
+    entry: &JournalEntry,
+    closes: &[(NaiveDate, f64)],
+    benchmark: &[(NaiveDate, f64)],
+    today: NaiveDate,
+) -> DecisionReview {
+    let horizon_end = entry
+        .horizon_days
+        .map(|days| entry.transaction_date + Duration::days(days as i64));
+    let window: Vec<(NaiveDate, f64)> = closes
+        .iter()
+        .copied()
+        .filter(|(d, _)| *d >= entry.transaction_date && horizon_end.is_none_or(|end| *d <= end))
+        .collect();
+
+    let entry_price = entry
+        .transaction_price
+        .filter(|p| *p > 0.0)
+        .or_else(|| window.first().map(|(_, close)| *close));
+    let review = window.last().copied();
+    let return_since_entry = match (entry_price, review) {
+        (Some(entry_price), Some((_, close))) => Some((close / entry_price - 1.0) * 100.0),
+        _ => None,
+    };
+    let benchmark_return = review.and_then(|(review_date, _)| {
+        let start = close_near(benchmark, entry.transaction_date)?;
+        let end = close_near(benchmark, review_date)?;
+        (start > 0.0).then(|| (end / start - 1.0) * 100.0)
+    });
+
+    let is_sell = entry.transaction_type == "SELL";
+    let excess_return = match (return_since_entry, benchmark_return) {
+        (Some(r), Some(b)) if is_sell => Some(b - r),
+        (Some(r), Some(b)) => Some(r - b),
+        _ => None,
+    };
+
+    let highest = window.iter().map(|(_, c)| *c).reduce(f64::max);
+    let lowest = window.iter().map(|(_, c)| *c).reduce(f64::min);
+    let mut thesis_checks = Vec::new();
+    if let Some(target) = entry.target_price {
+        thesis_checks.push(if is_sell {
+            check("target_price", target, lowest, |low| low <= target)
+        } else {
+            check("target_price", target, highest, |high| high >= target)
+        });
+    }
+    if let Some(stop) = entry.stop_price {
+        // Met when the stop was never breached
+        thesis_checks.push(if is_sell {
+            check("stop_price", stop, highest, |high| high < stop)
+        } else {
+            check("stop_price", stop, lowest, |low| low > stop)
+        });
+    }
+    if let Some(expected) = entry.expected_return_pct {
+        thesis_checks.push(if is_sell {
+            check("expected_return", expected, return_since_entry, |r| r <= expected)
+        } else {
+            check("expected_return", expected, return_since_entry, |r| r >= expected)
+        });
+    }
+
+    // Without a benchmark, judge the direction-adjusted return alone
+    let edge = excess_return.or(return_since_entry.map(|r| if is_sell { -r } else { r }));
+    let outcome = match edge {
+        Some(e) if e > 0.0 => DecisionOutcome::Outperformed,
+        Some(_) => DecisionOutcome::Underperformed,
+        None => DecisionOutcome::InsufficientData,
+    };
+    let judged: Vec<bool> = thesis_checks.iter().filter_map(|c| c.met).collect();
+    let score = edge.map(|e| {
+        let performance = (50.0 + e * SCORE_POINTS_PER_PCT).clamp(0.0, 100.0);
+        if judged.is_empty() {
+            performance
+        } else {
+            let thesis = judged.iter().filter(|m| **m).count() as f64 / judged.len() as f64 * 100.0;
+            performance * (1.0 - THESIS_WEIGHT) + thesis * THESIS_WEIGHT
+        }
+    });
+
+    DecisionReview {
+        transaction_id: entry.transaction_id,
+        ticker: entry.ticker.clone(),
+        transaction_type: entry.transaction_type.clone(),
+        transaction_date: entry.transaction_date,
+        rationale: entry.rationale.clone(),
+        confidence: entry.confidence,
+        entry_price,
+        review_date: review.map(|(d, _)| d),
+        review_price: review.map(|(_, c)| c),
+        horizon_elapsed: horizon_end.is_some_and(|end| today >= end),
+        return_since_entry,
+        benchmark_return,
+        excess_return,
+        thesis_checks,
+        outcome,
+        score,
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(s, n), v| (s + v, n + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Aggregate decision reviews into hit rates and averages
+pub fn summarize(portfolio_id: Uuid, benchmark: &str, decisions: Vec<DecisionReview>) -> JournalReview {
+    let reviewed: Vec<&DecisionReview> = decisions
+        .iter()
+        .filter(|d| d.outcome != DecisionOutcome::InsufficientData)
+        .collect();
+    let hit_rate = mean(
+        reviewed
+            .iter()
+            .map(|d| if d.outcome == DecisionOutcome::Outperformed { 100.0 } else { 0.0 }),
+    );
+    let judged: Vec<bool> = decisions
+        .iter()
+        .flat_map(|d| d.thesis_checks.iter().filter_map(|c| c.met))
+        .collect();
+
+    JournalReview {
+        portfolio_id,
+        benchmark: benchmark.to_string(),
+        reviewed: reviewed.len(),
+        hit_rate,
+        average_excess_return: mean(decisions.iter().filter_map(|d| d.excess_return)),
+        thesis_hit_rate: mean(judged.iter().map(|m| if *m { 100.0 } else { 0.0 })),
+        average_score: mean(decisions.iter().filter_map(|d| d.score)),
+        decisions,
+    }
+}
+
+fn to_series(points: Vec<PricePoint>) -> Vec<(NaiveDate, f64)> {
+    points
+        .into_iter()
+        .filter_map(|p| p.close_price.to_f64().map(|close| (p.date, close)))
+        .collect()
+}
+
+/// Review every journaled decision in a portfolio against `benchmark`
+pub async fn review_portfolio(pool: &PgPool, portfolio_id: Uuid, benchmark: &str) -> Result<JournalReview, AppError> {
+    let benchmark = benchmark.trim().to_uppercase();
+    if benchmark.is_empty() {
+        return Err(AppError::Validation("Benchmark cannot be empty".into()));
+    }
+    let entries = journal_queries::fetch_for_portfolio(pool, portfolio_id).await?;
+    let today = clock::today();
+    let Some(earliest) = entries.iter().map(|e| e.transaction_date).min() else {
+        return Ok(summarize(portfolio_id, &benchmark, Vec::new()));
+    };
+
+    let mut first_trade: HashMap<&str, NaiveDate> = HashMap::new();
+    for entry in &entries {
+        let date = first_trade.entry(entry.ticker.as_str()).or_insert(entry.transaction_date);
+        *date = (*date).min(entry.transaction_date);
+    }
+    let mut closes: HashMap<&str, Vec<(NaiveDate, f64)>> = HashMap::new();
+    for (ticker, from) in first_trade {
+        closes.insert(ticker, to_series(price_queries::fetch_range(pool, ticker, from, today).await?));
+    }
+    let benchmark_closes = to_series(
+        price_queries::fetch_range(pool, &benchmark, earliest - Duration::days(BENCHMARK_LOOKBACK_DAYS), today).await?,
+    );
+
+    let decisions = entries
+        .iter()
+        .map(|entry| {
+            let ticker_closes = closes.get(entry.ticker.as_str()).map(Vec::as_slice).unwrap_or_default();
+            review_decision(entry, ticker_closes, &benchmark_closes, today)
+        })
+        .collect();
+    Ok(summarize(portfolio_id, &benchmark, decisions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    fn entry(transaction_type: &str, price: Option<f64>) -> JournalEntry {
+        JournalEntry {
+            id: Uuid::new_v4(),
+            transaction_id: Uuid::new_v4(),
+            account_id: Uuid::new_v4(),
+            ticker: "AAPL".to_string(),
+            transaction_type: transaction_type.to_string(),
+            transaction_date: date(2),
+            transaction_price: price,
+            rationale: "Services growth".to_string(),
+            target_price: None,
+            stop_price: None,
+            expected_return_pct: None,
+            horizon_days: None,
+            confidence: Some(4),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn series(closes: &[(u32, f64)]) -> Vec<(NaiveDate, f64)> {
+        closes.iter().map(|(d, c)| (date(*d), *c)).collect()
+    }
+
+    #[test]
+    fn test_buy_scored_against_benchmark_and_thesis() {
+        let mut buy = entry("BUY", Some(100.0));
+        buy.target_price = Some(115.0);
+        buy.stop_price = Some(90.0);
+        buy.expected_return_pct = Some(15.0);
+        buy.horizon_days = Some(5);
+        let closes = series(&[(2, 100.0), (3, 104.0), (5, 112.0), (9, 130.0)]);
+        let benchmark = series(&[(1, 400.0), (2, 400.0), (5, 408.0), (9, 420.0)]);
+
+        let review = review_decision(&buy, &closes, &benchmark, date(10));
+        // The horizon ends on 7 March, so the close of 9 March is ignored
+        assert_eq!(review.review_date, Some(date(5)));
+        assert!(review.horizon_elapsed);
+        assert!((review.return_since_entry.unwrap() - 12.0).abs() < 1e-9);
+        assert!((review.benchmark_return.unwrap() - 2.0).abs() < 1e-9);
+        assert!((review.excess_return.unwrap() - 10.0).abs() < 1e-9);
+        assert_eq!(review.outcome, DecisionOutcome::Outperformed);
+
+        let met: Vec<Option<bool>> = review.thesis_checks.iter().map(|c| c.met).collect();
+        assert_eq!(met, vec![Some(false), Some(true), Some(false)]);
+        // 60% of a 75 performance score plus 40% of a one-in-three thesis hit rate
+        let expected_score = 75.0 * 0.6 + 100.0 / 3.0 * 0.4;
+        assert!((review.score.unwrap() - expected_score).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sell_pays_off_when_stock_lags() {
+        let mut sell = entry("SELL", None);
+        sell.target_price = Some(85.0);
+        let closes = series(&[(2, 100.0), (4, 92.0), (6, 80.0), (9, 90.0)]);
+        let benchmark = series(&[(2, 400.0), (9, 404.0)]);
+
+        let review = review_decision(&sell, &closes, &benchmark, date(10));
+        assert_eq!(review.entry_price, Some(100.0));
+        assert!(!review.horizon_elapsed);
+        assert!((review.return_since_entry.unwrap() + 10.0).abs() < 1e-9);
+        assert!((review.excess_return.unwrap() - 11.0).abs() < 1e-9);
+        assert_eq!(review.outcome, DecisionOutcome::Outperformed);
+        assert_eq!(review.thesis_checks[0].actual, Some(80.0));
+        assert_eq!(review.thesis_checks[0].met, Some(true));
+    }
+
+    #[test]
+    fn test_missing_prices_and_summary() {
+        let unpriced = review_decision(&entry("BUY", Some(50.0)), &[], &[], date(10));
+        assert_eq!(unpriced.outcome, DecisionOutcome::InsufficientData);
+        assert_eq!(unpriced.score, None);
+
+        let closes = series(&[(2, 100.0), (9, 95.0)]);
+        let loser = review_decision(&entry("BUY", None), &closes, &[], date(10));
+        assert_eq!(loser.benchmark_return, None);
+        assert_eq!(loser.outcome, DecisionOutcome::Underperformed);
+
+        let summary = summarize(Uuid::nil(), "SPY", vec![unpriced, loser]);
+        assert_eq!(summary.reviewed, 1);
+        assert_eq!(summary.hit_rate, Some(0.0));
+        assert_eq!(summary.average_excess_return, None);
+    }
+
+    #[test]
+    fn test_validate_entry() {
+        let valid = UpsertJournalEntry {
+            rationale: "  Breakout above resistance ".to_string(),
+            confidence: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(validate(valid).unwrap().rationale, "Breakout above resistance");
+        assert!(validate(UpsertJournalEntry { rationale: " ".to_string(), ..Default::default() }).is_err());
+        assert!(validate(UpsertJournalEntry {
+            rationale: "Value".to_string(),
+            confidence: Some(6),
+            ..Default::default()
+        })
+        .is_err());
+        assert!(validate(UpsertJournalEntry {
+            rationale: "Value".to_string(),
+            target_price: Some(-1.0),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
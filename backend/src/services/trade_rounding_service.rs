@@ -0,0 +1,240 @@
+//! Share-aware trade lists.
+//!
+//! Trades are sized in dollars by the optimizers. This converts them to share
+//! counts at the latest holding price: fractional where every account holding the
+//! ticker supports fractional shares, whole shares (rounded toward zero) otherwise,
+//! and reports the cash left over by rounding.
+
+use std::collections::{HashMap, HashSet};
+
+use bigdecimal::ToPrimitive;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::account_queries;
+use crate::errors::AppError;
+use crate::models::{AdjustmentAction, LatestAccountHolding, PositionAdjustment, TradeResidual, TradeRoundingReport};
+
+const FRACTIONAL_DECIMALS: i32 = 6;
+/// Residuals below this (in dollars) are not reported
+const MIN_RESIDUAL: f64 = 0.005;
+
+/// Prices, quantities and fractional-share support per ticker
+#[derive(Debug, Clone, Default)]
+pub struct RoundingContext {
+    pub prices: HashMap<String, f64>,
+    pub quantities: HashMap<String, f64>,
+    pub fractional: HashSet<String>,
+}
+
+impl RoundingContext {
+    pub fn from_holdings(holdings: &[LatestAccountHolding], fractional_accounts: &HashSet<Uuid>) -> Self {
+        let mut context = RoundingContext::default();
+        let mut whole_only = HashSet::new();
+        for holding in holdings {
+            if let Some(price) = holding.price.to_f64().filter(|p| *p > 0.0) {
+                context.prices.insert(holding.ticker.clone(), price);
+            }
+            *context.quantities.entry(holding.ticker.clone()).or_insert(0.0) += holding.quantity.to_f64().unwrap_or(0.0);
+            if !fractional_accounts.contains(&holding.account_id) {
+                whole_only.insert(holding.ticker.clone());
+            }
+        }
+        context.fractional = context.quantities.keys().filter(|t| !whole_only.contains(*t)).cloned().collect();
+        context
+    }
+
+    /// Share change and executed dollar amount for a trade of `amount`; selling the
+    /// whole position sells every share held, fractional or not
+    pub fn round_trade(&self, ticker: &str, amount: f64, sell_all: bool) -> Option<(f64, f64)> {
+        let price = *self.prices.get(ticker)?;
+        if sell_all {
+            let held = self.quantities.get(ticker).copied().unwrap_or(0.0);
+            return Some((-held, -held * price));
+        }
+        let shares = round_shares(amount / price, self.fractional.contains(ticker));
+        Some((shares, shares * price))
+    }
+}
+
+/// Round a share count toward zero, to whole shares or to the fractional precision
+pub fn round_shares(shares: f64, fractional: bool) -> f64 {
+    if fractional {
+        let factor = 10f64.powi(FRACTIONAL_DECIMALS);
+        (shares * factor).trunc() / factor
+    } else {
+        shares.trunc()
+    }
+}
+
+pub async fn load_context(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    holdings: &[LatestAccountHolding],
+) -> Result<RoundingContext, AppError> {
+    let fractional_accounts: HashSet<Uuid> = account_queries::fetch_all(pool, portfolio_id)
+        .await?
+        .into_iter()
+        .filter(|a| a.fractional_shares)
+        .map(|a| a.id)
+        .collect();
+    Ok(RoundingContext::from_holdings(holdings, &fractional_accounts))
+}
+
+/// Collects residuals while a trade list is rounded
+#[derive(Debug, Default)]
+pub struct RoundingTally {
+    trades: Vec<TradeResidual>,
+}
+
+impl RoundingTally {
+    pub fn record(&mut self, ticker: &str, target_amount: f64, executed_amount: f64) {
+        let residual = target_amount - executed_amount;
+        if residual.abs() >= MIN_RESIDUAL {
+            self.trades.push(TradeResidual {
+                ticker: ticker.to_string(),
+                target_amount,
+                executed_amount,
+                residual,
+            });
+        }
+    }
+
+    pub fn report(self) -> Option<TradeRoundingReport> {
+        (!self.trades.is_empty()).then(|| TradeRoundingReport {
+            residual_cash: self.trades.iter().map(|t| t.residual).sum(),
+            trades: self.trades,
+        })
+    }
+}
+
+/// Fill in share counts for a recommendation's trades and resize them to what can be executed
+pub fn round_adjustments(
+    context: &RoundingContext,
+    total_value: f64,
+    adjustments: &mut [PositionAdjustment],
+) -> Option<TradeRoundingReport> {
+    let mut tally = RoundingTally::default();
+    for adjustment in adjustments.iter_mut() {
+        if adjustment.action == AdjustmentAction::Hold {
+            continue;
+        }
+        let sell_all = adjustment.action == AdjustmentAction::Sell && adjustment.recommended_value <= 0.0;
+        let Some((shares, executed)) = context.round_trade(&adjustment.ticker, adjustment.amount_change, sell_all) else {
+            continue;
+        };
+        tally.record(&adjustment.ticker, adjustment.amount_change, executed);
+
+        adjustment.shares_change = Some(shares);
+        adjustment.amount_change = executed;
+        adjustment.recommended_value = adjustment.current_value + executed;
+        if total_value > 0.0 {
+            adjustment.recommended_weight = adjustment.recommended_value / total_value * 100.0;
+        }
+        if shares == 0.0 {
+            adjustment.action = AdjustmentAction::Hold;
+        }
+    }
+    tally.report()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(fractional: bool) -> RoundingContext {
+        RoundingContext {
+            prices: [("AAPL".to_string(), 150.0), ("MSFT".to_string(), 400.0)].into_iter().collect(),
+            quantities: [("AAPL".to_string(), 10.5), ("MSFT".to_string(), 5.0)].into_iter().collect(),
+            fractional: if fractional { ["AAPL".to_string(), "MSFT".to_string()].into_iter().collect() } else { HashSet::new() },
+        }
+    }
+
+    fn adjustment(ticker: &str, current: f64, change: f64) -> PositionAdjustment {
+        PositionAdjustment {
+            ticker: ticker.to_string(),
+            holding_name: None,
+            current_value: current,
+            current_weight: current / 100.0,
+            recommended_value: current + change,
+            recommended_weight: (current + change) / 100.0,
+            action: if change > 0.0 { AdjustmentAction::Buy } else { AdjustmentAction::Sell },
+            amount_change: change,
+            shares_change: None,
+        }
+    }
+
+    #[test]
+    fn test_round_shares() {
+        assert_eq!(round_shares(3.7, false), 3.0);
+        assert_eq!(round_shares(-3.7, false), -3.0);
+        assert_eq!(round_shares(3.12345678, true), 3.123456);
+    }
+
+    #[test]
+    fn test_whole_share_rounding_reports_residual() {
+        let mut adjustments = vec![adjustment("AAPL", 1575.0, 500.0), adjustment("MSFT", 2000.0, -1000.0)];
+        let report = round_adjustments(&context(false), 10_000.0, &mut adjustments).unwrap();
+
+        assert_eq!(adjustments[0].shares_change, Some(3.0));
+        assert_eq!(adjustments[0].amount_change, 450.0);
+        assert_eq!(adjustments[1].shares_change, Some(-2.0));
+        assert_eq!(adjustments[1].amount_change, -800.0);
+        // 50 unspent on the buy, 200 less raised on the sell
+        assert!((report.residual_cash - (50.0 - 200.0)).abs() < 1e-9);
+        assert_eq!(report.trades.len(), 2);
+    }
+
+    #[test]
+    fn test_fractional_and_sell_all() {
+        let mut adjustments = vec![adjustment("AAPL", 1575.0, -1575.0)];
+        let report = round_adjustments(&context(false), 10_000.0, &mut adjustments);
+        assert_eq!(adjustments[0].shares_change, Some(-10.5));
+        assert!(report.is_none());
+
+        let mut adjustments = vec![adjustment("MSFT", 2000.0, 100.0)];
+        round_adjustments(&context(true), 10_000.0, &mut adjustments);
+        assert_eq!(adjustments[0].shares_change, Some(0.25));
+
+        let mut adjustments = vec![adjustment("MSFT", 2000.0, 100.0)];
+        round_adjustments(&context(false), 10_000.0, &mut adjustments);
+        assert_eq!(adjustments[0].action, AdjustmentAction::Hold);
+    }
+
+    #[test]
+    fn test_context_requires_every_account_to_be_fractional() {
+        use bigdecimal::BigDecimal;
+        use std::str::FromStr;
+
+        let fractional_account = Uuid::new_v4();
+        let whole_account = Uuid::new_v4();
+        let holding = |account_id: Uuid, ticker: &str| LatestAccountHolding {
+            id: Uuid::new_v4(),
+            account_id,
+            account_nickname: String::new(),
+            account_number: String::new(),
+            ticker: ticker.to_string(),
+            holding_name: None,
+            asset_category: None,
+            industry: None,
+            quantity: BigDecimal::from_str("2").unwrap(),
+            price: BigDecimal::from_str("10").unwrap(),
+            market_value: BigDecimal::from_str("20").unwrap(),
+            gain_loss: None,
+            gain_loss_pct: None,
+            snapshot_date: chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            ticker_type: "equity".to_string(),
+            currency: "USD".to_string(),
+        };
+        let holdings = vec![
+            holding(fractional_account, "AAPL"),
+            holding(fractional_account, "MSFT"),
+            holding(whole_account, "MSFT"),
+        ];
+        let context = RoundingContext::from_holdings(&holdings, &[fractional_account].into_iter().collect());
+
+        assert!(context.fractional.contains("AAPL"));
+        assert!(!context.fractional.contains("MSFT"));
+        assert_eq!(context.quantities["MSFT"], 4.0);
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use bigdecimal::{BigDecimal, ToPrimitive, FromPrimitive};
 use chrono::NaiveDate;
-use sqlx::PgPool;
+use sqlx::PgConnection;
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -9,14 +9,14 @@ use crate::db::{detected_transaction_queries, holding_snapshot_queries, cash_flo
 use crate::models::{CreateDetectedTransaction, HoldingSnapshot, TransactionType, CreateCashFlow, FlowType};
 
 pub async fn detect_transactions_between_snapshots(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     account_id: Uuid,
     from_date: NaiveDate,
     to_date: NaiveDate,
 ) -> Result<usize> {
     // Fetch holdings from both snapshots
-    let from_holdings = holding_snapshot_queries::fetch_by_account_and_date(pool, account_id, from_date).await?;
-    let to_holdings = holding_snapshot_queries::fetch_by_account_and_date(pool, account_id, to_date).await?;
+    let from_holdings = holding_snapshot_queries::fetch_by_account_and_date(&mut *conn, account_id, from_date).await?;
+    let to_holdings = holding_snapshot_queries::fetch_by_account_and_date(&mut *conn, account_id, to_date).await?;
 
     // Build maps for easier comparison
     let from_map: HashMap<String, &HoldingSnapshot> = from_holdings
@@ -30,7 +30,16 @@ pub async fn detect_transactions_between_snapshots(
         .collect();
 
     // Delete any existing transactions for this snapshot
-    detected_transaction_queries::delete_transactions_for_snapshot(pool, account_id, to_date).await?;
+    detected_transaction_queries::delete_transactions_for_snapshot(&mut *conn, account_id, to_date).await?;
+
+    // Shares bought by dividend reinvestment between the snapshots are already
+    // recorded as DRIP transactions and must not be detected again as buys
+    let drip_quantities: HashMap<String, f64> =
+        detected_transaction_queries::fetch_drip_quantities(&mut *conn, account_id, from_date, to_date)
+            .await?
+            .into_iter()
+            .map(|(ticker, qty)| (ticker, qty.to_f64().unwrap_or(0.0)))
+            .collect();
 
     let mut transactions_created = 0;
 
@@ -64,10 +73,10 @@ pub async fn detect_transactions_between_snapshots(
                 )),
             };
 
-            cash_flow_queries::create(pool, account_id, cash_flow).await?;
+            cash_flow_queries::create(&mut *conn, account_id, cash_flow).await?;
 
             // Update account totals
-            cash_flow_queries::update_account_totals(pool, account_id).await?;
+            cash_flow_queries::update_account_totals(&mut *conn, account_id).await?;
 
             transactions_created += 1;
         }
@@ -83,8 +92,8 @@ pub async fn detect_transactions_between_snapshots(
                 description: Some(format!("Initial cash position: ${:.2}", cash_amount)),
             };
 
-            cash_flow_queries::create(pool, account_id, cash_flow).await?;
-            cash_flow_queries::update_account_totals(pool, account_id).await?;
+            cash_flow_queries::create(&mut *conn, account_id, cash_flow).await?;
+            cash_flow_queries::update_account_totals(&mut *conn, account_id).await?;
 
             transactions_created += 1;
         }
@@ -100,10 +109,11 @@ pub async fn detect_transactions_between_snapshots(
             // Holding existed in both snapshots - check for quantity change
             let from_qty = from_holding.quantity.to_f64().unwrap_or(0.0);
             let to_qty = to_holding.quantity.to_f64().unwrap_or(0.0);
+            let drip_qty = drip_quantities.get(ticker).copied().unwrap_or(0.0);
 
-            if (to_qty - from_qty).abs() > 0.01 {
-                // Quantity changed
-                let qty_change = to_qty - from_qty;
+            if (to_qty - from_qty - drip_qty).abs() > 0.01 {
+                // Quantity changed beyond reinvested dividends
+                let qty_change = to_qty - from_qty - drip_qty;
                 let transaction_type = if qty_change > 0.0 {
                     TransactionType::Buy
                 } else {
@@ -129,17 +139,23 @@ pub async fn detect_transactions_between_snapshots(
                     )),
                 };
 
-                detected_transaction_queries::create(pool, account_id, to_date, transaction).await?;
+                detected_transaction_queries::create(&mut *conn, account_id, to_date, transaction).await?;
                 transactions_created += 1;
             }
         } else {
             // New holding - BUY
-            let amount = &to_holding.quantity * &to_holding.price;
+            let drip_qty = drip_quantities.get(ticker).copied().unwrap_or(0.0);
+            let buy_qty = to_holding.quantity.to_f64().unwrap_or(0.0) - drip_qty;
+            if buy_qty <= 0.01 {
+                continue;
+            }
+            let buy_qty_bd = BigDecimal::from_f64(buy_qty).unwrap_or_else(|| BigDecimal::from(0));
+            let amount = &buy_qty_bd * &to_holding.price;
 
             let transaction = CreateDetectedTransaction {
                 transaction_type: TransactionType::Buy,
                 ticker: ticker.clone(),
-                quantity: Some(to_holding.quantity.clone()),
+                quantity: Some(buy_qty_bd),
                 price: Some(to_holding.price.clone()),
                 amount: Some(amount),
                 from_snapshot_date: Some(from_date),
@@ -147,7 +163,7 @@ pub async fn detect_transactions_between_snapshots(
                 description: Some(format!("New position detected")),
             };
 
-            detected_transaction_queries::create(pool, account_id, to_date, transaction).await?;
+            detected_transaction_queries::create(&mut *conn, account_id, to_date, transaction).await?;
             transactions_created += 1;
         }
     }
@@ -174,7 +190,7 @@ pub async fn detect_transactions_between_snapshots(
                 description: Some(format!("Position closed")),
             };
 
-            detected_transaction_queries::create(pool, account_id, to_date, transaction).await?;
+            detected_transaction_queries::create(&mut *conn, account_id, to_date, transaction).await?;
             transactions_created += 1;
         }
     }
@@ -183,7 +199,7 @@ pub async fn detect_transactions_between_snapshots(
 }
 
 pub async fn detect_transactions_for_new_snapshot(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     account_id: Uuid,
     new_snapshot_date: NaiveDate,
 ) -> Result<usize> {
@@ -196,16 +212,16 @@ pub async fn detect_transactions_for_new_snapshot(
     )
     .bind(account_id)
     .bind(new_snapshot_date)
-    .fetch_optional(pool)
+    .fetch_optional(&mut *conn)
     .await?;
 
     if let Some(previous_date) = all_dates {
-        detect_transactions_between_snapshots(pool, account_id, previous_date, new_snapshot_date).await
+        detect_transactions_between_snapshots(&mut *conn, account_id, previous_date, new_snapshot_date).await
     } else {
         // No previous snapshot - this is the first import
         // Create an initial deposit for the total account value
         let first_snapshot_holdings = holding_snapshot_queries::fetch_by_account_and_date(
-            pool,
+            &mut *conn,
             account_id,
             new_snapshot_date
         ).await?;
@@ -229,8 +245,8 @@ pub async fn detect_transactions_for_new_snapshot(
                 description: Some(format!("Initial account value: ${:.2}", total_value)),
             };
 
-            cash_flow_queries::create(pool, account_id, cash_flow).await?;
-            cash_flow_queries::update_account_totals(pool, account_id).await?;
+            cash_flow_queries::create(&mut *conn, account_id, cash_flow).await?;
+            cash_flow_queries::update_account_totals(&mut *conn, account_id).await?;
 
             Ok(1)
         } else {
@@ -0,0 +1,121 @@
+//! Personal data export and erasure.
+//!
+//! Erasure is export-first: `DELETE /api/users/me/data` is refused unless the
+//! user downloaded an export within the last [`EXPORT_VALID_HOURS`] hours, or
+//! explicitly passes `skip_export=true`. Every export and erasure is written to
+//! `data_erasure_audit` with per-table row counts (never the data itself); the
+//! erasure record is written in the same transaction as the deletes.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db::user_data_queries;
+use crate::errors::AppError;
+use crate::models::{DataErasureQuery, DataErasureSummary, UserDataExport};
+
+/// How long an export counts as "recent" for the export-first check
+pub const EXPORT_VALID_HOURS: i64 = 24;
+
+/// Collect everything stored about the user and record the export
+pub async fn export_user_data(pool: &PgPool, user_id: Uuid) -> Result<UserDataExport, AppError> {
+    let user = user_data_queries::fetch_user_profile(pool, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    let tables = user_data_queries::fetch_user_tables(pool, user_id).await?;
+
+    let counts = tables
+        .iter()
+        .map(|(table, rows)| (table.clone(), rows.len() as u64))
+        .collect();
+    user_data_queries::record_export(pool, user_id, &counts).await?;
+    info!("Exported personal data for user {} ({} tables)", user_id, tables.len());
+
+    Ok(UserDataExport {
+        exported_at: Utc::now(),
+        user,
+        tables,
+    })
+}
+
+/// Check the erasure request against the export-first rule
+pub fn check_erasure_allowed(
+    query: &DataErasureQuery,
+    last_export_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    if !query.confirm {
+        return Err(AppError::Validation(
+            "Data erasure cannot be undone; pass confirm=true to proceed".to_string(),
+        ));
+    }
+    if query.skip_export {
+        return Ok(());
+    }
+    match last_export_at {
+        Some(at) if now - at <= Duration::hours(EXPORT_VALID_HOURS) => Ok(()),
+        _ => Err(AppError::Validation(format!(
+            "Download your data from GET /api/users/me/data/export first (exports are valid for {} hours), \
+             or pass skip_export=true to erase without one",
+            EXPORT_VALID_HOURS
+        ))),
+    }
+}
+
+/// Erase all of the user's data, and the account itself with `delete_account`
+pub async fn erase_user_data(
+    pool: &PgPool,
+    user_id: Uuid,
+    query: &DataErasureQuery,
+) -> Result<DataErasureSummary, AppError> {
+    let last_export_at = user_data_queries::fetch_last_export_at(pool, user_id).await?;
+    check_erasure_allowed(query, last_export_at, Utc::now())?;
+
+    let rows_deleted = user_data_queries::erase_user_data(pool, user_id, query.delete_account).await?;
+    let total_rows_deleted = rows_deleted.values().sum();
+    info!(
+        "Erased personal data for user {}: {} rows (account deleted: {})",
+        user_id, total_rows_deleted, query.delete_account
+    );
+
+    Ok(DataErasureSummary {
+        erased_at: Utc::now(),
+        account_deleted: query.delete_account,
+        rows_deleted,
+        total_rows_deleted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(confirm: bool, skip_export: bool) -> DataErasureQuery {
+        DataErasureQuery {
+            confirm,
+            delete_account: false,
+            skip_export,
+        }
+    }
+
+    #[test]
+    fn test_erasure_requires_confirmation() {
+        let now = Utc::now();
+        let result = check_erasure_allowed(&query(false, true), Some(now), now);
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn test_erasure_requires_recent_export() {
+        let now = Utc::now();
+        assert!(check_erasure_allowed(&query(true, false), None, now).is_err());
+        assert!(check_erasure_allowed(&query(true, false), Some(now - Duration::hours(25)), now).is_err());
+        assert!(check_erasure_allowed(&query(true, false), Some(now - Duration::hours(2)), now).is_ok());
+    }
+
+    #[test]
+    fn test_skip_export_bypasses_export_check() {
+        assert!(check_erasure_allowed(&query(true, true), None, Utc::now()).is_ok());
+    }
+}
@@ -77,6 +77,7 @@ pub async fn reset_user_preferences(
         technical_weight: Some(0.4),
         fundamental_weight: Some(0.3),
         custom_settings: None,
+        share_anonymous_stats: Some(false),
     };
 
     risk_preferences_queries::upsert_preferences(pool, user_id, &update)
@@ -3,6 +3,7 @@ use crate::models::forecast::{VolatilityForecast, GarchParameters, VolatilityFor
 use crate::models::PricePoint;
 use crate::db::price_queries;
 use crate::services::price_service;
+use crate::services::clock;
 use chrono::{Duration, Utc};
 use sqlx::PgPool;
 
@@ -352,7 +353,7 @@ fn forecast_volatility_multistep(
         let lower_bound = (forecasted_vol - z_score * forecast_std_error).max(0.0);
         let upper_bound = forecasted_vol + z_score * forecast_std_error;
 
-        let forecast_date = (Utc::now() + Duration::days(h as i64))
+        let forecast_date = (clock::now() + Duration::days(h as i64))
             .format("%Y-%m-%d")
             .to_string();
 
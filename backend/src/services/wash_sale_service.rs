@@ -0,0 +1,223 @@
+//! Wash sale detection.
+//!
+//! A sale at a loss is a wash sale when shares of the same ticker are bought
+//! within 30 days before or after it. The loss is disallowed in proportion to the
+//! shares replaced; each replacement purchase can only be matched once.
+//! Detection runs over a portfolio's lot ledger, so purchases in any of the
+//! portfolio's accounts count as replacements.
+
+use std::collections::{HashMap, HashSet};
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::detected_transaction_queries;
+use crate::errors::AppError;
+use crate::models::WashSale;
+use crate::services::lot_service::{self, LotLedger, LotTrade, TradeSide};
+
+const WASH_SALE_WINDOW_DAYS: i64 = 30;
+const LOSS_EPSILON: f64 = 0.005;
+
+/// Result of re-scanning a portfolio for wash sales
+#[derive(Debug, Clone, Default)]
+pub struct WashSaleScan {
+    pub wash_sales: Vec<WashSale>,
+    /// Wash sales that were not flagged before this scan
+    pub newly_flagged: Vec<WashSale>,
+}
+
+/// Find wash sales among the realized lots of `ledger`, using `trades` for replacement purchases
+pub fn detect_wash_sales(trades: &[LotTrade], ledger: &LotLedger) -> Vec<WashSale> {
+    // Aggregate realized lots per sell transaction
+    let mut sales: Vec<WashSale> = Vec::new();
+    let mut sold_lots: HashMap<Uuid, HashSet<Uuid>> = HashMap::new();
+    for lot in &ledger.realized {
+        if let Some(lot_tx) = lot.lot_transaction_id {
+            sold_lots.entry(lot.sell_transaction_id).or_default().insert(lot_tx);
+        }
+        match sales.iter_mut().find(|s| s.sell_transaction_id == lot.sell_transaction_id) {
+            Some(sale) => {
+                sale.quantity_sold += lot.quantity;
+                sale.loss -= lot.gain;
+            }
+            None => sales.push(WashSale {
+                sell_transaction_id: lot.sell_transaction_id,
+                account_id: lot.account_id,
+                ticker: lot.ticker.clone(),
+                sold_date: lot.sold_date,
+                quantity_sold: lot.quantity,
+                loss: -lot.gain,
+                disallowed_loss: 0.0,
+                replacement_quantity: 0.0,
+                replacement_transaction_ids: Vec::new(),
+            }),
+        }
+    }
+    sales.retain(|s| s.loss > LOSS_EPSILON);
+    sales.sort_by_key(|s| s.sold_date);
+
+    // Replacement shares still available for matching, per buy transaction
+    let mut available: HashMap<Uuid, f64> = trades
+        .iter()
+        .filter(|t| t.side == TradeSide::Buy)
+        .filter_map(|t| t.transaction_id.map(|id| (id, t.quantity)))
+        .collect();
+
+    let mut buys: Vec<&LotTrade> = trades
+        .iter()
+        .filter(|t| t.side == TradeSide::Buy && t.transaction_id.is_some())
+        .collect();
+    buys.sort_by_key(|b| b.date);
+
+    let empty = HashSet::new();
+    for sale in &mut sales {
+        let own_lots = sold_lots.get(&sale.sell_transaction_id).unwrap_or(&empty);
+        let mut needed = sale.quantity_sold;
+
+        for buy in &buys {
+            if needed <= 0.0 {
+                break;
+            }
+            let Some(buy_id) = buy.transaction_id else { continue };
+            let in_window = (buy.date - sale.sold_date).num_days().abs() <= WASH_SALE_WINDOW_DAYS;
+            if buy.ticker != sale.ticker || !in_window || own_lots.contains(&buy_id) {
+                continue;
+            }
+            let Some(remaining) = available.get_mut(&buy_id) else { continue };
+            let matched = needed.min(*remaining);
+            if matched <= 0.0 {
+                continue;
+            }
+            *remaining -= matched;
+            needed -= matched;
+            sale.replacement_quantity += matched;
+            sale.replacement_transaction_ids.push(buy_id);
+        }
+
+        if sale.quantity_sold > 0.0 {
+            sale.disallowed_loss = sale.loss * (sale.replacement_quantity / sale.quantity_sold).min(1.0);
+        }
+    }
+
+    sales.retain(|s| s.replacement_quantity > 0.0);
+    sales
+}
+
+pub fn warning_message(sale: &WashSale) -> String {
+    format!(
+        "Wash sale: {} sold {} at a ${:.2} loss with {} shares repurchased within {} days; ${:.2} of the loss is disallowed",
+        sale.ticker,
+        sale.sold_date,
+        sale.loss,
+        sale.replacement_quantity,
+        WASH_SALE_WINDOW_DAYS,
+        sale.disallowed_loss
+    )
+}
+
+/// Re-scan a portfolio for wash sales and store the disallowed loss on each
+/// flagged sell transaction. Called after transactions are recorded or imported.
+pub async fn refresh_portfolio_wash_sales(pool: &PgPool, portfolio_id: Uuid) -> Result<WashSaleScan, AppError> {
+    let trades = lot_service::load_portfolio_trades(pool, portfolio_id).await?;
+    let matching = lot_service::load_lot_matching(pool, portfolio_id).await?;
+    let ledger = lot_service::build_ledger(trades.clone(), &matching);
+    let wash_sales = detect_wash_sales(&trades, &ledger);
+
+    let previously_flagged: HashSet<Uuid> = detected_transaction_queries::fetch_wash_sale_ids(pool, portfolio_id)
+        .await?
+        .into_iter()
+        .collect();
+
+    let flags: Vec<(Uuid, f64)> = wash_sales
+        .iter()
+        .map(|s| (s.sell_transaction_id, s.disallowed_loss))
+        .collect();
+    detected_transaction_queries::replace_wash_sale_flags(pool, portfolio_id, &flags).await?;
+
+    let newly_flagged = wash_sales
+        .iter()
+        .filter(|s| !previously_flagged.contains(&s.sell_transaction_id))
+        .cloned()
+        .collect();
+
+    Ok(WashSaleScan { wash_sales, newly_flagged })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::lot_service::LotMatching;
+    use chrono::NaiveDate;
+
+    fn trade(side: TradeSide, day: u32, qty: f64, price: f64) -> LotTrade {
+        LotTrade {
+            account_id: Uuid::nil(),
+            ticker: "AAPL".to_string(),
+            date: NaiveDate::from_ymd_opt(2026, 1, day).unwrap(),
+            side,
+            quantity: qty,
+            price,
+            transaction_id: Some(Uuid::new_v4()),
+        }
+    }
+
+    fn scan(trades: Vec<LotTrade>) -> Vec<WashSale> {
+        let ledger = lot_service::build_ledger(trades.clone(), &LotMatching::default());
+        detect_wash_sales(&trades, &ledger)
+    }
+
+    #[test]
+    fn test_loss_with_repurchase_is_wash_sale() {
+        let sales = scan(vec![
+            trade(TradeSide::Buy, 2, 10.0, 100.0),
+            trade(TradeSide::Sell, 10, 10.0, 80.0),
+            trade(TradeSide::Buy, 20, 4.0, 85.0),
+        ]);
+        assert_eq!(sales.len(), 1);
+        assert!((sales[0].loss - 200.0).abs() < 1e-9);
+        assert!((sales[0].replacement_quantity - 4.0).abs() < 1e-9);
+        assert!((sales[0].disallowed_loss - 80.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gain_or_distant_repurchase_is_not_wash_sale() {
+        assert!(scan(vec![
+            trade(TradeSide::Buy, 2, 10.0, 100.0),
+            trade(TradeSide::Sell, 10, 10.0, 120.0),
+            trade(TradeSide::Buy, 12, 10.0, 110.0),
+        ])
+        .is_empty());
+
+        let mut late_buy = trade(TradeSide::Buy, 1, 10.0, 85.0);
+        late_buy.date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        assert!(scan(vec![
+            trade(TradeSide::Buy, 2, 10.0, 100.0),
+            trade(TradeSide::Sell, 10, 10.0, 80.0),
+            late_buy,
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn test_lot_being_sold_is_not_a_replacement() {
+        // Bought 5 days before selling the same shares at a loss: no other purchase
+        assert!(scan(vec![
+            trade(TradeSide::Buy, 5, 10.0, 100.0),
+            trade(TradeSide::Sell, 10, 10.0, 90.0),
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn test_replacement_shares_match_once() {
+        let sales = scan(vec![
+            trade(TradeSide::Buy, 1, 20.0, 100.0),
+            trade(TradeSide::Sell, 10, 10.0, 90.0),
+            trade(TradeSide::Sell, 11, 10.0, 90.0),
+            trade(TradeSide::Buy, 15, 10.0, 92.0),
+        ]);
+        assert_eq!(sales.len(), 1);
+        assert!((sales[0].disallowed_loss - 100.0).abs() < 1e-9);
+    }
+}
@@ -1,8 +1,11 @@
-use crate::db::{price_queries, watchlist_queries};
+use bigdecimal::ToPrimitive;
+use crate::db::{alert_queries, annotation_queries, price_queries, user_preferences_queries, watchlist_queries};
 use crate::models::watchlist::*;
+use crate::models::{FiftyTwoWeekRange, PositionAnnotation, PositionLevelCrossing};
 use crate::services::indicators;
 use serde_json::json;
 use sqlx::PgPool;
+use std::collections::HashMap;
 
 const ALERT_COOLDOWN_HOURS: i32 = 4;
 const RSI_PERIOD: usize = 14;
@@ -13,17 +16,25 @@ const VOLUME_PERIOD: usize = 20;
 // Threshold Breach Detection
 // ==============================================================================
 
+/// Current price and derived indicators for a ticker, checked against thresholds.
+#[derive(Debug, Clone, Copy)]
+pub struct TickerMetrics {
+    pub current_price: f64,
+    pub rsi: Option<f64>,
+    pub volume_ratio: Option<f64>,
+    pub volatility: Option<f64>,
+    pub fifty_two_week: Option<FiftyTwoWeekRange>,
+}
+
 /// Check all enabled thresholds for a given ticker and its current data.
 /// Returns a list of monitoring results (alerts to generate).
 pub async fn check_thresholds(
     pool: &PgPool,
     item: &WatchlistItem,
     user_id: uuid::Uuid,
-    current_price: f64,
-    rsi_value: Option<f64>,
-    volume_ratio: Option<f64>,
-    volatility: Option<f64>,
+    metrics: &TickerMetrics,
 ) -> Result<Vec<MonitoringResult>, sqlx::Error> {
+    let TickerMetrics { current_price, rsi: rsi_value, volume_ratio, volatility, fifty_two_week: range } = *metrics;
     let thresholds = watchlist_queries::get_thresholds_for_item(pool, item.id).await?;
     let mut results = Vec::new();
 
@@ -52,14 +63,14 @@ pub async fn check_thresholds(
         };
 
         // Convert BigDecimal threshold value to f64 for comparison
-        let threshold_f64 = threshold.value.to_string().parse::<f64>().unwrap_or(0.0);
+        let threshold_f64 = threshold.value.to_f64().unwrap_or(0.0);
 
         let triggered = match threshold.threshold_type.as_str() {
             "price_above" | "price_below" => {
                 comparison.evaluate(current_price, threshold_f64)
             }
             "price_change_pct" => {
-                if let Some(added_price) = item.added_price.as_ref().and_then(|p| p.to_string().parse::<f64>().ok()) {
+                if let Some(added_price) = item.added_price.as_ref().and_then(|p| p.to_f64()) {
                     if added_price > 0.0 {
                         let change_pct = ((current_price - added_price) / added_price) * 100.0;
                         comparison.evaluate(change_pct.abs(), threshold_f64)
@@ -99,6 +110,20 @@ pub async fn check_thresholds(
                     false
                 }
             }
+            "pct_off_52w_high" => {
+                if let Some(r) = range {
+                    comparison.evaluate(r.pct_off_high, threshold_f64)
+                } else {
+                    false
+                }
+            }
+            "pct_above_52w_low" => {
+                if let Some(r) = range {
+                    comparison.evaluate(r.pct_above_low, threshold_f64)
+                } else {
+                    false
+                }
+            }
             _ => false,
         };
 
@@ -106,7 +131,7 @@ pub async fn check_thresholds(
             let actual = match threshold.threshold_type.as_str() {
                 "price_above" | "price_below" => current_price,
                 "price_change_pct" => {
-                    if let Some(added) = item.added_price.as_ref().and_then(|p| p.to_string().parse::<f64>().ok()) {
+                    if let Some(added) = item.added_price.as_ref().and_then(|p| p.to_f64()) {
                         ((current_price - added) / added) * 100.0
                     } else {
                         0.0
@@ -115,6 +140,8 @@ pub async fn check_thresholds(
                 "volatility" => volatility.unwrap_or(0.0),
                 "volume_spike" => volume_ratio.unwrap_or(0.0),
                 "rsi_overbought" | "rsi_oversold" => rsi_value.unwrap_or(0.0),
+                "pct_off_52w_high" => range.map(|r| r.pct_off_high).unwrap_or(0.0),
+                "pct_above_52w_low" => range.map(|r| r.pct_above_low).unwrap_or(0.0),
                 _ => 0.0,
             };
 
@@ -140,6 +167,7 @@ pub async fn check_thresholds(
                     "rsi": rsi_value,
                     "volume_ratio": volume_ratio,
                     "volatility": volatility,
+                    "fifty_two_week": range,
                 }),
             });
         }
@@ -338,7 +366,7 @@ pub async fn monitor_ticker(
 
     let price_values: Vec<f64> = prices
         .iter()
-        .map(|p| p.close_price.to_string().parse::<f64>().unwrap_or(0.0))
+        .map(|p| p.close_price.to_f64().unwrap_or(0.0))
         .collect();
 
     let current_price = *price_values.last().unwrap_or(&0.0);
@@ -368,6 +396,14 @@ pub async fn monitor_ticker(
     // Volume ratio (placeholder - we don't have volume data in price_points)
     let volume_ratio: Option<f64> = None;
 
+    let metrics = TickerMetrics {
+        current_price,
+        rsi: current_rsi,
+        volume_ratio,
+        volatility,
+        fifty_two_week: FiftyTwoWeekRange::from_points(&prices),
+    };
+
     // Get all watchlist items for this ticker
     let items_with_users = watchlist_queries::get_all_items_for_ticker(pool, ticker).await?;
 
@@ -377,10 +413,7 @@ pub async fn monitor_ticker(
             pool,
             item,
             *user_id,
-            current_price,
-            current_rsi,
-            volume_ratio,
-            volatility,
+            &metrics,
         )
         .await?;
         all_results.extend(threshold_results);
@@ -428,6 +461,179 @@ pub async fn monitor_ticker(
     Ok(all_results)
 }
 
+// ==============================================================================
+// Position Target / Stop-Loss Crossings
+// ==============================================================================
+
+/// Levels of a position that `price` has crossed and that have not yet been alerted.
+///
+/// The target is crossed at or above it, the stop-loss and trailing stop at or below them.
+pub fn detect_level_crossings(annotation: &PositionAnnotation, price: f64) -> Vec<PositionLevelCrossing> {
+    let mut crossings = Vec::new();
+    let levels = [
+        ("target", annotation.target_price, annotation.target_alerted_at.is_some(), true),
+        ("stop_loss", annotation.stop_loss, annotation.stop_alerted_at.is_some(), false),
+        ("trailing_stop", annotation.trailing_stop(), annotation.trailing_alerted_at.is_some(), false),
+    ];
+
+    for (level_type, level, alerted, above) in levels {
+        let Some(level) = level else { continue };
+        let crossed = if above { price >= level } else { price <= level };
+        if crossed && !alerted {
+            crossings.push(PositionLevelCrossing {
+                annotation_id: annotation.id,
+                ticker: annotation.ticker.clone(),
+                level_type,
+                level,
+                price,
+                peak: (level_type == "trailing_stop").then_some(annotation.trailing_high).flatten(),
+            });
+        }
+    }
+    crossings
+}
+
+/// Alerted levels that `price` has moved back across, so their alert can fire again
+pub fn levels_to_rearm(annotation: &PositionAnnotation, price: f64) -> Vec<&'static str> {
+    let mut rearm = Vec::new();
+    if let (Some(target), Some(_)) = (annotation.target_price, annotation.target_alerted_at) {
+        if price < target {
+            rearm.push("target");
+        }
+    }
+    if let (Some(stop), Some(_)) = (annotation.stop_loss, annotation.stop_alerted_at) {
+        if price > stop {
+            rearm.push("stop_loss");
+        }
+    }
+    if let (Some(stop), Some(_)) = (annotation.trailing_stop(), annotation.trailing_alerted_at) {
+        if price > stop {
+            rearm.push("trailing_stop");
+        }
+    }
+    rearm
+}
+
+/// High-water mark after `price`: the stored mark, or `since_entry` (the highest
+/// stored close since the position was opened) before one is stored
+pub fn next_trailing_high(stored: Option<f64>, since_entry: Option<f64>, price: f64) -> f64 {
+    stored.or(since_entry).map_or(price, |high| high.max(price))
+}
+
+fn format_level_message(crossing: &PositionLevelCrossing, account: &str, thesis: Option<&str>) -> String {
+    let mut message = match (crossing.level_type, crossing.peak) {
+        ("target", _) => format!(
+            "{}: Price ${:.2} reached your target price of ${:.2} ({})",
+            crossing.ticker, crossing.price, crossing.level, account
+        ),
+        ("trailing_stop", Some(peak)) => format!(
+            "{}: Price ${:.2} is {:.1}% below its high of ${:.2} since entry, through your trailing stop at ${:.2} ({})",
+            crossing.ticker,
+            crossing.price,
+            (1.0 - crossing.price / peak) * 100.0,
+            peak,
+            crossing.level,
+            account
+        ),
+        _ => format!(
+            "{}: Price ${:.2} fell to your stop-loss of ${:.2} ({})",
+            crossing.ticker, crossing.price, crossing.level, account
+        ),
+    };
+    if let Some(thesis) = thesis.map(str::trim).filter(|t| !t.is_empty()) {
+        message.push_str(&format!(". Your thesis: \"{}\"", thesis));
+    }
+    message
+}
+
+/// Check every position with a target price, stop-loss or trailing stop against its
+/// latest close and notify the owner of new crossings. Trailing stops first raise their
+/// high-water mark to the close. Crossings during the owner's quiet hours are left
+/// unalerted, so they're notified on the first check afterwards. Returns the number of
+/// notifications created.
+pub async fn check_position_levels(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let positions = annotation_queries::fetch_monitored_positions(pool).await?;
+    if positions.is_empty() {
+        return Ok(0);
+    }
+
+    let mut tickers: Vec<String> = positions.iter().map(|p| p.annotation.ticker.clone()).collect();
+    tickers.sort();
+    tickers.dedup();
+    let prices = price_queries::fetch_latest_batch(pool, &tickers).await?;
+
+    let now = chrono::Utc::now().time();
+    let mut quiet_users: HashMap<uuid::Uuid, bool> = HashMap::new();
+    let mut notified = 0;
+    for position in &positions {
+        let mut annotation = position.annotation.clone();
+        let Some(price) = prices
+            .get(&annotation.ticker)
+            .and_then(|p| p.close_price.to_f64())
+            .filter(|p| *p > 0.0)
+        else {
+            continue;
+        };
+
+        if annotation.trailing_stop_pct.is_some() {
+            let since_entry = match annotation.trailing_high {
+                Some(_) => None,
+                None => annotation_queries::fetch_high_since_entry(pool, annotation.account_id, &annotation.ticker).await?,
+            };
+            let high = next_trailing_high(annotation.trailing_high, since_entry, price);
+            if annotation.trailing_high != Some(high) {
+                annotation_queries::set_trailing_high(pool, annotation.id, high).await?;
+                annotation.trailing_high = Some(high);
+            }
+        }
+
+        for level_type in levels_to_rearm(&annotation, price) {
+            annotation_queries::set_level_alerted(pool, annotation.id, level_type, false).await?;
+        }
+
+        let crossings = detect_level_crossings(&annotation, price);
+        if crossings.is_empty() {
+            continue;
+        }
+        let quiet = match quiet_users.get(&position.user_id) {
+            Some(quiet) => *quiet,
+            None => {
+                let settings = user_preferences_queries::get_notification_settings(pool, position.user_id).await?;
+                *quiet_users.entry(position.user_id).or_insert(settings.is_quiet_at(now))
+            }
+        };
+        if quiet {
+            continue;
+        }
+
+        for crossing in crossings {
+            let title = match crossing.level_type {
+                "target" => format!("🎯 {} reached target price", crossing.ticker),
+                "trailing_stop" => format!("📉 {} hit trailing stop", crossing.ticker),
+                _ => format!("🛑 {} hit stop-loss", crossing.ticker),
+            };
+            let message = format_level_message(&crossing, &position.account_nickname, annotation.thesis.as_deref());
+            let link = format!("/portfolios/{}", position.portfolio_id);
+
+            alert_queries::create_notification(
+                pool,
+                position.user_id,
+                None,
+                &title,
+                &message,
+                "alert",
+                Some(&link),
+                None,
+            )
+            .await?;
+            annotation_queries::set_level_alerted(pool, annotation.id, crossing.level_type, true).await?;
+            notified += 1;
+        }
+    }
+
+    Ok(notified)
+}
+
 // ==============================================================================
 // Helper Functions
 // ==============================================================================
@@ -465,6 +671,7 @@ fn determine_severity(threshold_type: &str, actual: f64, threshold: f64) -> Stri
                 "medium"
             }
         }
+        "pct_off_52w_high" if actual >= 30.0 => "high",
         _ => "medium",
     }
     .to_string()
@@ -505,6 +712,14 @@ fn format_alert_message(
             "{}: RSI at {:.1} below oversold threshold {:.1}",
             ticker, actual, threshold
         ),
+        "pct_off_52w_high" => format!(
+            "{}: Trading {:.2}% below its 52-week high (threshold: {:.2}%)",
+            ticker, actual, threshold
+        ),
+        "pct_above_52w_low" => format!(
+            "{}: Trading {:.2}% above its 52-week low (threshold: {:.2}%)",
+            ticker, actual, threshold
+        ),
         _ => format!(
             "{}: {} alert - value {:.2} (threshold: {:.2})",
             ticker, threshold_type, actual, threshold
@@ -538,6 +753,15 @@ mod tests {
         assert!(msg.contains("150.00"));
     }
 
+    #[test]
+    fn test_52_week_threshold_alerts() {
+        assert_eq!(determine_severity("pct_off_52w_high", 35.0, 20.0), "high");
+        assert_eq!(determine_severity("pct_off_52w_high", 22.0, 20.0), "medium");
+        let msg = format_alert_message("AAPL", "pct_off_52w_high", 22.5, 20.0);
+        assert!(msg.contains("52-week high"));
+        assert!(msg.contains("22.50"));
+    }
+
     #[test]
     fn test_detect_patterns_insufficient_data() {
         let prices = vec![100.0; 10]; // Only 10 prices, need 30
@@ -574,4 +798,88 @@ mod tests {
         assert!(r.alert_type.contains("negative"));
         assert_eq!(r.severity, "high"); // shift of 0.7 >= 0.6
     }
+
+    fn levels(target: Option<f64>, stop: Option<f64>) -> PositionAnnotation {
+        PositionAnnotation {
+            id: uuid::Uuid::new_v4(),
+            account_id: uuid::Uuid::new_v4(),
+            ticker: "AAPL".to_string(),
+            tags: vec![],
+            notes: None,
+            target_price: target,
+            stop_loss: stop,
+            thesis: None,
+            trailing_stop_pct: None,
+            trailing_high: None,
+            target_alerted_at: None,
+            stop_alerted_at: None,
+            trailing_alerted_at: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_detect_level_crossings() {
+        let annotation = levels(Some(200.0), Some(150.0));
+        assert!(detect_level_crossings(&annotation, 175.0).is_empty());
+
+        let above = detect_level_crossings(&annotation, 201.0);
+        assert_eq!(above.len(), 1);
+        assert_eq!(above[0].level_type, "target");
+
+        let below = detect_level_crossings(&annotation, 150.0);
+        assert_eq!(below.len(), 1);
+        assert_eq!(below[0].level_type, "stop_loss");
+    }
+
+    #[test]
+    fn test_level_crossing_alerts_once_until_rearmed() {
+        let mut annotation = levels(Some(200.0), None);
+        annotation.target_alerted_at = Some(chrono::Utc::now());
+        assert!(detect_level_crossings(&annotation, 210.0).is_empty());
+        assert!(levels_to_rearm(&annotation, 210.0).is_empty());
+        assert_eq!(levels_to_rearm(&annotation, 190.0), vec!["target"]);
+    }
+
+    #[test]
+    fn test_trailing_stop_follows_high_since_entry() {
+        let mut annotation = levels(None, None);
+        annotation.trailing_stop_pct = Some(10.0);
+
+        // Seeded from the best close since entry, then raised by new highs only
+        let high = next_trailing_high(None, Some(180.0), 170.0);
+        assert_eq!(high, 180.0);
+        annotation.trailing_high = Some(next_trailing_high(Some(high), None, 200.0));
+        assert_eq!(annotation.trailing_high, Some(200.0));
+
+        // A fixed stop at 150 wouldn't fire yet; 10% off the 200 peak does
+        assert!(detect_level_crossings(&annotation, 181.0).is_empty());
+        let crossings = detect_level_crossings(&annotation, 179.0);
+        assert_eq!(crossings.len(), 1);
+        assert_eq!(crossings[0].level_type, "trailing_stop");
+        assert!((crossings[0].level - 180.0).abs() < 1e-9);
+
+        let msg = format_level_message(&crossings[0], "RRSP", None);
+        assert!(msg.contains("10.5% below its high of $200.00"));
+
+        annotation.trailing_alerted_at = Some(chrono::Utc::now());
+        assert!(detect_level_crossings(&annotation, 175.0).is_empty());
+        assert_eq!(levels_to_rearm(&annotation, 185.0), vec!["trailing_stop"]);
+    }
+
+    #[test]
+    fn test_format_level_message_includes_thesis() {
+        let crossing = PositionLevelCrossing {
+            annotation_id: uuid::Uuid::new_v4(),
+            ticker: "AAPL".to_string(),
+            level_type: "stop_loss",
+            level: 150.0,
+            price: 149.5,
+            peak: None,
+        };
+        let msg = format_level_message(&crossing, "TFSA", Some("Services growth"));
+        assert!(msg.contains("stop-loss of $150.00"));
+        assert!(msg.contains("Services growth"));
+    }
 }
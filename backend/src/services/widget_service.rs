@@ -0,0 +1,185 @@
+//! Embeddable portfolio widgets.
+//!
+//! With `WIDGET_API_ENABLED=true`, portfolio admins can create widget tokens
+//! for a personal site. A widget token is narrower than a public API token:
+//! it reads one portfolio's latest value, day change and risk score, nothing
+//! else, and browsers only get the response on the one origin named when the
+//! token was created. The token ends up in the embedding page's source, so
+//! anyone visiting the site can see it; deleting it is the way to revoke it.
+
+use bigdecimal::ToPrimitive;
+use sqlx::PgPool;
+use url::Url;
+use uuid::Uuid;
+
+use crate::auth;
+use crate::db::{holding_snapshot_queries, risk_snapshot_queries, widget_token_queries};
+use crate::errors::AppError;
+use crate::models::{CreateWidgetToken, CreatedWidgetToken, WidgetGrant, WidgetRisk, WidgetValue};
+use crate::services::behavioral_analytics_service::portfolio_values;
+
+/// Longest token label accepted
+const MAX_TOKEN_NAME_LEN: usize = 100;
+/// Widget tokens are prefixed so they can't be mistaken for public API tokens
+const TOKEN_PREFIX: &str = "rfw_";
+/// Seconds browsers and CDNs may cache a widget payload; valuations change
+/// at most a few times a day
+pub const CACHE_MAX_AGE_SECS: u32 = 300;
+
+#[derive(Debug, Clone)]
+pub struct WidgetConfig {
+    /// `/api/widgets` is only mounted when enabled
+    pub enabled: bool,
+}
+
+impl WidgetConfig {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("WIDGET_API_ENABLED")
+                .ok()
+                .and_then(|s| s.parse::<bool>().ok())
+                .unwrap_or(false),
+        }
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    auth::hash_refresh_token(token)
+}
+
+/// Serialized origin of a site, e.g. "https://example.com". Plain http is
+/// only accepted for localhost, for trying a widget out locally.
+pub fn normalize_origin(value: &str) -> Result<String, AppError> {
+    let invalid = || AppError::Validation(format!("Invalid origin: {}", value.trim()));
+    let url = Url::parse(value.trim()).map_err(|_| invalid())?;
+    if !url.username().is_empty() || url.password().is_some() || url.query().is_some() || url.fragment().is_some() {
+        return Err(invalid());
+    }
+    if !matches!(url.path(), "" | "/") {
+        return Err(AppError::Validation(format!(
+            "The origin must not include a path: {}",
+            value.trim()
+        )));
+    }
+    let loopback = matches!(url.host_str(), Some("localhost") | Some("127.0.0.1"));
+    match url.scheme() {
+        "https" => {}
+        "http" if loopback => {}
+        _ => return Err(AppError::Validation("The origin must use https".to_string())),
+    }
+    let origin = url.origin();
+    if !origin.is_tuple() {
+        return Err(invalid());
+    }
+    Ok(origin.ascii_serialization())
+}
+
+/// Whether a request may read the widget. Requests without an `Origin`
+/// header (server-side renders, the token owner's own tools) are let through;
+/// browsers send one with every cross-origin fetch.
+pub fn origin_permitted(grant: &WidgetGrant, request_origin: Option<&str>) -> bool {
+    request_origin.is_none_or(|origin| {
+        normalize_origin(origin).is_ok_and(|origin| origin == grant.allowed_origin)
+    })
+}
+
+pub async fn create_token(
+    pool: &PgPool,
+    portfolio_id: Uuid,
+    user_id: Uuid,
+    request: &CreateWidgetToken,
+) -> Result<CreatedWidgetToken, AppError> {
+    if !WidgetConfig::from_env().enabled {
+        return Err(AppError::ServiceUnavailable("Widgets are not enabled".to_string()));
+    }
+    let name = request.name.trim();
+    if name.is_empty() || name.len() > MAX_TOKEN_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Token name must be between 1 and {} characters",
+            MAX_TOKEN_NAME_LEN
+        )));
+    }
+    let allowed_origin = normalize_origin(&request.allowed_origin)?;
+
+    let secret = format!("{}{}", TOKEN_PREFIX, auth::generate_refresh_token());
+    let token =
+        widget_token_queries::create(pool, portfolio_id, user_id, name, &allowed_origin, &hash_token(&secret)).await?;
+    Ok(CreatedWidgetToken { token, secret })
+}
+
+/// The portfolio and origin a widget token grants
+pub async fn authenticate(pool: &PgPool, token: &str) -> Result<WidgetGrant, AppError> {
+    if !token.starts_with(TOKEN_PREFIX) {
+        return Err(AppError::Unauthorized);
+    }
+    widget_token_queries::authenticate(pool, &hash_token(token))
+        .await?
+        .ok_or(AppError::Unauthorized)
+}
+
+/// Latest value and the change since the valuation before it
+pub fn widget_value(values: &[(chrono::NaiveDate, f64)]) -> WidgetValue {
+    let latest = values.last();
+    let previous = values.len().checked_sub(2).map(|i| values[i].1);
+    let value = latest.map(|(_, v)| *v).unwrap_or(0.0);
+    WidgetValue {
+        as_of: latest.map(|(d, _)| *d),
+        value,
+        day_change: previous.map(|p| value - p),
+        day_change_pct: previous.filter(|p| *p > 0.0).map(|p| (value - p) / p * 100.0),
+    }
+}
+
+pub async fn value(pool: &PgPool, grant: &WidgetGrant) -> Result<WidgetValue, AppError> {
+    let history = holding_snapshot_queries::fetch_portfolio_value_history(pool, grant.portfolio_id).await?;
+    Ok(widget_value(&portfolio_values(&history)))
+}
+
+pub async fn risk(pool: &PgPool, grant: &WidgetGrant) -> Result<WidgetRisk, AppError> {
+    let snapshot = risk_snapshot_queries::fetch_latest(pool, grant.portfolio_id, None)
+        .await?
+        .ok_or_else(|| AppError::NotFound("The portfolio has no risk snapshot yet".to_string()))?;
+    Ok(WidgetRisk {
+        as_of: snapshot.snapshot_date,
+        risk_score: snapshot.risk_score.to_f64().unwrap_or(0.0),
+        risk_level: snapshot.risk_level,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_normalize_origin() {
+        assert_eq!(normalize_origin(" https://Example.com/ ").unwrap(), "https://example.com");
+        assert_eq!(normalize_origin("https://example.com:8443").unwrap(), "https://example.com:8443");
+        assert_eq!(normalize_origin("http://localhost:3000").unwrap(), "http://localhost:3000");
+        assert!(normalize_origin("http://example.com").is_err());
+        assert!(normalize_origin("https://example.com/blog").is_err());
+        assert!(normalize_origin("*").is_err());
+        assert!(normalize_origin("null").is_err());
+        assert!(normalize_origin("https://user@example.com").is_err());
+    }
+
+    #[test]
+    fn test_origin_permitted() {
+        let grant = WidgetGrant { portfolio_id: Uuid::new_v4(), allowed_origin: "https://example.com".to_string() };
+        assert!(origin_permitted(&grant, None));
+        assert!(origin_permitted(&grant, Some("https://example.com")));
+        assert!(!origin_permitted(&grant, Some("https://evil.example.com")));
+        assert!(!origin_permitted(&grant, Some("http://example.com")));
+        assert!(!origin_permitted(&grant, Some("null")));
+    }
+
+    #[test]
+    fn test_widget_value_reports_day_change() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let value = widget_value(&[(day(3), 2000.0), (day(4), 1900.0)]);
+        assert_eq!(value.as_of, Some(day(4)));
+        assert_eq!(value.day_change, Some(-100.0));
+        assert!((value.day_change_pct.unwrap() + 5.0).abs() < 1e-9);
+        assert_eq!(widget_value(&[]), WidgetValue { as_of: None, value: 0.0, day_change: None, day_change_pct: None });
+    }
+}
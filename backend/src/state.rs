@@ -1,6 +1,15 @@
 use std::sync::Arc;
 use sqlx::PgPool;
+use crate::external::analyst_provider::AnalystProvider;
+use crate::external::chain_provider::ChainProvider;
+use crate::external::dividend_provider::DividendProvider;
+use crate::external::etf_holdings_provider::EtfHoldingsProvider;
+use crate::external::fx_provider::FxProvider;
+use crate::external::fundamentals_provider::FundamentalsProvider;
+use crate::external::news::NewsProvider;
+use crate::external::ownership_provider::OwnershipProvider;
 use crate::external::price_provider::PriceProvider;
+use crate::repositories::Repositories;
 use crate::services::failure_cache::FailureCache;
 use crate::services::llm_service::LlmService;
 use crate::services::news_service::NewsService;
@@ -9,7 +18,21 @@ use crate::services::rate_limiter::RateLimiter;
 #[derive(Clone)]
 pub struct AppState {
     pub pool: PgPool,
+    pub repos: Repositories,
     pub price_provider: Arc<dyn PriceProvider>,
+    /// PRICE_PROVIDER as selected at startup, e.g. "composite"
+    pub price_provider_name: String,
+    pub ownership_provider: Arc<dyn OwnershipProvider>,
+    pub analyst_provider: Arc<dyn AnalystProvider>,
+    /// Only configured when FINNHUB_API_KEY is set
+    pub fundamentals_provider: Option<Arc<dyn FundamentalsProvider>>,
+    /// Scored news for the sentiment cache; only configured when ALPHAVANTAGE_API_KEY is set
+    pub news_provider: Option<Arc<dyn NewsProvider>>,
+    pub etf_holdings_provider: Arc<dyn EtfHoldingsProvider>,
+    pub dividend_provider: Arc<dyn DividendProvider>,
+    pub fx_provider: Arc<dyn FxProvider>,
+    pub fx_provider_name: String,
+    pub chain_provider: Arc<dyn ChainProvider>,
     pub failure_cache: FailureCache,
     pub rate_limiter: Arc<RateLimiter>,
     pub risk_free_rate: f64, // Annual risk-free rate (e.g., 0.045 for 4.5%)
@@ -17,7 +17,7 @@ import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
 import { useState, useEffect } from 'react';
 import {
     getUserPreferences,
-    updateLlmConsent,
+    recordLlmConsent,
     getLlmUsageStats,
     updateUserPreferences,
 } from '../lib/endpoints';
@@ -57,7 +57,7 @@ export default function LlmSettings() {
 
     // Mutation for updating consent
     const updateConsentMutation = useMutation({
-        mutationFn: (consent: boolean) => updateLlmConsent(DEMO_USER_ID, consent),
+        mutationFn: (consent: boolean) => recordLlmConsent(consent),
         onSuccess: () => {
             queryClient.invalidateQueries({ queryKey: ['userPreferences', DEMO_USER_ID] });
             queryClient.invalidateQueries({ queryKey: ['llmUsageStats', DEMO_USER_ID] });
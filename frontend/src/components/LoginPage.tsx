@@ -1,9 +1,9 @@
-import { useState } from 'react';
+import { useEffect, useState } from 'react';
 import {
-    Box, Button, TextField, Typography, Paper, Tab, Tabs, Alert, CircularProgress, Link
+    Box, Button, TextField, Typography, Paper, Tab, Tabs, Alert, CircularProgress, Link, Divider
 } from '@mui/material';
 import { useAuth } from '../contexts/AuthContext';
-import { requestPasswordReset, resetPassword } from '../lib/endpoints';
+import { listOidcProviders, OidcProvider, requestPasswordReset, resetPassword } from '../lib/endpoints';
 
 export function LoginPage() {
     const [tab, setTab] = useState(0);
@@ -25,6 +25,29 @@ export function LoginPage() {
     const [forgotError, setForgotError] = useState('');
     const [forgotSuccess, setForgotSuccess] = useState('');
 
+    // Single sign-on providers configured on the server
+    const [providers, setProviders] = useState<OidcProvider[]>([]);
+
+    useEffect(() => {
+        listOidcProviders().then(setProviders).catch(() => setProviders([]));
+
+        // A failed sign-on comes back as ?sso_error=...
+        const params = new URLSearchParams(window.location.search);
+        const ssoError = params.get('sso_error');
+        if (ssoError) {
+            setError(ssoError);
+            params.delete('sso_error');
+            const query = params.toString();
+            window.history.replaceState(null, '', window.location.pathname + (query ? `?${query}` : ''));
+        }
+    }, []);
+
+    const startSso = (provider: OidcProvider) => {
+        const redirectTo = window.location.pathname + window.location.search;
+        window.location.href = `/api/auth/oidc/login?provider=${encodeURIComponent(provider.id)}`
+            + `&redirect_to=${encodeURIComponent(redirectTo)}`;
+    };
+
     const handleSubmit = async (e: React.FormEvent) => {
         e.preventDefault();
         setError('');
@@ -296,6 +319,21 @@ export function LoginPage() {
                         {loading ? <CircularProgress size={24} /> : tab === 0 ? 'Sign In' : 'Register'}
                     </Button>
                 </Box>
+                {providers.length > 0 && (
+                    <Box display="flex" flexDirection="column" gap={1} mt={3}>
+                        <Divider sx={{ mb: 1 }}>or</Divider>
+                        {providers.map(provider => (
+                            <Button
+                                key={provider.id}
+                                variant="outlined"
+                                size="large"
+                                onClick={() => startSso(provider)}
+                            >
+                                Continue with {provider.name}
+                            </Button>
+                        ))}
+                    </Box>
+                )}
             </Paper>
         </Box>
     );
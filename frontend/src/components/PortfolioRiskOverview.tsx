@@ -476,8 +476,13 @@ export function PortfolioRiskOverview({
             <Grid item xs={12} sm={6} md={3}>
               <StatCard
                 label="Portfolio Beta"
-                value={riskData.portfolio_beta?.toFixed(2) ?? 'N/A'}
-                subValue="vs SPY benchmark"
+                value={(riskData.portfolio_regression_beta ?? riskData.portfolio_beta)?.toFixed(2) ?? 'N/A'}
+                subValue={
+                  riskData.portfolio_regression_beta != null
+                    ? `vs SPY, R² ${riskData.portfolio_r_squared?.toFixed(2) ?? 'N/A'}` +
+                      (riskData.portfolio_beta != null ? `; position average: ${riskData.portfolio_beta.toFixed(2)}` : '')
+                    : 'vs SPY benchmark'
+                }
                 helpKey="beta"
               />
             </Grid>
@@ -486,7 +491,11 @@ export function PortfolioRiskOverview({
               <StatCard
                 label="Sharpe Ratio"
                 value={riskData.portfolio_sharpe?.toFixed(2) ?? 'N/A'}
-                subValue="Risk-adjusted return"
+                subValue={
+                  riskData.portfolio_sharpe_weighted_average != null
+                    ? `Position average: ${riskData.portfolio_sharpe_weighted_average.toFixed(2)}`
+                    : 'Risk-adjusted return'
+                }
                 helpKey="sharpe_ratio"
               />
             </Grid>
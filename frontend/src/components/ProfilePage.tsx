@@ -12,7 +12,7 @@ import {
 import { useAuth } from '../contexts/AuthContext';
 
 export function ProfilePage() {
-    const { user, logout, updateProfile, changePassword } = useAuth();
+    const { user, logout, logoutAll, updateProfile, changePassword } = useAuth();
 
     // Profile edit state
     const [editEmail, setEditEmail] = useState(user?.email ?? '');
@@ -87,6 +87,15 @@ export function ProfilePage() {
         }
     };
 
+    const handleLogoutAll = async () => {
+        setLogoutLoading(true);
+        try {
+            await logoutAll();
+        } finally {
+            setLogoutLoading(false);
+        }
+    };
+
     return (
         <Box maxWidth={560}>
             <Typography variant="h5" fontWeight={600} mb={3}>
@@ -198,6 +207,14 @@ export function ProfilePage() {
                 >
                     {logoutLoading ? <CircularProgress size={22} /> : 'Sign Out'}
                 </Button>
+                <Button
+                    color="error"
+                    onClick={handleLogoutAll}
+                    disabled={logoutLoading}
+                    sx={{ ml: 1 }}
+                >
+                    Sign Out Everywhere
+                </Button>
             </Paper>
         </Box>
     );
@@ -32,7 +32,22 @@ import {
 import { Warning as WarningIcon, HelpOutline } from '@mui/icons-material';
 import { getRiskHistory, getRiskAlerts } from '../lib/endpoints';
 import { MetricHelpDialog } from './MetricHelpDialog';
-import type { RiskSnapshot, RiskAlert, RiskThresholdSettings } from '../types';
+import type { ChangeDriver, RiskSnapshot, RiskAlert, RiskThresholdSettings } from '../types';
+
+const pct = (fraction: number) => `${(fraction * 100).toFixed(1)}%`;
+
+function describeChangeDriver(driver: ChangeDriver): string {
+  switch (driver.kind) {
+    case 'position_added':
+      return `${driver.ticker} added (${pct(driver.weight)})`;
+    case 'position_removed':
+      return `${driver.ticker} removed (was ${pct(driver.previous_weight)})`;
+    case 'price_shock':
+      return `${driver.ticker} price ${driver.price_change_pct >= 0 ? '+' : ''}${driver.price_change_pct.toFixed(1)}%`;
+    case 'weight_shift':
+      return `${driver.ticker} weight ${pct(driver.previous_weight)} → ${pct(driver.weight)}`;
+  }
+}
 
 interface RiskHistoryChartProps {
   portfolioId: string;
@@ -96,6 +111,12 @@ export function RiskHistoryChart({ portfolioId, ticker, thresholds }: RiskHistor
     risk_level: snapshot.risk_level,
   })) || [];
 
+  // Most recent snapshots whose risk score move was explained, newest first
+  const scoreChanges = (historyQuery.data ?? [])
+    .filter((snapshot: RiskSnapshot) => snapshot.change_drivers && snapshot.change_drivers.length > 0)
+    .slice(-5)
+    .reverse();
+
   // Create alert markers
   const alertMarkers = alertsQuery.data?.map((alert: RiskAlert) => ({
     date: alert.date,
@@ -638,6 +659,30 @@ export function RiskHistoryChart({ portfolioId, ticker, thresholds }: RiskHistor
             </Typography>
           </Box>
         )}
+
+        {!ticker && scoreChanges.length > 0 && (
+          <Box mt={2}>
+            <Typography variant="subtitle2" gutterBottom>
+              Why the risk score moved
+            </Typography>
+            <Stack spacing={1}>
+              {scoreChanges.map((snapshot: RiskSnapshot) => (
+                <Box key={snapshot.id}>
+                  <Typography variant="caption" color="text.secondary">
+                    {new Date(snapshot.snapshot_date).toLocaleDateString('en-US', { month: 'short', day: 'numeric' })}
+                    {' • score '}
+                    {Number(snapshot.risk_score).toFixed(1)}
+                  </Typography>
+                  <Stack direction="row" spacing={0.5} flexWrap="wrap" useFlexGap>
+                    {snapshot.change_drivers!.map((driver, idx) => (
+                      <Chip key={idx} size="small" variant="outlined" label={describeChangeDriver(driver)} />
+                    ))}
+                  </Stack>
+                </Box>
+              ))}
+            </Stack>
+          </Box>
+        )}
       </CardContent>
     </Card>
   );
@@ -3,6 +3,7 @@ import {
     getMe,
     login as apiLogin,
     logout as apiLogout,
+    logoutAll as apiLogoutAll,
     register as apiRegister,
     updateProfile as apiUpdateProfile,
     changePassword as apiChangePassword,
@@ -13,6 +14,7 @@ interface AuthContextType {
     user: AuthUser | null;
     login: (email: string, password: string) => Promise<void>;
     logout: () => Promise<void>;
+    logoutAll: () => Promise<void>;
     register: (email: string, password: string, name?: string) => Promise<void>;
     updateProfile: (data: { email: string; name?: string }) => Promise<void>;
     changePassword: (currentPassword: string, newPassword: string) => Promise<void>;
@@ -42,6 +44,11 @@ export function AuthProvider({ children }: { children: ReactNode }) {
         setUser(null);
     };
 
+    const logoutAll = async () => {
+        await apiLogoutAll();
+        setUser(null);
+    };
+
     const register = async (email: string, password: string, name?: string) => {
         await apiRegister(email, password, name);
     };
@@ -56,7 +63,7 @@ export function AuthProvider({ children }: { children: ReactNode }) {
     };
 
     return (
-        <AuthContext.Provider value={{ user, login, logout, register, updateProfile, changePassword, isLoading }}>
+        <AuthContext.Provider value={{ user, login, logout, logoutAll, register, updateProfile, changePassword, isLoading }}>
             {children}
         </AuthContext.Provider>
     );
@@ -1,4 +1,4 @@
-import axios from 'axios'
+import axios, { AxiosError, InternalAxiosRequestConfig } from 'axios'
 
 export async function getHealth(): Promise<void> {
   await axios.get('/health')
@@ -9,4 +9,39 @@ export async function getHealth(): Promise<void> {
 export const api = axios.create({
     baseURL: import.meta.env.VITE_API_BASE_URL ?? "",
     withCredentials: true,
-});
\ No newline at end of file
+});
+
+// Access tokens are short-lived: on a 401, renew the session with the refresh
+// token cookie once and retry the request. Concurrent failures share one refresh.
+type RetriableRequest = InternalAxiosRequestConfig & { _retried?: boolean };
+
+// A 401 from these means bad credentials or an ended session, not an expired token
+const NO_REFRESH_PATHS = ['/api/auth/login', '/api/auth/refresh', '/api/auth/logout'];
+
+let refreshing: Promise<void> | null = null;
+
+api.interceptors.response.use(undefined, async (error: AxiosError) => {
+    const request = error.config as RetriableRequest | undefined;
+    if (
+        error.response?.status !== 401 ||
+        !request ||
+        request._retried ||
+        NO_REFRESH_PATHS.some((path) => request.url?.startsWith(path))
+    ) {
+        return Promise.reject(error);
+    }
+
+    request._retried = true;
+    refreshing ??= api
+        .post('/api/auth/refresh')
+        .then(() => undefined)
+        .finally(() => {
+            refreshing = null;
+        });
+    try {
+        await refreshing;
+    } catch {
+        return Promise.reject(error);
+    }
+    return api(request);
+});
@@ -1,6 +1,8 @@
 import { api } from "./api";
 import type {
     AnalyticsResponse,
+    IntradayInterval,
+    IntradayPrices,
     Portfolio,
     Position,
     PricePoint,
@@ -27,6 +29,7 @@ import type {
     OptimizationAnalysis,
     UserPreferences,
     UpdateUserPreferences,
+    LlmConsentStatus,
     LlmUsageStats,
     PortfolioNarrative,
     PortfolioNewsAnalysis,
@@ -106,6 +109,7 @@ import type {
     CreateHouseholdExpenseRequest,
     UpdateHouseholdExpenseRequest,
 } from "../types";
+import { LLM_CONSENT_VERSION } from "../types";
 
 export interface AuthUser {
     id: string;
@@ -127,6 +131,11 @@ export async function logout(): Promise<void> {
     await api.post('/api/auth/logout');
 }
 
+/** Sign out of every browser and device, including this one */
+export async function logoutAll(): Promise<void> {
+    await api.post('/api/auth/logout-all');
+}
+
 export async function getMe(): Promise<AuthUser> {
     const { data } = await api.get<AuthUser>('/api/auth/me');
     return data;
@@ -152,6 +161,16 @@ export async function resetPassword(token: string, newPassword: string): Promise
     await api.post('/api/auth/reset-password', { token, new_password: newPassword });
 }
 
+export interface OidcProvider {
+    id: string;
+    name: string;
+}
+
+export async function listOidcProviders(): Promise<OidcProvider[]> {
+    const { data } = await api.get<OidcProvider[]>('/api/auth/oidc/providers');
+    return data;
+}
+
 export async function listPortfolios(): Promise<Portfolio[]> {
     const res = await api.get("/api/portfolios");
     return res.data;
@@ -222,6 +241,14 @@ export async function getLatestPrice(ticker: string): Promise<PricePoint> {
     return res.data;
 }
 
+export async function getIntradayPrices(
+    ticker: string,
+    interval: IntradayInterval = '5min'
+): Promise<IntradayPrices> {
+    const res = await api.get(`/api/prices/${ticker}/intraday?interval=${interval}`);
+    return res.data;
+}
+
 export async function getPriceHistory(ticker: string): Promise<PricePoint[]> {
     const res = await api.get(`/api/prices/${ticker}`);
     return res.data;
@@ -350,6 +377,37 @@ export async function resetAllData(): Promise<{ message: string; tables_cleared:
     return res.data;
 }
 
+export type UserRole = 'viewer' | 'editor' | 'admin';
+
+export interface AdminUserRecord {
+    id: string;
+    email: string;
+    name?: string | null;
+    role: UserRole;
+    disabled_at: string | null;
+    created_at: string;
+    updated_at: string;
+}
+
+export async function listAdminUsers(): Promise<AdminUserRecord[]> {
+    const res = await api.get('/api/admin/users');
+    return res.data;
+}
+
+export async function setUserDisabled(userId: string, disabled: boolean): Promise<AdminUserRecord> {
+    const res = await api.post(`/api/admin/users/${userId}/${disabled ? 'disable' : 'enable'}`);
+    return res.data;
+}
+
+export async function adminResetPassword(userId: string, newPassword: string): Promise<void> {
+    await api.post(`/api/admin/users/${userId}/reset-password`, { new_password: newPassword });
+}
+
+export async function setUserRole(userId: string, role: UserRole): Promise<AdminUserRecord> {
+    const res = await api.put(`/api/admin/users/${userId}/role`, { role });
+    return res.data;
+}
+
 // Risk endpoints
 export async function getPositionRisk(
     ticker: string,
@@ -640,11 +698,16 @@ export async function updateUserPreferences(
     return res.data;
 }
 
-export async function updateLlmConsent(
-    userId: string,
-    consent: boolean
-): Promise<UserPreferences> {
-    const res = await api.post(`/api/llm/users/${userId}/llm-consent`, { consent });
+export async function getLlmConsent(): Promise<LlmConsentStatus> {
+    const res = await api.get(`/api/users/me/llm-consent`);
+    return res.data;
+}
+
+export async function recordLlmConsent(granted: boolean): Promise<LlmConsentStatus> {
+    const res = await api.post(`/api/users/me/llm-consent`, {
+        granted,
+        version: LLM_CONSENT_VERSION,
+    });
     return res.data;
 }
 
@@ -21,6 +21,30 @@ export type PricePoint = {
     created_at: string;
 };
 
+export type IntradayInterval = '1min' | '5min' | '15min' | '30min' | '60min';
+
+export type IntradayPrice = {
+    bar_time: string;
+    open: number;
+    high: number;
+    low: number;
+    close: number;
+    volume: number | null;
+};
+
+export type IntradayPrices = {
+    ticker: string;
+    interval: IntradayInterval;
+    currency: string;
+    session_date: string; // YYYY-MM-DD
+    previous_close: number | null;
+    last_price: number;
+    change: number | null;
+    change_pct: number | null;
+    bars: IntradayPrice[];
+    fetched_at: string;
+};
+
 export type ChartPoint = {
     date: string;
     value: number;
@@ -110,6 +134,7 @@ export type AccountValueHistory = {
     total_cost: string; // BigDecimal
     total_gain_loss: string | null; // BigDecimal (optional)
     total_gain_loss_pct: string | null; // BigDecimal (optional)
+    source: 'import' | 'valuation'; // Imported statement or scheduled valuation
 };
 
 export type ImportResponse = {
@@ -231,8 +256,16 @@ export type PortfolioRisk = {
     total_value: number;
     portfolio_volatility: number;
     portfolio_max_drawdown: number;
+    // Weighted average of position betas
     portfolio_beta: number | null;
+    // Regression of the portfolio's daily returns on the benchmark's
+    portfolio_regression_beta: number | null;
+    portfolio_alpha: number | null;
+    portfolio_r_squared: number | null;
     portfolio_sharpe: number | null;
+    portfolio_sortino: number | null;
+    // Weighted average of position Sharpes; ignores diversification and cash
+    portfolio_sharpe_weighted_average: number | null;
     portfolio_risk_score: number;
     risk_level: RiskLevel;
     position_risks: PositionRiskContribution[];
@@ -452,9 +485,17 @@ export type RiskSnapshot = {
     risk_level: RiskLevel;
     total_value?: number | string; // BigDecimal from backend (comes as string)
     market_value?: number | string; // BigDecimal from backend (comes as string)
+    change_drivers?: ChangeDriver[] | null; // Why the score moved since the previous snapshot
     created_at: string;
 };
 
+// Weights are fractions of portfolio market value
+export type ChangeDriver =
+    | { kind: 'position_added'; ticker: string; weight: number }
+    | { kind: 'position_removed'; ticker: string; previous_weight: number }
+    | { kind: 'price_shock'; ticker: string; price_change_pct: number; weight: number }
+    | { kind: 'weight_shift'; ticker: string; previous_weight: number; weight: number };
+
 export type RiskAlert = {
     portfolio_id: string;
     ticker?: string;
@@ -601,6 +642,23 @@ export type UpdateUserPreferences = {
     narrative_cache_hours?: number;
 };
 
+// Version of the consent text shown in ConsentDialog; must match the backend's
+export const LLM_CONSENT_VERSION = 1;
+
+export type LlmConsent = {
+    id: string;
+    user_id: string;
+    version: number;
+    granted: boolean;
+    created_at: string;
+};
+
+export type LlmConsentStatus = {
+    current_version: number;
+    consent_required: boolean;
+    latest: LlmConsent | null;
+};
+
 export type LlmUsageStats = {
     total_requests: number;
     total_prompt_tokens: number;
@@ -918,6 +976,8 @@ export type PositionDownsideContribution = {
 export type PortfolioDownsideRisk = {
     portfolio_id: string;
     portfolio_metrics: DownsideRiskMetrics;
+    weighted_average_sortino: number | null;
+    weighted_average_sharpe: number | null;
     position_downside_risks: PositionDownsideContribution[];
     days: number;
     benchmark: string;